@@ -15,8 +15,12 @@ pub mod routes_bans;
 pub mod routes_characters;
 pub mod routes_globals;
 pub mod routes_items;
+pub mod routes_logs;
 pub mod routes_map;
+pub mod routes_players;
+pub mod routes_protocol;
 pub mod routes_templates;
+pub mod routes_tick_profile;
 pub mod routes_world_actions;
 pub mod types;
 
@@ -68,6 +72,10 @@ pub fn build_admin_router(state: ApiState) -> Option<Router> {
         )
         .route("/world/map", get(routes_map::get_map_bulk))
         .route("/world/globals", get(routes_globals::get_globals))
+        .route(
+            "/world/tick-profile",
+            get(routes_tick_profile::get_tick_profile),
+        )
         .route("/world/map/version", get(routes_map::get_map_version))
         .route("/world/map/reload", post(routes_map::request_map_reload))
         .route(
@@ -113,6 +121,10 @@ pub fn build_admin_router(state: ApiState) -> Option<Router> {
             "/world/characters/reload/status",
             get(routes_characters::get_characters_reload_status),
         )
+        .route(
+            "/world/characters/by-name",
+            get(routes_characters::find_characters_by_name),
+        )
         .route(
             "/world/characters/{id}",
             get(routes_characters::get_character).put(routes_characters::put_character),
@@ -125,6 +137,9 @@ pub fn build_admin_router(state: ApiState) -> Option<Router> {
             "/world/actions/status",
             get(routes_world_actions::get_world_action_status),
         )
+        .route("/players/online", get(routes_players::get_online_players))
+        .route("/players/kick", post(routes_players::kick_player))
+        .route("/players/broadcast", post(routes_players::broadcast))
         .route(
             "/bans",
             get(routes_bans::list_bans).post(routes_bans::create_ban),
@@ -160,6 +175,8 @@ pub fn build_admin_router(state: ApiState) -> Option<Router> {
             "/text/badwords/entry",
             get(routes_badwords::get_badword_entry),
         )
+        .route("/logs/tail", get(routes_logs::tail_logs))
+        .route("/protocol", get(routes_protocol::get_protocol))
         .route("/text/reload", post(routes_badwords::request_text_reload))
         .route(
             "/text/reload/status",
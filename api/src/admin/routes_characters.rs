@@ -6,9 +6,10 @@
 
 use crate::ApiState;
 use crate::admin::types::{
-    ErrorResponse, PutWorldEntityResponse, WorldEntityListQuery, WorldEntityListResponse,
-    WorldEntityReloadRequest, WorldEntityReloadResponse, WorldEntityReloadStatusResponse,
-    WorldEntitySummary, WorldEntityVersionResponse,
+    ErrorResponse, PutWorldEntityResponse, WorldCharacterByNameQuery, WorldCharacterByNameResponse,
+    WorldCharacterMatch, WorldEntityListQuery, WorldEntityListResponse, WorldEntityReloadRequest,
+    WorldEntityReloadResponse, WorldEntityReloadStatusResponse, WorldEntitySummary,
+    WorldEntityVersionResponse,
 };
 use axum::Json;
 use axum::body::Bytes;
@@ -189,6 +190,79 @@ pub(crate) async fn list_characters(
     .into_response()
 }
 
+/// GET `/admin/world/characters/by-name?name=…` — scans every stored
+/// character slot and returns the used slots whose name matches
+/// case-insensitively.
+pub(crate) async fn find_characters_by_name(
+    State(state): State<ApiState>,
+    Query(q): Query<WorldCharacterByNameQuery>,
+) -> Response {
+    let query = q.name.trim().to_owned();
+    if query.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("missing_name", "Provide ?name=<name>")),
+        )
+            .into_response();
+    }
+
+    let total = CHARACTER_SLOT_COUNT;
+    let mut con = state.con.clone();
+    let mut characters = Vec::new();
+
+    for batch_start in (0..total).step_by(PIPELINE_BATCH_SIZE) {
+        let batch_end = (batch_start + PIPELINE_BATCH_SIZE).min(total);
+        let mut pipeline = pipe();
+        for idx in batch_start..batch_end {
+            pipeline.cmd("GET").arg(character_store::character_key(idx));
+        }
+
+        let bytes_batch: Vec<Option<Vec<u8>>> =
+            match pipeline.query_async::<Vec<Option<Vec<u8>>>>(&mut con).await {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("admin find_characters_by_name pipeline failed: {}", e);
+                    return internal_error("keydb_error", "Failed to read characters");
+                }
+            };
+
+        for (rel, bytes) in bytes_batch.into_iter().enumerate() {
+            let Some(ch) = bytes.as_deref().and_then(Character::from_bytes) else {
+                continue;
+            };
+            if ch.used != 0
+                && string_operations::c_string_to_str(&ch.name).eq_ignore_ascii_case(&query)
+            {
+                characters.push(character_match(batch_start + rel, &ch));
+            }
+        }
+    }
+
+    Json(WorldCharacterByNameResponse {
+        query,
+        count: characters.len(),
+        characters,
+    })
+    .into_response()
+}
+
+fn character_match(id: usize, ch: &Character) -> WorldCharacterMatch {
+    WorldCharacterMatch {
+        id,
+        name: string_operations::c_string_to_str(&ch.name).to_owned(),
+        reference: string_operations::c_string_to_str(&ch.reference).to_owned(),
+        used: ch.used != 0,
+        is_player: ch.player != 0,
+        template: ch.temp,
+        x: ch.x,
+        y: ch.y,
+        gold: ch.gold,
+        points_tot: ch.points_tot,
+        login_date: ch.login_date,
+        total_online_time: ch.total_online_time,
+    }
+}
+
 fn character_summary(id: usize, ch: &Character) -> WorldEntitySummary {
     WorldEntitySummary {
         id,
@@ -0,0 +1,197 @@
+//! Admin route streaming the game server's recent log records.
+//!
+//! The server mirrors its log output into the capped KeyDB list
+//! [`LOG_TAIL_KEY`]. `GET /admin/logs/tail` polls that list and forwards the
+//! records matching the query filters as server-sent events, so operators can
+//! watch for specific warnings without shell access to the game host.
+
+use crate::ApiState;
+use crate::admin::types::ErrorResponse;
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use futures_util::stream;
+use log::warn;
+use mag_core::log_tail_store::{LOG_TAIL_KEY, LOG_TAIL_MAX_LEN, LogTailFilter, LogTailRecord};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How often KeyDB is polled for new records.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Number of newest list entries read per poll. New records beyond this many
+/// per poll interval are skipped.
+const POLL_WINDOW: usize = 500;
+
+/// Matching records replayed when a client connects, unless overridden.
+const DEFAULT_BACKLOG: usize = 50;
+
+/// Query for `GET /admin/logs/tail`.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct LogTailQuery {
+    /// Least severe level to include, e.g. `warn`.
+    level: Option<String>,
+    /// Module path prefix, e.g. `server::god`.
+    module: Option<String>,
+    /// Only records about this character (`chlog!` prefix).
+    character_id: Option<u32>,
+    /// Number of already-logged matching records to replay first.
+    backlog: Option<usize>,
+}
+
+/// Per-connection streaming state.
+struct TailState {
+    con: redis::aio::ConnectionManager,
+    filter: LogTailFilter,
+    /// Sequence number of the newest record already examined.
+    last_seq: Option<u64>,
+    backlog: usize,
+    pending: VecDeque<LogTailRecord>,
+}
+
+/// GET `/admin/logs/tail` - server-sent events, one `log` event per record.
+pub(crate) async fn tail_logs(
+    State(state): State<ApiState>,
+    Query(q): Query<LogTailQuery>,
+) -> Response {
+    let filter = match LogTailFilter::new(q.level.as_deref(), q.module, q.character_id) {
+        Ok(filter) => filter,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse::new("invalid_level", message)),
+            )
+                .into_response();
+        }
+    };
+
+    let tail = TailState {
+        con: state.con.clone(),
+        filter,
+        last_seq: None,
+        backlog: q.backlog.unwrap_or(DEFAULT_BACKLOG).min(LOG_TAIL_MAX_LEN),
+        pending: VecDeque::new(),
+    };
+    let events = stream::unfold(tail, |mut tail| async move {
+        loop {
+            if let Some(record) = tail.pending.pop_front() {
+                let event = Event::default()
+                    .id(record.seq.to_string())
+                    .event("log")
+                    .json_data(&record);
+                return Some((event, tail));
+            }
+            if tail.last_seq.is_some() {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+            poll(&mut tail).await;
+        }
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Read the newest list entries and queue the unseen matching ones.
+async fn poll(tail: &mut TailState) {
+    let first = tail.last_seq.is_none();
+    let window = if first { LOG_TAIL_MAX_LEN } else { POLL_WINDOW };
+    let raw: Vec<Vec<u8>> = match redis::cmd("LRANGE")
+        .arg(LOG_TAIL_KEY)
+        .arg(-(window as i64))
+        .arg(-1)
+        .query_async(&mut tail.con)
+        .await
+    {
+        Ok(raw) => raw,
+        Err(error) => {
+            warn!("admin tail_logs LRANGE {} failed: {}", LOG_TAIL_KEY, error);
+            tail.last_seq.get_or_insert(0);
+            return;
+        }
+    };
+    let records: Vec<LogTailRecord> = raw
+        .iter()
+        .filter_map(|bytes| LogTailRecord::from_bytes(bytes))
+        .collect();
+
+    let start = unseen_start(&records, tail.last_seq.unwrap_or(0));
+    let matching = records[start..]
+        .iter()
+        .filter(|record| tail.filter.matches(record));
+    if first {
+        let matching: Vec<&LogTailRecord> = matching.collect();
+        let skip = matching.len().saturating_sub(tail.backlog);
+        tail.pending
+            .extend(matching.into_iter().skip(skip).cloned());
+    } else {
+        tail.pending.extend(matching.cloned());
+    }
+    if let Some(newest) = records.last() {
+        tail.last_seq = Some(newest.seq);
+    } else {
+        tail.last_seq.get_or_insert(0);
+    }
+}
+
+/// Find where the records newer than `last_seq` begin.
+///
+/// Walks back from the newest record while sequence numbers keep
+/// decreasing. A server restart resets the sequence, so when the newest
+/// record is older than `last_seq` every record since the restart is new.
+///
+/// # Arguments
+///
+/// * `records` - List window, oldest first.
+/// * `last_seq` - Newest sequence number already examined (0 for none).
+///
+/// # Returns
+///
+/// * Index of the first unseen record (`records.len()` when none).
+fn unseen_start(records: &[LogTailRecord], last_seq: u64) -> usize {
+    let Some(newest) = records.last() else {
+        return 0;
+    };
+    let restarted = newest.seq < last_seq;
+    let mut start = records.len();
+    while start > 0 {
+        let seq = records[start - 1].seq;
+        let continues = start == records.len() || seq < records[start].seq;
+        if !continues || (!restarted && seq <= last_seq) {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(seqs: &[u64]) -> Vec<LogTailRecord> {
+        seqs.iter()
+            .map(|&seq| LogTailRecord {
+                seq,
+                unix_millis: 0,
+                level: "INFO".to_owned(),
+                target: "server".to_owned(),
+                message: String::new(),
+                character_id: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unseen_start_handles_new_records_and_restarts() {
+        assert_eq!(unseen_start(&records(&[5, 6, 7, 8]), 6), 2);
+        assert_eq!(unseen_start(&records(&[5, 6, 7, 8]), 8), 4);
+        assert_eq!(unseen_start(&records(&[5, 6, 7, 8]), 0), 0);
+        // Server restarted after record 7: 1 and 2 are new.
+        assert_eq!(unseen_start(&records(&[6, 7, 1, 2]), 7), 2);
+        assert_eq!(unseen_start(&[], 3), 0);
+    }
+}
@@ -0,0 +1,137 @@
+//! Admin endpoints for online players: roster, kick and broadcast.
+//!
+//! The roster is read from the snapshot the server publishes under
+//! [`ONLINE_ROSTER_KEY`]; kick and broadcast are queued as world actions and
+//! executed on the server's tick thread, so callers poll
+//! `/admin/world/actions/status` for the outcome.
+
+use crate::ApiState;
+use crate::admin::routes_world_actions::enqueue_world_action;
+use crate::admin::types::{BroadcastRequest, ErrorResponse, PlayerKickRequest};
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use log::warn;
+use mag_core::online_roster_store::{ONLINE_ROSTER_KEY, OnlineRoster};
+use mag_core::world_action_store::WorldActionKind;
+use redis::AsyncCommands;
+
+/// Longest announcement accepted by the broadcast endpoint.
+const MAX_BROADCAST_LEN: usize = 200;
+
+/// GET `/admin/players/online` - returns the latest online roster as JSON.
+pub(crate) async fn get_online_players(State(state): State<ApiState>) -> Response {
+    let mut con = state.con.clone();
+    let bytes: Option<Vec<u8>> = match con.get(ONLINE_ROSTER_KEY).await {
+        Ok(value) => value,
+        Err(error) => {
+            warn!(
+                "admin get_online_players GET {} failed: {}",
+                ONLINE_ROSTER_KEY, error
+            );
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "keydb_error",
+                "Failed to read online roster",
+            );
+        }
+    };
+
+    let Some(bytes) = bytes else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "not_available",
+            "No online roster; the game server is not running or not publishing",
+        );
+    };
+
+    match OnlineRoster::from_bytes(&bytes) {
+        Some(roster) => Json(roster).into_response(),
+        None => {
+            warn!(
+                "admin get_online_players decode failed for {}",
+                ONLINE_ROSTER_KEY
+            );
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "decode_error",
+                "Failed to decode online roster",
+            )
+        }
+    }
+}
+
+/// POST `/admin/players/kick` - queues a kick for the named online player.
+pub(crate) async fn kick_player(
+    State(state): State<ApiState>,
+    Json(body): Json<PlayerKickRequest>,
+) -> Response {
+    let name = body.name.trim();
+    if name.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "missing_name", "Provide a name");
+    }
+    enqueue_world_action(
+        &state,
+        WorldActionKind::KickPlayer {
+            name: name.to_owned(),
+        },
+    )
+    .await
+}
+
+/// POST `/admin/players/broadcast` - queues an announcement to all players.
+pub(crate) async fn broadcast(
+    State(state): State<ApiState>,
+    Json(body): Json<BroadcastRequest>,
+) -> Response {
+    let message = match validate_broadcast(&body.message) {
+        Ok(message) => message,
+        Err(reason) => return error_response(StatusCode::BAD_REQUEST, "invalid_message", reason),
+    };
+    enqueue_world_action(&state, WorldActionKind::Broadcast { message }).await
+}
+
+/// Trim and check a broadcast message.
+///
+/// # Arguments
+///
+/// * `message` - Text supplied by the admin.
+///
+/// # Returns
+///
+/// * `Ok(trimmed)` when the message is non-empty, single-line and at most
+///   [`MAX_BROADCAST_LEN`] characters; otherwise `Err(reason)`.
+fn validate_broadcast(message: &str) -> Result<String, &'static str> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err("Message is empty");
+    }
+    if message.chars().count() > MAX_BROADCAST_LEN {
+        return Err("Message is longer than 200 characters");
+    }
+    if message.chars().any(char::is_control) {
+        return Err("Message must be a single line without control characters");
+    }
+    Ok(message.to_owned())
+}
+
+fn error_response(status: StatusCode, code: &str, message: &str) -> Response {
+    (status, Json(ErrorResponse::new(code, message))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_broadcast_trims_and_rejects_bad_input() {
+        assert_eq!(
+            validate_broadcast("  Restart in 5 minutes  ").as_deref(),
+            Ok("Restart in 5 minutes")
+        );
+        assert!(validate_broadcast("   ").is_err());
+        assert!(validate_broadcast("two\nlines").is_err());
+        assert!(validate_broadcast(&"x".repeat(MAX_BROADCAST_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+//! Admin route serving the protocol message catalog.
+//!
+//! The catalog is generated from the `core` type definitions by
+//! `cargo xtask gen-protocol-docs` (checked in CI with `--check`) and
+//! embedded when the API is built, so it always matches the protocol the
+//! API and server were built against.
+
+use axum::http::header;
+use axum::response::IntoResponse;
+
+/// Generated Markdown catalog of every client and server message.
+const PROTOCOL_CATALOG: &str = include_str!("../../../docs/protocol.md");
+
+/// GET `/admin/protocol` - returns the protocol message catalog (opcodes,
+/// fields, sizes and the release that introduced each message) as Markdown.
+pub(crate) async fn get_protocol() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+        PROTOCOL_CATALOG,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_lists_both_directions() {
+        assert!(PROTOCOL_CATALOG.contains("## Client to server (`ClientCommandType`)"));
+        assert!(PROTOCOL_CATALOG.contains("## Server to client (`ServerCommandType`)"));
+        assert!(
+            PROTOCOL_CATALOG.contains("| Opcode | Name | Size | Fields | Since | Description |")
+        );
+    }
+}
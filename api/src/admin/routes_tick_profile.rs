@@ -0,0 +1,71 @@
+//! Admin route exposing the server's tick cost attribution report.
+
+use crate::ApiState;
+use crate::admin::types::ErrorResponse;
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use log::warn;
+use mag_core::tick_profile_store::{TICK_PROFILE_KEY, TickProfileReport};
+use redis::AsyncCommands;
+
+/// Query for `GET /admin/world/tick-profile`.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct TickProfileQuery {
+    /// Limit `top_characters` and `zones` to this many entries.
+    top: Option<usize>,
+}
+
+/// GET `/admin/world/tick-profile` - returns the latest tick cost report as
+/// JSON.
+pub(crate) async fn get_tick_profile(
+    State(state): State<ApiState>,
+    Query(q): Query<TickProfileQuery>,
+) -> Response {
+    let mut con = state.con.clone();
+    let bytes: Option<Vec<u8>> = match con.get(TICK_PROFILE_KEY).await {
+        Ok(value) => value,
+        Err(error) => {
+            warn!(
+                "admin get_tick_profile GET {} failed: {}",
+                TICK_PROFILE_KEY, error
+            );
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "keydb_error",
+                "Failed to read tick profile",
+            );
+        }
+    };
+
+    let Some(bytes) = bytes else {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            "not_available",
+            "No tick profile yet; the server publishes one every 30 seconds",
+        );
+    };
+
+    let Some(mut report) = TickProfileReport::from_bytes(&bytes) else {
+        warn!(
+            "admin get_tick_profile decode failed for {}",
+            TICK_PROFILE_KEY
+        );
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "decode_error",
+            "Failed to decode tick profile",
+        );
+    };
+
+    if let Some(top) = q.top {
+        report.top_characters.truncate(top);
+        report.zones.truncate(top);
+    }
+    Json(report).into_response()
+}
+
+fn error_response(status: StatusCode, code: &str, message: &str) -> Response {
+    (status, Json(ErrorResponse::new(code, message))).into_response()
+}
@@ -22,6 +22,21 @@ pub(crate) async fn request_world_action(
     State(state): State<ApiState>,
     Json(action): Json<WorldActionKind>,
 ) -> Response {
+    enqueue_world_action(&state, action).await
+}
+
+/// Queue `action` for the server and answer `202 Accepted` with its request
+/// id, or an error response when KeyDB cannot be written.
+///
+/// # Arguments
+///
+/// * `state` - Shared API state holding the KeyDB connection.
+/// * `action` - Action to queue.
+///
+/// # Returns
+///
+/// * The HTTP response to hand back to the admin caller.
+pub(crate) async fn enqueue_world_action(state: &ApiState, action: WorldActionKind) -> Response {
     let request_id = generate_request_id();
     let requested_at = SystemTime::now()
         .duration_since(UNIX_EPOCH)
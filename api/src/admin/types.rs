@@ -398,13 +398,13 @@ pub struct GlobalsResponse {
     pub ticker: i32,
     /// Total player online time.
     pub total_online_time: i64,
-    /// Online time bucketed by hour.
+    /// Online time bucketed by UTC hour.
     pub online_per_hour: [i64; 24],
     /// Global flag bitfield.
     pub flags: i32,
     /// Total server uptime.
     pub uptime: i64,
-    /// Server uptime bucketed by hour.
+    /// Server uptime bucketed by UTC hour.
     pub uptime_per_hour: [i64; 24],
     /// Awake-state counter.
     pub awake: i32,
@@ -426,7 +426,7 @@ pub struct GlobalsResponse {
     pub load: i64,
     /// Maximum online player count.
     pub max_online: i32,
-    /// Maximum online count bucketed by hour.
+    /// Maximum online count bucketed by UTC hour.
     pub max_online_per_hour: [i32; 24],
     /// Full-moon marker.
     pub fullmoon: i8,
@@ -573,3 +573,68 @@ pub struct WorldEntityVersionResponse {
     /// Current value of the kind's version counter.
     pub version: u64,
 }
+
+// ---------------------------------------------------------------------------
+//  Online players
+// ---------------------------------------------------------------------------
+
+/// Body for `POST /admin/players/kick`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerKickRequest {
+    /// Name of the online character to disconnect (case-insensitive).
+    pub name: String,
+}
+
+/// Body for `POST /admin/players/broadcast`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastRequest {
+    /// Announcement text shown to every online player.
+    pub message: String,
+}
+
+/// Query for `GET /admin/world/characters/by-name`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorldCharacterByNameQuery {
+    /// Character name to look up (case-insensitive, exact match).
+    pub name: String,
+}
+
+/// Game character slot matched by name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldCharacterMatch {
+    /// Game character slot index.
+    pub id: usize,
+    /// Character name, NUL-trimmed.
+    pub name: String,
+    /// Character reference text, NUL-trimmed.
+    pub reference: String,
+    /// `true` when the slot's `used` field is non-zero.
+    pub used: bool,
+    /// `true` for player characters.
+    pub is_player: bool,
+    /// Template the character was created from.
+    pub template: u16,
+    /// Map position.
+    pub x: i16,
+    /// Map position.
+    pub y: i16,
+    /// Carried gold.
+    pub gold: i32,
+    /// Total experience.
+    pub points_tot: i32,
+    /// Last login (Unix seconds) as stored by the server.
+    pub login_date: u32,
+    /// Accumulated online time as stored by the server.
+    pub total_online_time: u32,
+}
+
+/// Response for `GET /admin/world/characters/by-name`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldCharacterByNameResponse {
+    /// Name that was looked up.
+    pub query: String,
+    /// Number of matching slots.
+    pub count: usize,
+    /// Matching slots in slot order.
+    pub characters: Vec<WorldCharacterMatch>,
+}
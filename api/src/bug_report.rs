@@ -0,0 +1,106 @@
+//! Bug report uploads from the game client.
+//!
+//! Pressing Shift+F11 in the client packages a screenshot, the tail of the
+//! client log, a player-state snapshot and the client version into a zip and
+//! posts it here. Reports are written unmodified to [`BUG_REPORT_DIR_ENV`]
+//! (default `bugreports/`), named after the upload time and the account, so
+//! they can be picked up from the volume without going through KeyDB.
+
+use axum::body::Bytes;
+use axum::http::StatusCode;
+use log::{error, info, warn};
+use std::env;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::auth_extractor::AuthUser;
+
+/// Environment variable naming the directory reports are stored in.
+pub const BUG_REPORT_DIR_ENV: &str = "API_BUG_REPORT_DIR";
+
+/// Largest accepted report, in bytes.
+pub const MAX_BUG_REPORT_BYTES: usize = 8 * 1024 * 1024;
+
+/// Local file header signature every zip archive starts with.
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+fn bug_report_dir() -> PathBuf {
+    env::var(BUG_REPORT_DIR_ENV)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("bugreports"))
+}
+
+/// File name a report is stored under.
+///
+/// # Arguments
+/// * `unix_ms` - Upload time.
+/// * `account_id` - Uploading account.
+fn report_file_name(unix_ms: u128, account_id: u64) -> String {
+    format!("bugreport_{unix_ms}_{account_id}.zip")
+}
+
+/// Stores an uploaded bug report.
+///
+/// # Arguments
+/// * `auth` - Authenticated account.
+/// * `body` - The zip archive.
+///
+/// # Returns
+/// * `201` once stored, `400` if the body is not a zip archive, `401`
+///   without a valid token, `413` above [`MAX_BUG_REPORT_BYTES`] (enforced
+///   by the route's body limit), `500` on I/O failure.
+pub(crate) async fn submit_bug_report(auth: AuthUser, body: Bytes) -> StatusCode {
+    if !body.starts_with(ZIP_MAGIC) {
+        warn!(
+            "Rejected bug report from {}: body is not a zip archive",
+            auth.username_lc
+        );
+        return StatusCode::BAD_REQUEST;
+    }
+
+    let dir = bug_report_dir();
+    if let Err(err) = tokio::fs::create_dir_all(&dir).await {
+        error!(
+            "Failed to create bug report directory {}: {}",
+            dir.display(),
+            err
+        );
+        return StatusCode::INTERNAL_SERVER_ERROR;
+    }
+
+    let unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let path = dir.join(report_file_name(unix_ms, auth.account_id));
+    match tokio::fs::write(&path, &body).await {
+        Ok(()) => {
+            info!(
+                "Stored {} byte bug report from {} at {}",
+                body.len(),
+                auth.username_lc,
+                path.display()
+            );
+            StatusCode::CREATED
+        }
+        Err(err) => {
+            error!("Failed to write bug report {}: {}", path.display(), err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_names_sort_by_time_and_carry_the_account() {
+        let first = report_file_name(1_700_000_000_000, 42);
+        let second = report_file_name(1_700_000_000_001, 7);
+        assert_eq!(first, "bugreport_1700000000000_42.zip");
+        assert!(first < second);
+    }
+}
@@ -0,0 +1,446 @@
+//! Optional Discord account linking and role sync.
+//!
+//! A player links their game account to Discord through the OAuth2
+//! authorization-code flow: `POST /discord/link` returns the Discord
+//! authorization URL, and Discord redirects back to `GET /discord/callback`,
+//! where the code is exchanged for the player's Discord user id.
+//!
+//! The game server queues a [`DiscordRoleEvent`] whenever the rank or guild of
+//! a linked account's character changes (see [`mag_core::discord_store`]).
+//! When a bot endpoint is configured, [`spawn_role_sync`] forwards those
+//! events so community Discord servers can grant roles automatically.
+//!
+//! Configured via environment variables:
+//! - `DISCORD_CLIENT_ID` — OAuth2 application id (required).
+//! - `DISCORD_CLIENT_SECRET` — OAuth2 application secret (required).
+//! - `DISCORD_REDIRECT_URI` — public URL of `/discord/callback` (required).
+//! - `DISCORD_BOT_ENDPOINT` — URL role events are POSTed to (optional).
+//! - `DISCORD_BOT_TOKEN` — sent as `Authorization: Bearer` to the bot
+//!   endpoint (optional).
+
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use log::{error, info, warn};
+use mag_core::discord_store::{DISCORD_ROLE_EVENTS_KEY, DiscordGuild, DiscordRoleEvent};
+use mag_core::types::DiscordLinkResponse;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::time::Duration;
+
+use crate::auth_extractor::AuthUser;
+use crate::{ApiState, pipelines};
+
+const DISCORD_AUTHORIZE_URL: &str = "https://discord.com/oauth2/authorize";
+const DISCORD_TOKEN_URL: &str = "https://discord.com/api/oauth2/token";
+const DISCORD_ME_URL: &str = "https://discord.com/api/users/@me";
+
+/// Seconds a player has to finish the Discord authorization.
+const OAUTH_STATE_TTL_SECS: u64 = 600;
+
+/// Random bytes in an OAuth `state` value (hex-encoded to twice this length).
+const OAUTH_STATE_BYTES: usize = 16;
+
+/// How often queued role events are forwarded to the bot.
+const ROLE_SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Discord OAuth2 application and bot endpoint configuration.
+#[derive(Clone)]
+pub struct DiscordLink {
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+    bot_endpoint: Option<String>,
+    bot_token: Option<String>,
+    http: reqwest::Client,
+}
+
+/// Query Discord appends to the redirect URI.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set instead of `code` when the player declined.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscordUser {
+    id: String,
+}
+
+/// Body POSTed to the bot endpoint for each role event.
+#[derive(Debug, Serialize)]
+struct BotRoleUpdate<'a> {
+    discord_user_id: &'a str,
+    character_name: &'a str,
+    rank: &'a str,
+    guild: DiscordGuild,
+    unix_secs: u64,
+}
+
+impl DiscordLink {
+    /// Attempts to build the Discord configuration from environment variables.
+    ///
+    /// Returns `None` when any of the OAuth2 variables is unset or empty — the
+    /// API will start without Discord support and the link endpoints will
+    /// return 503.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(DiscordLink)` when OAuth2 is fully configured.
+    /// * `None` when required env vars are missing.
+    pub fn from_env() -> Option<Self> {
+        let non_empty = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let client_id = non_empty("DISCORD_CLIENT_ID")?;
+        let client_secret = non_empty("DISCORD_CLIENT_SECRET")?;
+        let redirect_uri = non_empty("DISCORD_REDIRECT_URI")?;
+        let bot_endpoint = non_empty("DISCORD_BOT_ENDPOINT");
+        let bot_token = non_empty("DISCORD_BOT_TOKEN");
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .ok()?;
+
+        info!(
+            "Discord linking configured (role sync {})",
+            if bot_endpoint.is_some() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+        Some(Self {
+            client_id,
+            client_secret,
+            redirect_uri,
+            bot_endpoint,
+            bot_token,
+            http,
+        })
+    }
+
+    /// Builds the Discord authorization URL for `oauth_state`.
+    fn authorize_url(&self, oauth_state: &str) -> String {
+        format!(
+            "{}?response_type=code&scope=identify&client_id={}&redirect_uri={}&state={}",
+            DISCORD_AUTHORIZE_URL,
+            percent_encode(&self.client_id),
+            percent_encode(&self.redirect_uri),
+            oauth_state
+        )
+    }
+
+    /// Exchanges an authorization code for the Discord user id.
+    ///
+    /// # Returns
+    /// * `Ok(discord_user_id)`, or `Err(message)` on any HTTP failure.
+    async fn resolve_user_id(&self, code: &str) -> Result<String, String> {
+        let token: TokenResponse = self
+            .http
+            .post(DISCORD_TOKEN_URL)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("token exchange failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("token response invalid: {}", e))?;
+
+        let user: DiscordUser = self
+            .http
+            .get(DISCORD_ME_URL)
+            .bearer_auth(&token.access_token)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("user lookup failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("user response invalid: {}", e))?;
+        Ok(user.id)
+    }
+}
+
+/// Percent-encodes a query parameter value.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Generates a new random OAuth `state` value.
+fn generate_oauth_state() -> String {
+    let mut bytes = [0u8; OAUTH_STATE_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Returns `true` if `oauth_state` has the shape of a generated state.
+///
+/// Checked before any KeyDB lookup so arbitrary input never reaches a key
+/// name.
+fn is_valid_oauth_state(oauth_state: &str) -> bool {
+    oauth_state.len() == OAUTH_STATE_BYTES * 2
+        && oauth_state
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Starts linking the authenticated account to Discord.
+///
+/// # Arguments
+/// * `state` - Shared API state.
+/// * `auth` - Authenticated account.
+///
+/// # Returns
+/// * `200` with the Discord authorization URL, `503` when Discord is not
+///   configured, `500` on KeyDB failure.
+pub(crate) async fn start_discord_link(
+    State(state): State<ApiState>,
+    auth: AuthUser,
+) -> (StatusCode, Json<DiscordLinkResponse>) {
+    let failure = |status: StatusCode, message: &str| {
+        (
+            status,
+            Json(DiscordLinkResponse {
+                url: None,
+                error: Some(message.to_owned()),
+            }),
+        )
+    };
+
+    let Some(discord) = state.discord.as_ref() else {
+        return failure(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Discord linking is not available",
+        );
+    };
+
+    let oauth_state = generate_oauth_state();
+    let mut con = state.con.clone();
+    if let Err(err) = pipelines::set_discord_oauth_state(
+        &mut con,
+        &oauth_state,
+        auth.account_id,
+        OAUTH_STATE_TTL_SECS,
+    )
+    .await
+    {
+        error!("Failed to store Discord OAuth state: {}", err);
+        return failure(StatusCode::INTERNAL_SERVER_ERROR, "Server error");
+    }
+
+    (
+        StatusCode::OK,
+        Json(DiscordLinkResponse {
+            url: Some(discord.authorize_url(&oauth_state)),
+            error: None,
+        }),
+    )
+}
+
+/// Completes a Discord link after the player authorized the application.
+///
+/// Opened in the player's browser by Discord's redirect, so it answers with
+/// plain text rather than JSON.
+///
+/// # Arguments
+/// * `state` - Shared API state.
+/// * `query` - `code` and `state` (or `error`) from Discord.
+///
+/// # Returns
+/// * `200` when linked, `400` for a declined, unknown or expired request,
+///   `502` when Discord cannot be reached, `503` when Discord is not
+///   configured, `500` on KeyDB failure.
+pub(crate) async fn discord_callback(
+    State(state): State<ApiState>,
+    Query(query): Query<CallbackQuery>,
+) -> (StatusCode, &'static str) {
+    let Some(discord) = state.discord.as_ref() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Discord linking is not available.",
+        );
+    };
+    if query.error.is_some() {
+        return (StatusCode::BAD_REQUEST, "Discord linking was cancelled.");
+    }
+    let (Some(code), Some(oauth_state)) = (query.code, query.state) else {
+        return (StatusCode::BAD_REQUEST, "Invalid Discord response.");
+    };
+    if !is_valid_oauth_state(&oauth_state) {
+        return (StatusCode::BAD_REQUEST, "Invalid Discord response.");
+    }
+
+    let mut con = state.con.clone();
+    let account_id = match pipelines::take_discord_oauth_state(&mut con, &oauth_state).await {
+        Ok(Some(account_id)) => account_id,
+        Ok(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "This link has expired. Please start again from the game.",
+            );
+        }
+        Err(err) => {
+            error!("Redis read failed: {}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Server error.");
+        }
+    };
+
+    let discord_user_id = match discord.resolve_user_id(&code).await {
+        Ok(id) => id,
+        Err(err) => {
+            warn!("Discord link for account {} failed: {}", account_id, err);
+            return (StatusCode::BAD_GATEWAY, "Could not reach Discord.");
+        }
+    };
+
+    if let Err(err) = pipelines::link_discord_account(&mut con, account_id, &discord_user_id).await
+    {
+        error!("Failed to store Discord link: {}", err);
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Server error.");
+    }
+
+    info!(
+        "Account {} linked to Discord user {}",
+        account_id, discord_user_id
+    );
+    (
+        StatusCode::OK,
+        "Your Discord account is linked. You can close this window.",
+    )
+}
+
+/// Removes the authenticated account's Discord link.
+///
+/// # Arguments
+/// * `state` - Shared API state.
+/// * `auth` - Authenticated account.
+///
+/// # Returns
+/// * `200` when unlinked (or nothing was linked), `500` on KeyDB failure.
+pub(crate) async fn unlink_discord(State(state): State<ApiState>, auth: AuthUser) -> StatusCode {
+    let mut con = state.con.clone();
+    match pipelines::unlink_discord_account(&mut con, auth.account_id).await {
+        Ok(unlinked) => {
+            if unlinked {
+                info!("Account {} unlinked from Discord", auth.username_lc);
+            }
+            StatusCode::OK
+        }
+        Err(err) => {
+            error!("Failed to unlink Discord: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Spawns the task that forwards queued role events to the bot endpoint.
+///
+/// Does nothing when no bot endpoint is configured. Events of accounts that
+/// were unlinked meanwhile are dropped. When the bot cannot be reached the
+/// event is put back and retried on the next pass.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `discord` - Discord configuration.
+pub fn spawn_role_sync(mut con: redis::aio::ConnectionManager, discord: DiscordLink) {
+    if discord.bot_endpoint.is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ROLE_SYNC_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = forward_role_events(&mut con, &discord).await {
+                warn!("Discord role sync: {}", err);
+            }
+        }
+    });
+}
+
+/// Forwards every queued role event, oldest first.
+async fn forward_role_events(
+    con: &mut redis::aio::ConnectionManager,
+    discord: &DiscordLink,
+) -> Result<(), String> {
+    let Some(endpoint) = discord.bot_endpoint.as_deref() else {
+        return Ok(());
+    };
+    loop {
+        let bytes: Option<Vec<u8>> = con
+            .rpop(DISCORD_ROLE_EVENTS_KEY, None)
+            .await
+            .map_err(|e| format!("RPOP {} failed: {}", DISCORD_ROLE_EVENTS_KEY, e))?;
+        let Some(bytes) = bytes else {
+            return Ok(());
+        };
+        let Some(event) = DiscordRoleEvent::from_bytes(&bytes) else {
+            warn!("Discord role sync: dropping undecodable event");
+            continue;
+        };
+        let discord_user_id = pipelines::get_account_discord_id(con, event.account_id)
+            .await
+            .map_err(|e| format!("Redis read failed: {}", e))?;
+        let Some(discord_user_id) = discord_user_id else {
+            continue;
+        };
+
+        let update = BotRoleUpdate {
+            discord_user_id: &discord_user_id,
+            character_name: &event.character_name,
+            rank: &event.rank,
+            guild: event.guild,
+            unix_secs: event.unix_secs,
+        };
+        let mut request = discord.http.post(endpoint).json(&update);
+        if let Some(token) = discord.bot_token.as_deref() {
+            request = request.bearer_auth(token);
+        }
+        if let Err(err) = request.send().await.and_then(|r| r.error_for_status()) {
+            // Put it back at the consuming end so order is kept.
+            let _: Result<(), _> = con.rpush(DISCORD_ROLE_EVENTS_KEY, bytes).await;
+            return Err(format!("bot endpoint failed: {}", err));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oauth_state_and_query_encoding() {
+        let a = generate_oauth_state();
+        assert!(is_valid_oauth_state(&a));
+        assert_ne!(a, generate_oauth_state());
+        assert!(!is_valid_oauth_state("discord:user:1"));
+
+        assert_eq!(
+            percent_encode("https://example.com/discord/callback?x=1"),
+            "https%3A%2F%2Fexample.com%2Fdiscord%2Fcallback%3Fx%3D1"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Public, read-only economy endpoints for community sites.
+//!
+//! The game server aggregates merchant stock and trades into an
+//! [`EconomyReport`] and refreshes it every [`ECONOMY_INTERVAL_SECS`] (see
+//! [`mag_core::economy_store`]). These handlers serve views of that report.
+//! Responses are cacheable for one refresh interval and readable from any
+//! origin; the public router's per-IP rate limit applies.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use log::{error, warn};
+use mag_core::economy_store::{
+    ECONOMY_INTERVAL_SECS, ECONOMY_REPORT_KEY, EconomyReport, ItemPriceHistory, MAX_RECENT_TRADES,
+    MerchantListing, Trade,
+};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::ApiState;
+
+/// Trades returned by `GET /economy/trades` when no limit is given.
+const DEFAULT_TRADE_LIMIT: usize = 25;
+
+/// Body of `GET /economy/listings`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ListingsResponse {
+    pub generated_at: u64,
+    pub listings: Vec<MerchantListing>,
+}
+
+/// Body of `GET /economy/trades`.
+#[derive(Debug, Serialize)]
+pub(crate) struct TradesResponse {
+    pub generated_at: u64,
+    pub trades: Vec<Trade>,
+}
+
+/// Body of `GET /economy/prices/{template_id}`.
+#[derive(Debug, Serialize)]
+pub(crate) struct PriceHistoryResponse {
+    pub generated_at: u64,
+    pub template_id: u32,
+    /// One entry per trade side the item has been traded on.
+    pub history: Vec<ItemPriceHistory>,
+}
+
+/// Query for `GET /economy/trades`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct TradesQuery {
+    pub limit: Option<usize>,
+}
+
+/// Reads the latest economy report from KeyDB.
+///
+/// # Returns
+/// * `Ok(report)`, or the status code to return when the report is missing
+///   (`503`) or unreadable (`500`).
+async fn load_report(state: &ApiState) -> Result<EconomyReport, StatusCode> {
+    let mut con = state.con.clone();
+    let bytes: Option<Vec<u8>> = con.get(ECONOMY_REPORT_KEY).await.map_err(|err| {
+        error!("Redis read failed: {}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let Some(bytes) = bytes else {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+    EconomyReport::from_bytes(&bytes).ok_or_else(|| {
+        warn!("Economy report decode failed");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Returns the current stock of every merchant.
+///
+/// # Returns
+/// * `200` with [`ListingsResponse`] JSON, `503` before the server has
+///   published a report, `500` on KeyDB or decode failure.
+pub(crate) async fn get_listings(State(state): State<ApiState>) -> Response {
+    let response = match load_report(&state).await {
+        Ok(report) => Json(ListingsResponse {
+            generated_at: report.generated_at,
+            listings: report.listings,
+        })
+        .into_response(),
+        Err(status) => status.into_response(),
+    };
+    with_public_cache(response)
+}
+
+/// Returns the most recent merchant trades, newest first.
+///
+/// # Arguments
+/// * `query` - Optional `?limit=`, clamped to [`MAX_RECENT_TRADES`].
+///
+/// # Returns
+/// * `200` with [`TradesResponse`] JSON, `503` before the server has
+///   published a report, `500` on KeyDB or decode failure.
+pub(crate) async fn get_recent_trades(
+    State(state): State<ApiState>,
+    Query(query): Query<TradesQuery>,
+) -> Response {
+    let response = match load_report(&state).await {
+        Ok(mut report) => {
+            report.recent_trades.truncate(trade_limit(query.limit));
+            Json(TradesResponse {
+                generated_at: report.generated_at,
+                trades: report.recent_trades,
+            })
+            .into_response()
+        }
+        Err(status) => status.into_response(),
+    };
+    with_public_cache(response)
+}
+
+/// Returns the hourly price history of one item template.
+///
+/// # Arguments
+/// * `template_id` - Item template id from the path.
+///
+/// # Returns
+/// * `200` with [`PriceHistoryResponse`] JSON, `404` if the item has no
+///   trades in the kept history, `503` before the server has published a
+///   report, `500` on KeyDB or decode failure.
+pub(crate) async fn get_price_history(
+    State(state): State<ApiState>,
+    Path(template_id): Path<u32>,
+) -> Response {
+    let response = match load_report(&state).await {
+        Ok(report) => {
+            let history: Vec<ItemPriceHistory> = report
+                .price_history
+                .into_iter()
+                .filter(|h| h.template_id == template_id)
+                .collect();
+            if history.is_empty() {
+                StatusCode::NOT_FOUND.into_response()
+            } else {
+                Json(PriceHistoryResponse {
+                    generated_at: report.generated_at,
+                    template_id,
+                    history,
+                })
+                .into_response()
+            }
+        }
+        Err(status) => status.into_response(),
+    };
+    with_public_cache(response)
+}
+
+/// Clamps a requested trade count to `1..=MAX_RECENT_TRADES`.
+fn trade_limit(requested: Option<usize>) -> usize {
+    requested
+        .unwrap_or(DEFAULT_TRADE_LIMIT)
+        .clamp(1, MAX_RECENT_TRADES)
+}
+
+/// Lets browsers and proxies cache a response for one refresh interval and
+/// lets pages on any origin read it.
+fn with_public_cache(mut response: Response) -> Response {
+    response.headers_mut().insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    if response.status().is_success() {
+        let cache_control = format!("public, max-age={}", ECONOMY_INTERVAL_SECS);
+        if let Ok(value) = HeaderValue::from_str(&cache_control) {
+            response.headers_mut().insert(header::CACHE_CONTROL, value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trade_limit_is_clamped() {
+        assert_eq!(trade_limit(None), DEFAULT_TRADE_LIMIT);
+        assert_eq!(trade_limit(Some(0)), 1);
+        assert_eq!(trade_limit(Some(10)), 10);
+        assert_eq!(trade_limit(Some(100_000)), MAX_RECENT_TRADES);
+    }
+}
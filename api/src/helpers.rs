@@ -1,4 +1,6 @@
-use jsonwebtoken::{DecodingKey, TokenData, Validation};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, TokenData, Validation};
 use lazy_static::lazy_static;
 use log::error;
 use mag_core::types::JwtClaims;
@@ -32,6 +34,36 @@ pub fn verify_token(token: &str, secret: &[u8]) -> Result<TokenData<JwtClaims>,
     Ok(token_data)
 }
 
+/// Lifetime of an issued session token, in seconds.
+pub(crate) const TOKEN_TTL_SECS: u64 = 3600;
+
+/// Signs a session token for `username_lc` that expires [`TOKEN_TTL_SECS`]
+/// from now.
+///
+/// # Arguments
+/// * `username_lc` - Lowercase username stored in the `sub` claim.
+/// * `secret` - HMAC signing secret (raw bytes) for HS256.
+///
+/// # Returns
+/// * `Ok(String)` with the encoded JWT.
+/// * `Err(String)` if encoding fails.
+pub(crate) fn issue_token(username_lc: &str, secret: &[u8]) -> Result<String, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let claims = JwtClaims {
+        sub: username_lc.to_owned(),
+        exp: (now + TOKEN_TTL_SECS) as usize,
+    };
+    jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret),
+    )
+    .map_err(|err| format!("JWT encode failed: {}", err))
+}
+
 /// Retrieves the JWT token from the `Authorization` header in the provided headers.
 ///
 /// # Arguments
@@ -253,7 +285,7 @@ mod tests {
     use jsonwebtoken::{EncodingKey, Header};
     use mag_core::types::JwtClaims;
 
-    use super::verify_token;
+    use super::{issue_token, verify_token};
 
     #[test]
     fn token_from_headers_extracts_bearer() {
@@ -303,6 +335,20 @@ mod tests {
         assert!(result.is_ok(), "expected valid token");
     }
 
+    #[test]
+    fn issued_token_verifies_with_future_expiry() {
+        let token = issue_token("tester", b"test-secret").expect("issue token");
+        let data = verify_token(&token, b"test-secret").expect("verify token");
+        assert_eq!(data.claims.sub, "tester");
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as usize;
+        assert!(data.claims.exp > now);
+        assert!(data.claims.exp <= now + super::TOKEN_TTL_SECS as usize);
+    }
+
     #[test]
     fn verify_token_rejects_invalid_jwt() {
         let result = verify_token("not-a-jwt", b"test-secret");
@@ -1,13 +1,18 @@
 pub mod admin;
 pub mod auth_extractor;
+pub mod bug_report;
+pub mod discord;
+pub mod economy;
 pub mod email;
 pub mod helpers;
+pub mod overlay;
 pub mod password;
 pub mod pipelines;
 pub mod rate_limit;
 pub mod routes;
 
 use axum::Router;
+use axum::extract::DefaultBodyLimit;
 use axum::middleware;
 use axum::routing::{delete, get, post, put};
 use log::{LevelFilter, error, info, warn};
@@ -18,6 +23,7 @@ use std::sync::Arc;
 use std::time::Duration as StdDuration;
 use tokio::time::sleep;
 
+use crate::discord::DiscordLink;
 use crate::email::EmailSender;
 
 /// Minimum acceptable JWT secret length, in bytes. Anything shorter forces a
@@ -33,6 +39,9 @@ pub struct ApiState {
     pub con: redis::aio::ConnectionManager,
     /// Optional email sender (None when SMTP is not configured).
     pub email_sender: Option<EmailSender>,
+    /// Optional Discord linking configuration (None when OAuth2 is not
+    /// configured).
+    pub discord: Option<DiscordLink>,
     /// HMAC signing secret for HS256 JWTs. Cached once at startup so
     /// per-request handlers never hit `env::var`.
     pub jwt_secret: Arc<Vec<u8>>,
@@ -192,9 +201,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         warn!("SMTP not configured — password reset emails will not be sent (set SMTP_HOST)");
     }
 
+    let discord = DiscordLink::from_env();
+    match &discord {
+        Some(link) => discord::spawn_role_sync(con.clone(), link.clone()),
+        None => warn!("Discord not configured — account linking disabled (set DISCORD_CLIENT_ID)"),
+    }
+
     let state = ApiState {
         con,
         email_sender,
+        discord,
         jwt_secret,
     };
 
@@ -215,6 +231,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let public_router = Router::new()
         // Public routes
         .route("/login", post(routes::login))
+        .route("/refresh", post(routes::refresh_token))
         .route("/accounts", post(routes::create_account))
         .route(
             "/accounts/reset-password/request",
@@ -224,24 +241,68 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/accounts/reset-password/confirm",
             post(routes::confirm_password_reset),
         )
+        // Short aliases for the reset endpoints.
+        .route(
+            "/accounts/reset-request",
+            post(routes::request_password_reset),
+        )
+        .route(
+            "/accounts/reset-confirm",
+            post(routes::confirm_password_reset),
+        )
         // Token required routes
         .route("/game/login_ticket", post(routes::create_game_login_ticket))
         .route("/characters", get(routes::get_characters))
         .route("/characters", post(routes::create_new_character))
         .route("/characters/{id}", put(routes::update_character))
         .route("/characters/{id}", delete(routes::delete_character))
+        .route(
+            "/characters/{id}/overlay_token",
+            post(overlay::create_overlay_token),
+        )
+        .route(
+            "/characters/{id}/overlay_token",
+            delete(overlay::revoke_overlay_token),
+        )
+        .route(
+            "/bugreport",
+            post(bug_report::submit_bug_report)
+                .layer(DefaultBodyLimit::max(bug_report::MAX_BUG_REPORT_BYTES)),
+        )
+        .route("/discord/link", post(discord::start_discord_link))
+        .route("/discord/link", delete(discord::unlink_discord))
+        // Discord OAuth2 redirect (authenticated by the OAuth state)
+        .route("/discord/callback", get(discord::discord_callback))
+        // Overlay token (not session JWT) routes
+        .route("/overlay/stats", get(overlay::get_overlay_stats))
+        // Public economy data for community sites
+        .route("/economy/listings", get(economy::get_listings))
+        .route("/economy/trades", get(economy::get_recent_trades))
+        .route(
+            "/economy/prices/{template_id}",
+            get(economy::get_price_history),
+        )
         .layer(middleware::from_fn_with_state(
             state.clone(),
             rate_limit::per_ip_rate_limit,
         ))
         .with_state(state.clone());
 
+    // Probes stay outside the per-IP rate limit; orchestrators poll them
+    // every few seconds from the same address.
+    let health_router = Router::new()
+        .route("/healthz", get(routes::healthz))
+        .route("/readyz", get(routes::readyz))
+        .with_state(state.clone());
+
     let app = match admin_router {
         Some(admin) => Router::new()
+            .merge(health_router)
             .merge(public_router)
             .nest("/admin", admin)
             .layer(RealIpLayer::default()),
         None => Router::new()
+            .merge(health_router)
             .merge(public_router)
             .layer(RealIpLayer::default()),
     };
@@ -0,0 +1,247 @@
+//! Stream overlay endpoints.
+//!
+//! A player opts a character in by requesting an overlay token for it. The
+//! token is scoped to that one character and only grants read access to its
+//! overlay stats, so it can be pasted into streaming software without
+//! exposing the account's session token. Issuing a new token revokes the old
+//! one; deleting the token opts the character out again.
+//!
+//! The stats themselves are published to KeyDB by the game server (see
+//! [`mag_core::stream_overlay_store`]) while the character is online.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use log::{error, info, warn};
+use mag_core::stream_overlay_store::{OverlayStats, overlay_stats_key};
+use mag_core::types::OverlayTokenResponse;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use redis::AsyncCommands;
+use serde::Deserialize;
+
+use crate::auth_extractor::AuthUser;
+use crate::{ApiState, helpers, pipelines};
+
+/// Random bytes in an overlay token (hex-encoded to twice this length).
+const OVERLAY_TOKEN_BYTES: usize = 24;
+
+/// Query for `GET /overlay/stats`.
+///
+/// Browser sources cannot set headers, so the token may be passed as
+/// `?token=` instead of `Authorization: Bearer`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct OverlayQuery {
+    pub token: Option<String>,
+}
+
+/// Generates a new random overlay token.
+///
+/// # Returns
+/// * Lowercase hex string of [`OVERLAY_TOKEN_BYTES`] random bytes.
+fn generate_overlay_token() -> String {
+    let mut bytes = [0u8; OVERLAY_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Returns `true` if `token` has the shape of an overlay token.
+///
+/// Checked before any KeyDB lookup so arbitrary input never reaches a key
+/// name.
+///
+/// # Arguments
+/// * `token` - Token presented by the caller.
+fn is_valid_overlay_token(token: &str) -> bool {
+    token.len() == OVERLAY_TOKEN_BYTES * 2
+        && token
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Checks that the authenticated account owns `character_id`.
+///
+/// # Returns
+/// * `Ok(())` if owned, otherwise the status code to return.
+async fn check_owner(
+    state: &ApiState,
+    auth: &AuthUser,
+    character_id: u64,
+) -> Result<(), StatusCode> {
+    let mut con = state.con.clone();
+    match pipelines::get_character_account_id(&mut con, character_id).await {
+        Ok(Some(owner)) if owner == auth.account_id => Ok(()),
+        Ok(_) => {
+            warn!(
+                "Overlay request rejected: character {} does not belong to user {}",
+                character_id, auth.username_lc
+            );
+            Err(StatusCode::UNAUTHORIZED)
+        }
+        Err(err) => {
+            error!("Redis read failed: {}", err);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Issues a stream overlay token for an owned character and opts it in.
+///
+/// # Arguments
+/// * `state` - Shared API state.
+/// * `auth` - Authenticated account.
+/// * `character_id` - API character ID from the path.
+///
+/// # Returns
+/// * `200` with the new token, `401` if the character is not owned, `500` on
+///   KeyDB failure.
+pub(crate) async fn create_overlay_token(
+    State(state): State<ApiState>,
+    auth: AuthUser,
+    Path(character_id): Path<u64>,
+) -> (StatusCode, Json<OverlayTokenResponse>) {
+    let failure = |status: StatusCode, message: &str| {
+        (
+            status,
+            Json(OverlayTokenResponse {
+                token: None,
+                error: Some(message.to_owned()),
+            }),
+        )
+    };
+
+    if let Err(status) = check_owner(&state, &auth, character_id).await {
+        return failure(status, "Character not found");
+    }
+
+    let token = generate_overlay_token();
+    let mut con = state.con.clone();
+    if let Err(err) = pipelines::set_overlay_token(&mut con, character_id, &token).await {
+        error!("Failed to store overlay token: {}", err);
+        return failure(StatusCode::INTERNAL_SERVER_ERROR, "Server error");
+    }
+
+    info!(
+        "Overlay token issued for character {} (account {})",
+        character_id, auth.username_lc
+    );
+    (
+        StatusCode::OK,
+        Json(OverlayTokenResponse {
+            token: Some(token),
+            error: None,
+        }),
+    )
+}
+
+/// Revokes a character's overlay token and opts it out.
+///
+/// # Arguments
+/// * `state` - Shared API state.
+/// * `auth` - Authenticated account.
+/// * `character_id` - API character ID from the path.
+///
+/// # Returns
+/// * `200` when revoked (or nothing was issued), `401` if the character is
+///   not owned, `500` on KeyDB failure.
+pub(crate) async fn revoke_overlay_token(
+    State(state): State<ApiState>,
+    auth: AuthUser,
+    Path(character_id): Path<u64>,
+) -> StatusCode {
+    if let Err(status) = check_owner(&state, &auth, character_id).await {
+        return status;
+    }
+
+    let mut con = state.con.clone();
+    match pipelines::revoke_overlay_token(&mut con, character_id).await {
+        Ok(revoked) => {
+            if revoked {
+                info!("Overlay token revoked for character {}", character_id);
+            }
+            StatusCode::OK
+        }
+        Err(err) => {
+            error!("Failed to revoke overlay token: {}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Returns the live overlay stats for the character an overlay token was
+/// issued for.
+///
+/// # Arguments
+/// * `state` - Shared API state.
+/// * `headers` - Request headers (`Authorization: Bearer <overlay token>`).
+/// * `query` - Optional `?token=` alternative to the header.
+///
+/// # Returns
+/// * `200` with [`OverlayStats`] JSON, `401` for an unknown token, `404`
+///   while the character is offline, `500` on KeyDB or decode failure.
+pub(crate) async fn get_overlay_stats(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Query(query): Query<OverlayQuery>,
+) -> Response {
+    let token = helpers::get_token_from_headers(&headers).or(query.token);
+    let Some(token) = token.filter(|t| is_valid_overlay_token(t)) else {
+        return with_cors(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let mut con = state.con.clone();
+    let character_id = match pipelines::get_overlay_character(&mut con, &token).await {
+        Ok(Some(id)) => id,
+        Ok(None) => return with_cors(StatusCode::UNAUTHORIZED.into_response()),
+        Err(err) => {
+            error!("Redis read failed: {}", err);
+            return with_cors(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+
+    let bytes: Option<Vec<u8>> = match con.get(overlay_stats_key(character_id)).await {
+        Ok(value) => value,
+        Err(err) => {
+            error!("Redis read failed: {}", err);
+            return with_cors(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+    let response = match bytes.as_deref().map(OverlayStats::from_bytes) {
+        Some(Some(stats)) => Json(stats).into_response(),
+        Some(None) => {
+            warn!("Overlay stats decode failed for character {}", character_id);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    };
+    with_cors(response)
+}
+
+/// Allows overlay pages served from any origin (e.g. a local file in
+/// streaming software) to read the response.
+fn with_cors(mut response: Response) -> Response {
+    response.headers_mut().insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_tokens_are_valid_and_distinct() {
+        let a = generate_overlay_token();
+        let b = generate_overlay_token();
+        assert!(is_valid_overlay_token(&a));
+        assert_ne!(a, b);
+
+        assert!(!is_valid_overlay_token(""));
+        assert!(!is_valid_overlay_token(&a.to_uppercase()));
+        assert!(!is_valid_overlay_token(&a[1..]));
+        assert!(!is_valid_overlay_token(&format!("{}:x", &a[2..])));
+    }
+}
@@ -1,4 +1,6 @@
 use log::info;
+use mag_core::discord_store::DISCORD_LINKED_KEY;
+use mag_core::stream_overlay_store::{OVERLAY_OPT_IN_KEY, overlay_stats_key};
 use mag_core::traits::{self, Class, Sex};
 use mag_core::types::CharacterSummary;
 use mag_core::{constants, template_store};
@@ -682,6 +684,9 @@ pub(crate) async fn delete_character(
     {
         log::warn!("failed to release character-name claim for '{name}': {err}");
     }
+    if let Err(err) = revoke_overlay_token(con, character_id).await {
+        log::warn!("failed to revoke overlay token for character {character_id}: {err}");
+    }
 
     con.del(character_key).await
 }
@@ -1196,6 +1201,252 @@ pub(crate) async fn get_character_id_by_name(
     con.get(&key).await
 }
 
+/// Builds the KeyDB key mapping a stream overlay token to its character.
+///
+/// # Arguments
+/// * `token` - Overlay token.
+///
+/// # Returns
+/// * Key in the form `overlay_token:{token}`.
+fn overlay_token_key(token: &str) -> String {
+    format!("overlay_token:{}", token)
+}
+
+/// Issues `token` as the character's stream overlay token and opts the
+/// character in to overlay publishing.
+///
+/// Any previously issued token for the character stops working.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `character_id` - API character ID.
+/// * `token` - Freshly generated overlay token.
+///
+/// # Returns
+/// * `Ok(())` on success.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn set_overlay_token(
+    con: &mut redis::aio::ConnectionManager,
+    character_id: u64,
+    token: &str,
+) -> Result<(), redis::RedisError> {
+    let character_key = format!("character:{}", character_id);
+    let previous: Option<String> = con.hget(&character_key, "overlay_token").await?;
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    if let Some(previous) = previous.as_deref() {
+        pipe.del(overlay_token_key(previous)).ignore();
+    }
+    pipe.set(overlay_token_key(token), character_id)
+        .ignore()
+        .hset(&character_key, "overlay_token", token)
+        .ignore()
+        .sadd(OVERLAY_OPT_IN_KEY, character_id)
+        .ignore();
+    pipe.query_async(&mut *con).await
+}
+
+/// Revokes the character's stream overlay token and opts it out of overlay
+/// publishing. Does nothing if no token was issued.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `character_id` - API character ID.
+///
+/// # Returns
+/// * `Ok(true)` if a token was revoked, `Ok(false)` if none existed.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn revoke_overlay_token(
+    con: &mut redis::aio::ConnectionManager,
+    character_id: u64,
+) -> Result<bool, redis::RedisError> {
+    let character_key = format!("character:{}", character_id);
+    let previous: Option<String> = con.hget(&character_key, "overlay_token").await?;
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    if let Some(previous) = previous.as_deref() {
+        pipe.del(overlay_token_key(previous)).ignore();
+    }
+    pipe.hdel(&character_key, "overlay_token")
+        .ignore()
+        .srem(OVERLAY_OPT_IN_KEY, character_id)
+        .ignore()
+        .del(overlay_stats_key(character_id))
+        .ignore();
+    pipe.query_async::<()>(&mut *con).await?;
+    Ok(previous.is_some())
+}
+
+/// Resolves a stream overlay token to the character it was issued for.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `token` - Overlay token presented by the caller.
+///
+/// # Returns
+/// * `Ok(Some(character_id))` if the token is current.
+/// * `Ok(None)` if the token is unknown or revoked.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn get_overlay_character(
+    con: &mut redis::aio::ConnectionManager,
+    token: &str,
+) -> Result<Option<u64>, redis::RedisError> {
+    con.get(overlay_token_key(token)).await
+}
+
+/// Builds the KeyDB key for a pending Discord OAuth `state` value.
+///
+/// # Arguments
+/// * `oauth_state` - Random state sent to Discord.
+///
+/// # Returns
+/// * Key in the form `discord:oauth_state:{state}`.
+fn discord_oauth_state_key(oauth_state: &str) -> String {
+    format!("discord:oauth_state:{}", oauth_state)
+}
+
+/// Builds the KeyDB key mapping a Discord user to the linked account.
+///
+/// # Arguments
+/// * `discord_user_id` - Discord user snowflake.
+///
+/// # Returns
+/// * Key in the form `discord:user:{id}`.
+fn discord_user_key(discord_user_id: &str) -> String {
+    format!("discord:user:{}", discord_user_id)
+}
+
+/// Remembers which account started a Discord link for `ttl_secs`.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `oauth_state` - Random state sent to Discord.
+/// * `account_id` - Account that requested the link.
+/// * `ttl_secs` - How long the link may take to complete.
+///
+/// # Returns
+/// * `Ok(())` on success.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn set_discord_oauth_state(
+    con: &mut redis::aio::ConnectionManager,
+    oauth_state: &str,
+    account_id: u64,
+    ttl_secs: u64,
+) -> Result<(), redis::RedisError> {
+    con.set_ex(discord_oauth_state_key(oauth_state), account_id, ttl_secs)
+        .await
+}
+
+/// Consumes a pending Discord OAuth state.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `oauth_state` - State returned by Discord.
+///
+/// # Returns
+/// * `Ok(Some(account_id))` if the state was pending; it is deleted.
+/// * `Ok(None)` if it is unknown, expired or already used.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn take_discord_oauth_state(
+    con: &mut redis::aio::ConnectionManager,
+    oauth_state: &str,
+) -> Result<Option<u64>, redis::RedisError> {
+    redis::cmd("GETDEL")
+        .arg(discord_oauth_state_key(oauth_state))
+        .query_async(&mut *con)
+        .await
+}
+
+/// Links an account to a Discord user and enables role sync for it.
+///
+/// Replaces the account's previous link, and moves the Discord user away
+/// from any other account it was linked to.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `account_id` - Account to link.
+/// * `discord_user_id` - Discord user snowflake.
+///
+/// # Returns
+/// * `Ok(())` on success.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn link_discord_account(
+    con: &mut redis::aio::ConnectionManager,
+    account_id: u64,
+    discord_user_id: &str,
+) -> Result<(), redis::RedisError> {
+    let account_key = format!("account:{}", account_id);
+    let previous_user: Option<String> = con.hget(&account_key, "discord_id").await?;
+    let previous_account: Option<u64> = con.get(discord_user_key(discord_user_id)).await?;
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    if let Some(previous_user) = previous_user.as_deref() {
+        pipe.del(discord_user_key(previous_user)).ignore();
+    }
+    if let Some(previous_account) = previous_account.filter(|id| *id != account_id) {
+        pipe.hdel(format!("account:{}", previous_account), "discord_id")
+            .ignore()
+            .srem(DISCORD_LINKED_KEY, previous_account)
+            .ignore();
+    }
+    pipe.set(discord_user_key(discord_user_id), account_id)
+        .ignore()
+        .hset(&account_key, "discord_id", discord_user_id)
+        .ignore()
+        .sadd(DISCORD_LINKED_KEY, account_id)
+        .ignore();
+    pipe.query_async(&mut *con).await
+}
+
+/// Removes an account's Discord link and disables role sync for it.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `account_id` - Account to unlink.
+///
+/// # Returns
+/// * `Ok(true)` if a link was removed, `Ok(false)` if none existed.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn unlink_discord_account(
+    con: &mut redis::aio::ConnectionManager,
+    account_id: u64,
+) -> Result<bool, redis::RedisError> {
+    let account_key = format!("account:{}", account_id);
+    let previous: Option<String> = con.hget(&account_key, "discord_id").await?;
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    if let Some(previous) = previous.as_deref() {
+        pipe.del(discord_user_key(previous)).ignore();
+    }
+    pipe.hdel(&account_key, "discord_id")
+        .ignore()
+        .srem(DISCORD_LINKED_KEY, account_id)
+        .ignore();
+    pipe.query_async::<()>(&mut *con).await?;
+    Ok(previous.is_some())
+}
+
+/// Looks up the Discord user an account is linked to.
+///
+/// # Arguments
+/// * `con` - KeyDB connection.
+/// * `account_id` - Account ID.
+///
+/// # Returns
+/// * `Ok(Some(discord_user_id))` if linked, `Ok(None)` otherwise.
+/// * `Err(redis::RedisError)` on KeyDB failure.
+pub(crate) async fn get_account_discord_id(
+    con: &mut redis::aio::ConnectionManager,
+    account_id: u64,
+) -> Result<Option<String>, redis::RedisError> {
+    con.hget(format!("account:{}", account_id), "discord_id")
+        .await
+}
+
 /// Lists characters belonging to an account using the per-account set and a
 /// pipelined `HGETALL` batch.
 ///
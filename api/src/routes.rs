@@ -13,8 +13,6 @@ use crate::rate_limit;
 
 use axum::response::IntoResponse;
 use axum::{Json, extract::ConnectInfo, extract::Path, extract::State, http::StatusCode};
-use jsonwebtoken::EncodingKey;
-use jsonwebtoken::Header;
 use log::{error, info, warn};
 use mag_core::types::CharacterSummary;
 use mag_core::types::CreateAccountRequest;
@@ -24,9 +22,9 @@ use mag_core::types::CreateGameLoginTicketRequest;
 use mag_core::types::CreateGameLoginTicketResponse;
 use mag_core::types::GameLoginTicketMetadata;
 use mag_core::types::GetCharactersResponse;
-use mag_core::types::JwtClaims;
 use mag_core::types::LoginRequest;
 use mag_core::types::LoginResponse;
+use mag_core::types::MAX_CHARACTERS_PER_ACCOUNT;
 use mag_core::types::ResetPasswordConfirm;
 use mag_core::types::ResetPasswordConfirmResponse;
 use mag_core::types::ResetPasswordRequest;
@@ -38,8 +36,6 @@ use rand::rngs::OsRng;
 use redis::AsyncCommands;
 use subtle::ConstantTimeEq;
 
-const MAX_CHARACTERS_PER_ACCOUNT: usize = 10;
-
 enum CharacterNameValidationError {
     BadRequest(String),
     Unprocessable(String),
@@ -1004,23 +1000,10 @@ pub(crate) async fn login(
         }
     }
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or(Duration::from_secs(0))
-        .as_secs();
-    let claims = JwtClaims {
-        sub: username_lc,
-        exp: (now + 3600) as usize,
-    };
-
-    let token = match jsonwebtoken::encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(state.jwt_secret.as_ref()),
-    ) {
+    let token = match helpers::issue_token(&username_lc, state.jwt_secret.as_ref()) {
         Ok(value) => value,
         Err(err) => {
-            error!("JWT encode failed: {}", err);
+            error!("{}", err);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 login_response(None, Some("Server error")),
@@ -1036,6 +1019,63 @@ pub(crate) async fn login(
     (StatusCode::OK, login_response(Some(token), None)).into_response()
 }
 
+/// Exchanges a still-valid session token for a fresh one.
+///
+/// Lets a long-running client stay signed in without storing the password.
+/// The presented token must verify and not be expired; the account must
+/// still exist and must not be banned.
+///
+/// # Arguments
+///
+/// * `state` - Shared API state (KeyDB connection, JWT secret).
+/// * `auth` - Account resolved from the presented bearer token.
+///
+/// # Returns
+///
+/// * `200` with a new token, `403` if the account is banned, or `500` on
+///   storage/signing errors. Invalid or expired tokens are rejected with
+///   `401` by the [`AuthUser`] extractor.
+pub(crate) async fn refresh_token(
+    State(state): State<ApiState>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let mut con = state.con.clone();
+    match routes_bans::account_is_banned(&mut con, auth.account_id).await {
+        Ok(true) => {
+            warn!(
+                "Token refresh rejected: account {} is banned",
+                auth.account_id
+            );
+            return (
+                StatusCode::FORBIDDEN,
+                login_response(None, Some("Account banned")),
+            )
+                .into_response();
+        }
+        Ok(false) => {}
+        Err(err) => {
+            error!("Redis read failed: {}", err);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                login_response(None, Some("Server error")),
+            )
+                .into_response();
+        }
+    }
+
+    match helpers::issue_token(&auth.username_lc, state.jwt_secret.as_ref()) {
+        Ok(token) => (StatusCode::OK, login_response(Some(token), None)).into_response(),
+        Err(err) => {
+            error!("{}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                login_response(None, Some("Server error")),
+            )
+                .into_response()
+        }
+    }
+}
+
 /// Builds the JSON body for a `/login` response.
 ///
 /// # Arguments
@@ -1060,6 +1100,10 @@ const MAX_RESET_ATTEMPTS_PER_IP: u64 = 3;
 /// TTL in seconds for both password reset codes and per-IP attempt counters.
 const RESET_TTL_SECS: u64 = 900;
 
+/// Wrong codes accepted against one issued reset code before it is burned.
+/// Without a cap the 6-digit code could be brute-forced within its TTL.
+const MAX_RESET_CONFIRM_FAILURES: u64 = 5;
+
 /// Initiates a password reset by sending a 6-digit code to the email on file.
 ///
 /// Always returns 200 with a generic message regardless of whether the
@@ -1258,9 +1302,23 @@ pub(crate) async fn confirm_password_reset(
         }
     };
 
-    // ── Retrieve stored code ─────────────────────────────────────────
+    // ── Count the attempt and retrieve stored code ───────────────────
+    // The attempt is counted before the code is compared, in one script, so
+    // parallel guesses cannot all read the code before any of them is
+    // counted. Nothing is counted once the code is gone: a plain HINCRBY
+    // after it expired would recreate the hash without a TTL.
     let reset_key = format!("password_reset:{}", account_id);
-    let stored_code: Option<String> = match con.hget(&reset_key, "code").await {
+    let attempt: Option<(u64, String)> = match redis::cmd("EVAL")
+        .arg(
+            "local code = redis.call('HGET', KEYS[1], 'code') \
+             if not code then return nil end \
+             return {redis.call('HINCRBY', KEYS[1], 'failed_attempts', 1), code}",
+        )
+        .arg(1)
+        .arg(&reset_key)
+        .query_async(&mut con)
+        .await
+    {
         Ok(v) => v,
         Err(err) => {
             error!("Redis read failed during password reset confirm: {err}");
@@ -1273,14 +1331,20 @@ pub(crate) async fn confirm_password_reset(
         }
     };
 
-    let stored_code = match stored_code {
-        Some(c) => c,
+    let (attempts, stored_code) = match attempt {
+        Some(a) => a,
         None => {
             warn!("Password reset confirm: no active reset code for account {account_id}");
             return fail("Invalid or expired reset code");
         }
     };
 
+    if attempts > MAX_RESET_CONFIRM_FAILURES {
+        warn!("Password reset confirm: code for account {account_id} has no attempts left");
+        let _: Result<(), _> = con.del(&reset_key).await;
+        return fail("Invalid or expired reset code");
+    }
+
     // ── Constant-time comparison ─────────────────────────────────────
     if stored_code
         .as_bytes()
@@ -1288,7 +1352,15 @@ pub(crate) async fn confirm_password_reset(
         .unwrap_u8()
         != 1
     {
-        warn!("Password reset confirm: code mismatch for account {account_id}");
+        if attempts >= MAX_RESET_CONFIRM_FAILURES {
+            warn!(
+                "Password reset confirm: code mismatch for account {account_id}; \
+                 code revoked after {attempts} failures"
+            );
+            let _: Result<(), _> = con.del(&reset_key).await;
+        } else {
+            warn!("Password reset confirm: code mismatch for account {account_id}");
+        }
         return fail("Invalid or expired reset code");
     }
 
@@ -1328,3 +1400,40 @@ pub(crate) async fn confirm_password_reset(
         }),
     )
 }
+
+/// Liveness probe: the process is up and serving HTTP.
+///
+/// # Returns
+/// * `StatusCode::OK` with body `ok`.
+pub(crate) async fn healthz() -> (StatusCode, &'static str) {
+    (StatusCode::OK, "ok")
+}
+
+/// Readiness probe: KeyDB is reachable and has finished loading its dataset.
+///
+/// Used by container healthchecks and Kubernetes readiness probes so traffic
+/// is only routed here once logins can actually succeed.
+///
+/// # Arguments
+/// * `state` - Shared API state holding the KeyDB connection.
+///
+/// # Returns
+/// * `StatusCode::OK` with body `ready` when KeyDB answers `INFO persistence` with `loading:0`.
+/// * `StatusCode::SERVICE_UNAVAILABLE` when KeyDB is unreachable or still loading.
+pub(crate) async fn readyz(State(state): State<ApiState>) -> (StatusCode, &'static str) {
+    let mut con = state.con.clone();
+    let info: redis::RedisResult<String> = redis::cmd("INFO")
+        .arg("persistence")
+        .query_async(&mut con)
+        .await;
+    match info {
+        Ok(info) if info.lines().any(|line| line.trim() == "loading:0") => {
+            (StatusCode::OK, "ready")
+        }
+        Ok(_) => (StatusCode::SERVICE_UNAVAILABLE, "keydb loading"),
+        Err(err) => {
+            warn!("Readiness check failed: {err}");
+            (StatusCode::SERVICE_UNAVAILABLE, "keydb unavailable")
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Screen-reader mode: text and speech output of key game state.
+//!
+//! When enabled, the game scene feeds new chat lines, the player's health
+//! and mana and the selected target into an [`Announcer`], which turns them
+//! into short sentences. Every sentence is appended to
+//! `mag_accessibility.txt` in the client data directory (truncated when the
+//! mode is switched on), so any screen reader or braille tool that follows a
+//! text file can read the game. Clients built with the `screen-reader`
+//! feature also speak the sentences through the OS speech API (SAPI on
+//! Windows, AVFoundation on macOS, Speech Dispatcher on Linux).
+//!
+//! Health and mana are not read on every tick: a change is announced once it
+//! reaches [`VITAL_STEP_PERCENT`] of the maximum, and dropping below
+//! [`LOW_VITAL_PERCENT`] is always announced.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::preferences;
+use crate::types::log_message::LogMessage;
+
+/// Share of the maximum a vital has to move before it is announced again.
+pub const VITAL_STEP_PERCENT: i32 = 10;
+
+/// Share of the maximum below which a vital is announced as low.
+pub const LOW_VITAL_PERCENT: i32 = 25;
+
+/// Tracks one vital (health or mana) and decides when it is worth reading.
+#[derive(Clone, Debug)]
+pub struct VitalWatch {
+    label: &'static str,
+    /// Value at the last announcement; `None` until the first update.
+    announced: Option<i32>,
+}
+
+impl VitalWatch {
+    /// Creates a watch that has not seen a value yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Name read before the value, e.g. `"Health"`.
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            announced: None,
+        }
+    }
+
+    /// Feeds the current value and returns what to announce, if anything.
+    ///
+    /// The first value is only remembered, so logging in does not read out
+    /// full health.
+    ///
+    /// # Arguments
+    ///
+    /// * `current` - Current value.
+    /// * `max` - Maximum value; nothing is announced while it is zero.
+    ///
+    /// # Returns
+    ///
+    /// * A sentence such as `"Health low, 20 of 100"`, or `None`.
+    pub fn update(&mut self, current: i32, max: i32) -> Option<String> {
+        if max <= 0 {
+            return None;
+        }
+        let current = current.clamp(0, max);
+        let Some(last) = self.announced else {
+            self.announced = Some(current);
+            return None;
+        };
+        let low = current * 100 < max * LOW_VITAL_PERCENT;
+        let was_low = last * 100 < max * LOW_VITAL_PERCENT;
+        let stepped = (current - last).abs() * 100 >= max * VITAL_STEP_PERCENT;
+        if !stepped && (low == was_low || current == last) {
+            return None;
+        }
+        self.announced = Some(current);
+        Some(if low {
+            format!("{} low, {} of {}", self.label, current, max)
+        } else {
+            format!("{} {} of {}", self.label, current, max)
+        })
+    }
+}
+
+/// Builds the sentence read when the selected target changes.
+///
+/// # Arguments
+///
+/// * `target` - Name and health percentage of the new target, or `None`
+///   when the selection was cleared.
+///
+/// # Returns
+///
+/// * The sentence to announce.
+pub fn target_announcement(target: Option<(&str, u8)>) -> String {
+    match target {
+        Some((name, proz)) => format!("Target {}, {} percent health", name, proz),
+        None => "Target cleared".to_owned(),
+    }
+}
+
+/// Turns game state into sentences for the screen reader.
+pub struct Announcer {
+    hp: VitalWatch,
+    mana: VitalWatch,
+    /// `(nr, id)` of the last announced target; `(0, 0)` for none.
+    target: (u16, u16),
+    export: Option<BufWriter<File>>,
+    #[cfg(feature = "screen-reader")]
+    speech: Option<tts::Tts>,
+}
+
+impl Announcer {
+    /// Opens the text export and, when built with the `screen-reader`
+    /// feature, the OS speech engine.
+    ///
+    /// Either output failing to open is logged; the announcer keeps working
+    /// with whatever is left.
+    ///
+    /// # Returns
+    ///
+    /// * A new announcer.
+    pub fn open() -> Self {
+        let path = preferences::accessibility_export_file_path();
+        let export = match File::create(&path) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(err) => {
+                log::warn!(
+                    "Screen reader export {} unavailable: {}",
+                    path.display(),
+                    err
+                );
+                None
+            }
+        };
+        #[cfg(feature = "screen-reader")]
+        let speech = tts::Tts::default()
+            .map_err(|err| log::warn!("Speech output unavailable: {}", err))
+            .ok();
+        Self {
+            hp: VitalWatch::new("Health"),
+            mana: VitalWatch::new("Mana"),
+            target: (0, 0),
+            export,
+            #[cfg(feature = "screen-reader")]
+            speech,
+        }
+    }
+
+    /// Announces a chat line.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - New chat log entry.
+    pub fn chat(&mut self, message: &LogMessage) {
+        let text = message.message.trim();
+        if !text.is_empty() {
+            self.announce(text, false);
+        }
+    }
+
+    /// Announces health and mana once they changed enough.
+    ///
+    /// # Arguments
+    ///
+    /// * `hp` - Current health.
+    /// * `max_hp` - Maximum health.
+    /// * `mana` - Current mana.
+    /// * `max_mana` - Maximum mana.
+    pub fn vitals(&mut self, hp: i32, max_hp: i32, mana: i32, max_mana: i32) {
+        if let Some(text) = self.hp.update(hp, max_hp) {
+            // Health trumps whatever is still being read.
+            self.announce(&text, true);
+        }
+        if let Some(text) = self.mana.update(mana, max_mana) {
+            self.announce(&text, false);
+        }
+    }
+
+    /// Announces the selected target when it changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `nr` - Character number of the selection; `0` for none.
+    /// * `id` - Character id of the selection.
+    /// * `name` - Target name, if known.
+    /// * `proz` - Target health percentage.
+    pub fn target(&mut self, nr: u16, id: u16, name: Option<&str>, proz: u8) {
+        if (nr, id) == self.target {
+            return;
+        }
+        self.target = (nr, id);
+        let text = if nr == 0 {
+            target_announcement(None)
+        } else {
+            target_announcement(Some((name.unwrap_or("unknown"), proz)))
+        };
+        self.announce(&text, true);
+    }
+
+    /// Writes a sentence to the export file and the speech engine.
+    fn announce(&mut self, text: &str, interrupt: bool) {
+        if let Some(export) = self.export.as_mut() {
+            let result = writeln!(export, "{}", text).and_then(|_| export.flush());
+            if let Err(err) = result {
+                log::warn!("Screen reader export failed: {}", err);
+                self.export = None;
+            }
+        }
+        self.speak(text, interrupt);
+    }
+
+    /// Reads a sentence through the OS speech engine.
+    #[cfg(feature = "screen-reader")]
+    fn speak(&mut self, text: &str, interrupt: bool) {
+        if let Some(speech) = self.speech.as_mut()
+            && let Err(err) = speech.speak(text, interrupt)
+        {
+            log::warn!("Speech output failed: {}", err);
+        }
+    }
+
+    /// Speech output is compiled out without the `screen-reader` feature.
+    #[cfg(not(feature = "screen-reader"))]
+    fn speak(&mut self, _text: &str, _interrupt: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vitals_are_read_in_steps_and_when_low() {
+        let mut hp = VitalWatch::new("Health");
+        assert_eq!(hp.update(100, 100), None);
+        assert_eq!(hp.update(95, 100), None);
+        assert_eq!(hp.update(88, 100), Some("Health 88 of 100".to_owned()));
+        assert_eq!(hp.update(30, 100), Some("Health 30 of 100".to_owned()));
+        // Crossing the low mark is read even for a small step.
+        assert_eq!(hp.update(24, 100), Some("Health low, 24 of 100".to_owned()));
+        assert_eq!(hp.update(22, 100), None);
+        assert_eq!(hp.update(0, 0), None);
+    }
+
+    #[test]
+    fn target_sentences() {
+        assert_eq!(
+            target_announcement(Some(("Goblin", 80))),
+            "Target Goblin, 80 percent health"
+        );
+        assert_eq!(target_announcement(None), "Target cleared");
+    }
+}
@@ -313,6 +313,44 @@ pub fn delete_character(base_url: &str, token: &str, character_id: u64) -> Resul
     Err(format!("{message} ({})", status.as_u16()))
 }
 
+/// Uploads a packaged bug report zip.
+///
+/// # Arguments
+/// * `base_url` - API base URL.
+/// * `token` - JWT bearer token.
+/// * `archive` - Zip archive built by [`crate::bug_report`].
+///
+/// # Returns
+/// * `Ok(())` on success.
+/// * `Err(String)` when the request fails.
+pub fn submit_bug_report(base_url: &str, token: &str, archive: Vec<u8>) -> Result<(), String> {
+    let client = cert_trust::build_reqwest_client()?;
+
+    let url = format!("{}/bugreport", base_url.trim_end_matches('/'));
+    let resp = client
+        .post(url)
+        .bearer_auth(token)
+        .header(reqwest::header::CONTENT_TYPE, "application/zip")
+        .body(archive)
+        .send()
+        .map_err(|err| format!("Bug report upload failed: {err}"))?;
+
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let message = match status {
+        StatusCode::BAD_REQUEST => "Invalid bug report",
+        StatusCode::UNAUTHORIZED => "Unauthorized",
+        StatusCode::PAYLOAD_TOO_LARGE => "Bug report too large",
+        StatusCode::INTERNAL_SERVER_ERROR => "Server error",
+        _ => "Bug report upload failed",
+    };
+
+    Err(format!("{message} ({})", status.as_u16()))
+}
+
 /// Creates a short-lived, one-time login ticket for the game server.
 ///
 /// The returned ticket is meant to be sent over the TCP login handshake using `CL_API_LOGIN`.
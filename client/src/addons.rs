@@ -0,0 +1,440 @@
+//! Read-only client addons.
+//!
+//! Addons are small [rhai](https://rhai.rs) scripts that players drop into
+//! the `addons/` folder of the client data directory, one sub-folder per
+//! addon with a `main.rhai` entry file. The folder name is the addon's name
+//! and is what `/addon enable|disable <name>` refers to.
+//!
+//! Scripts run in a sandboxed engine: there is no file, network or module
+//! access, every call has an operation budget, and the only data they get are
+//! copies of the player's state and chat lines. They cannot send anything to
+//! the server. An addon that errors is switched off for the rest of the
+//! session.
+//!
+//! A script may define any of these functions; `this` is a per-addon map that
+//! keeps its state between calls:
+//!
+//! * `init()` - called once after loading.
+//! * `on_chat(text, channel)` - called for every chat line; `channel` is
+//!   e.g. `"say"`, `"tell"` or `"combat"`.
+//! * `on_update(player)` - called a few times per second with a map of the
+//!   player's name, vitals, gold, points and position.
+//! * `panel()` - returns `#{ title: "...", lines: [...] }` to draw a panel,
+//!   or `()` to draw nothing.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use mag_core::chat::ChatChannel;
+use mag_core::string_operations::c_string_to_str;
+use mag_core::types::ClientPlayer;
+use rhai::{AST, CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope};
+
+use crate::types::log_message::LogMessage;
+
+/// Script file loaded from each addon folder.
+pub const ADDON_ENTRY_FILE: &str = "main.rhai";
+
+/// Largest script accepted, in bytes.
+const MAX_SCRIPT_LEN: u64 = 256 * 1024;
+
+/// Operation budget for a single hook call.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Seconds between `on_update` / `panel` calls.
+const UPDATE_INTERVAL_SECS: f32 = 0.25;
+
+/// Most lines kept from a panel.
+pub const MAX_PANEL_LINES: usize = 12;
+
+/// Most characters kept per panel line or title.
+pub const MAX_PANEL_LINE_LEN: usize = 36;
+
+/// Text panel returned by an addon's `panel()` hook.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddonPanel {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// Load state of an addon, as listed by `/addon`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddonStatus {
+    Enabled,
+    /// Turned off in the settings; the script is not loaded.
+    Disabled,
+    /// Failed to load or errored while running.
+    Failed(String),
+}
+
+/// One loaded addon.
+struct Addon {
+    name: String,
+    /// `None` for disabled addons and scripts that failed to compile.
+    ast: Option<AST>,
+    scope: Scope<'static>,
+    /// Value bound to `this` in every hook call.
+    state: Dynamic,
+    status: AddonStatus,
+    panel: Option<AddonPanel>,
+}
+
+impl Addon {
+    fn unloaded(name: &str, status: AddonStatus) -> Self {
+        Self {
+            name: name.to_owned(),
+            ast: None,
+            scope: Scope::new(),
+            state: Dynamic::UNIT,
+            status,
+            panel: None,
+        }
+    }
+
+    /// Returns `true` if the script defines `hook`.
+    fn has_hook(&self, hook: &str) -> bool {
+        self.ast
+            .as_ref()
+            .is_some_and(|ast| ast.iter_functions().any(|f| f.name == hook))
+    }
+}
+
+/// Runs all addons of the current session.
+pub struct AddonHost {
+    engine: Engine,
+    addons: Vec<Addon>,
+    update_elapsed: f32,
+}
+
+impl Default for AddonHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AddonHost {
+    /// Creates a host with no addons and a sandboxed script engine.
+    ///
+    /// # Returns
+    ///
+    /// * An empty `AddonHost`.
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(4096);
+        engine.set_max_array_size(1024);
+        engine.set_max_map_size(1024);
+        engine.on_print(|text| log::info!("addon: {}", text));
+        Self {
+            engine,
+            addons: Vec::new(),
+            update_elapsed: 0.0,
+        }
+    }
+
+    /// Replaces the loaded addons with the ones found in `dir`.
+    ///
+    /// A missing directory simply means no addons are installed.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Addon folder; each sub-folder holding [`ADDON_ENTRY_FILE`]
+    ///   is one addon.
+    /// * `disabled` - Names of addons the player switched off.
+    pub fn load_dir(&mut self, dir: &Path, disabled: &[String]) {
+        self.addons.clear();
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        let mut folders: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.join(ADDON_ENTRY_FILE).is_file())
+            .collect();
+        folders.sort();
+
+        for folder in folders {
+            let Some(name) = folder.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if disabled.iter().any(|d| d.eq_ignore_ascii_case(name)) {
+                self.addons
+                    .push(Addon::unloaded(name, AddonStatus::Disabled));
+                continue;
+            }
+            match read_script(&folder.join(ADDON_ENTRY_FILE)) {
+                Ok(source) => self.load_source(name, &source),
+                Err(err) => {
+                    log::warn!("Addon '{}' not loaded: {}", name, err);
+                    self.addons
+                        .push(Addon::unloaded(name, AddonStatus::Failed(err)));
+                }
+            }
+        }
+        log::info!(
+            "Loaded {} addon(s) from {}",
+            self.addons.len(),
+            dir.display()
+        );
+    }
+
+    /// Compiles and initialises one addon.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Addon name.
+    /// * `source` - Script source.
+    pub fn load_source(&mut self, name: &str, source: &str) {
+        let mut addon = match self.engine.compile(source) {
+            Ok(ast) => Addon {
+                ast: Some(ast),
+                state: Dynamic::from_map(Map::new()),
+                ..Addon::unloaded(name, AddonStatus::Enabled)
+            },
+            Err(err) => {
+                log::warn!("Addon '{}' failed to compile: {}", name, err);
+                Addon::unloaded(name, AddonStatus::Failed(err.to_string()))
+            }
+        };
+        call_hook(&self.engine, &mut addon, "init", ());
+        self.addons.push(addon);
+    }
+
+    /// Lists the addons of this session and their state.
+    ///
+    /// # Returns
+    ///
+    /// * `(name, status)` per addon, sorted by name.
+    pub fn statuses(&self) -> Vec<(String, AddonStatus)> {
+        self.addons
+            .iter()
+            .map(|addon| (addon.name.clone(), addon.status.clone()))
+            .collect()
+    }
+
+    /// Passes a chat line to every addon's `on_chat` hook.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The chat line, as shown in the chat box.
+    pub fn on_chat(&mut self, message: &LogMessage) {
+        let channel = channel_name(message.style.channel);
+        for addon in &mut self.addons {
+            call_hook(
+                &self.engine,
+                addon,
+                "on_chat",
+                (message.message.clone(), channel.to_owned()),
+            );
+        }
+    }
+
+    /// Runs `on_update` and refreshes the panels at a fixed rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Frame time.
+    /// * `player` - Current player state, if in game.
+    pub fn update(&mut self, dt: Duration, player: Option<&ClientPlayer>) {
+        self.update_elapsed += dt.as_secs_f32();
+        if self.update_elapsed < UPDATE_INTERVAL_SECS {
+            return;
+        }
+        self.update_elapsed = 0.0;
+
+        let player = player.map(player_map);
+        for addon in &mut self.addons {
+            if let Some(player) = &player {
+                call_hook(&self.engine, addon, "on_update", (player.clone(),));
+            }
+            if let Some(value) = call_hook(&self.engine, addon, "panel", ()) {
+                addon.panel = parse_panel(value, &addon.name);
+            }
+            if addon.status != AddonStatus::Enabled {
+                addon.panel = None;
+            }
+        }
+    }
+
+    /// Returns the panels addons currently want drawn.
+    ///
+    /// # Returns
+    ///
+    /// * Panels in addon name order.
+    pub fn panels(&self) -> impl Iterator<Item = &AddonPanel> {
+        self.addons.iter().filter_map(|addon| addon.panel.as_ref())
+    }
+}
+
+/// Reads an addon script, refusing oversized files.
+fn read_script(path: &Path) -> Result<String, String> {
+    let len = fs::metadata(path)
+        .map_err(|err| format!("Failed to stat {}: {err}", path.display()))?
+        .len();
+    if len > MAX_SCRIPT_LEN {
+        return Err(format!(
+            "{} is larger than {MAX_SCRIPT_LEN} bytes",
+            path.display()
+        ));
+    }
+    fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))
+}
+
+/// Calls `hook` on an enabled addon with its state bound to `this`.
+///
+/// Errors are logged and switch the addon off.
+///
+/// # Returns
+///
+/// * The hook's return value, or `None` if it is not defined or failed.
+fn call_hook(
+    engine: &Engine,
+    addon: &mut Addon,
+    hook: &str,
+    args: impl FuncArgs,
+) -> Option<Dynamic> {
+    if addon.status != AddonStatus::Enabled || !addon.has_hook(hook) {
+        return None;
+    }
+    let ast = addon.ast.as_ref()?;
+    let options = CallFnOptions::new()
+        .eval_ast(false)
+        .bind_this_ptr(&mut addon.state);
+    match engine.call_fn_with_options::<Dynamic>(options, &mut addon.scope, ast, hook, args) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log::warn!(
+                "Addon '{}' disabled after error in {}(): {}",
+                addon.name,
+                hook,
+                err
+            );
+            addon.status = AddonStatus::Failed(err.to_string());
+            None
+        }
+    }
+}
+
+/// Builds the read-only player map handed to `on_update`.
+fn player_map(player: &ClientPlayer) -> Map {
+    let mut map = Map::new();
+    let name = c_string_to_str(&player.name).to_owned();
+    map.insert("name".into(), name.into());
+    map.insert("hp".into(), i64::from(player.a_hp).into());
+    map.insert("max_hp".into(), i64::from(player.hp[5]).into());
+    map.insert("endurance".into(), i64::from(player.a_end).into());
+    map.insert("max_endurance".into(), i64::from(player.end[5]).into());
+    map.insert("mana".into(), i64::from(player.a_mana).into());
+    map.insert("max_mana".into(), i64::from(player.mana[5]).into());
+    map.insert("gold".into(), i64::from(player.gold).into());
+    map.insert("points".into(), i64::from(player.points).into());
+    map.insert("total_points".into(), i64::from(player.points_tot).into());
+    map.insert("x".into(), i64::from(player.x).into());
+    map.insert("y".into(), i64::from(player.y).into());
+    map
+}
+
+/// Name of a chat channel as seen by scripts.
+fn channel_name(channel: ChatChannel) -> &'static str {
+    match channel {
+        ChatChannel::System => "system",
+        ChatChannel::Say => "say",
+        ChatChannel::Tell => "tell",
+        ChatChannel::Group => "group",
+        ChatChannel::Shout => "shout",
+        ChatChannel::Staff => "staff",
+        ChatChannel::Imp => "imp",
+        ChatChannel::Announce => "announce",
+        ChatChannel::Npc => "npc",
+        ChatChannel::Combat => "combat",
+        ChatChannel::Reward => "reward",
+        ChatChannel::Guild => "guild",
+    }
+}
+
+/// Converts a `panel()` return value, clamping it to the panel size limits.
+fn parse_panel(value: Dynamic, default_title: &str) -> Option<AddonPanel> {
+    let map = value.try_cast::<Map>()?;
+    let clamp = |text: String| text.chars().take(MAX_PANEL_LINE_LEN).collect::<String>();
+    let title = map
+        .get("title")
+        .map(|title| title.to_string())
+        .unwrap_or_else(|| default_title.to_owned());
+    let lines = map
+        .get("lines")
+        .and_then(|lines| lines.clone().try_cast::<rhai::Array>())
+        .unwrap_or_default()
+        .into_iter()
+        .take(MAX_PANEL_LINES)
+        .map(|line| clamp(line.to_string()))
+        .collect();
+    Some(AddonPanel {
+        title: clamp(title),
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mag_core::chat::ChatStyle;
+
+    const DAMAGE_METER: &str = r#"
+        fn init() { this.kills = 0; }
+        fn on_chat(text, channel) {
+            if channel == "combat" && text.starts_with("You killed") { this.kills += 1; }
+        }
+        fn on_update(player) { this.hp = player.hp; }
+        fn panel() { #{ title: "Meter", lines: [`Kills: ${this.kills}`, `HP: ${this.hp}`] } }
+    "#;
+
+    fn combat(text: &str) -> LogMessage {
+        LogMessage {
+            message: text.to_owned(),
+            color: crate::types::log_message::LogMessageColor::Red,
+            style: ChatStyle::new(ChatChannel::Combat),
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn hooks_keep_state_and_build_panel() {
+        let mut host = AddonHost::new();
+        host.load_source("meter", DAMAGE_METER);
+        host.on_chat(&combat("You killed Rat."));
+        host.on_chat(&combat("You killed Rat."));
+        let player = ClientPlayer {
+            a_hp: 42,
+            ..Default::default()
+        };
+        host.update(Duration::from_secs(1), Some(&player));
+
+        let panels: Vec<_> = host.panels().cloned().collect();
+        assert_eq!(
+            panels,
+            [AddonPanel {
+                title: "Meter".to_owned(),
+                lines: vec!["Kills: 2".to_owned(), "HP: 42".to_owned()],
+            }]
+        );
+    }
+
+    #[test]
+    fn runaway_and_broken_scripts_are_switched_off() {
+        let mut host = AddonHost::new();
+        host.load_source("spin", "fn init() { loop {} }");
+        host.load_source("typo", "fn panel( {");
+        host.update(Duration::from_secs(1), None);
+
+        let statuses = host.statuses();
+        assert!(matches!(statuses[0].1, AddonStatus::Failed(_)));
+        assert!(matches!(statuses[1].1, AddonStatus::Failed(_)));
+        assert_eq!(host.panels().count(), 0);
+    }
+}
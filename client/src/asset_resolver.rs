@@ -0,0 +1,213 @@
+//! Case-insensitive lookup of files under the client asset directory.
+//!
+//! Asset names in code do not always match the on-disk case (the legacy
+//! data uses names like `TLB.ICO`), which works on Windows and default macOS
+//! volumes but fails on case-sensitive filesystems. [`AssetResolver`] indexes
+//! the asset tree once and maps any relative path to the real file
+//! regardless of case. Missing assets are logged once per path with the
+//! path that was requested, so a broken install reports each problem
+//! clearly instead of failing repeatedly in every loader.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::filepaths;
+
+/// Assets the client cannot run without, checked once at startup.
+pub const REQUIRED_ASSETS: &[&str] = &["gfx/images.zip", "sfx", "music", "fonts"];
+
+/// Index of every file and directory below an asset root.
+pub struct AssetResolver {
+    root: PathBuf,
+    /// Lowercased `/`-separated relative path -> real path on disk.
+    index: HashMap<String, PathBuf>,
+    reported_missing: Mutex<HashSet<String>>,
+}
+
+impl AssetResolver {
+    /// Walks `root` recursively and indexes every entry.
+    ///
+    /// An unreadable root yields an empty index; every lookup then reports
+    /// the asset as missing.
+    ///
+    /// # Arguments
+    /// * `root` - Asset directory to index.
+    ///
+    /// # Returns
+    /// * A resolver for `root`.
+    pub fn scan(root: PathBuf) -> Self {
+        let mut index: HashMap<String, PathBuf> = HashMap::new();
+        let mut pending = vec![root.clone()];
+        while let Some(dir) = pending.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    log::warn!("Cannot read asset directory {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Some(key) = path.strip_prefix(&root).ok().and_then(index_key) else {
+                    continue;
+                };
+                if let Some(previous) = index.get(&key) {
+                    log::warn!(
+                        "Asset names differ only by case: {} and {}; using the first",
+                        previous.display(),
+                        path.display()
+                    );
+                    continue;
+                }
+                if path.is_dir() {
+                    pending.push(path.clone());
+                }
+                index.insert(key, path);
+            }
+        }
+        log::info!(
+            "Indexed {} asset entries under {}",
+            index.len(),
+            root.display()
+        );
+        Self {
+            root,
+            index,
+            reported_missing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Resolves a path relative to the asset root, ignoring case.
+    ///
+    /// # Arguments
+    /// * `relative` - Path relative to the asset root, e.g. `gfx/TLB.ICO`.
+    ///
+    /// # Returns
+    /// * `Some(path)` with the real on-disk path, or `None` if no such asset
+    ///   exists.
+    pub fn resolve(&self, relative: impl AsRef<Path>) -> Option<PathBuf> {
+        let key = index_key(relative.as_ref())?;
+        self.index.get(&key).cloned()
+    }
+
+    /// Resolves a path relative to the asset root, logging it once if missing.
+    ///
+    /// # Arguments
+    /// * `relative` - Path relative to the asset root.
+    ///
+    /// # Returns
+    /// * The real on-disk path, or the path joined to the asset root as-is
+    ///   when the asset is missing (so callers' own errors name it).
+    pub fn resolve_or_report(&self, relative: impl AsRef<Path>) -> PathBuf {
+        let relative = relative.as_ref();
+        if let Some(path) = self.resolve(relative) {
+            return path;
+        }
+        self.report_missing(relative);
+        self.root.join(relative)
+    }
+
+    /// Maps a path that may point into the asset root onto the real file.
+    ///
+    /// Paths outside the root, and paths that already exist, are returned
+    /// unchanged.
+    ///
+    /// # Arguments
+    /// * `path` - Absolute or relative path built by a loader.
+    ///
+    /// # Returns
+    /// * The real on-disk path when it can be resolved, otherwise `path`.
+    pub fn resolve_path(&self, path: &Path) -> PathBuf {
+        if path.exists() {
+            return path.to_path_buf();
+        }
+        match path.strip_prefix(&self.root) {
+            Ok(relative) => self.resolve_or_report(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    /// Returns the entries of `required` that do not exist.
+    ///
+    /// # Arguments
+    /// * `required` - Paths relative to the asset root.
+    ///
+    /// # Returns
+    /// * The missing paths, in input order.
+    pub fn missing<'a>(&self, required: &[&'a str]) -> Vec<&'a str> {
+        required
+            .iter()
+            .copied()
+            .filter(|relative| self.resolve(relative).is_none())
+            .collect()
+    }
+
+    fn report_missing(&self, relative: &Path) {
+        let key = index_key(relative).unwrap_or_else(|| relative.display().to_string());
+        let mut reported = self
+            .reported_missing
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if reported.insert(key) {
+            log::error!(
+                "Missing asset: {} (looked in {})",
+                relative.display(),
+                self.root.display()
+            );
+        }
+    }
+}
+
+/// Builds the lookup key for a relative path: lowercase, `/`-separated.
+///
+/// Returns `None` for paths that leave the root (`..`) or are absolute.
+fn index_key(relative: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?.to_lowercase()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Returns the process-wide resolver for [`filepaths::get_asset_directory`],
+/// indexing it on first use.
+///
+/// # Returns
+/// * The shared resolver.
+pub fn assets() -> &'static AssetResolver {
+    static RESOLVER: OnceLock<AssetResolver> = OnceLock::new();
+    RESOLVER.get_or_init(|| AssetResolver::scan(filepaths::get_asset_directory()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_regardless_of_case() {
+        let root = std::env::temp_dir().join(format!("mag_assets_{}", std::process::id()));
+        std::fs::create_dir_all(root.join("GFX")).unwrap();
+        std::fs::write(root.join("GFX").join("TLB.ICO"), b"icon").unwrap();
+
+        let resolver = AssetResolver::scan(root.clone());
+        let expected = root.join("GFX").join("TLB.ICO");
+        assert_eq!(resolver.resolve("gfx/tlb.ico"), Some(expected.clone()));
+        assert_eq!(resolver.resolve("Gfx").unwrap(), root.join("GFX"));
+        assert_eq!(
+            resolver.resolve_path(&root.join("gfx").join("Tlb.Ico")),
+            expected
+        );
+        assert_eq!(resolver.resolve("../outside"), None);
+        assert_eq!(
+            resolver.missing(&["gfx/tlb.ico", "sfx/click.wav"]),
+            ["sfx/click.wav"]
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
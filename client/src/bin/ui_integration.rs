@@ -36,6 +36,7 @@ use client::constants::{TARGET_HEIGHT_INT, TARGET_WIDTH_INT};
 use client::filepaths;
 use client::font_cache::{self, TextEngine, TextStyle};
 use client::gfx_cache::GraphicsCache;
+use client::network::clock_sync::ServerTime;
 use client::types::log_message::{LogMessage, LogMessageColor};
 use client::ui::hud::button_bar::HudButtonBar;
 use client::ui::hud::chat_box::ChatBox;
@@ -223,18 +224,26 @@ fn main() -> Result<(), String> {
     chat_box.push_message(LogMessage {
         message: "Welcome to the UI test!".into(),
         color: LogMessageColor::Green,
+        style: Default::default(),
+        links: Vec::new(),
     });
     chat_box.push_message(LogMessage {
         message: "Type here and press Enter.".into(),
         color: LogMessageColor::Yellow,
+        style: Default::default(),
+        links: Vec::new(),
     });
     chat_box.push_message(LogMessage {
         message: "An error-styled message.".into(),
         color: LogMessageColor::Red,
+        style: Default::default(),
+        links: Vec::new(),
     });
     chat_box.push_message(LogMessage {
         message: "A blue informational note.".into(),
         color: LogMessageColor::Blue,
+        style: Default::default(),
+        links: Vec::new(),
     });
 
     let mut mode_button = ModeButton::new(COL3_X + 30, 250, 18);
@@ -287,7 +296,9 @@ fn main() -> Result<(), String> {
         citem: 0,
         citem_p: 0,
         gold: 12345,
+        hide_gold: false,
         selected_char: 0,
+        item_names: std::array::from_fn(|_| String::new()),
     });
 
     let mut settings_panel = SettingsPanel::new(
@@ -334,6 +345,7 @@ fn main() -> Result<(), String> {
         ],
         secondary_keybinds: [None; client::ui::hud::skill_bar::TOP_CELLS],
         show_secondary: false,
+        cooldowns: [None; client::ui::hud::skill_bar::TOP_CELLS],
     });
 
     let mut spell_effect_icons =
@@ -353,7 +365,12 @@ fn main() -> Result<(), String> {
     demo_spell[3] = 1;
     demo_active[3] = 12;
     demo_spell_type[3] = skills::SK_WIMPY as i16;
-    spell_effect_icons.sync(&demo_spell, &demo_active, &demo_spell_type);
+    spell_effect_icons.sync(
+        &demo_spell,
+        &demo_active,
+        &demo_spell_type,
+        ServerTime::default(),
+    );
 
     // Per-widget render-timing statistics (capacity: last 1 000 frames, µs).
     let mut t_label: StatisticsBuffer<f32> = StatisticsBuffer::new(1_000);
@@ -552,6 +569,11 @@ fn main() -> Result<(), String> {
                     HudPanel::KeyBindings => {}
                     HudPanel::Talents => {}
                     HudPanel::QuestLog => {}
+                    HudPanel::Profile => {}
+                    HudPanel::Guild => {}
+                    HudPanel::Mail => {}
+                    HudPanel::Friends => {}
+                    HudPanel::CombatLog => {}
                 }
             }
         }
@@ -0,0 +1,260 @@
+//! In-client bug report packaging (F11 / Shift+F11).
+//!
+//! The game scene only records a [`BugReportRequest`] on [`AppState`]; the
+//! main loop calls [`capture`] after the frame has been drawn and before it
+//! is presented, so the screenshot shows exactly what the player saw. The
+//! report is a zip in the log directory holding:
+//!
+//! * `screenshot.png` — the back buffer at window resolution,
+//! * `client.log` — the last [`LOG_TAIL_LINES`] lines of the client log,
+//! * `player_state.txt` — a snapshot of the local [`PlayerState`],
+//! * `version.txt` — client and protocol version, platform.
+//!
+//! [`BugReportRequest::SaveAndUpload`] additionally posts the archive to the
+//! API's `/bugreport` endpoint on a background thread.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sdl2::image::SaveSurface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::surface::Surface;
+use sdl2::video::Window;
+
+use crate::player_state::PlayerState;
+use crate::state::AppState;
+use crate::{account_api, preferences};
+
+/// Client log lines included in a report.
+pub const LOG_TAIL_LINES: usize = 500;
+
+/// What the player asked for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BugReportRequest {
+    /// Write the zip to the log directory (F11).
+    Save,
+    /// Write the zip and upload it to the API (Shift+F11).
+    SaveAndUpload,
+}
+
+/// Returns the last `lines` lines of `text`.
+///
+/// # Arguments
+/// * `text` - Full log contents.
+/// * `lines` - Number of trailing lines to keep.
+///
+/// # Returns
+/// * The tail, including its final newline if `text` had one.
+fn tail_lines(text: &str, lines: usize) -> &str {
+    if lines == 0 {
+        return "";
+    }
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    match body.rmatch_indices('\n').nth(lines - 1) {
+        Some((idx, _)) => &text[idx + 1..],
+        None => text,
+    }
+}
+
+/// Human-readable dump of the state most bug reports hinge on.
+///
+/// # Arguments
+/// * `ps` - Local player state, if in game.
+///
+/// # Returns
+/// * The `player_state.txt` contents.
+fn player_snapshot(ps: Option<&PlayerState>) -> String {
+    let Some(ps) = ps else {
+        return "Not in game.\n".to_owned();
+    };
+    let ci = ps.character_info();
+    let name_len = ci
+        .name
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(ci.name.len());
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "name: {}",
+        String::from_utf8_lossy(&ci.name[..name_len])
+    );
+    let _ = writeln!(out, "kindred: {:#x}  mode: {}", ci.kindred, ci.mode);
+    let _ = writeln!(
+        out,
+        "hp: {}/{}  end: {}/{}  mana: {}/{}",
+        ci.a_hp, ci.hp[5], ci.a_end, ci.end[5], ci.a_mana, ci.mana[5]
+    );
+    let _ = writeln!(
+        out,
+        "points: {}  points_tot: {}  gold: {}",
+        ci.points, ci.points_tot, ci.gold
+    );
+    let _ = writeln!(out, "attrib totals: {:?}", ci.attrib.map(|a| a[5]));
+    let _ = writeln!(out, "items: {:?}", ci.item);
+    let _ = writeln!(out, "worn: {:?}", ci.worn);
+    let _ = writeln!(out, "citem: {}", ci.citem);
+    let _ = writeln!(out, "spells: {:?}", ci.spell);
+    let _ = writeln!(
+        out,
+        "attack_cn: {}  goto: ({}, {})  misc_action: {} ({}, {})  dir: {}",
+        ci.attack_cn,
+        ci.goto_x,
+        ci.goto_y,
+        ci.misc_action,
+        ci.misc_target1,
+        ci.misc_target2,
+        ci.dir
+    );
+    let _ = writeln!(out, "selected_char: {}", ps.selected_char());
+    let _ = writeln!(out, "talents: {:?}", ps.talents());
+    let _ = writeln!(out, "max_view_radius: {}", ps.max_view_radius());
+    let _ = writeln!(out, "title_selected: {}", ps.title_selected());
+    let _ = writeln!(
+        out,
+        "active quest: {} step {}",
+        ps.active_quest_template_id(),
+        ps.active_quest_step_idx()
+    );
+    let _ = writeln!(
+        out,
+        "look open: {}  shop open: {} (grave: {})",
+        ps.should_show_look(),
+        ps.should_show_shop(),
+        ps.shop_is_grave()
+    );
+    out
+}
+
+/// Contents of `version.txt`.
+fn version_info(app_state: &AppState<'_>) -> String {
+    format!(
+        "client: {}\nprotocol: {:#08x}\nplatform: {:?}\nos: {} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        mag_core::constants::VERSION,
+        app_state.platform,
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+/// Encodes the canvas' back buffer as PNG.
+///
+/// SDL_image only writes PNGs to files, so the image goes through a
+/// temporary file next to the report.
+fn screenshot_png(canvas: &Canvas<Window>, tmp_path: &Path) -> Result<Vec<u8>, String> {
+    let (width, height) = canvas.output_size()?;
+    let format = PixelFormatEnum::ABGR8888;
+    let mut pixels = canvas.read_pixels(None, format)?;
+    let surface = Surface::from_data(&mut pixels, width, height, width * 4, format)?;
+    surface.save(tmp_path)?;
+    let png = fs::read(tmp_path).map_err(|err| format!("Failed to read screenshot: {err}"));
+    let _ = fs::remove_file(tmp_path);
+    png
+}
+
+/// Builds the report archive.
+fn build_zip(files: &[(&str, &[u8])]) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    for (name, data) in files {
+        zip.start_file(*name, options)
+            .and_then(|()| zip.write_all(data).map_err(Into::into))
+            .map_err(|err| format!("Failed to add {name} to bug report: {err}"))?;
+    }
+    let cursor = zip
+        .finish()
+        .map_err(|err| format!("Failed to finish bug report: {err}"))?;
+    Ok(cursor.into_inner())
+}
+
+/// Packages a bug report and, if requested, uploads it.
+///
+/// # Arguments
+/// * `canvas` - Canvas holding the fully drawn, not yet presented frame.
+/// * `app_state` - Application state (player state, API session).
+/// * `request` - Whether to upload as well.
+///
+/// # Returns
+/// * Path of the written zip, or `Err` if it could not be written. Upload
+///   failures are only logged.
+pub fn capture(
+    canvas: &Canvas<Window>,
+    app_state: &AppState<'_>,
+    request: BugReportRequest,
+) -> Result<PathBuf, String> {
+    let log_path = preferences::log_file_path();
+    let dir = log_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let zip_path = dir.join(format!("bugreport_{stamp}.zip"));
+
+    let screenshot = screenshot_png(canvas, &dir.join(format!("bugreport_{stamp}.png")))
+        .unwrap_or_else(|err| {
+            log::warn!("Bug report without screenshot: {err}");
+            Vec::new()
+        });
+    let log = fs::read_to_string(&log_path).unwrap_or_default();
+    let snapshot = player_snapshot(app_state.player_state.as_ref());
+    let version = version_info(app_state);
+
+    let mut files: Vec<(&str, &[u8])> = vec![
+        ("client.log", tail_lines(&log, LOG_TAIL_LINES).as_bytes()),
+        ("player_state.txt", snapshot.as_bytes()),
+        ("version.txt", version.as_bytes()),
+    ];
+    if !screenshot.is_empty() {
+        files.push(("screenshot.png", &screenshot));
+    }
+    let archive = build_zip(&files)?;
+    fs::write(&zip_path, &archive)
+        .map_err(|err| format!("Failed to write {}: {err}", zip_path.display()))?;
+    log::info!("Bug report written to {}", zip_path.display());
+
+    if request == BugReportRequest::SaveAndUpload {
+        match app_state.api.token.clone() {
+            Some(token) => {
+                let base_url = app_state.api.base_url.clone();
+                std::thread::spawn(move || {
+                    match account_api::submit_bug_report(&base_url, &token, archive) {
+                        Ok(()) => log::info!("Bug report uploaded"),
+                        Err(err) => log::warn!("{err}"),
+                    }
+                });
+            }
+            None => log::warn!("Bug report not uploaded: not logged in to the account API"),
+        }
+    }
+    Ok(zip_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_lines_keeps_the_last_lines() {
+        let log = "a\nb\nc\nd\n";
+        assert_eq!(tail_lines(log, 2), "c\nd\n");
+        assert_eq!(tail_lines(log, 10), log);
+        assert_eq!(tail_lines("a\nb", 1), "b");
+        assert_eq!(tail_lines(log, 0), "");
+    }
+
+    #[test]
+    fn zip_contains_every_file() {
+        let archive = build_zip(&[("a.txt", &b"one"[..]), ("b.txt", &b"two"[..])]).unwrap();
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(zip.len(), 2);
+        assert_eq!(zip.by_index(1).unwrap().name(), "b.txt");
+    }
+}
@@ -0,0 +1,386 @@
+//! Combat log: every hit reported by `SV_COMBATEVENT`, grouped into fights.
+//!
+//! The server breaks each hit the player's character deals or takes down
+//! into raw, resisted, mitigated and dealt damage (see
+//! [`mag_core::combat_log`]). The log keeps the most recent
+//! [`MAX_LOGGED_HITS`] of them stamped with the client tick they arrived
+//! on. A pause of [`FIGHT_GAP_TICKS`] without hits ends a fight; the combat
+//! log panel shows one [`FightSummary`] per fight, and the whole log can be
+//! exported as CSV (`mag_combat_log.csv` in the client data directory) for
+//! balancing spreadsheets.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use mag_core::combat_log::{CombatEvent, DamageKind};
+use mag_core::constants::TICKS;
+
+/// Most hits kept; the oldest are dropped first.
+pub const MAX_LOGGED_HITS: usize = 2000;
+
+/// Ticks without a hit that end a fight (eight seconds).
+pub const FIGHT_GAP_TICKS: u32 = TICKS as u32 * 8;
+
+/// Header line of the CSV export.
+pub const CSV_HEADER: &str =
+    "fight,tick,direction,attacker,target,kind,skill,raw,resisted,mitigated,dealt,killed";
+
+/// One hit and the client tick it arrived on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoggedHit {
+    /// Value of [`CombatLog::now`] when the hit arrived.
+    pub tick: u32,
+    /// The hit as the server reported it.
+    pub event: CombatEvent,
+}
+
+/// Totals of one fight, in thousandths of a hit point like the events.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FightSummary {
+    /// Number of the fight, counting from 1 at the oldest logged hit.
+    pub number: usize,
+    /// Tick of the first hit.
+    pub start_tick: u32,
+    /// Tick of the last hit.
+    pub end_tick: u32,
+    /// Opponent hit or hit by most often.
+    pub opponent: String,
+    /// Other opponents in the fight.
+    pub other_opponents: usize,
+    /// Hits dealt by the player's character.
+    pub hits_dealt: u32,
+    /// Damage the player's character dealt.
+    pub dealt: u64,
+    /// Damage of the player's hits that enemy armor absorbed.
+    pub dealt_mitigated: u64,
+    /// Damage of the player's spells that enemy immunity resisted.
+    pub dealt_resisted: u64,
+    /// Hits taken by the player's character.
+    pub hits_taken: u32,
+    /// Damage the player's character took.
+    pub taken: u64,
+    /// Damage of incoming hits the player's armor absorbed.
+    pub taken_mitigated: u64,
+    /// Damage of incoming spells the player's immunity resisted.
+    pub taken_resisted: u64,
+    /// Kills made by the player's character.
+    pub kills: u32,
+}
+
+impl FightSummary {
+    /// Length of the fight in seconds, at least one tick.
+    pub fn seconds(&self) -> f32 {
+        (self.end_tick - self.start_tick).max(1) as f32 / TICKS as f32
+    }
+
+    /// Damage dealt per second, in hit points.
+    pub fn dealt_per_second(&self) -> f32 {
+        self.dealt as f32 / 1000.0 / self.seconds()
+    }
+}
+
+/// Formats thousandths of a hit point as hit points with one decimal.
+///
+/// # Arguments
+///
+/// * `amount` - Damage in thousandths of a hit point.
+///
+/// # Returns
+///
+/// * E.g. `"12.3"` for `12_345`.
+pub fn format_hp(amount: u64) -> String {
+    format!("{}.{}", amount / 1000, amount % 1000 / 100)
+}
+
+/// Quotes a CSV field when it holds a comma or a quote.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Recent hits dealt and taken by the player's character.
+#[derive(Debug, Default)]
+pub struct CombatLog {
+    hits: VecDeque<LoggedHit>,
+    now: u32,
+}
+
+impl CombatLog {
+    /// Creates an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the log clock by one tick.
+    pub fn tick(&mut self) {
+        self.now = self.now.wrapping_add(1);
+    }
+
+    /// Current value of the log clock.
+    pub fn now(&self) -> u32 {
+        self.now
+    }
+
+    /// Records a hit at the current tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The hit from `SV_COMBATEVENT`.
+    pub fn push(&mut self, event: CombatEvent) {
+        if self.hits.len() == MAX_LOGGED_HITS {
+            self.hits.pop_front();
+        }
+        self.hits.push_back(LoggedHit {
+            tick: self.now,
+            event,
+        });
+    }
+
+    /// Forgets every hit.
+    pub fn clear(&mut self) {
+        self.hits.clear();
+    }
+
+    /// Number of logged hits.
+    pub fn len(&self) -> usize {
+        self.hits.len()
+    }
+
+    /// Whether no hit is logged.
+    pub fn is_empty(&self) -> bool {
+        self.hits.is_empty()
+    }
+
+    /// Logged hits, oldest first.
+    pub fn hits(&self) -> impl Iterator<Item = &LoggedHit> {
+        self.hits.iter()
+    }
+
+    /// Fight number of each logged hit, oldest first.
+    fn fight_numbers(&self) -> impl Iterator<Item = (usize, &LoggedHit)> {
+        let mut number = 0;
+        let mut last_tick = None;
+        self.hits.iter().map(move |hit| {
+            if last_tick.is_none_or(|last: u32| hit.tick.wrapping_sub(last) > FIGHT_GAP_TICKS) {
+                number += 1;
+            }
+            last_tick = Some(hit.tick);
+            (number, hit)
+        })
+    }
+
+    /// Sums up each fight.
+    ///
+    /// # Returns
+    ///
+    /// * One summary per fight, oldest first.
+    pub fn fights(&self) -> Vec<FightSummary> {
+        let mut fights: Vec<FightSummary> = Vec::new();
+        let mut opponents: Vec<(String, u32)> = Vec::new();
+        for (number, hit) in self.fight_numbers() {
+            if fights.last().is_none_or(|f| f.number != number) {
+                if let Some(fight) = fights.last_mut() {
+                    Self::set_opponents(fight, &mut opponents);
+                }
+                fights.push(FightSummary {
+                    number,
+                    start_tick: hit.tick,
+                    ..FightSummary::default()
+                });
+            }
+            let fight = fights.last_mut().unwrap();
+            let event = &hit.event;
+            fight.end_tick = hit.tick;
+
+            let opponent = if event.is_outgoing() {
+                fight.hits_dealt += 1;
+                fight.dealt += u64::from(event.dealt);
+                fight.dealt_mitigated += u64::from(event.mitigated);
+                fight.dealt_resisted += u64::from(event.resisted);
+                fight.kills += u32::from(event.is_kill());
+                &event.target
+            } else {
+                fight.hits_taken += 1;
+                fight.taken += u64::from(event.dealt);
+                fight.taken_mitigated += u64::from(event.mitigated);
+                fight.taken_resisted += u64::from(event.resisted);
+                &event.attacker
+            };
+            if !opponent.is_empty() {
+                match opponents.iter_mut().find(|(name, _)| name == opponent) {
+                    Some((_, count)) => *count += 1,
+                    None => opponents.push((opponent.clone(), 1)),
+                }
+            }
+        }
+        if let Some(fight) = fights.last_mut() {
+            Self::set_opponents(fight, &mut opponents);
+        }
+        fights
+    }
+
+    /// Moves the opponent tally of a finished fight into its summary.
+    fn set_opponents(fight: &mut FightSummary, opponents: &mut Vec<(String, u32)>) {
+        // The first opponent wins ties, so the one that opened the fight.
+        let mut main: Option<&(String, u32)> = None;
+        for entry in opponents.iter() {
+            if main.is_none_or(|m| entry.1 > m.1) {
+                main = Some(entry);
+            }
+        }
+        fight.opponent = main.map(|(name, _)| name.clone()).unwrap_or_default();
+        fight.other_opponents = opponents.len().saturating_sub(1);
+        opponents.clear();
+    }
+
+    /// Renders the log as CSV, one line per hit after [`CSV_HEADER`].
+    ///
+    /// Damage columns are in hit points with three decimals.
+    ///
+    /// # Returns
+    ///
+    /// * The CSV text.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::with_capacity(64 * (self.hits.len() + 1));
+        csv.push_str(CSV_HEADER);
+        csv.push('\n');
+        let hp = |amount: u32| format!("{}.{:03}", amount / 1000, amount % 1000);
+        for (number, hit) in self.fight_numbers() {
+            let event = &hit.event;
+            let line = [
+                number.to_string(),
+                hit.tick.to_string(),
+                if event.is_outgoing() { "out" } else { "in" }.to_owned(),
+                csv_field(&event.attacker),
+                csv_field(&event.target),
+                DamageKind::from_u8(event.kind).label().to_owned(),
+                csv_field(event.skill_name()),
+                hp(event.raw),
+                hp(event.resisted),
+                hp(event.mitigated),
+                hp(event.dealt),
+                u8::from(event.is_kill()).to_string(),
+            ];
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Writes [`Self::to_csv`] to `path`, replacing the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File to write.
+    ///
+    /// # Returns
+    ///
+    /// * The number of hits written, or the write error.
+    pub fn export_csv(&self, path: &Path) -> Result<usize, String> {
+        fs::write(path, self.to_csv()).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(self.hits.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mag_core::combat_log::{COMBAT_FLAG_KILLED, COMBAT_FLAG_OUTGOING, NO_SKILL};
+    use mag_core::skills;
+
+    fn hit(outgoing: bool, opponent: &str, dealt: u32, flags: u8) -> CombatEvent {
+        let (attacker, target) = if outgoing {
+            ("Tester", opponent)
+        } else {
+            (opponent, "Tester")
+        };
+        CombatEvent {
+            kind: DamageKind::Melee as u8,
+            skill: skills::SK_WEAPON as u8,
+            flags: flags | if outgoing { COMBAT_FLAG_OUTGOING } else { 0 },
+            raw: dealt + 500,
+            resisted: 0,
+            mitigated: 500,
+            dealt,
+            attacker: attacker.to_owned(),
+            target: target.to_owned(),
+        }
+    }
+
+    fn advance(log: &mut CombatLog, ticks: u32) {
+        for _ in 0..ticks {
+            log.tick();
+        }
+    }
+
+    #[test]
+    fn pauses_split_fights_and_totals_add_up() {
+        let mut log = CombatLog::new();
+        log.push(hit(true, "Grolm", 4_000, 0));
+        advance(&mut log, TICKS as u32);
+        log.push(hit(false, "Grolm", 2_500, 0));
+        log.push(hit(true, "Rat", 1_000, 0));
+        advance(&mut log, TICKS as u32);
+        log.push(hit(true, "Grolm", 6_000, COMBAT_FLAG_KILLED));
+        advance(&mut log, FIGHT_GAP_TICKS + 1);
+        log.push(hit(false, "Rat", 1_200, 0));
+
+        let fights = log.fights();
+        assert_eq!(fights.len(), 2);
+        let first = &fights[0];
+        assert_eq!((first.number, first.opponent.as_str()), (1, "Grolm"));
+        assert_eq!(first.other_opponents, 1);
+        assert_eq!((first.hits_dealt, first.dealt), (3, 11_000));
+        assert_eq!((first.hits_taken, first.taken), (1, 2_500));
+        assert_eq!((first.dealt_mitigated, first.taken_mitigated), (1_500, 500));
+        assert_eq!(first.kills, 1);
+        assert_eq!(first.seconds(), 2.0);
+        assert_eq!(format_hp(first.dealt), "11.0");
+        assert_eq!(
+            (fights[1].opponent.as_str(), fights[1].taken),
+            ("Rat", 1_200)
+        );
+    }
+
+    #[test]
+    fn log_drops_the_oldest_hits() {
+        let mut log = CombatLog::new();
+        for n in 0..MAX_LOGGED_HITS as u32 + 3 {
+            log.push(hit(true, "Rat", n, 0));
+        }
+        assert_eq!(log.len(), MAX_LOGGED_HITS);
+        assert_eq!(log.hits().next().unwrap().event.dealt, 3);
+        log.clear();
+        assert!(log.is_empty() && log.fights().is_empty());
+    }
+
+    #[test]
+    fn csv_has_one_line_per_hit() {
+        let mut log = CombatLog::new();
+        log.push(hit(true, "Grolm, the Big", 4_250, COMBAT_FLAG_KILLED));
+        let mut trap = hit(false, "", 350_000, 0);
+        trap.kind = DamageKind::Direct as u8;
+        trap.skill = NO_SKILL;
+        log.push(trap);
+
+        let csv = log.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            format!(
+                "1,0,out,Tester,\"Grolm, the Big\",melee,{},4.750,0.000,0.500,4.250,1",
+                skills::get_skill_name(skills::SK_WEAPON)
+            )
+        );
+        assert_eq!(
+            lines[2],
+            "1,0,in,,Tester,direct,,350.500,0.000,0.500,350.000,0"
+        );
+        assert_eq!(lines.len(), 3);
+    }
+}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use crate::asset_resolver;
+
 /// Returns the directory containing the running executable.
 ///
 /// Falls back to `"."` if `current_exe()` cannot be resolved (should be rare).
@@ -34,10 +36,12 @@ pub fn get_asset_directory() -> PathBuf {
 
 /// Returns the path to the `images.zip` sprite archive.
 ///
+/// Resolved case-insensitively through [`asset_resolver::assets`].
+///
 /// # Returns
 /// * `PathBuf` pointing to `<asset_dir>/gfx/images.zip`.
 pub fn get_gfx_zipfile() -> PathBuf {
-    let zip_file_path = get_asset_directory().join("gfx").join("images.zip");
+    let zip_file_path = asset_resolver::assets().resolve_or_report("gfx/images.zip");
     log::info!("Using gfx.zip file at: {}", zip_file_path.display());
     zip_file_path
 }
@@ -47,7 +51,7 @@ pub fn get_gfx_zipfile() -> PathBuf {
 /// # Returns
 /// * `PathBuf` pointing to `<asset_dir>/sfx/`.
 pub fn get_sfx_directory() -> PathBuf {
-    let sfx_directory = get_asset_directory().join("sfx");
+    let sfx_directory = asset_resolver::assets().resolve_or_report("sfx");
     log::info!("Using sfx directory at: {}", sfx_directory.display());
     sfx_directory
 }
@@ -57,17 +61,37 @@ pub fn get_sfx_directory() -> PathBuf {
 /// # Returns
 /// * `PathBuf` pointing to `<asset_dir>/music/`.
 pub fn get_music_directory() -> PathBuf {
-    let music_directory = get_asset_directory().join("music");
+    let music_directory = asset_resolver::assets().resolve_or_report("music");
     log::info!("Using music directory at: {}", music_directory.display());
     music_directory
 }
 
+/// Builds the NPC dialogue catalog: the built-in English lines plus any
+/// `<locale>.txt` translations in `<asset_dir>/dialogue/`.
+///
+/// # Returns
+/// * The catalog; translation errors are logged and skipped.
+pub fn load_dialogue_catalog() -> mag_core::dialogue::DialogueCatalog {
+    let mut catalog = mag_core::dialogue::DialogueCatalog::default();
+    if let Some(dir) = asset_resolver::assets().resolve("dialogue") {
+        match catalog.load_dir(&dir) {
+            Ok(loaded) => log::info!(
+                "Loaded {} dialogue translation(s) from {}",
+                loaded,
+                dir.display()
+            ),
+            Err(err) => log::warn!("{err}"),
+        }
+    }
+    catalog
+}
+
 /// Returns the path to the directory containing TrueType font files.
 ///
 /// # Returns
 /// * `PathBuf` pointing to `<asset_dir>/fonts/`.
 pub fn get_fonts_directory() -> PathBuf {
-    let fonts_directory = get_asset_directory().join("fonts");
+    let fonts_directory = asset_resolver::assets().resolve_or_report("fonts");
     log::info!("Using fonts directory at: {}", fonts_directory.display());
     fonts_directory
 }
@@ -52,6 +52,7 @@ pub const BITMAP_GLYPH_ADVANCE: u32 = BITMAP_GLYPH_W;
 /// - `TextStyle::tinted(color)` — color-modulated text.
 /// - `TextStyle::faded(alpha)` — semi-transparent text.
 /// - `TextStyle::centered()` — horizontally centered around `x`.
+/// - `.with_italic()` — faux italics (bitmap font only).
 /// - Chain with `.with_tint()` for combined styles.
 #[derive(Clone, Copy, Debug)]
 pub struct TextStyle {
@@ -63,6 +64,9 @@ pub struct TextStyle {
     pub centered: bool,
     /// If true, a 1-pixel black drop shadow is drawn at (+1, +1) behind the text.
     pub drop_shadow: bool,
+    /// If true, bitmap glyphs are slanted by shifting their upper half one
+    /// pixel right. Ignored by the TTF path.
+    pub italic: bool,
 }
 
 impl TextStyle {
@@ -72,6 +76,7 @@ impl TextStyle {
         alpha: None,
         centered: false,
         drop_shadow: false,
+        italic: false,
     };
 
     /// Creates a style with the given tint color.
@@ -146,6 +151,16 @@ impl TextStyle {
         self
     }
 
+    /// Returns a copy of this style with faux italics enabled.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `with_italic`.
+    pub fn with_italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
     /// Creates a plain style with a 1-pixel black drop shadow.
     ///
     /// # Returns
@@ -229,6 +244,7 @@ pub fn draw_text(
             y + 1,
             Some(sdl2::pixels::Color::RGB(0, 0, 0)),
             style.alpha,
+            style.italic,
         )?;
     }
 
@@ -241,6 +257,7 @@ pub fn draw_text(
         y,
         style.tint,
         style.alpha,
+        style.italic,
     )
 }
 
@@ -254,6 +271,7 @@ fn draw_text_impl(
     y: i32,
     tint: Option<sdl2::pixels::Color>,
     alpha: Option<u8>,
+    italic: bool,
 ) -> Result<(), String> {
     let sprite_id = BITMAP_FONT_FIRST_SPRITE_ID + (font % BITMAP_FONT_COUNT);
 
@@ -277,14 +295,36 @@ fn draw_text_impl(
 
         // Re-fetch each iteration to avoid holding a reference across the `copy` call.
         let texture = gfx_cache.get_texture(sprite_id);
-        let src = sdl2::rect::Rect::new(
-            glyph * BITMAP_GLYPH_W as i32,
-            BITMAP_GLYPH_Y_OFFSET,
-            BITMAP_GLYPH_W - 1,
-            BITMAP_GLYPH_H,
-        );
-        let dst = sdl2::rect::Rect::new(cx, y, BITMAP_GLYPH_W - 1, BITMAP_GLYPH_H);
-        if let Err(err) = canvas.copy(texture, Some(src), Some(dst)) {
+        let result = if italic {
+            // Two horizontal strips; the upper one is nudged right to fake a slant.
+            let top_h = BITMAP_GLYPH_H / 2;
+            let bottom_h = BITMAP_GLYPH_H - top_h;
+            let src_x = glyph * BITMAP_GLYPH_W as i32;
+            let top_src =
+                sdl2::rect::Rect::new(src_x, BITMAP_GLYPH_Y_OFFSET, BITMAP_GLYPH_W - 1, top_h);
+            let top_dst = sdl2::rect::Rect::new(cx + 1, y, BITMAP_GLYPH_W - 1, top_h);
+            let bottom_src = sdl2::rect::Rect::new(
+                src_x,
+                BITMAP_GLYPH_Y_OFFSET + top_h as i32,
+                BITMAP_GLYPH_W - 1,
+                bottom_h,
+            );
+            let bottom_dst =
+                sdl2::rect::Rect::new(cx, y + top_h as i32, BITMAP_GLYPH_W - 1, bottom_h);
+            canvas
+                .copy(texture, Some(top_src), Some(top_dst))
+                .and_then(|()| canvas.copy(texture, Some(bottom_src), Some(bottom_dst)))
+        } else {
+            let src = sdl2::rect::Rect::new(
+                glyph * BITMAP_GLYPH_W as i32,
+                BITMAP_GLYPH_Y_OFFSET,
+                BITMAP_GLYPH_W - 1,
+                BITMAP_GLYPH_H,
+            );
+            let dst = sdl2::rect::Rect::new(cx, y, BITMAP_GLYPH_W - 1, BITMAP_GLYPH_H);
+            canvas.copy(texture, Some(src), Some(dst))
+        };
+        if let Err(err) = result {
             first_error = Some(err);
             break;
         }
@@ -424,6 +464,30 @@ pub fn wrap_lines_bitmap(text: &str, max_width: u32) -> Vec<String> {
     lines
 }
 
+/// Truncates ASCII text to fit within `max_width` pixels of bitmap font.
+///
+/// # Arguments
+///
+/// * `text` - Text to fit.
+/// * `max_width` - Maximum pixel width available.
+///
+/// # Returns
+///
+/// * The original text when it fits, or a `...`-terminated copy.
+pub fn fit_text_bitmap(text: &str, max_width: i32) -> String {
+    let max_chars = (max_width.max(0) as u32 / BITMAP_GLYPH_ADVANCE) as usize;
+    if text.len() <= max_chars {
+        return text.to_owned();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+    if max_chars <= 3 {
+        return ".".repeat(max_chars);
+    }
+    format!("{}...", &text[..max_chars - 3])
+}
+
 /// Measures the pixel dimensions of `text` when wrapped via
 /// [`wrap_lines_bitmap`] with the given `max_width`.
 ///
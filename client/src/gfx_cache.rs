@@ -201,6 +201,7 @@ impl<'tc> GraphicsCache<'tc> {
     ///
     /// The assigned sprite ID on success, or an error message.
     pub fn load_texture_from_path(&mut self, path: &std::path::Path) -> Result<usize, String> {
+        let path = &crate::asset_resolver::assets().resolve_path(path);
         let mut file =
             File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
         let mut buffer = Vec::new();
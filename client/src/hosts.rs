@@ -55,6 +55,30 @@ pub fn get_server_ip() -> String {
         .unwrap_or_else(|| default_server_ip().to_owned())
 }
 
+/// Returns the server's WebSocket port when the client should connect over
+/// WebSocket instead of the raw game port.
+///
+/// Reads `MAG_WS_PORT`, the same variable that enables the server's
+/// WebSocket listener.
+///
+/// # Returns
+///
+/// * `Some(port)` when `MAG_WS_PORT` holds a valid port, otherwise `None`.
+pub fn get_ws_port() -> Option<u16> {
+    let value = std::env::var("MAG_WS_PORT").ok()?;
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    match value.parse::<u16>() {
+        Ok(port) if port != 0 => Some(port),
+        _ => {
+            log::warn!("Ignoring invalid MAG_WS_PORT value '{value}'");
+            None
+        }
+    }
+}
+
 /// Returns the build-mode default server hostname.
 ///
 /// Debug builds default to localhost for local development; release builds
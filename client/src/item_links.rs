@@ -0,0 +1,261 @@
+//! Item links in chat, as received with `SV_ITEMLINK`.
+//!
+//! The server sends the snapshot of every linked item right before the chat
+//! line that mentions it; [`ItemLinkCache`] keeps the most recent ones.
+//! Before a line is word-wrapped, [`ItemLinkCache::mark_links`] replaces each
+//! link token with the item name in brackets, framed by [`ITEM_LINK_MARK`]
+//! and with its spaces swapped out so wrapping never breaks a link apart.
+//! [`LinkUnmarker`] then turns every wrapped line back into plain text plus
+//! the byte ranges of its links, which the chat box tints and shows a
+//! tooltip for.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use mag_core::item_link::{ChatSegment, ITEM_LINK_MARK, ItemLink, split_item_links};
+
+use crate::font_cache;
+use crate::types::log_message::ChatItemLink;
+
+/// Most item snapshots kept; the oldest are dropped first.
+pub const MAX_CACHED_ITEM_LINKS: usize = 256;
+
+/// Stands in for the spaces of a marked link while its line is wrapped.
+const LINK_SPACE: char = '\u{1e}';
+
+/// Widest item description line in a tooltip, in characters.
+const TOOLTIP_COLUMNS: u32 = 36;
+
+/// Item snapshots from `SV_ITEMLINK`, oldest first.
+#[derive(Debug, Default)]
+pub struct ItemLinkCache {
+    links: VecDeque<Arc<ItemLink>>,
+}
+
+impl ItemLinkCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores a snapshot, replacing any older one with the same link id.
+    ///
+    /// # Arguments
+    ///
+    /// * `link` - The snapshot.
+    pub fn insert(&mut self, link: ItemLink) {
+        self.links.retain(|known| known.id != link.id);
+        self.links.push_back(Arc::new(link));
+        if self.links.len() > MAX_CACHED_ITEM_LINKS {
+            self.links.pop_front();
+        }
+    }
+
+    /// Looks up a snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Link id from a link token.
+    pub fn get(&self, id: u32) -> Option<Arc<ItemLink>> {
+        self.links.iter().rev().find(|link| link.id == id).cloned()
+    }
+
+    /// Replaces the link tokens of a chat line with marked item names.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The line as received.
+    ///
+    /// # Returns
+    ///
+    /// * The line to word-wrap, and the unmarker for its wrapped lines.
+    pub fn mark_links(&self, text: &str) -> (String, LinkUnmarker) {
+        let mut out = String::with_capacity(text.len());
+        let mut items = VecDeque::new();
+        for segment in split_item_links(text) {
+            match segment {
+                ChatSegment::Text(part) => out.push_str(part),
+                ChatSegment::Slot(_) => {}
+                ChatSegment::Link(id) => {
+                    let item = self.get(id);
+                    let label = link_label(item.as_deref().map_or("item", |link| &link.name));
+                    out.push(ITEM_LINK_MARK);
+                    out.extend(label.chars().map(|c| if c == ' ' { LINK_SPACE } else { c }));
+                    out.push(ITEM_LINK_MARK);
+                    items.push_back(item);
+                }
+            }
+        }
+        (out, LinkUnmarker { items, open: None })
+    }
+}
+
+/// Chat text of a link to the item `name`.
+///
+/// # Arguments
+///
+/// * `name` - Item name.
+pub fn link_label(name: &str) -> String {
+    format!("[{name}]")
+}
+
+/// Splits the wrapped lines of one marked chat line into text and links.
+#[derive(Debug, Default)]
+pub struct LinkUnmarker {
+    /// Snapshots of the links not reached yet, in order.
+    items: VecDeque<Option<Arc<ItemLink>>>,
+    /// Link a hard cut left open at the end of the previous line.
+    open: Option<Option<Arc<ItemLink>>>,
+}
+
+impl LinkUnmarker {
+    /// Unmarks the next wrapped line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line, as cut by the word wrap.
+    ///
+    /// # Returns
+    ///
+    /// * The plain text of the line and the links in it.
+    pub fn unmark_line(&mut self, line: &str) -> (String, Vec<ChatItemLink>) {
+        let mut text = String::with_capacity(line.len());
+        let mut links = Vec::new();
+        let mut start = 0;
+        for c in line.chars() {
+            match c {
+                ITEM_LINK_MARK => match self.open.take() {
+                    Some(item) => links.push(ChatItemLink {
+                        range: start..text.len(),
+                        item,
+                    }),
+                    None => {
+                        self.open = Some(self.items.pop_front().flatten());
+                        start = text.len();
+                    }
+                },
+                LINK_SPACE => text.push(' '),
+                c => text.push(c),
+            }
+        }
+        if let Some(item) = &self.open
+            && start < text.len()
+        {
+            links.push(ChatItemLink {
+                range: start..text.len(),
+                item: item.clone(),
+            });
+        }
+        (text, links)
+    }
+}
+
+/// Lines of the tooltip shown for a linked item.
+///
+/// # Arguments
+///
+/// * `link` - The snapshot.
+///
+/// # Returns
+///
+/// * The item name, one line per modifier or requirement, the rank needed
+///   and the wrapped description.
+pub fn tooltip_lines(link: &ItemLink) -> Vec<String> {
+    let mut lines = vec![link.name.clone()];
+    for stat in &link.stats {
+        let label = stat.label();
+        lines.push(match (stat.bonus, stat.required) {
+            (0, required) => format!("Requires {label} {required}"),
+            (bonus, 0) => format!("{label} {bonus:+}"),
+            (bonus, required) => format!("{label} {bonus:+} (min {required})"),
+        });
+    }
+    if link.min_rank > 0 {
+        lines.push(format!(
+            "Requires rank {}",
+            mag_core::ranks::rank_name_by_index(usize::from(link.min_rank))
+        ));
+    }
+    if !link.description.is_empty() {
+        lines.extend(font_cache::wrap_lines_bitmap(
+            &link.description,
+            TOOLTIP_COLUMNS * font_cache::BITMAP_GLYPH_ADVANCE,
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mag_core::item_link::{ITEM_STAT_WEAPON, ItemLinkStat, link_token};
+
+    fn sword(id: u32) -> ItemLink {
+        ItemLink {
+            id,
+            name: "Steel Sword".to_owned(),
+            min_rank: 0,
+            stats: vec![
+                ItemLinkStat {
+                    stat: ITEM_STAT_WEAPON,
+                    bonus: 18,
+                    required: 0,
+                },
+                ItemLinkStat {
+                    stat: 4,
+                    bonus: 0,
+                    required: 25,
+                },
+            ],
+            description: "A well-balanced steel sword.".to_owned(),
+        }
+    }
+
+    #[test]
+    fn links_survive_word_wrap_as_ranges() {
+        let mut cache = ItemLinkCache::new();
+        cache.insert(sword(7));
+        let (marked, mut unmarker) =
+            cache.mark_links(&format!("wts {} and {}", link_token(7), link_token(8)));
+
+        let (text, links) = unmarker.unmark_line(&marked);
+        assert_eq!(text, "wts [Steel Sword] and [item]");
+        assert_eq!(links.len(), 2);
+        assert_eq!(&text[links[0].range.clone()], "[Steel Sword]");
+        assert_eq!(links[0].item.as_ref().unwrap().id, 7);
+        assert_eq!(&text[links[1].range.clone()], "[item]");
+        assert!(links[1].item.is_none());
+    }
+
+    #[test]
+    fn hard_cut_links_continue_on_the_next_line() {
+        let mut cache = ItemLinkCache::new();
+        cache.insert(sword(7));
+        let (marked, mut unmarker) = cache.mark_links(&link_token(7));
+        let (head, tail) = marked.split_at(7);
+
+        let (text, links) = unmarker.unmark_line(head);
+        assert_eq!(text, "[Steel");
+        assert_eq!(links[0].range, 0..6);
+        let (text, links) = unmarker.unmark_line(tail);
+        assert_eq!(text, " Sword]");
+        assert_eq!(links[0].range, 0..7);
+        assert!(links[0].item.is_some());
+    }
+
+    #[test]
+    fn tooltip_lists_stats_and_description() {
+        let mut link = sword(1);
+        link.min_rank = 2;
+        assert_eq!(
+            tooltip_lines(&link),
+            vec![
+                "Steel Sword".to_owned(),
+                "Weapon +18".to_owned(),
+                "Requires Strength 25".to_owned(),
+                format!("Requires rank {}", mag_core::ranks::rank_name_by_index(2)),
+                "A well-balanced steel sword.".to_owned(),
+            ]
+        );
+    }
+}
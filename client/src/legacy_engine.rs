@@ -242,6 +242,16 @@ fn eng_item(it_sprite: u16, it_status: &mut u8, ctick: usize, ticker: u32) -> i3
             base + 4
         }
         21 => base + ((ticker & 63) as i32),
+        // One-shot opening animation (chests): plays four frames once and
+        // holds the last one. Starts at `mag_core::chests::ITEM_STATUS_OPENING`.
+        22..=24 => {
+            let frame = i32::from(*it_status - 22);
+            if SPEEDTAB[10][tick] != 0 {
+                *it_status += 1;
+            }
+            base + frame
+        }
+        25 => base + 3,
         _ => base,
     }
 }
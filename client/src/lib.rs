@@ -2,8 +2,13 @@
 //!
 //! Re-exports all modules so that both the main client binary and auxiliary
 
+pub mod accessibility;
 pub mod account_api;
+pub mod addons;
+pub mod asset_resolver;
+pub mod bug_report;
 pub mod cert_trust;
+pub mod combat_log;
 pub mod constants;
 pub mod dpi_scaling;
 pub mod filepaths;
@@ -11,6 +16,7 @@ pub mod font_cache;
 pub mod game_map;
 pub mod gfx_cache;
 pub mod hosts;
+pub mod item_links;
 pub mod legacy_engine;
 pub mod network;
 pub mod platform;
@@ -19,5 +25,6 @@ pub mod preferences;
 pub mod scenes;
 pub mod sfx_cache;
 pub mod state;
+pub mod streamer_mode;
 pub mod types;
 pub mod ui;
@@ -15,14 +15,17 @@ use client::sfx_cache::SoundCache;
 use client::state::{ApiTokenState, AppState, DisplayCommand};
 use client::ui::visuals::panning_background::PanningBackground;
 use client::ui::widget::Bounds;
-use client::{constants, dpi_scaling, filepaths, hosts, preferences, scenes};
+use client::{
+    asset_resolver, bug_report, constants, dpi_scaling, filepaths, hosts, preferences, scenes,
+};
 
 /// Application entry point.
 ///
 /// Initialises logging, SDL2 subsystems (video, audio, mixer), creates the
 /// window and canvas, builds the scene manager, and enters the main loop.
 /// The loop polls events, updates the active scene, renders world + UI layers,
-/// and caps at 60 FPS via `FPSManager`.
+/// and paces frames via `FPSManager` at [`preferences::Settings::frame_rate`]
+/// (lower while the window is in the background).
 fn main() -> Result<(), String> {
     // Build the log-file path relative to the executable so that the logger
     // resolves correctly inside a macOS .app bundle (where the OS sets CWD to
@@ -40,7 +43,8 @@ fn main() -> Result<(), String> {
 
     log::info!("Initializing SDL2 contexts...");
     let mut fps_manager = FPSManager::new();
-    fps_manager.set_framerate(60)?;
+    let mut frame_rate = preferences::DEFAULT_FPS_CAP;
+    fps_manager.set_framerate(frame_rate)?;
     let sdl_context = sdl2::init()?;
     let _image_context = sdl2::image::init(InitFlag::PNG)?;
     let _audio_subsystem = sdl_context
@@ -111,6 +115,16 @@ fn main() -> Result<(), String> {
 
     let mut event_pump = sdl_context.event_pump()?;
 
+    let missing_assets = asset_resolver::assets().missing(asset_resolver::REQUIRED_ASSETS);
+    if !missing_assets.is_empty() {
+        log::error!(
+            "{} required asset(s) missing from {}: {}",
+            missing_assets.len(),
+            filepaths::get_asset_directory().display(),
+            missing_assets.join(", ")
+        );
+    }
+
     log::info!("Initializing graphics and sound caches (audio_available={audio_available})...");
     let texture_creator = canvas.texture_creator();
     let gfx_cache = GraphicsCache::new(filepaths::get_gfx_zipfile(), &texture_creator);
@@ -198,6 +212,10 @@ fn main() -> Result<(), String> {
     // ----------------------------------------------------------------------
 
     let mut scene_manager = scenes::scene::SceneManager::new();
+    app_state.replay_path = scenes::game::replay_path_from_env();
+    if app_state.replay_path.is_some() {
+        scene_manager.request_scene_change(SceneType::Replay, &mut app_state);
+    }
     let mut last_frame = Instant::now();
 
     // Log info about the monitor, graphics card, etc.
@@ -358,12 +376,31 @@ fn main() -> Result<(), String> {
         let _ = canvas.set_integer_scale(false);
         let _ = canvas.set_logical_size(0, 0);
 
+        if let Some(request) = app_state.bug_report_request.take() {
+            let result = bug_report::capture(&canvas, &app_state, request);
+            if let Some(ps) = app_state.player_state.as_mut() {
+                match result {
+                    Ok(path) => ps.tlog(1, format!("Bug report saved to {}.", path.display())),
+                    Err(err) => ps.tlog(0, format!("Bug report failed: {err}")),
+                }
+            } else if let Err(err) = result {
+                log::error!("Bug report failed: {err}");
+            }
+        }
+
         if scene_manager.get_scene() == SceneType::Exit {
             break 'running;
         }
 
         canvas.present();
 
+        let wanted_rate = app_state.settings.frame_rate(window_in_foreground(&canvas));
+        if wanted_rate != frame_rate {
+            match fps_manager.set_framerate(wanted_rate) {
+                Ok(()) => frame_rate = wanted_rate,
+                Err(e) => log::warn!("Failed to set frame rate to {wanted_rate}: {e}"),
+            }
+        }
         fps_manager.delay();
     }
 
@@ -411,6 +448,13 @@ fn apply_display_mode(
     applied_mode
 }
 
+/// Whether the window has input focus and is not minimized.
+fn window_in_foreground(canvas: &sdl2::render::Canvas<sdl2::video::Window>) -> bool {
+    let flags = canvas.window().window_flags();
+    flags & sdl2::sys::SDL_WindowFlags::SDL_WINDOW_INPUT_FOCUS as u32 != 0
+        && flags & sdl2::sys::SDL_WindowFlags::SDL_WINDOW_MINIMIZED as u32 == 0
+}
+
 /// Toggles VSync on the renderer at runtime via raw SDL2 FFI.
 fn apply_vsync(canvas: &sdl2::render::Canvas<sdl2::video::Window>, enabled: bool) {
     let raw = canvas.raw();
@@ -0,0 +1,219 @@
+//! Client-side estimate of the authoritative server clock.
+//!
+//! The server sends `SV_TIMESYNC` with its tick counter and wall-clock right
+//! after login and every few seconds afterwards. [`ClockSync`] anchors a
+//! local monotonic [`Instant`] to the last tick it received (shifted by half
+//! the measured round-trip time) and extrapolates at [`TICKS`] per second in
+//! between. Timer UIs read [`ClockSync::now`] instead of counting `SV_TICK`
+//! packets or their own `Instant`s, so every countdown shares one clock that
+//! stays within a tick of the server.
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use mag_core::constants::TICKS;
+
+/// Drift (in ticks) that is corrected gradually instead of by a jump.
+const SLEW_LIMIT_TICKS: f64 = 1.0;
+
+/// Drift (in ticks) beyond which timers must discard their history.
+const RESYNC_LIMIT_TICKS: f64 = TICKS as f64;
+
+/// A point on the server timeline, as handed to timer UIs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ServerTime {
+    /// Seconds since server tick 0 (fractional).
+    pub secs: f64,
+    /// Bumped whenever the clock jumps by more than a second, e.g. on the
+    /// first `SV_TIMESYNC`. Timers holding earlier samples should reset.
+    pub generation: u32,
+}
+
+/// Anchor pairing a server tick with the local instant it applied to.
+#[derive(Clone, Copy, Debug)]
+struct Anchor {
+    tick: f64,
+    at: Instant,
+}
+
+/// Smoothed mapping from local monotonic time to server ticks.
+#[derive(Debug)]
+pub struct ClockSync {
+    /// Last corrected anchor; `None` until the first `SV_TIMESYNC`.
+    anchor: Option<Anchor>,
+    /// Local start time, used to run timers before the first sync.
+    local_origin: Instant,
+    /// Server wall-clock minus local wall-clock, in milliseconds.
+    wall_offset_ms: Option<i64>,
+    /// See [`ServerTime::generation`].
+    generation: u32,
+}
+
+impl Default for ClockSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockSync {
+    /// Creates an unsynchronised clock that counts local time until the
+    /// first `SV_TIMESYNC` arrives.
+    ///
+    /// # Returns
+    ///
+    /// * A new clock.
+    pub fn new() -> Self {
+        Self {
+            anchor: None,
+            local_origin: Instant::now(),
+            wall_offset_ms: None,
+            generation: 0,
+        }
+    }
+
+    /// Applies an `SV_TIMESYNC` packet.
+    ///
+    /// Drift up to [`SLEW_LIMIT_TICKS`] is halved on each sync so countdowns
+    /// never visibly jump; larger drift snaps to the server value, and drift
+    /// beyond a second also bumps the generation.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - Server tick from the packet.
+    /// * `unix_ms` - Server wall-clock from the packet.
+    /// * `received_at` - When the packet was read off the socket.
+    /// * `rtt` - Current round-trip estimate; half of it is assumed to be
+    ///   the packet's transit time.
+    pub fn on_time_sync(&mut self, tick: u32, unix_ms: u64, received_at: Instant, rtt: Duration) {
+        let one_way = rtt.as_secs_f64() / 2.0;
+        let target = f64::from(tick) + one_way * f64::from(TICKS);
+        let corrected = match self.anchor {
+            Some(_) => {
+                let predicted = self.tick_at(received_at);
+                let drift = target - predicted;
+                if drift.abs() > RESYNC_LIMIT_TICKS {
+                    log::info!(
+                        "Server clock jumped by {:.1} ticks, resyncing timers",
+                        drift
+                    );
+                    self.generation = self.generation.wrapping_add(1);
+                    target
+                } else if drift.abs() > SLEW_LIMIT_TICKS {
+                    target
+                } else {
+                    predicted + drift / 2.0
+                }
+            }
+            None => {
+                self.generation = self.generation.wrapping_add(1);
+                target
+            }
+        };
+        self.anchor = Some(Anchor {
+            tick: corrected,
+            at: received_at,
+        });
+
+        let local_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        self.wall_offset_ms = Some(unix_ms as i64 + (one_way * 1000.0) as i64 - local_ms);
+    }
+
+    /// Returns `true` once at least one `SV_TIMESYNC` has been applied.
+    pub fn is_synced(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Estimated server tick at `at`.
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - Local instant to convert.
+    ///
+    /// # Returns
+    ///
+    /// * Fractional server tick (local ticks since start before any sync).
+    pub fn tick_at(&self, at: Instant) -> f64 {
+        match self.anchor {
+            Some(anchor) => anchor.tick + signed_secs(anchor.at, at) * f64::from(TICKS),
+            None => signed_secs(self.local_origin, at) * f64::from(TICKS),
+        }
+    }
+
+    /// Current position on the server timeline.
+    ///
+    /// # Returns
+    ///
+    /// * The current [`ServerTime`].
+    pub fn now(&self) -> ServerTime {
+        ServerTime {
+            secs: self.tick_at(Instant::now()) / f64::from(TICKS),
+            generation: self.generation,
+        }
+    }
+
+    /// Estimated server wall-clock in Unix milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(ms)` once synced, `None` otherwise.
+    pub fn server_unix_ms(&self) -> Option<u64> {
+        let offset = self.wall_offset_ms?;
+        let local_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        u64::try_from(local_ms + offset).ok()
+    }
+}
+
+/// Seconds from `from` to `to`, negative when `to` is earlier.
+fn signed_secs(from: Instant, to: Instant) -> f64 {
+    match to.checked_duration_since(from) {
+        Some(d) => d.as_secs_f64(),
+        None => -from.duration_since(to).as_secs_f64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_sync_anchors_to_server_tick_plus_latency() {
+        let mut clock = ClockSync::new();
+        let t0 = Instant::now();
+        clock.on_time_sync(1_000, 0, t0, Duration::from_millis(500));
+
+        assert!(clock.is_synced());
+        assert_eq!(clock.now().generation, 1);
+        let expected = 1_000.0 + 0.25 * f64::from(TICKS);
+        assert!((clock.tick_at(t0) - expected).abs() < 1e-6);
+        let later = t0 + Duration::from_secs(2);
+        assert!((clock.tick_at(later) - expected - 2.0 * f64::from(TICKS)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn small_drift_is_slewed_and_large_drift_snaps() {
+        let mut clock = ClockSync::new();
+        let t0 = Instant::now();
+        clock.on_time_sync(1_000, 0, t0, Duration::ZERO);
+
+        // Server is just under a tick ahead of the prediction: halve the error.
+        let t1 = t0 + Duration::from_secs(1);
+        clock.on_time_sync(1_000 + TICKS as u32, 0, t1, Duration::ZERO);
+        let t2 = t1 + Duration::from_millis(1);
+        let before = clock.tick_at(t2);
+        clock.on_time_sync(1_000 + TICKS as u32 + 1, 0, t2, Duration::ZERO);
+        let after = clock.tick_at(t2);
+        assert!(after > before);
+        assert!((f64::from(1_000 + TICKS as u32 + 1) - after).abs() < 1.0);
+        assert_eq!(clock.now().generation, 1);
+
+        // Minutes of drift: snap and start a new generation.
+        clock.on_time_sync(10_000, 0, t2, Duration::ZERO);
+        assert!((clock.tick_at(t2) - 10_000.0).abs() < 1e-6);
+        assert_eq!(clock.now().generation, 2);
+    }
+}
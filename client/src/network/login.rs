@@ -1,152 +1,236 @@
 use std::{
     io::{Read, Write},
-    net::{Shutdown, TcpStream},
+    net::TcpStream,
     sync::mpsc,
     time::{Duration, Instant},
 };
 
 use flate2::{Decompress, FlushDecompress, Status};
+use mag_core::constants::SERVER_SILENCE_TIMEOUT_SECS;
 use mag_core::server_commands::ServerCommandData;
 use mag_core::{client_commands, server_commands::ServerCommand};
 use mag_core::{
+    disconnect::describe_disconnect,
     logout_reasons::{LogoutReason, get_exit_reason},
     server_commands::ServerCommandType,
 };
 
+use super::reconnect::{self, ResumeCredentials};
+use super::transport::{GameTransport, TlsTransport, Transport, WsTransport};
 use super::{NetworkCommand, NetworkEvent};
 
-/// A game connection backed by a TLS session over TCP.
-struct GameConnection {
-    stream: rustls::StreamOwned<rustls::ClientConnection, TcpStream>,
+/// Why a connection attempt or a running connection ended.
+#[derive(Debug)]
+struct NetError {
+    message: String,
+    /// Whether logging in again could pick the session back up: the
+    /// connection dropped, rather than the server refusing or ending it.
+    resumable: bool,
 }
 
-impl GameConnection {
-    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
-        self.stream.sock.set_nonblocking(nonblocking)
-    }
-
-    fn shutdown(&mut self) {
-        let stream = &mut self.stream;
-        let _ = stream.sock.set_nonblocking(false);
-
-        stream.conn.send_close_notify();
-
-        let (conn, sock) = (&mut stream.conn, &mut stream.sock);
-        while conn.wants_write() {
-            match conn.write_tls(sock) {
-                Ok(0) => break,
-                Ok(_) => {}
-                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
-                Err(err) => {
-                    log::debug!("Failed to write TLS close_notify: {err}");
-                    break;
-                }
-            }
+impl NetError {
+    /// The connection or the route to the server failed.
+    fn lost(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            resumable: true,
         }
-
-        let _ = sock.shutdown(Shutdown::Both);
-    }
-}
-
-impl Read for GameConnection {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.stream.read(buf)
-    }
-}
-
-impl Write for GameConnection {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.stream.write(buf)
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        self.stream.flush()
+    /// The server refused or ended the session, or spoke garbage.
+    fn fatal(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            resumable: false,
+        }
     }
 }
 
-/// Runs the network task: connect, wrap in TLS, handshake, then main loop.
+/// Runs the network task: connect, wrap in TLS (and WebSocket), handshake,
+/// then main loop. When the connection drops and `resume` is set, logs in
+/// again with a fresh ticket instead of giving up (see [`super::reconnect`]).
 ///
 /// Intended to be called from `std::thread::spawn`.
 pub(crate) fn run_network_task(
     host: String,
     port: u16,
+    transport: GameTransport,
     ticket: u64,
+    resume: Option<ResumeCredentials>,
     command_rx: mpsc::Receiver<NetworkCommand>,
     event_tx: mpsc::Sender<NetworkEvent>,
 ) {
-    let _ = event_tx.send(NetworkEvent::Status(format!(
-        "Connecting to {host}:{port} (TLS)..."
-    )));
-
-    let addr = format!("{host}:{port}");
-    let tcp_stream = match TcpStream::connect(&addr) {
-        Ok(s) => s,
+    let mut conn = match connect(&host, port, transport, ticket, &event_tx) {
+        Ok(conn) => conn,
         Err(e) => {
-            let _ = event_tx.send(NetworkEvent::Error(format!("Connect failed: {e}")));
+            let _ = event_tx.send(NetworkEvent::Error(e.message));
+            return;
+        }
+    };
+    let _ = event_tx.send(NetworkEvent::LoggedIn);
+
+    loop {
+        let lost = match run_network_loop(conn, &command_rx, &event_tx) {
+            Ok(()) => return,
+            Err(e) => {
+                log::error!("network loop exited with error: {}", e.message);
+                e
+            }
+        };
+        let Some(resume) = resume.as_ref().filter(|_| lost.resumable) else {
+            let _ = event_tx.send(NetworkEvent::Error(lost.message));
             return;
+        };
+        match reconnect(&host, port, transport, resume, &command_rx, &event_tx) {
+            Ok(Some(resumed)) => conn = resumed,
+            Ok(None) => return,
+            Err(e) => {
+                let _ = event_tx.send(NetworkEvent::Error(e.message));
+                return;
+            }
+        }
+    }
+}
+
+/// Logs in again after a dropped connection, backing off between attempts.
+///
+/// Commands queued while the connection is down are dropped; they were
+/// meant for a world state the player no longer sees.
+///
+/// # Returns
+///
+/// * `Ok(Some(conn))` once logged in again; the main thread has been sent
+///   [`NetworkEvent::Resumed`] in place of `LoggedIn`.
+/// * `Ok(None)` when the main thread asked for shutdown meanwhile.
+/// * `Err` when the server refused the login or every attempt failed.
+fn reconnect(
+    host: &str,
+    port: u16,
+    transport: GameTransport,
+    resume: &ResumeCredentials,
+    command_rx: &mpsc::Receiver<NetworkCommand>,
+    event_tx: &mpsc::Sender<NetworkEvent>,
+) -> Result<Option<Box<dyn Transport>>, NetError> {
+    for attempt in 1..=reconnect::MAX_RECONNECT_ATTEMPTS {
+        let delay = reconnect::reconnect_delay(attempt);
+        let _ = event_tx.send(NetworkEvent::Reconnecting { attempt, delay });
+        if !reconnect::wait_for_retry(command_rx, delay) {
+            return Ok(None);
         }
+
+        let ticket = match crate::account_api::create_game_login_ticket(
+            &resume.api_base_url,
+            &resume.api_token,
+            resume.character_id,
+        ) {
+            Ok(ticket) => ticket,
+            Err(e) => {
+                log::warn!("Reconnect attempt {attempt}: ticket request failed: {e}");
+                continue;
+            }
+        };
+
+        match connect(host, port, transport, ticket, event_tx) {
+            Ok(conn) => {
+                // Sent before any packet of the new session is read, so the
+                // main thread drops stale state before it is resent.
+                let _ = event_tx.send(NetworkEvent::Resumed);
+                return Ok(Some(conn));
+            }
+            Err(e) if e.resumable => {
+                log::warn!("Reconnect attempt {attempt} failed: {}", e.message);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(NetError::fatal(format!(
+        "Connection lost; could not reconnect after {} attempts",
+        reconnect::MAX_RECONNECT_ATTEMPTS
+    )))
+}
+
+/// Opens a connection to the game server and logs in with `ticket`.
+fn connect(
+    host: &str,
+    port: u16,
+    transport: GameTransport,
+    ticket: u64,
+    event_tx: &mpsc::Sender<NetworkEvent>,
+) -> Result<Box<dyn Transport>, NetError> {
+    let label = match transport {
+        GameTransport::Tls => "TLS",
+        GameTransport::WebSocket => "WebSocket",
     };
+    let _ = event_tx.send(NetworkEvent::Status(format!(
+        "Connecting to {host}:{port} ({label})..."
+    )));
+
+    let addr = format!("{host}:{port}");
+    let tcp_stream =
+        TcpStream::connect(&addr).map_err(|e| NetError::lost(format!("Connect failed: {e}")))?;
 
     if let Err(e) = tcp_stream.set_read_timeout(Some(Duration::from_millis(5000))) {
         log::warn!("Failed to set read timeout: {e}");
     }
 
     let _ = event_tx.send(NetworkEvent::Status("TLS handshake...".to_owned()));
-    let mut conn = match crate::cert_trust::build_game_tls_connector(&host) {
-        Ok(tls_conn) => {
-            let tls_stream = rustls::StreamOwned::new(tls_conn, tcp_stream);
-            GameConnection { stream: tls_stream }
-        }
-        Err(e) => {
-            let _ = event_tx.send(NetworkEvent::Error(format!("TLS setup failed: {e}")));
-            return;
+    let tls_stream = match crate::cert_trust::build_game_tls_connector(host) {
+        Ok(tls_conn) => rustls::StreamOwned::new(tls_conn, tcp_stream),
+        Err(e) => return Err(NetError::fatal(format!("TLS setup failed: {e}"))),
+    };
+    let mut conn: Box<dyn Transport> = match transport {
+        GameTransport::Tls => Box::new(TlsTransport::new(tls_stream)),
+        GameTransport::WebSocket => {
+            let _ = event_tx.send(NetworkEvent::Status("WebSocket handshake...".to_owned()));
+            Box::new(WsTransport::connect(tls_stream, host, port).map_err(NetError::lost)?)
         }
     };
 
     let _ = event_tx.send(NetworkEvent::Status("Connected. Logging in...".to_owned()));
 
-    if let Err(e) = login_handshake(&mut conn, ticket, &event_tx) {
-        log::error!("login_handshake failed: {e}");
+    if let Err(e) = login_handshake(conn.as_mut(), ticket, event_tx) {
+        log::error!("login_handshake failed: {}", e.message);
         conn.shutdown();
-        let _ = event_tx.send(NetworkEvent::Error(e));
-        return;
-    }
-
-    if let Err(e) = run_network_loop(conn, command_rx, event_tx.clone()) {
-        log::error!("network loop exited with error: {e}");
-        let _ = event_tx.send(NetworkEvent::Error(e));
+        return Err(e);
     }
+    Ok(conn)
 }
 
-/// Reads one login-phase server command (16 bytes, or 2 bytes for tick/exit).
-fn get_server_response(stream: &mut GameConnection) -> Result<ServerCommand, String> {
-    let mut header = [0u8; 1];
-    stream.read_exact(&mut header).map_err(|e| {
+/// Reads one login-phase server command (16 bytes, 2 bytes for tick/exit,
+/// or the length-prefixed `SV_DISCONNECT`).
+fn get_server_response(stream: &mut dyn Transport) -> Result<ServerCommand, String> {
+    let read_err = |e: std::io::Error| {
         if e.kind() == std::io::ErrorKind::WouldBlock {
             "Timed out waiting for server response (check game server IP/port)".to_owned()
         } else {
             format!("Read failed: {e}")
         }
-    })?;
-
-    let opcode = header[0];
-    let remaining = match opcode {
-        27 | 48 => 1usize,
-        _ => 15usize,
     };
 
-    let mut buf = Vec::with_capacity(1 + remaining);
+    let mut header = [0u8; 1];
+    stream.read_exact(&mut header).map_err(read_err)?;
+
+    let opcode = header[0];
+    let mut buf = Vec::with_capacity(16);
     buf.push(opcode);
 
+    let remaining = if opcode == ServerCommandType::Disconnect as u8 {
+        let mut len = [0u8; 2];
+        stream.read_exact(&mut len).map_err(read_err)?;
+        buf.extend_from_slice(&len);
+        usize::from(u16::from_le_bytes(len))
+            .max(mag_core::disconnect::DISCONNECT_HEADER_LEN)
+            .saturating_sub(3)
+    } else {
+        match opcode {
+            27 | 48 => 1usize,
+            _ => 15usize,
+        }
+    };
+
     if remaining > 0 {
         let mut rest = vec![0u8; remaining];
-        stream.read_exact(&mut rest).map_err(|e| {
-            if e.kind() == std::io::ErrorKind::WouldBlock {
-                "Timed out waiting for server response (check game server IP/port)".to_owned()
-            } else {
-                format!("Read failed: {e}")
-            }
-        })?;
+        stream.read_exact(&mut rest).map_err(read_err)?;
         buf.extend_from_slice(&rest);
     }
 
@@ -158,26 +242,66 @@ fn get_server_response(stream: &mut GameConnection) -> Result<ServerCommand, Str
 /// Flow: `CL_API_LOGIN(ticket)` --> loop until `SV_LOGIN_OK`, while accepting
 /// login-time mod data and server exits.
 fn login_handshake(
-    stream: &mut GameConnection,
+    stream: &mut dyn Transport,
     ticket: u64,
     event_tx: &mpsc::Sender<NetworkEvent>,
-) -> Result<(), String> {
+) -> Result<(), NetError> {
     log::info!("Sending api login command (CL_API_LOGIN)");
-    let cmd = client_commands::ClientCommand::new_api_login(ticket);
+    // Announced with the ticket so a rejected login is explained.
+    let cmd = client_commands::ClientCommand::new_api_login(
+        ticket,
+        mag_core::constants::CLIENT_CAP_DISCONNECT_REASON,
+    );
     stream
         .write_all(&cmd.to_bytes())
-        .map_err(|e| format!("Send failed: {e}"))?;
+        .map_err(|e| NetError::lost(format!("Send failed: {e}")))?;
 
     let _ = event_tx.send(NetworkEvent::Status("Login command sent.".to_owned()));
 
     loop {
-        let response = get_server_response(stream)?;
+        let response = get_server_response(stream).map_err(NetError::lost)?;
 
         match response.structured_data {
             ServerCommandData::LoginOk { server_version } => {
                 let _ = event_tx.send(NetworkEvent::Status("Login successful.".to_owned()));
                 log::info!("Logged in with server version: {}", server_version);
-                let _ = event_tx.send(NetworkEvent::LoggedIn);
+                // Ask for full character-sheet snapshots instead of relying
+                // solely on piecemeal SV_SETCHAR* updates, for clock packets
+                // to drive timer UIs, for the view radius camera zoom is
+                // limited to, to have duplicated commands dropped, for NPC
+                // dialogue as keys we translate locally, for area sounds with
+                // their source position so the mixer can pan them, for
+                // blood and scorch decals, for backpack item names the
+                // inventory search matches against, for the guild panel's
+                // name, message and roster, for the mailbox, for the
+                // page buttons and search box of a broker's shop window, for
+                // the reason the server gives when it disconnects us, for the
+                // friends panel, for steering the observer camera, for the
+                // per-hit breakdown the combat log panel shows, and for the
+                // item snapshots behind links in chat.
+                let caps = client_commands::ClientCommand::new_client_caps(
+                    mag_core::constants::CLIENT_CAP_CHAR_SHEET
+                        | mag_core::constants::CLIENT_CAP_TIME_SYNC
+                        | mag_core::constants::CLIENT_CAP_WIDE_VIEW
+                        | mag_core::constants::CLIENT_CAP_MAP_MARKERS
+                        | mag_core::constants::CLIENT_CAP_SKILL_TIMERS
+                        | mag_core::constants::CLIENT_CAP_COMMAND_SEQ
+                        | mag_core::constants::CLIENT_CAP_DIALOGUE_KEYS
+                        | mag_core::constants::CLIENT_CAP_POSITIONAL_SOUND
+                        | mag_core::constants::CLIENT_CAP_MAP_DECALS
+                        | mag_core::constants::CLIENT_CAP_ITEM_NAMES
+                        | mag_core::constants::CLIENT_CAP_GUILDS
+                        | mag_core::constants::CLIENT_CAP_MAIL
+                        | mag_core::constants::CLIENT_CAP_CONSIGNMENT
+                        | mag_core::constants::CLIENT_CAP_DISCONNECT_REASON
+                        | mag_core::constants::CLIENT_CAP_FRIENDS
+                        | mag_core::constants::CLIENT_CAP_OBSERVER
+                        | mag_core::constants::CLIENT_CAP_COMBAT_LOG
+                        | mag_core::constants::CLIENT_CAP_ITEM_LINKS,
+                );
+                stream
+                    .write_all(&caps.to_bytes())
+                    .map_err(|e| NetError::lost(format!("Send failed: {e}")))?;
                 return Ok(());
             }
             ServerCommandData::Mod1 { .. }
@@ -190,39 +314,66 @@ fn login_handshake(
             | ServerCommandData::Mod8 { .. } => {
                 log::info!("Received mod data during login, ignoring");
             }
+            ServerCommandData::Disconnect { reason, text } => {
+                // The `SV_EXIT` that follows carries nothing more.
+                log::warn!("Server refused login, reason={reason}: {text}");
+                return Err(NetError::fatal(describe_disconnect(
+                    LogoutReason::from(reason),
+                    &text,
+                )));
+            }
             ServerCommandData::Exit { reason } => {
                 log::warn!("Server demanded exit during login, reason={reason}");
-                return Err(get_exit_reason(LogoutReason::from(reason as u8)).to_owned());
+                return Err(NetError::fatal(get_exit_reason(LogoutReason::from(
+                    reason as u8,
+                ))));
             }
             _ => {
                 log::error!(
                     "Unexpected server response during login completion: {:?}",
                     response
                 );
-                return Err(format!(
+                return Err(NetError::fatal(format!(
                     "Unexpected server response during login {:?}",
                     response
-                ));
+                )));
             }
         }
     }
 }
 
 /// Main network loop: reads framed tick packets from the server, sends outgoing commands.
+///
+/// # Returns
+///
+/// * `Ok(())` after a requested shutdown, or the error that ended the
+///   connection; it is resumable unless the server sent `SV_EXIT` first or
+///   the stream was malformed.
 fn run_network_loop(
-    mut stream: GameConnection,
-    command_rx: mpsc::Receiver<NetworkCommand>,
-    event_tx: mpsc::Sender<NetworkEvent>,
-) -> Result<(), String> {
+    mut stream: Box<dyn Transport>,
+    command_rx: &mpsc::Receiver<NetworkCommand>,
+    event_tx: &mpsc::Sender<NetworkEvent>,
+) -> Result<(), NetError> {
     log::info!("Entering network loop");
 
     stream
         .set_nonblocking(true)
-        .map_err(|e| format!("Failed to set stream to nonblocking mode: {e}"))?;
+        .map_err(|e| NetError::lost(format!("Failed to set stream to nonblocking mode: {e}")))?;
 
     let mut recv_buf: Vec<u8> = Vec::with_capacity(16 * 1024);
     let mut tick_buffer = [0u8; 4096];
     let mut zlib = Decompress::new(true);
+    let silence_timeout = Duration::from_secs(SERVER_SILENCE_TIMEOUT_SECS);
+    let mut last_received = Instant::now();
+    // A server that said goodbye closes the socket next; that is not a drop.
+    let mut server_exited = false;
+    let lost = |server_exited: bool, message: String| {
+        if server_exited {
+            NetError::fatal(message)
+        } else {
+            NetError::lost(message)
+        }
+    };
 
     loop {
         let mut did_work = false;
@@ -236,7 +387,7 @@ fn run_network_loop(
                         NetworkCommand::Send(bytes) => {
                             stream
                                 .write_all(&bytes)
-                                .map_err(|e| format!("Send failed: {e}"))?;
+                                .map_err(|e| lost(server_exited, format!("Send failed: {e}")))?;
                         }
                         NetworkCommand::Shutdown => {
                             stream.shutdown();
@@ -257,14 +408,33 @@ fn run_network_loop(
         match stream.read(&mut tick_buffer) {
             Ok(0) => {
                 log::warn!("Server closed connection");
-                return Err("Server closed connection".to_owned());
+                return Err(lost(server_exited, "Server closed connection".to_owned()));
             }
             Ok(n) => {
                 did_work = true;
+                last_received = Instant::now();
                 recv_buf.extend_from_slice(&tick_buffer[..n]);
             }
-            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
-            Err(e) => return Err(format!("Read failed: {e}")),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                // The server sends a tick packet every tick; a silent server
+                // means a dead peer (sleep/resume, NAT timeout, lost route).
+                if last_received.elapsed() > silence_timeout {
+                    log::warn!(
+                        "No data from server for {}s; treating connection as dead",
+                        silence_timeout.as_secs()
+                    );
+                    // Skip the TLS close_notify (and WebSocket close):
+                    // writing to a dead peer can block.
+                    return Err(lost(
+                        server_exited,
+                        format!(
+                            "Connection lost: server not responding for {}s",
+                            silence_timeout.as_secs()
+                        ),
+                    ));
+                }
+            }
+            Err(e) => return Err(lost(server_exited, format!("Read failed: {e}"))),
         }
 
         // Parse complete framed packets.
@@ -279,7 +449,9 @@ fn run_network_loop(
 
             if total_len < 2 {
                 log::error!("Invalid packet length header: 0x{len_flags:04X}");
-                return Err(format!("Invalid packet length header: 0x{len_flags:04X}"));
+                return Err(NetError::fatal(format!(
+                    "Invalid packet length header: 0x{len_flags:04X}"
+                )));
             }
 
             if recv_buf.len() < total_len {
@@ -298,7 +470,7 @@ fn run_network_loop(
             if is_compressed {
                 let inflated = inflate_chunk(&mut zlib, &payload).map_err(|e| {
                     log::error!("Tick inflate failed: {e}");
-                    e
+                    NetError::fatal(e)
                 })?;
                 if inflated.is_empty() {
                     let _ = event_tx.send(NetworkEvent::Tick);
@@ -307,8 +479,9 @@ fn run_network_loop(
 
                 let cmds = split_tick_payload(&inflated).map_err(|e| {
                     log::error!("Tick parse failed (compressed): {e}");
-                    format!("Tick parse failed (compressed): {e}")
+                    NetError::fatal(format!("Tick parse failed (compressed): {e}"))
                 })?;
+                server_exited |= contains_exit(&cmds);
                 for cmd in cmds {
                     let _ = event_tx.send(NetworkEvent::Bytes {
                         bytes: cmd,
@@ -319,8 +492,9 @@ fn run_network_loop(
             } else {
                 let cmds = split_tick_payload(&payload).map_err(|e| {
                     log::error!("Tick parse failed (uncompressed): {e}");
-                    format!("Tick parse failed (uncompressed): {e}")
+                    NetError::fatal(format!("Tick parse failed (uncompressed): {e}"))
                 })?;
+                server_exited |= contains_exit(&cmds);
                 for cmd in cmds {
                     let _ = event_tx.send(NetworkEvent::Bytes {
                         bytes: cmd,
@@ -337,6 +511,12 @@ fn run_network_loop(
     }
 }
 
+/// Whether a tick carries `SV_EXIT`, after which the server hangs up.
+fn contains_exit(cmds: &[Vec<u8>]) -> bool {
+    cmds.iter()
+        .any(|cmd| cmd.first() == Some(&(ServerCommandType::Exit as u8)))
+}
+
 /// Decode one zlib-compressed chunk from a continuous zlib stream.
 fn inflate_chunk(z: &mut Decompress, input: &[u8]) -> Result<Vec<u8>, String> {
     if input.is_empty() {
@@ -481,4 +661,21 @@ mod tests {
         let result = split_tick_payload(&payload);
         assert!(result.is_err(), "3-byte SV_SETMAP4 should be rejected");
     }
+
+    /// A disconnect reason is split off ahead of the short trailing SV_EXIT.
+    #[test]
+    fn split_tick_payload_keeps_disconnect_before_exit() {
+        let mut payload = mag_core::disconnect::encode_disconnect(
+            ServerCommandType::Disconnect as u8,
+            LogoutReason::Shutdown,
+            "Back soon.",
+        );
+        let disconnect_len = payload.len();
+        payload.extend_from_slice(&[ServerCommandType::Exit as u8, LogoutReason::Shutdown as u8]);
+
+        let cmds = split_tick_payload(&payload).expect("should parse without error");
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[0].len(), disconnect_len);
+        assert_eq!(cmds[1][0], ServerCommandType::Exit as u8);
+    }
 }
@@ -1,14 +1,22 @@
+pub mod clock_sync;
 mod login;
+mod reconnect;
+mod transport;
 
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
 use mag_core::client_commands::{ClientCommand, ClientCommandType};
 
+use self::clock_sync::ClockSync;
+pub use self::reconnect::ResumeCredentials;
+pub use self::transport::GameTransport;
+
 /// Commands sent from the main thread to the background network thread.
 pub enum NetworkCommand {
-    /// Raw bytes to write to the TCP stream.
+    /// Raw bytes to write to the game connection.
     Send(Vec<u8>),
     /// Request a graceful disconnect.
     Shutdown,
@@ -26,6 +34,14 @@ pub enum NetworkEvent {
     Tick,
     Error(String),
     LoggedIn,
+    /// The connection dropped; the thread retries after `delay`.
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+    },
+    /// Logged in again after [`NetworkEvent::Reconnecting`]; comes in place
+    /// of `LoggedIn`, before any packet of the new session.
+    Resumed,
 }
 
 /// Manages the background network thread and its communication channels.
@@ -46,30 +62,48 @@ pub struct NetworkRuntime {
     pings_in_flight: HashMap<u32, Instant>,
     pub last_rtt_ms: Option<u32>,
     pub rtt_ewma_ms: Option<f32>,
+    /// Server clock estimate fed by `SV_TIMESYNC`; timer UIs read this.
+    pub clock: ClockSync,
+    /// Last `CmdSeq` number handed out; bumped by [`Self::send`].
+    command_seq: Cell<u32>,
+    /// Highest `CmdSeq` number the server reported as applied.
+    pub last_acked_seq: u32,
 }
 
 impl NetworkRuntime {
     /// Creates and starts the network runtime, spawning the background thread.
     ///
     /// The background thread wraps the TCP connection in a TLS layer using the
-    /// TOFU certificate verifier before starting the login handshake.
+    /// TOFU certificate verifier, and for [`GameTransport::WebSocket`] in a
+    /// WebSocket on top of that, before starting the login handshake.
     ///
     /// # Arguments
     ///
     /// * `host` - Value passed to `new`.
     /// * `port` - Value passed to `new`.
+    /// * `transport` - Connection type `port` expects.
     /// * `ticket` - Value passed to `new`.
+    /// * `resume` - Lets the thread log in again when the connection drops;
+    ///   `None` ends the session on the first drop.
     ///
     /// # Returns
     ///
     /// * A new instance configured by `new`.
-    pub fn new(host: String, port: u16, ticket: u64) -> Self {
+    pub fn new(
+        host: String,
+        port: u16,
+        transport: GameTransport,
+        ticket: u64,
+        resume: Option<ResumeCredentials>,
+    ) -> Self {
         let (command_tx, command_rx) = mpsc::channel::<NetworkCommand>();
         let (event_tx, event_rx) = mpsc::channel::<NetworkEvent>();
 
         let tls_host = host.clone();
         let handle = std::thread::spawn(move || {
-            login::run_network_task(tls_host, port, ticket, command_rx, event_tx);
+            login::run_network_task(
+                tls_host, port, transport, ticket, resume, command_rx, event_tx,
+            );
         });
 
         Self {
@@ -85,11 +119,18 @@ impl NetworkRuntime {
             pings_in_flight: HashMap::new(),
             last_rtt_ms: None,
             rtt_ewma_ms: None,
+            clock: ClockSync::new(),
+            command_seq: Cell::new(0),
+            last_acked_seq: 0,
         }
     }
 
     /// Serialises `cmd`, logs it at DEBUG level, and queues the bytes for the network thread.
     ///
+    /// Non-idempotent commands (see [`ClientCommandType::is_sequenced`]) are
+    /// prefixed with a `CmdSeq` frame in the same write, so the server can
+    /// drop them if they ever arrive twice.
+    ///
     /// # Arguments
     ///
     /// * `cmd` - Value passed to `send`.
@@ -100,10 +141,44 @@ impl NetworkRuntime {
             } else {
                 log::info!("Sending command: {}", cmd.get_description());
             }
-            let _ = tx.send(NetworkCommand::Send(cmd.to_bytes()));
+            let bytes = if cmd.header.is_sequenced() {
+                let seq = self.command_seq.get().wrapping_add(1).max(1);
+                self.command_seq.set(seq);
+                let mut bytes = ClientCommand::new_seq(seq).to_bytes();
+                bytes.extend_from_slice(&cmd.to_bytes());
+                bytes
+            } else {
+                cmd.to_bytes()
+            };
+            let _ = tx.send(NetworkCommand::Send(bytes));
         }
     }
 
+    /// Records an `SV_COMMANDACK`.
+    ///
+    /// # Arguments
+    ///
+    /// * `seq` - Highest sequence number the server has applied.
+    pub fn handle_command_ack(&mut self, seq: u32) {
+        self.last_acked_seq = self.last_acked_seq.max(seq);
+        log::debug!(
+            "Command ack {} ({} tagged commands unacknowledged)",
+            seq,
+            self.command_seq.get().saturating_sub(self.last_acked_seq)
+        );
+    }
+
+    /// Resets per-session counters after [`NetworkEvent::Resumed`], since the
+    /// server starts the new session from scratch.
+    pub fn on_resumed(&mut self) {
+        self.client_ticker = 0;
+        self.last_ctick_sent = 0;
+        self.last_ping_sent_at = None;
+        self.pings_in_flight.clear();
+        self.last_acked_seq = 0;
+        self.command_seq.set(0);
+    }
+
     /// Requests a graceful shutdown and joins the background thread.
     pub fn shutdown(&mut self) {
         if let Some(tx) = self.command_tx.take() {
@@ -157,6 +232,19 @@ impl NetworkRuntime {
         }
     }
 
+    /// Applies an `SV_TIMESYNC` packet to [`Self::clock`], compensating
+    /// for transit time with the smoothed RTT.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - Server tick from the packet.
+    /// * `unix_ms` - Server wall-clock from the packet.
+    /// * `received_at` - When the packet was read off the socket.
+    pub fn handle_time_sync(&mut self, tick: u32, unix_ms: u64, received_at: Instant) {
+        let rtt = Duration::from_secs_f32(self.rtt_ewma_ms.unwrap_or(0.0).max(0.0) / 1000.0);
+        self.clock.on_time_sync(tick, unix_ms, received_at, rtt);
+    }
+
     /// Sends a `CL_PING` if the interval has elapsed and we're not over the
     /// in-flight limit. Handles its own timing and sequence numbering.
     pub fn maybe_send_ping(&mut self) {
@@ -0,0 +1,107 @@
+//! Automatic reconnect after a dropped connection.
+//!
+//! Game login tickets are one-time, so the network thread keeps the API
+//! session token instead and asks the API for a fresh ticket before every
+//! attempt. The server keeps a dropped character in the world as linkdead
+//! for a grace period (60 seconds by default); logging in within it picks
+//! the character up where it stands, and the server resends the full map,
+//! character sheet and panels as for any login. The backoff below spans a
+//! little more than that grace period.
+
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use super::NetworkCommand;
+
+/// Attempts made before the client gives up and returns to character
+/// selection.
+pub const MAX_RECONNECT_ATTEMPTS: u32 = 8;
+
+/// Longest wait between two attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(15);
+
+/// What the network thread needs to log the character in again.
+#[derive(Clone, Debug)]
+pub struct ResumeCredentials {
+    /// Account API base URL.
+    pub api_base_url: String,
+    /// Bearer token of the logged-in account.
+    pub api_token: String,
+    /// Character being played.
+    pub character_id: u64,
+}
+
+/// Wait before reconnect attempt `attempt` (1-based): 1, 2, 4 and 8
+/// seconds, then [`MAX_RECONNECT_DELAY`].
+///
+/// # Arguments
+///
+/// * `attempt` - Attempt number, starting at 1.
+///
+/// # Returns
+///
+/// * The delay before that attempt.
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    let secs = 1u64 << attempt.saturating_sub(1).min(4);
+    Duration::from_secs(secs).min(MAX_RECONNECT_DELAY)
+}
+
+/// Sleeps for `delay` while draining commands queued for the dead
+/// connection.
+///
+/// # Arguments
+///
+/// * `command_rx` - Commands from the main thread.
+/// * `delay` - How long to wait.
+///
+/// # Returns
+///
+/// * `false` if the main thread asked for shutdown (or went away) meanwhile.
+pub fn wait_for_retry(command_rx: &mpsc::Receiver<NetworkCommand>, delay: Duration) -> bool {
+    let deadline = Instant::now() + delay;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return true;
+        }
+        match command_rx.recv_timeout(remaining) {
+            Ok(NetworkCommand::Send(_)) => {}
+            Ok(NetworkCommand::Shutdown) => return false,
+            Err(mpsc::RecvTimeoutError::Timeout) => return true,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles_then_caps() {
+        let delays: Vec<u64> = (1..=MAX_RECONNECT_ATTEMPTS)
+            .map(|attempt| reconnect_delay(attempt).as_secs())
+            .collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 15, 15, 15, 15]);
+    }
+
+    #[test]
+    fn attempts_outlast_the_default_linkdead_grace() {
+        let total: Duration = (1..=MAX_RECONNECT_ATTEMPTS).map(reconnect_delay).sum();
+        assert!(total > Duration::from_secs(60));
+    }
+
+    #[test]
+    fn wait_drops_queued_commands_and_stops_on_shutdown() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(NetworkCommand::Send(vec![1, 2, 3])).unwrap();
+        assert!(wait_for_retry(&rx, Duration::from_millis(10)));
+        assert!(rx.try_recv().is_err());
+
+        tx.send(NetworkCommand::Shutdown).unwrap();
+        assert!(!wait_for_retry(&rx, Duration::from_secs(5)));
+
+        drop(tx);
+        assert!(!wait_for_retry(&rx, Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,206 @@
+//! Connections the game protocol can run over.
+//!
+//! The login handshake and the network loop only need a byte stream they can
+//! read, write, switch to non-blocking and close; [`Transport`] is that
+//! interface. [`TlsTransport`] is the default TLS-over-TCP connection to the
+//! game port. [`WsTransport`] carries the same stream in binary WebSocket
+//! messages over TLS (`wss://`), for networks that only let HTTPS through.
+
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+
+use tungstenite::{Message, WebSocket};
+
+type TlsStream = rustls::StreamOwned<rustls::ClientConnection, TcpStream>;
+
+/// Which transport the client connects with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameTransport {
+    /// TLS over TCP to the game port.
+    Tls,
+    /// WebSocket over TLS to the server's WebSocket port.
+    WebSocket,
+}
+
+/// A connection that carries the game's byte stream.
+pub(crate) trait Transport: Read + Write + Send {
+    /// Switches the underlying socket between blocking and non-blocking.
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+
+    /// Closes the connection, telling the server where the transport allows.
+    fn shutdown(&mut self);
+}
+
+/// Sends the TLS `close_notify` and closes the socket.
+fn close_tls(stream: &mut TlsStream) {
+    let _ = stream.sock.set_nonblocking(false);
+
+    stream.conn.send_close_notify();
+
+    let (conn, sock) = (&mut stream.conn, &mut stream.sock);
+    while conn.wants_write() {
+        match conn.write_tls(sock) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => continue,
+            Err(err) => {
+                log::debug!("Failed to write TLS close_notify: {err}");
+                break;
+            }
+        }
+    }
+
+    let _ = sock.shutdown(Shutdown::Both);
+}
+
+/// A game connection backed by a TLS session over TCP.
+pub(crate) struct TlsTransport {
+    stream: TlsStream,
+}
+
+impl TlsTransport {
+    /// Wraps a TLS stream whose handshake runs on first use.
+    pub(crate) fn new(stream: TlsStream) -> Self {
+        Self { stream }
+    }
+}
+
+impl Read for TlsTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for TlsTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Transport for TlsTransport {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.stream.sock.set_nonblocking(nonblocking)
+    }
+
+    fn shutdown(&mut self) {
+        close_tls(&mut self.stream);
+    }
+}
+
+/// A game connection carried in binary WebSocket messages over TLS.
+///
+/// Every write is sent as one message; reads hand out message payloads as a
+/// continuous stream, so the framing code above it sees the same bytes as
+/// over [`TlsTransport`].
+pub(crate) struct WsTransport {
+    ws: WebSocket<TlsStream>,
+    /// Payload of the last message, handed out by `read`.
+    pending: Vec<u8>,
+    /// Bytes of `pending` already handed out.
+    pos: usize,
+}
+
+impl WsTransport {
+    /// Performs the WebSocket opening handshake on `stream`, which runs the
+    /// TLS handshake first.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - Blocking TLS stream to the server's WebSocket port.
+    /// * `host` - Server host, for the request URL.
+    /// * `port` - Server WebSocket port, for the request URL.
+    ///
+    /// # Returns
+    ///
+    /// * The upgraded connection, or an error message.
+    pub(crate) fn connect(stream: TlsStream, host: &str, port: u16) -> Result<Self, String> {
+        let url = format!("wss://{host}:{port}/");
+        let (ws, _) = tungstenite::client(url.as_str(), stream).map_err(|e| match e {
+            tungstenite::HandshakeError::Interrupted(_) => {
+                "Timed out during WebSocket handshake (check game server IP/port)".to_owned()
+            }
+            tungstenite::HandshakeError::Failure(e) => format!("WebSocket handshake failed: {e}"),
+        })?;
+        Ok(Self {
+            ws,
+            pending: Vec::new(),
+            pos: 0,
+        })
+    }
+}
+
+/// Converts a WebSocket error into the `io::Error` the framing code expects;
+/// `WouldBlock` passes through unchanged.
+fn ws_io_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(e) => e,
+        other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+    }
+}
+
+impl Read for WsTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.pending.len() {
+            match self.ws.read() {
+                Ok(Message::Binary(data)) => {
+                    self.pending = data.into();
+                    self.pos = 0;
+                }
+                Ok(Message::Close(_)) => return Ok(0),
+                Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_)) => {}
+                Ok(Message::Text(_)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "text WebSocket message on the game stream",
+                    ));
+                }
+                Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                    return Ok(0);
+                }
+                Err(e) => return Err(ws_io_error(e)),
+            }
+        }
+        let len = buf.len().min(self.pending.len() - self.pos);
+        buf[..len].copy_from_slice(&self.pending[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+impl Write for WsTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        match self.ws.send(Message::binary(buf.to_vec())) {
+            Ok(()) => Ok(buf.len()),
+            // Queued; tungstenite sends the rest on the next write or read.
+            Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {
+                Ok(buf.len())
+            }
+            Err(e) => Err(ws_io_error(e)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.ws.flush().map_err(ws_io_error)
+    }
+}
+
+impl Transport for WsTransport {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.ws.get_ref().sock.set_nonblocking(nonblocking)
+    }
+
+    fn shutdown(&mut self) {
+        let _ = self.ws.get_ref().sock.set_nonblocking(false);
+        if let Err(err) = self.ws.close(None) {
+            log::debug!("Failed to send WebSocket close: {err}");
+        }
+        close_tls(self.ws.get_mut());
+    }
+}
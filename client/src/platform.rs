@@ -115,7 +115,7 @@ impl PlatformProfile {
                 settings.display_mode = DisplayMode::Fullscreen;
                 settings.vsync_enabled = true;
                 settings.pixel_perfect_scaling = false;
-                // A sensible non-zero starting volume.
+                // Start quieter on the built-in speakers.
                 settings.master_volume = 0.5;
             }
             Platform::Linux | Platform::MacOS | Platform::Windows => {
@@ -1,14 +1,27 @@
 use mag_core::{
+    char_sheet,
     circular_buffer::CircularBuffer,
+    consignment::ConsignmentPage,
     constants::{MAX_SPEEDTAB_INDEX, TICKS},
+    friends::FriendOp,
+    guilds::{GuildRank, GuildRosterOp},
     logout_reasons::get_exit_reason,
+    mail::{MailEntry, MailOp},
     server_commands::{ServerCommand, ServerCommandData, ServerCommandType},
+    skill_timers::{SkillTimer, SkillTimerKind},
+    skill_trainers::TrainerOfferEntry,
     types::ClientPlayer,
 };
 
 use crate::{
+    combat_log::CombatLog,
     game_map::GameMap,
-    types::{log_message::LogMessage, look::Look},
+    item_links::ItemLinkCache,
+    types::{
+        client_event::{ClientEvent, ClientEventQueue},
+        log_message::{ChatItemLink, LogMessage},
+        look::Look,
+    },
 };
 
 /// Central per-character gameplay state on the client side.
@@ -39,6 +52,9 @@ pub struct PlayerState {
 
     look_names: Vec<Option<LookNameEntry>>,
     pending_log: String,
+    /// Partial `SV_LOGSTYLED` message and the style it was sent with.
+    pending_styled_log: String,
+    pending_log_style: u8,
     server_version: u32,
     load_percentage: u32,
 
@@ -48,12 +64,75 @@ pub struct PlayerState {
 
     exit_requested_reason: Option<u32>,
 
+    /// Latest `SV_NPCMENU` as `(npc character number, option mask)`, until
+    /// the scene opens it.
+    pending_npc_menu: Option<(u16, u8)>,
+
+    /// Latest `SV_TRAINEROFFERS` as `(trainer character number, offers)`,
+    /// until the scene shows it.
+    pending_trainer_offers: Option<(u16, Vec<TrainerOfferEntry>)>,
+
+    /// Local checksum to report in `CmdRequestResync` after an
+    /// `SV_CHARCHECKSUM` mismatch, until the scene sends it.
+    pending_resync: Option<u32>,
+
     /// Latest server snapshot of the 25-byte packed talent state.
     ///
     /// `talents[0]` is the unspent points pool; `talents[1..24]` are the
     /// per-layer bit fields (8 nodes per byte). See `core::talent_trees`.
     talents: [u8; 25],
 
+    /// Largest view radius the server allows the camera to zoom out to,
+    /// from `SV_SETVIEW`. See `core::view`.
+    max_view_radius: u8,
+
+    /// Minimap markers from `SV_MAPMARKER`, indexed by `MapMarkerKind`;
+    /// `(0, 0)` = not set. See `core::map_markers`.
+    map_markers: [(u16, u16); mag_core::map_markers::MapMarkerKind::ALL.len()],
+
+    /// Names of the backpack items from `SV_ITEMNAME`, indexed by slot;
+    /// empty for empty slots or servers that do not send names.
+    item_names: [String; 40],
+
+    /// Guild from `SV_GUILDINFO` / `SV_GUILDMEMBER`; `None` when not in a
+    /// guild or the server does not send guilds.
+    guild: Option<GuildInfo>,
+
+    /// Letters from `SV_MAILENTRY`, ordered by id (oldest first).
+    mailbox: Vec<MailEntry>,
+
+    /// Friends list from `SV_FRIENDENTRY`, in the order the server sent it.
+    friends: Vec<FriendEntry>,
+
+    /// `true` while the server reports observer mode with `SV_OBSERVER`.
+    observer: bool,
+
+    /// Hits dealt and taken, from `SV_COMBATEVENT`.
+    combat_log: CombatLog,
+
+    /// Snapshots of the items linked in chat, from `SV_ITEMLINK`.
+    item_links: ItemLinkCache,
+
+    /// Last `SV_CONSIGNPAGE`: the broker page the shop window shows.
+    consignment_page: Option<ConsignmentPage>,
+
+    /// Running skill cooldowns from `SV_SKILLTIMER`, keyed by skill index
+    /// and counted down once per tick. See `core::skill_timers`.
+    skill_cooldowns: std::collections::HashMap<u8, SkillTimer>,
+    /// Skill being cast and the time until it takes effect.
+    skill_cast: Option<(u8, SkillTimer)>,
+
+    /// Earned-title bitmask from `SV_SETCHARTITLES`. See `core::titles`.
+    titles_earned: u32,
+    /// Currently displayed title id (`0` = none).
+    title_selected: u8,
+    /// `true` once the login title snapshot arrived; later snapshots that
+    /// add titles raise an achievement toast.
+    titles_received: bool,
+
+    /// Notification events waiting to be shown as toasts.
+    client_events: ClientEventQueue,
+
     /// Immutable per-session catalog of NPC quests. Sent once at login
     /// via `SV_SETQUESTCATALOG`.
     quest_catalog: Vec<mag_core::quest_defs::QuestCatalogEntry>,
@@ -70,6 +149,39 @@ pub struct PlayerState {
     /// Tile coordinates of the currently focused NPC, when known. Used
     /// by `QuestStep::ReturnToQuestGiver` to drive the minimap pin.
     active_quest_npc_pos: Option<(u16, u16)>,
+
+    /// Templates `SV_DIALOGUE` lines are rendered from. See
+    /// `core::dialogue`.
+    dialogue: mag_core::dialogue::DialogueCatalog,
+    /// Language code `SV_DIALOGUE` lines are rendered in.
+    dialogue_locale: String,
+}
+
+/// The player's guild as last sent by the server.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuildInfo {
+    pub name: String,
+    /// The player's own rank.
+    pub rank: GuildRank,
+    /// Guild message of the day; empty when none is set.
+    pub motd: String,
+    /// Members in the order the server sent them.
+    pub members: Vec<GuildRosterEntry>,
+}
+
+/// One member of the player's guild.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuildRosterEntry {
+    pub name: String,
+    pub rank: GuildRank,
+    pub online: bool,
+}
+
+/// One character on the account's friends list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FriendEntry {
+    pub name: String,
+    pub online: bool,
 }
 
 /// A cached (nr --> name) entry used by the auto-look name overlay.
@@ -77,6 +189,8 @@ pub struct PlayerState {
 struct LookNameEntry {
     id: u16,
     name: String,
+    name_color: mag_core::profile::NameColor,
+    title: u8,
 }
 
 impl Default for PlayerState {
@@ -100,6 +214,8 @@ impl Default for PlayerState {
             look_names: Vec::new(),
 
             pending_log: String::new(),
+            pending_styled_log: String::new(),
+            pending_log_style: 0,
 
             server_version: 0,
             load_percentage: 0,
@@ -109,14 +225,49 @@ impl Default for PlayerState {
             local_ctick: 0,
 
             exit_requested_reason: None,
+            pending_npc_menu: None,
+            pending_trainer_offers: None,
+            pending_resync: None,
 
             talents: [0; 25],
 
+            max_view_radius: mag_core::view::LEGACY_VIEW_RADIUS,
+
+            map_markers: [(0, 0); mag_core::map_markers::MapMarkerKind::ALL.len()],
+
+            item_names: std::array::from_fn(|_| String::new()),
+
+            guild: None,
+
+            mailbox: Vec::new(),
+
+            friends: Vec::new(),
+
+            observer: false,
+
+            combat_log: CombatLog::new(),
+
+            item_links: ItemLinkCache::new(),
+
+            consignment_page: None,
+
+            skill_cooldowns: std::collections::HashMap::new(),
+            skill_cast: None,
+
+            titles_earned: 0,
+            title_selected: 0,
+            titles_received: false,
+
+            client_events: ClientEventQueue::default(),
+
             quest_catalog: Vec::new(),
             quest_completion_counts: [-1; mag_core::quest_defs::MAX_QUEST_CATALOG],
             active_quest_template_id: 0,
             active_quest_step_idx: 0,
             active_quest_npc_pos: None,
+
+            dialogue: mag_core::dialogue::DialogueCatalog::default(),
+            dialogue_locale: mag_core::dialogue::DEFAULT_LOCALE.to_owned(),
         }
     }
 }
@@ -143,6 +294,30 @@ impl PlayerState {
         self.message_log.total_pushed()
     }
 
+    /// Drops what the server resends on login, keeping the chat log, the
+    /// character sheet shown until the new one arrives, quest progress and
+    /// dialogue settings.
+    ///
+    /// Called when the network thread logged in again after a dropped
+    /// connection: the server starts the new session from an empty map, so
+    /// the client's copy has to start empty as well for the deltas to add up.
+    pub fn reset_for_resume(&mut self) {
+        let previous = std::mem::take(self);
+        *self = Self {
+            message_log: previous.message_log,
+            character_info: previous.character_info,
+            client_events: previous.client_events,
+            quest_catalog: previous.quest_catalog,
+            quest_completion_counts: previous.quest_completion_counts,
+            active_quest_template_id: previous.active_quest_template_id,
+            active_quest_step_idx: previous.active_quest_step_idx,
+            active_quest_npc_pos: previous.active_quest_npc_pos,
+            dialogue: previous.dialogue,
+            dialogue_locale: previous.dialogue_locale,
+            ..Self::default()
+        };
+    }
+
     /// Takes and returns the pending server-requested exit reason, if any.
     ///
     /// # Returns
@@ -151,6 +326,30 @@ impl PlayerState {
         self.exit_requested_reason.take()
     }
 
+    /// Takes the NPC interaction menu the server sent, if any.
+    ///
+    /// # Returns
+    /// * `Some((npc_nr, option_mask))` once per `SV_NPCMENU`.
+    pub fn take_npc_menu(&mut self) -> Option<(u16, u8)> {
+        self.pending_npc_menu.take()
+    }
+
+    /// Takes the trainer offer list the server sent, if any.
+    ///
+    /// # Returns
+    /// * `Some((trainer_nr, offers))` once per `SV_TRAINEROFFERS`.
+    pub fn take_trainer_offers(&mut self) -> Option<(u16, Vec<TrainerOfferEntry>)> {
+        self.pending_trainer_offers.take()
+    }
+
+    /// Takes the pending resync request raised by a checksum mismatch.
+    ///
+    /// # Returns
+    /// * `Some(local_checksum)` once per mismatching `SV_CHARCHECKSUM`.
+    pub fn take_resync_request(&mut self) -> Option<u32> {
+        self.pending_resync.take()
+    }
+
     /// Returns a shared reference to the visible tile map.
     ///
     /// # Returns
@@ -190,6 +389,158 @@ impl PlayerState {
         &self.talents
     }
 
+    /// Returns the view radius the server allows (see `core::view`).
+    ///
+    /// # Returns
+    ///
+    /// * `LEGACY_VIEW_RADIUS` until the server sends `SV_SETVIEW`.
+    pub fn max_view_radius(&self) -> u8 {
+        self.max_view_radius
+    }
+
+    /// Returns the position of a server-announced minimap marker.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Marker kind.
+    ///
+    /// # Returns
+    ///
+    /// * `Some((x, y))` in world tiles, or `None` if the server has not set
+    ///   the marker.
+    pub fn map_marker(&self, kind: mag_core::map_markers::MapMarkerKind) -> Option<(u16, u16)> {
+        let pos = self.map_markers[kind as usize];
+        (pos != (0, 0)).then_some(pos)
+    }
+
+    /// Returns the names of the backpack items, indexed by slot.
+    ///
+    /// # Returns
+    ///
+    /// * One name per backpack slot; empty where the slot is empty or the
+    ///   server has not named the item.
+    pub fn item_names(&self) -> &[String; 40] {
+        &self.item_names
+    }
+
+    /// Returns the player's guild.
+    ///
+    /// # Returns
+    ///
+    /// * The guild, or `None` when the player is in no guild.
+    pub fn guild(&self) -> Option<&GuildInfo> {
+        self.guild.as_ref()
+    }
+
+    /// Returns the letters in the player's mailbox, oldest first.
+    pub fn mailbox(&self) -> &[MailEntry] {
+        &self.mailbox
+    }
+
+    /// Returns the account's friends list.
+    pub fn friends(&self) -> &[FriendEntry] {
+        &self.friends
+    }
+
+    /// Returns `true` while the character observes (`#observe`): it has no
+    /// tile and move commands move its camera.
+    pub fn is_observer(&self) -> bool {
+        self.observer
+    }
+
+    /// Returns the hits the character dealt and took.
+    pub fn combat_log(&self) -> &CombatLog {
+        &self.combat_log
+    }
+
+    /// Returns the combat log for clearing it.
+    pub fn combat_log_mut(&mut self) -> &mut CombatLog {
+        &mut self.combat_log
+    }
+
+    /// Returns the broker page the open shop window shows.
+    ///
+    /// # Returns
+    ///
+    /// * The page, or `None` when the shop is no broker's or the server does
+    ///   not send pages.
+    pub fn consignment_page(&self) -> Option<&ConsignmentPage> {
+        self.consignment_page
+            .as_ref()
+            .filter(|page| page.broker == self.shop_target.nr())
+    }
+
+    /// Returns the running cooldown of a skill.
+    ///
+    /// # Arguments
+    ///
+    /// * `skill` - Skill index.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(timer)` while the skill is recharging, otherwise `None`.
+    pub fn skill_cooldown(&self, skill: usize) -> Option<SkillTimer> {
+        let skill = u8::try_from(skill).ok()?;
+        self.skill_cooldowns.get(&skill).copied()
+    }
+
+    /// Returns the skill being cast and its cast timer.
+    ///
+    /// # Returns
+    ///
+    /// * `Some((skill, timer))` while a cast is in progress.
+    pub fn skill_cast(&self) -> Option<(usize, SkillTimer)> {
+        self.skill_cast
+            .map(|(skill, timer)| (usize::from(skill), timer))
+    }
+
+    /// Counts the cast and cooldown timers down by one tick, dropping the
+    /// ones that ran out.
+    fn tick_skill_timers(&mut self) {
+        self.skill_cooldowns.retain(|_, timer| timer.tick());
+        if let Some((_, timer)) = &mut self.skill_cast
+            && !timer.tick()
+        {
+            self.skill_cast = None;
+        }
+    }
+
+    /// Returns the player's earned-title bitmask (see `core::titles`).
+    ///
+    /// # Returns
+    ///
+    /// * Bit `id` is set for every earned title id.
+    pub fn titles_earned(&self) -> u32 {
+        self.titles_earned
+    }
+
+    /// Queues a notification event for the toast stack.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Event to show.
+    pub fn push_client_event(&mut self, event: ClientEvent) {
+        self.client_events.push(event);
+    }
+
+    /// Removes and returns all pending notification events, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// * The drained events.
+    pub fn drain_client_events(&mut self) -> Vec<ClientEvent> {
+        self.client_events.drain()
+    }
+
+    /// Returns the title the player currently displays.
+    ///
+    /// # Returns
+    ///
+    /// * The selected title id, or `0` for none.
+    pub fn title_selected(&self) -> u8 {
+        self.title_selected
+    }
+
     /// Returns the immutable per-session quest catalog snapshot.
     ///
     /// # Returns
@@ -341,6 +692,40 @@ impl PlayerState {
             .map(|e| e.name.as_str())
     }
 
+    /// Looks up the cached profile name color by tile `nr` and optional `id`.
+    ///
+    /// # Arguments
+    /// * `nr` - Tile character number.
+    /// * `id` - Character ID (0 matches any).
+    ///
+    /// # Returns
+    /// * The cached [`NameColor`](mag_core::profile::NameColor), or
+    ///   `NameColor::Default` when the name is not cached.
+    pub fn lookup_name_color(&self, nr: u16, id: u16) -> mag_core::profile::NameColor {
+        self.look_names
+            .get(nr as usize)
+            .and_then(|e| e.as_ref())
+            .filter(|e| id == 0 || e.id == id)
+            .map(|e| e.name_color)
+            .unwrap_or_default()
+    }
+
+    /// Looks up the cached display title by tile `nr` and optional `id`.
+    ///
+    /// # Arguments
+    /// * `nr` - Tile character number.
+    /// * `id` - Character ID (0 matches any).
+    ///
+    /// # Returns
+    /// * The selected title id (`0` = none or not cached).
+    pub fn lookup_title(&self, nr: u16, id: u16) -> u8 {
+        self.look_names
+            .get(nr as usize)
+            .and_then(|e| e.as_ref())
+            .filter(|e| id == 0 || e.id == id)
+            .map_or(0, |e| e.title)
+    }
+
     /// Returns the `ch_nr` of the currently selected (clicked) character tile.
     ///
     /// # Returns
@@ -350,6 +735,15 @@ impl PlayerState {
         self.selected_char
     }
 
+    /// Returns the `id` of the currently selected character tile.
+    ///
+    /// # Returns
+    ///
+    /// * The id passed to `set_selected_char_with_id`, `0` when none.
+    pub fn selected_char_id(&self) -> u16 {
+        self.selected_char_id
+    }
+
     /// Sets both the selected character `nr` and `id`.
     ///
     /// # Arguments
@@ -367,7 +761,14 @@ impl PlayerState {
         self.selected_char_id = 0;
     }
 
-    fn set_known_name(&mut self, nr: u16, id: u16, name: &str) {
+    fn set_known_name(
+        &mut self,
+        nr: u16,
+        id: u16,
+        name: &str,
+        name_color: mag_core::profile::NameColor,
+        title: u8,
+    ) {
         let idx = nr as usize;
         if self.look_names.len() <= idx {
             self.look_names.resize_with(idx + 1, || None);
@@ -375,6 +776,8 @@ impl PlayerState {
         self.look_names[idx] = Some(LookNameEntry {
             id,
             name: name.to_owned(),
+            name_color,
+            title,
         });
     }
 
@@ -396,6 +799,9 @@ impl PlayerState {
             }
         }
 
+        self.tick_skill_timers();
+        self.combat_log.tick();
+
         if self.server_ctick_pending {
             self.local_ctick = self.server_ctick.min(MAX_SPEEDTAB_INDEX as u8);
             self.server_ctick_pending = false;
@@ -406,6 +812,17 @@ impl PlayerState {
         crate::legacy_engine::engine_tick(self, client_ticker, self.local_ctick as usize);
     }
 
+    /// Sets the catalog and language `SV_DIALOGUE` lines are rendered with.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog` - Built-in templates plus any installed translations.
+    /// * `locale` - Language code from the settings.
+    pub fn set_dialogue(&mut self, catalog: mag_core::dialogue::DialogueCatalog, locale: String) {
+        self.dialogue = catalog;
+        self.dialogue_locale = locale;
+    }
+
     /// Maps a network font index to a [`LogMessageColor`](crate::types::log_message::LogMessageColor).
     fn log_color_from_font(font: u8) -> crate::types::log_message::LogMessageColor {
         use crate::types::log_message::LogMessageColor;
@@ -418,10 +835,18 @@ impl PlayerState {
         }
     }
 
-    fn push_log_message(&mut self, text: String, font: u8) {
+    fn push_log_message(
+        &mut self,
+        text: String,
+        font: u8,
+        style: mag_core::chat::ChatStyle,
+        links: Vec<ChatItemLink>,
+    ) {
         let msg = LogMessage {
             message: text,
             color: Self::log_color_from_font(font),
+            style,
+            links,
         };
         self.message_log.push(msg);
     }
@@ -507,13 +932,39 @@ impl PlayerState {
     /// * `font` - Network font index (0=red, 1=yellow, 2=green, 3=blue).
     /// * `text` - The message text.
     pub fn tlog(&mut self, font: u8, text: impl AsRef<str>) {
+        self.tlog_styled(font, mag_core::chat::ChatStyle::default(), text);
+    }
+
+    /// Appends a styled chat message to the log, word-wrapping it first.
+    ///
+    /// Item link tokens become the linked item names, kept on one line.
+    ///
+    /// # Arguments
+    /// * `font` - Base network font index for the message.
+    /// * `style` - Channel and presentation flags applied to every wrapped line.
+    /// * `text` - The message text.
+    fn tlog_styled(&mut self, font: u8, style: mag_core::chat::ChatStyle, text: impl AsRef<str>) {
         const XS: usize = 49;
 
-        let wrapped = Self::wrap_log_text(text.as_ref(), XS);
+        let text = text.as_ref();
+        if !text.contains(mag_core::item_link::ITEM_LINK_MARK) {
+            let wrapped = Self::wrap_log_text(text, XS);
+            for line in wrapped.split('\n') {
+                let line = line.trim_end_matches('\r');
+                if !line.is_empty() {
+                    self.push_log_message(line.to_owned(), font, style, Vec::new());
+                }
+            }
+            return;
+        }
+
+        let (marked, mut unmarker) = self.item_links.mark_links(text);
+        let wrapped = Self::wrap_log_text(&marked, XS);
         for line in wrapped.split('\n') {
             let line = line.trim_end_matches('\r');
             if !line.is_empty() {
-                self.push_log_message(line.to_owned(), font);
+                let (line, links) = unmarker.unmark_line(line);
+                self.push_log_message(line, font, style, links);
             }
         }
     }
@@ -541,6 +992,25 @@ impl PlayerState {
         }
     }
 
+    fn handle_styled_log_chunk(&mut self, style: u8, chunk: &str) {
+        if self.pending_styled_log.len() > 1024 || style != self.pending_log_style {
+            self.pending_styled_log.clear();
+        }
+        self.pending_log_style = style;
+        self.pending_styled_log.push_str(chunk);
+
+        let style = mag_core::chat::ChatStyle::from_byte(style);
+        while let Some(idx) = self.pending_styled_log.find('\n') {
+            let line = self.pending_styled_log[..idx].to_string();
+            if style.channel == mag_core::chat::ChatChannel::Reward {
+                self.client_events
+                    .push(ClientEvent::RewardDelivered { text: line.clone() });
+            }
+            self.tlog_styled(style.channel.legacy_font(), style, line);
+            self.pending_styled_log.drain(..=idx);
+        }
+    }
+
     /// Applies a single parsed server command to this player state,
     /// updating the map, stats, look panel, chat log, and other fields
     /// as appropriate.
@@ -616,6 +1086,141 @@ impl PlayerState {
             ServerCommandData::SetCharTalents { values } => {
                 self.talents = *values;
             }
+            ServerCommandData::SetCharTitles { earned, selected } => {
+                if self.titles_received {
+                    for title in mag_core::titles::earned_titles(*earned & !self.titles_earned) {
+                        self.client_events.push(ClientEvent::AchievementUnlocked {
+                            name: format!("Title earned: {}", title.name),
+                        });
+                    }
+                }
+                self.titles_received = true;
+                self.titles_earned = *earned;
+                self.title_selected = *selected;
+            }
+            ServerCommandData::SetView { radius } => {
+                self.max_view_radius = mag_core::view::clamp_view_radius(*radius);
+            }
+            ServerCommandData::MapMarker { kind, x, y } => {
+                if let Some(kind) = mag_core::map_markers::MapMarkerKind::from_u8(*kind) {
+                    self.map_markers[kind as usize] = (*x, *y);
+                }
+            }
+            ServerCommandData::ItemName { slot, name } => {
+                if let Some(entry) = self.item_names.get_mut(usize::from(*slot)) {
+                    entry.clone_from(name);
+                }
+            }
+            ServerCommandData::GuildInfo { rank, name, motd } => {
+                self.guild = (!name.is_empty()).then(|| GuildInfo {
+                    name: name.clone(),
+                    rank: GuildRank::from_u8(*rank).unwrap_or_default(),
+                    motd: motd.clone(),
+                    members: Vec::new(),
+                });
+            }
+            ServerCommandData::GuildMember {
+                op,
+                rank,
+                online,
+                name,
+            } => {
+                if let Some(guild) = self.guild.as_mut() {
+                    let existing = guild.members.iter().position(|m| m.name == *name);
+                    match (GuildRosterOp::from_u8(*op), existing) {
+                        (GuildRosterOp::Remove, Some(idx)) => {
+                            guild.members.remove(idx);
+                        }
+                        (GuildRosterOp::Remove, None) => {}
+                        (GuildRosterOp::Set, existing) => {
+                            let entry = GuildRosterEntry {
+                                name: name.clone(),
+                                rank: GuildRank::from_u8(*rank).unwrap_or_default(),
+                                online: *online,
+                            };
+                            match existing {
+                                Some(idx) => guild.members[idx] = entry,
+                                None => guild.members.push(entry),
+                            }
+                        }
+                    }
+                }
+            }
+            ServerCommandData::MailEntry { op, entry } => match MailOp::from_u8(*op) {
+                MailOp::Clear => self.mailbox.clear(),
+                MailOp::Remove => self.mailbox.retain(|letter| letter.id != entry.id),
+                MailOp::Set => match self.mailbox.binary_search_by_key(&entry.id, |l| l.id) {
+                    Ok(idx) => self.mailbox[idx] = entry.clone(),
+                    Err(idx) => self.mailbox.insert(idx, entry.clone()),
+                },
+            },
+            ServerCommandData::FriendEntry { op, online, name } => {
+                let existing = self.friends.iter().position(|f| f.name == *name);
+                match (FriendOp::from_u8(*op), existing) {
+                    (FriendOp::Clear, _) => self.friends.clear(),
+                    (FriendOp::Remove, Some(idx)) => {
+                        self.friends.remove(idx);
+                    }
+                    (FriendOp::Remove, None) => {}
+                    (FriendOp::Set, Some(idx)) => self.friends[idx].online = *online,
+                    (FriendOp::Set, None) => self.friends.push(FriendEntry {
+                        name: name.clone(),
+                        online: *online,
+                    }),
+                }
+            }
+            ServerCommandData::Observer { active } => {
+                self.observer = *active;
+            }
+            ServerCommandData::CombatEvent(event) => {
+                self.combat_log.push(event.clone());
+            }
+            ServerCommandData::ItemLink(link) => {
+                self.item_links.insert(link.clone());
+            }
+            ServerCommandData::ConsignmentPage(page) => {
+                self.consignment_page = Some(page.clone());
+            }
+            ServerCommandData::SkillTimer {
+                kind,
+                skill,
+                remaining,
+                total,
+            } => {
+                let timer = SkillTimer::new(*remaining, *total);
+                match SkillTimerKind::from_u8(*kind) {
+                    Some(SkillTimerKind::Cast) => {
+                        self.skill_cast = (*remaining > 0).then_some((*skill, timer));
+                    }
+                    Some(SkillTimerKind::Cooldown) if *remaining > 0 => {
+                        self.skill_cooldowns.insert(*skill, timer);
+                    }
+                    Some(SkillTimerKind::Cooldown) => {
+                        self.skill_cooldowns.remove(skill);
+                    }
+                    None => {}
+                }
+            }
+            ServerCommandData::NpcMenu { target, options } => {
+                self.pending_npc_menu = Some((*target, *options));
+            }
+            ServerCommandData::TrainerOffers { target, offers } => {
+                self.pending_trainer_offers = Some((*target, offers.clone()));
+            }
+            ServerCommandData::SetCharSheet(sheet) => {
+                sheet.apply_to(&mut self.character_info);
+            }
+            ServerCommandData::CharChecksum { checksum } => {
+                let local = char_sheet::client_checksum(&self.character_info);
+                if local != *checksum {
+                    log::warn!(
+                        "Character state out of sync (local {:#010x}, server {:#010x}), requesting resync",
+                        local,
+                        checksum
+                    );
+                    self.pending_resync = Some(local);
+                }
+            }
             ServerCommandData::SetQuestCatalog { entries } => {
                 self.quest_catalog = entries.clone();
             }
@@ -627,7 +1232,16 @@ impl PlayerState {
                     }
                     QuestCompletionPayload::Delta { idx, count } => {
                         if let Some(slot) = self.quest_completion_counts.get_mut(*idx as usize) {
+                            let previous = *slot;
                             *slot = *count;
+                            if let Some(entry) = self.quest_catalog.get(*idx as usize)
+                                && previous < i16::from(entry.stages)
+                                && *count >= i16::from(entry.stages)
+                            {
+                                self.client_events.push(ClientEvent::AchievementUnlocked {
+                                    name: format!("Completed {}'s quest", entry.npc_name),
+                                });
+                            }
                         }
                     }
                 }
@@ -738,6 +1352,18 @@ impl PlayerState {
             ServerCommandData::Log { font, chunk } => {
                 self.handle_log_chunk(*font, chunk);
             }
+            ServerCommandData::LogStyled { style, chunk } => {
+                self.handle_styled_log_chunk(*style, chunk);
+            }
+            ServerCommandData::Dialogue { speaker, line } => {
+                let text = self.dialogue.render(line, &self.dialogue_locale);
+                let style = mag_core::chat::ChatStyle::new(mag_core::chat::ChatChannel::Npc);
+                self.tlog_styled(
+                    style.channel.legacy_font(),
+                    style,
+                    format!("{}: \"{}\"", speaker, text),
+                );
+            }
             ServerCommandData::Mod1 { text }
             | ServerCommandData::Mod2 { text }
             | ServerCommandData::Mod3 { text }
@@ -776,8 +1402,10 @@ impl PlayerState {
                 points,
                 hp,
                 worn10,
+                name_color,
             } => {
                 self.incoming_look.set_worn(9, *worn9);
+                self.incoming_look.set_name_color(*name_color);
                 self.incoming_look.set_sprite(*sprite);
                 self.incoming_look.set_points(*points);
                 self.incoming_look.set_hp(*hp);
@@ -791,6 +1419,7 @@ impl PlayerState {
                 id,
                 mana,
                 a_mana,
+                title,
             } => {
                 self.incoming_look.set_end(*end);
                 self.incoming_look.set_a_hp(*a_hp);
@@ -799,6 +1428,7 @@ impl PlayerState {
                 self.incoming_look.set_id(*id);
                 self.incoming_look.set_mana(*mana);
                 self.incoming_look.set_a_mana(*a_mana);
+                self.incoming_look.set_title(*title);
             }
             ServerCommandData::Look4 {
                 worn1,
@@ -824,7 +1454,9 @@ impl PlayerState {
                 let id = self.incoming_look.id();
                 // Always cache the name — autolook responses are for nameplate display.
                 if !name.is_empty() {
-                    self.set_known_name(nr, id, name);
+                    let name_color = self.incoming_look.name_color();
+                    let title = self.incoming_look.title();
+                    self.set_known_name(nr, id, name, name_color, title);
                 }
 
                 // Only commit to look_target (and show the look panel) when this
@@ -855,9 +1487,11 @@ impl PlayerState {
                         self.shop_target = self.incoming_look;
                         // Detect graves: merchant items always have a non-zero barter
                         // price, while corpse (grave) items are always free (price == 0).
-                        self.shop_is_grave = !(0..62usize).any(|i| {
-                            self.shop_target.item(i) != 0 && self.shop_target.price(i) != 0
-                        });
+                        // A broker's page may be empty but is never a grave.
+                        self.shop_is_grave = self.consignment_page().is_none()
+                            && !(0..62usize).any(|i| {
+                                self.shop_target.item(i) != 0 && self.shop_target.price(i) != 0
+                            });
                     }
                 }
             }
@@ -966,6 +1600,7 @@ mod tests {
                     points: 0,
                     hp: 0,
                     worn10: 0,
+                    name_color: 0,
                 },
                 _payload: Vec::new(),
             },
@@ -979,6 +1614,7 @@ mod tests {
                     id,
                     mana: 0,
                     a_mana: 0,
+                    title: 0,
                 },
                 _payload: Vec::new(),
             },
@@ -1062,12 +1698,175 @@ mod tests {
     #[test]
     fn lookup_name_requires_matching_id() {
         let mut ps = PlayerState::default();
-        ps.set_known_name(5, 42, "Bob");
+        ps.set_known_name(5, 42, "Bob", mag_core::profile::NameColor::Default, 0);
         assert_eq!(ps.lookup_name(5, 42), Some("Bob"));
         assert_eq!(ps.lookup_name(5, 43), None);
         assert_eq!(ps.lookup_name(6, 42), None);
     }
 
+    #[test]
+    fn lookup_name_color_follows_cached_entry() {
+        let mut ps = PlayerState::default();
+        ps.set_known_name(5, 42, "Bob", mag_core::profile::NameColor::Emerald, 0);
+        assert_eq!(
+            ps.lookup_name_color(5, 42),
+            mag_core::profile::NameColor::Emerald
+        );
+        assert_eq!(
+            ps.lookup_name_color(5, 43),
+            mag_core::profile::NameColor::Default
+        );
+    }
+
+    #[test]
+    fn char_sheet_discards_stale_items_and_spells() {
+        let mut ps = PlayerState::default();
+        ps.update_from_server_command(&ServerCommand {
+            header: ServerCommandType::SetCharSpell,
+            structured_data: ServerCommandData::SetCharSpell {
+                index: 2,
+                spell: 88,
+                active: 8,
+                skill_nr: 21,
+            },
+            _payload: Vec::new(),
+        });
+        assert_eq!(ps.character_info().spell[2], 88);
+
+        let mut sheet = mag_core::char_sheet::CharSheet::default();
+        sheet.item[0] = 1234;
+        sheet.gold = 500;
+        ps.update_from_server_command(&ServerCommand {
+            header: ServerCommandType::SetCharSheet,
+            structured_data: ServerCommandData::SetCharSheet(Box::new(sheet)),
+            _payload: Vec::new(),
+        });
+        assert_eq!(ps.character_info().spell[2], 0);
+        assert_eq!(ps.character_info().spell_type[2], 0);
+        assert_eq!(ps.character_info().item[0], 1234);
+        assert_eq!(ps.character_info().gold, 500);
+    }
+
+    #[test]
+    fn reset_for_resume_keeps_log_and_drops_session_state() {
+        let mut ps = PlayerState::default();
+        ps.tlog(1, "Before the drop.");
+        ps.set_selected_char_with_id(5, 77);
+        ps.map_mut().tile_at_index_mut(0).unwrap().ba_sprite = 1234;
+        let logged = ps.log_len();
+
+        ps.reset_for_resume();
+
+        assert_eq!(ps.log_len(), logged);
+        assert_eq!(ps.selected_char(), 0);
+        assert_eq!(ps.map().tile_at_index(0).unwrap().ba_sprite, 0);
+    }
+
+    #[test]
+    fn checksum_mismatch_requests_resync_once() {
+        let mut ps = PlayerState::default();
+        let checksum_cmd = |checksum| ServerCommand {
+            header: ServerCommandType::CharChecksum,
+            structured_data: ServerCommandData::CharChecksum { checksum },
+            _payload: Vec::new(),
+        };
+        let local = mag_core::char_sheet::client_checksum(ps.character_info());
+
+        ps.update_from_server_command(&checksum_cmd(local));
+        assert_eq!(ps.take_resync_request(), None);
+
+        ps.update_from_server_command(&checksum_cmd(local ^ 1));
+        assert_eq!(ps.take_resync_request(), Some(local));
+        assert_eq!(ps.take_resync_request(), None);
+    }
+
+    #[test]
+    fn set_view_is_clamped_to_the_protocol_range() {
+        let mut ps = PlayerState::default();
+        assert_eq!(ps.max_view_radius(), mag_core::view::LEGACY_VIEW_RADIUS);
+        let view = |radius: u8| ServerCommand {
+            header: ServerCommandType::SetView,
+            structured_data: ServerCommandData::SetView { radius },
+            _payload: Vec::new(),
+        };
+        ps.update_from_server_command(&view(35));
+        assert_eq!(ps.max_view_radius(), 35);
+        ps.update_from_server_command(&view(200));
+        assert_eq!(ps.max_view_radius(), mag_core::view::MAX_VIEW_RADIUS);
+    }
+
+    #[test]
+    fn skill_timers_count_down_per_tick() {
+        let mut ps = PlayerState::default();
+        let timer = |kind: SkillTimerKind, skill: u8, remaining: u16| ServerCommand {
+            header: ServerCommandType::SkillTimer,
+            structured_data: ServerCommandData::SkillTimer {
+                kind: kind as u8,
+                skill,
+                remaining,
+                total: 10,
+            },
+            _payload: Vec::new(),
+        };
+        ps.update_from_server_command(&timer(SkillTimerKind::Cooldown, 40, 2));
+        ps.update_from_server_command(&timer(SkillTimerKind::Cooldown, 41, 5));
+        ps.update_from_server_command(&timer(SkillTimerKind::Cast, 1, 1));
+        assert_eq!(ps.skill_cooldown(40), Some(SkillTimer::new(2, 10)));
+        assert_eq!(ps.skill_cast().map(|(skill, _)| skill), Some(1));
+
+        ps.tick_skill_timers();
+        assert!(ps.skill_cast().is_none());
+        assert_eq!(ps.skill_cooldown(40).map(|t| t.remaining), Some(1));
+        ps.tick_skill_timers();
+        assert!(ps.skill_cooldown(40).is_none());
+
+        // Cleared early by the server.
+        ps.update_from_server_command(&timer(SkillTimerKind::Cooldown, 41, 0));
+        assert!(ps.skill_cooldown(41).is_none());
+    }
+
+    #[test]
+    fn set_char_titles_updates_snapshot_and_lookup_title() {
+        let mut ps = PlayerState::default();
+        ps.update_from_server_command(&ServerCommand {
+            header: ServerCommandType::SetCharTitles,
+            structured_data: ServerCommandData::SetCharTitles {
+                earned: 1 << 16,
+                selected: 16,
+            },
+            _payload: Vec::new(),
+        });
+        assert_eq!(ps.titles_earned(), 1 << 16);
+        assert_eq!(ps.title_selected(), 16);
+
+        ps.set_known_name(5, 42, "Bob", mag_core::profile::NameColor::Default, 17);
+        assert_eq!(ps.lookup_title(5, 42), 17);
+        assert_eq!(ps.lookup_title(5, 43), 0);
+    }
+
+    #[test]
+    fn new_titles_after_login_snapshot_raise_achievement_events() {
+        let mut ps = PlayerState::default();
+        let titles = |earned: u32| ServerCommand {
+            header: ServerCommandType::SetCharTitles,
+            structured_data: ServerCommandData::SetCharTitles {
+                earned,
+                selected: 0,
+            },
+            _payload: Vec::new(),
+        };
+        ps.update_from_server_command(&titles(1 << 1));
+        assert!(ps.drain_client_events().is_empty());
+
+        ps.update_from_server_command(&titles((1 << 1) | (1 << 2)));
+        assert_eq!(
+            ps.drain_client_events(),
+            vec![ClientEvent::AchievementUnlocked {
+                name: "Title earned: Officer".to_owned()
+            }]
+        );
+    }
+
     #[test]
     fn tlog_adds_message_lines() {
         let mut ps = PlayerState::default();
@@ -1076,6 +1875,72 @@ mod tests {
         assert_eq!(msg.message, "hello world");
     }
 
+    #[test]
+    fn styled_log_chunks_assemble_with_style() {
+        use mag_core::chat::{ChatChannel, ChatStyle};
+        let style = ChatStyle::new(ChatChannel::Tell).with_gm(true);
+        let mut ps = PlayerState::default();
+        for chunk in ["Ishtar tells y", "ou: \"hi\"\n"] {
+            ps.update_from_server_command(&ServerCommand {
+                header: ServerCommandType::LogStyled,
+                structured_data: ServerCommandData::LogStyled {
+                    style: style.to_byte(),
+                    chunk: chunk.to_owned(),
+                },
+                _payload: Vec::new(),
+            });
+        }
+        let msg = ps.log_message(0).expect("expected styled message");
+        assert_eq!(msg.message, "Ishtar tells you: \"hi\"");
+        assert_eq!(msg.style, style);
+        assert_eq!(
+            msg.color,
+            PlayerState::log_color_from_font(ChatChannel::Tell.legacy_font())
+        );
+    }
+
+    #[test]
+    fn dialogue_lines_render_in_the_chosen_language() {
+        use mag_core::dialogue::{DialogueCatalog, DialogueLine, parse_catalog};
+        let mut catalog = DialogueCatalog::default();
+        catalog.insert_locale(
+            "de",
+            parse_catalog("npc.give.no_money = Von dir nehme ich kein Geld!").unwrap(),
+        );
+        let mut ps = PlayerState::default();
+        ps.set_dialogue(catalog, "de".to_owned());
+        ps.update_from_server_command(&ServerCommand {
+            header: ServerCommandType::Dialogue,
+            structured_data: ServerCommandData::Dialogue {
+                speaker: "Gunther".to_owned(),
+                line: DialogueLine::new("npc.give.no_money"),
+            },
+            _payload: Vec::new(),
+        });
+        let msg = ps.log_message(0).expect("expected dialogue line");
+        assert_eq!(msg.message, "Gunther: \"Von dir nehme ich kein Geld!\"");
+    }
+
+    #[test]
+    fn reward_notices_raise_a_toast() {
+        use mag_core::chat::{ChatChannel, ChatStyle};
+        let mut ps = PlayerState::default();
+        ps.update_from_server_command(&ServerCommand {
+            header: ServerCommandType::LogStyled,
+            structured_data: ServerCommandData::LogStyled {
+                style: ChatStyle::new(ChatChannel::Reward).to_byte(),
+                chunk: "Sent to depot.\n".to_owned(),
+            },
+            _payload: Vec::new(),
+        });
+        assert_eq!(
+            ps.drain_client_events(),
+            vec![ClientEvent::RewardDelivered {
+                text: "Sent to depot.".to_owned()
+            }]
+        );
+    }
+
     #[test]
     fn take_exit_requested_reason() {
         let mut ps = PlayerState::default();
@@ -1087,6 +1952,213 @@ mod tests {
         assert_eq!(ps.take_exit_requested_reason(), None);
     }
 
+    #[test]
+    fn item_names_fill_backpack_slots() {
+        let mut ps = PlayerState::default();
+        let named = |slot, name: &str| ServerCommand {
+            header: ServerCommandType::ItemName,
+            structured_data: ServerCommandData::ItemName {
+                slot,
+                name: name.to_owned(),
+            },
+            _payload: Vec::new(),
+        };
+        apply_commands(&mut ps, &[named(3, "Steel Sword"), named(40, "Ignored")]);
+        assert_eq!(ps.item_names()[3], "Steel Sword");
+        apply_commands(&mut ps, &[named(3, "")]);
+        assert!(ps.item_names().iter().all(String::is_empty));
+    }
+
+    #[test]
+    fn guild_packets_build_the_roster() {
+        let mut ps = PlayerState::default();
+        let info = |name: &str| ServerCommand {
+            header: ServerCommandType::GuildInfo,
+            structured_data: ServerCommandData::GuildInfo {
+                rank: GuildRank::Officer.as_u8(),
+                name: name.to_owned(),
+                motd: "Raid at dusk".to_owned(),
+            },
+            _payload: Vec::new(),
+        };
+        let member = |op: GuildRosterOp, online: bool| ServerCommand {
+            header: ServerCommandType::GuildMember,
+            structured_data: ServerCommandData::GuildMember {
+                op: op as u8,
+                rank: GuildRank::Leader.as_u8(),
+                online,
+                name: "Ishtar".to_owned(),
+            },
+            _payload: Vec::new(),
+        };
+
+        apply_commands(
+            &mut ps,
+            &[
+                info("Iron Wolves"),
+                member(GuildRosterOp::Set, true),
+                member(GuildRosterOp::Set, false),
+            ],
+        );
+        let guild = ps.guild().unwrap();
+        assert_eq!(guild.rank, GuildRank::Officer);
+        assert_eq!(guild.members.len(), 1);
+        assert!(!guild.members[0].online);
+
+        apply_commands(&mut ps, &[member(GuildRosterOp::Remove, false)]);
+        assert!(ps.guild().unwrap().members.is_empty());
+        apply_commands(&mut ps, &[info("")]);
+        assert!(ps.guild().is_none());
+    }
+
+    #[test]
+    fn mail_packets_keep_the_mailbox_sorted() {
+        let mut ps = PlayerState::default();
+        let letter = |op: MailOp, id: u32| ServerCommand {
+            header: ServerCommandType::MailEntry,
+            structured_data: ServerCommandData::MailEntry {
+                op: op as u8,
+                entry: MailEntry {
+                    id,
+                    from: "Ishtar".to_owned(),
+                    body: "Hello".to_owned(),
+                    ..MailEntry::default()
+                },
+            },
+            _payload: Vec::new(),
+        };
+
+        apply_commands(
+            &mut ps,
+            &[
+                letter(MailOp::Set, 7),
+                letter(MailOp::Set, 3),
+                letter(MailOp::Set, 7),
+            ],
+        );
+        let ids: Vec<u32> = ps.mailbox().iter().map(|l| l.id).collect();
+        assert_eq!(ids, vec![3, 7]);
+
+        apply_commands(&mut ps, &[letter(MailOp::Remove, 3)]);
+        assert_eq!(ps.mailbox().len(), 1);
+        apply_commands(&mut ps, &[letter(MailOp::Clear, 0)]);
+        assert!(ps.mailbox().is_empty());
+    }
+
+    #[test]
+    fn friend_packets_track_presence() {
+        let mut ps = PlayerState::default();
+        let friend = |op: FriendOp, name: &str, online: bool| ServerCommand {
+            header: ServerCommandType::FriendEntry,
+            structured_data: ServerCommandData::FriendEntry {
+                op: op as u8,
+                online,
+                name: name.to_owned(),
+            },
+            _payload: Vec::new(),
+        };
+
+        apply_commands(
+            &mut ps,
+            &[
+                friend(FriendOp::Set, "Ishtar", false),
+                friend(FriendOp::Set, "Arn", true),
+                friend(FriendOp::Set, "Ishtar", true),
+            ],
+        );
+        assert_eq!(ps.friends().len(), 2);
+        assert!(ps.friends()[0].online);
+
+        apply_commands(&mut ps, &[friend(FriendOp::Remove, "Arn", false)]);
+        assert_eq!(ps.friends().len(), 1);
+        apply_commands(&mut ps, &[friend(FriendOp::Clear, "", false)]);
+        assert!(ps.friends().is_empty());
+    }
+
+    #[test]
+    fn observer_packet_sets_observer_mode() {
+        let mut ps = PlayerState::default();
+        let observer = |active: bool| ServerCommand {
+            header: ServerCommandType::Observer,
+            structured_data: ServerCommandData::Observer { active },
+            _payload: Vec::new(),
+        };
+        assert!(!ps.is_observer());
+        ps.update_from_server_command(&observer(true));
+        assert!(ps.is_observer());
+        ps.update_from_server_command(&observer(false));
+        assert!(!ps.is_observer());
+    }
+
+    #[test]
+    fn combat_events_fill_the_combat_log() {
+        let mut ps = PlayerState::default();
+        let event = mag_core::combat_log::CombatEvent {
+            dealt: 3_000,
+            target: "Grolm".to_owned(),
+            ..Default::default()
+        };
+        ps.update_from_server_command(&ServerCommand {
+            header: ServerCommandType::CombatEvent,
+            structured_data: ServerCommandData::CombatEvent(event.clone()),
+            _payload: Vec::new(),
+        });
+        assert_eq!(ps.combat_log().len(), 1);
+        assert_eq!(ps.combat_log().hits().next().unwrap().event, event);
+        ps.combat_log_mut().clear();
+        assert!(ps.combat_log().is_empty());
+    }
+
+    #[test]
+    fn item_links_in_chat_lines_carry_their_snapshot() {
+        let mut ps = PlayerState::default();
+        let link = mag_core::item_link::ItemLink {
+            id: 4,
+            name: "Steel Sword".to_owned(),
+            ..Default::default()
+        };
+        ps.update_from_server_command(&ServerCommand {
+            header: ServerCommandType::ItemLink,
+            structured_data: ServerCommandData::ItemLink(link.clone()),
+            _payload: Vec::new(),
+        });
+        ps.tlog(
+            1,
+            format!("Arn says: wts {}", mag_core::item_link::link_token(4)),
+        );
+
+        let msg = ps.log_message(0).unwrap();
+        assert_eq!(msg.message, "Arn says: wts [Steel Sword]");
+        assert_eq!(msg.links.len(), 1);
+        assert_eq!(&msg.message[msg.links[0].range.clone()], "[Steel Sword]");
+        assert_eq!(msg.links[0].item.as_deref(), Some(&link));
+    }
+
+    #[test]
+    fn empty_broker_page_is_no_grave() {
+        let mut ps = PlayerState::default();
+        let page = ServerCommand {
+            header: ServerCommandType::ConsignmentPage,
+            structured_data: ServerCommandData::ConsignmentPage(ConsignmentPage {
+                broker: 17,
+                pages: 1,
+                query: "ring".to_owned(),
+                ..ConsignmentPage::default()
+            }),
+            _payload: Vec::new(),
+        };
+        apply_commands(&mut ps, &[page]);
+        apply_commands(&mut ps, &shop_sequence(0, 17, 33, Vec::new()));
+
+        assert!(ps.should_show_shop());
+        assert!(!ps.shop_is_grave());
+        assert_eq!(ps.consignment_page().unwrap().query, "ring");
+
+        apply_commands(&mut ps, &shop_sequence(0, 18, 34, Vec::new()));
+        assert!(ps.consignment_page().is_none());
+        assert!(ps.shop_is_grave());
+    }
+
     #[test]
     fn selected_char_roundtrip() {
         let mut ps = PlayerState::default();
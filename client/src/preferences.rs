@@ -4,8 +4,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use mag_core::map_markers::MapMarkerKind;
 use serde::{Deserialize, Serialize};
 
+use crate::sfx_cache::AudioMix;
 use crate::types::controller::ControllerBindings;
 use crate::types::mouse::MouseModifierBindings;
 use crate::ui::widget::KeyBindings;
@@ -13,6 +15,26 @@ use crate::ui::widget::KeyBindings;
 /// Number of skill-bar binding slots.
 pub const NUMBER_OF_KEYBINDS: usize = 10;
 
+/// Default [`Settings::wall_fade_radius`].
+pub const DEFAULT_WALL_FADE_RADIUS: u8 = 3;
+
+/// Largest selectable [`Settings::wall_fade_radius`].
+pub const MAX_WALL_FADE_RADIUS: u8 = 6;
+
+/// Selectable [`Settings::fps_cap`] values. SDL_gfx paces at most 200 Hz.
+pub const FPS_CAP_CHOICES: [u32; 5] = [30, 60, 120, 144, 200];
+
+/// Default [`Settings::fps_cap`].
+pub const DEFAULT_FPS_CAP: u32 = 60;
+
+/// Frame rate while the window is unfocused or minimized and
+/// [`Settings::background_throttle`] is on. Network processing handles up to
+/// 32 server ticks per frame, so this still keeps up with the server.
+pub const BACKGROUND_FPS: u32 = 10;
+
+/// Frame rate ceiling in [`Settings::low_power_mode`].
+pub const LOW_POWER_FPS: u32 = 30;
+
 // ---------------------------------------------------------------------------
 // Per-character settings
 // ---------------------------------------------------------------------------
@@ -54,6 +76,27 @@ pub struct CharacterSettings {
     /// each server tick. Defaults to `true`. Toggle with `/autoloot`.
     #[serde(default = "default_auto_loot_graves")]
     pub auto_loot_graves: bool,
+    /// Whether the on-screen quest tracker is collapsed to its header.
+    #[serde(default)]
+    pub quest_tracker_collapsed: bool,
+    /// Saved travel destinations, in the order they were added. Managed
+    /// with `/mark`, `/unmark` and the travel menu.
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// Largest number of [`CharacterSettings::bookmarks`] kept per character.
+pub const MAX_BOOKMARKS: usize = 20;
+
+/// A named world position the player can auto-walk to with `/go`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Name typed after `/go`; matched case-insensitively.
+    pub name: String,
+    /// World tile X.
+    pub x: u16,
+    /// World tile Y.
+    pub y: u16,
 }
 
 /// Returns the default value of `true` for
@@ -74,6 +117,8 @@ impl Default for CharacterSettings {
             controller_bindings: ControllerBindings::default(),
             mouse_modifier_bindings: MouseModifierBindings::default(),
             auto_loot_graves: true,
+            quest_tracker_collapsed: false,
+            bookmarks: Vec::new(),
         }
     }
 }
@@ -106,9 +151,74 @@ impl DisplayMode {
     ];
 }
 
+/// Which points of interest the minimap draws (see
+/// [`mag_core::map_markers`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MinimapMarkerSettings {
+    pub temple: bool,
+    pub tavern: bool,
+    pub bank: bool,
+    pub death: bool,
+}
+
+impl Default for MinimapMarkerSettings {
+    fn default() -> Self {
+        Self {
+            temple: true,
+            tavern: true,
+            bank: true,
+            death: true,
+        }
+    }
+}
+
+impl MinimapMarkerSettings {
+    /// Returns whether markers of `kind` are shown.
+    pub fn enabled(&self, kind: MapMarkerKind) -> bool {
+        match kind {
+            MapMarkerKind::Temple => self.temple,
+            MapMarkerKind::Tavern => self.tavern,
+            MapMarkerKind::Bank => self.bank,
+            MapMarkerKind::Death => self.death,
+        }
+    }
+
+    /// Shows or hides markers of `kind`.
+    pub fn set_enabled(&mut self, kind: MapMarkerKind, enabled: bool) {
+        match kind {
+            MapMarkerKind::Temple => self.temple = enabled,
+            MapMarkerKind::Tavern => self.tavern = enabled,
+            MapMarkerKind::Bank => self.bank = enabled,
+            MapMarkerKind::Death => self.death = enabled,
+        }
+    }
+}
+
 const LOG_FILE_NAME: &str = "mag_client.log";
 const PROFILE_FILE_NAME: &str = "mag_profile.json";
 const KNOWN_HOSTS_FILE: &str = "mag_known_hosts.json";
+const CHAT_FILTER_FILE: &str = "mag_chat_filter.txt";
+const ACCESSIBILITY_EXPORT_FILE: &str = "mag_accessibility.txt";
+const ADDONS_DIR: &str = "addons";
+const COMBAT_LOG_EXPORT_FILE: &str = "mag_combat_log.csv";
+
+/// Schema version written to [`ProfileStorage::version`].
+///
+/// Bump it together with a new entry in [`PROFILE_MIGRATIONS`] whenever a
+/// field is renamed, moved or changes meaning. Fields that are merely added
+/// only need a serde default.
+const PROFILE_VERSION: u32 = 1;
+
+/// Upgrades a raw profile document by one schema version.
+type ProfileMigration = fn(&mut serde_json::Map<String, serde_json::Value>);
+
+/// `PROFILE_MIGRATIONS[n]` upgrades a version-`n` profile to `n + 1`.
+const PROFILE_MIGRATIONS: [ProfileMigration; PROFILE_VERSION as usize] = [
+    // 0 -> 1: profiles saved before the `version` field existed have the
+    // same layout; only the version is stamped.
+    |_| {},
+];
 
 /// Identifies a specific character for profile look-up.
 #[derive(Clone, Debug)]
@@ -142,6 +252,16 @@ pub struct Settings {
     /// Whether VSync is enabled.
     #[serde(default = "default_true")]
     pub vsync_enabled: bool,
+    /// Foreground frame rate cap; one of [`FPS_CAP_CHOICES`].
+    #[serde(default = "default_fps_cap")]
+    pub fps_cap: u32,
+    /// Drop to [`BACKGROUND_FPS`] while the window is unfocused or minimized.
+    #[serde(default = "default_true")]
+    pub background_throttle: bool,
+    /// Battery saver: caps the frame rate at [`LOW_POWER_FPS`] and skips
+    /// shadows and particle effects regardless of their own toggles.
+    #[serde(default)]
+    pub low_power_mode: bool,
     /// Whether shadow rendering is enabled.
     #[serde(default = "default_true")]
     pub shadows_enabled: bool,
@@ -152,11 +272,25 @@ pub struct Settings {
     #[serde(default = "default_true")]
     pub weather_enabled: bool,
     /// Master volume (0.0–1.0).
-    #[serde(default)]
+    #[serde(default = "default_volume")]
     pub master_volume: f32,
-    /// Wall-hiding toggle.
+    /// Music volume (0.0–1.0), scaled by [`Self::master_volume`].
+    #[serde(default = "default_volume")]
+    pub music_volume: f32,
+    /// Sound effect volume (0.0–1.0), scaled by [`Self::master_volume`].
+    #[serde(default = "default_volume")]
+    pub effects_volume: f32,
+    /// Interface click volume (0.0–1.0), scaled by [`Self::master_volume`].
+    #[serde(default = "default_volume")]
+    pub ui_volume: f32,
+    /// Wall-hiding toggle (legacy hard hide). Takes precedence over
+    /// [`Self::wall_fade_radius`].
     #[serde(default)]
     pub hide: bool,
+    /// Radius in tiles around the player and the hovered tile inside which
+    /// walls and roofs in front of them fade out; `0` turns the fade off.
+    #[serde(default = "default_wall_fade_radius")]
+    pub wall_fade_radius: u8,
     /// Overhead player name display toggle.
     #[serde(default = "default_true")]
     pub show_names: bool,
@@ -169,6 +303,34 @@ pub struct Settings {
     /// Whether helper text is replaced with the cursor's logical screen position.
     #[serde(default)]
     pub show_positions: bool,
+    /// Streamer mode: mask chat profanity, hide gold and show
+    /// [`Self::streamer_alias`] instead of the character name.
+    #[serde(default)]
+    pub streamer_mode: bool,
+    /// Name shown for the player's own character in streamer mode.
+    #[serde(default = "default_streamer_alias")]
+    pub streamer_alias: String,
+    /// Screen-reader mode: mirror chat, health, mana and the selected target
+    /// to speech and a text file. See [`crate::accessibility`].
+    #[serde(default)]
+    pub screen_reader: bool,
+    /// High-contrast HUD: opaque panel backgrounds and vivid vitality bars.
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Names of installed addons the player switched off.
+    #[serde(default)]
+    pub disabled_addons: Vec<String>,
+    /// Language NPC dialogue is shown in, e.g. `de`; empty follows the
+    /// system locale. See [`Self::dialogue_locale`].
+    #[serde(default)]
+    pub language: String,
+    /// Preferred camera view radius in tiles; the server may allow less.
+    /// See [`mag_core::view`].
+    #[serde(default = "default_view_radius")]
+    pub view_radius: u8,
+    /// Points of interest drawn on the minimap.
+    #[serde(default)]
+    pub minimap_markers: MinimapMarkerSettings,
     /// Per-character settings (skill keybinds and UI panel positions).
     #[serde(default)]
     pub character: CharacterSettings,
@@ -181,20 +343,95 @@ impl Default for Settings {
             display_mode: DisplayMode::default(),
             pixel_perfect_scaling: false,
             vsync_enabled: true,
+            fps_cap: DEFAULT_FPS_CAP,
+            background_throttle: true,
+            low_power_mode: false,
             shadows_enabled: true,
             spell_effects_enabled: true,
             weather_enabled: true,
-            master_volume: 0.0,
+            master_volume: default_volume(),
+            music_volume: default_volume(),
+            effects_volume: default_volume(),
+            ui_volume: default_volume(),
             hide: false,
+            wall_fade_radius: DEFAULT_WALL_FADE_RADIUS,
             show_names: true,
             show_proz: true,
             show_helper_text: true,
             show_positions: false,
+            streamer_mode: false,
+            streamer_alias: default_streamer_alias(),
+            screen_reader: false,
+            high_contrast: false,
+            disabled_addons: Vec::new(),
+            language: String::new(),
+            view_radius: default_view_radius(),
+            minimap_markers: MinimapMarkerSettings::default(),
             character: CharacterSettings::default(),
         }
     }
 }
 
+impl Settings {
+    /// Whether shadows should be drawn this frame.
+    pub fn shadows_active(&self) -> bool {
+        self.shadows_enabled && !self.low_power_mode
+    }
+
+    /// Whether weather / ambient particles should be drawn this frame.
+    pub fn weather_active(&self) -> bool {
+        self.weather_enabled && !self.low_power_mode
+    }
+
+    /// Frame rate the main loop should pace to.
+    ///
+    /// # Arguments
+    /// * `focused` - Whether the window has input focus and is not minimized.
+    ///
+    /// # Returns
+    /// * Frames per second, within SDL_gfx's 1..=200 range.
+    pub fn frame_rate(&self, focused: bool) -> u32 {
+        if !focused && self.background_throttle {
+            return BACKGROUND_FPS;
+        }
+        let cap = self
+            .fps_cap
+            .clamp(1, FPS_CAP_CHOICES[FPS_CAP_CHOICES.len() - 1]);
+        if self.low_power_mode {
+            cap.min(LOW_POWER_FPS)
+        } else {
+            cap
+        }
+    }
+
+    /// Current volume levels for the sound mixer.
+    pub fn audio_mix(&self) -> AudioMix {
+        AudioMix {
+            master: self.master_volume,
+            music: self.music_volume,
+            effects: self.effects_volume,
+            ui: self.ui_volume,
+        }
+    }
+
+    /// Language code NPC dialogue is rendered in.
+    ///
+    /// # Returns
+    /// * [`Self::language`] if set, otherwise the language of the first of
+    ///   `LC_ALL`, `LC_MESSAGES` and `LANG` that names one, otherwise
+    ///   English.
+    pub fn dialogue_locale(&self) -> String {
+        std::iter::once(self.language.clone())
+            .chain(
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .into_iter()
+                    .filter_map(|var| std::env::var(var).ok()),
+            )
+            .find_map(|raw| mag_core::dialogue::normalize_locale(&raw))
+            .unwrap_or_else(|| mag_core::dialogue::DEFAULT_LOCALE.to_owned())
+    }
+}
+
 /// Internal JSON container for a character's saved settings.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct CharacterEntry {
@@ -220,7 +457,7 @@ struct ProfileStorage {
 impl Default for ProfileStorage {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: PROFILE_VERSION,
             last_username: None,
             global: Settings::default(),
             characters: BTreeMap::new(),
@@ -233,6 +470,31 @@ fn default_true() -> bool {
     true
 }
 
+/// Serde helper: default for the per-category volumes.
+fn default_volume() -> f32 {
+    1.0
+}
+
+/// Serde helper: default for [`Settings::streamer_alias`].
+fn default_streamer_alias() -> String {
+    crate::streamer_mode::DEFAULT_ALIAS.to_owned()
+}
+
+/// Serde helper: default for [`Settings::wall_fade_radius`].
+fn default_wall_fade_radius() -> u8 {
+    DEFAULT_WALL_FADE_RADIUS
+}
+
+/// Serde helper: default for [`Settings::fps_cap`].
+fn default_fps_cap() -> u32 {
+    DEFAULT_FPS_CAP
+}
+
+/// Serde helper: default for [`Settings::view_radius`] (unzoomed).
+fn default_view_radius() -> u8 {
+    mag_core::view::LEGACY_VIEW_RADIUS
+}
+
 /// Returns a `Settings` snapshot containing only global fields.
 ///
 /// Character-scoped fields are always reset to defaults so account-level
@@ -244,15 +506,30 @@ fn global_settings_only(settings: &Settings) -> Settings {
         display_mode: settings.display_mode,
         pixel_perfect_scaling: settings.pixel_perfect_scaling,
         vsync_enabled: settings.vsync_enabled,
+        fps_cap: settings.fps_cap,
+        background_throttle: settings.background_throttle,
+        low_power_mode: settings.low_power_mode,
         shadows_enabled: settings.shadows_enabled,
         spell_effects_enabled: settings.spell_effects_enabled,
         weather_enabled: settings.weather_enabled,
         master_volume: settings.master_volume.clamp(0.0, 1.0),
+        music_volume: settings.music_volume.clamp(0.0, 1.0),
+        effects_volume: settings.effects_volume.clamp(0.0, 1.0),
+        ui_volume: settings.ui_volume.clamp(0.0, 1.0),
         hide: settings.hide,
+        wall_fade_radius: settings.wall_fade_radius.min(MAX_WALL_FADE_RADIUS),
         show_names: settings.show_names,
         show_proz: settings.show_proz,
         show_helper_text: settings.show_helper_text,
         show_positions: settings.show_positions,
+        streamer_mode: settings.streamer_mode,
+        streamer_alias: crate::streamer_mode::sanitize_alias(&settings.streamer_alias),
+        screen_reader: settings.screen_reader,
+        high_contrast: settings.high_contrast,
+        disabled_addons: settings.disabled_addons.clone(),
+        language: settings.language.clone(),
+        view_radius: settings.view_radius,
+        minimap_markers: settings.minimap_markers,
         character: CharacterSettings::default(),
     }
 }
@@ -357,20 +634,148 @@ pub fn known_hosts_file_path() -> PathBuf {
     data_directory().join(KNOWN_HOSTS_FILE)
 }
 
-fn read_storage(path: &Path) -> ProfileStorage {
+/// Returns the path to the streamer-mode chat filter list
+/// (`mag_chat_filter.txt`).
+///
+/// # Returns
+///
+/// * Value returned by `chat_filter_file_path`.
+pub fn chat_filter_file_path() -> PathBuf {
+    data_directory().join(CHAT_FILTER_FILE)
+}
+
+/// Returns the path to the screen-reader text export
+/// (`mag_accessibility.txt`).
+///
+/// # Returns
+///
+/// * Value returned by `accessibility_export_file_path`.
+pub fn accessibility_export_file_path() -> PathBuf {
+    data_directory().join(ACCESSIBILITY_EXPORT_FILE)
+}
+
+/// Returns the path to the addon folder (`addons/`).
+///
+/// # Returns
+///
+/// * Value returned by `addons_directory_path`.
+pub fn addons_directory_path() -> PathBuf {
+    data_directory().join(ADDONS_DIR)
+}
+
+/// Returns the path the combat log is exported to (`mag_combat_log.csv`).
+///
+/// # Returns
+///
+/// * Value returned by `combat_log_export_file_path`.
+pub fn combat_log_export_file_path() -> PathBuf {
+    data_directory().join(COMBAT_LOG_EXPORT_FILE)
+}
+
+/// Why a profile file could not be used.
+#[derive(Debug, PartialEq, Eq)]
+enum ProfileError {
+    /// The file is not a profile document; saving replaces it.
+    Malformed(String),
+    /// The file was written by a client with a newer schema version and is
+    /// never overwritten.
+    Newer(u64),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Malformed(err) => f.write_str(err),
+            ProfileError::Newer(version) => write!(
+                f,
+                "profile schema version {version} is newer than supported version {PROFILE_VERSION}"
+            ),
+        }
+    }
+}
+
+/// Parses a profile document, upgrading older schema versions.
+///
+/// # Arguments
+/// * `raw` - Contents of the profile file.
+///
+/// # Returns
+/// * The current-version storage, or `Err` when the document is malformed
+///   or was written by a client with a newer schema.
+fn parse_storage(raw: &str) -> Result<ProfileStorage, ProfileError> {
+    let mut doc = match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(serde_json::Value::Object(doc)) => doc,
+        Ok(_) => {
+            return Err(ProfileError::Malformed(
+                "profile is not a JSON object".to_owned(),
+            ));
+        }
+        Err(err) => return Err(ProfileError::Malformed(err.to_string())),
+    };
+
+    let version = doc
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(0);
+    if version > u64::from(PROFILE_VERSION) {
+        return Err(ProfileError::Newer(version));
+    }
+    for (from, migrate) in PROFILE_MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migrate(&mut doc);
+        log::info!(
+            "Migrated profile from schema version {} to {}",
+            from,
+            from + 1
+        );
+    }
+    doc.insert("version".to_owned(), PROFILE_VERSION.into());
+
+    serde_json::from_value(serde_json::Value::Object(doc))
+        .map_err(|err| ProfileError::Malformed(err.to_string()))
+}
+
+/// Reads the profile file; a missing file reads as defaults.
+fn read_storage(path: &Path) -> Result<ProfileStorage, ProfileError> {
     let Ok(raw) = fs::read_to_string(path) else {
-        return ProfileStorage::default();
+        return Ok(ProfileStorage::default());
     };
 
-    match serde_json::from_str::<ProfileStorage>(&raw) {
-        Ok(storage) => storage,
-        Err(err) => {
-            log::warn!(
-                "Failed to parse persisted SDL client profile at {}: {}",
-                path.display(),
-                err
-            );
-            ProfileStorage::default()
+    parse_storage(&raw).inspect_err(|err| {
+        log::warn!(
+            "Failed to parse persisted SDL client profile at {}: {}",
+            path.display(),
+            err
+        );
+    })
+}
+
+/// Reads the profile file that a save is about to replace.
+///
+/// # Arguments
+/// * `path` - Profile file path.
+///
+/// # Returns
+/// * The storage to update. An unreadable file is first copied to a
+///   timestamped backup and reads as defaults.
+/// * `Err` when the file was written by a newer client; the save must not
+///   overwrite it.
+fn read_storage_for_update(path: &Path) -> Result<ProfileStorage, String> {
+    match read_storage(path) {
+        Ok(storage) => Ok(storage),
+        Err(ProfileError::Newer(version)) => Err(format!(
+            "Not saving over profile {}: it was written by a newer client (schema version {version})",
+            path.display()
+        )),
+        Err(ProfileError::Malformed(_)) => {
+            let stamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            let backup = path.with_extension(format!("json.{stamp}.bak"));
+            match fs::copy(path, &backup) {
+                Ok(_) => log::warn!("Kept a copy of the profile at {}", backup.display()),
+                Err(err) => log::warn!("Failed to back up the profile: {err}"),
+            }
+            Ok(ProfileStorage::default())
         }
     }
 }
@@ -403,7 +808,7 @@ fn write_storage(path: &Path, storage: &ProfileStorage) -> Result<(), String> {
 ///   when present, otherwise they fall back to defaults.
 pub fn load_settings(identity: &CharacterIdentity) -> Settings {
     let path = profile_file_path();
-    let storage = read_storage(&path);
+    let storage = read_storage(&path).unwrap_or_default();
     let mut settings = global_settings_only(&storage.global);
     let key = profile_key(identity);
     if let Some(entry) = storage.characters.get(&key) {
@@ -422,7 +827,7 @@ pub fn load_settings(identity: &CharacterIdentity) -> Settings {
 /// * A [`Settings`] whose global fields are populated from disk.
 pub fn load_global_settings() -> Settings {
     let path = profile_file_path();
-    let storage = read_storage(&path);
+    let storage = read_storage(&path).unwrap_or_default();
     global_settings_only(&storage.global)
 }
 
@@ -435,10 +840,11 @@ pub fn load_global_settings() -> Settings {
 /// * `settings` - The settings whose global fields to save.
 ///
 /// # Returns
-/// * `Ok(())` on success, `Err(String)` with a description on I/O failure.
+/// * `Ok(())` on success, `Err(String)` with a description on I/O failure
+///   or when the profile was written by a newer client.
 pub fn save_global_settings(settings: &Settings) -> Result<(), String> {
     let path = profile_file_path();
-    let mut storage = read_storage(&path);
+    let mut storage = read_storage_for_update(&path)?;
     storage.global = global_settings_only(settings);
     write_storage(&path, &storage)
 }
@@ -451,7 +857,7 @@ pub fn save_global_settings(settings: &Settings) -> Result<(), String> {
 /// * `Some` value when `load_last_username` produces one, otherwise `None`.
 pub fn load_last_username() -> Option<String> {
     let path = profile_file_path();
-    read_storage(&path).last_username
+    read_storage(&path).ok()?.last_username
 }
 
 /// Persists `username` as the most recently used login name.
@@ -460,10 +866,11 @@ pub fn load_last_username() -> Option<String> {
 /// * `username` - The account name to remember.
 ///
 /// # Returns
-/// * `Ok(())` on success, `Err(String)` with a description on I/O failure.
+/// * `Ok(())` on success, `Err(String)` with a description on I/O failure
+///   or when the profile was written by a newer client.
 pub fn save_last_username(username: &str) -> Result<(), String> {
     let path = profile_file_path();
-    let mut storage = read_storage(&path);
+    let mut storage = read_storage_for_update(&path)?;
     storage.last_username = Some(username.to_owned());
     write_storage(&path, &storage)
 }
@@ -478,10 +885,11 @@ pub fn save_last_username(username: &str) -> Result<(), String> {
 /// * `settings` - The full settings to persist.
 ///
 /// # Returns
-/// * `Ok(())` on success, `Err(String)` with a description on I/O failure.
+/// * `Ok(())` on success, `Err(String)` with a description on I/O failure
+///   or when the profile was written by a newer client.
 pub fn save_settings(identity: &CharacterIdentity, settings: &Settings) -> Result<(), String> {
     let path = profile_file_path();
-    let mut storage = read_storage(&path);
+    let mut storage = read_storage_for_update(&path)?;
     // Update global fields.
     storage.global = global_settings_only(settings);
     // Insert / update character entry.
@@ -553,6 +961,7 @@ mod tests {
         assert_eq!(deserialized.display_mode, defaults.display_mode);
         assert_eq!(deserialized.shadows_enabled, defaults.shadows_enabled);
         assert!((deserialized.master_volume - defaults.master_volume).abs() < f32::EPSILON);
+        assert_eq!(deserialized.audio_mix(), defaults.audio_mix());
         assert_eq!(deserialized.show_helper_text, defaults.show_helper_text);
         assert_eq!(deserialized.show_positions, defaults.show_positions);
         assert_eq!(
@@ -625,6 +1034,52 @@ mod tests {
         assert!(deserialized.characters.is_empty());
     }
 
+    #[test]
+    fn parse_storage_migrates_unversioned_and_refuses_newer_profiles() {
+        let storage = parse_storage(r#"{"last_username": "bob"}"#).unwrap();
+        assert_eq!(storage.version, PROFILE_VERSION);
+        assert_eq!(storage.last_username.as_deref(), Some("bob"));
+
+        let newer = format!(r#"{{"version": {}}}"#, PROFILE_VERSION + 1);
+        assert_eq!(
+            parse_storage(&newer).unwrap_err(),
+            ProfileError::Newer(u64::from(PROFILE_VERSION) + 1)
+        );
+        assert!(
+            parse_storage(&newer)
+                .unwrap_err()
+                .to_string()
+                .contains("newer")
+        );
+        assert!(matches!(
+            parse_storage("[]"),
+            Err(ProfileError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn saves_back_up_broken_profiles_and_skip_newer_ones() {
+        let dir = std::env::temp_dir().join(format!("mag-profile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("profile.json");
+
+        let newer = format!(r#"{{"version": {}}}"#, PROFILE_VERSION + 1);
+        fs::write(&path, &newer).unwrap();
+        assert!(read_storage_for_update(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+
+        fs::write(&path, "{ not json").unwrap();
+        assert!(read_storage_for_update(&path).is_ok());
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0].path()).unwrap(), "{ not json");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn character_settings_skill_keybinds_default_all_none() {
         let cs = CharacterSettings::default();
@@ -659,6 +1114,7 @@ mod tests {
         assert!(s.show_names);
         assert!(s.show_proz);
         assert!(!s.hide);
+        assert_eq!(s.wall_fade_radius, DEFAULT_WALL_FADE_RADIUS);
         assert!(s.show_helper_text);
         assert!(!s.show_positions);
         assert!(s.spell_effects_enabled);
@@ -722,4 +1178,31 @@ mod tests {
         assert_eq!(global.character.inventory_panel_pos, None);
         assert_eq!(global.character.settings_panel_pos, None);
     }
+
+    #[test]
+    fn frame_rate_follows_focus_and_low_power() {
+        let mut settings = Settings {
+            fps_cap: 144,
+            ..Settings::default()
+        };
+        assert_eq!(settings.frame_rate(true), 144);
+        assert_eq!(settings.frame_rate(false), BACKGROUND_FPS);
+
+        settings.low_power_mode = true;
+        assert_eq!(settings.frame_rate(true), LOW_POWER_FPS);
+        assert!(!settings.shadows_active());
+        assert!(!settings.weather_active());
+
+        settings.background_throttle = false;
+        assert_eq!(settings.frame_rate(false), LOW_POWER_FPS);
+    }
+
+    #[test]
+    fn explicit_language_overrides_the_system_locale() {
+        let settings = Settings {
+            language: "pl_PL".to_owned(),
+            ..Settings::default()
+        };
+        assert_eq!(settings.dialogue_locale(), "pl");
+    }
 }
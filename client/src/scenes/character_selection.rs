@@ -170,7 +170,7 @@ impl Scene for CharacterSelectionScene {
         self.form.set_selected(None);
         self.form
             .set_status(Some("Loading characters...".to_owned()));
-        self.form.set_error(None);
+        self.form.set_error(app_state.disconnect_message.take());
         self.form.set_username(app_state.api.username.clone());
 
         let Some(token) = app_state.api.token.as_deref() else {
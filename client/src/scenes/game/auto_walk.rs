@@ -0,0 +1,321 @@
+//! Auto-walk to bookmarks and other far-away destinations.
+//!
+//! The client only sees a small window of the map, so a long trip is walked
+//! in legs: each leg is the click-to-move route (see [`super::path_preview`])
+//! to the visible tile closest to the destination, sent as `CmdWaypoints`.
+//! The next leg is planned once the current one is reached or the player
+//! stops moving. Taking damage or entering combat ends the walk.
+
+use mag_core::constants::{TILEX, TILEY};
+use mag_core::waypoints::{MAX_WAYPOINTS, path_to_waypoints, span};
+
+use super::path_preview::{plan_route, view_tile_walkable};
+use crate::game_map::GameMap;
+use crate::preferences::Bookmark;
+
+/// Ticks without moving before the current leg is replanned.
+const LEG_STALL_TICKS: u32 = 12;
+
+/// Visible tiles tried as the end of a leg, closest to the target first.
+const LEG_CANDIDATES: usize = 6;
+
+/// Legs in a row that may fail to get closer before the walk gives up.
+const MAX_FUTILE_LEGS: u32 = 3;
+
+/// A travel chat command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum TravelCommand {
+    /// `/go <name>` or `/go <x> <y>`: start walking.
+    Go,
+    /// `/mark <name>`: bookmark the current position.
+    Mark,
+    /// `/unmark <name>`: delete a bookmark.
+    Unmark,
+    /// `/marks`: list the bookmarks.
+    List,
+    /// `/stop`: cancel the walk.
+    Stop,
+    /// `/travel`: show or hide the travel menu.
+    Menu,
+}
+
+/// Splits a travel chat command into the command and its arguments.
+///
+/// # Arguments
+///
+/// * `text` - Chat box text.
+///
+/// # Returns
+///
+/// * The command and its trimmed arguments, or `None` if `text` is some
+///   other command or message.
+pub(super) fn travel_command(text: &str) -> Option<(TravelCommand, &str)> {
+    let text = text.trim();
+    let (head, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let command = match head.to_ascii_lowercase().as_str() {
+        "/go" => TravelCommand::Go,
+        "/mark" => TravelCommand::Mark,
+        "/unmark" => TravelCommand::Unmark,
+        "/marks" => TravelCommand::List,
+        "/stop" => TravelCommand::Stop,
+        "/travel" => TravelCommand::Menu,
+        _ => return None,
+    };
+    Some((command, rest.trim()))
+}
+
+/// Recognises the server's `#follow` / `/follow` command in chat text.
+///
+/// # Arguments
+///
+/// * `text` - Chat box text.
+///
+/// # Returns
+///
+/// * `Some(true)` when it starts following someone, `Some(false)` for
+///   `follow self`, and `None` for anything else (including the bare query).
+pub(super) fn follow_command(text: &str) -> Option<bool> {
+    let text = text.trim();
+    let (head, rest) = text.split_once(' ')?;
+    let rest = rest.trim();
+    if !(head.eq_ignore_ascii_case("/follow") || head.eq_ignore_ascii_case("#follow"))
+        || rest.is_empty()
+    {
+        return None;
+    }
+    Some(!rest.eq_ignore_ascii_case("self"))
+}
+
+/// Finds a bookmark by name, ignoring case.
+///
+/// # Arguments
+///
+/// * `bookmarks` - Saved bookmarks.
+/// * `name` - Name to look up.
+///
+/// # Returns
+///
+/// * The index of the bookmark, if any.
+pub(super) fn find_bookmark(bookmarks: &[Bookmark], name: &str) -> Option<usize> {
+    bookmarks
+        .iter()
+        .position(|b| b.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Parses `/go` arguments of the form `<x> <y>`.
+pub(super) fn parse_coords(args: &str) -> Option<(u16, u16)> {
+    let (x, y) = args.split_once(|c: char| c == ' ' || c == ',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Orders visible tiles by how close they bring the player to `target`.
+///
+/// # Arguments
+///
+/// * `tiles` - `(view tile, world tile)` pairs of walkable tiles.
+/// * `target` - Destination world tile.
+/// * `limit` - Number of tiles to return.
+///
+/// # Returns
+///
+/// * Up to `limit` view tiles, closest to `target` first.
+fn leg_candidates(
+    tiles: impl Iterator<Item = ((usize, usize), (u16, u16))>,
+    target: (u16, u16),
+    limit: usize,
+) -> Vec<(usize, usize)> {
+    let mut tiles: Vec<_> = tiles
+        .map(|(view, world)| {
+            let straight = world.0.abs_diff(target.0) + world.1.abs_diff(target.1);
+            ((span(world, target), straight), view)
+        })
+        .collect();
+    tiles.sort_unstable();
+    tiles
+        .into_iter()
+        .take(limit)
+        .map(|(_, view)| view)
+        .collect()
+}
+
+/// What the scene should do for the walk this tick.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum AutoWalkStep {
+    /// Keep walking the current leg.
+    Walking,
+    /// Send these waypoints as the next leg.
+    Leg(Vec<(u16, u16)>),
+    /// The destination was reached.
+    Arrived,
+    /// The player was hit or started fighting.
+    Interrupted,
+    /// No visible route gets any closer.
+    Stuck,
+}
+
+/// An auto-walk in progress.
+pub struct AutoWalk {
+    /// Destination name shown in messages and the travel menu.
+    pub(super) name: String,
+    /// Destination world tile.
+    pub(super) target: (u16, u16),
+    /// Hit points on the previous tick.
+    last_hp: i32,
+    /// Player position on the previous tick.
+    last_pos: Option<(u16, u16)>,
+    /// End of the leg being walked.
+    leg_end: Option<(u16, u16)>,
+    /// Ticks the player has not moved.
+    stalled: u32,
+    /// Closest the player got to the target when a leg was planned.
+    best_span: u16,
+    /// Legs in a row that did not get closer than `best_span`.
+    futile_legs: u32,
+}
+
+impl AutoWalk {
+    /// Starts a walk to `target`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Destination name.
+    /// * `target` - Destination world tile.
+    /// * `hp` - Current hit points, to detect damage.
+    pub(super) fn new(name: impl Into<String>, target: (u16, u16), hp: i32) -> Self {
+        Self {
+            name: name.into(),
+            target,
+            last_hp: hp,
+            last_pos: None,
+            leg_end: None,
+            stalled: 0,
+            best_span: u16::MAX,
+            futile_legs: 0,
+        }
+    }
+
+    /// Returns `true` if the player took damage or entered combat since the
+    /// last tick.
+    fn interrupted(&mut self, hp: i32, attack_cn: i32) -> bool {
+        let hurt = hp < self.last_hp;
+        self.last_hp = hp;
+        hurt || attack_cn != 0
+    }
+
+    /// Advances the walk by one server tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - Visible map window; the player stands at its centre.
+    /// * `hp` - Current hit points.
+    /// * `attack_cn` - Current attack target (`0` = none).
+    ///
+    /// # Returns
+    ///
+    /// * What to do next; anything but `Walking` and `Leg` ends the walk.
+    pub(super) fn tick(&mut self, map: &GameMap, hp: i32, attack_cn: i32) -> AutoWalkStep {
+        if self.interrupted(hp, attack_cn) {
+            return AutoWalkStep::Interrupted;
+        }
+        let Some(pos) = map.tile_at_xy(TILEX / 2, TILEY / 2).map(|t| (t.x, t.y)) else {
+            return AutoWalkStep::Walking;
+        };
+        if pos == self.target {
+            return AutoWalkStep::Arrived;
+        }
+
+        if self.last_pos == Some(pos) {
+            self.stalled += 1;
+        } else {
+            self.stalled = 0;
+        }
+        self.last_pos = Some(pos);
+        if self.leg_end.is_some_and(|end| end != pos) && self.stalled < LEG_STALL_TICKS {
+            return AutoWalkStep::Walking;
+        }
+
+        let remaining = span(pos, self.target);
+        if remaining < self.best_span {
+            self.best_span = remaining;
+            self.futile_legs = 0;
+        } else {
+            self.futile_legs += 1;
+            if self.futile_legs >= MAX_FUTILE_LEGS {
+                return AutoWalkStep::Stuck;
+            }
+        }
+
+        let tiles = (0..TILEY)
+            .flat_map(|y| (0..TILEX).map(move |x| (x, y)))
+            .filter(|&(x, y)| view_tile_walkable(map, x, y))
+            .filter_map(|(x, y)| map.tile_at_xy(x, y).map(|t| ((x, y), (t.x, t.y))));
+        for goal in leg_candidates(tiles, self.target, LEG_CANDIDATES) {
+            let Some(route) = plan_route(map, goal) else {
+                continue;
+            };
+            let mut waypoints = path_to_waypoints(pos, &route);
+            waypoints.truncate(MAX_WAYPOINTS);
+            if let Some(&end) = waypoints.last() {
+                self.leg_end = Some(end);
+                self.stalled = 0;
+                return AutoWalkStep::Leg(waypoints);
+            }
+        }
+        AutoWalkStep::Stuck
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn travel_commands_are_recognised() {
+        assert_eq!(
+            travel_command("/go  Bank "),
+            Some((TravelCommand::Go, "Bank"))
+        );
+        assert_eq!(
+            travel_command("/MARK home"),
+            Some((TravelCommand::Mark, "home"))
+        );
+        assert_eq!(travel_command("/marks"), Some((TravelCommand::List, "")));
+        assert_eq!(travel_command("/gold"), None);
+        assert_eq!(travel_command("hello"), None);
+        assert_eq!(follow_command("/follow Ishtar"), Some(true));
+        assert_eq!(follow_command("#FOLLOW self"), Some(false));
+        assert_eq!(follow_command("/follow"), None);
+        assert_eq!(follow_command("/followers x"), None);
+        assert_eq!(parse_coords("512, 498"), Some((512, 498)));
+        assert_eq!(parse_coords("bank"), None);
+
+        let marks = vec![Bookmark {
+            name: "Home".into(),
+            x: 1,
+            y: 2,
+        }];
+        assert_eq!(find_bookmark(&marks, "home"), Some(0));
+        assert_eq!(find_bookmark(&marks, "bank"), None);
+    }
+
+    #[test]
+    fn legs_head_for_the_tiles_closest_to_the_target() {
+        let tiles = [
+            ((0, 0), (100, 100)),
+            ((1, 0), (110, 100)),
+            ((2, 0), (110, 104)),
+            ((3, 0), (108, 101)),
+        ];
+        let order = leg_candidates(tiles.into_iter(), (120, 104), 3);
+        assert_eq!(order, vec![(2, 0), (1, 0), (3, 0)]);
+    }
+
+    #[test]
+    fn damage_and_combat_interrupt() {
+        let mut walk = AutoWalk::new("Home", (10, 10), 50);
+        assert!(!walk.interrupted(50, 0));
+        assert!(!walk.interrupted(60, 0));
+        assert!(walk.interrupted(55, 0));
+        assert!(walk.interrupted(55, 7));
+    }
+}
@@ -123,6 +123,16 @@ impl GameScene {
                     }
                 }
 
+                // Radial inventory menu intercept (modal while open).
+                if self.radial_menu.is_visible() {
+                    match button {
+                        Btn::A | Btn::RightStick => self.use_radial_selection(app_state),
+                        Btn::B => self.radial_menu.close(),
+                        _ => {}
+                    }
+                    return None;
+                }
+
                 // Skill picker popup intercept (modal for controller input).
                 if self.skill_picker.is_visible() {
                     match button {
@@ -306,8 +316,15 @@ impl GameScene {
                 }
 
                 // Right stick press (R3) → activate highlighted skill or assign empty slot.
-                // When LT is held, operate on the secondary bar page.
+                // When LT is held, operate on the secondary bar page. With no
+                // slot highlighted, R3 opens the radial inventory menu instead.
                 if *button == Btn::RightStick && self.controller_mode {
+                    if self.skill_bar.controller_selected_slot().is_none() {
+                        if let Some(ps) = app_state.player_state.as_ref() {
+                            self.radial_menu.open(&ps.character_info().item);
+                        }
+                        return None;
+                    }
                     if let Some(slot) = self.skill_bar.controller_selected_slot() {
                         let skill_nr = if self.lt_held {
                             app_state.settings.character.skill_keybinds_secondary[slot]
@@ -565,10 +582,7 @@ impl GameScene {
                     // Hold threshold not reached (would have been consumed
                     // in update()), so this is a short press → select.
                     if let Some(ps) = app_state.player_state.as_ref() {
-                        let (cam_xoff, cam_yoff) = Self::camera_offsets(ps);
-                        if let Some((mx, my)) =
-                            Self::screen_to_map_tile(self.mouse_x, self.mouse_y, cam_xoff, cam_yoff)
-                        {
+                        if let Some((mx, my)) = self.pick_map_tile(ps, self.mouse_x, self.mouse_y) {
                             use mag_core::constants::ISCHAR;
                             let selected_char = ps.selected_char();
                             if let Some((sx, sy)) = Self::nearest_tile_with_flag(ps, mx, my, ISCHAR)
@@ -702,4 +716,21 @@ impl GameScene {
             _ => None,
         }
     }
+
+    /// Use the item highlighted in the radial menu and close it.
+    ///
+    /// Sends the same `CmdInv` use action as a left click on the backpack
+    /// slot in the inventory panel.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (network access).
+    fn use_radial_selection(&mut self, app_state: &AppState<'_>) {
+        let slot = self.radial_menu.highlighted_slot();
+        self.radial_menu.close();
+        if let (Some(slot), Some(net)) = (slot, app_state.network.as_ref()) {
+            self.play_click_sound(app_state);
+            net.send(ClientCommand::new_inv(6, slot as u32, 0));
+        }
+    }
 }
@@ -0,0 +1,142 @@
+//! Short-lived map decals (blood, scorch marks) from `SV_MAPDECAL`.
+//!
+//! The server sends each decal once with its tile, sprite and lifetime; the
+//! client keeps it until the lifetime runs out and draws it on the floor,
+//! fading out as it ages.
+
+use std::time::{Duration, Instant};
+
+use mag_core::constants::TICKS;
+use mag_core::decals::DecalKind;
+
+/// Most decals kept at once; the oldest is dropped to make room.
+const MAX_DECALS: usize = 256;
+
+/// Opacity of a fresh decal.
+const DECAL_MAX_ALPHA: f32 = 220.0;
+
+/// One decal on a world tile.
+#[derive(Clone, Copy, Debug)]
+struct Decal {
+    x: u16,
+    y: u16,
+    sprite: i32,
+    kind: DecalKind,
+    created: Instant,
+    ttl: Duration,
+}
+
+/// Live decals, oldest first.
+#[derive(Debug, Default)]
+pub struct DecalLayer {
+    decals: Vec<Decal>,
+}
+
+impl DecalLayer {
+    /// Adds a decal received from the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`, `y` - World tile.
+    /// * `sprite` - Sprite to draw.
+    /// * `ttl_ticks` - Lifetime in server ticks.
+    /// * `kind` - Wire byte of the [`DecalKind`].
+    /// * `now` - Time the packet arrived.
+    pub fn add(&mut self, x: u16, y: u16, sprite: u32, ttl_ticks: u16, kind: u8, now: Instant) {
+        if ttl_ticks == 0 || sprite == 0 {
+            return;
+        }
+        self.decals.retain(|decal| !decal.expired(now));
+        if self.decals.len() >= MAX_DECALS {
+            self.decals.remove(0);
+        }
+        self.decals.push(Decal {
+            x,
+            y,
+            sprite: sprite as i32,
+            kind: DecalKind::from_u8(kind),
+            created: now,
+            ttl: Duration::from_millis(u64::from(ttl_ticks) * 1000 / TICKS as u64),
+        });
+    }
+
+    /// Decals on world tile `x`/`y` that are still visible.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`, `y` - World tile.
+    /// * `now` - Frame time.
+    ///
+    /// # Returns
+    ///
+    /// * `(sprite, alpha, kind)` for each decal, oldest first.
+    pub fn at(
+        &self,
+        x: u16,
+        y: u16,
+        now: Instant,
+    ) -> impl Iterator<Item = (i32, u8, DecalKind)> + '_ {
+        self.decals
+            .iter()
+            .filter(move |decal| decal.x == x && decal.y == y && !decal.expired(now))
+            .map(move |decal| (decal.sprite, decal.alpha(now), decal.kind))
+    }
+
+    /// Whether no decals are held.
+    pub fn is_empty(&self) -> bool {
+        self.decals.is_empty()
+    }
+
+    /// Forgets every decal (on logout).
+    pub fn clear(&mut self) {
+        self.decals.clear();
+    }
+}
+
+impl Decal {
+    fn expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.created) >= self.ttl
+    }
+
+    /// Opacity that falls linearly from [`DECAL_MAX_ALPHA`] to zero.
+    fn alpha(&self, now: Instant) -> u8 {
+        let age = now.saturating_duration_since(self.created).as_secs_f32();
+        let left = 1.0 - age / self.ttl.as_secs_f32();
+        (DECAL_MAX_ALPHA * left.clamp(0.0, 1.0)) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decals_fade_and_expire() {
+        let start = Instant::now();
+        let mut layer = DecalLayer::default();
+        layer.add(10, 12, 1080, TICKS as u16 * 2, 0, start);
+
+        let fresh: Vec<_> = layer.at(10, 12, start).collect();
+        assert_eq!(fresh, vec![(1080, DECAL_MAX_ALPHA as u8, DecalKind::Blood)]);
+        assert_eq!(layer.at(11, 12, start).count(), 0);
+
+        let (_, half_alpha, _) = layer
+            .at(10, 12, start + Duration::from_secs(1))
+            .next()
+            .unwrap();
+        assert!(half_alpha > 100 && half_alpha < 120);
+        assert_eq!(layer.at(10, 12, start + Duration::from_secs(2)).count(), 0);
+    }
+
+    #[test]
+    fn oldest_decal_makes_room() {
+        let now = Instant::now();
+        let mut layer = DecalLayer::default();
+        for i in 0..=MAX_DECALS as u16 {
+            layer.add(i, 0, 1080, 100, 1, now);
+        }
+        assert_eq!(layer.decals.len(), MAX_DECALS);
+        assert_eq!(layer.at(0, 0, now).count(), 0);
+        assert_eq!(layer.at(MAX_DECALS as u16, 0, now).count(), 1);
+    }
+}
@@ -0,0 +1,173 @@
+//! Free camera.
+//!
+//! With the free camera on (`ToggleFreeCamera`, F9 by default), the arrow
+//! keys move the view away from the player's tile. A normal character can only look around the tiles the
+//! server already sends, so the view slides at most [`MAX_LOCAL_PAN`]
+//! tiles and springs back when the camera is turned off. An observer (see
+//! `SV_OBSERVER`) has no tile: each arrow key press asks the server to move
+//! the observer camera [`OBSERVER_PAN_STEP`] tiles, and the map streams in
+//! around the new centre.
+
+use sdl2::keyboard::Keycode;
+
+use mag_core::client_commands::ClientCommand;
+use mag_core::constants::{TILEX, TILEY};
+
+use crate::network::NetworkRuntime;
+use crate::player_state::PlayerState;
+
+use super::{FLOOR_TILE_WIDTH, GameScene};
+
+/// Furthest a normal character's free camera pans from its tile, in tiles.
+pub(super) const MAX_LOCAL_PAN: i32 = 10;
+
+/// Tiles an observer camera moves per arrow key press.
+pub(super) const OBSERVER_PAN_STEP: i32 = 4;
+
+impl GameScene {
+    /// Map direction of an arrow key, as `(dx, dy)` in tiles, so the view
+    /// moves the way the arrow points on screen.
+    ///
+    /// # Returns
+    ///
+    /// * `None` for other keys.
+    pub(super) fn pan_direction(key: Keycode) -> Option<(i32, i32)> {
+        match key {
+            Keycode::Up => Some((-1, 1)),
+            Keycode::Down => Some((1, -1)),
+            Keycode::Left => Some((-1, -1)),
+            Keycode::Right => Some((1, 1)),
+            _ => None,
+        }
+    }
+
+    /// Moves a local pan one step, staying within [`MAX_LOCAL_PAN`].
+    ///
+    /// # Arguments
+    ///
+    /// * `pan` - Current pan in tiles.
+    /// * `dir` - Step from [`Self::pan_direction`].
+    ///
+    /// # Returns
+    ///
+    /// * The new pan.
+    pub(super) fn step_local_pan(pan: (i32, i32), dir: (i32, i32)) -> (i32, i32) {
+        (
+            (pan.0 + dir.0).clamp(-MAX_LOCAL_PAN, MAX_LOCAL_PAN),
+            (pan.1 + dir.1).clamp(-MAX_LOCAL_PAN, MAX_LOCAL_PAN),
+        )
+    }
+
+    /// Camera shift that centres the view on the tile `pan` tiles away from
+    /// the centre tile.
+    ///
+    /// # Returns
+    ///
+    /// * `(dx, dy)` in world units, `(0, 0)` without a pan.
+    pub(super) fn pan_shift(pan: (i32, i32)) -> (i32, i32) {
+        let (dx, dy) = pan;
+        (
+            -(FLOOR_TILE_WIDTH / 2) * (dx + dy),
+            -(FLOOR_TILE_WIDTH / 4) * (dx - dy),
+        )
+    }
+
+    /// Turns the free camera on or off; turning it off re-centres the view.
+    ///
+    /// # Arguments
+    ///
+    /// * `ps` - Player state, for the chat notice.
+    pub(super) fn toggle_free_camera(&mut self, ps: &mut PlayerState) {
+        self.free_camera = !self.free_camera;
+        self.camera_pan = (0, 0);
+        if self.free_camera {
+            ps.tlog(1, "Free camera on. Use the arrow keys to look around.");
+        } else {
+            ps.tlog(1, "Free camera off.");
+        }
+    }
+
+    /// Pans the free camera one step in direction `dir`.
+    ///
+    /// Observers move the server-side camera; everyone else slides the
+    /// local view. Does nothing while the free camera is off.
+    ///
+    /// # Arguments
+    ///
+    /// * `ps` - Player state holding the map and observer state.
+    /// * `net` - Connection for observer camera moves.
+    /// * `dir` - Step from [`Self::pan_direction`].
+    pub(super) fn pan_free_camera(
+        &mut self,
+        ps: &PlayerState,
+        net: Option<&NetworkRuntime>,
+        dir: (i32, i32),
+    ) {
+        if !self.free_camera {
+            return;
+        }
+        if !ps.is_observer() {
+            self.camera_pan = Self::step_local_pan(self.camera_pan, dir);
+            return;
+        }
+
+        self.camera_pan = (0, 0);
+        let centre = ps.map().tile_at_xy(TILEX / 2, TILEY / 2);
+        if let (Some(net), Some(tile)) = (net, centre) {
+            let x = (i32::from(tile.x) + dir.0 * OBSERVER_PAN_STEP).max(0);
+            let y = (i32::from(tile.y) + dir.1 * OBSERVER_PAN_STEP).max(0);
+            net.send(ClientCommand::new_move(x as i16, y));
+        }
+    }
+
+    /// Current local pan as a camera shift in world units.
+    ///
+    /// # Returns
+    ///
+    /// * `(0, 0)` when the free camera is off or the player observes.
+    pub(super) fn free_camera_shift(&self) -> (i32, i32) {
+        if self.free_camera {
+            Self::pan_shift(self.camera_pan)
+        } else {
+            (0, 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pan_shift_centres_the_panned_tile() {
+        let (cx, cy) = (TILEX / 2, TILEY / 2);
+        let (ox, oy) = GameScene::tile_ground_diamond_origin(cx, cy, 0, 0);
+        for key in [Keycode::Up, Keycode::Down, Keycode::Left, Keycode::Right] {
+            let (dx, dy) = GameScene::pan_direction(key).unwrap();
+            let pan = (dx * 3, dy * 3);
+            let (sx, sy) = GameScene::pan_shift(pan);
+            let tile = ((cx as i32 + pan.0) as usize, (cy as i32 + pan.1) as usize);
+            assert_eq!(
+                GameScene::tile_ground_diamond_origin(tile.0, tile.1, sx, sy),
+                (ox, oy)
+            );
+        }
+
+        let (_, up) = GameScene::pan_shift(GameScene::pan_direction(Keycode::Up).unwrap());
+        assert!(up > 0, "panning up moves the world down");
+        assert_eq!(GameScene::pan_direction(Keycode::W), None);
+    }
+
+    #[test]
+    fn local_pan_is_bounded() {
+        let mut pan = (0, 0);
+        for _ in 0..MAX_LOCAL_PAN + 5 {
+            pan = GameScene::step_local_pan(pan, (1, 1));
+        }
+        assert_eq!(pan, (MAX_LOCAL_PAN, MAX_LOCAL_PAN));
+        assert_eq!(
+            GameScene::step_local_pan(pan, (-1, 1)),
+            (MAX_LOCAL_PAN - 1, MAX_LOCAL_PAN)
+        );
+    }
+}
@@ -5,12 +5,45 @@ use crate::player_state::PlayerState;
 use super::{FLOOR_TILE_HEIGHT, FLOOR_TILE_WIDTH, GameScene, MAP_ORIGIN_X, MAP_ORIGIN_Y};
 
 impl GameScene {
+    /// Alpha of the most faded wall ring in front of the player.
+    pub(super) const OCCLUSION_MIN_ALPHA: u8 = 80;
+
+    /// Alpha of the goto markers tracing the click-to-move route preview.
+    pub(super) const PATH_PREVIEW_ALPHA: u8 = 110;
+
     /// Returns `true` if the tile at `(x, y)` should be hidden in the "hide"
     /// display mode. Ported from the C `autohide()` function.
     pub(super) fn autohide(x: usize, y: usize) -> bool {
         !(x >= (TILEX / 2) || y <= (TILEX / 2))
     }
 
+    /// Alpha for an object sprite at `(x, y)` that may occlude `focus`.
+    ///
+    /// Only tiles drawn in front of `focus` (further down the screen) within
+    /// `radius` tiles fade; the closest ring is the most transparent and the
+    /// fade eases back to opaque towards the edge of the radius.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`, `y` - View tile of the object sprite.
+    /// * `focus` - View tile that should stay visible (player or hovered tile).
+    /// * `radius` - Fade radius in tiles; `0` disables the fade.
+    ///
+    /// # Returns
+    ///
+    /// * Alpha in `OCCLUSION_MIN_ALPHA..=255`.
+    pub(super) fn occlusion_alpha(x: usize, y: usize, focus: (usize, usize), radius: u8) -> u8 {
+        let dx = x as i32 - focus.0 as i32;
+        let dy = y as i32 - focus.1 as i32;
+        let dist = dx.abs().max(dy.abs());
+        if radius == 0 || dx - dy <= 0 || dist > i32::from(radius) {
+            return 255;
+        }
+        let span = i32::from(255 - Self::OCCLUSION_MIN_ALPHA);
+        let alpha = i32::from(Self::OCCLUSION_MIN_ALPHA) + span * (dist - 1) / i32::from(radius);
+        alpha as u8
+    }
+
     /// Returns `true` if tile `(x, y)` is the cell directly in front of the
     /// player given facing direction `dir` (1=E, 2=W, 3=N, 4=S).
     pub(super) fn facing(x: usize, y: usize, dir: i32) -> bool {
@@ -191,6 +224,34 @@ mod tests {
         assert!(!GameScene::autohide(10, 0));
     }
 
+    // -- occlusion_alpha --
+
+    #[test]
+    fn occlusion_fades_only_tiles_in_front_of_focus() {
+        let focus = (TILEX / 2, TILEY / 2);
+        let (cx, cy) = focus;
+        // Directly in front (down-screen) is the most transparent.
+        assert_eq!(
+            GameScene::occlusion_alpha(cx + 1, cy - 1, focus, 3),
+            GameScene::OCCLUSION_MIN_ALPHA
+        );
+        // Behind the focus, beside it, or outside the radius stays opaque.
+        assert_eq!(GameScene::occlusion_alpha(cx - 1, cy + 1, focus, 3), 255);
+        assert_eq!(GameScene::occlusion_alpha(cx + 1, cy + 1, focus, 3), 255);
+        assert_eq!(GameScene::occlusion_alpha(cx + 4, cy, focus, 3), 255);
+        // Radius 0 disables the fade.
+        assert_eq!(GameScene::occlusion_alpha(cx + 1, cy - 1, focus, 0), 255);
+    }
+
+    #[test]
+    fn occlusion_fade_eases_out_with_distance() {
+        let focus = (TILEX / 2, TILEY / 2);
+        let near = GameScene::occlusion_alpha(focus.0 + 1, focus.1, focus, 4);
+        let mid = GameScene::occlusion_alpha(focus.0 + 2, focus.1, focus, 4);
+        let far = GameScene::occlusion_alpha(focus.0 + 4, focus.1, focus, 4);
+        assert!(near < mid && mid < far && far < 255);
+    }
+
     // -- facing --
 
     #[test]
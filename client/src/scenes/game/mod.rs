@@ -9,15 +9,24 @@
 //! | [`world_render`] | Isometric tile/sprite/shadow/effect drawing |
 //! | [`net_events`] | Per-frame network tick processing and auto-look |
 //! | [`perf_profiler`] | Wall-clock profiler for rendering functions (activated from escape menu) |
+//! | [`replay`] | Offline [`ReplayScene`] for server tick recordings |
 
+mod auto_walk;
 mod controller_input;
+mod decals;
+mod free_camera;
 mod game_math;
 mod net_events;
+mod path_preview;
 mod perf_profiler;
 mod profile;
+mod replay;
 mod weather;
 mod world_input;
 mod world_render;
+mod zoom;
+
+pub use replay::{ReplayScene, replay_path_from_env};
 
 use mag_core::traits::class_from_kindred;
 use perf_profiler::{PerfLabel, PerfProfiler};
@@ -35,10 +44,11 @@ use mag_core::{
 };
 
 use crate::{
+    bug_report::BugReportRequest,
     cert_trust,
     constants::{TARGET_HEIGHT_INT, TARGET_WIDTH_INT},
     gfx_cache::GraphicsCache,
-    network::NetworkRuntime,
+    network::{GameTransport, NetworkRuntime, ResumeCredentials},
     player_state::PlayerState,
     preferences::{self, CharacterIdentity},
     scenes::scene::{Scene, SceneType},
@@ -63,6 +73,7 @@ use crate::{
         style::Padding,
         visuals::rank_progress_line::RankProgressLine,
         visuals::rank_sigil::RankSigil,
+        visuals::skill_timers::CastBar,
         visuals::spell_effect_icons::SpellEffectIcons,
         visuals::vitality_bars::VitalityChevrons,
         widget::{Bounds, GameAction, KeyBindings, KeyModifiers, UiEvent, Widget, WidgetAction},
@@ -155,6 +166,8 @@ const INV_PANEL_W: u32 = 190;
 const INV_PANEL_H: u32 = 280;
 /// Semi-transparent background color shared by all HUD panels.
 const HUD_PANEL_BG: Color = Color::RGBA(10, 10, 30, 180);
+/// Opaque background used for HUD panels by the high-contrast HUD.
+const HIGH_CONTRAST_PANEL_BG: Color = Color::RGBA(0, 0, 0, 255);
 
 // ---- Minimap toggle button ---- //
 
@@ -197,8 +210,16 @@ const SHOP_PANEL_H: u32 = crate::ui::hud::shop_panel::SHOP_PANEL_H;
 const SHOP_PANEL_X: i32 = (crate::constants::TARGET_WIDTH_INT as i32 - SHOP_PANEL_W as i32) / 2;
 /// Y position of the shop panel (vertically centered).
 const SHOP_PANEL_Y: i32 = (crate::constants::TARGET_HEIGHT_INT as i32 - SHOP_PANEL_H as i32) / 2;
+
+/// Left edge of the addon panel stack (aligned with the look panel).
+const ADDON_PANELS_X: i32 = LOOK_PANEL_X;
+
+/// Top of the addon panel stack (below the look panel).
+const ADDON_PANELS_Y: i32 = LOOK_PANEL_Y + LOOK_PANEL_H as i32 + 8;
 /// Maximum character count for one helper-text line.
 const HELPER_TEXT_MAX_CHARS: u32 = 50;
+/// Fill behind the item tooltip of a chat link.
+const ITEM_LINK_TOOLTIP_BG: Color = Color::RGBA(10, 10, 30, 220);
 /// Minimum margin (in logical pixels) between helper text and the screen
 /// edges. The tooltip is repositioned to honour this margin.
 const HELPER_TEXT_SCREEN_MARGIN: i32 = 4;
@@ -308,6 +329,13 @@ const RANK_SIGIL_X: i32 = 4;
 /// Y position of the rank sigil widget.
 const RANK_SIGIL_Y: i32 = 4;
 
+// ---- Quest tracker (right of the rank sigil) ---- //
+
+/// X position of the quest tracker overlay.
+const QUEST_TRACKER_X: i32 = RANK_SIGIL_X + crate::ui::visuals::rank_sigil::SIGIL_WIDTH + 8 + 6;
+/// Y position of the quest tracker overlay.
+const QUEST_TRACKER_Y: i32 = RANK_SIGIL_Y;
+
 // ---- Status panel (WV/AV, right of skill bar) ---- //
 
 /// X position of the weapon/armor panel (8 px to the right of the skill bar's right edge).
@@ -320,6 +348,11 @@ const VITALITY_BARS_X: i32 = TARGET_WIDTH_INT as i32 / 2;
 /// Y position of the vitality chevron feet.
 const VITALITY_BARS_Y: i32 = TARGET_HEIGHT_INT as i32 - 42;
 
+/// X position of the cast bar (horizontal centre of the player sprite).
+const CAST_BAR_X: i32 = TARGET_WIDTH_INT as i32 / 2;
+/// Height of the cast bar above the player's tile centre at normal zoom.
+const CAST_BAR_LIFT: i32 = 72;
+
 // ---------------------------------------------------------------------------
 // GameScene struct
 // ---------------------------------------------------------------------------
@@ -359,6 +392,64 @@ fn active_quest_destination(
     npc_pos_fallback
 }
 
+/// Builds the quest tracker's objective line for a catalog entry.
+///
+/// Uses the authored walkthrough step when a static quest definition
+/// exists, otherwise the generic "Bring <item> to <npc>" wording.
+///
+/// # Arguments
+///
+/// * `entry`    - Quest catalog entry.
+/// * `step_idx` - Walkthrough step to describe (clamped to the last step).
+///
+/// # Returns
+///
+/// * The objective text.
+fn tracker_objective(entry: &mag_core::quest_defs::QuestCatalogEntry, step_idx: usize) -> String {
+    if let Some(def) = mag_core::quest_defs::find_quest_def(entry.template_id)
+        && let Some(step) = def
+            .steps
+            .get(step_idx.min(def.steps.len().saturating_sub(1)))
+    {
+        return match step {
+            mag_core::quest_defs::QuestStep::FixedLocation { desc, .. }
+            | mag_core::quest_defs::QuestStep::ReturnToQuestGiver { desc } => (*desc).to_owned(),
+        };
+    }
+    let item = if entry.item_name.is_empty() {
+        "?"
+    } else {
+        &entry.item_name
+    };
+    let npc = if entry.npc_name.is_empty() {
+        "NPC"
+    } else {
+        &entry.npc_name
+    };
+    format!("Bring {item} to {npc}")
+}
+
+/// Tiles within this Chebyshev distance of a known bank belong to that bank.
+const MINIMAP_BANK_MERGE_DIST: u16 = 12;
+
+/// Records a bank tile for the minimap, merging it into a nearby known bank.
+///
+/// Every visible floor tile of a bank carries the `IS_BANK` hint, so only
+/// the first tile seen of each bank is kept as its marker.
+///
+/// # Arguments
+///
+/// * `banks` - Known bank positions.
+/// * `x`, `y` - World tile carrying `IS_BANK`.
+fn remember_bank(banks: &mut Vec<(u16, u16)>, x: u16, y: u16) {
+    let near = banks.iter().any(|&(bx, by)| {
+        bx.abs_diff(x) <= MINIMAP_BANK_MERGE_DIST && by.abs_diff(y) <= MINIMAP_BANK_MERGE_DIST
+    });
+    if !near {
+        banks.push((x, y));
+    }
+}
+
 /// The primary in-game scene.
 ///
 /// Holds all transient gameplay state: input buffer, modifier-key flags,
@@ -373,6 +464,20 @@ pub struct GameScene {
     pub(super) skills_panel: SkillsPanel,
     pub(super) talent_panel: TalentPanel,
     pub(super) quest_log_panel: crate::ui::hud::quest_log_panel::QuestLogPanel,
+    /// Collapsible objective list shown next to the rank sigil.
+    pub(super) quest_tracker: crate::ui::hud::quest_tracker::QuestTracker,
+    /// Character profile editor (name color + bio), opened with `/profile`.
+    pub(super) profile_panel: crate::ui::hud::profile_panel::ProfilePanel,
+    /// Guild name, message and roster, opened with `/guild`.
+    pub(super) guild_panel: crate::ui::hud::guild_panel::GuildPanel,
+    /// Mailbox and compose line, opened with `/mail`.
+    pub(super) mail_panel: crate::ui::hud::mail_panel::MailPanel,
+    /// Friends list with quick-tell buttons, opened with `/friends`.
+    pub(super) friends_panel: crate::ui::hud::friends_panel::FriendsPanel,
+    /// Per-fight damage summaries, opened with `/combatlog`.
+    pub(super) combat_log_panel: crate::ui::hud::combat_log_panel::CombatLogPanel,
+    /// Bookmark list opened with `/travel`, drawn below the quest tracker.
+    pub(super) travel_menu: crate::ui::hud::travel_menu::TravelMenu,
     pub(super) inventory_panel: InventoryPanel,
     pub(super) settings_panel: SettingsPanel,
     pub(super) minimap_widget: MinimapWidget,
@@ -380,9 +485,30 @@ pub struct GameScene {
     pub(super) look_panel: LookPanel,
     pub(super) shop_panel: ShopPanel,
     pub(super) vitality_bars: VitalityChevrons,
+    pub(super) cast_bar: CastBar,
     pub(super) spell_effect_icons: SpellEffectIcons,
     pub(super) skill_bar: SkillBar,
     pub(super) skill_picker: SkillPickerPopup,
+    /// NPC interaction context menu, opened when the server answers a
+    /// right-click with `SV_NPCMENU`.
+    pub(super) npc_menu: crate::ui::hud::npc_menu::NpcMenu,
+    /// Screen position of the last character right-click; the NPC menu
+    /// opens here.
+    pub(super) npc_menu_anchor: (i32, i32),
+    /// Skill offers of a trainer NPC, opened when the server answers
+    /// "Train" with `SV_TRAINEROFFERS`.
+    pub(super) trainer_popup: crate::ui::hud::trainer_popup::TrainerPopup,
+    /// Notification toasts fed from `PlayerState`'s client event queue.
+    pub(super) toast_stack: crate::ui::hud::toast_stack::ToastStack,
+    /// Controller radial menu for using backpack items (R3 with no skill
+    /// slot highlighted).
+    pub(super) radial_menu: crate::ui::hud::radial_menu::RadialMenu,
+    /// Scripted addons loaded from the addon folder on scene enter.
+    pub(super) addons: crate::addons::AddonHost,
+    /// Panels drawn on behalf of `addons`.
+    pub(super) addon_panels: crate::ui::hud::addon_panels::AddonPanels,
+    /// Screen-reader output; `Some` while screen-reader mode is enabled.
+    pub(super) announcer: Option<crate::accessibility::Announcer>,
     pub(super) last_synced_log_len: usize,
     pub(super) pending_exit: Option<String>,
     pub(super) certificate_mismatch: Option<cert_trust::FingerprintMismatch>,
@@ -417,6 +543,14 @@ pub struct GameScene {
     /// This matches the C xmap column-major storage: `xmap[map[m].y + map[m].x*1024]`.
     pub(super) minimap_xmap: Vec<u8>,
     pub(super) minimap_last_xy: Option<(u16, u16)>,
+    /// One world position per bank seen this session, for the minimap legend.
+    pub(super) minimap_banks: Vec<(u16, u16)>,
+    /// Route drawn to the hovered tile while the cursor shows "WALK".
+    pub(super) path_preview: path_preview::PathPreview,
+    /// Walk to a bookmark started with `/go` or the travel menu.
+    pub(super) auto_walk: Option<auto_walk::AutoWalk>,
+    /// Set while the server walks the player after someone (`/follow`).
+    pub(super) following: bool,
     pub(super) look_step: u32,
     pub(super) last_look_tick: u32,
     /// World-coordinate keys `(x, y)` of tombstone tiles for which a
@@ -432,6 +566,16 @@ pub struct GameScene {
     perf_profiler: PerfProfiler,
     /// Active client-side weather/ambient overlay state.
     pub(super) weather: weather::WeatherState,
+    /// Fading blood and scorch marks received from the server.
+    pub(super) decals: decals::DecalLayer,
+    /// View radius the world was last drawn with; mouse picking uses it to
+    /// undo the zoom (see [`zoom`]).
+    pub(super) view_radius: u8,
+    /// `true` while the camera is detached from the player's tile (see
+    /// [`free_camera`]).
+    pub(super) free_camera: bool,
+    /// Local free-camera pan in map tiles.
+    pub(super) camera_pan: (i32, i32),
     /// `true` when the player is using a game controller (mirrors
     /// `AppState::controller_active`). Stored locally so `handle_event` can
     /// read it without re-borrowing `AppState`.
@@ -570,9 +714,38 @@ impl GameScene {
                 Bounds::new(panel_x, panel_y, HUD_PANEL_W, HUD_PANEL_H),
                 HUD_PANEL_BG,
             ),
+            quest_tracker: crate::ui::hud::quest_tracker::QuestTracker::new(
+                QUEST_TRACKER_X,
+                QUEST_TRACKER_Y,
+            ),
+            profile_panel: crate::ui::hud::profile_panel::ProfilePanel::new(
+                Bounds::new(panel_x, panel_y, HUD_PANEL_W, HUD_PANEL_H),
+                HUD_PANEL_BG,
+            ),
+            guild_panel: crate::ui::hud::guild_panel::GuildPanel::new(
+                Bounds::new(panel_x, panel_y, HUD_PANEL_W, HUD_PANEL_H),
+                HUD_PANEL_BG,
+            ),
+            mail_panel: crate::ui::hud::mail_panel::MailPanel::new(
+                Bounds::new(panel_x, panel_y, HUD_PANEL_W, HUD_PANEL_H),
+                HUD_PANEL_BG,
+            ),
+            friends_panel: crate::ui::hud::friends_panel::FriendsPanel::new(
+                Bounds::new(panel_x, panel_y, HUD_PANEL_W, HUD_PANEL_H),
+                HUD_PANEL_BG,
+            ),
+            combat_log_panel: crate::ui::hud::combat_log_panel::CombatLogPanel::new(
+                Bounds::new(panel_x, panel_y, HUD_PANEL_W, HUD_PANEL_H),
+                HUD_PANEL_BG,
+            ),
+            travel_menu: crate::ui::hud::travel_menu::TravelMenu::new(
+                QUEST_TRACKER_X,
+                QUEST_TRACKER_Y,
+            ),
             minimap_widget: MinimapWidget::new(MINIMAP_BTN_CX, MINIMAP_BTN_CY, MINIMAP_BTN_RADIUS),
             mode_button: ModeButton::new(MODE_BTN_CX, MODE_BTN_CY, MODE_BTN_RADIUS),
             vitality_bars: VitalityChevrons::new(VITALITY_BARS_X, VITALITY_BARS_Y),
+            cast_bar: CastBar::new(CAST_BAR_X, TARGET_HEIGHT_INT as i32 / 2 - CAST_BAR_LIFT),
             spell_effect_icons: SpellEffectIcons::new(
                 positive_start_x,
                 negative_right_x,
@@ -588,6 +761,23 @@ impl GameScene {
             ),
             skill_bar: SkillBar::new(),
             skill_picker: SkillPickerPopup::new(),
+            npc_menu: crate::ui::hud::npc_menu::NpcMenu::new(),
+            npc_menu_anchor: (0, 0),
+            trainer_popup: crate::ui::hud::trainer_popup::TrainerPopup::new(),
+            toast_stack: crate::ui::hud::toast_stack::ToastStack::new(
+                CHATBOX_X + CHATBOX_W as i32,
+                CHATBOX_Y + CHATBOX_H as i32 + 6,
+            ),
+            radial_menu: crate::ui::hud::radial_menu::RadialMenu::new(
+                TARGET_WIDTH_INT as i32 / 2,
+                TARGET_HEIGHT_INT as i32 / 2,
+            ),
+            addons: crate::addons::AddonHost::new(),
+            addon_panels: crate::ui::hud::addon_panels::AddonPanels::new(
+                ADDON_PANELS_X,
+                ADDON_PANELS_Y,
+            ),
+            announcer: None,
             last_synced_log_len: 0,
             pending_exit: None,
             certificate_mismatch: None,
@@ -609,6 +799,10 @@ impl GameScene {
             stat_points_used: 0,
             minimap_xmap: vec![0u8; MINIMAP_WORLD_SIZE * MINIMAP_WORLD_SIZE * 4],
             minimap_last_xy: None,
+            minimap_banks: Vec::new(),
+            path_preview: path_preview::PathPreview::default(),
+            auto_walk: None,
+            following: false,
             look_step: 0,
             last_look_tick: 0,
             autoloot_visited: HashSet::new(),
@@ -616,6 +810,10 @@ impl GameScene {
             active_profile_character: None,
             perf_profiler: PerfProfiler::new(),
             weather: weather::WeatherState::new(),
+            decals: decals::DecalLayer::default(),
+            view_radius: mag_core::view::LEGACY_VIEW_RADIUS,
+            free_camera: false,
+            camera_pan: (0, 0),
             controller_mode: false,
             vcursor_x: TARGET_WIDTH_INT as f32 / 2.0,
             vcursor_y: TARGET_HEIGHT_INT as f32 / 2.0,
@@ -666,7 +864,7 @@ impl GameScene {
     pub(super) fn play_click_sound(&self, app_state: &AppState) {
         app_state
             .sfx_cache
-            .play_click(app_state.settings.master_volume);
+            .play_click(&app_state.settings.audio_mix());
     }
 
     /// Build a [`SettingsPanelData`] snapshot from current game state.
@@ -685,15 +883,25 @@ impl GameScene {
             shadows_enabled: app_state.settings.shadows_enabled,
             spell_effects_enabled: app_state.settings.spell_effects_enabled,
             weather_enabled: app_state.settings.weather_enabled,
+            streamer_mode: app_state.settings.streamer_mode,
+            screen_reader: app_state.settings.screen_reader,
+            high_contrast: app_state.settings.high_contrast,
             show_names: app_state.settings.show_names,
             show_health_pct: app_state.settings.show_proz,
             hide_walls: app_state.settings.hide,
+            wall_fade_radius: app_state.settings.wall_fade_radius,
             show_helper_text: app_state.settings.show_helper_text,
             show_positions: app_state.settings.show_positions,
             master_volume: app_state.settings.master_volume,
+            music_volume: app_state.settings.music_volume,
+            effects_volume: app_state.settings.effects_volume,
+            ui_volume: app_state.settings.ui_volume,
             display_mode: app_state.settings.display_mode,
             pixel_perfect_scaling: app_state.settings.pixel_perfect_scaling,
             vsync_enabled: app_state.settings.vsync_enabled,
+            fps_cap: app_state.settings.fps_cap,
+            background_throttle: app_state.settings.background_throttle,
+            low_power_mode: app_state.settings.low_power_mode,
             last_rtt_ms: last_rtt,
             profiler_active: self.perf_profiler.is_active(),
             profiler_remaining_secs: if self.perf_profiler.is_active() {
@@ -811,6 +1019,21 @@ impl GameScene {
                     app_state.settings.weather_enabled = v;
                     profile_changed = true;
                 }
+                WidgetAction::SetStreamerMode(v) => {
+                    app_state.settings.streamer_mode = v;
+                    self.apply_streamer_mode(&app_state.settings);
+                    profile_changed = true;
+                }
+                WidgetAction::SetScreenReader(v) => {
+                    app_state.settings.screen_reader = v;
+                    self.apply_accessibility(&app_state.settings);
+                    profile_changed = true;
+                }
+                WidgetAction::SetHighContrast(v) => {
+                    app_state.settings.high_contrast = v;
+                    self.apply_accessibility(&app_state.settings);
+                    profile_changed = true;
+                }
                 WidgetAction::SetShowNames(v) => {
                     app_state.settings.show_names = v;
                     profile_changed = true;
@@ -823,6 +1046,10 @@ impl GameScene {
                     app_state.settings.hide = v;
                     profile_changed = true;
                 }
+                WidgetAction::SetWallFadeRadius(radius) => {
+                    app_state.settings.wall_fade_radius = radius;
+                    profile_changed = true;
+                }
                 WidgetAction::SetShowHelperText(v) => {
                     app_state.settings.show_helper_text = v;
                     profile_changed = true;
@@ -833,6 +1060,24 @@ impl GameScene {
                 }
                 WidgetAction::SetMasterVolume(v) => {
                     app_state.settings.master_volume = v;
+                    app_state
+                        .sfx_cache
+                        .update_music_volume(&app_state.settings.audio_mix());
+                    profile_changed = true;
+                }
+                WidgetAction::SetMusicVolume(v) => {
+                    app_state.settings.music_volume = v;
+                    app_state
+                        .sfx_cache
+                        .update_music_volume(&app_state.settings.audio_mix());
+                    profile_changed = true;
+                }
+                WidgetAction::SetEffectsVolume(v) => {
+                    app_state.settings.effects_volume = v;
+                    profile_changed = true;
+                }
+                WidgetAction::SetUiVolume(v) => {
+                    app_state.settings.ui_volume = v;
                     profile_changed = true;
                 }
                 WidgetAction::SetDisplayMode(m) => {
@@ -844,6 +1089,18 @@ impl GameScene {
                 WidgetAction::SetVSync(v) => {
                     app_state.display_command = Some(DisplayCommand::SetVSync(v));
                 }
+                WidgetAction::SetFpsCap(fps) => {
+                    app_state.settings.fps_cap = fps;
+                    profile_changed = true;
+                }
+                WidgetAction::SetBackgroundThrottle(v) => {
+                    app_state.settings.background_throttle = v;
+                    profile_changed = true;
+                }
+                WidgetAction::SetLowPowerMode(v) => {
+                    app_state.settings.low_power_mode = v;
+                    profile_changed = true;
+                }
                 WidgetAction::Disconnect => {
                     scene_change = Some(SceneType::CharacterSelection);
                 }
@@ -900,7 +1157,8 @@ impl GameScene {
         scene_change
     }
 
-    /// Forward any new log messages from `PlayerState` into the `ChatBox`.
+    /// Forward any new log messages from `PlayerState` into the `ChatBox` and
+    /// the addons.
     ///
     /// Messages are fetched in insertion order (oldest-first) starting from
     /// `last_synced_log_len` so the ChatBox receives them chronologically.
@@ -919,11 +1177,44 @@ impl GameScene {
         // retrieve what's still in the buffer.
         let fetchable = new_count.min(available);
         let start = available - fetchable;
-        let new_messages = (start..available).filter_map(|i| ps.log_message(i).cloned());
-        self.chat_box.push_messages(new_messages);
+        let new_messages: Vec<_> = (start..available)
+            .filter_map(|i| ps.log_message(i).cloned())
+            .collect();
+        for message in &new_messages {
+            self.addons.on_chat(message);
+            if let Some(announcer) = self.announcer.as_mut() {
+                announcer.chat(message);
+            }
+        }
+        self.chat_box.push_messages(new_messages.into_iter());
         self.last_synced_log_len = total_pushed;
     }
 
+    /// Reads a changed target selection to the screen reader.
+    ///
+    /// # Arguments
+    ///
+    /// * `ps` - Current player state.
+    fn announce_target(&mut self, ps: &PlayerState) {
+        let Some(announcer) = self.announcer.as_mut() else {
+            return;
+        };
+        let (nr, id) = (ps.selected_char(), ps.selected_char_id());
+        let mut proz = 0;
+        if nr != 0 {
+            for y in 0..TILEY {
+                for x in 0..TILEX {
+                    if let Some(tile) = ps.map().tile_at_xy(x, y)
+                        && tile.ch_nr == nr
+                    {
+                        proz = tile.ch_proz;
+                    }
+                }
+            }
+        }
+        announcer.target(nr, id, ps.lookup_name(nr, id), proz);
+    }
+
     fn is_selected_visible(ps: &PlayerState) -> bool {
         let selected = ps.selected_char();
         if selected == 0 {
@@ -1024,6 +1315,44 @@ impl GameScene {
             return true;
         }
 
+        if self.quest_tracker.is_visible() && self.quest_tracker.bounds().contains_point(mx, my) {
+            return true;
+        }
+
+        if self.travel_menu.is_visible() && self.travel_menu.bounds().contains_point(mx, my) {
+            return true;
+        }
+
+        if self.npc_menu.is_visible() && self.npc_menu.bounds().contains_point(mx, my) {
+            return true;
+        }
+
+        if self.trainer_popup.is_visible() && self.trainer_popup.bounds().contains_point(mx, my) {
+            return true;
+        }
+
+        if self.guild_panel.is_visible() && self.guild_panel.bounds().contains_point(mx, my) {
+            return true;
+        }
+
+        if self.mail_panel.is_visible() && self.mail_panel.bounds().contains_point(mx, my) {
+            return true;
+        }
+
+        if self.friends_panel.is_visible() && self.friends_panel.bounds().contains_point(mx, my) {
+            return true;
+        }
+
+        if self.combat_log_panel.is_visible()
+            && self.combat_log_panel.bounds().contains_point(mx, my)
+        {
+            return true;
+        }
+
+        if self.profile_panel.is_visible() && self.profile_panel.bounds().contains_point(mx, my) {
+            return true;
+        }
+
         if self.settings_panel.is_visible() && self.settings_panel.bounds().contains_point(mx, my) {
             return true;
         }
@@ -1041,8 +1370,19 @@ impl GameScene {
             || (self.talent_panel.is_visible() && self.talent_panel.bounds().contains_point(mx, my))
             || (self.quest_log_panel.is_visible()
                 && self.quest_log_panel.bounds().contains_point(mx, my))
+            || (self.profile_panel.is_visible()
+                && self.profile_panel.bounds().contains_point(mx, my))
+            || (self.guild_panel.is_visible() && self.guild_panel.bounds().contains_point(mx, my))
+            || (self.mail_panel.is_visible() && self.mail_panel.bounds().contains_point(mx, my))
+            || (self.friends_panel.is_visible()
+                && self.friends_panel.bounds().contains_point(mx, my))
+            || (self.combat_log_panel.is_visible()
+                && self.combat_log_panel.bounds().contains_point(mx, my))
             || (self.shop_panel.is_visible() && self.shop_panel.bounds().contains_point(mx, my))
             || (self.skill_picker.is_visible() && self.skill_picker.bounds().contains_point(mx, my))
+            || (self.npc_menu.is_visible() && self.npc_menu.bounds().contains_point(mx, my))
+            || (self.trainer_popup.is_visible()
+                && self.trainer_popup.bounds().contains_point(mx, my))
     }
 
     /// Draws context-sensitive helper text below and to the right of the
@@ -1065,6 +1405,10 @@ impl GameScene {
         show_helper_text: bool,
         show_positions: bool,
     ) -> Result<(), String> {
+        // Item link tooltips are part of the chat, not optional helper text.
+        if let Some(link) = self.chat_box.hovered_item_link() {
+            return self.draw_item_link_tooltip(canvas, gfx, &link);
+        }
         if !show_helper_text {
             return Ok(());
         }
@@ -1157,6 +1501,58 @@ impl GameScene {
         .map(|_| ())
     }
 
+    /// Draws the stats of a linked item near the cursor, on a dark box
+    /// placed like the helper text.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - SDL2 canvas.
+    /// * `gfx` - Graphics cache holding the bitmap fonts.
+    /// * `link` - Snapshot of the hovered item.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an SDL2 error string.
+    fn draw_item_link_tooltip(
+        &self,
+        canvas: &mut Canvas<Window>,
+        gfx: &mut GraphicsCache<'_>,
+        link: &mag_core::item_link::ItemLink,
+    ) -> Result<(), String> {
+        let lines = crate::item_links::tooltip_lines(link);
+        let line_h = crate::font_cache::BITMAP_GLYPH_H as i32;
+        let text_w = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32
+            * crate::font_cache::BITMAP_GLYPH_ADVANCE as i32;
+        let text_h = lines.len() as i32 * line_h;
+        let (x, y) = helper_text_origin(
+            self.mouse_x,
+            self.mouse_y,
+            text_w,
+            text_h,
+            TARGET_WIDTH_INT as i32,
+            TARGET_HEIGHT_INT as i32,
+        );
+
+        canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+        canvas.set_draw_color(ITEM_LINK_TOOLTIP_BG);
+        canvas.fill_rect(sdl2::rect::Rect::new(
+            x - 3,
+            y - 2,
+            text_w as u32 + 6,
+            text_h as u32 + 4,
+        ))?;
+        for (n, line) in lines.iter().enumerate() {
+            let style = if n == 0 {
+                crate::font_cache::TextStyle::drop_shadow()
+                    .with_tint(crate::ui::style::CHAT_ITEM_LINK_TINT)
+            } else {
+                crate::font_cache::TextStyle::drop_shadow()
+            };
+            crate::font_cache::draw_text(canvas, gfx, 1, line, x, y + n as i32 * line_h, style)?;
+        }
+        Ok(())
+    }
+
     /// Draw a crosshair cursor at the virtual cursor position when controller
     /// mode is active.
     ///
@@ -1241,6 +1637,10 @@ impl GameScene {
                 }
                 let cell = (gy + gx * MINIMAP_WORLD_SIZE) * 4;
 
+                if (tile.flags2 & mag_core::constants::IS_BANK) != 0 {
+                    remember_bank(&mut self.minimap_banks, tile.x, tile.y);
+                }
+
                 // Use the network-authoritative ba_sprite rather than the
                 // engine_tick-computed `tile.back` — the latter is briefly
                 // zeroed during engine_tick phase 1 and introduces an ordering
@@ -1312,9 +1712,16 @@ impl GameScene {
         let host = crate::hosts::get_host_from_api_base_url(&app_state.api.base_url)
             .unwrap_or_else(crate::hosts::get_server_ip);
 
+        let (port, transport) = match crate::hosts::get_ws_port() {
+            Some(port) => (port, GameTransport::WebSocket),
+            None => (5555, GameTransport::Tls),
+        };
+
         log::info!(
-            "GameScene: connecting to {}:5555 with ticket={} (api_base_url={})",
+            "GameScene: connecting to {}:{} ({:?}) with ticket={} (api_base_url={})",
             host,
+            port,
+            transport,
             login_target.ticket,
             app_state.api.base_url
         );
@@ -1323,9 +1730,32 @@ impl GameScene {
             net.shutdown();
         }
 
-        app_state.network = Some(NetworkRuntime::new(host, 5555, login_target.ticket));
+        // The API token lets the network thread fetch a new ticket and log
+        // back in if the connection drops.
+        let resume = app_state
+            .api
+            .token
+            .clone()
+            .map(|api_token| ResumeCredentials {
+                api_base_url: app_state.api.base_url.clone(),
+                api_token,
+                character_id: login_target.character_id,
+            });
+
+        app_state.network = Some(NetworkRuntime::new(
+            host,
+            port,
+            transport,
+            login_target.ticket,
+            resume,
+        ));
 
-        app_state.player_state = Some(PlayerState::default());
+        let mut player_state = PlayerState::default();
+        player_state.set_dialogue(
+            crate::filepaths::load_dialogue_catalog(),
+            app_state.settings.dialogue_locale(),
+        );
+        app_state.player_state = Some(player_state);
         self.pending_exit = None;
         self.certificate_mismatch = None;
         Ok(())
@@ -1371,6 +1801,9 @@ impl Scene for GameScene {
         self.stat_points_used = 0;
         self.minimap_xmap.fill(0);
         self.minimap_last_xy = None;
+        self.minimap_banks.clear();
+        self.auto_walk = None;
+        self.following = false;
         self.look_step = 0;
         self.last_look_tick = 0;
         self.autoloot_visited.clear();
@@ -1427,6 +1860,7 @@ impl Scene for GameScene {
         }
         app_state.player_state = None;
         self.weather.reset();
+        self.decals.clear();
     }
 
     /// Dispatch SDL2 events to the appropriate handler.
@@ -1484,6 +1918,26 @@ impl Scene for GameScene {
                 self.quest_log_panel.toggle();
             }
 
+            if self.profile_panel.is_visible() {
+                self.profile_panel.toggle();
+            }
+
+            if self.guild_panel.is_visible() {
+                self.guild_panel.toggle();
+            }
+
+            if self.mail_panel.is_visible() {
+                self.mail_panel.toggle();
+            }
+
+            if self.friends_panel.is_visible() {
+                self.friends_panel.toggle();
+            }
+
+            if self.combat_log_panel.is_visible() {
+                self.combat_log_panel.toggle();
+            }
+
             if self.minimap_widget.is_visible() {
                 self.minimap_widget.toggle();
             }
@@ -1582,7 +2036,12 @@ impl Scene for GameScene {
         {
             let mods = KeyModifiers::from_sdl2(*keymod);
             let has_modifier = mods.ctrl || mods.alt;
-            if (has_modifier || !self.chat_box.is_focused())
+            if (has_modifier
+                || (!self.chat_box.is_focused()
+                    && !self.profile_panel.is_text_focused()
+                    && !self.mail_panel.is_text_focused()
+                    && !self.inventory_panel.is_text_focused()
+                    && !self.shop_panel.is_text_focused()))
                 && let Some(action) = app_state
                     .settings
                     .character
@@ -1592,11 +2051,64 @@ impl Scene for GameScene {
                 match action {
                     GameAction::ToggleSkills => self.skills_panel.toggle(),
                     GameAction::ToggleInventory => self.inventory_panel.toggle(),
+                    GameAction::ToggleTalents => self.talent_panel.toggle(),
+                    GameAction::ToggleQuestLog => self.quest_log_panel.toggle(),
+                    GameAction::ToggleMinimap => self.minimap_widget.toggle(),
+                    GameAction::SaveBugReport | GameAction::SendBugReport => {
+                        app_state.bug_report_request =
+                            Some(if action == GameAction::SendBugReport {
+                                BugReportRequest::SaveAndUpload
+                            } else {
+                                BugReportRequest::Save
+                            });
+                    }
+                    GameAction::ToggleFreeCamera => {
+                        if let Some(ps) = app_state.player_state.as_mut() {
+                            self.toggle_free_camera(ps);
+                        }
+                    }
+                    GameAction::ZoomIn | GameAction::ZoomOut => {
+                        if let Some(ps) = app_state.player_state.as_ref() {
+                            let radius = Self::step_zoom(
+                                &mut app_state.settings,
+                                ps,
+                                action == GameAction::ZoomOut,
+                            );
+                            log::info!(
+                                "Camera zoom {}% (view radius {})",
+                                mag_core::view::zoom_percent(radius),
+                                radius
+                            );
+                        }
+                    }
+                    _ => {
+                        if let Some(slot) = action.skill_slot() {
+                            self.handle_skill_slot_hotkey(app_state, slot);
+                        }
+                    }
                 }
                 return None;
             }
         }
 
+        // --- Free camera: arrow keys pan the view ---
+        if let Event::KeyDown {
+            keycode: Some(kc), ..
+        } = event
+            && self.free_camera
+            && !self.chat_box.is_focused()
+            && !self.profile_panel.is_text_focused()
+            && !self.mail_panel.is_text_focused()
+            && !self.inventory_panel.is_text_focused()
+            && !self.shop_panel.is_text_focused()
+            && let Some(dir) = Self::pan_direction(*kc)
+        {
+            if let Some(ps) = app_state.player_state.as_ref() {
+                self.pan_free_camera(ps, app_state.network.as_ref(), dir);
+            }
+            return None;
+        }
+
         // --- Controller events ---
         if matches!(
             event,
@@ -1607,28 +2119,6 @@ impl Scene for GameScene {
             return self.handle_controller_event(app_state, event);
         }
 
-        // --- Num0-9 hotkeys ---
-        if let Event::KeyDown {
-            keycode: Some(kc), ..
-        } = event
-            && matches!(
-                *kc,
-                Keycode::Num0
-                    | Keycode::Num1
-                    | Keycode::Num2
-                    | Keycode::Num3
-                    | Keycode::Num4
-                    | Keycode::Num5
-                    | Keycode::Num6
-                    | Keycode::Num7
-                    | Keycode::Num8
-                    | Keycode::Num9
-            )
-        {
-            self.handle_num_hotkey(app_state, *kc);
-            return None;
-        }
-
         // --- Mouse world interactions ---
         if !ui_consumed
             && let Event::MouseButtonUp {
@@ -1667,6 +2157,41 @@ impl Scene for GameScene {
         }
         self.mode_button.update(dt);
         self.shop_panel.update(dt);
+        self.profile_panel.update(dt);
+        self.mail_panel.update(dt);
+        if self.profile_panel.is_visible()
+            && let Some(ps) = app_state.player_state.as_ref()
+        {
+            self.profile_panel
+                .sync_titles(ps.titles_earned(), ps.title_selected());
+        }
+        self.toast_stack.update(dt);
+        self.addons.update(
+            dt,
+            app_state
+                .player_state
+                .as_ref()
+                .map(|ps| ps.character_info()),
+        );
+        self.addon_panels.sync(self.addons.panels());
+        if let Some(ps) = app_state.player_state.as_mut() {
+            for event in ps.drain_client_events() {
+                self.toast_stack.push(&event);
+            }
+            if let Some((target, options)) = ps.take_npc_menu() {
+                let (x, y) = self.npc_menu_anchor;
+                self.npc_menu.open(x, y, target, options);
+            }
+            if let Some((target, offers)) = ps.take_trainer_offers() {
+                let (x, y) = self.npc_menu_anchor;
+                self.trainer_popup.open(x, y, target, offers);
+            }
+            if let Some(local) = ps.take_resync_request()
+                && let Some(net) = app_state.network.as_ref()
+            {
+                net.send(ClientCommand::new_request_resync(local));
+            }
+        }
         self.perf_profiler.check_expired();
 
         // --- Right-side HUD button fade ---
@@ -1763,7 +2288,9 @@ impl Scene for GameScene {
             self.right_stick_cooldown = (self.right_stick_cooldown - dt.as_secs_f32()).max(0.0);
 
             const RS_DEADZONE: f32 = 8000.0;
-            if self.skill_picker.is_visible() {
+            if self.radial_menu.is_visible() {
+                self.radial_menu.aim(self.right_stick_x, self.right_stick_y);
+            } else if self.skill_picker.is_visible() {
                 let rs_y = f32::from(self.right_stick_y);
                 if self.right_stick_cooldown <= 0.0 && rs_y.abs() > RS_DEADZONE {
                     self.skill_picker
@@ -1800,10 +2327,7 @@ impl Scene for GameScene {
             if pressed_at.elapsed() >= L3_HOLD_THRESHOLD {
                 self.l3_pressed_at = None; // consumed
                 if let Some(ps) = app_state.player_state.as_ref() {
-                    let (cam_xoff, cam_yoff) = Self::camera_offsets(ps);
-                    if let Some((mx, my)) =
-                        Self::screen_to_map_tile(self.mouse_x, self.mouse_y, cam_xoff, cam_yoff)
-                    {
+                    if let Some((mx, my)) = self.pick_map_tile(ps, self.mouse_x, self.mouse_y) {
                         use mag_core::constants::ISCHAR;
                         if let Some((sx, sy)) = Self::nearest_tile_with_flag(ps, mx, my, ISCHAR) {
                             let tile = ps.map().tile_at_xy(sx, sy);
@@ -1848,6 +2372,7 @@ impl Scene for GameScene {
                 self.last_look_tick = tick_now;
                 self.maybe_send_autolook_and_shop_refresh(app_state);
                 self.maybe_send_autoloot_graves(app_state);
+                self.advance_auto_walk(app_state);
             }
         }
         scene
@@ -1865,6 +2390,7 @@ impl Scene for GameScene {
         // Sync new log messages from PlayerState into the ChatBox before rendering.
         if let Some(ps) = app_state.player_state.as_ref() {
             self.sync_chat_messages(ps);
+            self.announce_target(ps);
         }
         if let Some(skills) = app_state
             .player_state
@@ -1891,13 +2417,13 @@ impl Scene for GameScene {
         };
 
         // 1. World tiles (two-pass painter order)
-        let shadows_on = settings.shadows_enabled;
+        let shadows_on = settings.shadows_active();
         let effects_on = settings.spell_effects_enabled;
 
         // Advance weather state up-front so its shake offset is available to
         // the world camera below. Rendering the weather overlay still happens
         // *after* the world pass so particles/tints layer on top.
-        if settings.weather_enabled {
+        if settings.weather_active() {
             self.weather
                 .update_auto(TARGET_WIDTH_INT as i32, TARGET_HEIGHT_INT as i32);
         } else {
@@ -1905,14 +2431,27 @@ impl Scene for GameScene {
             // server only re-sends when the resolved state changes.
             self.weather.pause();
         }
-        let camera_shake = if settings.weather_enabled {
+        let camera_shake = if settings.weather_active() {
             self.weather.shake_offset()
         } else {
             (0, 0)
         };
 
+        // Zoomed out, the world is drawn at a reduced scale into a larger
+        // logical area; the HUD below goes back to the normal scale.
+        self.view_radius = Self::effective_view_radius(settings, ps);
+        let (zoom_x, zoom_y) = Self::zoom_shift(self.view_radius);
+        let (pan_x, pan_y) = self.free_camera_shift();
+        let zoom_scale = Self::zoom_render_scale(self.view_radius);
+        self.cast_bar.y =
+            TARGET_HEIGHT_INT as i32 / 2 - (CAST_BAR_LIFT as f32 * zoom_scale).round() as i32;
+        let (scale_x, scale_y) = canvas.scale();
+
         self.perf_profiler.begin_sample(PerfLabel::DrawWorld);
-        self.draw_world(
+        if zoom_scale < 1.0 {
+            canvas.set_scale(scale_x * zoom_scale, scale_y * zoom_scale)?;
+        }
+        let world_result = self.draw_world(
             canvas,
             gfx_cache,
             ps,
@@ -1921,13 +2460,24 @@ impl Scene for GameScene {
             settings.show_names,
             settings.show_proz,
             settings.hide,
-            camera_shake,
-        )?;
+            settings.wall_fade_radius,
+            settings
+                .streamer_mode
+                .then_some(settings.streamer_alias.as_str()),
+            (
+                camera_shake.0 + zoom_x + pan_x,
+                camera_shake.1 + zoom_y + pan_y,
+            ),
+        );
+        if zoom_scale < 1.0 {
+            canvas.set_scale(scale_x, scale_y)?;
+        }
+        world_result?;
         self.perf_profiler.end_sample(PerfLabel::DrawWorld);
 
         // 1b. Weather / ambient overlay (rendered above world tiles, below HUD).
         self.perf_profiler.begin_sample(PerfLabel::DrawWeather);
-        if settings.weather_enabled {
+        if settings.weather_active() {
             self.weather.render_post_world(canvas)?;
         }
         self.perf_profiler.end_sample(PerfLabel::DrawWeather);
@@ -1958,6 +2508,14 @@ impl Scene for GameScene {
                 self.spell_effect_icons.negative_right_x = wap.x + wap.width as i32;
                 self.rank_progress_line.sync(ci.points_tot as u32);
                 self.mode_button.sync(ci.mode);
+                let cast = ps.skill_cast().map(|(skill, timer)| {
+                    let name = match crate::ui::visuals::spell_icons::spell_icon_meta(skill) {
+                        Some(meta) => meta.name,
+                        None => mag_core::skills::get_skill_name(skill),
+                    };
+                    (name, 1.0 - timer.fraction_remaining())
+                });
+                self.cast_bar.sync(cast);
                 self.vitality_bars.sync(
                     ci.a_hp,
                     i32::from(ci.hp[5]),
@@ -1966,8 +2524,21 @@ impl Scene for GameScene {
                     ci.a_mana,
                     i32::from(ci.mana[5]),
                 );
+                if let Some(announcer) = self.announcer.as_mut() {
+                    announcer.vitals(
+                        ci.a_hp,
+                        i32::from(ci.hp[5]),
+                        ci.a_mana,
+                        i32::from(ci.mana[5]),
+                    );
+                }
+                let server_now = app_state
+                    .network
+                    .as_ref()
+                    .map(|net| net.clock.now())
+                    .unwrap_or_default();
                 self.spell_effect_icons
-                    .sync(&ci.spell, &ci.active, &ci.spell_type);
+                    .sync(&ci.spell, &ci.active, &ci.spell_type, server_now);
                 use crate::ui::hud::skills_panel::{SkillsPanel as SP, SkillsPanelData};
                 let sorted = SP::build_sorted_skills(&ci.skill);
                 self.skills_panel.update_data(SkillsPanelData {
@@ -1984,6 +2555,8 @@ impl Scene for GameScene {
                 self.hud_buttons.set_talent_points_badge(
                     mag_core::talent_trees::available_talent_points(ps.talents()),
                 );
+                self.chat_box
+                    .set_display_own_name(mag_core::string_operations::c_string_to_str(&ci.name));
                 use crate::ui::hud::inventory_panel::InventoryPanelData;
                 self.inventory_panel.update_data(InventoryPanelData {
                     items: ci.item,
@@ -1993,8 +2566,16 @@ impl Scene for GameScene {
                     citem: ci.citem,
                     citem_p: ci.citem_p,
                     gold: ci.gold,
+                    hide_gold: app_state.settings.streamer_mode,
                     selected_char: ps.selected_char(),
+                    item_names: ps.item_names().clone(),
                 });
+                self.guild_panel.update_data(ps.guild());
+                self.mail_panel.update_data(ps.mailbox());
+                self.friends_panel.update_data(ps.friends());
+                if self.combat_log_panel.is_visible() {
+                    self.combat_log_panel.update_data(ps.combat_log().fights());
+                }
 
                 // Skill bar: keybinds for the 11 assignable skill slots.
                 {
@@ -2011,13 +2592,33 @@ impl Scene for GameScene {
                     );
                     let show_secondary =
                         self.effective_shift_held() || (self.controller_mode && self.lt_held);
+                    let shown = if show_secondary {
+                        &secondary_keybinds
+                    } else {
+                        &keybinds
+                    };
+                    let cooldowns =
+                        shown.map(|skill| skill.and_then(|skill| ps.skill_cooldown(skill)));
                     self.skill_bar.update_data(SkillBarData {
                         keybinds,
                         secondary_keybinds,
                         show_secondary,
+                        cooldowns,
                     });
                 }
 
+                // Click-to-move route preview to the hovered tile; an
+                // observer's click moves the camera, so it has no route.
+                let preview_goal = if !ps.is_observer()
+                    && self.resolve_helper_text(ps) == Some("WALK")
+                {
+                    self.pick_map_tile(ps, self.mouse_x, self.mouse_y)
+                        .filter(|&(mx, my)| mx.abs_diff(TILEX / 2).max(my.abs_diff(TILEY / 2)) > 1)
+                } else {
+                    None
+                };
+                self.path_preview.update(ps.map(), preview_goal);
+
                 // Update minimap xmap buffer, then push viewport pixels to the widget.
                 if let Some((cx, cy)) = self.update_minimap_xmap(gfx_cache, ps) {
                     self.minimap_widget
@@ -2029,6 +2630,7 @@ impl Scene for GameScene {
                     use crate::ui::hud::quest_log_panel::{
                         QuestEntryDisplay, QuestLogPanelData, QuestTitle,
                     };
+                    use crate::ui::hud::quest_tracker::QuestTrackerEntry;
                     let catalog = ps.quest_catalog();
                     let counts = ps.quest_completion_counts();
                     let active_template = ps.active_quest_template_id();
@@ -2036,6 +2638,7 @@ impl Scene for GameScene {
                     let active_npc_pos = ps.active_quest_npc_pos();
 
                     let mut display_entries: Vec<QuestEntryDisplay> = Vec::new();
+                    let mut tracker_entries: Vec<QuestTrackerEntry> = Vec::new();
                     for (idx, entry) in catalog.iter().enumerate() {
                         let count = counts.get(idx).copied().unwrap_or(-1);
                         // Skip quests the player has not yet discovered
@@ -2088,6 +2691,23 @@ impl Scene for GameScene {
                                     Vec::new(),
                                 ),
                             };
+                        tracker_entries.push(QuestTrackerEntry {
+                            template_id: entry.template_id,
+                            objective: tracker_objective(
+                                entry,
+                                if entry.template_id == active_template {
+                                    active_step_idx
+                                } else {
+                                    0
+                                },
+                            ),
+                            completed: count.clamp(0, i16::from(u8::MAX)) as u8,
+                            stages: if entry.repeatable {
+                                0
+                            } else {
+                                entry.stages.max(1)
+                            },
+                        });
                         for _ in 0..stage_rows {
                             display_entries.push(QuestEntryDisplay {
                                 template_id: entry.template_id,
@@ -2104,6 +2724,8 @@ impl Scene for GameScene {
                         entries: display_entries,
                         active_template_id: active_template,
                     });
+                    self.quest_tracker
+                        .update_data(tracker_entries, active_template);
 
                     // Minimap markers: every quest giver in the catalog.
                     let givers: Vec<(u16, u16)> =
@@ -2115,6 +2737,52 @@ impl Scene for GameScene {
                     };
                     self.minimap_widget.set_quest_markers(givers, active_marker);
                 }
+
+                // Travel menu, stacked below the quest tracker.
+                {
+                    use crate::ui::hud::travel_menu::TravelMenuEntry;
+                    let entries = app_state
+                        .settings
+                        .character
+                        .bookmarks
+                        .iter()
+                        .map(|b| TravelMenuEntry {
+                            name: b.name.clone(),
+                            x: b.x,
+                            y: b.y,
+                        })
+                        .collect();
+                    self.travel_menu
+                        .update_data(entries, self.auto_walk.as_ref().map(|w| w.name.clone()));
+                    let y = if self.quest_tracker.is_visible() {
+                        let b = self.quest_tracker.bounds();
+                        b.y + b.height as i32 + 4
+                    } else {
+                        QUEST_TRACKER_Y
+                    };
+                    self.travel_menu.set_position(QUEST_TRACKER_X, y);
+                }
+
+                // Minimap points of interest.
+                {
+                    use mag_core::map_markers::MapMarkerKind;
+                    let mut markers: Vec<(MapMarkerKind, u16, u16)> = [
+                        MapMarkerKind::Temple,
+                        MapMarkerKind::Tavern,
+                        MapMarkerKind::Death,
+                    ]
+                    .into_iter()
+                    .filter_map(|kind| ps.map_marker(kind).map(|(x, y)| (kind, x, y)))
+                    .collect();
+                    markers.extend(
+                        self.minimap_banks
+                            .iter()
+                            .map(|&(x, y)| (MapMarkerKind::Bank, x, y)),
+                    );
+                    let enabled = MapMarkerKind::ALL
+                        .map(|kind| app_state.settings.minimap_markers.enabled(kind));
+                    self.minimap_widget.set_poi_markers(markers, enabled);
+                }
             }
             let mut ctx = RenderContext {
                 canvas,
@@ -2126,6 +2794,7 @@ impl Scene for GameScene {
             }
             self.weapon_armor_panel.render(&mut ctx)?;
             self.vitality_bars.render(&mut ctx)?;
+            self.cast_bar.render(&mut ctx)?;
             self.spell_effect_icons.render(&mut ctx)?;
         }
         self.perf_profiler.end_sample(PerfLabel::SyncAndDrawStatus);
@@ -2138,11 +2807,19 @@ impl Scene for GameScene {
                 gfx: gfx_cache,
                 text: text_engine,
             };
+            self.quest_tracker.render(&mut ctx)?;
+            self.travel_menu.render(&mut ctx)?;
+            self.addon_panels.render(&mut ctx)?;
             self.skills_panel.render(&mut ctx)?;
             self.inventory_panel.render(&mut ctx)?;
             self.settings_panel.render(&mut ctx)?;
             self.talent_panel.render(&mut ctx)?;
             self.quest_log_panel.render(&mut ctx)?;
+            self.profile_panel.render(&mut ctx)?;
+            self.guild_panel.render(&mut ctx)?;
+            self.mail_panel.render(&mut ctx)?;
+            self.friends_panel.render(&mut ctx)?;
+            self.combat_log_panel.render(&mut ctx)?;
             self.hud_buttons.render(&mut ctx)?;
             self.minimap_widget.render(&mut ctx)?;
             self.mode_button.render(&mut ctx)?;
@@ -2150,6 +2827,10 @@ impl Scene for GameScene {
             self.weapon_armor_panel.render(&mut ctx)?;
             self.rank_progress_line.render(&mut ctx)?;
             self.skill_picker.render(&mut ctx)?;
+            self.trainer_popup.render(&mut ctx)?;
+            self.npc_menu.render(&mut ctx)?;
+            self.toast_stack.render(&mut ctx)?;
+            self.radial_menu.render(&mut ctx)?;
         }
         self.perf_profiler.end_sample(PerfLabel::DrawHudPanels);
 
@@ -2186,6 +2867,7 @@ impl Scene for GameScene {
                     citem: ps.character_info().citem,
                     visible: ps.should_show_shop(),
                     is_grave: ps.shop_is_grave(),
+                    page: ps.consignment_page().cloned(),
                 });
             }
             let mut ctx = RenderContext {
@@ -2250,7 +2932,7 @@ mod tests {
     use super::{
         GameScene, HELPER_TEXT_CURSOR_FLIP_GAP_Y, HELPER_TEXT_CURSOR_GAP_X,
         HELPER_TEXT_CURSOR_GAP_Y, HELPER_TEXT_SCREEN_MARGIN, helper_text_origin,
-        normalize_lava_blast_keybind_arrays,
+        normalize_lava_blast_keybind_arrays, remember_bank, tracker_objective,
     };
     use mag_core::skills::{SK_BLAST, SK_LAVA_BLAST, SkillIndex};
 
@@ -2261,6 +2943,15 @@ mod tests {
         [[0; SkillIndex::MaxIndex as usize]; 100]
     }
 
+    #[test]
+    fn bank_tiles_merge_into_one_marker_per_bank() {
+        let mut banks = Vec::new();
+        remember_bank(&mut banks, 100, 100);
+        remember_bank(&mut banks, 105, 110);
+        remember_bank(&mut banks, 200, 100);
+        assert_eq!(banks, vec![(100, 100), (200, 100)]);
+    }
+
     #[test]
     fn helper_text_default_anchor_in_center() {
         let (x, y) = helper_text_origin(400, 300, 60, 20, SCREEN_W, SCREEN_H);
@@ -2293,6 +2984,17 @@ mod tests {
         assert_eq!(x, HELPER_TEXT_SCREEN_MARGIN);
     }
 
+    #[test]
+    fn tracker_objective_falls_back_to_bring_item_wording() {
+        let entry = mag_core::quest_defs::QuestCatalogEntry {
+            template_id: 0,
+            npc_name: "Jamil".to_owned(),
+            item_name: "Silver Ring".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(tracker_objective(&entry, 3), "Bring Silver Ring to Jamil");
+    }
+
     #[test]
     fn effective_modifiers_include_mouse_held_state() {
         let mut scene = GameScene::new();
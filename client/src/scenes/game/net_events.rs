@@ -1,11 +1,15 @@
 use mag_core::client_commands::ClientCommand;
 use mag_core::constants::{IS_GRAVE, TILEX, TILEY};
+use mag_core::disconnect::describe_disconnect;
+use mag_core::logout_reasons::LogoutReason;
 use mag_core::server_commands::{ServerCommand, ServerCommandData};
 use mag_core::skills;
 
 use crate::{
+    addons::AddonStatus,
     cert_trust,
     network::NetworkEvent,
+    preferences,
     scenes::scene::SceneType,
     state::AppState,
     ui::{
@@ -15,6 +19,10 @@ use crate::{
     },
 };
 
+use super::auto_walk::{
+    AutoWalk, AutoWalkStep, TravelCommand, find_bookmark, follow_command, parse_coords,
+    travel_command,
+};
 use super::{GameScene, MAX_TICK_GROUPS_PER_FRAME, QSIZE};
 
 /// Result of routing a [`UiEvent`] through the widget stack.
@@ -71,11 +79,34 @@ impl GameScene {
                     }
                     self.pending_exit = Some(e);
                 }
-                NetworkEvent::LoggedIn => {
+                NetworkEvent::Reconnecting { attempt, delay } => {
+                    log::warn!(
+                        "Connection lost; reconnect attempt {} in {:?}",
+                        attempt,
+                        delay
+                    );
+                    if let Some(net) = app_state.network.as_mut() {
+                        net.logged_in = false;
+                    }
+                    if attempt == 1
+                        && let Some(ps) = app_state.player_state.as_mut()
+                    {
+                        ps.tlog(0, "Connection lost. Reconnecting...");
+                    }
+                }
+                NetworkEvent::Resumed => {
+                    if let Some(ps) = app_state.player_state.as_mut() {
+                        ps.reset_for_resume();
+                        ps.tlog(1, "Reconnected.");
+                    }
                     if let Some(net) = app_state.network.as_mut() {
-                        net.logged_in = true;
+                        net.on_resumed();
                     }
-                    log::info!("Logged in to game server");
+                    self.minimap_last_xy = None;
+                    Self::finish_login(app_state);
+                }
+                NetworkEvent::LoggedIn => {
+                    Self::finish_login(app_state);
                 }
                 NetworkEvent::Bytes { bytes, received_at } => {
                     if bytes.is_empty() {
@@ -89,15 +120,43 @@ impl GameScene {
                                     net.handle_pong(*seq, received_at);
                                 }
                             }
+                            ServerCommandData::TimeSync { tick, unix_ms } => {
+                                if let Some(net) = app_state.network.as_mut() {
+                                    net.handle_time_sync(*tick, *unix_ms, received_at);
+                                }
+                            }
+                            ServerCommandData::CommandAck { seq } => {
+                                if let Some(net) = app_state.network.as_mut() {
+                                    net.handle_command_ack(*seq);
+                                }
+                            }
                             ServerCommandData::PlaySound { nr, vol, pan } => {
                                 log::info!("PlaySound: nr={} vol={} pan={}", nr, vol, pan);
                                 app_state.sfx_cache.play_sfx(
                                     *nr as usize,
                                     *vol,
                                     *pan,
-                                    app_state.settings.master_volume,
+                                    &app_state.settings.audio_mix(),
+                                );
+                            }
+                            ServerCommandData::PlaySoundAt { nr, dx, dy } => {
+                                log::debug!("PlaySoundAt: nr={} dx={} dy={}", nr, dx, dy);
+                                app_state.sfx_cache.play_sfx_at(
+                                    *nr as usize,
+                                    i32::from(*dx),
+                                    i32::from(*dy),
+                                    &app_state.settings.audio_mix(),
                                 );
                             }
+                            ServerCommandData::MapDecal {
+                                x,
+                                y,
+                                sprite,
+                                ttl,
+                                kind,
+                            } => {
+                                self.decals.add(*x, *y, *sprite, *ttl, *kind, received_at);
+                            }
                             ServerCommandData::SetWeather {
                                 kind,
                                 intensity,
@@ -120,6 +179,11 @@ impl GameScene {
                                     *flags,
                                 );
                             }
+                            ServerCommandData::Disconnect { reason, text } => {
+                                log::info!("Server is disconnecting us ({}): {}", reason, text);
+                                app_state.disconnect_message =
+                                    Some(describe_disconnect(LogoutReason::from(*reason), text));
+                            }
                             ServerCommandData::Exit { reason } => {
                                 log::info!("Received exit command from server: {}", reason);
                                 if let Some(ps) = app_state.player_state.as_mut() {
@@ -150,19 +214,38 @@ impl GameScene {
             }
         }
 
+        // The character selection scene shows `disconnect_message`; a
+        // server without `SV_DISCONNECT` still leaves the reason's label.
         if let Some(ps) = app_state.player_state.as_mut()
-            && ps.take_exit_requested_reason().is_some()
+            && let Some(reason) = ps.take_exit_requested_reason()
         {
+            let reason = LogoutReason::from(reason as u8);
+            if reason != LogoutReason::Exit && app_state.disconnect_message.is_none() {
+                app_state.disconnect_message = Some(describe_disconnect(reason, ""));
+            }
             return Some(SceneType::CharacterSelection);
         }
 
-        if self.pending_exit.take().is_some() {
+        if let Some(error) = self.pending_exit.take() {
+            app_state.disconnect_message.get_or_insert(error);
             return Some(SceneType::CharacterSelection);
         }
 
         None
     }
 
+    /// Marks the session logged in and tells the server the dialogue
+    /// language, after a first login or a resumed one.
+    fn finish_login(app_state: &mut AppState<'_>) {
+        if let Some(net) = app_state.network.as_mut() {
+            net.logged_in = true;
+            net.send(ClientCommand::new_client_locale(
+                &app_state.settings.dialogue_locale(),
+            ));
+        }
+        log::info!("Logged in to game server");
+    }
+
     /// Periodically sends auto-look commands (for nameplates) and shop refresh.
     ///
     /// Called once per server tick. Increments an internal step counter and fires
@@ -240,11 +323,304 @@ impl GameScene {
         }
     }
 
+    /// Advances the auto-walk in progress, if any: sends the next leg of the
+    /// route, or ends the walk on arrival, damage, combat or a dead end.
+    ///
+    /// Called once per server tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (network, player state).
+    pub(super) fn advance_auto_walk(&mut self, app_state: &mut AppState<'_>) {
+        let Some(walk) = self.auto_walk.as_mut() else {
+            return;
+        };
+        let (Some(net), Some(ps)) = (app_state.network.as_ref(), app_state.player_state.as_mut())
+        else {
+            return;
+        };
+        let info = ps.character_info();
+        let fighting = info.attack_cn != 0;
+        let (font, message) = match walk.tick(ps.map(), info.a_hp, info.attack_cn) {
+            AutoWalkStep::Walking => return,
+            AutoWalkStep::Leg(waypoints) => {
+                for packet in ClientCommand::new_waypoint_packets(&waypoints) {
+                    net.send(packet);
+                }
+                return;
+            }
+            AutoWalkStep::Arrived => (2, format!("Arrived at {}.", walk.name)),
+            AutoWalkStep::Interrupted => {
+                // Stop the current leg, but never cancel an attack.
+                if !fighting {
+                    net.send(ClientCommand::new_reset());
+                }
+                (
+                    0,
+                    format!("Stopped walking to {}: under attack.", walk.name),
+                )
+            }
+            AutoWalkStep::Stuck => (
+                0,
+                format!("Stopped walking to {}: no way found.", walk.name),
+            ),
+        };
+        ps.tlog(font, message);
+        self.auto_walk = None;
+    }
+
+    /// Cancels the auto-walk in progress and stops the current leg.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (network, player state).
+    fn stop_auto_walk(&mut self, app_state: &mut AppState<'_>) {
+        let Some(ps) = app_state.player_state.as_mut() else {
+            return;
+        };
+        match self.auto_walk.take() {
+            Some(walk) => {
+                if let Some(net) = app_state.network.as_ref() {
+                    net.send(ClientCommand::new_reset());
+                }
+                ps.tlog(1, format!("Stopped walking to {}.", walk.name));
+            }
+            None => ps.tlog(1, "You are not walking anywhere."),
+        }
+    }
+
+    /// Starts an auto-walk, replacing any walk in progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (player state).
+    /// * `name` - Destination name for messages.
+    /// * `target` - Destination world tile.
+    fn start_auto_walk(&mut self, app_state: &mut AppState<'_>, name: String, target: (u16, u16)) {
+        let Some(ps) = app_state.player_state.as_mut() else {
+            return;
+        };
+        ps.tlog(
+            1,
+            format!(
+                "Walking to {name} ({}, {}). Type /stop to cancel.",
+                target.0, target.1
+            ),
+        );
+        self.auto_walk = Some(AutoWalk::new(name, target, ps.character_info().a_hp));
+    }
+
+    /// Resolves a `/go` destination: a bookmark, `<x> <y>`, or one of the
+    /// known points of interest (`temple`, `tavern`, `bank`).
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (settings, player state).
+    /// * `args` - Text after `/go`, already trimmed.
+    ///
+    /// # Returns
+    ///
+    /// * The destination name and world tile, or `None` if unknown.
+    fn resolve_destination(
+        &self,
+        app_state: &AppState<'_>,
+        args: &str,
+    ) -> Option<(String, (u16, u16))> {
+        use mag_core::map_markers::MapMarkerKind;
+
+        let bookmarks = &app_state.settings.character.bookmarks;
+        if let Some(idx) = find_bookmark(bookmarks, args) {
+            let b = &bookmarks[idx];
+            return Some((b.name.clone(), (b.x, b.y)));
+        }
+        if let Some(pos) = parse_coords(args) {
+            return Some((format!("{},{}", pos.0, pos.1), pos));
+        }
+        let ps = app_state.player_state.as_ref()?;
+        let target = match args.to_ascii_lowercase().as_str() {
+            "temple" => ps.map_marker(MapMarkerKind::Temple)?,
+            "tavern" => ps.map_marker(MapMarkerKind::Tavern)?,
+            "bank" => {
+                let here = ps
+                    .map()
+                    .tile_at_xy(TILEX / 2, TILEY / 2)
+                    .map(|t| (t.x, t.y))?;
+                self.minimap_banks
+                    .iter()
+                    .copied()
+                    .min_by_key(|&bank| mag_core::waypoints::span(here, bank))?
+            }
+            _ => return None,
+        };
+        Some((args.to_ascii_lowercase(), target))
+    }
+
+    /// Bookmarks the player's current position, replacing a bookmark of
+    /// the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (settings, player state).
+    /// * `name` - Bookmark name; a free "Spot N" name is picked if empty.
+    fn bookmark_here(&mut self, app_state: &mut AppState<'_>, name: &str) {
+        let Some(ps) = app_state.player_state.as_mut() else {
+            return;
+        };
+        let Some((x, y)) = ps
+            .map()
+            .tile_at_xy(TILEX / 2, TILEY / 2)
+            .map(|t| (t.x, t.y))
+        else {
+            return;
+        };
+        let bookmarks = &mut app_state.settings.character.bookmarks;
+        let name = if name.is_empty() {
+            (1..)
+                .map(|n| format!("Spot {n}"))
+                .find(|n| find_bookmark(bookmarks, n).is_none())
+                .unwrap_or_default()
+        } else {
+            name.to_owned()
+        };
+        match find_bookmark(bookmarks, &name) {
+            Some(idx) => {
+                bookmarks[idx].x = x;
+                bookmarks[idx].y = y;
+            }
+            None if bookmarks.len() >= preferences::MAX_BOOKMARKS => {
+                ps.tlog(
+                    0,
+                    format!(
+                        "You can keep at most {} bookmarks. Remove one with /unmark first.",
+                        preferences::MAX_BOOKMARKS
+                    ),
+                );
+                return;
+            }
+            None => bookmarks.push(preferences::Bookmark {
+                name: name.clone(),
+                x,
+                y,
+            }),
+        }
+        ps.tlog(2, format!("Bookmarked {name} at {x}, {y}."));
+        self.save_active_profile(app_state);
+    }
+
+    /// Deletes the bookmark at `idx`.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (settings, player state).
+    /// * `idx` - Index into the character's bookmarks.
+    fn remove_bookmark(&mut self, app_state: &mut AppState<'_>, idx: usize) {
+        let bookmarks = &mut app_state.settings.character.bookmarks;
+        if idx >= bookmarks.len() {
+            return;
+        }
+        let removed = bookmarks.remove(idx);
+        if let Some(ps) = app_state.player_state.as_mut() {
+            ps.tlog(1, format!("Removed bookmark {}.", removed.name));
+        }
+        self.save_active_profile(app_state);
+    }
+
+    /// Applies a travel chat command (`/go`, `/mark`, `/unmark`, `/marks`,
+    /// `/stop`, `/travel`).
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state.
+    /// * `command` - The command.
+    /// * `args` - Its arguments, already trimmed.
+    fn handle_travel_command(
+        &mut self,
+        app_state: &mut AppState<'_>,
+        command: TravelCommand,
+        args: &str,
+    ) {
+        let mut reply: Option<(u8, String)> = None;
+        match command {
+            TravelCommand::Go if args.is_empty() => {
+                reply = Some((
+                    1,
+                    "Usage: /go <bookmark | x y | temple | tavern | bank>".into(),
+                ));
+            }
+            TravelCommand::Go => match self.resolve_destination(app_state, args) {
+                Some((name, target)) => self.start_auto_walk(app_state, name, target),
+                None => reply = Some((0, format!("Unknown destination: {args}."))),
+            },
+            TravelCommand::Mark => self.bookmark_here(app_state, args),
+            TravelCommand::Unmark => {
+                match find_bookmark(&app_state.settings.character.bookmarks, args) {
+                    Some(idx) => self.remove_bookmark(app_state, idx),
+                    None => reply = Some((0, format!("No bookmark named {args}."))),
+                }
+            }
+            TravelCommand::List => {
+                let bookmarks = &app_state.settings.character.bookmarks;
+                let text = if bookmarks.is_empty() {
+                    "No bookmarks. Add one with /mark <name>.".to_owned()
+                } else {
+                    let names: Vec<String> = bookmarks
+                        .iter()
+                        .map(|b| format!("{} ({}, {})", b.name, b.x, b.y))
+                        .collect();
+                    format!("Bookmarks: {}", names.join(", "))
+                };
+                reply = Some((1, text));
+            }
+            TravelCommand::Stop => self.stop_auto_walk(app_state),
+            TravelCommand::Menu => self.travel_menu.toggle(),
+        }
+        if let (Some((font, text)), Some(ps)) = (reply, app_state.player_state.as_mut()) {
+            ps.tlog(font, text);
+        }
+    }
+
+    /// Drain pending `WidgetAction`s from the travel menu.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state.
+    pub(crate) fn process_travel_menu_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.travel_menu.take_actions() {
+            match action {
+                WidgetAction::TravelTo { index } => {
+                    self.play_click_sound(app_state);
+                    if let Some(b) = app_state.settings.character.bookmarks.get(index).cloned() {
+                        self.start_auto_walk(app_state, b.name, (b.x, b.y));
+                    }
+                }
+                WidgetAction::RemoveBookmark { index } => {
+                    self.play_click_sound(app_state);
+                    self.remove_bookmark(app_state, index);
+                }
+                WidgetAction::BookmarkHere => {
+                    self.play_click_sound(app_state);
+                    self.bookmark_here(app_state, "");
+                }
+                WidgetAction::StopAutoWalk => {
+                    self.play_click_sound(app_state);
+                    self.stop_auto_walk(app_state);
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Drain pending `WidgetAction`s from the chat box and act on them.
     ///
     /// Intercepts the `/autoloot` command client-side: toggles per-character
     /// auto-loot and prints a confirmation to the chat log without sending
-    /// anything to the server.  All other text is forwarded as say-packets.
+    /// anything to the server.  `/streamer` toggles streamer mode and
+    /// `/streamer alias <name>` sets its display alias.  `/screenreader` and
+    /// `/contrast` toggle the accessibility modes.  `/addon` lists,
+    /// toggles and reloads client addons.  `/go`, `/mark`, `/unmark`,
+    /// `/marks`, `/stop` and `/travel` manage bookmarks and auto-walk.
+    /// `/profile`, `/guild` and `/mail` toggle their panels.  All other text
+    /// is forwarded as say-packets.
     ///
     /// # Arguments
     ///
@@ -266,6 +642,69 @@ impl GameScene {
                     self.save_active_profile(app_state);
                     continue;
                 }
+                if let Some(args) = streamer_command_args(&text) {
+                    self.handle_streamer_command(app_state, args);
+                    continue;
+                }
+                if text.trim().eq_ignore_ascii_case("/screenreader") {
+                    app_state.settings.screen_reader = !app_state.settings.screen_reader;
+                    self.apply_accessibility(&app_state.settings);
+                    let status = if app_state.settings.screen_reader {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    };
+                    if let Some(ps) = app_state.player_state.as_mut() {
+                        ps.tlog(1, format!("Screen reader: {status}."));
+                    }
+                    self.save_active_profile(app_state);
+                    continue;
+                }
+                if text.trim().eq_ignore_ascii_case("/contrast") {
+                    app_state.settings.high_contrast = !app_state.settings.high_contrast;
+                    self.apply_accessibility(&app_state.settings);
+                    let status = if app_state.settings.high_contrast {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    };
+                    if let Some(ps) = app_state.player_state.as_mut() {
+                        ps.tlog(1, format!("High contrast HUD: {status}."));
+                    }
+                    self.save_active_profile(app_state);
+                    continue;
+                }
+                if let Some(args) = addon_command_args(&text) {
+                    self.handle_addon_command(app_state, args);
+                    continue;
+                }
+                if let Some((command, args)) = travel_command(&text) {
+                    self.handle_travel_command(app_state, command, args);
+                    continue;
+                }
+                if text.trim().eq_ignore_ascii_case("/profile") {
+                    self.profile_panel.toggle();
+                    continue;
+                }
+                if text.trim().eq_ignore_ascii_case("/guild") {
+                    self.guild_panel.toggle();
+                    continue;
+                }
+                if text.trim().eq_ignore_ascii_case("/mail") {
+                    self.mail_panel.toggle();
+                    continue;
+                }
+                if text.trim().eq_ignore_ascii_case("/friends") {
+                    self.friends_panel.toggle();
+                    continue;
+                }
+                if text.trim().eq_ignore_ascii_case("/combatlog") {
+                    self.combat_log_panel.toggle();
+                    continue;
+                }
+                if let Some(start) = follow_command(&text) {
+                    self.following = start;
+                }
                 if let Some(net) = app_state.network.as_ref() {
                     for pkt in ClientCommand::new_say_packets(text.as_bytes()) {
                         net.send(pkt);
@@ -275,6 +714,137 @@ impl GameScene {
         }
     }
 
+    /// Tells the server to stop following when the player takes over.
+    ///
+    /// Does nothing unless a `/follow` is active.
+    pub(super) fn cancel_follow(&mut self, app_state: &AppState) {
+        if !std::mem::take(&mut self.following) {
+            return;
+        }
+        if let Some(net) = app_state.network.as_ref() {
+            for pkt in ClientCommand::new_say_packets(b"#follow self") {
+                net.send(pkt);
+            }
+        }
+    }
+
+    /// Applies an `/addon` chat command.
+    ///
+    /// Without arguments (or with `list`) the installed addons are listed;
+    /// `enable <name>` / `disable <name>` toggle one and `reload` re-reads
+    /// the addon folder.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (settings access).
+    /// * `args` - Text after `/addon`, already trimmed.
+    fn handle_addon_command(&mut self, app_state: &mut AppState, args: &str) {
+        let (verb, name) = args.split_once(' ').unwrap_or((args, ""));
+        let name = name.trim();
+        let verb = verb.to_ascii_lowercase();
+
+        let mut lines: Vec<(u8, String)> = Vec::new();
+        match verb.as_str() {
+            "" | "list" => {
+                let statuses = self.addons.statuses();
+                if statuses.is_empty() {
+                    lines.push((
+                        1,
+                        format!(
+                            "No addons installed in {}.",
+                            preferences::addons_directory_path().display()
+                        ),
+                    ));
+                }
+                for (addon, status) in statuses {
+                    let line = match status {
+                        AddonStatus::Enabled => (2, format!("{addon}: enabled")),
+                        AddonStatus::Disabled => (1, format!("{addon}: disabled")),
+                        AddonStatus::Failed(err) => (0, format!("{addon}: error: {err}")),
+                    };
+                    lines.push(line);
+                }
+            }
+            "enable" | "disable" if !name.is_empty() => {
+                let known = self
+                    .addons
+                    .statuses()
+                    .into_iter()
+                    .find(|(addon, _)| addon.eq_ignore_ascii_case(name));
+                match known {
+                    Some((addon, _)) => {
+                        let disabled = &mut app_state.settings.disabled_addons;
+                        disabled.retain(|d| !d.eq_ignore_ascii_case(&addon));
+                        if verb == "disable" {
+                            disabled.push(addon.clone());
+                        }
+                        self.load_addons(&app_state.settings);
+                        self.save_active_profile(app_state);
+                        lines.push((1, format!("Addon {addon} {verb}d.")));
+                    }
+                    None => lines.push((0, format!("No addon named {name}."))),
+                }
+            }
+            "reload" => {
+                self.load_addons(&app_state.settings);
+                lines.push((
+                    1,
+                    format!("Reloaded {} addon(s).", self.addons.statuses().len()),
+                ));
+            }
+            _ => lines.push((
+                0,
+                "Usage: /addon [list|reload|enable <name>|disable <name>]".to_owned(),
+            )),
+        }
+
+        if let Some(ps) = app_state.player_state.as_mut() {
+            for (font, line) in lines {
+                ps.tlog(font, line);
+            }
+        }
+    }
+
+    /// Applies a `/streamer` chat command.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (settings access).
+    /// * `args` - Text after `/streamer`, already trimmed.
+    fn handle_streamer_command(&mut self, app_state: &mut AppState, args: &str) {
+        let message = if args.is_empty() {
+            app_state.settings.streamer_mode = !app_state.settings.streamer_mode;
+            if app_state.settings.streamer_mode {
+                format!(
+                    "Streamer mode enabled (alias: {}).",
+                    app_state.settings.streamer_alias
+                )
+            } else {
+                "Streamer mode disabled.".to_owned()
+            }
+        } else if let Some(alias) = args
+            .strip_prefix("alias")
+            .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+        {
+            app_state.settings.streamer_alias = crate::streamer_mode::sanitize_alias(alias);
+            format!(
+                "Streamer alias set to {}.",
+                app_state.settings.streamer_alias
+            )
+        } else {
+            if let Some(ps) = app_state.player_state.as_mut() {
+                ps.tlog(0, "Usage: /streamer [alias <name>]");
+            }
+            return;
+        };
+
+        self.apply_streamer_mode(&app_state.settings);
+        if let Some(ps) = app_state.player_state.as_mut() {
+            ps.tlog(1, message);
+        }
+        self.save_active_profile(app_state);
+    }
+
     /// Drain pending `WidgetAction`s from the mode button and send mode
     /// commands to the server.
     ///
@@ -291,6 +861,27 @@ impl GameScene {
         }
     }
 
+    /// Drain minimap legend toggles and persist them to the active profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (settings and profile).
+    pub(crate) fn process_minimap_actions(&mut self, app_state: &mut AppState<'_>) {
+        let mut changed = false;
+        for action in self.minimap_widget.take_actions() {
+            if let WidgetAction::SetMinimapMarker { kind, enabled } = action {
+                app_state
+                    .settings
+                    .minimap_markers
+                    .set_enabled(kind, enabled);
+                changed = true;
+            }
+        }
+        if changed {
+            self.save_active_profile(app_state);
+        }
+    }
+
     /// Drain and process actions produced by the skills panel.
     ///
     /// # Arguments
@@ -539,6 +1130,15 @@ impl GameScene {
                         net.send(ClientCommand::new_inv_look(a, b, c));
                     }
                 }
+                WidgetAction::SortInventory(key) => {
+                    if let Some(net) = app_state.network.as_ref() {
+                        self.play_click_sound(app_state);
+                        net.send(ClientCommand::new_sort_inventory(key));
+                    }
+                }
+                WidgetAction::LinkItem { slot, name } => {
+                    self.chat_box.insert_item_link(slot, &name);
+                }
                 WidgetAction::TogglePanel(_) => {
                     // Panel was closed via its title bar X button.
                     self.save_active_profile(app_state);
@@ -600,6 +1200,167 @@ impl GameScene {
         }
     }
 
+    /// Drain pending `WidgetAction`s from the NPC context menu and send the
+    /// chosen option to the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (network access).
+    pub(crate) fn process_npc_menu_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.npc_menu.take_actions() {
+            if let WidgetAction::NpcMenuChoice { target, option } = action {
+                self.play_click_sound(app_state);
+                if let Some(net) = app_state.network.as_ref() {
+                    net.send(ClientCommand::new_npc_action(target, option));
+                }
+            }
+        }
+    }
+
+    /// Drain pending `WidgetAction`s from the trainer popup and send the
+    /// chosen skill purchase to the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (network access).
+    pub(crate) fn process_trainer_popup_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.trainer_popup.take_actions() {
+            if let WidgetAction::LearnSkill { target, skill } = action {
+                self.play_click_sound(app_state);
+                if let Some(net) = app_state.network.as_ref() {
+                    net.send(ClientCommand::new_learn_skill(target, skill));
+                }
+            }
+        }
+    }
+
+    /// Drain pending `WidgetAction`s from the quest tracker: persist the
+    /// collapsed state, or focus the clicked quest and open the quest log.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (settings + player state).
+    pub(crate) fn process_quest_tracker_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.quest_tracker.take_actions() {
+            match action {
+                WidgetAction::SetQuestTrackerCollapsed(collapsed) => {
+                    self.play_click_sound(app_state);
+                    app_state.settings.character.quest_tracker_collapsed = collapsed;
+                    self.save_active_profile(app_state);
+                }
+                WidgetAction::SetActiveQuest { npc_template_id } => {
+                    self.play_click_sound(app_state);
+                    if let Some(ps) = app_state.player_state.as_mut() {
+                        ps.set_active_quest(npc_template_id);
+                    }
+                    if !self.quest_log_panel.is_visible() {
+                        self.quest_log_panel.toggle();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Drain pending `WidgetAction`s from the profile editor and upload the
+    /// profile or title selection to the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (network access).
+    pub(crate) fn process_profile_panel_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.profile_panel.take_actions() {
+            match action {
+                WidgetAction::SaveProfile { name_color, bio } => {
+                    self.play_click_sound(app_state);
+                    if let Some(net) = app_state.network.as_ref() {
+                        for pkt in ClientCommand::new_profile_packets(name_color, bio.as_bytes()) {
+                            net.send(pkt);
+                        }
+                    }
+                }
+                WidgetAction::SetTitle { title_id } => {
+                    self.play_click_sound(app_state);
+                    if let Some(net) = app_state.network.as_ref() {
+                        net.send(ClientCommand::new_set_title(title_id));
+                    }
+                }
+                WidgetAction::TogglePanel(_) => {
+                    // Panel was closed via its title bar X button.
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Drain pending `WidgetAction`s from the mail panel and send its `#mail`
+    /// commands as say packets.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (network access).
+    pub(crate) fn process_mail_panel_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.mail_panel.take_actions() {
+            if let WidgetAction::SendChat(text) = action {
+                self.play_click_sound(app_state);
+                if let Some(net) = app_state.network.as_ref() {
+                    for pkt in ClientCommand::new_say_packets(text.as_bytes()) {
+                        net.send(pkt);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drain pending `WidgetAction`s from the friends panel: a quick-tell
+    /// button starts a `#tell` line in the chat input.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (click sound).
+    pub(crate) fn process_friends_panel_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.friends_panel.take_actions() {
+            if let WidgetAction::StartChat(text) = action {
+                self.play_click_sound(app_state);
+                self.chat_box.start_input(&text);
+            }
+        }
+    }
+
+    /// Drain pending `WidgetAction`s from the combat log panel: export the
+    /// logged hits to a CSV file or clear the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_state` - Shared application state (player state, click sound).
+    pub(crate) fn process_combat_log_panel_actions(&mut self, app_state: &mut AppState<'_>) {
+        for action in self.combat_log_panel.take_actions() {
+            match action {
+                WidgetAction::ExportCombatLog => {
+                    self.play_click_sound(app_state);
+                    let Some(ps) = app_state.player_state.as_mut() else {
+                        continue;
+                    };
+                    let path = preferences::combat_log_export_file_path();
+                    match ps.combat_log().export_csv(&path) {
+                        Ok(hits) => ps.tlog(
+                            1,
+                            format!("Combat log saved to {} ({} hits).", path.display(), hits),
+                        ),
+                        Err(e) => ps.tlog(0, format!("Could not save the combat log: {e}")),
+                    }
+                }
+                WidgetAction::ClearCombatLog => {
+                    self.play_click_sound(app_state);
+                    if let Some(ps) = app_state.player_state.as_mut() {
+                        ps.combat_log_mut().clear();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Drain pending `WidgetAction`s from the shop panel and send the
     /// corresponding network commands, or close the shop.
     ///
@@ -620,6 +1381,14 @@ impl GameScene {
                         ps.close_shop();
                     }
                 }
+                WidgetAction::SendChat(text) => {
+                    if let Some(net) = app_state.network.as_ref() {
+                        self.play_click_sound(app_state);
+                        for pkt in ClientCommand::new_say_packets(text.as_bytes()) {
+                            net.send(pkt);
+                        }
+                    }
+                }
                 _ => {}
             }
         }
@@ -695,6 +1464,23 @@ impl GameScene {
             return UiHandleResult::Consumed;
         }
 
+        // --- NPC interaction menu (closes on any click) ---
+        if self.npc_menu.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            self.process_npc_menu_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+
+        // --- Trainer offers popup ---
+        if self.trainer_popup.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            self.process_trainer_popup_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+
+        // --- Notification toasts (click to dismiss) ---
+        if self.toast_stack.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            return UiHandleResult::Consumed;
+        }
+
         // --- Rank sigil (upper-left) ---
         if self.rank_sigil.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
             return UiHandleResult::Consumed;
@@ -712,6 +1498,53 @@ impl GameScene {
             return UiHandleResult::Consumed;
         }
 
+        // --- Profile editor (before chat so its text input keeps typed keys) ---
+        if self.profile_panel.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            if self.profile_panel.is_text_focused() {
+                self.chat_box.set_focused(false);
+            }
+            self.process_profile_panel_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+
+        // --- Mailbox (before chat so its compose line keeps typed keys) ---
+        if self.mail_panel.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            if self.mail_panel.is_text_focused() {
+                self.chat_box.set_focused(false);
+            }
+            self.process_mail_panel_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+        if self.friends_panel.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            self.process_friends_panel_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+        if self.combat_log_panel.handle_event(ui_event)
+            == crate::ui::widget::EventResponse::Consumed
+        {
+            self.process_combat_log_panel_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+
+        // --- Inventory search (before chat while it has focus, so typed keys stay there) ---
+        if self.inventory_panel.is_text_focused()
+            && self.inventory_panel.handle_event(ui_event)
+                == crate::ui::widget::EventResponse::Consumed
+        {
+            self.chat_box.set_focused(false);
+            self.process_inventory_panel_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+
+        // --- Broker search (before chat while it has focus, so typed keys stay there) ---
+        if self.shop_panel.is_text_focused()
+            && self.shop_panel.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed
+        {
+            self.chat_box.set_focused(false);
+            self.process_shop_panel_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+
         if self.chat_box.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
             self.process_chat_box_actions(app_state);
             return UiHandleResult::Consumed;
@@ -722,8 +1555,14 @@ impl GameScene {
             self.process_skills_panel_actions(app_state);
             return UiHandleResult::Consumed;
         }
+        // Shift-clicking a backpack item while typing links it in chat.
+        self.inventory_panel
+            .set_link_items(self.chat_box.is_focused());
         if self.inventory_panel.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed
         {
+            if self.inventory_panel.is_text_focused() {
+                self.chat_box.set_focused(false);
+            }
             self.process_inventory_panel_actions(app_state);
             return UiHandleResult::Consumed;
         }
@@ -743,9 +1582,25 @@ impl GameScene {
             self.process_quest_log_panel_actions(app_state);
             return UiHandleResult::Consumed;
         }
+        if self.guild_panel.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            // The only action is the title bar close, which already hid it.
+            self.guild_panel.take_actions();
+            return UiHandleResult::Consumed;
+        }
+        if self.quest_tracker.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            self.process_quest_tracker_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
+        if self.travel_menu.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            self.process_travel_menu_actions(app_state);
+            return UiHandleResult::Consumed;
+        }
 
         // --- Dispatch to shop/depot/grave overlay (modal — eats outside clicks) ---
         if self.shop_panel.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed {
+            if self.shop_panel.is_text_focused() {
+                self.chat_box.set_focused(false);
+            }
             self.process_shop_panel_actions(app_state);
             return UiHandleResult::Consumed;
         }
@@ -753,6 +1608,7 @@ impl GameScene {
         // --- Dispatch to minimap toggle button / panel ---
         if self.minimap_widget.handle_event(ui_event) == crate::ui::widget::EventResponse::Consumed
         {
+            self.process_minimap_actions(app_state);
             return UiHandleResult::Consumed;
         }
 
@@ -791,6 +1647,11 @@ impl GameScene {
                         HudPanel::KeyBindings => {}
                         HudPanel::Talents => self.talent_panel.toggle(),
                         HudPanel::QuestLog => self.quest_log_panel.toggle(),
+                        HudPanel::Profile => self.profile_panel.toggle(),
+                        HudPanel::Guild => self.guild_panel.toggle(),
+                        HudPanel::Mail => self.mail_panel.toggle(),
+                        HudPanel::Friends => self.friends_panel.toggle(),
+                        HudPanel::CombatLog => self.combat_log_panel.toggle(),
                     }
                 }
             }
@@ -800,3 +1661,27 @@ impl GameScene {
         UiHandleResult::NotConsumed
     }
 }
+
+/// Returns the arguments of an `/addon` chat command, or `None` if `text` is
+/// some other command or message.
+fn addon_command_args(text: &str) -> Option<&str> {
+    let text = text.trim();
+    let head = text.get(..6)?;
+    if !head.eq_ignore_ascii_case("/addon") {
+        return None;
+    }
+    let rest = &text[6..];
+    (rest.is_empty() || rest.starts_with(' ')).then(|| rest.trim())
+}
+
+/// Returns the arguments of a `/streamer` chat command, or `None` if `text`
+/// is some other command or message.
+fn streamer_command_args(text: &str) -> Option<&str> {
+    let text = text.trim();
+    let head = text.get(..9)?;
+    if !head.eq_ignore_ascii_case("/streamer") {
+        return None;
+    }
+    let rest = &text[9..];
+    (rest.is_empty() || rest.starts_with(' ')).then(|| rest.trim())
+}
@@ -0,0 +1,223 @@
+//! Click-to-move route preview.
+//!
+//! While the cursor shows "WALK", the route to the hovered tile is planned
+//! with A* over the tiles the client can see and drawn on the floor. A left
+//! click sends the same route as `CmdWaypoints`, which the server checks hop
+//! by hop before walking it.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use mag_core::constants::{INVIS, SPR_EMPTY, TILEX, TILEY, WALK_BLOCKED};
+
+use crate::game_map::GameMap;
+
+/// Step costs matching the server path finder (straight 2, diagonal 3).
+const STRAIGHT_COST: u32 = 2;
+const DIAGONAL_COST: u32 = 3;
+
+/// Returns whether view tile `(x, y)` looks walkable from the client's side.
+///
+/// Unseen tiles, tiles the server hinted as `WALK_BLOCKED`, and tiles with
+/// no floor sprite are treated as blocked. Characters are ignored; they
+/// usually move out of the way before the route reaches them.
+///
+/// # Arguments
+///
+/// * `map` - Visible map window.
+/// * `x`, `y` - View tile.
+///
+/// # Returns
+///
+/// * `true` if the route may pass through the tile.
+pub(super) fn view_tile_walkable(map: &GameMap, x: usize, y: usize) -> bool {
+    map.tile_at_xy(x, y).is_some_and(|tile| {
+        (tile.flags & INVIS) == 0
+            && (tile.flags2 & WALK_BLOCKED) == 0
+            && tile.ba_sprite > 0
+            && tile.ba_sprite != SPR_EMPTY as i16
+    })
+}
+
+/// A* over the `TILEX × TILEY` view grid.
+///
+/// Diagonal steps need both neighbouring straight tiles to be walkable,
+/// the same corner rule the server applies.
+///
+/// # Arguments
+///
+/// * `start` - View tile the route starts from (not checked for walkability).
+/// * `goal` - View tile to reach.
+/// * `walkable` - Passability test for a view tile.
+///
+/// # Returns
+///
+/// * The view tiles from the first step to `goal`, or `None` if the goal is
+///   unreachable or equal to `start`.
+pub(super) fn find_path(
+    start: (usize, usize),
+    goal: (usize, usize),
+    walkable: impl Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    if start == goal || goal.0 >= TILEX || goal.1 >= TILEY || !walkable(goal.0, goal.1) {
+        return None;
+    }
+
+    let heuristic = |x: usize, y: usize| {
+        let dx = x.abs_diff(goal.0) as u32;
+        let dy = y.abs_diff(goal.1) as u32;
+        DIAGONAL_COST * dx.min(dy) + STRAIGHT_COST * (dx.max(dy) - dx.min(dy))
+    };
+
+    let index = |x: usize, y: usize| x + y * TILEX;
+    let mut cost = vec![u32::MAX; TILEX * TILEY];
+    let mut came_from = vec![usize::MAX; TILEX * TILEY];
+    let mut open = BinaryHeap::new();
+
+    cost[index(start.0, start.1)] = 0;
+    open.push(Reverse((heuristic(start.0, start.1), 0u32, start)));
+
+    while let Some(Reverse((_, g, (x, y)))) = open.pop() {
+        if (x, y) == goal {
+            let mut path = vec![goal];
+            let mut at = index(x, y);
+            while came_from[at] != index(start.0, start.1) {
+                at = came_from[at];
+                path.push((at % TILEX, at / TILEX));
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if g > cost[index(x, y)] {
+            continue;
+        }
+
+        for dy in -1i32..=1 {
+            for dx in -1i32..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= TILEX as i32 || ny >= TILEY as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if !walkable(nx, ny) {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                if diagonal && (!walkable(nx, y) || !walkable(x, ny)) {
+                    continue;
+                }
+
+                let next = g + if diagonal {
+                    DIAGONAL_COST
+                } else {
+                    STRAIGHT_COST
+                };
+                let ni = index(nx, ny);
+                if next < cost[ni] {
+                    cost[ni] = next;
+                    came_from[ni] = index(x, y);
+                    open.push(Reverse((next + heuristic(nx, ny), next, (nx, ny))));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Plans a route from the player to view tile `goal` in world tiles.
+///
+/// # Arguments
+///
+/// * `map` - Visible map window; the player stands at its centre.
+/// * `goal` - Target view tile.
+///
+/// # Returns
+///
+/// * The world tiles from the first step to the goal, or `None` if there
+///   is no route over known walkable tiles.
+pub(super) fn plan_route(map: &GameMap, goal: (usize, usize)) -> Option<Vec<(u16, u16)>> {
+    let path = find_path((TILEX / 2, TILEY / 2), goal, |x, y| {
+        view_tile_walkable(map, x, y)
+    })?;
+    path.iter()
+        .map(|&(x, y)| map.tile_at_xy(x, y).map(|t| (t.x, t.y)))
+        .collect()
+}
+
+/// Cached route from the player to the hovered tile.
+///
+/// Only replanned when the player or the hovered tile moves in the world.
+#[derive(Default)]
+pub struct PathPreview {
+    /// `(player, goal)` world tiles the route was planned for.
+    key: Option<((u16, u16), (u16, u16))>,
+    /// Per view tile: whether the route crosses it.
+    mask: Vec<bool>,
+}
+
+impl PathPreview {
+    /// Replans the route to view tile `goal`, or clears it for `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - Visible map window; the player stands at its centre.
+    /// * `goal` - Hovered view tile, if a route should be shown.
+    pub(super) fn update(&mut self, map: &GameMap, goal: Option<(usize, usize)>) {
+        let start = (TILEX / 2, TILEY / 2);
+        let world = |(x, y): (usize, usize)| map.tile_at_xy(x, y).map(|t| (t.x, t.y));
+        let key = goal.and_then(|goal| Some((world(start)?, world(goal)?)));
+        if key == self.key {
+            return;
+        }
+        self.key = key;
+        self.mask.clear();
+
+        let Some(goal) = goal.filter(|_| key.is_some()) else {
+            return;
+        };
+        if let Some(path) = find_path(start, goal, |x, y| view_tile_walkable(map, x, y)) {
+            self.mask = vec![false; TILEX * TILEY];
+            for &(x, y) in &path {
+                self.mask[x + y * TILEX] = true;
+            }
+        }
+    }
+
+    /// Returns `true` if the previewed route crosses view tile `(x, y)`.
+    pub(super) fn contains(&self, x: usize, y: usize) -> bool {
+        self.mask.get(x + y * TILEX).copied().unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_path_goes_around_a_wall() {
+        // Wall at x = 42 from y = 35 to 45, between the start and the goal.
+        let walkable = |x: usize, y: usize| !(x == 42 && (35..=45).contains(&y));
+        let path = find_path((40, 40), (44, 40), walkable).expect("route exists");
+
+        assert_eq!(path.last(), Some(&(44, 40)));
+        assert!(path.iter().all(|&(x, y)| walkable(x, y)));
+        for pair in path.windows(2) {
+            assert!(pair[0].0.abs_diff(pair[1].0) <= 1 && pair[0].1.abs_diff(pair[1].1) <= 1);
+        }
+        assert!(path.len() > 4);
+    }
+
+    #[test]
+    fn find_path_fails_for_blocked_or_enclosed_goals() {
+        assert_eq!(find_path((40, 40), (44, 40), |x, _| x != 44), None);
+
+        // Goal enclosed by a ring of walls.
+        let walkable = |x: usize, y: usize| x.abs_diff(50).max(y.abs_diff(50)) != 1;
+        assert_eq!(find_path((40, 40), (50, 50), walkable), None);
+        assert_eq!(find_path((40, 40), (40, 40), |_, _| true), None);
+    }
+}
@@ -1,11 +1,13 @@
 use crate::{
+    accessibility::Announcer,
     preferences::{self, CharacterIdentity, CharacterSettings, Settings},
     state::AppState,
+    streamer_mode::StreamerFilter,
     ui::widget::Widget,
     ui::widgets::title_bar::clamp_to_viewport,
 };
 
-use super::GameScene;
+use super::{GameScene, HIGH_CONTRAST_PANEL_BG, HUD_PANEL_BG};
 
 impl GameScene {
     /// Returns the default top-left position for the skills and settings panels.
@@ -92,6 +94,11 @@ impl GameScene {
         // characters do not inherit another character's bindings or HUD layout.
         app_state.settings = preferences::load_settings(identity);
         self.apply_character_panel_positions(&app_state.settings.character);
+        self.quest_tracker
+            .set_collapsed(app_state.settings.character.quest_tracker_collapsed);
+        self.apply_streamer_mode(&app_state.settings);
+        self.apply_accessibility(&app_state.settings);
+        self.load_addons(&app_state.settings);
 
         log::info!(
             "Applied SDL profile state for character '{}' (id={})",
@@ -100,6 +107,76 @@ impl GameScene {
         );
     }
 
+    /// Installs or removes the streamer-mode chat filter to match `settings`.
+    ///
+    /// The filter list is re-read from disk each time the mode is switched on,
+    /// so edits to `mag_chat_filter.txt` apply without restarting.
+    ///
+    /// # Arguments
+    /// * `settings` – current settings (`streamer_mode`, `streamer_alias`).
+    pub(super) fn apply_streamer_mode(&mut self, settings: &Settings) {
+        let filter = settings
+            .streamer_mode
+            .then(|| StreamerFilter::load(&settings.streamer_alias));
+        self.chat_box.set_display_filter(filter);
+    }
+
+    /// Switches screen-reader output and the high-contrast HUD to match
+    /// `settings`.
+    ///
+    /// Turning screen-reader mode on starts a fresh export file; leaving it on
+    /// keeps the current announcer so unchanged vitals are not read again.
+    ///
+    /// # Arguments
+    /// * `settings` – current settings (`screen_reader`, `high_contrast`).
+    pub(super) fn apply_accessibility(&mut self, settings: &Settings) {
+        if !settings.screen_reader {
+            self.announcer = None;
+        } else if self.announcer.is_none() {
+            self.announcer = Some(Announcer::open());
+        }
+
+        let bg = if settings.high_contrast {
+            HIGH_CONTRAST_PANEL_BG
+        } else {
+            HUD_PANEL_BG
+        };
+        let panels: [&mut dyn Widget; 15] = [
+            &mut self.chat_box,
+            &mut self.skills_panel,
+            &mut self.inventory_panel,
+            &mut self.settings_panel,
+            &mut self.talent_panel,
+            &mut self.quest_log_panel,
+            &mut self.profile_panel,
+            &mut self.guild_panel,
+            &mut self.mail_panel,
+            &mut self.friends_panel,
+            &mut self.combat_log_panel,
+            &mut self.look_panel,
+            &mut self.shop_panel,
+            &mut self.weapon_armor_panel,
+            &mut self.rank_sigil,
+        ];
+        for panel in panels {
+            panel.set_background(bg);
+        }
+        self.vitality_bars.set_high_contrast(settings.high_contrast);
+    }
+
+    /// (Re)loads the installed addons, skipping the ones `settings` disables.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings holding the disabled addon list.
+    pub(super) fn load_addons(&mut self, settings: &Settings) {
+        self.addons.load_dir(
+            &preferences::addons_directory_path(),
+            &settings.disabled_addons,
+        );
+        self.addon_panels.sync(self.addons.panels());
+    }
+
     /// Builds a [`Settings`] snapshot from current in-game settings.
     ///
     /// Clones the live `app_state.settings` and patches in the current panel
@@ -0,0 +1,465 @@
+//! Offline viewer for server tick recordings.
+//!
+//! Plays a [`ReplayRecording`] produced by `world_snapshot export-replay`
+//! without a server connection. Each frame is copied into a private
+//! [`PlayerState`] map, run through the legacy engine for sprite selection
+//! and drawn with the same sprite helpers as [`GameScene`]. A timeline along
+//! the bottom edge can be clicked or dragged to scrub.
+//!
+//! Start the client with `MAG_REPLAY_PATH=<file.mgrp>` to open the viewer
+//! instead of the login screen.
+//!
+//! Controls: Space plays/pauses, Left/Right step one frame, Page Up/Down
+//! step a hundred, Home/End jump to either end, `+`/`-` change the playback
+//! speed and Escape leaves the viewer.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sdl2::{
+    event::Event, keyboard::Keycode, mouse::MouseButton, pixels::Color, rect::Rect, render::Canvas,
+    video::Window,
+};
+
+use mag_core::constants::{ISCHAR, TICKS, TILEX, TILEY};
+use mag_core::replay_store::{ReplayFrame, ReplayRecording};
+
+use crate::{
+    constants::{TARGET_HEIGHT_INT, TARGET_WIDTH_INT},
+    font_cache,
+    game_map::GameMap,
+    gfx_cache::GraphicsCache,
+    legacy_engine,
+    player_state::PlayerState,
+    scenes::scene::{Scene, SceneType},
+    state::AppState,
+    types::map::CMapTile,
+};
+
+use super::GameScene;
+
+/// Environment variable naming the recording to open at startup.
+const REPLAY_PATH_ENV: &str = "MAG_REPLAY_PATH";
+
+/// Horizontal inset of the timeline from the screen edges.
+const TIMELINE_INSET: i32 = 20;
+
+/// Top of the timeline bar.
+const TIMELINE_Y: i32 = TARGET_HEIGHT_INT as i32 - 24;
+
+/// Height of the timeline bar.
+const TIMELINE_H: u32 = 8;
+
+/// Bitmap font for the status line (yellow).
+const STATUS_FONT: usize = 1;
+
+/// Playback speeds selectable with `+` / `-`.
+const SPEEDS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// Index of 1x in [`SPEEDS`].
+const DEFAULT_SPEED: usize = 2;
+
+/// Returns the recording named by `MAG_REPLAY_PATH`, if set.
+///
+/// # Returns
+///
+/// * The path to open in the replay viewer, or `None` for a normal start.
+pub fn replay_path_from_env() -> Option<PathBuf> {
+    std::env::var(REPLAY_PATH_ENV)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Scene that plays back a recording.
+pub struct ReplayScene {
+    recording: Option<ReplayRecording>,
+    /// Local map the frames are copied into; never shared with the network.
+    player_state: PlayerState,
+    frame: usize,
+    /// Fractional server tick the playhead is at.
+    playhead: f64,
+    playing: bool,
+    speed: usize,
+    /// `true` while the left button is held on the timeline.
+    scrubbing: bool,
+    ctick: usize,
+    error: Option<String>,
+}
+
+impl Default for ReplayScene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayScene {
+    /// Creates an empty replay scene; the recording is loaded in `on_enter`.
+    ///
+    /// # Returns
+    ///
+    /// * A new `ReplayScene`.
+    pub fn new() -> Self {
+        Self {
+            recording: None,
+            player_state: PlayerState::default(),
+            frame: 0,
+            playhead: 0.0,
+            playing: false,
+            speed: DEFAULT_SPEED,
+            scrubbing: false,
+            ctick: 0,
+            error: None,
+        }
+    }
+
+    /// Reads and decodes a recording file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Recording written by `world_snapshot export-replay`.
+    ///
+    /// # Returns
+    ///
+    /// * The recording, or a description of the failure.
+    fn load(path: &Path) -> Result<ReplayRecording, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Failed to read replay {}: {e}", path.display()))?;
+        ReplayRecording::from_bytes(&bytes)
+    }
+
+    /// Moves the playhead to frame `index` and shows it.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Frame to show; clamped to the recording.
+    fn seek_frame(&mut self, index: usize) {
+        let Some(recording) = self.recording.as_ref() else {
+            return;
+        };
+        let Some(last) = recording.frames.len().checked_sub(1) else {
+            return;
+        };
+        self.frame = index.min(last);
+        self.playhead = f64::from(recording.frames[self.frame].tick);
+        self.show_frame();
+    }
+
+    /// Steps the playhead by `delta` frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - Frames to move; negative steps backward.
+    fn step(&mut self, delta: isize) {
+        self.playing = false;
+        self.seek_frame(self.frame.saturating_add_signed(delta));
+    }
+
+    /// Copies the current frame into the local map.
+    fn show_frame(&mut self) {
+        let Some(frame) = self
+            .recording
+            .as_ref()
+            .and_then(|recording| recording.frames.get(self.frame))
+        else {
+            return;
+        };
+        fill_map(&mut self.player_state, frame);
+        self.ctick = self.ctick.wrapping_add(1);
+        legacy_engine::engine_tick(&mut self.player_state, frame.tick as u32, self.ctick);
+    }
+
+    /// Maps a screen x coordinate on the timeline to a frame index.
+    fn frame_at_screen_x(&self, x: i32) -> Option<usize> {
+        let recording = self.recording.as_ref()?;
+        let first = recording.frames.first()?.tick;
+        let last = recording.frames.last()?.tick;
+        let width = TARGET_WIDTH_INT as i32 - 2 * TIMELINE_INSET;
+        let fraction = f64::from((x - TIMELINE_INSET).clamp(0, width)) / f64::from(width);
+        let tick = first + (f64::from(last - first) * fraction).round() as i32;
+        recording.frame_at_tick(tick)
+    }
+
+    /// Returns `true` when `(x, y)` lies on the timeline.
+    fn on_timeline(x: i32, y: i32) -> bool {
+        let slop = 6;
+        x >= TIMELINE_INSET
+            && x <= TARGET_WIDTH_INT as i32 - TIMELINE_INSET
+            && y >= TIMELINE_Y - slop
+            && y <= TIMELINE_Y + TIMELINE_H as i32 + slop
+    }
+
+    /// Draws the timeline and the status line.
+    fn draw_timeline(
+        &self,
+        canvas: &mut Canvas<Window>,
+        gfx: &mut GraphicsCache<'_>,
+    ) -> Result<(), String> {
+        let width = TARGET_WIDTH_INT - 2 * TIMELINE_INSET as u32;
+        canvas.set_draw_color(Color::RGB(40, 40, 60));
+        canvas.fill_rect(Rect::new(TIMELINE_INSET, TIMELINE_Y, width, TIMELINE_H))?;
+
+        let status = match (&self.error, self.recording.as_ref()) {
+            (Some(error), _) => error.clone(),
+            (None, Some(recording)) if !recording.frames.is_empty() => {
+                let first = recording.frames[0].tick;
+                let last = recording.frames[recording.frames.len() - 1].tick;
+                let frame = &recording.frames[self.frame];
+                let span = (last - first).max(1);
+                let filled = (i64::from(width) * i64::from(frame.tick - first) / i64::from(span))
+                    .clamp(0, i64::from(width)) as u32;
+                if filled > 0 {
+                    canvas.set_draw_color(Color::RGB(160, 140, 60));
+                    canvas.fill_rect(Rect::new(TIMELINE_INSET, TIMELINE_Y, filled, TIMELINE_H))?;
+                }
+                format!(
+                    "Tick {} ({}/{})  {}  {}x  char {} at {},{}",
+                    frame.tick,
+                    self.frame + 1,
+                    recording.frames.len(),
+                    if self.playing { "Playing" } else { "Paused" },
+                    SPEEDS[self.speed],
+                    recording.follow,
+                    frame.center_x,
+                    frame.center_y,
+                )
+            }
+            _ => "Replay has no frames".to_owned(),
+        };
+        font_cache::draw_text(
+            canvas,
+            gfx,
+            STATUS_FONT,
+            &status,
+            TIMELINE_INSET,
+            TIMELINE_Y - font_cache::BITMAP_GLYPH_H as i32 - 4,
+            font_cache::TextStyle::default(),
+        )
+    }
+}
+
+/// Copies `frame` into the map so that its centre lands on the view centre.
+///
+/// # Arguments
+///
+/// * `ps` - Local player state whose map is overwritten.
+/// * `frame` - Frame to show.
+fn fill_map(ps: &mut PlayerState, frame: &ReplayFrame) {
+    let map = ps.map_mut();
+    for y in 0..TILEY {
+        for x in 0..TILEX {
+            let dx = x as i16 - (TILEX / 2) as i16;
+            let dy = y as i16 - (TILEY / 2) as i16;
+            let Some(index) = GameMap::tile_index(x, y) else {
+                continue;
+            };
+            let Some(tile) = map.tile_at_index_mut(index) else {
+                continue;
+            };
+            let source = frame.tile(dx, dy).copied().unwrap_or_default();
+            *tile = CMapTile {
+                x: (frame.center_x + dx).max(0) as u16,
+                y: (frame.center_y + dy).max(0) as u16,
+                ba_sprite: source.back as i16,
+                it_sprite: source.item,
+                ch_sprite: source.character,
+                ch_nr: source.character_nr,
+                flags: if source.character != 0 { ISCHAR } else { 0 },
+                ..CMapTile::default()
+            };
+        }
+    }
+}
+
+impl Scene for ReplayScene {
+    fn on_enter(&mut self, app_state: &mut AppState<'_>) {
+        *self = Self::new();
+        let Some(path) = app_state.replay_path.clone() else {
+            self.error = Some(format!("Set {REPLAY_PATH_ENV} to open a replay"));
+            return;
+        };
+        match Self::load(&path) {
+            Ok(recording) => {
+                log::info!(
+                    "Loaded replay {} ({} frames)",
+                    path.display(),
+                    recording.frames.len()
+                );
+                self.recording = Some(recording);
+                self.seek_frame(0);
+            }
+            Err(e) => {
+                log::error!("{e}");
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, _app_state: &mut AppState<'_>, event: &Event) -> Option<SceneType> {
+        match event {
+            Event::KeyDown {
+                keycode: Some(key), ..
+            } => match *key {
+                Keycode::Escape => return Some(SceneType::Login),
+                Keycode::Space => self.playing = !self.playing,
+                Keycode::Left => self.step(-1),
+                Keycode::Right => self.step(1),
+                Keycode::PageUp => self.step(-100),
+                Keycode::PageDown => self.step(100),
+                Keycode::Home => self.step(isize::MIN),
+                Keycode::End => self.step(isize::MAX),
+                Keycode::Plus | Keycode::KpPlus | Keycode::Equals => {
+                    self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+                }
+                Keycode::Minus | Keycode::KpMinus => {
+                    self.speed = self.speed.saturating_sub(1);
+                }
+                _ => {}
+            },
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } if Self::on_timeline(*x, *y) => {
+                self.scrubbing = true;
+                self.playing = false;
+                if let Some(frame) = self.frame_at_screen_x(*x) {
+                    self.seek_frame(frame);
+                }
+            }
+            Event::MouseMotion { x, .. } if self.scrubbing => {
+                if let Some(frame) = self.frame_at_screen_x(*x) {
+                    self.seek_frame(frame);
+                }
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => self.scrubbing = false,
+            _ => {}
+        }
+        None
+    }
+
+    fn update(&mut self, _app_state: &mut AppState<'_>, dt: Duration) -> Option<SceneType> {
+        if !self.playing {
+            return None;
+        }
+        let recording = self.recording.as_ref()?;
+        self.playhead += dt.as_secs_f64() * f64::from(TICKS) * f64::from(SPEEDS[self.speed]);
+        let frame = recording
+            .frame_at_tick(self.playhead.floor() as i32)
+            .unwrap_or(0);
+        if frame + 1 >= recording.frames.len() {
+            self.playing = false;
+        }
+        if frame != self.frame {
+            let playhead = self.playhead;
+            self.seek_frame(frame);
+            self.playhead = playhead;
+        }
+        None
+    }
+
+    fn render_world(
+        &mut self,
+        app_state: &mut AppState<'_>,
+        canvas: &mut Canvas<Window>,
+    ) -> Result<(), String> {
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+
+        let gfx = &mut app_state.gfx_cache;
+        let map = self.player_state.map();
+        let (cam_xoff, cam_yoff) = GameScene::camera_offsets(&self.player_state);
+
+        // Same two-pass painter order as the live world renderer.
+        for y in (0..TILEY).rev() {
+            for x in 0..TILEX {
+                if let Some(tile) = map.tile_at_xy(x, y) {
+                    GameScene::draw_world_sprite(
+                        canvas, gfx, tile.back, x, y, cam_xoff, cam_yoff, 0, 0, tile.light,
+                    )?;
+                }
+            }
+        }
+        for y in (0..TILEY).rev() {
+            for x in 0..TILEX {
+                let Some(tile) = map.tile_at_xy(x, y) else {
+                    continue;
+                };
+                GameScene::draw_world_sprite(
+                    canvas, gfx, tile.obj1, x, y, cam_xoff, cam_yoff, 0, 0, tile.light,
+                )?;
+                if app_state.settings.shadows_active() {
+                    GameScene::draw_shadow(
+                        canvas,
+                        gfx,
+                        tile.obj2,
+                        x,
+                        y,
+                        cam_xoff,
+                        cam_yoff,
+                        tile.obj_xoff,
+                        tile.obj_yoff + 4,
+                    )?;
+                }
+                GameScene::draw_world_sprite(
+                    canvas,
+                    gfx,
+                    tile.obj2,
+                    x,
+                    y,
+                    cam_xoff,
+                    cam_yoff,
+                    tile.obj_xoff,
+                    tile.obj_yoff,
+                    tile.light,
+                )?;
+            }
+        }
+
+        self.draw_timeline(canvas, gfx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mag_core::replay_store::{REPLAY_VIEW_SIZE, ReplayTile};
+
+    #[test]
+    fn fill_map_centres_frame_and_blanks_outside() {
+        let mut tiles = vec![ReplayTile::default(); REPLAY_VIEW_SIZE * REPLAY_VIEW_SIZE];
+        let centre = REPLAY_VIEW_SIZE / 2 + (REPLAY_VIEW_SIZE / 2) * REPLAY_VIEW_SIZE;
+        tiles[centre] = ReplayTile {
+            back: 1001,
+            item: 0,
+            character: 2000,
+            character_nr: 7,
+        };
+        let frame = ReplayFrame {
+            tick: 1,
+            center_x: 300,
+            center_y: 400,
+            tiles,
+        };
+        let mut ps = PlayerState::default();
+        ps.map_mut()
+            .tile_at_index_mut(0)
+            .expect("map has tiles")
+            .ba_sprite = 55;
+
+        fill_map(&mut ps, &frame);
+
+        let tile = ps.map().tile_at_xy(TILEX / 2, TILEY / 2).unwrap();
+        assert_eq!((tile.x, tile.y), (300, 400));
+        assert_eq!(
+            (tile.ba_sprite, tile.ch_sprite, tile.ch_nr),
+            (1001, 2000, 7)
+        );
+        assert_eq!(tile.flags, ISCHAR);
+        assert_eq!(ps.map().tile_at_index(0).unwrap().ba_sprite, 0);
+    }
+}
@@ -1,41 +1,46 @@
 //! World (map) input handling for [`GameScene`].
 //!
-//! Keyboard number hotkeys and mouse-button-up world interactions are split
+//! Skill-slot hotkeys and mouse-button-up world interactions are split
 //! out here so that the main `handle_event` in `mod.rs` stays readable.
 
-use sdl2::{keyboard::Keycode, mouse::MouseButton};
+use sdl2::mouse::MouseButton;
 
 use mag_core::client_commands::ClientCommand;
-use mag_core::constants::{ISCHAR, ISITEM, ISUSABLE};
+use mag_core::constants::{ISCHAR, ISITEM, ISUSABLE, TILEX, TILEY};
+use mag_core::waypoints::{MAX_WAYPOINTS, path_to_waypoints};
 
 use crate::{scenes::scene::SceneType, state::AppState};
 
 use super::GameScene;
 
 impl GameScene {
-    /// Dispatch a `KeyDown` Num0–Num9 event to the appropriate skill keybind slot.
+    /// Use the skill bound to a skill-bar slot.
     ///
-    /// `Num0` maps to primary slot 9 (the 10th slot). `Num1`–`Num9` map to
-    /// primary slots 0–8. When Shift is held the corresponding secondary slot
-    /// is used instead (`skill_keybinds_secondary`).
+    /// Called for the `SkillSlot*` key bindings (`1`–`0` by default). When
+    /// Shift is held the corresponding secondary slot is used instead
+    /// (`skill_keybinds_secondary`).
     ///
-    /// Silently no-ops when chat is focused or no network/player-state is
-    /// available, so callers do not need to pre-check those conditions.
+    /// Silently no-ops when chat or the profile editor is focused, or no
+    /// network/player-state is available, so callers do not need to
+    /// pre-check those conditions.
     ///
     /// # Arguments
     ///
     /// * `app_state` - Shared application state.
-    /// * `kc` - The number keycode (`Num0`–`Num9`).
-    pub(super) fn handle_num_hotkey(&mut self, app_state: &mut AppState<'_>, kc: Keycode) {
-        if self.chat_box.is_focused() {
+    /// * `key_slot` - Skill-bar slot (0–9).
+    pub(super) fn handle_skill_slot_hotkey(
+        &mut self,
+        app_state: &mut AppState<'_>,
+        key_slot: usize,
+    ) {
+        if self.chat_box.is_focused()
+            || self.profile_panel.is_text_focused()
+            || self.mail_panel.is_text_focused()
+            || self.inventory_panel.is_text_focused()
+            || self.shop_panel.is_text_focused()
+        {
             return;
         }
-        // Num0 → slot 9 (10th slot); Num1 → slot 0, …, Num9 → slot 8.
-        let key_slot = if kc == Keycode::Num0 {
-            9
-        } else {
-            (i32::from(kc) - i32::from(Keycode::Num1)) as usize
-        };
         if let (Some(net), Some(ps)) = (app_state.network.as_ref(), app_state.player_state.as_ref())
         {
             let skill_nr = if self.effective_shift_held() {
@@ -77,14 +82,16 @@ impl GameScene {
         x: i32,
         y: i32,
     ) -> Option<SceneType> {
+        // Any click in the world takes over from an auto-walk or a follow.
+        self.auto_walk = None;
+        self.cancel_follow(app_state);
+
         let Some(ps) = app_state.player_state.as_ref() else {
             log::warn!("Mouse click with no player state");
             return None;
         };
 
-        let (cam_xoff, cam_yoff) = Self::camera_offsets(ps);
-
-        let Some((mx, my)) = Self::screen_to_map_tile(x, y, cam_xoff, cam_yoff) else {
+        let Some((mx, my)) = self.pick_map_tile(ps, x, y) else {
             log::warn!("Click outside of map area: screen=({}, {})", x, y);
             return None;
         };
@@ -134,7 +141,8 @@ impl GameScene {
             }
             MouseButton::Right if has_alt && target_cn != 0 => {
                 self.play_click_sound(app_state);
-                net.send(ClientCommand::new_look(target_cn));
+                self.npc_menu_anchor = (x, y);
+                net.send(ClientCommand::new_npc_menu(target_cn as u16));
             }
             MouseButton::Left if has_ctrl && target_cn != 0 => {
                 self.play_click_sound(app_state);
@@ -146,7 +154,8 @@ impl GameScene {
             }
             MouseButton::Right if has_ctrl && target_cn != 0 => {
                 self.play_click_sound(app_state);
-                net.send(ClientCommand::new_look(target_cn));
+                self.npc_menu_anchor = (x, y);
+                net.send(ClientCommand::new_npc_menu(target_cn as u16));
             }
             MouseButton::Left if has_shift => {
                 let tile_flags = tile.map(|t| t.flags).unwrap_or(0);
@@ -170,10 +179,32 @@ impl GameScene {
                 self.play_click_sound(app_state);
                 net.send(ClientCommand::new_look_item(world_x, world_y));
             }
-            MouseButton::Left => {
+            MouseButton::Left if ps.is_observer() => {
+                // Observers do not walk: the server moves the camera here.
                 self.play_click_sound(app_state);
                 net.send(ClientCommand::new_move(world_x, world_y));
             }
+            MouseButton::Left => {
+                self.play_click_sound(app_state);
+                // Send the previewed route when it fits in one waypoint list;
+                // otherwise let the server plan the walk.
+                let start = ps
+                    .map()
+                    .tile_at_xy(TILEX / 2, TILEY / 2)
+                    .map(|t| (t.x, t.y));
+                let waypoints = start
+                    .zip(super::path_preview::plan_route(ps.map(), (mx, my)))
+                    .map(|(start, route)| path_to_waypoints(start, &route))
+                    .filter(|w| !w.is_empty() && w.len() <= MAX_WAYPOINTS);
+                match waypoints {
+                    Some(waypoints) => {
+                        for packet in ClientCommand::new_waypoint_packets(&waypoints) {
+                            net.send(packet);
+                        }
+                    }
+                    None => net.send(ClientCommand::new_move(world_x, world_y)),
+                }
+            }
             MouseButton::Right => {
                 self.play_click_sound(app_state);
                 net.send(ClientCommand::new_turn(world_x, world_y));
@@ -1,11 +1,14 @@
+use std::time::Instant;
+
 use sdl2::{pixels::Color, render::Canvas, video::Window};
 
 use mag_core::constants::{
     CMAGIC, DEATH, DR_DROP, DR_GIVE, DR_PICKUP, DR_USE, EMAGIC, GMAGIC, INJURED, INJURED1,
-    INJURED2, INVIS, ISCHAR, ISITEM, ISUSABLE, MF_ARENA, MF_BANK, MF_DEATHTRAP, MF_INDOORS,
-    MF_MOVEBLOCK, MF_NOEXPIRE, MF_NOLAG, MF_NOMAGIC, MF_NOMONST, MF_SIGHTBLOCK, MF_TAVERN,
-    MF_UWATER, SPR_EMPTY, TILEX, TILEY, TOMB,
+    INJURED2, INVIS, ISCHAR, ISITEM, ISUSABLE, LOOT_PROTECTED, MF_ARENA, MF_BANK, MF_DEATHTRAP,
+    MF_INDOORS, MF_MOVEBLOCK, MF_NOEXPIRE, MF_NOLAG, MF_NOMAGIC, MF_NOMONST, MF_SIGHTBLOCK,
+    MF_TAVERN, MF_UWATER, SPR_EMPTY, TILEX, TILEY, TOMB,
 };
+use mag_core::decals::DecalKind;
 
 use crate::{font_cache, gfx_cache::GraphicsCache, player_state::PlayerState};
 
@@ -13,6 +16,10 @@ use super::{FLOOR_TILE_HEIGHT, FLOOR_TILE_WIDTH, GameScene};
 
 const PERCENT_HEALTH_TEXT_OFFSET_Y: i32 = 47;
 
+/// Minimum darkness level for items reserved for another player, so
+/// protected loot reads as greyed out even on brightly lit tiles.
+const PROTECTED_LOOT_DARKNESS: u8 = 9;
+
 #[derive(Copy, Clone)]
 enum HoverHighlight {
     Character {
@@ -52,6 +59,27 @@ impl GameScene {
         xoff: i32,
         yoff: i32,
         light: u8,
+    ) -> Result<(), String> {
+        Self::draw_world_sprite_alpha(
+            canvas, gfx, sprite_id, tile_x, tile_y, cam_xoff, cam_yoff, xoff, yoff, light, 255,
+        )
+    }
+
+    /// Draws a world sprite like [`Self::draw_world_sprite`] at a reduced
+    /// opacity (used to fade walls in front of the player).
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn draw_world_sprite_alpha(
+        canvas: &mut Canvas<Window>,
+        gfx: &mut GraphicsCache<'_>,
+        sprite_id: i32,
+        tile_x: usize,
+        tile_y: usize,
+        cam_xoff: i32,
+        cam_yoff: i32,
+        xoff: i32,
+        yoff: i32,
+        light: u8,
+        alpha: u8,
     ) -> Result<(), String> {
         if sprite_id <= 0 {
             return Ok(());
@@ -74,6 +102,9 @@ impl GameScene {
             let factor = (255 * Self::LEFFECT / (darkness * darkness + Self::LEFFECT)) as u8;
             texture.set_color_mod(factor, factor, factor);
         }
+        if alpha < 255 {
+            texture.set_alpha_mod(alpha);
+        }
 
         let result = canvas.copy(
             texture,
@@ -81,10 +112,11 @@ impl GameScene {
             Some(sdl2::rect::Rect::new(rx, ry, q.width, q.height)),
         );
 
-        // Reset color modulation so next draw of this sprite is unaffected.
-        if darkness > 0 {
+        // Reset color/alpha modulation so next draw of this sprite is unaffected.
+        if darkness > 0 || alpha < 255 {
             let texture = gfx.get_texture(sprite_id as usize);
             texture.set_color_mod(255, 255, 255);
+            texture.set_alpha_mod(255);
         }
 
         result
@@ -327,11 +359,11 @@ impl GameScene {
             return None;
         }
 
-        let (cam_xoff, cam_yoff) = Self::camera_offsets(ps);
-        if !Self::cursor_in_map_interaction_area(self.mouse_x, self.mouse_y, cam_xoff, cam_yoff) {
+        let (wx, wy, cam_xoff, cam_yoff) = self.world_cursor(ps, self.mouse_x, self.mouse_y);
+        if !Self::cursor_in_map_interaction_area(wx, wy, cam_xoff, cam_yoff) {
             return None;
         }
-        let (mx, my) = Self::screen_to_map_tile(self.mouse_x, self.mouse_y, cam_xoff, cam_yoff)?;
+        let (mx, my) = Self::screen_to_map_tile(wx, wy, cam_xoff, cam_yoff)?;
 
         if !(3..=TILEX - 7).contains(&mx) || !(7..=TILEY - 3).contains(&my) {
             return None;
@@ -432,11 +464,11 @@ impl GameScene {
                 .hovered_label(self.effective_shift_held());
         }
 
-        let (cam_xoff, cam_yoff) = Self::camera_offsets(ps);
-        if !Self::cursor_in_map_interaction_area(self.mouse_x, self.mouse_y, cam_xoff, cam_yoff) {
+        let (wx, wy, cam_xoff, cam_yoff) = self.world_cursor(ps, self.mouse_x, self.mouse_y);
+        if !Self::cursor_in_map_interaction_area(wx, wy, cam_xoff, cam_yoff) {
             return None;
         }
-        let (mx, my) = Self::screen_to_map_tile(self.mouse_x, self.mouse_y, cam_xoff, cam_yoff)?;
+        let (mx, my) = Self::screen_to_map_tile(wx, wy, cam_xoff, cam_yoff)?;
 
         let citem = ps.character_info().citem;
         let has_item = citem > 0;
@@ -478,6 +510,12 @@ impl GameScene {
 
     /// Render all world tiles in two painter-order passes (backgrounds, then
     /// objects/characters/effects). This is the main world-drawing entry point.
+    ///
+    /// `own_alias`, when set (streamer mode), replaces the player's own name
+    /// on the center nameplate. `camera_shift` is added to the camera
+    /// offsets (weather shake plus the zoom centring shift). When `hide` is
+    /// off, walls in front of the player or the hovered tile fade out within
+    /// `wall_fade_radius` tiles (see [`Self::occlusion_alpha`]).
     #[allow(clippy::too_many_arguments)]
     pub(super) fn draw_world(
         &self,
@@ -489,15 +527,32 @@ impl GameScene {
         show_names: bool,
         show_proz: bool,
         hide: bool,
-        camera_shake: (i32, i32),
+        wall_fade_radius: u8,
+        own_alias: Option<&str>,
+        camera_shift: (i32, i32),
     ) -> Result<(), String> {
         let map = ps.map();
         let ci = ps.character_info();
         let (cam_xoff_base, cam_yoff_base) = Self::camera_offsets(ps);
-        let cam_xoff = cam_xoff_base + camera_shake.0;
-        let cam_yoff = cam_yoff_base + camera_shake.1;
+        let cam_xoff = cam_xoff_base + camera_shift.0;
+        let cam_yoff = cam_yoff_base + camera_shift.1;
         let hover_highlight = self.resolve_hover_highlight(ps);
 
+        // Tiles kept visible by the wall fade: the player and the hovered tile.
+        let fade_radius = if hide { 0 } else { wall_fade_radius };
+        let hovered_tile = if fade_radius > 0 {
+            let (wx, wy, hx, hy) = self.world_cursor(ps, self.mouse_x, self.mouse_y);
+            if Self::cursor_in_map_interaction_area(wx, wy, hx, hy) {
+                Self::screen_to_map_tile(wx, wy, hx, hy)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        let fade_focus = [Some((TILEX / 2, TILEY / 2)), hovered_tile];
+        let frame_time = Instant::now();
+
         // Pass 1: Background / terrain sprites (legacy eng_display order: y descending).
         for y in (0..TILEY).rev() {
             for x in 0..TILEX {
@@ -534,6 +589,20 @@ impl GameScene {
                     tile.light,
                 )?;
 
+                if !self.decals.is_empty() {
+                    for (sprite, alpha, kind) in self.decals.at(tile.x, tile.y, frame_time) {
+                        // Scorch marks reuse the blood splatter, drawn at full
+                        // darkness so it reads as soot.
+                        let light = match kind {
+                            DecalKind::Scorch => tile.light | 0x0F,
+                            DecalKind::Blood => tile.light,
+                        };
+                        Self::draw_world_sprite_alpha(
+                            canvas, gfx, sprite, x, y, cam_xoff, cam_yoff, 0, 0, light, alpha,
+                        )?;
+                    }
+                }
+
                 if let Some(HoverHighlight::Floor {
                     x: hx,
                     y: hy,
@@ -552,6 +621,20 @@ impl GameScene {
                     Self::draw_world_sprite(
                         canvas, gfx, 31, x, y, cam_xoff, cam_yoff, 0, 0, tile.light,
                     )?;
+                } else if self.path_preview.contains(x, y) {
+                    Self::draw_world_sprite_alpha(
+                        canvas,
+                        gfx,
+                        31,
+                        x,
+                        y,
+                        cam_xoff,
+                        cam_yoff,
+                        0,
+                        0,
+                        tile.light,
+                        Self::PATH_PREVIEW_ALPHA,
+                    )?;
                 }
             }
         }
@@ -617,8 +700,23 @@ impl GameScene {
                         }
                     }
 
-                    Self::draw_world_sprite(
-                        canvas, gfx, obj, x, y, cam_xoff, cam_yoff, 0, 0, tile.light,
+                    let light = if (tile.flags & LOOT_PROTECTED) != 0 {
+                        (tile.light & 0xF0) | (tile.light & 0x0F).max(PROTECTED_LOOT_DARKNESS)
+                    } else {
+                        tile.light
+                    };
+                    let alpha = if is_item {
+                        255
+                    } else {
+                        fade_focus
+                            .iter()
+                            .flatten()
+                            .map(|&focus| Self::occlusion_alpha(x, y, focus, fade_radius))
+                            .min()
+                            .unwrap_or(255)
+                    };
+                    Self::draw_world_sprite_alpha(
+                        canvas, gfx, obj, x, y, cam_xoff, cam_yoff, 0, 0, light, alpha,
                     )?;
 
                     if let Some(HoverHighlight::Item {
@@ -707,16 +805,23 @@ impl GameScene {
 
                     let name: Option<String> = if show_names {
                         if is_center {
-                            let own = mag_core::string_operations::c_string_to_str(
-                                &ps.character_info().name,
-                            );
+                            let own = own_alias.unwrap_or_else(|| {
+                                mag_core::string_operations::c_string_to_str(
+                                    &ps.character_info().name,
+                                )
+                            });
                             if !own.is_empty() {
-                                Some(own.to_owned())
+                                Some(mag_core::titles::titled_name(ps.title_selected(), own))
                             } else {
                                 None
                             }
                         } else {
-                            ps.lookup_name(tile.ch_nr, tile.ch_id).map(|s| s.to_owned())
+                            ps.lookup_name(tile.ch_nr, tile.ch_id).map(|s| {
+                                mag_core::titles::titled_name(
+                                    ps.lookup_title(tile.ch_nr, tile.ch_id),
+                                    s,
+                                )
+                            })
                         }
                     } else {
                         None
@@ -738,6 +843,14 @@ impl GameScene {
                         _ => String::new(),
                     };
 
+                    let name_tint = if show_names && !is_center {
+                        crate::ui::style::name_color_tint(
+                            ps.lookup_name_color(tile.ch_nr, tile.ch_id),
+                        )
+                    } else {
+                        None
+                    };
+
                     if !text.is_empty() {
                         // dd_gputtext formula (ported from engine.c + nameplates.rs):
                         // horizontally centered, shifted 64px up relative to sprite origin.
@@ -751,7 +864,10 @@ impl GameScene {
                             &text,
                             np_rx,
                             np_ry,
-                            font_cache::TextStyle::drop_shadow(),
+                            match name_tint {
+                                Some(tint) => font_cache::TextStyle::drop_shadow().with_tint(tint),
+                                None => font_cache::TextStyle::drop_shadow(),
+                            },
                         )?;
                     }
                 }
@@ -0,0 +1,170 @@
+//! Camera zoom.
+//!
+//! Zooming out widens the view radius the camera shows (see
+//! [`mag_core::view`]). The world pass is drawn with a reduced render scale
+//! into a correspondingly larger logical area, shifted so the player's tile
+//! stays centred; the HUD is drawn at the normal scale afterwards. Servers
+//! that never send `SV_SETVIEW` keep the camera at the legacy radius.
+
+use mag_core::view::{self, LEGACY_VIEW_RADIUS};
+
+use crate::constants::{TARGET_HEIGHT, TARGET_WIDTH};
+use crate::player_state::PlayerState;
+use crate::preferences::Settings;
+
+use super::GameScene;
+
+impl GameScene {
+    /// View radius the camera shows: the player's preference, limited to
+    /// what the server allows.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings holding the preferred radius.
+    /// * `ps` - Player state holding the server's radius.
+    ///
+    /// # Returns
+    ///
+    /// * The widest allowed zoom step not beyond the preference.
+    pub(super) fn effective_view_radius(settings: &Settings, ps: &PlayerState) -> u8 {
+        view::zoom_steps(ps.max_view_radius())
+            .into_iter()
+            .rev()
+            .find(|&radius| radius <= settings.view_radius)
+            .unwrap_or(LEGACY_VIEW_RADIUS)
+    }
+
+    /// Moves the preferred view radius one zoom step in or out.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Settings to update.
+    /// * `ps` - Player state holding the server's radius.
+    /// * `zoom_out` - `true` to widen the view, `false` to narrow it.
+    ///
+    /// # Returns
+    ///
+    /// * The new effective radius.
+    pub(super) fn step_zoom(settings: &mut Settings, ps: &PlayerState, zoom_out: bool) -> u8 {
+        let steps = view::zoom_steps(ps.max_view_radius());
+        let current = Self::effective_view_radius(settings, ps);
+        let index = steps.iter().position(|&r| r == current).unwrap_or(0);
+        let next = if zoom_out {
+            (index + 1).min(steps.len() - 1)
+        } else {
+            index.saturating_sub(1)
+        };
+        settings.view_radius = steps[next];
+        steps[next]
+    }
+
+    /// World units drawn per logical screen unit at `radius`.
+    fn zoom_factor(radius: u8) -> f32 {
+        f32::from(radius.max(LEGACY_VIEW_RADIUS)) / f32::from(LEGACY_VIEW_RADIUS)
+    }
+
+    /// Render scale multiplier for the world pass at `radius`.
+    ///
+    /// # Returns
+    ///
+    /// * `1.0` for the legacy radius, smaller for wider views.
+    pub(super) fn zoom_render_scale(radius: u8) -> f32 {
+        1.0 / Self::zoom_factor(radius)
+    }
+
+    /// Camera shift that keeps the centre tile centred in the enlarged world
+    /// area drawn at `radius`.
+    ///
+    /// # Returns
+    ///
+    /// * `(dx, dy)` in world units, `(0, 0)` for the legacy radius.
+    pub(super) fn zoom_shift(radius: u8) -> (i32, i32) {
+        let extra = Self::zoom_factor(radius) - 1.0;
+        (
+            (TARGET_WIDTH * extra / 2.0).round() as i32,
+            (TARGET_HEIGHT * extra / 2.0).round() as i32,
+        )
+    }
+
+    /// Converts a logical screen position to world coordinates and the
+    /// camera offsets to pick tiles with, undoing the current zoom and
+    /// free-camera pan.
+    ///
+    /// # Arguments
+    ///
+    /// * `ps` - Player state holding the camera offsets.
+    /// * `screen_x` - Logical screen X.
+    /// * `screen_y` - Logical screen Y.
+    ///
+    /// # Returns
+    ///
+    /// * `(world_x, world_y, cam_xoff, cam_yoff)` for
+    ///   [`Self::screen_to_map_tile`].
+    pub(super) fn world_cursor(
+        &self,
+        ps: &PlayerState,
+        screen_x: i32,
+        screen_y: i32,
+    ) -> (i32, i32, i32, i32) {
+        let factor = Self::zoom_factor(self.view_radius);
+        let (cam_xoff, cam_yoff) = Self::camera_offsets(ps);
+        let (shift_x, shift_y) = Self::zoom_shift(self.view_radius);
+        let (pan_x, pan_y) = self.free_camera_shift();
+        (
+            (screen_x as f32 * factor).round() as i32,
+            (screen_y as f32 * factor).round() as i32,
+            cam_xoff + shift_x + pan_x,
+            cam_yoff + shift_y + pan_y,
+        )
+    }
+
+    /// Returns the map tile under a logical screen position, taking the
+    /// current zoom into account.
+    ///
+    /// # Arguments
+    ///
+    /// * `ps` - Player state holding the map and camera offsets.
+    /// * `screen_x` - Logical screen X.
+    /// * `screen_y` - Logical screen Y.
+    ///
+    /// # Returns
+    ///
+    /// * `Some((mx, my))` if a tile lies under the position.
+    pub(super) fn pick_map_tile(
+        &self,
+        ps: &PlayerState,
+        screen_x: i32,
+        screen_y: i32,
+    ) -> Option<(usize, usize)> {
+        let (world_x, world_y, cam_xoff, cam_yoff) = self.world_cursor(ps, screen_x, screen_y);
+        Self::screen_to_map_tile(world_x, world_y, cam_xoff, cam_yoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mag_core::constants::{TILEX, TILEY};
+
+    #[test]
+    fn legacy_radius_is_unzoomed() {
+        assert_eq!(GameScene::zoom_shift(LEGACY_VIEW_RADIUS), (0, 0));
+        assert_eq!(GameScene::zoom_render_scale(LEGACY_VIEW_RADIUS), 1.0);
+    }
+
+    #[test]
+    fn zoom_scales_the_world_around_the_viewport_centre() {
+        let radius = view::MAX_VIEW_RADIUS;
+        let factor = GameScene::zoom_factor(radius);
+        let (shift_x, shift_y) = GameScene::zoom_shift(radius);
+        let (centre_x, centre_y) = (TARGET_WIDTH / 2.0, TARGET_HEIGHT / 2.0);
+        for (tx, ty) in [(TILEX / 2, TILEY / 2), (TILEX / 2 + 5, TILEY / 2 - 3)] {
+            let (ux, uy) = GameScene::tile_ground_diamond_origin(tx, ty, 0, 0);
+            let (zx, zy) = GameScene::tile_ground_diamond_origin(tx, ty, shift_x, shift_y);
+            let expected_x = centre_x + (ux as f32 - centre_x) / factor;
+            let expected_y = centre_y + (uy as f32 - centre_y) / factor;
+            assert!((zx as f32 / factor - expected_x).abs() <= 1.0);
+            assert!((zy as f32 / factor - expected_y).abs() <= 1.0);
+        }
+    }
+}
@@ -97,7 +97,9 @@ impl LoginScene {
         app_state.settings.music_enabled = settings.music_enabled;
 
         if app_state.settings.music_enabled {
-            app_state.sfx_cache.play_music(MusicTrack::LoginTheme);
+            app_state
+                .sfx_cache
+                .play_music(MusicTrack::LoginTheme, &app_state.settings.audio_mix());
         } else {
             app_state.sfx_cache.stop_music();
         }
@@ -349,7 +351,9 @@ impl Scene for LoginScene {
                 LoginFormAction::ToggleMusic(enabled) => {
                     app_state.settings.music_enabled = enabled;
                     if enabled {
-                        app_state.sfx_cache.play_music(MusicTrack::LoginTheme);
+                        app_state
+                            .sfx_cache
+                            .play_music(MusicTrack::LoginTheme, &app_state.settings.audio_mix());
                     } else {
                         app_state.sfx_cache.stop_music();
                     }
@@ -40,6 +40,7 @@ pub enum SceneType {
     NewAccount,
     RequestReset,
     EnterResetCode,
+    Replay,
     Exit,
 }
 
@@ -104,6 +105,11 @@ impl SceneManager {
             Box::new(crate::scenes::character_selection::CharacterSelectionScene::new()),
         );
 
+        scene_map.insert(
+            SceneType::Replay,
+            Box::new(crate::scenes::game::ReplayScene::new()),
+        );
+
         scene_map.insert(
             SceneType::Exit,
             Box::new(crate::scenes::exit::ExitScene::new()),
@@ -3,6 +3,57 @@ use std::{collections::HashMap, path::PathBuf};
 
 const LOGIN_MUSIC_CHANNEL: i32 = 0;
 
+/// Isometric distance (in tiles) at which positional sounds fall silent.
+/// The server only sends area sounds from within 8 tiles on each axis.
+pub const SFX_ROLLOFF_TILES: f32 = 14.0;
+
+/// Horizontal screen distance (in tiles) at which a positional sound is
+/// panned hard to one side.
+pub const SFX_PAN_SPREAD_TILES: f32 = 8.0;
+
+/// Per-category volumes, each 0.0–1.0, applied on top of the master volume.
+///
+/// Built from the player's settings with [`crate::preferences::Settings::audio_mix`]
+/// and passed to every playback call so slider changes apply immediately.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioMix {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+    pub ui: f32,
+}
+
+impl AudioMix {
+    /// Effective level of one category on the slider scale: master ×
+    /// category, each clamped to 0.0–1.0.
+    fn gain(&self, category: f32) -> f32 {
+        self.master.clamp(0.0, 1.0) * category.clamp(0.0, 1.0)
+    }
+}
+
+/// Volume and pan of a sound `dx`/`dy` tiles away from the listener.
+///
+/// Tile offsets are projected onto the screen the same way the map is
+/// drawn (one tile step moves half a tile sideways and a quarter down), so
+/// sounds are as loud and as far left or right as they look.
+///
+/// # Arguments
+/// * `dx`, `dy` - Tile offset of the source from the listener.
+///
+/// # Returns
+/// * `(gain, pan)`: gain in 0.0–1.0 on the same perceptual scale as the
+///   volume sliders, and pan in -1.0 (left) – 1.0 (right).
+pub fn positional_gain_pan(dx: i32, dy: i32) -> (f32, f32) {
+    // Screen offset in tile widths: x = (dx + dy) / 2, y = (dx - dy) / 4.
+    let screen_x = (dx + dy) as f32 / 2.0;
+    let screen_y = (dx - dy) as f32 / 4.0;
+    // Scale back so a straight step along either map axis counts as one tile.
+    let distance = screen_x.hypot(screen_y) / 0.5f32.hypot(0.25);
+    let falloff = (1.0 - distance / SFX_ROLLOFF_TILES).clamp(0.0, 1.0);
+    let pan = (screen_x * 2.0 / SFX_PAN_SPREAD_TILES).clamp(-1.0, 1.0);
+    (falloff, pan)
+}
+
 /// Manages pre-loaded sound effects and background music tracks.
 ///
 /// Sound effects are identified by numeric sprite IDs; music tracks by the
@@ -43,6 +94,15 @@ impl SoundCache {
         (base as f32 * master).round() as i32
     }
 
+    /// Converts a pan in -1.0–1.0 to SDL left/right channel levels using an
+    /// equal-power curve, so a sound keeps its loudness as it moves across.
+    fn equal_power_panning(pan: f32) -> (u8, u8) {
+        let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+        let left = (angle.cos() * 255.0).round() as u8;
+        let right = (angle.sin() * 255.0).round() as u8;
+        (left, right)
+    }
+
     fn convert_server_pan(pan: i32) -> u8 {
         if (-500..=500).contains(&pan) {
             // Server pan convention: -500 = hard left, 0 = center, 500 = hard right.
@@ -124,7 +184,8 @@ impl SoundCache {
 
         let mut music_cache: HashMap<MusicTrack, Chunk> = HashMap::new();
 
-        let music_path = music_directory.join("login.mp3");
+        let music_path =
+            crate::asset_resolver::assets().resolve_path(&music_directory.join("login.mp3"));
         match Chunk::from_file(&music_path) {
             Ok(chunk) => {
                 music_cache.insert(MusicTrack::LoginTheme, chunk);
@@ -147,7 +208,7 @@ impl SoundCache {
     }
 
     /// Play a sound effect by numeric ID. `vol` is 0-127, `pan` is 0 (left) – 255 (right)
-    /// with 128 as center. `master_volume` is a 0.0–1.0 multiplier applied on top.
+    /// with 128 as center. The effects volume of `mix` is applied on top.
     /// Mismatched or missing IDs are silently ignored.
     ///
     /// # Arguments
@@ -155,8 +216,8 @@ impl SoundCache {
     /// * `nr` - Numeric identifier used by this function.
     /// * `vol` - Value passed to `play_sfx`.
     /// * `pan` - Value passed to `play_sfx`.
-    /// * `master_volume` - Value passed to `play_sfx`.
-    pub fn play_sfx(&self, nr: usize, vol: i32, pan: i32, master_volume: f32) {
+    /// * `mix` - Current category volumes.
+    pub fn play_sfx(&self, nr: usize, vol: i32, pan: i32, mix: &AudioMix) {
         if self.disabled {
             return;
         }
@@ -167,7 +228,7 @@ impl SoundCache {
         match Channel::all().play(chunk, 0) {
             Ok(ch) => {
                 // SDL_mixer volume is 0-128.
-                let scaled = Self::convert_server_volume(vol, master_volume);
+                let scaled = Self::convert_server_volume(vol, mix.gain(mix.effects));
                 let sdl_vol = scaled * 128 / 127;
                 ch.set_volume(sdl_vol.clamp(0, 128));
                 // Panning: left + right must sum to ~255.
@@ -181,12 +242,47 @@ impl SoundCache {
         }
     }
 
+    /// Play a sound effect emitted `dx`/`dy` tiles away from the listener,
+    /// attenuated and panned by [`positional_gain_pan`] and scaled by the
+    /// effects volume of `mix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `nr` - Numeric sound identifier.
+    /// * `dx`, `dy` - Tile offset of the source from the listener.
+    /// * `mix` - Current category volumes.
+    pub fn play_sfx_at(&self, nr: usize, dx: i32, dy: i32, mix: &AudioMix) {
+        if self.disabled {
+            return;
+        }
+        let Some(chunk) = self.sfx_cache.get(&nr) else {
+            return;
+        };
+        let (falloff, pan) = positional_gain_pan(dx, dy);
+        if falloff <= 0.0 {
+            return;
+        }
+        match Channel::all().play(chunk, 0) {
+            Ok(ch) => {
+                // Distance falloff takes the place of the server's attenuation.
+                let scaled = Self::convert_server_volume(0, mix.gain(mix.effects) * falloff);
+                let sdl_vol = scaled * 128 / 127;
+                ch.set_volume(sdl_vol.clamp(0, 128));
+                let (left, right) = Self::equal_power_panning(pan);
+                let _ = ch.set_panning(left, right);
+            }
+            Err(e) => {
+                log::warn!("Failed to play sfx {}: {}", nr, e);
+            }
+        }
+    }
+
     /// Plays the classic UI click sound (`click.wav`) if present in the asset pack.
     ///
     /// # Arguments
     ///
-    /// * `master_volume` - Value passed to `play_click`.
-    pub fn play_click(&self, master_volume: f32) {
+    /// * `mix` - Current category volumes; the UI volume applies.
+    pub fn play_click(&self, mix: &AudioMix) {
         if self.disabled {
             return;
         }
@@ -196,7 +292,7 @@ impl SoundCache {
 
         match Channel::all().play(chunk, 0) {
             Ok(ch) => {
-                let scaled = Self::convert_server_volume(-1000, master_volume);
+                let scaled = Self::convert_server_volume(-1000, mix.gain(mix.ui));
                 let sdl_vol = scaled * 128 / 127;
                 ch.set_volume(sdl_vol.clamp(0, 128));
                 let right = Self::convert_server_pan(0);
@@ -213,17 +309,38 @@ impl SoundCache {
     ///
     /// # Arguments
     /// * `track` - The [`MusicTrack`] to play.
-    pub fn play_music(&self, track: MusicTrack) {
+    /// * `mix` - Current category volumes; the music volume applies.
+    pub fn play_music(&self, track: MusicTrack, mix: &AudioMix) {
         if self.disabled {
             return;
         }
-        if let Some(chunk) = self.music_cache.get(&track)
-            && let Err(e) = Channel(LOGIN_MUSIC_CHANNEL).play(chunk, -1)
-        {
-            log::warn!("Failed to play music: {}", e);
+        if let Some(chunk) = self.music_cache.get(&track) {
+            match Channel(LOGIN_MUSIC_CHANNEL).play(chunk, -1) {
+                Ok(ch) => {
+                    ch.set_volume(Self::music_channel_volume(mix));
+                }
+                Err(e) => log::warn!("Failed to play music: {}", e),
+            };
         }
     }
 
+    /// Applies a changed music volume to the music channel while it plays.
+    ///
+    /// # Arguments
+    /// * `mix` - Current category volumes.
+    pub fn update_music_volume(&self, mix: &AudioMix) {
+        if self.disabled {
+            return;
+        }
+        Channel(LOGIN_MUSIC_CHANNEL).set_volume(Self::music_channel_volume(mix));
+    }
+
+    /// SDL channel volume (0–128) for music under `mix`.
+    fn music_channel_volume(mix: &AudioMix) -> i32 {
+        let scaled = Self::convert_server_volume(0, mix.gain(mix.music));
+        (scaled * 128 / 127).clamp(0, 128)
+    }
+
     /// Stops any currently playing music on the dedicated music channel.
     pub fn stop_music(&self) {
         if self.disabled {
@@ -274,6 +391,46 @@ mod tests {
         assert!(sdl_vol(0, 0.1) * 10 < sdl_vol(0, 1.0));
     }
 
+    #[test]
+    fn categories_scale_with_master() {
+        let mix = AudioMix {
+            master: 0.5,
+            music: 1.0,
+            effects: 0.5,
+            ui: 0.0,
+        };
+        assert!((mix.gain(mix.music) - 0.5).abs() < f32::EPSILON);
+        assert!((mix.gain(mix.effects) - 0.25).abs() < f32::EPSILON);
+        assert_eq!(mix.gain(mix.ui), 0.0);
+    }
+
+    #[test]
+    fn positional_sounds_fade_and_pan_with_screen_position() {
+        let (gain, pan) = positional_gain_pan(0, 0);
+        assert_eq!((gain, pan), (1.0, 0.0));
+
+        // One step along either map axis is one tile away.
+        let (near_x, _) = positional_gain_pan(1, 0);
+        let (near_y, _) = positional_gain_pan(0, 1);
+        assert!((near_x - near_y).abs() < 1e-6);
+        assert!(near_x < 1.0 && near_x > positional_gain_pan(6, 0).0);
+
+        // (+1, +1) is straight right on screen, (+1, -1) straight down.
+        assert!(positional_gain_pan(3, 3).1 > 0.5);
+        assert!(positional_gain_pan(-3, -3).1 < -0.5);
+        assert_eq!(positional_gain_pan(3, -3).1, 0.0);
+
+        assert_eq!(positional_gain_pan(8, 8).0, 0.0);
+    }
+
+    #[test]
+    fn equal_power_pan_is_centered_and_saturates() {
+        let (l, r) = SoundCache::equal_power_panning(0.0);
+        assert_eq!(l, r);
+        assert_eq!(SoundCache::equal_power_panning(1.0), (0, 255));
+        assert_eq!(SoundCache::equal_power_panning(-1.0), (255, 0));
+    }
+
     #[test]
     fn server_attenuation_still_applied() {
         // Server vol -5000 (maximum attenuation) should produce near-zero even at master=1.
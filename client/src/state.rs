@@ -1,4 +1,7 @@
+use std::path::PathBuf;
+
 use crate::{
+    bug_report::BugReportRequest,
     font_cache::TextEngine,
     gfx_cache::GraphicsCache,
     network::NetworkRuntime,
@@ -79,6 +82,8 @@ pub struct AppState<'tc> {
     pub settings: Settings,
     /// Pending display change to be applied by the main loop.
     pub display_command: Option<DisplayCommand>,
+    /// Bug report to package once the current frame has been drawn.
+    pub bug_report_request: Option<BugReportRequest>,
     /// `true` when the most recent input came from a game controller rather
     /// than keyboard/mouse. Widgets read this flag to adapt their rendering
     /// (e.g. show controller button prompts instead of key hints).
@@ -87,8 +92,13 @@ pub struct AppState<'tc> {
     pub panning_background: PanningBackground,
     /// Username carried between the request-reset and enter-reset-code scenes.
     pub reset_username: Option<String>,
+    /// Recording opened by the replay scene (from `MAG_REPLAY_PATH`).
+    pub replay_path: Option<PathBuf>,
     /// The platform detected at startup, used for platform-specific behaviour.
     pub platform: PlatformProfile,
+    /// Why the last game session ended, shown once by the character
+    /// selection scene. `None` after a normal logout.
+    pub disconnect_message: Option<String>,
 }
 
 impl<'tc> AppState<'tc> {
@@ -124,10 +134,13 @@ impl<'tc> AppState<'tc> {
             player_state: None,
             settings: Settings::default(),
             display_command: None,
+            bug_report_request: None,
             controller_active: false,
             panning_background,
             reset_username: None,
+            replay_path: None,
             platform,
+            disconnect_message: None,
         }
     }
 }
@@ -0,0 +1,246 @@
+//! Streamer mode: display-side filtering for recording and streaming.
+//!
+//! When enabled, chat lines are shown with profanity masked, the player's
+//! own character name is replaced by a display alias (in chat and on the
+//! overhead nameplate) and the carried gold amount is hidden. Nothing sent
+//! to or received from the server changes; the filter only rewrites text at
+//! render time, so toggling the mode also applies to lines already in the
+//! chat log.
+//!
+//! The profanity list is read from `mag_chat_filter.txt` in the client data
+//! directory (one entry per line, `#` starts a comment). Without that file a
+//! short built-in list is used.
+
+use std::borrow::Cow;
+use std::fs;
+
+use crate::preferences;
+
+/// Alias shown instead of the character name when none is configured.
+pub const DEFAULT_ALIAS: &str = "Adventurer";
+
+/// Longest alias accepted, matching the character name limit.
+pub const MAX_ALIAS_LEN: usize = 15;
+
+/// Entries used when no local filter list exists.
+const DEFAULT_FILTER_WORDS: &[&str] = &[
+    "asshole", "bastard", "bitch", "cunt", "fuck", "motherf", "shit", "slut", "twat", "wank",
+    "whore",
+];
+
+/// Parse a filter list file.
+///
+/// # Arguments
+///
+/// * `raw` - File contents, one entry per line.
+///
+/// # Returns
+///
+/// * Lowercased, de-duplicated entries; blank lines and `#` comments are
+///   skipped.
+pub fn parse_filter_list(raw: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        let word = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        if !word.is_empty() && !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// Clean up a user-supplied alias.
+///
+/// # Arguments
+///
+/// * `raw` - Alias text as typed.
+///
+/// # Returns
+///
+/// * The alias limited to ASCII letters, digits and spaces and at most
+///   [`MAX_ALIAS_LEN`] characters, or [`DEFAULT_ALIAS`] if nothing is left.
+pub fn sanitize_alias(raw: &str) -> String {
+    let alias: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == ' ')
+        .take(MAX_ALIAS_LEN)
+        .collect();
+    let alias = alias.trim();
+    if alias.is_empty() {
+        DEFAULT_ALIAS.to_owned()
+    } else {
+        alias.to_owned()
+    }
+}
+
+/// Text rewriting applied while streamer mode is on.
+#[derive(Clone, Debug)]
+pub struct StreamerFilter {
+    /// Lowercased profanity entries; a word is masked when it starts with
+    /// one of them.
+    words: Vec<String>,
+    /// Own character name, lowercased; empty until known.
+    own_name: String,
+    alias: String,
+}
+
+impl StreamerFilter {
+    /// Creates a filter with an explicit word list.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - Profanity entries (see [`parse_filter_list`]).
+    /// * `alias` - Name shown instead of the player's own.
+    ///
+    /// # Returns
+    ///
+    /// * A filter that does not know the own name yet.
+    pub fn new(words: Vec<String>, alias: &str) -> Self {
+        Self {
+            words,
+            own_name: String::new(),
+            alias: sanitize_alias(alias),
+        }
+    }
+
+    /// Creates a filter from the local filter list, falling back to the
+    /// built-in entries when the file is missing or empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - Name shown instead of the player's own.
+    ///
+    /// # Returns
+    ///
+    /// * The loaded filter.
+    pub fn load(alias: &str) -> Self {
+        let path = preferences::chat_filter_file_path();
+        let words = fs::read_to_string(&path)
+            .map(|raw| parse_filter_list(&raw))
+            .unwrap_or_default();
+        if words.is_empty() {
+            let defaults = DEFAULT_FILTER_WORDS.iter().map(|w| (*w).to_owned());
+            return Self::new(defaults.collect(), alias);
+        }
+        log::info!(
+            "Loaded {} chat filter entries from {}",
+            words.len(),
+            path.display()
+        );
+        Self::new(words, alias)
+    }
+
+    /// Sets the player's own character name so it can be replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Character name as received from the server.
+    pub fn set_own_name(&mut self, name: &str) {
+        let name = name.trim();
+        if !self.own_name.eq_ignore_ascii_case(name) {
+            self.own_name = name.to_ascii_lowercase();
+        }
+    }
+
+    /// Returns the alias shown instead of the own name.
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    /// Rewrites one line of chat for display.
+    ///
+    /// Profane words are replaced by `*` of the same length and whole-word
+    /// occurrences of the own name by the alias.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Line as received.
+    ///
+    /// # Returns
+    ///
+    /// * The line unchanged (borrowed) when nothing matched.
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut out: Option<String> = None;
+        let mut copied = 0;
+        for (start, end) in words_of(text) {
+            let word = &text[start..end];
+            let replacement =
+                if !self.own_name.is_empty() && word.eq_ignore_ascii_case(&self.own_name) {
+                    Some(self.alias.clone())
+                } else if self.is_profane(word) {
+                    Some("*".repeat(word.chars().count()))
+                } else {
+                    None
+                };
+            if let Some(replacement) = replacement {
+                let buf = out.get_or_insert_with(|| String::with_capacity(text.len()));
+                buf.push_str(&text[copied..start]);
+                buf.push_str(&replacement);
+                copied = end;
+            }
+        }
+        match out {
+            Some(mut buf) => {
+                buf.push_str(&text[copied..]);
+                Cow::Owned(buf)
+            }
+            None => Cow::Borrowed(text),
+        }
+    }
+
+    fn is_profane(&self, word: &str) -> bool {
+        let lower = word.to_ascii_lowercase();
+        self.words
+            .iter()
+            .any(|entry| lower.starts_with(entry.as_str()))
+    }
+}
+
+/// Byte ranges of the alphanumeric words in `text`.
+fn words_of(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while chars.next_if(|(_, c)| !c.is_alphanumeric()).is_some() {}
+        let (start, _) = *chars.peek()?;
+        let mut end = start;
+        while let Some((i, c)) = chars.next_if(|(_, c)| c.is_alphanumeric()) {
+            end = i + c.len_utf8();
+        }
+        Some((start, end))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_masks_profanity_and_replaces_own_name() {
+        let mut filter =
+            StreamerFilter::new(parse_filter_list("# list\nShit\nfrak # scifi\n"), "Hero");
+        filter.set_own_name("Ishtar");
+
+        assert_eq!(
+            filter.apply("Bob says: Ishtar, this is shitty frakking loot!"),
+            "Bob says: Hero, this is ****** ******** loot!"
+        );
+        // Substrings of other words are left alone.
+        assert_eq!(
+            filter.apply("Ishtaros shouts: hello"),
+            "Ishtaros shouts: hello"
+        );
+        assert!(matches!(filter.apply("all clean"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn sanitize_alias_limits_charset_and_length() {
+        assert_eq!(sanitize_alias("  Cool Dude!!  "), "Cool Dude");
+        assert_eq!(sanitize_alias("<>"), DEFAULT_ALIAS);
+        assert_eq!(sanitize_alias(&"x".repeat(40)).len(), MAX_ALIAS_LEN);
+    }
+}
@@ -0,0 +1,135 @@
+//! Typed client-side notification events.
+//!
+//! Systems that want to tell the player about something important push a
+//! [`ClientEvent`] into the [`ClientEventQueue`] owned by `PlayerState`
+//! instead of writing a line into the chat log. The game scene drains the
+//! queue once per frame and turns each event into a toast.
+
+use std::collections::VecDeque;
+
+/// Maximum number of undrained events kept; the oldest are dropped first.
+const MAX_QUEUED_EVENTS: usize = 32;
+
+/// A notification-worthy event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientEvent {
+    /// A friend logged in.
+    FriendOnline {
+        /// Character name of the friend.
+        name: String,
+    },
+    /// Another player wants to trade.
+    TradeRequest {
+        /// Character name of the requesting player.
+        from: String,
+    },
+    /// New mail arrived.
+    MailReceived {
+        /// Character name of the sender.
+        from: String,
+    },
+    /// The player unlocked something (title, completed quest, ...).
+    AchievementUnlocked {
+        /// Short description of what was unlocked.
+        name: String,
+    },
+    /// An earned item did not fit into the pack and was delivered elsewhere.
+    RewardDelivered {
+        /// Server notice saying where the item went.
+        text: String,
+    },
+}
+
+impl ClientEvent {
+    /// Returns the toast heading for this event.
+    ///
+    /// # Returns
+    ///
+    /// * A short, static heading.
+    pub fn heading(&self) -> &'static str {
+        match self {
+            ClientEvent::FriendOnline { .. } => "Friend online",
+            ClientEvent::TradeRequest { .. } => "Trade request",
+            ClientEvent::MailReceived { .. } => "New mail",
+            ClientEvent::AchievementUnlocked { .. } => "Achievement",
+            ClientEvent::RewardDelivered { .. } => "Pack full",
+        }
+    }
+
+    /// Returns the toast body text for this event.
+    ///
+    /// # Returns
+    ///
+    /// * A one-line description.
+    pub fn body(&self) -> String {
+        match self {
+            ClientEvent::FriendOnline { name } => format!("{} is now online.", name),
+            ClientEvent::TradeRequest { from } => format!("{} wants to trade.", from),
+            ClientEvent::MailReceived { from } => format!("From {}.", from),
+            ClientEvent::AchievementUnlocked { name } => name.clone(),
+            ClientEvent::RewardDelivered { text } => text.clone(),
+        }
+    }
+}
+
+/// FIFO of pending [`ClientEvent`]s.
+#[derive(Default, Debug)]
+pub struct ClientEventQueue {
+    events: VecDeque<ClientEvent>,
+}
+
+impl ClientEventQueue {
+    /// Queues an event, dropping the oldest one when the queue is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Event to queue.
+    pub fn push(&mut self, event: ClientEvent) {
+        if self.events.len() >= MAX_QUEUED_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Removes and returns all queued events, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// * The drained events.
+    pub fn drain(&mut self) -> Vec<ClientEvent> {
+        self.events.drain(..).collect()
+    }
+
+    /// Returns `true` when no events are queued.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_drains_in_order_and_caps_length() {
+        let mut q = ClientEventQueue::default();
+        for i in 0..MAX_QUEUED_EVENTS + 2 {
+            q.push(ClientEvent::AchievementUnlocked {
+                name: format!("#{}", i),
+            });
+        }
+        let drained = q.drain();
+        assert_eq!(drained.len(), MAX_QUEUED_EVENTS);
+        assert_eq!(
+            drained[0],
+            ClientEvent::AchievementUnlocked {
+                name: "#2".to_owned()
+            }
+        );
+        assert!(q.is_empty());
+    }
+}
@@ -12,4 +12,18 @@ pub enum LogMessageColor {
 pub struct LogMessage {
     pub message: String,
     pub color: LogMessageColor,
+    /// Channel and presentation flags from `SV_LOGSTYLED`; default for
+    /// legacy `SV_LOG0..3` lines and client-side messages.
+    pub style: mag_core::chat::ChatStyle,
+    /// Item links in `message`, in order; empty for most lines.
+    pub links: Vec<ChatItemLink>,
+}
+
+/// An item link inside a chat line, drawn as its `[name]` text.
+#[derive(Clone, Debug)]
+pub struct ChatItemLink {
+    /// Byte range of the `[name]` text in [`LogMessage::message`].
+    pub range: std::ops::Range<usize>,
+    /// Snapshot from `SV_ITEMLINK`, or `None` when it never arrived.
+    pub item: Option<std::sync::Arc<mag_core::item_link::ItemLink>>,
 }
@@ -21,6 +21,8 @@ pub struct Look {
     item: [u16; 62],
     price: [u32; 62],
     pl_price: u32,
+    name_color: u8,
+    title: u8,
 }
 
 impl Default for Look {
@@ -43,6 +45,8 @@ impl Default for Look {
             item: [0; 62],
             price: [0; 62],
             pl_price: 0,
+            name_color: 0,
+            title: 0,
         }
     }
 }
@@ -243,6 +247,42 @@ impl Look {
         self.sprite = sprite;
     }
 
+    /// Returns the looked-at character's profile name color.
+    ///
+    /// # Returns
+    ///
+    /// * The decoded [`NameColor`](mag_core::profile::NameColor).
+    pub fn name_color(&self) -> mag_core::profile::NameColor {
+        mag_core::profile::NameColor::from(self.name_color)
+    }
+
+    /// Sets the looked-at character's profile name color byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_color` - Raw name color byte from `SV_LOOK2`.
+    pub fn set_name_color(&mut self, name_color: u8) {
+        self.name_color = name_color;
+    }
+
+    /// Returns the looked-at character's selected title id.
+    ///
+    /// # Returns
+    ///
+    /// * Title id (see `mag_core::titles`), `0` = none.
+    pub fn title(&self) -> u8 {
+        self.title
+    }
+
+    /// Sets the looked-at character's selected title id.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - Raw title byte from `SV_LOOK3`.
+    pub fn set_title(&mut self, title: u8) {
+        self.title = title;
+    }
+
     /// Sets the looked-at character's point total.
     ///
     /// # Arguments
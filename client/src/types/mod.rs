@@ -1,3 +1,4 @@
+pub mod client_event;
 pub mod controller;
 pub mod log_message;
 pub mod look;
@@ -11,6 +11,8 @@ use std::time::Duration;
 use sdl2::pixels::Color;
 use sdl2::render::BlendMode;
 
+use mag_core::types::MAX_CHARACTERS_PER_ACCOUNT;
+
 use crate::constants::{TARGET_HEIGHT_INT, TARGET_WIDTH_INT};
 use crate::font_cache;
 use crate::ui::RenderContext;
@@ -274,6 +276,18 @@ impl CharacterSelectionForm {
             .map(|(_, name)| name.as_str())
     }
 
+    /// Queues [`CharacterSelectionFormAction::CreateNew`], or shows an
+    /// error instead when the roster is already full.
+    fn request_create(&mut self) {
+        if self.character_list.item_count() >= MAX_CHARACTERS_PER_ACCOUNT {
+            self.error_text = Some(format!(
+                "You can have at most {MAX_CHARACTERS_PER_ACCOUNT} characters."
+            ));
+            return;
+        }
+        self.actions.push(CharacterSelectionFormAction::CreateNew);
+    }
+
     /// Number of buttons below the list.
     const BUTTON_COUNT: usize = 4;
 
@@ -346,7 +360,7 @@ impl Widget for CharacterSelectionForm {
                         self.character_list.confirm_controller_cursor();
                     }
                     Some(i) if i == list_len => {
-                        self.actions.push(CharacterSelectionFormAction::CreateNew);
+                        self.request_create();
                     }
                     Some(i) if i == list_len + 1 => {
                         if let Some(id) = self.character_list.selected_id() {
@@ -387,7 +401,7 @@ impl Widget for CharacterSelectionForm {
 
         // Forward to buttons.
         if self.create_button.handle_event(event) == EventResponse::Consumed {
-            self.actions.push(CharacterSelectionFormAction::CreateNew);
+            self.request_create();
             return EventResponse::Consumed;
         }
         if self.continue_button.handle_event(event) == EventResponse::Consumed {
@@ -501,12 +515,16 @@ impl Widget for CharacterSelectionForm {
         }
         cursor_y += font_cache::BITMAP_GLYPH_H as i32 + 4;
 
-        // "Characters" label.
+        // "Characters" label with the roster size.
+        let roster_label = format!(
+            "Characters ({}/{MAX_CHARACTERS_PER_ACCOUNT})",
+            self.character_list.item_count()
+        );
         font_cache::draw_text(
             ctx.canvas,
             ctx.gfx,
             FONT,
-            "Characters",
+            &roster_label,
             self.bounds.x + PAD_X,
             cursor_y,
             font_cache::TextStyle::PLAIN,
@@ -598,6 +616,29 @@ mod tests {
         assert_eq!(form.character_names.len(), 1);
     }
 
+    #[test]
+    fn create_is_refused_when_roster_is_full() {
+        let mut form = make_form();
+        form.request_create();
+        assert!(matches!(
+            form.take_actions().as_slice(),
+            [CharacterSelectionFormAction::CreateNew]
+        ));
+
+        let items = (0..MAX_CHARACTERS_PER_ACCOUNT as u64)
+            .map(|id| ListItem {
+                id,
+                label: format!("Hero {id}"),
+                sprite_id: None,
+                rank_index: None,
+            })
+            .collect();
+        form.set_characters(items, Vec::new());
+        form.request_create();
+        assert!(form.take_actions().is_empty());
+        assert!(form.error_text.is_some());
+    }
+
     #[test]
     fn set_error_and_status() {
         let mut form = make_form();
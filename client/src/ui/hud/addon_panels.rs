@@ -0,0 +1,166 @@
+//! Text panels drawn on behalf of client addons.
+//!
+//! GameScene copies the panels returned by the
+//! [`AddonHost`](crate::addons::AddonHost) into this widget each frame. Panels
+//! stack downward from the anchor in addon name order and never take input,
+//! so an addon cannot block clicks on the world.
+
+use std::time::Duration;
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::addons::{AddonPanel, MAX_PANEL_LINE_LEN};
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, UiEvent, Widget};
+
+/// Bitmap font for panel titles (yellow).
+const TITLE_FONT: usize = 1;
+
+/// Bitmap font for panel lines (blue).
+const LINE_FONT: usize = 3;
+
+/// Inner padding of a panel.
+const PANEL_PAD: u32 = 4;
+
+/// Vertical gap between stacked panels.
+const PANEL_GAP: i32 = 4;
+
+/// Width of every panel: the longest allowed line plus padding.
+const PANEL_W: u32 = MAX_PANEL_LINE_LEN as u32 * font_cache::BITMAP_GLYPH_ADVANCE + 2 * PANEL_PAD;
+
+/// Panel background fill.
+const PANEL_BG: Color = Color::RGBA(10, 10, 30, 180);
+
+/// Panel border.
+const PANEL_BORDER: Color = Color::RGBA(120, 120, 140, 200);
+
+/// Stack of addon panels.
+pub struct AddonPanels {
+    /// Top-left anchor; the height grows with the panels shown.
+    bounds: Bounds,
+    panels: Vec<AddonPanel>,
+}
+
+impl AddonPanels {
+    /// Creates an empty panel stack.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Screen x of the stack's left edge.
+    /// * `y` - Screen y of the first panel.
+    ///
+    /// # Returns
+    ///
+    /// * A new, empty `AddonPanels`.
+    pub fn new(x: i32, y: i32) -> Self {
+        Self {
+            bounds: Bounds::new(x, y, PANEL_W, 0),
+            panels: Vec::new(),
+        }
+    }
+
+    /// Replaces the panels to draw.
+    ///
+    /// # Arguments
+    ///
+    /// * `panels` - Current addon panels, top to bottom.
+    pub fn sync<'a>(&mut self, panels: impl Iterator<Item = &'a AddonPanel>) {
+        self.panels.clear();
+        self.panels.extend(panels.cloned());
+        self.bounds.height = self
+            .panels
+            .iter()
+            .map(|panel| Self::panel_height(panel) + PANEL_GAP as u32)
+            .sum();
+    }
+
+    /// Height of one panel: title plus its lines.
+    fn panel_height(panel: &AddonPanel) -> u32 {
+        (panel.lines.len() as u32 + 1) * font_cache::BITMAP_GLYPH_H + 2 * PANEL_PAD
+    }
+}
+
+impl Widget for AddonPanels {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn handle_event(&mut self, _event: &UiEvent) -> EventResponse {
+        EventResponse::Ignored
+    }
+
+    fn update(&mut self, _dt: Duration) {}
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        let mut y = self.bounds.y;
+        for panel in &self.panels {
+            let height = Self::panel_height(panel);
+            let rect = sdl2::rect::Rect::new(self.bounds.x, y, PANEL_W, height);
+            ctx.canvas.set_draw_color(PANEL_BG);
+            ctx.canvas.fill_rect(rect)?;
+            ctx.canvas.set_draw_color(PANEL_BORDER);
+            ctx.canvas.draw_rect(rect)?;
+
+            let text_x = self.bounds.x + PANEL_PAD as i32;
+            let mut text_y = y + PANEL_PAD as i32;
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                TITLE_FONT,
+                &panel.title,
+                text_x,
+                text_y,
+                font_cache::TextStyle::default(),
+            )?;
+            for line in &panel.lines {
+                text_y += font_cache::BITMAP_GLYPH_H as i32;
+                font_cache::draw_text(
+                    ctx.canvas,
+                    ctx.gfx,
+                    LINE_FONT,
+                    line,
+                    text_x,
+                    text_y,
+                    font_cache::TextStyle::default(),
+                )?;
+            }
+            y += height as i32 + PANEL_GAP;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_sizes_stack_to_panels() {
+        let mut stack = AddonPanels::new(4, 300);
+        let panels = [
+            AddonPanel {
+                title: "Meter".to_owned(),
+                lines: vec!["Kills: 2".to_owned()],
+            },
+            AddonPanel {
+                title: "Clock".to_owned(),
+                lines: Vec::new(),
+            },
+        ];
+        stack.sync(panels.iter());
+        let glyph = font_cache::BITMAP_GLYPH_H;
+        let expected = (2 * glyph + 2 * PANEL_PAD) + (glyph + 2 * PANEL_PAD) + 2 * PANEL_GAP as u32;
+        assert_eq!(stack.bounds().height, expected);
+
+        stack.sync(std::iter::empty());
+        assert_eq!(stack.bounds().height, 0);
+    }
+}
@@ -132,6 +132,11 @@ impl HudButtonBar {
                     HudPanel::Minimap => "Minimap",
                     HudPanel::KeyBindings => "Key Bindings",
                     HudPanel::QuestLog => "Quest Log",
+                    HudPanel::Profile => "Profile",
+                    HudPanel::Guild => "Guild",
+                    HudPanel::Mail => "Mail",
+                    HudPanel::Friends => "Friends",
+                    HudPanel::CombatLog => "Combat Log",
                 });
             }
         }
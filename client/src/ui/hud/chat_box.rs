@@ -4,18 +4,36 @@
 //! `ChatBox` owns the message log, input buffer, and sent-message history.  It
 //! renders a semi-transparent background over the world view and handles
 //! scroll, typing, and history-navigation events internally.
+//!
+//! A row of [`ChatTab`]s along the top filters the log by channel. All tabs
+//! share one scrollback, so switching tabs (or zones) never loses history;
+//! inactive tabs show how many of their messages arrived since last viewed.
+//!
+//! Shift-clicking a backpack item while typing puts `[name]` into the input;
+//! on send it becomes a slot token the server turns into an item link. Links
+//! in the log are tinted, and [`ChatBox::hovered_item_link`] tells the scene
+//! which one the mouse is over so it can show the item's tooltip.
 
+use std::borrow::Cow;
+use std::sync::Arc;
 use std::time::Duration;
 
+use mag_core::item_link::{ItemLink, slot_token};
+
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::render::BlendMode;
 
 use crate::font_cache;
-use crate::types::log_message::{LogMessage, LogMessageColor};
+use crate::item_links::link_label;
+use crate::streamer_mode::StreamerFilter;
+use crate::types::log_message::{ChatItemLink, LogMessage, LogMessageColor};
 
 use crate::ui::RenderContext;
-use crate::ui::style::Padding;
+use crate::ui::hud::chat_tabs::ChatTab;
+use crate::ui::style::{
+    CHAT_GM_HIGHLIGHT, CHAT_GM_TINT, CHAT_ITEM_LINK_TINT, Padding, chat_channel_tint,
+};
 use crate::ui::widget::{Bounds, EventResponse, UiEvent, Widget, WidgetAction};
 
 /// Maximum characters allowed in the chat input buffer.
@@ -24,12 +42,33 @@ const MAX_INPUT_LEN: usize = 120;
 /// Maximum number of previously sent messages kept in history.
 const MAX_HISTORY_LEN: usize = 100;
 
+/// Maximum number of log messages kept as scrollback (shared by all tabs).
+const MAX_SCROLLBACK_LEN: usize = 2000;
+
 /// Command names that Tab history should treat as reusable chat prefixes.
 const CHAT_PREFIX_COMMANDS: &[&str] = &["shout", "gtell", "itell", "stell", "tell", "me", "emote"];
 
 /// Height reserved for the input area (separator gap + one line of text).
 const INPUT_AREA_H: u32 = font_cache::BITMAP_GLYPH_H + 4; // 2px gap above + 2px below
 
+/// Height reserved for the tab row (one line of text + 2px gap below).
+const TAB_BAR_H: u32 = font_cache::BITMAP_GLYPH_H + 2;
+
+/// Horizontal gap between tab captions.
+const TAB_GAP: u32 = 8;
+
+/// Bitmap font for the active tab (yellow).
+const ACTIVE_TAB_FONT: usize = 1;
+
+/// Bitmap font for inactive tabs with unread messages (green).
+const UNREAD_TAB_FONT: usize = 2;
+
+/// Bitmap font for inactive tabs without unread messages (blue).
+const IDLE_TAB_FONT: usize = 3;
+
+/// Fill drawn behind the active tab caption.
+const ACTIVE_TAB_BG: Color = Color::RGBA(60, 60, 90, 200);
+
 /// Default bitmap font index for the input line (yellow).
 const INPUT_FONT: usize = 1;
 
@@ -57,8 +96,16 @@ pub struct ChatBox {
 
     // -- Scroll state --
     scroll_offset: usize,
+    /// Value of the active tab's `received` counter at the last scroll sync.
     last_message_count: usize,
 
+    // -- Tabs --
+    active_tab: ChatTab,
+    /// Messages ever accepted per tab; unlike the scrollback it never shrinks.
+    received: [usize; ChatTab::TABS.len()],
+    /// Messages accepted per tab while it was not active.
+    unread: [usize; ChatTab::TABS.len()],
+
     // -- Computed layout (derived from bounds + padding) --
     visible_lines: usize,
     line_height: u32,
@@ -67,13 +114,19 @@ pub struct ChatBox {
     messages: Vec<LogMessage>,
     input_buf: String,
     input_cursor: usize,
+    /// `[name]` placeholders in the input and the backpack slots they link.
+    input_links: Vec<(String, usize)>,
     sent_chat_history: Vec<String>,
     chat_history_index: Option<usize>,
     chat_history_draft: Option<String>,
     chat_prefix_history_index: Option<usize>,
+    /// Streamer-mode rewriting applied to log lines at draw time.
+    display_filter: Option<StreamerFilter>,
 
     // -- Focus & actions --
     focused: bool,
+    /// Last mouse position seen, for item link tooltips.
+    mouse: Option<(i32, i32)>,
     pending_actions: Vec<WidgetAction>,
 
     // -- Idle fade --
@@ -108,16 +161,22 @@ impl ChatBox {
             padding,
             scroll_offset: 0,
             last_message_count: 0,
+            active_tab: ChatTab::All,
+            received: [0; ChatTab::TABS.len()],
+            unread: [0; ChatTab::TABS.len()],
             visible_lines,
             line_height,
             messages: Vec::new(),
             input_buf: String::new(),
             input_cursor: 0,
+            input_links: Vec::new(),
             sent_chat_history: Vec::new(),
             chat_history_index: None,
             chat_history_draft: None,
             chat_prefix_history_index: None,
+            display_filter: None,
             focused: false,
+            mouse: None,
             pending_actions: Vec::new(),
             idle_elapsed: 0.0,
             caret_elapsed: 0.0,
@@ -132,7 +191,8 @@ impl ChatBox {
     /// * `message` - The log message to add.
     pub fn push_message(&mut self, message: LogMessage) {
         self.idle_elapsed = 0.0;
-        self.messages.push(message);
+        self.record_message(message);
+        self.trim_scrollback();
     }
 
     /// Appends multiple messages to the log.
@@ -142,7 +202,90 @@ impl ChatBox {
     /// * `messages` - An iterator of log messages to add.
     pub fn push_messages(&mut self, messages: impl Iterator<Item = LogMessage>) {
         self.idle_elapsed = 0.0;
-        self.messages.extend(messages);
+        for message in messages {
+            self.record_message(message);
+        }
+        self.trim_scrollback();
+    }
+
+    /// Stores a message and bumps the counters of every tab that shows it.
+    fn record_message(&mut self, message: LogMessage) {
+        for tab in ChatTab::TABS {
+            if tab.accepts(message.style.channel) {
+                self.received[tab.index()] += 1;
+                if tab != self.active_tab {
+                    self.unread[tab.index()] += 1;
+                }
+            }
+        }
+        self.messages.push(message);
+    }
+
+    /// Drops the oldest messages beyond [`MAX_SCROLLBACK_LEN`].
+    fn trim_scrollback(&mut self) {
+        if self.messages.len() > MAX_SCROLLBACK_LEN {
+            let excess = self.messages.len() - MAX_SCROLLBACK_LEN;
+            self.messages.drain(..excess);
+        }
+    }
+
+    /// Returns the currently selected tab.
+    ///
+    /// # Returns
+    ///
+    /// * The active [`ChatTab`].
+    pub fn active_tab(&self) -> ChatTab {
+        self.active_tab
+    }
+
+    /// Returns how many messages arrived on `tab` since it was last active.
+    ///
+    /// # Arguments
+    ///
+    /// * `tab` - Tab to query.
+    ///
+    /// # Returns
+    ///
+    /// * Unread message count (always 0 for the active tab).
+    pub fn unread_count(&self, tab: ChatTab) -> usize {
+        self.unread[tab.index()]
+    }
+
+    /// Switches the log to `tab`, jumping to its newest messages and clearing
+    /// its unread badge.
+    ///
+    /// # Arguments
+    ///
+    /// * `tab` - Tab to show.
+    pub fn set_active_tab(&mut self, tab: ChatTab) {
+        self.idle_elapsed = 0.0;
+        self.active_tab = tab;
+        self.unread[tab.index()] = 0;
+        self.scroll_offset = 0;
+        self.last_message_count = self.received[tab.index()];
+    }
+
+    /// Sets or clears the streamer-mode filter used when drawing log lines.
+    ///
+    /// Stored messages are never modified, so clearing the filter shows the
+    /// original text again.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Filter to apply, or `None` to draw messages verbatim.
+    pub fn set_display_filter(&mut self, filter: Option<StreamerFilter>) {
+        self.display_filter = filter;
+    }
+
+    /// Updates the own character name known to the display filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Current character name.
+    pub fn set_display_own_name(&mut self, name: &str) {
+        if let Some(filter) = self.display_filter.as_mut() {
+            filter.set_own_name(name);
+        }
     }
 
     /// Returns the total number of stored messages.
@@ -185,6 +328,54 @@ impl ChatBox {
         }
     }
 
+    /// Replaces the input with `text`, puts the cursor after it and focuses
+    /// the input, so the player only has to finish the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - Text to start the line with; cut to the input limit.
+    pub fn start_input(&mut self, text: &str) {
+        let mut end = text.len().min(MAX_INPUT_LEN);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.input_buf = text[..end].to_owned();
+        self.input_cursor = self.input_buf.len();
+        self.input_links.clear();
+        self.set_focused(true);
+    }
+
+    /// Puts a link to a backpack item at the input cursor and focuses the
+    /// input.
+    ///
+    /// The input shows `[name]`; [`Self::submit_input`] sends a slot token
+    /// in its place, unless the player edited the placeholder.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - Backpack slot of the item.
+    /// * `name` - Item name.
+    pub fn insert_item_link(&mut self, slot: usize, name: &str) {
+        let label = link_label(name);
+        self.set_focused(true);
+        self.normalize_cursor();
+        if self.input_buf.len() + label.len() <= MAX_INPUT_LEN {
+            self.insert_text_at_cursor(&label);
+            self.input_links.push((label, slot));
+        }
+    }
+
+    /// Swaps the first occurrence of each item link placeholder in `text`
+    /// for its slot token.
+    fn expand_item_links(&mut self, mut text: String) -> String {
+        for (label, slot) in self.input_links.drain(..) {
+            if let Some(at) = text.find(&label) {
+                text.replace_range(at..at + label.len(), &slot_token(slot));
+            }
+        }
+        text
+    }
+
     /// Injects a single character into the input buffer (for the on-screen
     /// keyboard).
     ///
@@ -231,17 +422,18 @@ impl ChatBox {
     /// Call this once per frame before rendering so that new messages push the
     /// viewport correctly.
     fn sync_scroll(&mut self) {
-        let total = self.messages.len();
+        let received = self.received[self.active_tab.index()];
 
         // Follow-tail: if new messages arrived while manually scrolled up,
         // shift the offset so the viewport stays on the same messages.
-        if total > self.last_message_count && self.scroll_offset > 0 {
-            let delta = total - self.last_message_count;
+        if received > self.last_message_count && self.scroll_offset > 0 {
+            let delta = received - self.last_message_count;
             self.scroll_offset = self.scroll_offset.saturating_add(delta);
         }
-        self.last_message_count = total;
+        self.last_message_count = received;
 
         // Clamp to valid range. 0 = newest-at-bottom.
+        let total = self.filtered_count();
         let max_scroll = total.saturating_sub(self.visible_lines);
         self.scroll_offset = self.scroll_offset.min(max_scroll);
     }
@@ -259,7 +451,7 @@ impl ChatBox {
     /// Number of visible lines.
     fn compute_visible_lines(bounds: &Bounds, padding: &Padding, line_height: u32) -> usize {
         let inner = bounds.inner(padding);
-        let log_area_h = inner.height.saturating_sub(INPUT_AREA_H);
+        let log_area_h = inner.height.saturating_sub(TAB_BAR_H + INPUT_AREA_H);
         (log_area_h / line_height) as usize
     }
 
@@ -273,13 +465,138 @@ impl ChatBox {
         }
     }
 
-    /// Returns a message by index-from-most-recent (0 = newest).
-    fn message_from_end(&self, index: usize) -> Option<&LogMessage> {
-        if index < self.messages.len() {
-            Some(&self.messages[self.messages.len() - 1 - index])
+    /// Builds the text style for a log line from its chat style metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - Message being rendered.
+    /// * `alpha` - Current idle-fade alpha.
+    ///
+    /// # Returns
+    ///
+    /// * Faded style with the channel tint (GM tint wins) and italics for emotes.
+    fn text_style_for(msg: &LogMessage, alpha: u8) -> font_cache::TextStyle {
+        let mut style = font_cache::TextStyle::faded(alpha);
+        let tint = if msg.style.gm {
+            Some(CHAT_GM_TINT)
         } else {
-            None
+            chat_channel_tint(msg.style.channel)
+        };
+        if let Some(tint) = tint {
+            style = style.with_tint(tint);
+        }
+        if msg.style.emote {
+            style = style.with_italic();
+        }
+        style
+    }
+
+    /// Returns a message of the active tab by index-from-most-recent
+    /// (0 = newest).
+    fn message_from_end(&self, index: usize) -> Option<&LogMessage> {
+        self.messages
+            .iter()
+            .rev()
+            .filter(|msg| self.active_tab.accepts(msg.style.channel))
+            .nth(index)
+    }
+
+    /// Splits a log line into the pieces drawn one after another: text run
+    /// through the display filter, and item links drawn verbatim.
+    fn line_segments<'m>(
+        &self,
+        msg: &'m LogMessage,
+    ) -> Vec<(Cow<'m, str>, Option<&'m ChatItemLink>)> {
+        let filter = |text: &'m str| match &self.display_filter {
+            Some(filter) => filter.apply(text),
+            None => Cow::Borrowed(text),
+        };
+        let mut segments = Vec::new();
+        let mut at = 0;
+        for link in &msg.links {
+            let Some(label) = msg.message.get(link.range.clone()) else {
+                continue;
+            };
+            if link.range.start < at {
+                continue;
+            }
+            if link.range.start > at {
+                segments.push((filter(&msg.message[at..link.range.start]), None));
+            }
+            segments.push((Cow::Borrowed(label), Some(link)));
+            at = link.range.end;
         }
+        if at < msg.message.len() {
+            segments.push((filter(&msg.message[at..]), None));
+        }
+        segments
+    }
+
+    /// Returns the item link under the mouse.
+    ///
+    /// # Returns
+    ///
+    /// * The snapshot of the hovered link; `None` while the box is faded out
+    ///   or when the snapshot never arrived.
+    pub fn hovered_item_link(&self) -> Option<Arc<ItemLink>> {
+        let (x, y) = self.mouse?;
+        if self.alpha == 0 || !self.bounds.contains_point(x, y) {
+            return None;
+        }
+        let inner = self.bounds.inner(&self.padding);
+        let log_y = inner.y + TAB_BAR_H as i32;
+        if y < log_y {
+            return None;
+        }
+        let line = ((y - log_y) / self.line_height as i32) as usize;
+        if line >= self.visible_lines {
+            return None;
+        }
+        let idx_from_most_recent = self
+            .scroll_offset
+            .saturating_add(self.visible_lines - 1 - line);
+        let msg = self.message_from_end(idx_from_most_recent)?;
+        if msg.links.is_empty() {
+            return None;
+        }
+
+        let mut start = inner.x;
+        for (text, link) in self.line_segments(msg) {
+            let end = start + text.len() as i32 * font_cache::BITMAP_GLYPH_ADVANCE as i32;
+            if (start..end).contains(&x) {
+                return link.and_then(|link| link.item.clone());
+            }
+            start = end;
+        }
+        None
+    }
+
+    /// Returns the number of stored messages shown on the active tab.
+    fn filtered_count(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|msg| self.active_tab.accepts(msg.style.channel))
+            .count()
+    }
+
+    /// Lays out the tab captions left to right along the top of the box.
+    ///
+    /// # Returns
+    ///
+    /// * One `(tab, caption, bounds)` entry per tab, in display order.
+    fn tab_layout(&self) -> Vec<(ChatTab, String, Bounds)> {
+        let inner = self.bounds.inner(&self.padding);
+        let mut x = inner.x;
+        ChatTab::TABS
+            .iter()
+            .map(|&tab| {
+                let caption = tab.caption(self.unread[tab.index()]);
+                let width = caption.chars().count() as u32 * font_cache::BITMAP_GLYPH_ADVANCE;
+                let bounds = Bounds::new(x, inner.y, width, self.line_height);
+                x += (width + TAB_GAP) as i32;
+                (tab, caption, bounds)
+            })
+            .collect()
     }
 
     /// Resets the caret blink cycle so the caret is immediately visible.
@@ -354,6 +671,7 @@ impl ChatBox {
     fn replace_input(&mut self, text: String) {
         self.input_buf = text;
         self.input_cursor = self.input_buf.len();
+        self.input_links.clear();
         self.reset_caret_blink();
     }
 
@@ -387,6 +705,7 @@ impl ChatBox {
         self.focused = false;
         if self.input_buf.is_empty() {
             self.input_cursor = 0;
+            self.input_links.clear();
             return;
         }
         let text = self.input_buf.clone();
@@ -401,6 +720,7 @@ impl ChatBox {
         self.chat_history_draft = None;
         self.chat_prefix_history_index = None;
 
+        let text = self.expand_item_links(text);
         self.pending_actions.push(WidgetAction::SendChat(text));
     }
 
@@ -508,6 +828,10 @@ impl Widget for ChatBox {
         self.bounds.y = y;
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
         match event {
             UiEvent::MouseWheel { x, y, delta } => {
@@ -523,9 +847,20 @@ impl Widget for ChatBox {
                 EventResponse::Consumed
             }
 
-            UiEvent::MouseClick { x: _, y: _, .. } => {
-                // Do nothing intentionally
-                EventResponse::Ignored
+            UiEvent::MouseClick { x, y, .. } => {
+                // Only the tab row reacts to clicks; the log itself is
+                // click-through.
+                let hit = self
+                    .tab_layout()
+                    .into_iter()
+                    .find(|(_, _, bounds)| bounds.contains_point(*x, *y));
+                match hit {
+                    Some((tab, _, _)) => {
+                        self.set_active_tab(tab);
+                        EventResponse::Consumed
+                    }
+                    None => EventResponse::Ignored,
+                }
             }
 
             UiEvent::TextInput { text } => {
@@ -537,7 +872,11 @@ impl Widget for ChatBox {
                 EventResponse::Consumed
             }
 
-            UiEvent::KeyDown { keycode, .. } => {
+            UiEvent::KeyDown { keycode, modifiers } => {
+                if *keycode == Keycode::Tab && modifiers.ctrl {
+                    self.set_active_tab(self.active_tab.cycle(modifiers.shift));
+                    return EventResponse::Consumed;
+                }
                 if !self.focused {
                     match *keycode {
                         Keycode::Return | Keycode::KpEnter | Keycode::Slash => {
@@ -622,8 +961,13 @@ impl Widget for ChatBox {
                 }
             }
 
-            UiEvent::MouseMove { .. }
-            | UiEvent::MouseDown { .. }
+            UiEvent::MouseMove { x, y } => {
+                // Tracked for item link tooltips; the log stays click-through.
+                self.mouse = Some((*x, *y));
+                EventResponse::Ignored
+            }
+
+            UiEvent::MouseDown { .. }
             | UiEvent::NavNext
             | UiEvent::NavPrev
             | UiEvent::NavConfirm
@@ -680,7 +1024,41 @@ impl Widget for ChatBox {
 
         let inner = self.bounds.inner(&self.padding);
 
-        // 2. Render log lines (top-->bottom, newest at bottom)
+        // 2. Tab row with unread badges
+        for (tab, caption, tab_bounds) in self.tab_layout() {
+            let font = if tab == self.active_tab {
+                let a = (f32::from(ACTIVE_TAB_BG.a) * (f32::from(self.alpha) / 255.0)) as u8;
+                ctx.canvas.set_draw_color(Color::RGBA(
+                    ACTIVE_TAB_BG.r,
+                    ACTIVE_TAB_BG.g,
+                    ACTIVE_TAB_BG.b,
+                    a,
+                ));
+                ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+                    tab_bounds.x - 2,
+                    tab_bounds.y - 1,
+                    tab_bounds.width + 4,
+                    tab_bounds.height,
+                ))?;
+                ACTIVE_TAB_FONT
+            } else if self.unread[tab.index()] > 0 {
+                UNREAD_TAB_FONT
+            } else {
+                IDLE_TAB_FONT
+            };
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                font,
+                &caption,
+                tab_bounds.x,
+                tab_bounds.y,
+                font_cache::TextStyle::faded(self.alpha),
+            )?;
+        }
+        let log_y = inner.y + TAB_BAR_H as i32;
+
+        // 3. Render log lines (top-->bottom, newest at bottom)
         for line in 0..self.visible_lines {
             let idx_from_most_recent = self
                 .scroll_offset
@@ -688,20 +1066,37 @@ impl Widget for ChatBox {
 
             if let Some(msg) = self.message_from_end(idx_from_most_recent) {
                 let font = Self::font_for_color(msg.color);
-                let y = inner.y + (line as i32) * self.line_height as i32;
-                font_cache::draw_text(
-                    ctx.canvas,
-                    ctx.gfx,
-                    font,
-                    &msg.message,
-                    inner.x,
-                    y,
-                    font_cache::TextStyle::faded(self.alpha),
-                )?;
+                let y = log_y + (line as i32) * self.line_height as i32;
+                if msg.style.gm {
+                    let a =
+                        (f32::from(CHAT_GM_HIGHLIGHT.a) * (f32::from(self.alpha) / 255.0)) as u8;
+                    ctx.canvas.set_draw_color(Color::RGBA(
+                        CHAT_GM_HIGHLIGHT.r,
+                        CHAT_GM_HIGHLIGHT.g,
+                        CHAT_GM_HIGHLIGHT.b,
+                        a,
+                    ));
+                    ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+                        inner.x - 2,
+                        y - 1,
+                        inner.width + 4,
+                        self.line_height,
+                    ))?;
+                }
+                let style = Self::text_style_for(msg, self.alpha);
+                let mut x = inner.x;
+                for (text, link) in self.line_segments(msg) {
+                    let style = match link {
+                        Some(_) => style.with_tint(CHAT_ITEM_LINK_TINT),
+                        None => style,
+                    };
+                    font_cache::draw_text(ctx.canvas, ctx.gfx, font, &text, x, y, style)?;
+                    x += text.len() as i32 * font_cache::BITMAP_GLYPH_ADVANCE as i32;
+                }
             }
         }
 
-        // 3. Separator line between log and input (alpha-scaled)
+        // 4. Separator line between log and input (alpha-scaled)
         let sep_a = (f32::from(SEPARATOR_COLOR.a) * (f32::from(self.alpha) / 255.0)) as u8;
         let sep_color = Color::RGBA(
             SEPARATOR_COLOR.r,
@@ -709,14 +1104,14 @@ impl Widget for ChatBox {
             SEPARATOR_COLOR.b,
             sep_a,
         );
-        let sep_y = inner.y + (self.visible_lines as i32) * self.line_height as i32 + 1;
+        let sep_y = log_y + (self.visible_lines as i32) * self.line_height as i32 + 1;
         ctx.canvas.set_draw_color(sep_color);
         ctx.canvas.draw_line(
             sdl2::rect::Point::new(inner.x, sep_y),
             sdl2::rect::Point::new(inner.x + inner.width as i32 - 1, sep_y),
         )?;
 
-        // 4. Input line below separator
+        // 5. Input line below separator
         // TODO: This is really inefficient to do this every frame;
         // just cache a "visible input substring" that gets updated on input events.
         const MAX_INPUT_TO_SHOW: usize = 43;
@@ -777,18 +1172,39 @@ mod tests {
         LogMessage {
             message: text.to_owned(),
             color,
+            style: Default::default(),
+            links: Vec::new(),
         }
     }
 
+    // -- text_style_for --
+
+    #[test]
+    fn gm_and_emote_lines_get_distinct_styles() {
+        use mag_core::chat::{ChatChannel, ChatStyle};
+        let mut msg = make_msg("Ishtar waves.", LogMessageColor::Blue);
+        let plain = ChatBox::text_style_for(&msg, 255);
+        assert!(plain.tint.is_none());
+        assert!(!plain.italic);
+
+        msg.style = ChatStyle::new(ChatChannel::Say).emote();
+        assert!(ChatBox::text_style_for(&msg, 255).italic);
+
+        msg.style = ChatStyle::new(ChatChannel::Shout).with_gm(true);
+        let gm = ChatBox::text_style_for(&msg, 128);
+        assert_eq!(gm.tint, Some(CHAT_GM_TINT));
+        assert_eq!(gm.alpha, Some(128));
+    }
+
     // -- visible_lines --
 
     #[test]
     fn visible_lines_computed_correctly() {
         let cb = test_chat_box();
         // Inner height = 180 - 4 - 4 = 172
-        // Log area = 172 - INPUT_AREA_H(14) = 158
-        // Lines = 158 / 10 = 15
-        assert_eq!(cb.visible_lines, 15);
+        // Log area = 172 - TAB_BAR_H(12) - INPUT_AREA_H(14) = 146
+        // Lines = 146 / 10 = 14
+        assert_eq!(cb.visible_lines, 14);
     }
 
     // -- sync_scroll --
@@ -807,7 +1223,7 @@ mod tests {
     #[test]
     fn page_up_and_down_scroll_the_log() {
         let mut cb = test_chat_box();
-        // visible_lines == 15, so the page step is 14.
+        // visible_lines == 14, so the page step is 13.
         let step = cb.visible_lines.saturating_sub(1).max(1);
         for i in 0..50 {
             cb.push_message(make_msg(&format!("msg {}", i), LogMessageColor::Yellow));
@@ -855,7 +1271,7 @@ mod tests {
         }
         cb.scroll_offset = 100;
         cb.sync_scroll();
-        // max_scroll = 3 - 15 = 0
+        // max_scroll = 3 - 14 = 0
         assert_eq!(cb.scroll_offset, 0);
     }
 
@@ -1315,6 +1731,16 @@ mod tests {
         assert_eq!(cb.input_text(), "#tell Alice ");
     }
 
+    #[test]
+    fn start_input_prefills_and_focuses() {
+        let mut cb = test_chat_box();
+        cb.input_buf = "half typed".to_owned();
+        cb.start_input("#tell Ishtar ");
+        assert!(cb.is_focused());
+        assert_eq!(cb.input_text(), "#tell Ishtar ");
+        assert_eq!(cb.input_cursor, cb.input_text().len());
+    }
+
     #[test]
     fn submit_adds_to_history() {
         let mut cb = test_chat_box();
@@ -1324,6 +1750,55 @@ mod tests {
         assert_eq!(cb.sent_chat_history[0], "test msg");
     }
 
+    #[test]
+    fn item_link_placeholders_are_sent_as_slot_tokens() {
+        let mut cb = test_chat_box();
+        cb.input_buf = "wts ".to_owned();
+        cb.input_cursor = 4;
+        cb.insert_item_link(12, "Steel Sword");
+        assert!(cb.is_focused());
+        assert_eq!(cb.input_text(), "wts [Steel Sword]");
+
+        cb.submit_input();
+        assert_eq!(cb.sent_chat_history[0], "wts [Steel Sword]");
+        let sent = format!("wts {}", slot_token(12));
+        let actions = cb.take_actions();
+        assert!(matches!(&actions[..], [WidgetAction::SendChat(text)] if *text == sent));
+        assert!(cb.input_links.is_empty());
+    }
+
+    #[test]
+    fn hovering_an_item_link_returns_its_snapshot() {
+        let mut cb = test_chat_box();
+        let sword = Arc::new(ItemLink {
+            id: 3,
+            name: "Sword".to_owned(),
+            ..Default::default()
+        });
+        let mut msg = make_msg("wts [Sword]", LogMessageColor::Yellow);
+        msg.links.push(ChatItemLink {
+            range: 4..11,
+            item: Some(sword.clone()),
+        });
+        cb.push_message(msg);
+
+        // Newest line is the last visible one; "[Sword]" starts 4 glyphs in.
+        let inner = cb.bounds.inner(&cb.padding);
+        let y = inner.y + TAB_BAR_H as i32 + (cb.visible_lines as i32 - 1) * 10 + 5;
+        let glyph = font_cache::BITMAP_GLYPH_ADVANCE as i32;
+        cb.handle_event(&UiEvent::MouseMove {
+            x: inner.x + 5 * glyph,
+            y,
+        });
+        assert_eq!(cb.hovered_item_link(), Some(sword));
+
+        cb.handle_event(&UiEvent::MouseMove {
+            x: inner.x + glyph,
+            y,
+        });
+        assert_eq!(cb.hovered_item_link(), None);
+    }
+
     #[test]
     fn history_capped_at_max() {
         let mut cb = test_chat_box();
@@ -1419,4 +1894,83 @@ mod tests {
         cb.update(Duration::ZERO);
         assert_eq!(cb.alpha, 0);
     }
+
+    // -- tabs --
+
+    fn channel_msg(text: &str, channel: mag_core::chat::ChatChannel) -> LogMessage {
+        LogMessage {
+            message: text.to_owned(),
+            color: LogMessageColor::Yellow,
+            style: mag_core::chat::ChatStyle::new(channel),
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn tabs_filter_log_and_track_unread() {
+        use mag_core::chat::ChatChannel;
+        let mut cb = test_chat_box();
+        cb.push_message(channel_msg("hello", ChatChannel::Say));
+        cb.push_message(channel_msg("You killed Rat.", ChatChannel::Combat));
+        cb.push_message(channel_msg("psst", ChatChannel::Tell));
+        assert_eq!(cb.unread_count(ChatTab::All), 0);
+        assert_eq!(cb.unread_count(ChatTab::Combat), 1);
+        assert_eq!(cb.unread_count(ChatTab::Tell), 1);
+
+        cb.set_active_tab(ChatTab::Combat);
+        assert_eq!(cb.unread_count(ChatTab::Combat), 0);
+        assert_eq!(cb.filtered_count(), 1);
+        assert_eq!(cb.message_from_end(0).unwrap().message, "You killed Rat.");
+
+        cb.push_message(channel_msg("hi again", ChatChannel::Say));
+        assert_eq!(cb.unread_count(ChatTab::All), 1);
+        assert_eq!(cb.message_from_end(0).unwrap().message, "You killed Rat.");
+        // Other tabs keep the full scrollback.
+        cb.set_active_tab(ChatTab::All);
+        assert_eq!(cb.filtered_count(), 4);
+    }
+
+    #[test]
+    fn tab_row_click_and_ctrl_tab_switch_tabs() {
+        let mut cb = test_chat_box();
+        let (tab, _, bounds) = cb.tab_layout()[2].clone();
+        assert_eq!(tab, ChatTab::Tell);
+        let resp = cb.handle_event(&UiEvent::MouseClick {
+            x: bounds.x + 1,
+            y: bounds.y + 1,
+            button: crate::ui::widget::MouseButton::Left,
+            modifiers: crate::ui::widget::KeyModifiers::default(),
+        });
+        assert_eq!(resp, EventResponse::Consumed);
+        assert_eq!(cb.active_tab(), ChatTab::Tell);
+
+        let ctrl = crate::ui::widget::KeyModifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        cb.handle_event(&UiEvent::KeyDown {
+            keycode: Keycode::Tab,
+            modifiers: ctrl,
+        });
+        assert_eq!(cb.active_tab(), ChatTab::System);
+        cb.handle_event(&UiEvent::KeyDown {
+            keycode: Keycode::Tab,
+            modifiers: crate::ui::widget::KeyModifiers {
+                shift: true,
+                ..ctrl
+            },
+        });
+        assert_eq!(cb.active_tab(), ChatTab::Tell);
+    }
+
+    #[test]
+    fn scrollback_is_capped() {
+        let mut cb = test_chat_box();
+        cb.push_messages(
+            (0..MAX_SCROLLBACK_LEN + 10)
+                .map(|i| make_msg(&format!("msg {}", i), LogMessageColor::Yellow)),
+        );
+        assert_eq!(cb.message_count(), MAX_SCROLLBACK_LEN);
+        assert_eq!(cb.messages[0].message, "msg 10");
+    }
 }
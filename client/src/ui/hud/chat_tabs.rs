@@ -0,0 +1,157 @@
+//! Chat tabs shown along the top of the [`ChatBox`](super::chat_box::ChatBox).
+//!
+//! Each tab is a fixed filter over [`ChatChannel`]s. The chat box keeps one
+//! shared scrollback and shows the messages the active tab accepts; tabs that
+//! are not active count the accepted messages they missed as unread.
+
+use mag_core::chat::ChatChannel;
+
+/// Unread counts above this are shown as `99+`.
+const MAX_BADGE_COUNT: usize = 99;
+
+/// One chat tab and its channel filter.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ChatTab {
+    /// Every message.
+    #[default]
+    All,
+    /// Local speech, NPC speech and shouts.
+    Say,
+    /// Private, group, staff and imp chat.
+    Tell,
+    /// Server notices and announcements.
+    System,
+    /// Kills, deaths and low-health warnings.
+    Combat,
+}
+
+impl ChatTab {
+    /// All tabs in display order.
+    pub const TABS: [ChatTab; 5] = [
+        ChatTab::All,
+        ChatTab::Say,
+        ChatTab::Tell,
+        ChatTab::System,
+        ChatTab::Combat,
+    ];
+
+    /// Returns the tab's position in [`Self::TABS`].
+    ///
+    /// # Returns
+    ///
+    /// * Index into [`Self::TABS`].
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the short label drawn on the tab.
+    ///
+    /// # Returns
+    ///
+    /// * Tab label.
+    pub fn label(self) -> &'static str {
+        match self {
+            ChatTab::All => "All",
+            ChatTab::Say => "Say",
+            ChatTab::Tell => "Tell",
+            ChatTab::System => "System",
+            ChatTab::Combat => "Combat",
+        }
+    }
+
+    /// Returns `true` if the tab shows messages sent on `channel`.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - Channel of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the message belongs on this tab.
+    pub fn accepts(self, channel: ChatChannel) -> bool {
+        match self {
+            ChatTab::All => true,
+            ChatTab::Say => matches!(
+                channel,
+                ChatChannel::Say | ChatChannel::Npc | ChatChannel::Shout
+            ),
+            ChatTab::Tell => matches!(
+                channel,
+                ChatChannel::Tell
+                    | ChatChannel::Group
+                    | ChatChannel::Guild
+                    | ChatChannel::Staff
+                    | ChatChannel::Imp
+            ),
+            ChatTab::System => matches!(
+                channel,
+                ChatChannel::System | ChatChannel::Announce | ChatChannel::Reward
+            ),
+            ChatTab::Combat => channel == ChatChannel::Combat,
+        }
+    }
+
+    /// Returns the next tab, wrapping around.
+    ///
+    /// # Arguments
+    ///
+    /// * `backward` - Step to the previous tab instead.
+    ///
+    /// # Returns
+    ///
+    /// * The neighbouring tab.
+    pub fn cycle(self, backward: bool) -> ChatTab {
+        let len = Self::TABS.len();
+        let next = if backward {
+            (self.index() + len - 1) % len
+        } else {
+            (self.index() + 1) % len
+        };
+        Self::TABS[next]
+    }
+
+    /// Builds the text drawn on the tab, with an unread badge if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `unread` - Messages received while the tab was not active.
+    ///
+    /// # Returns
+    ///
+    /// * E.g. `"Tell"` or `"Tell(3)"`.
+    pub fn caption(self, unread: usize) -> String {
+        match unread {
+            0 => self.label().to_owned(),
+            n if n > MAX_BADGE_COUNT => format!("{}({}+)", self.label(), MAX_BADGE_COUNT),
+            n => format!("{}({})", self.label(), n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_channel_has_a_tab_besides_all() {
+        for byte in 0..=9u8 {
+            let channel = ChatChannel::from(byte);
+            let tabs = ChatTab::TABS
+                .iter()
+                .filter(|tab| **tab != ChatTab::All && tab.accepts(channel))
+                .count();
+            assert_eq!(tabs, 1, "{:?}", channel);
+        }
+    }
+
+    #[test]
+    fn cycling_wraps_and_captions_show_badges() {
+        assert_eq!(ChatTab::Combat.cycle(false), ChatTab::All);
+        assert_eq!(ChatTab::All.cycle(true), ChatTab::Combat);
+        assert_eq!(ChatTab::Say.cycle(false), ChatTab::Tell);
+
+        assert_eq!(ChatTab::Tell.caption(0), "Tell");
+        assert_eq!(ChatTab::Tell.caption(3), "Tell(3)");
+        assert_eq!(ChatTab::Combat.caption(250), "Combat(99+)");
+    }
+}
@@ -0,0 +1,416 @@
+//! Combat log overlay with one damage breakdown per fight, newest first.
+//!
+//! GameScene hands the panel the fights summed up by
+//! [`crate::combat_log::CombatLog`] while it is open. Each fight shows the
+//! main opponent, its length and damage per second, then what the player
+//! dealt and took: damage, hits, what armor absorbed and what immunity
+//! resisted. "Export" asks the scene to write every logged hit to
+//! `mag_combat_log.csv` ([`WidgetAction::ExportCombatLog`]) and "Clear"
+//! empties the log ([`WidgetAction::ClearCombatLog`]). Opened with
+//! `/combatlog`.
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::combat_log::{FightSummary, format_hp};
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::style::{Background, Border};
+use crate::ui::widget::{Bounds, EventResponse, HudPanel, UiEvent, Widget, WidgetAction};
+use crate::ui::widgets::button::RectButton;
+use crate::ui::widgets::title_bar::{TITLE_BAR_H, TitleBar, clamp_to_viewport};
+
+/// Bitmap font index used for panel text.
+const FONT: usize = 1;
+
+/// Horizontal inset from panel edges.
+const H_INSET: i32 = 6;
+
+/// Vertical pixel height of one text line.
+const ROW_H: i32 = 14;
+
+/// Lines each fight takes: title, dealt, taken.
+const FIGHT_ROWS: usize = 3;
+
+/// Fights visible at once before scrolling kicks in.
+pub const VISIBLE_FIGHTS: usize = 4;
+
+/// Height of the buttons.
+const CONTROL_H: u32 = 16;
+
+/// Width of the buttons.
+const BTN_W: u32 = 52;
+
+/// X offsets of the damage, hits, armor and resist columns.
+const COLUMNS: [i32; 4] = [60, 120, 168, 222];
+
+/// Tint of the column header and the per-fight totals.
+const DETAIL_COLOR: Color = Color::RGBA(190, 190, 190, 255);
+
+/// Tint of the damage dealt line.
+const DEALT_COLOR: Color = Color::RGBA(130, 235, 150, 255);
+
+/// Tint of the damage taken line.
+const TAKEN_COLOR: Color = Color::RGBA(240, 140, 120, 255);
+
+/// The combat log HUD panel.
+pub struct CombatLogPanel {
+    bounds: Bounds,
+    bg_color: Color,
+    border_color: Color,
+    visible: bool,
+    /// Fights newest first.
+    fights: Vec<FightSummary>,
+    /// First visible fight.
+    scroll: usize,
+    pending_actions: Vec<WidgetAction>,
+    title_bar: TitleBar,
+    export_button: RectButton,
+    clear_button: RectButton,
+}
+
+impl CombatLogPanel {
+    /// Creates a new (hidden) combat log panel.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds`   - Screen-space bounds of the panel.
+    /// * `bg_color` - Semi-transparent background color.
+    ///
+    /// # Returns
+    ///
+    /// * A new `CombatLogPanel`, initially hidden, with no fights.
+    pub fn new(bounds: Bounds, bg_color: Color) -> Self {
+        let title_bar = TitleBar::new("Combat Log", bounds.x, bounds.y, bounds.width);
+        let button = |x: i32, label: &str| {
+            let y = bounds.y + bounds.height as i32 - 6 - CONTROL_H as i32;
+            RectButton::new(
+                Bounds::new(x, y, BTN_W, CONTROL_H),
+                Background::SolidColor(Color::RGBA(40, 40, 60, 220)),
+            )
+            .with_label(label, FONT)
+            .with_border(Border {
+                color: Color::RGBA(120, 120, 140, 200),
+                width: 1,
+            })
+        };
+        Self {
+            bounds,
+            bg_color,
+            border_color: Color::RGBA(120, 120, 140, 200),
+            visible: false,
+            fights: Vec::new(),
+            scroll: 0,
+            pending_actions: Vec::new(),
+            title_bar,
+            export_button: button(bounds.x + H_INSET, "Export"),
+            clear_button: button(bounds.x + H_INSET + BTN_W as i32 + 4, "Clear"),
+        }
+    }
+
+    /// Toggles the panel's visibility.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Returns `true` when the panel is currently visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Replaces the displayed fights when they changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `fights` - Fight summaries, oldest first.
+    pub fn update_data(&mut self, fights: Vec<FightSummary>) {
+        let mut fights = fights;
+        fights.reverse();
+        if fights == self.fights {
+            return;
+        }
+        self.fights = fights;
+        self.scroll = self
+            .scroll
+            .min(self.fights.len().saturating_sub(VISIBLE_FIGHTS));
+    }
+
+    /// Y coordinate (top edge) of line `row`; line 0 is the column header.
+    fn row_y(&self, row: usize) -> i32 {
+        self.bounds.y + TITLE_BAR_H + 4 + row as i32 * ROW_H
+    }
+
+    /// Draws the damage, hits, armor and resist columns of one line.
+    fn draw_columns(
+        &self,
+        ctx: &mut RenderContext<'_, '_>,
+        cells: [String; 4],
+        y: i32,
+        style: font_cache::TextStyle,
+    ) -> Result<(), String> {
+        for (cell, offset) in cells.iter().zip(COLUMNS) {
+            Self::draw(ctx, cell, self.bounds.x + offset, y, style)?;
+        }
+        Ok(())
+    }
+
+    /// Draws the three lines of `fight` starting at line `row`.
+    fn draw_fight(
+        &self,
+        ctx: &mut RenderContext<'_, '_>,
+        fight: &FightSummary,
+        row: usize,
+    ) -> Result<(), String> {
+        let text_x = self.bounds.x + H_INSET;
+        let y = self.row_y(row);
+
+        let mut title = format!("#{} vs {}", fight.number, fight.opponent);
+        if fight.opponent.is_empty() {
+            title = format!("#{}", fight.number);
+        }
+        if fight.other_opponents > 0 {
+            title.push_str(&format!(" +{}", fight.other_opponents));
+        }
+        match fight.kills {
+            0 => {}
+            1 => title.push_str(", 1 kill"),
+            n => title.push_str(&format!(", {} kills", n)),
+        }
+        Self::draw(ctx, &title, text_x, y, font_cache::TextStyle::PLAIN)?;
+        let pace = format!("{:.1}s {:.1}/s", fight.seconds(), fight.dealt_per_second());
+        let right = self.bounds.x + self.bounds.width as i32 - H_INSET;
+        Self::draw(
+            ctx,
+            &pace,
+            right - font_cache::text_width(&pace) as i32,
+            y,
+            font_cache::TextStyle::tinted(DETAIL_COLOR),
+        )?;
+
+        let lines = [
+            (
+                "Dealt",
+                DEALT_COLOR,
+                [
+                    format_hp(fight.dealt),
+                    fight.hits_dealt.to_string(),
+                    format_hp(fight.dealt_mitigated),
+                    format_hp(fight.dealt_resisted),
+                ],
+            ),
+            (
+                "Taken",
+                TAKEN_COLOR,
+                [
+                    format_hp(fight.taken),
+                    fight.hits_taken.to_string(),
+                    format_hp(fight.taken_mitigated),
+                    format_hp(fight.taken_resisted),
+                ],
+            ),
+        ];
+        for (n, (label, color, cells)) in lines.into_iter().enumerate() {
+            let y = self.row_y(row + 1 + n);
+            let style = font_cache::TextStyle::tinted(color);
+            Self::draw(ctx, label, text_x + H_INSET, y, style)?;
+            self.draw_columns(ctx, cells, y, style)?;
+        }
+        Ok(())
+    }
+
+    fn draw(
+        ctx: &mut RenderContext<'_, '_>,
+        text: &str,
+        x: i32,
+        y: i32,
+        style: font_cache::TextStyle,
+    ) -> Result<(), String> {
+        font_cache::draw_text(ctx.canvas, ctx.gfx, FONT, text, x, y, style)?;
+        Ok(())
+    }
+}
+
+impl Widget for CombatLogPanel {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        let dx = x - self.bounds.x;
+        let dy = y - self.bounds.y;
+        self.bounds.x = x;
+        self.bounds.y = y;
+        self.title_bar.set_bar_position(x, y);
+        for button in [&mut self.export_button, &mut self.clear_button] {
+            let b = *button.bounds();
+            button.set_position(b.x + dx, b.y + dy);
+        }
+    }
+
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+
+        let (tb_resp, drag_pos) = self.title_bar.handle_event(event);
+        if let Some((nx, ny)) = drag_pos {
+            let (cx, cy) = clamp_to_viewport(nx, ny, self.bounds.width, self.bounds.height);
+            self.set_position(cx, cy);
+            return EventResponse::Consumed;
+        }
+        if self.title_bar.was_close_requested() {
+            self.visible = false;
+            self.pending_actions
+                .push(WidgetAction::TogglePanel(HudPanel::CombatLog));
+            return EventResponse::Consumed;
+        }
+        if tb_resp == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        if self.export_button.handle_event(event) == EventResponse::Consumed {
+            self.pending_actions.push(WidgetAction::ExportCombatLog);
+            return EventResponse::Consumed;
+        }
+        if self.clear_button.handle_event(event) == EventResponse::Consumed {
+            self.pending_actions.push(WidgetAction::ClearCombatLog);
+            return EventResponse::Consumed;
+        }
+
+        match event {
+            UiEvent::MouseClick { x, y, .. } if self.bounds.contains_point(*x, *y) => {
+                EventResponse::Consumed
+            }
+            UiEvent::MouseWheel { x, y, delta } => {
+                if !self.bounds.contains_point(*x, *y) {
+                    return EventResponse::Ignored;
+                }
+                let max_scroll = self.fights.len().saturating_sub(VISIBLE_FIGHTS);
+                if *delta > 0 {
+                    self.scroll = self.scroll.saturating_sub(*delta as usize);
+                } else if *delta < 0 {
+                    self.scroll = (self.scroll + (-delta) as usize).min(max_scroll);
+                }
+                EventResponse::Consumed
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let rect = sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(self.bg_color);
+        ctx.canvas.fill_rect(rect)?;
+        ctx.canvas.set_draw_color(self.border_color);
+        ctx.canvas.draw_rect(rect)?;
+        self.title_bar.render(ctx)?;
+
+        if self.fights.is_empty() {
+            Self::draw(
+                ctx,
+                "No hits logged yet.",
+                self.bounds.x + H_INSET,
+                self.row_y(0),
+                font_cache::TextStyle::PLAIN,
+            )?;
+        } else {
+            let header = ["Damage", "Hits", "Armor", "Resist"].map(str::to_owned);
+            self.draw_columns(
+                ctx,
+                header,
+                self.row_y(0),
+                font_cache::TextStyle::tinted(DETAIL_COLOR),
+            )?;
+            let fights = std::mem::take(&mut self.fights);
+            for (n, fight) in fights
+                .iter()
+                .skip(self.scroll)
+                .take(VISIBLE_FIGHTS)
+                .enumerate()
+            {
+                self.draw_fight(ctx, fight, 1 + n * FIGHT_ROWS)?;
+            }
+            self.fights = fights;
+        }
+
+        self.export_button.render(ctx)?;
+        self.clear_button.render(ctx)?;
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widget::MouseButton;
+
+    fn panel() -> CombatLogPanel {
+        let mut p = CombatLogPanel::new(Bounds::new(0, 0, 300, 250), Color::RGBA(0, 0, 0, 200));
+        p.toggle();
+        p
+    }
+
+    fn fight(number: usize) -> FightSummary {
+        FightSummary {
+            number,
+            opponent: "Grolm".to_owned(),
+            ..FightSummary::default()
+        }
+    }
+
+    fn left_click(x: i32, y: i32) -> UiEvent {
+        UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        }
+    }
+
+    #[test]
+    fn newest_fight_comes_first_and_scroll_is_clamped() {
+        let mut p = panel();
+        p.update_data((1..=6).map(fight).collect());
+        assert_eq!(p.fights[0].number, 6);
+
+        p.handle_event(&UiEvent::MouseWheel {
+            x: 10,
+            y: 60,
+            delta: -5,
+        });
+        assert_eq!(p.scroll, 6 - VISIBLE_FIGHTS);
+        p.update_data(vec![fight(1)]);
+        assert_eq!(p.scroll, 0);
+    }
+
+    #[test]
+    fn buttons_request_export_and_clear() {
+        let mut p = panel();
+        let b = *p.export_button.bounds();
+        p.handle_event(&left_click(b.x + 2, b.y + 2));
+        let b = *p.clear_button.bounds();
+        p.handle_event(&left_click(b.x + 2, b.y + 2));
+        assert!(matches!(
+            p.take_actions().as_slice(),
+            [WidgetAction::ExportCombatLog, WidgetAction::ClearCombatLog]
+        ));
+    }
+}
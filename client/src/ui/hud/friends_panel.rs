@@ -0,0 +1,434 @@
+//! Friends overlay listing the account's friends, online ones first.
+//!
+//! GameScene hands the panel the list kept by
+//! [`crate::player_state::PlayerState`] each frame. Online friends get a
+//! "Tell" button that opens the chat input with `#tell <name> ` typed
+//! ([`WidgetAction::StartChat`]); clicking the "Offline" header collapses or
+//! expands the offline part of the list. Friends are added and removed with
+//! the `#friend` chat commands.
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::font_cache;
+use crate::player_state::FriendEntry;
+use crate::ui::RenderContext;
+use crate::ui::widget::{
+    Bounds, EventResponse, HudPanel, MouseButton, UiEvent, Widget, WidgetAction,
+};
+use crate::ui::widgets::title_bar::{TITLE_BAR_H, TitleBar, clamp_to_viewport};
+
+/// Font index used for panel text (yellow bitmap font, matches other HUD
+/// panels).
+const PANEL_FONT: usize = 1;
+
+/// Vertical pixel height of one row.
+const ROW_H: i32 = 14;
+
+/// Inner horizontal padding from the panel border to row content.
+const H_INSET: i32 = 6;
+
+/// Rows visible at once before scrolling kicks in.
+pub const VISIBLE_ROWS: usize = 14;
+
+/// Width of the quick-tell button.
+const TELL_W: i32 = 30;
+
+/// Tint of friends that are logged in.
+const ONLINE_COLOR: Color = Color::RGBA(130, 235, 150, 255);
+
+/// Tint of friends that are logged out and of the section headers.
+const OFFLINE_COLOR: Color = Color::RGBA(150, 150, 150, 255);
+
+/// Background of the quick-tell buttons.
+const TELL_BG: Color = Color::RGBA(40, 40, 60, 220);
+
+/// One line of the list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Row {
+    /// "Online (n)" or "Offline (n)" section header.
+    Header { online: bool },
+    /// Index into `friends`.
+    Friend(usize),
+}
+
+/// The friends HUD panel.
+pub struct FriendsPanel {
+    bounds: Bounds,
+    bg_color: Color,
+    border_color: Color,
+    visible: bool,
+    /// Friends in display order: online first, then by name.
+    friends: Vec<FriendEntry>,
+    /// Whether the offline friends are folded under their header.
+    offline_collapsed: bool,
+    scroll: usize,
+    pending_actions: Vec<WidgetAction>,
+    title_bar: TitleBar,
+}
+
+impl FriendsPanel {
+    /// Creates a new (hidden) friends panel.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds`   - Screen-space bounds of the panel.
+    /// * `bg_color` - Semi-transparent background color.
+    ///
+    /// # Returns
+    ///
+    /// * A new `FriendsPanel`, initially hidden, with no friends.
+    pub fn new(bounds: Bounds, bg_color: Color) -> Self {
+        let title_bar = TitleBar::new("Friends", bounds.x, bounds.y, bounds.width);
+        Self {
+            bounds,
+            bg_color,
+            border_color: Color::RGBA(120, 120, 140, 200),
+            visible: false,
+            friends: Vec::new(),
+            offline_collapsed: false,
+            scroll: 0,
+            pending_actions: Vec::new(),
+            title_bar,
+        }
+    }
+
+    /// Toggles the panel's visibility.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Returns `true` when the panel is currently visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Replaces the displayed friends when they changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `friends` - The account's friends list.
+    pub fn update_data(&mut self, friends: &[FriendEntry]) {
+        let mut sorted = friends.to_vec();
+        sorted.sort_by(|a, b| {
+            b.online
+                .cmp(&a.online)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        if sorted == self.friends {
+            return;
+        }
+        self.friends = sorted;
+        self.clamp_scroll();
+    }
+
+    /// Lines of the list in display order.
+    fn rows(&self) -> Vec<Row> {
+        let online = self.friends.iter().filter(|f| f.online).count();
+        let mut rows = Vec::with_capacity(self.friends.len() + 2);
+        rows.push(Row::Header { online: true });
+        rows.extend((0..online).map(Row::Friend));
+        if online < self.friends.len() {
+            rows.push(Row::Header { online: false });
+            if !self.offline_collapsed {
+                rows.extend((online..self.friends.len()).map(Row::Friend));
+            }
+        }
+        rows
+    }
+
+    fn clamp_scroll(&mut self) {
+        let max_scroll = self.rows().len().saturating_sub(VISIBLE_ROWS);
+        self.scroll = self.scroll.min(max_scroll);
+    }
+
+    /// Y coordinate (top edge) of visible row `row`.
+    fn row_y(&self, row: usize) -> i32 {
+        self.bounds.y + TITLE_BAR_H + 4 + row as i32 * ROW_H
+    }
+
+    /// Line under `(x, y)`, if any.
+    fn row_at(&self, x: i32, y: i32) -> Option<Row> {
+        let top = self.row_y(0);
+        if !self.bounds.contains_point(x, y) || y < top {
+            return None;
+        }
+        let row = ((y - top) / ROW_H) as usize;
+        if row >= VISIBLE_ROWS {
+            return None;
+        }
+        self.rows().get(self.scroll + row).copied()
+    }
+
+    /// Left edge of the quick-tell buttons.
+    fn tell_x(&self) -> i32 {
+        self.bounds.x + self.bounds.width as i32 - H_INSET - TELL_W
+    }
+
+    /// Handles a left click at `(x, y)` inside the panel.
+    fn click(&mut self, x: i32, y: i32) {
+        match self.row_at(x, y) {
+            Some(Row::Header { online: false }) => {
+                self.offline_collapsed = !self.offline_collapsed;
+                self.clamp_scroll();
+            }
+            Some(Row::Friend(idx)) if self.friends[idx].online && x >= self.tell_x() => {
+                self.pending_actions.push(WidgetAction::StartChat(format!(
+                    "#tell {} ",
+                    self.friends[idx].name
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(
+        ctx: &mut RenderContext<'_, '_>,
+        text: &str,
+        x: i32,
+        y: i32,
+        style: font_cache::TextStyle,
+    ) -> Result<(), String> {
+        font_cache::draw_text(ctx.canvas, ctx.gfx, PANEL_FONT, text, x, y, style)?;
+        Ok(())
+    }
+}
+
+impl Widget for FriendsPanel {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+        self.title_bar.set_bar_position(x, y);
+    }
+
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+
+        let (tb_resp, drag_pos) = self.title_bar.handle_event(event);
+        if let Some((new_x, new_y)) = drag_pos {
+            let (cx, cy) = clamp_to_viewport(new_x, new_y, self.bounds.width, self.bounds.height);
+            self.set_position(cx, cy);
+        }
+        if self.title_bar.was_close_requested() {
+            self.visible = false;
+            self.pending_actions
+                .push(WidgetAction::TogglePanel(HudPanel::Friends));
+            return EventResponse::Consumed;
+        }
+        if tb_resp == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        match event {
+            UiEvent::MouseClick {
+                x,
+                y,
+                button: MouseButton::Left,
+                ..
+            } if self.bounds.contains_point(*x, *y) => {
+                self.click(*x, *y);
+                EventResponse::Consumed
+            }
+            UiEvent::MouseClick { x, y, .. } if self.bounds.contains_point(*x, *y) => {
+                EventResponse::Consumed
+            }
+            UiEvent::MouseWheel { x, y, delta } => {
+                if !self.bounds.contains_point(*x, *y) {
+                    return EventResponse::Ignored;
+                }
+                let max_scroll = self.rows().len().saturating_sub(VISIBLE_ROWS);
+                if *delta > 0 {
+                    self.scroll = self.scroll.saturating_sub(*delta as usize);
+                } else if *delta < 0 {
+                    self.scroll = (self.scroll + (-delta) as usize).min(max_scroll);
+                }
+                EventResponse::Consumed
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let rect = sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(self.bg_color);
+        ctx.canvas.fill_rect(rect)?;
+        ctx.canvas.set_draw_color(self.border_color);
+        ctx.canvas.draw_rect(rect)?;
+        self.title_bar.render(ctx)?;
+
+        let text_x = self.bounds.x + H_INSET;
+        if self.friends.is_empty() {
+            Self::draw(
+                ctx,
+                "Your friends list is empty.",
+                text_x,
+                self.row_y(0),
+                font_cache::TextStyle::PLAIN,
+            )?;
+            Self::draw(
+                ctx,
+                "Type #friend add <name> in chat.",
+                text_x,
+                self.row_y(1),
+                font_cache::TextStyle::PLAIN,
+            )?;
+            return Ok(());
+        }
+
+        let online = self.friends.iter().filter(|f| f.online).count();
+        let offline = self.friends.len() - online;
+        let right = self.bounds.x + self.bounds.width as i32 - H_INSET;
+        let tell_x = self.tell_x();
+        for (row, line) in self
+            .rows()
+            .into_iter()
+            .skip(self.scroll)
+            .take(VISIBLE_ROWS)
+            .enumerate()
+        {
+            let y = self.row_y(row);
+            match line {
+                Row::Header { online: true } => {
+                    Self::draw(
+                        ctx,
+                        &format!("Online ({})", online),
+                        text_x,
+                        y,
+                        font_cache::TextStyle::PLAIN,
+                    )?;
+                }
+                Row::Header { online: false } => {
+                    Self::draw(
+                        ctx,
+                        &format!("Offline ({})", offline),
+                        text_x,
+                        y,
+                        font_cache::TextStyle::PLAIN,
+                    )?;
+                    let marker = if self.offline_collapsed { "[+]" } else { "[-]" };
+                    Self::draw(
+                        ctx,
+                        marker,
+                        right - font_cache::text_width(marker) as i32,
+                        y,
+                        font_cache::TextStyle::PLAIN,
+                    )?;
+                }
+                Row::Friend(idx) => {
+                    let friend = &self.friends[idx];
+                    let color = if friend.online {
+                        ONLINE_COLOR
+                    } else {
+                        OFFLINE_COLOR
+                    };
+                    Self::draw(
+                        ctx,
+                        &friend.name,
+                        text_x + H_INSET,
+                        y,
+                        font_cache::TextStyle::tinted(color),
+                    )?;
+                    if friend.online {
+                        let button =
+                            sdl2::rect::Rect::new(tell_x, y - 1, TELL_W as u32, (ROW_H - 2) as u32);
+                        ctx.canvas.set_draw_color(TELL_BG);
+                        ctx.canvas.fill_rect(button)?;
+                        ctx.canvas.set_draw_color(self.border_color);
+                        ctx.canvas.draw_rect(button)?;
+                        let label_x = tell_x + (TELL_W - font_cache::text_width("Tell") as i32) / 2;
+                        Self::draw(ctx, "Tell", label_x, y, font_cache::TextStyle::PLAIN)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn friend(name: &str, online: bool) -> FriendEntry {
+        FriendEntry {
+            name: name.to_owned(),
+            online,
+        }
+    }
+
+    fn panel() -> FriendsPanel {
+        let mut p = FriendsPanel::new(Bounds::new(0, 0, 220, 260), Color::RGBA(0, 0, 0, 200));
+        p.toggle();
+        p.update_data(&[
+            friend("Zed", true),
+            friend("Ishtar", false),
+            friend("Arn", true),
+        ]);
+        p
+    }
+
+    fn left_click(x: i32, y: i32) -> UiEvent {
+        UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        }
+    }
+
+    #[test]
+    fn online_friends_come_first_with_tell_buttons() {
+        let mut p = panel();
+        let names: Vec<_> = p.friends.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["Arn", "Zed", "Ishtar"]);
+
+        // Row 0 is the "Online" header, row 1 is Arn.
+        let y = p.row_y(1) + 2;
+        p.handle_event(&left_click(10, y));
+        assert!(p.take_actions().is_empty());
+        p.handle_event(&left_click(p.tell_x() + 2, y));
+        assert!(matches!(
+            p.take_actions().as_slice(),
+            [WidgetAction::StartChat(text)] if text == "#tell Arn "
+        ));
+    }
+
+    #[test]
+    fn offline_header_collapses_offline_friends() {
+        let mut p = panel();
+        assert_eq!(p.rows().len(), 5);
+        let header_y = p.row_y(3) + 2;
+        p.handle_event(&left_click(10, header_y));
+        assert!(p.offline_collapsed);
+        assert_eq!(p.rows().len(), 4);
+
+        p.handle_event(&left_click(p.tell_x() + 2, header_y));
+        assert!(!p.offline_collapsed);
+        assert!(p.take_actions().is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! Guild overlay showing the player's guild, rank, message of the day and
+//! roster.
+//!
+//! GameScene hands the panel the [`GuildInfo`] kept by
+//! [`crate::player_state::PlayerState`] each frame. The panel only displays
+//! it; guilds are managed with the `#guild` chat commands.
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use mag_core::guilds::GuildRank;
+
+use crate::font_cache;
+use crate::player_state::{GuildInfo, GuildRosterEntry};
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, HudPanel, UiEvent, Widget, WidgetAction};
+use crate::ui::widgets::title_bar::{TITLE_BAR_H, TitleBar, clamp_to_viewport};
+
+/// Font index used for panel text (yellow bitmap font, matches other HUD
+/// panels).
+const PANEL_FONT: usize = 1;
+
+/// Vertical pixel height of one text line.
+const ROW_H: i32 = 14;
+
+/// Inner horizontal padding from the panel border to row content.
+const H_INSET: i32 = 6;
+
+/// Header lines above the roster: guild name and rank, then the MOTD.
+const HEADER_ROWS: i32 = 2;
+
+/// Maximum number of roster rows visible at once before scrolling kicks in.
+pub const VISIBLE_MEMBER_ROWS: usize = 12;
+
+/// Tint of members that are logged in.
+const ONLINE_COLOR: Color = Color::RGBA(130, 235, 150, 255);
+
+/// Tint of members that are logged out.
+const OFFLINE_COLOR: Color = Color::RGBA(150, 150, 150, 255);
+
+/// The guild HUD panel.
+pub struct GuildPanel {
+    bounds: Bounds,
+    bg_color: Color,
+    border_color: Color,
+    visible: bool,
+    guild: Option<GuildInfo>,
+    /// Roster in display order: online first, then by rank and name.
+    members: Vec<GuildRosterEntry>,
+    pending_actions: Vec<WidgetAction>,
+    scroll: usize,
+    title_bar: TitleBar,
+}
+
+impl GuildPanel {
+    /// Creates a new (hidden) guild panel.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds`   - Screen-space bounds of the panel.
+    /// * `bg_color` - Semi-transparent background color.
+    ///
+    /// # Returns
+    ///
+    /// * A new `GuildPanel`, initially hidden, with no guild.
+    pub fn new(bounds: Bounds, bg_color: Color) -> Self {
+        let title_bar = TitleBar::new("Guild", bounds.x, bounds.y, bounds.width);
+        Self {
+            bounds,
+            bg_color,
+            border_color: Color::RGBA(120, 120, 140, 200),
+            visible: false,
+            guild: None,
+            members: Vec::new(),
+            pending_actions: Vec::new(),
+            scroll: 0,
+            title_bar,
+        }
+    }
+
+    /// Toggles the panel's visibility.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Returns `true` when the panel is currently visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Replaces the displayed guild when it changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `guild` - The player's guild, or `None` when in no guild.
+    pub fn update_data(&mut self, guild: Option<&GuildInfo>) {
+        if self.guild.as_ref() == guild {
+            return;
+        }
+        self.guild = guild.cloned();
+        self.members = guild.map(|g| g.members.clone()).unwrap_or_default();
+        self.members.sort_by(|a, b| {
+            b.online
+                .cmp(&a.online)
+                .then(b.rank.cmp(&a.rank))
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        let max_scroll = self.members.len().saturating_sub(VISIBLE_MEMBER_ROWS);
+        self.scroll = self.scroll.min(max_scroll);
+    }
+
+    /// Y coordinate (top edge) of text line `line`.
+    fn line_y(&self, line: i32) -> i32 {
+        self.bounds.y + TITLE_BAR_H + 4 + line * ROW_H
+    }
+
+    fn draw(
+        ctx: &mut RenderContext<'_, '_>,
+        text: &str,
+        x: i32,
+        y: i32,
+        style: font_cache::TextStyle,
+    ) -> Result<(), String> {
+        font_cache::draw_text(ctx.canvas, ctx.gfx, PANEL_FONT, text, x, y, style)?;
+        Ok(())
+    }
+}
+
+impl Widget for GuildPanel {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+        self.title_bar.set_bar_position(x, y);
+    }
+
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+
+        let (tb_resp, drag_pos) = self.title_bar.handle_event(event);
+        if let Some((new_x, new_y)) = drag_pos {
+            let (cx, cy) = clamp_to_viewport(new_x, new_y, self.bounds.width, self.bounds.height);
+            self.set_position(cx, cy);
+        }
+        if self.title_bar.was_close_requested() {
+            self.visible = false;
+            self.pending_actions
+                .push(WidgetAction::TogglePanel(HudPanel::Guild));
+            return EventResponse::Consumed;
+        }
+        if tb_resp == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        match event {
+            UiEvent::MouseClick { x, y, .. } if self.bounds.contains_point(*x, *y) => {
+                EventResponse::Consumed
+            }
+            UiEvent::MouseWheel { x, y, delta } => {
+                if !self.bounds.contains_point(*x, *y) {
+                    return EventResponse::Ignored;
+                }
+                let max_scroll = self.members.len().saturating_sub(VISIBLE_MEMBER_ROWS);
+                if *delta > 0 {
+                    self.scroll = self.scroll.saturating_sub(*delta as usize);
+                } else if *delta < 0 {
+                    self.scroll = (self.scroll + (-delta) as usize).min(max_scroll);
+                }
+                EventResponse::Consumed
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let rect = sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(self.bg_color);
+        ctx.canvas.fill_rect(rect)?;
+        ctx.canvas.set_draw_color(self.border_color);
+        ctx.canvas.draw_rect(rect)?;
+        self.title_bar.render(ctx)?;
+
+        let text_x = self.bounds.x + H_INSET;
+        let Some(guild) = &self.guild else {
+            Self::draw(
+                ctx,
+                "You are not in a guild.",
+                text_x,
+                self.line_y(0),
+                font_cache::TextStyle::PLAIN,
+            )?;
+            Self::draw(
+                ctx,
+                "Type #guild help in chat.",
+                text_x,
+                self.line_y(1),
+                font_cache::TextStyle::PLAIN,
+            )?;
+            return Ok(());
+        };
+
+        let online = self.members.iter().filter(|m| m.online).count();
+        Self::draw(
+            ctx,
+            &format!(
+                "{} - {} ({}/{} online)",
+                guild.name,
+                guild.rank.label(),
+                online,
+                self.members.len()
+            ),
+            text_x,
+            self.line_y(0),
+            font_cache::TextStyle::PLAIN,
+        )?;
+        let motd = if guild.motd.is_empty() {
+            "No message of the day."
+        } else {
+            guild.motd.as_str()
+        };
+        Self::draw(
+            ctx,
+            motd,
+            text_x,
+            self.line_y(1),
+            font_cache::TextStyle::tinted(OFFLINE_COLOR),
+        )?;
+
+        for (row, member) in self
+            .members
+            .iter()
+            .skip(self.scroll)
+            .take(VISIBLE_MEMBER_ROWS)
+            .enumerate()
+        {
+            let y = self.line_y(HEADER_ROWS + row as i32) + 4;
+            let color = if member.online {
+                ONLINE_COLOR
+            } else {
+                OFFLINE_COLOR
+            };
+            Self::draw(
+                ctx,
+                &member.name,
+                text_x,
+                y,
+                font_cache::TextStyle::tinted(color),
+            )?;
+            if member.rank != GuildRank::Member {
+                let label = member.rank.label();
+                let label_x = self.bounds.x + self.bounds.width as i32
+                    - H_INSET
+                    - font_cache::text_width(label) as i32;
+                Self::draw(ctx, label, label_x, y, font_cache::TextStyle::PLAIN)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, rank: GuildRank, online: bool) -> GuildRosterEntry {
+        GuildRosterEntry {
+            name: name.to_owned(),
+            rank,
+            online,
+        }
+    }
+
+    #[test]
+    fn roster_lists_online_members_first_by_rank() {
+        let mut panel = GuildPanel::new(Bounds::new(0, 0, 220, 240), Color::RGBA(0, 0, 0, 200));
+        let guild = GuildInfo {
+            name: "Iron Wolves".to_owned(),
+            rank: GuildRank::Member,
+            motd: String::new(),
+            members: vec![
+                member("Zed", GuildRank::Member, true),
+                member("Ishtar", GuildRank::Leader, false),
+                member("Arn", GuildRank::Officer, true),
+            ],
+        };
+        panel.update_data(Some(&guild));
+        let names: Vec<_> = panel.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Arn", "Zed", "Ishtar"]);
+
+        panel.update_data(None);
+        assert!(panel.members.is_empty());
+    }
+}
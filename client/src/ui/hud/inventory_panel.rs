@@ -4,7 +4,14 @@
 //! 2×6 labeled equipment grid (right). When the player carries an item
 //! (`citem > 0`), invalid equipment slots are overlaid with a blocking
 //! sprite and the carried item follows the mouse cursor.
+//!
+//! Below the grids a search box highlights backpack items whose name
+//! (from `SV_ITEMNAME`) contains the typed text, and two buttons ask the
+//! server to sort the backpack by type or value.
+
+use std::time::Duration;
 
+use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::render::BlendMode;
 
@@ -13,12 +20,16 @@ use mag_core::constants::{
     PL_TWOHAND, PL_WEAPON, WN_ARMS, WN_BELT, WN_BODY, WN_CLOAK, WN_FEET, WN_HEAD, WN_LEGS,
     WN_LHAND, WN_LRING, WN_NECK, WN_RHAND, WN_RRING,
 };
+use mag_core::inventory_sort::InventorySortKey;
 
 use crate::font_cache;
 use crate::ui::RenderContext;
+use crate::ui::style::{Background, Border};
 use crate::ui::widget::{
     Bounds, EventResponse, HudPanel, MouseButton, UiEvent, Widget, WidgetAction,
 };
+use crate::ui::widgets::button::RectButton;
+use crate::ui::widgets::text_input::TextInput;
 use crate::ui::widgets::title_bar::{TITLE_BAR_H, TitleBar, clamp_to_viewport};
 
 // ---------------------------------------------------------------------------
@@ -67,6 +78,19 @@ const UI_FONT: usize = 1;
 /// Golden highlight color for controller-selected slots.
 const CONTROLLER_SELECT_COLOR: Color = Color::RGBA(255, 200, 50, 220);
 
+/// Height of the search box and sort buttons.
+const SEARCH_ROW_H: u32 = 20;
+/// Gap between the bottom of the search row and the panel's bottom edge.
+const SEARCH_ROW_PAD_BOTTOM: i32 = 8;
+/// Width of each sort button.
+const SORT_BTN_W: u32 = 38;
+/// Longest search text.
+const MAX_SEARCH_LEN: usize = 24;
+/// Outline drawn around backpack items matching the search.
+const SEARCH_MATCH_COLOR: Color = Color::RGBA(90, 220, 255, 230);
+/// Sort orders offered as buttons, left to right.
+const SORT_KEYS: [InventorySortKey; 2] = [InventorySortKey::Type, InventorySortKey::Value];
+
 /// Maps the 12 equipment grid positions (row-major, 2 cols × 6 rows) to
 /// `WN_*` wear-slot indices.  Matches the original C `wntab[]` order.
 /// TODO: Refactor this to put this logic all in one place.
@@ -117,8 +141,12 @@ pub struct InventoryPanelData {
     pub citem_p: i32,
     /// Total gold (gold = val/100, silver = val%100).
     pub gold: i32,
+    /// Draw a placeholder instead of the gold amount (streamer mode).
+    pub hide_gold: bool,
     /// Currently selected/targeted character (0 = self).
     pub selected_char: u16,
+    /// Backpack item names from `SV_ITEMNAME`, used by the search box.
+    pub item_names: [String; 40],
 }
 
 // ---------------------------------------------------------------------------
@@ -145,6 +173,13 @@ pub struct InventoryPanel {
     title_bar: TitleBar,
     /// Controller-selected slot (persisted across toggle cycles).
     controller_selected: Option<InvSlotKind>,
+    /// Backpack search box.
+    search_input: TextInput,
+    /// One button per entry of [`SORT_KEYS`].
+    sort_buttons: [RectButton; 2],
+    /// Whether shift-clicking a backpack item links it in chat instead of
+    /// picking it up; set while the chat input has focus.
+    link_items: bool,
 }
 
 impl InventoryPanel {
@@ -160,6 +195,38 @@ impl InventoryPanel {
     /// A new `InventoryPanel`, initially hidden.
     pub fn new(bounds: Bounds, bg_color: Color) -> Self {
         let title_bar = TitleBar::new("Inventory", bounds.x, bounds.y, bounds.width);
+        let row_y = bounds.y + bounds.height as i32 - SEARCH_ROW_PAD_BOTTOM - SEARCH_ROW_H as i32;
+        let buttons_x = bounds.x + bounds.width as i32 - INV_GRID_PAD_X - 2 * SORT_BTN_W as i32 - 4;
+        let search_input = TextInput::new(
+            Bounds::new(
+                bounds.x + INV_GRID_PAD_X,
+                row_y,
+                (buttons_x - 4 - bounds.x - INV_GRID_PAD_X).max(0) as u32,
+                SEARCH_ROW_H,
+            ),
+            "Search...",
+            UI_FONT,
+            MAX_SEARCH_LEN,
+            false,
+            Color::RGBA(120, 120, 140, 200),
+            Color::RGBA(200, 200, 120, 255),
+        );
+        let sort_buttons = std::array::from_fn(|i| {
+            RectButton::new(
+                Bounds::new(
+                    buttons_x + i as i32 * (SORT_BTN_W as i32 + 4),
+                    row_y,
+                    SORT_BTN_W,
+                    SEARCH_ROW_H,
+                ),
+                Background::SolidColor(Color::RGBA(40, 40, 60, 220)),
+            )
+            .with_label(SORT_KEYS[i].label(), UI_FONT)
+            .with_border(Border {
+                color: Color::RGBA(120, 120, 140, 200),
+                width: 1,
+            })
+        });
         Self {
             bounds,
             bg_color,
@@ -172,6 +239,9 @@ impl InventoryPanel {
             actions: Vec::new(),
             title_bar,
             controller_selected: None,
+            search_input,
+            sort_buttons,
+            link_items: false,
         }
     }
 
@@ -184,9 +254,67 @@ impl InventoryPanel {
         self.data = Some(data);
     }
 
-    /// Toggles the panel's visibility.
+    /// Makes shift-clicks on backpack items link them in chat.
+    ///
+    /// # Arguments
+    ///
+    /// * `link_items` - `true` while the chat input has focus.
+    pub fn set_link_items(&mut self, link_items: bool) {
+        self.link_items = link_items;
+    }
+
+    /// Toggles the panel's visibility. Hiding the panel drops search focus.
     pub fn toggle(&mut self) {
         self.visible = !self.visible;
+        if !self.visible {
+            self.search_input.set_focused(false);
+        }
+    }
+
+    /// Returns `true` while the search box has keyboard focus.
+    ///
+    /// GameScene uses this to suppress key bindings while the player types.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the panel is visible and the search box is focused.
+    pub fn is_text_focused(&self) -> bool {
+        self.visible && self.search_input.is_focused()
+    }
+
+    /// Returns which backpack slots match the search text.
+    ///
+    /// Matching is a case-insensitive substring test on the item name;
+    /// nothing matches while the search box is empty.
+    ///
+    /// # Returns
+    ///
+    /// * One flag per backpack slot.
+    fn search_matches(&self) -> [bool; INV_TOTAL_SLOTS] {
+        let mut matches = [false; INV_TOTAL_SLOTS];
+        let query = self.search_input.value().trim().to_lowercase();
+        let Some(data) = self.data.as_ref() else {
+            return matches;
+        };
+        if query.is_empty() {
+            return matches;
+        }
+        for (slot, matched) in matches.iter_mut().enumerate() {
+            *matched =
+                data.items[slot] > 0 && data.item_names[slot].to_lowercase().contains(&query);
+        }
+        matches
+    }
+
+    /// Scrolls the backpack so the first search match is visible.
+    fn scroll_to_first_match(&mut self) {
+        let Some(first) = self.search_matches().iter().position(|&m| m) else {
+            return;
+        };
+        let visible = INV_VISIBLE_ROWS * 2;
+        if first < self.inv_scroll || first >= self.inv_scroll + visible {
+            self.inv_scroll = (first & !1usize).min(INV_SCROLL_MAX);
+        }
     }
 
     /// Returns whether the panel is currently visible.
@@ -319,7 +447,9 @@ impl InventoryPanel {
             if data.items[idx] <= 0 {
                 return if data.citem > 0 { Some("DROP") } else { None };
             }
-            return Some(if shift {
+            return Some(if shift && self.link_items {
+                "LINK"
+            } else if shift {
                 if data.citem > 0 { "SWAP" } else { "PICK UP" }
             } else {
                 "USE"
@@ -622,9 +752,21 @@ impl Widget for InventoryPanel {
     }
 
     fn set_position(&mut self, x: i32, y: i32) {
+        let dx = x - self.bounds.x;
+        let dy = y - self.bounds.y;
         self.bounds.x = x;
         self.bounds.y = y;
         self.title_bar.set_bar_position(x, y);
+        let sb = *self.search_input.bounds();
+        self.search_input.set_position(sb.x + dx, sb.y + dy);
+        for button in &mut self.sort_buttons {
+            let b = *button.bounds();
+            button.set_position(b.x + dx, b.y + dy);
+        }
+    }
+
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
     }
 
     fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
@@ -640,6 +782,7 @@ impl Widget for InventoryPanel {
         }
         if self.title_bar.was_close_requested() {
             self.visible = false;
+            self.search_input.set_focused(false);
             self.actions
                 .push(WidgetAction::TogglePanel(HudPanel::Inventory));
             return EventResponse::Consumed;
@@ -648,6 +791,32 @@ impl Widget for InventoryPanel {
             return EventResponse::Consumed;
         }
 
+        // --- Search box and sort buttons ---
+        if self.search_input.handle_event(event) == EventResponse::Consumed {
+            if self.search_input.was_changed() {
+                self.scroll_to_first_match();
+            }
+            return EventResponse::Consumed;
+        }
+        if self.search_input.is_focused()
+            && let UiEvent::KeyDown { keycode, .. } = event
+        {
+            if matches!(
+                *keycode,
+                Keycode::Return | Keycode::KpEnter | Keycode::Escape
+            ) {
+                self.search_input.set_focused(false);
+            }
+            // Keep typed keys away from the game's key bindings.
+            return EventResponse::Consumed;
+        }
+        for (key, button) in SORT_KEYS.iter().zip(self.sort_buttons.iter_mut()) {
+            if button.handle_event(event) == EventResponse::Consumed {
+                self.actions.push(WidgetAction::SortInventory(*key));
+                return EventResponse::Consumed;
+            }
+        }
+
         match event {
             UiEvent::MouseMove { x, y } => {
                 self.mouse_x = *x;
@@ -699,6 +868,18 @@ impl Widget for InventoryPanel {
                                 c: selected_char,
                             });
                         }
+                        MouseButton::Left if modifiers.shift && self.link_items => {
+                            if data.items[idx] > 0 {
+                                let name = match data.item_names[idx].as_str() {
+                                    "" => "item",
+                                    name => name,
+                                };
+                                self.actions.push(WidgetAction::LinkItem {
+                                    slot: idx,
+                                    name: name.to_owned(),
+                                });
+                            }
+                        }
                         MouseButton::Left => {
                             let a = if modifiers.shift { 0u32 } else { 6u32 };
                             self.actions.push(WidgetAction::InvAction {
@@ -742,6 +923,10 @@ impl Widget for InventoryPanel {
         }
     }
 
+    fn update(&mut self, dt: Duration) {
+        self.search_input.update(dt);
+    }
+
     fn take_actions(&mut self) -> Vec<WidgetAction> {
         std::mem::take(&mut self.actions)
     }
@@ -774,9 +959,11 @@ impl Widget for InventoryPanel {
         // --- Money line ---
         let money_cx = self.bounds.x + self.bounds.width as i32 / 2;
         let money_y = self.bounds.y + TITLE_BAR_H + 2;
-        let gold = data.gold / 100;
-        let silver = data.gold % 100;
-        let money_text = format!("{}G {}S", gold, silver);
+        let money_text = if data.hide_gold {
+            "--G --S".to_owned()
+        } else {
+            format!("{}G {}S", data.gold / 100, data.gold % 100)
+        };
         font_cache::draw_text(
             ctx.canvas,
             ctx.gfx,
@@ -804,6 +991,7 @@ impl Widget for InventoryPanel {
             ))?;
         }
 
+        let matches = self.search_matches();
         for n in 0..(INV_VISIBLE_ROWS * 2) {
             let idx = self.inv_scroll + n;
             if idx >= INV_TOTAL_SLOTS {
@@ -816,6 +1004,15 @@ impl Widget for InventoryPanel {
             let y = inv_y + row * CELL;
             let hovered = hovered_inv == Some(idx);
             Self::draw_item(ctx, sprite, x, y, hovered)?;
+            if matches[idx] {
+                ctx.canvas.set_draw_color(SEARCH_MATCH_COLOR);
+                ctx.canvas.draw_rect(sdl2::rect::Rect::new(
+                    x + 1,
+                    y + 1,
+                    (CELL - 2) as u32,
+                    (CELL - 2) as u32,
+                ))?;
+            }
         }
 
         // --- Inventory scrollbar ---
@@ -900,6 +1097,12 @@ impl Widget for InventoryPanel {
             }
         }
 
+        // --- Search box and sort buttons ---
+        self.search_input.render(ctx)?;
+        for button in &mut self.sort_buttons {
+            button.render(ctx)?;
+        }
+
         // --- Controller selection highlight (golden stroke) ---
         if let Some(sel) = self.controller_selected {
             let sel_rect = match sel {
@@ -972,7 +1175,9 @@ mod tests {
             citem: 0,
             citem_p: 0,
             gold: 0,
+            hide_gold: false,
             selected_char: 0,
+            item_names: std::array::from_fn(|_| String::new()),
         }
     }
 
@@ -1018,6 +1223,40 @@ mod tests {
         assert_eq!(resp, EventResponse::Consumed);
     }
 
+    #[test]
+    fn shift_click_links_items_while_chatting() {
+        let mut panel =
+            InventoryPanel::new(Bounds::new(10, 10, 200, 300), Color::RGBA(0, 0, 0, 180));
+        panel.toggle();
+        let mut data = test_data();
+        data.items[1] = 77;
+        data.item_names[1] = "Steel Sword".to_owned();
+        panel.update_data(data);
+        let (ox, oy) = panel.inv_origin();
+        let shift_click = UiEvent::MouseClick {
+            x: ox + CELL + 2,
+            y: oy + 2,
+            button: MouseButton::Left,
+            modifiers: KeyModifiers {
+                shift: true,
+                ..Default::default()
+            },
+        };
+
+        panel.handle_event(&shift_click);
+        assert!(matches!(
+            panel.take_actions().as_slice(),
+            [WidgetAction::InvAction { a: 0, b: 1, .. }]
+        ));
+
+        panel.set_link_items(true);
+        panel.handle_event(&shift_click);
+        assert!(matches!(
+            panel.take_actions().as_slice(),
+            [WidgetAction::LinkItem { slot: 1, name }] if name == "Steel Sword"
+        ));
+    }
+
     #[test]
     fn visible_panel_ignores_clicks_outside() {
         let mut panel =
@@ -1150,4 +1389,57 @@ mod tests {
         assert_eq!(panel.hovered_label(false), None);
         assert_eq!(panel.hovered_label(true), None);
     }
+
+    #[test]
+    fn search_highlights_matches_and_scrolls_to_them() {
+        let mut panel = InventoryPanel::new(Bounds::new(0, 0, 190, 280), Color::RGBA(0, 0, 0, 180));
+        panel.toggle();
+        let mut data = test_data();
+        data.items[3] = 100;
+        data.item_names[3] = "Steel Sword".to_owned();
+        data.items[25] = 101;
+        data.item_names[25] = "Bronze Sword".to_owned();
+        data.item_names[30] = "Sword Without Sprite".to_owned();
+        panel.update_data(data);
+
+        // Click into the search box, then type.
+        let resp = panel.handle_event(&UiEvent::MouseClick {
+            x: INV_GRID_PAD_X + 5,
+            y: 280 - SEARCH_ROW_PAD_BOTTOM - 5,
+            button: MouseButton::Left,
+            modifiers: KeyModifiers::default(),
+        });
+        assert_eq!(resp, EventResponse::Consumed);
+        assert!(panel.is_text_focused());
+        panel.handle_event(&UiEvent::TextInput {
+            text: "bronze".to_owned(),
+        });
+
+        let matches = panel.search_matches();
+        assert_eq!(matches.iter().filter(|&&m| m).count(), 1);
+        assert!(matches[25]);
+        assert_eq!(panel.inv_scroll, 24);
+
+        panel.search_input.set_value("SWORD");
+        assert_eq!(panel.search_matches().iter().filter(|&&m| m).count(), 2);
+    }
+
+    #[test]
+    fn sort_buttons_emit_sort_actions() {
+        let mut panel = InventoryPanel::new(Bounds::new(0, 0, 190, 280), Color::RGBA(0, 0, 0, 180));
+        panel.toggle();
+        panel.update_data(test_data());
+        let value_button = *panel.sort_buttons[1].bounds();
+        panel.handle_event(&UiEvent::MouseClick {
+            x: value_button.x + 2,
+            y: value_button.y + 2,
+            button: MouseButton::Left,
+            modifiers: KeyModifiers::default(),
+        });
+        let actions = panel.take_actions();
+        assert!(matches!(
+            actions.as_slice(),
+            [WidgetAction::SortInventory(InventorySortKey::Value)]
+        ));
+    }
 }
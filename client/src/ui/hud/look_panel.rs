@@ -18,6 +18,7 @@ use crate::font_cache;
 use crate::player_state::PlayerState;
 
 use crate::ui::RenderContext;
+use crate::ui::style::name_color_tint;
 use crate::ui::widget::{Bounds, EventResponse, UiEvent, Widget};
 
 // ---------------------------------------------------------------------------
@@ -132,7 +133,10 @@ const EQUIP_WNTAB: [usize; 12] = [
 #[derive(Clone, Debug)]
 struct LookSnapshot {
     visible: bool,
+    /// Display name, prefixed with the look target's selected title.
     name: String,
+    /// Profile name color chosen by the look target.
+    name_color: mag_core::profile::NameColor,
     /// Sprite ID from tile obj2 (pre-computed by engine_tick).
     sprite_id: i32,
     worn: [u16; 12],
@@ -153,6 +157,7 @@ impl Default for LookSnapshot {
         Self {
             visible: false,
             name: String::new(),
+            name_color: mag_core::profile::NameColor::Default,
             sprite_id: 0,
             worn: [0; 12],
             a_hp: 0,
@@ -253,7 +258,8 @@ impl LookPanel {
 
         self.snap = LookSnapshot {
             visible: true,
-            name: look.name().unwrap_or("").to_owned(),
+            name: mag_core::titles::titled_name(look.title(), look.name().unwrap_or("")),
+            name_color: look.name_color(),
             sprite_id,
             worn,
             a_hp: look.a_hp(),
@@ -324,6 +330,10 @@ impl Widget for LookPanel {
         self.bounds.y = y;
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     /// Input events are ignored — the panel is display-only.
     ///
     /// # Arguments
@@ -417,7 +427,10 @@ impl Widget for LookPanel {
             &self.snap.name.clone(),
             text_x,
             text_block_y,
-            font_cache::TextStyle::PLAIN,
+            match name_color_tint(self.snap.name_color) {
+                Some(tint) => font_cache::TextStyle::tinted(tint),
+                None => font_cache::TextStyle::PLAIN,
+            },
         )?;
         font_cache::draw_text(
             ctx.canvas,
@@ -0,0 +1,557 @@
+//! Mailbox overlay listing the player's letters, with a compose line.
+//!
+//! GameScene hands the panel the mailbox kept by
+//! [`crate::player_state::PlayerState`] each frame. Clicking a letter shows
+//! its text; the buttons and the compose line emit `#mail` commands as
+//! [`WidgetAction::SendChat`], so the panel needs no protocol of its own and
+//! the server reports every outcome in the chat log.
+
+use std::time::Duration;
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use mag_core::mail::{MAX_MAIL_BODY_LEN, MailEntry};
+
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::style::{Background, Border};
+use crate::ui::widget::{
+    Bounds, EventResponse, HudPanel, MouseButton, UiEvent, Widget, WidgetAction,
+};
+use crate::ui::widgets::button::RectButton;
+use crate::ui::widgets::text_input::TextInput;
+use crate::ui::widgets::title_bar::{TITLE_BAR_H, TitleBar, clamp_to_viewport};
+
+/// Bitmap font index used for panel text.
+const FONT: usize = 1;
+
+/// Horizontal inset from panel edges.
+const H_INSET: i32 = 6;
+
+/// Vertical pixel height of one text line.
+const ROW_H: i32 = 14;
+
+/// Letters visible at once before scrolling kicks in.
+pub const VISIBLE_LETTER_ROWS: usize = 6;
+
+/// Lines of the selected letter's text shown below the list.
+const BODY_LINES: usize = 4;
+
+/// Height of the buttons and text inputs.
+const CONTROL_H: u32 = 16;
+
+/// Width of the buttons.
+const BTN_W: u32 = 52;
+
+/// Width of the recipient input.
+const TO_INPUT_W: u32 = 96;
+
+/// Longest character name the recipient input accepts.
+const MAX_NAME_LEN: usize = 15;
+
+/// Tint of unread letters.
+const UNREAD_COLOR: Color = Color::RGBA(255, 230, 140, 255);
+
+/// Tint of read letters and the attachment line.
+const READ_COLOR: Color = Color::RGBA(170, 170, 170, 255);
+
+/// Background of the highlighted letter row.
+const SELECTED_BG: Color = Color::RGBA(70, 70, 110, 200);
+
+/// The mailbox HUD panel.
+pub struct MailPanel {
+    bounds: Bounds,
+    bg_color: Color,
+    border_color: Color,
+    visible: bool,
+    letters: Vec<MailEntry>,
+    /// Id of the letter whose text is shown.
+    selected: Option<u32>,
+    scroll: usize,
+    pending_actions: Vec<WidgetAction>,
+    title_bar: TitleBar,
+    take_button: RectButton,
+    delete_button: RectButton,
+    to_input: TextInput,
+    body_input: TextInput,
+    send_button: RectButton,
+    /// Like "Send", but attaches the item on the cursor.
+    parcel_button: RectButton,
+}
+
+impl MailPanel {
+    /// Creates a new (hidden) mail panel.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds`   - Screen-space bounds of the panel.
+    /// * `bg_color` - Semi-transparent background color.
+    ///
+    /// # Returns
+    ///
+    /// * A new `MailPanel`, initially hidden, with an empty mailbox.
+    pub fn new(bounds: Bounds, bg_color: Color) -> Self {
+        let title_bar = TitleBar::new("Mail", bounds.x, bounds.y, bounds.width);
+        let button = |x: i32, y: i32, label: &str| {
+            RectButton::new(
+                Bounds::new(x, y, BTN_W, CONTROL_H),
+                Background::SolidColor(Color::RGBA(40, 40, 60, 220)),
+            )
+            .with_label(label, FONT)
+            .with_border(Border {
+                color: Color::RGBA(120, 120, 140, 200),
+                width: 1,
+            })
+        };
+        let right = bounds.x + bounds.width as i32 - H_INSET;
+        let body_y = bounds.y + bounds.height as i32 - 6 - CONTROL_H as i32;
+        let to_y = body_y - 4 - CONTROL_H as i32;
+        let action_y = to_y - 8 - CONTROL_H as i32;
+        let input = |x: i32, y: i32, width: u32, placeholder: &str, max_len: usize| {
+            TextInput::new(
+                Bounds::new(x, y, width, CONTROL_H),
+                placeholder,
+                FONT,
+                max_len,
+                false,
+                Color::RGBA(120, 120, 140, 200),
+                Color::RGBA(200, 200, 120, 255),
+            )
+        };
+        Self {
+            bounds,
+            bg_color,
+            border_color: Color::RGBA(120, 120, 140, 200),
+            visible: false,
+            letters: Vec::new(),
+            selected: None,
+            scroll: 0,
+            pending_actions: Vec::new(),
+            title_bar,
+            take_button: button(bounds.x + H_INSET, action_y, "Take"),
+            delete_button: button(bounds.x + H_INSET + BTN_W as i32 + 4, action_y, "Delete"),
+            to_input: input(bounds.x + H_INSET, to_y, TO_INPUT_W, "To...", MAX_NAME_LEN),
+            body_input: input(
+                bounds.x + H_INSET,
+                body_y,
+                bounds.width.saturating_sub(2 * H_INSET as u32),
+                "Write a letter...",
+                MAX_MAIL_BODY_LEN,
+            ),
+            send_button: button(right - 2 * BTN_W as i32 - 4, to_y, "Send"),
+            parcel_button: button(right - BTN_W as i32, to_y, "Parcel"),
+        }
+    }
+
+    /// Toggles the panel's visibility. Hiding the panel drops input focus.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if !self.visible {
+            self.to_input.set_focused(false);
+            self.body_input.set_focused(false);
+        }
+    }
+
+    /// Returns `true` when the panel is currently visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Returns `true` while one of the compose inputs has keyboard focus.
+    ///
+    /// GameScene uses this to suppress key bindings while the player types.
+    pub fn is_text_focused(&self) -> bool {
+        self.visible && (self.to_input.is_focused() || self.body_input.is_focused())
+    }
+
+    /// Replaces the displayed mailbox when it changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `letters` - The player's letters, oldest first.
+    pub fn update_data(&mut self, letters: &[MailEntry]) {
+        if self.letters == letters {
+            return;
+        }
+        self.letters = letters.to_vec();
+        if self
+            .selected
+            .is_some_and(|id| !self.letters.iter().any(|l| l.id == id))
+        {
+            self.selected = None;
+        }
+        let max_scroll = self.letters.len().saturating_sub(VISIBLE_LETTER_ROWS);
+        self.scroll = self.scroll.min(max_scroll);
+    }
+
+    fn selected_letter(&self) -> Option<&MailEntry> {
+        let id = self.selected?;
+        self.letters.iter().find(|l| l.id == id)
+    }
+
+    /// Y coordinate (top edge) of letter row `row`.
+    fn row_y(&self, row: usize) -> i32 {
+        self.bounds.y + TITLE_BAR_H + 4 + row as i32 * ROW_H
+    }
+
+    /// Index into `letters` of the row under `(x, y)`.
+    fn letter_at(&self, x: i32, y: i32) -> Option<usize> {
+        let top = self.row_y(0);
+        let inside_x = x >= self.bounds.x && x < self.bounds.x + self.bounds.width as i32;
+        if !inside_x || y < top {
+            return None;
+        }
+        let row = ((y - top) / ROW_H) as usize;
+        (row < VISIBLE_LETTER_ROWS)
+            .then_some(self.scroll + row)
+            .filter(|&idx| idx < self.letters.len())
+    }
+
+    /// Selects the letter at `idx`; opening an unread letter asks the server
+    /// to mark it read.
+    fn select(&mut self, idx: usize) {
+        let letter = &self.letters[idx];
+        self.selected = Some(letter.id);
+        if letter.unread {
+            self.pending_actions
+                .push(WidgetAction::SendChat(format!("#mail read {}", letter.id)));
+        }
+    }
+
+    /// Queues `#mail <command> <id>` for the selected letter.
+    fn act_on_selected(&mut self, command: &str) {
+        if let Some(id) = self.selected {
+            self.pending_actions
+                .push(WidgetAction::SendChat(format!("#mail {} {}", command, id)));
+        }
+    }
+
+    /// Queues the composed letter and clears its text.
+    ///
+    /// # Arguments
+    ///
+    /// * `parcel` - Attach the item on the cursor.
+    fn submit(&mut self, parcel: bool) {
+        let to = self.to_input.value().trim().to_owned();
+        let body = self.body_input.value().trim().to_owned();
+        if to.is_empty() || body.is_empty() {
+            return;
+        }
+        let command = if parcel { "parcel" } else { "send" };
+        self.pending_actions.push(WidgetAction::SendChat(format!(
+            "#mail {} {} {}",
+            command, to, body
+        )));
+        self.body_input.clear();
+        self.body_input.set_focused(false);
+    }
+
+    fn draw(
+        ctx: &mut RenderContext<'_, '_>,
+        text: &str,
+        x: i32,
+        y: i32,
+        style: font_cache::TextStyle,
+    ) -> Result<(), String> {
+        font_cache::draw_text(ctx.canvas, ctx.gfx, FONT, text, x, y, style)?;
+        Ok(())
+    }
+}
+
+impl Widget for MailPanel {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        let dx = x - self.bounds.x;
+        let dy = y - self.bounds.y;
+        self.bounds.x = x;
+        self.bounds.y = y;
+        self.title_bar.set_bar_position(x, y);
+        for input in [&mut self.to_input, &mut self.body_input] {
+            let b = *input.bounds();
+            input.set_position(b.x + dx, b.y + dy);
+        }
+        for button in [
+            &mut self.take_button,
+            &mut self.delete_button,
+            &mut self.send_button,
+            &mut self.parcel_button,
+        ] {
+            let b = *button.bounds();
+            button.set_position(b.x + dx, b.y + dy);
+        }
+    }
+
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+
+        let (tb_resp, drag_pos) = self.title_bar.handle_event(event);
+        if let Some((nx, ny)) = drag_pos {
+            let (cx, cy) = clamp_to_viewport(nx, ny, self.bounds.width, self.bounds.height);
+            self.set_position(cx, cy);
+            return EventResponse::Consumed;
+        }
+        if self.title_bar.was_close_requested() {
+            self.toggle();
+            self.pending_actions
+                .push(WidgetAction::TogglePanel(HudPanel::Mail));
+            return EventResponse::Consumed;
+        }
+        if tb_resp == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        let to_resp = self.to_input.handle_event(event);
+        let body_resp = self.body_input.handle_event(event);
+        if to_resp == EventResponse::Consumed || body_resp == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        if self.body_input.is_focused()
+            && let UiEvent::KeyDown { keycode, .. } = event
+            && matches!(
+                *keycode,
+                sdl2::keyboard::Keycode::Return | sdl2::keyboard::Keycode::KpEnter
+            )
+        {
+            self.submit(false);
+            return EventResponse::Consumed;
+        }
+
+        if self.send_button.handle_event(event) == EventResponse::Consumed {
+            self.submit(false);
+            return EventResponse::Consumed;
+        }
+        if self.parcel_button.handle_event(event) == EventResponse::Consumed {
+            self.submit(true);
+            return EventResponse::Consumed;
+        }
+        if self.take_button.handle_event(event) == EventResponse::Consumed {
+            self.act_on_selected("take");
+            return EventResponse::Consumed;
+        }
+        if self.delete_button.handle_event(event) == EventResponse::Consumed {
+            self.act_on_selected("delete");
+            return EventResponse::Consumed;
+        }
+
+        match event {
+            UiEvent::MouseClick {
+                x,
+                y,
+                button: MouseButton::Left,
+                ..
+            } if self.bounds.contains_point(*x, *y) => {
+                if let Some(idx) = self.letter_at(*x, *y) {
+                    self.select(idx);
+                }
+                EventResponse::Consumed
+            }
+            UiEvent::MouseClick { x, y, .. } | UiEvent::MouseDown { x, y, .. } => {
+                if self.bounds.contains_point(*x, *y) {
+                    EventResponse::Consumed
+                } else {
+                    EventResponse::Ignored
+                }
+            }
+            UiEvent::MouseWheel { x, y, delta } => {
+                if !self.bounds.contains_point(*x, *y) {
+                    return EventResponse::Ignored;
+                }
+                let max_scroll = self.letters.len().saturating_sub(VISIBLE_LETTER_ROWS);
+                if *delta > 0 {
+                    self.scroll = self.scroll.saturating_sub(*delta as usize);
+                } else if *delta < 0 {
+                    self.scroll = (self.scroll + (-delta) as usize).min(max_scroll);
+                }
+                EventResponse::Consumed
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn update(&mut self, dt: Duration) {
+        self.to_input.update(dt);
+        self.body_input.update(dt);
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let rect = sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(self.bg_color);
+        ctx.canvas.fill_rect(rect)?;
+        ctx.canvas.set_draw_color(self.border_color);
+        ctx.canvas.draw_rect(rect)?;
+        self.title_bar.render(ctx)?;
+
+        let text_x = self.bounds.x + H_INSET;
+        let text_w = self.bounds.width.saturating_sub(2 * H_INSET as u32);
+        if self.letters.is_empty() {
+            Self::draw(
+                ctx,
+                "Your mailbox is empty.",
+                text_x,
+                self.row_y(0),
+                font_cache::TextStyle::PLAIN,
+            )?;
+        }
+        for (row, letter) in self
+            .letters
+            .iter()
+            .skip(self.scroll)
+            .take(VISIBLE_LETTER_ROWS)
+            .enumerate()
+        {
+            let y = self.row_y(row);
+            if self.selected == Some(letter.id) {
+                ctx.canvas.set_draw_color(SELECTED_BG);
+                ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+                    self.bounds.x + 2,
+                    y - 1,
+                    self.bounds.width.saturating_sub(4),
+                    ROW_H as u32,
+                ))?;
+            }
+            let color = if letter.unread {
+                UNREAD_COLOR
+            } else {
+                READ_COLOR
+            };
+            let marker = if letter.attachment.is_empty() {
+                ""
+            } else {
+                " +item"
+            };
+            Self::draw(
+                ctx,
+                &format!("#{} {}{}", letter.id, letter.from, marker),
+                text_x,
+                y,
+                font_cache::TextStyle::tinted(color),
+            )?;
+        }
+
+        let body_top = self.row_y(VISIBLE_LETTER_ROWS) + 4;
+        if let Some(letter) = self.selected_letter() {
+            let mut lines = font_cache::wrap_lines_bitmap(&letter.body, text_w);
+            lines.truncate(BODY_LINES);
+            for (i, line) in lines.iter().enumerate() {
+                Self::draw(
+                    ctx,
+                    line,
+                    text_x,
+                    body_top + i as i32 * ROW_H,
+                    font_cache::TextStyle::PLAIN,
+                )?;
+            }
+            if !letter.attachment.is_empty() {
+                Self::draw(
+                    ctx,
+                    &format!("Attached: {}", letter.attachment),
+                    text_x,
+                    body_top + BODY_LINES as i32 * ROW_H,
+                    font_cache::TextStyle::tinted(READ_COLOR),
+                )?;
+            }
+        }
+
+        self.take_button.render(ctx)?;
+        self.delete_button.render(ctx)?;
+        self.to_input.render(ctx)?;
+        self.send_button.render(ctx)?;
+        self.parcel_button.render(ctx)?;
+        self.body_input.render(ctx)?;
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panel() -> MailPanel {
+        let mut p = MailPanel::new(Bounds::new(0, 0, 300, 250), Color::RGBA(0, 0, 0, 200));
+        p.toggle();
+        p
+    }
+
+    fn left_click(x: i32, y: i32) -> UiEvent {
+        UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        }
+    }
+
+    fn letter(id: u32, unread: bool) -> MailEntry {
+        MailEntry {
+            id,
+            unread,
+            from: "Ishtar".to_owned(),
+            body: "Meet me in Aston.".to_owned(),
+            ..MailEntry::default()
+        }
+    }
+
+    fn chats(p: &mut MailPanel) -> Vec<String> {
+        p.take_actions()
+            .into_iter()
+            .filter_map(|a| match a {
+                WidgetAction::SendChat(text) => Some(text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn opening_letters_marks_them_read_and_buttons_act_on_them() {
+        let mut p = panel();
+        p.update_data(&[letter(3, false), letter(8, true)]);
+        let y = p.row_y(1);
+        p.handle_event(&left_click(10, y + 2));
+        assert_eq!(p.selected, Some(8));
+        let take = *p.take_button.bounds();
+        p.handle_event(&left_click(take.x + 1, take.y + 1));
+        assert_eq!(chats(&mut p), ["#mail read 8", "#mail take 8"]);
+
+        p.update_data(&[letter(3, false)]);
+        assert_eq!(p.selected, None);
+        let delete = *p.delete_button.bounds();
+        p.handle_event(&left_click(delete.x + 1, delete.y + 1));
+        assert!(chats(&mut p).is_empty());
+    }
+
+    #[test]
+    fn compose_line_sends_mail_commands() {
+        let mut p = panel();
+        p.to_input.set_value("Ishtar");
+        p.body_input.set_value("  See you at dusk. ");
+        p.submit(true);
+        p.submit(false);
+        assert_eq!(chats(&mut p), ["#mail parcel Ishtar See you at dusk."]);
+        assert!(p.body_input.value().is_empty());
+    }
+}
@@ -3,13 +3,18 @@
 //! Displays a circular button near the top-right of the screen. When clicked
 //! the button opens a framed minimap viewport anchored to the left of the
 //! button. Clicking again hides the viewport.
+//!
+//! The mouse wheel zooms the viewport and dragging it pans away from the
+//! player; the `o` button re-centres it. A legend row under the panel shows
+//! and toggles the points-of-interest markers (temple, tavern, bank, death).
 
+use mag_core::map_markers::MapMarkerKind;
 use sdl2::pixels::Color;
 
 use crate::filepaths;
 use crate::ui::RenderContext;
 use crate::ui::style::{Background, Border};
-use crate::ui::widget::{Bounds, EventResponse, UiEvent, Widget};
+use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget, WidgetAction};
 use crate::ui::widgets::button::{CircularImageButton, RectButton};
 
 // ---------------------------------------------------------------------------
@@ -59,6 +64,15 @@ const ZOOM_SAMPLE_SIZES: [u32; 5] = [64, 96, 128, 160, 192];
 /// Default zoom level preserving the current 128×128 sampling behavior.
 const DEFAULT_ZOOM_LEVEL: usize = 2;
 
+/// Size of one marker legend swatch in pixels.
+const LEGEND_SWATCH: u32 = 10;
+
+/// Horizontal gap between legend swatches.
+const LEGEND_GAP: i32 = 4;
+
+/// Vertical gap between the minimap panel and the legend row.
+const LEGEND_PANEL_GAP: i32 = 4;
+
 /// Background color for the panel area behind the minimap pixels.
 const PANEL_BG: Color = Color::RGBA(10, 10, 30, 200);
 
@@ -68,6 +82,24 @@ const PANEL_BORDER_COLOR: Color = Color::RGBA(120, 120, 140, 220);
 /// Whole-button image filename for the minimap toggle.
 const BUTTON_IMAGE_FILE: &str = "map.png";
 
+/// Color a point-of-interest marker (and its legend swatch) is drawn in.
+fn marker_color(kind: MapMarkerKind) -> Color {
+    match kind {
+        MapMarkerKind::Temple => Color::RGBA(120, 200, 255, 255),
+        MapMarkerKind::Tavern => Color::RGBA(255, 160, 60, 255),
+        MapMarkerKind::Bank => Color::RGBA(80, 255, 120, 255),
+        MapMarkerKind::Death => Color::RGBA(255, 60, 60, 255),
+    }
+}
+
+/// Drag-to-pan state captured on mouse-down over the panel.
+#[derive(Clone, Copy, Debug)]
+struct PanDrag {
+    start_x: i32,
+    start_y: i32,
+    start_pan: (i32, i32),
+}
+
 // ---------------------------------------------------------------------------
 // MinimapWidget
 // ---------------------------------------------------------------------------
@@ -86,6 +118,8 @@ pub struct MinimapWidget {
     zoom_in_button: RectButton,
     /// Button that increases the sampled world area, zooming the minimap out.
     zoom_out_button: RectButton,
+    /// Button that re-centres a panned viewport on the player.
+    recenter_button: RectButton,
     /// Whether the expanded map panel is currently shown.
     visible: bool,
     /// Index into [`ZOOM_SAMPLE_SIZES`] selecting the current zoom level.
@@ -115,6 +149,20 @@ pub struct MinimapWidget {
     last_center_x: u16,
     /// World tile Y of the most recent viewport center.
     last_center_y: u16,
+    /// Viewport offset from the player in world tiles `(x, y)`.
+    pan: (i32, i32),
+    /// Active drag-to-pan gesture, if any.
+    drag: Option<PanDrag>,
+    /// Points of interest as `(kind, world_x, world_y)`.
+    poi_markers: Vec<(MapMarkerKind, u16, u16)>,
+    /// Whether each [`MapMarkerKind`] is shown, indexed by kind.
+    marker_enabled: [bool; MapMarkerKind::ALL.len()],
+    /// Legend swatch bounds, indexed by kind.
+    legend_bounds: [Bounds; MapMarkerKind::ALL.len()],
+    /// Legend swatch under the mouse, if any.
+    hovered_legend: Option<MapMarkerKind>,
+    /// Actions waiting to be drained by the scene.
+    pending_actions: Vec<WidgetAction>,
 }
 
 impl MinimapWidget {
@@ -155,6 +203,7 @@ impl MinimapWidget {
             button,
             zoom_in_button: Self::make_zoom_button("+"),
             zoom_out_button: Self::make_zoom_button("-"),
+            recenter_button: Self::make_zoom_button("o"),
             visible: false,
             zoom_level: DEFAULT_ZOOM_LEVEL,
             viewport_pixels: vec![0u8; (view as usize) * (view as usize) * 4],
@@ -169,6 +218,13 @@ impl MinimapWidget {
             active_quest_marker: None,
             last_center_x: 0,
             last_center_y: 0,
+            pan: (0, 0),
+            drag: None,
+            poi_markers: Vec::new(),
+            marker_enabled: [true; MapMarkerKind::ALL.len()],
+            legend_bounds: [Bounds::new(0, 0, 0, 0); MapMarkerKind::ALL.len()],
+            hovered_legend: None,
+            pending_actions: Vec::new(),
         };
         widget.recompute_layout();
         widget
@@ -179,6 +235,9 @@ impl MinimapWidget {
         self.visible = !self.visible;
         self.zoom_in_button.set_hovered(false);
         self.zoom_out_button.set_hovered(false);
+        self.recenter_button.set_hovered(false);
+        self.hovered_legend = None;
+        self.drag = None;
     }
 
     /// Returns whether the map viewport is currently visible.
@@ -197,10 +256,18 @@ impl MinimapWidget {
     /// * `Some("Minimap")` when the button is hovered, or `None`.
     pub fn hover_text(&self) -> Option<&'static str> {
         if self.button.is_hovered() {
-            Some("Minimap")
-        } else {
-            None
+            return Some("Minimap");
+        }
+        if self.visible && self.recenter_button.is_hovered() {
+            return Some("Re-center minimap");
         }
+        let kind = self.hovered_legend.filter(|_| self.visible)?;
+        Some(match kind {
+            MapMarkerKind::Temple => "Temple marker (click to toggle)",
+            MapMarkerKind::Tavern => "Tavern marker (click to toggle)",
+            MapMarkerKind::Bank => "Bank markers (click to toggle)",
+            MapMarkerKind::Death => "Death marker (click to toggle)",
+        })
     }
 
     /// Sets the draw opacity for the toggle button only.
@@ -264,9 +331,21 @@ impl MinimapWidget {
         let zoom_y = panel_y - ZOOM_BUTTON_H as i32 - ZOOM_BUTTON_PANEL_GAP;
         let zoom_in_x = panel_x;
         let zoom_out_x = zoom_in_x + ZOOM_BUTTON_W as i32 + ZOOM_BUTTON_GAP;
+        let recenter_x = zoom_out_x + ZOOM_BUTTON_W as i32 + ZOOM_BUTTON_GAP;
 
         self.zoom_in_button.set_position(zoom_in_x, zoom_y);
         self.zoom_out_button.set_position(zoom_out_x, zoom_y);
+        self.recenter_button.set_position(recenter_x, zoom_y);
+
+        let legend_y = panel_y + self.panel_h as i32 + LEGEND_PANEL_GAP;
+        for (i, bounds) in self.legend_bounds.iter_mut().enumerate() {
+            *bounds = Bounds::new(
+                panel_x + i as i32 * (LEGEND_SWATCH as i32 + LEGEND_GAP),
+                legend_y,
+                LEGEND_SWATCH,
+                LEGEND_SWATCH,
+            );
+        }
 
         self.panel_x = panel_x;
         self.panel_y = panel_y;
@@ -274,6 +353,8 @@ impl MinimapWidget {
 
         let zoom_in_bounds = *self.zoom_in_button.bounds();
         let zoom_out_bounds = *self.zoom_out_button.bounds();
+        let recenter_bounds = *self.recenter_button.bounds();
+        let legend_last = self.legend_bounds[self.legend_bounds.len() - 1];
         let min_x = button_bounds
             .x
             .min(panel_x)
@@ -287,11 +368,14 @@ impl MinimapWidget {
         let max_x = (button_bounds.x + button_bounds.width as i32)
             .max(panel_x + self.panel_w as i32)
             .max(zoom_in_bounds.x + zoom_in_bounds.width as i32)
-            .max(zoom_out_bounds.x + zoom_out_bounds.width as i32);
+            .max(zoom_out_bounds.x + zoom_out_bounds.width as i32)
+            .max(recenter_bounds.x + recenter_bounds.width as i32)
+            .max(legend_last.x + legend_last.width as i32);
         let max_y = (button_bounds.y + button_bounds.height as i32)
             .max(panel_y + self.panel_h as i32)
             .max(zoom_in_bounds.y + zoom_in_bounds.height as i32)
-            .max(zoom_out_bounds.y + zoom_out_bounds.height as i32);
+            .max(zoom_out_bounds.y + zoom_out_bounds.height as i32)
+            .max(legend_last.y + legend_last.height as i32);
 
         self.bounds_expanded =
             Bounds::new(min_x, min_y, (max_x - min_x) as u32, (max_y - min_y) as u32);
@@ -324,6 +408,9 @@ impl MinimapWidget {
     /// * `center_x` - Player X in world-map coordinates.
     /// * `center_y` - Player Y in world-map coordinates.
     pub fn update_viewport(&mut self, xmap: &[u8], center_x: u16, center_y: u16) {
+        let max = WORLD_SIZE as i32 - 1;
+        let center_x = (i32::from(center_x) + self.pan.0).clamp(0, max) as u16;
+        let center_y = (i32::from(center_y) + self.pan.1).clamp(0, max) as u16;
         self.last_center_x = center_x;
         self.last_center_y = center_y;
         // Always update the pixel buffer even when hidden, so the minimap
@@ -359,7 +446,6 @@ impl MinimapWidget {
     }
 
     /// Returns `true` if `(px, py)` lands inside the map panel rectangle.
-    #[allow(dead_code)]
     fn panel_contains(&self, px: i32, py: i32) -> bool {
         px >= self.panel_x
             && py >= self.panel_y
@@ -380,6 +466,41 @@ impl MinimapWidget {
         self.active_quest_marker = active;
     }
 
+    /// Updates the points-of-interest markers and which kinds are shown.
+    ///
+    /// # Arguments
+    ///
+    /// * `markers` - `(kind, world_x, world_y)` for every known marker.
+    /// * `enabled` - Whether each kind is shown, indexed by kind.
+    pub fn set_poi_markers(
+        &mut self,
+        markers: Vec<(MapMarkerKind, u16, u16)>,
+        enabled: [bool; MapMarkerKind::ALL.len()],
+    ) {
+        self.poi_markers = markers;
+        self.marker_enabled = enabled;
+    }
+
+    /// Moves the viewport centre while a drag is in progress.
+    ///
+    /// The minimap is rotated relative to the world (screen X tracks world
+    /// Y and screen Y tracks world X, see [`Self::project_world_to_screen`]),
+    /// so the drag axes are swapped when converted to world tiles.
+    fn drag_to(&mut self, x: i32, y: i32) {
+        let Some(drag) = self.drag else {
+            return;
+        };
+        let view = MINIMAP_WIDGET_VIEW_SIZE as i32;
+        let sample = self.current_sample_size() as i32;
+        let world = WORLD_SIZE as i32;
+        let dx = (y - drag.start_y) * sample / view;
+        let dy = (x - drag.start_x) * sample / view;
+        self.pan = (
+            (drag.start_pan.0 - dx).clamp(-world, world),
+            (drag.start_pan.1 - dy).clamp(-world, world),
+        );
+    }
+
     /// Projects a world tile position into screen-space coordinates inside the
     /// minimap viewport, mirroring the math performed by `update_viewport`.
     ///
@@ -432,6 +553,18 @@ impl MinimapWidget {
             }
         }
 
+        // Points of interest, 4×4 so they stand out from quest givers.
+        for &(kind, wx, wy) in &self.poi_markers {
+            if !self.marker_enabled[kind as usize] {
+                continue;
+            }
+            if let Some((sx, sy)) = self.project_world_to_screen(wx, wy) {
+                ctx.canvas.set_draw_color(marker_color(kind));
+                ctx.canvas
+                    .fill_rect(sdl2::rect::Rect::new(sx - 2, sy - 2, 4, 4))?;
+            }
+        }
+
         // Active quest marker (magenta) drawn on top so it is always visible.
         if let Some((wx, wy)) = self.active_quest_marker
             && let Some((sx, sy)) = self.project_world_to_screen(wx, wy)
@@ -463,11 +596,53 @@ impl Widget for MinimapWidget {
 
     fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
         // Always delegate mouse-move to the button for hover tracking.
-        if let UiEvent::MouseMove { .. } = event {
+        if let UiEvent::MouseMove { x, y } = event {
             self.button.handle_event(event);
             if self.visible {
                 self.zoom_in_button.handle_event(event);
                 self.zoom_out_button.handle_event(event);
+                self.recenter_button.handle_event(event);
+                self.hovered_legend = MapMarkerKind::ALL
+                    .into_iter()
+                    .find(|kind| self.legend_bounds[*kind as usize].contains_point(*x, *y));
+                if self.drag.is_some() {
+                    self.drag_to(*x, *y);
+                    return EventResponse::Consumed;
+                }
+            }
+        }
+
+        if !self.visible {
+            self.drag = None;
+        } else {
+            match event {
+                UiEvent::MouseWheel { x, y, delta } if self.panel_contains(*x, *y) => {
+                    if *delta > 0 {
+                        self.zoom_in();
+                    } else if *delta < 0 {
+                        self.zoom_out();
+                    }
+                    return EventResponse::Consumed;
+                }
+                UiEvent::MouseDown {
+                    x,
+                    y,
+                    button: MouseButton::Left,
+                    ..
+                } if self.panel_contains(*x, *y) => {
+                    self.drag = Some(PanDrag {
+                        start_x: *x,
+                        start_y: *y,
+                        start_pan: self.pan,
+                    });
+                    return EventResponse::Consumed;
+                }
+                UiEvent::MouseClick { x, y, .. } if self.drag.is_some() => {
+                    self.drag_to(*x, *y);
+                    self.drag = None;
+                    return EventResponse::Consumed;
+                }
+                _ => {}
             }
         }
 
@@ -487,12 +662,31 @@ impl Widget for MinimapWidget {
                     self.zoom_out();
                     return EventResponse::Consumed;
                 }
+                if self.recenter_button.handle_event(event) == EventResponse::Consumed {
+                    self.pan = (0, 0);
+                    return EventResponse::Consumed;
+                }
+                if let UiEvent::MouseClick { x, y, .. } = event
+                    && let Some(kind) = MapMarkerKind::ALL
+                        .into_iter()
+                        .find(|kind| self.legend_bounds[*kind as usize].contains_point(*x, *y))
+                {
+                    let enabled = !self.marker_enabled[kind as usize];
+                    self.marker_enabled[kind as usize] = enabled;
+                    self.pending_actions
+                        .push(WidgetAction::SetMinimapMarker { kind, enabled });
+                    return EventResponse::Consumed;
+                }
             }
         }
 
         EventResponse::Ignored
     }
 
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+
     fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
         // Always draw the toggle button.
         self.button.render(ctx)?;
@@ -549,6 +743,19 @@ impl Widget for MinimapWidget {
 
         self.zoom_in_button.render(ctx)?;
         self.zoom_out_button.render(ctx)?;
+        self.recenter_button.render(ctx)?;
+
+        // Legend: filled swatch = shown, outline only = hidden.
+        for kind in MapMarkerKind::ALL {
+            let b = self.legend_bounds[kind as usize];
+            let rect = sdl2::rect::Rect::new(b.x, b.y, b.width, b.height);
+            ctx.canvas.set_draw_color(marker_color(kind));
+            if self.marker_enabled[kind as usize] {
+                ctx.canvas.fill_rect(rect)?;
+            } else {
+                ctx.canvas.draw_rect(rect)?;
+            }
+        }
 
         Ok(())
     }
@@ -557,7 +764,6 @@ impl Widget for MinimapWidget {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ui::widget::MouseButton;
 
     fn click_in_bounds(bounds: Bounds) -> UiEvent {
         UiEvent::MouseClick {
@@ -800,4 +1006,79 @@ mod tests {
         assert_eq!(w.viewport_pixels[last], 190);
         assert_eq!(w.viewport_pixels[last + 1], 190);
     }
+
+    #[test]
+    fn mouse_wheel_over_panel_zooms() {
+        let mut w = MinimapWidget::new(200, 30, 14);
+        w.toggle();
+        let wheel = |delta| UiEvent::MouseWheel {
+            x: w.panel_x + 10,
+            y: w.panel_y + 10,
+            delta,
+        };
+        let (up, down) = (wheel(1), wheel(-1));
+
+        assert_eq!(w.handle_event(&up), EventResponse::Consumed);
+        assert_eq!(w.current_sample_size(), 96);
+        assert_eq!(w.handle_event(&down), EventResponse::Consumed);
+        assert_eq!(w.handle_event(&down), EventResponse::Consumed);
+        assert_eq!(w.current_sample_size(), 160);
+    }
+
+    #[test]
+    fn dragging_the_panel_pans_the_viewport() {
+        let mut w = MinimapWidget::new(200, 30, 14);
+        w.toggle();
+        let (x, y) = (w.panel_x + 10, w.panel_y + 10);
+        let step = MINIMAP_WIDGET_VIEW_SIZE as i32 / 4;
+
+        let down = UiEvent::MouseDown {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        };
+        assert_eq!(w.handle_event(&down), EventResponse::Consumed);
+        let drag = UiEvent::MouseMove {
+            x: x + step,
+            y: y + step,
+        };
+        assert_eq!(w.handle_event(&drag), EventResponse::Consumed);
+        let release = UiEvent::MouseClick {
+            x: x + step,
+            y: y + step,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        };
+        assert_eq!(w.handle_event(&release), EventResponse::Consumed);
+
+        // A quarter of the view at the default 128-tile sample is 32 tiles.
+        let xmap = vec![0u8; WORLD_SIZE * WORLD_SIZE * 4];
+        w.update_viewport(&xmap, 512, 512);
+        assert_eq!((w.last_center_x, w.last_center_y), (480, 480));
+
+        let recenter = click_in_bounds(*w.recenter_button.bounds());
+        assert_eq!(w.handle_event(&recenter), EventResponse::Consumed);
+        w.update_viewport(&xmap, 512, 512);
+        assert_eq!((w.last_center_x, w.last_center_y), (512, 512));
+    }
+
+    #[test]
+    fn legend_click_toggles_marker_and_emits_action() {
+        let mut w = MinimapWidget::new(200, 30, 14);
+        w.toggle();
+        let click = click_in_bounds(w.legend_bounds[MapMarkerKind::Bank as usize]);
+
+        assert_eq!(w.handle_event(&click), EventResponse::Consumed);
+        assert!(!w.marker_enabled[MapMarkerKind::Bank as usize]);
+        let actions = w.take_actions();
+        assert!(matches!(
+            actions.as_slice(),
+            [WidgetAction::SetMinimapMarker {
+                kind: MapMarkerKind::Bank,
+                enabled: false
+            }]
+        ));
+        assert!(w.take_actions().is_empty());
+    }
 }
@@ -1,15 +1,28 @@
+pub mod addon_panels;
 pub mod button_bar;
 pub mod chat_box;
+pub mod chat_tabs;
+pub mod combat_log_panel;
+pub mod friends_panel;
+pub mod guild_panel;
 pub mod inventory_panel;
 pub mod keybindings_panel;
 pub mod look_panel;
+pub mod mail_panel;
 pub mod minimap_widget;
 pub mod mode_button;
+pub mod npc_menu;
+pub mod profile_panel;
 pub mod quest_log_panel;
+pub mod quest_tracker;
+pub mod radial_menu;
 pub mod settings_panel;
 pub mod shop_panel;
 pub mod skill_bar;
 pub mod skill_picker_popup;
 pub mod skills_panel;
 pub mod talent_panel;
+pub mod toast_stack;
+pub mod trainer_popup;
+pub mod travel_menu;
 pub mod weapon_armor_panel;
@@ -0,0 +1,260 @@
+//! Context menu listing an NPC's interaction options.
+//!
+//! Opened by GameScene at the cursor when the server answers a right-click
+//! with `SV_NPCMENU`. Choosing an entry emits
+//! [`WidgetAction::NpcMenuChoice`]; clicking anywhere else or pressing
+//! Escape closes the menu without a choice.
+
+use mag_core::npc_menu::{NpcMenuOption, options_in_mask};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget, WidgetAction};
+use crate::ui::widgets::title_bar::clamp_to_viewport;
+
+/// Font index used for menu labels.
+const MENU_FONT: usize = 1;
+
+/// Menu width in logical pixels.
+const MENU_W: u32 = 70;
+
+/// Height of a single entry.
+const ROW_H: i32 = 14;
+
+/// Inner horizontal padding.
+const H_INSET: i32 = 6;
+
+/// Menu background.
+const MENU_BG: Color = Color::RGBA(10, 10, 30, 220);
+
+/// Menu border.
+const MENU_BORDER: Color = Color::RGBA(120, 120, 140, 220);
+
+/// Highlight behind the hovered entry.
+const HOVER_BG: Color = Color::RGBA(80, 80, 30, 200);
+
+/// The NPC interaction context menu.
+pub struct NpcMenu {
+    bounds: Bounds,
+    visible: bool,
+    target: u16,
+    options: Vec<NpcMenuOption>,
+    hovered: Option<usize>,
+    pending_actions: Vec<WidgetAction>,
+}
+
+impl NpcMenu {
+    /// Creates a hidden menu.
+    ///
+    /// # Returns
+    ///
+    /// * A new, closed `NpcMenu`.
+    pub fn new() -> Self {
+        Self {
+            bounds: Bounds::new(0, 0, MENU_W, 0),
+            visible: false,
+            target: 0,
+            options: Vec::new(),
+            hovered: None,
+            pending_actions: Vec::new(),
+        }
+    }
+
+    /// Opens the menu at `(x, y)`, clamped to the viewport.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Preferred left edge (usually the click position).
+    /// * `y` - Preferred top edge.
+    /// * `target` - NPC character number the options belong to.
+    /// * `mask` - `SV_NPCMENU` option bitmask.
+    pub fn open(&mut self, x: i32, y: i32, target: u16, mask: u8) {
+        self.options = options_in_mask(mask).collect();
+        if self.options.is_empty() {
+            self.close();
+            return;
+        }
+        let height = (self.options.len() as i32 * ROW_H + 2) as u32;
+        let (cx, cy) = clamp_to_viewport(x, y, MENU_W, height);
+        self.bounds = Bounds::new(cx, cy, MENU_W, height);
+        self.target = target;
+        self.hovered = None;
+        self.visible = true;
+    }
+
+    /// Closes the menu without choosing anything.
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.hovered = None;
+    }
+
+    /// Returns `true` while the menu is open.
+    ///
+    /// # Returns
+    ///
+    /// * The visibility flag.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Returns the entry index under `(x, y)`, if any.
+    fn row_at(&self, x: i32, y: i32) -> Option<usize> {
+        if !self.bounds.contains_point(x, y) {
+            return None;
+        }
+        let idx = ((y - self.bounds.y - 1) / ROW_H).max(0) as usize;
+        (idx < self.options.len()).then_some(idx)
+    }
+}
+
+impl Default for NpcMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for NpcMenu {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+        match event {
+            UiEvent::MouseMove { x, y } => {
+                self.hovered = self.row_at(*x, *y);
+                EventResponse::Ignored
+            }
+            UiEvent::MouseDown { x, y, .. } if self.bounds.contains_point(*x, *y) => {
+                EventResponse::Consumed
+            }
+            UiEvent::MouseClick { x, y, button, .. } => {
+                if let (MouseButton::Left, Some(idx)) = (button, self.row_at(*x, *y)) {
+                    self.pending_actions.push(WidgetAction::NpcMenuChoice {
+                        target: self.target,
+                        option: self.options[idx],
+                    });
+                }
+                // Any click closes the menu and is swallowed so it does not
+                // also move the character.
+                self.close();
+                EventResponse::Consumed
+            }
+            UiEvent::KeyDown {
+                keycode: Keycode::Escape,
+                ..
+            } => {
+                self.close();
+                EventResponse::Consumed
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        let rect = sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(MENU_BG);
+        ctx.canvas.fill_rect(rect)?;
+
+        for (idx, option) in self.options.iter().enumerate() {
+            let top = self.bounds.y + 1 + idx as i32 * ROW_H;
+            if self.hovered == Some(idx) {
+                ctx.canvas.set_draw_color(HOVER_BG);
+                ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+                    self.bounds.x + 1,
+                    top,
+                    self.bounds.width - 2,
+                    ROW_H as u32,
+                ))?;
+            }
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                MENU_FONT,
+                option.label(),
+                self.bounds.x + H_INSET,
+                top + 2,
+                font_cache::TextStyle::PLAIN,
+            )?;
+        }
+
+        ctx.canvas.set_draw_color(MENU_BORDER);
+        ctx.canvas.draw_rect(rect)?;
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widget::KeyModifiers;
+
+    fn click(x: i32, y: i32) -> UiEvent {
+        UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: KeyModifiers::default(),
+        }
+    }
+
+    #[test]
+    fn clicking_an_entry_emits_choice_and_closes() {
+        let mut menu = NpcMenu::new();
+        let mask = NpcMenuOption::Talk.bit() | NpcMenuOption::Trade.bit();
+        menu.open(100, 100, 42, mask);
+        assert!(menu.is_visible());
+
+        let y = menu.bounds().y + 1 + ROW_H + 2;
+        assert_eq!(menu.handle_event(&click(110, y)), EventResponse::Consumed);
+        assert!(!menu.is_visible());
+        assert!(matches!(
+            menu.take_actions().as_slice(),
+            [WidgetAction::NpcMenuChoice {
+                target: 42,
+                option: NpcMenuOption::Trade
+            }]
+        ));
+    }
+
+    #[test]
+    fn clicking_outside_closes_without_choice() {
+        let mut menu = NpcMenu::new();
+        menu.open(100, 100, 42, NpcMenuOption::Look.bit());
+        assert_eq!(menu.handle_event(&click(5, 5)), EventResponse::Consumed);
+        assert!(!menu.is_visible());
+        assert!(menu.take_actions().is_empty());
+    }
+
+    #[test]
+    fn open_clamps_to_viewport() {
+        let mut menu = NpcMenu::new();
+        menu.open(10_000, 10_000, 1, NpcMenuOption::Look.bit());
+        let b = *menu.bounds();
+        assert!(b.x + b.width as i32 <= crate::constants::TARGET_WIDTH_INT as i32);
+        assert!(b.y + b.height as i32 <= crate::constants::TARGET_HEIGHT_INT as i32);
+    }
+}
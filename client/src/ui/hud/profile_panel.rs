@@ -0,0 +1,594 @@
+//! Character profile editor (name color, bio and title).
+//!
+//! The panel lets the player pick a [`NameColor`] from a swatch row and type
+//! a short bio. Clicking "Save" emits a [`WidgetAction::SaveProfile`] which
+//! GameScene uploads to the server as `CmdSetProfile` chunks. The server
+//! validates the bio (length, ASCII, must mention the character name, no
+//! banned words) and reports the outcome in the chat log, so the panel does
+//! only a cheap local length check before sending.
+//!
+//! The "<" / ">" buttons cycle through the player's earned titles and emit a
+//! [`WidgetAction::SetTitle`] immediately; the server echoes the accepted
+//! selection back in `SV_SETCHARTITLES`.
+
+use std::time::Duration;
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use mag_core::profile::{MAX_BIO_LEN, MIN_BIO_LEN, NameColor};
+use mag_core::titles::{earned_titles, title_by_id};
+
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::style::{Background, Border, name_color_tint};
+use crate::ui::widget::{
+    Bounds, EventResponse, HudPanel, MouseButton, UiEvent, Widget, WidgetAction,
+};
+use crate::ui::widgets::button::RectButton;
+use crate::ui::widgets::text_input::TextInput;
+use crate::ui::widgets::title_bar::{TITLE_BAR_H, TitleBar, clamp_to_viewport};
+
+/// Bitmap font index used for panel text.
+const FONT: usize = 1;
+
+/// Horizontal inset from panel edges.
+const H_INSET: i32 = 8;
+
+/// Vertical spacing between text lines.
+const LINE_H: i32 = 14;
+
+/// Size of a single name-color swatch.
+const SWATCH_W: u32 = 28;
+const SWATCH_H: u32 = 14;
+
+/// Horizontal gap between swatches.
+const SWATCH_GAP: i32 = 6;
+
+/// Height of the bio text input.
+const INPUT_H: u32 = 16;
+
+/// Width/height of the "Save" button.
+const SAVE_BTN_W: u32 = 80;
+const SAVE_BTN_H: u32 = 16;
+
+/// Width of the "<" / ">" title cycle buttons.
+const TITLE_BTN_W: u32 = 16;
+
+/// Swatch fill used for [`NameColor::Default`], which has no tint.
+const DEFAULT_SWATCH: Color = Color::RGBA(90, 90, 100, 255);
+
+/// Border drawn around the selected swatch.
+const SELECTED_BORDER: Color = Color::RGBA(255, 255, 255, 255);
+
+/// The profile editor HUD panel.
+pub struct ProfilePanel {
+    bounds: Bounds,
+    bg_color: Color,
+    border_color: Color,
+    visible: bool,
+    pending_actions: Vec<WidgetAction>,
+    title_bar: TitleBar,
+    /// Currently selected name color.
+    name_color: NameColor,
+    /// Bio text input.
+    bio_input: TextInput,
+    /// "Save" button at the bottom of the panel.
+    save_button: RectButton,
+    /// Local validation message shown above the save button.
+    status: Option<String>,
+    /// Earned-title bitmask last received from the server.
+    titles_earned: u32,
+    /// Title id currently displayed (`0` = none).
+    title_selected: u8,
+    /// Cycles to the previous earned title.
+    title_prev: RectButton,
+    /// Cycles to the next earned title.
+    title_next: RectButton,
+}
+
+impl ProfilePanel {
+    /// Creates a new (hidden) profile panel.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds`   - Screen-space bounds of the panel.
+    /// * `bg_color` - Semi-transparent background color.
+    ///
+    /// # Returns
+    ///
+    /// * A new `ProfilePanel`, initially hidden, with the default color and
+    ///   an empty bio.
+    pub fn new(bounds: Bounds, bg_color: Color) -> Self {
+        let title_bar = TitleBar::new("Profile", bounds.x, bounds.y, bounds.width);
+        let bio_input = TextInput::new(
+            Bounds::new(
+                bounds.x + H_INSET,
+                Self::bio_input_y(bounds.y),
+                bounds.width.saturating_sub(2 * H_INSET as u32),
+                INPUT_H,
+            ),
+            "Describe your character...",
+            FONT,
+            MAX_BIO_LEN,
+            false,
+            Color::RGBA(120, 120, 140, 200),
+            Color::RGBA(200, 200, 120, 255),
+        );
+        let bottom_y = bounds.y + bounds.height as i32 - 6 - SAVE_BTN_H as i32;
+        let save_button = RectButton::new(
+            Bounds::new(
+                bounds.x + bounds.width as i32 - H_INSET - SAVE_BTN_W as i32,
+                bottom_y,
+                SAVE_BTN_W,
+                SAVE_BTN_H,
+            ),
+            Background::SolidColor(Color::RGBA(30, 60, 30, 220)),
+        )
+        .with_label("Save", FONT)
+        .with_border(Border {
+            color: Color::RGBA(100, 160, 100, 220),
+            width: 1,
+        });
+        let title_button = |x: i32, label: &str| {
+            RectButton::new(
+                Bounds::new(x, bottom_y, TITLE_BTN_W, SAVE_BTN_H),
+                Background::SolidColor(Color::RGBA(40, 40, 60, 220)),
+            )
+            .with_label(label, FONT)
+            .with_border(Border {
+                color: Color::RGBA(120, 120, 140, 200),
+                width: 1,
+            })
+        };
+        let title_prev = title_button(bounds.x + H_INSET, "<");
+        let title_next = title_button(bounds.x + H_INSET + TITLE_BTN_W as i32 + 4, ">");
+        Self {
+            bounds,
+            bg_color,
+            border_color: Color::RGBA(120, 120, 140, 200),
+            visible: false,
+            pending_actions: Vec::new(),
+            title_bar,
+            name_color: NameColor::Default,
+            bio_input,
+            save_button,
+            status: None,
+            titles_earned: 0,
+            title_selected: 0,
+            title_prev,
+            title_next,
+        }
+    }
+
+    /// Toggles the panel's visibility. Hiding the panel drops input focus.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if !self.visible {
+            self.bio_input.set_focused(false);
+        }
+    }
+
+    /// Returns `true` when the panel is currently visible.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the panel is shown, otherwise `false`.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Returns `true` while the bio input has keyboard focus.
+    ///
+    /// GameScene uses this to suppress key bindings while the player types.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the panel is visible and the bio input is focused.
+    pub fn is_text_focused(&self) -> bool {
+        self.visible && self.bio_input.is_focused()
+    }
+
+    /// Returns the currently selected name color.
+    ///
+    /// # Returns
+    ///
+    /// * The selected [`NameColor`].
+    pub fn name_color(&self) -> NameColor {
+        self.name_color
+    }
+
+    /// Pre-fills the editor with an existing profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_color` - Color to select.
+    /// * `bio` - Bio text to place in the input.
+    pub fn set_profile(&mut self, name_color: NameColor, bio: &str) {
+        self.name_color = name_color;
+        self.bio_input.set_value(bio);
+        self.status = None;
+    }
+
+    /// Updates the title picker from the server's title snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `earned` - Earned-title bitmask.
+    /// * `selected` - Currently displayed title id (`0` = none).
+    pub fn sync_titles(&mut self, earned: u32, selected: u8) {
+        self.titles_earned = earned;
+        self.title_selected = selected;
+    }
+
+    /// Selects the previous (`step < 0`) or next earned title, wrapping
+    /// through "no title", and queues a [`WidgetAction::SetTitle`].
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - Direction to move through the earned titles.
+    fn cycle_title(&mut self, step: i32) {
+        let options: Vec<u8> = std::iter::once(0)
+            .chain(earned_titles(self.titles_earned).map(|t| t.id))
+            .collect();
+        if options.len() < 2 {
+            return;
+        }
+        let current = options
+            .iter()
+            .position(|&id| id == self.title_selected)
+            .unwrap_or(0) as i32;
+        let next = options[(current + step).rem_euclid(options.len() as i32) as usize];
+        self.title_selected = next;
+        self.pending_actions
+            .push(WidgetAction::SetTitle { title_id: next });
+    }
+
+    /// Y coordinate (top edge) of the swatch row for a panel at `panel_y`.
+    fn swatch_y(panel_y: i32) -> i32 {
+        panel_y + TITLE_BAR_H + 6 + LINE_H
+    }
+
+    /// Y coordinate (top edge) of the bio input for a panel at `panel_y`.
+    fn bio_input_y(panel_y: i32) -> i32 {
+        Self::swatch_y(panel_y) + SWATCH_H as i32 + 8 + LINE_H
+    }
+
+    /// Screen-space bounds of the swatch for `NameColor::ALL[idx]`.
+    fn swatch_bounds(&self, idx: usize) -> Bounds {
+        Bounds::new(
+            self.bounds.x + H_INSET + idx as i32 * (SWATCH_W as i32 + SWATCH_GAP),
+            Self::swatch_y(self.bounds.y),
+            SWATCH_W,
+            SWATCH_H,
+        )
+    }
+
+    /// Validates the bio locally and queues a save action.
+    fn submit(&mut self) {
+        let bio = self.bio_input.value().trim().to_owned();
+        if !bio.is_empty() && bio.len() < MIN_BIO_LEN {
+            self.status = Some(format!("Bio must be at least {} characters.", MIN_BIO_LEN));
+            return;
+        }
+        self.status = None;
+        self.bio_input.set_focused(false);
+        self.pending_actions.push(WidgetAction::SaveProfile {
+            name_color: self.name_color.as_u8(),
+            bio,
+        });
+    }
+}
+
+impl Widget for ProfilePanel {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        let dx = x - self.bounds.x;
+        let dy = y - self.bounds.y;
+        self.bounds.x = x;
+        self.bounds.y = y;
+        self.title_bar.set_bar_position(x, y);
+        let ib = *self.bio_input.bounds();
+        self.bio_input.set_position(ib.x + dx, ib.y + dy);
+        for button in [
+            &mut self.save_button,
+            &mut self.title_prev,
+            &mut self.title_next,
+        ] {
+            let b = *button.bounds();
+            button.set_position(b.x + dx, b.y + dy);
+        }
+    }
+
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+
+        let (tb_resp, drag_pos) = self.title_bar.handle_event(event);
+        if let Some((nx, ny)) = drag_pos {
+            let (cx, cy) = clamp_to_viewport(nx, ny, self.bounds.width, self.bounds.height);
+            self.set_position(cx, cy);
+            return EventResponse::Consumed;
+        }
+        if self.title_bar.was_close_requested() {
+            self.visible = false;
+            self.bio_input.set_focused(false);
+            self.pending_actions
+                .push(WidgetAction::TogglePanel(HudPanel::Profile));
+            return EventResponse::Consumed;
+        }
+        if tb_resp == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        if self.bio_input.handle_event(event) == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        if self.bio_input.is_focused()
+            && let UiEvent::KeyDown { keycode, .. } = event
+            && matches!(
+                *keycode,
+                sdl2::keyboard::Keycode::Return | sdl2::keyboard::Keycode::KpEnter
+            )
+        {
+            self.submit();
+            return EventResponse::Consumed;
+        }
+
+        if self.save_button.handle_event(event) == EventResponse::Consumed {
+            self.submit();
+            return EventResponse::Consumed;
+        }
+
+        if self.title_prev.handle_event(event) == EventResponse::Consumed {
+            self.cycle_title(-1);
+            return EventResponse::Consumed;
+        }
+        if self.title_next.handle_event(event) == EventResponse::Consumed {
+            self.cycle_title(1);
+            return EventResponse::Consumed;
+        }
+
+        if let UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            ..
+        } = event
+            && let Some(idx) =
+                (0..NameColor::ALL.len()).find(|&i| self.swatch_bounds(i).contains_point(*x, *y))
+        {
+            self.name_color = NameColor::ALL[idx];
+            return EventResponse::Consumed;
+        }
+
+        // Eat clicks/wheel inside our bounds to prevent click-through.
+        match event {
+            UiEvent::MouseClick { x, y, .. }
+            | UiEvent::MouseDown { x, y, .. }
+            | UiEvent::MouseWheel { x, y, .. } => {
+                if self.bounds.contains_point(*x, *y) {
+                    EventResponse::Consumed
+                } else {
+                    EventResponse::Ignored
+                }
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn update(&mut self, dt: Duration) {
+        self.bio_input.update(dt);
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let rect = sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(self.bg_color);
+        ctx.canvas.fill_rect(rect)?;
+
+        ctx.canvas.set_draw_color(self.border_color);
+        ctx.canvas.draw_rect(rect)?;
+
+        self.title_bar.render(ctx)?;
+
+        let text_x = self.bounds.x + H_INSET;
+
+        // Name color swatches.
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            FONT,
+            &format!("Name color: {}", self.name_color.label()),
+            text_x,
+            self.bounds.y + TITLE_BAR_H + 6,
+            name_color_tint(self.name_color)
+                .map(font_cache::TextStyle::tinted)
+                .unwrap_or(font_cache::TextStyle::PLAIN),
+        )?;
+        for (idx, color) in NameColor::ALL.iter().enumerate() {
+            let b = self.swatch_bounds(idx);
+            let swatch = sdl2::rect::Rect::new(b.x, b.y, b.width, b.height);
+            ctx.canvas
+                .set_draw_color(name_color_tint(*color).unwrap_or(DEFAULT_SWATCH));
+            ctx.canvas.fill_rect(swatch)?;
+            if *color == self.name_color {
+                ctx.canvas.set_draw_color(SELECTED_BORDER);
+                ctx.canvas.draw_rect(swatch)?;
+            }
+        }
+
+        // Bio input.
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            FONT,
+            "Bio (must mention your name):",
+            text_x,
+            Self::bio_input_y(self.bounds.y) - LINE_H,
+            font_cache::TextStyle::PLAIN,
+        )?;
+        self.bio_input.render(ctx)?;
+
+        let counter_y = self.bio_input.bounds().y + INPUT_H as i32 + 4;
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            FONT,
+            &format!("{}/{}", self.bio_input.value().len(), MAX_BIO_LEN),
+            text_x,
+            counter_y,
+            font_cache::TextStyle::PLAIN,
+        )?;
+
+        if let Some(status) = &self.status {
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                FONT,
+                status,
+                text_x,
+                counter_y + LINE_H,
+                font_cache::TextStyle::tinted(Color::RGB(230, 90, 90)),
+            )?;
+        }
+
+        // Title picker.
+        self.title_prev.render(ctx)?;
+        self.title_next.render(ctx)?;
+        let title_label = match title_by_id(self.title_selected) {
+            Some(t) => format!("Title: {}", t.name),
+            None if self.titles_earned == 0 => "Title: (none earned)".to_owned(),
+            None => "Title: none".to_owned(),
+        };
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            FONT,
+            &title_label,
+            self.title_next.bounds().x + TITLE_BTN_W as i32 + 6,
+            self.title_next.bounds().y + 4,
+            font_cache::TextStyle::PLAIN,
+        )?;
+
+        self.save_button.render(ctx)?;
+
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panel() -> ProfilePanel {
+        let mut p = ProfilePanel::new(Bounds::new(0, 0, 300, 250), Color::RGBA(0, 0, 0, 200));
+        p.toggle();
+        p
+    }
+
+    fn left_click(x: i32, y: i32) -> UiEvent {
+        UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        }
+    }
+
+    #[test]
+    fn toggle_flips_visibility_and_drops_focus() {
+        let mut p = panel();
+        assert!(p.is_visible());
+        p.bio_input.set_focused(true);
+        assert!(p.is_text_focused());
+        p.toggle();
+        assert!(!p.is_visible());
+        assert!(!p.is_text_focused());
+    }
+
+    #[test]
+    fn clicking_swatch_selects_color() {
+        let mut p = panel();
+        let b = p.swatch_bounds(4);
+        let resp = p.handle_event(&left_click(b.x + 1, b.y + 1));
+        assert_eq!(resp, EventResponse::Consumed);
+        assert_eq!(p.name_color(), NameColor::ALL[4]);
+    }
+
+    #[test]
+    fn save_emits_profile_action() {
+        let mut p = panel();
+        p.set_profile(NameColor::Gold, "  Tester guards the gate.  ");
+        let b = *p.save_button.bounds();
+        p.handle_event(&left_click(b.x + 1, b.y + 1));
+        let actions = p.take_actions();
+        assert!(matches!(
+            actions.as_slice(),
+            [WidgetAction::SaveProfile { name_color, bio }]
+                if *name_color == NameColor::Gold.as_u8() && bio == "Tester guards the gate."
+        ));
+    }
+
+    #[test]
+    fn short_bio_is_rejected_locally() {
+        let mut p = panel();
+        p.set_profile(NameColor::Default, "short");
+        p.submit();
+        assert!(p.take_actions().is_empty());
+        assert!(p.status.is_some());
+    }
+
+    #[test]
+    fn title_buttons_cycle_through_earned_titles() {
+        let mut p = panel();
+        p.sync_titles((1 << 1) | (1 << 16), 0);
+        let next = *p.title_next.bounds();
+        p.handle_event(&left_click(next.x + 1, next.y + 1));
+        p.handle_event(&left_click(next.x + 1, next.y + 1));
+        let prev = *p.title_prev.bounds();
+        p.handle_event(&left_click(prev.x + 1, prev.y + 1));
+        p.handle_event(&left_click(prev.x + 1, prev.y + 1));
+        p.handle_event(&left_click(prev.x + 1, prev.y + 1));
+        let ids: Vec<u8> = p
+            .take_actions()
+            .into_iter()
+            .filter_map(|a| match a {
+                WidgetAction::SetTitle { title_id } => Some(title_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, vec![1, 16, 1, 0, 16]);
+    }
+
+    #[test]
+    fn title_buttons_do_nothing_without_earned_titles() {
+        let mut p = panel();
+        let next = *p.title_next.bounds();
+        p.handle_event(&left_click(next.x + 1, next.y + 1));
+        assert!(p.take_actions().is_empty());
+    }
+}
@@ -287,6 +287,10 @@ impl Widget for QuestLogPanel {
         self.title_bar.set_bar_position(x, y);
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
         if !self.visible {
             return EventResponse::Ignored;
@@ -0,0 +1,351 @@
+//! Collapsible on-screen tracker listing the player's open quest objectives.
+//!
+//! GameScene builds a [`QuestTrackerEntry`] list each frame from the same
+//! quest catalog / completion data that drives the quest log panel and
+//! feeds it via [`QuestTracker::update_data`]. Clicking the header collapses
+//! or expands the tracker ([`WidgetAction::SetQuestTrackerCollapsed`], which
+//! the scene persists per character); clicking an objective focuses that
+//! quest ([`WidgetAction::SetActiveQuest`]) and opens the full quest log.
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget, WidgetAction};
+
+/// Font index used for tracker text (yellow bitmap font).
+const TRACKER_FONT: usize = 1;
+
+/// Tracker width in logical pixels.
+pub const TRACKER_W: u32 = 190;
+
+/// Height of the clickable header row.
+const HEADER_H: i32 = 14;
+
+/// Height of a single objective row.
+const ROW_H: i32 = 12;
+
+/// Inner horizontal padding.
+const H_INSET: i32 = 4;
+
+/// Maximum number of objectives listed; the rest are summarised in the
+/// header count.
+const MAX_TRACKED_ROWS: usize = 5;
+
+/// Background fill.
+const TRACKER_BG: Color = Color::RGBA(10, 10, 30, 150);
+
+/// Highlight behind the focused quest's row.
+const ACTIVE_HIGHLIGHT: Color = Color::RGBA(80, 80, 30, 200);
+
+/// Tint for the progress counter.
+const PROGRESS_COLOR: Color = Color::RGB(170, 220, 170);
+
+/// One objective line in the tracker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuestTrackerEntry {
+    /// NPC template ID of the quest giver (wire id for `SetActiveQuest`).
+    pub template_id: u16,
+    /// Current objective text.
+    pub objective: String,
+    /// Turn-ins completed so far.
+    pub completed: u8,
+    /// Turn-ins required, or `0` for repeatable quests (no counter shown).
+    pub stages: u8,
+}
+
+impl QuestTrackerEntry {
+    /// Formats the progress counter shown right-aligned on the row.
+    ///
+    /// # Returns
+    ///
+    /// * `Some("done/total")` for staged quests, `None` for repeatable ones.
+    fn progress_label(&self) -> Option<String> {
+        (self.stages > 0).then(|| format!("{}/{}", self.completed.min(self.stages), self.stages))
+    }
+}
+
+/// The quest tracker overlay.
+pub struct QuestTracker {
+    bounds: Bounds,
+    collapsed: bool,
+    entries: Vec<QuestTrackerEntry>,
+    active_template_id: u16,
+    pending_actions: Vec<WidgetAction>,
+}
+
+impl QuestTracker {
+    /// Creates an empty, expanded tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Left edge in logical pixels.
+    /// * `y` - Top edge in logical pixels.
+    ///
+    /// # Returns
+    ///
+    /// * A new `QuestTracker` with no entries.
+    pub fn new(x: i32, y: i32) -> Self {
+        Self {
+            bounds: Bounds::new(x, y, TRACKER_W, HEADER_H as u32),
+            collapsed: false,
+            entries: Vec::new(),
+            active_template_id: 0,
+            pending_actions: Vec::new(),
+        }
+    }
+
+    /// Returns `true` when only the header is shown.
+    ///
+    /// # Returns
+    ///
+    /// * The collapsed flag.
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Collapses or expands the tracker without emitting an action (used
+    /// when applying saved settings).
+    ///
+    /// # Arguments
+    ///
+    /// * `collapsed` - New collapsed state.
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        self.collapsed = collapsed;
+        self.recompute_height();
+    }
+
+    /// Returns `true` when there is at least one objective to show.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the tracker is drawn at all.
+    pub fn is_visible(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    /// Replaces the tracked objectives.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Open objectives, in quest log order.
+    /// * `active_template_id` - Focused quest (`0` = none).
+    pub fn update_data(&mut self, entries: Vec<QuestTrackerEntry>, active_template_id: u16) {
+        self.entries = entries;
+        self.active_template_id = active_template_id;
+        self.recompute_height();
+    }
+
+    /// Number of objective rows currently drawn.
+    fn visible_rows(&self) -> usize {
+        if self.collapsed {
+            0
+        } else {
+            self.entries.len().min(MAX_TRACKED_ROWS)
+        }
+    }
+
+    /// Resizes the bounds to fit the header plus visible rows.
+    fn recompute_height(&mut self) {
+        self.bounds.height = (HEADER_H + self.visible_rows() as i32 * ROW_H + 2) as u32;
+    }
+
+    /// Y coordinate (top edge) of objective row `idx`.
+    fn row_y(&self, idx: usize) -> i32 {
+        self.bounds.y + HEADER_H + idx as i32 * ROW_H
+    }
+}
+
+impl Widget for QuestTracker {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.is_visible() {
+            return EventResponse::Ignored;
+        }
+        let UiEvent::MouseClick { x, y, button, .. } = event else {
+            return EventResponse::Ignored;
+        };
+        if !self.bounds.contains_point(*x, *y) {
+            return EventResponse::Ignored;
+        }
+        if *button != MouseButton::Left {
+            return EventResponse::Consumed;
+        }
+
+        if *y < self.bounds.y + HEADER_H {
+            self.set_collapsed(!self.collapsed);
+            self.pending_actions
+                .push(WidgetAction::SetQuestTrackerCollapsed(self.collapsed));
+            return EventResponse::Consumed;
+        }
+
+        for idx in 0..self.visible_rows() {
+            let top = self.row_y(idx);
+            if *y >= top && *y < top + ROW_H {
+                self.pending_actions.push(WidgetAction::SetActiveQuest {
+                    npc_template_id: self.entries[idx].template_id,
+                });
+                break;
+            }
+        }
+        EventResponse::Consumed
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.is_visible() {
+            return Ok(());
+        }
+
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(TRACKER_BG);
+        ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        ))?;
+
+        let marker = if self.collapsed { "[+]" } else { "[-]" };
+        let header = format!("Quests ({})", self.entries.len());
+        let text_x = self.bounds.x + H_INSET;
+        let right = self.bounds.x + self.bounds.width as i32 - H_INSET;
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            TRACKER_FONT,
+            &header,
+            text_x,
+            self.bounds.y + 2,
+            font_cache::TextStyle::drop_shadow(),
+        )?;
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            TRACKER_FONT,
+            marker,
+            right - font_cache::text_width(marker) as i32,
+            self.bounds.y + 2,
+            font_cache::TextStyle::drop_shadow(),
+        )?;
+
+        for idx in 0..self.visible_rows() {
+            let entry = &self.entries[idx];
+            let top = self.row_y(idx);
+
+            if entry.template_id == self.active_template_id && self.active_template_id != 0 {
+                ctx.canvas.set_draw_color(ACTIVE_HIGHLIGHT);
+                ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+                    self.bounds.x + 1,
+                    top,
+                    self.bounds.width.saturating_sub(2),
+                    ROW_H as u32,
+                ))?;
+            }
+
+            let mut objective_w = right - text_x;
+            if let Some(progress) = entry.progress_label() {
+                let w = font_cache::text_width(&progress) as i32;
+                objective_w -= w + H_INSET;
+                font_cache::draw_text(
+                    ctx.canvas,
+                    ctx.gfx,
+                    TRACKER_FONT,
+                    &progress,
+                    right - w,
+                    top + 1,
+                    font_cache::TextStyle::tinted(PROGRESS_COLOR),
+                )?;
+            }
+            let objective = font_cache::fit_text_bitmap(&entry.objective, objective_w);
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                TRACKER_FONT,
+                &objective,
+                text_x,
+                top + 1,
+                font_cache::TextStyle::PLAIN,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widget::KeyModifiers;
+
+    fn entry(template_id: u16, completed: u8, stages: u8) -> QuestTrackerEntry {
+        QuestTrackerEntry {
+            template_id,
+            objective: format!("Objective {}", template_id),
+            completed,
+            stages,
+        }
+    }
+
+    fn click(x: i32, y: i32) -> UiEvent {
+        UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: KeyModifiers::default(),
+        }
+    }
+
+    #[test]
+    fn header_click_toggles_collapse_and_reports_it() {
+        let mut t = QuestTracker::new(0, 0);
+        t.update_data(vec![entry(11, 0, 1), entry(22, 1, 2)], 0);
+        let expanded_h = t.bounds().height;
+
+        assert_eq!(t.handle_event(&click(5, 2)), EventResponse::Consumed);
+        assert!(t.is_collapsed());
+        assert!(t.bounds().height < expanded_h);
+        assert!(matches!(
+            t.take_actions().as_slice(),
+            [WidgetAction::SetQuestTrackerCollapsed(true)]
+        ));
+    }
+
+    #[test]
+    fn row_click_focuses_quest() {
+        let mut t = QuestTracker::new(0, 0);
+        t.update_data(vec![entry(11, 0, 1), entry(22, 1, 2)], 0);
+        let y = t.row_y(1) + 1;
+        assert_eq!(t.handle_event(&click(5, y)), EventResponse::Consumed);
+        assert!(matches!(
+            t.take_actions().as_slice(),
+            [WidgetAction::SetActiveQuest {
+                npc_template_id: 22
+            }]
+        ));
+    }
+
+    #[test]
+    fn empty_tracker_ignores_clicks() {
+        let mut t = QuestTracker::new(0, 0);
+        assert_eq!(t.handle_event(&click(5, 2)), EventResponse::Ignored);
+    }
+
+    #[test]
+    fn progress_label_hidden_for_repeatable_quests() {
+        assert_eq!(entry(1, 1, 2).progress_label().as_deref(), Some("1/2"));
+        assert_eq!(entry(1, 1, 0).progress_label(), None);
+    }
+}
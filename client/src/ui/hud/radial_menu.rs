@@ -0,0 +1,272 @@
+//! Controller radial menu for backpack items.
+//!
+//! Opened with R3 while no skill-bar slot is highlighted. Up to
+//! [`RADIAL_SLOTS`] occupied backpack slots are laid out clockwise from the
+//! top around the screen centre; the right stick picks a sector and A (or R3
+//! again) uses the highlighted item exactly like a left click in the
+//! inventory panel. B closes the menu without using anything.
+
+use std::f32::consts::TAU;
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, UiEvent, Widget};
+
+/// Maximum number of items shown on the wheel.
+pub const RADIAL_SLOTS: usize = 8;
+
+/// Stick deflection below which no sector is highlighted.
+const STICK_DEADZONE: f32 = 12_000.0;
+
+/// Distance from the wheel centre to each item cell centre.
+const RING_RADIUS: i32 = 70;
+
+/// Size of each item cell in pixels.
+const CELL: u32 = 36;
+
+/// Backdrop behind the wheel.
+const BACKDROP: Color = Color::RGBA(10, 10, 25, 190);
+
+/// Border around an unhighlighted cell.
+const CELL_BORDER: Color = Color::RGBA(80, 80, 100, 200);
+
+/// Border around the highlighted cell.
+const HIGHLIGHT: Color = Color::RGBA(255, 200, 50, 230);
+
+/// Maps a right-stick deflection to one of `sectors` equal wedges.
+///
+/// Sector 0 is centred straight up and indices increase clockwise.
+///
+/// # Arguments
+///
+/// * `x` - Raw stick X axis (positive = right).
+/// * `y` - Raw stick Y axis (positive = down).
+/// * `sectors` - Number of wedges on the wheel.
+///
+/// # Returns
+///
+/// * The selected sector, or `None` inside the deadzone or with no sectors.
+pub fn sector_for_stick(x: i16, y: i16, sectors: usize) -> Option<usize> {
+    let (fx, fy) = (f32::from(x), f32::from(y));
+    if sectors == 0 || fx.hypot(fy) < STICK_DEADZONE {
+        return None;
+    }
+    let angle = fx.atan2(-fy).rem_euclid(TAU);
+    let width = TAU / sectors as f32;
+    Some(((angle + width / 2.0) / width) as usize % sectors)
+}
+
+/// One item on the wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RadialEntry {
+    /// Absolute backpack slot (0..39).
+    slot: usize,
+    /// Item sprite ID.
+    sprite: i32,
+}
+
+/// Radial backpack menu driven by the right stick.
+pub struct RadialMenu {
+    bounds: Bounds,
+    visible: bool,
+    entries: Vec<RadialEntry>,
+    highlighted: Option<usize>,
+}
+
+impl RadialMenu {
+    /// Creates a hidden radial menu centred on `(cx, cy)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cx` - Screen x of the wheel centre.
+    /// * `cy` - Screen y of the wheel centre.
+    ///
+    /// # Returns
+    ///
+    /// * A new, hidden `RadialMenu`.
+    pub fn new(cx: i32, cy: i32) -> Self {
+        let extent = (RING_RADIUS * 2) as u32 + CELL + 12;
+        Self {
+            bounds: Bounds::new(
+                cx - extent as i32 / 2,
+                cy - extent as i32 / 2,
+                extent,
+                extent,
+            ),
+            visible: false,
+            entries: Vec::new(),
+            highlighted: None,
+        }
+    }
+
+    /// Opens the wheel with the first occupied backpack slots.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - Backpack item sprites, `0` for empty slots.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the wheel opened, `false` when the backpack is empty.
+    pub fn open(&mut self, items: &[i32]) -> bool {
+        self.entries = items
+            .iter()
+            .enumerate()
+            .filter(|(_, sprite)| **sprite > 0)
+            .take(RADIAL_SLOTS)
+            .map(|(slot, sprite)| RadialEntry {
+                slot,
+                sprite: *sprite,
+            })
+            .collect();
+        self.highlighted = None;
+        self.visible = !self.entries.is_empty();
+        self.visible
+    }
+
+    /// Hides the wheel.
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.highlighted = None;
+    }
+
+    /// Returns `true` while the wheel is shown.
+    ///
+    /// # Returns
+    ///
+    /// * Visibility flag.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Updates the highlighted sector from the right stick.
+    ///
+    /// Returning the stick to centre keeps the last highlight so a release
+    /// before pressing A does not lose the selection.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Raw right-stick X axis.
+    /// * `y` - Raw right-stick Y axis.
+    pub fn aim(&mut self, x: i16, y: i16) {
+        if let Some(sector) = sector_for_stick(x, y, self.entries.len()) {
+            self.highlighted = Some(sector);
+        }
+    }
+
+    /// Backpack slot of the highlighted item.
+    ///
+    /// # Returns
+    ///
+    /// * The absolute backpack slot, or `None` if nothing is highlighted.
+    pub fn highlighted_slot(&self) -> Option<usize> {
+        self.highlighted
+            .and_then(|i| self.entries.get(i))
+            .map(|e| e.slot)
+    }
+
+    /// Top-left corner of the cell for wheel position `idx`.
+    fn cell_origin(&self, idx: usize) -> (i32, i32) {
+        let angle = TAU * idx as f32 / self.entries.len().max(1) as f32;
+        let cx = self.bounds.x + self.bounds.width as i32 / 2;
+        let cy = self.bounds.y + self.bounds.height as i32 / 2;
+        let x = cx + (RING_RADIUS as f32 * angle.sin()).round() as i32;
+        let y = cy - (RING_RADIUS as f32 * angle.cos()).round() as i32;
+        (x - CELL as i32 / 2, y - CELL as i32 / 2)
+    }
+}
+
+impl Widget for RadialMenu {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn handle_event(&mut self, _event: &UiEvent) -> EventResponse {
+        EventResponse::Ignored
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(BACKDROP);
+        ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        ))?;
+
+        for (idx, entry) in self.entries.iter().enumerate() {
+            let (x, y) = self.cell_origin(idx);
+            let tex = ctx.gfx.get_texture(entry.sprite as usize);
+            let q = tex.query();
+            ctx.canvas.copy(
+                tex,
+                None,
+                Some(sdl2::rect::Rect::new(
+                    x + (CELL as i32 - q.width as i32) / 2,
+                    y + (CELL as i32 - q.height as i32) / 2,
+                    q.width,
+                    q.height,
+                )),
+            )?;
+
+            let highlighted = self.highlighted == Some(idx);
+            ctx.canvas
+                .set_draw_color(if highlighted { HIGHLIGHT } else { CELL_BORDER });
+            ctx.canvas
+                .draw_rect(sdl2::rect::Rect::new(x, y, CELL, CELL))?;
+            if highlighted {
+                ctx.canvas
+                    .draw_rect(sdl2::rect::Rect::new(x - 1, y - 1, CELL + 2, CELL + 2))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_directions_map_to_clockwise_sectors() {
+        assert_eq!(sector_for_stick(0, 0, 8), None);
+        assert_eq!(sector_for_stick(0, -32_000, 8), Some(0));
+        assert_eq!(sector_for_stick(32_000, 0, 8), Some(2));
+        assert_eq!(sector_for_stick(0, 32_000, 8), Some(4));
+        assert_eq!(sector_for_stick(-32_000, 0, 8), Some(6));
+        // Slightly left of straight up still belongs to the top wedge.
+        assert_eq!(sector_for_stick(-3_000, -32_000, 8), Some(0));
+        assert_eq!(sector_for_stick(0, -32_000, 0), None);
+    }
+
+    #[test]
+    fn open_skips_empty_slots_and_keeps_highlight_on_release() {
+        let mut items = [0i32; 40];
+        items[3] = 100;
+        items[10] = 200;
+        let mut menu = RadialMenu::new(480, 270);
+        assert!(menu.open(&items));
+        assert_eq!(menu.highlighted_slot(), None);
+
+        // Two entries: down selects the second one.
+        menu.aim(0, 32_000);
+        assert_eq!(menu.highlighted_slot(), Some(10));
+        menu.aim(0, 0);
+        assert_eq!(menu.highlighted_slot(), Some(10));
+
+        menu.close();
+        assert!(!menu.open(&[0i32; 40]));
+        assert!(!menu.is_visible());
+    }
+}
@@ -1,7 +1,8 @@
 //! Settings / options panel.
 //!
 //! Presents a compact main menu with category buttons (Display Settings,
-//! Diagnostics, Controls), an inline volume slider, and session controls.
+//! Diagnostics, Controls, Audio), an inline master volume slider, and
+//! session controls.
 //! Each category button opens a sub-panel that overlaps the main panel
 //! content. Only one sub-panel is visible at a time.
 
@@ -10,7 +11,9 @@ use sdl2::pixels::Color;
 use sdl2::render::BlendMode;
 
 use crate::font_cache;
-use crate::preferences::DisplayMode;
+use crate::preferences::{
+    DEFAULT_FPS_CAP, DEFAULT_WALL_FADE_RADIUS, DisplayMode, FPS_CAP_CHOICES, MAX_WALL_FADE_RADIUS,
+};
 use crate::types::controller::{CONTROLLER_BIND_SLOTS, ControllerBindings, ControllerButton};
 use crate::types::mouse::{ExtraMouseButton, MouseModifier, MouseModifierBindings};
 use crate::ui::RenderContext;
@@ -46,7 +49,8 @@ const Y_DIAG_BTN: i32 = Y_DISPLAY_BTN + BTN_H as i32 + 6;
 const Y_CONTROLS_BTN: i32 = Y_DIAG_BTN + BTN_H as i32 + 6;
 const Y_CONTROLLER_BTN: i32 = Y_CONTROLS_BTN + BTN_H as i32 + 6;
 const Y_MOUSE_BTN: i32 = Y_CONTROLLER_BTN + BTN_H as i32 + 6;
-const Y_VOLUME: i32 = Y_MOUSE_BTN + BTN_H as i32 + 10;
+const Y_AUDIO_BTN: i32 = Y_MOUSE_BTN + BTN_H as i32 + 6;
+const Y_VOLUME: i32 = Y_AUDIO_BTN + BTN_H as i32 + 10;
 const Y_SEPARATOR: i32 = Y_VOLUME + ROW_H + 8;
 const Y_SESSION_BTNS: i32 = Y_SEPARATOR + 10;
 const Y_RETURN_BTN: i32 = Y_SESSION_BTNS + BTN_H as i32 + 6;
@@ -65,12 +69,19 @@ const DS_Y_NAMES: i32 = DS_Y_SPELL_FX + DS_ROW_H;
 const DS_Y_HEALTH: i32 = DS_Y_NAMES + DS_ROW_H;
 const DS_Y_HELPER_TEXT: i32 = DS_Y_HEALTH + DS_ROW_H;
 const DS_Y_WALLS: i32 = DS_Y_HELPER_TEXT + DS_ROW_H;
-const DS_Y_SEP: i32 = DS_Y_WALLS + DS_ROW_H + 4;
+const DS_Y_WALL_FADE: i32 = DS_Y_WALLS + DS_ROW_H + 2;
+const DS_Y_SEP: i32 = DS_Y_WALL_FADE + 16 + 6;
 const DS_Y_DISPLAY_MODE: i32 = DS_Y_SEP + 8;
 const DS_Y_PIXEL_PERFECT: i32 = DS_Y_DISPLAY_MODE + 20;
 const DS_Y_VSYNC: i32 = DS_Y_PIXEL_PERFECT + DS_ROW_H;
 const DS_Y_WEATHER: i32 = DS_Y_VSYNC + DS_ROW_H;
-const DS_PANEL_H: u32 = (DS_Y_WEATHER + DS_ROW_H + 10 + BTN_H as i32 + 8) as u32;
+const DS_Y_STREAMER: i32 = DS_Y_WEATHER + DS_ROW_H;
+const DS_Y_SCREEN_READER: i32 = DS_Y_STREAMER + DS_ROW_H;
+const DS_Y_HIGH_CONTRAST: i32 = DS_Y_SCREEN_READER + DS_ROW_H;
+const DS_Y_FPS_CAP: i32 = DS_Y_HIGH_CONTRAST + DS_ROW_H + 4;
+const DS_Y_BACKGROUND: i32 = DS_Y_FPS_CAP + 20;
+const DS_Y_LOW_POWER: i32 = DS_Y_BACKGROUND + DS_ROW_H;
+const DS_PANEL_H: u32 = (DS_Y_LOW_POWER + DS_ROW_H + 10 + BTN_H as i32 + 8) as u32;
 
 // ---------------------------------------------------------------------------
 // Layout constants — Diagnostics sub-panel
@@ -134,6 +145,15 @@ const fn mouse_panel_height(modifier_count: usize) -> u32 {
 
 const MS_PANEL_H: u32 = mouse_panel_height(MouseModifier::ALL.len());
 
+// ---------------------------------------------------------------------------
+// Layout constants — Audio sub-panel
+// ---------------------------------------------------------------------------
+
+const AU_Y_MUSIC: i32 = TITLE_BAR_H + 8;
+const AU_Y_EFFECTS: i32 = AU_Y_MUSIC + ROW_H + 8;
+const AU_Y_UI: i32 = AU_Y_EFFECTS + ROW_H + 8;
+const AU_PANEL_H: u32 = (AU_Y_UI + ROW_H + 10 + BTN_H as i32 + 8) as u32;
+
 // ---------------------------------------------------------------------------
 // Which sub-panel is active
 // ---------------------------------------------------------------------------
@@ -151,6 +171,8 @@ enum SettingsSubPanel {
     Controller,
     /// Mouse side-button modifier bindings.
     Mouse,
+    /// Music, effects and interface volumes.
+    Audio,
 }
 
 // ---------------------------------------------------------------------------
@@ -220,6 +242,30 @@ fn keybinding_button_label(bindings: &KeyBindings, action: GameAction) -> String
         .unwrap_or_else(|| "Unbound".to_owned())
 }
 
+/// Returns the FPS cap dropdown label for a frame rate.
+fn fps_cap_label(fps: u32) -> String {
+    format!("FPS Cap: {fps}")
+}
+
+/// Returns the FPS cap dropdown index for a frame rate, falling back to the
+/// default cap for values not in [`FPS_CAP_CHOICES`].
+fn fps_cap_index(fps: u32) -> usize {
+    FPS_CAP_CHOICES
+        .iter()
+        .position(|&choice| choice == fps)
+        .or_else(|| FPS_CAP_CHOICES.iter().position(|&c| c == DEFAULT_FPS_CAP))
+        .unwrap_or(0)
+}
+
+/// Returns the wall fade dropdown label for a radius in tiles.
+fn wall_fade_label(radius: u8) -> String {
+    match radius {
+        0 => "Wall Fade: Off".to_owned(),
+        1 => "Wall Fade: 1 tile".to_owned(),
+        n => format!("Wall Fade: {n} tiles"),
+    }
+}
+
 /// Draw a sub-panel background and border rectangle.
 fn draw_sub_panel_frame(
     ctx: &mut RenderContext,
@@ -243,8 +289,9 @@ fn draw_sub_panel_frame(
 /// Sub-panel for display/visual settings.
 ///
 /// Contains visual toggles (shadows, spell effects, names, health, helper
-/// text, hide walls) and display controls (mode, pixel-perfect scaling,
-/// VSync).
+/// text, hide walls, wall fade radius) and display controls (mode, pixel-perfect scaling,
+/// VSync, streamer mode, screen reader, high contrast, frame rate cap,
+/// background throttling, low power).
 struct DisplaySettingsSubPanel {
     bounds: Bounds,
     visible: bool,
@@ -255,15 +302,24 @@ struct DisplaySettingsSubPanel {
     chk_show_health: Checkbox,
     chk_helper_text: Checkbox,
     chk_hide_walls: Checkbox,
+    drp_wall_fade: Dropdown,
     drp_display_mode: Dropdown,
     chk_pixel_perfect: Checkbox,
     chk_vsync: Checkbox,
     chk_weather: Checkbox,
+    chk_streamer_mode: Checkbox,
+    chk_screen_reader: Checkbox,
+    chk_high_contrast: Checkbox,
+    drp_fps_cap: Dropdown,
+    chk_background_throttle: Checkbox,
+    chk_low_power: Checkbox,
     btn_close: RectButton,
     pending_actions: Vec<WidgetAction>,
     /// Controller focus index. 0=Shadows, 1=SpellEffects, 2=ShowNames,
-    /// 3=ShowHealth, 4=HelperText, 5=HideWalls, 6=DisplayMode,
-    /// 7=PixelPerfect, 8=VSync, 9=Weather, 10=Close.
+    /// 3=ShowHealth, 4=HelperText, 5=HideWalls, 6=WallFade, 7=DisplayMode,
+    /// 8=PixelPerfect, 9=VSync, 10=Weather, 11=StreamerMode,
+    /// 12=ScreenReader, 13=HighContrast, 14=FpsCap, 15=BackgroundThrottle,
+    /// 16=LowPower, 17=Close.
     controller_focused: Option<usize>,
 }
 
@@ -317,6 +373,12 @@ impl DisplaySettingsSubPanel {
                 "Hide Walls",
                 0,
             ),
+            drp_wall_fade: Dropdown::new(
+                Bounds::new(x, origin_y + DS_Y_WALL_FADE, w, 16),
+                (0..=MAX_WALL_FADE_RADIUS).map(wall_fade_label).collect(),
+                usize::from(DEFAULT_WALL_FADE_RADIUS),
+                0,
+            ),
             drp_display_mode: Dropdown::new(
                 Bounds::new(x, origin_y + DS_Y_DISPLAY_MODE, w, 16),
                 DisplayMode::ALL.iter().map(|m| m.to_string()).collect(),
@@ -338,6 +400,37 @@ impl DisplaySettingsSubPanel {
                 "Enable Particle Effects",
                 0,
             ),
+            chk_streamer_mode: Checkbox::new(
+                Bounds::new(x, origin_y + DS_Y_STREAMER, w, DS_ROW_H as u32),
+                "Streamer Mode",
+                0,
+            ),
+            chk_screen_reader: Checkbox::new(
+                Bounds::new(x, origin_y + DS_Y_SCREEN_READER, w, DS_ROW_H as u32),
+                "Screen Reader",
+                0,
+            ),
+            chk_high_contrast: Checkbox::new(
+                Bounds::new(x, origin_y + DS_Y_HIGH_CONTRAST, w, DS_ROW_H as u32),
+                "High Contrast HUD",
+                0,
+            ),
+            drp_fps_cap: Dropdown::new(
+                Bounds::new(x, origin_y + DS_Y_FPS_CAP, w, 16),
+                FPS_CAP_CHOICES.iter().copied().map(fps_cap_label).collect(),
+                fps_cap_index(DEFAULT_FPS_CAP),
+                0,
+            ),
+            chk_background_throttle: Checkbox::new(
+                Bounds::new(x, origin_y + DS_Y_BACKGROUND, w, DS_ROW_H as u32),
+                "Reduce FPS In Background",
+                0,
+            ),
+            chk_low_power: Checkbox::new(
+                Bounds::new(x, origin_y + DS_Y_LOW_POWER, w, DS_ROW_H as u32),
+                "Low Power Mode",
+                0,
+            ),
             btn_close: RectButton::new(Bounds::new(x, close_y, w, BTN_H), btn_bg())
                 .with_label("Close", 0)
                 .with_border(btn_border()),
@@ -347,7 +440,7 @@ impl DisplaySettingsSubPanel {
     }
 
     /// Number of focusable elements in the display sub-panel.
-    const FOCUSABLE_COUNT: usize = 11;
+    const FOCUSABLE_COUNT: usize = 18;
 
     /// Applies controller focus highlighting.
     fn apply_controller_focus(&mut self) {
@@ -358,11 +451,18 @@ impl DisplaySettingsSubPanel {
         self.chk_show_health.set_hovered(f == Some(3));
         self.chk_helper_text.set_hovered(f == Some(4));
         self.chk_hide_walls.set_hovered(f == Some(5));
-        self.drp_display_mode.set_hovered(f == Some(6));
-        self.chk_pixel_perfect.set_hovered(f == Some(7));
-        self.chk_vsync.set_hovered(f == Some(8));
-        self.chk_weather.set_hovered(f == Some(9));
-        self.btn_close.set_hovered(f == Some(10));
+        self.drp_wall_fade.set_hovered(f == Some(6));
+        self.drp_display_mode.set_hovered(f == Some(7));
+        self.chk_pixel_perfect.set_hovered(f == Some(8));
+        self.chk_vsync.set_hovered(f == Some(9));
+        self.chk_weather.set_hovered(f == Some(10));
+        self.chk_streamer_mode.set_hovered(f == Some(11));
+        self.chk_screen_reader.set_hovered(f == Some(12));
+        self.chk_high_contrast.set_hovered(f == Some(13));
+        self.drp_fps_cap.set_hovered(f == Some(14));
+        self.chk_background_throttle.set_hovered(f == Some(15));
+        self.chk_low_power.set_hovered(f == Some(16));
+        self.btn_close.set_hovered(f == Some(17));
     }
 
     /// Loads widget values from the data snapshot.
@@ -378,10 +478,19 @@ impl DisplaySettingsSubPanel {
         self.chk_show_health.set_checked(data.show_health_pct);
         self.chk_helper_text.set_checked(data.show_helper_text);
         self.chk_hide_walls.set_checked(data.hide_walls);
+        self.drp_wall_fade
+            .set_selected(usize::from(data.wall_fade_radius.min(MAX_WALL_FADE_RADIUS)));
         self.chk_pixel_perfect
             .set_checked(data.pixel_perfect_scaling);
         self.chk_vsync.set_checked(data.vsync_enabled);
         self.chk_weather.set_checked(data.weather_enabled);
+        self.chk_streamer_mode.set_checked(data.streamer_mode);
+        self.chk_screen_reader.set_checked(data.screen_reader);
+        self.chk_high_contrast.set_checked(data.high_contrast);
+        self.drp_fps_cap.set_selected(fps_cap_index(data.fps_cap));
+        self.chk_background_throttle
+            .set_checked(data.background_throttle);
+        self.chk_low_power.set_checked(data.low_power_mode);
 
         let mode_idx = DisplayMode::ALL
             .iter()
@@ -419,6 +528,11 @@ impl DisplaySettingsSubPanel {
                 self.chk_helper_text.is_checked(),
             ));
         }
+        if self.drp_wall_fade.was_changed() {
+            self.pending_actions.push(WidgetAction::SetWallFadeRadius(
+                self.drp_wall_fade.selected_index() as u8,
+            ));
+        }
         if self.drp_display_mode.was_changed() {
             let mode = DisplayMode::ALL[self.drp_display_mode.selected_index()];
             self.pending_actions
@@ -438,6 +552,37 @@ impl DisplaySettingsSubPanel {
             self.pending_actions
                 .push(WidgetAction::SetWeather(self.chk_weather.is_checked()));
         }
+        if self.chk_streamer_mode.was_toggled() {
+            self.pending_actions.push(WidgetAction::SetStreamerMode(
+                self.chk_streamer_mode.is_checked(),
+            ));
+        }
+        if self.chk_screen_reader.was_toggled() {
+            self.pending_actions.push(WidgetAction::SetScreenReader(
+                self.chk_screen_reader.is_checked(),
+            ));
+        }
+        if self.chk_high_contrast.was_toggled() {
+            self.pending_actions.push(WidgetAction::SetHighContrast(
+                self.chk_high_contrast.is_checked(),
+            ));
+        }
+        if self.drp_fps_cap.was_changed() {
+            self.pending_actions.push(WidgetAction::SetFpsCap(
+                FPS_CAP_CHOICES[self.drp_fps_cap.selected_index()],
+            ));
+        }
+        if self.chk_background_throttle.was_toggled() {
+            self.pending_actions
+                .push(WidgetAction::SetBackgroundThrottle(
+                    self.chk_background_throttle.is_checked(),
+                ));
+        }
+        if self.chk_low_power.was_toggled() {
+            self.pending_actions.push(WidgetAction::SetLowPowerMode(
+                self.chk_low_power.is_checked(),
+            ));
+        }
     }
 
     /// Shifts all widgets by a pixel delta.
@@ -452,10 +597,17 @@ impl DisplaySettingsSubPanel {
         shift(&mut self.chk_show_health, dx, dy);
         shift(&mut self.chk_helper_text, dx, dy);
         shift(&mut self.chk_hide_walls, dx, dy);
+        shift(&mut self.drp_wall_fade, dx, dy);
         shift(&mut self.drp_display_mode, dx, dy);
         shift(&mut self.chk_pixel_perfect, dx, dy);
         shift(&mut self.chk_vsync, dx, dy);
         shift(&mut self.chk_weather, dx, dy);
+        shift(&mut self.chk_streamer_mode, dx, dy);
+        shift(&mut self.chk_screen_reader, dx, dy);
+        shift(&mut self.chk_high_contrast, dx, dy);
+        shift(&mut self.drp_fps_cap, dx, dy);
+        shift(&mut self.chk_background_throttle, dx, dy);
+        shift(&mut self.chk_low_power, dx, dy);
         shift(&mut self.btn_close, dx, dy);
     }
 
@@ -533,6 +685,14 @@ impl DisplaySettingsSubPanel {
                         self.pending_actions.push(WidgetAction::SetHideWalls(v));
                     }
                     Some(6) => {
+                        // Cycle wall fade radius dropdown.
+                        let next = (self.drp_wall_fade.selected_index() + 1)
+                            % (usize::from(MAX_WALL_FADE_RADIUS) + 1);
+                        self.drp_wall_fade.set_selected(next);
+                        self.pending_actions
+                            .push(WidgetAction::SetWallFadeRadius(next as u8));
+                    }
+                    Some(7) => {
                         // Cycle display mode dropdown.
                         let next =
                             (self.drp_display_mode.selected_index() + 1) % DisplayMode::ALL.len();
@@ -540,23 +700,56 @@ impl DisplaySettingsSubPanel {
                         self.pending_actions
                             .push(WidgetAction::SetDisplayMode(DisplayMode::ALL[next]));
                     }
-                    Some(7) => {
+                    Some(8) => {
                         let v = !self.chk_pixel_perfect.is_checked();
                         self.chk_pixel_perfect.set_checked(v);
                         self.pending_actions
                             .push(WidgetAction::SetPixelPerfectScaling(v));
                     }
-                    Some(8) => {
+                    Some(9) => {
                         let v = !self.chk_vsync.is_checked();
                         self.chk_vsync.set_checked(v);
                         self.pending_actions.push(WidgetAction::SetVSync(v));
                     }
-                    Some(9) => {
+                    Some(10) => {
                         let v = !self.chk_weather.is_checked();
                         self.chk_weather.set_checked(v);
                         self.pending_actions.push(WidgetAction::SetWeather(v));
                     }
-                    Some(10) => {
+                    Some(11) => {
+                        let v = !self.chk_streamer_mode.is_checked();
+                        self.chk_streamer_mode.set_checked(v);
+                        self.pending_actions.push(WidgetAction::SetStreamerMode(v));
+                    }
+                    Some(12) => {
+                        let v = !self.chk_screen_reader.is_checked();
+                        self.chk_screen_reader.set_checked(v);
+                        self.pending_actions.push(WidgetAction::SetScreenReader(v));
+                    }
+                    Some(13) => {
+                        let v = !self.chk_high_contrast.is_checked();
+                        self.chk_high_contrast.set_checked(v);
+                        self.pending_actions.push(WidgetAction::SetHighContrast(v));
+                    }
+                    Some(14) => {
+                        // Cycle FPS cap dropdown.
+                        let next = (self.drp_fps_cap.selected_index() + 1) % FPS_CAP_CHOICES.len();
+                        self.drp_fps_cap.set_selected(next);
+                        self.pending_actions
+                            .push(WidgetAction::SetFpsCap(FPS_CAP_CHOICES[next]));
+                    }
+                    Some(15) => {
+                        let v = !self.chk_background_throttle.is_checked();
+                        self.chk_background_throttle.set_checked(v);
+                        self.pending_actions
+                            .push(WidgetAction::SetBackgroundThrottle(v));
+                    }
+                    Some(16) => {
+                        let v = !self.chk_low_power.is_checked();
+                        self.chk_low_power.set_checked(v);
+                        self.pending_actions.push(WidgetAction::SetLowPowerMode(v));
+                    }
+                    Some(17) => {
                         self.visible = false;
                         self.controller_focused = None;
                     }
@@ -581,7 +774,14 @@ impl DisplaySettingsSubPanel {
             return EventResponse::Consumed;
         }
 
-        // Expanded dropdown gets priority.
+        // Expanded dropdowns get priority.
+        if self.drp_wall_fade.is_expanded() {
+            let resp = self.drp_wall_fade.handle_event(event);
+            self.collect_child_actions();
+            if resp == EventResponse::Consumed {
+                return EventResponse::Consumed;
+            }
+        }
         if self.drp_display_mode.is_expanded() {
             let resp = self.drp_display_mode.handle_event(event);
             self.collect_child_actions();
@@ -589,6 +789,13 @@ impl DisplaySettingsSubPanel {
                 return EventResponse::Consumed;
             }
         }
+        if self.drp_fps_cap.is_expanded() {
+            let resp = self.drp_fps_cap.handle_event(event);
+            self.collect_child_actions();
+            if resp == EventResponse::Consumed {
+                return EventResponse::Consumed;
+            }
+        }
 
         let children_responses = [
             self.chk_shadows.handle_event(event),
@@ -597,6 +804,11 @@ impl DisplaySettingsSubPanel {
             self.chk_show_health.handle_event(event),
             self.chk_helper_text.handle_event(event),
             self.chk_hide_walls.handle_event(event),
+            if !self.drp_wall_fade.is_expanded() {
+                self.drp_wall_fade.handle_event(event)
+            } else {
+                EventResponse::Ignored
+            },
             if !self.drp_display_mode.is_expanded() {
                 self.drp_display_mode.handle_event(event)
             } else {
@@ -605,6 +817,16 @@ impl DisplaySettingsSubPanel {
             self.chk_pixel_perfect.handle_event(event),
             self.chk_vsync.handle_event(event),
             self.chk_weather.handle_event(event),
+            self.chk_streamer_mode.handle_event(event),
+            self.chk_screen_reader.handle_event(event),
+            self.chk_high_contrast.handle_event(event),
+            if !self.drp_fps_cap.is_expanded() {
+                self.drp_fps_cap.handle_event(event)
+            } else {
+                EventResponse::Ignored
+            },
+            self.chk_background_throttle.handle_event(event),
+            self.chk_low_power.handle_event(event),
         ];
 
         self.collect_child_actions();
@@ -642,9 +864,16 @@ impl DisplaySettingsSubPanel {
         self.chk_pixel_perfect.render(ctx)?;
         self.chk_vsync.render(ctx)?;
         self.chk_weather.render(ctx)?;
+        self.chk_streamer_mode.render(ctx)?;
+        self.chk_screen_reader.render(ctx)?;
+        self.chk_high_contrast.render(ctx)?;
+        self.chk_background_throttle.render(ctx)?;
+        self.chk_low_power.render(ctx)?;
         self.btn_close.render(ctx)?;
-        // Dropdown last so expanded list overlays.
+        // Dropdowns last so expanded lists overlay; the upper one on top.
+        self.drp_fps_cap.render(ctx)?;
         self.drp_display_mode.render(ctx)?;
+        self.drp_wall_fade.render(ctx)?;
 
         Ok(())
     }
@@ -1585,6 +1814,258 @@ impl ControllerBindingsSubPanel {
     }
 }
 
+// ===========================================================================
+// AudioSettingsSubPanel
+// ===========================================================================
+
+/// Sub-panel with the per-category volume sliders.
+///
+/// Each slider is scaled by the master volume on the main panel.
+struct AudioSettingsSubPanel {
+    bounds: Bounds,
+    visible: bool,
+    title_bar: TitleBar,
+    sld_music: Slider,
+    sld_effects: Slider,
+    sld_ui: Slider,
+    btn_close: RectButton,
+    pending_actions: Vec<WidgetAction>,
+    /// Controller focus index. 0=Music, 1=Effects, 2=Interface, 3=Close.
+    controller_focused: Option<usize>,
+    /// `true` while NavNext/NavPrev adjust the focused slider.
+    adjusting: bool,
+}
+
+impl AudioSettingsSubPanel {
+    /// Creates a new audio sub-panel positioned at the given origin.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_x` - Left edge of the sub-panel.
+    /// * `origin_y` - Top edge of the sub-panel.
+    /// * `width` - Panel width.
+    ///
+    /// # Returns
+    ///
+    /// A new `AudioSettingsSubPanel`, initially hidden.
+    fn new(origin_x: i32, origin_y: i32, width: u32) -> Self {
+        let x = origin_x + H_INSET;
+        let w = CONTROL_W.min(width.saturating_sub(H_INSET as u32 * 2));
+        let close_y = origin_y + AU_PANEL_H as i32 - BTN_H as i32 - 8;
+        let slider = |y: i32, label: &str| {
+            Slider::new(
+                Bounds::new(x, origin_y + y, w, ROW_H as u32),
+                label,
+                0.0,
+                1.0,
+                1.0,
+                0,
+            )
+        };
+        Self {
+            bounds: Bounds::new(origin_x, origin_y, width, AU_PANEL_H),
+            visible: false,
+            title_bar: TitleBar::new_static("Audio", origin_x, origin_y, width),
+            sld_music: slider(AU_Y_MUSIC, "Music"),
+            sld_effects: slider(AU_Y_EFFECTS, "Effects"),
+            sld_ui: slider(AU_Y_UI, "Interface"),
+            btn_close: RectButton::new(Bounds::new(x, close_y, w, BTN_H), btn_bg())
+                .with_label("Close", 0)
+                .with_border(btn_border()),
+            pending_actions: Vec::new(),
+            controller_focused: None,
+            adjusting: false,
+        }
+    }
+
+    /// Number of focusable elements.
+    const FOCUSABLE_COUNT: usize = 4;
+
+    /// Applies controller focus highlighting.
+    fn apply_controller_focus(&mut self) {
+        let f = self.controller_focused;
+        self.sld_music.set_hovered(f == Some(0));
+        self.sld_effects.set_hovered(f == Some(1));
+        self.sld_ui.set_hovered(f == Some(2));
+        self.btn_close.set_hovered(f == Some(3));
+        self.sld_music.set_active(self.adjusting && f == Some(0));
+        self.sld_effects.set_active(self.adjusting && f == Some(1));
+        self.sld_ui.set_active(self.adjusting && f == Some(2));
+    }
+
+    /// Loads widget values from the data snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Snapshot of current settings values.
+    fn sync_state(&mut self, data: &SettingsPanelData) {
+        self.sld_music.set_value(data.music_volume);
+        self.sld_effects.set_value(data.effects_volume);
+        self.sld_ui.set_value(data.ui_volume);
+    }
+
+    /// The slider at controller focus index `index`, if any.
+    fn slider_mut(&mut self, index: Option<usize>) -> Option<&mut Slider> {
+        match index {
+            Some(0) => Some(&mut self.sld_music),
+            Some(1) => Some(&mut self.sld_effects),
+            Some(2) => Some(&mut self.sld_ui),
+            _ => None,
+        }
+    }
+
+    /// Queues an action for every slider whose value changed.
+    fn collect_slider_actions(&mut self) {
+        if self.sld_music.was_changed() {
+            self.pending_actions
+                .push(WidgetAction::SetMusicVolume(self.sld_music.value()));
+        }
+        if self.sld_effects.was_changed() {
+            self.pending_actions
+                .push(WidgetAction::SetEffectsVolume(self.sld_effects.value()));
+        }
+        if self.sld_ui.was_changed() {
+            self.pending_actions
+                .push(WidgetAction::SetUiVolume(self.sld_ui.value()));
+        }
+    }
+
+    /// Hides the sub-panel and resets controller state.
+    fn hide(&mut self) {
+        self.visible = false;
+        self.adjusting = false;
+        self.controller_focused = None;
+        self.apply_controller_focus();
+    }
+
+    /// Shifts all widgets by a pixel delta.
+    fn shift_all(&mut self, dx: i32, dy: i32) {
+        self.bounds.x += dx;
+        self.bounds.y += dy;
+        self.title_bar
+            .set_bar_position(self.bounds.x, self.bounds.y);
+        shift(&mut self.sld_music, dx, dy);
+        shift(&mut self.sld_effects, dx, dy);
+        shift(&mut self.sld_ui, dx, dy);
+        shift(&mut self.btn_close, dx, dy);
+    }
+
+    /// Handles a UI event. Returns `Consumed` if the sub-panel ate it.
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+
+        let (tb_resp, _drag) = self.title_bar.handle_event(event);
+        if self.title_bar.was_close_requested() {
+            self.hide();
+            return EventResponse::Consumed;
+        }
+        if tb_resp == EventResponse::Consumed {
+            return EventResponse::Consumed;
+        }
+
+        // Controller adjust mode: NavNext/NavPrev move the focused slider.
+        if self.adjusting {
+            const VOLUME_STEP: f32 = 0.05;
+            let step = match event {
+                UiEvent::NavNext => Some(VOLUME_STEP),
+                UiEvent::NavPrev => Some(-VOLUME_STEP),
+                UiEvent::NavConfirm | UiEvent::NavBack => {
+                    self.adjusting = false;
+                    self.apply_controller_focus();
+                    return EventResponse::Consumed;
+                }
+                _ => None,
+            };
+            if let Some(step) = step {
+                if let Some(slider) = self.slider_mut(self.controller_focused) {
+                    slider.adjust_by(step);
+                }
+                self.collect_slider_actions();
+                return EventResponse::Consumed;
+            }
+        }
+
+        match event {
+            UiEvent::NavNext => {
+                self.controller_focused = Some(match self.controller_focused {
+                    None => 0,
+                    Some(i) => (i + 1) % Self::FOCUSABLE_COUNT,
+                });
+                self.apply_controller_focus();
+                return EventResponse::Consumed;
+            }
+            UiEvent::NavPrev => {
+                self.controller_focused = Some(match self.controller_focused {
+                    None => Self::FOCUSABLE_COUNT - 1,
+                    Some(0) => Self::FOCUSABLE_COUNT - 1,
+                    Some(i) => i - 1,
+                });
+                self.apply_controller_focus();
+                return EventResponse::Consumed;
+            }
+            UiEvent::NavConfirm => {
+                match self.controller_focused {
+                    Some(0..=2) => {
+                        self.adjusting = true;
+                        self.apply_controller_focus();
+                    }
+                    Some(3) => self.hide(),
+                    _ => {}
+                }
+                return EventResponse::Consumed;
+            }
+            UiEvent::NavBack => {
+                self.hide();
+                return EventResponse::Consumed;
+            }
+            UiEvent::MouseMove { .. } if self.controller_focused.is_some() => {
+                self.controller_focused = None;
+                self.adjusting = false;
+                self.apply_controller_focus();
+            }
+            _ => {}
+        }
+
+        if self.btn_close.handle_event(event) == EventResponse::Consumed {
+            self.hide();
+            return EventResponse::Consumed;
+        }
+
+        let consumed = [&mut self.sld_music, &mut self.sld_effects, &mut self.sld_ui]
+            .into_iter()
+            .any(|slider| slider.handle_event(event) == EventResponse::Consumed);
+        if consumed {
+            self.collect_slider_actions();
+            return EventResponse::Consumed;
+        }
+
+        consume_mouse_events_in_bounds(&self.bounds, event)
+    }
+
+    /// Renders the sub-panel and its children.
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        draw_sub_panel_frame(ctx, &self.bounds, SUB_PANEL_BG, BORDER_COLOR)?;
+        self.title_bar.render(ctx)?;
+        self.sld_music.render(ctx)?;
+        self.sld_effects.render(ctx)?;
+        self.sld_ui.render(ctx)?;
+        self.btn_close.render(ctx)?;
+
+        Ok(())
+    }
+
+    /// Drains pending actions.
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
 // ===========================================================================
 // MouseSettingsSubPanel
 // ===========================================================================
@@ -1977,24 +2458,44 @@ pub struct SettingsPanelData {
     pub spell_effects_enabled: bool,
     /// Whether weather / ambient particle effects are rendered.
     pub weather_enabled: bool,
+    /// Whether streamer mode is enabled.
+    pub streamer_mode: bool,
+    /// Whether screen-reader mode is enabled.
+    pub screen_reader: bool,
+    /// Whether the high-contrast HUD is enabled.
+    pub high_contrast: bool,
     /// Whether overhead player names are shown.
     pub show_names: bool,
     /// Whether overhead health percentages are shown.
     pub show_health_pct: bool,
     /// Whether walls are hidden.
     pub hide_walls: bool,
+    /// Wall fade radius in tiles (0 = off).
+    pub wall_fade_radius: u8,
     /// Whether context-sensitive helper text is shown near the cursor.
     pub show_helper_text: bool,
     /// Whether helper text is replaced with the cursor's logical screen position.
     pub show_positions: bool,
     /// Master volume (0.0–1.0).
     pub master_volume: f32,
+    /// Music volume (0.0–1.0).
+    pub music_volume: f32,
+    /// Sound effect volume (0.0–1.0).
+    pub effects_volume: f32,
+    /// Interface click volume (0.0–1.0).
+    pub ui_volume: f32,
     /// Current display mode.
     pub display_mode: DisplayMode,
     /// Whether pixel-perfect (integer) scaling is active.
     pub pixel_perfect_scaling: bool,
     /// Whether VSync is enabled.
     pub vsync_enabled: bool,
+    /// Foreground frame rate cap.
+    pub fps_cap: u32,
+    /// Whether the frame rate drops while the window is in the background.
+    pub background_throttle: bool,
+    /// Whether low power mode is on.
+    pub low_power_mode: bool,
     /// Latest network round-trip time, if available.
     pub last_rtt_ms: Option<u32>,
     /// Whether the performance profiler is currently running.
@@ -2016,7 +2517,7 @@ pub struct SettingsPanelData {
 /// The settings / options HUD panel.
 ///
 /// Presents a compact menu of category buttons (Display,
-/// Diagnostics, Controls), an inline volume slider, and session controls
+/// Diagnostics, Controls, Audio), an inline master volume slider, and session controls
 /// (Disconnect, Quit, Return to Game). Each category button opens a
 /// sub-panel that overlaps the main panel content.
 pub struct SettingsPanel {
@@ -2035,6 +2536,7 @@ pub struct SettingsPanel {
     btn_controls: RectButton,
     btn_controller: RectButton,
     btn_mouse: RectButton,
+    btn_audio: RectButton,
 
     // --- Inline volume ---
     sld_volume: Slider,
@@ -2053,13 +2555,14 @@ pub struct SettingsPanel {
     sub_controls: ControlsSubPanel,
     sub_controller: ControllerBindingsSubPanel,
     sub_mouse: MouseSettingsSubPanel,
+    sub_audio: AudioSettingsSubPanel,
 
     /// Controller focus index into the focusable elements list, if any.
     /// Order: 0=Display, 1=Diagnostics, 2=Controls, 3=Controller,
-    ///        4=Mouse, 5=Volume, 6=Disconnect, 7=Quit, 8=Return.
+    ///        4=Mouse, 5=Audio, 6=Volume, 7=Disconnect, 8=Quit, 9=Return.
     controller_focused: Option<usize>,
     /// `true` when the controller is actively adjusting the volume slider
-    /// (entered via NavConfirm on index 4, exited via NavConfirm or NavBack).
+    /// (entered via NavConfirm on index 6, exited via NavConfirm or NavBack).
     volume_adjusting: bool,
 }
 
@@ -2087,6 +2590,7 @@ impl SettingsPanel {
         let (controller_x, controller_y) =
             centered_sub_panel_origin(&bounds, bounds.width, CB_PANEL_H);
         let (mouse_x, mouse_y) = centered_sub_panel_origin(&bounds, bounds.width, MS_PANEL_H);
+        let (audio_x, audio_y) = centered_sub_panel_origin(&bounds, bounds.width, AU_PANEL_H);
 
         Self {
             bounds,
@@ -2129,9 +2633,13 @@ impl SettingsPanel {
                 .with_label("Mouse Settings", 0)
                 .with_border(btn_border()),
 
+            btn_audio: RectButton::new(Bounds::new(x, bounds.y + Y_AUDIO_BTN, w, BTN_H), btn_bg())
+                .with_label("Audio", 0)
+                .with_border(btn_border()),
+
             sld_volume: Slider::new(
                 Bounds::new(x, bounds.y + Y_VOLUME, w, ROW_H as u32),
-                "Volume",
+                "Master Volume",
                 0.0,
                 1.0,
                 1.0,
@@ -2173,6 +2681,7 @@ impl SettingsPanel {
                 bounds.width,
             ),
             sub_mouse: MouseSettingsSubPanel::new(mouse_x, mouse_y, bounds.width),
+            sub_audio: AudioSettingsSubPanel::new(audio_x, audio_y, bounds.width),
 
             controller_focused: None,
             volume_adjusting: false,
@@ -2213,6 +2722,7 @@ impl SettingsPanel {
         self.sub_controls.sync_state(data);
         self.sub_controller.sync_state(&data.controller_bindings);
         self.sub_mouse.sync_state(&data.mouse_modifier_bindings);
+        self.sub_audio.sync_state(data);
     }
 
     /// Updates the ping readout label.
@@ -2312,6 +2822,7 @@ impl SettingsPanel {
             SettingsSubPanel::Controls => self.sub_controls.show(),
             SettingsSubPanel::Controller => self.sub_controller.show(),
             SettingsSubPanel::Mouse => self.sub_mouse.show(),
+            SettingsSubPanel::Audio => self.sub_audio.visible = true,
         }
     }
 
@@ -2324,12 +2835,13 @@ impl SettingsPanel {
                 SettingsSubPanel::Controls => self.sub_controls.hide(),
                 SettingsSubPanel::Controller => self.sub_controller.hide(),
                 SettingsSubPanel::Mouse => self.sub_mouse.hide(),
+                SettingsSubPanel::Audio => self.sub_audio.hide(),
             }
         }
     }
 
     /// Number of focusable elements on the main panel.
-    const MAIN_FOCUSABLE_COUNT: usize = 9;
+    const MAIN_FOCUSABLE_COUNT: usize = 10;
 
     /// Applies controller focus highlighting to the main panel widgets.
     fn apply_controller_focus(&mut self) {
@@ -2339,11 +2851,12 @@ impl SettingsPanel {
         self.btn_controls.set_hovered(f == Some(2));
         self.btn_controller.set_hovered(f == Some(3));
         self.btn_mouse.set_hovered(f == Some(4));
-        self.sld_volume.set_hovered(f == Some(5));
+        self.btn_audio.set_hovered(f == Some(5));
+        self.sld_volume.set_hovered(f == Some(6));
         self.sld_volume.set_active(self.volume_adjusting);
-        self.btn_disconnect.set_hovered(f == Some(6));
-        self.btn_quit.set_hovered(f == Some(7));
-        self.btn_return.set_hovered(f == Some(8));
+        self.btn_disconnect.set_hovered(f == Some(7));
+        self.btn_quit.set_hovered(f == Some(8));
+        self.btn_return.set_hovered(f == Some(9));
     }
 
     /// Resets the controller focus (e.g. when mouse takes over).
@@ -2372,6 +2885,7 @@ impl SettingsPanel {
         self.pending_actions
             .extend(self.sub_controller.take_actions());
         self.pending_actions.extend(self.sub_mouse.take_actions());
+        self.pending_actions.extend(self.sub_audio.take_actions());
     }
 }
 
@@ -2392,6 +2906,7 @@ impl Widget for SettingsPanel {
         shift(&mut self.btn_controls, dx, dy);
         shift(&mut self.btn_controller, dx, dy);
         shift(&mut self.btn_mouse, dx, dy);
+        shift(&mut self.btn_audio, dx, dy);
         shift(&mut self.sld_volume, dx, dy);
         shift(&mut self.btn_disconnect, dx, dy);
         shift(&mut self.btn_quit, dx, dy);
@@ -2403,6 +2918,11 @@ impl Widget for SettingsPanel {
         self.sub_controls.shift_all(dx, dy);
         self.sub_controller.shift_all(dx, dy);
         self.sub_mouse.shift_all(dx, dy);
+        self.sub_audio.shift_all(dx, dy);
+    }
+
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
     }
 
     fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
@@ -2435,6 +2955,7 @@ impl Widget for SettingsPanel {
                 SettingsSubPanel::Controls => self.sub_controls.handle_event(event),
                 SettingsSubPanel::Controller => self.sub_controller.handle_event(event),
                 SettingsSubPanel::Mouse => self.sub_mouse.handle_event(event),
+                SettingsSubPanel::Audio => self.sub_audio.handle_event(event),
             };
             self.collect_sub_panel_actions();
 
@@ -2444,6 +2965,7 @@ impl Widget for SettingsPanel {
                 SettingsSubPanel::Controls => !self.sub_controls.visible,
                 SettingsSubPanel::Controller => !self.sub_controller.visible,
                 SettingsSubPanel::Mouse => !self.sub_mouse.visible,
+                SettingsSubPanel::Audio => !self.sub_audio.visible,
             };
             if closed {
                 self.active_sub_panel = None;
@@ -2507,20 +3029,21 @@ impl Widget for SettingsPanel {
                     Some(2) => self.open_sub_panel(SettingsSubPanel::Controls),
                     Some(3) => self.open_sub_panel(SettingsSubPanel::Controller),
                     Some(4) => self.open_sub_panel(SettingsSubPanel::Mouse),
-                    Some(5) => {
+                    Some(5) => self.open_sub_panel(SettingsSubPanel::Audio),
+                    Some(6) => {
                         // Volume slider: enter adjust mode so NavNext/NavPrev
                         // will increase/decrease volume until confirmed.
                         self.volume_adjusting = true;
                         self.sld_volume.set_active(true);
                     }
-                    Some(6) => {
+                    Some(7) => {
                         self.pending_actions.push(WidgetAction::Disconnect);
                     }
-                    Some(7) => {
+                    Some(8) => {
                         self.quit_dialog.center_on(&self.bounds);
                         self.quit_dialog.show();
                     }
-                    Some(8) => {
+                    Some(9) => {
                         self.visible = false;
                         self.close_active_sub_panel();
                         self.pending_actions
@@ -2606,6 +3129,14 @@ impl Widget for SettingsPanel {
             }
             return EventResponse::Consumed;
         }
+        if self.btn_audio.handle_event(event) == EventResponse::Consumed {
+            if self.active_sub_panel == Some(SettingsSubPanel::Audio) {
+                self.close_active_sub_panel();
+            } else {
+                self.open_sub_panel(SettingsSubPanel::Audio);
+            }
+            return EventResponse::Consumed;
+        }
 
         // 5. Volume slider.
         if self.sld_volume.handle_event(event) == EventResponse::Consumed {
@@ -2671,6 +3202,7 @@ impl Widget for SettingsPanel {
         self.btn_controls.render(ctx)?;
         self.btn_controller.render(ctx)?;
         self.btn_mouse.render(ctx)?;
+        self.btn_audio.render(ctx)?;
 
         // Volume slider
         self.sld_volume.render(ctx)?;
@@ -2700,6 +3232,7 @@ impl Widget for SettingsPanel {
         self.sub_controls.render(ctx)?;
         self.sub_controller.render(ctx)?;
         self.sub_mouse.render(ctx)?;
+        self.sub_audio.render(ctx)?;
 
         // Quit confirmation rendered topmost.
         self.quit_dialog.render(ctx)?;
@@ -2735,15 +3268,25 @@ mod tests {
             shadows_enabled: true,
             spell_effects_enabled: false,
             weather_enabled: true,
+            streamer_mode: false,
+            screen_reader: false,
+            high_contrast: false,
             show_names: true,
             show_health_pct: true,
             hide_walls: false,
+            wall_fade_radius: 2,
             show_helper_text: true,
             show_positions: true,
             master_volume: 0.75,
+            music_volume: 0.5,
+            effects_volume: 0.25,
+            ui_volume: 1.0,
             display_mode: DisplayMode::Fullscreen,
             pixel_perfect_scaling: true,
             vsync_enabled: false,
+            fps_cap: 144,
+            background_throttle: true,
+            low_power_mode: true,
             last_rtt_ms: Some(42),
             profiler_active: false,
             profiler_remaining_secs: None,
@@ -2824,13 +3367,48 @@ mod tests {
         assert!(panel.sub_display.chk_show_health.is_checked());
         assert!(!panel.sub_display.chk_hide_walls.is_checked());
         assert!(panel.sub_display.chk_helper_text.is_checked());
+        assert_eq!(panel.sub_display.drp_wall_fade.selected_index(), 2);
         assert_eq!(panel.sub_display.drp_display_mode.selected_index(), 1);
         assert!(panel.sub_display.chk_pixel_perfect.is_checked());
         assert!(!panel.sub_display.chk_vsync.is_checked());
+        assert_eq!(panel.sub_display.drp_fps_cap.selected_index(), 3);
+        assert!(panel.sub_display.chk_low_power.is_checked());
         // Diagnostics sub-panel.
         assert!(panel.sub_diagnostics.chk_show_positions.is_checked());
         // Volume on main panel.
         assert!((panel.sld_volume.value() - 0.75).abs() < 0.01);
+        // Audio sub-panel.
+        assert!((panel.sub_audio.sld_music.value() - 0.5).abs() < 0.01);
+        assert!((panel.sub_audio.sld_effects.value() - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn controller_adjusts_audio_sliders() {
+        let mut panel = make_panel();
+        panel.toggle();
+        panel.sync_state(&make_data());
+        panel.handle_event(&left_click(15, Y_AUDIO_BTN + 5));
+        assert_eq!(panel.active_sub_panel, Some(SettingsSubPanel::Audio));
+        let _ = panel.take_actions();
+
+        // Focus Effects, enter adjust mode, raise it one step.
+        panel.handle_event(&UiEvent::NavNext);
+        panel.handle_event(&UiEvent::NavNext);
+        panel.handle_event(&UiEvent::NavConfirm);
+        panel.handle_event(&UiEvent::NavNext);
+        let actions = panel.take_actions();
+        assert!(
+            actions
+                .iter()
+                .any(|a| matches!(a, WidgetAction::SetEffectsVolume(v) if (*v - 0.3).abs() < 0.01)),
+            "Expected SetEffectsVolume action, got {:?}",
+            actions
+        );
+
+        // Leaving adjust mode, then NavBack closes the sub-panel.
+        panel.handle_event(&UiEvent::NavConfirm);
+        panel.handle_event(&UiEvent::NavBack);
+        assert_eq!(panel.active_sub_panel, None);
     }
 
     #[test]
@@ -3,13 +3,22 @@
 //! Renders an 8-column × 8-row grid of up to 62 item slots (shops, depots,
 //! and graves all use the same layout). Shows sell/buy price labels at the
 //! bottom. Clicking outside the panel while it is visible closes it.
+//!
+//! A broker's window (see `mag_core::consignment`) also has a search box
+//! and page buttons in its header. Both turn into `#consign browse`
+//! commands; the server answers with the requested page.
+
+use std::time::Duration;
 
+use mag_core::consignment::{ConsignmentPage, MAX_CONSIGN_QUERY_LEN};
+use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::render::BlendMode;
 
 use crate::font_cache;
 use crate::ui::RenderContext;
 use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget, WidgetAction};
+use crate::ui::widgets::text_input::TextInput;
 
 // ---------------------------------------------------------------------------
 // Layout constants
@@ -67,6 +76,17 @@ const CLOSE_X_COLOR: Color = Color::RGBA(200, 220, 255, 240);
 /// Additive hover highlight alpha for icon buttons.
 const ICON_HOVER_ALPHA: u8 = 64;
 
+/// Broker search box: offset from the panel's left edge, width and height.
+const SEARCH_X: i32 = 50;
+const SEARCH_W: u32 = 120;
+const SEARCH_H: u32 = 14;
+
+/// Offsets of the broker's previous / next page buttons from the panel's
+/// left edge; the page label sits between them.
+const PREV_PAGE_X: i32 = 176;
+const PAGE_LABEL_X: i32 = 192;
+const NEXT_PAGE_X: i32 = 236;
+
 // ---------------------------------------------------------------------------
 // Data snapshot
 // ---------------------------------------------------------------------------
@@ -93,6 +113,9 @@ pub struct ShopPanelData {
     /// `true` when this overlay represents a corpse/grave rather than a merchant.
     /// Controls the title text displayed in the panel header.
     pub is_grave: bool,
+    /// Page of listings when this overlay is a broker's; shows the search
+    /// box and page buttons.
+    pub page: Option<ConsignmentPage>,
 }
 
 // ---------------------------------------------------------------------------
@@ -115,6 +138,8 @@ pub struct ShopPanel {
     controller_selected: Option<usize>,
     /// Whether the mouse cursor is currently over the close button.
     hovered_close: bool,
+    /// Search box shown for brokers.
+    search_input: TextInput,
 }
 
 impl ShopPanel {
@@ -138,6 +163,20 @@ impl ShopPanel {
             actions: Vec::new(),
             controller_selected: None,
             hovered_close: false,
+            search_input: TextInput::new(
+                Bounds::new(
+                    bounds.x + SEARCH_X,
+                    bounds.y + (PAD_TOP - SEARCH_H as i32) / 2,
+                    SEARCH_W,
+                    SEARCH_H,
+                ),
+                "Search...",
+                UI_FONT,
+                MAX_CONSIGN_QUERY_LEN,
+                false,
+                Color::RGBA(120, 120, 140, 200),
+                Color::RGBA(200, 200, 120, 255),
+            ),
         }
     }
 
@@ -150,9 +189,46 @@ impl ShopPanel {
     ///
     /// * `data` - The latest shop state from `PlayerState`.
     pub fn update_data(&mut self, data: ShopPanelData) {
+        match &data.page {
+            // Show the search the server answered, unless the player is
+            // typing a new one.
+            Some(page) if data.visible => {
+                if !self.search_input.is_focused() && self.search_input.value() != page.query {
+                    self.search_input.set_value(&page.query);
+                }
+            }
+            _ => self.search_input.set_focused(false),
+        }
         self.data = Some(data);
     }
 
+    /// Returns `true` while the broker search box has keyboard focus.
+    pub fn is_text_focused(&self) -> bool {
+        self.broker_page().is_some() && self.search_input.is_focused()
+    }
+
+    /// The broker page shown, when the visible overlay is a broker's.
+    fn broker_page(&self) -> Option<&ConsignmentPage> {
+        self.data
+            .as_ref()
+            .filter(|d| d.visible)
+            .and_then(|d| d.page.as_ref())
+    }
+
+    /// Asks the server for another page of the broker's listings.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - Zero-based page.
+    /// * `query` - Search text.
+    fn request_page(&mut self, page: u16, query: &str) {
+        self.actions.push(WidgetAction::SendChat(
+            format!("#consign browse {} {}", page + 1, query.trim())
+                .trim_end()
+                .to_owned(),
+        ));
+    }
+
     /// Returns whether the panel is currently visible.
     ///
     /// # Returns
@@ -178,6 +254,7 @@ impl ShopPanel {
                     citem: 0,
                     visible: true,
                     is_grave: false,
+                    page: None,
                 });
             }
         }
@@ -200,6 +277,69 @@ impl ShopPanel {
         )
     }
 
+    /// Pixel rect of a broker page button in the panel header.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset_x` - [`PREV_PAGE_X`] or [`NEXT_PAGE_X`].
+    fn page_button_rect(&self, offset_x: i32) -> Bounds {
+        Bounds::new(
+            self.bounds.x + offset_x,
+            self.bounds.y + (PAD_TOP - CLOSE_ICON_SIZE) / 2,
+            CLOSE_ICON_SIZE as u32,
+            CLOSE_ICON_SIZE as u32,
+        )
+    }
+
+    /// Handles an event aimed at the broker header.
+    ///
+    /// # Returns
+    ///
+    /// * `Some` response when the search box or a page button took the
+    ///   event, `None` to let the rest of the panel handle it.
+    fn handle_broker_event(&mut self, event: &UiEvent) -> Option<EventResponse> {
+        let page = self.broker_page()?.clone();
+        if self.search_input.handle_event(event) == EventResponse::Consumed {
+            return Some(EventResponse::Consumed);
+        }
+        if self.search_input.is_focused()
+            && let UiEvent::KeyDown { keycode, .. } = event
+        {
+            match *keycode {
+                Keycode::Return | Keycode::KpEnter => {
+                    self.search_input.set_focused(false);
+                    let query = self.search_input.value().to_owned();
+                    self.request_page(0, &query);
+                }
+                Keycode::Escape => self.search_input.set_focused(false),
+                _ => {}
+            }
+            // Keep typed keys away from the game's key bindings.
+            return Some(EventResponse::Consumed);
+        }
+        if let UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            ..
+        } = event
+        {
+            if self.page_button_rect(PREV_PAGE_X).contains_point(*x, *y) {
+                if page.page > 0 {
+                    self.request_page(page.page - 1, &page.query);
+                }
+                return Some(EventResponse::Consumed);
+            }
+            if self.page_button_rect(NEXT_PAGE_X).contains_point(*x, *y) {
+                if page.page + 1 < page.pages {
+                    self.request_page(page.page + 1, &page.query);
+                }
+                return Some(EventResponse::Consumed);
+            }
+        }
+        None
+    }
+
     // ── Hit-testing helpers ─────────────────────────────────────────────
 
     /// Returns the context-sensitive helper text label for the item slot
@@ -342,10 +482,17 @@ impl Widget for ShopPanel {
     /// * `x` - New X position.
     /// * `y` - New Y position.
     fn set_position(&mut self, x: i32, y: i32) {
+        let sb = *self.search_input.bounds();
+        self.search_input
+            .set_position(sb.x + x - self.bounds.x, sb.y + y - self.bounds.y);
         self.bounds.x = x;
         self.bounds.y = y;
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     /// Process an input event.
     ///
     /// When the shop is visible:
@@ -366,6 +513,9 @@ impl Widget for ShopPanel {
         if !self.is_visible() {
             return EventResponse::Ignored;
         }
+        if let Some(response) = self.handle_broker_event(event) {
+            return response;
+        }
 
         match event {
             UiEvent::MouseMove { x, y } => {
@@ -483,7 +633,9 @@ impl Widget for ShopPanel {
         ctx.canvas.draw_rect(rect)?;
 
         // Title.
-        let title = if data.is_grave {
+        let title = if data.page.is_some() {
+            "Broker".to_owned()
+        } else if data.is_grave {
             "Grave".to_owned()
         } else {
             "Shop".to_owned()
@@ -531,6 +683,35 @@ impl Widget for ShopPanel {
             }
         }
 
+        // Broker header: search box and page buttons.
+        if let Some(page) = data.page.as_ref() {
+            for (offset_x, label) in [(PREV_PAGE_X, "<"), (NEXT_PAGE_X, ">")] {
+                let br = self.page_button_rect(offset_x);
+                ctx.canvas.set_draw_color(CLOSE_ICON_OUTLINE);
+                ctx.canvas
+                    .draw_rect(sdl2::rect::Rect::new(br.x, br.y, br.width, br.height))?;
+                font_cache::draw_text(
+                    ctx.canvas,
+                    ctx.gfx,
+                    UI_FONT,
+                    label,
+                    br.x + 3,
+                    br.y + 2,
+                    font_cache::TextStyle::PLAIN,
+                )?;
+            }
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                UI_FONT,
+                &format!("{}/{}", page.page + 1, page.pages.max(1)),
+                self.bounds.x + PAGE_LABEL_X,
+                self.bounds.y + 4,
+                font_cache::TextStyle::PLAIN,
+            )?;
+            self.search_input.render(ctx)?;
+        }
+
         let grid_x = self.bounds.x + PAD_X;
         let grid_y = self.bounds.y + PAD_TOP;
         let hovered = self.hovered_slot();
@@ -624,6 +805,10 @@ impl Widget for ShopPanel {
         Ok(())
     }
 
+    fn update(&mut self, dt: Duration) {
+        self.search_input.update(dt);
+    }
+
     /// Drain any pending shop actions.
     ///
     /// # Returns
@@ -654,6 +839,7 @@ mod tests {
             citem: 0,
             visible: true,
             is_grave: false,
+            page: None,
         };
         data.items[0] = 100; // put an item in slot 0
         data.prices[0] = 500;
@@ -798,4 +984,79 @@ mod tests {
         panel.mouse_y = 100 + PAD_TOP + 7 * CELL + 5;
         assert_eq!(panel.hovered_slot(), Some(61));
     }
+
+    #[test]
+    fn broker_header_requests_pages() {
+        let mut panel = make_panel();
+        let mut data = make_visible_data();
+        data.page = Some(ConsignmentPage {
+            broker: 42,
+            page: 1,
+            pages: 3,
+            total: 150,
+            query: "ring".to_owned(),
+        });
+        panel.update_data(data);
+        assert_eq!(panel.search_input.value(), "ring");
+
+        let click = |x: i32| UiEvent::MouseClick {
+            x: 100 + x + 2,
+            y: 100 + PAD_TOP / 2,
+            button: MouseButton::Left,
+            modifiers: crate::ui::widget::KeyModifiers::default(),
+        };
+        assert_eq!(
+            panel.handle_event(&click(NEXT_PAGE_X)),
+            EventResponse::Consumed
+        );
+        assert_eq!(
+            panel.handle_event(&click(PREV_PAGE_X)),
+            EventResponse::Consumed
+        );
+        let requests: Vec<String> = panel
+            .take_actions()
+            .into_iter()
+            .filter_map(|action| match action {
+                WidgetAction::SendChat(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            requests,
+            vec!["#consign browse 3 ring", "#consign browse 1 ring"]
+        );
+    }
+
+    #[test]
+    fn broker_search_sends_on_enter() {
+        let mut panel = make_panel();
+        let mut data = make_visible_data();
+        data.page = Some(ConsignmentPage {
+            broker: 42,
+            pages: 1,
+            ..ConsignmentPage::default()
+        });
+        panel.update_data(data);
+
+        panel.handle_event(&UiEvent::MouseClick {
+            x: 100 + SEARCH_X + 5,
+            y: 100 + PAD_TOP / 2,
+            button: MouseButton::Left,
+            modifiers: crate::ui::widget::KeyModifiers::default(),
+        });
+        assert!(panel.is_text_focused());
+        panel.handle_event(&UiEvent::TextInput {
+            text: "sword".to_owned(),
+        });
+        let enter = UiEvent::KeyDown {
+            keycode: Keycode::Return,
+            modifiers: crate::ui::widget::KeyModifiers::default(),
+        };
+        assert_eq!(panel.handle_event(&enter), EventResponse::Consumed);
+        assert!(!panel.is_text_focused());
+        let actions = panel.take_actions();
+        assert!(
+            matches!(&actions[..], [WidgetAction::SendChat(text)] if text == "#consign browse 1 sword")
+        );
+    }
 }
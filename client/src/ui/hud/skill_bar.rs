@@ -14,12 +14,15 @@ use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::BlendMode;
 
+use mag_core::constants::TICKS;
+use mag_core::skill_timers::SkillTimer;
 use mag_core::skills;
 
 use crate::constants::{TARGET_HEIGHT_INT, TARGET_WIDTH_INT};
 use crate::filepaths;
 use crate::font_cache;
 use crate::ui::RenderContext;
+use crate::ui::visuals::skill_timers::draw_cooldown_sweep;
 use crate::ui::visuals::spell_icons::{SpellIconMeta, spell_icon_meta, spell_icon_path};
 use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget, WidgetAction};
 
@@ -94,6 +97,8 @@ pub struct SkillBarData {
     pub secondary_keybinds: [Option<usize>; TOP_CELLS],
     /// When `true` the bar displays and operates on the secondary page (Shift / LT held).
     pub show_secondary: bool,
+    /// Running cooldown of the skill in each displayed slot, shaded as a sweep.
+    pub cooldowns: [Option<SkillTimer>; TOP_CELLS],
 }
 
 // ---------------------------------------------------------------------------
@@ -348,8 +353,8 @@ impl Widget for SkillBar {
     }
 
     fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
-        let (keybinds, secondary_keybinds) = match self.data.as_ref() {
-            Some(d) => (d.keybinds, d.secondary_keybinds),
+        let (keybinds, secondary_keybinds, cooldowns) = match self.data.as_ref() {
+            Some(d) => (d.keybinds, d.secondary_keybinds, d.cooldowns),
             None => return Ok(()),
         };
         let active_keybinds = if self.show_secondary {
@@ -408,6 +413,24 @@ impl Widget for SkillBar {
                 }
             }
 
+            // Cooldown sweep with the whole seconds left.
+            if let Some(timer) = cooldowns[i].filter(|_| bound_skill.is_some()) {
+                draw_cooldown_sweep(ctx.canvas, rect, timer.fraction_remaining())?;
+                let seconds = (i32::from(timer.remaining) + TICKS - 1) / TICKS;
+                font_cache::draw_text(
+                    ctx.canvas,
+                    ctx.gfx,
+                    UI_FONT,
+                    &seconds.to_string(),
+                    x + CELL / 2,
+                    y + CELL / 2,
+                    font_cache::TextStyle::centered()
+                        .with_tint(SKILL_TEXT_COLOR)
+                        .with_drop_shadow(),
+                )?;
+                ctx.canvas.set_blend_mode(BlendMode::Blend);
+            }
+
             // Hover highlight.
             if self.hit_top_cell(self.mouse_x, self.mouse_y) == Some(i) {
                 ctx.canvas.set_draw_color(HOVER_COLOR);
@@ -476,6 +499,7 @@ mod tests {
             keybinds: [None; TOP_CELLS],
             secondary_keybinds: [None; TOP_CELLS],
             show_secondary: false,
+            cooldowns: [None; TOP_CELLS],
         }
     }
 
@@ -667,6 +691,7 @@ mod tests {
             keybinds: [None; TOP_CELLS],
             secondary_keybinds,
             show_secondary: true,
+            cooldowns: [None; TOP_CELLS],
         }
     }
 
@@ -10,6 +10,7 @@ use std::cmp::Ordering;
 use sdl2::pixels::Color;
 use sdl2::render::BlendMode;
 
+use mag_core::balance;
 use mag_core::skills::{
     MAX_SKILLS, attribute_desc, get_skill_desc, get_skill_name, get_skill_nr, get_skill_sortkey,
     is_legacy_weapon_skill,
@@ -407,30 +408,22 @@ impl SkillsPanel {
         }
     }
 
-    // ---- Cost calculation (mirrors legacy formulas) ----------------------
+    // ---- Cost calculation (core::balance) --------------------------------
 
-    // TODO: Deduplicate these formulas into one place.
     /// Cost to raise attribute `n` by one from base value `v`.
     fn attrib_cost(data: &SkillsPanelData, n: usize, v: i32) -> i32 {
-        let max_v = i32::from(data.attrib[n][2]);
-        if v >= max_v {
+        if v >= i32::from(data.attrib[n][2]) {
             return i32::MAX;
         }
-        let diff = i32::from(data.attrib[n][3]);
-        let v64 = i64::from(v);
-        ((v64 * v64 * v64) * i64::from(diff) / 20).clamp(0, i64::from(i32::MAX)) as i32
+        balance::attrib_needed(v, i32::from(data.attrib[n][3]))
     }
 
     /// Cost to raise skill `n` by one from base value `v`.
     fn skill_cost(data: &SkillsPanelData, n: usize, v: i32) -> i32 {
-        let max_v = i32::from(data.skill[n][2]);
-        if v >= max_v {
+        if v >= i32::from(data.skill[n][2]) {
             return i32::MAX;
         }
-        let diff = i32::from(data.skill[n][3]);
-        let v64 = i64::from(v);
-        let cubic = ((v64 * v64 * v64) * i64::from(diff) / 40).clamp(0, i64::from(i32::MAX)) as i32;
-        v.max(cubic)
+        balance::skill_needed(v, i32::from(data.skill[n][3]))
     }
 
     /// Cost to raise HP by one from base value `v`.
@@ -438,7 +431,7 @@ impl SkillsPanel {
         if v >= i32::from(data.hp[2]) {
             return i32::MAX;
         }
-        (i64::from(v) * i64::from(data.hp[3])).clamp(0, i64::from(i32::MAX)) as i32
+        balance::hp_needed(v, i32::from(data.hp[3]))
     }
 
     /// Cost to raise Endurance by one from base value `v`.
@@ -446,7 +439,7 @@ impl SkillsPanel {
         if v >= i32::from(data.end[2]) {
             return i32::MAX;
         }
-        (i64::from(v) * i64::from(data.end[3]) / 2).clamp(0, i64::from(i32::MAX)) as i32
+        balance::end_needed(v, i32::from(data.end[3]))
     }
 
     /// Cost to raise Mana by one from base value `v`.
@@ -454,7 +447,7 @@ impl SkillsPanel {
         if v >= i32::from(data.mana[2]) {
             return i32::MAX;
         }
-        (i64::from(v) * i64::from(data.mana[3])).clamp(0, i64::from(i32::MAX)) as i32
+        balance::mana_needed(v, i32::from(data.mana[3]))
     }
 
     // ---- Click handling ---------------------------------------------------
@@ -804,6 +797,10 @@ impl Widget for SkillsPanel {
         self.title_bar.set_bar_position(x, y);
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
         if !self.visible {
             return EventResponse::Ignored;
@@ -291,30 +291,6 @@ impl TalentPanel {
         }
         NodeStatus::Available
     }
-
-    /// Truncates ASCII UI text to fit within `max_width` pixels.
-    ///
-    /// # Arguments
-    ///
-    /// * `text` - Text to fit.
-    /// * `max_width` - Maximum pixel width available.
-    ///
-    /// # Returns
-    ///
-    /// The original text when it fits, or a `...`-terminated copy.
-    fn fit_text(text: &str, max_width: i32) -> String {
-        let max_chars = (max_width.max(0) as u32 / font_cache::BITMAP_GLYPH_ADVANCE) as usize;
-        if text.len() <= max_chars {
-            return text.to_owned();
-        }
-        if max_chars == 0 {
-            return String::new();
-        }
-        if max_chars <= 3 {
-            return ".".repeat(max_chars);
-        }
-        format!("{}...", &text[..max_chars - 3])
-    }
 }
 
 impl Widget for TalentPanel {
@@ -337,6 +313,10 @@ impl Widget for TalentPanel {
         self.update_visible_button_positions();
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
         if !self.visible {
             return EventResponse::Ignored;
@@ -439,7 +419,7 @@ impl Widget for TalentPanel {
             _ => (0, 0, "(no class)"),
         };
         let header = format!("{}   Unspent: {}   Spent: {}", class_label, avail, spent);
-        let header = Self::fit_text(&header, self.bounds.width as i32 - H_INSET * 2);
+        let header = font_cache::fit_text_bitmap(&header, self.bounds.width as i32 - H_INSET * 2);
         font_cache::draw_text(
             ctx.canvas,
             ctx.gfx,
@@ -473,7 +453,7 @@ impl Widget for TalentPanel {
                 status_tag, row.meta.slot.layer, row.meta.name, row.meta.cost
             );
             let label_width = row.button.bounds().x - label_x - 4;
-            let line = Self::fit_text(&line, label_width);
+            let line = font_cache::fit_text_bitmap(&line, label_width);
             font_cache::draw_text(
                 ctx.canvas,
                 ctx.gfx,
@@ -619,7 +599,7 @@ mod tests {
     #[test]
     fn fit_text_truncates_long_text() {
         let max_width = font_cache::BITMAP_GLYPH_ADVANCE as i32 * 10;
-        let fitted = TalentPanel::fit_text("Protective Spells Boost 1", max_width);
+        let fitted = font_cache::fit_text_bitmap("Protective Spells Boost 1", max_width);
         assert_eq!(fitted, "Protect...");
     }
 
@@ -627,7 +607,7 @@ mod tests {
     #[test]
     fn fit_text_preserves_short_text() {
         let max_width = font_cache::BITMAP_GLYPH_ADVANCE as i32 * 20;
-        let fitted = TalentPanel::fit_text("Distract", max_width);
+        let fitted = font_cache::fit_text_bitmap("Distract", max_width);
         assert_eq!(fitted, "Distract");
     }
 
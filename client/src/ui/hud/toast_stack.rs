@@ -0,0 +1,277 @@
+//! Top-right stack of auto-dismissing notification toasts.
+//!
+//! GameScene drains `PlayerState`'s [`ClientEvent`] queue every frame and
+//! pushes each event here. Toasts stack downward from the anchor, newest at
+//! the top, fade out at the end of their lifetime, and can be dismissed
+//! early with a left click.
+
+use std::time::Duration;
+
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::font_cache;
+use crate::types::client_event::ClientEvent;
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget};
+
+/// Bitmap font index used for toast text.
+const FONT: usize = 1;
+
+/// Size of a single toast.
+const TOAST_W: u32 = 190;
+const TOAST_H: u32 = 30;
+
+/// Vertical gap between stacked toasts.
+const TOAST_GAP: i32 = 4;
+
+/// Width of the colored accent strip on the left edge.
+const ACCENT_W: u32 = 3;
+
+/// Maximum number of toasts shown at once; older ones are dropped.
+const MAX_TOASTS: usize = 4;
+
+/// Seconds a toast stays on screen.
+const TOAST_LIFETIME_SECS: f32 = 6.0;
+
+/// Seconds of fade-out at the end of the lifetime.
+const TOAST_FADE_SECS: f32 = 0.75;
+
+/// Toast background fill.
+const TOAST_BG: Color = Color::RGBA(15, 15, 30, 210);
+
+/// Toast border.
+const TOAST_BORDER: Color = Color::RGBA(120, 120, 140, 200);
+
+/// A single visible toast.
+struct Toast {
+    heading: &'static str,
+    body: String,
+    accent: Color,
+    age_secs: f32,
+}
+
+impl Toast {
+    /// Current opacity factor in `0.0..=1.0`.
+    fn opacity(&self) -> f32 {
+        let remaining = TOAST_LIFETIME_SECS - self.age_secs;
+        (remaining / TOAST_FADE_SECS).clamp(0.0, 1.0)
+    }
+}
+
+/// Accent strip color for an event kind.
+fn accent_for(event: &ClientEvent) -> Color {
+    match event {
+        ClientEvent::FriendOnline { .. } => Color::RGB(90, 200, 110),
+        ClientEvent::TradeRequest { .. } => Color::RGB(230, 180, 70),
+        ClientEvent::MailReceived { .. } => Color::RGB(110, 170, 240),
+        ClientEvent::AchievementUnlocked { .. } => Color::RGB(255, 215, 90),
+        ClientEvent::RewardDelivered { .. } => Color::RGB(200, 150, 255),
+    }
+}
+
+/// Scales a color's alpha by `opacity`.
+fn faded(color: Color, opacity: f32) -> Color {
+    Color::RGBA(
+        color.r,
+        color.g,
+        color.b,
+        (f32::from(color.a) * opacity) as u8,
+    )
+}
+
+/// Stack of notification toasts anchored at the top-right.
+pub struct ToastStack {
+    /// Area covered by a full stack; toasts are laid out from its top edge.
+    bounds: Bounds,
+    /// Visible toasts, newest first.
+    toasts: Vec<Toast>,
+}
+
+impl ToastStack {
+    /// Creates an empty toast stack.
+    ///
+    /// # Arguments
+    ///
+    /// * `right` - Screen x of the stack's right edge.
+    /// * `top` - Screen y of the first toast.
+    ///
+    /// # Returns
+    ///
+    /// * A new, empty `ToastStack`.
+    pub fn new(right: i32, top: i32) -> Self {
+        let height = MAX_TOASTS as u32 * (TOAST_H + TOAST_GAP as u32);
+        Self {
+            bounds: Bounds::new(right - TOAST_W as i32, top, TOAST_W, height),
+            toasts: Vec::new(),
+        }
+    }
+
+    /// Shows a toast for `event`.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Event to display.
+    pub fn push(&mut self, event: &ClientEvent) {
+        self.toasts.insert(
+            0,
+            Toast {
+                heading: event.heading(),
+                body: event.body(),
+                accent: accent_for(event),
+                age_secs: 0.0,
+            },
+        );
+        self.toasts.truncate(MAX_TOASTS);
+    }
+
+    /// Returns the number of toasts currently shown.
+    ///
+    /// # Returns
+    ///
+    /// * Visible toast count.
+    pub fn len(&self) -> usize {
+        self.toasts.len()
+    }
+
+    /// Returns `true` when no toast is shown.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the stack is empty.
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    /// Screen-space bounds of the toast at stack index `idx`.
+    fn toast_bounds(&self, idx: usize) -> Bounds {
+        Bounds::new(
+            self.bounds.x,
+            self.bounds.y + idx as i32 * (TOAST_H as i32 + TOAST_GAP),
+            TOAST_W,
+            TOAST_H,
+        )
+    }
+}
+
+impl Widget for ToastStack {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if let UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            ..
+        } = event
+            && let Some(idx) =
+                (0..self.toasts.len()).find(|&i| self.toast_bounds(i).contains_point(*x, *y))
+        {
+            self.toasts.remove(idx);
+            return EventResponse::Consumed;
+        }
+        EventResponse::Ignored
+    }
+
+    fn update(&mut self, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        for toast in &mut self.toasts {
+            toast.age_secs += dt;
+        }
+        self.toasts.retain(|t| t.age_secs < TOAST_LIFETIME_SECS);
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        for (idx, toast) in self.toasts.iter().enumerate() {
+            let b = self.toast_bounds(idx);
+            let opacity = toast.opacity();
+            let rect = sdl2::rect::Rect::new(b.x, b.y, b.width, b.height);
+
+            ctx.canvas.set_draw_color(faded(TOAST_BG, opacity));
+            ctx.canvas.fill_rect(rect)?;
+            ctx.canvas.set_draw_color(faded(toast.accent, opacity));
+            ctx.canvas
+                .fill_rect(sdl2::rect::Rect::new(b.x, b.y, ACCENT_W, b.height))?;
+            ctx.canvas.set_draw_color(faded(TOAST_BORDER, opacity));
+            ctx.canvas.draw_rect(rect)?;
+
+            let alpha = (255.0 * opacity) as u8;
+            let text_x = b.x + ACCENT_W as i32 + 5;
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                FONT,
+                toast.heading,
+                text_x,
+                b.y + 4,
+                font_cache::TextStyle::faded(alpha).with_tint(toast.accent),
+            )?;
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                FONT,
+                &toast.body,
+                text_x,
+                b.y + 4 + font_cache::BITMAP_GLYPH_H as i32 + 2,
+                font_cache::TextStyle::faded(alpha),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn achievement(name: &str) -> ClientEvent {
+        ClientEvent::AchievementUnlocked {
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn newest_toast_is_on_top_and_stack_is_capped() {
+        let mut stack = ToastStack::new(800, 200);
+        for i in 0..MAX_TOASTS + 1 {
+            stack.push(&achievement(&i.to_string()));
+        }
+        assert_eq!(stack.len(), MAX_TOASTS);
+        assert_eq!(stack.toasts[0].body, MAX_TOASTS.to_string());
+    }
+
+    #[test]
+    fn toasts_expire_after_lifetime() {
+        let mut stack = ToastStack::new(800, 200);
+        stack.push(&achievement("a"));
+        stack.update(Duration::from_secs_f32(TOAST_LIFETIME_SECS - 0.1));
+        assert_eq!(stack.len(), 1);
+        stack.update(Duration::from_secs_f32(0.2));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn clicking_a_toast_dismisses_it() {
+        let mut stack = ToastStack::new(800, 200);
+        stack.push(&achievement("a"));
+        stack.push(&achievement("b"));
+        let b = stack.toast_bounds(1);
+        let resp = stack.handle_event(&UiEvent::MouseClick {
+            x: b.x + 5,
+            y: b.y + 5,
+            button: MouseButton::Left,
+            modifiers: Default::default(),
+        });
+        assert_eq!(resp, EventResponse::Consumed);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.toasts[0].body, "b");
+    }
+}
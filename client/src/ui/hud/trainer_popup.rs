@@ -0,0 +1,352 @@
+//! Popup listing the skills a trainer NPC offers.
+//!
+//! Opened by GameScene next to the NPC menu when the server answers "Train"
+//! with `SV_TRAINEROFFERS`, and refreshed in place after every purchase.
+//! Clicking an available offer emits [`WidgetAction::LearnSkill`]; offers
+//! the player cannot buy are greyed out with their [`OfferStatus`] label.
+//! Clicking outside the popup or pressing Escape closes it.
+
+use mag_core::skill_trainers::{OfferStatus, TrainerOfferEntry};
+use mag_core::skills;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget, WidgetAction};
+use crate::ui::widgets::title_bar::clamp_to_viewport;
+
+/// Font index used for popup text.
+const POPUP_FONT: usize = 1;
+
+/// Popup width in logical pixels.
+const POPUP_W: u32 = 170;
+
+/// Height of the title row.
+const HEADER_H: i32 = 14;
+
+/// Height of a single offer row.
+const ROW_H: i32 = 14;
+
+/// Inner horizontal padding.
+const H_INSET: i32 = 6;
+
+/// Popup background.
+const POPUP_BG: Color = Color::RGBA(10, 10, 30, 220);
+
+/// Popup border.
+const POPUP_BORDER: Color = Color::RGBA(120, 120, 140, 220);
+
+/// Highlight behind the hovered, available offer.
+const HOVER_BG: Color = Color::RGBA(80, 80, 30, 200);
+
+/// Alpha used for offers that cannot be bought.
+const UNAVAILABLE_ALPHA: u8 = 110;
+
+/// Formats a silver price the way the server logs it.
+///
+/// # Arguments
+///
+/// * `price` - Price in silver.
+///
+/// # Returns
+///
+/// * `"25G"`, or `"25G 50S"` when there are silver coins.
+fn format_price(price: u32) -> String {
+    if price.is_multiple_of(100) {
+        format!("{}G", price / 100)
+    } else {
+        format!("{}G {}S", price / 100, price % 100)
+    }
+}
+
+/// The trainer offer popup.
+pub struct TrainerPopup {
+    bounds: Bounds,
+    visible: bool,
+    target: u16,
+    offers: Vec<TrainerOfferEntry>,
+    hovered: Option<usize>,
+    pending_actions: Vec<WidgetAction>,
+}
+
+impl TrainerPopup {
+    /// Creates a hidden popup.
+    ///
+    /// # Returns
+    ///
+    /// * A new, closed `TrainerPopup`.
+    pub fn new() -> Self {
+        Self {
+            bounds: Bounds::new(0, 0, POPUP_W, 0),
+            visible: false,
+            target: 0,
+            offers: Vec::new(),
+            hovered: None,
+            pending_actions: Vec::new(),
+        }
+    }
+
+    /// Shows `offers` for trainer `target`. A refresh for the trainer that
+    /// is already shown keeps the popup where it is; otherwise it opens at
+    /// `(x, y)`, clamped to the viewport.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Preferred left edge.
+    /// * `y` - Preferred top edge.
+    /// * `target` - Trainer character number.
+    /// * `offers` - Offers from `SV_TRAINEROFFERS`.
+    pub fn open(&mut self, x: i32, y: i32, target: u16, offers: Vec<TrainerOfferEntry>) {
+        if offers.is_empty() {
+            self.close();
+            return;
+        }
+        let (x, y) = if self.visible && self.target == target {
+            (self.bounds.x, self.bounds.y)
+        } else {
+            (x, y)
+        };
+        let height = (HEADER_H + offers.len() as i32 * ROW_H + 2) as u32;
+        let (cx, cy) = clamp_to_viewport(x, y, POPUP_W, height);
+        self.bounds = Bounds::new(cx, cy, POPUP_W, height);
+        self.target = target;
+        self.offers = offers;
+        self.hovered = None;
+        self.visible = true;
+    }
+
+    /// Closes the popup.
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.hovered = None;
+    }
+
+    /// Returns `true` while the popup is open.
+    ///
+    /// # Returns
+    ///
+    /// * The visibility flag.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Returns the offer index under `(x, y)`, if any.
+    fn row_at(&self, x: i32, y: i32) -> Option<usize> {
+        if !self.bounds.contains_point(x, y) {
+            return None;
+        }
+        let rel = y - self.bounds.y - HEADER_H;
+        if rel < 0 {
+            return None;
+        }
+        let idx = (rel / ROW_H) as usize;
+        (idx < self.offers.len()).then_some(idx)
+    }
+}
+
+impl Default for TrainerPopup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for TrainerPopup {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+        match event {
+            UiEvent::MouseMove { x, y } => {
+                self.hovered = self.row_at(*x, *y);
+                EventResponse::Ignored
+            }
+            UiEvent::MouseDown { x, y, .. } if self.bounds.contains_point(*x, *y) => {
+                EventResponse::Consumed
+            }
+            UiEvent::MouseClick { x, y, button, .. } => {
+                if !self.bounds.contains_point(*x, *y) {
+                    self.close();
+                    return EventResponse::Consumed;
+                }
+                if let (MouseButton::Left, Some(idx)) = (button, self.row_at(*x, *y)) {
+                    let offer = self.offers[idx];
+                    if offer.status == OfferStatus::Available {
+                        self.pending_actions.push(WidgetAction::LearnSkill {
+                            target: self.target,
+                            skill: offer.skill,
+                        });
+                    }
+                }
+                EventResponse::Consumed
+            }
+            UiEvent::KeyDown {
+                keycode: Keycode::Escape,
+                ..
+            } => {
+                self.close();
+                EventResponse::Consumed
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+        let rect = sdl2::rect::Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        );
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(POPUP_BG);
+        ctx.canvas.fill_rect(rect)?;
+
+        let text_x = self.bounds.x + H_INSET;
+        let right = self.bounds.x + self.bounds.width as i32 - H_INSET;
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            POPUP_FONT,
+            "Train skills",
+            text_x,
+            self.bounds.y + 3,
+            font_cache::TextStyle::drop_shadow(),
+        )?;
+
+        for (idx, offer) in self.offers.iter().enumerate() {
+            let top = self.bounds.y + HEADER_H + idx as i32 * ROW_H;
+            let available = offer.status == OfferStatus::Available;
+            if available && self.hovered == Some(idx) {
+                ctx.canvas.set_draw_color(HOVER_BG);
+                ctx.canvas.fill_rect(sdl2::rect::Rect::new(
+                    self.bounds.x + 1,
+                    top,
+                    self.bounds.width - 2,
+                    ROW_H as u32,
+                ))?;
+            }
+            let style = if available {
+                font_cache::TextStyle::PLAIN
+            } else {
+                font_cache::TextStyle::faded(UNAVAILABLE_ALPHA)
+            };
+
+            let right_label = if available {
+                format_price(offer.price)
+            } else {
+                offer.status.label().to_owned()
+            };
+            let right_w = font_cache::text_width(&right_label) as i32;
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                POPUP_FONT,
+                &right_label,
+                right - right_w,
+                top + 2,
+                style,
+            )?;
+            let name = font_cache::fit_text_bitmap(
+                skills::get_skill_name(offer.skill as usize),
+                right - right_w - H_INSET - text_x,
+            );
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                POPUP_FONT,
+                &name,
+                text_x,
+                top + 2,
+                style,
+            )?;
+        }
+
+        ctx.canvas.set_draw_color(POPUP_BORDER);
+        ctx.canvas.draw_rect(rect)?;
+        Ok(())
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widget::KeyModifiers;
+
+    fn click(x: i32, y: i32) -> UiEvent {
+        UiEvent::MouseClick {
+            x,
+            y,
+            button: MouseButton::Left,
+            modifiers: KeyModifiers::default(),
+        }
+    }
+
+    fn offers() -> Vec<TrainerOfferEntry> {
+        vec![
+            TrainerOfferEntry {
+                skill: skills::SK_REST as u8,
+                price: 2_500,
+                status: OfferStatus::Known,
+            },
+            TrainerOfferEntry {
+                skill: skills::SK_MEDIT as u8,
+                price: 10_000,
+                status: OfferStatus::Available,
+            },
+        ]
+    }
+
+    #[test]
+    fn only_available_offers_emit_learn_actions() {
+        let mut popup = TrainerPopup::new();
+        popup.open(100, 100, 7, offers());
+        let row_y = |idx: i32| popup.bounds().y + HEADER_H + idx * ROW_H + 2;
+
+        let (y0, y1) = (row_y(0), row_y(1));
+        assert_eq!(popup.handle_event(&click(110, y0)), EventResponse::Consumed);
+        assert!(popup.take_actions().is_empty());
+
+        assert_eq!(popup.handle_event(&click(110, y1)), EventResponse::Consumed);
+        assert!(matches!(
+            popup.take_actions().as_slice(),
+            [WidgetAction::LearnSkill { target: 7, skill }] if *skill == skills::SK_MEDIT as u8
+        ));
+        assert!(popup.is_visible());
+    }
+
+    #[test]
+    fn refresh_keeps_position_and_outside_click_closes() {
+        let mut popup = TrainerPopup::new();
+        popup.open(100, 100, 7, offers());
+        let (x, y) = (popup.bounds().x, popup.bounds().y);
+        popup.open(300, 300, 7, offers());
+        assert_eq!((popup.bounds().x, popup.bounds().y), (x, y));
+
+        assert_eq!(popup.handle_event(&click(1, 1)), EventResponse::Consumed);
+        assert!(!popup.is_visible());
+    }
+
+    #[test]
+    fn price_formatting() {
+        assert_eq!(format_price(2_500), "25G");
+        assert_eq!(format_price(2_550), "25G 50S");
+    }
+}
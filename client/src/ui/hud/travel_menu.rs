@@ -0,0 +1,353 @@
+//! Small travel menu listing the player's bookmarks.
+//!
+//! Opened with `/travel`. Left-clicking a bookmark auto-walks there
+//! ([`WidgetAction::TravelTo`]), right-clicking deletes it
+//! ([`WidgetAction::RemoveBookmark`]). The footer row bookmarks the current
+//! position ([`WidgetAction::BookmarkHere`]), or stops the walk in progress
+//! ([`WidgetAction::StopAutoWalk`]).
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+
+use crate::font_cache;
+use crate::ui::RenderContext;
+use crate::ui::widget::{Bounds, EventResponse, MouseButton, UiEvent, Widget, WidgetAction};
+
+/// Font index used for menu text (yellow bitmap font).
+const MENU_FONT: usize = 1;
+
+/// Menu width in logical pixels.
+pub const TRAVEL_MENU_W: u32 = 150;
+
+/// Height of the header row.
+const HEADER_H: i32 = 14;
+
+/// Height of a bookmark or footer row.
+const ROW_H: i32 = 12;
+
+/// Inner horizontal padding.
+const H_INSET: i32 = 4;
+
+/// Maximum number of bookmarks listed.
+const MAX_MENU_ROWS: usize = 10;
+
+/// Background fill.
+const MENU_BG: Color = Color::RGBA(10, 10, 30, 170);
+
+/// Highlight behind the hovered row.
+const HOVER_HIGHLIGHT: Color = Color::RGBA(80, 80, 30, 200);
+
+/// Tint for coordinates and the footer row.
+const DETAIL_COLOR: Color = Color::RGB(170, 220, 170);
+
+/// One bookmark line in the menu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TravelMenuEntry {
+    /// Bookmark name.
+    pub name: String,
+    /// World tile.
+    pub x: u16,
+    /// World tile.
+    pub y: u16,
+}
+
+/// The travel menu overlay.
+pub struct TravelMenu {
+    bounds: Bounds,
+    visible: bool,
+    entries: Vec<TravelMenuEntry>,
+    /// Destination of the walk in progress, if any.
+    walking_to: Option<String>,
+    hovered_row: Option<usize>,
+    pending_actions: Vec<WidgetAction>,
+}
+
+impl TravelMenu {
+    /// Creates a hidden, empty menu.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Left edge in logical pixels.
+    /// * `y` - Top edge in logical pixels.
+    ///
+    /// # Returns
+    ///
+    /// * A new `TravelMenu`.
+    pub fn new(x: i32, y: i32) -> Self {
+        let mut menu = Self {
+            bounds: Bounds::new(x, y, TRAVEL_MENU_W, 0),
+            visible: false,
+            entries: Vec::new(),
+            walking_to: None,
+            hovered_row: None,
+            pending_actions: Vec::new(),
+        };
+        menu.recompute_height();
+        menu
+    }
+
+    /// Returns `true` while the menu is shown.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows or hides the menu.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.hovered_row = None;
+    }
+
+    /// Replaces the listed bookmarks and the walk status.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Bookmarks, in settings order.
+    /// * `walking_to` - Destination of the walk in progress, if any.
+    pub fn update_data(&mut self, entries: Vec<TravelMenuEntry>, walking_to: Option<String>) {
+        self.entries = entries;
+        self.walking_to = walking_to;
+        self.recompute_height();
+    }
+
+    /// Number of bookmark rows drawn.
+    fn visible_rows(&self) -> usize {
+        self.entries.len().min(MAX_MENU_ROWS)
+    }
+
+    /// Resizes the bounds to fit the header, bookmarks and footer.
+    fn recompute_height(&mut self) {
+        self.bounds.height = (HEADER_H + (self.visible_rows() as i32 + 1) * ROW_H + 2) as u32;
+    }
+
+    /// Y coordinate (top edge) of row `idx`; the footer follows the last
+    /// bookmark.
+    fn row_y(&self, idx: usize) -> i32 {
+        self.bounds.y + HEADER_H + idx as i32 * ROW_H
+    }
+
+    /// Row under `y`: bookmarks first, then the footer at
+    /// `visible_rows()`.
+    fn row_at(&self, y: i32) -> Option<usize> {
+        let offset = y - self.bounds.y - HEADER_H;
+        if offset < 0 {
+            return None;
+        }
+        let idx = (offset / ROW_H) as usize;
+        (idx <= self.visible_rows()).then_some(idx)
+    }
+
+    /// Text of the footer row.
+    fn footer_label(&self) -> &'static str {
+        if self.walking_to.is_some() {
+            "Stop walking"
+        } else {
+            "+ Mark this spot"
+        }
+    }
+}
+
+impl Widget for TravelMenu {
+    fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    fn set_position(&mut self, x: i32, y: i32) {
+        self.bounds.x = x;
+        self.bounds.y = y;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> EventResponse {
+        if !self.visible {
+            return EventResponse::Ignored;
+        }
+        match event {
+            UiEvent::MouseMove { x, y } => {
+                self.hovered_row = if self.bounds.contains_point(*x, *y) {
+                    self.row_at(*y)
+                } else {
+                    None
+                };
+                EventResponse::Ignored
+            }
+            UiEvent::MouseClick { x, y, button, .. } => {
+                if !self.bounds.contains_point(*x, *y) {
+                    return EventResponse::Ignored;
+                }
+                let Some(idx) = self.row_at(*y) else {
+                    return EventResponse::Consumed;
+                };
+                let action = match button {
+                    MouseButton::Left if idx == self.visible_rows() => {
+                        if self.walking_to.is_some() {
+                            Some(WidgetAction::StopAutoWalk)
+                        } else {
+                            Some(WidgetAction::BookmarkHere)
+                        }
+                    }
+                    MouseButton::Left => Some(WidgetAction::TravelTo { index: idx }),
+                    MouseButton::Right if idx < self.visible_rows() => {
+                        Some(WidgetAction::RemoveBookmark { index: idx })
+                    }
+                    _ => None,
+                };
+                self.pending_actions.extend(action);
+                EventResponse::Consumed
+            }
+            _ => EventResponse::Ignored,
+        }
+    }
+
+    fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(MENU_BG);
+        ctx.canvas.fill_rect(Rect::new(
+            self.bounds.x,
+            self.bounds.y,
+            self.bounds.width,
+            self.bounds.height,
+        ))?;
+
+        let text_x = self.bounds.x + H_INSET;
+        let right = self.bounds.x + self.bounds.width as i32 - H_INSET;
+        let header = match &self.walking_to {
+            Some(name) => format!("Travel: to {name}"),
+            None => "Travel".to_owned(),
+        };
+        let header = font_cache::fit_text_bitmap(&header, right - text_x);
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            MENU_FONT,
+            &header,
+            text_x,
+            self.bounds.y + 2,
+            font_cache::TextStyle::drop_shadow(),
+        )?;
+
+        if let Some(idx) = self.hovered_row {
+            ctx.canvas.set_draw_color(HOVER_HIGHLIGHT);
+            ctx.canvas.fill_rect(Rect::new(
+                self.bounds.x + 1,
+                self.row_y(idx),
+                self.bounds.width.saturating_sub(2),
+                ROW_H as u32,
+            ))?;
+        }
+
+        for idx in 0..self.visible_rows() {
+            let entry = &self.entries[idx];
+            let top = self.row_y(idx);
+            let coords = format!("{},{}", entry.x, entry.y);
+            let coords_w = font_cache::text_width(&coords) as i32;
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                MENU_FONT,
+                &coords,
+                right - coords_w,
+                top + 1,
+                font_cache::TextStyle::tinted(DETAIL_COLOR),
+            )?;
+            let name =
+                font_cache::fit_text_bitmap(&entry.name, right - text_x - coords_w - H_INSET);
+            font_cache::draw_text(
+                ctx.canvas,
+                ctx.gfx,
+                MENU_FONT,
+                &name,
+                text_x,
+                top + 1,
+                font_cache::TextStyle::PLAIN,
+            )?;
+        }
+
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            MENU_FONT,
+            self.footer_label(),
+            text_x,
+            self.row_y(self.visible_rows()) + 1,
+            font_cache::TextStyle::tinted(DETAIL_COLOR),
+        )
+    }
+
+    fn take_actions(&mut self) -> Vec<WidgetAction> {
+        std::mem::take(&mut self.pending_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::widget::KeyModifiers;
+
+    fn click(y: i32, button: MouseButton) -> UiEvent {
+        UiEvent::MouseClick {
+            x: 5,
+            y,
+            button,
+            modifiers: KeyModifiers::default(),
+        }
+    }
+
+    fn menu() -> TravelMenu {
+        let mut m = TravelMenu::new(0, 0);
+        m.toggle();
+        m.update_data(
+            vec![
+                TravelMenuEntry {
+                    name: "Home".into(),
+                    x: 10,
+                    y: 20,
+                },
+                TravelMenuEntry {
+                    name: "Bank".into(),
+                    x: 30,
+                    y: 40,
+                },
+            ],
+            None,
+        );
+        m
+    }
+
+    #[test]
+    fn rows_travel_remove_and_mark() {
+        let mut m = menu();
+        m.handle_event(&click(m.row_y(1) + 1, MouseButton::Left));
+        m.handle_event(&click(m.row_y(0) + 1, MouseButton::Right));
+        m.handle_event(&click(m.row_y(2) + 1, MouseButton::Left));
+        assert!(matches!(
+            m.take_actions().as_slice(),
+            [
+                WidgetAction::TravelTo { index: 1 },
+                WidgetAction::RemoveBookmark { index: 0 },
+                WidgetAction::BookmarkHere
+            ]
+        ));
+
+        // While walking the footer stops the walk instead.
+        m.update_data(Vec::new(), Some("Bank".into()));
+        m.handle_event(&click(m.row_y(0) + 1, MouseButton::Left));
+        assert!(matches!(
+            m.take_actions().as_slice(),
+            [WidgetAction::StopAutoWalk]
+        ));
+    }
+
+    #[test]
+    fn hidden_menu_ignores_clicks() {
+        let mut m = menu();
+        m.toggle();
+        assert_eq!(
+            m.handle_event(&click(m.row_y(0) + 1, MouseButton::Left)),
+            EventResponse::Ignored
+        );
+    }
+}
@@ -167,6 +167,10 @@ impl Widget for WeaponArmorPanel {
         self.bounds.y = y;
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     /// Handle input events.
     ///
     /// Clicks within the panel bounds are consumed to prevent world
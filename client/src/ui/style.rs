@@ -80,6 +80,56 @@ pub struct Border {
     pub width: u32,
 }
 
+/// SDL tint for a profile name color.
+///
+/// # Arguments
+///
+/// * `color` - The player's selected [`NameColor`](mag_core::profile::NameColor).
+///
+/// # Returns
+///
+/// * `Some(Color)` for tinted palette entries, `None` for the default font color.
+pub fn name_color_tint(color: mag_core::profile::NameColor) -> Option<Color> {
+    color.rgb().map(|(r, g, b)| Color::RGB(r, g, b))
+}
+
+/// Tint applied to GM chat lines (see [`mag_core::chat::CHAT_STYLE_GM`]).
+pub const CHAT_GM_TINT: Color = Color::RGB(255, 215, 90);
+
+/// Background bar drawn behind GM chat lines.
+pub const CHAT_GM_HIGHLIGHT: Color = Color::RGBA(120, 90, 20, 110);
+
+/// Tint of item links in chat lines.
+pub const CHAT_ITEM_LINK_TINT: Color = Color::RGB(120, 200, 255);
+
+/// SDL tint for a styled chat channel.
+///
+/// # Arguments
+///
+/// * `channel` - Channel from the message's [`ChatStyle`](mag_core::chat::ChatStyle).
+///
+/// # Returns
+///
+/// * `Some(Color)` for channels with their own color, `None` to keep the
+///   channel's base font color.
+pub fn chat_channel_tint(channel: mag_core::chat::ChatChannel) -> Option<Color> {
+    use mag_core::chat::ChatChannel;
+    match channel {
+        ChatChannel::Tell => Some(Color::RGB(255, 150, 230)),
+        ChatChannel::Shout => Some(Color::RGB(255, 175, 90)),
+        ChatChannel::Staff => Some(Color::RGB(120, 230, 255)),
+        ChatChannel::Imp => Some(Color::RGB(190, 160, 255)),
+        ChatChannel::Reward => Some(Color::RGB(255, 215, 90)),
+        ChatChannel::Guild => Some(Color::RGB(130, 235, 150)),
+        ChatChannel::System
+        | ChatChannel::Say
+        | ChatChannel::Group
+        | ChatChannel::Announce
+        | ChatChannel::Npc
+        | ChatChannel::Combat => None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -106,6 +156,27 @@ mod tests {
         assert_eq!(p.bottom, 6);
     }
 
+    #[test]
+    fn name_color_tint_default_is_untinted() {
+        use mag_core::profile::NameColor;
+        assert!(name_color_tint(NameColor::Default).is_none());
+        assert_eq!(
+            name_color_tint(NameColor::Crimson),
+            Some(Color::RGB(220, 60, 60))
+        );
+    }
+
+    #[test]
+    fn chat_channel_tint_keeps_base_color_for_say() {
+        use mag_core::chat::ChatChannel;
+        assert!(chat_channel_tint(ChatChannel::Say).is_none());
+        assert!(chat_channel_tint(ChatChannel::Tell).is_some());
+        assert_ne!(
+            chat_channel_tint(ChatChannel::Shout),
+            chat_channel_tint(ChatChannel::Staff)
+        );
+    }
+
     #[test]
     fn zero_padding() {
         let p = Padding::ZERO;
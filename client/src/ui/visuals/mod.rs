@@ -1,6 +1,7 @@
 pub mod panning_background;
 pub mod rank_progress_line;
 pub mod rank_sigil;
+pub mod skill_timers;
 pub mod spell_effect_icons;
 pub mod spell_icons;
 pub mod vitality_bars;
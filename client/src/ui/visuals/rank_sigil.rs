@@ -202,6 +202,10 @@ impl Widget for RankSigil {
         self.bounds.y = y;
     }
 
+    fn set_background(&mut self, color: Color) {
+        self.bg_color = color;
+    }
+
     /// Handle mouse events.
     ///
     /// Hover state is updated on every [`UiEvent::MouseMove`]. Clicks that
@@ -0,0 +1,207 @@
+//! Cast-progress bar and cooldown sweeps for skills.
+//!
+//! Both are driven by the `SV_SKILLTIMER` timers kept in `PlayerState`:
+//!
+//! - [`draw_cooldown_sweep`] darkens the part of a skill-bar cell that is
+//!   still recharging, as a clockwise sweep starting at 12 o'clock.
+//! - [`CastBar`] shows the name and progress of the skill being cast above
+//!   the player.
+
+use std::f32::consts::TAU;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+use crate::font_cache;
+use crate::ui::RenderContext;
+
+/// Shade laid over the recharging part of a cell.
+const SWEEP_COLOR: Color = Color::RGBA(0, 0, 0, 150);
+
+/// Width of the cast bar in pixels.
+const CAST_BAR_W: u32 = 64;
+
+/// Height of the cast bar in pixels.
+const CAST_BAR_H: u32 = 6;
+
+/// Cast bar background track.
+const CAST_TRACK_COLOR: Color = Color::RGBA(20, 20, 30, 200);
+
+/// Cast bar fill.
+const CAST_FILL_COLOR: Color = Color::RGB(220, 180, 60);
+
+/// Cast bar outline.
+const CAST_BORDER_COLOR: Color = Color::RGBA(80, 80, 100, 220);
+
+/// Text color for the skill name above the cast bar.
+const CAST_TEXT_COLOR: Color = Color::RGB(220, 200, 140);
+
+/// Bitmap font index (yellow, sprite 701).
+const UI_FONT: usize = 1;
+
+/// Returns `true` if pixel `(px, py)` of `rect` lies in the shaded part of
+/// a cooldown sweep.
+///
+/// # Arguments
+///
+/// * `rect` - Cell being shaded.
+/// * `fraction` - Share of the cooldown still to run (`1.0` = all shaded).
+/// * `px`, `py` - Pixel to test.
+///
+/// # Returns
+///
+/// * `true` when the pixel's clockwise angle from 12 o'clock falls in the
+///   last `fraction` of the turn.
+fn sweep_covers(rect: Rect, fraction: f32, px: i32, py: i32) -> bool {
+    let center = rect.center();
+    let dx = (px - center.x()) as f32;
+    let dy = (py - center.y()) as f32;
+    let angle = dx.atan2(-dy).rem_euclid(TAU);
+    angle >= (1.0 - fraction.clamp(0.0, 1.0)) * TAU
+}
+
+/// Shades the recharging part of a skill-bar cell.
+///
+/// # Arguments
+///
+/// * `canvas` - Target canvas.
+/// * `rect` - Cell to shade.
+/// * `fraction` - Share of the cooldown still to run, `0.0..=1.0`.
+pub fn draw_cooldown_sweep(
+    canvas: &mut Canvas<Window>,
+    rect: Rect,
+    fraction: f32,
+) -> Result<(), String> {
+    if fraction <= 0.0 {
+        return Ok(());
+    }
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(SWEEP_COLOR);
+    if fraction >= 1.0 {
+        return canvas.fill_rect(rect);
+    }
+
+    // Fan of lines from the centre to every shaded border pixel.
+    let center = rect.center();
+    let (left, top) = (rect.x(), rect.y());
+    let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+    let border = (left..=right)
+        .flat_map(|x| [(x, top), (x, bottom)])
+        .chain((top + 1..bottom).flat_map(|y| [(left, y), (right, y)]));
+    for (x, y) in border {
+        if sweep_covers(rect, fraction, x, y) {
+            canvas.draw_line(center, (x, y))?;
+        }
+    }
+    Ok(())
+}
+
+/// Progress bar for the skill being cast, drawn above the player.
+///
+/// Modify the public [`x`] and [`y`] fields to reposition it.
+///
+/// [`x`]: CastBar::x
+/// [`y`]: CastBar::y
+pub struct CastBar {
+    /// Horizontal centre in logical pixels.
+    pub x: i32,
+    /// Top of the bar in logical pixels.
+    pub y: i32,
+    /// Skill name and progress (`0.0..=1.0`) of the running cast.
+    cast: Option<(String, f32)>,
+}
+
+impl CastBar {
+    /// Creates a hidden cast bar centred at `x` with its top at `y`.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Horizontal centre.
+    /// * `y` - Top of the bar.
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y, cast: None }
+    }
+
+    /// Updates the cast shown by the bar.
+    ///
+    /// # Arguments
+    ///
+    /// * `cast` - Skill name and progress, or `None` to hide the bar.
+    pub fn sync(&mut self, cast: Option<(&str, f32)>) {
+        self.cast = cast.map(|(name, progress)| (name.to_owned(), progress.clamp(0.0, 1.0)));
+    }
+
+    /// Draws the bar and the skill name above it while a cast is running.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Render context.
+    pub fn render(&mut self, ctx: &mut RenderContext<'_, '_>) -> Result<(), String> {
+        let Some((name, progress)) = &self.cast else {
+            return Ok(());
+        };
+        let track = Rect::new(
+            self.x - CAST_BAR_W as i32 / 2,
+            self.y,
+            CAST_BAR_W,
+            CAST_BAR_H,
+        );
+        ctx.canvas.set_blend_mode(BlendMode::Blend);
+        ctx.canvas.set_draw_color(CAST_TRACK_COLOR);
+        ctx.canvas.fill_rect(track)?;
+
+        let fill_w = ((CAST_BAR_W - 2) as f32 * progress).round() as u32;
+        if fill_w > 0 {
+            ctx.canvas.set_draw_color(CAST_FILL_COLOR);
+            ctx.canvas.fill_rect(Rect::new(
+                track.x() + 1,
+                track.y() + 1,
+                fill_w,
+                CAST_BAR_H - 2,
+            ))?;
+        }
+        ctx.canvas.set_draw_color(CAST_BORDER_COLOR);
+        ctx.canvas.draw_rect(track)?;
+
+        font_cache::draw_text(
+            ctx.canvas,
+            ctx.gfx,
+            UI_FONT,
+            name,
+            self.x,
+            self.y - 11,
+            font_cache::TextStyle::centered()
+                .with_tint(CAST_TEXT_COLOR)
+                .with_drop_shadow(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweep_shades_the_trailing_part_of_the_turn() {
+        let rect = Rect::new(0, 0, 30, 30);
+        // Three quarters left: everything but the top-right quadrant.
+        assert!(!sweep_covers(rect, 0.75, 25, 2));
+        assert!(sweep_covers(rect, 0.75, 25, 28));
+        assert!(sweep_covers(rect, 0.75, 2, 2));
+        // A quarter left: only the top-left quadrant.
+        assert!(sweep_covers(rect, 0.25, 2, 2));
+        assert!(!sweep_covers(rect, 0.25, 2, 28));
+        assert!(!sweep_covers(rect, 0.0, 2, 2));
+    }
+
+    #[test]
+    fn cast_bar_clamps_progress() {
+        let mut bar = CastBar::new(100, 50);
+        bar.sync(Some(("Heal", 1.5)));
+        assert_eq!(bar.cast, Some(("Heal".to_owned(), 1.0)));
+        bar.sync(None);
+        assert!(bar.cast.is_none());
+    }
+}
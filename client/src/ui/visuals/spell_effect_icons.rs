@@ -10,12 +10,12 @@
 //! has been observed, e.g. `"Bless (~1m 30s)"`.
 
 use std::collections::HashMap;
-use std::time::Instant;
 
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::BlendMode;
 
+use crate::network::clock_sync::ServerTime;
 use crate::ui::RenderContext;
 use crate::ui::visuals::spell_icons::{
     SpellIconMeta, active_spell_effect_icon_meta, spell_icon_path,
@@ -201,8 +201,9 @@ struct DurationTracker {
     sprite: i16,
     /// Fill fraction at the most recent observed fill change.
     last_fill: f32,
-    /// Instant when `last_fill` was observed.
-    last_change_at: Instant,
+    /// Server time (seconds, see [`ServerTime`]) when `last_fill` was
+    /// observed.
+    last_change_at: f64,
     /// Estimated fill-fraction decay rate per second.
     rate_per_sec: Option<f32>,
 }
@@ -214,12 +215,12 @@ impl DurationTracker {
     ///
     /// * `sprite` - Sprite tile number of the effect occupying the slot.
     /// * `fill` - Current fill fraction in `[0.0, 1.0]`.
-    /// * `now` - Observation time.
+    /// * `now` - Observation time in server seconds.
     ///
     /// # Returns
     ///
     /// * A tracker without a decay-rate estimate yet.
-    fn new(sprite: i16, fill: f32, now: Instant) -> Self {
+    fn new(sprite: i16, fill: f32, now: f64) -> Self {
         Self {
             sprite,
             last_fill: fill,
@@ -238,8 +239,8 @@ impl DurationTracker {
     /// # Arguments
     ///
     /// * `fill` - Current fill fraction in `[0.0, 1.0]`.
-    /// * `now` - Observation time.
-    fn update(&mut self, fill: f32, now: Instant) {
+    /// * `now` - Observation time in server seconds.
+    fn update(&mut self, fill: f32, now: f64) {
         if fill > self.last_fill + FILL_EPSILON {
             self.last_fill = fill;
             self.last_change_at = now;
@@ -248,7 +249,7 @@ impl DurationTracker {
         }
 
         if fill < self.last_fill - FILL_EPSILON {
-            let elapsed = now.duration_since(self.last_change_at).as_secs_f32();
+            let elapsed = (now - self.last_change_at) as f32;
             if elapsed > 0.1 {
                 self.rate_per_sec = Some((self.last_fill - fill) / elapsed);
             }
@@ -259,15 +260,19 @@ impl DurationTracker {
 
     /// Estimates remaining seconds from the most recent fill change.
     ///
+    /// # Arguments
+    ///
+    /// * `now` - Current server time in seconds.
+    ///
     /// # Returns
     ///
     /// * `Some(seconds)` once a decay rate has been observed, `None` otherwise.
-    fn remaining_secs(&self) -> Option<f32> {
+    fn remaining_secs(&self, now: f64) -> Option<f32> {
         let rate = self.rate_per_sec?;
         if rate <= 0.0 {
             return None;
         }
-        let elapsed_since_change = self.last_change_at.elapsed().as_secs_f32();
+        let elapsed_since_change = (now - self.last_change_at).max(0.0) as f32;
         Some((self.last_fill / rate - elapsed_since_change).max(0.0))
     }
 }
@@ -299,6 +304,8 @@ pub struct SpellEffectIcons {
     hovered: Option<HoveredIcon>,
     /// Observed decay trackers keyed by spell slot index.
     duration_trackers: HashMap<usize, DurationTracker>,
+    /// Server clock reading from the latest [`Self::sync`].
+    now: ServerTime,
     /// Lazily-loaded texture IDs for spell icons, keyed by icon filename.
     ///
     /// Keying by filename rather than the server-provided `sprite[1]` value avoids
@@ -329,6 +336,7 @@ impl SpellEffectIcons {
             negatives: Vec::new(),
             hovered: None,
             duration_trackers: HashMap::new(),
+            now: ServerTime::default(),
             icon_texture_ids: HashMap::new(),
         }
     }
@@ -345,11 +353,23 @@ impl SpellEffectIcons {
     /// * `spell` - The character's 20-element spell item-index array.
     /// * `active` - The character's 20-element active-fraction array (`0..=16`).
     /// * `spell_type` - The corresponding `SK_*` skill-number for each slot.
-    pub fn sync(&mut self, spell: &[i32; 20], active: &[i8; 20], spell_type: &[i16; 20]) {
+    /// * `now` - Current server time from [`crate::network::clock_sync::ClockSync`];
+    ///   decay samples from an older clock generation are discarded.
+    pub fn sync(
+        &mut self,
+        spell: &[i32; 20],
+        active: &[i8; 20],
+        spell_type: &[i16; 20],
+        now: ServerTime,
+    ) {
         self.positives.clear();
         self.negatives.clear();
 
-        let now = Instant::now();
+        if now.generation != self.now.generation {
+            self.duration_trackers.clear();
+        }
+        self.now = now;
+        let now = now.secs;
         let mut active_slots = Vec::new();
         for i in 0..20usize {
             if spell[i] <= 0 || active[i] <= 0 {
@@ -392,8 +412,8 @@ impl SpellEffectIcons {
     /// * `slot_index` - Spell slot index in the server-provided arrays.
     /// * `sprite` - Sprite tile number of the effect occupying the slot.
     /// * `fill` - Current fill fraction in `[0.0, 1.0]`.
-    /// * `now` - Observation time.
-    fn update_duration_tracker(&mut self, slot_index: usize, sprite: i16, fill: f32, now: Instant) {
+    /// * `now` - Observation time in server seconds.
+    fn update_duration_tracker(&mut self, slot_index: usize, sprite: i16, fill: f32, now: f64) {
         match self.duration_trackers.get_mut(&slot_index) {
             Some(tracker) if tracker.sprite == sprite => tracker.update(fill, now),
             _ => {
@@ -513,7 +533,7 @@ impl SpellEffectIcons {
         if let Some(remaining_secs) = self
             .duration_trackers
             .get(&entry.slot_index)
-            .and_then(DurationTracker::remaining_secs)
+            .and_then(|tracker| tracker.remaining_secs(self.now.secs))
         {
             return Some(format!(
                 "{} (~{})",
@@ -657,6 +677,13 @@ impl SpellEffectIcons {
 mod tests {
     use super::*;
 
+    fn at(secs: f64) -> ServerTime {
+        ServerTime {
+            secs,
+            generation: 0,
+        }
+    }
+
     fn make_spell_state(
         idx: usize,
         spell_val: i32,
@@ -685,7 +712,7 @@ mod tests {
         active[1] = 4;
         spell_type[1] = skills::SK_CURSE as i16;
 
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
 
         assert_eq!(icons.positives.len(), 1);
         assert_eq!(icons.negatives.len(), 1);
@@ -697,7 +724,7 @@ mod tests {
     fn fill_fraction() {
         let mut icons = SpellEffectIcons::new(100, 700, 500);
         let (spell, active, spell_type) = make_spell_state(0, 1, 8, skills::SK_PROTECT as i16);
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
         assert!((icons.positives[0].fill - 0.5).abs() < 0.01);
     }
 
@@ -723,7 +750,7 @@ mod tests {
     fn hover_text_format() {
         let mut icons = SpellEffectIcons::new(100, 700, 500);
         let (spell, active, spell_type) = make_spell_state(0, 1, 12, skills::SK_BLESS as i16);
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
         icons.hovered = Some(HoveredIcon {
             kind: SpellEffectKind::Positive,
             index: 0,
@@ -737,7 +764,7 @@ mod tests {
             DurationTracker {
                 sprite: 1,
                 last_fill: 0.75,
-                last_change_at: Instant::now(),
+                last_change_at: 0.0,
                 rate_per_sec: Some(1.0 / 120.0),
             },
         );
@@ -750,17 +777,15 @@ mod tests {
         let mut icons = SpellEffectIcons::new(100, 700, 500);
         let (mut spell, mut active, spell_type) =
             make_spell_state(0, 1, 16, skills::SK_PROTECT as i16);
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
         icons.hovered = Some(HoveredIcon {
             kind: SpellEffectKind::Positive,
             index: 0,
         });
 
-        icons.duration_trackers.get_mut(&0).unwrap().last_change_at =
-            Instant::now() - std::time::Duration::from_secs(30);
         active[0] = 15;
         spell[0] = 1;
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(30.0));
 
         assert_eq!(icons.hover_text().unwrap(), "Protection (~7m 30s)");
     }
@@ -769,14 +794,12 @@ mod tests {
     fn unchanged_fill_does_not_reset_decay_sample() {
         let mut icons = SpellEffectIcons::new(100, 700, 500);
         let (spell, mut active, spell_type) = make_spell_state(0, 1, 16, skills::SK_CURSE as i16);
-        icons.sync(&spell, &active, &spell_type);
-        icons.duration_trackers.get_mut(&0).unwrap().last_change_at =
-            Instant::now() - std::time::Duration::from_secs(30);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
         active[0] = 15;
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(30.0));
 
         let before = icons.duration_trackers.get(&0).unwrap().clone();
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(31.0));
         let after = icons.duration_trackers.get(&0).unwrap();
 
         assert_eq!(after.last_fill, before.last_fill);
@@ -784,13 +807,31 @@ mod tests {
         assert_eq!(after.last_change_at, before.last_change_at);
     }
 
+    #[test]
+    fn clock_generation_change_discards_decay_samples() {
+        let mut icons = SpellEffectIcons::new(100, 700, 500);
+        let (spell, mut active, spell_type) = make_spell_state(0, 1, 16, skills::SK_BLESS as i16);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
+        active[0] = 15;
+        icons.sync(&spell, &active, &spell_type, at(30.0));
+        assert!(icons.duration_trackers[&0].rate_per_sec.is_some());
+
+        let resynced = ServerTime {
+            secs: 5_000.0,
+            generation: 1,
+        };
+        icons.sync(&spell, &active, &spell_type, resynced);
+        assert!(icons.duration_trackers[&0].rate_per_sec.is_none());
+        assert_eq!(icons.duration_trackers[&0].last_change_at, 5_000.0);
+    }
+
     #[test]
     fn sync_caps_at_max_icons() {
         let mut icons = SpellEffectIcons::new(100, 700, 500);
         let spell = [1i32; 20];
         let active = [8i8; 20];
         let spell_type = [skills::SK_PROTECT as i16; 20];
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
         assert_eq!(icons.positives.len(), MAX_ICONS);
         assert_eq!(icons.negatives.len(), 0);
     }
@@ -811,7 +852,7 @@ mod tests {
         active[2] = 16;
         spell_type[2] = skills::SK_BLESS as i16;
 
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
         assert_eq!(icons.positives.len(), 1);
     }
 
@@ -851,7 +892,7 @@ mod tests {
     fn hover_hit_tests_full_icon_bounds() {
         let mut icons = SpellEffectIcons::new(100, 700, 500);
         let (spell, active, spell_type) = make_spell_state(0, 1, 16, skills::SK_WIMPY as i16);
-        icons.sync(&spell, &active, &spell_type);
+        icons.sync(&spell, &active, &spell_type, at(0.0));
         let rect = icons.icon_rect(SpellEffectKind::Negative, 0);
 
         assert!(icons.contains_point(rect.x(), rect.y()));
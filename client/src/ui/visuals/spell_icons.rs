@@ -10,7 +10,7 @@ use std::path::PathBuf;
 use mag_core::skills;
 use sdl2::pixels::Color;
 
-use crate::filepaths;
+use crate::asset_resolver;
 
 /// Display metadata for a spell icon asset.
 #[derive(Clone, Copy, Debug)]
@@ -329,10 +329,11 @@ pub fn active_spell_effect_icon_meta(skill_nr: usize, sprite: i16) -> Option<Spe
 ///
 /// * Path to the PNG under `assets/gfx/spells`.
 pub fn spell_icon_path(meta: SpellIconMeta) -> PathBuf {
-    filepaths::get_asset_directory()
-        .join("gfx")
-        .join("spells")
-        .join(meta.icon_filename)
+    asset_resolver::assets().resolve_or_report(
+        std::path::Path::new("gfx")
+            .join("spells")
+            .join(meta.icon_filename),
+    )
 }
 
 #[cfg(test)]
@@ -60,6 +60,18 @@ const END_COLOR: Color = Color::RGB(200, 180, 40);
 /// Mana chevron fill color.
 const MANA_COLOR: Color = Color::RGB(40, 80, 200);
 
+/// Track color used by the high-contrast HUD.
+const HC_TRACK_COLOR: Color = Color::RGB(0, 0, 0);
+
+/// HP fill color used by the high-contrast HUD.
+const HC_HP_COLOR: Color = Color::RGB(255, 60, 60);
+
+/// Endurance fill color used by the high-contrast HUD.
+const HC_END_COLOR: Color = Color::RGB(255, 235, 0);
+
+/// Mana fill color used by the high-contrast HUD.
+const HC_MANA_COLOR: Color = Color::RGB(0, 220, 255);
+
 /// Identifies which vitality chevron is currently hovered.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum HoveredChevron {
@@ -106,6 +118,8 @@ pub struct VitalityChevrons {
     mana_max: i32,
     /// Chevron currently under the cursor, if any.
     hovered: Option<HoveredChevron>,
+    /// Whether the high-contrast palette is used.
+    high_contrast: bool,
 }
 
 impl VitalityChevrons {
@@ -139,6 +153,7 @@ impl VitalityChevrons {
             mana_current: 0,
             mana_max: 0,
             hovered: None,
+            high_contrast: false,
         }
     }
 
@@ -354,7 +369,7 @@ impl VitalityChevrons {
     /// Inner geometry uses the same slope with a smaller half-width,
     /// `half_w - ARM_THICKNESS`, which creates the chevron band thickness.
     /// Pixels are filled left-to-right across the full outer span; pixels to
-    /// the right of the fill boundary use [`TRACK_COLOR`].
+    /// the right of the fill boundary use `track`.
     ///
     /// # Arguments
     ///
@@ -365,6 +380,8 @@ impl VitalityChevrons {
     /// * `height` - Vertical span from feet to tip.
     /// * `fill` - Fill fraction in `[0.0, 1.0]` (left-to-right).
     /// * `color` - Fill color for the filled portion.
+    /// * `track` - Color for the empty portion.
+    #[allow(clippy::too_many_arguments)]
     fn draw_chevron(
         canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
         cx: i32,
@@ -373,6 +390,7 @@ impl VitalityChevrons {
         height: i32,
         fill: f32,
         color: Color,
+        track: Color,
     ) -> Result<(), String> {
         if height <= 0 || half_w <= 0 {
             return Ok(());
@@ -414,18 +432,18 @@ impl VitalityChevrons {
                 let inner_right = cx + inner_offset;
 
                 for px in outer_left..inner_left {
-                    let c = if px <= fill_end { color } else { TRACK_COLOR };
+                    let c = if px <= fill_end { color } else { track };
                     canvas.set_draw_color(c);
                     canvas.draw_point(sdl2::rect::Point::new(px, y))?;
                 }
                 for px in (inner_right + 1)..=outer_right {
-                    let c = if px <= fill_end { color } else { TRACK_COLOR };
+                    let c = if px <= fill_end { color } else { track };
                     canvas.set_draw_color(c);
                     canvas.draw_point(sdl2::rect::Point::new(px, y))?;
                 }
             } else {
                 for px in outer_left..=outer_right {
-                    let c = if px <= fill_end { color } else { TRACK_COLOR };
+                    let c = if px <= fill_end { color } else { track };
                     canvas.set_draw_color(c);
                     canvas.draw_point(sdl2::rect::Point::new(px, y))?;
                 }
@@ -435,6 +453,24 @@ impl VitalityChevrons {
         Ok(())
     }
 
+    /// Switches between the normal and the high-contrast palette.
+    ///
+    /// # Arguments
+    ///
+    /// * `on` - `true` for bright fills on a black track.
+    pub fn set_high_contrast(&mut self, on: bool) {
+        self.high_contrast = on;
+    }
+
+    /// Returns the track, HP, endurance and mana colors in that order.
+    fn palette(&self) -> [Color; 4] {
+        if self.high_contrast {
+            [HC_TRACK_COLOR, HC_HP_COLOR, HC_END_COLOR, HC_MANA_COLOR]
+        } else {
+            [TRACK_COLOR, HP_COLOR, END_COLOR, MANA_COLOR]
+        }
+    }
+
     /// Draw the three nested chevrons onto the canvas.
     ///
     /// HP is outermost (widest / tallest), Endurance is middle, Mana is
@@ -455,10 +491,11 @@ impl VitalityChevrons {
         canvas.set_blend_mode(BlendMode::None);
         let layer_inset = ARM_THICKNESS + LAYER_GAP;
 
+        let [track, hp, end, mana] = self.palette();
         let layers: [(f32, Color, i32); 3] = [
-            (self.hp_fill, HP_COLOR, 0),
-            (self.end_fill, END_COLOR, layer_inset),
-            (self.mana_fill, MANA_COLOR, layer_inset * 2),
+            (self.hp_fill, hp, 0),
+            (self.end_fill, end, layer_inset),
+            (self.mana_fill, mana, layer_inset * 2),
         ];
 
         for (fill, color, inset) in layers {
@@ -470,7 +507,7 @@ impl VitalityChevrons {
             // Use rounded division and clamp to at least 1 so even very narrow
             // inner chevrons still render a visible tip row.
             let h = Self::chevron_height(hw);
-            Self::draw_chevron(canvas, self.x, self.y, hw, h, fill, color)?;
+            Self::draw_chevron(canvas, self.x, self.y, hw, h, fill, color, track)?;
         }
 
         Ok(())
@@ -571,4 +608,15 @@ mod tests {
         bars.handle_event(&UiEvent::MouseMove { x: 10, y: 10 });
         assert_eq!(bars.hover_text(), None);
     }
+
+    #[test]
+    fn high_contrast_swaps_palette() {
+        let mut bars = VitalityChevrons::new(100, 200);
+        assert_eq!(bars.palette()[1], HP_COLOR);
+        bars.set_high_contrast(true);
+        assert_eq!(
+            bars.palette(),
+            [HC_TRACK_COLOR, HC_HP_COLOR, HC_END_COLOR, HC_MANA_COLOR]
+        );
+    }
 }
@@ -4,6 +4,7 @@ use std::fmt;
 use std::time::Duration;
 
 use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
 use serde::{Deserialize, Serialize};
 
 use super::RenderContext;
@@ -241,6 +242,16 @@ pub enum HudPanel {
     Talents,
     /// Quest log overlay listing NPC quest givers.
     QuestLog,
+    /// Character profile editor (name color + bio).
+    Profile,
+    /// Guild name, message of the day and roster.
+    Guild,
+    /// Mailbox and letter compose line.
+    Mail,
+    /// Friends list with online state and quick-tell buttons.
+    Friends,
+    /// Per-fight damage summaries and CSV export.
+    CombatLog,
 }
 
 /// A side-effect that a widget wants the owning scene to perform.
@@ -251,6 +262,8 @@ pub enum HudPanel {
 pub enum WidgetAction {
     /// Send a chat message through the network.
     SendChat(String),
+    /// Focus the chat input with this text already typed, e.g. `#tell Name `.
+    StartChat(String),
     /// Toggle visibility of a HUD panel.
     TogglePanel(HudPanel),
     /// Commit pending stat/skill raises to the server.
@@ -300,6 +313,19 @@ pub enum WidgetAction {
         /// Target character.
         c: u32,
     },
+    /// Link a backpack item in the chat line being typed.
+    ///
+    /// Mapped to `ChatBox::insert_item_link(slot, name)` by the scene.
+    LinkItem {
+        /// Backpack slot of the item.
+        slot: usize,
+        /// Item name shown in the chat input.
+        name: String,
+    },
+    /// Ask the server to reorder the backpack.
+    ///
+    /// Mapped to `ClientCommand::new_sort_inventory(key)` by the scene.
+    SortInventory(mag_core::inventory_sort::InventorySortKey),
     /// Change the player's speed mode.
     ///
     /// Mapped to `ClientCommand::new_mode(mode)` by the scene.
@@ -329,24 +355,51 @@ pub enum WidgetAction {
     SetSpellEffects(bool),
     /// Toggle weather / ambient particle effects.
     SetWeather(bool),
+    /// Toggle streamer mode (chat masking, hidden gold, name alias).
+    SetStreamerMode(bool),
+    /// Toggle screen-reader mode (spoken chat, vitals and target).
+    SetScreenReader(bool),
+    /// Toggle the high-contrast HUD.
+    SetHighContrast(bool),
     /// Toggle overhead player name display.
     SetShowNames(bool),
     /// Toggle overhead health percentage display.
     SetShowHealthPct(bool),
     /// Toggle wall hiding.
     SetHideWalls(bool),
+    /// Change the wall fade radius in tiles (0 = off).
+    SetWallFadeRadius(u8),
     /// Change the master volume (0.0 = muted, 1.0 = full).
     SetMasterVolume(f32),
+    /// Change the music volume (0.0 = muted, 1.0 = full).
+    SetMusicVolume(f32),
+    /// Change the sound effect volume (0.0 = muted, 1.0 = full).
+    SetEffectsVolume(f32),
+    /// Change the interface click volume (0.0 = muted, 1.0 = full).
+    SetUiVolume(f32),
     /// Change the display mode (windowed, fullscreen, borderless).
     SetDisplayMode(DisplayMode),
     /// Toggle pixel-perfect (integer-only) scaling.
     SetPixelPerfectScaling(bool),
     /// Toggle vertical sync.
     SetVSync(bool),
+    /// Change the foreground frame rate cap (frames per second).
+    SetFpsCap(u32),
+    /// Toggle the reduced frame rate while the window is in the background.
+    SetBackgroundThrottle(bool),
+    /// Toggle low power mode (30 FPS cap, no shadows or particles).
+    SetLowPowerMode(bool),
     /// Toggle context-sensitive helper text near the cursor.
     SetShowHelperText(bool),
     /// Toggle rendering the cursor's logical screen coordinates as helper text.
     SetShowPositions(bool),
+    /// Show or hide one kind of minimap point of interest.
+    SetMinimapMarker {
+        /// Marker kind.
+        kind: mag_core::map_markers::MapMarkerKind,
+        /// Whether the kind is shown.
+        enabled: bool,
+    },
     /// Update a keyboard binding for a game action.
     UpdateKeyBinding {
         /// The action whose binding changed.
@@ -387,6 +440,65 @@ pub enum WidgetAction {
         /// NPC template ID of the quest giver to focus.
         npc_template_id: u16,
     },
+    /// Pick an entry of an NPC interaction menu.
+    ///
+    /// Mapped to `ClientCommand::new_npc_action(target, option)` by the
+    /// scene.
+    NpcMenuChoice {
+        /// Character number of the NPC the menu belongs to.
+        target: u16,
+        /// Chosen entry.
+        option: mag_core::npc_menu::NpcMenuOption,
+    },
+    /// Buy a skill from a trainer.
+    ///
+    /// Mapped to `ClientCommand::new_learn_skill(target, skill)` by the
+    /// scene.
+    LearnSkill {
+        /// Character number of the trainer.
+        target: u16,
+        /// Skill index of the chosen offer.
+        skill: u8,
+    },
+    /// Collapse (`true`) or expand (`false`) the on-screen quest tracker.
+    ///
+    /// Stored in the per-character settings by the scene.
+    SetQuestTrackerCollapsed(bool),
+    /// Save the player's profile from the profile editor.
+    ///
+    /// Mapped to `ClientCommand::new_profile_packets(name_color, bio)` by
+    /// the scene.
+    SaveProfile {
+        /// Selected `mag_core::profile::NameColor` byte.
+        name_color: u8,
+        /// Trimmed bio text; empty keeps the current description.
+        bio: String,
+    },
+    /// Choose the title displayed in front of the player's name.
+    ///
+    /// Mapped to `ClientCommand::new_set_title(title_id)` by the scene.
+    SetTitle {
+        /// `mag_core::titles` id, or `0` to clear.
+        title_id: u8,
+    },
+    /// Auto-walk to the bookmark at `index` in the per-character settings.
+    TravelTo {
+        /// Index into `CharacterSettings::bookmarks`.
+        index: usize,
+    },
+    /// Delete the bookmark at `index` in the per-character settings.
+    RemoveBookmark {
+        /// Index into `CharacterSettings::bookmarks`.
+        index: usize,
+    },
+    /// Bookmark the player's current position.
+    BookmarkHere,
+    /// Cancel the auto-walk in progress.
+    StopAutoWalk,
+    /// Write the combat log to `mag_combat_log.csv`.
+    ExportCombatLog,
+    /// Forget every hit in the combat log.
+    ClearCombatLog,
 }
 
 // ---------------------------------------------------------------------------
@@ -400,11 +512,90 @@ pub enum GameAction {
     ToggleSkills,
     /// Open / close the inventory panel.
     ToggleInventory,
+    /// Open / close the talent tree.
+    ToggleTalents,
+    /// Open / close the quest log.
+    ToggleQuestLog,
+    /// Show / hide the minimap.
+    ToggleMinimap,
+    /// Zoom the camera in one step.
+    ZoomIn,
+    /// Zoom the camera out one step, as far as the server allows.
+    ZoomOut,
+    /// Detach the camera from the player's tile (arrow keys pan), or
+    /// re-attach it.
+    ToggleFreeCamera,
+    /// Use the skill in skill-bar slot 1 (secondary bar with Shift).
+    SkillSlot1,
+    /// Use the skill in skill-bar slot 2.
+    SkillSlot2,
+    /// Use the skill in skill-bar slot 3.
+    SkillSlot3,
+    /// Use the skill in skill-bar slot 4.
+    SkillSlot4,
+    /// Use the skill in skill-bar slot 5.
+    SkillSlot5,
+    /// Use the skill in skill-bar slot 6.
+    SkillSlot6,
+    /// Use the skill in skill-bar slot 7.
+    SkillSlot7,
+    /// Use the skill in skill-bar slot 8.
+    SkillSlot8,
+    /// Use the skill in skill-bar slot 9.
+    SkillSlot9,
+    /// Use the skill in skill-bar slot 10.
+    SkillSlot10,
+    /// Save a bug report (screenshot, log tail, state) to the log folder.
+    SaveBugReport,
+    /// Save a bug report and upload it to the account API.
+    SendBugReport,
 }
 
 impl GameAction {
     /// All defined actions, in display order.
-    pub const ALL: &'static [GameAction] = &[GameAction::ToggleSkills, GameAction::ToggleInventory];
+    pub const ALL: &'static [GameAction] = &[
+        GameAction::ToggleSkills,
+        GameAction::ToggleInventory,
+        GameAction::ToggleTalents,
+        GameAction::ToggleQuestLog,
+        GameAction::ToggleMinimap,
+        GameAction::ZoomIn,
+        GameAction::ZoomOut,
+        GameAction::ToggleFreeCamera,
+        GameAction::SkillSlot1,
+        GameAction::SkillSlot2,
+        GameAction::SkillSlot3,
+        GameAction::SkillSlot4,
+        GameAction::SkillSlot5,
+        GameAction::SkillSlot6,
+        GameAction::SkillSlot7,
+        GameAction::SkillSlot8,
+        GameAction::SkillSlot9,
+        GameAction::SkillSlot10,
+        GameAction::SaveBugReport,
+        GameAction::SendBugReport,
+    ];
+
+    /// Skill-bar slot used by this action.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(0..=9)` for the `SkillSlot*` actions, otherwise `None`.
+    pub fn skill_slot(self) -> Option<usize> {
+        match self {
+            GameAction::SkillSlot1 => Some(0),
+            GameAction::SkillSlot2 => Some(1),
+            GameAction::SkillSlot3 => Some(2),
+            GameAction::SkillSlot4 => Some(3),
+            GameAction::SkillSlot5 => Some(4),
+            GameAction::SkillSlot6 => Some(5),
+            GameAction::SkillSlot7 => Some(6),
+            GameAction::SkillSlot8 => Some(7),
+            GameAction::SkillSlot9 => Some(8),
+            GameAction::SkillSlot10 => Some(9),
+            _ => None,
+        }
+    }
 
     /// Human-readable label for this action.
     ///
@@ -415,6 +606,24 @@ impl GameAction {
         match self {
             GameAction::ToggleSkills => "Toggle Skills Panel",
             GameAction::ToggleInventory => "Toggle Inventory Panel",
+            GameAction::ToggleTalents => "Toggle Talent Tree",
+            GameAction::ToggleQuestLog => "Toggle Quest Log",
+            GameAction::ToggleMinimap => "Toggle Minimap",
+            GameAction::ZoomIn => "Zoom In",
+            GameAction::ZoomOut => "Zoom Out",
+            GameAction::ToggleFreeCamera => "Toggle Free Camera",
+            GameAction::SkillSlot1 => "Skill Slot 1",
+            GameAction::SkillSlot2 => "Skill Slot 2",
+            GameAction::SkillSlot3 => "Skill Slot 3",
+            GameAction::SkillSlot4 => "Skill Slot 4",
+            GameAction::SkillSlot5 => "Skill Slot 5",
+            GameAction::SkillSlot6 => "Skill Slot 6",
+            GameAction::SkillSlot7 => "Skill Slot 7",
+            GameAction::SkillSlot8 => "Skill Slot 8",
+            GameAction::SkillSlot9 => "Skill Slot 9",
+            GameAction::SkillSlot10 => "Skill Slot 10",
+            GameAction::SaveBugReport => "Save Bug Report",
+            GameAction::SendBugReport => "Send Bug Report",
         }
     }
 }
@@ -505,26 +714,93 @@ impl fmt::Display for KeyBinding {
 
 /// A complete set of keyboard bindings mapping [`GameAction`]s to
 /// [`KeyBinding`]s.
+///
+/// Saved profiles are merged over [`KeyBindings::default`] when loaded, so
+/// actions added after a profile was written start out with their default
+/// key.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "SavedKeyBindings")]
 pub struct KeyBindings {
     /// One entry per bindable action.
     entries: Vec<(GameAction, KeyBinding)>,
 }
 
+/// Serialized form of [`KeyBindings`], possibly missing newer actions.
+#[derive(Deserialize)]
+struct SavedKeyBindings {
+    entries: Vec<(GameAction, KeyBinding)>,
+}
+
+impl From<SavedKeyBindings> for KeyBindings {
+    fn from(saved: SavedKeyBindings) -> Self {
+        let mut bindings = Self::default();
+        for (action, binding) in saved.entries {
+            bindings.set_binding(action, binding);
+        }
+        bindings
+    }
+}
+
 impl Default for KeyBindings {
     fn default() -> Self {
-        Self {
-            entries: vec![
-                (
-                    GameAction::ToggleSkills,
-                    KeyBinding::new(Keycode::S, KeyModifiers::default()),
-                ),
-                (
-                    GameAction::ToggleInventory,
-                    KeyBinding::new(Keycode::I, KeyModifiers::default()),
+        let plain = KeyModifiers::default();
+        let slot_keys = [
+            Keycode::Num1,
+            Keycode::Num2,
+            Keycode::Num3,
+            Keycode::Num4,
+            Keycode::Num5,
+            Keycode::Num6,
+            Keycode::Num7,
+            Keycode::Num8,
+            Keycode::Num9,
+            Keycode::Num0,
+        ];
+        let mut entries = vec![
+            (GameAction::ToggleSkills, KeyBinding::new(Keycode::S, plain)),
+            (
+                GameAction::ToggleInventory,
+                KeyBinding::new(Keycode::I, plain),
+            ),
+            (
+                GameAction::ToggleTalents,
+                KeyBinding::new(Keycode::T, plain),
+            ),
+            (
+                GameAction::ToggleQuestLog,
+                KeyBinding::new(Keycode::Q, plain),
+            ),
+            (
+                GameAction::ToggleMinimap,
+                KeyBinding::new(Keycode::M, plain),
+            ),
+            (GameAction::ZoomIn, KeyBinding::new(Keycode::Equals, plain)),
+            (GameAction::ZoomOut, KeyBinding::new(Keycode::Minus, plain)),
+            (
+                GameAction::ToggleFreeCamera,
+                KeyBinding::new(Keycode::F9, plain),
+            ),
+            (
+                GameAction::SaveBugReport,
+                KeyBinding::new(Keycode::F11, plain),
+            ),
+            (
+                GameAction::SendBugReport,
+                KeyBinding::new(
+                    Keycode::F11,
+                    KeyModifiers {
+                        shift: true,
+                        ..plain
+                    },
                 ),
-            ],
+            ),
+        ];
+        for action in GameAction::ALL {
+            if let Some(slot) = action.skill_slot() {
+                entries.push((*action, KeyBinding::new(slot_keys[slot], plain)));
+            }
         }
+        Self { entries }
     }
 }
 
@@ -540,9 +816,23 @@ impl KeyBindings {
     ///
     /// The matching `GameAction`, or `None`.
     pub fn action_for_key(&self, keycode: Keycode, modifiers: KeyModifiers) -> Option<GameAction> {
-        self.entries
+        let exact = self
+            .entries
             .iter()
             .find(|(_, kb)| kb.matches(keycode, modifiers))
+            .map(|(action, _)| *action);
+        if exact.is_some() || !modifiers.shift {
+            return exact;
+        }
+        // Shift selects the secondary skill bar, so skill slots also fire
+        // with Shift added to their binding.
+        let unshifted = KeyModifiers {
+            shift: false,
+            ..modifiers
+        };
+        self.entries
+            .iter()
+            .find(|(action, kb)| action.skill_slot().is_some() && kb.matches(keycode, unshifted))
             .map(|(action, _)| *action)
     }
 
@@ -602,6 +892,16 @@ pub trait Widget {
     /// * `y` - New top edge.
     fn set_position(&mut self, x: i32, y: i32);
 
+    /// Replaces the background fill, e.g. for the high-contrast HUD.
+    ///
+    /// The default implementation is a no-op for widgets without a
+    /// background.
+    ///
+    /// # Arguments
+    ///
+    /// * `_color` - New background color.
+    fn set_background(&mut self, _color: Color) {}
+
     /// Process an input event.
     ///
     /// # Arguments
@@ -806,9 +1106,48 @@ mod tests {
     // -- KeyBindings --
 
     #[test]
-    fn keybindings_default_has_two_entries() {
+    fn keybindings_default_binds_every_action() {
+        let kb = KeyBindings::default();
+        assert_eq!(kb.entries().len(), GameAction::ALL.len());
+        for action in GameAction::ALL {
+            assert!(kb.binding_for(*action).is_some(), "{action:?}");
+        }
+    }
+
+    #[test]
+    fn keybindings_skill_slots_also_match_with_shift() {
         let kb = KeyBindings::default();
-        assert_eq!(kb.entries().len(), 2);
+        let shift = KeyModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            kb.action_for_key(Keycode::Num0, KeyModifiers::default()),
+            Some(GameAction::SkillSlot10),
+        );
+        assert_eq!(
+            kb.action_for_key(Keycode::Num3, shift),
+            Some(GameAction::SkillSlot3),
+        );
+        // Panel toggles still need their exact modifiers.
+        assert_eq!(kb.action_for_key(Keycode::S, shift), None);
+    }
+
+    #[test]
+    fn keybindings_old_profiles_gain_new_defaults() {
+        let json = format!(
+            r#"{{"entries":[["ToggleSkills",{{"keycode":{},"modifiers":{{"ctrl":false,"shift":false,"alt":false}}}}]]}}"#,
+            i32::from(Keycode::K)
+        );
+        let kb: KeyBindings = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            kb.action_for_key(Keycode::K, KeyModifiers::default()),
+            Some(GameAction::ToggleSkills),
+        );
+        assert_eq!(
+            kb.action_for_key(Keycode::Num1, KeyModifiers::default()),
+            Some(GameAction::SkillSlot1),
+        );
     }
 
     #[test]
@@ -1,62 +1,29 @@
-/// Pure functions for calculating character experience points.
-///
-/// These functions operate on `Character` structs directly, with no dependency
-/// on `Repository` or any other server-global state. They are used by both
-/// the live server (via `populate::reset_char`) and the server utilities
-/// (template viewer) to keep `points_tot` in sync when template stats change.
-use core::{skills, types::Character};
+//! Canonical progression formulas shared by the server and the client.
+//!
+//! The server charges experience with these costs when a player raises an
+//! attribute, a stat or a skill, and the client shows the same numbers in
+//! its skills panel before the request is sent. Keeping both on this module
+//! means a balance change cannot leave the client quoting stale prices.
+//! Rank thresholds and names live in [`crate::ranks`] and are re-exported
+//! here so callers find every progression rule in one place.
 
-/// Calculate the total experience points a character template is worth.
-///
-/// The formula mirrors the inline calculation in legacy `populate::reset_char`:
-///
-/// 1. **Attributes** - `attrib_needed(value, 3)` for `value` in `10..attrib[z][0]`.
-/// 2. **HP** - `hp_needed(value, 3)` for `value` in `50..hp[0]`.
-/// 3. **Endurance** - `end_needed(value, 2)` for `value` in `50..end[0]`.
-/// 4. **Mana** - `mana_needed(value, 3)` for `value` in `50..mana[0]`.
-/// 5. **Skills** - `skill_needed(value, 2)` for `value` in `1..skill[z][0]`,
-///    excluding perception, stealth, and lock picking.
-///
-/// # Arguments
-///
-/// * `character` - The character or character template to evaluate.
-///
-/// # Returns
-///
-/// * The computed total experience points.
-#[allow(dead_code)] // Used by server-utils via the lib target (server::points::calculate_points_tot)
-pub fn calculate_points_tot(character: &Character) -> i32 {
-    let mut points = 0;
+use crate::skills;
+use crate::types::Character;
 
-    for attribute in 0..5 {
-        for value in 10..i32::from(character.attrib[attribute][0]) {
-            points += attrib_needed(value, 3);
-        }
-    }
-
-    for value in 50..i32::from(character.hp[0]) {
-        points += hp_needed(value, 3);
-    }
+pub use crate::ranks::{RANK_THRESHOLDS, TOTAL_RANKS, points2rank, rank_name};
 
-    for value in 50..i32::from(character.end[0]) {
-        points += end_needed(value, 2);
-    }
+/// Attribute value every character starts at for free.
+pub const ATTRIB_BASE: i32 = 10;
 
-    for value in 50..i32::from(character.mana[0]) {
-        points += mana_needed(value, 3);
-    }
+/// HP, endurance and mana value every character starts at for free.
+pub const STAT_BASE: i32 = 50;
 
-    for skill in 0..skills::MAX_SKILLS {
-        if skill == skills::SK_PERCEPT || skill == skills::SK_STEALTH || skill == skills::SK_LOCK {
-            continue;
-        }
+/// Lowest skill value that costs experience to raise.
+pub const SKILL_BASE: i32 = 1;
 
-        for value in 1..i32::from(character.skill[skill][0]) {
-            points += skill_needed(value, 2);
-        }
-    }
-
-    points
+/// Clamps an `i64` cost into `i32`, so out-of-range inputs cannot wrap.
+fn clamp_cost(cost: i64) -> i32 {
+    cost.clamp(0, i64::from(i32::MAX)) as i32
 }
 
 /// Return the legacy point cost for an attribute value.
@@ -69,8 +36,9 @@ pub fn calculate_points_tot(character: &Character) -> i32 {
 /// # Returns
 ///
 /// * Point cost for that increment.
-pub(crate) fn attrib_needed(value: i32, difficulty: i32) -> i32 {
-    value * value * value * difficulty / 20
+pub fn attrib_needed(value: i32, difficulty: i32) -> i32 {
+    let value = i64::from(value);
+    clamp_cost(value * value * value * i64::from(difficulty) / 20)
 }
 
 /// Return the legacy point cost for an HP value.
@@ -83,8 +51,8 @@ pub(crate) fn attrib_needed(value: i32, difficulty: i32) -> i32 {
 /// # Returns
 ///
 /// * Point cost for that increment.
-pub(crate) fn hp_needed(value: i32, difficulty: i32) -> i32 {
-    value * difficulty
+pub fn hp_needed(value: i32, difficulty: i32) -> i32 {
+    clamp_cost(i64::from(value) * i64::from(difficulty))
 }
 
 /// Return the legacy point cost for an endurance value.
@@ -97,8 +65,8 @@ pub(crate) fn hp_needed(value: i32, difficulty: i32) -> i32 {
 /// # Returns
 ///
 /// * Point cost for that increment.
-pub(crate) fn end_needed(value: i32, difficulty: i32) -> i32 {
-    value * difficulty / 2
+pub fn end_needed(value: i32, difficulty: i32) -> i32 {
+    clamp_cost(i64::from(value) * i64::from(difficulty) / 2)
 }
 
 /// Return the legacy point cost for a mana value.
@@ -111,8 +79,8 @@ pub(crate) fn end_needed(value: i32, difficulty: i32) -> i32 {
 /// # Returns
 ///
 /// * Point cost for that increment.
-pub(crate) fn mana_needed(value: i32, difficulty: i32) -> i32 {
-    value * difficulty
+pub fn mana_needed(value: i32, difficulty: i32) -> i32 {
+    clamp_cost(i64::from(value) * i64::from(difficulty))
 }
 
 /// Return the legacy point cost for a skill value.
@@ -125,8 +93,61 @@ pub(crate) fn mana_needed(value: i32, difficulty: i32) -> i32 {
 /// # Returns
 ///
 /// * Point cost for that increment.
-pub(crate) fn skill_needed(value: i32, difficulty: i32) -> i32 {
-    value.max(value * value * value * difficulty / 40)
+pub fn skill_needed(value: i32, difficulty: i32) -> i32 {
+    let wide = i64::from(value);
+    value.max(clamp_cost(wide * wide * wide * i64::from(difficulty) / 40))
+}
+
+/// Calculate the total experience points a character template is worth.
+///
+/// The formula mirrors the inline calculation in legacy `populate::reset_char`:
+///
+/// 1. **Attributes** - `attrib_needed(value, 3)` for `value` in `10..attrib[z][0]`.
+/// 2. **HP** - `hp_needed(value, 3)` for `value` in `50..hp[0]`.
+/// 3. **Endurance** - `end_needed(value, 2)` for `value` in `50..end[0]`.
+/// 4. **Mana** - `mana_needed(value, 3)` for `value` in `50..mana[0]`.
+/// 5. **Skills** - `skill_needed(value, 2)` for `value` in `1..skill[z][0]`,
+///    excluding perception, stealth, and lock picking.
+///
+/// # Arguments
+///
+/// * `character` - The character or character template to evaluate.
+///
+/// # Returns
+///
+/// * The computed total experience points.
+pub fn calculate_points_tot(character: &Character) -> i32 {
+    let mut points = 0;
+
+    for attribute in 0..5 {
+        for value in ATTRIB_BASE..i32::from(character.attrib[attribute][0]) {
+            points += attrib_needed(value, 3);
+        }
+    }
+
+    for value in STAT_BASE..i32::from(character.hp[0]) {
+        points += hp_needed(value, 3);
+    }
+
+    for value in STAT_BASE..i32::from(character.end[0]) {
+        points += end_needed(value, 2);
+    }
+
+    for value in STAT_BASE..i32::from(character.mana[0]) {
+        points += mana_needed(value, 3);
+    }
+
+    for skill in 0..skills::MAX_SKILLS {
+        if skill == skills::SK_PERCEPT || skill == skills::SK_STEALTH || skill == skills::SK_LOCK {
+            continue;
+        }
+
+        for value in SKILL_BASE..i32::from(character.skill[skill][0]) {
+            points += skill_needed(value, 2);
+        }
+    }
+
+    points
 }
 
 #[cfg(test)]
@@ -140,6 +161,25 @@ mod tests {
         assert_eq!(calculate_points_tot(&character), 0);
     }
 
+    /// Costs match the legacy integer formulas.
+    #[test]
+    fn costs_match_legacy_formulas() {
+        assert_eq!(attrib_needed(10, 3), 150);
+        assert_eq!(hp_needed(50, 3), 150);
+        assert_eq!(end_needed(51, 2), 51);
+        assert_eq!(mana_needed(60, 3), 180);
+        assert_eq!(skill_needed(3, 2), 3);
+        assert_eq!(skill_needed(20, 2), 400);
+    }
+
+    /// Out-of-range inputs saturate instead of wrapping.
+    #[test]
+    fn huge_values_saturate() {
+        assert_eq!(attrib_needed(100_000, 3), i32::MAX);
+        assert_eq!(skill_needed(100_000, 3), i32::MAX);
+        assert_eq!(hp_needed(i32::MAX, 2), i32::MAX);
+    }
+
     /// Attributes use the legacy cubic cost above baseline 10.
     #[test]
     fn attributes_use_legacy_cost_above_baseline() {
@@ -0,0 +1,462 @@
+//! Aggregated character sheet snapshot (`SV_SETCHARSHEET`).
+//!
+//! During play the client assembles its copy of the character from many
+//! small `SV_SETCHAR*` delta packets. If one of them is lost or applied out
+//! of order the client drifts (phantom items, spells that never expire).
+//! Clients advertising [`CLIENT_CAP_CHAR_SHEET`](crate::constants::CLIENT_CAP_CHAR_SHEET)
+//! receive one [`CharSheet`] right after login and on `/refresh`; it
+//! replaces everything the deltas would have built up, and the server
+//! resynchronises its own delta baseline to the same values.
+//!
+//! To catch drift between snapshots the server periodically sends
+//! `SV_CHARCHECKSUM` with [`CharSheet::checksum`] of what it last sent; the
+//! client compares it against [`client_checksum`] of its own copy and asks
+//! for a fresh sheet when they differ.
+//!
+//! Values are in client units: items, worn items, spells and the cursor
+//! item carry sprite numbers, and `a_hp` / `a_end` / `a_mana` are whole
+//! points rather than thousandths.
+
+use crate::skills::MAX_SKILLS;
+use crate::types::ClientPlayer;
+
+/// Total `SV_SETCHARSHEET` packet size: opcode (1) + name (40) + mode (1) +
+/// dir (1) + attributes (5 × 6) + hp/end/mana (3 × 6 × u16) + current
+/// hp/end/mana (3 × u16) + points/points_tot/kindred (3 × u32) +
+/// gold/armor/weapon (3 × i32) + skills (`MAX_SKILLS` × 6) + items
+/// (40 × (sprite u16 + placement i16)) + worn (20 × 4) + spells
+/// (20 × (sprite u16 + active i16 + skill i16)) + cursor item (4).
+pub const CHAR_SHEET_PACKET_LEN: usize = 1
+    + 40
+    + 1
+    + 1
+    + 5 * 6
+    + 3 * 6 * 2
+    + 3 * 2
+    + 3 * 4
+    + 3 * 4
+    + MAX_SKILLS * 6
+    + 40 * 4
+    + 20 * 4
+    + 20 * 6
+    + 4;
+
+/// FNV-1a over the slots covered by the state checksum: inventory and
+/// equipment (sprite + placement) and spells (sprite + skill). Values are
+/// truncated to their 16-bit wire width so both ends hash the same bits.
+fn checksum_slots(
+    item: impl Iterator<Item = (i32, i32)>,
+    worn: impl Iterator<Item = (i32, i32)>,
+    spell: impl Iterator<Item = (i32, i32)>,
+) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for (a, b) in item.chain(worn).chain(spell) {
+        for byte in (a as u16)
+            .to_le_bytes()
+            .into_iter()
+            .chain((b as u16).to_le_bytes())
+        {
+            hash ^= u32::from(byte);
+            hash = hash.wrapping_mul(0x0100_0193);
+        }
+    }
+    hash
+}
+
+/// Checksum of the client's inventory, equipment and spells, comparable to
+/// [`CharSheet::checksum`].
+///
+/// # Arguments
+///
+/// * `player` - Client-side character state.
+///
+/// # Returns
+///
+/// * The 32-bit state checksum.
+pub fn client_checksum(player: &ClientPlayer) -> u32 {
+    checksum_slots(
+        player
+            .item
+            .iter()
+            .copied()
+            .zip(player.item_p.iter().copied()),
+        player
+            .worn
+            .iter()
+            .copied()
+            .zip(player.worn_p.iter().copied()),
+        player
+            .spell
+            .iter()
+            .copied()
+            .zip(player.spell_type.iter().map(|&v| i32::from(v))),
+    )
+}
+
+/// Full client-visible character state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CharSheet {
+    /// NUL-padded character name.
+    pub name: [u8; 40],
+    /// Speed mode (0 = slow, 1 = normal, 2 = fast).
+    pub mode: u8,
+    /// Facing direction.
+    pub dir: u8,
+    /// Attribute matrix, see `ClientPlayer::attrib`.
+    pub attrib: [[u8; 6]; 5],
+    /// Hit point matrix.
+    pub hp: [u16; 6],
+    /// Endurance matrix.
+    pub end: [u16; 6],
+    /// Mana matrix.
+    pub mana: [u16; 6],
+    /// Current hit points.
+    pub a_hp: u16,
+    /// Current endurance.
+    pub a_end: u16,
+    /// Current mana.
+    pub a_mana: u16,
+    /// Unspent experience.
+    pub points: u32,
+    /// Total experience.
+    pub points_tot: u32,
+    /// Kindred flags.
+    pub kindred: u32,
+    /// Gold in silver.
+    pub gold: i32,
+    /// Total armor value.
+    pub armor: i32,
+    /// Total weapon value.
+    pub weapon: i32,
+    /// Skill matrix.
+    pub skill: [[u8; 6]; MAX_SKILLS],
+    /// Inventory sprites.
+    pub item: [u16; 40],
+    /// Inventory placement bits.
+    pub item_p: [i16; 40],
+    /// Equipment sprites.
+    pub worn: [u16; 20],
+    /// Equipment placement bits.
+    pub worn_p: [i16; 20],
+    /// Active spell sprites.
+    pub spell: [u16; 20],
+    /// Remaining spell duration in sixteenths.
+    pub active: [i16; 20],
+    /// Skill number that produced each spell.
+    pub spell_type: [i16; 20],
+    /// Cursor item sprite.
+    pub citem: u16,
+    /// Cursor item placement bits.
+    pub citem_p: i16,
+}
+
+impl Default for CharSheet {
+    fn default() -> Self {
+        Self {
+            name: [0; 40],
+            mode: 0,
+            dir: 0,
+            attrib: [[0; 6]; 5],
+            hp: [0; 6],
+            end: [0; 6],
+            mana: [0; 6],
+            a_hp: 0,
+            a_end: 0,
+            a_mana: 0,
+            points: 0,
+            points_tot: 0,
+            kindred: 0,
+            gold: 0,
+            armor: 0,
+            weapon: 0,
+            skill: [[0; 6]; MAX_SKILLS],
+            item: [0; 40],
+            item_p: [0; 40],
+            worn: [0; 20],
+            worn_p: [0; 20],
+            spell: [0; 20],
+            active: [0; 20],
+            spell_type: [0; 20],
+            citem: 0,
+            citem_p: 0,
+        }
+    }
+}
+
+/// Little-endian reader over a packet body.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let out = self.bytes.get(self.pos..self.pos + N)?.try_into().ok()?;
+        self.pos += N;
+        Some(out)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        self.take().map(i16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.take().map(i32::from_le_bytes)
+    }
+}
+
+impl CharSheet {
+    /// Encodes the sheet as a complete `SV_SETCHARSHEET` packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `opcode` - Opcode byte to prefix.
+    ///
+    /// # Returns
+    ///
+    /// * [`CHAR_SHEET_PACKET_LEN`] bytes.
+    pub fn to_bytes(&self, opcode: u8) -> Vec<u8> {
+        let mut out = Vec::with_capacity(CHAR_SHEET_PACKET_LEN);
+        out.push(opcode);
+        out.extend_from_slice(&self.name);
+        out.push(self.mode);
+        out.push(self.dir);
+        for row in &self.attrib {
+            out.extend_from_slice(row);
+        }
+        for v in self.hp.iter().chain(&self.end).chain(&self.mana) {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in [self.a_hp, self.a_end, self.a_mana] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in [self.points, self.points_tot, self.kindred] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in [self.gold, self.armor, self.weapon] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for row in &self.skill {
+            out.extend_from_slice(row);
+        }
+        for (sprite, placement) in self.item.iter().zip(&self.item_p) {
+            out.extend_from_slice(&sprite.to_le_bytes());
+            out.extend_from_slice(&placement.to_le_bytes());
+        }
+        for (sprite, placement) in self.worn.iter().zip(&self.worn_p) {
+            out.extend_from_slice(&sprite.to_le_bytes());
+            out.extend_from_slice(&placement.to_le_bytes());
+        }
+        for i in 0..20 {
+            out.extend_from_slice(&self.spell[i].to_le_bytes());
+            out.extend_from_slice(&self.active[i].to_le_bytes());
+            out.extend_from_slice(&self.spell_type[i].to_le_bytes());
+        }
+        out.extend_from_slice(&self.citem.to_le_bytes());
+        out.extend_from_slice(&self.citem_p.to_le_bytes());
+        debug_assert_eq!(out.len(), CHAR_SHEET_PACKET_LEN);
+        out
+    }
+
+    /// Decodes a complete `SV_SETCHARSHEET` packet (opcode included).
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Packet bytes starting at the opcode.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(CharSheet)`, or `None` if the packet is truncated.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut r = Reader { bytes, pos: 1 };
+        let mut sheet = CharSheet {
+            name: r.take()?,
+            mode: r.u8()?,
+            dir: r.u8()?,
+            ..CharSheet::default()
+        };
+        for row in sheet.attrib.iter_mut() {
+            *row = r.take()?;
+        }
+        for v in sheet.hp.iter_mut() {
+            *v = r.u16()?;
+        }
+        for v in sheet.end.iter_mut() {
+            *v = r.u16()?;
+        }
+        for v in sheet.mana.iter_mut() {
+            *v = r.u16()?;
+        }
+        sheet.a_hp = r.u16()?;
+        sheet.a_end = r.u16()?;
+        sheet.a_mana = r.u16()?;
+        sheet.points = r.u32()?;
+        sheet.points_tot = r.u32()?;
+        sheet.kindred = r.u32()?;
+        sheet.gold = r.i32()?;
+        sheet.armor = r.i32()?;
+        sheet.weapon = r.i32()?;
+        for row in sheet.skill.iter_mut() {
+            *row = r.take()?;
+        }
+        for i in 0..40 {
+            sheet.item[i] = r.u16()?;
+            sheet.item_p[i] = r.i16()?;
+        }
+        for i in 0..20 {
+            sheet.worn[i] = r.u16()?;
+            sheet.worn_p[i] = r.i16()?;
+        }
+        for i in 0..20 {
+            sheet.spell[i] = r.u16()?;
+            sheet.active[i] = r.i16()?;
+            sheet.spell_type[i] = r.i16()?;
+        }
+        sheet.citem = r.u16()?;
+        sheet.citem_p = r.i16()?;
+        Some(sheet)
+    }
+
+    /// Checksum of the sheet's inventory, equipment and spells.
+    ///
+    /// # Returns
+    ///
+    /// * The value a client holding exactly this sheet reports from
+    ///   [`client_checksum`].
+    pub fn checksum(&self) -> u32 {
+        checksum_slots(
+            self.item
+                .iter()
+                .zip(&self.item_p)
+                .map(|(&a, &b)| (i32::from(a), i32::from(b))),
+            self.worn
+                .iter()
+                .zip(&self.worn_p)
+                .map(|(&a, &b)| (i32::from(a), i32::from(b))),
+            self.spell
+                .iter()
+                .zip(&self.spell_type)
+                .map(|(&a, &b)| (i32::from(a), i32::from(b))),
+        )
+    }
+
+    /// Overwrites every sheet-backed field of `player`, discarding whatever
+    /// the delta packets had built up. Target and movement fields are left
+    /// alone; skills beyond [`MAX_SKILLS`] are cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `player` - Client-side character state to reset.
+    pub fn apply_to(&self, player: &mut ClientPlayer) {
+        player.name = self.name;
+        player.mode = i32::from(self.mode);
+        player.dir = i32::from(self.dir);
+        player.attrib = self.attrib;
+        player.hp = self.hp;
+        player.end = self.end;
+        player.mana = self.mana;
+        player.a_hp = i32::from(self.a_hp);
+        player.a_end = i32::from(self.a_end);
+        player.a_mana = i32::from(self.a_mana);
+        player.points = self.points as i32;
+        player.points_tot = self.points_tot as i32;
+        player.kindred = self.kindred as i32;
+        player.gold = self.gold;
+        player.armor = self.armor;
+        player.weapon = self.weapon;
+        player.skill = [[0; 6]; 100];
+        player.skill[..MAX_SKILLS].copy_from_slice(&self.skill);
+        player.item = self.item.map(i32::from);
+        player.item_p = self.item_p.map(i32::from);
+        player.worn = self.worn.map(i32::from);
+        player.worn_p = self.worn_p.map(i32::from);
+        player.spell = self.spell.map(i32::from);
+        player.active = self.active.map(|v| v as i8);
+        player.spell_type = self.spell_type;
+        player.citem = i32::from(self.citem);
+        player.citem_p = i32::from(self.citem_p);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> CharSheet {
+        let mut sheet = CharSheet {
+            mode: 2,
+            dir: 3,
+            a_hp: 123,
+            points: 50_000,
+            gold: 12_345,
+            weapon: 40,
+            citem: 37,
+            ..CharSheet::default()
+        };
+        sheet.name[..5].copy_from_slice(b"Ishtr");
+        sheet.attrib[4] = [1, 2, 3, 4, 5, 6];
+        sheet.skill[MAX_SKILLS - 1] = [9, 8, 7, 6, 5, 4];
+        sheet.item[39] = 0xBEEF;
+        sheet.item_p[39] = -2;
+        sheet.spell[19] = 97;
+        sheet.active[19] = 16;
+        sheet.spell_type[19] = 45;
+        sheet
+    }
+
+    #[test]
+    fn roundtrip_preserves_every_field() {
+        let sheet = sample();
+        let bytes = sheet.to_bytes(81);
+        assert_eq!(bytes.len(), CHAR_SHEET_PACKET_LEN);
+        assert_eq!(bytes[0], 81);
+        assert_eq!(CharSheet::from_bytes(&bytes), Some(sheet));
+        assert_eq!(
+            CharSheet::from_bytes(&bytes[..CHAR_SHEET_PACKET_LEN - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn apply_replaces_stale_client_state() {
+        let mut player = ClientPlayer::default();
+        player.item[3] = 500;
+        player.spell[0] = 88;
+        player.skill[90] = [1; 6];
+        player.goto_x = 17;
+
+        sample().apply_to(&mut player);
+        assert_eq!(player.item[3], 0);
+        assert_eq!(player.item[39], 0xBEEF);
+        assert_eq!(player.spell[0], 0);
+        assert_eq!(player.active[19], 16);
+        assert_eq!(player.skill[90], [0; 6]);
+        assert_eq!(player.gold, 12_345);
+        assert_eq!(player.goto_x, 17);
+    }
+
+    #[test]
+    fn checksum_matches_applied_sheet_and_detects_drift() {
+        let sheet = sample();
+        let mut player = ClientPlayer::default();
+        assert_ne!(client_checksum(&player), sheet.checksum());
+
+        sheet.apply_to(&mut player);
+        assert_eq!(client_checksum(&player), sheet.checksum());
+
+        player.worn_p[5] = 1;
+        assert_ne!(client_checksum(&player), sheet.checksum());
+    }
+}
@@ -0,0 +1,232 @@
+//! Chat message styling metadata carried by `SV_LOGSTYLED`.
+//!
+//! Legacy log packets (`SV_LOG0..3`) encode the message color in the opcode
+//! itself, which leaves no room to tell a shout from a system notice. Chat
+//! traffic is instead sent as `SV_LOGSTYLED` packets whose second byte is a
+//! [`ChatStyle`]: the low nibble is the [`ChatChannel`] and the high bits
+//! are presentation flags (GM highlight, emote). The client picks the
+//! colors; the server only says what kind of message it is.
+
+/// Mask selecting the [`ChatChannel`] nibble of a style byte.
+pub const CHAT_STYLE_CHANNEL_MASK: u8 = 0b0000_1111;
+
+/// Style bit marking a message from a game master (rendered highlighted).
+pub const CHAT_STYLE_GM: u8 = 0b0100_0000;
+
+/// Style bit marking an emote (rendered in italics).
+pub const CHAT_STYLE_EMOTE: u8 = 0b1000_0000;
+
+/// Number of text bytes carried by a single `SV_LOGSTYLED` packet.
+pub const STYLED_LOG_CHUNK_LEN: usize = 14;
+
+/// Chat channel a styled message was sent on.
+///
+/// Numeric values are part of the wire protocol — do not renumber.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ChatChannel {
+    /// Server notices that are not player speech.
+    #[default]
+    System = 0,
+    /// Local speech from a player.
+    Say = 1,
+    /// Private `#tell` messages, sent or received.
+    Tell = 2,
+    /// `#gtell` group chat.
+    Group = 3,
+    /// `#shout` to every player.
+    Shout = 4,
+    /// `#stell` staff chat.
+    Staff = 5,
+    /// `#itell` imp chat.
+    Imp = 6,
+    /// Server-wide announcements (`#announce`, `#caution`, logins).
+    Announce = 7,
+    /// Local speech from an NPC.
+    Npc = 8,
+    /// Combat notices (kills, deaths, low-health warnings).
+    Combat = 9,
+    /// Earned items that did not fit into a full pack and were delivered
+    /// elsewhere. Clients also raise a toast for these.
+    Reward = 10,
+    /// `#guildtell` guild chat.
+    Guild = 11,
+}
+
+impl ChatChannel {
+    /// Returns the wire-protocol byte representation.
+    ///
+    /// # Returns
+    ///
+    /// * The discriminant byte for this channel.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the legacy `FontColor` index this channel was sent with
+    /// before styled logs existed.
+    ///
+    /// # Returns
+    ///
+    /// * `0` red, `1` yellow, `2` green or `3` blue.
+    pub fn legacy_font(self) -> u8 {
+        match self {
+            ChatChannel::Combat => 0,
+            ChatChannel::System | ChatChannel::Tell | ChatChannel::Npc => 1,
+            ChatChannel::Group
+            | ChatChannel::Guild
+            | ChatChannel::Announce
+            | ChatChannel::Reward => 2,
+            ChatChannel::Say | ChatChannel::Shout | ChatChannel::Staff | ChatChannel::Imp => 3,
+        }
+    }
+}
+
+impl From<u8> for ChatChannel {
+    /// Decodes a channel nibble. Unknown values map to
+    /// [`ChatChannel::System`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The channel value to decode.
+    ///
+    /// # Returns
+    ///
+    /// * The matching [`ChatChannel`].
+    fn from(value: u8) -> Self {
+        match value {
+            1 => ChatChannel::Say,
+            2 => ChatChannel::Tell,
+            3 => ChatChannel::Group,
+            4 => ChatChannel::Shout,
+            5 => ChatChannel::Staff,
+            6 => ChatChannel::Imp,
+            7 => ChatChannel::Announce,
+            8 => ChatChannel::Npc,
+            9 => ChatChannel::Combat,
+            10 => ChatChannel::Reward,
+            11 => ChatChannel::Guild,
+            _ => ChatChannel::System,
+        }
+    }
+}
+
+/// Decoded `SV_LOGSTYLED` style byte.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChatStyle {
+    /// Channel the message was sent on.
+    pub channel: ChatChannel,
+    /// Message comes from a game master and should stand out.
+    pub gm: bool,
+    /// Message is an emote.
+    pub emote: bool,
+}
+
+impl ChatStyle {
+    /// Creates a plain style for `channel`.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - Channel the message is sent on.
+    ///
+    /// # Returns
+    ///
+    /// * A style with no presentation flags set.
+    pub const fn new(channel: ChatChannel) -> Self {
+        Self {
+            channel,
+            gm: false,
+            emote: false,
+        }
+    }
+
+    /// Returns a copy with the GM highlight flag set to `gm`.
+    ///
+    /// # Arguments
+    ///
+    /// * `gm` - Whether the sender is a game master.
+    ///
+    /// # Returns
+    ///
+    /// * The updated style.
+    pub const fn with_gm(mut self, gm: bool) -> Self {
+        self.gm = gm;
+        self
+    }
+
+    /// Returns a copy with the emote flag set.
+    ///
+    /// # Returns
+    ///
+    /// * The updated style.
+    pub const fn emote(mut self) -> Self {
+        self.emote = true;
+        self
+    }
+
+    /// Encodes the style into its wire byte.
+    ///
+    /// # Returns
+    ///
+    /// * Channel nibble OR'd with the flag bits.
+    pub fn to_byte(self) -> u8 {
+        let mut byte = self.channel.as_u8() & CHAT_STYLE_CHANNEL_MASK;
+        if self.gm {
+            byte |= CHAT_STYLE_GM;
+        }
+        if self.emote {
+            byte |= CHAT_STYLE_EMOTE;
+        }
+        byte
+    }
+
+    /// Decodes a wire style byte. Unknown bits are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte` - Style byte from `SV_LOGSTYLED`.
+    ///
+    /// # Returns
+    ///
+    /// * The decoded [`ChatStyle`].
+    pub fn from_byte(byte: u8) -> Self {
+        Self {
+            channel: ChatChannel::from(byte & CHAT_STYLE_CHANNEL_MASK),
+            gm: byte & CHAT_STYLE_GM != 0,
+            emote: byte & CHAT_STYLE_EMOTE != 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn style_byte_roundtrip() {
+        let style = ChatStyle::new(ChatChannel::Announce).with_gm(true);
+        assert_eq!(style.to_byte(), 7 | CHAT_STYLE_GM);
+        assert_eq!(ChatStyle::from_byte(style.to_byte()), style);
+
+        let emote = ChatStyle::new(ChatChannel::Say).emote();
+        assert_eq!(ChatStyle::from_byte(emote.to_byte()), emote);
+
+        let combat = ChatStyle::new(ChatChannel::Combat);
+        assert_eq!(ChatStyle::from_byte(combat.to_byte()), combat);
+        assert_eq!(ChatChannel::Combat.legacy_font(), 0);
+
+        let reward = ChatStyle::new(ChatChannel::Reward);
+        assert_eq!(ChatStyle::from_byte(reward.to_byte()), reward);
+
+        let guild = ChatStyle::new(ChatChannel::Guild);
+        assert_eq!(ChatStyle::from_byte(guild.to_byte()), guild);
+    }
+
+    #[test]
+    fn unknown_channel_decodes_as_system() {
+        let style = ChatStyle::from_byte(0x0F | CHAT_STYLE_EMOTE);
+        assert_eq!(style.channel, ChatChannel::System);
+        assert!(style.emote);
+        assert!(!style.gm);
+    }
+}
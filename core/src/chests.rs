@@ -0,0 +1,157 @@
+//! Lootable chests: lock levels, keys, loot tables and respawn.
+//!
+//! A chest is a map item using driver [`CHEST_DRIVER`]. Using it checks the
+//! lock, rolls its contents from a [`LootTable`] straight into the opener's
+//! backpack and then activates the item for `Item::duration` ticks; while
+//! active the chest shows its open sprite and is empty. When the activation
+//! runs out the chest closes and can be looted again.
+//!
+//! Item layout (`Item::data`):
+//!
+//! * `data[0]` — template id of the key that opens it (`0` = no key).
+//! * `data[1]` — lock level checked against `SK_LOCK` when picking
+//!   (`0` = cannot be picked; with no key either, the chest is unlocked).
+//! * `data[2]` — [`LootTable::id`] of the contents.
+//! * `data[3]` — non-zero if the key vanishes when used.
+//!
+//! Chest templates need `IF_USE | IF_USESPECIAL | IF_USEACTIVATE`, the
+//! closed sprite in `sprite[0]` and the first of four opening frames in
+//! `sprite[1]`. Opening sets `status[1]` to [`ITEM_STATUS_OPENING`], which
+//! the client plays once and holds on the last frame.
+
+/// Item driver number for chests.
+pub const CHEST_DRIVER: u8 = 70;
+
+/// Item status that plays a four-frame animation once and then holds the
+/// last frame. Part of the wire protocol — do not renumber.
+pub const ITEM_STATUS_OPENING: u8 = 22;
+
+/// Healing potion template.
+const IT_HEALING_POTION: u16 = 101;
+
+/// Mana potion template.
+const IT_MANA_POTION: u16 = 102;
+
+/// One possible item in a loot table.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LootEntry {
+    /// Item template created when this entry is rolled.
+    pub template: u16,
+    /// Relative chance against the other entries of the table.
+    pub weight: u32,
+}
+
+/// Contents of a chest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LootTable {
+    /// Id stored in the chest's `data[2]`.
+    pub id: u32,
+    /// Number of entries rolled per opening.
+    pub rolls: usize,
+    /// Inclusive range of silver added on top of the items.
+    pub gold: (u32, u32),
+    /// Weighted entries to roll from.
+    pub entries: &'static [LootEntry],
+}
+
+impl LootTable {
+    /// Picks the entry that a roll of `roll` lands on.
+    ///
+    /// # Arguments
+    ///
+    /// * `roll` - Random value in `0..total_weight()`.
+    ///
+    /// # Returns
+    ///
+    /// * The template of the chosen entry, or `None` for an empty table.
+    pub fn pick(&self, roll: u32) -> Option<u16> {
+        let mut remaining = roll % self.total_weight().max(1);
+        for entry in self.entries {
+            if remaining < entry.weight {
+                return Some(entry.template);
+            }
+            remaining -= entry.weight;
+        }
+        None
+    }
+
+    /// Sum of all entry weights.
+    ///
+    /// # Returns
+    ///
+    /// * The total weight of the table.
+    pub fn total_weight(&self) -> u32 {
+        self.entries.iter().map(|e| e.weight).sum()
+    }
+}
+
+/// Authored loot tables, looked up by id via [`find_loot_table`].
+pub static LOOT_TABLES: &[LootTable] = &[
+    LootTable {
+        id: 1,
+        rolls: 1,
+        gold: (50, 300),
+        entries: &[
+            LootEntry {
+                template: IT_HEALING_POTION,
+                weight: 3,
+            },
+            LootEntry {
+                template: IT_MANA_POTION,
+                weight: 2,
+            },
+        ],
+    },
+    LootTable {
+        id: 2,
+        rolls: 2,
+        gold: (500, 2000),
+        entries: &[
+            LootEntry {
+                template: IT_HEALING_POTION,
+                weight: 1,
+            },
+            LootEntry {
+                template: IT_MANA_POTION,
+                weight: 1,
+            },
+        ],
+    },
+];
+
+/// Looks up a loot table.
+///
+/// # Arguments
+///
+/// * `id` - Table id from a chest's `data[2]`.
+///
+/// # Returns
+///
+/// * The table, or `None` if no table has that id.
+pub fn find_loot_table(id: u32) -> Option<&'static LootTable> {
+    LOOT_TABLES.iter().find(|t| t.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_ids_are_unique_and_rollable() {
+        for (i, table) in LOOT_TABLES.iter().enumerate() {
+            assert!(LOOT_TABLES[..i].iter().all(|t| t.id != table.id));
+            assert!(table.total_weight() > 0);
+            assert!(table.gold.0 <= table.gold.1);
+        }
+        assert!(find_loot_table(0).is_none());
+    }
+
+    #[test]
+    fn pick_respects_weights() {
+        let table = find_loot_table(1).unwrap();
+        assert_eq!(table.pick(0), Some(IT_HEALING_POTION));
+        assert_eq!(table.pick(2), Some(IT_HEALING_POTION));
+        assert_eq!(table.pick(3), Some(IT_MANA_POTION));
+        assert_eq!(table.pick(5), Some(IT_HEALING_POTION));
+    }
+}
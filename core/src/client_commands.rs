@@ -1,5 +1,8 @@
 /// Opcode byte for outgoing client commands (first byte of the 16-byte wire
 /// packet).
+///
+/// New variants get a `Since: <version>` doc line; it feeds the *Since*
+/// column of the generated protocol catalog (`cargo xtask gen-protocol-docs`).
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum ClientCommandType {
@@ -49,6 +52,105 @@ pub enum ClientCommandType {
     CmdLearnTalent = 37,
     /// Refund all spent talent points.  No payload (all-zero past the opcode).
     CmdResetTalents = 38,
+    /// Upload one chunk of the player's profile bio.
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `39`
+    /// * byte 1: chunk index, OR'd with
+    ///   [`PROFILE_FINAL_CHUNK`](crate::profile::PROFILE_FINAL_CHUNK) on the
+    ///   last chunk
+    /// * byte 2: selected [`NameColor`](crate::profile::NameColor)
+    /// * bytes 3..16: 13 bytes of bio text (zero-padded)
+    CmdSetProfile = 39,
+    /// Select the title displayed in front of the player's name.
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `40`
+    /// * byte 1: title id (see [`crate::titles`]; `0` clears the title)
+    /// * bytes 2..16: zero-padding
+    CmdSetTitle = 40,
+    /// Ask for the interaction menu of a character (right-click).
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `41`
+    /// * bytes 1..3: target character number (u16 LE)
+    /// * bytes 3..16: zero-padding
+    CmdNpcMenu = 41,
+    /// Pick an entry of an NPC interaction menu.
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `42`
+    /// * bytes 1..3: target character number (u16 LE)
+    /// * byte 3: [`NpcMenuOption`](crate::npc_menu::NpcMenuOption)
+    /// * bytes 4..16: zero-padding
+    CmdNpcAction = 42,
+    /// Buy a skill from a trainer NPC.
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `43`
+    /// * bytes 1..3: trainer character number (u16 LE)
+    /// * byte 3: skill index (see [`crate::skill_trainers`])
+    /// * bytes 4..16: zero-padding
+    CmdLearnSkill = 43,
+    /// Advertise optional protocol features the client understands.
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `44`
+    /// * bytes 1..5: capability mask (u32 LE, `CLIENT_CAP_*` in
+    ///   [`crate::constants`])
+    /// * bytes 5..16: zero-padding
+    CmdClientCaps = 44,
+    /// Ask for a fresh character sheet after an `SV_CHARCHECKSUM` mismatch.
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `45`
+    /// * bytes 1..5: the client's own checksum (u32 LE), for logging
+    /// * bytes 5..16: zero-padding
+    CmdRequestResync = 45,
+    /// Walk a route planned by the client (see [`crate::waypoints`]).
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `46`
+    /// * byte 1: waypoint count (1..=3), OR'd with
+    ///   [`WAYPOINTS_APPEND`](crate::waypoints::WAYPOINTS_APPEND) on every
+    ///   packet after the first of a route
+    /// * bytes 2..14: waypoints, each `x: u16 LE, y: u16 LE`
+    /// * bytes 14..16: zero-padding
+    CmdWaypoints = 46,
+    /// Tag the command in the next frame with a sequence number, so a
+    /// retransmitted duplicate is dropped instead of applied twice.
+    ///
+    /// Only honoured for clients advertising
+    /// [`CLIENT_CAP_COMMAND_SEQ`](crate::constants::CLIENT_CAP_COMMAND_SEQ);
+    /// the server acknowledges with `SV_COMMANDACK`.
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `47`
+    /// * bytes 1..5: sequence number (u32 LE, starts at 1)
+    /// * bytes 5..16: zero-padding
+    ///
+    /// Since: 1.5.0
+    CmdSeq = 47,
+    /// Announce the language NPC dialogue should be rendered in (see
+    /// [`crate::dialogue`]).
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `48`
+    /// * bytes 1..9: language code, ASCII, zero-padded (up to
+    ///   [`MAX_LOCALE_LEN`](crate::dialogue::MAX_LOCALE_LEN) bytes)
+    /// * bytes 9..16: zero-padding
+    ///
+    /// Since: 1.5.0
+    CmdClientLocale = 48,
+    /// Reorder the backpack (see [`crate::inventory_sort`]).
+    ///
+    /// Wire format:
+    /// * byte 0: opcode `49`
+    /// * byte 1: sort key ([`InventorySortKey`](crate::inventory_sort::InventorySortKey))
+    /// * bytes 2..16: zero-padding
+    ///
+    /// Since: 1.5.0
+    CmdSortInventory = 49,
     CmdCTick = 255,
 }
 
@@ -87,6 +189,17 @@ impl From<u8> for ClientCommandType {
             36 => ClientCommandType::CmdAutoloot,
             37 => ClientCommandType::CmdLearnTalent,
             38 => ClientCommandType::CmdResetTalents,
+            39 => ClientCommandType::CmdSetProfile,
+            40 => ClientCommandType::CmdSetTitle,
+            41 => ClientCommandType::CmdNpcMenu,
+            42 => ClientCommandType::CmdNpcAction,
+            43 => ClientCommandType::CmdLearnSkill,
+            44 => ClientCommandType::CmdClientCaps,
+            45 => ClientCommandType::CmdRequestResync,
+            46 => ClientCommandType::CmdWaypoints,
+            47 => ClientCommandType::CmdSeq,
+            48 => ClientCommandType::CmdClientLocale,
+            49 => ClientCommandType::CmdSortInventory,
             255 => ClientCommandType::CmdCTick,
             _ => {
                 log::error!("Unknown client command type: {}", value);
@@ -96,6 +209,37 @@ impl From<u8> for ClientCommandType {
     }
 }
 
+impl ClientCommandType {
+    /// Whether applying this command twice has a different effect than
+    /// applying it once (moving items, spending gold or points).
+    ///
+    /// Clients advertising
+    /// [`CLIENT_CAP_COMMAND_SEQ`](crate::constants::CLIENT_CAP_COMMAND_SEQ)
+    /// tag these with a `CmdSeq` frame.
+    ///
+    /// # Returns
+    ///
+    /// * `true` for commands worth deduplicating.
+    pub fn is_sequenced(self) -> bool {
+        matches!(
+            self,
+            ClientCommandType::CmdInv
+                | ClientCommandType::CmdGive
+                | ClientCommandType::CmdDrop
+                | ClientCommandType::CmdPickup
+                | ClientCommandType::CmdUse
+                | ClientCommandType::CmdShop
+                | ClientCommandType::CmdStat
+                | ClientCommandType::CmdSkill
+                | ClientCommandType::CmdAutoloot
+                | ClientCommandType::CmdLearnTalent
+                | ClientCommandType::CmdResetTalents
+                | ClientCommandType::CmdLearnSkill
+                | ClientCommandType::CmdSortInventory
+        )
+    }
+}
+
 /// A single outgoing command to the game server.
 ///
 /// Serialised to a fixed 16-byte packet by [`to_bytes`](Self::to_bytes).
@@ -176,15 +320,19 @@ impl ClientCommand {
     /// # Arguments
     ///
     /// * `ticket` - Value passed to `new_api_login`.
+    /// * `caps` - `CLIENT_CAP_*` bits that already apply while logging in,
+    ///   e.g. [`crate::constants::CLIENT_CAP_DISCONNECT_REASON`] so a
+    ///   rejected login is explained. Older clients leave these bytes zero.
     ///
     /// # Returns
     ///
     /// * A new instance configured by `new_api_login`.
-    pub fn new_api_login(ticket: u64) -> Self {
-        let mut payload = Vec::with_capacity(8);
+    pub fn new_api_login(ticket: u64, caps: u32) -> Self {
+        let mut payload = Vec::with_capacity(12);
         payload.extend_from_slice(&ticket.to_le_bytes());
+        payload.extend_from_slice(&caps.to_le_bytes());
         let mut cmd = Self::new(ClientCommandType::ApiLogin, payload);
-        cmd.context = Some(format!("ticket={ticket}"));
+        cmd.context = Some(format!("ticket={ticket} caps={caps:#x}"));
         cmd
     }
 
@@ -586,6 +734,241 @@ impl ClientCommand {
     pub fn new_reset_talents() -> Self {
         Self::new(ClientCommandType::CmdResetTalents, Vec::new())
     }
+
+    /// Creates a select-title command.
+    ///
+    /// # Arguments
+    ///
+    /// * `title_id` - Title to display (`0` clears the current title).
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_set_title`.
+    pub fn new_set_title(title_id: u8) -> Self {
+        let mut cmd = Self::new(ClientCommandType::CmdSetTitle, vec![title_id]);
+        cmd.context = Some(format!("title={}", title_id));
+        cmd
+    }
+
+    /// Creates an interaction-menu request for a character.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Character number that was right-clicked.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_npc_menu`.
+    pub fn new_npc_menu(target: u16) -> Self {
+        let mut cmd = Self::new(ClientCommandType::CmdNpcMenu, target.to_le_bytes().to_vec());
+        cmd.context = Some(format!("target={}", target));
+        cmd
+    }
+
+    /// Creates an NPC menu selection command.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Character number the menu belongs to.
+    /// * `option` - Chosen menu entry.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_npc_action`.
+    pub fn new_npc_action(target: u16, option: crate::npc_menu::NpcMenuOption) -> Self {
+        let mut payload = target.to_le_bytes().to_vec();
+        payload.push(option as u8);
+        let mut cmd = Self::new(ClientCommandType::CmdNpcAction, payload);
+        cmd.context = Some(format!("target={} option={:?}", target, option));
+        cmd
+    }
+
+    /// Creates a request to buy a skill from a trainer.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Trainer character number.
+    /// * `skill` - Skill index from the trainer's offer list.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_learn_skill`.
+    pub fn new_learn_skill(target: u16, skill: u8) -> Self {
+        let mut payload = target.to_le_bytes().to_vec();
+        payload.push(skill);
+        let mut cmd = Self::new(ClientCommandType::CmdLearnSkill, payload);
+        cmd.context = Some(format!("target={} skill={}", target, skill));
+        cmd
+    }
+
+    /// Creates the capability announcement sent right after login.
+    ///
+    /// # Arguments
+    ///
+    /// * `caps` - Bitmask of `CLIENT_CAP_*` flags.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_client_caps`.
+    pub fn new_client_caps(caps: u32) -> Self {
+        let mut cmd = Self::new(
+            ClientCommandType::CmdClientCaps,
+            caps.to_le_bytes().to_vec(),
+        );
+        cmd.context = Some(format!("caps={:#x}", caps));
+        cmd
+    }
+
+    /// Creates a resync request after a state checksum mismatch.
+    ///
+    /// # Arguments
+    ///
+    /// * `local_checksum` - Checksum of the client's own state.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_request_resync`.
+    pub fn new_request_resync(local_checksum: u32) -> Self {
+        let mut cmd = Self::new(
+            ClientCommandType::CmdRequestResync,
+            local_checksum.to_le_bytes().to_vec(),
+        );
+        cmd.context = Some(format!("local={:#010x}", local_checksum));
+        cmd
+    }
+
+    /// Creates the `CmdSeq` frame that tags the command sent right after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `seq` - Per-connection sequence number (starts at 1).
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_seq`.
+    pub fn new_seq(seq: u32) -> Self {
+        let mut cmd = Self::cmd_u32(ClientCommandType::CmdSeq, seq);
+        cmd.context = Some(format!("seq={seq}"));
+        cmd
+    }
+
+    /// Creates a language announcement for NPC dialogue.
+    ///
+    /// Codes longer than [`MAX_LOCALE_LEN`](crate::dialogue::MAX_LOCALE_LEN)
+    /// bytes are truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Language code, e.g. `de`.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_client_locale`.
+    pub fn new_client_locale(locale: &str) -> Self {
+        let mut payload = [0u8; crate::dialogue::MAX_LOCALE_LEN];
+        for (dst, src) in payload.iter_mut().zip(locale.bytes()) {
+            *dst = src;
+        }
+        let mut cmd = Self::new(ClientCommandType::CmdClientLocale, payload.to_vec());
+        cmd.context = Some(format!("locale={locale}"));
+        cmd
+    }
+
+    /// Creates a request to reorder the backpack.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Order to sort by.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance configured by `new_sort_inventory`.
+    pub fn new_sort_inventory(key: crate::inventory_sort::InventorySortKey) -> Self {
+        let mut cmd = Self::new(ClientCommandType::CmdSortInventory, vec![key.as_u8()]);
+        cmd.context = Some(format!("key={key:?}"));
+        cmd
+    }
+
+    /// Splits a route into `CmdWaypoints` packets.
+    ///
+    /// Waypoints beyond [`MAX_WAYPOINTS`](crate::waypoints::MAX_WAYPOINTS)
+    /// are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `waypoints` - World tiles to walk through, in order.
+    ///
+    /// # Returns
+    ///
+    /// * The packets to send, in order; empty if `waypoints` is empty.
+    pub fn new_waypoint_packets(waypoints: &[(u16, u16)]) -> Vec<Self> {
+        use crate::waypoints::{MAX_WAYPOINTS, WAYPOINTS_APPEND, WAYPOINTS_PER_PACKET};
+
+        let waypoints = &waypoints[..waypoints.len().min(MAX_WAYPOINTS)];
+        waypoints
+            .chunks(WAYPOINTS_PER_PACKET)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut flags = chunk.len() as u8;
+                if i > 0 {
+                    flags |= WAYPOINTS_APPEND;
+                }
+                let mut payload = Vec::with_capacity(1 + chunk.len() * 4);
+                payload.push(flags);
+                for (x, y) in chunk {
+                    payload.extend_from_slice(&x.to_le_bytes());
+                    payload.extend_from_slice(&y.to_le_bytes());
+                }
+                let mut cmd = Self::new(ClientCommandType::CmdWaypoints, payload);
+                cmd.context = Some(format!("chunk={} count={}", i, chunk.len()));
+                cmd
+            })
+            .collect()
+    }
+
+    /// Splits a profile bio into `CmdSetProfile` chunk packets.
+    ///
+    /// Always produces at least one packet so an empty bio still carries the
+    /// name color; text beyond [`MAX_BIO_LEN`](crate::profile::MAX_BIO_LEN)
+    /// is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_color` - Selected name color byte.
+    /// * `bio` - Bio text bytes.
+    ///
+    /// # Returns
+    ///
+    /// * The packets to send, in order; the last one carries the final flag.
+    pub fn new_profile_packets(name_color: u8, bio: &[u8]) -> Vec<Self> {
+        use crate::profile::{MAX_BIO_LEN, PROFILE_CHUNK_LEN, PROFILE_FINAL_CHUNK};
+
+        let bio = &bio[..bio.len().min(MAX_BIO_LEN)];
+        let chunks: Vec<&[u8]> = if bio.is_empty() {
+            vec![&[][..]]
+        } else {
+            bio.chunks(PROFILE_CHUNK_LEN).collect()
+        };
+
+        let last = chunks.len() - 1;
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut index = i as u8;
+                if i == last {
+                    index |= PROFILE_FINAL_CHUNK;
+                }
+                let mut payload = vec![0u8; 2 + PROFILE_CHUNK_LEN];
+                payload[0] = index;
+                payload[1] = name_color;
+                payload[2..2 + chunk.len()].copy_from_slice(chunk);
+                let mut cmd = Self::new(ClientCommandType::CmdSetProfile, payload);
+                cmd.context = Some(format!("chunk={} color={}", i, name_color));
+                cmd
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -598,6 +981,43 @@ mod tests {
         assert_eq!(cmd.to_bytes().len(), 16);
     }
 
+    #[test]
+    fn seq_frame_carries_u32_and_round_trips_opcode() {
+        let bytes = ClientCommand::new_seq(0x0102_0304).to_bytes();
+        assert_eq!(ClientCommandType::from(bytes[0]), ClientCommandType::CmdSeq);
+        assert_eq!(
+            u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]),
+            0x0102_0304
+        );
+        assert!(bytes[5..].iter().all(|&b| b == 0));
+        assert!(ClientCommandType::CmdInv.is_sequenced());
+        assert!(!ClientCommandType::CmdMove.is_sequenced());
+    }
+
+    #[test]
+    fn client_locale_is_zero_padded_ascii() {
+        let bytes = ClientCommand::new_client_locale("de").to_bytes();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(
+            ClientCommandType::from(bytes[0]),
+            ClientCommandType::CmdClientLocale
+        );
+        assert_eq!(&bytes[1..4], b"de\0");
+    }
+
+    #[test]
+    fn sort_inventory_carries_key_byte() {
+        use crate::inventory_sort::InventorySortKey;
+        let bytes = ClientCommand::new_sort_inventory(InventorySortKey::Value).to_bytes();
+        assert_eq!(
+            ClientCommandType::from(bytes[0]),
+            ClientCommandType::CmdSortInventory
+        );
+        assert_eq!(bytes[1], 1);
+        assert!(bytes[2..].iter().all(|&b| b == 0));
+        assert!(ClientCommandType::CmdSortInventory.is_sequenced());
+    }
+
     #[test]
     fn autoloot_graves_opcode_and_coords() {
         let cmd = ClientCommand::new_autoloot_graves(100, 200);
@@ -668,7 +1088,7 @@ mod tests {
 
     #[test]
     fn api_login_opcode_and_payload() {
-        let cmd = ClientCommand::new_api_login(0xDEAD_BEEF_CAFE_BABE);
+        let cmd = ClientCommand::new_api_login(0xDEAD_BEEF_CAFE_BABE, 0x2000);
         let bytes = cmd.to_bytes();
         assert_eq!(bytes[0], ClientCommandType::ApiLogin as u8);
         assert_eq!(
@@ -677,6 +1097,10 @@ mod tests {
             ]),
             0xDEAD_BEEF_CAFE_BABE
         );
+        assert_eq!(
+            u32::from_le_bytes([bytes[9], bytes[10], bytes[11], bytes[12]]),
+            0x2000
+        );
     }
 
     #[test]
@@ -758,6 +1182,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn set_title_opcode_and_id() {
+        let bytes = ClientCommand::new_set_title(17).to_bytes();
+        assert_eq!(bytes[0], ClientCommandType::CmdSetTitle as u8);
+        assert_eq!(bytes[1], 17);
+        assert!(bytes[2..].iter().all(|b| *b == 0));
+        assert_eq!(ClientCommandType::from(40), ClientCommandType::CmdSetTitle);
+    }
+
+    #[test]
+    fn npc_menu_and_action_layout() {
+        let menu = ClientCommand::new_npc_menu(0x1234).to_bytes();
+        assert_eq!(menu[0], ClientCommandType::CmdNpcMenu as u8);
+        assert_eq!(u16::from_le_bytes([menu[1], menu[2]]), 0x1234);
+
+        let action =
+            ClientCommand::new_npc_action(77, crate::npc_menu::NpcMenuOption::Trade).to_bytes();
+        assert_eq!(action[0], ClientCommandType::CmdNpcAction as u8);
+        assert_eq!(u16::from_le_bytes([action[1], action[2]]), 77);
+        assert_eq!(action[3], 1);
+        assert_eq!(ClientCommandType::from(42), ClientCommandType::CmdNpcAction);
+    }
+
+    #[test]
+    fn learn_skill_layout() {
+        let bytes = ClientCommand::new_learn_skill(0x0102, 29).to_bytes();
+        assert_eq!(bytes[0], ClientCommandType::CmdLearnSkill as u8);
+        assert_eq!(u16::from_le_bytes([bytes[1], bytes[2]]), 0x0102);
+        assert_eq!(bytes[3], 29);
+        assert!(bytes[4..].iter().all(|b| *b == 0));
+        assert_eq!(
+            ClientCommandType::from(43),
+            ClientCommandType::CmdLearnSkill
+        );
+    }
+
+    #[test]
+    fn client_caps_layout() {
+        let bytes =
+            ClientCommand::new_client_caps(crate::constants::CLIENT_CAP_CHAR_SHEET).to_bytes();
+        assert_eq!(bytes[0], ClientCommandType::CmdClientCaps as u8);
+        assert_eq!(
+            u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]),
+            crate::constants::CLIENT_CAP_CHAR_SHEET
+        );
+        assert_eq!(
+            ClientCommandType::from(44),
+            ClientCommandType::CmdClientCaps
+        );
+    }
+
     #[test]
     fn learn_talent_roundtrip_max_slot_bytes() {
         let cmd = ClientCommand::new_learn_talent(crate::talent_trees::TalentRef {
@@ -799,4 +1274,64 @@ mod tests {
         assert_eq!(ClientCommandType::from(4u8), ClientCommandType::_Empty);
         assert_eq!(ClientCommandType::from(19u8), ClientCommandType::_Empty);
     }
+
+    #[test]
+    fn profile_packets_chunk_and_flag_last() {
+        let bio = b"Ishtar is a wandering sellsword.";
+        let packets = ClientCommand::new_profile_packets(3, bio);
+        assert_eq!(packets.len(), 3);
+
+        let first = packets[0].to_bytes();
+        assert_eq!(first[0], ClientCommandType::CmdSetProfile as u8);
+        assert_eq!(first[1], 0);
+        assert_eq!(first[2], 3);
+        assert_eq!(&first[3..16], &bio[..13]);
+
+        let last = packets[2].to_bytes();
+        assert_eq!(last[1], 2 | crate::profile::PROFILE_FINAL_CHUNK);
+        assert_eq!(&last[3..9], &bio[26..]);
+        assert!(last[9..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn waypoint_packets_round_trip_through_decode() {
+        use crate::waypoints::decode_waypoints;
+
+        let route = [(10, 20), (11, 21), (12, 22), (13, 23)];
+        let packets = ClientCommand::new_waypoint_packets(&route);
+        assert_eq!(packets.len(), 2);
+
+        let first = packets[0].to_bytes();
+        assert_eq!(first[0], ClientCommandType::CmdWaypoints as u8);
+        assert_eq!(
+            decode_waypoints(&first[1..]),
+            Some((false, route[..3].to_vec()))
+        );
+        let second = packets[1].to_bytes();
+        assert_eq!(
+            decode_waypoints(&second[1..]),
+            Some((true, route[3..].to_vec()))
+        );
+        assert_eq!(ClientCommandType::from(46), ClientCommandType::CmdWaypoints);
+    }
+
+    #[test]
+    fn profile_packets_empty_bio_sends_one_final_packet() {
+        let packets = ClientCommand::new_profile_packets(1, b"");
+        assert_eq!(packets.len(), 1);
+        let bytes = packets[0].to_bytes();
+        assert_eq!(bytes[1], crate::profile::PROFILE_FINAL_CHUNK);
+        assert_eq!(bytes[2], 1);
+    }
+
+    #[test]
+    fn profile_packets_truncate_to_max_chunks() {
+        let bio = vec![b'a'; 500];
+        let packets = ClientCommand::new_profile_packets(0, &bio);
+        assert_eq!(packets.len(), crate::profile::MAX_PROFILE_CHUNKS);
+        assert_eq!(
+            ClientCommandType::from(39u8),
+            ClientCommandType::CmdSetProfile
+        );
+    }
 }
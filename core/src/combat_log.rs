@@ -0,0 +1,204 @@
+//! Shared combat event model and wire helpers for `SV_COMBATEVENT`.
+//!
+//! Every hit resolved by the server's `do_hurt` is reported to the players
+//! on both ends of it, if their client advertises
+//! [`CLIENT_CAP_COMBAT_LOG`](crate::constants::CLIENT_CAP_COMBAT_LOG). One
+//! event breaks the hit down into what spell immunity resisted, what the
+//! attack was worth before armor, what armor and magic shields absorbed, and
+//! what the target lost. Amounts are in thousandths of a hit point, the unit
+//! the server keeps `a_hp` in, so small hits are not rounded away.
+
+use crate::skills;
+use crate::string_operations::{c_string_to_str, write_ascii_into_fixed};
+
+/// Bytes of each character name in `SV_COMBATEVENT`, NUL padded.
+pub const COMBAT_NAME_LEN: usize = 16;
+
+/// Total size of an `SV_COMBATEVENT` packet: opcode, kind, skill, flags,
+/// four `u32` amounts and the two names.
+pub const COMBAT_EVENT_PACKET_LEN: usize = 4 + 4 * 4 + 2 * COMBAT_NAME_LEN;
+
+/// Skill byte of an event that no skill caused (traps, reflected damage).
+pub const NO_SKILL: u8 = u8::MAX;
+
+/// Flag bit: the receiver's character dealt the hit.
+pub const COMBAT_FLAG_OUTGOING: u8 = 1 << 0;
+
+/// Flag bit: the hit was fatal.
+pub const COMBAT_FLAG_KILLED: u8 = 1 << 1;
+
+/// How a hit was delivered; mirrors the `type` argument of `do_hurt`.
+///
+/// Numeric values are part of the wire protocol — do not renumber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DamageKind {
+    /// Weapon or unarmed blow; armor counts in full.
+    Melee = 0,
+    /// Offensive spell; armor counts for less.
+    Spell = 1,
+    /// Damage that awards no experience, such as item and trap effects.
+    Direct = 2,
+    /// Damage reflected back by the target's gethit, which ignores armor.
+    Reflect = 3,
+}
+
+impl DamageKind {
+    /// Decodes a kind byte; unknown values fall back to
+    /// [`DamageKind::Direct`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Kind byte from the packet, or a `do_hurt` type.
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => DamageKind::Melee,
+            1 => DamageKind::Spell,
+            3 => DamageKind::Reflect,
+            _ => DamageKind::Direct,
+        }
+    }
+
+    /// Short lowercase label used by the combat log panel and its CSV export.
+    pub fn label(self) -> &'static str {
+        match self {
+            DamageKind::Melee => "melee",
+            DamageKind::Spell => "spell",
+            DamageKind::Direct => "direct",
+            DamageKind::Reflect => "reflect",
+        }
+    }
+}
+
+/// One hit as reported by `SV_COMBATEVENT`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CombatEvent {
+    /// How the hit was delivered.
+    pub kind: u8,
+    /// Skill number that caused the hit, or [`NO_SKILL`].
+    pub skill: u8,
+    /// `COMBAT_FLAG_*` bits.
+    pub flags: u8,
+    /// Damage before armor.
+    pub raw: u32,
+    /// Damage spell immunity removed before `raw` was rolled.
+    pub resisted: u32,
+    /// Part of `raw` that armor, magic shields or immortality absorbed.
+    pub mitigated: u32,
+    /// Hit points the target lost.
+    pub dealt: u32,
+    /// Attacker name; empty for damage without a source.
+    pub attacker: String,
+    /// Target name.
+    pub target: String,
+}
+
+impl CombatEvent {
+    /// Whether the receiving character dealt this hit.
+    pub fn is_outgoing(&self) -> bool {
+        self.flags & COMBAT_FLAG_OUTGOING != 0
+    }
+
+    /// Whether this hit killed the target.
+    pub fn is_kill(&self) -> bool {
+        self.flags & COMBAT_FLAG_KILLED != 0
+    }
+
+    /// Name of the skill that caused the hit.
+    ///
+    /// # Returns
+    ///
+    /// * The skill name, or an empty string for [`NO_SKILL`].
+    pub fn skill_name(&self) -> &'static str {
+        if self.skill == NO_SKILL {
+            ""
+        } else {
+            skills::get_skill_name(usize::from(self.skill))
+        }
+    }
+}
+
+/// Builds an `SV_COMBATEVENT` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::CombatEvent` as a byte.
+/// * `event` - The hit, with names cut to [`COMBAT_NAME_LEN`].
+///
+/// # Returns
+///
+/// * The encoded packet.
+pub fn encode_combat_event(opcode: u8, event: &CombatEvent) -> [u8; COMBAT_EVENT_PACKET_LEN] {
+    let mut buf = [0u8; COMBAT_EVENT_PACKET_LEN];
+    buf[0] = opcode;
+    buf[1] = event.kind;
+    buf[2] = event.skill;
+    buf[3] = event.flags;
+    for (n, amount) in [event.raw, event.resisted, event.mitigated, event.dealt]
+        .into_iter()
+        .enumerate()
+    {
+        buf[4 + n * 4..8 + n * 4].copy_from_slice(&amount.to_le_bytes());
+    }
+    // One extra byte so a full-length name keeps all its letters.
+    let mut name_buf = [0u8; COMBAT_NAME_LEN + 1];
+    write_ascii_into_fixed(&mut name_buf, &event.attacker);
+    buf[20..20 + COMBAT_NAME_LEN].copy_from_slice(&name_buf[..COMBAT_NAME_LEN]);
+    write_ascii_into_fixed(&mut name_buf, &event.target);
+    buf[20 + COMBAT_NAME_LEN..].copy_from_slice(&name_buf[..COMBAT_NAME_LEN]);
+    buf
+}
+
+/// Decodes an `SV_COMBATEVENT` packet.
+///
+/// # Arguments
+///
+/// * `bytes` - The whole packet, opcode included.
+///
+/// # Returns
+///
+/// * The event, or `None` when the packet is truncated.
+pub fn decode_combat_event(bytes: &[u8]) -> Option<CombatEvent> {
+    let bytes = bytes.get(..COMBAT_EVENT_PACKET_LEN)?;
+    let amount = |n: usize| u32::from_le_bytes(bytes[4 + n * 4..8 + n * 4].try_into().unwrap());
+    Some(CombatEvent {
+        kind: bytes[1],
+        skill: bytes[2],
+        flags: bytes[3],
+        raw: amount(0),
+        resisted: amount(1),
+        mitigated: amount(2),
+        dealt: amount(3),
+        attacker: c_string_to_str(&bytes[20..20 + COMBAT_NAME_LEN]).to_owned(),
+        target: c_string_to_str(&bytes[20 + COMBAT_NAME_LEN..]).to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_round_trips() {
+        let event = CombatEvent {
+            kind: DamageKind::Spell as u8,
+            skill: skills::SK_BLAST as u8,
+            flags: COMBAT_FLAG_OUTGOING | COMBAT_FLAG_KILLED,
+            raw: 45_000,
+            resisted: 6_000,
+            mitigated: 3_750,
+            dealt: 41_250,
+            attacker: "Sixteen Letterss".to_owned(),
+            target: "Grolm".to_owned(),
+        };
+        let pkt = encode_combat_event(99, &event);
+        assert_eq!(pkt[0], 99);
+        assert_eq!(decode_combat_event(&pkt), Some(event.clone()));
+        assert_eq!(decode_combat_event(&pkt[..30]), None);
+
+        assert!(event.is_outgoing() && event.is_kill());
+        assert_eq!(event.skill_name(), skills::get_skill_name(skills::SK_BLAST));
+        assert_eq!(DamageKind::from_u8(event.kind), DamageKind::Spell);
+        assert_eq!(DamageKind::from_u8(9), DamageKind::Direct);
+    }
+}
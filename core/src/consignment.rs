@@ -0,0 +1,208 @@
+//! Shared consignment limits, fees and wire helpers for `SV_CONSIGNPAGE`.
+//!
+//! Players hand an item to a broker NPC (a character flagged
+//! [`CharacterFlags::Broker`](crate::constants::CharacterFlags::Broker))
+//! with `#consign sell <gold> [silver]`. The listing waits with the server
+//! (see `server/src/consignment`) until another player buys it through the
+//! broker's shop window or it expires and is mailed back to the seller.
+//!
+//! The broker keeps a listing fee up front and a commission on every sale,
+//! so both leave the economy. A client advertising
+//! [`CLIENT_CAP_CONSIGNMENT`](crate::constants::CLIENT_CAP_CONSIGNMENT)
+//! receives one `ConsignmentPage` packet before each page of listings, which
+//! the shop window uses for its page buttons and search box.
+
+use crate::string_operations::{c_string_to_str, write_ascii_into_fixed};
+
+/// Listings shown per page; one per shop window slot.
+pub const CONSIGN_PAGE_SLOTS: usize = 62;
+
+/// Most listings one seller may have with the brokers at a time.
+pub const MAX_LISTINGS_PER_SELLER: usize = 20;
+
+/// How long a listing stays up, in seconds (three days).
+pub const CONSIGN_LISTING_SECS: i64 = 3 * 24 * 60 * 60;
+
+/// Highest asking price in silver (100 000 gold).
+pub const MAX_CONSIGN_PRICE: i32 = 10_000_000;
+
+/// Share of the asking price kept as the listing fee, in percent.
+pub const CONSIGN_FEE_PERCENT: i32 = 2;
+
+/// Share of the sale price the broker keeps as commission, in percent.
+pub const CONSIGN_COMMISSION_PERCENT: i32 = 10;
+
+/// Longest search text in bytes.
+pub const MAX_CONSIGN_QUERY_LEN: usize = 20;
+
+/// Total `SV_CONSIGNPAGE` packet size: opcode (1) + broker (u16) + page
+/// (u16) + pages (u16) + total (u16) + search text.
+pub const CONSIGN_PAGE_PACKET_LEN: usize = 9 + MAX_CONSIGN_QUERY_LEN;
+
+/// Fee the broker charges for putting an item up at `price`.
+///
+/// # Arguments
+///
+/// * `price` - Asking price in silver, at most [`MAX_CONSIGN_PRICE`].
+///
+/// # Returns
+///
+/// * [`CONSIGN_FEE_PERCENT`] of the price, at least one silver.
+pub fn consign_fee(price: i32) -> i32 {
+    (price * CONSIGN_FEE_PERCENT / 100).max(1)
+}
+
+/// What the seller receives when an item sells for `price`.
+///
+/// # Arguments
+///
+/// * `price` - Sale price in silver, at most [`MAX_CONSIGN_PRICE`].
+///
+/// # Returns
+///
+/// * The price less [`CONSIGN_COMMISSION_PERCENT`].
+pub fn consign_proceeds(price: i32) -> i32 {
+    price - price * CONSIGN_COMMISSION_PERCENT / 100
+}
+
+/// Checks an asking price.
+///
+/// # Arguments
+///
+/// * `price` - Asking price in silver.
+///
+/// # Returns
+///
+/// * `Ok(())` for 1..=[`MAX_CONSIGN_PRICE`], or a message for the player.
+pub fn validate_consign_price(price: i64) -> Result<(), String> {
+    if price <= 0 {
+        return Err("The broker will not give your item away.".to_owned());
+    }
+    if price > i64::from(MAX_CONSIGN_PRICE) {
+        return Err(format!(
+            "The broker will not ask more than {}G for an item.",
+            MAX_CONSIGN_PRICE / 100
+        ));
+    }
+    Ok(())
+}
+
+/// Whether an item name matches a search.
+///
+/// # Arguments
+///
+/// * `name` - Item name.
+/// * `query` - Search text; every word must appear in the name, ignoring
+///   case. An empty search matches everything.
+pub fn matches_query(name: &str, query: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    query
+        .split_whitespace()
+        .all(|word| name.contains(&word.to_ascii_lowercase()))
+}
+
+/// Number of pages needed for `total` listings; at least one.
+pub fn page_count(total: usize) -> usize {
+    total.div_ceil(CONSIGN_PAGE_SLOTS).max(1)
+}
+
+/// Which page of a broker's listings the receiver is looking at.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsignmentPage {
+    /// Character number of the broker, as in the shop window's `SV_LOOK3`.
+    pub broker: u16,
+    /// Zero-based page shown.
+    pub page: u16,
+    /// Number of pages for the current search.
+    pub pages: u16,
+    /// Number of listings matching the current search.
+    pub total: u16,
+    /// Current search text; empty for all listings.
+    pub query: String,
+}
+
+/// Builds an `SV_CONSIGNPAGE` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::ConsignmentPage` as a byte.
+/// * `page` - The page about to be sent as shop window slots.
+///
+/// # Returns
+///
+/// * The encoded packet, [`CONSIGN_PAGE_PACKET_LEN`] bytes.
+pub fn encode_consignment_page(opcode: u8, page: &ConsignmentPage) -> Vec<u8> {
+    let mut buf = vec![0u8; CONSIGN_PAGE_PACKET_LEN];
+    buf[0] = opcode;
+    buf[1..3].copy_from_slice(&page.broker.to_le_bytes());
+    buf[3..5].copy_from_slice(&page.page.to_le_bytes());
+    buf[5..7].copy_from_slice(&page.pages.to_le_bytes());
+    buf[7..9].copy_from_slice(&page.total.to_le_bytes());
+    // One extra byte so a full-length search keeps all its letters.
+    let mut query = [0u8; MAX_CONSIGN_QUERY_LEN + 1];
+    write_ascii_into_fixed(&mut query, &page.query);
+    buf[9..].copy_from_slice(&query[..MAX_CONSIGN_QUERY_LEN]);
+    buf
+}
+
+/// Decodes an `SV_CONSIGNPAGE` packet.
+///
+/// # Arguments
+///
+/// * `bytes` - The whole packet, opcode included.
+///
+/// # Returns
+///
+/// * The page, or `None` when the packet is truncated.
+pub fn decode_consignment_page(bytes: &[u8]) -> Option<ConsignmentPage> {
+    let word = |at: usize| -> Option<u16> {
+        Some(u16::from_le_bytes([*bytes.get(at)?, *bytes.get(at + 1)?]))
+    };
+    Some(ConsignmentPage {
+        broker: word(1)?,
+        page: word(3)?,
+        pages: word(5)?,
+        total: word(7)?,
+        query: c_string_to_str(bytes.get(9..CONSIGN_PAGE_PACKET_LEN)?).to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_round_trips() {
+        let page = ConsignmentPage {
+            broker: 812,
+            page: 2,
+            pages: 5,
+            total: 290,
+            query: "golden ring of hope".to_owned(),
+        };
+        let pkt = encode_consignment_page(95, &page);
+        assert_eq!(pkt.len(), CONSIGN_PAGE_PACKET_LEN);
+        assert_eq!(decode_consignment_page(&pkt), Some(page));
+        assert_eq!(decode_consignment_page(&pkt[..12]), None);
+    }
+
+    #[test]
+    fn fees_and_commission_leave_the_economy() {
+        assert_eq!(consign_fee(10_000), 200);
+        assert_eq!(consign_fee(10), 1);
+        assert_eq!(consign_proceeds(10_000), 9_000);
+        assert_eq!(consign_proceeds(MAX_CONSIGN_PRICE), 9_000_000);
+        assert!(validate_consign_price(0).is_err());
+        assert!(validate_consign_price(i64::from(MAX_CONSIGN_PRICE) + 1).is_err());
+        assert!(validate_consign_price(1).is_ok());
+    }
+
+    #[test]
+    fn searches_need_every_word() {
+        assert!(matches_query("Golden Ring", ""));
+        assert!(matches_query("Golden Ring", "ring GOLD"));
+        assert!(!matches_query("Golden Ring", "silver ring"));
+        assert_eq!(page_count(0), 1);
+        assert_eq!(page_count(CONSIGN_PAGE_SLOTS + 1), 2);
+    }
+}
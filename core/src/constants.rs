@@ -10,6 +10,96 @@ use bitflags::bitflags;
 pub const VERSION: u32 = 0x020E07;
 pub const MINVERSION: u32 = 0x020E06;
 
+/// Client capability bit: the client understands `SV_SETCHARSHEET`
+/// snapshots (see [`crate::char_sheet`]). Advertised with `CmdClientCaps`.
+pub const CLIENT_CAP_CHAR_SHEET: u32 = 1 << 0;
+
+/// Client capability bit: the client understands `SV_TIMESYNC` clock
+/// packets. Advertised with `CmdClientCaps`.
+pub const CLIENT_CAP_TIME_SYNC: u32 = 1 << 1;
+
+/// Client capability bit: the client understands `SV_SETVIEW` and can zoom
+/// its camera out to the announced view radius (see [`crate::view`]).
+/// Advertised with `CmdClientCaps`.
+pub const CLIENT_CAP_WIDE_VIEW: u32 = 1 << 2;
+
+/// Client capability bit: the client understands `SV_MAPMARKER` minimap
+/// points of interest (see [`crate::map_markers`]). Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_MAP_MARKERS: u32 = 1 << 3;
+
+/// Client capability bit: the client understands `SV_SKILLTIMER` cast and
+/// cooldown timers (see [`crate::skill_timers`]). Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_SKILL_TIMERS: u32 = 1 << 4;
+
+/// Client capability bit: the client tags non-idempotent commands with
+/// `CmdSeq` and understands `SV_COMMANDACK`. The server then drops commands
+/// whose sequence number it has already applied. Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_COMMAND_SEQ: u32 = 1 << 5;
+
+/// Client capability bit: the client renders keyed NPC dialogue from
+/// `SV_DIALOGUE` with its own catalog (see [`crate::dialogue`]). Without it
+/// the server sends dialogue as chat text rendered in the client's locale.
+/// Advertised with `CmdClientCaps`.
+pub const CLIENT_CAP_DIALOGUE_KEYS: u32 = 1 << 6;
+
+/// Client capability bit: the client attenuates and pans area sounds itself
+/// and accepts `SV_PLAYSOUNDAT` with the source's tile offset instead of the
+/// server-computed volume and pan of `SV_PLAYSOUND`. Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_POSITIONAL_SOUND: u32 = 1 << 7;
+
+/// Client capability bit: the client draws short-lived decals (blood,
+/// scorch marks) from `SV_MAPDECAL` (see [`crate::decals`]). Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_MAP_DECALS: u32 = 1 << 8;
+
+/// Client capability bit: the client keeps the names of its backpack items
+/// from `SV_ITEMNAME`, used by the inventory search box. Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_ITEM_NAMES: u32 = 1 << 9;
+
+/// Client capability bit: the client shows its guild from `SV_GUILDINFO`
+/// and `SV_GUILDMEMBER` in the guild panel. Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_GUILDS: u32 = 1 << 10;
+
+/// Client capability bit: the client shows its mailbox from `SV_MAILENTRY`
+/// in the mail panel. Advertised with `CmdClientCaps`.
+pub const CLIENT_CAP_MAIL: u32 = 1 << 11;
+
+/// Client capability bit: the client shows page buttons and a search box in
+/// a broker's shop window from `SV_CONSIGNPAGE`. Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_CONSIGNMENT: u32 = 1 << 12;
+
+/// Client capability bit: the client shows why it was disconnected from
+/// `SV_DISCONNECT`. Advertised with `CmdClientCaps` and, so login
+/// rejections can carry it too, with `CL_API_LOGIN`.
+pub const CLIENT_CAP_DISCONNECT_REASON: u32 = 1 << 13;
+
+/// Client capability bit: the client shows its friends list and their
+/// online state from `SV_FRIENDENTRY` in the friends panel. Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_FRIENDS: u32 = 1 << 14;
+
+/// Client capability bit: the client follows `SV_OBSERVER` and switches its
+/// free camera to moving the server-side observer camera. Advertised with
+/// `CmdClientCaps`.
+pub const CLIENT_CAP_OBSERVER: u32 = 1 << 15;
+
+/// Client capability bit: the client keeps a combat log from
+/// `SV_COMBATEVENT`, one packet per hit its character deals or takes.
+/// Advertised with `CmdClientCaps`.
+pub const CLIENT_CAP_COMBAT_LOG: u32 = 1 << 16;
+
+/// Client capability bit: the client renders item link tokens in chat and
+/// keeps the `SV_ITEMLINK` snapshots sent before them for its tooltips.
+/// Advertised with `CmdClientCaps`.
+pub const CLIENT_CAP_ITEM_LINKS: u32 = 1 << 17;
+
 /// Ticks per second
 pub const TICKS: i32 = 36;
 
@@ -23,6 +113,15 @@ pub const LEGACY_TICKS: i32 = 18;
 /// Microseconds per tick
 pub const TICK: i64 = 1_000_000 / TICKS as i64;
 
+/// Seconds without a client keepalive (`CmdCTick` or `Ping`) before the
+/// server treats the connection as dead and frees the slot.
+pub const CLIENT_KEEPALIVE_TIMEOUT_SECS: i32 = 20;
+
+/// Seconds without any bytes from the server before the client treats the
+/// connection as dead. The server sends a tick packet every tick, so
+/// silence this long means the peer or the path is gone.
+pub const SERVER_SILENCE_TIMEOUT_SECS: u64 = 10;
+
 /// Server map dimensions
 pub const SERVER_MAPX: i32 = 1024;
 pub const SERVER_MAPY: i32 = 1024;
@@ -279,6 +378,10 @@ bitflags::bitflags! {
         const GreaterGod = 1u64 << 45;
         /// Greater invisibility privilege.
         const GreaterInv = 1u64 << 46;
+        /// Consignment broker: players list items for sale with this NPC.
+        const Broker = 1u64 << 47;
+        /// GM observer: off the map, unseen, moving a free camera.
+        const Observer = 1u64 << 48;
     }
 }
 
@@ -339,6 +442,8 @@ pub fn character_flags_name(flag: CharacterFlags) -> &'static str {
         CharacterFlags::SaveMe => "SaveMe",
         CharacterFlags::GreaterGod => "GreaterGod",
         CharacterFlags::GreaterInv => "GreaterInv",
+        CharacterFlags::Broker => "Broker",
+        CharacterFlags::Observer => "Observer",
         _ => "UnknownFlag",
     }
 }
@@ -387,6 +492,9 @@ pub const GMAGIC: u32 = (1 << 25) | (1 << 26) | (1 << 27);
 pub const GMAGIC1: u32 = 1 << 25;
 pub const CMAGIC: u32 = (1 << 28) | (1 << 29) | (1 << 30);
 pub const CMAGIC1: u32 = 1 << 28;
+/// Set on a client smap tile whose item is loot still reserved for another
+/// player (see [`crate::loot`]). The client draws such items greyed out.
+pub const LOOT_PROTECTED: u32 = 1 << 31;
 
 // Map tile flags
 pub const MF_MOVEBLOCK: u32 = 1 << 0;
@@ -402,6 +510,16 @@ pub const MF_DEATHTRAP: u32 = 1 << 9;
 pub const MF_ARENA: u32 = 1 << 11;
 pub const MF_NOEXPIRE: u32 = 1 << 13;
 pub const MF_NOFIGHT: u64 = 1 << 14;
+/// Shrine tile: `#pray` here buys a blessing (see [`crate::shrines`]).
+pub const MF_SHRINE: u64 = 1 << 15;
+
+// Client smap `flags2` hints, sent for every visible tile. The low bits of
+// `flags2` are reserved for the raw `MF_*` tile flags the god overlay shows.
+/// The tile blocks walking (terrain or a blocking item other than a door).
+/// Used by the client's path preview (see [`crate::waypoints`]).
+pub const WALK_BLOCKED: u32 = 1 << 16;
+/// The tile is part of a bank (`MF_BANK`).
+pub const IS_BANK: u32 = 1 << 17;
 
 // Dynamic map flags (32 bits offset)
 pub const MF_GFX_INJURED: u64 = 1 << 32;
@@ -436,6 +554,9 @@ pub const GF_CLOSEENEMY: i32 = 1 << 2;
 pub const GF_CAP: i32 = 1 << 3;
 pub const GF_SPEEDY: i32 = 1 << 4;
 pub const GF_DIRTY: i32 = 1 << 5;
+/// Hourly statistics in `Global` are indexed by UTC hour rather than the
+/// host's local hour.
+pub const GF_UTC_HOURS: i32 = 1 << 6;
 
 // =============================================================================
 // Character Data Indices (from data.h)
@@ -543,6 +664,12 @@ bitflags! {
         const IF_NOEXPIRE = 1 << 38;
         /// item was enhanced by a soulstone
         const IF_SOULSTONE = 1 << 39;
+        /// quest item: may not be dropped, given or sold
+        const IF_QUEST = 1 << 40;
+        /// binds to the first character to pick it up
+        const IF_BIND_PICKUP = 1 << 41;
+        /// binds to the first character to equip it
+        const IF_BIND_EQUIP = 1 << 42;
 
         /// Composite: all weapon types
         const IF_WEAPON = Self::IF_WP_SWORD.bits() | Self::IF_WP_DAGGER.bits()
@@ -1004,6 +1131,8 @@ mod tests {
             CharacterFlags::SaveMe,
             CharacterFlags::GreaterGod,
             CharacterFlags::GreaterInv,
+            CharacterFlags::Broker,
+            CharacterFlags::Observer,
         ];
 
         // Verify each flag is a power of 2 (has exactly one bit set)
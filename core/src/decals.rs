@@ -0,0 +1,80 @@
+//! Shared kinds for short-lived map decals sent with `SV_MAPDECAL`
+//! (`MapDecal`).
+//!
+//! Decals are purely cosmetic: the server emits one when a fight leaves a
+//! mark on a tile and forgets about it immediately; the client fades it out
+//! over the packet's TTL. Nothing is stored in the map.
+
+/// Longest TTL the server will send, in ticks (one minute).
+pub const MAX_DECAL_TTL: u16 = 60 * crate::constants::TICKS as u16;
+
+/// Kinds of decal the server can emit.
+///
+/// Numeric values are part of the wire protocol — do not renumber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DecalKind {
+    /// Blood drops left by a melee hit.
+    Blood = 0,
+    /// Soot left by a damaging spell; drawn as a darkened splatter.
+    Scorch = 1,
+}
+
+impl DecalKind {
+    /// Returns the wire-protocol byte representation.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Decodes a wire byte; unknown values fall back to [`DecalKind::Blood`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Kind byte from the packet.
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => DecalKind::Scorch,
+            _ => DecalKind::Blood,
+        }
+    }
+
+    /// Picks the sprite for a decal of this kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `heavy` - Whether the hit was strong enough for the larger splatter.
+    ///
+    /// # Returns
+    ///
+    /// * Sprite number from the shared injury splatter set (1079–1082).
+    pub fn sprite(self, heavy: bool) -> u32 {
+        match (self, heavy) {
+            (DecalKind::Blood, false) => 1080,
+            (DecalKind::Blood, true) => 1082,
+            (DecalKind::Scorch, false) => 1081,
+            (DecalKind::Scorch, true) => 1082,
+        }
+    }
+
+    /// Default lifetime of a decal of this kind, in ticks.
+    pub fn default_ttl(self) -> u16 {
+        match self {
+            DecalKind::Blood => 20 * crate::constants::TICKS as u16,
+            DecalKind::Scorch => 30 * crate::constants::TICKS as u16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_round_trips_and_ttls_fit_the_cap() {
+        for kind in [DecalKind::Blood, DecalKind::Scorch] {
+            assert_eq!(DecalKind::from_u8(kind.as_u8()), kind);
+            assert!(kind.default_ttl() <= MAX_DECAL_TTL);
+        }
+        assert_eq!(DecalKind::from_u8(200), DecalKind::Blood);
+    }
+}
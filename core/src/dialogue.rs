@@ -0,0 +1,445 @@
+//! Keyed, translatable NPC dialogue.
+//!
+//! NPC and quest lines are referenced by a key plus named parameters
+//! ([`DialogueLine`]) instead of being formatted on the server. Each key has
+//! one template per language in a [`DialogueCatalog`]; templates use
+//! `{name}` placeholders for the parameters.
+//!
+//! Clients advertising
+//! [`CLIENT_CAP_DIALOGUE_KEYS`](crate::constants::CLIENT_CAP_DIALOGUE_KEYS)
+//! get the key and parameters in `SV_DIALOGUE` and render the line in their
+//! own language. For all other clients the server renders the line in the
+//! locale announced with `CmdClientLocale` (English by default) and sends
+//! plain chat text.
+//!
+//! Catalogs are plain text, one file per language named after the locale
+//! (`de.txt`, `pl.txt`, ...), so translations can be contributed without
+//! touching code. Each non-empty line not starting with `#` maps a key to
+//! its template:
+//!
+//! ```text
+//! npc.give.no_money = I don't take money from you!
+//! ```
+//!
+//! Keys missing from a translation fall back to English.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Locale every key has a template in.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Longest locale code carried by `CmdClientLocale`.
+pub const MAX_LOCALE_LEN: usize = 8;
+
+/// Most parameters a single line may carry on the wire.
+pub const MAX_DIALOGUE_PARAMS: usize = 8;
+
+/// Longest key, parameter name or parameter value on the wire, in bytes.
+pub const MAX_DIALOGUE_FIELD_LEN: usize = 255;
+
+/// Built-in English templates.
+pub const DEFAULT_DIALOGUE: &str = "\
+# key = template; {name} is replaced by the parameter of that name.
+npc.quests.looking_for = I am looking for {item}, {player}.
+npc.train.bring_item = Bring me {item} and I will teach you {skill}, {player}.
+npc.give.no_money = I don't take money from you!
+quest.turn_in.thanks = Thank you {player}. That's the {item} I wanted.
+quest.turn_in.black_candle = Ah, a black candle! Great work, {player}! Now we will have peace for a while...
+quest.turn_in.again_immunity = Bring me the item again to learn Immunity, {player}!
+quest.turn_in.again_surround_hit = Bring me the item again to learn Surround Hit, {player}!
+quest.turn_in.teach_skill = Now I'll teach you {skill}.
+quest.turn_in.already_known = But you already know {skill}, {player}!
+quest.turn_in.teach_experience = Now I'll teach you a bit about life, the world and everything, {player}.
+quest.turn_in.return_gift = Here is your {item} in exchange.
+";
+
+/// A dialogue line: template key plus named parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DialogueLine {
+    /// Catalog key, e.g. `quest.turn_in.thanks`.
+    pub key: String,
+    /// `(name, value)` pairs substituted into the template.
+    pub params: Vec<(String, String)>,
+}
+
+impl DialogueLine {
+    /// Creates a line without parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Catalog key.
+    ///
+    /// # Returns
+    ///
+    /// * A new `DialogueLine`.
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_owned(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Adds a parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Placeholder name, without braces.
+    /// * `value` - Replacement text.
+    ///
+    /// # Returns
+    ///
+    /// * The line, for chaining.
+    pub fn with(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.params.push((name.to_owned(), value.into()));
+        self
+    }
+}
+
+/// Reduces a locale string to the lowercase language code used as a
+/// catalog name, e.g. `de_DE.UTF-8` and `de-AT` both become `de`.
+///
+/// # Arguments
+///
+/// * `raw` - Locale from the OS, the settings or `CmdClientLocale`.
+///
+/// # Returns
+///
+/// * The language code, or `None` if `raw` does not start with one.
+pub fn normalize_locale(raw: &str) -> Option<String> {
+    let language = raw
+        .trim()
+        .split(['-', '_', '.', '@'])
+        .next()
+        .unwrap_or_default();
+    let valid =
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic());
+    valid.then(|| language.to_ascii_lowercase())
+}
+
+/// Substitutes `{name}` placeholders; unknown placeholders are kept as-is.
+///
+/// # Arguments
+///
+/// * `template` - Template text.
+/// * `params` - `(name, value)` pairs.
+///
+/// # Returns
+///
+/// * The rendered text.
+pub fn render_template(template: &str, params: &[(String, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            params
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| (v, close))
+        });
+        match value {
+            Some((value, close)) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parses one catalog file.
+///
+/// # Arguments
+///
+/// * `text` - File contents.
+///
+/// # Returns
+///
+/// * Key to template map, or an error naming the first bad line.
+pub fn parse_catalog(text: &str) -> Result<HashMap<String, String>, String> {
+    let mut entries = HashMap::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |msg: &str| format!("line {}: {}", line_no + 1, msg);
+        let (key, template) = line
+            .split_once('=')
+            .ok_or_else(|| err("expected 'key = template'"))?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(err("bad key"));
+        }
+        if entries
+            .insert(key.to_owned(), template.trim().to_owned())
+            .is_some()
+        {
+            return Err(err(&format!("duplicate key '{}'", key)));
+        }
+    }
+    Ok(entries)
+}
+
+/// Templates for every key, per locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogueCatalog {
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for DialogueCatalog {
+    fn default() -> Self {
+        let english = parse_catalog(DEFAULT_DIALOGUE).expect("built-in dialogue must parse");
+        Self {
+            locales: HashMap::from([(DEFAULT_LOCALE.to_owned(), english)]),
+        }
+    }
+}
+
+impl DialogueCatalog {
+    /// Adds or extends a translation.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Language code.
+    /// * `entries` - Key to template map; replaces existing templates.
+    pub fn insert_locale(&mut self, locale: &str, entries: HashMap<String, String>) {
+        self.locales
+            .entry(locale.to_owned())
+            .or_default()
+            .extend(entries);
+    }
+
+    /// Loads every `<locale>.txt` file in `dir` on top of the built-in
+    /// English templates.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory holding the catalog files.
+    ///
+    /// # Returns
+    ///
+    /// * Number of files loaded, or an error naming the first file that
+    ///   cannot be read or parsed.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize, String> {
+        let read_dir = std::fs::read_dir(dir)
+            .map_err(|e| format!("cannot read dialogue directory '{}': {}", dir.display(), e))?;
+        let mut loaded = 0;
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("txt") {
+                continue;
+            }
+            let Some(locale) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(normalize_locale)
+            else {
+                log::warn!(
+                    "Skipping dialogue file {}: not named after a locale",
+                    path.display()
+                );
+                continue;
+            };
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read '{}': {}", path.display(), e))?;
+            let entries =
+                parse_catalog(&text).map_err(|e| format!("invalid '{}': {}", path.display(), e))?;
+            self.insert_locale(&locale, entries);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Whether the catalog has templates for `locale`.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Language code.
+    pub fn has_locale(&self, locale: &str) -> bool {
+        self.locales.contains_key(locale)
+    }
+
+    /// Looks up the template for `key`, falling back to English.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Catalog key.
+    /// * `locale` - Preferred language code.
+    ///
+    /// # Returns
+    ///
+    /// * The template, or `None` if the key is unknown.
+    pub fn template(&self, key: &str, locale: &str) -> Option<&str> {
+        [locale, DEFAULT_LOCALE]
+            .into_iter()
+            .find_map(|l| self.locales.get(l)?.get(key))
+            .map(String::as_str)
+    }
+
+    /// Renders a line in `locale`.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Key and parameters.
+    /// * `locale` - Preferred language code.
+    ///
+    /// # Returns
+    ///
+    /// * The rendered text; the bare key if no template exists.
+    pub fn render(&self, line: &DialogueLine, locale: &str) -> String {
+        match self.template(&line.key, locale) {
+            Some(template) => render_template(template, &line.params),
+            None => line.key.clone(),
+        }
+    }
+}
+
+/// Truncates `s` to at most [`MAX_DIALOGUE_FIELD_LEN`] bytes on a char
+/// boundary.
+fn wire_field(s: &str) -> &str {
+    let mut end = s.len().min(MAX_DIALOGUE_FIELD_LEN);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Appends a length-prefixed field.
+fn push_field(buf: &mut Vec<u8>, s: &str) {
+    let s = wire_field(s);
+    buf.push(s.len() as u8);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Reads a length-prefixed field at `*pos`.
+fn read_field(bytes: &[u8], pos: &mut usize) -> Option<String> {
+    let len = *bytes.get(*pos)? as usize;
+    let field = bytes.get(*pos + 1..*pos + 1 + len)?;
+    *pos += 1 + len;
+    Some(String::from_utf8_lossy(field).into_owned())
+}
+
+/// Builds an `SV_DIALOGUE` packet.
+///
+/// Wire format: opcode (1) + total length (u16 LE) + speaker name + key +
+/// parameter count (1) + count × (name + value); every string is a length
+/// byte followed by UTF-8. Parameters beyond [`MAX_DIALOGUE_PARAMS`] are
+/// dropped.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::Dialogue` as a byte.
+/// * `speaker` - Name shown in front of the line.
+/// * `line` - Key and parameters.
+///
+/// # Returns
+///
+/// * The packet.
+pub fn encode_dialogue_packet(opcode: u8, speaker: &str, line: &DialogueLine) -> Vec<u8> {
+    let mut buf = vec![opcode, 0, 0];
+    push_field(&mut buf, speaker);
+    push_field(&mut buf, &line.key);
+    let params = &line.params[..line.params.len().min(MAX_DIALOGUE_PARAMS)];
+    buf.push(params.len() as u8);
+    for (name, value) in params {
+        push_field(&mut buf, name);
+        push_field(&mut buf, value);
+    }
+    let len = buf.len() as u16;
+    buf[1..3].copy_from_slice(&len.to_le_bytes());
+    buf
+}
+
+/// Parses an `SV_DIALOGUE` packet built by [`encode_dialogue_packet`].
+///
+/// # Arguments
+///
+/// * `bytes` - The packet, starting at the opcode.
+///
+/// # Returns
+///
+/// * `(speaker, line)`, or `None` if the packet is malformed.
+pub fn decode_dialogue_packet(bytes: &[u8]) -> Option<(String, DialogueLine)> {
+    let len = u16::from_le_bytes([*bytes.get(1)?, *bytes.get(2)?]) as usize;
+    let bytes = bytes.get(..len)?;
+    let mut pos = 3;
+    let speaker = read_field(bytes, &mut pos)?;
+    let key = read_field(bytes, &mut pos)?;
+    let count = (*bytes.get(pos)? as usize).min(MAX_DIALOGUE_PARAMS);
+    pos += 1;
+    let mut params = Vec::with_capacity(count);
+    for _ in 0..count {
+        let name = read_field(bytes, &mut pos)?;
+        let value = read_field(bytes, &mut pos)?;
+        params.push((name, value));
+    }
+    Some((speaker, DialogueLine { key, params }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_fall_back_to_english_per_key() {
+        let mut catalog = DialogueCatalog::default();
+        catalog.insert_locale(
+            "de",
+            parse_catalog("quest.turn_in.thanks = Danke {player}. Das ist {item}, das ich wollte.")
+                .unwrap(),
+        );
+        let line = DialogueLine::new("quest.turn_in.thanks")
+            .with("player", "Ishtar")
+            .with("item", "the Amulet");
+        assert_eq!(
+            catalog.render(&line, "de"),
+            "Danke Ishtar. Das ist the Amulet, das ich wollte."
+        );
+        assert_eq!(
+            catalog.render(&line, "fr"),
+            "Thank you Ishtar. That's the the Amulet I wanted."
+        );
+        let untranslated = DialogueLine::new("npc.give.no_money");
+        assert_eq!(
+            catalog.render(&untranslated, "de"),
+            "I don't take money from you!"
+        );
+        assert_eq!(
+            catalog.render(&DialogueLine::new("no.such.key"), "de"),
+            "no.such.key"
+        );
+        assert_eq!(
+            render_template("{a} {b} {", &[("a".into(), "x".into())]),
+            "x {b} {"
+        );
+        assert_eq!(normalize_locale("de_DE.UTF-8").as_deref(), Some("de"));
+        assert_eq!(normalize_locale("C"), None);
+    }
+
+    #[test]
+    fn packet_roundtrip() {
+        let line = DialogueLine::new("npc.train.bring_item")
+            .with("item", "a Golden Ring")
+            .with("skill", "Dagger")
+            .with("player", "Ishtar");
+        let packet = encode_dialogue_packet(88, "Gunther", &line);
+        assert_eq!(
+            u16::from_le_bytes([packet[1], packet[2]]) as usize,
+            packet.len()
+        );
+        assert_eq!(
+            decode_dialogue_packet(&packet),
+            Some(("Gunther".to_owned(), line))
+        );
+        assert_eq!(decode_dialogue_packet(&packet[..packet.len() - 1]), None);
+    }
+}
@@ -0,0 +1,163 @@
+//! Wire helpers for `SV_DISCONNECT`, the reason sent before a connection is
+//! closed.
+//!
+//! The original protocol only has `SV_EXIT` with a one-byte
+//! [`LogoutReason`], which clients can merely map to a fixed label. A client
+//! advertising
+//! [`CLIENT_CAP_DISCONNECT_REASON`](crate::constants::CLIENT_CAP_DISCONNECT_REASON)
+//! additionally receives one `SV_DISCONNECT` right before `SV_EXIT`, carrying
+//! the reason and a sentence for the player (ban reason and expiry, the
+//! required client version, ...).
+
+use crate::logout_reasons::{LogoutReason, get_exit_reason};
+
+/// Longest disconnect text in bytes.
+pub const MAX_DISCONNECT_TEXT_LEN: usize = 200;
+
+/// Size of the fixed part of `SV_DISCONNECT` (opcode, length, reason).
+pub const DISCONNECT_HEADER_LEN: usize = 4;
+
+/// Encodes an `SV_DISCONNECT` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::Disconnect` as a byte.
+/// * `reason` - Why the connection is closed.
+/// * `text` - Sentence for the player; cut to [`MAX_DISCONNECT_TEXT_LEN`]
+///   bytes on a character boundary.
+///
+/// # Returns
+///
+/// * The encoded packet.
+pub fn encode_disconnect(opcode: u8, reason: LogoutReason, text: &str) -> Vec<u8> {
+    let mut end = text.len().min(MAX_DISCONNECT_TEXT_LEN);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let text = &text.as_bytes()[..end];
+    let total = DISCONNECT_HEADER_LEN + text.len();
+    let mut buf = Vec::with_capacity(total);
+    buf.push(opcode);
+    buf.extend_from_slice(&(total as u16).to_le_bytes());
+    buf.push(reason as u8);
+    buf.extend_from_slice(text);
+    buf
+}
+
+/// Decodes the payload of an `SV_DISCONNECT` packet.
+///
+/// # Arguments
+///
+/// * `bytes` - The whole packet, opcode included.
+///
+/// # Returns
+///
+/// * The reason byte and the text, or `None` when the packet is truncated.
+pub fn decode_disconnect(bytes: &[u8]) -> Option<(u8, String)> {
+    let total = usize::from(u16::from_le_bytes([*bytes.get(1)?, *bytes.get(2)?]));
+    let text = bytes.get(DISCONNECT_HEADER_LEN..total.max(DISCONNECT_HEADER_LEN))?;
+    Some((*bytes.get(3)?, String::from_utf8_lossy(text).into_owned()))
+}
+
+/// What a client shows the player after a disconnect.
+///
+/// # Arguments
+///
+/// * `reason` - Reason code from `SV_DISCONNECT` or `SV_EXIT`.
+/// * `text` - Text from `SV_DISCONNECT`; empty for a bare `SV_EXIT`.
+///
+/// # Returns
+///
+/// * The server's text, or the fixed label of the reason when there is none.
+pub fn describe_disconnect(reason: LogoutReason, text: &str) -> String {
+    let text = text.trim();
+    if text.is_empty() {
+        get_exit_reason(reason).to_owned()
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Disconnect text for a banned player.
+///
+/// # Arguments
+///
+/// * `reason` - Operator-supplied ban reason; may be empty.
+/// * `expires_at` - Unix time the ban ends, `None` for a permanent ban.
+/// * `now_secs` - Current Unix time.
+pub fn ban_text(reason: &str, expires_at: Option<u64>, now_secs: u64) -> String {
+    let mut text = "You are banned".to_owned();
+    let reason = reason.trim();
+    if !reason.is_empty() {
+        text.push_str(&format!(": {}", reason));
+    }
+    match expires_at {
+        Some(expires_at) => {
+            let left = expires_at.saturating_sub(now_secs);
+            text.push_str(&format!(". The ban ends in {}.", duration_text(left)));
+        }
+        None => text.push_str(". The ban is permanent."),
+    }
+    text
+}
+
+/// Rounds a duration up to whole minutes, hours or days.
+fn duration_text(secs: u64) -> String {
+    let (count, unit) = if secs <= 3600 {
+        (secs.div_ceil(60).max(1), "minute")
+    } else if secs <= 2 * 86400 {
+        (secs.div_ceil(3600), "hour")
+    } else {
+        (secs.div_ceil(86400), "day")
+    };
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disconnect_round_trips_and_caps_text() {
+        let pkt = encode_disconnect(96, LogoutReason::Banned, "Cheating.");
+        assert_eq!(pkt.len(), DISCONNECT_HEADER_LEN + 9);
+        assert_eq!(u16::from_le_bytes([pkt[1], pkt[2]]) as usize, pkt.len());
+        assert_eq!(
+            decode_disconnect(&pkt),
+            Some((LogoutReason::Banned as u8, "Cheating.".to_owned()))
+        );
+        assert_eq!(decode_disconnect(&pkt[..6]), None);
+
+        // Never cut a character in half.
+        let long = "é".repeat(MAX_DISCONNECT_TEXT_LEN);
+        let pkt = encode_disconnect(96, LogoutReason::Kicked, &long);
+        assert_eq!(pkt.len(), DISCONNECT_HEADER_LEN + MAX_DISCONNECT_TEXT_LEN);
+        let (_, text) = decode_disconnect(&pkt).unwrap();
+        assert_eq!(text.chars().count(), MAX_DISCONNECT_TEXT_LEN / 2);
+    }
+
+    #[test]
+    fn texts_fall_back_to_the_reason_label() {
+        assert_eq!(
+            describe_disconnect(LogoutReason::Shutdown, " "),
+            "[SHUTDOWN] Server shutting down"
+        );
+        assert_eq!(describe_disconnect(LogoutReason::Kicked, "Bye."), "Bye.");
+        assert_eq!(
+            ban_text("", None, 0),
+            "You are banned. The ban is permanent."
+        );
+        assert_eq!(
+            ban_text("spam", Some(1000 + 90), 1000),
+            "You are banned: spam. The ban ends in 2 minutes."
+        );
+        assert_eq!(
+            ban_text("", Some(3 * 86400 + 5), 0),
+            "You are banned. The ban ends in 4 days."
+        );
+    }
+}
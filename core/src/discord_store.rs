@@ -0,0 +1,105 @@
+//! Shared KeyDB keys and payload for Discord role sync.
+//!
+//! When a player links their account to Discord through the API, the account
+//! id is added to [`DISCORD_LINKED_KEY`]. Every [`DISCORD_ROLE_INTERVAL_SECS`]
+//! the game server compares the rank and guild of each linked account's
+//! online character with what it last reported and pushes a
+//! [`DiscordRoleEvent`] onto [`DISCORD_ROLE_EVENTS_KEY`] when either changed.
+//! The API pops the events and forwards them to the configured Discord bot.
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// KeyDB set of account ids linked to a Discord user.
+pub const DISCORD_LINKED_KEY: &str = "game:discord:linked_accounts";
+
+/// KeyDB list of pending role events (server `LPUSH`es, API `RPOP`s).
+pub const DISCORD_ROLE_EVENTS_KEY: &str = "game:discord:role_events";
+
+/// Events kept in [`DISCORD_ROLE_EVENTS_KEY`] while nobody consumes them.
+pub const MAX_QUEUED_ROLE_EVENTS: usize = 1000;
+
+/// How often the server checks linked accounts for changes (seconds).
+pub const DISCORD_ROLE_INTERVAL_SECS: u64 = 30;
+
+/// Guild membership reported to Discord.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscordGuild {
+    #[default]
+    None,
+    /// Member of the Purples of Honor.
+    PurpleOfHonor,
+    /// Leader among the Purples of Honor.
+    PurpleOfHonorLeader,
+}
+
+/// Rank and guild of a linked account's character.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct DiscordRoleEvent {
+    /// API account id.
+    pub account_id: u64,
+    pub character_name: String,
+    /// Rank display name.
+    pub rank: String,
+    pub guild: DiscordGuild,
+    /// Wall-clock time the change was seen (seconds since Unix epoch).
+    pub unix_secs: u64,
+}
+
+impl DiscordRoleEvent {
+    /// Encode the event to canonical bincode bytes.
+    ///
+    /// # Returns
+    ///
+    /// * Encoded event bytes.
+    ///
+    /// # Panics
+    ///
+    /// * Panics if bincode serialization fails.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("DiscordRoleEvent::to_bytes failed")
+    }
+
+    /// Decode an event from canonical bincode bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Encoded event payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(event)` when all bytes decode successfully, otherwise `None`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (value, consumed): (Self, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+        if consumed == bytes.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_roundtrip_rejects_trailing_bytes() {
+        let event = DiscordRoleEvent {
+            account_id: 42,
+            character_name: "Ishtar".to_owned(),
+            rank: "Sergeant".to_owned(),
+            guild: DiscordGuild::PurpleOfHonor,
+            unix_secs: 1_700_000_000,
+        };
+        let mut bytes = event.to_bytes();
+        assert_eq!(DiscordRoleEvent::from_bytes(&bytes), Some(event));
+        bytes.push(0);
+        assert_eq!(DiscordRoleEvent::from_bytes(&bytes), None);
+    }
+}
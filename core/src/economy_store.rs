@@ -0,0 +1,203 @@
+//! Shared KeyDB key and payload for the public economy report.
+//!
+//! The game server aggregates merchant trades and merchant stock into an
+//! [`EconomyReport`] and writes it to [`ECONOMY_REPORT_KEY`] every
+//! [`ECONOMY_INTERVAL_SECS`]. The API serves read-only views of it so
+//! community sites can track prices. Player names are never included.
+//!
+//! The key has no TTL: it doubles as the persisted price history, which the
+//! server reloads on start so history survives restarts.
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// KeyDB key holding the latest bincode-encoded [`EconomyReport`].
+pub const ECONOMY_REPORT_KEY: &str = "game:economy:report";
+
+/// How often the server refreshes [`ECONOMY_REPORT_KEY`] (seconds).
+pub const ECONOMY_INTERVAL_SECS: u64 = 60;
+
+/// Hours of price history kept per item.
+pub const PRICE_HISTORY_HOURS: u64 = 7 * 24;
+
+/// Number of trades kept in [`EconomyReport::recent_trades`].
+pub const MAX_RECENT_TRADES: usize = 100;
+
+/// Direction of a merchant trade, seen from the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    /// The player bought the item from a merchant.
+    Buy,
+    /// The player sold the item to a merchant.
+    Sell,
+}
+
+/// One completed merchant trade.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct Trade {
+    /// Wall-clock time of the trade (seconds since Unix epoch).
+    pub unix_secs: u64,
+    /// Item template id.
+    pub template_id: u32,
+    pub item_name: String,
+    /// Name of the merchant the player traded with.
+    pub merchant: String,
+    pub side: TradeSide,
+    /// Price paid in silver (100 silver = 1 gold).
+    pub price: i32,
+}
+
+/// Trades of one item in one hour.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct PriceBucket {
+    /// Start of the hour (seconds since Unix epoch, UTC).
+    pub hour_start: u64,
+    pub trades: u32,
+    /// Lowest price in silver.
+    pub min_price: i32,
+    /// Highest price in silver.
+    pub max_price: i32,
+    /// Sum of all prices in silver.
+    pub total_price: i64,
+}
+
+impl PriceBucket {
+    /// Average price of the bucket in silver.
+    ///
+    /// # Returns
+    ///
+    /// * Rounded-down average, or `0` for an empty bucket.
+    pub fn average_price(&self) -> i64 {
+        if self.trades == 0 {
+            0
+        } else {
+            self.total_price / i64::from(self.trades)
+        }
+    }
+}
+
+/// Hourly price history of one item for one trade side.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct ItemPriceHistory {
+    pub template_id: u32,
+    pub item_name: String,
+    pub side: TradeSide,
+    /// Buckets oldest first, at most [`PRICE_HISTORY_HOURS`].
+    pub buckets: Vec<PriceBucket>,
+}
+
+/// One item a merchant has for sale.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct ListedItem {
+    pub template_id: u32,
+    pub item_name: String,
+    /// Item value in silver before the buyer's barter skill is applied.
+    pub base_price: u32,
+}
+
+/// Current stock of one merchant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct MerchantListing {
+    /// Merchant character slot.
+    pub merchant_id: u32,
+    pub merchant: String,
+    /// Area name(s) at the merchant's position.
+    pub area: String,
+    pub items: Vec<ListedItem>,
+}
+
+/// Everything the economy endpoints serve.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct EconomyReport {
+    /// Wall-clock time the report was taken (seconds since Unix epoch).
+    pub generated_at: u64,
+    /// Merchant stock at `generated_at`.
+    pub listings: Vec<MerchantListing>,
+    /// Most recent trades, newest first, at most [`MAX_RECENT_TRADES`].
+    pub recent_trades: Vec<Trade>,
+    /// Price history per item and side, ordered by template id.
+    pub price_history: Vec<ItemPriceHistory>,
+}
+
+impl EconomyReport {
+    /// Encode the report to canonical bincode bytes.
+    ///
+    /// # Returns
+    ///
+    /// * Encoded report bytes.
+    ///
+    /// # Panics
+    ///
+    /// * Panics if bincode serialization fails.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("EconomyReport::to_bytes failed")
+    }
+
+    /// Decode a report from canonical bincode bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Encoded report payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(report)` when all bytes decode successfully, otherwise `None`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (value, consumed): (Self, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+        if consumed == bytes.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_roundtrip_rejects_trailing_bytes() {
+        let report = EconomyReport {
+            generated_at: 1_700_000_000,
+            listings: vec![MerchantListing {
+                merchant_id: 12,
+                merchant: "Jefferson".to_owned(),
+                area: "Aston".to_owned(),
+                items: vec![ListedItem {
+                    template_id: 59,
+                    item_name: "Bronze Dagger".to_owned(),
+                    base_price: 250,
+                }],
+            }],
+            recent_trades: vec![Trade {
+                unix_secs: 1_699_999_000,
+                template_id: 59,
+                item_name: "Bronze Dagger".to_owned(),
+                merchant: "Jefferson".to_owned(),
+                side: TradeSide::Buy,
+                price: 900,
+            }],
+            price_history: vec![ItemPriceHistory {
+                template_id: 59,
+                item_name: "Bronze Dagger".to_owned(),
+                side: TradeSide::Buy,
+                buckets: vec![PriceBucket {
+                    hour_start: 1_699_995_600,
+                    trades: 3,
+                    min_price: 850,
+                    max_price: 1000,
+                    total_price: 2750,
+                }],
+            }],
+        };
+        let mut bytes = report.to_bytes();
+        assert_eq!(EconomyReport::from_bytes(&bytes), Some(report.clone()));
+        assert_eq!(report.price_history[0].buckets[0].average_price(), 916);
+        bytes.push(0);
+        assert_eq!(EconomyReport::from_bytes(&bytes), None);
+    }
+}
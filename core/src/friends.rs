@@ -0,0 +1,113 @@
+//! Shared friends list limits and wire helpers for `SV_FRIENDENTRY`.
+//!
+//! Every account keeps a list of player characters it wants to hear about
+//! (see `server/src/friends`). A client advertising
+//! [`CLIENT_CAP_FRIENDS`](crate::constants::CLIENT_CAP_FRIENDS) receives the
+//! list as one `FriendEntry` packet per friend at login, and another one each
+//! time a friend logs in or out.
+
+use crate::string_operations::{c_string_to_str, write_ascii_into_fixed};
+
+/// Most friends an account can list.
+pub const MAX_FRIENDS: usize = 50;
+
+/// Bytes of the friend name in `SV_FRIENDENTRY`, NUL padded.
+pub const FRIEND_NAME_LEN: usize = 16;
+
+/// Total size of an `SV_FRIENDENTRY` packet (opcode, op, online, name).
+pub const FRIEND_ENTRY_PACKET_LEN: usize = 3 + FRIEND_NAME_LEN;
+
+/// What an `SV_FRIENDENTRY` packet does to the client's friends list.
+///
+/// Numeric values are part of the wire protocol — do not renumber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FriendOp {
+    /// Adds the friend or replaces its online state.
+    Set = 0,
+    /// Removes the friend.
+    Remove = 1,
+    /// Empties the list; sent before the whole list is resent.
+    Clear = 2,
+}
+
+impl FriendOp {
+    /// Decodes an op byte; unknown values fall back to [`FriendOp::Set`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Op byte from the packet.
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => FriendOp::Remove,
+            2 => FriendOp::Clear,
+            _ => FriendOp::Set,
+        }
+    }
+}
+
+/// Builds an `SV_FRIENDENTRY` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::FriendEntry` as a byte.
+/// * `op` - Whether the friend is set or removed, or the list cleared.
+/// * `online` - Whether the friend is logged in.
+/// * `name` - Friend name, cut to [`FRIEND_NAME_LEN`]; empty for
+///   [`FriendOp::Clear`].
+///
+/// # Returns
+///
+/// * The encoded packet.
+pub fn encode_friend_entry(
+    opcode: u8,
+    op: FriendOp,
+    online: bool,
+    name: &str,
+) -> [u8; FRIEND_ENTRY_PACKET_LEN] {
+    let mut buf = [0u8; FRIEND_ENTRY_PACKET_LEN];
+    buf[0] = opcode;
+    buf[1] = op as u8;
+    buf[2] = u8::from(online);
+    // One extra byte so a full-length name keeps all its letters.
+    let mut name_buf = [0u8; FRIEND_NAME_LEN + 1];
+    write_ascii_into_fixed(&mut name_buf, name);
+    buf[3..].copy_from_slice(&name_buf[..FRIEND_NAME_LEN]);
+    buf
+}
+
+/// Decodes an `SV_FRIENDENTRY` packet.
+///
+/// # Arguments
+///
+/// * `bytes` - The whole packet, opcode included.
+///
+/// # Returns
+///
+/// * The op byte, online flag and name, or `None` when the packet is
+///   truncated.
+pub fn decode_friend_entry(bytes: &[u8]) -> Option<(u8, bool, String)> {
+    Some((
+        *bytes.get(1)?,
+        *bytes.get(2)? != 0,
+        c_string_to_str(bytes.get(3..FRIEND_ENTRY_PACKET_LEN)?).to_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_round_trips_with_full_length_name() {
+        let pkt = encode_friend_entry(97, FriendOp::Set, true, "Sixteen Letterss");
+        assert_eq!(&pkt[3..], b"Sixteen Letterss");
+        assert_eq!(
+            decode_friend_entry(&pkt),
+            Some((FriendOp::Set as u8, true, "Sixteen Letterss".to_owned()))
+        );
+        assert_eq!(decode_friend_entry(&pkt[..10]), None);
+        assert_eq!(FriendOp::from_u8(2), FriendOp::Clear);
+        assert_eq!(FriendOp::from_u8(9), FriendOp::Set);
+    }
+}
@@ -0,0 +1,230 @@
+//! Shared guild ranks, limits and wire helpers for `SV_GUILDINFO` and
+//! `SV_GUILDMEMBER`.
+//!
+//! Guilds are player-run groups kept by the server (see `server/src/guilds`).
+//! A client advertising [`CLIENT_CAP_GUILDS`](crate::constants::CLIENT_CAP_GUILDS)
+//! receives its own guild's name, rank and message of the day in one
+//! `GuildInfo` packet and the roster as one `GuildMember` packet per member.
+
+use crate::string_operations::write_ascii_into_fixed;
+
+/// Longest guild name in bytes.
+pub const MAX_GUILD_NAME_LEN: usize = 16;
+
+/// Shortest guild name in bytes.
+pub const MIN_GUILD_NAME_LEN: usize = 3;
+
+/// Longest guild message of the day in bytes.
+pub const MAX_GUILD_MOTD_LEN: usize = 80;
+
+/// Most members a guild can hold.
+pub const MAX_GUILD_MEMBERS: usize = 50;
+
+/// Bytes of a member name in `SV_GUILDMEMBER`, NUL padded.
+pub const GUILD_MEMBER_NAME_LEN: usize = 16;
+
+/// Size of the fixed part of `SV_GUILDINFO` (opcode, length, rank, name).
+pub const GUILD_INFO_HEADER_LEN: usize = 4 + MAX_GUILD_NAME_LEN;
+
+/// Total size of an `SV_GUILDMEMBER` packet.
+pub const GUILD_MEMBER_PACKET_LEN: usize = 4 + GUILD_MEMBER_NAME_LEN;
+
+/// Rank of a guild member.
+///
+/// Numeric values are part of the wire protocol and the stored roster — do
+/// not renumber.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum GuildRank {
+    #[default]
+    Member = 0,
+    /// May invite players and set the message of the day.
+    Officer = 1,
+    /// Founder or successor; may promote, demote and remove anyone.
+    Leader = 2,
+}
+
+impl GuildRank {
+    /// Returns the wire-protocol byte representation.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Decodes a rank byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Rank byte from a packet or the stored roster.
+    ///
+    /// # Returns
+    ///
+    /// * The rank, or `None` for an unknown value.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(GuildRank::Member),
+            1 => Some(GuildRank::Officer),
+            2 => Some(GuildRank::Leader),
+            _ => None,
+        }
+    }
+
+    /// Name shown in the roster and chat.
+    pub fn label(self) -> &'static str {
+        match self {
+            GuildRank::Member => "Member",
+            GuildRank::Officer => "Officer",
+            GuildRank::Leader => "Leader",
+        }
+    }
+
+    /// Whether this rank may invite players and change the MOTD.
+    pub fn can_manage(self) -> bool {
+        self >= GuildRank::Officer
+    }
+}
+
+/// What an `SV_GUILDMEMBER` packet does to the client's roster.
+///
+/// Numeric values are part of the wire protocol — do not renumber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GuildRosterOp {
+    /// Adds the member or replaces its rank and online state.
+    Set = 0,
+    /// Removes the member.
+    Remove = 1,
+}
+
+impl GuildRosterOp {
+    /// Decodes an op byte; unknown values fall back to [`GuildRosterOp::Set`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Op byte from the packet.
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => GuildRosterOp::Remove,
+            _ => GuildRosterOp::Set,
+        }
+    }
+}
+
+/// Checks a proposed guild name.
+///
+/// # Arguments
+///
+/// * `name` - Name as typed by the founder.
+///
+/// # Returns
+///
+/// * `Ok(())` for [`MIN_GUILD_NAME_LEN`]..=[`MAX_GUILD_NAME_LEN`] ASCII
+///   letters and single inner spaces, or a message for the player.
+pub fn validate_guild_name(name: &str) -> Result<(), String> {
+    if !(MIN_GUILD_NAME_LEN..=MAX_GUILD_NAME_LEN).contains(&name.len()) {
+        return Err(format!(
+            "Guild names must be {} to {} letters long.",
+            MIN_GUILD_NAME_LEN, MAX_GUILD_NAME_LEN
+        ));
+    }
+    if !name.bytes().all(|b| b.is_ascii_alphabetic() || b == b' ')
+        || name.starts_with(' ')
+        || name.ends_with(' ')
+        || name.contains("  ")
+    {
+        return Err("Guild names may only contain letters and single spaces.".to_owned());
+    }
+    Ok(())
+}
+
+/// Builds an `SV_GUILDINFO` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::GuildInfo` as a byte.
+/// * `rank` - The receiver's rank.
+/// * `name` - Guild name; empty when the receiver is in no guild.
+/// * `motd` - Message of the day, cut to [`MAX_GUILD_MOTD_LEN`].
+///
+/// # Returns
+///
+/// * The encoded packet.
+pub fn encode_guild_info(opcode: u8, rank: GuildRank, name: &str, motd: &str) -> Vec<u8> {
+    let motd = &motd.as_bytes()[..motd.len().min(MAX_GUILD_MOTD_LEN)];
+    let total = GUILD_INFO_HEADER_LEN + motd.len();
+    let mut buf = vec![0u8; GUILD_INFO_HEADER_LEN];
+    buf[0] = opcode;
+    buf[1..3].copy_from_slice(&(total as u16).to_le_bytes());
+    buf[3] = rank.as_u8();
+    // One extra byte so a full-length name keeps all its letters.
+    let mut name_buf = [0u8; MAX_GUILD_NAME_LEN + 1];
+    write_ascii_into_fixed(&mut name_buf, name);
+    buf[4..].copy_from_slice(&name_buf[..MAX_GUILD_NAME_LEN]);
+    buf.extend_from_slice(motd);
+    buf
+}
+
+/// Builds an `SV_GUILDMEMBER` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::GuildMember` as a byte.
+/// * `op` - Whether the member is set or removed.
+/// * `rank` - Member rank.
+/// * `online` - Whether the member is logged in.
+/// * `name` - Member name, cut to [`GUILD_MEMBER_NAME_LEN`].
+///
+/// # Returns
+///
+/// * The encoded packet.
+pub fn encode_guild_member(
+    opcode: u8,
+    op: GuildRosterOp,
+    rank: GuildRank,
+    online: bool,
+    name: &str,
+) -> [u8; GUILD_MEMBER_PACKET_LEN] {
+    let mut buf = [0u8; GUILD_MEMBER_PACKET_LEN];
+    buf[0] = opcode;
+    buf[1] = op as u8;
+    buf[2] = rank.as_u8();
+    buf[3] = u8::from(online);
+    let mut name_buf = [0u8; GUILD_MEMBER_NAME_LEN + 1];
+    write_ascii_into_fixed(&mut name_buf, name);
+    buf[4..].copy_from_slice(&name_buf[..GUILD_MEMBER_NAME_LEN]);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_round_trip_and_order() {
+        for rank in [GuildRank::Member, GuildRank::Officer, GuildRank::Leader] {
+            assert_eq!(GuildRank::from_u8(rank.as_u8()), Some(rank));
+        }
+        assert_eq!(GuildRank::from_u8(9), None);
+        assert!(GuildRank::Leader > GuildRank::Officer);
+        assert!(GuildRank::Officer.can_manage());
+        assert!(!GuildRank::Member.can_manage());
+    }
+
+    #[test]
+    fn guild_names_are_letters_and_single_spaces() {
+        assert!(validate_guild_name("Iron Wolves").is_ok());
+        assert!(validate_guild_name("Ab").is_err());
+        assert!(validate_guild_name("Much Too Long A Name").is_err());
+        assert!(validate_guild_name("Wolves 2").is_err());
+        assert!(validate_guild_name("Iron  Wolves").is_err());
+        assert!(validate_guild_name(" Wolves").is_err());
+    }
+
+    #[test]
+    fn info_packet_keeps_full_length_name() {
+        let pkt = encode_guild_info(92, GuildRank::Officer, "Sixteen Letterss", "Hi");
+        assert_eq!(pkt.len(), GUILD_INFO_HEADER_LEN + 2);
+        assert_eq!(u16::from_le_bytes([pkt[1], pkt[2]]) as usize, pkt.len());
+        assert_eq!(&pkt[4..20], b"Sixteen Letterss");
+        assert_eq!(&pkt[20..], b"Hi");
+    }
+}
@@ -0,0 +1,64 @@
+//! Sort orders a client can request for its backpack with
+//! `CmdSortInventory`.
+//!
+//! The client only names the order; the server works out the new layout
+//! from its own item data and applies it as a series of slot swaps, so a
+//! forged request can never do more than rearrange the backpack.
+
+/// How `CmdSortInventory` orders the backpack.
+///
+/// Numeric values are part of the wire protocol — do not renumber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum InventorySortKey {
+    /// Group by equipment placement (weapons, armour pieces, rings, …),
+    /// unwearable items last, then by name.
+    Type = 0,
+    /// Most valuable first, then by name.
+    Value = 1,
+}
+
+impl InventorySortKey {
+    /// Returns the wire-protocol byte representation.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Decodes a wire byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Sort key byte from the packet.
+    ///
+    /// # Returns
+    ///
+    /// * The key, or `None` for an unknown value.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(InventorySortKey::Type),
+            1 => Some(InventorySortKey::Value),
+            _ => None,
+        }
+    }
+
+    /// Short button label.
+    pub fn label(self) -> &'static str {
+        match self {
+            InventorySortKey::Type => "Type",
+            InventorySortKey::Value => "Value",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_round_trips_and_rejects_unknown() {
+        for key in [InventorySortKey::Type, InventorySortKey::Value] {
+            assert_eq!(InventorySortKey::from_u8(key.as_u8()), Some(key));
+        }
+        assert_eq!(InventorySortKey::from_u8(2), None);
+    }
+}
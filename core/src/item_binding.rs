@@ -0,0 +1,166 @@
+//! Quest items and character-bound items.
+//!
+//! Three item flags restrict where an item may go:
+//!
+//! * [`IF_QUEST`](crate::constants::ItemFlags::IF_QUEST) — quest-critical;
+//!   it can never be dropped, given away or sold.
+//! * [`IF_BIND_PICKUP`](crate::constants::ItemFlags::IF_BIND_PICKUP) — binds
+//!   to the first character that picks it up, buys it or is handed it.
+//! * [`IF_BIND_EQUIP`](crate::constants::ItemFlags::IF_BIND_EQUIP) — binds
+//!   to the first character that wears it.
+//!
+//! A bound item follows the same rules as a quest item. Both stay with
+//! their carrier on death and may still be stored in the carrier's own
+//! depot. The server checks [`transfer_refusal`] at every point an item
+//! changes hands.
+//!
+//! Persistence layout (`Item::future3`):
+//!
+//! * `future3[BOUND_OWNER_SLOT]` — character id the item is bound to
+//!   (`0` = unbound). Slots 0 and 1 belong to [`crate::loot`].
+
+use crate::constants::ItemFlags;
+use crate::types::Item;
+
+/// Index into `Item::future3` holding the bound owner's character id.
+pub const BOUND_OWNER_SLOT: usize = 2;
+
+/// Ways an item can leave its carrier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transfer {
+    /// Put on the ground.
+    Drop,
+    /// Handed to another character.
+    Give,
+    /// Sold to a merchant.
+    Sell,
+}
+
+/// Returns the character `item` is bound to.
+///
+/// # Arguments
+///
+/// * `item` - Item to check.
+///
+/// # Returns
+///
+/// * The owner's character id, or `None` if the item is not bound.
+pub fn bound_owner(item: &Item) -> Option<usize> {
+    let owner = item.future3[BOUND_OWNER_SLOT];
+    (owner > 0).then_some(owner as usize)
+}
+
+/// Binds `item` to `cn` if it is not bound yet and carries `trigger`.
+///
+/// # Arguments
+///
+/// * `item` - Item being acquired or equipped.
+/// * `cn` - Character acquiring it.
+/// * `trigger` - `IF_BIND_PICKUP` or `IF_BIND_EQUIP`.
+///
+/// # Returns
+///
+/// * `true` if the item became bound by this call.
+pub fn bind_on(item: &mut Item, cn: usize, trigger: ItemFlags) -> bool {
+    if item.flags & trigger.bits() == 0 || bound_owner(item).is_some() || cn == 0 {
+        return false;
+    }
+    item.future3[BOUND_OWNER_SLOT] = cn as i32;
+    true
+}
+
+/// Whether `item` must stay with its carrier: a quest item or bound.
+///
+/// # Arguments
+///
+/// * `item` - Item to check.
+///
+/// # Returns
+///
+/// * `true` if the item may not change hands.
+pub fn is_restricted(item: &Item) -> bool {
+    item.flags & ItemFlags::IF_QUEST.bits() != 0 || bound_owner(item).is_some()
+}
+
+/// Whether `cn` may take `item` into their possession.
+///
+/// # Arguments
+///
+/// * `item` - Item being taken.
+/// * `cn` - Character taking it.
+///
+/// # Returns
+///
+/// * `false` only for items bound to somebody else.
+pub fn may_possess(item: &Item, cn: usize) -> bool {
+    bound_owner(item).is_none_or(|owner| owner == cn)
+}
+
+/// Player-facing reason `item` may not leave its carrier through `kind`.
+///
+/// # Arguments
+///
+/// * `item` - Item being transferred.
+/// * `kind` - How it would leave.
+///
+/// # Returns
+///
+/// * A newline-terminated message, or `None` if the transfer is allowed.
+pub fn transfer_refusal(item: &Item, kind: Transfer) -> Option<String> {
+    let what = if item.flags & ItemFlags::IF_QUEST.bits() != 0 {
+        "Quest items"
+    } else if bound_owner(item).is_some() {
+        "Items bound to you"
+    } else {
+        return None;
+    };
+    let how = match kind {
+        Transfer::Drop => "dropped",
+        Transfer::Give => "given away",
+        Transfer::Sell => "sold",
+    };
+    Some(format!("{what} cannot be {how}.\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_only_happens_once_and_for_the_right_trigger() {
+        let mut item = Item {
+            flags: ItemFlags::IF_BIND_EQUIP.bits(),
+            ..Item::default()
+        };
+        assert!(!bind_on(&mut item, 5, ItemFlags::IF_BIND_PICKUP));
+        assert_eq!(bound_owner(&item), None);
+        assert!(may_possess(&item, 9));
+
+        assert!(bind_on(&mut item, 5, ItemFlags::IF_BIND_EQUIP));
+        assert!(!bind_on(&mut item, 9, ItemFlags::IF_BIND_EQUIP));
+        assert_eq!(bound_owner(&item), Some(5));
+        assert!(may_possess(&item, 5));
+        assert!(!may_possess(&item, 9));
+    }
+
+    #[test]
+    fn quest_and_bound_items_refuse_every_transfer() {
+        let mut item = Item::default();
+        assert!(!is_restricted(&item));
+        assert_eq!(transfer_refusal(&item, Transfer::Drop), None);
+
+        item.flags |= ItemFlags::IF_QUEST.bits();
+        assert_eq!(
+            transfer_refusal(&item, Transfer::Sell).as_deref(),
+            Some("Quest items cannot be sold.\n")
+        );
+
+        item.flags = ItemFlags::IF_BIND_PICKUP.bits();
+        bind_on(&mut item, 3, ItemFlags::IF_BIND_PICKUP);
+        assert!(is_restricted(&item));
+        assert_eq!(
+            transfer_refusal(&item, Transfer::Give).as_deref(),
+            Some("Items bound to you cannot be given away.\n")
+        );
+    }
+}
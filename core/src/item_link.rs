@@ -0,0 +1,310 @@
+//! Shared item link model, chat tokens and wire helpers for `SV_ITEMLINK`.
+//!
+//! A player links an item from their backpack by typing a slot token
+//! ([`slot_token`]) into a chat line. The server snapshots the item, files
+//! the snapshot under a link id and replaces the slot token with a link
+//! token ([`link_token`]). Every receiver whose client advertises
+//! [`CLIENT_CAP_ITEM_LINKS`](crate::constants::CLIENT_CAP_ITEM_LINKS) gets
+//! an [`ItemLink`] packet right before the line and renders the token as a
+//! link; other clients get the item name in brackets instead.
+//!
+//! Tokens are framed by [`ITEM_LINK_MARK`], a control character the bitmap
+//! font cannot draw and players cannot type.
+
+use crate::skills;
+use crate::string_operations::{c_string_to_str, write_ascii_into_fixed};
+
+/// Character framing item link tokens in chat text (ASCII unit separator).
+pub const ITEM_LINK_MARK: char = '\u{1f}';
+
+/// Tag starting the payload of a slot token, followed by the backpack slot.
+pub const ITEM_LINK_SLOT_TAG: char = 'S';
+
+/// Most items one chat line links; further slot tokens become plain names.
+pub const MAX_LINKS_PER_MESSAGE: usize = 3;
+
+/// Bytes of the item name in `SV_ITEMLINK`, NUL padded.
+pub const ITEM_LINK_NAME_LEN: usize = 40;
+
+/// Longest item description carried by `SV_ITEMLINK`.
+pub const MAX_ITEM_LINK_DESC_LEN: usize = 200;
+
+/// Size of the fixed part of `SV_ITEMLINK` (opcode, length, id, minimum
+/// rank, stat count and name).
+pub const ITEM_LINK_HEADER_LEN: usize = 9 + ITEM_LINK_NAME_LEN;
+
+/// Bytes of one stat entry in `SV_ITEMLINK`.
+pub const ITEM_LINK_STAT_LEN: usize = 5;
+
+/// Stat code of the hit point modifier; attributes use codes `0..5`.
+pub const ITEM_STAT_HP: u8 = 5;
+
+/// Stat code of the endurance modifier.
+pub const ITEM_STAT_END: u8 = 6;
+
+/// Stat code of the mana modifier.
+pub const ITEM_STAT_MANA: u8 = 7;
+
+/// Stat code of the armor value.
+pub const ITEM_STAT_ARMOR: u8 = 8;
+
+/// Stat code of the weapon value.
+pub const ITEM_STAT_WEAPON: u8 = 9;
+
+/// Stat code of skill 0; skill `n` uses `ITEM_STAT_SKILL_BASE + n`.
+pub const ITEM_STAT_SKILL_BASE: u8 = 16;
+
+/// One modifier or requirement of a linked item.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ItemLinkStat {
+    /// What the entry is about; see the `ITEM_STAT_*` codes.
+    pub stat: u8,
+    /// Modifier the item gives while worn, in its current state.
+    pub bonus: i16,
+    /// Value the wearer needs to wear the item; 0 when there is none.
+    pub required: i16,
+}
+
+impl ItemLinkStat {
+    /// Display name of the stat.
+    ///
+    /// # Returns
+    ///
+    /// * An attribute, skill or pool name; empty for unknown codes.
+    pub fn label(&self) -> &'static str {
+        match self.stat {
+            n @ 0..5 => skills::attribute_name(usize::from(n)),
+            ITEM_STAT_HP => "Hitpoints",
+            ITEM_STAT_END => "Endurance",
+            ITEM_STAT_MANA => "Mana",
+            ITEM_STAT_ARMOR => "Armor",
+            ITEM_STAT_WEAPON => "Weapon",
+            n if n >= ITEM_STAT_SKILL_BASE => {
+                skills::get_skill_name(usize::from(n - ITEM_STAT_SKILL_BASE))
+            }
+            _ => "",
+        }
+    }
+}
+
+/// Snapshot of a linked item, as carried by `SV_ITEMLINK`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ItemLink {
+    /// Server-wide link id used by link tokens.
+    pub id: u32,
+    /// Item name.
+    pub name: String,
+    /// Rank index needed to wear the item; 0 for none.
+    pub min_rank: u8,
+    /// Non-zero modifiers and requirements.
+    pub stats: Vec<ItemLinkStat>,
+    /// Item description.
+    pub description: String,
+}
+
+/// One piece of a chat line split by [`split_item_links`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChatSegment<'a> {
+    /// Plain text.
+    Text(&'a str),
+    /// A slot token typed by a client.
+    Slot(usize),
+    /// A link token sent by the server.
+    Link(u32),
+}
+
+/// Builds the token a client puts into a chat line to link a backpack item.
+///
+/// # Arguments
+///
+/// * `slot` - Backpack slot (0..40).
+pub fn slot_token(slot: usize) -> String {
+    format!("{ITEM_LINK_MARK}{ITEM_LINK_SLOT_TAG}{slot}{ITEM_LINK_MARK}")
+}
+
+/// Builds the token the server puts into a chat line for a filed link.
+///
+/// # Arguments
+///
+/// * `id` - Link id.
+pub fn link_token(id: u32) -> String {
+    format!("{ITEM_LINK_MARK}{id}{ITEM_LINK_MARK}")
+}
+
+/// Splits a chat line into text and item link tokens.
+///
+/// A token that is not terminated or does not parse is kept as text,
+/// without its marks.
+///
+/// # Arguments
+///
+/// * `text` - The chat line.
+///
+/// # Returns
+///
+/// * The segments in order; a line without tokens is one `Text` segment.
+pub fn split_item_links(text: &str) -> Vec<ChatSegment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(ITEM_LINK_MARK) {
+        if start > 0 {
+            segments.push(ChatSegment::Text(&rest[..start]));
+        }
+        let after = &rest[start + ITEM_LINK_MARK.len_utf8()..];
+        let Some(end) = after.find(ITEM_LINK_MARK) else {
+            rest = after;
+            break;
+        };
+        let payload = &after[..end];
+        let slot = payload
+            .strip_prefix(ITEM_LINK_SLOT_TAG)
+            .and_then(|slot| slot.parse().ok());
+        segments.push(match (slot, payload.parse()) {
+            (Some(slot), _) => ChatSegment::Slot(slot),
+            (None, Ok(id)) => ChatSegment::Link(id),
+            (None, Err(_)) => ChatSegment::Text(payload),
+        });
+        rest = &after[end + ITEM_LINK_MARK.len_utf8()..];
+    }
+    if !rest.is_empty() {
+        segments.push(ChatSegment::Text(rest));
+    }
+    segments
+}
+
+/// Builds an `SV_ITEMLINK` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::ItemLink` as a byte.
+/// * `link` - The linked item; at most 255 stats and
+///   [`MAX_ITEM_LINK_DESC_LEN`] bytes of description are sent.
+///
+/// # Returns
+///
+/// * The encoded packet.
+pub fn encode_item_link(opcode: u8, link: &ItemLink) -> Vec<u8> {
+    let stats = &link.stats[..link.stats.len().min(usize::from(u8::MAX))];
+    let description =
+        &link.description.as_bytes()[..link.description.len().min(MAX_ITEM_LINK_DESC_LEN)];
+    let total = ITEM_LINK_HEADER_LEN + stats.len() * ITEM_LINK_STAT_LEN + description.len();
+    let mut buf = vec![0u8; ITEM_LINK_HEADER_LEN];
+    buf[0] = opcode;
+    buf[1..3].copy_from_slice(&(total as u16).to_le_bytes());
+    buf[3..7].copy_from_slice(&link.id.to_le_bytes());
+    buf[7] = link.min_rank;
+    buf[8] = stats.len() as u8;
+    // One extra byte so a full-length name keeps all its letters.
+    let mut name = [0u8; ITEM_LINK_NAME_LEN + 1];
+    write_ascii_into_fixed(&mut name, &link.name);
+    buf[9..].copy_from_slice(&name[..ITEM_LINK_NAME_LEN]);
+    for stat in stats {
+        buf.push(stat.stat);
+        buf.extend_from_slice(&stat.bonus.to_le_bytes());
+        buf.extend_from_slice(&stat.required.to_le_bytes());
+    }
+    buf.extend_from_slice(description);
+    buf
+}
+
+/// Decodes an `SV_ITEMLINK` packet.
+///
+/// # Arguments
+///
+/// * `bytes` - The whole packet, opcode included.
+///
+/// # Returns
+///
+/// * The linked item, or `None` when the packet is truncated.
+pub fn decode_item_link(bytes: &[u8]) -> Option<ItemLink> {
+    let total = usize::from(u16::from_le_bytes([*bytes.get(1)?, *bytes.get(2)?]));
+    let count = usize::from(*bytes.get(8)?);
+    let stats_end = ITEM_LINK_HEADER_LEN + count * ITEM_LINK_STAT_LEN;
+    let stats = bytes
+        .get(ITEM_LINK_HEADER_LEN..stats_end)?
+        .chunks_exact(ITEM_LINK_STAT_LEN)
+        .map(|entry| ItemLinkStat {
+            stat: entry[0],
+            bonus: i16::from_le_bytes([entry[1], entry[2]]),
+            required: i16::from_le_bytes([entry[3], entry[4]]),
+        })
+        .collect();
+    let description = bytes.get(stats_end..total.max(stats_end))?;
+    Some(ItemLink {
+        id: u32::from_le_bytes(bytes.get(3..7)?.try_into().ok()?),
+        name: c_string_to_str(bytes.get(9..ITEM_LINK_HEADER_LEN)?).to_owned(),
+        min_rank: *bytes.get(7)?,
+        stats,
+        description: String::from_utf8_lossy(description).into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_round_trips() {
+        let link = ItemLink {
+            id: 70_001,
+            name: "Sword of the Golden Dawn".to_owned(),
+            min_rank: 4,
+            stats: vec![
+                ItemLinkStat {
+                    stat: 4,
+                    bonus: 2,
+                    required: 30,
+                },
+                ItemLinkStat {
+                    stat: ITEM_STAT_WEAPON,
+                    bonus: 24,
+                    required: 0,
+                },
+                ItemLinkStat {
+                    stat: ITEM_STAT_SKILL_BASE + skills::SK_SWORD as u8,
+                    bonus: -1,
+                    required: 45,
+                },
+            ],
+            description: "A long sword with a golden hilt.".to_owned(),
+        };
+        let pkt = encode_item_link(102, &link);
+        assert_eq!(pkt[0], 102);
+        assert_eq!(usize::from(u16::from_le_bytes([pkt[1], pkt[2]])), pkt.len());
+        assert_eq!(decode_item_link(&pkt), Some(link.clone()));
+        assert_eq!(decode_item_link(&pkt[..ITEM_LINK_HEADER_LEN + 2]), None);
+
+        assert_eq!(link.stats[0].label(), "Strength");
+        assert_eq!(link.stats[1].label(), "Weapon");
+        assert_eq!(
+            link.stats[2].label(),
+            skills::get_skill_name(skills::SK_SWORD)
+        );
+    }
+
+    #[test]
+    fn tokens_split_out_of_chat_text() {
+        let text = format!("buy {} or {}!", slot_token(12), link_token(7));
+        assert_eq!(
+            split_item_links(&text),
+            vec![
+                ChatSegment::Text("buy "),
+                ChatSegment::Slot(12),
+                ChatSegment::Text(" or "),
+                ChatSegment::Link(7),
+                ChatSegment::Text("!"),
+            ]
+        );
+
+        assert_eq!(split_item_links("plain"), vec![ChatSegment::Text("plain")]);
+        assert_eq!(
+            split_item_links("a\u{1f}oops\u{1f}b\u{1f}7"),
+            vec![
+                ChatSegment::Text("a"),
+                ChatSegment::Text("oops"),
+                ChatSegment::Text("b"),
+                ChatSegment::Text("7"),
+            ]
+        );
+    }
+}
@@ -1,41 +1,74 @@
 use log::{LevelFilter, SetLoggerError};
 use log4rs::{
     append::{
+        Append,
         console::{ConsoleAppender, Target},
         file::FileAppender,
     },
-    config::{Appender, Config, Root},
     encode::{Encode, pattern::PatternEncoder},
-    filter::threshold::ThresholdFilter,
 };
-use std::{backtrace, env};
+use std::{backtrace, env, sync::Arc};
 
 pub mod result {
     pub use std::result::*;
 }
 
 pub mod area;
+pub mod balance;
 pub mod ban_action_store;
 pub mod ban_store;
+pub mod char_sheet;
 pub mod character_store;
+pub mod chat;
+pub mod chests;
 pub mod circular_buffer;
 pub mod client_commands;
+pub mod combat_log;
+pub mod consignment;
 pub mod constants;
+pub mod decals;
+pub mod dialogue;
+pub mod disconnect;
+pub mod discord_store;
+pub mod economy_store;
+pub mod friends;
+pub mod guilds;
+pub mod inventory_sort;
+pub mod item_binding;
+pub mod item_link;
 pub mod item_store;
+pub mod log_levels;
+pub mod log_tail_store;
 pub mod logout_reasons;
+pub mod loot;
+pub mod mail;
+pub mod map_markers;
 pub mod map_store;
 pub mod names;
+pub mod npc_menu;
+pub mod online_roster_store;
+pub mod profile;
 pub mod quest_defs;
 pub mod ranks;
+pub mod replay_store;
 pub mod server_commands;
+pub mod shrines;
+pub mod skill_timers;
+pub mod skill_trainers;
 pub mod skills;
+pub mod starter_kits;
 pub mod stat_buffer;
+pub mod stream_overlay_store;
 pub mod string_operations;
 pub mod talent_trees;
 pub mod template_store;
 pub mod text_store;
+pub mod tick_profile_store;
+pub mod titles;
 pub mod traits;
 pub mod types;
+pub mod view;
+pub mod waypoints;
 pub mod weather;
 pub mod weather_areas;
 pub mod world_action_store;
@@ -103,7 +136,32 @@ pub fn initialize_logger(
     log_level: LevelFilter,
     file_path: Option<&str>,
 ) -> Result<(), SetLoggerError> {
-    const LOGGING_PATTERN: &str = "{d} {l} {f}:{L} - {m}\n";
+    initialize_logger_with_tail(log_level, file_path, None)
+}
+
+/// Initializes the global logger like [`initialize_logger`], additionally
+/// mirroring every record at `log_level` or above into a log-tail appender.
+///
+/// `log_level` is only the starting level; [`log_levels`] changes the root
+/// and per-module levels of the running logger.
+///
+/// # Arguments
+///
+/// * `log_level` - Minimum severity that reaches stderr and the tail.
+/// * `file_path` - Optional path to a log file.
+/// * `tail` - Optional appender feeding the admin log tail.
+///
+/// # Returns
+///
+/// * `Ok(())` on success, or a `SetLoggerError` if a logger was already set.
+pub fn initialize_logger_with_tail(
+    log_level: LevelFilter,
+    file_path: Option<&str>,
+    tail: Option<log_tail_store::LogTailAppender>,
+) -> Result<(), SetLoggerError> {
+    // Timestamps are always UTC so log lines line up with stored timestamps
+    // and stay monotonic across daylight-saving changes.
+    const LOGGING_PATTERN: &str = "{d(%Y-%m-%dT%H:%M:%S%.3fZ)(utc)} {l} {f}:{L} - {m}\n";
 
     // Build a stderr logger - always on.
     let stderr = ConsoleAppender::builder()
@@ -111,8 +169,7 @@ pub fn initialize_logger(
         .encoder(Box::new(BacktracePatternEncoder::new(LOGGING_PATTERN)))
         .build();
 
-    let mut config_builder = Config::builder();
-    let mut file_appender_added = false;
+    let mut appenders: Vec<(String, Arc<dyn Append>)> = Vec::new();
 
     if let Some(path) = file_path {
         match FileAppender::builder()
@@ -120,11 +177,7 @@ pub fn initialize_logger(
             .encoder(Box::new(BacktracePatternEncoder::new(LOGGING_PATTERN)))
             .build(path)
         {
-            Ok(logfile) => {
-                config_builder = config_builder
-                    .appender(Appender::builder().build("logfile", Box::new(logfile)));
-                file_appender_added = true;
-            }
+            Ok(logfile) => appenders.push(("logfile".to_owned(), Arc::new(logfile))),
             Err(e) => {
                 // Cannot write to the requested log file (e.g. permission denied
                 // when CWD is "/" inside a macOS .app bundle). Fall back to
@@ -136,27 +189,16 @@ pub fn initialize_logger(
             }
         }
     }
-
-    // Log Trace level output to file where trace is the default level
-    // and the programmatically specified level to stderr.
-    let mut root_builder = Root::builder();
-    if file_appender_added {
-        root_builder = root_builder.appender("logfile");
+    if let Some(tail) = tail {
+        appenders.push(("tail".to_owned(), Arc::new(tail)));
     }
-    let config = config_builder
-        .appender(
-            Appender::builder()
-                .filter(Box::new(ThresholdFilter::new(log_level)))
-                .build("stderr", Box::new(stderr)),
-        )
-        .build(root_builder.appender("stderr").build(log_level))
-        .unwrap();
+    appenders.push(("stderr".to_owned(), Arc::new(stderr)));
 
-    // Use this to change log levels at runtime.
-    // This means you can change the default log level to trace
-    // if you are trying to debug an issue and need more logs on then turn it off
-    // once you are done.
-    let _handle = log4rs::init_config(config)?;
+    // Every appender receives what passes the root and module levels; keep
+    // the handle so they can be changed at runtime (see `log_levels`).
+    let levels = log_levels::LogLevels::new(log_level);
+    let handle = log4rs::init_config(log_levels::build_config(&appenders, &levels))?;
+    log_levels::install(handle, appenders, levels);
 
     Ok(())
 }
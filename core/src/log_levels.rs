@@ -0,0 +1,253 @@
+//! Runtime control of the root and per-module log levels.
+//!
+//! [`initialize_logger_with_tail`](crate::initialize_logger_with_tail) keeps
+//! the log4rs handle and its appenders here. Changing a level rebuilds the
+//! log4rs configuration around the same appenders, so the log file stays
+//! open and nothing is lost while switching. The server exposes this through
+//! the operator console (`loglevel debug`, `loglevel server::driver trace`).
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use log::LevelFilter;
+use log4rs::Handle;
+use log4rs::append::Append;
+use log4rs::config::{Appender, Config, Logger, Root};
+
+/// Root level and per-module overrides of the running logger.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogLevels {
+    /// Level for every module without an override.
+    pub root: LevelFilter,
+    /// Overrides by module path, e.g. `server::driver`.
+    pub modules: BTreeMap<String, LevelFilter>,
+}
+
+impl LogLevels {
+    /// Levels with no module overrides.
+    pub fn new(root: LevelFilter) -> Self {
+        Self {
+            root,
+            modules: BTreeMap::new(),
+        }
+    }
+
+    /// One-line description, e.g. `root=info server::driver=trace`.
+    pub fn describe(&self) -> String {
+        let mut text = format!("root={}", level_name(self.root));
+        for (module, level) in &self.modules {
+            text.push_str(&format!(" {}={}", module, level_name(*level)));
+        }
+        text
+    }
+}
+
+/// Appenders shared between successive configurations.
+#[derive(Debug)]
+struct SharedAppender(Arc<dyn Append>);
+
+impl Append for SharedAppender {
+    fn append(&self, record: &log::Record) -> anyhow::Result<()> {
+        self.0.append(record)
+    }
+
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+/// Everything needed to rebuild the configuration.
+struct LogControl {
+    handle: Handle,
+    appenders: Vec<(String, Arc<dyn Append>)>,
+    startup: LogLevels,
+    current: LogLevels,
+}
+
+static CONTROL: OnceLock<Mutex<LogControl>> = OnceLock::new();
+
+/// Builds a log4rs configuration writing to every appender at `levels`.
+///
+/// # Arguments
+///
+/// * `appenders` - Named appenders; all of them are attached to the root.
+/// * `levels` - Root level and module overrides.
+pub(crate) fn build_config(appenders: &[(String, Arc<dyn Append>)], levels: &LogLevels) -> Config {
+    let mut builder = Config::builder();
+    let mut root = Root::builder();
+    for (name, appender) in appenders {
+        builder = builder.appender(
+            Appender::builder().build(name.clone(), Box::new(SharedAppender(appender.clone()))),
+        );
+        root = root.appender(name.clone());
+    }
+    for (module, level) in &levels.modules {
+        builder = builder.logger(Logger::builder().build(module.clone(), *level));
+    }
+    builder
+        .build(root.build(levels.root))
+        .expect("appender names are unique")
+}
+
+/// Remembers the installed logger so its levels can be changed later.
+///
+/// # Arguments
+///
+/// * `handle` - Handle returned by `log4rs::init_config`.
+/// * `appenders` - The appenders the configuration was built from.
+/// * `levels` - Levels the logger started with.
+pub(crate) fn install(
+    handle: Handle,
+    appenders: Vec<(String, Arc<dyn Append>)>,
+    levels: LogLevels,
+) {
+    let _ = CONTROL.set(Mutex::new(LogControl {
+        handle,
+        appenders,
+        startup: levels.clone(),
+        current: levels,
+    }));
+}
+
+/// Applies `change` to the current levels and reconfigures the logger.
+fn update(change: impl FnOnce(&mut LogLevels, &LogLevels)) -> Result<LogLevels, String> {
+    let control = CONTROL
+        .get()
+        .ok_or_else(|| "the logger was not initialised".to_owned())?;
+    let mut control = control
+        .lock()
+        .map_err(|_| "the log level lock is poisoned".to_owned())?;
+    let LogControl {
+        startup, current, ..
+    } = &mut *control;
+    change(current, startup);
+    let config = build_config(&control.appenders, &control.current);
+    control.handle.set_config(config);
+    Ok(control.current.clone())
+}
+
+/// Levels of the running logger.
+///
+/// # Returns
+///
+/// * `None` before the logger is initialised.
+pub fn current_levels() -> Option<LogLevels> {
+    CONTROL
+        .get()
+        .and_then(|control| control.lock().ok())
+        .map(|control| control.current.clone())
+}
+
+/// Sets the level of every module without an override.
+///
+/// # Returns
+///
+/// * The levels now in effect.
+pub fn set_root_level(level: LevelFilter) -> Result<LogLevels, String> {
+    update(|levels, _| levels.root = level)
+}
+
+/// Overrides the level of one module and its submodules.
+///
+/// # Arguments
+///
+/// * `module` - Module path, e.g. `server::driver`.
+/// * `level` - New level, or `None` to follow the root level again.
+///
+/// # Returns
+///
+/// * The levels now in effect, or why the module path was rejected.
+pub fn set_module_level(module: &str, level: Option<LevelFilter>) -> Result<LogLevels, String> {
+    validate_module(module)?;
+    update(|levels, _| match level {
+        Some(level) => {
+            levels.modules.insert(module.to_owned(), level);
+        }
+        None => {
+            levels.modules.remove(module);
+        }
+    })
+}
+
+/// Restores the levels the logger started with.
+///
+/// # Returns
+///
+/// * The levels now in effect.
+pub fn reset_levels() -> Result<LogLevels, String> {
+    update(|levels, startup| *levels = startup.clone())
+}
+
+/// Parses a level name such as `debug` or `off`, ignoring case.
+pub fn parse_level(text: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(text).map_err(|_| {
+        format!(
+            "unknown log level '{}' (off, error, warn, info, debug, trace)",
+            text
+        )
+    })
+}
+
+/// Checks that `module` looks like a Rust module path.
+fn validate_module(module: &str) -> Result<(), String> {
+    let valid = !module.is_empty()
+        && module.split("::").all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a module path", module))
+    }
+}
+
+fn level_name(level: LevelFilter) -> String {
+    level.to_string().to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_parse_and_describe() {
+        assert_eq!(parse_level("DEBUG"), Ok(LevelFilter::Debug));
+        assert_eq!(parse_level("off"), Ok(LevelFilter::Off));
+        assert!(parse_level("loud").is_err());
+
+        let mut levels = LogLevels::new(LevelFilter::Info);
+        levels
+            .modules
+            .insert("server::driver".to_owned(), LevelFilter::Trace);
+        assert_eq!(levels.describe(), "root=info server::driver=trace");
+
+        assert!(validate_module("server::driver").is_ok());
+        assert!(validate_module("server::").is_err());
+        assert!(validate_module("server driver").is_err());
+    }
+
+    #[test]
+    fn config_carries_module_overrides() {
+        #[derive(Debug)]
+        struct Nothing;
+        impl Append for Nothing {
+            fn append(&self, _: &log::Record) -> anyhow::Result<()> {
+                Ok(())
+            }
+            fn flush(&self) {}
+        }
+
+        let mut levels = LogLevels::new(LevelFilter::Warn);
+        levels
+            .modules
+            .insert("server::driver".to_owned(), LevelFilter::Trace);
+        let appenders: Vec<(String, Arc<dyn Append>)> =
+            vec![("stderr".to_owned(), Arc::new(Nothing))];
+        let config = build_config(&appenders, &levels);
+        assert_eq!(config.root().level(), LevelFilter::Warn);
+        assert_eq!(config.root().appenders(), ["stderr"]);
+        assert_eq!(config.loggers()[0].name(), "server::driver");
+        assert_eq!(config.loggers()[0].level(), LevelFilter::Trace);
+    }
+}
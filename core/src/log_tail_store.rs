@@ -0,0 +1,254 @@
+//! Shared KeyDB key schema and payloads for the admin log tail.
+//!
+//! The game server mirrors its log records into a capped KeyDB list under
+//! [`LOG_TAIL_KEY`]. The admin API reads that list and streams the records
+//! matching a [`LogTailFilter`] to operators as server-sent events.
+
+use std::str::FromStr;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bincode::{Decode, Encode};
+use log::Level;
+use log4rs::append::Append;
+use serde::{Deserialize, Serialize};
+
+/// KeyDB list key holding the most recent [`LogTailRecord`] entries, oldest
+/// first.
+pub const LOG_TAIL_KEY: &str = "game:admin:log_tail";
+
+/// Number of records kept in [`LOG_TAIL_KEY`].
+pub const LOG_TAIL_MAX_LEN: usize = 5000;
+
+/// One mirrored log record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct LogTailRecord {
+    /// Monotonic sequence number assigned by the server; restarts at 1 when
+    /// the server restarts.
+    pub seq: u64,
+    /// Wall-clock time of the record (milliseconds since Unix epoch).
+    pub unix_millis: u64,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`.
+    pub level: String,
+    /// Module path the record was logged from, e.g. `server::god`.
+    pub target: String,
+    pub message: String,
+    /// Character the record is about, when it carries a `chlog!` prefix.
+    pub character_id: Option<u32>,
+}
+
+impl LogTailRecord {
+    /// Encode the record to canonical bincode bytes.
+    ///
+    /// # Returns
+    ///
+    /// * Encoded record bytes.
+    ///
+    /// # Panics
+    ///
+    /// * Panics if bincode serialization fails.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("LogTailRecord::to_bytes failed")
+    }
+
+    /// Decode a record from canonical bincode bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Encoded record payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(record)` when all bytes decode successfully, otherwise `None`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (value, consumed): (Self, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+        if consumed == bytes.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extract the character id from a `chlog!`-style `"Character N: "` prefix.
+///
+/// # Arguments
+///
+/// * `message` - Formatted log message.
+///
+/// # Returns
+///
+/// * `Some(id)` when the message starts with the prefix, otherwise `None`.
+pub fn character_id_from_message(message: &str) -> Option<u32> {
+    let rest = message.strip_prefix("Character ")?;
+    let (id, _) = rest.split_once(": ")?;
+    id.parse().ok()
+}
+
+/// Selects which records an operator wants to see.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogTailFilter {
+    /// Least severe level to include (`Warn` includes warnings and errors).
+    pub level: Option<Level>,
+    /// Only records whose target starts with this module path.
+    pub module: Option<String>,
+    pub character_id: Option<u32>,
+}
+
+impl LogTailFilter {
+    /// Build a filter from optional query-string values.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - Level name such as `warn` (case-insensitive).
+    /// * `module` - Module path prefix.
+    /// * `character_id` - Character id.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(filter)`, or `Err` naming the unknown level.
+    pub fn new(
+        level: Option<&str>,
+        module: Option<String>,
+        character_id: Option<u32>,
+    ) -> Result<Self, String> {
+        let level = match level {
+            Some(name) => {
+                Some(Level::from_str(name).map_err(|_| format!("unknown log level '{}'", name))?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            level,
+            module: module.filter(|module| !module.is_empty()),
+            character_id,
+        })
+    }
+
+    /// Check whether a record passes the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - Mirrored log record.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when every configured criterion matches.
+    pub fn matches(&self, record: &LogTailRecord) -> bool {
+        if let Some(max) = self.level {
+            match Level::from_str(&record.level) {
+                Ok(level) if level <= max => {}
+                _ => return false,
+            }
+        }
+        if let Some(module) = &self.module
+            && !record.target.starts_with(module.as_str())
+        {
+            return false;
+        }
+        if self.character_id.is_some() && record.character_id != self.character_id {
+            return false;
+        }
+        true
+    }
+}
+
+/// log4rs appender that hands records to a publisher thread.
+///
+/// Sending never blocks: when the channel is full the record is dropped, so a
+/// slow or unreachable KeyDB cannot stall logging on the tick thread.
+#[derive(Debug)]
+pub struct LogTailAppender {
+    tx: SyncSender<LogTailRecord>,
+    /// Targets whose records are not mirrored (the publisher's own logs).
+    skip_target: &'static str,
+}
+
+impl LogTailAppender {
+    /// Create an appender and the receiving end for the publisher.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - Maximum number of records buffered before dropping.
+    /// * `skip_target` - Module path prefix whose records are not mirrored.
+    ///
+    /// # Returns
+    ///
+    /// * The appender and the record receiver.
+    pub fn channel(capacity: usize, skip_target: &'static str) -> (Self, Receiver<LogTailRecord>) {
+        let (tx, rx) = sync_channel(capacity);
+        (Self { tx, skip_target }, rx)
+    }
+}
+
+impl Append for LogTailAppender {
+    fn append(&self, record: &log::Record) -> anyhow::Result<()> {
+        if record.target().starts_with(self.skip_target) {
+            return Ok(());
+        }
+        let message = record.args().to_string();
+        let unix_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        let entry = LogTailRecord {
+            seq: 0,
+            unix_millis,
+            level: record.level().to_string(),
+            target: record.target().to_owned(),
+            character_id: character_id_from_message(&message),
+            message,
+        };
+        // Dropped records are acceptable; the tail is a best-effort mirror.
+        let _ = self.tx.try_send(entry);
+        Ok(())
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: &str, target: &str, message: &str) -> LogTailRecord {
+        LogTailRecord {
+            seq: 1,
+            unix_millis: 0,
+            level: level.to_owned(),
+            target: target.to_owned(),
+            message: message.to_owned(),
+            character_id: character_id_from_message(message),
+        }
+    }
+
+    #[test]
+    fn filter_by_level_module_and_character() {
+        let reset = record("WARN", "server::god", "Character 42: item reset");
+        let info = record("INFO", "server::server", "tick");
+
+        let warn = LogTailFilter::new(Some("warn"), None, None).unwrap();
+        assert!(warn.matches(&reset));
+        assert!(!warn.matches(&info));
+
+        let god = LogTailFilter::new(None, Some("server::god".to_owned()), Some(42)).unwrap();
+        assert!(god.matches(&reset));
+        assert!(
+            !LogTailFilter::new(None, None, Some(7))
+                .unwrap()
+                .matches(&reset)
+        );
+        assert!(LogTailFilter::new(Some("loud"), None, None).is_err());
+
+        assert_eq!(LogTailRecord::from_bytes(&reset.to_bytes()), Some(reset));
+    }
+
+    #[test]
+    fn character_id_requires_chlog_prefix() {
+        assert_eq!(character_id_from_message("Character 17: died"), Some(17));
+        assert_eq!(character_id_from_message("Character Bob: died"), None);
+        assert_eq!(character_id_from_message("Saved character 17"), None);
+    }
+}
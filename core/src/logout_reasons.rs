@@ -35,6 +35,13 @@ pub enum LogoutReason {
     Usurp = 13,
     /// Player was kicked by an administrator. `LO_KICKED = 14`
     Kicked = 14,
+    /// Client sent a malformed, oversized or out-of-order packet stream.
+    /// `LO_PROTOCOL = 15`
+    ProtocolViolation = 15,
+    /// Account, character or address is banned. Clients without
+    /// [`CLIENT_CAP_DISCONNECT_REASON`](crate::constants::CLIENT_CAP_DISCONNECT_REASON)
+    /// are sent [`LogoutReason::Kicked`] instead. `LO_BANNED = 16`
+    Banned = 16,
 }
 
 impl From<u8> for LogoutReason {
@@ -54,6 +61,8 @@ impl From<u8> for LogoutReason {
             12 => LogoutReason::Exit,
             13 => LogoutReason::Usurp,
             14 => LogoutReason::Kicked,
+            15 => LogoutReason::ProtocolViolation,
+            16 => LogoutReason::Banned,
             _ => LogoutReason::Unknown,
         }
     }
@@ -84,6 +93,8 @@ pub fn get_exit_reason(reason: LogoutReason) -> &'static str {
         LogoutReason::Exit => "[EXIT] Client exit",
         LogoutReason::Usurp => "[USURP] Logged in elsewhere",
         LogoutReason::Kicked => "[KICKED] Kicked from server",
+        LogoutReason::ProtocolViolation => "[PROTOCOL] Malformed packet",
+        LogoutReason::Banned => "[BANNED] Banned from server",
         _ => "[UNKNOWN] Unrecognized reason code",
     }
 }
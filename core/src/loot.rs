@@ -0,0 +1,146 @@
+//! Loot protection and ground decay tiers.
+//!
+//! Items dropped into a grave are tagged with the player entitled to them
+//! (the killer, or the dead player when no player made the kill). For
+//! [`LOOT_PROTECT_SECONDS`] only that player and their acknowledged group may
+//! take them; the server enforces this in its pickup paths and marks
+//! protected tiles with [`crate::constants::LOOT_PROTECTED`] so other
+//! clients can draw them greyed out.
+//!
+//! Items lying on the ground age by [`LootTier`]: magic and unique items
+//! decay more slowly than common ones.
+//!
+//! Persistence layout (`Item::future3`):
+//!
+//! * `future3[LOOT_OWNER_SLOT]` — character id of the owner (`0` = none).
+//! * `future3[LOOT_UNTIL_SLOT]` — `globals.ticker` at which protection ends.
+
+use crate::constants::{ItemFlags, TICKS};
+use crate::types::Item;
+
+/// Index into `Item::future3` holding the loot owner's character id.
+pub const LOOT_OWNER_SLOT: usize = 0;
+
+/// Index into `Item::future3` holding the tick protection expires at.
+pub const LOOT_UNTIL_SLOT: usize = 1;
+
+/// How long freshly dropped loot is reserved for its owner.
+pub const LOOT_PROTECT_SECONDS: i32 = 60;
+
+/// Rarity class deciding how fast an item decays on the ground.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LootTier {
+    Common,
+    Magic,
+    Unique,
+}
+
+impl LootTier {
+    /// Classifies an item by its flags.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - `Item::flags`.
+    ///
+    /// # Returns
+    ///
+    /// * `Unique` for `IF_UNIQUE`, `Magic` for `IF_MAGIC`, else `Common`.
+    pub fn of(flags: u64) -> Self {
+        if flags & ItemFlags::IF_UNIQUE.bits() != 0 {
+            LootTier::Unique
+        } else if flags & ItemFlags::IF_MAGIC.bits() != 0 {
+            LootTier::Magic
+        } else {
+            LootTier::Common
+        }
+    }
+
+    /// Scales one ground-aging step for this tier.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - Age a common item gains in the same time.
+    ///
+    /// # Returns
+    ///
+    /// * The age this tier gains: the full step for common items, half for
+    ///   magic and a quarter for unique ones (never less than one).
+    pub fn ground_age_step(self, step: u32) -> u32 {
+        let divisor = match self {
+            LootTier::Common => 1,
+            LootTier::Magic => 2,
+            LootTier::Unique => 4,
+        };
+        (step / divisor).max(1)
+    }
+}
+
+/// Reserves `item` for `owner` for [`LOOT_PROTECT_SECONDS`].
+///
+/// # Arguments
+///
+/// * `item` - Item to tag.
+/// * `owner` - Character id entitled to the item.
+/// * `ticker` - Current `globals.ticker`.
+pub fn protect(item: &mut Item, owner: usize, ticker: i32) {
+    item.future3[LOOT_OWNER_SLOT] = owner as i32;
+    item.future3[LOOT_UNTIL_SLOT] = ticker.saturating_add(LOOT_PROTECT_SECONDS * TICKS);
+}
+
+/// Removes any ownership tag from `item`.
+///
+/// # Arguments
+///
+/// * `item` - Item to release.
+pub fn release(item: &mut Item) {
+    item.future3[LOOT_OWNER_SLOT] = 0;
+    item.future3[LOOT_UNTIL_SLOT] = 0;
+}
+
+/// Returns the character `item` is still reserved for.
+///
+/// # Arguments
+///
+/// * `item` - Item to check.
+/// * `ticker` - Current `globals.ticker`.
+///
+/// # Returns
+///
+/// * The owner while protection lasts, otherwise `None`.
+pub fn owner(item: &Item, ticker: i32) -> Option<usize> {
+    let owner = item.future3[LOOT_OWNER_SLOT];
+    if owner <= 0 || ticker >= item.future3[LOOT_UNTIL_SLOT] {
+        return None;
+    }
+    Some(owner as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protection_expires_and_release_clears_it() {
+        let mut item = Item::default();
+        assert_eq!(owner(&item, 0), None);
+
+        protect(&mut item, 7, 1000);
+        assert_eq!(owner(&item, 1000), Some(7));
+        assert_eq!(owner(&item, 1000 + LOOT_PROTECT_SECONDS * TICKS), None);
+
+        release(&mut item);
+        assert_eq!(owner(&item, 1000), None);
+    }
+
+    #[test]
+    fn rarer_items_age_slower() {
+        let unique = ItemFlags::IF_UNIQUE.bits() | ItemFlags::IF_MAGIC.bits();
+        assert_eq!(LootTier::of(0).ground_age_step(100), 100);
+        assert_eq!(
+            LootTier::of(ItemFlags::IF_MAGIC.bits()).ground_age_step(100),
+            50
+        );
+        assert_eq!(LootTier::of(unique), LootTier::Unique);
+        assert_eq!(LootTier::Unique.ground_age_step(2), 1);
+    }
+}
@@ -0,0 +1,185 @@
+//! Shared mail limits and wire helpers for `SV_MAILENTRY`.
+//!
+//! Players send letters with `#mail send`, optionally with the item on
+//! their cursor attached (`#mail parcel`). Letters are kept by the server
+//! (see `server/src/mail`) until the recipient deletes them, so they reach
+//! characters that are offline. A client advertising
+//! [`CLIENT_CAP_MAIL`](crate::constants::CLIENT_CAP_MAIL) receives its
+//! mailbox as one `MailEntry` packet per letter.
+
+use crate::string_operations::{c_string_to_str, write_ascii_into_fixed};
+
+/// Longest letter text in bytes.
+pub const MAX_MAIL_BODY_LEN: usize = 200;
+
+/// Most letters a mailbox holds; sending to a full mailbox is refused.
+pub const MAX_MAILBOX_LETTERS: usize = 30;
+
+/// Bytes of the sender name in `SV_MAILENTRY`, NUL padded.
+pub const MAIL_SENDER_NAME_LEN: usize = 16;
+
+/// Bytes of the attachment name in `SV_MAILENTRY`, NUL padded.
+pub const MAIL_ATTACHMENT_NAME_LEN: usize = 40;
+
+/// Size of the fixed part of `SV_MAILENTRY` (opcode, length, op, id, sent
+/// time, unread flag, sender and attachment names).
+pub const MAIL_ENTRY_HEADER_LEN: usize = 13 + MAIL_SENDER_NAME_LEN + MAIL_ATTACHMENT_NAME_LEN;
+
+/// What an `SV_MAILENTRY` packet does to the client's mailbox.
+///
+/// Numeric values are part of the wire protocol — do not renumber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MailOp {
+    /// Adds the letter or replaces the one with the same id.
+    Set = 0,
+    /// Removes the letter with this id.
+    Remove = 1,
+    /// Empties the mailbox; sent before the whole mailbox is resent.
+    Clear = 2,
+}
+
+impl MailOp {
+    /// Decodes an op byte; unknown values fall back to [`MailOp::Set`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Op byte from the packet.
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => MailOp::Remove,
+            2 => MailOp::Clear,
+            _ => MailOp::Set,
+        }
+    }
+}
+
+/// One letter as shown in a mailbox.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MailEntry {
+    /// Server-wide letter id, used by `#mail read|take|delete`.
+    pub id: u32,
+    /// When the letter was sent, in Unix seconds.
+    pub sent_unix: u32,
+    /// Whether the recipient has not read the letter yet.
+    pub unread: bool,
+    /// Sender's character name.
+    pub from: String,
+    /// Name of the attached item; empty when nothing is attached.
+    pub attachment: String,
+    pub body: String,
+}
+
+/// Checks the text of a letter.
+///
+/// # Arguments
+///
+/// * `body` - Text as typed by the sender.
+///
+/// # Returns
+///
+/// * `Ok(())` for 1..=[`MAX_MAIL_BODY_LEN`] bytes of printable ASCII, or a
+///   message for the player.
+pub fn validate_mail_body(body: &str) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("What do you want to write?".to_owned());
+    }
+    if body.len() > MAX_MAIL_BODY_LEN {
+        return Err(format!(
+            "Letters may be at most {} characters long.",
+            MAX_MAIL_BODY_LEN
+        ));
+    }
+    if !body.bytes().all(|b| (0x20..0x7f).contains(&b)) {
+        return Err("Letters may only contain plain text.".to_owned());
+    }
+    Ok(())
+}
+
+/// Builds an `SV_MAILENTRY` packet.
+///
+/// # Arguments
+///
+/// * `opcode` - `ServerCommandType::MailEntry` as a byte.
+/// * `op` - Whether the letter is set or removed, or the mailbox cleared.
+/// * `entry` - The letter; only `id` matters for [`MailOp::Remove`].
+///
+/// # Returns
+///
+/// * The encoded packet.
+pub fn encode_mail_entry(opcode: u8, op: MailOp, entry: &MailEntry) -> Vec<u8> {
+    let body = &entry.body.as_bytes()[..entry.body.len().min(MAX_MAIL_BODY_LEN)];
+    let total = MAIL_ENTRY_HEADER_LEN + body.len();
+    let mut buf = vec![0u8; MAIL_ENTRY_HEADER_LEN];
+    buf[0] = opcode;
+    buf[1..3].copy_from_slice(&(total as u16).to_le_bytes());
+    buf[3] = op as u8;
+    buf[4..8].copy_from_slice(&entry.id.to_le_bytes());
+    buf[8..12].copy_from_slice(&entry.sent_unix.to_le_bytes());
+    buf[12] = u8::from(entry.unread);
+    // One extra byte so full-length names keep all their letters.
+    let mut from = [0u8; MAIL_SENDER_NAME_LEN + 1];
+    write_ascii_into_fixed(&mut from, &entry.from);
+    buf[13..13 + MAIL_SENDER_NAME_LEN].copy_from_slice(&from[..MAIL_SENDER_NAME_LEN]);
+    let mut attachment = [0u8; MAIL_ATTACHMENT_NAME_LEN + 1];
+    write_ascii_into_fixed(&mut attachment, &entry.attachment);
+    buf[13 + MAIL_SENDER_NAME_LEN..].copy_from_slice(&attachment[..MAIL_ATTACHMENT_NAME_LEN]);
+    buf.extend_from_slice(body);
+    buf
+}
+
+/// Decodes the payload of an `SV_MAILENTRY` packet.
+///
+/// # Arguments
+///
+/// * `bytes` - The whole packet, opcode included.
+///
+/// # Returns
+///
+/// * The op byte and the letter, or `None` when the packet is truncated.
+pub fn decode_mail_entry(bytes: &[u8]) -> Option<(u8, MailEntry)> {
+    let total = usize::from(u16::from_le_bytes([*bytes.get(1)?, *bytes.get(2)?]));
+    let names = 13 + MAIL_SENDER_NAME_LEN;
+    let body = bytes.get(MAIL_ENTRY_HEADER_LEN..total.max(MAIL_ENTRY_HEADER_LEN))?;
+    Some((
+        *bytes.get(3)?,
+        MailEntry {
+            id: u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?),
+            sent_unix: u32::from_le_bytes(bytes.get(8..12)?.try_into().ok()?),
+            unread: *bytes.get(12)? != 0,
+            from: c_string_to_str(bytes.get(13..names)?).to_owned(),
+            attachment: c_string_to_str(bytes.get(names..MAIL_ENTRY_HEADER_LEN)?).to_owned(),
+            body: String::from_utf8_lossy(body).into_owned(),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_round_trips() {
+        let entry = MailEntry {
+            id: 41,
+            sent_unix: 1_790_000_000,
+            unread: true,
+            from: "Ishtar".to_owned(),
+            attachment: "Golden Ring".to_owned(),
+            body: "Happy birthday!".to_owned(),
+        };
+        let pkt = encode_mail_entry(94, MailOp::Set, &entry);
+        assert_eq!(pkt.len(), MAIL_ENTRY_HEADER_LEN + entry.body.len());
+        assert_eq!(u16::from_le_bytes([pkt[1], pkt[2]]) as usize, pkt.len());
+        assert_eq!(decode_mail_entry(&pkt), Some((MailOp::Set as u8, entry)));
+        assert_eq!(decode_mail_entry(&pkt[..20]), None);
+    }
+
+    #[test]
+    fn bodies_must_be_short_plain_text() {
+        assert!(validate_mail_body("See you at the inn.").is_ok());
+        assert!(validate_mail_body("   ").is_err());
+        assert!(validate_mail_body(&"a".repeat(MAX_MAIL_BODY_LEN + 1)).is_err());
+        assert!(validate_mail_body("tab\there").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+//! Points of interest drawn on the client minimap.
+//!
+//! The server knows where a character respawns (temple), where they last
+//! rested (tavern) and where they last died, and announces those with
+//! `SV_MAPMARKER` to clients advertising
+//! [`crate::constants::CLIENT_CAP_MAP_MARKERS`]. Banks are not announced:
+//! the client remembers the tiles it has seen with the `IS_BANK` hint.
+
+/// Kind of a minimap point of interest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum MapMarkerKind {
+    /// Respawn point (`temple_x`, `temple_y`).
+    Temple = 0,
+    /// Rest / login point (`tavern_x`, `tavern_y`).
+    Tavern = 1,
+    /// A bank the player has seen.
+    Bank = 2,
+    /// Where the player last died this session.
+    Death = 3,
+}
+
+impl MapMarkerKind {
+    /// Every marker kind, in wire order.
+    pub const ALL: [MapMarkerKind; 4] = [
+        MapMarkerKind::Temple,
+        MapMarkerKind::Tavern,
+        MapMarkerKind::Bank,
+        MapMarkerKind::Death,
+    ];
+
+    /// Decodes a wire value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Kind byte from `SV_MAPMARKER`.
+    ///
+    /// # Returns
+    ///
+    /// * The kind, or `None` for an unknown value.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(value)).copied()
+    }
+
+    /// Short display name used for legends and tooltips.
+    pub fn label(self) -> &'static str {
+        match self {
+            MapMarkerKind::Temple => "Temple",
+            MapMarkerKind::Tavern => "Tavern",
+            MapMarkerKind::Bank => "Bank",
+            MapMarkerKind::Death => "Death",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_round_trip_through_wire_values() {
+        for kind in MapMarkerKind::ALL {
+            assert_eq!(MapMarkerKind::from_u8(kind as u8), Some(kind));
+        }
+        assert_eq!(MapMarkerKind::from_u8(4), None);
+    }
+}
@@ -24,8 +24,20 @@ const SYL3: [&str; 5] = ["a", "e", "i", "o", "u"];
 ///
 /// * A random name string (e.g. `"Arturo"`, `"Nimkan"`).
 pub fn randomly_generate_name() -> String {
-    let mut rng = rand::thread_rng();
+    generate_name_with(&mut rand::thread_rng())
+}
 
+/// Generates a name like [`randomly_generate_name`] from the given generator,
+/// so seeded callers get repeatable names.
+///
+/// # Arguments
+///
+/// * `rng` - Random generator to draw the syllables from.
+///
+/// # Returns
+///
+/// * A random name string.
+pub fn generate_name_with<R: Rng + ?Sized>(rng: &mut R) -> String {
     let mut name = String::new();
 
     let n = rng.gen_range(0..SYL1.len());
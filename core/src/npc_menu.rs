@@ -0,0 +1,117 @@
+//! NPC interaction menu shared between client and server.
+//!
+//! Right-clicking a character sends `CmdNpcMenu`. For NPCs with a role the
+//! server answers with `SV_NPCMENU`, a bitmask of the [`NpcMenuOption`]s
+//! that NPC supports; the client shows them as a context menu and sends the
+//! chosen one back as `CmdNpcAction`. Characters without any role (and all
+//! players) just get the legacy look.
+//!
+//! Option values are part of the wire protocol — do not renumber.
+
+/// One entry of the NPC interaction menu.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum NpcMenuOption {
+    /// Greet the NPC (the player says hello and the NPC answers).
+    Talk = 0,
+    /// Open the merchant's shop.
+    Trade = 1,
+    /// Ask which skill the NPC teaches and what it wants in return, or list
+    /// a trainer's offers (see [`crate::skill_trainers`]).
+    Train = 2,
+    /// Ask which item the NPC is looking for.
+    Quests = 3,
+    /// Legacy look (description and equipment).
+    Look = 4,
+}
+
+impl NpcMenuOption {
+    /// All options in menu display order.
+    pub const ALL: [NpcMenuOption; 5] = [
+        NpcMenuOption::Talk,
+        NpcMenuOption::Trade,
+        NpcMenuOption::Train,
+        NpcMenuOption::Quests,
+        NpcMenuOption::Look,
+    ];
+
+    /// Returns the option's bit in an `SV_NPCMENU` mask.
+    ///
+    /// # Returns
+    ///
+    /// * `1 << discriminant`.
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Returns the label shown in the context menu.
+    ///
+    /// # Returns
+    ///
+    /// * A short, static label.
+    pub fn label(self) -> &'static str {
+        match self {
+            NpcMenuOption::Talk => "Talk",
+            NpcMenuOption::Trade => "Trade",
+            NpcMenuOption::Train => "Train",
+            NpcMenuOption::Quests => "Quests",
+            NpcMenuOption::Look => "Look",
+        }
+    }
+
+    /// Decodes an option byte from `CmdNpcAction`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Option discriminant.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(option)` for a known value, otherwise `None`.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|o| *o as u8 == value)
+    }
+}
+
+/// Iterates the options present in an `SV_NPCMENU` mask, in display order.
+///
+/// # Arguments
+///
+/// * `mask` - Option bitmask.
+///
+/// # Returns
+///
+/// * Iterator over the set options.
+pub fn options_in_mask(mask: u8) -> impl Iterator<Item = NpcMenuOption> {
+    NpcMenuOption::ALL
+        .into_iter()
+        .filter(move |o| mask & o.bit() != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_bytes_roundtrip() {
+        for option in NpcMenuOption::ALL {
+            assert_eq!(NpcMenuOption::from_u8(option as u8), Some(option));
+        }
+        assert_eq!(NpcMenuOption::from_u8(5), None);
+    }
+
+    #[test]
+    fn mask_lists_options_in_display_order() {
+        let mask =
+            NpcMenuOption::Look.bit() | NpcMenuOption::Talk.bit() | NpcMenuOption::Quests.bit();
+        let options: Vec<_> = options_in_mask(mask).collect();
+        assert_eq!(
+            options,
+            vec![
+                NpcMenuOption::Talk,
+                NpcMenuOption::Quests,
+                NpcMenuOption::Look
+            ]
+        );
+    }
+}
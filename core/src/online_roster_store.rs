@@ -0,0 +1,106 @@
+//! Shared KeyDB key and payload for the online player roster.
+//!
+//! The game server periodically writes an [`OnlineRoster`] listing every
+//! session that is logged in and playing to [`ONLINE_ROSTER_KEY`]. The admin
+//! API serves it as JSON so operators can see who is online without reading
+//! the server log.
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// KeyDB key holding the latest bincode-encoded [`OnlineRoster`].
+pub const ONLINE_ROSTER_KEY: &str = "game:admin:online_roster";
+
+/// How often the server refreshes [`ONLINE_ROSTER_KEY`] (seconds).
+pub const ONLINE_ROSTER_INTERVAL_SECS: u64 = 5;
+
+/// One logged-in player session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct OnlinePlayer {
+    /// Server player slot.
+    pub player_slot: u32,
+    /// Game character slot the session controls.
+    pub character_id: u32,
+    pub name: String,
+    /// API account that owns the character (0 for legacy logins).
+    pub account_id: u64,
+    /// API character id (0 for legacy logins).
+    pub api_character_id: u64,
+    pub x: i16,
+    pub y: i16,
+    /// Area name(s) at the character's position, empty outside all areas.
+    pub area: String,
+}
+
+/// Players online at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct OnlineRoster {
+    /// Wall-clock time the roster was taken (seconds since Unix epoch).
+    pub generated_at: u64,
+    /// Server ticker value when the roster was taken.
+    pub ticker: i32,
+    /// Online players ordered by name.
+    pub players: Vec<OnlinePlayer>,
+}
+
+impl OnlineRoster {
+    /// Encode the roster to canonical bincode bytes.
+    ///
+    /// # Returns
+    ///
+    /// * Encoded roster bytes.
+    ///
+    /// # Panics
+    ///
+    /// * Panics if bincode serialization fails.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("OnlineRoster::to_bytes failed")
+    }
+
+    /// Decode a roster from canonical bincode bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Encoded roster payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(roster)` when all bytes decode successfully, otherwise `None`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (value, consumed): (Self, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+        if consumed == bytes.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roster_roundtrip_rejects_trailing_bytes() {
+        let roster = OnlineRoster {
+            generated_at: 1_700_000_000,
+            ticker: 420,
+            players: vec![OnlinePlayer {
+                player_slot: 3,
+                character_id: 1201,
+                name: "Ishtar".to_owned(),
+                account_id: 9,
+                api_character_id: 77,
+                x: 512,
+                y: 512,
+                area: "Aston".to_owned(),
+            }],
+        };
+        let mut bytes = roster.to_bytes();
+        assert_eq!(OnlineRoster::from_bytes(&bytes), Some(roster));
+        bytes.push(0);
+        assert_eq!(OnlineRoster::from_bytes(&bytes), None);
+    }
+}
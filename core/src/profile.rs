@@ -0,0 +1,248 @@
+//! Shared player profile (bio + name color) definitions.
+//!
+//! The client's profile editor uploads the bio through `CmdSetProfile`
+//! (see [`crate::client_commands`]) in [`PROFILE_CHUNK_LEN`]-byte chunks.
+//! The server validates the assembled text with [`validate_bio`], stores it
+//! in `Character::description`, and mirrors it into the API-side
+//! `character:{id}` hash so it survives the next login sync.
+//!
+//! Persistence layout (`Character::future3`):
+//!
+//! * `future3[PROFILE_NAME_COLOR_SLOT]` — [`NameColor`] discriminant
+//!   (`0` = default, which is what every pre-existing character holds).
+//! * `future3[1..=2]` — earned/selected titles, see [`crate::titles`].
+
+/// Maximum bio length in bytes. Matches the width of `Character::description`.
+pub const MAX_BIO_LEN: usize = 200;
+
+/// Minimum bio length in bytes (after trimming).
+pub const MIN_BIO_LEN: usize = 10;
+
+/// Number of bio bytes carried by a single `CmdSetProfile` packet.
+pub const PROFILE_CHUNK_LEN: usize = 13;
+
+/// Maximum number of `CmdSetProfile` chunks for one upload.
+pub const MAX_PROFILE_CHUNKS: usize = MAX_BIO_LEN.div_ceil(PROFILE_CHUNK_LEN);
+
+/// Bit in the chunk-index byte marking the last chunk of an upload.
+pub const PROFILE_FINAL_CHUNK: u8 = 0b1000_0000;
+
+/// Index into `Character::future3` holding the selected [`NameColor`].
+pub const PROFILE_NAME_COLOR_SLOT: usize = 0;
+
+/// Palette of name colors a player can pick in the profile editor.
+///
+/// Numeric values are part of the wire protocol and persisted on the
+/// character — do not renumber.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum NameColor {
+    /// Render with the normal bitmap font color.
+    #[default]
+    Default = 0,
+    Crimson = 1,
+    Amber = 2,
+    Emerald = 3,
+    Azure = 4,
+    Violet = 5,
+    Silver = 6,
+    Gold = 7,
+}
+
+impl NameColor {
+    /// All palette entries, in editor display order.
+    pub const ALL: [NameColor; 8] = [
+        NameColor::Default,
+        NameColor::Crimson,
+        NameColor::Amber,
+        NameColor::Emerald,
+        NameColor::Azure,
+        NameColor::Violet,
+        NameColor::Silver,
+        NameColor::Gold,
+    ];
+
+    /// Returns the wire-protocol byte representation.
+    ///
+    /// # Returns
+    ///
+    /// * The discriminant byte for this color.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Human-readable label for the profile editor.
+    ///
+    /// # Returns
+    ///
+    /// * A short display name.
+    pub fn label(self) -> &'static str {
+        match self {
+            NameColor::Default => "Default",
+            NameColor::Crimson => "Crimson",
+            NameColor::Amber => "Amber",
+            NameColor::Emerald => "Emerald",
+            NameColor::Azure => "Azure",
+            NameColor::Violet => "Violet",
+            NameColor::Silver => "Silver",
+            NameColor::Gold => "Gold",
+        }
+    }
+
+    /// RGB tint for this color.
+    ///
+    /// # Returns
+    ///
+    /// * `Some((r, g, b))` for a tinted color, or `None` for [`NameColor::Default`].
+    pub fn rgb(self) -> Option<(u8, u8, u8)> {
+        match self {
+            NameColor::Default => None,
+            NameColor::Crimson => Some((220, 60, 60)),
+            NameColor::Amber => Some((240, 160, 40)),
+            NameColor::Emerald => Some((60, 200, 100)),
+            NameColor::Azure => Some((80, 150, 240)),
+            NameColor::Violet => Some((170, 100, 230)),
+            NameColor::Silver => Some((200, 200, 210)),
+            NameColor::Gold => Some((250, 215, 60)),
+        }
+    }
+}
+
+impl From<u8> for NameColor {
+    /// Decodes a wire/persisted byte into a [`NameColor`].
+    ///
+    /// Unknown values map to [`NameColor::Default`].
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The byte value to decode.
+    ///
+    /// # Returns
+    ///
+    /// * The matching [`NameColor`].
+    fn from(value: u8) -> Self {
+        NameColor::ALL
+            .get(value as usize)
+            .copied()
+            .unwrap_or(NameColor::Default)
+    }
+}
+
+/// Validates a player-authored bio.
+///
+/// Uses the same rules the account API applies to character descriptions
+/// so a bio edited in-game is always accepted by the API later on.
+///
+/// # Arguments
+///
+/// * `name` - The character's name; the bio must mention it.
+/// * `bio` - The proposed bio text.
+///
+/// # Returns
+///
+/// * `Ok(())` when the bio is acceptable.
+/// * `Err(String)` with a user-facing reason otherwise.
+pub fn validate_bio(name: &str, bio: &str) -> Result<(), String> {
+    let name = name.trim();
+    let bio = bio.trim();
+
+    if name.is_empty() {
+        return Err("Character name is required".to_owned());
+    }
+
+    if bio.len() < MIN_BIO_LEN {
+        return Err(format!(
+            "Description must be at least {} characters",
+            MIN_BIO_LEN
+        ));
+    }
+
+    if bio.len() > MAX_BIO_LEN {
+        return Err(format!(
+            "Description must be at most {} characters",
+            MAX_BIO_LEN
+        ));
+    }
+
+    if !bio.bytes().all(|b| (32..=126).contains(&b)) {
+        return Err("Description must be ASCII-only (printable characters)".to_owned());
+    }
+
+    if bio.contains('"') {
+        return Err("Description must not contain double quotes".to_owned());
+    }
+
+    if !bio.contains(name) {
+        return Err("Description must contain the character name".to_owned());
+    }
+
+    Ok(())
+}
+
+/// Returns the first entry of `bad_words` found in `text`, if any.
+///
+/// Matching is ASCII case-insensitive and substring based; blank entries
+/// are ignored.
+///
+/// # Arguments
+///
+/// * `text` - Text to scan.
+/// * `bad_words` - Banned word list (as loaded from `game:badwords`).
+///
+/// # Returns
+///
+/// * `Some(word)` for the first match, `None` when the text is clean.
+pub fn find_bad_word<'a>(text: &str, bad_words: &'a [String]) -> Option<&'a str> {
+    let haystack = text.to_ascii_lowercase();
+    bad_words
+        .iter()
+        .map(|w| w.trim())
+        .filter(|w| !w.is_empty())
+        .find(|w| haystack.contains(&w.to_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_color_roundtrip() {
+        for c in NameColor::ALL {
+            assert_eq!(NameColor::from(c.as_u8()), c);
+        }
+        assert_eq!(NameColor::from(200), NameColor::Default);
+    }
+
+    #[test]
+    fn default_color_has_no_tint() {
+        assert!(NameColor::Default.rgb().is_none());
+        assert!(NameColor::Gold.rgb().is_some());
+    }
+
+    #[test]
+    fn chunk_count_covers_max_bio() {
+        assert!(MAX_PROFILE_CHUNKS * PROFILE_CHUNK_LEN >= MAX_BIO_LEN);
+        assert!((MAX_PROFILE_CHUNKS as u8) < PROFILE_FINAL_CHUNK);
+    }
+
+    #[test]
+    fn validate_bio_accepts_valid() {
+        assert!(validate_bio("Ishtar", "Ishtar is a wandering sellsword.").is_ok());
+    }
+
+    #[test]
+    fn validate_bio_rejects_bad_input() {
+        assert!(validate_bio("Ishtar", "short").is_err());
+        assert!(validate_bio("Ishtar", "Someone else entirely here.").is_err());
+        assert!(validate_bio("Ishtar", "Ishtar says \"hello\" to all").is_err());
+        assert!(validate_bio("Ishtar", &format!("Ishtar {}", "x".repeat(MAX_BIO_LEN))).is_err());
+        assert!(validate_bio("Ishtar", "Ishtar walks the \u{e9}pic road").is_err());
+    }
+
+    #[test]
+    fn find_bad_word_is_case_insensitive() {
+        let words = vec!["  ".to_owned(), "Darn".to_owned()];
+        assert_eq!(find_bad_word("Well DARN it", &words), Some("Darn"));
+        assert_eq!(find_bad_word("All clean", &words), None);
+    }
+}
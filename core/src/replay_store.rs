@@ -0,0 +1,207 @@
+//! Viewport recordings rendered by the client's replay viewer.
+//!
+//! `world_snapshot export-replay` re-applies a server journal onto a `.wsnap`
+//! snapshot one tick at a time and captures, after every tick, the square of
+//! tiles around a followed character. The client loads the resulting file
+//! and renders the frames with its normal world renderer, without a server
+//! connection.
+//!
+//! # File format
+//!
+//! A single bincode-encoded [`ReplayRecording`] whose
+//! [`magic`](ReplayRecording::magic) and [`version`](ReplayRecording::version)
+//! are checked on decode.
+
+use bincode::{Decode, Encode};
+
+/// Magic bytes at the start of every recording.
+pub const REPLAY_MAGIC: [u8; 4] = *b"MGRP";
+
+/// Recording format version.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// Tiles captured on each side of the followed character.
+pub const REPLAY_VIEW_RADIUS: i16 = 20;
+
+/// Width and height of a captured frame in tiles.
+pub const REPLAY_VIEW_SIZE: usize = REPLAY_VIEW_RADIUS as usize * 2 + 1;
+
+/// Sprites on one captured tile; `0` means nothing is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ReplayTile {
+    /// Background (floor) sprite.
+    pub back: u16,
+    /// Foreground or item sprite.
+    pub item: u16,
+    /// Base sprite of the character standing here.
+    pub character: u16,
+    /// Slot number of the character standing here.
+    pub character_nr: u16,
+}
+
+/// The world around the followed character after one tick.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ReplayFrame {
+    /// `globals.ticker` after the tick ran.
+    pub tick: i32,
+    /// Map position of the followed character.
+    pub center_x: i16,
+    /// Map position of the followed character.
+    pub center_y: i16,
+    /// `REPLAY_VIEW_SIZE` x `REPLAY_VIEW_SIZE` tiles in row-major order,
+    /// starting at `(center_x - REPLAY_VIEW_RADIUS, center_y - REPLAY_VIEW_RADIUS)`.
+    pub tiles: Vec<ReplayTile>,
+}
+
+impl ReplayFrame {
+    /// Returns the tile at an offset from the frame centre.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx` - Horizontal offset in tiles.
+    /// * `dy` - Vertical offset in tiles.
+    ///
+    /// # Returns
+    ///
+    /// * The captured tile, or `None` outside the captured square.
+    pub fn tile(&self, dx: i16, dy: i16) -> Option<&ReplayTile> {
+        if dx.abs() > REPLAY_VIEW_RADIUS || dy.abs() > REPLAY_VIEW_RADIUS {
+            return None;
+        }
+        let col = (dx + REPLAY_VIEW_RADIUS) as usize;
+        let row = (dy + REPLAY_VIEW_RADIUS) as usize;
+        self.tiles.get(col + row * REPLAY_VIEW_SIZE)
+    }
+}
+
+/// A complete recording.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ReplayRecording {
+    /// Magic bytes; must equal [`REPLAY_MAGIC`].
+    pub magic: [u8; 4],
+    /// Format version; must equal [`REPLAY_VERSION`].
+    pub version: u32,
+    /// Character slot the camera follows.
+    pub follow: u32,
+    /// Frames in tick order.
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl ReplayRecording {
+    /// Creates a recording with the current magic and version.
+    ///
+    /// # Arguments
+    ///
+    /// * `follow` - Character slot the camera follows.
+    /// * `frames` - Frames in tick order.
+    ///
+    /// # Returns
+    ///
+    /// * A new `ReplayRecording`.
+    pub fn new(follow: u32, frames: Vec<ReplayFrame>) -> Self {
+        Self {
+            magic: REPLAY_MAGIC,
+            version: REPLAY_VERSION,
+            follow,
+            frames,
+        }
+    }
+
+    /// Encodes the recording to bincode bytes.
+    ///
+    /// # Returns
+    ///
+    /// * Encoded recording bytes.
+    ///
+    /// # Panics
+    ///
+    /// * Panics if bincode serialization fails.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("ReplayRecording::to_bytes failed")
+    }
+
+    /// Decodes a recording and checks its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Contents of a recording file.
+    ///
+    /// # Returns
+    ///
+    /// * The decoded recording, or a description of why it was rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let (recording, _): (Self, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard())
+                .map_err(|e| format!("Failed to decode replay: {e}"))?;
+        if recording.magic != REPLAY_MAGIC {
+            return Err("Not a replay file (bad magic)".to_owned());
+        }
+        if recording.version != REPLAY_VERSION {
+            return Err(format!(
+                "Unsupported replay version {} (expected {})",
+                recording.version, REPLAY_VERSION
+            ));
+        }
+        Ok(recording)
+    }
+
+    /// Finds the frame showing the world at `tick`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - Server tick to show.
+    ///
+    /// # Returns
+    ///
+    /// * Index of the last frame recorded at or before `tick`, or `None`
+    ///   when `tick` precedes the first frame.
+    pub fn frame_at_tick(&self, tick: i32) -> Option<usize> {
+        self.frames
+            .partition_point(|frame| frame.tick <= tick)
+            .checked_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(tick: i32) -> ReplayFrame {
+        ReplayFrame {
+            tick,
+            center_x: 100,
+            center_y: 200,
+            tiles: vec![ReplayTile::default(); REPLAY_VIEW_SIZE * REPLAY_VIEW_SIZE],
+        }
+    }
+
+    #[test]
+    fn round_trips_and_rejects_foreign_files() {
+        let mut first = frame(5);
+        first.tiles[0].back = 1001;
+        let recording = ReplayRecording::new(7, vec![first, frame(6)]);
+        let decoded = ReplayRecording::from_bytes(&recording.to_bytes()).unwrap();
+        assert_eq!(decoded, recording);
+        assert_eq!(
+            decoded.frames[0]
+                .tile(-REPLAY_VIEW_RADIUS, -REPLAY_VIEW_RADIUS)
+                .map(|t| t.back),
+            Some(1001)
+        );
+        assert!(decoded.frames[0].tile(REPLAY_VIEW_RADIUS + 1, 0).is_none());
+
+        let mut foreign = recording;
+        foreign.magic = *b"MGSN";
+        assert!(ReplayRecording::from_bytes(&foreign.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn frame_at_tick_picks_latest_frame_not_after_tick() {
+        let recording = ReplayRecording::new(1, vec![frame(10), frame(12), frame(15)]);
+        assert_eq!(recording.frame_at_tick(9), None);
+        assert_eq!(recording.frame_at_tick(10), Some(0));
+        assert_eq!(recording.frame_at_tick(14), Some(1));
+        assert_eq!(recording.frame_at_tick(99), Some(2));
+    }
+}
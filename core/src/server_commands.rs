@@ -1,7 +1,16 @@
+use crate::char_sheet::{CHAR_SHEET_PACKET_LEN, CharSheet};
+use crate::dialogue::{DialogueLine, decode_dialogue_packet};
 use crate::quest_defs::{MAX_QUEST_CATALOG, QuestCatalogEntry};
+use crate::skill_trainers::{
+    MAX_TRAINER_OFFERS, OfferStatus, TRAINER_OFFER_ENTRY_LEN, TRAINER_OFFERS_PACKET_LEN,
+    TrainerOfferEntry,
+};
 use crate::string_operations::c_string_to_str;
 
 /// Opcode values for incoming server commands.
+///
+/// New variants get a `Since: <version>` doc line; it feeds the *Since*
+/// column of the generated protocol catalog (`cargo xtask gen-protocol-docs`).
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum ServerCommandType {
@@ -80,6 +89,197 @@ pub enum ServerCommandType {
     /// (u16 LE) + tint_r (1) + tint_g (1) + tint_b (1) + tint_a (1) + flags
     /// (1) = **10 bytes total**. See [`crate::weather::WeatherKind`].
     SetWeather = 76,
+    /// Snapshot of the character's earned and selected titles.
+    ///
+    /// Wire format: opcode (1) + earned mask (u32 LE) + selected title id
+    /// (1) = **6 bytes total**. See [`crate::titles`].
+    SetCharTitles = 77,
+    /// One chunk of a styled chat message.
+    ///
+    /// Wire format: opcode (1) + style (1) + text
+    /// ([`crate::chat::STYLED_LOG_CHUNK_LEN`] bytes, NUL padded) = **16 bytes
+    /// total**. Chunks are concatenated until a newline, like `Log0..3`.
+    /// See [`crate::chat::ChatStyle`].
+    LogStyled = 78,
+    /// Interaction menu for a right-clicked NPC.
+    ///
+    /// Wire format: opcode (1) + character number (u16 LE) + option mask
+    /// (1) = **4 bytes total**. See [`crate::npc_menu`].
+    NpcMenu = 79,
+    /// Skills offered by a trainer NPC.
+    ///
+    /// Wire format: opcode (1) + trainer character number (u16 LE) +
+    /// count (1) + count × (skill (1) + price (u32 LE) + status (1)),
+    /// zero-padded to [`TRAINER_OFFERS_PACKET_LEN`] = **40 bytes total**.
+    /// See [`crate::skill_trainers`].
+    TrainerOffers = 80,
+    /// Full character sheet, replacing all `SetChar*` state on the client.
+    ///
+    /// Wire format: opcode (1) + [`CharSheet`] fields, fixed
+    /// [`CHAR_SHEET_PACKET_LEN`] bytes. Only sent to clients advertising
+    /// [`crate::constants::CLIENT_CAP_CHAR_SHEET`]. See [`crate::char_sheet`].
+    SetCharSheet = 81,
+    /// Checksum of the inventory, equipment and spells the server believes
+    /// the client holds.
+    ///
+    /// Wire format: opcode (1) + checksum (u32 LE) = **5 bytes total**.
+    /// Only sent to clients advertising
+    /// [`crate::constants::CLIENT_CAP_CHAR_SHEET`]; see
+    /// [`crate::char_sheet::client_checksum`].
+    CharChecksum = 82,
+    /// Authoritative server clock, used by the client to drive countdowns.
+    ///
+    /// Wire format: opcode (1) + server tick (u32 LE) + server wall-clock in
+    /// Unix milliseconds (u64 LE) = **13 bytes total**. Sent right after
+    /// `CmdClientCaps` and then periodically, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_TIME_SYNC`].
+    TimeSync = 83,
+    /// View radius the server fills the map window to.
+    ///
+    /// Wire format: opcode (1) + radius (1) = **2 bytes total**. Sent right
+    /// after `CmdClientCaps`, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_WIDE_VIEW`]. See [`crate::view`].
+    SetView = 84,
+    /// Position of one minimap point of interest.
+    ///
+    /// Wire format: opcode (1) + kind (1) + x (2) + y (2) = **6 bytes
+    /// total**. `(0, 0)` clears the marker. Sent only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_MAP_MARKERS`]. See
+    /// [`crate::map_markers`].
+    MapMarker = 85,
+    /// Start (or early end) of a skill's cast or cooldown timer.
+    ///
+    /// Wire format: opcode (1) + kind (1) + skill (1) + remaining ticks (2)
+    /// + total ticks (2) = **7 bytes total**. `remaining == 0` clears the
+    /// timer. Sent only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_SKILL_TIMERS`]. See
+    /// [`crate::skill_timers`].
+    SkillTimer = 86,
+    /// Highest `CmdSeq` sequence number the server has applied.
+    ///
+    /// Wire format: opcode (1) + sequence number (u32 LE) = **5 bytes
+    /// total**. Sent at most once per tick, after a tagged command was
+    /// applied or dropped as a duplicate, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_COMMAND_SEQ`].
+    ///
+    /// Since: 1.5.0
+    CommandAck = 87,
+    /// One line of keyed NPC dialogue.
+    ///
+    /// Wire format: opcode (1) + total length (u16 LE) + speaker name + key
+    /// + parameter count (1) + count × (name + value), each string a length
+    /// byte followed by UTF-8. Sent instead of chat text only to clients
+    /// advertising [`crate::constants::CLIENT_CAP_DIALOGUE_KEYS`]. See
+    /// [`crate::dialogue`].
+    ///
+    /// Since: 1.5.0
+    Dialogue = 88,
+    /// Sound effect with the source position relative to the listener.
+    ///
+    /// Wire format: opcode (1) + sound number (u32 LE) + tile offset x
+    /// (i16 LE) + tile offset y (i16 LE) = **9 bytes total**. The client
+    /// derives volume and stereo pan from the offset itself. Sent instead of
+    /// `PlaySound` for area sounds, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_POSITIONAL_SOUND`].
+    ///
+    /// Since: 1.5.0
+    PlaySoundAt = 89,
+    /// Short-lived cosmetic decal on a map tile.
+    ///
+    /// Wire format: opcode (1) + tile x (u16 LE) + tile y (u16 LE) + sprite
+    /// (u32 LE) + TTL in ticks (u16 LE) + kind (u8, see
+    /// [`crate::decals::DecalKind`]) = **12 bytes total**. The client fades
+    /// the sprite out over the TTL. Only sent to clients advertising
+    /// [`crate::constants::CLIENT_CAP_MAP_DECALS`].
+    ///
+    /// Since: 1.5.0
+    MapDecal = 90,
+    /// Name of the item in a backpack slot.
+    ///
+    /// Wire format: opcode (1) + backpack slot (u8, 0..40) + name (40 bytes,
+    /// NUL-padded, empty for an empty slot) = **42 bytes total**. Sent when
+    /// the item in a slot changes, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_ITEM_NAMES`].
+    ///
+    /// Since: 1.5.0
+    ItemName = 91,
+    /// The receiver's guild, rank and guild message of the day.
+    ///
+    /// Wire format: opcode (1) + total length (u16 LE) + rank (u8, see
+    /// [`crate::guilds::GuildRank`]) + guild name (16 bytes, NUL-padded,
+    /// empty when not in a guild) + MOTD (up to 80 bytes of text). Sent at
+    /// login and whenever any of it changes, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_GUILDS`]. Clears the client's roster.
+    ///
+    /// Since: 1.5.0
+    GuildInfo = 92,
+    /// One entry of the receiver's guild roster.
+    ///
+    /// Wire format: opcode (1) + op (u8, see
+    /// [`crate::guilds::GuildRosterOp`]) + rank (u8) + online (u8) + member
+    /// name (16 bytes, NUL-padded) = **20 bytes total**. Only sent to clients
+    /// advertising [`crate::constants::CLIENT_CAP_GUILDS`].
+    ///
+    /// Since: 1.5.0
+    GuildMember = 93,
+    /// One letter of the receiver's mailbox.
+    ///
+    /// Wire format: opcode (1) + total length (u16 LE) + op (u8, see
+    /// [`crate::mail::MailOp`]) + id (u32 LE) + sent time (u32 LE, Unix
+    /// seconds) + unread (u8) + sender name (16 bytes, NUL-padded) +
+    /// attachment name (40 bytes, NUL-padded) + text (up to 200 bytes). Only
+    /// sent to clients advertising [`crate::constants::CLIENT_CAP_MAIL`].
+    ///
+    /// Since: 1.5.0
+    MailEntry = 94,
+    /// Which page of a broker's consignment listings follows.
+    ///
+    /// Wire format: opcode (1) + broker (u16 LE) + page (u16 LE, zero-based)
+    /// + pages (u16 LE) + total (u16 LE) + search text (20 bytes,
+    /// NUL-padded) = **29 bytes total**. Sent right before the `SV_LOOK1`..
+    /// `SV_LOOK6` packets of a broker's shop window, only to clients
+    /// advertising [`crate::constants::CLIENT_CAP_CONSIGNMENT`].
+    ///
+    /// Since: 1.5.0
+    ConsignmentPage = 95,
+    /// Why the server is about to close the connection.
+    ///
+    /// Wire format: opcode (1) + total length (u16 LE) + reason (u8, a
+    /// [`crate::logout_reasons::LogoutReason`]) + text (UTF-8, up to 200
+    /// bytes). Sent right before `SV_EXIT`, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_DISCONNECT_REASON`].
+    ///
+    /// Since: 1.5.0
+    Disconnect = 96,
+    /// One entry of the receiver's friends list.
+    ///
+    /// Wire format: opcode (1) + op (u8, see [`crate::friends::FriendOp`]) +
+    /// online (u8) + friend name (16 bytes, NUL-padded) = **19 bytes
+    /// total**. Sent at login and whenever a friend logs in or out, only to
+    /// clients advertising [`crate::constants::CLIENT_CAP_FRIENDS`].
+    ///
+    /// Since: 1.5.0
+    FriendEntry = 97,
+    /// Whether the receiver is observing.
+    ///
+    /// Wire format: opcode (1) + active (1) = **2 bytes total**. Sent when
+    /// `#observe` starts or ends, and with `CmdClientCaps` while observing,
+    /// only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_OBSERVER`].
+    ///
+    /// Since: 1.5.0
+    Observer = 98,
+    /// One hit dealt or taken by the receiver's character.
+    ///
+    /// Wire format: opcode (1) + kind (u8, see
+    /// [`crate::combat_log::DamageKind`]) + skill (u8) + flags (u8) + raw,
+    /// resisted, mitigated and dealt damage (4 × u32 LE, thousandths of a
+    /// hit point) + attacker and target names (2 × 16 bytes, NUL-padded) =
+    /// **52 bytes total**. Sent for every hit, only to clients advertising
+    /// [`crate::constants::CLIENT_CAP_COMBAT_LOG`].
+    ///
+    /// Since: 1.5.0
+    CombatEvent = 99,
     /// One-shot snapshot of the entire static quest catalog.
     ///
     /// Wire format: opcode (1) + count (1) + count × entry
@@ -95,6 +295,17 @@ pub enum ServerCommandType {
     /// (`opcode + mode + idx (u8) + count (i16 LE)` =
     /// `QUEST_COMPLETION_DELTA_LEN` bytes).
     SetQuestCompletion = 101,
+    /// Snapshot of an item linked in chat.
+    ///
+    /// Wire format: opcode (1) + total length (u16 LE) + link id (u32 LE) +
+    /// minimum rank (u8) + stat count (u8) + item name (40 bytes,
+    /// NUL-padded) + count × stat (code u8, bonus i16 LE, requirement i16
+    /// LE; see [`crate::item_link::ItemLinkStat`]) + description (up to 200
+    /// bytes). Sent right before each chat line that links the item, only
+    /// to clients advertising [`crate::constants::CLIENT_CAP_ITEM_LINKS`].
+    ///
+    /// Since: 1.5.0
+    ItemLink = 102,
     SetMap = 128,
 }
 
@@ -215,6 +426,52 @@ impl ServerCommandType {
             ServerCommandType::SetCharDir => 2,
             ServerCommandType::SetCharTalents => 26,
             ServerCommandType::SetWeather => 10,
+            ServerCommandType::SetCharTitles => 6,
+            ServerCommandType::LogStyled => 16,
+            ServerCommandType::NpcMenu => 4,
+            ServerCommandType::TrainerOffers => TRAINER_OFFERS_PACKET_LEN,
+            ServerCommandType::SetCharSheet => CHAR_SHEET_PACKET_LEN,
+            ServerCommandType::CharChecksum => 5,
+            ServerCommandType::TimeSync => 13,
+            ServerCommandType::SetView => 2,
+            ServerCommandType::MapMarker => 6,
+            ServerCommandType::SkillTimer => 7,
+            ServerCommandType::CommandAck => 5,
+            ServerCommandType::Dialogue => {
+                if bytes.len() < 3 {
+                    return Err("SV_DIALOGUE truncated (need length)".to_owned());
+                }
+                usize::from(u16::from_le_bytes([bytes[1], bytes[2]])).max(3)
+            }
+            ServerCommandType::PlaySoundAt => 9,
+            ServerCommandType::MapDecal => 12,
+            ServerCommandType::ItemName => 42,
+            ServerCommandType::GuildInfo => {
+                if bytes.len() < 3 {
+                    return Err("SV_GUILDINFO truncated (need length)".to_owned());
+                }
+                usize::from(u16::from_le_bytes([bytes[1], bytes[2]]))
+                    .max(crate::guilds::GUILD_INFO_HEADER_LEN)
+            }
+            ServerCommandType::GuildMember => crate::guilds::GUILD_MEMBER_PACKET_LEN,
+            ServerCommandType::MailEntry => {
+                if bytes.len() < 3 {
+                    return Err("SV_MAILENTRY truncated (need length)".to_owned());
+                }
+                usize::from(u16::from_le_bytes([bytes[1], bytes[2]]))
+                    .max(crate::mail::MAIL_ENTRY_HEADER_LEN)
+            }
+            ServerCommandType::ConsignmentPage => crate::consignment::CONSIGN_PAGE_PACKET_LEN,
+            ServerCommandType::Disconnect => {
+                if bytes.len() < 3 {
+                    return Err("SV_DISCONNECT truncated (need length)".to_owned());
+                }
+                usize::from(u16::from_le_bytes([bytes[1], bytes[2]]))
+                    .max(crate::disconnect::DISCONNECT_HEADER_LEN)
+            }
+            ServerCommandType::FriendEntry => crate::friends::FRIEND_ENTRY_PACKET_LEN,
+            ServerCommandType::Observer => 2,
+            ServerCommandType::CombatEvent => crate::combat_log::COMBAT_EVENT_PACKET_LEN,
             ServerCommandType::SetQuestCatalog => QUEST_CATALOG_PACKET_LEN,
             ServerCommandType::SetQuestCompletion => {
                 if bytes.len() < 2 {
@@ -230,6 +487,13 @@ impl ServerCommandType {
                     }
                 }
             }
+            ServerCommandType::ItemLink => {
+                if bytes.len() < 3 {
+                    return Err("SV_ITEMLINK truncated (need length)".to_owned());
+                }
+                usize::from(u16::from_le_bytes([bytes[1], bytes[2]]))
+                    .max(crate::item_link::ITEM_LINK_HEADER_LEN)
+            }
             ServerCommandType::SetCharPts => 13,
             ServerCommandType::SetCharGold => 13,
             ServerCommandType::SetCharItem => 9,
@@ -340,8 +604,32 @@ impl From<u8> for ServerCommandType {
             74 => ServerCommandType::Pong,
             75 => ServerCommandType::SetCharTalents,
             76 => ServerCommandType::SetWeather,
+            77 => ServerCommandType::SetCharTitles,
+            78 => ServerCommandType::LogStyled,
+            79 => ServerCommandType::NpcMenu,
+            80 => ServerCommandType::TrainerOffers,
+            81 => ServerCommandType::SetCharSheet,
+            82 => ServerCommandType::CharChecksum,
+            83 => ServerCommandType::TimeSync,
+            84 => ServerCommandType::SetView,
+            85 => ServerCommandType::MapMarker,
+            86 => ServerCommandType::SkillTimer,
+            87 => ServerCommandType::CommandAck,
+            88 => ServerCommandType::Dialogue,
+            89 => ServerCommandType::PlaySoundAt,
+            90 => ServerCommandType::MapDecal,
+            91 => ServerCommandType::ItemName,
+            92 => ServerCommandType::GuildInfo,
+            93 => ServerCommandType::GuildMember,
+            94 => ServerCommandType::MailEntry,
+            95 => ServerCommandType::ConsignmentPage,
+            96 => ServerCommandType::Disconnect,
+            97 => ServerCommandType::FriendEntry,
+            98 => ServerCommandType::Observer,
+            99 => ServerCommandType::CombatEvent,
             100 => ServerCommandType::SetQuestCatalog,
             101 => ServerCommandType::SetQuestCompletion,
+            102 => ServerCommandType::ItemLink,
             128 => ServerCommandType::SetMap,
             _ => {
                 log::error!("Unknown server command opcode: {value}");
@@ -534,6 +822,9 @@ pub enum ServerCommandData {
         points: u32,
         hp: u32,
         worn10: u16,
+        /// Profile [`NameColor`](crate::profile::NameColor) byte of the
+        /// looked-at character (byte 15; `0` from older servers).
+        name_color: u8,
     },
     Look3 {
         end: u16,
@@ -543,6 +834,9 @@ pub enum ServerCommandData {
         id: u16,
         mana: u16,
         a_mana: u16,
+        /// Selected title id of the looked-at character (byte 15; `0` =
+        /// none). See [`crate::titles`].
+        title: u8,
     },
     Look4 {
         worn1: u16,
@@ -587,6 +881,128 @@ pub enum ServerCommandData {
         tint: [u8; 4],
         flags: u8,
     },
+    /// Earned-title bitmask and selected title id for the player's own
+    /// character.
+    SetCharTitles {
+        earned: u32,
+        selected: u8,
+    },
+    /// One chunk of a styled chat message; decode `style` with
+    /// [`crate::chat::ChatStyle::from_byte`].
+    LogStyled {
+        style: u8,
+        chunk: String,
+    },
+    /// Interaction menu for NPC `target`; decode `options` with
+    /// [`crate::npc_menu::options_in_mask`].
+    NpcMenu {
+        target: u16,
+        options: u8,
+    },
+    /// Skills offered by trainer `target` (see [`crate::skill_trainers`]).
+    TrainerOffers {
+        target: u16,
+        offers: Vec<TrainerOfferEntry>,
+    },
+    /// Full character sheet (see [`crate::char_sheet`]).
+    SetCharSheet(Box<CharSheet>),
+    /// Server-side state checksum (see [`crate::char_sheet`]).
+    CharChecksum {
+        checksum: u32,
+    },
+    /// Server tick counter and wall-clock at the time of sending.
+    TimeSync {
+        tick: u32,
+        unix_ms: u64,
+    },
+    /// Largest view radius the client may zoom out to (see [`crate::view`]).
+    SetView {
+        radius: u8,
+    },
+    /// Minimap point of interest; `kind` is a
+    /// [`crate::map_markers::MapMarkerKind`] wire value.
+    MapMarker {
+        kind: u8,
+        x: u16,
+        y: u16,
+    },
+    /// Cast or cooldown timer of one skill; `kind` is a
+    /// [`crate::skill_timers::SkillTimerKind`] wire value.
+    SkillTimer {
+        kind: u8,
+        skill: u8,
+        remaining: u16,
+        total: u16,
+    },
+    /// Highest applied `CmdSeq` sequence number.
+    CommandAck {
+        seq: u32,
+    },
+    /// Keyed NPC dialogue line and the name of its speaker.
+    Dialogue {
+        speaker: String,
+        line: DialogueLine,
+    },
+    /// Sound effect `nr` played `dx`/`dy` tiles away from the listener.
+    PlaySoundAt {
+        nr: u32,
+        dx: i16,
+        dy: i16,
+    },
+    /// Decal `sprite` on tile `x`/`y`, fading out over `ttl` ticks.
+    MapDecal {
+        x: u16,
+        y: u16,
+        sprite: u32,
+        ttl: u16,
+        kind: u8,
+    },
+    /// Backpack `slot` now holds an item called `name` (empty when the
+    /// slot was emptied).
+    ItemName {
+        slot: u8,
+        name: String,
+    },
+    /// The receiver's guild (`name` empty when in none), rank byte and MOTD.
+    GuildInfo {
+        rank: u8,
+        name: String,
+        motd: String,
+    },
+    /// Roster entry `name`; `op` is a [`crate::guilds::GuildRosterOp`] wire
+    /// value.
+    GuildMember {
+        op: u8,
+        rank: u8,
+        online: bool,
+        name: String,
+    },
+    /// Mailbox letter; `op` is a [`crate::mail::MailOp`] wire value.
+    MailEntry {
+        op: u8,
+        entry: crate::mail::MailEntry,
+    },
+    /// Page of broker listings the following shop window shows.
+    ConsignmentPage(crate::consignment::ConsignmentPage),
+    /// Reason (a [`crate::logout_reasons::LogoutReason`] wire value) and
+    /// text for the `SV_EXIT` that follows.
+    Disconnect {
+        reason: u8,
+        text: String,
+    },
+    /// Friends list entry `name`; `op` is a [`crate::friends::FriendOp`]
+    /// wire value.
+    FriendEntry {
+        op: u8,
+        online: bool,
+        name: String,
+    },
+    /// Observer mode started (`true`) or ended.
+    Observer {
+        active: bool,
+    },
+    /// One hit dealt or taken by the receiver's character.
+    CombatEvent(crate::combat_log::CombatEvent),
     /// One-shot snapshot of the static quest catalog (sent once per
     /// session at login).
     SetQuestCatalog {
@@ -599,6 +1015,8 @@ pub enum ServerCommandData {
     /// Either a `Full` snapshot of all 49 counters (sent at login) or a
     /// `Delta` update for a single catalog index (sent on turn-in).
     SetQuestCompletion(QuestCompletionPayload),
+    /// Snapshot of an item linked in the chat line that follows.
+    ItemLink(crate::item_link::ItemLink),
     Load {
         load: u32,
     },
@@ -662,6 +1080,12 @@ fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
     ))
 }
 
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
 fn read_i32(bytes: &[u8], offset: usize) -> Option<i32> {
     Some(i32::from_le_bytes(
         bytes.get(offset..offset + 4)?.try_into().ok()?,
@@ -925,6 +1349,7 @@ fn from_bytes(bytes: &[u8]) -> Option<(ServerCommandType, ServerCommandData)> {
                 points: read_u32(bytes, 5)?,
                 hp: read_u32(bytes, 9)?,
                 worn10: read_u16(bytes, 13)?,
+                name_color: bytes.get(15).copied().unwrap_or(0),
             },
         )),
         40 => Some((
@@ -937,6 +1362,7 @@ fn from_bytes(bytes: &[u8]) -> Option<(ServerCommandType, ServerCommandData)> {
                 id: read_u16(bytes, 9)?,
                 mana: read_u16(bytes, 11)?,
                 a_mana: read_u16(bytes, 13)?,
+                title: bytes.get(15).copied().unwrap_or(0),
             },
         )),
         41 => Some((
@@ -1204,6 +1630,182 @@ fn from_bytes(bytes: &[u8]) -> Option<(ServerCommandType, ServerCommandData)> {
                 flags: *bytes.get(9)?,
             },
         )),
+        77 => Some((
+            ServerCommandType::SetCharTitles,
+            ServerCommandData::SetCharTitles {
+                earned: read_u32(bytes, 1)?,
+                selected: *bytes.get(5)?,
+            },
+        )),
+        78 => Some((
+            ServerCommandType::LogStyled,
+            ServerCommandData::LogStyled {
+                style: *bytes.get(1)?,
+                chunk: c_string_to_str(bytes.get(2..16)?).to_owned(),
+            },
+        )),
+        79 => Some((
+            ServerCommandType::NpcMenu,
+            ServerCommandData::NpcMenu {
+                target: read_u16(bytes, 1)?,
+                options: *bytes.get(3)?,
+            },
+        )),
+        80 => {
+            let count = (*bytes.get(3)?).min(MAX_TRAINER_OFFERS as u8) as usize;
+            let mut offers = Vec::with_capacity(count);
+            for i in 0..count {
+                let off = 4 + i * TRAINER_OFFER_ENTRY_LEN;
+                offers.push(TrainerOfferEntry {
+                    skill: *bytes.get(off)?,
+                    price: read_u32(bytes, off + 1)?,
+                    status: OfferStatus::from_u8(*bytes.get(off + 5)?),
+                });
+            }
+            Some((
+                ServerCommandType::TrainerOffers,
+                ServerCommandData::TrainerOffers {
+                    target: read_u16(bytes, 1)?,
+                    offers,
+                },
+            ))
+        }
+        81 => Some((
+            ServerCommandType::SetCharSheet,
+            ServerCommandData::SetCharSheet(Box::new(CharSheet::from_bytes(bytes)?)),
+        )),
+        82 => Some((
+            ServerCommandType::CharChecksum,
+            ServerCommandData::CharChecksum {
+                checksum: read_u32(bytes, 1)?,
+            },
+        )),
+        83 => Some((
+            ServerCommandType::TimeSync,
+            ServerCommandData::TimeSync {
+                tick: read_u32(bytes, 1)?,
+                unix_ms: read_u64(bytes, 5)?,
+            },
+        )),
+        84 => Some((
+            ServerCommandType::SetView,
+            ServerCommandData::SetView {
+                radius: *bytes.get(1)?,
+            },
+        )),
+        85 => Some((
+            ServerCommandType::MapMarker,
+            ServerCommandData::MapMarker {
+                kind: *bytes.get(1)?,
+                x: read_u16(bytes, 2)?,
+                y: read_u16(bytes, 4)?,
+            },
+        )),
+        86 => Some((
+            ServerCommandType::SkillTimer,
+            ServerCommandData::SkillTimer {
+                kind: *bytes.get(1)?,
+                skill: *bytes.get(2)?,
+                remaining: read_u16(bytes, 3)?,
+                total: read_u16(bytes, 5)?,
+            },
+        )),
+        87 => Some((
+            ServerCommandType::CommandAck,
+            ServerCommandData::CommandAck {
+                seq: read_u32(bytes, 1)?,
+            },
+        )),
+        88 => {
+            let (speaker, line) = decode_dialogue_packet(bytes)?;
+            Some((
+                ServerCommandType::Dialogue,
+                ServerCommandData::Dialogue { speaker, line },
+            ))
+        }
+        89 => Some((
+            ServerCommandType::PlaySoundAt,
+            ServerCommandData::PlaySoundAt {
+                nr: read_u32(bytes, 1)?,
+                dx: read_i16(bytes, 5)?,
+                dy: read_i16(bytes, 7)?,
+            },
+        )),
+        90 => Some((
+            ServerCommandType::MapDecal,
+            ServerCommandData::MapDecal {
+                x: read_u16(bytes, 1)?,
+                y: read_u16(bytes, 3)?,
+                sprite: read_u32(bytes, 5)?,
+                ttl: read_u16(bytes, 9)?,
+                kind: *bytes.get(11)?,
+            },
+        )),
+        91 => Some((
+            ServerCommandType::ItemName,
+            ServerCommandData::ItemName {
+                slot: *bytes.get(1)?,
+                name: c_string_to_str(bytes.get(2..42)?).to_owned(),
+            },
+        )),
+        92 => {
+            let total = usize::from(read_u16(bytes, 1)?);
+            let header = crate::guilds::GUILD_INFO_HEADER_LEN;
+            let motd = bytes.get(header..total.max(header))?;
+            Some((
+                ServerCommandType::GuildInfo,
+                ServerCommandData::GuildInfo {
+                    rank: *bytes.get(3)?,
+                    name: c_string_to_str(bytes.get(4..header)?).to_owned(),
+                    motd: String::from_utf8_lossy(motd).into_owned(),
+                },
+            ))
+        }
+        93 => Some((
+            ServerCommandType::GuildMember,
+            ServerCommandData::GuildMember {
+                op: *bytes.get(1)?,
+                rank: *bytes.get(2)?,
+                online: *bytes.get(3)? != 0,
+                name: c_string_to_str(bytes.get(4..crate::guilds::GUILD_MEMBER_PACKET_LEN)?)
+                    .to_owned(),
+            },
+        )),
+        94 => {
+            let (op, entry) = crate::mail::decode_mail_entry(bytes)?;
+            Some((
+                ServerCommandType::MailEntry,
+                ServerCommandData::MailEntry { op, entry },
+            ))
+        }
+        95 => Some((
+            ServerCommandType::ConsignmentPage,
+            ServerCommandData::ConsignmentPage(crate::consignment::decode_consignment_page(bytes)?),
+        )),
+        96 => {
+            let (reason, text) = crate::disconnect::decode_disconnect(bytes)?;
+            Some((
+                ServerCommandType::Disconnect,
+                ServerCommandData::Disconnect { reason, text },
+            ))
+        }
+        97 => {
+            let (op, online, name) = crate::friends::decode_friend_entry(bytes)?;
+            Some((
+                ServerCommandType::FriendEntry,
+                ServerCommandData::FriendEntry { op, online, name },
+            ))
+        }
+        98 => Some((
+            ServerCommandType::Observer,
+            ServerCommandData::Observer {
+                active: *bytes.get(1)? != 0,
+            },
+        )),
+        99 => Some((
+            ServerCommandType::CombatEvent,
+            ServerCommandData::CombatEvent(crate::combat_log::decode_combat_event(bytes)?),
+        )),
         100 => {
             let count = (*bytes.get(1)?).min(MAX_QUEST_CATALOG as u8) as usize;
             let mut entries = Vec::with_capacity(count);
@@ -1267,6 +1869,10 @@ fn from_bytes(bytes: &[u8]) -> Option<(ServerCommandType, ServerCommandData)> {
                 _ => None,
             }
         }
+        102 => Some((
+            ServerCommandType::ItemLink,
+            ServerCommandData::ItemLink(crate::item_link::decode_item_link(bytes)?),
+        )),
         _ => None,
     }
 }
@@ -1514,6 +2120,593 @@ mod tests {
         assert_eq!(ServerCommandType::from(76), ServerCommandType::SetWeather);
     }
 
+    // -- SV_SETCHARTITLES (opcode 77) --
+
+    #[test]
+    fn parse_set_char_titles() {
+        let pkt: [u8; 6] = [77, 0x02, 0x00, 0x01, 0x00, 16];
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            6
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::SetCharTitles);
+        match cmd.structured_data {
+            ServerCommandData::SetCharTitles { earned, selected } => {
+                assert_eq!(earned, 0x0001_0002);
+                assert_eq!(selected, 16);
+            }
+            _ => panic!("Expected SetCharTitles variant"),
+        }
+    }
+
+    // -- SV_LOGSTYLED (opcode 78) --
+
+    #[test]
+    fn parse_log_styled() {
+        let mut pkt = [0u8; 16];
+        pkt[0] = 78;
+        pkt[1] = 0x44;
+        pkt[2..2 + 14].copy_from_slice(b"Hello, world!\n");
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            16
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::LogStyled);
+        match cmd.structured_data {
+            ServerCommandData::LogStyled { style, chunk } => {
+                assert_eq!(style, 0x44);
+                assert_eq!(chunk, "Hello, world!\n");
+            }
+            _ => panic!("Expected LogStyled variant"),
+        }
+    }
+
+    // -- SV_NPCMENU (opcode 79) --
+
+    #[test]
+    fn parse_npc_menu() {
+        let pkt = [79u8, 0x34, 0x12, 0b1_0011];
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            4
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::NpcMenu);
+        match cmd.structured_data {
+            ServerCommandData::NpcMenu { target, options } => {
+                assert_eq!(target, 0x1234);
+                assert_eq!(options, 0b1_0011);
+            }
+            _ => panic!("Expected NpcMenu variant"),
+        }
+    }
+
+    // -- SV_TRAINEROFFERS (opcode 80) --
+
+    #[test]
+    fn parse_trainer_offers() {
+        let mut pkt = [0u8; TRAINER_OFFERS_PACKET_LEN];
+        pkt[0] = 80;
+        pkt[1..3].copy_from_slice(&300u16.to_le_bytes());
+        pkt[3] = 2;
+        pkt[4] = 29;
+        pkt[5..9].copy_from_slice(&2_500u32.to_le_bytes());
+        pkt[9] = OfferStatus::Known as u8;
+        pkt[10] = 30;
+        pkt[11..15].copy_from_slice(&10_000u32.to_le_bytes());
+        pkt[15] = OfferStatus::Available as u8;
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            40
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::TrainerOffers);
+        match cmd.structured_data {
+            ServerCommandData::TrainerOffers { target, offers } => {
+                assert_eq!(target, 300);
+                assert_eq!(
+                    offers,
+                    vec![
+                        TrainerOfferEntry {
+                            skill: 29,
+                            price: 2_500,
+                            status: OfferStatus::Known,
+                        },
+                        TrainerOfferEntry {
+                            skill: 30,
+                            price: 10_000,
+                            status: OfferStatus::Available,
+                        },
+                    ]
+                );
+            }
+            _ => panic!("Expected TrainerOffers variant"),
+        }
+    }
+
+    // -- SV_SETCHARSHEET (opcode 81) --
+
+    #[test]
+    fn parse_char_sheet() {
+        let sheet = CharSheet {
+            gold: 4_200,
+            a_mana: 17,
+            ..CharSheet::default()
+        };
+        let pkt = sheet.to_bytes(ServerCommandType::SetCharSheet as u8);
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            CHAR_SHEET_PACKET_LEN
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::SetCharSheet);
+        match cmd.structured_data {
+            ServerCommandData::SetCharSheet(parsed) => assert_eq!(*parsed, sheet),
+            _ => panic!("Expected SetCharSheet variant"),
+        }
+    }
+
+    // -- SV_CHARCHECKSUM (opcode 82) --
+
+    #[test]
+    fn parse_char_checksum() {
+        let mut pkt = vec![ServerCommandType::CharChecksum as u8];
+        pkt.extend_from_slice(&0xDEAD_BEEFu32.to_le_bytes());
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            5
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::CharChecksum);
+        match cmd.structured_data {
+            ServerCommandData::CharChecksum { checksum } => assert_eq!(checksum, 0xDEAD_BEEF),
+            _ => panic!("Expected CharChecksum variant"),
+        }
+    }
+
+    // -- SV_TIMESYNC (opcode 83) --
+
+    #[test]
+    fn parse_time_sync() {
+        let mut pkt = vec![ServerCommandType::TimeSync as u8];
+        pkt.extend_from_slice(&123_456u32.to_le_bytes());
+        pkt.extend_from_slice(&1_760_000_000_123u64.to_le_bytes());
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            13
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::TimeSync);
+        match cmd.structured_data {
+            ServerCommandData::TimeSync { tick, unix_ms } => {
+                assert_eq!(tick, 123_456);
+                assert_eq!(unix_ms, 1_760_000_000_123);
+            }
+            _ => panic!("Expected TimeSync variant"),
+        }
+    }
+
+    // -- SV_COMMANDACK (opcode 87) --
+
+    #[test]
+    fn parse_command_ack() {
+        let mut pkt = vec![ServerCommandType::CommandAck as u8];
+        pkt.extend_from_slice(&77u32.to_le_bytes());
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            5
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::CommandAck { seq } => assert_eq!(seq, 77),
+            _ => panic!("Expected CommandAck variant"),
+        }
+    }
+
+    #[test]
+    fn parse_play_sound_at() {
+        let mut pkt = vec![ServerCommandType::PlaySoundAt as u8];
+        pkt.extend_from_slice(&31u32.to_le_bytes());
+        pkt.extend_from_slice(&(-3i16).to_le_bytes());
+        pkt.extend_from_slice(&5i16.to_le_bytes());
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            9
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::PlaySoundAt { nr, dx, dy } => {
+                assert_eq!((nr, dx, dy), (31, -3, 5));
+            }
+            _ => panic!("Expected PlaySoundAt variant"),
+        }
+    }
+
+    #[test]
+    fn parse_map_decal() {
+        let mut pkt = vec![ServerCommandType::MapDecal as u8];
+        pkt.extend_from_slice(&512u16.to_le_bytes());
+        pkt.extend_from_slice(&498u16.to_le_bytes());
+        pkt.extend_from_slice(&1082u32.to_le_bytes());
+        pkt.extend_from_slice(&720u16.to_le_bytes());
+        pkt.push(1);
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            12
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::MapDecal {
+                x,
+                y,
+                sprite,
+                ttl,
+                kind,
+            } => {
+                assert_eq!((x, y, sprite, ttl, kind), (512, 498, 1082, 720, 1));
+            }
+            _ => panic!("Expected MapDecal variant"),
+        }
+    }
+
+    #[test]
+    fn parse_item_name() {
+        let mut pkt = vec![ServerCommandType::ItemName as u8, 7];
+        let mut name = [0u8; 40];
+        name[..11].copy_from_slice(b"Steel Sword");
+        pkt.extend_from_slice(&name);
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            42
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::ItemName { slot, name } => {
+                assert_eq!(slot, 7);
+                assert_eq!(name, "Steel Sword");
+            }
+            _ => panic!("Expected ItemName variant"),
+        }
+    }
+
+    #[test]
+    fn parse_guild_info_and_member() {
+        use crate::guilds::{GuildRank, GuildRosterOp, encode_guild_info, encode_guild_member};
+
+        let pkt = encode_guild_info(
+            ServerCommandType::GuildInfo as u8,
+            GuildRank::Leader,
+            "Iron Wolves",
+            "Raid at dusk",
+        );
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::GuildInfo { rank, name, motd } => {
+                assert_eq!(rank, GuildRank::Leader.as_u8());
+                assert_eq!(name, "Iron Wolves");
+                assert_eq!(motd, "Raid at dusk");
+            }
+            _ => panic!("Expected GuildInfo variant"),
+        }
+
+        let pkt = encode_guild_member(
+            ServerCommandType::GuildMember as u8,
+            GuildRosterOp::Remove,
+            GuildRank::Officer,
+            true,
+            "Ishtar",
+        );
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::GuildMember {
+                op,
+                rank,
+                online,
+                name,
+            } => {
+                assert_eq!(op, GuildRosterOp::Remove as u8);
+                assert_eq!(rank, GuildRank::Officer.as_u8());
+                assert!(online);
+                assert_eq!(name, "Ishtar");
+            }
+            _ => panic!("Expected GuildMember variant"),
+        }
+    }
+
+    #[test]
+    fn parse_mail_entry() {
+        use crate::mail::{MailEntry, MailOp, encode_mail_entry};
+
+        let entry = MailEntry {
+            id: 7,
+            sent_unix: 1_790_000_000,
+            unread: false,
+            from: "Ishtar".to_owned(),
+            attachment: String::new(),
+            body: "Meet me in Aston.".to_owned(),
+        };
+        let pkt = encode_mail_entry(ServerCommandType::MailEntry as u8, MailOp::Set, &entry);
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::MailEntry { op, entry: parsed } => {
+                assert_eq!(op, MailOp::Set as u8);
+                assert_eq!(parsed, entry);
+            }
+            _ => panic!("Expected MailEntry variant"),
+        }
+    }
+
+    #[test]
+    fn parse_consignment_page() {
+        use crate::consignment::{ConsignmentPage, encode_consignment_page};
+
+        let page = ConsignmentPage {
+            broker: 812,
+            page: 1,
+            pages: 3,
+            total: 130,
+            query: "ring".to_owned(),
+        };
+        let pkt = encode_consignment_page(ServerCommandType::ConsignmentPage as u8, &page);
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::ConsignmentPage(parsed) => assert_eq!(parsed, page),
+            _ => panic!("Expected ConsignmentPage variant"),
+        }
+    }
+
+    #[test]
+    fn parse_disconnect() {
+        use crate::disconnect::encode_disconnect;
+        use crate::logout_reasons::LogoutReason;
+
+        let pkt = encode_disconnect(
+            ServerCommandType::Disconnect as u8,
+            LogoutReason::VersionMismatch,
+            "Please update your client.",
+        );
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::Disconnect { reason, text } => {
+                assert_eq!(reason, LogoutReason::VersionMismatch as u8);
+                assert_eq!(text, "Please update your client.");
+            }
+            _ => panic!("Expected Disconnect variant"),
+        }
+    }
+
+    #[test]
+    fn parse_friend_entry() {
+        use crate::friends::{FriendOp, encode_friend_entry};
+
+        let pkt = encode_friend_entry(
+            ServerCommandType::FriendEntry as u8,
+            FriendOp::Set,
+            true,
+            "Ishtar",
+        );
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::FriendEntry { op, online, name } => {
+                assert_eq!(op, FriendOp::Set as u8);
+                assert!(online);
+                assert_eq!(name, "Ishtar");
+            }
+            _ => panic!("Expected FriendEntry variant"),
+        }
+    }
+
+    #[test]
+    fn parse_observer() {
+        let pkt = [ServerCommandType::Observer as u8, 1];
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            2
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::Observer { active } => assert!(active),
+            _ => panic!("Expected Observer variant"),
+        }
+    }
+
+    #[test]
+    fn parse_combat_event() {
+        use crate::combat_log::{
+            COMBAT_EVENT_PACKET_LEN, CombatEvent, DamageKind, NO_SKILL, encode_combat_event,
+        };
+
+        let event = CombatEvent {
+            kind: DamageKind::Reflect as u8,
+            skill: NO_SKILL,
+            flags: 0,
+            raw: 4_000,
+            resisted: 0,
+            mitigated: 0,
+            dealt: 4_000,
+            attacker: "Ishtar".to_owned(),
+            target: "Grolm".to_owned(),
+        };
+        let pkt = encode_combat_event(ServerCommandType::CombatEvent as u8, &event);
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            COMBAT_EVENT_PACKET_LEN
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::CombatEvent(decoded) => assert_eq!(decoded, event),
+            _ => panic!("Expected CombatEvent variant"),
+        }
+    }
+
+    #[test]
+    fn parse_item_link() {
+        use crate::item_link::{ITEM_STAT_ARMOR, ItemLink, ItemLinkStat, encode_item_link};
+
+        let link = ItemLink {
+            id: 12,
+            name: "Bronze Helmet".to_owned(),
+            min_rank: 0,
+            stats: vec![ItemLinkStat {
+                stat: ITEM_STAT_ARMOR,
+                bonus: 3,
+                required: 0,
+            }],
+            description: "A simple bronze helmet.".to_owned(),
+        };
+        let pkt = encode_item_link(ServerCommandType::ItemLink as u8, &link);
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        match ServerCommand::from_bytes(&pkt).unwrap().structured_data {
+            ServerCommandData::ItemLink(decoded) => assert_eq!(decoded, link),
+            _ => panic!("Expected ItemLink variant"),
+        }
+    }
+
+    // -- SV_DIALOGUE (opcode 88) --
+
+    #[test]
+    fn parse_dialogue() {
+        let line = DialogueLine::new("npc.give.no_money");
+        let pkt = crate::dialogue::encode_dialogue_packet(
+            ServerCommandType::Dialogue as u8,
+            "Gunther",
+            &line,
+        );
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            pkt.len()
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::Dialogue {
+                speaker,
+                line: parsed,
+            } => {
+                assert_eq!(speaker, "Gunther");
+                assert_eq!(parsed, line);
+            }
+            _ => panic!("Expected Dialogue variant"),
+        }
+    }
+
+    // -- SV_SETVIEW (opcode 84) --
+
+    #[test]
+    fn parse_set_view() {
+        let pkt = [ServerCommandType::SetView as u8, 37];
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            2
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::SetView);
+        match cmd.structured_data {
+            ServerCommandData::SetView { radius } => assert_eq!(radius, 37),
+            _ => panic!("Expected SetView variant"),
+        }
+    }
+
+    // -- SV_MAPMARKER (opcode 85) --
+
+    #[test]
+    fn parse_map_marker() {
+        let pkt = [
+            ServerCommandType::MapMarker as u8,
+            3,
+            0x2C,
+            0x02,
+            0x00,
+            0x02,
+        ];
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            6
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        assert_eq!(cmd.header, ServerCommandType::MapMarker);
+        match cmd.structured_data {
+            ServerCommandData::MapMarker { kind, x, y } => {
+                assert_eq!((kind, x, y), (3, 556, 512));
+            }
+            _ => panic!("Expected MapMarker variant"),
+        }
+    }
+
+    // -- SV_SKILLTIMER (opcode 86) --
+
+    #[test]
+    fn parse_skill_timer() {
+        let pkt = [
+            ServerCommandType::SkillTimer as u8,
+            1,
+            40,
+            0x10,
+            0x01,
+            0x20,
+            0x03,
+        ];
+        let mut last_n = 0i32;
+        assert_eq!(
+            ServerCommandType::get_expected_length(&pkt, &mut last_n).unwrap(),
+            7
+        );
+        let cmd = ServerCommand::from_bytes(&pkt).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::SkillTimer {
+                kind,
+                skill,
+                remaining,
+                total,
+            } => {
+                assert_eq!((kind, skill, remaining, total), (1, 40, 272, 800));
+            }
+            _ => panic!("Expected SkillTimer variant"),
+        }
+    }
+
     // -- SV_SETQUESTCATALOG (opcode 100) --
 
     /// Encode a quest-catalog packet identically to the server's helper.
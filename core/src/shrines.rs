@@ -0,0 +1,176 @@
+//! Shrine blessings.
+//!
+//! A tile flagged [`MF_SHRINE`](crate::constants::MF_SHRINE) is a shrine. A
+//! player standing on one can `#pray` to buy a timed blessing, which the
+//! server attaches as a spell item like any other buff. Which blessing a
+//! shrine grants depends on the area it stands in; shrines outside every
+//! configured area grant [`DEFAULT_SHRINE`].
+//!
+//! Praying again while blessed extends the running blessing, but each
+//! repeat adds half the time of the previous one and costs the full price,
+//! up to [`MAX_BLESSING_STACKS`] repeats.
+//!
+//! Like [`crate::weather_areas`], entries refer to areas by name so the
+//! table stays decoupled from the ordering of [`crate::area::AREAS`].
+
+use crate::area::AREAS;
+use crate::constants::TICKS;
+
+/// Item template id marking a shrine blessing spell item.
+///
+/// Spell temps are skill numbers (optionally `+ 100`); this sits above
+/// all of them.
+pub const SHRINE_BLESSING_TEMP: u16 = 199;
+
+/// Most times a running blessing can be extended.
+pub const MAX_BLESSING_STACKS: u32 = 3;
+
+/// Blessing granted by the shrines of one area.
+pub struct ShrineDef {
+    /// Shrine id, stored on the blessing to tell shrines apart.
+    pub id: u8,
+    /// Matches [`crate::area::Area::name`]; empty for [`DEFAULT_SHRINE`].
+    pub area_name: &'static str,
+    /// Spell name shown on the blessing.
+    pub blessing: &'static str,
+    /// Price in silver.
+    pub cost: u32,
+    /// Length of a fresh blessing in minutes.
+    pub minutes: u32,
+    /// Bonus to all five attributes.
+    pub attrib_bonus: i8,
+    /// Bonus to maximum hit points.
+    pub hp_bonus: i16,
+    /// Bonus to maximum mana.
+    pub mana_bonus: i16,
+    /// Spell power; decides which buff is dropped when all spell slots
+    /// are taken.
+    pub power: u32,
+    /// Buff sprite shown in the spell bar.
+    pub sprite: i16,
+}
+
+impl ShrineDef {
+    /// Ticks a blessing from this shrine adds on top of `stacks` earlier
+    /// purchases of the running blessing.
+    ///
+    /// # Arguments
+    ///
+    /// * `stacks` - Extensions already bought (`0` for a fresh blessing).
+    ///
+    /// # Returns
+    ///
+    /// * The full length for a fresh blessing, halved for every repeat.
+    pub fn blessing_ticks(&self, stacks: u32) -> u32 {
+        (self.minutes * 60 * TICKS as u32) >> stacks.min(MAX_BLESSING_STACKS)
+    }
+}
+
+/// Shrine used for shrine tiles outside every configured area.
+pub const DEFAULT_SHRINE: ShrineDef = ShrineDef {
+    id: 1,
+    area_name: "",
+    blessing: "Shrine Blessing",
+    cost: 2_000,
+    minutes: 15,
+    attrib_bonus: 2,
+    hp_bonus: 0,
+    mana_bonus: 0,
+    power: 20,
+    sprite: 88,
+};
+
+/// Per-area shrine table. The first entry whose area contains the shrine
+/// wins.
+pub const SHRINES: &[ShrineDef] = &[
+    ShrineDef {
+        id: 2,
+        area_name: "Temple of Skua",
+        blessing: "Skua's Favour",
+        cost: 5_000,
+        minutes: 20,
+        attrib_bonus: 3,
+        hp_bonus: 20,
+        mana_bonus: 0,
+        power: 35,
+        sprite: 88,
+    },
+    ShrineDef {
+        id: 3,
+        area_name: "Temple of the Purple One",
+        blessing: "Purple Communion",
+        cost: 5_000,
+        minutes: 20,
+        attrib_bonus: 3,
+        hp_bonus: 0,
+        mana_bonus: 20,
+        power: 35,
+        sprite: 88,
+    },
+    ShrineDef {
+        id: 4,
+        area_name: "Lizard Temple",
+        blessing: "Scaled Vigour",
+        cost: 12_000,
+        minutes: 30,
+        attrib_bonus: 5,
+        hp_bonus: 30,
+        mana_bonus: 30,
+        power: 60,
+        sprite: 88,
+    },
+];
+
+/// Returns the shrine definition for a shrine tile at `(x, y)`.
+///
+/// # Arguments
+///
+/// * `x` - Horizontal world tile coordinate.
+/// * `y` - Vertical world tile coordinate.
+///
+/// # Returns
+///
+/// * The matching [`SHRINES`] entry, or [`DEFAULT_SHRINE`].
+pub fn shrine_at(x: i32, y: i32) -> &'static ShrineDef {
+    SHRINES
+        .iter()
+        .find(|shrine| {
+            AREAS
+                .iter()
+                .any(|area| area.name == shrine.area_name && area.contains(x, y))
+        })
+        .unwrap_or(&DEFAULT_SHRINE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrines_are_found_by_area_and_ids_are_unique() {
+        let skua = AREAS
+            .iter()
+            .find(|a| a.name == "Temple of Skua")
+            .expect("Temple of Skua area");
+        assert_eq!(shrine_at(skua.x1, skua.y1).id, 2);
+        assert_eq!(shrine_at(0, 0).id, DEFAULT_SHRINE.id);
+
+        let mut ids: Vec<u8> = SHRINES.iter().map(|s| s.id).collect();
+        ids.push(DEFAULT_SHRINE.id);
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), SHRINES.len() + 1);
+    }
+
+    #[test]
+    fn repeated_blessings_add_less_time() {
+        let full = DEFAULT_SHRINE.blessing_ticks(0);
+        assert_eq!(full, 15 * 60 * TICKS as u32);
+        assert_eq!(DEFAULT_SHRINE.blessing_ticks(1), full / 2);
+        assert_eq!(DEFAULT_SHRINE.blessing_ticks(2), full / 4);
+        assert_eq!(
+            DEFAULT_SHRINE.blessing_ticks(9),
+            DEFAULT_SHRINE.blessing_ticks(MAX_BLESSING_STACKS)
+        );
+    }
+}
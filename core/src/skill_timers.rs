@@ -0,0 +1,101 @@
+//! Cast-progress and cooldown timers shown on the client skill bar.
+//!
+//! Clients advertising [`crate::constants::CLIENT_CAP_SKILL_TIMERS`] get an
+//! `SV_SKILLTIMER` packet when the player starts casting a skill, when a
+//! skill starts recharging, and when either ends early. Between packets the
+//! client counts the timers down itself, one step per server tick.
+
+/// What an `SV_SKILLTIMER` packet describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SkillTimerKind {
+    /// The skill animation that ends with the skill taking effect.
+    Cast = 0,
+    /// Time until the skill can be used again.
+    Cooldown = 1,
+}
+
+impl SkillTimerKind {
+    /// Decodes a wire value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Kind byte from `SV_SKILLTIMER`.
+    ///
+    /// # Returns
+    ///
+    /// * The kind, or `None` for unknown values.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SkillTimerKind::Cast),
+            1 => Some(SkillTimerKind::Cooldown),
+            _ => None,
+        }
+    }
+}
+
+/// A running timer, in server ticks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SkillTimer {
+    /// Ticks left.
+    pub remaining: u16,
+    /// Full length of the timer; never below `remaining`.
+    pub total: u16,
+}
+
+impl SkillTimer {
+    /// Creates a timer.
+    ///
+    /// # Arguments
+    ///
+    /// * `remaining` - Ticks left.
+    /// * `total` - Full length; raised to `remaining` if smaller.
+    pub fn new(remaining: u16, total: u16) -> Self {
+        SkillTimer {
+            remaining,
+            total: total.max(remaining),
+        }
+    }
+
+    /// Counts down one tick.
+    ///
+    /// # Returns
+    ///
+    /// * `true` while the timer is still running.
+    pub fn tick(&mut self) -> bool {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.remaining > 0
+    }
+
+    /// Share of the timer still to run.
+    ///
+    /// # Returns
+    ///
+    /// * `1.0` right after the timer started, `0.0` once it ran out.
+    pub fn fraction_remaining(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        f32::from(self.remaining) / f32::from(self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_counts_down_to_zero() {
+        let mut timer = SkillTimer::new(2, 4);
+        assert_eq!(timer.fraction_remaining(), 0.5);
+        assert!(timer.tick());
+        assert!(!timer.tick());
+        assert!(!timer.tick());
+        assert_eq!(timer.fraction_remaining(), 0.0);
+
+        // A total shorter than the remaining time is raised to it.
+        assert_eq!(SkillTimer::new(9, 3).total, 9);
+        assert_eq!(SkillTimerKind::from_u8(1), Some(SkillTimerKind::Cooldown));
+        assert_eq!(SkillTimerKind::from_u8(2), None);
+    }
+}
@@ -0,0 +1,189 @@
+//! Skill trainers: NPCs that teach skills for gold.
+//!
+//! Quest-givers with `data[50]` set still teach their single skill in
+//! exchange for an item. Trainers listed in [`SKILL_TRAINERS`] instead offer
+//! a fixed list of skills for a price, optionally gated behind a
+//! [`TrainingPrereq`]. Choosing "Train" in the NPC menu of a trainer makes
+//! the server send `SV_TRAINEROFFERS` with one [`TrainerOfferEntry`] per
+//! offer; the client answers with `CmdLearnSkill` and the server validates
+//! everything again before teaching the skill.
+//!
+//! Status values are part of the wire protocol — do not renumber.
+
+use crate::constants::CT_PRIEST;
+use crate::skills;
+
+/// Maximum number of offers a single trainer can list (and the number of
+/// entry slots in an `SV_TRAINEROFFERS` packet).
+pub const MAX_TRAINER_OFFERS: usize = 6;
+
+/// On-wire size of one offer: skill (1) + price (u32 LE) + status (1).
+pub const TRAINER_OFFER_ENTRY_LEN: usize = 1 + 4 + 1;
+
+/// Total `SV_TRAINEROFFERS` packet size: opcode (1) + trainer character
+/// number (u16 LE) + count (1) + [`MAX_TRAINER_OFFERS`] entry slots.
+pub const TRAINER_OFFERS_PACKET_LEN: usize = 4 + MAX_TRAINER_OFFERS * TRAINER_OFFER_ENTRY_LEN;
+
+/// Requirement that must be met before an offer can be bought.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TrainingPrereq {
+    /// No requirement beyond the price.
+    None,
+    /// The player must already know this skill.
+    Skill(usize),
+    /// The player must have completed the quest of this NPC template.
+    Quest(u16),
+}
+
+/// One skill a trainer teaches.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TrainerOffer {
+    /// Skill index taught.
+    pub skill: usize,
+    /// Price in silver (100 = 1 gold).
+    pub price: u32,
+    /// Requirement checked before teaching.
+    pub prereq: TrainingPrereq,
+}
+
+/// Static definition of a skill trainer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SkillTrainer {
+    /// Template ID of the trainer NPC.
+    pub npc_template_id: u16,
+    /// Offered skills, at most [`MAX_TRAINER_OFFERS`].
+    pub offers: &'static [TrainerOffer],
+}
+
+/// Authored skill trainers, looked up by NPC template via [`find_trainer`].
+pub static SKILL_TRAINERS: &[SkillTrainer] = &[SkillTrainer {
+    npc_template_id: CT_PRIEST as u16,
+    offers: &[
+        TrainerOffer {
+            skill: skills::SK_REST,
+            price: 2_500,
+            prereq: TrainingPrereq::None,
+        },
+        TrainerOffer {
+            skill: skills::SK_MEDIT,
+            price: 10_000,
+            prereq: TrainingPrereq::Skill(skills::SK_REST),
+        },
+        TrainerOffer {
+            skill: skills::SK_REGEN,
+            price: 10_000,
+            prereq: TrainingPrereq::Skill(skills::SK_REST),
+        },
+        // The black candle quest of the Cityguard.
+        TrainerOffer {
+            skill: skills::SK_PROTECT,
+            price: 25_000,
+            prereq: TrainingPrereq::Quest(518),
+        },
+    ],
+}];
+
+/// Look up a trainer by NPC template ID.
+///
+/// # Arguments
+///
+/// * `npc_template_id` - Template ID of the NPC.
+///
+/// # Returns
+///
+/// * `Some(&SkillTrainer)` if the NPC is a trainer, otherwise `None`.
+pub fn find_trainer(npc_template_id: u16) -> Option<&'static SkillTrainer> {
+    SKILL_TRAINERS
+        .iter()
+        .find(|t| t.npc_template_id == npc_template_id)
+}
+
+/// Whether a player may buy an offer right now (ignoring the price).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OfferStatus {
+    /// Can be learned.
+    Available = 0,
+    /// Already known.
+    Known = 1,
+    /// The [`TrainingPrereq`] is not met.
+    MissingPrereq = 2,
+    /// The player's race can never raise this skill.
+    CannotLearn = 3,
+}
+
+impl OfferStatus {
+    /// Decodes a status byte from `SV_TRAINEROFFERS`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Status discriminant.
+    ///
+    /// # Returns
+    ///
+    /// * The status; unknown values decode as [`OfferStatus::CannotLearn`].
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => OfferStatus::Available,
+            1 => OfferStatus::Known,
+            2 => OfferStatus::MissingPrereq,
+            _ => OfferStatus::CannotLearn,
+        }
+    }
+
+    /// Returns the short label shown next to an unavailable offer.
+    ///
+    /// # Returns
+    ///
+    /// * A static label, empty for [`OfferStatus::Available`].
+    pub fn label(self) -> &'static str {
+        match self {
+            OfferStatus::Available => "",
+            OfferStatus::Known => "known",
+            OfferStatus::MissingPrereq => "locked",
+            OfferStatus::CannotLearn => "n/a",
+        }
+    }
+}
+
+/// One offer as carried in `SV_TRAINEROFFERS`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TrainerOfferEntry {
+    /// Skill index taught.
+    pub skill: u8,
+    /// Price in silver.
+    pub price: u32,
+    /// Availability for the receiving player.
+    pub status: OfferStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trainers_fit_the_wire_format() {
+        for trainer in SKILL_TRAINERS {
+            assert!(trainer.offers.len() <= MAX_TRAINER_OFFERS);
+            for offer in trainer.offers {
+                assert!(offer.skill < skills::MAX_SKILLS);
+                assert!(offer.skill <= u8::MAX as usize);
+            }
+        }
+        assert!(find_trainer(CT_PRIEST as u16).is_some());
+        assert!(find_trainer(0).is_none());
+    }
+
+    #[test]
+    fn status_bytes_roundtrip() {
+        for status in [
+            OfferStatus::Available,
+            OfferStatus::Known,
+            OfferStatus::MissingPrereq,
+            OfferStatus::CannotLearn,
+        ] {
+            assert_eq!(OfferStatus::from_u8(status as u8), status);
+        }
+        assert_eq!(OfferStatus::from_u8(200), OfferStatus::CannotLearn);
+    }
+}
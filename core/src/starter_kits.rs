@@ -0,0 +1,217 @@
+//! Starter kits granted to brand-new characters.
+//!
+//! A new character gets the kit for its [`KitArchetype`], which is derived
+//! from the attribute and mana maximums of its race template, so a
+//! mana-less race gets armor and a sword while a caster gets potions for
+//! its mana pool. Kits replace the items hard-coded into the race
+//! templates.
+//!
+//! Kits are plain text so a server can ship its own set. Each non-empty
+//! line not starting with `#` defines one kit:
+//!
+//! ```text
+//! fighter gold=500 31 57 59 101x3
+//! ```
+//!
+//! The archetype comes first, then an optional `gold=<silver>` and a list of
+//! item template ids, each with an optional `x<count>` suffix.
+
+use crate::constants::{AT_AGIL, AT_BRAVE, AT_INT, AT_STREN, AT_WILL};
+use crate::skills::SkillIndex;
+use crate::types::Character;
+
+/// Built-in kits, used unless the server configures its own.
+pub const DEFAULT_STARTER_KITS: &str = "\
+# archetype gold=<silver> template[xcount] ...
+# 27 bronze dagger, 31 bronze sword, 57 bronze helmet, 59 bronze armor,
+# 101 healing potion, 102 mana potion
+fighter gold=500 31 57 59 101x3
+hybrid gold=500 27 57 101x2 102x2
+caster gold=500 27 101x2 102x4
+";
+
+/// Most items a single kit may hold, so it always fits a fresh backpack.
+pub const MAX_KIT_ITEMS: usize = 20;
+
+/// Broad play style a kit is chosen for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KitArchetype {
+    /// No mana: melee weapon and armor.
+    Fighter,
+    /// Mana plus a body leaning on strength and agility.
+    Hybrid,
+    /// Mana plus a body leaning on willpower and intuition.
+    Caster,
+}
+
+impl KitArchetype {
+    /// Parses an archetype name as used in kit definitions.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Lower-case archetype name.
+    ///
+    /// # Returns
+    ///
+    /// * The archetype, or `None` for an unknown name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fighter" => Some(KitArchetype::Fighter),
+            "hybrid" => Some(KitArchetype::Hybrid),
+            "caster" => Some(KitArchetype::Caster),
+            _ => None,
+        }
+    }
+
+    /// Chooses the archetype for a freshly created character.
+    ///
+    /// # Arguments
+    ///
+    /// * `ch` - Character created from its race template.
+    ///
+    /// # Returns
+    ///
+    /// * `Fighter` without mana, otherwise `Caster` when willpower and
+    ///   intuition outweigh agility and strength, else `Hybrid`.
+    pub fn for_character(ch: &Character) -> Self {
+        let max = SkillIndex::MaxValue as usize;
+        if ch.mana[max] == 0 {
+            return KitArchetype::Fighter;
+        }
+        let at = |n: i32| u32::from(ch.attrib[n as usize][max]);
+        let mind = at(AT_WILL) + at(AT_INT);
+        let body = at(AT_AGIL) + at(AT_STREN);
+        // Braveness helps both; it only breaks ties towards the fighter side.
+        if mind > body + at(AT_BRAVE) / 4 {
+            KitArchetype::Caster
+        } else {
+            KitArchetype::Hybrid
+        }
+    }
+}
+
+/// Items and money handed to a new character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StarterKit {
+    /// Archetype the kit is for.
+    pub archetype: KitArchetype,
+    /// Money added to the purse, in silver.
+    pub gold: u32,
+    /// Item templates, one entry per item.
+    pub items: Vec<u16>,
+}
+
+/// Parses kit definitions in the format described in the module docs.
+///
+/// # Arguments
+///
+/// * `text` - Kit definitions, one kit per line.
+///
+/// # Returns
+///
+/// * The kits, or a message naming the first bad line.
+pub fn parse_kits(text: &str) -> Result<Vec<StarterKit>, String> {
+    let mut kits: Vec<StarterKit> = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |msg: String| format!("line {}: {}", line_no + 1, msg);
+
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let archetype = KitArchetype::from_name(name)
+            .ok_or_else(|| err(format!("unknown archetype '{}'", name)))?;
+        if kits.iter().any(|k| k.archetype == archetype) {
+            return Err(err(format!("duplicate kit for '{}'", name)));
+        }
+
+        let mut kit = StarterKit {
+            archetype,
+            gold: 0,
+            items: Vec::new(),
+        };
+        for word in words {
+            if let Some(gold) = word.strip_prefix("gold=") {
+                kit.gold = gold
+                    .parse()
+                    .map_err(|_| err(format!("bad gold amount '{}'", gold)))?;
+                continue;
+            }
+            let (template, count) = word.split_once('x').unwrap_or((word, "1"));
+            let template: u16 = template
+                .parse()
+                .ok()
+                .filter(|&t| t != 0)
+                .ok_or_else(|| err(format!("bad item template '{}'", word)))?;
+            let count: usize = count
+                .parse()
+                .map_err(|_| err(format!("bad item count '{}'", word)))?;
+            kit.items.extend(std::iter::repeat_n(template, count));
+        }
+        if kit.items.len() > MAX_KIT_ITEMS {
+            return Err(err(format!("more than {} items", MAX_KIT_ITEMS)));
+        }
+        kits.push(kit);
+    }
+    Ok(kits)
+}
+
+/// Returns the built-in kits from [`DEFAULT_STARTER_KITS`].
+///
+/// # Returns
+///
+/// * The default kit list.
+pub fn default_kits() -> Vec<StarterKit> {
+    parse_kits(DEFAULT_STARTER_KITS).expect("built-in starter kits must parse")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_kits_cover_every_archetype() {
+        let kits = default_kits();
+        for archetype in [
+            KitArchetype::Fighter,
+            KitArchetype::Hybrid,
+            KitArchetype::Caster,
+        ] {
+            assert!(kits.iter().any(|k| k.archetype == archetype));
+        }
+        let fighter = &kits[0];
+        assert_eq!(fighter.gold, 500);
+        assert_eq!(fighter.items, vec![31, 57, 59, 101, 101, 101]);
+    }
+
+    #[test]
+    fn parse_reports_bad_lines() {
+        assert!(parse_kits("# only a comment\n\n").unwrap().is_empty());
+        assert_eq!(
+            parse_kits("rogue 27").unwrap_err(),
+            "line 1: unknown archetype 'rogue'"
+        );
+        assert!(parse_kits("fighter 27\nfighter 31").is_err());
+        assert!(parse_kits("caster 0").is_err());
+        assert!(parse_kits("caster 101x21").is_err());
+    }
+
+    #[test]
+    fn archetype_follows_mana_and_attributes() {
+        let max = SkillIndex::MaxValue as usize;
+        let mut ch = Character::default();
+        assert_eq!(KitArchetype::for_character(&ch), KitArchetype::Fighter);
+
+        ch.mana[max] = 50;
+        ch.attrib[AT_WILL as usize][max] = 40;
+        ch.attrib[AT_INT as usize][max] = 40;
+        ch.attrib[AT_AGIL as usize][max] = 20;
+        ch.attrib[AT_STREN as usize][max] = 20;
+        assert_eq!(KitArchetype::for_character(&ch), KitArchetype::Caster);
+
+        ch.attrib[AT_STREN as usize][max] = 60;
+        assert_eq!(KitArchetype::for_character(&ch), KitArchetype::Hybrid);
+    }
+}
@@ -0,0 +1,137 @@
+//! Shared KeyDB keys and payload for stream overlays.
+//!
+//! A player can opt a character in to stream overlays through the API, which
+//! adds its API character id to [`OVERLAY_OPT_IN_KEY`] and hands out a scoped
+//! overlay token. While that character is online the game server writes an
+//! [`OverlayStats`] snapshot to [`overlay_stats_key`] every
+//! [`OVERLAY_INTERVAL_SECS`]; the API serves it as JSON to whoever presents
+//! the token (typically a browser source in streaming software).
+//!
+//! Positions are deliberately left out so an overlay cannot be used to find
+//! the streamer in game.
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// KeyDB set of API character ids whose stats the server should publish.
+pub const OVERLAY_OPT_IN_KEY: &str = "game:overlay:opted_in";
+
+/// How often the server refreshes overlay snapshots (seconds).
+pub const OVERLAY_INTERVAL_SECS: u64 = 2;
+
+/// Number of kills kept in [`OverlayStats::recent_kills`].
+pub const MAX_RECENT_KILLS: usize = 10;
+
+/// KeyDB key holding the latest bincode-encoded [`OverlayStats`] for a
+/// character.
+///
+/// # Arguments
+///
+/// * `api_character_id` - API character id.
+///
+/// # Returns
+///
+/// * The snapshot key.
+pub fn overlay_stats_key(api_character_id: u64) -> String {
+    format!("game:overlay:character:{}", api_character_id)
+}
+
+/// One kill made by the character.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct OverlayKill {
+    /// Name of the character that was killed.
+    pub victim: String,
+    /// Whether the victim was a player character.
+    pub victim_is_player: bool,
+    /// Wall-clock time of the kill (seconds since Unix epoch).
+    pub unix_secs: u64,
+}
+
+/// Live stats of one opted-in character.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct OverlayStats {
+    /// Wall-clock time the snapshot was taken (seconds since Unix epoch).
+    pub generated_at: u64,
+    pub name: String,
+    /// Rank display name.
+    pub rank: String,
+    /// Total experience points.
+    pub experience: i32,
+    pub hp: i32,
+    pub max_hp: i32,
+    pub endurance: i32,
+    pub max_endurance: i32,
+    pub mana: i32,
+    pub max_mana: i32,
+    /// Area name(s) at the character's position, empty outside all areas.
+    pub area: String,
+    /// Most recent kills, newest first, at most [`MAX_RECENT_KILLS`].
+    pub recent_kills: Vec<OverlayKill>,
+}
+
+impl OverlayStats {
+    /// Encode the snapshot to canonical bincode bytes.
+    ///
+    /// # Returns
+    ///
+    /// * Encoded snapshot bytes.
+    ///
+    /// # Panics
+    ///
+    /// * Panics if bincode serialization fails.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("OverlayStats::to_bytes failed")
+    }
+
+    /// Decode a snapshot from canonical bincode bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Encoded snapshot payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(stats)` when all bytes decode successfully, otherwise `None`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (value, consumed): (Self, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+        if consumed == bytes.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_roundtrip_rejects_trailing_bytes() {
+        let stats = OverlayStats {
+            generated_at: 1_700_000_000,
+            name: "Ishtar".to_owned(),
+            rank: "Private".to_owned(),
+            experience: 1234,
+            hp: 40,
+            max_hp: 52,
+            endurance: 30,
+            max_endurance: 30,
+            mana: 0,
+            max_mana: 0,
+            area: "Aston".to_owned(),
+            recent_kills: vec![OverlayKill {
+                victim: "Rat".to_owned(),
+                victim_is_player: false,
+                unix_secs: 1_699_999_990,
+            }],
+        };
+        let mut bytes = stats.to_bytes();
+        assert_eq!(OverlayStats::from_bytes(&bytes), Some(stats));
+        bytes.push(0);
+        assert_eq!(OverlayStats::from_bytes(&bytes), None);
+        assert_eq!(overlay_stats_key(77), "game:overlay:character:77");
+    }
+}
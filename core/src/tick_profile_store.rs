@@ -0,0 +1,100 @@
+//! Shared KeyDB key and payload for tick cost attribution reports.
+//!
+//! The game server measures how much of each tick is spent acting for every
+//! character, aggregates the cost per map area and for the most expensive
+//! characters over a fixed window, and writes the latest
+//! [`TickProfileReport`] to [`TICK_PROFILE_KEY`]. The admin API serves it as
+//! JSON so hotspots can be found when ticks start missing their budget.
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// KeyDB key holding the latest bincode-encoded [`TickProfileReport`].
+pub const TICK_PROFILE_KEY: &str = "game:admin:tick_profile";
+
+/// Zone name used for characters outside every named area.
+pub const UNZONED: &str = "(no area)";
+
+/// Simulation cost attributed to one map area.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct ZoneCost {
+    /// Area name(s) as returned by [`crate::area::get_area_m`], or
+    /// [`UNZONED`].
+    pub zone: String,
+    /// Total time spent acting for characters in the zone (microseconds).
+    pub micros: u64,
+    /// Number of distinct characters that acted in the zone.
+    pub characters: u32,
+}
+
+/// Simulation cost attributed to one character.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct CharacterCost {
+    pub character_id: u32,
+    pub name: String,
+    pub is_player: bool,
+    /// Position at the end of the window.
+    pub x: i16,
+    pub y: i16,
+    pub zone: String,
+    /// Total time spent acting for the character (microseconds).
+    pub micros: u64,
+    /// Ticks in which the character acted.
+    pub ticks: u32,
+    /// Slowest single tick for the character (microseconds).
+    pub max_micros: u64,
+}
+
+/// Tick cost attribution over one measurement window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct TickProfileReport {
+    /// Wall-clock time the window closed (seconds since Unix epoch).
+    pub generated_at: u64,
+    /// Server ticker value when the window closed.
+    pub ticker: i32,
+    /// Number of ticks in the window.
+    pub window_ticks: u32,
+    /// Total game-tick time over the window (microseconds).
+    pub tick_micros: u64,
+    /// Time attributed to characters over the window (microseconds).
+    pub character_micros: u64,
+    /// Zones ordered by descending cost.
+    pub zones: Vec<ZoneCost>,
+    /// Most expensive characters, ordered by descending cost.
+    pub top_characters: Vec<CharacterCost>,
+}
+
+impl TickProfileReport {
+    /// Encode the report to canonical bincode bytes.
+    ///
+    /// # Returns
+    ///
+    /// * Encoded report bytes.
+    ///
+    /// # Panics
+    ///
+    /// * Panics if bincode serialization fails.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::encode_to_vec(self, bincode::config::standard())
+            .expect("TickProfileReport::to_bytes failed")
+    }
+
+    /// Decode a report from canonical bincode bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Encoded report payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(report)` when all bytes decode successfully, otherwise `None`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (value, consumed): (Self, usize) =
+            bincode::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+        if consumed == bytes.len() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Earnable character titles ("name badges").
+//!
+//! A title is a short label rendered in front of a player's name on
+//! nameplates and in the look panel (`"<Title> Name"`). Titles are either
+//! earned automatically by reaching a rank milestone or granted by staff
+//! with the `#granttitle` command. A player picks which earned title to
+//! display from the profile panel (or with `#title`).
+//!
+//! Persistence layout (`Character::future3`, see also [`crate::profile`]):
+//!
+//! * `future3[TITLE_EARNED_SLOT]` — bitmask of earned title ids (bit `id`).
+//! * `future3[TITLE_SELECTED_SLOT]` — id of the displayed title (`0` = none).
+//!
+//! Title ids are part of the wire protocol (`SV_SETCHARTITLES`, `SV_LOOK3`
+//! byte 15, `CmdSetTitle`) and persisted — never renumber an entry.
+
+use crate::ranks::Rank;
+
+/// Index into `Character::future3` holding the earned-title bitmask.
+pub const TITLE_EARNED_SLOT: usize = 1;
+
+/// Index into `Character::future3` holding the selected title id.
+pub const TITLE_SELECTED_SLOT: usize = 2;
+
+/// Highest title id that fits in the 32-bit earned mask.
+pub const MAX_TITLE_ID: u8 = 31;
+
+/// How a title is obtained.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TitleSource {
+    /// Unlocked automatically once the character reaches this rank.
+    Rank(Rank),
+    /// Only obtainable through a staff grant.
+    Granted,
+}
+
+/// Static definition of a single title.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TitleDef {
+    /// Stable wire/persistence id (`1..=MAX_TITLE_ID`).
+    pub id: u8,
+    /// Display text rendered in front of the character name.
+    pub name: &'static str,
+    /// How the title is obtained.
+    pub source: TitleSource,
+}
+
+/// Catalog of all titles, ordered by id.
+pub const TITLES: &[TitleDef] = &[
+    TitleDef {
+        id: 1,
+        name: "Veteran",
+        source: TitleSource::Rank(Rank::Sergeant),
+    },
+    TitleDef {
+        id: 2,
+        name: "Officer",
+        source: TitleSource::Rank(Rank::SecondLieutenant),
+    },
+    TitleDef {
+        id: 3,
+        name: "Commander",
+        source: TitleSource::Rank(Rank::Colonel),
+    },
+    TitleDef {
+        id: 4,
+        name: "Sir",
+        source: TitleSource::Rank(Rank::Knight),
+    },
+    TitleDef {
+        id: 5,
+        name: "Legendary",
+        source: TitleSource::Rank(Rank::Warlord),
+    },
+    TitleDef {
+        id: 16,
+        name: "Hero",
+        source: TitleSource::Granted,
+    },
+    TitleDef {
+        id: 17,
+        name: "Champion",
+        source: TitleSource::Granted,
+    },
+    TitleDef {
+        id: 18,
+        name: "Loremaster",
+        source: TitleSource::Granted,
+    },
+    TitleDef {
+        id: 19,
+        name: "Pathfinder",
+        source: TitleSource::Granted,
+    },
+];
+
+/// Looks up a title by id.
+///
+/// # Arguments
+///
+/// * `id` - Title id (`0` never matches).
+///
+/// # Returns
+///
+/// * `Some(&TitleDef)` for a known id, otherwise `None`.
+pub fn title_by_id(id: u8) -> Option<&'static TitleDef> {
+    TITLES.iter().find(|t| t.id == id)
+}
+
+/// Looks up a title by display name (ASCII case-insensitive) or numeric id.
+///
+/// # Arguments
+///
+/// * `query` - Title name or decimal id.
+///
+/// # Returns
+///
+/// * `Some(&TitleDef)` when a title matches, otherwise `None`.
+pub fn find_title(query: &str) -> Option<&'static TitleDef> {
+    let query = query.trim();
+    if let Ok(id) = query.parse::<u8>() {
+        return title_by_id(id);
+    }
+    TITLES.iter().find(|t| t.name.eq_ignore_ascii_case(query))
+}
+
+/// Returns the mask bit for a title id.
+///
+/// # Arguments
+///
+/// * `id` - Title id.
+///
+/// # Returns
+///
+/// * The bit for `id`, or `0` when `id` is out of range.
+pub fn title_bit(id: u8) -> u32 {
+    if id == 0 || id > MAX_TITLE_ID {
+        0
+    } else {
+        1u32 << id
+    }
+}
+
+/// Returns `true` when `id` is set in the earned `mask`.
+///
+/// # Arguments
+///
+/// * `mask` - Earned-title bitmask.
+/// * `id` - Title id to test.
+///
+/// # Returns
+///
+/// * `true` if the title has been earned.
+pub fn has_title(mask: u32, id: u8) -> bool {
+    let bit = title_bit(id);
+    bit != 0 && mask & bit != 0
+}
+
+/// Computes the titles unlocked by reaching `rank_idx`.
+///
+/// # Arguments
+///
+/// * `rank_idx` - Zero-based rank index.
+///
+/// # Returns
+///
+/// * Bitmask of every rank-milestone title at or below `rank_idx`.
+pub fn rank_titles_mask(rank_idx: usize) -> u32 {
+    TITLES
+        .iter()
+        .filter(|t| matches!(t.source, TitleSource::Rank(r) if r as usize <= rank_idx))
+        .fold(0, |mask, t| mask | title_bit(t.id))
+}
+
+/// Iterates the catalog entries present in `mask`, in catalog order.
+///
+/// # Arguments
+///
+/// * `mask` - Earned-title bitmask.
+///
+/// # Returns
+///
+/// * Iterator over earned [`TitleDef`]s.
+pub fn earned_titles(mask: u32) -> impl Iterator<Item = &'static TitleDef> {
+    TITLES.iter().filter(move |t| has_title(mask, t.id))
+}
+
+/// Formats a display name with an optional title prefix.
+///
+/// # Arguments
+///
+/// * `title_id` - Selected title id (`0` or unknown = no prefix).
+/// * `name` - Character name.
+///
+/// # Returns
+///
+/// * `"<Title> Name"`, or just `name` when there is no title.
+pub fn titled_name(title_id: u8, name: &str) -> String {
+    match title_by_id(title_id) {
+        Some(t) => format!("{} {}", t.name, name),
+        None => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_ids_are_unique_and_in_range() {
+        for (i, a) in TITLES.iter().enumerate() {
+            assert!(a.id > 0 && a.id <= MAX_TITLE_ID, "bad id {}", a.id);
+            for b in &TITLES[i + 1..] {
+                assert_ne!(a.id, b.id);
+            }
+        }
+    }
+
+    #[test]
+    fn rank_titles_unlock_cumulatively() {
+        assert_eq!(rank_titles_mask(Rank::Private as usize), 0);
+        let sgt = rank_titles_mask(Rank::Sergeant as usize);
+        assert!(has_title(sgt, 1));
+        assert!(!has_title(sgt, 2));
+        let warlord = rank_titles_mask(Rank::Warlord as usize);
+        assert!((1..=5).all(|id| has_title(warlord, id)));
+        assert!(
+            !has_title(warlord, 16),
+            "granted titles never come from rank"
+        );
+    }
+
+    #[test]
+    fn find_title_by_name_or_id() {
+        assert_eq!(find_title("hero").map(|t| t.id), Some(16));
+        assert_eq!(find_title("4").map(|t| t.name), Some("Sir"));
+        assert!(find_title("Emperor").is_none());
+        assert!(find_title("0").is_none());
+    }
+
+    #[test]
+    fn titled_name_prefixes_known_titles_only() {
+        assert_eq!(titled_name(17, "Ishtar"), "Champion Ishtar");
+        assert_eq!(titled_name(0, "Ishtar"), "Ishtar");
+        assert_eq!(titled_name(30, "Ishtar"), "Ishtar");
+    }
+}
@@ -167,6 +167,12 @@ impl Default for CharacterSummary {
     }
 }
 
+/// Most characters one account may own.
+///
+/// Enforced by the API on character creation; the client uses it to show
+/// how full the roster is and to refuse creation up front.
+pub const MAX_CHARACTERS_PER_ACCOUNT: usize = 10;
+
 /// List of characters belonging to an account.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GetCharactersResponse {
@@ -238,6 +244,30 @@ pub struct ResetPasswordConfirmResponse {
     pub message: String,
 }
 
+/// Discord authorization URL for linking an account.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiscordLinkResponse {
+    /// Discord OAuth2 URL the player should open. `None` when the request
+    /// fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Optional player-facing error message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Stream overlay token issued for one character.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OverlayTokenResponse {
+    /// Scoped token that only grants read access to the character's overlay
+    /// stats. `None` when the request fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Optional player-facing error message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -0,0 +1,157 @@
+//! Bound-checked indices into the flat character, item and player tables.
+//!
+//! The server keeps characters, items and player slots in fixed-size
+//! vectors and historically passed raw `usize` (or `i32` straight out of
+//! `Character::data`) indices around. A stale or corrupt index then only
+//! surfaced as an out-of-bounds panic deep inside a tick. These newtypes can
+//! only be built through validated constructors, so holding one proves the
+//! index is in `1..MAX*` (slot `0` is the "none" sentinel in every table).
+
+use std::fmt;
+
+use crate::constants::{MAXCHARS, MAXITEM, MAXPLAYER};
+
+macro_rules! table_id {
+    ($(#[$meta:meta])* $name:ident, $max:expr) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(usize);
+
+        impl $name {
+            /// One past the largest valid index.
+            pub const LIMIT: usize = $max;
+
+            /// Validates a raw index.
+            ///
+            /// # Arguments
+            ///
+            /// * `raw` - Table index.
+            ///
+            /// # Returns
+            ///
+            /// * `Some` when `raw` is in `1..LIMIT`, `None` for the `0`
+            ///   sentinel and anything out of range.
+            pub const fn new(raw: usize) -> Option<Self> {
+                if raw > 0 && raw < Self::LIMIT {
+                    Some(Self(raw))
+                } else {
+                    None
+                }
+            }
+
+            /// Validates an index stored as `i32` (e.g. in `Character::data`).
+            ///
+            /// # Arguments
+            ///
+            /// * `raw` - Stored index; negative values are rejected.
+            ///
+            /// # Returns
+            ///
+            /// * `Some` when `raw` is in `1..LIMIT`.
+            pub fn from_i32(raw: i32) -> Option<Self> {
+                usize::try_from(raw).ok().and_then(Self::new)
+            }
+
+            /// The raw table index.
+            pub const fn index(self) -> usize {
+                self.0
+            }
+
+            /// Looks the index up in `table`.
+            ///
+            /// # Arguments
+            ///
+            /// * `table` - The table this index addresses.
+            ///
+            /// # Returns
+            ///
+            /// * The entry, or `None` if `table` is shorter than `LIMIT`
+            ///   (e.g. a trimmed test fixture).
+            pub fn get<T>(self, table: &[T]) -> Option<&T> {
+                table.get(self.0)
+            }
+
+            /// Mutable variant of [`Self::get`].
+            pub fn get_mut<T>(self, table: &mut [T]) -> Option<&mut T> {
+                table.get_mut(self.0)
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(id: $name) -> usize {
+                id.0
+            }
+        }
+
+        impl TryFrom<usize> for $name {
+            type Error = String;
+
+            fn try_from(raw: usize) -> Result<Self, Self::Error> {
+                Self::new(raw).ok_or_else(|| {
+                    format!(
+                        "{} {} out of range 1..{}",
+                        stringify!($name),
+                        raw,
+                        Self::LIMIT
+                    )
+                })
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+table_id!(
+    /// Index into the character table (`1..MAXCHARS`).
+    CharId,
+    MAXCHARS
+);
+
+table_id!(
+    /// Index into the runtime item table (`1..MAXITEM`).
+    ItemId,
+    MAXITEM
+);
+
+table_id!(
+    /// Index into the server's player (connection) slots (`1..MAXPLAYER`).
+    PlayerSlot,
+    MAXPLAYER
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constructors_reject_sentinel_and_out_of_range() {
+        assert_eq!(CharId::new(0), None);
+        assert_eq!(CharId::new(MAXCHARS), None);
+        assert_eq!(
+            CharId::new(MAXCHARS - 1).map(CharId::index),
+            Some(MAXCHARS - 1)
+        );
+        assert_eq!(ItemId::from_i32(-1), None);
+        assert_eq!(ItemId::from_i32(42).map(usize::from), Some(42));
+        assert_eq!(PlayerSlot::new(MAXPLAYER), None);
+        assert!(PlayerSlot::try_from(MAXPLAYER).is_err());
+        assert_eq!(PlayerSlot::try_from(3).unwrap().to_string(), "3");
+    }
+
+    #[test]
+    fn lookups_are_checked_against_the_table() {
+        let table = vec![10, 20, 30];
+        let id = CharId::new(2).unwrap();
+        assert_eq!(id.get(&table), Some(&30));
+        assert_eq!(CharId::new(5).unwrap().get(&table), None);
+
+        let mut table = table;
+        *id.get_mut(&mut table).unwrap() = 7;
+        assert_eq!(table[2], 7);
+    }
+}
@@ -17,6 +17,7 @@ mod client_player;
 mod effect;
 mod enums;
 mod global;
+mod ids;
 mod item;
 mod map;
 mod see_map;
@@ -29,6 +30,7 @@ pub use client_player::ClientPlayer;
 pub use effect::Effect;
 pub use enums::*;
 pub use global::Global;
+pub use ids::{CharId, ItemId, PlayerSlot};
 pub use item::Item;
 pub use map::Map;
 pub use see_map::SeeMap;
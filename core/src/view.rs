@@ -0,0 +1,103 @@
+//! Camera view radius and zoom levels.
+//!
+//! Every player gets the same `TILEX` × `TILEY` map window, but the server
+//! only fills tiles within its view radius (the larger of the x and y
+//! distance from the centre tile); tiles further out are sent empty. The
+//! unzoomed 960×540 client camera needs [`LEGACY_VIEW_RADIUS`] tiles. A
+//! client advertising [`crate::constants::CLIENT_CAP_WIDE_VIEW`] is told the
+//! radius with `SV_SETVIEW` and may zoom out until it shows that many tiles.
+//!
+//! PvP servers set the radius to [`LEGACY_VIEW_RADIUS`] so a zoomed-out
+//! camera never sees further than the classic one.
+
+use crate::constants::TILEX;
+
+/// View radius the unzoomed camera needs to fill the 960×540 viewport,
+/// including the corners.
+pub const LEGACY_VIEW_RADIUS: u8 = 33;
+
+/// Largest view radius the map window can carry. The server trims a few
+/// rows and columns off the window edges, so this is below `TILEX / 2`.
+pub const MAX_VIEW_RADIUS: u8 = (TILEX / 2) as u8 - 2;
+
+/// Tiles added to the view radius by one zoom-out step.
+pub const VIEW_RADIUS_STEP: u8 = 2;
+
+/// Clamps a configured view radius to what the protocol supports.
+///
+/// # Arguments
+///
+/// * `radius` - Requested radius in tiles.
+///
+/// # Returns
+///
+/// * `radius` limited to `LEGACY_VIEW_RADIUS..=MAX_VIEW_RADIUS`.
+pub fn clamp_view_radius(radius: u8) -> u8 {
+    radius.clamp(LEGACY_VIEW_RADIUS, MAX_VIEW_RADIUS)
+}
+
+/// Whether a tile offset from the centre lies within `radius`.
+///
+/// # Arguments
+///
+/// * `dx` - X distance from the centre tile.
+/// * `dy` - Y distance from the centre tile.
+/// * `radius` - View radius in tiles.
+///
+/// # Returns
+///
+/// * `true` if the tile should be sent.
+pub fn in_view(dx: i32, dy: i32, radius: u8) -> bool {
+    dx.abs().max(dy.abs()) <= i32::from(radius)
+}
+
+/// The view radii a client may pick, nearest first.
+///
+/// # Arguments
+///
+/// * `max_radius` - Radius announced by the server.
+///
+/// # Returns
+///
+/// * `LEGACY_VIEW_RADIUS` followed by one entry per zoom-out step that
+///   still fits within `max_radius`.
+pub fn zoom_steps(max_radius: u8) -> Vec<u8> {
+    let max_radius = clamp_view_radius(max_radius);
+    (LEGACY_VIEW_RADIUS..=max_radius)
+        .step_by(usize::from(VIEW_RADIUS_STEP))
+        .collect()
+}
+
+/// Render scale for a view radius, in percent of the unzoomed camera.
+///
+/// # Arguments
+///
+/// * `radius` - View radius the camera shows.
+///
+/// # Returns
+///
+/// * `100` for the legacy radius, smaller for wider views.
+pub fn zoom_percent(radius: u8) -> u32 {
+    u32::from(LEGACY_VIEW_RADIUS) * 100 / u32::from(radius.max(LEGACY_VIEW_RADIUS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_steps_stop_at_the_server_radius() {
+        assert_eq!(zoom_steps(LEGACY_VIEW_RADIUS), vec![LEGACY_VIEW_RADIUS]);
+        assert_eq!(zoom_steps(0), vec![LEGACY_VIEW_RADIUS]);
+        assert_eq!(zoom_steps(u8::MAX), vec![33, 35, 37]);
+        assert_eq!(zoom_percent(LEGACY_VIEW_RADIUS), 100);
+        assert_eq!(zoom_percent(37), 89);
+    }
+
+    #[test]
+    fn in_view_uses_the_larger_axis_distance() {
+        assert!(in_view(33, -33, 33));
+        assert!(!in_view(34, 0, 33));
+        assert!(!in_view(0, -34, 33));
+    }
+}
@@ -0,0 +1,125 @@
+//! Client-planned walking routes (`CmdWaypoints`).
+//!
+//! The client plans a route with A* over the tiles it can see and sends it
+//! as a short list of waypoints. The server checks every hop and then walks
+//! to each waypoint in turn with its normal step logic, so a hop only has
+//! to be a short straight line from the previous waypoint.
+
+/// Waypoints carried by one `CmdWaypoints` packet.
+pub const WAYPOINTS_PER_PACKET: usize = 3;
+
+/// Set in the count byte of every `CmdWaypoints` packet after the first of
+/// a route; the first packet replaces any route the server still holds.
+pub const WAYPOINTS_APPEND: u8 = 0x80;
+
+/// Longest route, in waypoints, the server accepts.
+pub const MAX_WAYPOINTS: usize = 24;
+
+/// Longest hop, in tiles (Chebyshev distance), between two consecutive
+/// waypoints or between the character and the first waypoint.
+pub const MAX_WAYPOINT_SPAN: u16 = 8;
+
+/// Chebyshev distance between two tiles.
+///
+/// # Arguments
+///
+/// * `a`, `b` - World tile coordinates.
+///
+/// # Returns
+///
+/// * The number of king moves between `a` and `b`.
+pub fn span(a: (u16, u16), b: (u16, u16)) -> u16 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+/// Reduces a tile-by-tile route to waypoints.
+///
+/// Keeps every tile where the route turns plus the final tile, and splits
+/// straight runs so no hop exceeds [`MAX_WAYPOINT_SPAN`].
+///
+/// # Arguments
+///
+/// * `start` - Tile the route starts from (not part of `path`).
+/// * `path` - Consecutive, adjacent tiles from the first step to the goal.
+///
+/// # Returns
+///
+/// * The waypoints, in walking order; empty if `path` is empty.
+pub fn path_to_waypoints(start: (u16, u16), path: &[(u16, u16)]) -> Vec<(u16, u16)> {
+    let step = |a: (u16, u16), b: (u16, u16)| {
+        (
+            i32::from(b.0) - i32::from(a.0),
+            i32::from(b.1) - i32::from(a.1),
+        )
+    };
+
+    let mut waypoints = Vec::new();
+    let mut last = start;
+    let mut prev = start;
+    for (i, &tile) in path.iter().enumerate() {
+        let turns = path
+            .get(i + 1)
+            .is_none_or(|&next| step(prev, tile) != step(tile, next));
+        if turns || span(last, tile) >= MAX_WAYPOINT_SPAN {
+            waypoints.push(tile);
+            last = tile;
+        }
+        prev = tile;
+    }
+    waypoints
+}
+
+/// Decodes a `CmdWaypoints` payload (the packet without its opcode byte).
+///
+/// # Arguments
+///
+/// * `payload` - At least 13 bytes: the count byte and three waypoints.
+///
+/// # Returns
+///
+/// * `Some((append, waypoints))`, or `None` for a malformed count.
+pub fn decode_waypoints(payload: &[u8]) -> Option<(bool, Vec<(u16, u16)>)> {
+    let flags = *payload.first()?;
+    let count = usize::from(flags & !WAYPOINTS_APPEND);
+    if count == 0 || count > WAYPOINTS_PER_PACKET || payload.len() < 1 + count * 4 {
+        return None;
+    }
+    let waypoints = payload[1..1 + count * 4]
+        .chunks_exact(4)
+        .map(|c| {
+            (
+                u16::from_le_bytes([c[0], c[1]]),
+                u16::from_le_bytes([c[2], c[3]]),
+            )
+        })
+        .collect();
+    Some((flags & WAYPOINTS_APPEND != 0, waypoints))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waypoints_keep_turns_and_split_long_runs() {
+        // Ten steps east, then two south.
+        let mut path: Vec<(u16, u16)> = (11..=20).map(|x| (x, 10)).collect();
+        path.extend([(20, 11), (20, 12)]);
+
+        let waypoints = path_to_waypoints((10, 10), &path);
+        assert_eq!(waypoints, vec![(18, 10), (20, 10), (20, 12)]);
+        assert!(path_to_waypoints((10, 10), &[]).is_empty());
+    }
+
+    #[test]
+    fn decode_rejects_bad_counts() {
+        let mut payload = [0u8; 15];
+        assert_eq!(decode_waypoints(&payload), None);
+        payload[0] = 4;
+        assert_eq!(decode_waypoints(&payload), None);
+
+        payload[0] = 1 | WAYPOINTS_APPEND;
+        payload[1..5].copy_from_slice(&[7, 0, 9, 0]);
+        assert_eq!(decode_waypoints(&payload), Some((true, vec![(7, 9)])));
+    }
+}
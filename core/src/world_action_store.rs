@@ -66,6 +66,16 @@ pub enum WorldActionKind {
     },
     /// Reset all character and item templates.
     ResetAll,
+    /// Disconnect the online player controlling the named character.
+    KickPlayer {
+        /// Character name, matched case-insensitively.
+        name: String,
+    },
+    /// Send an announcement to every online player.
+    Broadcast {
+        /// Message text.
+        message: String,
+    },
 }
 
 impl WorldActionKind {
@@ -83,8 +93,22 @@ impl WorldActionKind {
             Self::ResetChar { .. } => "reset_char",
             Self::ResetItem { .. } => "reset_item",
             Self::ResetAll => "reset_all",
+            Self::KickPlayer { .. } => "kick_player",
+            Self::Broadcast { .. } => "broadcast",
         }
     }
+
+    /// Whether the action changes persisted world state.
+    ///
+    /// Session actions (kick, broadcast) return `false`, so the server skips
+    /// the save flush and full save it performs around world mutations.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the world must be saved after the action.
+    pub fn mutates_world(&self) -> bool {
+        !matches!(self, Self::KickPlayer { .. } | Self::Broadcast { .. })
+    }
 }
 
 /// Request queued by the admin API for the server to execute.
@@ -213,6 +237,37 @@ mod tests {
             "reset_item"
         );
         assert_eq!(WorldActionKind::ResetAll.name(), "reset_all");
+        assert_eq!(
+            WorldActionKind::KickPlayer {
+                name: "Ishtar".to_owned()
+            }
+            .name(),
+            "kick_player"
+        );
+        assert_eq!(
+            WorldActionKind::Broadcast {
+                message: "Restart in 5 minutes".to_owned()
+            }
+            .name(),
+            "broadcast"
+        );
+    }
+
+    #[test]
+    fn session_actions_do_not_mutate_world() {
+        assert!(WorldActionKind::ResetAll.mutates_world());
+        assert!(
+            !WorldActionKind::KickPlayer {
+                name: "Ishtar".to_owned()
+            }
+            .mutates_world()
+        );
+        assert!(
+            !WorldActionKind::Broadcast {
+                message: "hello".to_owned()
+            }
+            .mutates_world()
+        );
     }
 
     #[test]
@@ -0,0 +1,170 @@
+//! Random combat target tracking.
+//!
+//! The server only sends `SV_SETMAP` fields that changed since the last
+//! update and shifts its copy of the client's view with `SV_SCROLL_*`
+//! whenever the player moves one tile. [`NearbyCharacters`] mirrors just the
+//! character number of every visible tile the same way, so a bot always
+//! knows which characters currently stand around it without keeping a full
+//! client-side map.
+
+use mag_core::constants::{TILEX, TILEY};
+use mag_core::server_commands::ServerCommandType;
+use rand::Rng;
+
+/// Flat index of the tile the bot itself stands on.
+const CENTER_TILE: usize = (TILEY / 2) * TILEX + TILEX / 2;
+
+/// Character numbers of the tiles in a bot's view.
+pub struct NearbyCharacters {
+    /// `ch_nr` per view tile, row-major; `0` for an empty tile.
+    tiles: Vec<u16>,
+    /// Tile index of the most recent `SV_SETMAP`, for delta offsets.
+    last_setmap_index: Option<u16>,
+}
+
+impl NearbyCharacters {
+    /// Creates an empty view.
+    pub fn new() -> Self {
+        Self {
+            tiles: vec![0; TILEX * TILEY],
+            last_setmap_index: None,
+        }
+    }
+
+    /// Applies the tile addressing and character number of one `SV_SETMAP`.
+    ///
+    /// Mirrors the client's `apply_set_map`: `off == 0` addresses
+    /// `absolute_tile_index`, otherwise the tile `off` past the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `off` - Delta from the previous `SV_SETMAP` tile (0 = absolute).
+    /// * `absolute_tile_index` - Tile index used when `off` is 0.
+    /// * `ch_nr` - New character number of the tile, if it changed.
+    pub fn apply_set_map(&mut self, off: u8, absolute_tile_index: Option<u16>, ch_nr: Option<u16>) {
+        let next_index = if off == 0 {
+            absolute_tile_index
+        } else {
+            let base = self.last_setmap_index.map(i32::from).unwrap_or(-1);
+            u16::try_from(base + i32::from(off)).ok()
+        };
+        let Some(tile_index) = next_index else {
+            return;
+        };
+        if usize::from(tile_index) >= self.tiles.len() {
+            return;
+        }
+        self.last_setmap_index = Some(tile_index);
+        if let Some(nr) = ch_nr {
+            self.tiles[usize::from(tile_index)] = nr;
+        }
+    }
+
+    /// Shifts the view for an `SV_SCROLL_*` command, exactly like the
+    /// server shifts its copy.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - Command type; anything but a scroll is ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if `header` was a scroll command.
+    pub fn scroll(&mut self, header: ServerCommandType) -> bool {
+        let len = self.tiles.len();
+        match header {
+            ServerCommandType::ScrollRight => self.tiles.copy_within(1..len, 0),
+            ServerCommandType::ScrollLeft => self.tiles.copy_within(0..len - 1, 1),
+            ServerCommandType::ScrollDown => self.tiles.copy_within(TILEX..len, 0),
+            ServerCommandType::ScrollUp => self.tiles.copy_within(0..len - TILEX, TILEX),
+            ServerCommandType::ScrollLeftUp => {
+                self.tiles.copy_within(0..len - TILEX - 1, TILEX + 1);
+            }
+            ServerCommandType::ScrollLeftDown => self.tiles.copy_within(TILEX - 1..len, 0),
+            ServerCommandType::ScrollRightUp => {
+                self.tiles.copy_within(0..len - TILEX + 1, TILEX - 1);
+            }
+            ServerCommandType::ScrollRightDown => self.tiles.copy_within(TILEX + 1..len, 0),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Character numbers within `radius` tiles of the bot, excluding the
+    /// bot itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `radius` - Maximum distance on either axis.
+    ///
+    /// # Returns
+    ///
+    /// * Candidate targets, in view order.
+    pub fn targets(&self, radius: i16) -> Vec<u16> {
+        let own = self.tiles[CENTER_TILE];
+        let (cx, cy) = ((TILEX / 2) as i32, (TILEY / 2) as i32);
+        let radius = i32::from(radius.max(0));
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|&(i, &nr)| {
+                let (x, y) = ((i % TILEX) as i32, (i / TILEX) as i32);
+                nr != 0 && nr != own && (x - cx).abs() <= radius && (y - cy).abs() <= radius
+            })
+            .map(|(_, &nr)| nr)
+            .collect()
+    }
+
+    /// Picks a random target within `radius` tiles.
+    ///
+    /// # Arguments
+    ///
+    /// * `radius` - Maximum distance on either axis.
+    /// * `rng` - RNG used for the choice.
+    ///
+    /// # Returns
+    ///
+    /// * A character number, or `None` if nobody is close enough.
+    pub fn pick_target<R: Rng>(&self, radius: i16, rng: &mut R) -> Option<u16> {
+        let targets = self.targets(radius);
+        if targets.is_empty() {
+            None
+        } else {
+            Some(targets[rng.gen_range(0..targets.len())])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn targets_skip_self_and_distant_tiles() {
+        let mut view = NearbyCharacters::new();
+        view.apply_set_map(0, Some(CENTER_TILE as u16), Some(7));
+        // Two tiles east of the bot, then (via a delta) three east.
+        view.apply_set_map(2, None, Some(42));
+        view.apply_set_map(1, None, Some(43));
+        view.apply_set_map(0, Some(0), Some(99));
+
+        assert_eq!(view.targets(2), vec![42]);
+        assert_eq!(view.targets(3), vec![42, 43]);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(view.pick_target(2, &mut rng), Some(42));
+        assert_eq!(view.pick_target(1, &mut rng), None);
+    }
+
+    #[test]
+    fn scrolling_moves_characters_with_the_view() {
+        let mut view = NearbyCharacters::new();
+        view.apply_set_map(0, Some((CENTER_TILE + 1) as u16), Some(42));
+        // The bot stepped east: the neighbour is now under it.
+        assert!(view.scroll(ServerCommandType::ScrollRight));
+        assert_eq!(view.tiles[CENTER_TILE], 42);
+        assert!(!view.scroll(ServerCommandType::SetOrigin));
+    }
+}
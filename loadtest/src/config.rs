@@ -16,6 +16,8 @@ pub struct LoadTestConfig {
     pub run: RunConfig,
     /// Movement simulation parameters.
     pub movement: MovementConfig,
+    /// Random combat simulation parameters.
+    pub combat: CombatConfig,
     /// Network impairment simulation parameters.
     pub impairment: ImpairmentConfig,
     /// CL_PING keepalive settings.
@@ -122,6 +124,32 @@ impl Default for MovementConfig {
     }
 }
 
+/// Per-client random combat parameters.
+///
+/// Bots remember the characters they saw near them in recent map updates and
+/// periodically attack one at random, so the run exercises the combat,
+/// death and respawn paths as well as movement.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct CombatConfig {
+    /// Enable random attacks.
+    pub enabled: bool,
+    /// Milliseconds between attack attempts.
+    pub interval_ms: u64,
+    /// Maximum tile distance from the bot for a character to be a target.
+    pub radius: i16,
+}
+
+impl Default for CombatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 3000,
+            radius: 6,
+        }
+    }
+}
+
 /// App-level network impairment parameters.
 ///
 /// Applied to outgoing movement and ping commands.  CTick keepalive packets
@@ -286,6 +314,18 @@ mod tests {
         assert!(cfg.movement.enable_dispersion);
     }
 
+    #[test]
+    fn combat_section_parses() {
+        let cfg: LoadTestConfig = toml::from_str("").unwrap();
+        assert!(!cfg.combat.enabled);
+
+        let cfg: LoadTestConfig =
+            toml::from_str("[combat]\nenabled = true\ninterval_ms = 1000\n").unwrap();
+        assert!(cfg.combat.enabled);
+        assert_eq!(cfg.combat.interval_ms, 1000);
+        assert_eq!(cfg.combat.radius, 6);
+    }
+
     #[test]
     fn sex_parsing() {
         let mut a = AccountConfig::default();
@@ -8,9 +8,15 @@
 //! ```text
 //! mag-loadtest --config loadtest.toml
 //! mag-loadtest --config loadtest.toml --clients 50 --duration 120
+//! mag-loadtest --clients 200 --summary runs/200.json --label main
 //! ```
+//!
+//! Summaries from several runs can be compared, and checked against the
+//! limits in `slo.json`, with
+//! `mag-admin --auto loadtest-report --slo loadtest/slo.json runs/*.json`.
 
 mod api_bootstrap;
+mod combat;
 mod config;
 mod login_gate;
 mod metrics;
@@ -78,6 +84,18 @@ struct Cli {
     /// Override: enable one-shot login dispersion (god password + `/goto`).
     #[arg(long)]
     enable_dispersion: Option<bool>,
+
+    /// Override: enable random attacks on nearby characters.
+    #[arg(long)]
+    enable_combat: Option<bool>,
+
+    /// Write a JSON run summary to this path for `loadtest-report`.
+    #[arg(long)]
+    summary: Option<String>,
+
+    /// Name recorded in the run summary; defaults to the client count.
+    #[arg(long)]
+    label: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -131,6 +149,17 @@ async fn main() -> anyhow::Result<()> {
         config.impairment.jitter_ms,
         config.impairment.drop_pct * 100.0,
     );
+    log::info!(
+        "  Combat: {}",
+        if config.combat.enabled {
+            format!(
+                "radius={} interval={}ms",
+                config.combat.radius, config.combat.interval_ms
+            )
+        } else {
+            "disabled".to_owned()
+        },
+    );
     log::info!(
         "  Dispersion: {}",
         if config.movement.enable_dispersion {
@@ -219,6 +248,14 @@ async fn main() -> anyhow::Result<()> {
     // Print the final report.
     metrics.print_final(start.elapsed());
 
+    if let Some(path) = &cli.summary {
+        let label = cli
+            .label
+            .clone()
+            .unwrap_or_else(|| format!("{} clients", num));
+        write_summary(path, &metrics.summary(&label, num, start.elapsed()))?;
+    }
+
     Ok(())
 }
 
@@ -287,6 +324,27 @@ fn apply_overrides(config: &mut LoadTestConfig, cli: &Cli) {
     if let Some(d) = cli.enable_dispersion {
         config.movement.enable_dispersion = d;
     }
+    if let Some(c) = cli.enable_combat {
+        config.combat.enabled = c;
+    }
+}
+
+/// Writes the JSON run summary.
+///
+/// # Arguments
+///
+/// * `path` - File-system path of the summary file.
+/// * `summary` - Outcome of the run.
+///
+/// # Returns
+///
+/// * `Err` if the file cannot be written.
+fn write_summary(path: &str, summary: &metrics::RunSummary) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(summary)?;
+    std::fs::write(path, json)
+        .map_err(|e| anyhow::anyhow!("Cannot write summary '{path}': {e}"))?;
+    log::info!("Wrote run summary to '{path}'");
+    Ok(())
 }
 
 /// Resolves the god password required for login dispersion.
@@ -343,6 +401,9 @@ mod tests {
             api_url: None,
             api_rps: Some(2),
             enable_dispersion: Some(true),
+            enable_combat: Some(true),
+            summary: None,
+            label: None,
         };
         apply_overrides(&mut cfg, &cli);
         assert_eq!(cfg.run.num_clients, 99);
@@ -352,6 +413,7 @@ mod tests {
         assert_eq!(cfg.server.port, 5556);
         assert_eq!(cfg.api.requests_per_second, 2);
         assert!(cfg.movement.enable_dispersion);
+        assert!(cfg.combat.enabled);
     }
 
     #[test]
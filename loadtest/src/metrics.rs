@@ -6,6 +6,8 @@ use std::sync::{
 };
 use std::time::Duration;
 
+use serde::Serialize;
+
 /// Thread-safe counters and samples collected during a load-test run.
 pub struct Metrics {
     /// Clients that successfully completed the game handshake.
@@ -38,6 +40,8 @@ pub struct Metrics {
     pub commands_sent: AtomicU64,
     /// Total periodic slash-commands that failed to send.
     pub commands_errors: AtomicU64,
+    /// Total `CL_CMD_ATTACK` packets sent by random combat.
+    pub attacks_sent: AtomicU64,
     /// Collected RTT samples in milliseconds, from CL_PING / SV_PONG exchanges.
     pub rtt_samples: Mutex<Vec<u32>>,
 }
@@ -63,6 +67,7 @@ impl Metrics {
             dispersion_errors: AtomicU64::new(0),
             commands_sent: AtomicU64::new(0),
             commands_errors: AtomicU64::new(0),
+            attacks_sent: AtomicU64::new(0),
             rtt_samples: Mutex::new(Vec::new()),
         }
     }
@@ -188,6 +193,12 @@ impl Metrics {
             println!("  Errors:        {commands_errors}");
         }
 
+        let attacks_sent = self.attacks_sent.load(Ordering::Relaxed);
+        if attacks_sent > 0 {
+            println!("--- Combat ---");
+            println!("  Attacks sent:  {attacks_sent}");
+        }
+
         // RTT stats
         if let Ok(samples) = self.rtt_samples.lock() {
             if samples.is_empty() {
@@ -210,6 +221,65 @@ impl Metrics {
     }
 }
 
+/// Machine-readable outcome of one run, written as JSON with `--summary` for
+/// `loadtest-report` to compare against other runs.
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    /// Free-form name of the run, e.g. a branch or commit.
+    pub label: String,
+    /// Configured number of bot clients.
+    pub clients: usize,
+    /// Wall-clock duration of the run in seconds.
+    pub duration_secs: f64,
+    /// Clients that completed the game handshake.
+    pub connected: u64,
+    /// Clients that failed to connect or bootstrap.
+    pub connect_errors: u64,
+    /// Total inbound bytes across all clients.
+    pub bytes_in: u64,
+    /// Total outbound bytes across all clients.
+    pub bytes_out: u64,
+    /// Total tick packets received across all clients.
+    pub ticks_total: u64,
+    /// Sum of per-client connected durations in milliseconds.
+    pub client_connected_ms: u64,
+    /// Inter-tick gaps over 100 ms.
+    pub late_gaps: u64,
+    /// Every RTT sample in milliseconds, sorted ascending.
+    pub rtt_ms: Vec<u32>,
+}
+
+impl Metrics {
+    /// Snapshots the counters for the JSON run summary.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Name of the run.
+    /// * `clients` - Configured number of bot clients.
+    /// * `elapsed` - Total wall-clock duration of the test.
+    pub fn summary(&self, label: &str, clients: usize, elapsed: Duration) -> RunSummary {
+        let mut rtt_ms = self
+            .rtt_samples
+            .lock()
+            .map(|v| v.clone())
+            .unwrap_or_default();
+        rtt_ms.sort_unstable();
+        RunSummary {
+            label: label.to_owned(),
+            clients,
+            duration_secs: elapsed.as_secs_f64(),
+            connected: self.connected.load(Ordering::Relaxed),
+            connect_errors: self.connect_errors.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            ticks_total: self.ticks_total.load(Ordering::Relaxed),
+            client_connected_ms: self.total_client_connected_ms.load(Ordering::Relaxed),
+            late_gaps: self.tick_gap_late.load(Ordering::Relaxed),
+            rtt_ms,
+        }
+    }
+}
+
 impl Default for Metrics {
     fn default() -> Self {
         Self::new()
@@ -246,6 +316,21 @@ mod tests {
         assert_eq!(sorted[9], 100);
     }
 
+    #[test]
+    fn summary_sorts_rtt_samples() {
+        let m = Metrics::new();
+        for v in [30, 10, 20] {
+            m.push_rtt(v);
+        }
+        m.bytes_in.store(4096, Ordering::Relaxed);
+        let summary = m.summary("baseline", 50, Duration::from_secs(60));
+        assert_eq!(summary.rtt_ms, vec![10, 20, 30]);
+        assert_eq!(summary.clients, 50);
+        assert_eq!(summary.bytes_in, 4096);
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(json.contains("\"label\":\"baseline\""));
+    }
+
     #[test]
     fn human_bytes_formatting() {
         assert_eq!(human_bytes(512), "512B");
@@ -143,7 +143,7 @@ impl GameStream {
     /// * `Ok(())` on successful login.
     /// * `Err` if the server rejects the login or sends an unexpected packet.
     pub async fn handshake(&mut self, ticket: u64) -> anyhow::Result<()> {
-        let cmd = ClientCommand::new_api_login(ticket);
+        let cmd = ClientCommand::new_api_login(ticket, 0);
         self.inner
             .write_all(&cmd.to_bytes())
             .await
@@ -23,6 +23,9 @@
 //!    - Optionally send `CL_PING` every `ping.interval_secs` seconds.
 //!    - Send each configured `[[commands]]` entry (e.g. `/rank`, `/who`) on its
 //!      own `interval_secs` (see [`maybe_send_commands`]).
+//!    - If `combat.enabled` is set, attack a random character within
+//!      `combat.radius` tiles every `combat.interval_ms` milliseconds, using the
+//!      characters tracked from `SV_SETMAP`/`SV_SCROLL_*` (see [`NearbyCharacters`]).
 //! 7. On shutdown, the task exits and bumps the disconnect counter.
 
 use std::collections::HashMap;
@@ -41,6 +44,7 @@ use tokio::sync::broadcast;
 use tokio::time::{Duration, MissedTickBehavior, interval};
 
 use crate::api_bootstrap::{RateLimiter, bootstrap_client, mint_ticket};
+use crate::combat::NearbyCharacters;
 use crate::config::LoadTestConfig;
 use crate::login_gate::LoginGate;
 use crate::metrics::Metrics;
@@ -76,6 +80,8 @@ struct ClientState {
     /// Whether the one-shot login dispersion sequence (god password + `/goto`)
     /// has already been sent for this client.
     dispersion_done: bool,
+    /// Characters currently visible around the bot, for random combat.
+    nearby: NearbyCharacters,
 }
 
 impl ClientState {
@@ -90,6 +96,7 @@ impl ClientState {
             last_tick_instant: None,
             start: Instant::now(),
             dispersion_done: false,
+            nearby: NearbyCharacters::new(),
         }
     }
 }
@@ -280,6 +287,9 @@ async fn game_loop(
     let mut command_timer = interval(Duration::from_millis(100));
     command_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
+    let mut combat_timer = interval(Duration::from_millis(config.combat.interval_ms.max(1)));
+    combat_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
     'main: loop {
         // Precompute flag so the borrow checker is happy inside select!
         let has_position = state.self_x.is_some();
@@ -388,6 +398,24 @@ async fn game_loop(
                 .await;
             }
 
+            // Random combat
+            _ = combat_timer.tick(), if has_position && config.combat.enabled => {
+                if let Some(target) = state.nearby.pick_target(config.combat.radius, &mut rng) {
+                    let cmd = ClientCommand::new_attack(u32::from(target));
+                    send_impaired(
+                        index,
+                        &mut write_half,
+                        cmd,
+                        &config,
+                        &mut rng,
+                        &metrics,
+                    )
+                    .await;
+                    metrics.attacks_sent.fetch_add(1, Ordering::Relaxed);
+                    log::trace!("Client {index}: attacking character {target}");
+                }
+            }
+
             // Periodic slash-commands (`[[commands]]`)
             _ = command_timer.tick(), if has_position && !config.commands.is_empty() => {
                 maybe_send_commands(
@@ -415,6 +443,9 @@ fn process_command(index: usize, cmd_bytes: &[u8], state: &mut ClientState, metr
     let Some(cmd) = ServerCommand::from_bytes(cmd_bytes) else {
         return;
     };
+    if state.nearby.scroll(cmd.header) {
+        return;
+    }
 
     match cmd.structured_data {
         ServerCommandData::SetOrigin { x, y } => {
@@ -422,6 +453,14 @@ fn process_command(index: usize, cmd_bytes: &[u8], state: &mut ClientState, metr
             state.self_x = Some(x.wrapping_add(TILEX as i16 / 2));
             state.self_y = Some(y.wrapping_add(TILEY as i16 / 2));
         }
+        ServerCommandData::SetMap {
+            off,
+            absolute_tile_index,
+            ch_nr,
+            ..
+        } => {
+            state.nearby.apply_set_map(off, absolute_tile_index, ch_nr);
+        }
         ServerCommandData::Pong { seq, .. } => {
             if let Some(sent_at) = state.ping_times.remove(&seq) {
                 let rtt_ms = sent_at.elapsed().as_millis() as u32;
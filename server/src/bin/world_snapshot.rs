@@ -12,6 +12,18 @@
 //!
 //! # Inspect a snapshot without touching KeyDB
 //! world_snapshot verify --input world_seed.wsnap
+//!
+//! # Re-apply a MAG_JOURNAL_PATH journal onto a snapshot
+//! world_snapshot replay --input start.wsnap --journal world.mjl \
+//!     [--output replayed.wsnap] [--until-tick <n>] [--trace-item <n>]
+//!
+//! # Record the view around a character for the client's replay viewer
+//! world_snapshot export-replay --input start.wsnap --journal world.mjl \
+//!     --follow <n> --output session.mgrp [--until-tick <n>]
+//!
+//! # Follow a character's gold through a journal
+//! world_snapshot trace-gold --journal world.mjl --character <n> \
+//!     [--since <unix secs>] [--until <unix secs>]
 //! ```
 //!
 //! The resulting `.wsnap` file is a single `bincode`-encoded
@@ -26,6 +38,7 @@ use std::time::Instant;
 
 use redis::Commands;
 
+use server::journal::{self, JournalRecord, gold, replay};
 use server::keydb::connection as keydb;
 use server::keydb::snapshot::{SNAPSHOT_SCHEMA_VERSION, WorldSnapshot};
 use server::keydb::store;
@@ -47,6 +60,26 @@ enum Command {
     Verify {
         input: PathBuf,
     },
+    Replay {
+        input: PathBuf,
+        journal: PathBuf,
+        output: Option<PathBuf>,
+        until_tick: Option<i32>,
+        trace_item: Option<u32>,
+    },
+    ExportReplay {
+        input: PathBuf,
+        journal: PathBuf,
+        output: PathBuf,
+        follow: u32,
+        until_tick: Option<i32>,
+    },
+    TraceGold {
+        journal: PathBuf,
+        character: u32,
+        since: Option<i64>,
+        until: Option<i64>,
+    },
 }
 
 /// Parse `std::env::args` into a [`Command`].
@@ -65,6 +98,12 @@ fn parse_args() -> Command {
          \n  {prog} export  --output <file.wsnap>\
          \n  {prog} import  --input  <file.wsnap> [--skip-if-seeded] [--force]\
          \n  {prog} verify  --input  <file.wsnap>\
+         \n  {prog} replay  --input  <file.wsnap> --journal <file.mjl> [--output <file.wsnap>]\
+         \n                 [--until-tick <n>] [--trace-item <n>]\
+         \n  {prog} export-replay --input <file.wsnap> --journal <file.mjl> --follow <n>\
+         \n                 --output <file.mgrp> [--until-tick <n>]\
+         \n  {prog} trace-gold --journal <file.mjl> --character <n>\
+         \n                 [--since <unix secs>] [--until <unix secs>]\
          \n\nEnv vars:\
          \n  MAG_KEYDB_URL   — KeyDB connection URL (default: redis://127.0.0.1:5556/)\
          \n  KEYDB_PASSWORD  — password, if MAG_KEYDB_URL is not set\
@@ -105,6 +144,88 @@ fn parse_args() -> Command {
                 input: PathBuf::from(input),
             }
         }
+        "replay" => {
+            let (Some(input), Some(journal)) =
+                (flag_value(&args, "--input"), flag_value(&args, "--journal"))
+            else {
+                eprintln!(
+                    "Error: --input <file> and --journal <file> are required for 'replay'.\n\n{usage}"
+                );
+                process::exit(1);
+            };
+            let number = |flag: &str| {
+                flag_value(&args, flag).map(|value| {
+                    value.parse().unwrap_or_else(|_| {
+                        eprintln!("Error: {flag} expects a number, got {value:?}.\n\n{usage}");
+                        process::exit(1);
+                    })
+                })
+            };
+            Command::Replay {
+                input: PathBuf::from(input),
+                journal: PathBuf::from(journal),
+                output: flag_value(&args, "--output").map(PathBuf::from),
+                until_tick: number("--until-tick").map(|n: u32| n as i32),
+                trace_item: number("--trace-item"),
+            }
+        }
+        "export-replay" => {
+            let (Some(input), Some(journal), Some(output)) = (
+                flag_value(&args, "--input"),
+                flag_value(&args, "--journal"),
+                flag_value(&args, "--output"),
+            ) else {
+                eprintln!(
+                    "Error: --input, --journal and --output are required for 'export-replay'.\n\n{usage}"
+                );
+                process::exit(1);
+            };
+            let number = |flag: &str| {
+                flag_value(&args, flag).map(|value| {
+                    value.parse().unwrap_or_else(|_| {
+                        eprintln!("Error: {flag} expects a number, got {value:?}.\n\n{usage}");
+                        process::exit(1);
+                    })
+                })
+            };
+            let follow = number("--follow").unwrap_or_else(|| {
+                eprintln!("Error: --follow <n> is required for 'export-replay'.\n\n{usage}");
+                process::exit(1);
+            });
+            Command::ExportReplay {
+                input: PathBuf::from(input),
+                journal: PathBuf::from(journal),
+                output: PathBuf::from(output),
+                follow,
+                until_tick: number("--until-tick").map(|n: u32| n as i32),
+            }
+        }
+        "trace-gold" => {
+            let Some(journal) = flag_value(&args, "--journal") else {
+                eprintln!("Error: --journal <file> is required for 'trace-gold'.\n\n{usage}");
+                process::exit(1);
+            };
+            let number = |flag: &str| {
+                flag_value(&args, flag).map(|value| {
+                    value.parse().unwrap_or_else(|_| {
+                        eprintln!("Error: {flag} expects a number, got {value:?}.\n\n{usage}");
+                        process::exit(1);
+                    })
+                })
+            };
+            let character = flag_value(&args, "--character")
+                .and_then(|value| value.parse::<u32>().ok())
+                .unwrap_or_else(|| {
+                    eprintln!("Error: --character <n> is required for 'trace-gold'.\n\n{usage}");
+                    process::exit(1);
+                });
+            Command::TraceGold {
+                journal: PathBuf::from(journal),
+                character,
+                since: number("--since"),
+                until: number("--until"),
+            }
+        }
         _ => {
             eprintln!("Error: unknown sub-command {:?}.\n\n{usage}", sub);
             process::exit(1);
@@ -371,6 +492,193 @@ fn cmd_verify(input: &Path) {
     }
 }
 
+/// Re-apply a world journal onto a snapshot.
+///
+/// Loads the snapshot and journal, optionally prints every record touching
+/// `trace_item`, applies the records up to `until_tick` and writes the
+/// result to `output` when given.
+///
+/// # Arguments
+///
+/// * `input`      - Snapshot taken at or before the journal's first tick.
+/// * `journal`    - Journal file written via `MAG_JOURNAL_PATH`.
+/// * `output`     - Optional destination for the replayed snapshot.
+/// * `until_tick` - Last tick to apply; `None` applies the whole journal.
+/// * `trace_item` - Item slot whose history should be printed.
+fn cmd_replay(
+    input: &Path,
+    journal: &Path,
+    output: Option<&Path>,
+    until_tick: Option<i32>,
+    trace_item: Option<u32>,
+) {
+    println!("Reading snapshot from {}...", input.display());
+    let mut snapshot = WorldSnapshot::from_file(input).unwrap_or_else(|e| {
+        eprintln!("Failed to read snapshot: {e}");
+        process::exit(1);
+    });
+    println!("Reading journal from {}...", journal.display());
+    let records = journal::read_journal(journal).unwrap_or_else(|e| {
+        eprintln!("Failed to read journal: {e}");
+        process::exit(1);
+    });
+
+    if let Some(item) = trace_item {
+        println!("\nHistory of item {item}:");
+        let mut tick = None;
+        for record in &records {
+            if let JournalRecord::Tick { tick: t, .. } = record {
+                if until_tick.is_some_and(|limit| *t > limit) {
+                    break;
+                }
+                tick = Some(*t);
+            } else if record.mentions_item(item) {
+                println!(
+                    "  tick {:>10}: {}",
+                    tick.unwrap_or_default(),
+                    describe(record)
+                );
+            }
+        }
+        println!();
+    }
+
+    let stats = replay::apply(&mut snapshot, &records, until_tick);
+    println!(
+        "Applied {} ticks ({} character and {} item records, {} skipped), last tick {}.",
+        stats.ticks,
+        stats.characters,
+        stats.items,
+        stats.skipped,
+        stats
+            .last_tick
+            .map_or_else(|| "none".to_owned(), |t| t.to_string()),
+    );
+
+    if let Some(output) = output {
+        println!("Writing replayed snapshot to {}...", output.display());
+        snapshot.to_file(output).unwrap_or_else(|e| {
+            eprintln!("Failed to write snapshot: {e}");
+            process::exit(1);
+        });
+    }
+}
+
+/// Record the view around a character for the client's replay viewer.
+///
+/// # Arguments
+///
+/// * `input`      - Snapshot taken at or before the journal's first tick.
+/// * `journal`    - Journal file written via `MAG_JOURNAL_PATH`.
+/// * `output`     - Destination for the `.mgrp` recording.
+/// * `follow`     - Character slot the camera follows.
+/// * `until_tick` - Last tick to record; `None` records the whole journal.
+fn cmd_export_replay(
+    input: &Path,
+    journal: &Path,
+    output: &Path,
+    follow: u32,
+    until_tick: Option<i32>,
+) {
+    println!("Reading snapshot from {}...", input.display());
+    let mut snapshot = WorldSnapshot::from_file(input).unwrap_or_else(|e| {
+        eprintln!("Failed to read snapshot: {e}");
+        process::exit(1);
+    });
+    println!("Reading journal from {}...", journal.display());
+    let records = journal::read_journal(journal).unwrap_or_else(|e| {
+        eprintln!("Failed to read journal: {e}");
+        process::exit(1);
+    });
+
+    let recording =
+        replay::record(&mut snapshot, &records, follow, until_tick).unwrap_or_else(|e| {
+            eprintln!("Failed to record replay: {e}");
+            process::exit(1);
+        });
+    println!(
+        "Recorded {} frames following character {follow}.",
+        recording.frames.len()
+    );
+
+    println!("Writing replay to {}...", output.display());
+    std::fs::write(output, recording.to_bytes()).unwrap_or_else(|e| {
+        eprintln!("Failed to write replay: {e}");
+        process::exit(1);
+    });
+}
+
+/// Print every gold transfer of a character and who the gold came from and
+/// went to.
+///
+/// # Arguments
+///
+/// * `journal`   - Journal file written via `MAG_JOURNAL_PATH`.
+/// * `character` - Character slot to trace.
+/// * `since`     - First unix second to include.
+/// * `until`     - Last unix second to include.
+fn cmd_trace_gold(journal: &Path, character: u32, since: Option<i64>, until: Option<i64>) {
+    println!("Reading journal from {}...", journal.display());
+    let records = journal::read_journal(journal).unwrap_or_else(|e| {
+        eprintln!("Failed to read journal: {e}");
+        process::exit(1);
+    });
+
+    let trace = gold::trace_gold(&records, character, since, until);
+    println!("\nGold transfers of character {character}:");
+    for t in &trace.transfers {
+        println!(
+            "  tick {:>10} ({}): {:>5} -> {:<5} {:>12} {:?}",
+            t.tick,
+            t.unix_secs,
+            t.from,
+            t.to,
+            format_silver(i64::from(t.amount)),
+            t.kind
+        );
+    }
+
+    println!("\nBy counterparty (0 is the world):");
+    for flow in &trace.flows {
+        println!(
+            "  {:>5}: received {:>12}, sent {:>12}, net {:>12} in {} transfer(s)",
+            flow.counterparty,
+            format_silver(flow.received),
+            format_silver(flow.sent),
+            format_silver(flow.received - flow.sent),
+            flow.transfers
+        );
+    }
+}
+
+/// Format an amount of silver as `xG yS`.
+fn format_silver(silver: i64) -> String {
+    let sign = if silver < 0 { "-" } else { "" };
+    format!("{sign}{}G {}S", silver.abs() / 100, silver.abs() % 100)
+}
+
+/// One-line description of a journal record for `--trace-item`.
+fn describe(record: &JournalRecord) -> String {
+    match record {
+        JournalRecord::Tick { tick, .. } => format!("tick {tick}"),
+        JournalRecord::Item {
+            index, cause, item, ..
+        } => format!(
+            "item {index} {cause:?}: used={} temp={} carried={} pos=({}, {})",
+            item.used, item.temp, item.carried, item.x, item.y
+        ),
+        JournalRecord::Character {
+            index, x, y, citem, ..
+        } => format!("character {index} at ({x}, {y}) holds it (citem={citem})"),
+        JournalRecord::GoldTransfer {
+            from,
+            to,
+            amount,
+            kind,
+        } => format!("{kind:?} of {amount}S from {from} to {to}"),
+    }
+}
+
 // ---------------------------------------------------------------------------
 //  Entry point
 // ---------------------------------------------------------------------------
@@ -385,5 +693,25 @@ fn main() {
             force,
         } => cmd_import(&input, skip_if_seeded, force),
         Command::Verify { input } => cmd_verify(&input),
+        Command::Replay {
+            input,
+            journal,
+            output,
+            until_tick,
+            trace_item,
+        } => cmd_replay(&input, &journal, output.as_deref(), until_tick, trace_item),
+        Command::ExportReplay {
+            input,
+            journal,
+            output,
+            follow,
+            until_tick,
+        } => cmd_export_replay(&input, &journal, &output, follow, until_tick),
+        Command::TraceGold {
+            journal,
+            character,
+            since,
+            until,
+        } => cmd_trace_gold(&journal, character, since, until),
     }
 }
@@ -0,0 +1,363 @@
+//! Server settings from `server.toml`, overridable by environment
+//! variables.
+//!
+//! The file is read from `MAG_SERVER_CONFIG`, or `server.toml` in the
+//! working directory. Every setting is optional and a missing default file
+//! is not an error, so a server without one keeps the defaults:
+//!
+//! ```toml
+//! [network]
+//! bind_address = "0.0.0.0"
+//! port = 5555
+//!
+//! [keydb]
+//! url = "redis://127.0.0.1:5556/"
+//!
+//! [game]
+//! tick_rate = 20
+//! max_players = 250
+//!
+//! [log]
+//! level = "info"
+//! file = "server.log"
+//!
+//! [paths]
+//! script_dir = "server/scripts"
+//! dialogue_dir = "server/dialogue"
+//! ```
+//!
+//! An environment variable, when set and not empty, wins over the file so
+//! container deployments can keep configuring the server through the
+//! environment alone.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use core::constants::{MAXPLAYER, TICKS};
+
+/// Environment variable naming the configuration file.
+pub const CONFIG_PATH_ENV: &str = "MAG_SERVER_CONFIG";
+
+/// Configuration file read when `MAG_SERVER_CONFIG` is not set.
+pub const DEFAULT_CONFIG_PATH: &str = "server.toml";
+
+/// Overrides `network.bind_address`.
+pub const BIND_ADDRESS_ENV: &str = "MAG_BIND_ADDRESS";
+
+/// Overrides `network.port`.
+pub const PORT_ENV: &str = "MAG_PORT";
+
+/// Overrides `keydb.url`.
+pub const KEYDB_URL_ENV: &str = "MAG_KEYDB_URL";
+
+/// Overrides `game.tick_rate`.
+pub const TICK_RATE_ENV: &str = "MAG_TICK_RATE";
+
+/// Overrides `game.max_players`.
+pub const MAX_PLAYERS_ENV: &str = "MAG_MAX_PLAYERS";
+
+/// Overrides `log.level`.
+pub const LOG_LEVEL_ENV: &str = "MAG_LOG_LEVEL";
+
+/// Overrides `log.file`.
+pub const LOG_FILE_ENV: &str = "MAG_LOG_FILE";
+
+/// Highest accepted `game.tick_rate`.
+pub const MAX_TICK_RATE: u32 = 200;
+
+/// All server settings.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Game listener settings.
+    pub network: NetworkConfig,
+    /// KeyDB connection settings.
+    pub keydb: KeyDbConfig,
+    /// Tick pacing and capacity.
+    pub game: GameConfig,
+    /// Logger settings.
+    pub log: LogConfig,
+    /// Content directories.
+    pub paths: PathsConfig,
+}
+
+/// Game listener settings.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Address the game and WebSocket listeners bind to.
+    pub bind_address: String,
+    /// TCP port of the game listener.
+    pub port: u16,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: "0.0.0.0".into(),
+            port: 5555,
+        }
+    }
+}
+
+/// KeyDB connection settings.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyDbConfig {
+    /// Connection URL; `None` keeps the `KEYDB_PASSWORD` / localhost
+    /// fallback of `keydb::connection::keydb_url`.
+    pub url: Option<String>,
+}
+
+/// Tick pacing and capacity.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Game ticks run per real second. Game rules always count
+    /// [`TICKS`] ticks per game second, so any other rate makes the world
+    /// run faster or slower than real time; meant for test servers.
+    pub tick_rate: u32,
+    /// Most players connected at once, at most `MAXPLAYER - 1`.
+    pub max_players: usize,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            tick_rate: TICKS as u32,
+            max_players: MAXPLAYER - 1,
+        }
+    }
+}
+
+/// Logger settings.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// Level filter: `off`, `error`, `warn`, `info`, `debug` or `trace`.
+    pub level: String,
+    /// File the log is written to besides the console.
+    pub file: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".into(),
+            file: "server.log".into(),
+        }
+    }
+}
+
+/// Content directories.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    /// Rhai script directory; overridden by `MAG_SCRIPT_DIR`.
+    pub script_dir: Option<String>,
+    /// NPC dialogue directory; overridden by `MAG_DIALOGUE_DIR`.
+    pub dialogue_dir: Option<String>,
+}
+
+impl ServerConfig {
+    /// Loads the configuration file and applies the environment overrides.
+    ///
+    /// # Returns
+    ///
+    /// * The validated configuration, or an error when the file named by
+    ///   `MAG_SERVER_CONFIG` is missing, or the file or an override is
+    ///   malformed.
+    pub fn load() -> Result<Self, String> {
+        let explicit = env::var(CONFIG_PATH_ENV).ok().filter(|v| !v.is_empty());
+        let path = explicit.as_deref().unwrap_or(DEFAULT_CONFIG_PATH);
+        let mut config = if Path::new(path).exists() {
+            let text = fs::read_to_string(path).map_err(|e| format!("Read {}: {}", path, e))?;
+            Self::parse(&text).map_err(|e| format!("{}: {}", path, e))?
+        } else if explicit.is_some() {
+            return Err(format!(
+                "{} names {}, which does not exist",
+                CONFIG_PATH_ENV, path
+            ));
+        } else {
+            Self::default()
+        };
+        config.apply_env(|name| env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses the contents of a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - TOML text.
+    ///
+    /// # Returns
+    ///
+    /// * The configuration, or the parser's error for unknown keys and
+    ///   wrong types.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Replaces settings with the environment variables that are set.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookup` - Returns the value of an environment variable.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())`, or an error naming a variable that is not a number.
+    pub fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        let var = |name: &str| lookup(name).filter(|v| !v.trim().is_empty());
+
+        if let Some(value) = var(BIND_ADDRESS_ENV) {
+            self.network.bind_address = value;
+        }
+        if let Some(value) = var(PORT_ENV) {
+            self.network.port = number(PORT_ENV, value)?;
+        }
+        if let Some(value) = var(KEYDB_URL_ENV) {
+            self.keydb.url = Some(value);
+        }
+        if let Some(value) = var(TICK_RATE_ENV) {
+            self.game.tick_rate = number(TICK_RATE_ENV, value)?;
+        }
+        if let Some(value) = var(MAX_PLAYERS_ENV) {
+            self.game.max_players = number(MAX_PLAYERS_ENV, value)?;
+        }
+        if let Some(value) = var(LOG_LEVEL_ENV) {
+            self.log.level = value;
+        }
+        if let Some(value) = var(LOG_FILE_ENV) {
+            self.log.file = value;
+        }
+        if let Some(value) = var(crate::scripting::SCRIPT_DIR_ENV) {
+            self.paths.script_dir = Some(value);
+        }
+        if let Some(value) = var(crate::player::dialogue::DIALOGUE_DIR_ENV) {
+            self.paths.dialogue_dir = Some(value);
+        }
+        Ok(())
+    }
+
+    /// Checks ranges that the file format cannot express.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())`, or an error naming the first bad setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_TICK_RATE).contains(&self.game.tick_rate) {
+            return Err(format!(
+                "game.tick_rate {} is outside 1..={}",
+                self.game.tick_rate, MAX_TICK_RATE
+            ));
+        }
+        if !(1..MAXPLAYER).contains(&self.game.max_players) {
+            return Err(format!(
+                "game.max_players {} is outside 1..={}",
+                self.game.max_players,
+                MAXPLAYER - 1
+            ));
+        }
+        self.log_level()?;
+        Ok(())
+    }
+
+    /// The configured log level as a filter.
+    ///
+    /// # Returns
+    ///
+    /// * The filter, or an error for an unknown level name.
+    pub fn log_level(&self) -> Result<log::LevelFilter, String> {
+        self.log
+            .level
+            .trim()
+            .parse()
+            .map_err(|_| format!("log.level '{}' is not a log level", self.log.level))
+    }
+}
+
+/// Parses the numeric value of environment variable `name`.
+fn number<T: FromStr>(name: &str, value: String) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} '{}' is not a valid number", name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn empty_file_keeps_the_defaults() {
+        let config = ServerConfig::parse("").unwrap();
+        assert_eq!(config, ServerConfig::default());
+        assert_eq!(config.network.port, 5555);
+        assert_eq!(config.game.tick_rate, TICKS as u32);
+        assert_eq!(config.log_level(), Ok(log::LevelFilter::Info));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn file_settings_parse_and_unknown_keys_are_rejected() {
+        let config = ServerConfig::parse(
+            "[network]\nport = 6000\n[game]\nmax_players = 40\n[paths]\nscript_dir = \"s\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.network.port, 6000);
+        assert_eq!(config.network.bind_address, "0.0.0.0");
+        assert_eq!(config.game.max_players, 40);
+        assert_eq!(config.paths.script_dir.as_deref(), Some("s"));
+
+        assert!(ServerConfig::parse("[network]\nprot = 6000\n").is_err());
+        assert!(ServerConfig::parse("[network]\nport = \"x\"\n").is_err());
+    }
+
+    #[test]
+    fn environment_wins_over_the_file() {
+        let mut config =
+            ServerConfig::parse("[network]\nport = 6000\n[log]\nlevel = \"warn\"\n").unwrap();
+        let vars: HashMap<&str, &str> = [
+            (PORT_ENV, "7000"),
+            (KEYDB_URL_ENV, "redis://keydb:5556/"),
+            (LOG_LEVEL_ENV, ""),
+        ]
+        .into();
+        config
+            .apply_env(|name| vars.get(name).map(|v| v.to_string()))
+            .unwrap();
+        assert_eq!(config.network.port, 7000);
+        assert_eq!(config.keydb.url.as_deref(), Some("redis://keydb:5556/"));
+        assert_eq!(config.log.level, "warn");
+
+        let bad: HashMap<&str, &str> = [(MAX_PLAYERS_ENV, "lots")].into();
+        assert!(
+            config
+                .apply_env(|name| bad.get(name).map(|v| v.to_string()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn out_of_range_settings_fail_validation() {
+        let mut config = ServerConfig::default();
+        config.game.tick_rate = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = ServerConfig::default();
+        config.game.max_players = MAXPLAYER;
+        assert!(config.validate().is_err());
+
+        let mut config = ServerConfig::default();
+        config.log.level = "loud".into();
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,548 @@
+//! `#consign`, the broker's shop window and the expiry of old listings.
+
+use core::chat::{ChatChannel, ChatStyle};
+use core::consignment::{
+    CONSIGN_PAGE_SLOTS, ConsignmentPage, MAX_CONSIGN_QUERY_LEN, consign_fee, consign_proceeds,
+    encode_consignment_page, page_count, validate_consign_price,
+};
+use core::constants::{CLIENT_CAP_CONSIGNMENT, CharacterFlags, ItemFlags, MAXCHARS, USE_ACTIVE};
+use core::item_binding::Transfer;
+use core::server_commands::ServerCommandType;
+use core::string_operations::c_string_to_str;
+use core::types::FontColor;
+
+use super::{Listing, store};
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::helpers;
+use crate::network_manager;
+use crate::wall_clock;
+use server::journal::GoldTransferKind;
+
+const CONSIGN_HELP: &str = "Broker commands (stand near a broker): \
+#consign sell <gold> [silver] (offers the item on your cursor), #consign (list your offers), \
+#consign cancel <nr>, #consign browse [page] [search], #consign search <text>. \
+Click an item in the broker's window to buy it.\n";
+
+/// Name brokers sign their letters with.
+const BROKER_SENDER: &str = "Broker";
+
+/// How far away, in tiles, a broker still hears `#consign`.
+const BROKER_RANGE: i32 = 8;
+
+/// Formats a price in silver as gold and silver.
+fn format_price(price: i32) -> String {
+    format!("{}G {}S", price / 100, price % 100)
+}
+
+impl GameState {
+    /// Whether `co` is an active broker NPC.
+    pub(crate) fn is_broker(&self, co: usize) -> bool {
+        co != 0
+            && co < MAXCHARS
+            && self.characters[co].used == USE_ACTIVE
+            && self.characters[co].flags & CharacterFlags::Broker.bits() != 0
+    }
+
+    /// A broker `cn` can see within [`BROKER_RANGE`] tiles, if any.
+    fn broker_near(&mut self, cn: usize) -> Option<usize> {
+        let (x, y) = (
+            i32::from(self.characters[cn].x),
+            i32::from(self.characters[cn].y),
+        );
+        (1..MAXCHARS).find(|&co| {
+            self.is_broker(co)
+                && (i32::from(self.characters[co].x) - x).abs() <= BROKER_RANGE
+                && (i32::from(self.characters[co].y) - y).abs() <= BROKER_RANGE
+                && self.do_char_can_see(cn, co) != 0
+        })
+    }
+
+    /// Handles `#consign <subcommand> [arguments]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character issuing the command.
+    /// * `sub` - Subcommand; empty lists the character's listings.
+    /// * `rest` - Everything after the subcommand.
+    pub(crate) fn do_consign(&mut self, cn: usize, sub: &str, rest: &str) {
+        if self.characters[cn].flags & CharacterFlags::Player.bits() == 0 {
+            return;
+        }
+        let rest = rest.trim();
+        let result = match sub.to_ascii_lowercase().as_str() {
+            "" | "list" => {
+                self.do_consign_list(cn);
+                return;
+            }
+            "help" => {
+                self.do_character_log(cn, FontColor::Yellow, CONSIGN_HELP);
+                return;
+            }
+            "sell" => self.do_consign_sell(cn, rest),
+            "cancel" => rest
+                .trim_start_matches('#')
+                .parse::<u32>()
+                .map_err(|_| "Usage: #consign cancel <listing number>".to_owned())
+                .and_then(|id| self.do_consign_cancel(cn, id)),
+            "browse" => {
+                let (page, query) = match rest.split_once(' ') {
+                    Some((page, query)) => (page, query.trim()),
+                    None => (rest, ""),
+                };
+                match page.parse::<usize>() {
+                    Ok(page) => self.do_consign_browse(cn, page.saturating_sub(1), query),
+                    Err(_) if page.is_empty() => self.do_consign_browse(cn, 0, ""),
+                    Err(_) => self.do_consign_browse(cn, 0, rest),
+                }
+            }
+            "search" => self.do_consign_browse(cn, 0, rest),
+            _ => Err("Unknown broker command. Try #consign help.".to_owned()),
+        };
+        // An empty message means the player was already told why.
+        if let Err(message) = result
+            && !message.is_empty()
+        {
+            self.do_character_log(cn, FontColor::Red, &format!("{}\n", message));
+        }
+    }
+
+    fn do_consign_list(&mut self, cn: usize) {
+        let listings: Vec<Listing> = self.consignments.by_seller(cn).cloned().collect();
+        if listings.is_empty() {
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                "The brokers hold nothing of yours.\n",
+            );
+            return;
+        }
+        let style = ChatStyle::new(ChatChannel::Tell);
+        for listing in &listings {
+            let line = format!(
+                "#{} {} for {}, until {}\n",
+                listing.id,
+                listing.item_name,
+                format_price(listing.price),
+                wall_clock::format_display(listing.expires_unix)
+            );
+            self.do_character_styled_log(cn, style, &line);
+        }
+    }
+
+    fn do_consign_sell(&mut self, cn: usize, rest: &str) -> Result<(), String> {
+        let mut amounts = rest.split_whitespace().map(str::parse::<i64>);
+        let (Some(Ok(gold)), silver) = (amounts.next(), amounts.next().unwrap_or(Ok(0))) else {
+            return Err("Usage: #consign sell <gold> [silver]".to_owned());
+        };
+        let silver = silver.map_err(|_| "Usage: #consign sell <gold> [silver]".to_owned())?;
+        let price = gold.saturating_mul(100).saturating_add(silver);
+        validate_consign_price(price)?;
+        let price = price as i32;
+        if self.broker_near(cn).is_none() {
+            return Err("There is no broker nearby.".to_owned());
+        }
+        let item = self.consign_item(cn)?;
+        let fee = consign_fee(price);
+        if self.characters[cn].gold < fee {
+            return Err(format!(
+                "The broker wants {} for the listing, which you cannot afford.",
+                format_price(fee)
+            ));
+        }
+
+        let item_name = self.items[item].get_name().to_owned();
+        let id = self.consignments.list(Listing {
+            seller: cn,
+            seller_name: self.characters[cn].get_name().to_owned(),
+            item,
+            item_name: item_name.clone(),
+            price,
+            listed_unix: wall_clock::unix_now(),
+            ..Listing::default()
+        })?;
+        self.transfer_gold(cn, 0, fee, GoldTransferKind::ConsignmentFee);
+        self.characters[cn].citem = 0;
+        self.items[item].carried = 0;
+        self.characters[cn].set_do_update_flags();
+        if let Some(listing) = self.consignments.get(id) {
+            store::save_listing(&self.storage, listing);
+        }
+
+        log::info!(
+            "Character {} listed item {} for {} as consignment {}",
+            cn,
+            item,
+            price,
+            id
+        );
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!(
+                "The broker offers your {} for {} (listing #{}) and keeps {} as the fee.\n",
+                item_name,
+                format_price(price),
+                id,
+                format_price(fee)
+            ),
+        );
+        Ok(())
+    }
+
+    /// Checks the item on `cn`'s cursor before it is left with a broker.
+    ///
+    /// # Returns
+    ///
+    /// * The item, or a message for the player; empty when
+    ///   [`GameState::refuse_transfer`] already explained the refusal.
+    fn consign_item(&mut self, cn: usize) -> Result<usize, String> {
+        let citem = self.characters[cn].citem;
+        if citem == 0 {
+            return Err("Hold the item you want to sell on your cursor.".to_owned());
+        }
+        if citem & 0x8000_0000 != 0 {
+            return Err("The broker does not sell money.".to_owned());
+        }
+        let item = citem as usize;
+        if !self.do_maygive(cn, 0, item)
+            || self.items[item].flags & ItemFlags::IF_NODEPOT.bits() != 0
+        {
+            return Err("The broker will not take that.".to_owned());
+        }
+        if self.refuse_transfer(cn, item, Transfer::Give) {
+            return Err(String::new());
+        }
+        Ok(item)
+    }
+
+    fn do_consign_cancel(&mut self, cn: usize, id: u32) -> Result<(), String> {
+        if self.broker_near(cn).is_none() {
+            return Err("There is no broker nearby.".to_owned());
+        }
+        let listing = self.consignments.withdraw(cn, id)?;
+        if !God::give_character_item(self, cn, listing.item) {
+            self.consignments.restore(listing);
+            return Err("Your backpack is full.".to_owned());
+        }
+        store::save_deleted(&self.storage, id);
+        log::info!("Character {} withdrew consignment {}", cn, id);
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("The broker hands your {} back.\n", listing.item_name),
+        );
+        Ok(())
+    }
+
+    fn do_consign_browse(&mut self, cn: usize, page: usize, query: &str) -> Result<(), String> {
+        let co = self
+            .broker_near(cn)
+            .ok_or_else(|| "There is no broker nearby.".to_owned())?;
+        self.do_look_broker(cn, co, page, query);
+        Ok(())
+    }
+
+    /// Opens broker `co`'s shop window on a page of listings for `cn`.
+    ///
+    /// Capable clients first get an `SV_CONSIGNPAGE` naming the page; the
+    /// listings follow as `SV_LOOK1`..`SV_LOOK6`, one per shop window slot.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character looking.
+    /// * `co` - Broker.
+    /// * `page` - Zero-based page; clamped to the last page.
+    /// * `query` - Search text; empty for all listings.
+    pub(crate) fn do_look_broker(&mut self, cn: usize, co: usize, page: usize, query: &str) {
+        if !self.is_broker(co) || self.do_char_can_see(cn, co) == 0 {
+            return;
+        }
+        let player_id = self.characters[cn].player as usize;
+        if player_id == 0 {
+            return;
+        }
+        let query: String = query
+            .chars()
+            .filter(char::is_ascii)
+            .take(MAX_CONSIGN_QUERY_LEN)
+            .collect();
+        let (browse, total) = self.consignments.browse(cn, co, page, &query);
+        let page = browse.page;
+        let slots = browse.slots.clone();
+
+        if self.players[player_id].capabilities & CLIENT_CAP_CONSIGNMENT != 0 {
+            let buf = encode_consignment_page(
+                ServerCommandType::ConsignmentPage as u8,
+                &ConsignmentPage {
+                    broker: co as u16,
+                    page: page as u16,
+                    pages: page_count(total) as u16,
+                    total: total.min(usize::from(u16::MAX)) as u16,
+                    query,
+                },
+            );
+            network_manager::xsend(self, player_id, &buf, buf.len());
+        }
+
+        let mut buf = [0u8; 16];
+
+        // SV_LOOK1: no equipment shown.
+        buf[0] = ServerCommandType::Look1 as u8;
+        for i in 0..7 {
+            buf[1 + i * 2] = 35;
+        }
+        network_manager::xsend(self, player_id, &buf, 16);
+
+        buf = [0u8; 16];
+        buf[0] = ServerCommandType::Look2 as u8;
+        buf[1] = 35;
+        buf[13] = 35;
+        buf[3..5].copy_from_slice(&self.characters[co].sprite.to_le_bytes());
+        buf[5..9].copy_from_slice(&self.characters[co].points_tot.to_le_bytes());
+        buf[9..13].copy_from_slice(&u32::from(self.characters[co].hp[5]).to_le_bytes());
+        network_manager::xsend(self, player_id, &buf, 16);
+
+        buf = [0u8; 16];
+        buf[0] = ServerCommandType::Look3 as u8;
+        buf[1..3].copy_from_slice(&self.characters[co].end[5].to_le_bytes());
+        buf[7..9].copy_from_slice(&(co as u16).to_le_bytes());
+        buf[9..11].copy_from_slice(&(helpers::char_id(&self.characters[co]) as u16).to_le_bytes());
+        buf[11..13].copy_from_slice(&self.characters[co].mana[5].to_le_bytes());
+        network_manager::xsend(self, player_id, &buf, 16);
+
+        // SV_LOOK4: shop interface on; the broker buys nothing.
+        buf = [0u8; 16];
+        buf[0] = ServerCommandType::Look4 as u8;
+        for offset in [1, 3, 10, 12, 14] {
+            buf[offset] = 35;
+        }
+        buf[5] = 1;
+        network_manager::xsend(self, player_id, &buf, 16);
+
+        buf = [0u8; 16];
+        buf[0] = ServerCommandType::Look5 as u8;
+        buf[1..16].copy_from_slice(&self.characters[co].name[0..15]);
+        network_manager::xsend(self, player_id, &buf, 16);
+
+        for n in (0..CONSIGN_PAGE_SLOTS).step_by(2) {
+            buf = [0u8; 16];
+            buf[0] = ServerCommandType::Look6 as u8;
+            buf[1] = n as u8;
+            for m in n..(n + 2).min(CONSIGN_PAGE_SLOTS) {
+                let Some(listing) = self.consignments.get(slots[m]) else {
+                    continue;
+                };
+                let offset = 2 + (m - n) * 6;
+                let sprite = self.items[listing.item].sprite[0];
+                buf[offset..offset + 2].copy_from_slice(&sprite.to_le_bytes());
+                buf[offset + 2..offset + 6].copy_from_slice(&listing.price.to_le_bytes());
+            }
+            network_manager::xsend(self, player_id, &buf, 16);
+        }
+    }
+
+    /// Handles a click into broker `co`'s shop window.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character clicking.
+    /// * `co` - Broker.
+    /// * `nr` - Slot `0..62` to buy, or `62..124` to examine.
+    pub(crate) fn do_broker_shop(&mut self, cn: usize, co: usize, nr: i32) {
+        if !self.is_broker(co) || !(0..124).contains(&nr) || self.do_char_can_see(cn, co) == 0 {
+            return;
+        }
+        if self.characters[cn].citem != 0 {
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                "To offer an item, type #consign sell <gold> [silver].\n",
+            );
+            return;
+        }
+        let Some(browse) = self
+            .consignments
+            .browsing(cn)
+            .filter(|browse| browse.broker == co)
+            .cloned()
+        else {
+            self.do_look_broker(cn, co, 0, "");
+            return;
+        };
+        let slot = nr as usize % CONSIGN_PAGE_SLOTS;
+        let id = browse.slots.get(slot).copied().unwrap_or(0);
+        if nr as usize >= CONSIGN_PAGE_SLOTS {
+            self.do_examine_listing(cn, id);
+            return;
+        }
+        if let Err(message) = self.do_buy_listing(cn, id) {
+            self.do_character_log(cn, FontColor::Green, &format!("{}\n", message));
+        }
+        self.do_look_broker(cn, co, browse.page, &browse.query);
+    }
+
+    fn do_examine_listing(&mut self, cn: usize, id: u32) {
+        let Some(listing) = self.consignments.get(id).cloned() else {
+            return;
+        };
+        let description = c_string_to_str(&self.items[listing.item].description).to_owned();
+        self.do_character_log(cn, FontColor::Yellow, &format!("{}:\n", listing.item_name));
+        self.do_character_log(cn, FontColor::Yellow, &format!("{}\n", description));
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!(
+                "Offered by {} for {}, until {}.\n",
+                listing.seller_name,
+                format_price(listing.price),
+                wall_clock::format_display(listing.expires_unix)
+            ),
+        );
+    }
+
+    fn do_buy_listing(&mut self, cn: usize, id: u32) -> Result<(), String> {
+        let listing = self
+            .consignments
+            .get(id)
+            .cloned()
+            .ok_or_else(|| "Someone else bought that first.".to_owned())?;
+        if listing.seller == cn {
+            return Err(format!(
+                "That is your own offer. Type #consign cancel {} to take it back.",
+                id
+            ));
+        }
+        if self.characters[cn].gold < listing.price {
+            return Err("You cannot afford that.".to_owned());
+        }
+        if !God::give_character_item(self, cn, listing.item) {
+            return Err(format!(
+                "You cannot buy the {} because your inventory is full.",
+                listing.item_name
+            ));
+        }
+        self.consignments.remove(id);
+        store::save_deleted(&self.storage, id);
+        self.bind_item(cn, listing.item, ItemFlags::IF_BIND_PICKUP);
+        self.transfer_gold(cn, 0, listing.price, GoldTransferKind::ConsignmentBuy);
+        let proceeds = consign_proceeds(listing.price);
+        self.characters[listing.seller].data[13] += proceeds;
+        self.record_gold_transfer(
+            0,
+            listing.seller,
+            proceeds,
+            GoldTransferKind::ConsignmentSale,
+        );
+
+        log::info!(
+            "Character {} bought consignment {} (item {}) from {} for {}",
+            cn,
+            id,
+            listing.item,
+            listing.seller,
+            listing.price
+        );
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!(
+                "You bought a {} for {}.\n",
+                listing.item_name,
+                format_price(listing.price)
+            ),
+        );
+        let body = format!(
+            "Your {} sold for {}. After my commission, {} went into your bank account.",
+            listing.item_name,
+            format_price(listing.price),
+            format_price(proceeds)
+        );
+        if let Err(e) = self.send_system_letter(listing.seller, BROKER_SENDER, &body, 0, 0) {
+            log::warn!(
+                "Could not tell character {} about consignment {}: {}",
+                listing.seller,
+                id,
+                e
+            );
+        }
+        Ok(())
+    }
+
+    /// Mails every expired listing back to its seller.
+    ///
+    /// A listing whose seller's mailbox is full stays on the market and is
+    /// tried again on the next sweep.
+    pub(crate) fn expire_consignments(&mut self) {
+        for id in self.consignments.expired(wall_clock::unix_now()) {
+            let Some(listing) = self.consignments.get(id).cloned() else {
+                continue;
+            };
+            let body = format!(
+                "Nobody bought your {}, so I return it to you.",
+                listing.item_name
+            );
+            match self.send_system_letter(listing.seller, BROKER_SENDER, &body, listing.item, 0) {
+                Ok(_) => {
+                    self.consignments.remove(id);
+                    store::save_deleted(&self.storage, id);
+                    log::info!("Consignment {} expired and was mailed back", id);
+                }
+                Err(e) => log::debug!("Consignment {} expired but stays listed: {}", id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn listed_items_sell_to_other_players() {
+        with_test_gs(|gs| {
+            let (buyer, _) = add_test_player(gs);
+            // Infravision lets both players see the broker on the unlit test map.
+            gs.characters[buyer].flags |= CharacterFlags::Infrared.bits();
+            let broker = 3;
+            gs.characters[broker].used = USE_ACTIVE;
+            gs.characters[broker].flags = CharacterFlags::Broker.bits();
+            gs.characters[broker].x = gs.characters[buyer].x + 1;
+            gs.characters[broker].y = gs.characters[buyer].y;
+
+            let seller = 2;
+            gs.characters[seller].used = USE_ACTIVE;
+            gs.characters[seller].flags =
+                CharacterFlags::Player.bits() | CharacterFlags::Infrared.bits();
+            gs.characters[seller].set_name("Seller");
+            gs.characters[seller].x = gs.characters[buyer].x;
+            gs.characters[seller].y = gs.characters[buyer].y + 1;
+            gs.characters[seller].gold = 1_000;
+            let item = 5;
+            gs.items[item].used = USE_ACTIVE;
+            gs.items[item].carried = seller as u16;
+            gs.characters[seller].citem = item as u32;
+
+            gs.do_consign(seller, "sell", "20");
+            assert_eq!(gs.characters[seller].citem, 0);
+            assert_eq!(gs.characters[seller].gold, 1_000 - consign_fee(2_000));
+            let id = gs.consignments.by_seller(seller).next().expect("listed").id;
+
+            // A full item GC sweep must leave the listed item alone.
+            for _ in 0..core::constants::MAXITEM.div_ceil(256) {
+                crate::driver::item_tick_gc(gs);
+            }
+            assert_eq!(gs.items[item].used, USE_ACTIVE);
+
+            gs.characters[buyer].gold = 2_500;
+            gs.consignments.browse(buyer, broker, 0, "");
+            gs.do_broker_shop(buyer, broker, 0);
+            assert!(gs.consignments.get(id).is_none());
+            assert_eq!(gs.items[item].carried, buyer as u16);
+            assert_eq!(gs.characters[buyer].gold, 500);
+            assert_eq!(gs.characters[seller].data[13], consign_proceeds(2_000));
+            assert_eq!(gs.mail.unread_count(seller), 1);
+        });
+    }
+}
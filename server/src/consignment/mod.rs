@@ -0,0 +1,328 @@
+//! Consignment: items players leave with a broker NPC for other players to
+//! buy.
+//!
+//! `#consign sell <gold> [silver]` next to a broker lists the item on the
+//! seller's cursor for a fee. Anyone can browse every broker's listings in
+//! the broker's shop window, a page at a time and optionally filtered by
+//! name, and buy with a click. The price goes to the broker; the seller's
+//! bank account is credited with it less the commission and a letter tells
+//! them about the sale. Unsold items are mailed back once the listing
+//! expires.
+//!
+//! [`ConsignmentRegistry`] holds the listings and the rules and knows
+//! nothing about the world, so it can be tested on its own. [`commands`]
+//! moves items and gold, sends the shop window pages and expires listings.
+//! [`store`] persists every listing to a KeyDB hash.
+//!
+//! A listed item stays in the item table, carried by no one and placed
+//! nowhere, just like an item attached to a letter; the item garbage
+//! collector asks [`ConsignmentRegistry::holds_item`] before freeing it.
+
+pub mod commands;
+pub mod store;
+
+use std::collections::{BTreeMap, HashMap};
+
+use core::consignment::{
+    CONSIGN_LISTING_SECS, CONSIGN_PAGE_SLOTS, MAX_LISTINGS_PER_SELLER, matches_query,
+    validate_consign_price,
+};
+
+/// One item up for sale.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Listing {
+    /// Server-wide id; `0` until [`ConsignmentRegistry::list`] assigns one.
+    pub id: u32,
+    /// Character slot of the seller.
+    pub seller: usize,
+    /// Seller's name when the item was listed.
+    pub seller_name: String,
+    /// The item held by the broker.
+    pub item: usize,
+    /// Item name, matched by searches.
+    pub item_name: String,
+    /// Asking price in silver.
+    pub price: i32,
+    /// When the item was listed, in Unix seconds.
+    pub listed_unix: i64,
+    /// When the listing expires, in Unix seconds.
+    pub expires_unix: i64,
+}
+
+/// The page of listings a player has open in a broker's shop window.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Browse {
+    /// Broker whose window shows the page.
+    pub broker: usize,
+    /// Zero-based page.
+    pub page: usize,
+    /// Search text; empty for all listings.
+    pub query: String,
+    /// Listing id in each shop window slot; `0` for an empty slot.
+    pub slots: Vec<u32>,
+}
+
+/// Every listing in the world, and what each player is browsing.
+#[derive(Debug)]
+pub struct ConsignmentRegistry {
+    listings: BTreeMap<u32, Listing>,
+    next_id: u32,
+    /// Open shop window pages by character; never persisted.
+    browsing: HashMap<usize, Browse>,
+}
+
+impl Default for ConsignmentRegistry {
+    fn default() -> Self {
+        Self {
+            listings: BTreeMap::new(),
+            next_id: 1,
+            browsing: HashMap::new(),
+        }
+    }
+}
+
+impl ConsignmentRegistry {
+    /// Builds a registry from stored listings.
+    ///
+    /// # Arguments
+    ///
+    /// * `listings` - Listings as loaded by [`store::load_consignments`].
+    /// * `next_id` - Lowest id never handed out.
+    pub fn from_listings(listings: Vec<Listing>, next_id: u32) -> Self {
+        let next_id = listings
+            .iter()
+            .map(|listing| listing.id + 1)
+            .max()
+            .unwrap_or(1)
+            .max(next_id);
+        Self {
+            listings: listings
+                .into_iter()
+                .map(|listing| (listing.id, listing))
+                .collect(),
+            next_id,
+            browsing: HashMap::new(),
+        }
+    }
+
+    /// Listing with the given id.
+    pub fn get(&self, id: u32) -> Option<&Listing> {
+        self.listings.get(&id)
+    }
+
+    /// Whether `item` is listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - Item index.
+    pub fn holds_item(&self, item: usize) -> bool {
+        item != 0 && self.listings.values().any(|listing| listing.item == item)
+    }
+
+    /// Listings of `seller`, oldest first.
+    pub fn by_seller(&self, seller: usize) -> impl Iterator<Item = &Listing> {
+        self.listings
+            .values()
+            .filter(move |listing| listing.seller == seller)
+    }
+
+    /// Listings whose item matches `query`, oldest first.
+    pub fn search<'a>(&'a self, query: &'a str) -> impl Iterator<Item = &'a Listing> {
+        self.listings
+            .values()
+            .filter(move |listing| matches_query(&listing.item_name, query))
+    }
+
+    /// Ids of the listings that expired at or before `now`.
+    pub fn expired(&self, now: i64) -> Vec<u32> {
+        self.listings
+            .values()
+            .filter(|listing| listing.expires_unix <= now)
+            .map(|listing| listing.id)
+            .collect()
+    }
+
+    /// Puts an item up for sale.
+    ///
+    /// # Arguments
+    ///
+    /// * `listing` - The listing; its `id` and `expires_unix` are assigned
+    ///   here from `listed_unix`.
+    ///
+    /// # Returns
+    ///
+    /// * The new listing's id, or a message for the seller.
+    pub fn list(&mut self, mut listing: Listing) -> Result<u32, String> {
+        validate_consign_price(i64::from(listing.price))?;
+        if self.by_seller(listing.seller).count() >= MAX_LISTINGS_PER_SELLER {
+            return Err(format!(
+                "The brokers already hold {} items of yours.",
+                MAX_LISTINGS_PER_SELLER
+            ));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        listing.id = id;
+        listing.expires_unix = listing.listed_unix + CONSIGN_LISTING_SECS;
+        self.listings.insert(id, listing);
+        Ok(id)
+    }
+
+    /// Takes listing `id` off the market, e.g. because it was bought.
+    pub fn remove(&mut self, id: u32) -> Option<Listing> {
+        self.listings.remove(&id)
+    }
+
+    /// Takes listing `id` of `seller` off the market.
+    ///
+    /// # Returns
+    ///
+    /// * The withdrawn listing, or a message for the player.
+    pub fn withdraw(&mut self, seller: usize, id: u32) -> Result<Listing, String> {
+        if self
+            .listings
+            .get(&id)
+            .is_none_or(|listing| listing.seller != seller)
+        {
+            return Err(format!("You have no listing #{}.", id));
+        }
+        Ok(self.listings.remove(&id).expect("checked above"))
+    }
+
+    /// Puts a withdrawn listing back, e.g. when the item could not be
+    /// handed over.
+    pub fn restore(&mut self, listing: Listing) {
+        self.listings.insert(listing.id, listing);
+    }
+
+    /// Lays out page `page` of the listings matching `query` and remembers
+    /// it as what `cn` is browsing.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character browsing.
+    /// * `broker` - Broker whose window shows the page.
+    /// * `page` - Zero-based page; clamped to the last page.
+    /// * `query` - Search text.
+    ///
+    /// # Returns
+    ///
+    /// * The page as remembered, and the number of matching listings.
+    pub fn browse(
+        &mut self,
+        cn: usize,
+        broker: usize,
+        page: usize,
+        query: &str,
+    ) -> (&Browse, usize) {
+        let matching: Vec<u32> = self.search(query).map(|listing| listing.id).collect();
+        let page = page.min(core::consignment::page_count(matching.len()) - 1);
+        let mut slots: Vec<u32> = matching
+            .iter()
+            .copied()
+            .skip(page * CONSIGN_PAGE_SLOTS)
+            .take(CONSIGN_PAGE_SLOTS)
+            .collect();
+        slots.resize(CONSIGN_PAGE_SLOTS, 0);
+        let browse = Browse {
+            broker,
+            page,
+            query: query.to_owned(),
+            slots,
+        };
+        self.browsing.insert(cn, browse);
+        (&self.browsing[&cn], matching.len())
+    }
+
+    /// The page `cn` has open, if any.
+    pub fn browsing(&self, cn: usize) -> Option<&Browse> {
+        self.browsing.get(&cn)
+    }
+
+    /// Forgets the page `cn` has open, e.g. at logout.
+    pub fn stop_browsing(&mut self, cn: usize) {
+        self.browsing.remove(&cn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(seller: usize, item: usize, name: &str) -> Listing {
+        Listing {
+            seller,
+            seller_name: "Ishtar".to_owned(),
+            item,
+            item_name: name.to_owned(),
+            price: 1_500,
+            listed_unix: 1_790_000_000,
+            ..Listing::default()
+        }
+    }
+
+    #[test]
+    fn listings_are_capped_and_only_their_seller_withdraws() {
+        let mut registry = ConsignmentRegistry::default();
+        let id = registry.list(listing(10, 77, "Golden Ring")).unwrap();
+        assert_eq!(
+            registry.get(id).unwrap().expires_unix,
+            1_790_000_000 + CONSIGN_LISTING_SECS
+        );
+        assert!(registry.withdraw(11, id).is_err());
+        assert_eq!(registry.withdraw(10, id).unwrap().item, 77);
+        assert!(registry.get(id).is_none());
+
+        assert!(
+            registry
+                .list(Listing {
+                    price: 0,
+                    ..listing(10, 78, "Dagger")
+                })
+                .is_err()
+        );
+        for n in 0..MAX_LISTINGS_PER_SELLER {
+            registry.list(listing(12, 100 + n, "Dagger")).unwrap();
+        }
+        assert!(registry.list(listing(12, 99, "Dagger")).is_err());
+        assert_eq!(registry.expired(1_790_000_000).len(), 0);
+        assert_eq!(
+            registry.expired(1_790_000_000 + CONSIGN_LISTING_SECS).len(),
+            MAX_LISTINGS_PER_SELLER
+        );
+    }
+
+    #[test]
+    fn pages_hold_a_window_of_matching_listings() {
+        let mut registry = ConsignmentRegistry::default();
+        for n in 0..CONSIGN_PAGE_SLOTS + 3 {
+            registry
+                .list(listing(
+                    n + 1,
+                    200 + n,
+                    if n % 2 == 0 { "Dagger" } else { "Sword" },
+                ))
+                .unwrap();
+        }
+
+        let (browse, total) = registry.browse(5, 40, 1, "");
+        assert_eq!(total, CONSIGN_PAGE_SLOTS + 3);
+        assert_eq!(browse.page, 1);
+        assert_eq!(browse.slots.iter().filter(|&&id| id != 0).count(), 3);
+
+        let (browse, total) = registry.browse(5, 40, 9, "sword");
+        assert_eq!(total, (CONSIGN_PAGE_SLOTS + 3) / 2);
+        assert_eq!(browse.page, 0, "page is clamped to the last one");
+        let first = registry.browsing(5).unwrap().slots[0];
+        assert_eq!(registry.get(first).unwrap().item_name, "Sword");
+
+        let restored = ConsignmentRegistry::from_listings(
+            vec![Listing {
+                id: 9,
+                ..listing(1, 2, "Axe")
+            }],
+            4,
+        );
+        assert_eq!(restored.next_id, 10);
+    }
+}
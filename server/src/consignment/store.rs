@@ -0,0 +1,152 @@
+//! Persistence for consignment listings.
+//!
+//! Every listing is the record `game:consign:{id}` with the fields `seller`,
+//! `seller_name`, `item`, `item_name`, `price`, `listed` and `expires`,
+//! stored the same way as letters (see [`crate::mail::store`]). A listing
+//! that was bought, withdrawn or expired is written back with `seller` set
+//! to `0`; such records are removed the next time the server starts. The
+//! write-behind flusher retries that write until it lands, so a sold item
+//! cannot come back on the market after a restart.
+
+use std::collections::HashMap;
+
+use server::storage::StorageBackend;
+
+use super::{ConsignmentRegistry, Listing};
+
+/// Prefix of the per-listing record keys.
+pub const CONSIGN_KEY_PREFIX: &str = "game:consign:";
+
+/// Saves `listing`.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `listing` - Listing to save.
+pub fn save_listing(storage: &StorageBackend, listing: &Listing) {
+    storage.save_record(
+        format!("{}{}", CONSIGN_KEY_PREFIX, listing.id),
+        vec![
+            ("seller", listing.seller.to_string()),
+            ("seller_name", listing.seller_name.clone()),
+            ("item", listing.item.to_string()),
+            ("item_name", listing.item_name.clone()),
+            ("price", listing.price.to_string()),
+            ("listed", listing.listed_unix.to_string()),
+            ("expires", listing.expires_unix.to_string()),
+        ],
+    );
+}
+
+/// Blanks a listing that left the market.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `id` - Id of the listing.
+pub fn save_deleted(storage: &StorageBackend, id: u32) {
+    storage.save_record(
+        format!("{}{}", CONSIGN_KEY_PREFIX, id),
+        vec![("seller", "0".to_owned()), ("item", "0".to_owned())],
+    );
+}
+
+/// Loads every listing from the storage backend.
+///
+/// Records of listings that left the market are removed while loading.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+///
+/// # Returns
+///
+/// * The registry. A listing record that cannot be parsed is logged and
+///   skipped; an error means the backend could not be read at all.
+pub fn load_consignments(storage: &StorageBackend) -> Result<ConsignmentRegistry, String> {
+    let mut listings = Vec::new();
+    let mut closed = Vec::new();
+    let mut next_id = 1;
+    for (key, fields) in storage.load_records(CONSIGN_KEY_PREFIX)? {
+        let Some(id) = key
+            .strip_prefix(CONSIGN_KEY_PREFIX)
+            .and_then(|id| id.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        next_id = next_id.max(id + 1);
+        match listing_from_fields(id, &fields) {
+            Ok(Some(listing)) => listings.push(listing),
+            Ok(None) => closed.push(key),
+            Err(e) => log::error!("Skipping listing {}: {}", id, e),
+        }
+    }
+    if let Err(e) = storage.delete_records(&closed) {
+        log::warn!("Failed to remove closed listings: {}", e);
+    }
+
+    log::info!("Loaded {} consignment listing(s)", listings.len());
+    Ok(ConsignmentRegistry::from_listings(listings, next_id))
+}
+
+/// Builds a listing from its stored fields.
+///
+/// # Returns
+///
+/// * `Ok(None)` for a listing that left the market.
+fn listing_from_fields(
+    id: u32,
+    fields: &HashMap<String, String>,
+) -> Result<Option<Listing>, String> {
+    let number = |name: &str| -> Result<i64, String> {
+        fields
+            .get(name)
+            .map_or(Ok(0), |value| value.parse::<i64>())
+            .map_err(|_| format!("malformed {} field", name))
+    };
+    let seller = number("seller")? as usize;
+    if seller == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Listing {
+        id,
+        seller,
+        seller_name: fields.get("seller_name").cloned().unwrap_or_default(),
+        item: number("item")? as usize,
+        item_name: fields.get("item_name").cloned().unwrap_or_default(),
+        price: number("price")? as i32,
+        listed_unix: number("listed")?,
+        expires_unix: number("expires")?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_fields_load_back() {
+        let mut fields = HashMap::new();
+        fields.insert("seller".to_owned(), "0".to_owned());
+        assert_eq!(listing_from_fields(3, &fields), Ok(None));
+
+        for (name, value) in [
+            ("seller", "12"),
+            ("seller_name", "Ishtar"),
+            ("item", "5021"),
+            ("item_name", "Golden Ring"),
+            ("price", "150000"),
+            ("listed", "1790000000"),
+            ("expires", "1790259200"),
+        ] {
+            fields.insert(name.to_owned(), value.to_owned());
+        }
+        let listing = listing_from_fields(3, &fields).unwrap().unwrap();
+        assert_eq!(listing.seller, 12);
+        assert_eq!(listing.item, 5021);
+        assert_eq!(listing.price, 150_000);
+
+        fields.insert("price".to_owned(), "cheap".to_owned());
+        assert!(listing_from_fields(3, &fields).is_err());
+    }
+}
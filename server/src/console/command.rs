@@ -0,0 +1,384 @@
+//! Parsing and execution of operator console commands.
+
+use core::constants::{AT_AGIL, AT_BRAVE, AT_INT, AT_STREN, AT_WILL, USE_EMPTY};
+use core::log_levels;
+use core::world_action_store::WorldActionKind;
+use log::LevelFilter;
+
+use crate::chlog;
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::populate;
+
+/// One-line summary of every command, sent in reply to `help`.
+pub const HELP_TEXT: &str = "teleport <name> <x> <y> | spawn <name> <item template> | \
+setstat <name> <stat> <value> | announce <text> | \
+loglevel [<level> | <module> <level|default> | reset] | help";
+
+/// Character values `setstat` can change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsoleStat {
+    Hp,
+    Endurance,
+    Mana,
+    Gold,
+    Attribute(i32),
+}
+
+impl ConsoleStat {
+    /// Parses a stat name as typed by an operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Stat name, case-insensitive.
+    ///
+    /// # Returns
+    ///
+    /// * The stat, or `None` for an unknown name.
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "hp" => Self::Hp,
+            "end" | "endurance" => Self::Endurance,
+            "mana" => Self::Mana,
+            "gold" => Self::Gold,
+            "brave" | "braveness" => Self::Attribute(AT_BRAVE),
+            "will" | "willpower" => Self::Attribute(AT_WILL),
+            "int" | "intuition" => Self::Attribute(AT_INT),
+            "agil" | "agility" => Self::Attribute(AT_AGIL),
+            "str" | "strength" => Self::Attribute(AT_STREN),
+            _ => return None,
+        })
+    }
+}
+
+/// What `loglevel` does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogLevelChange {
+    /// Report the current levels.
+    Show,
+    /// Set the level of every module without an override.
+    Root(LevelFilter),
+    /// Override one module's level; `None` makes it follow the root again.
+    Module {
+        module: String,
+        level: Option<LevelFilter>,
+    },
+    /// Go back to the levels the server started with.
+    Reset,
+}
+
+/// A parsed console command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    /// Move an online character to a map tile.
+    Teleport { name: String, x: usize, y: usize },
+    /// Create an item from a template in an online character's inventory.
+    Spawn { name: String, template_id: usize },
+    /// Overwrite a base stat of an online character.
+    SetStat {
+        name: String,
+        stat: ConsoleStat,
+        value: i32,
+    },
+    /// Announce a message to every player.
+    Announce { message: String },
+    /// Show or change the running logger's levels.
+    LogLevel(LogLevelChange),
+    /// List the available commands.
+    Help,
+}
+
+/// Parses one console line.
+///
+/// # Arguments
+///
+/// * `line` - Command line without the trailing newline.
+///
+/// # Returns
+///
+/// * The command, or a usage message suitable for an `ERR` reply.
+pub fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let line = line.trim();
+    let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    let number = |value: &str, what: &str| {
+        value
+            .parse::<i64>()
+            .map_err(|_| format!("{} '{}' is not a number", what, value))
+    };
+
+    match verb.to_ascii_lowercase().as_str() {
+        "teleport" | "tp" => {
+            let [name, x, y] = args[..] else {
+                return Err("usage: teleport <name> <x> <y>".to_owned());
+            };
+            let x = number(x, "x")?;
+            let y = number(y, "y")?;
+            if x < 0 || y < 0 {
+                return Err("coordinates must not be negative".to_owned());
+            }
+            Ok(ConsoleCommand::Teleport {
+                name: name.to_owned(),
+                x: x as usize,
+                y: y as usize,
+            })
+        }
+        "spawn" => {
+            let [name, template] = args[..] else {
+                return Err("usage: spawn <name> <item template>".to_owned());
+            };
+            let template_id = number(template, "item template")?;
+            if template_id <= 0 {
+                return Err("item template must be positive".to_owned());
+            }
+            Ok(ConsoleCommand::Spawn {
+                name: name.to_owned(),
+                template_id: template_id as usize,
+            })
+        }
+        "setstat" => {
+            let [name, stat, value] = args[..] else {
+                return Err("usage: setstat <name> <stat> <value>".to_owned());
+            };
+            let stat = ConsoleStat::parse(stat).ok_or_else(|| {
+                format!(
+                    "unknown stat '{}' (hp, end, mana, gold, brave, will, int, agil, str)",
+                    stat
+                )
+            })?;
+            let value = i32::try_from(number(value, "value")?)
+                .map_err(|_| "value out of range".to_owned())?;
+            Ok(ConsoleCommand::SetStat {
+                name: name.to_owned(),
+                stat,
+                value,
+            })
+        }
+        "announce" => {
+            let message = rest.trim();
+            if message.is_empty() {
+                return Err("usage: announce <text>".to_owned());
+            }
+            Ok(ConsoleCommand::Announce {
+                message: message.to_owned(),
+            })
+        }
+        "loglevel" | "log" => {
+            let change = match args[..] {
+                [] => LogLevelChange::Show,
+                [word] if word.eq_ignore_ascii_case("reset") => LogLevelChange::Reset,
+                [level] => LogLevelChange::Root(log_levels::parse_level(level)?),
+                [module, level] => LogLevelChange::Module {
+                    module: module.to_owned(),
+                    level: if level.eq_ignore_ascii_case("default") {
+                        None
+                    } else {
+                        Some(log_levels::parse_level(level)?)
+                    },
+                },
+                _ => {
+                    return Err(
+                        "usage: loglevel [<level> | <module> <level|default> | reset]".to_owned(),
+                    );
+                }
+            };
+            Ok(ConsoleCommand::LogLevel(change))
+        }
+        "help" => Ok(ConsoleCommand::Help),
+        "" => Err("empty command".to_owned()),
+        other => Err(format!("unknown command '{}'; try help", other)),
+    }
+}
+
+/// Finds the online character called `name`.
+fn online_character(gs: &GameState, name: &str) -> Result<usize, String> {
+    populate::find_online_player(gs, name)
+        .map(|(_, character_id)| character_id)
+        .ok_or_else(|| format!("no online player named {}", name))
+}
+
+/// Runs a console command on the tick thread.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `command` - Parsed command.
+///
+/// # Returns
+///
+/// * A short description of what happened, or why nothing did.
+pub fn execute_command(gs: &mut GameState, command: &ConsoleCommand) -> Result<String, String> {
+    match command {
+        ConsoleCommand::Teleport { name, x, y } => {
+            let cn = online_character(gs, name)?;
+            if !God::transfer_char(gs, cn, *x, *y) {
+                return Err(format!("could not place {} near {},{}", name, x, y));
+            }
+            let character = &gs.characters[cn];
+            chlog!(cn, "CONSOLE: teleported to {},{}", character.x, character.y);
+            Ok(format!(
+                "{} teleported to {},{}",
+                character.get_name(),
+                character.x,
+                character.y
+            ))
+        }
+        ConsoleCommand::Spawn { name, template_id } => {
+            let cn = online_character(gs, name)?;
+            let item_id = God::create_item(gs, *template_id)
+                .ok_or_else(|| format!("item template {} cannot be created", template_id))?;
+            if !God::give_character_item(gs, cn, item_id) {
+                gs.items[item_id].used = USE_EMPTY;
+                return Err(format!("{}'s inventory is full", name));
+            }
+            let item_name = gs.items[item_id].get_name().to_owned();
+            chlog!(cn, "CONSOLE: received {}", item_name);
+            Ok(format!(
+                "gave {} to {}",
+                item_name,
+                gs.characters[cn].get_name()
+            ))
+        }
+        ConsoleCommand::SetStat { name, stat, value } => {
+            let cn = online_character(gs, name)?;
+            let character = &mut gs.characters[cn];
+            let applied = match stat {
+                ConsoleStat::Hp => {
+                    character.hp[0] = (*value).clamp(0, i32::from(u16::MAX)) as u16;
+                    i32::from(character.hp[0])
+                }
+                ConsoleStat::Endurance => {
+                    character.end[0] = (*value).clamp(0, i32::from(u16::MAX)) as u16;
+                    i32::from(character.end[0])
+                }
+                ConsoleStat::Mana => {
+                    character.mana[0] = (*value).clamp(0, i32::from(u16::MAX)) as u16;
+                    i32::from(character.mana[0])
+                }
+                ConsoleStat::Gold => {
+                    character.gold = (*value).max(0);
+                    character.gold
+                }
+                ConsoleStat::Attribute(n) => {
+                    character.attrib[*n as usize][0] = (*value).clamp(1, i32::from(u8::MAX)) as u8;
+                    i32::from(character.attrib[*n as usize][0])
+                }
+            };
+            character.set_do_update_flags();
+            chlog!(cn, "CONSOLE: {:?} set to {}", stat, applied);
+            Ok(format!(
+                "{:?} of {} set to {}",
+                stat,
+                gs.characters[cn].get_name(),
+                applied
+            ))
+        }
+        ConsoleCommand::Announce { message } => populate::execute_world_action(
+            gs,
+            &WorldActionKind::Broadcast {
+                message: message.clone(),
+            },
+        )
+        .map(|outcome| outcome.message),
+        ConsoleCommand::LogLevel(change) => {
+            let levels = match change {
+                LogLevelChange::Show => {
+                    return log_levels::current_levels()
+                        .map(|levels| levels.describe())
+                        .ok_or_else(|| "the logger was not initialised".to_owned());
+                }
+                LogLevelChange::Root(level) => log_levels::set_root_level(*level)?,
+                LogLevelChange::Module { module, level } => {
+                    log_levels::set_module_level(module, *level)?
+                }
+                LogLevelChange::Reset => log_levels::reset_levels()?,
+            };
+            // Logged at warn so the change is recorded even at quiet levels.
+            log::warn!("CONSOLE: log levels changed to {}", levels.describe());
+            Ok(levels.describe())
+        }
+        ConsoleCommand::Help => Ok(HELP_TEXT.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_command() {
+        assert_eq!(
+            parse_command("teleport Ishtar 512 498"),
+            Ok(ConsoleCommand::Teleport {
+                name: "Ishtar".to_owned(),
+                x: 512,
+                y: 498,
+            })
+        );
+        assert_eq!(
+            parse_command("SPAWN Ishtar 57"),
+            Ok(ConsoleCommand::Spawn {
+                name: "Ishtar".to_owned(),
+                template_id: 57,
+            })
+        );
+        assert_eq!(
+            parse_command("setstat Ishtar str 40"),
+            Ok(ConsoleCommand::SetStat {
+                name: "Ishtar".to_owned(),
+                stat: ConsoleStat::Attribute(AT_STREN),
+                value: 40,
+            })
+        );
+        assert_eq!(
+            parse_command("announce  Reboot in 5 minutes "),
+            Ok(ConsoleCommand::Announce {
+                message: "Reboot in 5 minutes".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_log_level_changes() {
+        assert_eq!(
+            parse_command("loglevel"),
+            Ok(ConsoleCommand::LogLevel(LogLevelChange::Show))
+        );
+        assert_eq!(
+            parse_command("loglevel DEBUG"),
+            Ok(ConsoleCommand::LogLevel(LogLevelChange::Root(
+                LevelFilter::Debug
+            )))
+        );
+        assert_eq!(
+            parse_command("loglevel server::driver trace"),
+            Ok(ConsoleCommand::LogLevel(LogLevelChange::Module {
+                module: "server::driver".to_owned(),
+                level: Some(LevelFilter::Trace),
+            }))
+        );
+        assert_eq!(
+            parse_command("loglevel server::driver default"),
+            Ok(ConsoleCommand::LogLevel(LogLevelChange::Module {
+                module: "server::driver".to_owned(),
+                level: None,
+            }))
+        );
+        assert_eq!(
+            parse_command("loglevel reset"),
+            Ok(ConsoleCommand::LogLevel(LogLevelChange::Reset))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        assert!(parse_command("teleport Ishtar 512").is_err());
+        assert!(parse_command("teleport Ishtar -1 4").is_err());
+        assert!(parse_command("spawn Ishtar sword").is_err());
+        assert!(parse_command("setstat Ishtar luck 3").is_err());
+        assert!(parse_command("announce").is_err());
+        assert!(parse_command("shutdown").is_err());
+        assert!(parse_command("loglevel loud").is_err());
+        assert!(parse_command("loglevel server driver trace").is_err());
+    }
+}
@@ -0,0 +1,379 @@
+//! Operator console on a local TCP socket.
+//!
+//! When [`TOKEN_ENV`] is set, a listener thread accepts connections on
+//! [`ADDR_ENV`] (loopback only, default [`DEFAULT_ADDR`]). Each connection
+//! speaks a line protocol:
+//!
+//! ```text
+//! > AUTH <token>
+//! < OK authenticated
+//! > teleport Ishtar 512 498
+//! < OK Ishtar teleported to 512,498
+//! > spawn Ishtar 99999
+//! < ERR item template 99999 cannot be created
+//! > loglevel server::driver trace
+//! < OK root=info server::driver=trace
+//! ```
+//!
+//! Connection threads only parse lines; every command is handed to the tick
+//! thread through [`ConsoleServer::try_recv`] and executed there, like the
+//! KeyDB admin watchers. `magctl` in `server-utils` is the matching client.
+
+pub mod command;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use command::ConsoleCommand;
+
+/// Environment variable holding the shared secret; the console is disabled
+/// while it is unset or empty.
+pub const TOKEN_ENV: &str = "MAG_CONSOLE_TOKEN";
+
+/// Environment variable overriding the listen address.
+pub const ADDR_ENV: &str = "MAG_CONSOLE_ADDR";
+
+/// Listen address used when [`ADDR_ENV`] is unset.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:5557";
+
+/// Longest accepted command line, in bytes.
+const MAX_LINE_LEN: usize = 1024;
+
+/// How often the accept loop checks for shutdown.
+const ACCEPT_POLL: Duration = Duration::from_millis(200);
+
+/// How long a connection waits for the tick thread to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Idle time after which a connection is dropped.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A command waiting for the tick thread.
+pub struct ConsoleRequest {
+    pub command: ConsoleCommand,
+    reply: Sender<Result<String, String>>,
+}
+
+impl ConsoleRequest {
+    /// Sends the outcome back to the waiting connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - Outcome of [`command::execute_command`].
+    pub fn respond(self, result: Result<String, String>) {
+        // The client may have hung up; nothing to do then.
+        let _ = self.reply.send(result);
+    }
+}
+
+/// Reads the console settings.
+///
+/// # Arguments
+///
+/// * `token` - Value of [`TOKEN_ENV`], if set.
+/// * `addr` - Value of [`ADDR_ENV`], if set.
+///
+/// # Returns
+///
+/// * `Ok(None)` when no token is configured, `Ok(Some((addr, token)))`
+///   otherwise, or an error for an unparsable or non-loopback address.
+pub fn parse_config(
+    token: Option<&str>,
+    addr: Option<&str>,
+) -> Result<Option<(SocketAddr, String)>, String> {
+    let token = match token.map(str::trim) {
+        None | Some("") => return Ok(None),
+        Some(token) => token.to_owned(),
+    };
+    let addr_text = match addr.map(str::trim) {
+        None | Some("") => DEFAULT_ADDR,
+        Some(addr) => addr,
+    };
+    let addr: SocketAddr = addr_text
+        .parse()
+        .map_err(|_| format!("Invalid {} value '{}'", ADDR_ENV, addr_text))?;
+    if !addr.ip().is_loopback() {
+        return Err(format!(
+            "{} must be a loopback address, got '{}'",
+            ADDR_ENV, addr_text
+        ));
+    }
+    Ok(Some((addr, token)))
+}
+
+/// Compares two tokens without short-circuiting on the first mismatch.
+fn token_matches(given: &str, expected: &str) -> bool {
+    let (given, expected) = (given.as_bytes(), expected.as_bytes());
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Handle for the console listener thread.
+pub struct ConsoleServer {
+    rx: Receiver<ConsoleRequest>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ConsoleServer {
+    /// Binds the console socket and starts the listener thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(console)` on success.
+    /// * `None` when no token is configured or the socket cannot be bound.
+    pub fn spawn() -> Option<Self> {
+        let token = std::env::var(TOKEN_ENV).ok();
+        let addr = std::env::var(ADDR_ENV).ok();
+        let (addr, token) = match parse_config(token.as_deref(), addr.as_deref()) {
+            Ok(Some(config)) => config,
+            Ok(None) => {
+                log::info!("Operator console disabled ({} not set)", TOKEN_ENV);
+                return None;
+            }
+            Err(error) => {
+                log::error!("Operator console disabled: {}", error);
+                return None;
+            }
+        };
+
+        let listener = match TcpListener::bind(addr).and_then(|listener| {
+            listener.set_nonblocking(true)?;
+            Ok(listener)
+        }) {
+            Ok(listener) => listener,
+            Err(error) => {
+                log::error!("Operator console failed to bind {}: {}", addr, error);
+                return None;
+            }
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_thread = Arc::clone(&shutdown);
+        let token = Arc::new(token);
+
+        let handle = thread::Builder::new()
+            .name("operator-console".into())
+            .spawn(move || accept_loop(listener, tx, token, shutdown_thread))
+            .ok()?;
+
+        log::info!("Operator console listening on {}", addr);
+        Some(Self {
+            rx,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// Try to receive the next pending command without blocking.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(request)` when a command is ready.
+    /// * `None` when nothing is queued or the listener has shut down.
+    pub fn try_recv(&self) -> Option<ConsoleRequest> {
+        match self.rx.try_recv() {
+            Ok(request) => Some(request),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Signal the listener to stop and join its thread.
+    pub fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ConsoleServer {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    tx: Sender<ConsoleRequest>,
+    token: Arc<String>,
+    shutdown: Arc<AtomicBool>,
+) {
+    while !shutdown.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let tx = tx.clone();
+                let token = Arc::clone(&token);
+                let spawned = thread::Builder::new()
+                    .name("operator-console-conn".into())
+                    .spawn(move || {
+                        if let Err(error) = serve_connection(stream, &tx, &token) {
+                            log::debug!("Operator console connection {} closed: {}", peer, error);
+                        }
+                    });
+                if let Err(error) = spawned {
+                    log::warn!("Operator console could not serve {}: {}", peer, error);
+                }
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL);
+            }
+            Err(error) => {
+                log::warn!("Operator console accept failed: {}", error);
+                thread::sleep(ACCEPT_POLL);
+            }
+        }
+    }
+}
+
+/// Answers one console line.
+///
+/// # Arguments
+///
+/// * `line` - Line received from the client.
+/// * `authenticated` - Whether `AUTH` already succeeded; updated on success.
+/// * `token` - Configured token.
+/// * `tx` - Channel to the tick thread.
+///
+/// # Returns
+///
+/// * The reply line, or `None` to close the connection.
+fn handle_line(
+    line: &str,
+    authenticated: &mut bool,
+    token: &str,
+    tx: &Sender<ConsoleRequest>,
+) -> Option<String> {
+    let line = line.trim();
+    if line.eq_ignore_ascii_case("quit") {
+        return None;
+    }
+    if !*authenticated {
+        let given = line
+            .strip_prefix("AUTH ")
+            .or_else(|| line.strip_prefix("auth "));
+        return match given {
+            Some(given) if token_matches(given.trim(), token) => {
+                *authenticated = true;
+                Some("OK authenticated".to_owned())
+            }
+            _ => {
+                log::warn!("Operator console rejected an unauthenticated command");
+                None
+            }
+        };
+    }
+
+    let command = match command::parse_command(line) {
+        Ok(command) => command,
+        Err(error) => return Some(format!("ERR {}", error)),
+    };
+    log::info!("Operator console: {}", line);
+    let (reply_tx, reply_rx) = mpsc::channel();
+    let request = ConsoleRequest {
+        command,
+        reply: reply_tx,
+    };
+    if tx.send(request).is_err() {
+        return Some("ERR server is shutting down".to_owned());
+    }
+    Some(match reply_rx.recv_timeout(REPLY_TIMEOUT) {
+        Ok(Ok(message)) => format!("OK {}", message),
+        Ok(Err(error)) => format!("ERR {}", error),
+        Err(_) => "ERR no reply from the game loop".to_owned(),
+    })
+}
+
+fn serve_connection(
+    stream: TcpStream,
+    tx: &Sender<ConsoleRequest>,
+    token: &str,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream).take(MAX_LINE_LEN as u64);
+    let mut authenticated = false;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        reader.set_limit(MAX_LINE_LEN as u64);
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if !line.ends_with('\n') && line.len() >= MAX_LINE_LEN {
+            writeln!(writer, "ERR line too long")?;
+            return Ok(());
+        }
+        let Some(reply) = handle_line(&line, &mut authenticated, token, tx) else {
+            if !authenticated {
+                writeln!(writer, "ERR authentication required")?;
+            }
+            return Ok(());
+        };
+        writeln!(writer, "{}", reply)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_requires_token_and_loopback() {
+        assert_eq!(parse_config(None, None), Ok(None));
+        assert_eq!(parse_config(Some(" "), Some("127.0.0.1:1")), Ok(None));
+        let (addr, token) = parse_config(Some("s3cret"), None).unwrap().unwrap();
+        assert_eq!(addr.to_string(), DEFAULT_ADDR);
+        assert_eq!(token, "s3cret");
+        assert!(parse_config(Some("s3cret"), Some("[::1]:6000")).is_ok());
+        assert!(parse_config(Some("s3cret"), Some("0.0.0.0:5557")).is_err());
+        assert!(parse_config(Some("s3cret"), Some("localhost")).is_err());
+    }
+
+    #[test]
+    fn commands_need_auth_first() {
+        let (tx, rx) = mpsc::channel();
+        let mut authenticated = false;
+        assert_eq!(handle_line("help", &mut authenticated, "tok", &tx), None);
+        assert_eq!(
+            handle_line("AUTH wrong", &mut authenticated, "tok", &tx),
+            None
+        );
+        assert!(!authenticated);
+        assert_eq!(
+            handle_line("AUTH tok\n", &mut authenticated, "tok", &tx).as_deref(),
+            Some("OK authenticated")
+        );
+        assert_eq!(
+            handle_line("warp x", &mut authenticated, "tok", &tx).as_deref(),
+            Some("ERR unknown command 'warp'; try help")
+        );
+
+        let worker = thread::spawn(move || {
+            let request = rx.recv().unwrap();
+            assert_eq!(request.command, ConsoleCommand::Help);
+            request.respond(Ok("listed".to_owned()));
+        });
+        assert_eq!(
+            handle_line("help", &mut authenticated, "tok", &tx).as_deref(),
+            Some("OK listed")
+        );
+        worker.join().unwrap();
+    }
+}
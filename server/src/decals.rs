@@ -0,0 +1,148 @@
+//! Short-lived cosmetic map decals (`SV_MAPDECAL`).
+//!
+//! Combat and spells call [`emit_decal`] to leave a mark on a tile. The
+//! decal is sent to nearby clients advertising
+//! [`CLIENT_CAP_MAP_DECALS`](core::constants::CLIENT_CAP_MAP_DECALS) and then
+//! forgotten — nothing touches the map or the save data. The only state kept
+//! is [`DecalLimiter`], which caps how many decals can be alive per
+//! `DECAL_AREA_SIZE` x `DECAL_AREA_SIZE` block so a long fight does not flood
+//! clients.
+
+use std::collections::{HashMap, VecDeque};
+
+use core::constants::{CLIENT_CAP_MAP_DECALS, SERVER_MAPX, SERVER_MAPY};
+use core::decals::{DecalKind, MAX_DECAL_TTL};
+use core::server_commands::ServerCommandType;
+
+use crate::game_state::GameState;
+
+/// Side length of a decal area in tiles.
+pub const DECAL_AREA_SIZE: u16 = 16;
+
+/// Most decals alive at once in one area.
+pub const MAX_DECALS_PER_AREA: usize = 12;
+
+/// Tracks when the decals of each area expire, to enforce
+/// [`MAX_DECALS_PER_AREA`].
+#[derive(Debug, Default)]
+pub struct DecalLimiter {
+    /// Expiry ticks of live decals per area, oldest first.
+    live: HashMap<(u16, u16), VecDeque<i32>>,
+}
+
+impl DecalLimiter {
+    /// Reserves a slot for a decal on `x`/`y`.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`, `y` - Tile the decal is placed on.
+    /// * `now` - Current tick.
+    /// * `ttl` - Lifetime of the decal in ticks.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the decal may be sent, `false` if its area is full.
+    pub fn try_reserve(&mut self, x: u16, y: u16, now: i32, ttl: u16) -> bool {
+        let area = (x / DECAL_AREA_SIZE, y / DECAL_AREA_SIZE);
+        let expiries = self.live.entry(area).or_default();
+        while expiries.front().is_some_and(|&expires| expires <= now) {
+            expiries.pop_front();
+        }
+        if expiries.len() >= MAX_DECALS_PER_AREA {
+            return false;
+        }
+        // Expiries are kept sorted so the front is always the next to go.
+        let expires = now.saturating_add(i32::from(ttl));
+        let at = expiries.partition_point(|&other| other <= expires);
+        expiries.insert(at, expires);
+        true
+    }
+
+    /// Drops areas whose decals have all expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current tick.
+    pub fn prune(&mut self, now: i32) {
+        self.live
+            .retain(|_, expiries| expiries.back().is_some_and(|&expires| expires > now));
+    }
+}
+
+/// Sends a decal to every nearby client that can draw it.
+///
+/// Does nothing when the decal's area already holds
+/// [`MAX_DECALS_PER_AREA`] live decals.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `x`, `y` - Tile the decal is placed on.
+/// * `kind` - What left the mark.
+/// * `heavy` - Whether to use the larger splatter.
+pub fn emit_decal(gs: &mut GameState, x: i32, y: i32, kind: DecalKind, heavy: bool) {
+    if !(0..SERVER_MAPX).contains(&x) || !(0..SERVER_MAPY).contains(&y) {
+        return;
+    }
+    let (x, y) = (x as u16, y as u16);
+    let ttl = kind.default_ttl().min(MAX_DECAL_TTL);
+    let now = gs.globals.ticker;
+    if !gs.decals.try_reserve(x, y, now, ttl) {
+        return;
+    }
+
+    let mut buf = [0u8; 12];
+    buf[0] = ServerCommandType::MapDecal as u8;
+    buf[1..3].copy_from_slice(&x.to_le_bytes());
+    buf[3..5].copy_from_slice(&y.to_le_bytes());
+    buf[5..9].copy_from_slice(&kind.sprite(heavy).to_le_bytes());
+    buf[9..11].copy_from_slice(&ttl.to_le_bytes());
+    buf[11] = kind.as_u8();
+
+    let range = i32::from(gs.view_radius);
+    for player_id in 1..gs.players.len() {
+        let player = &gs.players[player_id];
+        if player.capabilities & CLIENT_CAP_MAP_DECALS == 0
+            || player.state != core::constants::ST_NORMAL
+            || player.usnr == 0
+        {
+            continue;
+        }
+        let character = &gs.characters[player.usnr];
+        if (i32::from(character.x) - i32::from(x)).abs() > range
+            || (i32::from(character.y) - i32::from(y)).abs() > range
+        {
+            continue;
+        }
+        crate::network_manager::xsend(gs, player_id, &buf, buf.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps_live_decals_per_area() {
+        let mut limiter = DecalLimiter::default();
+        for i in 0..MAX_DECALS_PER_AREA {
+            assert!(limiter.try_reserve(100 + i as u16 % 4, 200, 0, 50));
+        }
+        assert!(!limiter.try_reserve(101, 201, 10, 50));
+        // A neighbouring area has its own budget.
+        assert!(limiter.try_reserve(100 + DECAL_AREA_SIZE, 200, 10, 50));
+        // Once the first batch expires the area takes new decals again.
+        assert!(limiter.try_reserve(101, 201, 50, 50));
+    }
+
+    #[test]
+    fn prune_forgets_expired_areas() {
+        let mut limiter = DecalLimiter::default();
+        assert!(limiter.try_reserve(10, 10, 0, 20));
+        assert!(limiter.try_reserve(40, 40, 0, 100));
+        limiter.prune(20);
+        assert_eq!(limiter.live.len(), 1);
+        limiter.prune(100);
+        assert!(limiter.live.is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+//! Deterministic simulation mode.
+//!
+//! `server --deterministic [seed]` makes a run repeatable: every random
+//! roll of the game rules comes from one ChaCha generator seeded with
+//! `seed` (0 when omitted), and the wall clock seen by the game rules —
+//! login dates, hourly statistics, mail and broker timestamps — starts at
+//! [`DETERMINISTIC_EPOCH`] and advances exactly one tick per game tick, no
+//! matter how fast the host runs them. Ticks are still paced in real time
+//! so clients can connect.
+//!
+//! `--state-hashes <file>` additionally writes a [`state_hash::StateHash`]
+//! of the world after every tick. Two runs from the same world, seed and
+//! input produce identical files. The golden-tick regression tests in
+//! `replay` feed recorded client input through the real game tick and
+//! compare against files in the same format.
+//!
+//! The generator and the clock are per thread, so tests running side by
+//! side do not see each other's seeds. Only the game thread is switched;
+//! the rayon workers of [`crate::tick_phases`] never roll dice.
+
+#[cfg(test)]
+pub mod replay;
+pub mod state_hash;
+
+use std::cell::RefCell;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Command-line flag enabling deterministic mode, optionally followed by
+/// a seed.
+pub const DETERMINISTIC_FLAG: &str = "--deterministic";
+
+/// Command-line flag naming the per-tick state hash file.
+pub const STATE_HASHES_FLAG: &str = "--state-hashes";
+
+/// Unix time the fixed clock starts at (2024-01-01 00:00:00 UTC).
+pub const DETERMINISTIC_EPOCH: i64 = 1_704_067_200;
+
+/// Deterministic options from the command line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeterministicOptions {
+    /// Seed for the game's random generator; `None` keeps the normal,
+    /// unseeded mode.
+    pub seed: Option<u64>,
+    /// File to write the per-tick state hashes to.
+    pub state_hashes: Option<String>,
+}
+
+/// Finds the deterministic options in the server's command line.
+///
+/// # Arguments
+///
+/// * `args` - Command-line arguments without the program name.
+///
+/// # Returns
+///
+/// * The options, or an error for a malformed seed or a missing path.
+pub fn parse_args(args: &[String]) -> Result<DeterministicOptions, String> {
+    let mut options = DeterministicOptions::default();
+    if let Some(pos) = args.iter().position(|arg| arg == DETERMINISTIC_FLAG) {
+        options.seed = Some(match args.get(pos + 1) {
+            Some(seed) if !seed.starts_with("--") => seed
+                .parse()
+                .map_err(|_| format!("{} seed '{}' is not a number", DETERMINISTIC_FLAG, seed))?,
+            _ => 0,
+        });
+    }
+    if let Some(pos) = args.iter().position(|arg| arg == STATE_HASHES_FLAG) {
+        match args.get(pos + 1) {
+            Some(path) if !path.starts_with("--") => options.state_hashes = Some(path.clone()),
+            _ => return Err(format!("{} needs a file path", STATE_HASHES_FLAG)),
+        }
+    }
+    Ok(options)
+}
+
+/// Seeded generator and tick clock of the current thread.
+struct Simulation {
+    rng: ChaCha8Rng,
+    ticks: i64,
+}
+
+thread_local! {
+    static SIMULATION: RefCell<Option<Simulation>> = const { RefCell::new(None) };
+}
+
+/// Switches the current thread to deterministic mode.
+///
+/// Restarts the generator from `seed` and the clock from
+/// [`DETERMINISTIC_EPOCH`] when called again.
+pub fn enable(seed: u64) {
+    SIMULATION.with(|sim| {
+        *sim.borrow_mut() = Some(Simulation {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            ticks: 0,
+        });
+    });
+}
+
+/// Returns the current thread to the unseeded generator and the real clock.
+#[cfg(test)]
+pub fn disable() {
+    SIMULATION.with(|sim| *sim.borrow_mut() = None);
+}
+
+/// Runs `f` with the game's random generator: the seeded one in
+/// deterministic mode, the thread's unseeded one otherwise.
+pub fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    SIMULATION.with(|sim| match sim.borrow_mut().as_mut() {
+        Some(simulation) => f(&mut simulation.rng),
+        None => f(&mut rand::thread_rng()),
+    })
+}
+
+/// Generates a character name with [`with_rng`].
+pub fn random_name() -> String {
+    with_rng(|rng| core::names::generate_name_with(rng))
+}
+
+/// Advances the fixed clock by one game tick; does nothing in normal mode.
+pub fn advance_clock() {
+    SIMULATION.with(|sim| {
+        if let Some(simulation) = sim.borrow_mut().as_mut() {
+            simulation.ticks += 1;
+        }
+    });
+}
+
+/// Unix time of the fixed clock.
+///
+/// # Returns
+///
+/// * `None` in normal mode, where the real clock applies.
+pub fn fixed_unix_now() -> Option<i64> {
+    SIMULATION.with(|sim| {
+        sim.borrow().as_ref().map(|simulation| {
+            DETERMINISTIC_EPOCH + simulation.ticks / i64::from(core::constants::TICKS)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::random_mod;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_seed_and_hash_file() {
+        assert_eq!(parse_args(&args(&[])), Ok(DeterministicOptions::default()));
+        assert_eq!(
+            parse_args(&args(&["--deterministic"])).unwrap().seed,
+            Some(0)
+        );
+        let options =
+            parse_args(&args(&["--deterministic", "42", "--state-hashes", "h.txt"])).unwrap();
+        assert_eq!(options.seed, Some(42));
+        assert_eq!(options.state_hashes.as_deref(), Some("h.txt"));
+        assert!(parse_args(&args(&["--deterministic", "many"])).is_err());
+        assert!(parse_args(&args(&["--state-hashes"])).is_err());
+    }
+
+    #[test]
+    fn seed_fixes_rolls_and_ticks_drive_the_clock() {
+        enable(7);
+        let first: Vec<u32> = (0..16).map(|_| random_mod(1000)).collect();
+        let name = random_name();
+        enable(7);
+        let second: Vec<u32> = (0..16).map(|_| random_mod(1000)).collect();
+        assert_eq!(first, second);
+        assert_eq!(name, random_name());
+
+        assert_eq!(crate::wall_clock::unix_now(), DETERMINISTIC_EPOCH);
+        for _ in 0..core::constants::TICKS * 3600 {
+            advance_clock();
+        }
+        assert_eq!(crate::wall_clock::unix_now(), DETERMINISTIC_EPOCH + 3600);
+        assert_eq!(crate::wall_clock::utc_hour(), 1);
+
+        disable();
+        assert!(fixed_unix_now().is_none());
+    }
+}
@@ -0,0 +1,412 @@
+//! Replays recorded client input and checks the world against golden
+//! state hashes.
+//!
+//! A recording is a small text file:
+//!
+//! ```text
+//! # Alpha picks up the stone at 16,10, then gives it to character 2.
+//! seed 7
+//! ticks 160
+//! 1 0 0610000a000000000000000000000000
+//! 40 0 0c020000000000000000000000000000
+//! ```
+//!
+//! After `seed` and `ticks`, every line is `<tick> <player> <frame>`: the
+//! client command frame, in hex, that the `player`-th player of the scene
+//! sends just before game tick `tick` (counted from 1). [`replay`] runs the
+//! real [`Server::game_tick`] in deterministic mode and hashes the world
+//! after every tick; [`check`] compares the result with a golden file in
+//! the `--state-hashes` format and names the first tick that diverged.
+
+use core::client_commands::ClientCommand;
+
+use super::state_hash::{self, StateHash};
+use crate::game_state::GameState;
+use crate::player;
+use crate::server::Server;
+
+/// One client command frame of a recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedInput {
+    /// Game tick (from 1) the frame arrives before.
+    pub tick: u32,
+    /// Index into the scene's players.
+    pub player: usize,
+    /// Raw client command frame.
+    pub frame: Vec<u8>,
+}
+
+/// Recorded client input for a replay.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// Seed for deterministic mode.
+    pub seed: u64,
+    /// Game ticks to run.
+    pub ticks: u32,
+    /// Frames in tick order.
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl Recording {
+    /// Starts an empty recording.
+    pub fn new(seed: u64, ticks: u32) -> Self {
+        Self {
+            seed,
+            ticks,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Records `command` from `player` before game tick `tick`.
+    pub fn record(&mut self, tick: u32, player: usize, command: &ClientCommand) {
+        self.inputs.push(RecordedInput {
+            tick,
+            player,
+            frame: command.to_bytes(),
+        });
+    }
+
+    /// Parses a recording file.
+    ///
+    /// # Returns
+    ///
+    /// * The recording, or a message naming the first bad line.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut seed = None;
+        let mut ticks = None;
+        let mut inputs: Vec<RecordedInput> = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad = |what: &str| format!("line {}: {}: '{}'", n + 1, what, line);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["seed", value] => seed = Some(value.parse().map_err(|_| bad("bad seed"))?),
+                ["ticks", value] => ticks = Some(value.parse().map_err(|_| bad("bad tick count"))?),
+                [tick, player, frame] => {
+                    let tick: u32 = tick.parse().map_err(|_| bad("bad tick"))?;
+                    if tick == 0 || inputs.last().is_some_and(|last| last.tick > tick) {
+                        return Err(bad("ticks must start at 1 and not go backwards"));
+                    }
+                    inputs.push(RecordedInput {
+                        tick,
+                        player: player.parse().map_err(|_| bad("bad player"))?,
+                        frame: parse_hex(frame).ok_or_else(|| bad("bad frame"))?,
+                    });
+                }
+                _ => return Err(bad("expected 'seed', 'ticks' or '<tick> <player> <frame>'")),
+            }
+        }
+        let recording = Self {
+            seed: seed.ok_or("recording has no seed")?,
+            ticks: ticks.ok_or("recording has no tick count")?,
+            inputs,
+        };
+        if let Some(last) = recording.inputs.last()
+            && last.tick > recording.ticks
+        {
+            return Err(format!(
+                "input at tick {} is past the end of the recording ({} ticks)",
+                last.tick, recording.ticks
+            ));
+        }
+        Ok(recording)
+    }
+
+    /// Formats the recording in the format read by [`Recording::parse`].
+    pub fn to_text(&self) -> String {
+        let mut text = format!("seed {}\nticks {}\n", self.seed, self.ticks);
+        for input in &self.inputs {
+            let frame: String = input.frame.iter().map(|b| format!("{:02x}", b)).collect();
+            text.push_str(&format!("{} {} {}\n", input.tick, input.player, frame));
+        }
+        text
+    }
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if text.is_empty() || !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Replays `recording` and hashes the world after every tick.
+///
+/// Runs with deterministic mode enabled on the current thread and switches
+/// it off again afterwards.
+///
+/// # Arguments
+///
+/// * `server` - Server used only to drive ticks; never initialized.
+/// * `gs` - World to replay into.
+/// * `recording` - Input to feed.
+/// * `players` - Player slots the recording's player indices refer to.
+/// * `before_tick` - Called with the tick number before each tick's input
+///   is fed, e.g. to inject a fault.
+///
+/// # Returns
+///
+/// * One hash per tick, or an error for input naming an unknown player.
+pub fn replay(
+    server: &mut Server,
+    gs: &mut GameState,
+    recording: &Recording,
+    players: &[usize],
+    mut before_tick: impl FnMut(&mut GameState, u32),
+) -> Result<Vec<StateHash>, String> {
+    if let Some(input) = recording.inputs.iter().find(|i| i.player >= players.len()) {
+        return Err(format!(
+            "input at tick {} is from player {}, the scene has {}",
+            input.tick,
+            input.player,
+            players.len()
+        ));
+    }
+
+    super::enable(recording.seed);
+    let mut hashes = Vec::with_capacity(recording.ticks as usize);
+    let mut inputs = recording.inputs.iter().peekable();
+    for tick in 1..=recording.ticks {
+        before_tick(gs, tick);
+        while let Some(input) = inputs.next_if(|input| input.tick == tick) {
+            let nr = players[input.player];
+            let inbuf = &mut gs.players[nr].inbuf;
+            inbuf.fill(0);
+            let len = input.frame.len().min(inbuf.len());
+            inbuf[..len].copy_from_slice(&input.frame[..len]);
+            gs.players[nr].in_len = len;
+            player::plr_cmd(gs, nr);
+        }
+        server.game_tick(gs);
+        hashes.push(StateHash::of(gs));
+    }
+    super::disable();
+    Ok(hashes)
+}
+
+/// Parses a line written by [`StateHash::to_line`].
+///
+/// # Returns
+///
+/// * The hash, or why the line is malformed or its total does not match
+///   its sections.
+pub fn parse_hash_line(line: &str) -> Result<StateHash, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 7 {
+        return Err(format!(
+            "expected 7 fields, got {}: '{}'",
+            fields.len(),
+            line
+        ));
+    }
+    let tick = fields[0]
+        .parse()
+        .map_err(|_| format!("bad tick '{}'", fields[0]))?;
+    let hex =
+        |text: &str| u64::from_str_radix(text, 16).map_err(|_| format!("bad hash '{}'", text));
+    let hash = StateHash {
+        tick,
+        globals: hex(fields[2])?,
+        characters: hex(fields[3])?,
+        items: hex(fields[4])?,
+        map: hex(fields[5])?,
+        effects: hex(fields[6])?,
+    };
+    if hash.total() != hex(fields[1])? {
+        return Err(format!("total does not match the sections: '{}'", line));
+    }
+    Ok(hash)
+}
+
+/// Parses a state hash file, skipping blank and `#` lines.
+pub fn parse_hashes(text: &str) -> Result<Vec<StateHash>, String> {
+    text.lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(n, line)| parse_hash_line(line).map_err(|e| format!("line {}: {}", n + 1, e)))
+        .collect()
+}
+
+/// Names of the world sections whose hashes differ between `a` and `b`.
+pub fn differences(a: &StateHash, b: &StateHash) -> Vec<&'static str> {
+    a.sections()
+        .iter()
+        .zip(b.sections())
+        .filter(|(mine, theirs)| mine.1 != theirs.1)
+        .map(|(mine, _)| mine.0)
+        .collect()
+}
+
+/// Compares replayed hashes with a golden state hash file.
+///
+/// # Returns
+///
+/// * `Err` naming the first tick and the world sections that differ.
+pub fn check(actual: &[StateHash], golden: &str) -> Result<(), String> {
+    let golden = parse_hashes(golden)?;
+    for (actual, expected) in actual.iter().zip(&golden) {
+        if actual != expected {
+            let sections = differences(actual, expected);
+            return Err(if sections.is_empty() {
+                format!(
+                    "tick {} was expected to be tick {}",
+                    actual.tick, expected.tick
+                )
+            } else {
+                format!(
+                    "tick {}: {} differ from the golden hashes",
+                    actual.tick,
+                    sections.join(", ")
+                )
+            });
+        }
+    }
+    if actual.len() != golden.len() {
+        return Err(format!(
+            "replay produced {} ticks, the golden file has {}",
+            actual.len(),
+            golden.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Formats hashes as a state hash file, as written by `--state-hashes`.
+pub fn to_golden(hashes: &[StateHash]) -> String {
+    let mut text = format!("{}\n", state_hash::HEADER);
+    for hash in hashes {
+        text.push_str(&hash.to_line());
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::constants::{CharacterFlags, USE_EMPTY, WN_BODY};
+
+    use crate::testutil::{CharSpec, ItemSpec, World};
+
+    /// Golden recording and hashes. Regenerate the hashes after an
+    /// intended change of the game rules with
+    /// `MAG_BLESS_GOLDEN=1 cargo test -p server golden`.
+    const WALK_AND_TRADE: &str = include_str!("golden/walk_and_trade.replay");
+    const WALK_AND_TRADE_HASHES: &str = include_str!("golden/walk_and_trade.hashes");
+    const HASHES_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/deterministic/golden/walk_and_trade.hashes"
+    );
+
+    /// Scene the golden recording was made in: two players, a rat and
+    /// loose items.
+    fn scene() -> World {
+        let immortal = CharacterFlags::Immortal.bits();
+        World::new()
+            .with_char(
+                CharSpec::player("Alpha")
+                    .at(10, 10)
+                    .flags(immortal)
+                    .carrying(ItemSpec::new("Torch"))
+                    .wearing(WN_BODY, ItemSpec::new("Tunic").armor(1)),
+            )
+            .with_char(
+                CharSpec::player("Beta")
+                    .at(14, 10)
+                    .flags(immortal)
+                    .holding(ItemSpec::new("Dagger").weapon(2)),
+            )
+            .with_char(
+                CharSpec::npc("Rat")
+                    .at(12, 16)
+                    .hp(10)
+                    .carrying(ItemSpec::new("Tail")),
+            )
+            .with_item_at(16, 10, ItemSpec::new("Stone"))
+            .with_item_at(8, 14, ItemSpec::new("Bone"))
+    }
+
+    /// Replays the golden recording, letting `meddle` change the world
+    /// before every tick.
+    fn run_golden(meddle: fn(&mut GameState, u32)) -> Vec<StateHash> {
+        scene().run(move |gs, w| {
+            let recording = Recording::parse(WALK_AND_TRADE).unwrap();
+            let players = [w.player(0), w.player(1)];
+            replay(&mut Server::new(), gs, &recording, &players, meddle).unwrap()
+        })
+    }
+
+    #[test]
+    fn recordings_round_trip() {
+        let mut recording = Recording::new(3, 10);
+        recording.record(1, 0, &ClientCommand::new_move(12, 10));
+        recording.record(4, 1, &ClientCommand::new_reset());
+        assert_eq!(Recording::parse(&recording.to_text()), Ok(recording));
+
+        assert!(Recording::parse("seed 1\nticks 5\n6 0 00").is_err());
+        assert!(Recording::parse("seed 1\nticks 5\n3 0 00\n2 0 00").is_err());
+        assert!(Recording::parse("seed 1\nticks 5\n1 0 0g").is_err());
+        assert!(Recording::parse("ticks 5").is_err());
+    }
+
+    #[test]
+    fn hash_lines_round_trip_and_reject_tampering() {
+        let hash = StateHash {
+            tick: 12,
+            globals: 1,
+            characters: 2,
+            items: 3,
+            map: 4,
+            effects: 5,
+        };
+        let line = hash.to_line();
+        assert_eq!(parse_hash_line(&line), Ok(hash));
+        assert_eq!(parse_hashes(&to_golden(&[hash])), Ok(vec![hash]));
+
+        let tampered = line.replacen(" 0000000000000003", " 0000000000000009", 1);
+        assert!(parse_hash_line(&tampered).is_err());
+        assert!(parse_hash_line("12 zz").is_err());
+    }
+
+    #[test]
+    fn golden_walk_and_trade_matches() {
+        let hashes = run_golden(|_, _| {});
+        if std::env::var_os("MAG_BLESS_GOLDEN").is_some() {
+            std::fs::write(HASHES_PATH, to_golden(&hashes)).unwrap();
+            return;
+        }
+        if let Err(e) = check(&hashes, WALK_AND_TRADE_HASHES) {
+            panic!(
+                "{}\nIf the game rules changed on purpose, rerun with MAG_BLESS_GOLDEN=1.",
+                e
+            );
+        }
+    }
+
+    #[test]
+    fn replays_repeat_and_catch_a_reset_item() {
+        let first = run_golden(|_, _| {});
+        assert_eq!(first, run_golden(|_, _| {}));
+
+        // An item vanishing mid-run, the kind of bug this harness is for.
+        let vanished = run_golden(|gs, tick| {
+            if tick == 30 {
+                let stone = (1..gs.items.len())
+                    .find(|&n| gs.items[n].get_name() == "Stone")
+                    .unwrap();
+                gs.items[stone].used = USE_EMPTY;
+            }
+        });
+        let golden = to_golden(&first);
+        let error = check(&vanished, &golden).unwrap_err();
+        assert!(error.starts_with("tick 30:"), "{}", error);
+        assert!(error.contains("items"), "{}", error);
+    }
+}
@@ -0,0 +1,230 @@
+//! Per-tick hash of the world state.
+//!
+//! The hash covers the globals, every used character, item and effect slot
+//! and every map tile, each section hashed separately so a mismatch names
+//! the part of the world that diverged. Fields that measure the host rather
+//! than the game (`load`, `load_avg` and the network byte counters) are left
+//! out. Player connection state is not part of the hash.
+//!
+//! The hasher is FNV-1a over 64-bit words, which, unlike
+//! `std::collections::hash_map::DefaultHasher`, is stable across builds and
+//! platforms, so hashes written by one build can be checked by another.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use bincode::Encode;
+use bincode::enc::write::Writer;
+use bincode::error::EncodeError;
+use core::constants::USE_EMPTY;
+
+use crate::game_state::GameState;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Header line of a state hash file.
+pub const HEADER: &str = "# tick total globals characters items map effects";
+
+/// Word-wise FNV-1a hasher, also usable as a bincode writer.
+struct WordHasher(u64);
+
+impl WordHasher {
+    fn new() -> Self {
+        Self(FNV_OFFSET)
+    }
+
+    fn word(&mut self, word: u64) {
+        self.0 = (self.0 ^ word).wrapping_mul(FNV_PRIME);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.word(u64::from_le_bytes(chunk.try_into().expect("chunk of 8")));
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut last = [0u8; 8];
+            last[..rest.len()].copy_from_slice(rest);
+            // Tag the padding so `[1]` and `[1, 0]` hash differently.
+            last[7] ^= rest.len() as u8;
+            self.word(u64::from_le_bytes(last));
+        }
+    }
+
+    fn encoded(&mut self, value: &impl Encode) {
+        bincode::encode_into_writer(value, &mut *self, bincode::config::standard())
+            .expect("hashing never fails to write");
+    }
+}
+
+impl Writer for WordHasher {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        self.bytes(bytes);
+        Ok(())
+    }
+}
+
+/// Hash of the world after one tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateHash {
+    /// `globals.ticker` when the hash was taken.
+    pub tick: i32,
+    pub globals: u64,
+    pub characters: u64,
+    pub items: u64,
+    pub map: u64,
+    pub effects: u64,
+}
+
+impl StateHash {
+    /// Hashes the current world.
+    pub fn of(gs: &GameState) -> Self {
+        let mut globals = gs.globals.clone();
+        globals.load = 0;
+        globals.load_avg = 0;
+        globals.recv = 0;
+        globals.send = 0;
+        let mut hasher = WordHasher::new();
+        hasher.encoded(&globals);
+        let globals = hasher.0;
+
+        let mut hasher = WordHasher::new();
+        for (index, character) in gs.characters.iter().enumerate() {
+            if character.used != USE_EMPTY {
+                hasher.word(index as u64);
+                hasher.encoded(character);
+            }
+        }
+        let characters = hasher.0;
+
+        let mut hasher = WordHasher::new();
+        for (index, item) in gs.items.iter().enumerate() {
+            if item.used != USE_EMPTY {
+                hasher.word(index as u64);
+                hasher.encoded(item);
+            }
+        }
+        let items = hasher.0;
+
+        let mut hasher = WordHasher::new();
+        for tile in &gs.map {
+            hasher.word(
+                u64::from(tile.sprite)
+                    | u64::from(tile.fsprite) << 16
+                    | u64::from(tile.dlight) << 32
+                    | u64::from(tile.light as u16) << 48,
+            );
+            hasher.word(u64::from(tile.ch) | u64::from(tile.to_ch) << 32);
+            hasher.word(u64::from(tile.it));
+            hasher.word(tile.flags);
+        }
+        let map = hasher.0;
+
+        let mut hasher = WordHasher::new();
+        for (index, effect) in gs.effects.iter().enumerate() {
+            if effect.used != 0 {
+                hasher.word(index as u64);
+                hasher.encoded(effect);
+            }
+        }
+        let effects = hasher.0;
+
+        Self {
+            tick: gs.globals.ticker,
+            globals,
+            characters,
+            items,
+            map,
+            effects,
+        }
+    }
+
+    /// One hash over all sections.
+    pub fn total(&self) -> u64 {
+        let mut hasher = WordHasher::new();
+        for section in self.sections() {
+            hasher.word(section.1);
+        }
+        hasher.0
+    }
+
+    /// Section names and their hashes, in file order.
+    pub fn sections(&self) -> [(&'static str, u64); 5] {
+        [
+            ("globals", self.globals),
+            ("characters", self.characters),
+            ("items", self.items),
+            ("map", self.map),
+            ("effects", self.effects),
+        ]
+    }
+
+    /// Formats the hash as a line of a state hash file (see [`HEADER`]).
+    pub fn to_line(&self) -> String {
+        let mut line = format!("{} {:016x}", self.tick, self.total());
+        for (_, hash) in self.sections() {
+            line.push_str(&format!(" {:016x}", hash));
+        }
+        line
+    }
+}
+
+/// Writes one [`StateHash`] line per tick, for `--state-hashes`.
+pub struct StateHashLog {
+    out: BufWriter<File>,
+}
+
+impl StateHashLog {
+    /// Creates (or truncates) the hash file and writes its header.
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create state hash file {}: {}", path, e))?;
+        let mut out = BufWriter::new(file);
+        writeln!(out, "{}", HEADER)
+            .map_err(|e| format!("Failed to write state hash file {}: {}", path, e))?;
+        Ok(Self { out })
+    }
+
+    /// Hashes the world and appends the line. Write errors are logged and
+    /// never interrupt the game loop.
+    pub fn record(&mut self, gs: &GameState) {
+        let line = StateHash::of(gs).to_line();
+        if let Err(e) = writeln!(self.out, "{}", line) {
+            log::warn!("Failed to write state hash: {}", e);
+        }
+    }
+}
+
+impl Drop for StateHashLog {
+    fn drop(&mut self) {
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deterministic::replay::differences;
+    use crate::test_helpers::with_test_gs;
+
+    #[test]
+    fn sections_track_their_own_part_of_the_world() {
+        with_test_gs(|gs| {
+            let before = StateHash::of(gs);
+            assert_eq!(before, StateHash::of(gs));
+
+            gs.items[5].used = core::constants::USE_ACTIVE;
+            let after = StateHash::of(gs);
+            assert_eq!(differences(&after, &before), vec!["items"]);
+
+            // Host measurements are not part of the world.
+            gs.globals.load = 250;
+            assert_eq!(StateHash::of(gs), after);
+
+            gs.map[77].it = 5;
+            assert_eq!(differences(&StateHash::of(gs), &after), vec!["map"]);
+        });
+    }
+}
@@ -0,0 +1,231 @@
+//! Chest driver ([`CHEST_DRIVER`]): locks, keys, loot rolls.
+//!
+//! The item layout and template requirements are documented in
+//! [`core::chests`]. Respawn needs no code here: `use_driver` activates the
+//! chest for `duration` ticks after a successful open, and `item_tick_expire`
+//! closes it again once that runs out.
+
+use core::chests::{CHEST_DRIVER, ITEM_STATUS_OPENING, LootTable, find_loot_table};
+use core::constants::USE_EMPTY;
+use core::skills;
+use core::types::FontColor;
+use server::journal::GoldTransferKind;
+
+use crate::driver::item_damage_citem;
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::helpers;
+
+/// Where the key that opened a chest was found.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum KeySlot {
+    /// Held under the mouse cursor.
+    Citem,
+    /// In backpack slot `n`.
+    Backpack(usize),
+}
+
+/// Finds a key with template `key` on `cn`.
+fn find_key(gs: &GameState, cn: usize, key: u32) -> Option<KeySlot> {
+    let ch = &gs.characters[cn];
+    let citem = ch.citem as usize;
+    if citem != 0 && citem & 0x8000_0000 == 0 && u32::from(gs.items[citem].temp) == key {
+        return Some(KeySlot::Citem);
+    }
+    ch.item
+        .iter()
+        .position(|&it| it != 0 && u32::from(gs.items[it as usize].temp) == key)
+        .map(KeySlot::Backpack)
+}
+
+/// Tries to pick the lock of chest `item_idx` with the lock-pick `cn` holds.
+///
+/// # Returns
+///
+/// * `None` if `cn` holds no lock-pick, otherwise whether the pick worked.
+fn try_pick_lock(gs: &mut GameState, cn: usize, item_idx: usize) -> Option<bool> {
+    let citem = gs.characters[cn].citem as usize;
+    if citem == 0 || citem & 0x8000_0000 != 0 || gs.items[citem].driver != 3 {
+        return None;
+    }
+    let level = gs.items[item_idx].data[1];
+    let skill = u32::from(gs.characters[cn].skill[skills::SK_LOCK][5]) + gs.items[citem].data[0];
+    let picked = level != 0 && skill >= level + helpers::random_mod(20);
+    if !picked {
+        gs.do_character_log(cn, FontColor::Blue, "You failed to pick the lock.\n");
+    }
+    item_damage_citem(gs, cn, 1);
+    Some(picked)
+}
+
+/// Rolls `table` into `cn`'s backpack and purse.
+fn give_loot(gs: &mut GameState, cn: usize, table: &LootTable) {
+    for _ in 0..table.rolls {
+        let Some(template) = table.pick(helpers::random_mod(table.total_weight())) else {
+            continue;
+        };
+        let Some(in2) = God::create_item(gs, template as usize) else {
+            log::error!("use_chest: could not create item template {}", template);
+            continue;
+        };
+        if !God::give_character_item(gs, cn, in2) {
+            gs.items[in2].used = USE_EMPTY;
+            continue;
+        }
+        let name = gs.items[in2].get_name().to_owned();
+        gs.do_character_log(cn, FontColor::Green, &format!("You found a {}.\n", name));
+    }
+
+    let (min, max) = table.gold;
+    let gold = min + helpers::random_mod(max - min + 1);
+    if gold != 0 {
+        gs.transfer_gold(0, cn, gold as i32, GoldTransferKind::ChestLoot);
+        gs.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("You found {}G {}S.\n", gold / 100, gold % 100),
+        );
+    }
+}
+
+/// Opens chest `item_idx` for `cn`.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `cn` - Character opening the chest; `0` when the item tick closes it.
+/// * `item_idx` - Chest item.
+///
+/// # Returns
+///
+/// * `true` if the chest opened (and should activate), otherwise `false`.
+pub fn use_chest(gs: &mut GameState, cn: usize, item_idx: usize) -> bool {
+    if cn == 0 {
+        // Respawn: the chest closes and is full again.
+        return true;
+    }
+    debug_assert_eq!(gs.items[item_idx].driver, CHEST_DRIVER);
+
+    if gs.items[item_idx].active != 0 {
+        gs.do_character_log(cn, FontColor::Yellow, "The chest is empty.\n");
+        return false;
+    }
+
+    let Some(table) = find_loot_table(gs.items[item_idx].data[2]) else {
+        log::error!(
+            "use_chest: item {} has unknown loot table {}",
+            item_idx,
+            gs.items[item_idx].data[2]
+        );
+        return false;
+    };
+
+    let free_slots = gs.characters[cn].item.iter().filter(|&&it| it == 0).count();
+    if free_slots < table.rolls {
+        gs.do_character_log(
+            cn,
+            FontColor::Blue,
+            "Your backpack is too full to empty the chest.\n",
+        );
+        return false;
+    }
+
+    let key = gs.items[item_idx].data[0];
+    let level = gs.items[item_idx].data[1];
+    if key != 0 || level != 0 {
+        let key_slot = if key != 0 {
+            find_key(gs, cn, key)
+        } else {
+            None
+        };
+        match key_slot {
+            Some(slot) => {
+                if gs.items[item_idx].data[3] != 0 {
+                    let used = match slot {
+                        KeySlot::Citem => std::mem::take(&mut gs.characters[cn].citem),
+                        KeySlot::Backpack(n) => std::mem::take(&mut gs.characters[cn].item[n]),
+                    };
+                    gs.items[used as usize].used = USE_EMPTY;
+                    gs.do_character_log(cn, FontColor::Yellow, "The key vanished.\n");
+                }
+            }
+            None => match try_pick_lock(gs, cn, item_idx) {
+                Some(true) => {}
+                Some(false) => return false,
+                None => {
+                    gs.do_character_log(
+                        cn,
+                        FontColor::Blue,
+                        "It's locked and you don't have the right key.\n",
+                    );
+                    return false;
+                }
+            },
+        }
+    }
+
+    give_loot(gs, cn, table);
+    gs.items[item_idx].status[1] = ITEM_STATUS_OPENING;
+    let (x, y) = (
+        i32::from(gs.items[item_idx].x),
+        i32::from(gs.items[item_idx].y),
+    );
+    gs.do_area_sound(0, 0, x, y, 10);
+    log::info!("Character {} opened chest {}", cn, item_idx);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+
+    fn setup_chest(gs: &mut GameState, key: u32, level: u32) -> usize {
+        let chest = 20;
+        gs.items[chest].used = USE_ACTIVE;
+        gs.items[chest].driver = CHEST_DRIVER;
+        gs.items[chest].data[0] = key;
+        gs.items[chest].data[1] = level;
+        gs.items[chest].data[2] = 1;
+        for template in [101, 102] {
+            gs.item_templates[template].used = USE_ACTIVE;
+        }
+        chest
+    }
+
+    #[test]
+    fn unlocked_chest_gives_loot_once_until_it_respawns() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            let chest = setup_chest(gs, 0, 0);
+
+            assert!(use_chest(gs, cn, chest));
+            assert_ne!(gs.characters[cn].item[0], 0);
+            assert!(gs.characters[cn].gold >= 50);
+            assert_eq!(gs.items[chest].status[1], ITEM_STATUS_OPENING);
+
+            gs.items[chest].active = 100;
+            assert!(!use_chest(gs, cn, chest));
+            assert_eq!(gs.characters[cn].item[1], 0);
+        });
+    }
+
+    #[test]
+    fn locked_chest_needs_key_and_consumes_vanishing_key() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            let chest = setup_chest(gs, 300, 0);
+            gs.items[chest].data[3] = 1;
+            assert!(!use_chest(gs, cn, chest));
+
+            let key = 21;
+            gs.items[key].used = USE_ACTIVE;
+            gs.items[key].temp = 300;
+            gs.characters[cn].item[5] = key as u32;
+            assert!(use_chest(gs, cn, chest));
+            assert_eq!(gs.characters[cn].item[5], 0);
+            assert_eq!(gs.items[key].used, USE_EMPTY);
+        });
+    }
+}
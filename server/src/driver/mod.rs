@@ -1,11 +1,14 @@
+pub mod chest;
 pub mod generic;
 pub mod look;
 pub mod npc;
 pub mod skill;
+pub mod skill_use;
 pub mod special;
 pub mod use_item;
 
 // Re-export all submodules so callers can use `crate::driver::<fn>`
+pub use chest::*;
 pub use generic::*;
 pub use look::*;
 pub use npc::*;
@@ -6,10 +6,12 @@ use crate::helpers;
 use crate::player;
 use crate::populate;
 use core::constants::*;
+use core::dialogue::DialogueLine;
 use core::skills;
 use core::string_operations::c_string_to_str;
 use core::traits;
 use core::types::Character;
+use server::journal::GoldTransferKind;
 
 // Helper functions
 
@@ -880,13 +882,9 @@ pub fn npc_give(gs: &mut GameState, cn: usize, co: usize, in_item: usize, money:
             God::take_from_char(gs, in_item, cn);
             gs.items[in_item].used = core::constants::USE_EMPTY;
 
-            gs.do_sayx(
-                cn,
-                &format!(
-                    "Ah, a black candle! Great work, {}! Now we will have peace for a while...",
-                    gs.characters[co].get_name()
-                ),
-            );
+            let line = DialogueLine::new("quest.turn_in.black_candle")
+                .with("player", gs.characters[co].get_name());
+            gs.do_say_line(cn, &line);
             gs.do_area_log(
                 cn,
                 0,
@@ -900,15 +898,10 @@ pub fn npc_give(gs: &mut GameState, cn: usize, co: usize, in_item: usize, money:
             );
         } else {
             // Thank you message
-            let ref_name = c_string_to_str(&gs.items[in_item].reference).to_owned();
-            gs.do_sayx(
-                cn,
-                &format!(
-                    "Thank you {}. That's the {} I wanted.",
-                    gs.characters[co].get_name(),
-                    ref_name
-                ),
-            );
+            let line = DialogueLine::new("quest.turn_in.thanks")
+                .with("player", gs.characters[co].get_name())
+                .with("item", c_string_to_str(&gs.items[in_item].reference));
+            gs.do_say_line(cn, &line);
         }
 
         // Quest-requested items: teach skill / give exp
@@ -941,36 +934,25 @@ pub fn npc_give(gs: &mut GameState, cn: usize, co: usize, in_item: usize, money:
             }
 
             if skill_nr == skills::SK_STUN && (co_kindred & traits::KIN_SEYAN_DU) != 0 {
-                gs.do_sayx(
-                    cn,
-                    &format!(
-                        "Bring me the item again to learn Immunity, {}!",
-                        gs.characters[co].get_name()
-                    ),
-                );
+                let line = DialogueLine::new("quest.turn_in.again_immunity")
+                    .with("player", gs.characters[co].get_name());
+                gs.do_say_line(cn, &line);
             }
             if skill_nr == skills::SK_CURSE && (co_kindred & traits::KIN_SEYAN_DU) != 0 {
-                gs.do_sayx(
-                    cn,
-                    &format!(
-                        "Bring me the item again to learn Surround Hit, {}!",
-                        gs.characters[co].get_name()
-                    ),
-                );
+                let line = DialogueLine::new("quest.turn_in.again_surround_hit")
+                    .with("player", gs.characters[co].get_name());
+                gs.do_say_line(cn, &line);
             }
 
             let skill_name = skills::get_skill_name(skill_nr);
-            gs.do_sayx(cn, &format!("Now I'll teach you {}.", skill_name));
+            let line = DialogueLine::new("quest.turn_in.teach_skill").with("skill", skill_name);
+            gs.do_say_line(cn, &line);
 
             if gs.characters[co].skill[skill_nr][0] != 0 {
-                gs.do_sayx(
-                    cn,
-                    &format!(
-                        "But you already know {}, {}!",
-                        skill_name,
-                        gs.characters[co].get_name()
-                    ),
-                );
+                let line = DialogueLine::new("quest.turn_in.already_known")
+                    .with("skill", skill_name)
+                    .with("player", gs.characters[co].get_name());
+                gs.do_say_line(cn, &line);
                 // give item back to player
                 God::take_from_char(gs, in_item, cn);
                 God::give_character_item(gs, co, in_item);
@@ -995,13 +977,9 @@ pub fn npc_give(gs: &mut GameState, cn: usize, co: usize, in_item: usize, money:
 
                 let give_exp = gs.characters[cn].data[51];
                 if give_exp != 0 {
-                    gs.do_sayx(
-                        cn,
-                        &format!(
-                            "Now I'll teach you a bit about life, the world and everything, {}.",
-                            gs.characters[co].get_name()
-                        ),
-                    );
+                    let line = DialogueLine::new("quest.turn_in.teach_experience")
+                        .with("player", gs.characters[co].get_name());
+                    gs.do_say_line(cn, &line);
                     gs.do_give_exp(co, give_exp, 0, -1);
                 }
 
@@ -1014,17 +992,15 @@ pub fn npc_give(gs: &mut GameState, cn: usize, co: usize, in_item: usize, money:
         // Return-gift
         let give_temp = gs.characters[cn].data[66];
         if give_temp != 0 {
-            gs.do_sayx(
-                cn,
-                &format!(
-                    "Here is your {} in exchange.",
-                    c_string_to_str(&gs.item_templates[give_temp as usize].reference).to_owned()
-                ),
+            let line = DialogueLine::new("quest.turn_in.return_gift").with(
+                "item",
+                c_string_to_str(&gs.item_templates[give_temp as usize].reference),
             );
+            gs.do_say_line(cn, &line);
             God::take_from_char(gs, in_item, cn);
             gs.items[in_item].used = core::constants::USE_EMPTY;
             if let Some(new_item) = God::create_item(gs, give_temp as usize) {
-                God::give_character_item(gs, co, new_item);
+                gs.give_reward_item(co, new_item);
             }
         }
 
@@ -1058,9 +1034,8 @@ pub fn npc_give(gs: &mut GameState, cn: usize, co: usize, in_item: usize, money:
         return false;
     } else if in_item == 0 && money != 0 {
         // NPC doesn't take money
-        gs.do_sayx(cn, "I don't take money from you!");
-        gs.characters[co].gold += money;
-        gs.characters[cn].gold -= money;
+        gs.do_say_line(cn, &DialogueLine::new("npc.give.no_money"));
+        gs.transfer_gold(cn, co, money, GoldTransferKind::NpcRefund);
     } else {
         // Not accepted - return item to giver
         God::take_from_char(gs, in_item, cn);
@@ -1162,22 +1137,14 @@ pub fn npc_sight_turn_in(gs: &mut GameState, cn: usize, co: usize, in_item: usiz
         }
 
         if skill_nr == skills::SK_STUN && (co_kindred & traits::KIN_SEYAN_DU) != 0 {
-            gs.do_sayx(
-                cn,
-                &format!(
-                    "Bring me the item again to learn Immunity, {}!",
-                    gs.characters[co].get_name()
-                ),
-            );
+            let line = DialogueLine::new("quest.turn_in.again_immunity")
+                .with("player", gs.characters[co].get_name());
+            gs.do_say_line(cn, &line);
         }
         if skill_nr == skills::SK_CURSE && (co_kindred & traits::KIN_SEYAN_DU) != 0 {
-            gs.do_sayx(
-                cn,
-                &format!(
-                    "Bring me the item again to learn Surround Hit, {}!",
-                    gs.characters[co].get_name()
-                ),
-            );
+            let line = DialogueLine::new("quest.turn_in.again_surround_hit")
+                .with("player", gs.characters[co].get_name());
+            gs.do_say_line(cn, &line);
         }
 
         if gs.characters[co].skill[skill_nr][0] != 0 {
@@ -1185,18 +1152,14 @@ pub fn npc_sight_turn_in(gs: &mut GameState, cn: usize, co: usize, in_item: usiz
             return false;
         }
 
-        let ref_name = c_string_to_str(&gs.items[in_item].reference).to_owned();
-        gs.do_sayx(
-            cn,
-            &format!(
-                "Thank you {}. That's the {} I wanted.",
-                gs.characters[co].get_name(),
-                ref_name
-            ),
-        );
+        let line = DialogueLine::new("quest.turn_in.thanks")
+            .with("player", gs.characters[co].get_name())
+            .with("item", c_string_to_str(&gs.items[in_item].reference));
+        gs.do_say_line(cn, &line);
 
         let skill_name = skills::get_skill_name(skill_nr);
-        gs.do_sayx(cn, &format!("Now I'll teach you {}.", skill_name));
+        let line = DialogueLine::new("quest.turn_in.teach_skill").with("skill", skill_name);
+        gs.do_say_line(cn, &line);
 
         // Teach and consume.
         gs.characters[co].skill[skill_nr][0] = 1;
@@ -1246,7 +1209,7 @@ pub fn npc_sight_turn_in(gs: &mut GameState, cn: usize, co: usize, in_item: usiz
         God::take_from_char(gs, in_item, co);
         gs.items[in_item].used = core::constants::USE_EMPTY;
         if let Some(new_item) = God::create_item(gs, give_temp as usize) {
-            God::give_character_item(gs, co, new_item);
+            gs.give_reward_item(co, new_item);
         }
         return true;
     }
@@ -3211,8 +3174,7 @@ pub fn npc_loot_grave(gs: &mut GameState, cn: usize, in_idx: usize) -> bool {
             co_gold as f32 / 100.0,
             co_name
         );
-        gs.characters[cn].gold += co_gold;
-        gs.characters[co].gold = 0;
+        gs.transfer_gold(co, cn, co_gold, GoldTransferKind::CorpseLoot);
         return true;
     }
 
@@ -5,16 +5,15 @@ use core::{
         NT_DIDHIT, NT_GOTHIT, NT_GOTMISS, TICKS, USE_EMPTY,
     },
     skills::{
-        SK_ANGUISH_EARTH, SK_ANGUISH_ICE, SK_ANGUISH_LAVA, SK_AXE, SK_BLADE_DANCE, SK_BLAST,
-        SK_BLESS, SK_CONCEN, SK_CONTAGION, SK_CURSE, SK_DAGGER, SK_DELIVER_DEATH, SK_DISARM,
-        SK_DISPEL, SK_DISTRACT, SK_ELEMENT_SWITCHING, SK_ENHANCE, SK_GASH, SK_GHOST, SK_HEAL,
-        SK_ICE_STUN, SK_IDENT, SK_IMMUN, SK_INNER_STRENGTH, SK_KINDRED_SPIRIT, SK_LAVA_BLAST,
-        SK_LIGHT, SK_LOCK, SK_MEDIT, SK_MSHIELD, SK_PARASITE, SK_PROTECT, SK_RAINS_OF_RENEWAL,
-        SK_RECALL, SK_REGEN, SK_REPAIR, SK_RESIST, SK_REST, SK_REVENANT_CONDUIT,
-        SK_REVENANT_CONDUIT2, SK_SEEING_RED, SK_SENSE, SK_SPECTRAL_PACT, SK_SPECTRAL_PACT2,
-        SK_SPELLCASTER_KINDRED_SPIRIT, SK_STAFF, SK_STUN, SK_SUNS_BLESSING, SK_SUNS_BLESSING2,
-        SK_SURROUND, SK_SWORD, SK_THUNDEROUS_FURY, SK_TWOHAND, SK_WARCRY, SK_WARCRY2, SK_WEAPON,
-        SK_WIMPY, attribute_name, get_skill_name,
+        SK_ANGUISH_EARTH, SK_ANGUISH_ICE, SK_ANGUISH_LAVA, SK_BLADE_DANCE, SK_BLAST, SK_BLESS,
+        SK_CONCEN, SK_CONTAGION, SK_CURSE, SK_DELIVER_DEATH, SK_DISARM, SK_DISPEL, SK_DISTRACT,
+        SK_ELEMENT_SWITCHING, SK_ENHANCE, SK_GASH, SK_GHOST, SK_HEAL, SK_ICE_STUN, SK_IDENT,
+        SK_IMMUN, SK_INNER_STRENGTH, SK_KINDRED_SPIRIT, SK_LAVA_BLAST, SK_LIGHT, SK_MSHIELD,
+        SK_PARASITE, SK_PROTECT, SK_RAINS_OF_RENEWAL, SK_RECALL, SK_REPAIR, SK_RESIST,
+        SK_REVENANT_CONDUIT, SK_REVENANT_CONDUIT2, SK_SEEING_RED, SK_SENSE, SK_SPECTRAL_PACT,
+        SK_SPECTRAL_PACT2, SK_SPELLCASTER_KINDRED_SPIRIT, SK_STUN, SK_SUNS_BLESSING,
+        SK_SUNS_BLESSING2, SK_THUNDEROUS_FURY, SK_WARCRY, SK_WARCRY2, SK_WEAPON, SK_WIMPY,
+        attribute_name, get_skill_name,
     },
     string_operations::c_string_to_str,
     talent_trees::harakim,
@@ -27,11 +26,15 @@ use core::{
 
 use crate::{
     chlog, driver,
+    driver::skill_use::{charge_skill, start_skill_cooldown, use_skill},
     effect::EffectManager,
     game_state::{ElementSwitchState, GameState},
     god::God,
-    helpers, points, populate,
+    helpers,
+    player::combat_log::HitSource,
+    populate,
 };
+use core::balance;
 use core::types::Character;
 
 use core::constants::LEGACY_TICKS;
@@ -71,6 +74,25 @@ pub fn player_or_ghost(cn: &Character, cn_idx: usize, co: &Character) -> bool {
     false
 }
 
+/// Applies the Concentration reduction to a mana cost.
+///
+/// # Arguments
+///
+/// * `ch` - Caster.
+/// * `cost` - Base spell cost in whole mana units.
+///
+/// # Returns
+///
+/// * The cost actually charged, in whole mana units.
+pub(crate) fn concentrated_mana_cost(ch: &Character, cost: i32) -> i32 {
+    // concentrate:
+    if ch.skill[SK_CONCEN][0] == 0 {
+        return cost;
+    }
+    let t = cost * i32::from(ch.skill[SK_CONCEN][5]) / 300;
+    if t > cost { 1 } else { cost - t }
+}
+
 /// Consumes mana for a spell after applying Concentration cost reduction.
 ///
 /// # Arguments
@@ -88,18 +110,7 @@ pub fn player_or_ghost(cn: &Character, cn_idx: usize, co: &Character) -> bool {
 /// * Panics if `cn` is not a valid character index.
 pub fn spellcost(gs: &mut GameState, cn: usize, cost: i32) -> i32 {
     // Ported from C++ spellcost(int cn, int cost)
-    // concentrate:
-    let mut cost = cost;
-    let concen_skill = gs.characters[cn].skill[SK_CONCEN][0];
-    if concen_skill != 0 {
-        let concen_val = gs.characters[cn].skill[SK_CONCEN][5];
-        let t = cost * i32::from(concen_val) / 300;
-        if t > cost {
-            cost = 1;
-        } else {
-            cost -= t;
-        }
-    }
+    let cost = concentrated_mana_cost(&gs.characters[cn], cost);
     let a_mana = gs.characters[cn].a_mana;
     if cost * 1000 > a_mana {
         gs.do_character_log(
@@ -819,7 +830,7 @@ pub fn skill_light(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 5) != 0 {
+    if !charge_skill(gs, cn, SK_LIGHT) {
         return;
     }
 
@@ -1051,7 +1062,7 @@ pub fn skill_protect(gs: &mut GameState, cn: usize) {
         co = cn;
     }
 
-    if spellcost(gs, cn, 15) != 0 {
+    if !charge_skill(gs, cn, SK_PROTECT) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -1269,7 +1280,7 @@ pub fn skill_enhance(gs: &mut GameState, cn: usize) {
         // change target to self
         let co = cn;
         // continue with self
-        if spellcost(gs, cn, 15) != 0 {
+        if !charge_skill(gs, cn, SK_ENHANCE) {
             return;
         }
         if chance(gs, cn, 18) != 0 {
@@ -1296,7 +1307,7 @@ pub fn skill_enhance(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 15) != 0 {
+    if !charge_skill(gs, cn, SK_ENHANCE) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -1509,7 +1520,7 @@ pub fn skill_bless(gs: &mut GameState, cn: usize) {
         );
         // change target to self
         let co = cn;
-        if spellcost(gs, cn, 35) != 0 {
+        if !charge_skill(gs, cn, SK_BLESS) {
             return;
         }
         if chance(gs, cn, 18) != 0 {
@@ -1535,7 +1546,7 @@ pub fn skill_bless(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 35) != 0 {
+    if !charge_skill(gs, cn, SK_BLESS) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -1592,18 +1603,10 @@ pub fn skill_wimp(gs: &mut GameState, cn: usize) {
         }
     }
 
-    let a_end = gs.characters[cn].a_end;
-    if a_end < 20000 {
-        gs.do_character_log(
-            cn,
-            core::types::FontColor::Red,
-            "You're too exhausted to call on your Guardian Angel.\n",
-        );
+    if !charge_skill(gs, cn, SK_WIMPY) {
         return;
     }
 
-    gs.characters[cn].a_end -= 20000;
-
     let in_opt = God::create_item(gs, 1);
     if in_opt.is_none() {
         log::error!("god_create_item failed in skill_wimp");
@@ -1807,7 +1810,7 @@ pub fn skill_mshield(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 25) != 0 {
+    if !charge_skill(gs, cn, SK_MSHIELD) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -1949,7 +1952,7 @@ pub fn skill_heal(gs: &mut GameState, cn: usize) {
             ),
         );
         co = cn;
-        if spellcost(gs, cn, 25) != 0 {
+        if !charge_skill(gs, cn, SK_HEAL) {
             return;
         }
         if chance(gs, cn, 18) != 0 {
@@ -1975,7 +1978,7 @@ pub fn skill_heal(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 25) != 0 {
+    if !charge_skill(gs, cn, SK_HEAL) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -2147,7 +2150,7 @@ pub fn skill_curse(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 35) != 0 {
+    if !charge_skill(gs, cn, SK_CURSE) {
         return;
     }
 
@@ -2367,13 +2370,10 @@ pub fn warcry(gs: &mut GameState, cn: usize, co: usize, power: i32) -> bool {
 ///
 /// * Panics if `cn` is invalid or a scanned map index is invalid.
 pub fn skill_warcry(gs: &mut GameState, cn: usize) {
-    if gs.characters[cn].a_end < 150 * 1000 {
-        gs.do_character_log(cn, core::types::FontColor::Red, "You're too exhausted!\n");
+    if !charge_skill(gs, cn, SK_WARCRY) {
         return;
     }
 
-    gs.characters[cn].a_end -= 150 * 1000;
-
     let power = i32::from(gs.characters[cn].skill[SK_WARCRY][5]);
 
     let xf = std::cmp::max(1, i32::from(gs.characters[cn].x) - 10);
@@ -2730,7 +2730,7 @@ pub fn skill_identify(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 25) != 0 {
+    if !charge_skill(gs, cn, SK_IDENT) {
         return;
     }
 
@@ -2870,9 +2870,11 @@ pub fn skill_blast(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    let mut power = i32::from(gs.characters[cn].skill[SK_BLAST][5]);
-    power = spell_immunity(gs, power, i32::from(gs.characters[co].skill[SK_IMMUN][5]));
+    let base_power = i32::from(gs.characters[cn].skill[SK_BLAST][5]);
+    let immun = i32::from(gs.characters[co].skill[SK_IMMUN][5]);
+    let mut power = spell_immunity(gs, base_power, immun);
     power = spell_race_mod(gs, power, gs.characters[cn].kindred);
+    let resisted = (spell_race_mod(gs, base_power, gs.characters[cn].kindred) - power) * 2;
 
     let mut dam = power * 2;
 
@@ -2944,7 +2946,7 @@ pub fn skill_blast(gs: &mut GameState, cn: usize) {
         gs.characters[co].get_name().to_owned(),
         power
     );
-    let tmp = gs.do_hurt(cn, co, dam, 1);
+    let tmp = gs.do_hurt_from(cn, co, dam, 1, HitSource::resisted(SK_BLAST, resisted));
 
     if tmp < 1 {
         gs.do_character_log(
@@ -2994,7 +2996,7 @@ pub fn skill_blast(gs: &mut GameState, cn: usize) {
         }
 
         gs.remember_pvp(cn, maybe_co);
-        let tmp2 = gs.do_hurt(cn, maybe_co, dam, 1);
+        let tmp2 = gs.do_hurt_from(cn, maybe_co, dam, 1, HitSource::skill(SK_BLAST));
         if tmp2 < 1 {
             gs.do_character_log(
                 cn,
@@ -3078,9 +3080,11 @@ pub fn skill_lava_blast(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    let mut power = i32::from(gs.characters[cn].skill[SK_LAVA_BLAST][5]);
-    power = spell_immunity(gs, power, i32::from(gs.characters[co].skill[SK_IMMUN][5]));
+    let base_power = i32::from(gs.characters[cn].skill[SK_LAVA_BLAST][5]);
+    let immun = i32::from(gs.characters[co].skill[SK_IMMUN][5]);
+    let mut power = spell_immunity(gs, base_power, immun);
     power = spell_race_mod(gs, power, gs.characters[cn].kindred);
+    let resisted = (spell_race_mod(gs, base_power, gs.characters[cn].kindred) - power) * 3 / 2;
     let mut dam = (power * 3) / 2;
 
     let mut cost = dam / 8 + 8;
@@ -3106,7 +3110,7 @@ pub fn skill_lava_blast(gs: &mut GameState, cn: usize) {
 
     dam = apply_harakim_element_damage_bonus(gs, cn, HARAKIM_ELEMENT_LAVA, dam);
 
-    let tmp = gs.do_hurt(cn, co, dam, 1);
+    let tmp = gs.do_hurt_from(cn, co, dam, 1, HitSource::resisted(SK_LAVA_BLAST, resisted));
     if tmp < 1 {
         gs.do_character_log(
             cn,
@@ -3148,7 +3152,7 @@ pub fn skill_lava_blast(gs: &mut GameState, cn: usize) {
             continue;
         }
         gs.remember_pvp(cn, maybe_co);
-        let tmp2 = gs.do_hurt(cn, maybe_co, dam, 1);
+        let tmp2 = gs.do_hurt_from(cn, maybe_co, dam, 1, HitSource::skill(SK_LAVA_BLAST));
         if tmp2 >= 1 {
             gs.do_character_log(
                 cn,
@@ -3282,7 +3286,7 @@ pub fn skill_recall(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 15) != 0 {
+    if !charge_skill(gs, cn, SK_RECALL) {
         return;
     }
 
@@ -3501,7 +3505,7 @@ pub fn skill_stun(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 20) != 0 {
+    if !charge_skill(gs, cn, SK_STUN) {
         return;
     }
 
@@ -3757,7 +3761,7 @@ pub fn skill_dispel(gs: &mut GameState, cn: usize) {
 
     let pwr = gs.items[in_idx].power as i32;
 
-    if spellcost(gs, cn, 25) != 0 {
+    if !charge_skill(gs, cn, SK_DISPEL) {
         return;
     }
 
@@ -4078,7 +4082,7 @@ pub fn skill_ghost(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    if spellcost(gs, cn, 45) != 0 {
+    if !charge_skill(gs, cn, SK_GHOST) {
         return;
     }
 
@@ -4139,7 +4143,7 @@ pub fn skill_ghost(gs: &mut GameState, cn: usize) {
     let random_name = {
         let mut candidate = None;
         for _ in 0..100 {
-            let name = core::names::randomly_generate_name();
+            let name = crate::deterministic::random_name();
             let name_exists = gs.characters.iter().enumerate().any(|(idx, other)| {
                 idx != cc && other.used != USE_EMPTY && other.get_name().eq_ignore_ascii_case(&name)
             });
@@ -4148,7 +4152,7 @@ pub fn skill_ghost(gs: &mut GameState, cn: usize) {
                 break;
             }
         }
-        candidate.unwrap_or_else(core::names::randomly_generate_name)
+        candidate.unwrap_or_else(crate::deterministic::random_name)
     };
 
     {
@@ -4241,25 +4245,25 @@ pub fn skill_ghost(gs: &mut GameState, cn: usize) {
 
     for attrib in &attribs[..5] {
         for m in 10..i32::from(attrib[0]) {
-            pts += points::attrib_needed(m, 3);
+            pts += balance::attrib_needed(m, 3);
         }
     }
 
     for m in 50..i32::from(hp0) {
-        pts += points::hp_needed(m, 3);
+        pts += balance::hp_needed(m, 3);
     }
 
     for m in 50..i32::from(end0) {
-        pts += points::end_needed(m, 2);
+        pts += balance::end_needed(m, 2);
     }
 
     for m in 50..i32::from(mana0) {
-        pts += points::mana_needed(m, 3);
+        pts += balance::mana_needed(m, 3);
     }
 
     for skill in &skills[..50] {
         for m in 1..i32::from(skill[0]) {
-            pts += points::skill_needed(m, 2);
+            pts += balance::skill_needed(m, 2);
         }
     }
 
@@ -4391,52 +4395,10 @@ pub fn is_back(cn: &Character, co: &Character) -> bool {
     }
 }
 
-/// Logs the standard no-magic failure message for a character.
-///
-/// # Arguments
-///
-/// * `gs` - Active game state used for character logging.
-/// * `cn` - Character index receiving the failure message.
-///
-/// # Panics
-///
-/// * Panics if `cn` is not a valid character index.
-pub fn nomagic(gs: &mut GameState, cn: usize) {
-    gs.do_character_log(
-        cn,
-        FontColor::Green,
-        "Your magic fails. You seem to be unable to cast spells.\n",
-    );
-}
-
-/// Returns true if the character currently has an active spell-item with the
-/// given skill `temp`, i.e. that skill is still on cooldown.
-///
-/// # Arguments
-///
-/// * `gs` - Mutable reference to the game state.
-/// * `cn` - Character index to inspect.
-/// * `skill_temp` - The `temp` value of the cooldown spell-item to look for
-///   (typically the originating skill constant such as `SK_DELIVER_DEATH`).
-///
-/// # Returns
-///
-/// * `true` if a matching cooldown spell-item is still present.
-fn skill_on_cooldown(gs: &mut GameState, cn: usize, skill_temp: u16) -> bool {
-    for n in 0..20 {
-        let in_ = gs.characters[cn].spell[n] as usize;
-        if in_ != 0 && gs.items[in_].temp == skill_temp {
-            gs.do_character_log(cn, FontColor::Red, "That ability is still recharging.\n");
-            return true;
-        }
-    }
-    false
-}
-
 /// Adds a per-skill cooldown spell-item to the caster.
 ///
 /// The created item has no stat effect; its sole purpose is to remain in
-/// `character.spell[]` for `len` ticks so that `skill_on_cooldown` can detect
+/// `character.spell[]` for `len` ticks so that `use_skill` can detect
 /// it. Mirrors the pattern of `add_exhaust` but uses a caller-supplied skill
 /// `temp` so each ability tracks its own recharge timer independently.
 ///
@@ -4447,7 +4409,13 @@ fn skill_on_cooldown(gs: &mut GameState, cn: usize, skill_temp: u16) -> bool {
 /// * `len` - Cooldown duration in ticks.
 /// * `skill_temp` - Originating skill constant stored in `item.temp`.
 /// * `name` - Display name used when the cooldown is reported (must fit 40 bytes).
-fn add_skill_cooldown(gs: &mut GameState, cn: usize, len: i32, skill_temp: u16, name: &[u8]) {
+pub(crate) fn add_skill_cooldown(
+    gs: &mut GameState,
+    cn: usize,
+    len: i32,
+    skill_temp: u16,
+    name: &[u8],
+) {
     let in_ = match God::create_item(gs, 1) {
         Some(i) => i,
         None => {
@@ -4599,7 +4567,7 @@ pub fn skill_parasite(gs: &mut GameState, cn: usize) {
     if !hostile_cast_preflight(gs, cn, co, "You cannot infect yourself.\n") {
         return;
     }
-    if spellcost(gs, cn, 20) != 0 {
+    if !charge_skill(gs, cn, SK_PARASITE) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -4655,7 +4623,7 @@ pub fn skill_distract(gs: &mut GameState, cn: usize) {
     if !hostile_cast_preflight(gs, cn, co, "You cannot distract yourself.\n") {
         return;
     }
-    if spellcost(gs, cn, 15) != 0 {
+    if !charge_skill(gs, cn, SK_DISTRACT) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -4737,9 +4705,6 @@ pub fn skill_deliver_death(gs: &mut GameState, cn: usize) {
     if !hostile_cast_preflight(gs, cn, co, "You cannot strike yourself down.\n") {
         return;
     }
-    if skill_on_cooldown(gs, cn, SK_DELIVER_DEATH as u16) {
-        return;
-    }
     // Adjacency check
     let dx = (i32::from(gs.characters[cn].x) - i32::from(gs.characters[co].x)).abs();
     let dy = (i32::from(gs.characters[cn].y) - i32::from(gs.characters[co].y)).abs();
@@ -4747,11 +4712,9 @@ pub fn skill_deliver_death(gs: &mut GameState, cn: usize) {
         gs.do_character_log(cn, FontColor::Red, "Your target is too far away.\n");
         return;
     }
-    if gs.characters[cn].a_end < 150 * 1000 {
-        gs.do_character_log(cn, FontColor::Red, "You're too exhausted!\n");
+    if !charge_skill(gs, cn, SK_DELIVER_DEATH) {
         return;
     }
-    gs.characters[cn].a_end -= 150 * 1000;
 
     let weapon = i32::from(gs.characters[cn].weapon).max(1);
     let max_hp = i32::from(gs.characters[co].hp[5]).max(1);
@@ -4762,7 +4725,7 @@ pub fn skill_deliver_death(gs: &mut GameState, cn: usize) {
         weapon * 2 + helpers::random_mod_i32(weapon)
     };
 
-    let applied = gs.do_hurt(cn, co, dam, 0);
+    let applied = gs.do_hurt_from(cn, co, dam, 0, HitSource::skill(SK_DELIVER_DEATH));
     if applied < 1 {
         gs.do_character_log(cn, FontColor::Green, "Your blow glances off harmlessly.\n");
     } else {
@@ -4780,13 +4743,7 @@ pub fn skill_deliver_death(gs: &mut GameState, cn: usize) {
     gs.do_area_sound(co, 0, tx, ty, i32::from(gs.characters[cn].sound) + 4);
     EffectManager::fx_add_effect(gs, 5, 0, tx, ty, 0);
 
-    add_skill_cooldown(
-        gs,
-        cn,
-        TICKS * 45,
-        SK_DELIVER_DEATH as u16,
-        b"Deliver Death Cooldown",
-    );
+    start_skill_cooldown(gs, cn, SK_DELIVER_DEATH);
 }
 
 /// Active spell: weakens the target's effective weapon skill for a short time,
@@ -4801,7 +4758,7 @@ pub fn skill_disarm(gs: &mut GameState, cn: usize) {
     if !hostile_cast_preflight(gs, cn, co, "You cannot disarm yourself.\n") {
         return;
     }
-    if spellcost(gs, cn, 25) != 0 {
+    if !charge_skill(gs, cn, SK_DISARM) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -4882,7 +4839,7 @@ pub fn skill_contagion(gs: &mut GameState, cn: usize) {
     if !hostile_cast_preflight(gs, cn, co, "You cannot infect yourself.\n") {
         return;
     }
-    if spellcost(gs, cn, 40) != 0 {
+    if !charge_skill(gs, cn, SK_CONTAGION) {
         return;
     }
     if chance(gs, cn, 18) != 0 {
@@ -4936,14 +4893,9 @@ pub fn skill_contagion(gs: &mut GameState, cn: usize) {
 /// * `gs` - Game state.
 /// * `cn` - Caster character index.
 pub fn skill_blade_dance(gs: &mut GameState, cn: usize) {
-    if skill_on_cooldown(gs, cn, SK_BLADE_DANCE as u16) {
+    if !charge_skill(gs, cn, SK_BLADE_DANCE) {
         return;
     }
-    if gs.characters[cn].a_end < 200 * 1000 {
-        gs.do_character_log(cn, FontColor::Red, "You're too exhausted!\n");
-        return;
-    }
-    gs.characters[cn].a_end -= 200 * 1000;
 
     let weapon = i32::from(gs.characters[cn].weapon).max(1);
     let caster_x = i32::from(gs.characters[cn].x);
@@ -4963,7 +4915,7 @@ pub fn skill_blade_dance(gs: &mut GameState, cn: usize) {
         // Mirror Surround Hit's reduction (3/4 of base) then double it for Blade Dance.
         let sdam = (base_dam - base_dam / 4) * 2;
         gs.remember_pvp(cn, co);
-        let applied = gs.do_hurt(cn, co, sdam, 0);
+        let applied = gs.do_hurt_from(cn, co, sdam, 0, HitSource::skill(SK_BLADE_DANCE));
         if applied > 0 {
             hits += 1;
             let name = gs.characters[co].get_name().to_owned();
@@ -4985,13 +4937,7 @@ pub fn skill_blade_dance(gs: &mut GameState, cn: usize) {
     );
     chlog!(cn, "Performed Blade Dance ({} hits)", hits);
 
-    add_skill_cooldown(
-        gs,
-        cn,
-        TICKS * 30,
-        SK_BLADE_DANCE as u16,
-        b"Blade Dance Cooldown",
-    );
+    start_skill_cooldown(gs, cn, SK_BLADE_DANCE);
 }
 
 // =====================================================================
@@ -5046,14 +4992,9 @@ pub(crate) fn has_active_seeing_red(gs: &GameState, cn: usize) -> bool {
 /// * `gs` - Game state.
 /// * `cn` - Caster character index.
 pub fn skill_rains_of_renewal(gs: &mut GameState, cn: usize) {
-    if skill_on_cooldown(gs, cn, SK_RAINS_OF_RENEWAL as u16) {
+    if !charge_skill(gs, cn, SK_RAINS_OF_RENEWAL) {
         return;
     }
-    if gs.characters[cn].a_end < 100 * 1000 {
-        gs.do_character_log(cn, FontColor::Red, "You're too exhausted!\n");
-        return;
-    }
-    gs.characters[cn].a_end -= 100 * 1000;
 
     let power = i32::from(gs.characters[cn].skill[SK_RAINS_OF_RENEWAL][5]);
     let duration = TICKS * 20;
@@ -5111,9 +5052,6 @@ pub fn skill_gash(gs: &mut GameState, cn: usize) {
     if !hostile_cast_preflight(gs, cn, co, "You cannot gash yourself.\n") {
         return;
     }
-    if skill_on_cooldown(gs, cn, SK_GASH as u16) {
-        return;
-    }
     let dx = (i32::from(gs.characters[cn].x) - i32::from(gs.characters[co].x)).abs();
     let dy = (i32::from(gs.characters[cn].y) - i32::from(gs.characters[co].y)).abs();
     if dx > 1 || dy > 1 {
@@ -5139,7 +5077,7 @@ pub fn skill_gash(gs: &mut GameState, cn: usize) {
     let bonus_pct = power.clamp(10, 200);
     let dam = weapon * (100 + bonus_pct) / 100 + helpers::random_mod_i32(weapon.max(1));
 
-    let applied = gs.do_hurt(cn, co, dam, 0);
+    let applied = gs.do_hurt_from(cn, co, dam, 0, HitSource::skill(SK_GASH));
     if applied < 1 {
         gs.do_character_log(cn, FontColor::Green, "Your blow glances off harmlessly.\n");
     } else {
@@ -5157,7 +5095,7 @@ pub fn skill_gash(gs: &mut GameState, cn: usize) {
     gs.do_area_sound(co, 0, tx, ty, i32::from(gs.characters[cn].sound) + 4);
     EffectManager::fx_add_effect(gs, 5, 0, tx, ty, 0);
 
-    add_skill_cooldown(gs, cn, TICKS * 10, SK_GASH as u16, b"Gash Cooldown");
+    start_skill_cooldown(gs, cn, SK_GASH);
 }
 
 /// Active self-buff: Sun's Blessing. No resource cost. Long cooldown set
@@ -5169,13 +5107,9 @@ pub fn skill_gash(gs: &mut GameState, cn: usize) {
 /// * `gs` - Game state.
 /// * `cn` - Caster character index.
 pub fn skill_suns_blessing(gs: &mut GameState, cn: usize) {
-    if skill_on_cooldown(gs, cn, SK_SUNS_BLESSING as u16) {
-        return;
-    }
     let power = i32::from(gs.characters[cn].skill[SK_SUNS_BLESSING][5]);
     let bonus = (power / 10 + 2).clamp(1, 30) as i8;
     let buff_duration = TICKS * 60;
-    let cooldown_len = TICKS * 55;
 
     let in_opt = God::create_item(gs, 1);
     if in_opt.is_none() {
@@ -5211,13 +5145,7 @@ pub fn skill_suns_blessing(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    add_skill_cooldown(
-        gs,
-        cn,
-        cooldown_len,
-        SK_SUNS_BLESSING as u16,
-        b"Sun's Blessing Cooldown",
-    );
+    start_skill_cooldown(gs, cn, SK_SUNS_BLESSING);
 
     gs.do_character_log(
         cn,
@@ -5244,21 +5172,15 @@ pub fn skill_suns_blessing(gs: &mut GameState, cn: usize) {
 /// * `gs` - Game state.
 /// * `cn` - Caster character index.
 pub fn skill_seeing_red(gs: &mut GameState, cn: usize) {
-    if skill_on_cooldown(gs, cn, SK_SEEING_RED as u16) {
-        return;
-    }
-    if gs.characters[cn].a_end < 150 * 1000 {
-        gs.do_character_log(cn, FontColor::Red, "You're too exhausted!\n");
+    if !charge_skill(gs, cn, SK_SEEING_RED) {
         return;
     }
-    gs.characters[cn].a_end -= 150 * 1000;
 
     let power = i32::from(gs.characters[cn].skill[SK_SEEING_RED][5]);
     // Roughly double outgoing damage by mirroring the caster's current
     // weapon value as a flat weapon[1] bonus, capped to i8 range.
     let weapon = i32::from(gs.characters[cn].weapon).clamp(1, 120) as i8;
     let duration = TICKS * (5 + (power / 5).clamp(0, 25));
-    let cooldown_len = TICKS * 60;
 
     let in_opt = God::create_item(gs, 1);
     if in_opt.is_none() {
@@ -5290,13 +5212,7 @@ pub fn skill_seeing_red(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    add_skill_cooldown(
-        gs,
-        cn,
-        cooldown_len,
-        SK_SEEING_RED as u16,
-        b"Seeing Red Cooldown",
-    );
+    start_skill_cooldown(gs, cn, SK_SEEING_RED);
 
     gs.do_character_log(cn, FontColor::Green, "You are seeing red!\n");
     chlog!(cn, "Cast Seeing Red");
@@ -5318,14 +5234,9 @@ pub fn skill_seeing_red(gs: &mut GameState, cn: usize) {
 /// * `gs` - Game state.
 /// * `cn` - Caster character index.
 pub fn skill_thunderous_fury(gs: &mut GameState, cn: usize) {
-    if skill_on_cooldown(gs, cn, SK_THUNDEROUS_FURY as u16) {
-        return;
-    }
-    if gs.characters[cn].a_end < 250 * 1000 {
-        gs.do_character_log(cn, FontColor::Red, "You're too exhausted!\n");
+    if !charge_skill(gs, cn, SK_THUNDEROUS_FURY) {
         return;
     }
-    gs.characters[cn].a_end -= 250 * 1000;
 
     let power = i32::from(gs.characters[cn].skill[SK_THUNDEROUS_FURY][5]);
     let blast_base = (power / 4).max(1);
@@ -5359,7 +5270,7 @@ pub fn skill_thunderous_fury(gs: &mut GameState, cn: usize) {
                     &format!("{}'s thunderous fury crashes over you!\n", name),
                 );
                 let dam = blast_base + helpers::random_mod_i32(blast_base);
-                let _ = gs.do_hurt(cn, co, dam, 0);
+                let _ = gs.do_hurt_from(cn, co, dam, 0, HitSource::skill(SK_THUNDEROUS_FURY));
                 let tx = i32::from(gs.characters[co].x);
                 let ty = i32::from(gs.characters[co].y);
                 EffectManager::fx_add_effect(gs, 5, 0, tx, ty, 0);
@@ -5381,13 +5292,7 @@ pub fn skill_thunderous_fury(gs: &mut GameState, cn: usize) {
     );
     chlog!(cn, "Cast Thunderous Fury ({} hits)", hit);
 
-    add_skill_cooldown(
-        gs,
-        cn,
-        TICKS * 30,
-        SK_THUNDEROUS_FURY as u16,
-        b"Thunderous Fury Cooldown",
-    );
+    start_skill_cooldown(gs, cn, SK_THUNDEROUS_FURY);
 }
 
 /// Active AoE + self buff: Inner Strength. Upgraded Warcry that stuns nearby
@@ -5398,14 +5303,9 @@ pub fn skill_thunderous_fury(gs: &mut GameState, cn: usize) {
 /// * `gs` - Game state.
 /// * `cn` - Caster character index.
 pub fn skill_inner_strength(gs: &mut GameState, cn: usize) {
-    if skill_on_cooldown(gs, cn, SK_INNER_STRENGTH as u16) {
-        return;
-    }
-    if gs.characters[cn].a_end < 200 * 1000 {
-        gs.do_character_log(cn, FontColor::Red, "You're too exhausted!\n");
+    if !charge_skill(gs, cn, SK_INNER_STRENGTH) {
         return;
     }
-    gs.characters[cn].a_end -= 200 * 1000;
 
     let power = i32::from(gs.characters[cn].skill[SK_INNER_STRENGTH][5]);
     let buff_amount = ((power / 5) + 2).clamp(1, 50) as i8;
@@ -5483,13 +5383,7 @@ pub fn skill_inner_strength(gs: &mut GameState, cn: usize) {
     );
     chlog!(cn, "Cast Inner Strength ({} hits)", hit);
 
-    add_skill_cooldown(
-        gs,
-        cn,
-        TICKS * 30,
-        SK_INNER_STRENGTH as u16,
-        b"Inner Strength Cooldown",
-    );
+    start_skill_cooldown(gs, cn, SK_INNER_STRENGTH);
 }
 
 /// Returns whether `cn` currently has an active spell-item whose `temp`
@@ -5576,7 +5470,7 @@ pub fn skill_revenant_conduit(gs: &mut GameState, cn: usize) {
         gs.do_character_log(cn, FontColor::Red, "You're too exhausted!\n");
         return;
     }
-    if spellcost(gs, cn, 35) != 0 {
+    if !charge_skill(gs, cn, SK_REVENANT_CONDUIT) {
         return;
     }
 
@@ -5676,7 +5570,7 @@ pub fn skill_spectral_pact(gs: &mut GameState, cn: usize) {
         gs.do_character_log(cn, FontColor::Red, "The pact is already in force.\n");
         return;
     }
-    if spellcost(gs, cn, 40) != 0 {
+    if !charge_skill(gs, cn, SK_SPECTRAL_PACT) {
         return;
     }
     let power = i32::from(gs.characters[cn].skill[SK_SPECTRAL_PACT][5]);
@@ -5930,258 +5824,21 @@ pub fn skill_anguish_ice(gs: &mut GameState, cn: usize) {
     chlog!(cn, "Cast Anguish-Ice on {}", name);
 }
 
-/// Dispatches direct skill use to the matching skill handler.
+/// Handles a `CmdSkill` request: runs [`use_skill`] and logs the reason if
+/// the skill could not be used.
 ///
 /// # Arguments
 ///
 /// * `gs` - Active game state used for skill lookup and handler execution.
 /// * `cn` - Character index using the skill.
-/// * `nr` - Skill number to dispatch.
+/// * `nr` - Skill number requested by the client.
 ///
 /// # Panics
 ///
-/// * Panics if `cn` is invalid or `nr` cannot be used as a skill-table index.
+/// * Panics if `cn` is invalid.
 pub fn skill_driver(gs: &mut GameState, cn: usize, nr: i32) {
-    // Check whether the character can use this skill/spell
-    if gs.characters[cn].skill[nr as usize][0] == 0 {
-        gs.do_character_log(cn, FontColor::Green, "You cannot use this skill/spell.\n");
-        return;
-    }
-
-    match nr {
-        x if x == SK_LIGHT as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_light(gs, cn);
-            }
-        }
-        x if x == SK_PROTECT as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_protect(gs, cn);
-            }
-        }
-        x if x == SK_ENHANCE as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_enhance(gs, cn);
-            }
-        }
-        x if x == SK_BLESS as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_bless(gs, cn);
-            }
-        }
-        x if x == SK_CURSE as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_curse(gs, cn);
-            }
-        }
-        x if x == SK_IDENT as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_identify(gs, cn);
-            }
-        }
-        x if x == SK_BLAST as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_blast(gs, cn);
-            }
-        }
-        x if x == SK_REPAIR as i32 => skill_repair(gs, cn),
-        x if x == SK_LOCK as i32 => gs.do_character_log(
-            cn,
-            FontColor::Green,
-            "You cannot use this skill directly. Hold a lock-pick under your mouse cursor and click on the door.\n",
-        ),
-        x if x == SK_RECALL as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_recall(gs, cn);
-            }
-        }
-        x if x == SK_STUN as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_stun(gs, cn);
-            }
-        }
-        x if x == SK_DISPEL as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_dispel(gs, cn);
-            }
-        }
-        x if x == SK_WIMPY as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_wimp(gs, cn);
-            }
-        }
-        x if x == SK_HEAL as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_heal(gs, cn);
-            }
-        }
-        x if x == SK_GHOST as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_ghost(gs, cn);
-            }
-        }
-        x if x == SK_MSHIELD as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_mshield(gs, cn);
-            }
-        }
-        x if x == SK_IMMUN as i32 => gs.do_character_log(
-            cn,
-            FontColor::Green,
-            "You use this skill automatically when someone casts evil spells on you.\n",
-        ),
-        x if x == SK_REGEN as i32 || x == SK_REST as i32 || x == SK_MEDIT as i32 => {
-            gs.do_character_log(
-                cn,
-                FontColor::Green,
-                "You use this skill automatically when you stand still.\n",
-            );
-        }
-        x if x == SK_WEAPON as i32
-            || x == SK_DAGGER as i32
-            || x == SK_SWORD as i32
-            || x == SK_AXE as i32
-            || x == SK_STAFF as i32
-            || x == SK_TWOHAND as i32
-            || x == SK_SURROUND as i32 =>
-        {
-            gs.do_character_log(
-                cn,
-                FontColor::Green,
-                "You use this skill automatically when you fight.\n",
-            );
-        }
-        x if x == SK_CONCEN as i32 => gs.do_character_log(
-            cn,
-            FontColor::Green,
-            "You use this skill automatically when you cast spells.\n",
-        ),
-        x if x == SK_WARCRY as i32 => skill_warcry(gs, cn),
-         x if x == SK_PARASITE as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_parasite(gs, cn);
-            }
-        }
-        x if x == SK_DISTRACT as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_distract(gs, cn);
-            }
-        }
-        x if x == SK_DELIVER_DEATH as i32 => skill_deliver_death(gs, cn),
-        x if x == SK_DISARM as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_disarm(gs, cn);
-            }
-        }
-        x if x == SK_CONTAGION as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_contagion(gs, cn);
-            }
-        }
-        x if x == SK_BLADE_DANCE as i32 => skill_blade_dance(gs, cn),
-        x if x == SK_RAINS_OF_RENEWAL as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_rains_of_renewal(gs, cn);
-            }
-        }
-        x if x == SK_GASH as i32 => skill_gash(gs, cn),
-        x if x == SK_SUNS_BLESSING as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_suns_blessing(gs, cn);
-            }
-        }
-        x if x == SK_SEEING_RED as i32 => skill_seeing_red(gs, cn),
-        x if x == SK_THUNDEROUS_FURY as i32 => skill_thunderous_fury(gs, cn),
-        x if x == SK_INNER_STRENGTH as i32 => skill_inner_strength(gs, cn),
-        x if x == SK_REVENANT_CONDUIT as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_revenant_conduit(gs, cn);
-            }
-        }
-        x if x == SK_KINDRED_SPIRIT as i32 => skill_kindred_spirit(gs, cn),
-        x if x == SK_SPECTRAL_PACT as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_spectral_pact(gs, cn);
-            }
-        }
-        x if x == SK_ANGUISH_LAVA as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_anguish_lava(gs, cn);
-            }
-        }
-        x if x == SK_ANGUISH_EARTH as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_anguish_earth(gs, cn);
-            }
-        }
-        x if x == SK_ANGUISH_ICE as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_anguish_ice(gs, cn);
-            }
-        }
-        x if x == SK_LAVA_BLAST as i32 => {
-            if (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
-                nomagic(gs, cn);
-            } else {
-                skill_lava_blast(gs, cn);
-            }
-        }
-        x if x == SK_SPELLCASTER_KINDRED_SPIRIT as i32 => {
-            skill_spellcaster_kindred_spirit(gs, cn);
-        }
-        _ => {
-            gs.do_character_log(cn, FontColor::Green, "You cannot use this skill/spell.\n");
-        }
+    if let Err(err) = use_skill(gs, cn, nr as usize) {
+        gs.do_character_log(cn, err.color(), err.message());
     }
 }
 
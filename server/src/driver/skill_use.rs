@@ -0,0 +1,500 @@
+//! Central entry point for player skill use.
+//!
+//! [`SKILL_USE_DEFS`] describes every skill a player can trigger from the
+//! skill bar: its handler (or the hint shown for passive skills), whether it
+//! is blocked in no-magic zones, its flat mana / endurance cost and its
+//! cooldown. [`use_skill`] runs the shared requirement checks before
+//! dispatching and reports failures as a typed [`SkillUseError`]; handlers
+//! charge the declared cost with [`charge_skill`] once their own target
+//! checks pass and start the declared cooldown with
+//! [`start_skill_cooldown`].
+//!
+//! Spells whose cost depends on power or target (Blast, Lava Blast, the
+//! Anguish family) declare no flat cost and still charge via `spellcost`.
+
+use core::constants::{CharacterFlags, TICKS};
+use core::skills::{self, MAX_SKILLS};
+use core::types::FontColor;
+
+use crate::driver::skill::{
+    add_skill_cooldown, concentrated_mana_cost, has_active_spell_temp, skill_anguish_earth,
+    skill_anguish_ice, skill_anguish_lava, skill_blade_dance, skill_blast, skill_bless,
+    skill_contagion, skill_curse, skill_deliver_death, skill_disarm, skill_dispel, skill_distract,
+    skill_enhance, skill_gash, skill_ghost, skill_heal, skill_identify, skill_inner_strength,
+    skill_kindred_spirit, skill_lava_blast, skill_light, skill_mshield, skill_parasite,
+    skill_protect, skill_rains_of_renewal, skill_recall, skill_repair, skill_revenant_conduit,
+    skill_seeing_red, skill_spectral_pact, skill_spellcaster_kindred_spirit, skill_stun,
+    skill_suns_blessing, skill_thunderous_fury, skill_warcry, skill_wimp,
+};
+use crate::game_state::GameState;
+
+/// Why a skill could not be used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SkillUseError {
+    /// The character has not learned the skill, or it cannot be triggered.
+    NotUsable,
+    /// The skill works automatically; carries the hint shown to the player.
+    Passive(&'static str),
+    /// The character stands in a no-magic area.
+    NoMagic,
+    /// The skill's cooldown marker is still active.
+    OnCooldown,
+    /// Not enough mana for the declared cost.
+    NotEnoughMana,
+    /// Not enough endurance for the declared cost.
+    NotEnoughEndurance,
+}
+
+impl SkillUseError {
+    /// Returns the log line shown to the player.
+    ///
+    /// # Returns
+    ///
+    /// * A newline-terminated message.
+    pub fn message(self) -> &'static str {
+        match self {
+            SkillUseError::NotUsable => "You cannot use this skill/spell.\n",
+            SkillUseError::Passive(hint) => hint,
+            SkillUseError::NoMagic => "Your magic fails. You seem to be unable to cast spells.\n",
+            SkillUseError::OnCooldown => "That ability is still recharging.\n",
+            SkillUseError::NotEnoughMana => "You don't have enough mana.\n",
+            SkillUseError::NotEnoughEndurance => "You're too exhausted!\n",
+        }
+    }
+
+    /// Returns the log color for [`SkillUseError::message`].
+    ///
+    /// # Returns
+    ///
+    /// * Red for resource and cooldown failures, green for informational
+    ///   messages (matching the legacy logs).
+    pub fn color(self) -> FontColor {
+        match self {
+            SkillUseError::OnCooldown
+            | SkillUseError::NotEnoughMana
+            | SkillUseError::NotEnoughEndurance => FontColor::Red,
+            SkillUseError::NotUsable | SkillUseError::Passive(_) | SkillUseError::NoMagic => {
+                FontColor::Green
+            }
+        }
+    }
+}
+
+/// What happens when a skill is triggered.
+#[derive(Copy, Clone)]
+pub enum SkillHandler {
+    /// Active skill; the handler performs the effect.
+    Active(fn(&mut GameState, usize)),
+    /// Passive skill; the hint explains when it applies.
+    Passive(&'static str),
+}
+
+/// Flat resource cost of a skill, in whole mana / endurance points.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct SkillCost {
+    /// Mana, before Concentration.
+    pub mana: i32,
+    /// Endurance.
+    pub endurance: i32,
+}
+
+impl SkillCost {
+    /// No cost.
+    pub const FREE: SkillCost = SkillCost {
+        mana: 0,
+        endurance: 0,
+    };
+
+    /// Mana-only cost.
+    const fn mana(mana: i32) -> Self {
+        SkillCost { mana, endurance: 0 }
+    }
+
+    /// Endurance-only cost.
+    const fn endurance(endurance: i32) -> Self {
+        SkillCost { mana: 0, endurance }
+    }
+}
+
+/// How a skill recharges. Both variants block use while a spell item with
+/// the skill's index as `temp` is attached to the character.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Recharge {
+    /// Usable again immediately.
+    None,
+    /// Usable again once the skill's own buff item expires.
+    WhileActive,
+    /// [`start_skill_cooldown`] attaches a marker item for `ticks`.
+    Cooldown {
+        /// Cooldown length in ticks.
+        ticks: i32,
+        /// Marker name shown in the spell list (at most 40 bytes).
+        name: &'static [u8],
+    },
+}
+
+/// Usage rules for one skill.
+#[derive(Copy, Clone)]
+pub struct SkillUseDef {
+    /// Skill index.
+    pub skill: usize,
+    /// Handler or passive hint.
+    pub handler: SkillHandler,
+    /// `true` if the skill fails in no-magic areas.
+    pub magic: bool,
+    /// Flat cost charged by [`charge_skill`].
+    pub cost: SkillCost,
+    /// When the skill can be used again.
+    pub recharge: Recharge,
+}
+
+impl SkillUseDef {
+    /// Active skill without cost or cooldown.
+    const fn active(skill: usize, handler: fn(&mut GameState, usize), magic: bool) -> Self {
+        SkillUseDef {
+            skill,
+            handler: SkillHandler::Active(handler),
+            magic,
+            cost: SkillCost::FREE,
+            recharge: Recharge::None,
+        }
+    }
+
+    /// Passive skill that only prints `hint`.
+    const fn passive(skill: usize, hint: &'static str) -> Self {
+        SkillUseDef {
+            skill,
+            handler: SkillHandler::Passive(hint),
+            magic: false,
+            cost: SkillCost::FREE,
+            recharge: Recharge::None,
+        }
+    }
+
+    /// Sets the flat cost.
+    const fn costs(mut self, cost: SkillCost) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Sets the cooldown.
+    const fn cooldown(mut self, ticks: i32, name: &'static [u8]) -> Self {
+        self.recharge = Recharge::Cooldown { ticks, name };
+        self
+    }
+
+    /// Blocks reuse while the skill's buff is active.
+    const fn while_active(mut self) -> Self {
+        self.recharge = Recharge::WhileActive;
+        self
+    }
+}
+
+const HINT_AUTO_FIGHT: &str = "You use this skill automatically when you fight.\n";
+const HINT_AUTO_REST: &str = "You use this skill automatically when you stand still.\n";
+
+/// Usage rules for every skill reachable from `CmdSkill`.
+pub static SKILL_USE_DEFS: &[SkillUseDef] = &[
+    SkillUseDef::active(skills::SK_LIGHT, skill_light, true).costs(SkillCost::mana(5)),
+    SkillUseDef::active(skills::SK_PROTECT, skill_protect, true).costs(SkillCost::mana(15)),
+    SkillUseDef::active(skills::SK_ENHANCE, skill_enhance, true).costs(SkillCost::mana(15)),
+    SkillUseDef::active(skills::SK_BLESS, skill_bless, true).costs(SkillCost::mana(35)),
+    SkillUseDef::active(skills::SK_CURSE, skill_curse, true).costs(SkillCost::mana(35)),
+    SkillUseDef::active(skills::SK_IDENT, skill_identify, true).costs(SkillCost::mana(25)),
+    SkillUseDef::active(skills::SK_BLAST, skill_blast, true),
+    SkillUseDef::active(skills::SK_REPAIR, skill_repair, false),
+    SkillUseDef::passive(
+        skills::SK_LOCK,
+        "You cannot use this skill directly. Hold a lock-pick under your mouse cursor and click on the door.\n",
+    ),
+    SkillUseDef::active(skills::SK_RECALL, skill_recall, true).costs(SkillCost::mana(15)),
+    SkillUseDef::active(skills::SK_STUN, skill_stun, true).costs(SkillCost::mana(20)),
+    SkillUseDef::active(skills::SK_DISPEL, skill_dispel, true).costs(SkillCost::mana(25)),
+    SkillUseDef::active(skills::SK_WIMPY, skill_wimp, true).costs(SkillCost::endurance(20)),
+    SkillUseDef::active(skills::SK_HEAL, skill_heal, true).costs(SkillCost::mana(25)),
+    SkillUseDef::active(skills::SK_GHOST, skill_ghost, true).costs(SkillCost::mana(45)),
+    SkillUseDef::active(skills::SK_MSHIELD, skill_mshield, true).costs(SkillCost::mana(25)),
+    SkillUseDef::passive(
+        skills::SK_IMMUN,
+        "You use this skill automatically when someone casts evil spells on you.\n",
+    ),
+    SkillUseDef::passive(skills::SK_REGEN, HINT_AUTO_REST),
+    SkillUseDef::passive(skills::SK_REST, HINT_AUTO_REST),
+    SkillUseDef::passive(skills::SK_MEDIT, HINT_AUTO_REST),
+    SkillUseDef::passive(skills::SK_WEAPON, HINT_AUTO_FIGHT),
+    SkillUseDef::passive(skills::SK_DAGGER, HINT_AUTO_FIGHT),
+    SkillUseDef::passive(skills::SK_SWORD, HINT_AUTO_FIGHT),
+    SkillUseDef::passive(skills::SK_AXE, HINT_AUTO_FIGHT),
+    SkillUseDef::passive(skills::SK_STAFF, HINT_AUTO_FIGHT),
+    SkillUseDef::passive(skills::SK_TWOHAND, HINT_AUTO_FIGHT),
+    SkillUseDef::passive(skills::SK_SURROUND, HINT_AUTO_FIGHT),
+    SkillUseDef::passive(
+        skills::SK_CONCEN,
+        "You use this skill automatically when you cast spells.\n",
+    ),
+    SkillUseDef::active(skills::SK_WARCRY, skill_warcry, false).costs(SkillCost::endurance(150)),
+    SkillUseDef::active(skills::SK_PARASITE, skill_parasite, true).costs(SkillCost::mana(20)),
+    SkillUseDef::active(skills::SK_DISTRACT, skill_distract, true).costs(SkillCost::mana(15)),
+    SkillUseDef::active(skills::SK_DELIVER_DEATH, skill_deliver_death, false)
+        .costs(SkillCost::endurance(150))
+        .cooldown(TICKS * 45, b"Deliver Death Cooldown"),
+    SkillUseDef::active(skills::SK_DISARM, skill_disarm, true).costs(SkillCost::mana(25)),
+    SkillUseDef::active(skills::SK_CONTAGION, skill_contagion, true).costs(SkillCost::mana(40)),
+    SkillUseDef::active(skills::SK_BLADE_DANCE, skill_blade_dance, false)
+        .costs(SkillCost::endurance(200))
+        .cooldown(TICKS * 30, b"Blade Dance Cooldown"),
+    SkillUseDef::active(skills::SK_RAINS_OF_RENEWAL, skill_rains_of_renewal, true)
+        .costs(SkillCost::endurance(100))
+        .while_active(),
+    SkillUseDef::active(skills::SK_GASH, skill_gash, false).cooldown(TICKS * 10, b"Gash Cooldown"),
+    // Cooldown slightly shorter than the 60 s buff so it can be recast as
+    // it expires without stacking.
+    SkillUseDef::active(skills::SK_SUNS_BLESSING, skill_suns_blessing, true)
+        .cooldown(TICKS * 55, b"Sun's Blessing Cooldown"),
+    SkillUseDef::active(skills::SK_SEEING_RED, skill_seeing_red, false)
+        .costs(SkillCost::endurance(150))
+        .cooldown(TICKS * 60, b"Seeing Red Cooldown"),
+    SkillUseDef::active(skills::SK_THUNDEROUS_FURY, skill_thunderous_fury, false)
+        .costs(SkillCost::endurance(250))
+        .cooldown(TICKS * 30, b"Thunderous Fury Cooldown"),
+    SkillUseDef::active(skills::SK_INNER_STRENGTH, skill_inner_strength, false)
+        .costs(SkillCost::endurance(200))
+        .cooldown(TICKS * 30, b"Inner Strength Cooldown"),
+    SkillUseDef::active(skills::SK_REVENANT_CONDUIT, skill_revenant_conduit, true)
+        .costs(SkillCost::mana(35)),
+    SkillUseDef::active(skills::SK_KINDRED_SPIRIT, skill_kindred_spirit, false),
+    SkillUseDef::active(skills::SK_SPECTRAL_PACT, skill_spectral_pact, true)
+        .costs(SkillCost::mana(40)),
+    SkillUseDef::active(skills::SK_ANGUISH_LAVA, skill_anguish_lava, true),
+    SkillUseDef::active(skills::SK_ANGUISH_EARTH, skill_anguish_earth, true),
+    SkillUseDef::active(skills::SK_ANGUISH_ICE, skill_anguish_ice, true),
+    SkillUseDef::active(skills::SK_LAVA_BLAST, skill_lava_blast, true),
+    SkillUseDef::active(
+        skills::SK_SPELLCASTER_KINDRED_SPIRIT,
+        skill_spellcaster_kindred_spirit,
+        false,
+    ),
+];
+
+/// Looks up the usage rules of a skill.
+///
+/// # Arguments
+///
+/// * `skill` - Skill index.
+///
+/// # Returns
+///
+/// * `Some(&SkillUseDef)` for skills that can be triggered, else `None`.
+pub fn find_skill_use_def(skill: usize) -> Option<&'static SkillUseDef> {
+    SKILL_USE_DEFS.iter().find(|d| d.skill == skill)
+}
+
+/// Runs the shared requirement checks for character `cn` using `def`.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character using the skill.
+/// * `def` - Usage rules of the skill.
+///
+/// # Returns
+///
+/// * `Ok(())` if the handler may run, otherwise the first failed check.
+pub fn check_skill_use(gs: &GameState, cn: usize, def: &SkillUseDef) -> Result<(), SkillUseError> {
+    if gs.characters[cn].skill[def.skill][0] == 0 {
+        return Err(SkillUseError::NotUsable);
+    }
+    if let SkillHandler::Passive(hint) = def.handler {
+        return Err(SkillUseError::Passive(hint));
+    }
+    if def.magic && (gs.characters[cn].flags & CharacterFlags::NoMagic.bits()) != 0 {
+        return Err(SkillUseError::NoMagic);
+    }
+    if def.recharge != Recharge::None && has_active_spell_temp(gs, cn, def.skill as u16) {
+        return Err(SkillUseError::OnCooldown);
+    }
+    Ok(())
+}
+
+/// Uses skill `nr` for character `cn`: checks the requirements from
+/// [`SKILL_USE_DEFS`] and runs the skill's handler.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character using the skill.
+/// * `nr` - Skill index from the client.
+///
+/// # Returns
+///
+/// * `Ok(())` once the handler ran (it may still fail on its own checks,
+///   which it reports itself).
+/// * `Err(SkillUseError)` if a shared requirement failed.
+pub fn use_skill(gs: &mut GameState, cn: usize, nr: usize) -> Result<(), SkillUseError> {
+    if nr >= MAX_SKILLS {
+        return Err(SkillUseError::NotUsable);
+    }
+    let def = find_skill_use_def(nr).ok_or(SkillUseError::NotUsable)?;
+    check_skill_use(gs, cn, def)?;
+    if let SkillHandler::Active(handler) = def.handler {
+        handler(gs, cn);
+    }
+    Ok(())
+}
+
+/// Deducts the flat cost of `skill` from character `cn`. Both resources
+/// are checked before either is taken.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character paying.
+/// * `skill` - Skill index.
+///
+/// # Returns
+///
+/// * `Ok(())` when paid (or the skill has no flat cost), otherwise the
+///   missing resource. Nothing is deducted on error.
+pub fn pay_skill_cost(gs: &mut GameState, cn: usize, skill: usize) -> Result<(), SkillUseError> {
+    let cost = find_skill_use_def(skill).map_or(SkillCost::FREE, |d| d.cost);
+    let ch = &gs.characters[cn];
+    let mana = if cost.mana > 0 {
+        concentrated_mana_cost(ch, cost.mana) * 1000
+    } else {
+        0
+    };
+    let endurance = cost.endurance * 1000;
+    if endurance > ch.a_end {
+        return Err(SkillUseError::NotEnoughEndurance);
+    }
+    if mana > ch.a_mana {
+        return Err(SkillUseError::NotEnoughMana);
+    }
+    let ch = &mut gs.characters[cn];
+    ch.a_end -= endurance;
+    ch.a_mana -= mana;
+    Ok(())
+}
+
+/// Charges the flat cost of `skill`, telling the player when they cannot
+/// afford it. Handlers call this once their own target checks passed.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character paying.
+/// * `skill` - Skill index.
+///
+/// # Returns
+///
+/// * `true` if the cost was paid and the skill should proceed.
+pub fn charge_skill(gs: &mut GameState, cn: usize, skill: usize) -> bool {
+    match pay_skill_cost(gs, cn, skill) {
+        Ok(()) => true,
+        Err(err) => {
+            gs.do_character_log(cn, err.color(), err.message());
+            false
+        }
+    }
+}
+
+/// Starts the declared cooldown of `skill` on character `cn`.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character that used the skill.
+/// * `skill` - Skill index.
+pub fn start_skill_cooldown(gs: &mut GameState, cn: usize, skill: usize) {
+    let Some(def) = find_skill_use_def(skill) else {
+        return;
+    };
+    if let Recharge::Cooldown { ticks, name } = def.recharge {
+        add_skill_cooldown(gs, cn, ticks, skill as u16, name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+
+    #[test]
+    fn skill_table_has_no_duplicates() {
+        for (i, def) in SKILL_USE_DEFS.iter().enumerate() {
+            assert!(def.skill < MAX_SKILLS);
+            assert!(
+                SKILL_USE_DEFS[i + 1..].iter().all(|d| d.skill != def.skill),
+                "skill {} listed twice",
+                def.skill
+            );
+            if let Recharge::Cooldown { ticks, name } = def.recharge {
+                assert!(ticks > 0 && name.len() <= 40);
+            }
+        }
+    }
+
+    #[test]
+    fn use_skill_reports_typed_failures() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            assert_eq!(
+                use_skill(gs, cn, skills::SK_HEAL),
+                Err(SkillUseError::NotUsable)
+            );
+            assert_eq!(
+                use_skill(gs, cn, MAX_SKILLS + 3),
+                Err(SkillUseError::NotUsable)
+            );
+
+            gs.characters[cn].skill[skills::SK_REST][0] = 1;
+            assert_eq!(
+                use_skill(gs, cn, skills::SK_REST),
+                Err(SkillUseError::Passive(HINT_AUTO_REST))
+            );
+
+            gs.characters[cn].skill[skills::SK_HEAL][0] = 1;
+            gs.characters[cn].flags |= CharacterFlags::NoMagic.bits();
+            assert_eq!(
+                use_skill(gs, cn, skills::SK_HEAL),
+                Err(SkillUseError::NoMagic)
+            );
+
+            let marker = 1;
+            gs.items[marker] = core::types::Item::default();
+            gs.items[marker].used = USE_ACTIVE;
+            gs.items[marker].temp = skills::SK_GASH as u16;
+            gs.characters[cn].spell[0] = marker as u32;
+            gs.characters[cn].skill[skills::SK_GASH][0] = 1;
+            assert_eq!(
+                use_skill(gs, cn, skills::SK_GASH),
+                Err(SkillUseError::OnCooldown)
+            );
+        });
+    }
+
+    #[test]
+    fn pay_skill_cost_deducts_only_when_affordable() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].a_end = 100 * 1000;
+            gs.characters[cn].a_mana = 10 * 1000;
+            assert_eq!(
+                pay_skill_cost(gs, cn, skills::SK_HEAL),
+                Err(SkillUseError::NotEnoughMana)
+            );
+            assert_eq!(
+                pay_skill_cost(gs, cn, skills::SK_WARCRY),
+                Err(SkillUseError::NotEnoughEndurance)
+            );
+            assert_eq!(gs.characters[cn].a_mana, 10 * 1000);
+            assert_eq!(gs.characters[cn].a_end, 100 * 1000);
+
+            assert_eq!(pay_skill_cost(gs, cn, skills::SK_LIGHT), Ok(()));
+            assert_eq!(pay_skill_cost(gs, cn, skills::SK_WIMPY), Ok(()));
+            assert_eq!(gs.characters[cn].a_mana, 5 * 1000);
+            assert_eq!(gs.characters[cn].a_end, 80 * 1000);
+        });
+    }
+}
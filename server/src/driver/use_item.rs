@@ -4,7 +4,8 @@ use crate::game_state::GameState;
 use crate::god::God;
 use crate::helpers::{self};
 use crate::populate::pop_create_char;
-use crate::{chlog, driver, player, points, populate};
+use crate::{chlog, driver, player, populate};
+use core::balance;
 use core::constants::{
     AT_AGIL, AT_INT, AT_STREN, AT_WILL, CharacterFlags, DX_RIGHT, ItemFlags, MAXITEM, MAXSKILL,
     MAXTITEM, MF_NOEXPIRE, NT_HITME, SERVER_MAPX, SERVER_MAPY, TICKS, USE_ACTIVE, USE_EMPTY,
@@ -14,6 +15,7 @@ use core::skills::{self, attribute_name};
 use core::string_operations::c_string_to_str;
 use core::traits;
 use core::types::FontColor;
+use server::journal::GoldTransferKind;
 
 // Helper function to take an item from a character
 fn take_item_from_char(gs: &mut GameState, item_idx: usize, cn: usize) {
@@ -393,7 +395,7 @@ pub fn use_create_gold(gs: &mut GameState, cn: usize, item_idx: usize) -> bool {
     }
 
     let gold_to_add = gold_amount * 100;
-    gs.characters[cn].gold += gold_to_add as i32;
+    gs.transfer_gold(0, cn, gold_to_add as i32, GoldTransferKind::ItemUse);
     gs.do_character_log(
         cn,
         core::types::FontColor::Green,
@@ -1436,7 +1438,7 @@ pub fn use_scroll(gs: &mut GameState, cn: usize, item_idx: usize) -> bool {
 
         let v = i32::from(current_val);
         let diff = i32::from(difficulty);
-        let pts = points::skill_needed(v, diff);
+        let pts = balance::skill_needed(v, diff);
         gs.characters[cn].points_tot += pts;
         gs.characters[cn].skill[skill_nr][0] += 1;
         gs.do_check_new_level(cn);
@@ -1859,21 +1861,21 @@ pub fn use_crystal_sub(gs: &mut GameState, _cn: usize, item_idx: usize) -> i32 {
         let mut pts = 0i32;
         for z in 0..5 {
             for m in 10..i32::from(ch.attrib[z][0]) {
-                pts += points::attrib_needed(m, 3);
+                pts += balance::attrib_needed(m, 3);
             }
         }
         for m in 50..i32::from(ch.hp[0]) {
-            pts += points::hp_needed(m, 3);
+            pts += balance::hp_needed(m, 3);
         }
         for m in 50..i32::from(ch.end[0]) {
-            pts += points::end_needed(m, 2);
+            pts += balance::end_needed(m, 2);
         }
         for m in 50..i32::from(ch.mana[0]) {
-            pts += points::mana_needed(m, 3);
+            pts += balance::mana_needed(m, 3);
         }
         for z in 0..core::skills::MAX_SKILLS {
             for m in 1..i32::from(ch.skill[z][0]) {
-                pts += points::skill_needed(m, 2);
+                pts += balance::skill_needed(m, 2);
             }
         }
 
@@ -2353,16 +2355,14 @@ pub fn rat_eye(gs: &mut GameState, cn: usize, item_idx: usize) -> bool {
         return false;
     }
 
-    let citem = gs.characters[cn].citem;
-
-    if citem == 0 || (citem & 0x80000000) != 0 {
+    let Some(citem) = gs.cursor_item(cn) else {
         gs.do_character_log(
             cn,
             core::types::FontColor::Green,
             "What do you want to do with it?\n",
         );
         return false;
-    }
+    };
 
     // Check if rat eye is carried (not on ground)
     let carried = gs.items[item_idx].carried;
@@ -2376,7 +2376,7 @@ pub fn rat_eye(gs: &mut GameState, cn: usize, item_idx: usize) -> bool {
     }
 
     // Check if citem matches any of the required templates in data[0-8]
-    let citem_temp = gs.items[citem as usize].temp;
+    let citem_temp = gs.item(citem).temp;
 
     let mut slot = None;
     for n in 0..9 {
@@ -2408,7 +2408,7 @@ pub fn rat_eye(gs: &mut GameState, cn: usize, item_idx: usize) -> bool {
 
     // Remove the citem
     {
-        gs.items[citem as usize].used = USE_EMPTY;
+        gs.item_mut(citem).used = USE_EMPTY;
     };
     {
         gs.characters[cn].citem = 0;
@@ -2687,20 +2687,18 @@ pub fn use_spawn(gs: &mut GameState, cn: usize, item_idx: usize) -> bool {
         let required_template = gs.items[item_idx].data[1] as usize;
 
         if required_template != 0 {
-            let citem = gs.characters[cn].citem;
-
-            if citem == 0 || (citem & 0x80000000) != 0 {
+            let Some(citem) = gs.cursor_item(cn) else {
                 return false;
-            }
+            };
 
-            let citem_template = gs.items[citem as usize].temp;
+            let citem_template = gs.item(citem).temp;
             if citem_template as usize != required_template {
                 return false;
             }
 
             // Remove the required item
             {
-                gs.items[citem as usize].used = USE_EMPTY;
+                gs.item_mut(citem).used = USE_EMPTY;
             };
             {
                 gs.characters[cn].citem = 0;
@@ -6069,6 +6067,14 @@ pub fn use_driver(gs: &mut GameState, cn: usize, item_idx: usize, carried: bool)
         return;
     }
 
+    // Scripts see the use first and may replace the item's driver.
+    if cn != 0 && gs.script_use_item(cn, item_idx) {
+        if !carried {
+            gs.characters[cn].cerrno = core::constants::ERR_SUCCESS as u16;
+        }
+        return;
+    }
+
     // If the character holds a flask (driver 4 + IF_USESPECIAL) in citem and is
     // using a potion ingredient, redirect to use_mix_potion with roles swapped so
     // that "flask on flower" and "flower on flask" both produce a potion.
@@ -6194,6 +6200,7 @@ pub fn use_driver(gs: &mut GameState, cn: usize, item_idx: usize, carried: bool)
             67 => use_garbage(gs, cn, item_idx),
             68 => use_soulstone(gs, cn, item_idx),
             69 => false,
+            70 => driver::use_chest(gs, cn, item_idx),
             _ => {
                 log::warn!(
                     "use_driver: Unknown use_driver {} for item {}",
@@ -7738,9 +7745,9 @@ pub fn item_tick_expire(gs: &mut GameState) {
             } else {
                 let act = if active != 0 { 1 } else { 0 };
 
-                {
-                    gs.items[in_idx].current_age[act] += EXP_TIME as u32;
-                };
+                // Rarer items linger on the ground longer (see core::loot).
+                gs.items[in_idx].current_age[act] +=
+                    core::loot::LootTier::of(flags).ground_age_step(EXP_TIME as u32);
 
                 if (flags & ItemFlags::IF_LIGHTAGE.bits()) != 0 {
                     lightage(gs, in_idx, EXP_TIME);
@@ -8016,6 +8023,11 @@ pub fn item_tick_gc(gs: &mut GameState) {
             }
         }
 
+        // Letter attachments and broker listings are carried by no one on purpose
+        if gs.mail.holds_item(n) || gs.consignments.holds_item(n) {
+            continue;
+        }
+
         // Item is garbage - remove it
         gs.items[n].used = USE_EMPTY;
         gs.globals.gc_cnt += 1;
@@ -8530,12 +8542,11 @@ pub fn step_portal2_lab13(gs: &mut GameState, cn: usize, _item_idx: usize) -> i3
         }
     }
 
-    let citem = gs.characters[cn].citem;
-    if citem != 0 && (citem & 0x80000000) == 0 {
-        let temp = gs.items[citem as usize].temp;
+    if let Some(citem) = gs.cursor_item(cn) {
+        let temp = gs.item(citem).temp;
         if temp == 664 {
             gs.characters[cn].citem = 0;
-            gs.items[citem as usize].used = USE_EMPTY;
+            gs.item_mut(citem).used = USE_EMPTY;
         }
     }
     gs.do_update_char(cn);
@@ -8560,13 +8571,12 @@ pub fn step_portal2_lab13(gs: &mut GameState, cn: usize, _item_idx: usize) -> i3
 /// * Panics if any legacy id or index parameter used by `step_portal_arena` is outside the corresponding game-state collection.
 pub fn step_portal_arena(gs: &mut GameState, cn: usize, item_idx: usize) -> i32 {
     // Check for arena token (temp 687) in citem
-    let citem = gs.characters[cn].citem;
     let mut flag = 0;
-    if citem != 0 && (citem & 0x80000000) == 0 {
-        let temp = gs.items[citem as usize].temp;
+    if let Some(citem) = gs.cursor_item(cn) {
+        let temp = gs.item(citem).temp;
         if temp == 687 {
             gs.characters[cn].citem = 0;
-            gs.items[citem as usize].used = USE_EMPTY;
+            gs.item_mut(citem).used = USE_EMPTY;
 
             flag = 1;
         }
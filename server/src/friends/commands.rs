@@ -0,0 +1,256 @@
+//! `#friend` and the `SV_FRIENDENTRY` updates that keep friends panels
+//! current.
+
+use core::chat::{ChatChannel, ChatStyle};
+use core::constants::{CLIENT_CAP_FRIENDS, CharacterFlags, MAXPLAYER, ST_NORMAL};
+use core::friends::{FriendOp, encode_friend_entry};
+use core::server_commands::ServerCommandType;
+use core::types::FontColor;
+
+use super::store;
+use crate::game_state::GameState;
+use crate::guilds::commands::online_slot;
+use crate::helpers::invis_level;
+use crate::mail::commands::player_named;
+use crate::network_manager;
+
+const FRIEND_HELP: &str = "Friend commands: #friend (list your friends and who is online), \
+#friend add <player>, #friend remove <player>. Your friends list is shared by all \
+characters of your account.\n";
+
+/// Whether `cn` is hidden from `viewer` by invisibility, the same rule
+/// `#who` and login announcements follow.
+fn hidden_from(gs: &GameState, cn: usize, viewer: usize) -> bool {
+    let hiding = CharacterFlags::Invisible.bits() | CharacterFlags::NoWho.bits();
+    gs.characters[cn].flags & hiding != 0
+        && invis_level(&gs.characters[cn]) > invis_level(&gs.characters[viewer])
+}
+
+/// Whether `viewer` should see `cn` as online.
+fn shown_online(gs: &GameState, cn: usize, viewer: usize) -> bool {
+    online_slot(gs, cn).is_some() && !hidden_from(gs, cn, viewer)
+}
+
+/// API account `cn` is logged in with, or `0` when there is none.
+fn account_of(gs: &GameState, cn: usize) -> u64 {
+    online_slot(gs, cn).map_or(0, |nr| gs.players[nr].api_account_id)
+}
+
+/// Sends the whole friends list of its account to player slot `nr`.
+///
+/// A `Clear` entry goes first, so friends removed by another character of
+/// the same account disappear too.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot; ignored unless it advertises `CLIENT_CAP_FRIENDS`.
+pub fn plr_send_friends(gs: &mut GameState, nr: usize) {
+    if gs.players[nr].capabilities & CLIENT_CAP_FRIENDS == 0 {
+        return;
+    }
+    let viewer = gs.players[nr].usnr;
+    let buf = encode_friend_entry(
+        ServerCommandType::FriendEntry as u8,
+        FriendOp::Clear,
+        false,
+        "",
+    );
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    let friends = gs
+        .friends
+        .friends_of(gs.players[nr].api_account_id)
+        .to_vec();
+    for cn in friends {
+        let buf = encode_friend_entry(
+            ServerCommandType::FriendEntry as u8,
+            FriendOp::Set,
+            shown_online(gs, cn, viewer),
+            gs.characters[cn].get_name(),
+        );
+        network_manager::xsend(gs, nr, &buf, buf.len());
+    }
+}
+
+/// Tells every online player whose account lists `cn` that it logged in or
+/// out.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character that logged in or out.
+/// * `online` - Whether it is now online.
+pub fn friend_presence_changed(gs: &mut GameState, cn: usize, online: bool) {
+    let watchers: Vec<u64> = gs.friends.watchers_of(cn).collect();
+    if watchers.is_empty() {
+        return;
+    }
+    let name = gs.characters[cn].get_name().to_owned();
+    let buf = encode_friend_entry(
+        ServerCommandType::FriendEntry as u8,
+        FriendOp::Set,
+        online,
+        &name,
+    );
+    let notice = format!(
+        "Your friend {} has {}.\n",
+        name,
+        if online { "logged in" } else { "logged out" }
+    );
+    for nr in 1..MAXPLAYER {
+        let player = &gs.players[nr];
+        if player.state != ST_NORMAL
+            || player.api_account_id == 0
+            || !watchers.contains(&player.api_account_id)
+        {
+            continue;
+        }
+        let viewer = player.usnr;
+        if viewer == cn || hidden_from(gs, cn, viewer) {
+            continue;
+        }
+        if gs.players[nr].capabilities & CLIENT_CAP_FRIENDS != 0 {
+            network_manager::xsend(gs, nr, &buf, buf.len());
+        }
+        gs.do_character_styled_log(viewer, ChatStyle::new(ChatChannel::System), &notice);
+    }
+}
+
+impl GameState {
+    /// Handles `#friend <subcommand> [player]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character issuing the command.
+    /// * `sub` - Subcommand; empty lists the friends.
+    /// * `rest` - Everything after the subcommand.
+    pub(crate) fn do_friend(&mut self, cn: usize, sub: &str, rest: &str) {
+        if self.characters[cn].flags & CharacterFlags::Player.bits() == 0 {
+            return;
+        }
+        let account = account_of(self, cn);
+        let result = if account == 0 {
+            Err("Friends lists need an account login.".to_owned())
+        } else {
+            let name = rest.trim();
+            match sub.to_ascii_lowercase().as_str() {
+                "" | "list" => {
+                    self.do_friend_list(cn, account);
+                    return;
+                }
+                "help" => {
+                    self.do_character_log(cn, FontColor::Yellow, FRIEND_HELP);
+                    return;
+                }
+                "add" => self.do_friend_add(cn, account, name),
+                "remove" | "delete" => self.do_friend_remove(cn, account, name),
+                _ => Err("Unknown friend command. Try #friend help.".to_owned()),
+            }
+        };
+        if let Err(message) = result {
+            self.do_character_log(cn, FontColor::Red, &format!("{}\n", message));
+        }
+    }
+
+    fn do_friend_list(&mut self, cn: usize, account: u64) {
+        let friends = self.friends.friends_of(account).to_vec();
+        if friends.is_empty() {
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                "Your friends list is empty. Add someone with #friend add <player>.\n",
+            );
+            return;
+        }
+        let (online, offline): (Vec<usize>, Vec<usize>) = friends
+            .into_iter()
+            .partition(|&co| shown_online(self, co, cn));
+        let names = |list: &[usize]| {
+            list.iter()
+                .map(|&co| self.characters[co].get_name().to_owned())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let (online_names, offline_names) = (names(&online), names(&offline));
+        if !online.is_empty() {
+            self.do_character_log(cn, FontColor::Green, &format!("Online: {}\n", online_names));
+        }
+        if !offline.is_empty() {
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                &format!("Offline: {}\n", offline_names),
+            );
+        }
+    }
+
+    fn do_friend_add(&mut self, cn: usize, account: u64, name: &str) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("Usage: #friend add <player>".to_owned());
+        }
+        let co = player_named(self, name)
+            .ok_or_else(|| format!("There is no player called {}.", name))?;
+        if co == cn {
+            return Err("You are always your own friend.".to_owned());
+        }
+        self.friends.add(account, co)?;
+        store::save_friends(&self.storage, account, self.friends.friends_of(account));
+        log::info!("Account {} added character {} as a friend", account, co);
+
+        let friend_name = self.characters[co].get_name().to_owned();
+        self.friends_changed(account, co, FriendOp::Set);
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("{} is now on your friends list.\n", friend_name),
+        );
+        Ok(())
+    }
+
+    fn do_friend_remove(&mut self, cn: usize, account: u64, name: &str) -> Result<(), String> {
+        if name.is_empty() {
+            return Err("Usage: #friend remove <player>".to_owned());
+        }
+        let co = self
+            .friends
+            .friends_of(account)
+            .iter()
+            .copied()
+            .find(|&co| self.characters[co].get_name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("{} is not on your friends list.", name))?;
+        self.friends.remove(account, co)?;
+        store::save_friends(&self.storage, account, self.friends.friends_of(account));
+        log::info!("Account {} removed character {} as a friend", account, co);
+
+        let friend_name = self.characters[co].get_name().to_owned();
+        self.friends_changed(account, co, FriendOp::Remove);
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("{} is no longer on your friends list.\n", friend_name),
+        );
+        Ok(())
+    }
+
+    /// Sends the added or removed friend `co` to the friends panel of every
+    /// online character of `account`.
+    fn friends_changed(&mut self, account: u64, co: usize, op: FriendOp) {
+        for nr in 1..MAXPLAYER {
+            let player = &self.players[nr];
+            if player.state != ST_NORMAL
+                || player.api_account_id != account
+                || player.capabilities & CLIENT_CAP_FRIENDS == 0
+            {
+                continue;
+            }
+            let online = op == FriendOp::Set && shown_online(self, co, player.usnr);
+            let buf = encode_friend_entry(
+                ServerCommandType::FriendEntry as u8,
+                op,
+                online,
+                self.characters[co].get_name(),
+            );
+            network_manager::xsend(self, nr, &buf, buf.len());
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Friends lists: player characters an account wants to know are online.
+//!
+//! `#friend add <player>` puts a character on the list of the account the
+//! issuer logged in with, so every character of that account shares it.
+//! Whenever a listed character logs in or out, every online character whose
+//! account lists it is told in chat and, with `CLIENT_CAP_FRIENDS`, through
+//! an `SV_FRIENDENTRY` update of the friends panel.
+//!
+//! [`FriendRegistry`] holds the lists and the rules and knows nothing about
+//! the world, so it can be tested on its own. [`commands`] turns text
+//! commands into registry calls and sends the presence updates. [`store`]
+//! persists every list to a KeyDB hash.
+
+pub mod commands;
+pub mod store;
+
+use std::collections::BTreeMap;
+
+use core::friends::MAX_FRIENDS;
+
+/// Every account's friends list, keyed by API account id.
+#[derive(Debug, Default)]
+pub struct FriendRegistry {
+    lists: BTreeMap<u64, Vec<usize>>,
+}
+
+impl FriendRegistry {
+    /// Builds a registry from stored lists.
+    ///
+    /// # Arguments
+    ///
+    /// * `lists` - `(account id, character slots)` pairs as loaded by
+    ///   [`store::load_friends`].
+    pub fn from_lists(lists: Vec<(u64, Vec<usize>)>) -> Self {
+        Self {
+            lists: lists
+                .into_iter()
+                .filter(|(_, friends)| !friends.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Characters on the list of `account`, in the order they were added.
+    pub fn friends_of(&self, account: u64) -> &[usize] {
+        self.lists.get(&account).map_or(&[], Vec::as_slice)
+    }
+
+    /// Accounts whose list contains `cn`.
+    pub fn watchers_of(&self, cn: usize) -> impl Iterator<Item = u64> + '_ {
+        self.lists
+            .iter()
+            .filter(move |(_, friends)| friends.contains(&cn))
+            .map(|(&account, _)| account)
+    }
+
+    /// Adds `cn` to the list of `account`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())`, or a message for the player when `cn` is already listed
+    ///   or the list is full.
+    pub fn add(&mut self, account: u64, cn: usize) -> Result<(), String> {
+        let friends = self.lists.entry(account).or_default();
+        if friends.contains(&cn) {
+            return Err("They are already on your friends list.".to_owned());
+        }
+        if friends.len() >= MAX_FRIENDS {
+            return Err(format!(
+                "Your friends list is full ({} names).",
+                MAX_FRIENDS
+            ));
+        }
+        friends.push(cn);
+        Ok(())
+    }
+
+    /// Removes `cn` from the list of `account`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())`, or a message for the player when `cn` is not listed.
+    pub fn remove(&mut self, account: u64, cn: usize) -> Result<(), String> {
+        let friends = self
+            .lists
+            .get_mut(&account)
+            .ok_or_else(|| "They are not on your friends list.".to_owned())?;
+        let idx = friends
+            .iter()
+            .position(|&friend| friend == cn)
+            .ok_or_else(|| "They are not on your friends list.".to_owned())?;
+        friends.remove(idx);
+        if friends.is_empty() {
+            self.lists.remove(&account);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_are_per_account_and_bounded() {
+        let mut registry = FriendRegistry::default();
+        registry.add(7, 100).unwrap();
+        registry.add(7, 101).unwrap();
+        registry.add(8, 100).unwrap();
+        assert!(registry.add(7, 100).is_err());
+        assert_eq!(registry.friends_of(7), [100, 101]);
+        assert_eq!(registry.watchers_of(100).collect::<Vec<_>>(), [7, 8]);
+        assert_eq!(registry.watchers_of(101).collect::<Vec<_>>(), [7]);
+
+        registry.remove(8, 100).unwrap();
+        assert!(registry.remove(8, 100).is_err());
+        assert!(registry.friends_of(8).is_empty());
+
+        for cn in 200..200 + MAX_FRIENDS - 2 {
+            registry.add(7, cn).unwrap();
+        }
+        assert!(registry.add(7, 999).is_err());
+    }
+
+    #[test]
+    fn empty_stored_lists_are_dropped() {
+        let registry = FriendRegistry::from_lists(vec![(3, Vec::new()), (4, vec![12])]);
+        assert_eq!(registry.lists.len(), 1);
+        assert_eq!(registry.friends_of(4), [12]);
+    }
+}
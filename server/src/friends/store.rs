@@ -0,0 +1,108 @@
+//! Persistence for friends lists.
+//!
+//! Every account's list is the record `game:friends:{account_id}` with the
+//! single field `friends`, the character slots separated by `;`, stored
+//! through [`StorageBackend::save_record`]. An emptied list is written back
+//! as an empty field; such records are removed the next time the server
+//! starts.
+
+use server::storage::StorageBackend;
+
+use super::FriendRegistry;
+
+/// Prefix of the per-account record keys.
+pub const FRIENDS_KEY_PREFIX: &str = "game:friends:";
+
+/// Encodes a list for the `friends` field.
+pub fn encode_friends(friends: &[usize]) -> String {
+    friends
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Decodes the `friends` field.
+///
+/// # Arguments
+///
+/// * `text` - Stored list.
+///
+/// # Returns
+///
+/// * The character slots, or an error naming the first malformed entry.
+pub fn decode_friends(text: &str) -> Result<Vec<usize>, String> {
+    text.split(';')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse::<usize>()
+                .map_err(|_| format!("malformed friend '{}'", entry))
+        })
+        .collect()
+}
+
+/// Saves the list of `account`.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `account` - API account id.
+/// * `friends` - The account's current list; empty after the last removal.
+pub fn save_friends(storage: &StorageBackend, account: u64, friends: &[usize]) {
+    storage.save_record(
+        format!("{}{}", FRIENDS_KEY_PREFIX, account),
+        vec![("friends", encode_friends(friends))],
+    );
+}
+
+/// Loads every friends list from the storage backend.
+///
+/// Records of emptied lists are removed while loading.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+///
+/// # Returns
+///
+/// * The registry. A list that cannot be parsed is logged and skipped; an
+///   error means the backend could not be read at all.
+pub fn load_friends(storage: &StorageBackend) -> Result<FriendRegistry, String> {
+    let mut lists = Vec::new();
+    let mut emptied = Vec::new();
+    for (key, fields) in storage.load_records(FRIENDS_KEY_PREFIX)? {
+        let Some(account) = key
+            .strip_prefix(FRIENDS_KEY_PREFIX)
+            .and_then(|id| id.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        match decode_friends(fields.get("friends").map_or("", String::as_str)) {
+            Ok(friends) if friends.is_empty() => emptied.push(key),
+            Ok(friends) => lists.push((account, friends)),
+            Err(e) => log::error!("Skipping friends list of account {}: {}", account, e),
+        }
+    }
+    if let Err(e) = storage.delete_records(&emptied) {
+        log::warn!("Failed to remove empty friends lists: {}", e);
+    }
+
+    log::info!("Loaded {} friends list(s)", lists.len());
+    Ok(FriendRegistry::from_lists(lists))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn friends_round_trip() {
+        let friends = vec![12, 340, 5];
+        let text = encode_friends(&friends);
+        assert_eq!(text, "12;340;5");
+        assert_eq!(decode_friends(&text), Ok(friends));
+        assert_eq!(decode_friends(""), Ok(Vec::new()));
+        assert!(decode_friends("12;lots").is_err());
+    }
+}
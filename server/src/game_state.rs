@@ -2,7 +2,8 @@ use crate::path_finding::PathFinder;
 use crate::types::server_player::ServerPlayer;
 use core::constants::{CharacterFlags, USE_EMPTY};
 use core::talent_trees::total_points_spent;
-use std::collections::HashMap;
+use core::types::{CharId, ItemId, PlayerSlot};
+use std::collections::{HashMap, VecDeque};
 
 /// Runtime state for the Harakim Element Switching passive.
 ///
@@ -34,10 +35,11 @@ pub struct ElementSwitchState {
 ///   gs.shutdown();
 /// ```
 ///
-/// All persistence is backed by KeyDB.  Use the `world-snapshot` binary to
+/// Persistence is backed by KeyDB or SQLite ([`StorageBackend`]).  Use the `world-snapshot` binary to
 /// export or import the complete world state as a portable `.wsnap` file.
-use server::keydb::connection as keydb;
-use server::keydb::store;
+use server::journal::recorder::JournalRecorder;
+use server::keydb::template_reload::ReloadRequest;
+use server::storage::StorageBackend;
 
 /// The unified in-memory game state for the server.
 ///
@@ -112,6 +114,33 @@ pub struct GameState {
     pub talent_primary_hit_counts: Vec<u8>,
     /// Runtime-only last-element state for the Harakim Element Switching passive.
     pub element_switch_states: HashMap<usize, ElementSwitchState>,
+    /// Runtime-only linkdead characters awaiting reconnect, mapped to the
+    /// tick at which they are logged out.
+    pub linkdead: HashMap<usize, i32>,
+    /// Runtime-only recent kills per player character, newest first, for
+    /// stream overlays.
+    pub overlay_kills: HashMap<usize, VecDeque<core::stream_overlay_store::OverlayKill>>,
+    /// Runtime-only merchant trades not yet handed to the economy publisher,
+    /// oldest first.
+    pub market_trades: Vec<core::economy_store::Trade>,
+    /// Runtime-only per-area budget for cosmetic map decals.
+    pub decals: crate::decals::DecalLimiter,
+    /// Runtime-only snapshots of the items linked in chat.
+    pub item_links: crate::player::item_links::ItemLinkRegistry,
+    /// Player guilds and pending invitations.
+    pub guilds: crate::guilds::GuildRegistry,
+    /// Player mail waiting in mailboxes.
+    pub mail: crate::mail::MailRegistry,
+    /// Per-account friends lists.
+    pub friends: crate::friends::FriendRegistry,
+    /// Items left with brokers for sale, and open broker pages.
+    pub consignments: crate::consignment::ConsignmentRegistry,
+    /// Quest and dialogue scripts and their per-character flags; see
+    /// [`crate::scripting`].
+    pub scripts: crate::scripting::ScriptHost,
+    /// Runtime-only weather shared by outdoor players outside the
+    /// area-weather table.
+    pub world_weather: crate::state::weather::WorldWeather,
 
     // -- Labyrinth 9 --
     pub lab9: crate::lab9::Labyrinth9,
@@ -123,6 +152,14 @@ pub struct GameState {
     // -- Persistence (private) --
     /// Set to `true` until loaded runtime data needs a final persistence pass.
     saved_cleanly: bool,
+    /// Backend world data is loaded from and saved to.
+    pub storage: StorageBackend,
+    /// Template reloads requested by `#reload` or `SIGHUP`, applied by the
+    /// server between ticks.
+    pub pending_template_reloads: Vec<ReloadRequest>,
+    /// World mutation journal, recorded after every tick when
+    /// `MAG_JOURNAL_PATH` is set.
+    pub journal: Option<JournalRecorder>,
 
     // -- Runtime mode flags --
     /// When `true`, playtest-only commands such as `/equip` are available to all players.
@@ -131,6 +168,41 @@ pub struct GameState {
     /// normal gameplay behaviour outside of commands explicitly gated on this flag.
     pub playtest_mode: bool,
 
+    /// Ticks a disconnected character stays in the world awaiting a
+    /// reconnect before it is logged out. `0` logs out immediately.
+    ///
+    /// Set from the `MAG_LINKDEAD_GRACE_SECS` environment variable.
+    pub linkdead_grace_ticks: i32,
+
+    /// When `true`, area and world weather are sent to players.
+    ///
+    /// Set from the `MAG_WEATHER` environment variable.
+    pub weather_enabled: bool,
+
+    /// Kits granted to brand-new characters, or `None` to hand out the
+    /// legacy items from the race templates instead.
+    ///
+    /// Set from the `MAG_STARTER_KITS` environment variable.
+    pub starter_kits: Option<Vec<core::starter_kits::StarterKit>>,
+
+    /// Tiles around a player the map window is filled to; see
+    /// [`core::view`].
+    ///
+    /// Set from the `MAG_VIEW_RADIUS` environment variable.
+    pub view_radius: u8,
+
+    /// Ticks over which every visible map tile is resent once; `0` turns
+    /// the rolling keyframes off. See [`crate::player::map_keyframe`].
+    ///
+    /// Set from the `MAG_MAP_KEYFRAME_SECS` environment variable.
+    pub map_keyframe_ticks: i32,
+
+    /// Keyed NPC dialogue templates; see [`core::dialogue`].
+    ///
+    /// Translations are loaded from the `MAG_DIALOGUE_DIR` environment
+    /// variable.
+    pub dialogue: core::dialogue::DialogueCatalog,
+
     /// God-mode activation password loaded from the `MAG_GOD_PASSWORD` environment variable.
     ///
     /// Any player who types this string in chat is immediately granted all god-level flags.
@@ -211,18 +283,97 @@ impl GameState {
             penta_needed: 5,
             talent_primary_hit_counts: vec![0; core::constants::MAXCHARS],
             element_switch_states: HashMap::new(),
+            linkdead: HashMap::new(),
+            overlay_kills: HashMap::new(),
+            market_trades: Vec::new(),
+            decals: crate::decals::DecalLimiter::default(),
+            item_links: crate::player::item_links::ItemLinkRegistry::default(),
+            guilds: crate::guilds::GuildRegistry::default(),
+            mail: crate::mail::MailRegistry::default(),
+            friends: crate::friends::FriendRegistry::default(),
+            consignments: crate::consignment::ConsignmentRegistry::default(),
+            scripts: crate::scripting::ScriptHost::default(),
+            world_weather: crate::state::weather::WorldWeather::default(),
             // Labyrinth 9
             lab9: crate::lab9::Labyrinth9::new(),
             // Pathfinding
             pathfinder: PathFinder::new(),
-            // Persistence is enabled only after KeyDB data loads successfully.
+            // Persistence is enabled only after world data loads successfully.
             saved_cleanly: true,
+            storage: StorageBackend::KeyDb,
+            pending_template_reloads: Vec::new(),
+            journal: None,
             // Runtime mode flags
             playtest_mode: false,
+            linkdead_grace_ticks: core::constants::TICKS
+                * crate::state::linkdead::DEFAULT_LINKDEAD_GRACE_SECS,
+            weather_enabled: false,
+            starter_kits: Some(core::starter_kits::default_kits()),
+            view_radius: core::view::MAX_VIEW_RADIUS,
+            map_keyframe_ticks: core::constants::TICKS
+                * crate::player::map_keyframe::DEFAULT_MAP_KEYFRAME_SECS,
+            dialogue: core::dialogue::DialogueCatalog::default(),
             god_password: String::new(),
         }
     }
 
+    /// Character at a validated index.
+    ///
+    /// The tables are allocated at their full `MAX*` size in [`Self::new`],
+    /// so a [`CharId`] (and [`ItemId`] / [`PlayerSlot`] below) is always in
+    /// bounds.
+    pub(crate) fn character(&self, id: CharId) -> &core::types::Character {
+        &self.characters[id.index()]
+    }
+
+    /// Runtime item at a validated index.
+    pub(crate) fn item(&self, id: ItemId) -> &core::types::Item {
+        &self.items[id.index()]
+    }
+
+    /// Mutable variant of [`Self::item`].
+    pub(crate) fn item_mut(&mut self, id: ItemId) -> &mut core::types::Item {
+        &mut self.items[id.index()]
+    }
+
+    /// Player slot at a validated index.
+    pub(crate) fn player(&self, slot: PlayerSlot) -> &ServerPlayer {
+        &self.players[slot.index()]
+    }
+
+    /// Returns the item on the mouse cursor of character `cn`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character number.
+    ///
+    /// # Returns
+    ///
+    /// * `None` when the cursor is empty, holds gold (high bit of `citem`
+    ///   set) or holds an out-of-range item number.
+    pub(crate) fn cursor_item(&self, cn: usize) -> Option<ItemId> {
+        let citem = self.characters.get(cn)?.citem;
+        if citem & 0x8000_0000 != 0 {
+            return None;
+        }
+        ItemId::new(citem as usize)
+    }
+
+    /// Returns the player slot currently controlling character `cn`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character number.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(slot)` when `cn` names a valid player slot whose `usnr` points
+    ///   back at `cn`, `None` for NPCs and stale slots.
+    pub(crate) fn owning_player(&self, cn: usize) -> Option<PlayerSlot> {
+        let slot = PlayerSlot::from_i32(self.characters.get(cn)?.player)?;
+        (self.player(slot).usnr == cn).then_some(slot)
+    }
+
     /// Removes expired Element Switching state entries.
     ///
     /// # Arguments
@@ -233,40 +384,44 @@ impl GameState {
             .retain(|_, state| state.expires_at_tick > current_tick);
     }
 
-    /// Initialize a new `GameState` by loading all data from KeyDB.
+    /// Initialize a new `GameState` by loading all data from the configured
+    /// storage backend.
     ///
-    /// Allocates the struct, connects to KeyDB, and loads all world data.
-    /// Returns the fully populated game state or an error if loading fails.
+    /// Allocates the struct, reads [`StorageBackend::from_env`], and loads all
+    /// world data. Returns the fully populated game state or an error if
+    /// loading fails.
     ///
-    /// Requires KeyDB to have been seeded with `world-snapshot import` before
-    /// the server starts.
+    /// The KeyDB backend requires KeyDB to have been seeded with
+    /// `world-snapshot import` before the server starts; the SQLite backend
+    /// seeds an empty database from the world snapshot itself.
     ///
     /// # Returns
     ///
     /// * `Ok(GameState)` on success.
-    /// * `Err(String)` if the KeyDB connection or data load fails.
+    /// * `Err(String)` if the backend connection or data load fails.
     pub fn initialize() -> Result<GameState, String> {
         let mut gs = Self::new();
-        gs.load_from_keydb()?;
+        gs.storage = StorageBackend::from_env()?;
+        gs.load_from_storage()?;
         gs.saved_cleanly = false;
         Ok(gs)
     }
 
-    /// Fetch the latest MOTD from KeyDB for login-time display.
+    /// Fetch the latest MOTD from storage for login-time display.
     ///
-    /// Re-reads `game:motd` on each call so that operators can update the
+    /// Re-reads the MOTD on each call so that operators can update the
     /// message without restarting the server.  Falls back to the
-    /// boot-cached value if the KeyDB read fails.
+    /// boot-cached value if the read fails.
     ///
     /// # Returns
     ///
     /// * The current message of the day string.
     pub fn latest_message_of_the_day(&self) -> String {
-        match keydb::load_message_of_the_day() {
+        match self.storage.load_message_of_the_day() {
             Ok(motd) => Self::normalize_message_of_the_day(motd),
             Err(error) => {
                 log::warn!(
-                    "Falling back to cached MOTD after KeyDB read failure: {}",
+                    "Falling back to cached MOTD after storage read failure: {}",
                     error
                 );
                 self.message_of_the_day.clone()
@@ -274,15 +429,14 @@ impl GameState {
         }
     }
 
-    /// Load all data from KeyDB.
+    /// Load all data from the storage backend.
     ///
     /// # Returns
     ///
     /// * `Ok(())` on success.
-    /// * `Err(String)` if the KeyDB connection or load fails.
-    fn load_from_keydb(&mut self) -> Result<(), String> {
-        let mut con = keydb::connect()?;
-        let data = store::load_all(&mut con)?;
+    /// * `Err(String)` if the backend connection or load fails.
+    fn load_from_storage(&mut self) -> Result<(), String> {
+        let data = self.storage.load_all()?;
 
         self.map = data.map;
         self.items = data.items;
@@ -291,11 +445,26 @@ impl GameState {
         self.character_templates = data.character_templates;
         self.effects = data.effects;
         self.globals = data.globals;
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        if crate::wall_clock::migrate_hourly_buckets(&mut self.globals, local_offset) {
+            log::info!(
+                "Migrated hourly statistics from local hours (UTC{:+}s) to UTC hours",
+                local_offset
+            );
+            self.globals.set_dirty(true);
+        }
         self.bad_names = data.bad_names;
         self.bad_words = data.bad_words;
         self.message_of_the_day = data.message_of_the_day;
+        self.guilds = crate::guilds::store::load_guilds(&self.storage)?;
+        self.mail = crate::mail::store::load_mail(&self.storage)?;
+        self.friends = crate::friends::store::load_friends(&self.storage)?;
+        self.consignments = crate::consignment::store::load_consignments(&self.storage)?;
+        self.scripts
+            .set_flags(crate::scripting::store::load_script_flags(&self.storage)?);
 
         self.mark_talent_characters_for_stat_recompute();
+        self.pathfinder.build_regions(&self.map, &self.items);
 
         log::info!(
             "Globals data: dirty={}, character_cnt={}, ticker={}, fullmoon={}, newmoon={}, unique={}, cap={}",
@@ -329,7 +498,7 @@ impl GameState {
         }
     }
 
-    /// Save mutable runtime game data to KeyDB.
+    /// Save mutable runtime game data to the storage backend.
     ///
     /// Bad names, bad words, and MOTD are externally-managed content and are
     /// intentionally excluded from runtime saves.  Use `world-snapshot import`
@@ -338,21 +507,9 @@ impl GameState {
     /// # Returns
     ///
     /// * `Ok(())` on success.
-    /// * `Err(String)` if the KeyDB connection or save fails.
+    /// * `Err(String)` if the backend connection or save fails.
     pub fn save(&mut self) -> Result<(), String> {
-        self.save_to_keydb()
-    }
-
-    /// Save all mutable runtime game data to KeyDB.
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(())` on success.
-    /// * `Err(String)` if the KeyDB connection or save fails.
-    fn save_to_keydb(&self) -> Result<(), String> {
-        let mut con = keydb::connect()?;
-        store::save_runtime_data(
-            &mut con,
+        self.storage.save_runtime_data(
             &self.map,
             &self.items,
             &self.characters,
@@ -361,8 +518,16 @@ impl GameState {
         )
     }
 
+    /// Drop unsaved changes: the state will not be saved when it is dropped.
+    ///
+    /// Used by throwaway runs such as `--scenario` that must never write
+    /// their modified world back to storage.
+    pub(crate) fn discard_changes(&mut self) {
+        self.saved_cleanly = true;
+    }
+
     /// Perform a clean shutdown of the game state by clearing the dirty flag
-    /// and saving all data to KeyDB.
+    /// and saving all data to the storage backend.
     pub fn shutdown(&mut self) {
         self.globals.set_dirty(false);
         if let Err(e) = self.save() {
@@ -399,6 +564,26 @@ impl Drop for GameState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn owning_player_and_cursor_item_validate_raw_indices() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            assert_eq!(gs.owning_player(cn).map(PlayerSlot::index), Some(nr));
+            gs.characters[cn].player = core::constants::MAXPLAYER as i32;
+            assert_eq!(gs.owning_player(cn), None);
+            gs.characters[cn].player = -1;
+            assert_eq!(gs.owning_player(cn), None);
+
+            gs.characters[cn].citem = 0x8000_0010;
+            assert_eq!(gs.cursor_item(cn), None);
+            gs.characters[cn].citem = core::constants::MAXITEM as u32;
+            assert_eq!(gs.cursor_item(cn), None);
+            gs.characters[cn].citem = 42;
+            assert_eq!(gs.cursor_item(cn).map(ItemId::index), Some(42));
+        });
+    }
 
     #[test]
     fn normalize_motd_short_unchanged() {
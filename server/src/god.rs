@@ -4,6 +4,7 @@ use core::{
         ArmorType, CharacterFlags, DX_DOWN, DX_LEFT, DX_LEFTDOWN, DX_LEFTUP, DX_RIGHT,
         DX_RIGHTDOWN, DX_RIGHTUP, DX_UP, MagicArmorType, character_flags_name,
     },
+    disconnect::ban_text,
     logout_reasons::LogoutReason,
     ranks, skills,
     string_operations::c_string_to_str,
@@ -11,7 +12,8 @@ use core::{
     types::{Character, Map},
 };
 
-use server::keydb::{ban as keydb_ban, connection as keydb};
+use server::journal::GoldTransferKind;
+use server::keydb::{ban as keydb_ban, connection as keydb, template_reload};
 
 use crate::{
     area, chlog, driver, effect::EffectManager, game_state::GameState, helpers, player, populate,
@@ -430,7 +432,7 @@ impl God {
 
         loop {
             log::info!("Generating random name for new character...");
-            let potential_new_name = core::names::randomly_generate_name();
+            let potential_new_name = crate::deterministic::random_name();
 
             let name_exists = gs.characters.iter().any(|existing_char| {
                 existing_char.used != core::constants::USE_EMPTY
@@ -761,8 +763,18 @@ impl God {
         true
     }
 
-    /// Place a character using an explicit game-state borrow.
-    pub(crate) fn drop_char(gs: &mut GameState, character_id: usize, x: usize, y: usize) -> bool {
+    /// Whether a character may be placed on tile `(x, y)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Active game state used by this function.
+    /// * `x` - X coordinate used by this function.
+    /// * `y` - Y coordinate used by this function.
+    ///
+    /// # Returns
+    ///
+    /// * `true` for a free tile that is not blocked, a tavern or a death trap.
+    pub(crate) fn can_drop_char_at(gs: &GameState, x: usize, y: usize) -> bool {
         if !Map::is_sane_coordinates(x, y) {
             return false;
         }
@@ -770,16 +782,19 @@ impl God {
         let map_index = x + y * core::constants::SERVER_MAPX as usize;
 
         let item_on_tile = gs.map[map_index].it;
-        let move_is_valid = !(gs.map[map_index].ch != 0
+        !(gs.map[map_index].ch != 0
             || (item_on_tile != 0
                 && gs.items[item_on_tile as usize].flags
                     & core::constants::ItemFlags::IF_MOVEBLOCK.bits()
                     != 0)
             || gs.map[map_index].flags & u64::from(core::constants::MF_MOVEBLOCK) != 0
             || gs.map[map_index].flags & u64::from(core::constants::MF_TAVERN) != 0
-            || gs.map[map_index].flags & u64::from(core::constants::MF_DEATHTRAP) != 0);
+            || gs.map[map_index].flags & u64::from(core::constants::MF_DEATHTRAP) != 0)
+    }
 
-        if !move_is_valid {
+    /// Place a character using an explicit game-state borrow.
+    pub(crate) fn drop_char(gs: &mut GameState, character_id: usize, x: usize, y: usize) -> bool {
+        if !Self::can_drop_char_at(gs, x, y) {
             return false;
         }
 
@@ -873,7 +888,7 @@ impl God {
     }
 
     /// Try to drop an item near a tile while reusing an existing game-state borrow.
-    fn drop_item_fuzzy(gs: &mut GameState, nr: usize, x: usize, y: usize) -> bool {
+    pub(crate) fn drop_item_fuzzy(gs: &mut GameState, nr: usize, x: usize, y: usize) -> bool {
         let positions_to_try: [(usize, usize); 25] = [
             (x, y),
             (x + 1, y),
@@ -3133,9 +3148,8 @@ impl God {
                 return;
             };
 
-        let target = &mut gs.characters[co];
-        target.gold = (target.gold + total_silver as i32).max(0);
-        target.set_do_update_flags();
+        gs.transfer_gold(0, co, total_silver as i32, GoldTransferKind::GmGrant);
+        gs.characters[co].set_do_update_flags();
         gs.do_character_log(
             cn,
             core::types::FontColor::Green,
@@ -3280,7 +3294,13 @@ impl God {
         let name_str = c_string_to_str(&character_name);
         let player_id = gs.characters[co].player as usize;
 
-        player::connection::plr_logout(gs, co, player_id, LogoutReason::IdleTooLong);
+        player::connection::plr_logout_with_text(
+            gs,
+            co,
+            player_id,
+            LogoutReason::IdleTooLong,
+            "An administrator kicked you from the game.",
+        );
 
         gs.do_character_log(
             cn,
@@ -3454,7 +3474,10 @@ impl God {
     /// Find a character by name (case-insensitive).
     ///
     /// Returns the character index and name string if found, or None if not.
-    fn find_character_by_name_or_id(gs: &mut GameState, arg: &str) -> Option<(usize, String)> {
+    pub(crate) fn find_character_by_name_or_id(
+        gs: &mut GameState,
+        arg: &str,
+    ) -> Option<(usize, String)> {
         if arg.chars().all(|c| c.is_numeric()) {
             // Search by character number
             let co = arg.parse::<usize>().unwrap_or(0);
@@ -3758,6 +3781,42 @@ impl God {
         true
     }
 
+    /// Queue a reload of item and/or character templates from KeyDB.
+    ///
+    /// The reload is applied by the server between ticks, swapping the
+    /// template arrays only once every requested kind has loaded; the
+    /// outcome is reported back to `cn`.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Active game state the request is queued on.
+    /// * `cn` - Character issuing the command.
+    /// * `kinds` - `items`, `chars`, or empty/`all` for both.
+    pub fn reload_templates(gs: &mut GameState, cn: usize, kinds: &str) {
+        let Some((reload_items, reload_characters)) = template_reload::parse_reload_kinds(kinds)
+        else {
+            gs.do_character_log(
+                cn,
+                core::types::FontColor::Red,
+                "Usage: #reload [items|chars|all]\n",
+            );
+            return;
+        };
+
+        chlog!(cn, "Requested template reload ({})", kinds);
+        gs.pending_template_reloads
+            .push(template_reload::ReloadRequest::local(
+                template_reload::ReloadOrigin::Command(cn),
+                reload_items,
+                reload_characters,
+            ));
+        gs.do_character_log(
+            cn,
+            core::types::FontColor::Yellow,
+            "Template reload queued.\n",
+        );
+    }
+
     /// Command to make `co` perform a slap animation (cosmetic/admin).
     ///
     /// # Arguments
@@ -4600,7 +4659,8 @@ impl God {
 
         match keydb_ban::upsert_ban_record(&record) {
             Ok(_) => {
-                let kicked = Self::kick_matching_ban_target(gs, &target);
+                let text = ban_text(&record.reason, record.expires_at, now);
+                let kicked = Self::kick_matching_ban_target(gs, &target, &text);
                 let target_value = match &target {
                     BanTarget::Ipv4 { address } => ipv4_to_string(*address),
                     _ => target.value(),
@@ -4624,7 +4684,7 @@ impl God {
         }
     }
 
-    fn kick_matching_ban_target(gs: &mut GameState, target: &BanTarget) -> usize {
+    fn kick_matching_ban_target(gs: &mut GameState, target: &BanTarget, text: &str) -> usize {
         let mut players_to_kick = Vec::new();
         for player_id in 1..core::constants::MAXPLAYER.min(gs.players.len()) {
             if gs.players[player_id].sock.is_none() {
@@ -4647,7 +4707,13 @@ impl God {
         let kicked = players_to_kick.len();
         for player_id in players_to_kick {
             let character_id = gs.players[player_id].usnr;
-            player::connection::plr_logout(gs, character_id, player_id, LogoutReason::Kicked);
+            player::connection::plr_logout_with_text(
+                gs,
+                character_id,
+                player_id,
+                LogoutReason::Banned,
+                text,
+            );
         }
         kicked
     }
@@ -0,0 +1,384 @@
+//! `#guild` and `#guildtell`, and the `SV_GUILDINFO` / `SV_GUILDMEMBER`
+//! updates that keep guild panels current.
+
+use core::chat::{ChatChannel, ChatStyle};
+use core::constants::{CLIENT_CAP_GUILDS, CharacterFlags, MAXPLAYER, ST_NORMAL};
+use core::guilds::{GuildRank, GuildRosterOp, encode_guild_info, encode_guild_member};
+use core::server_commands::ServerCommandType;
+use core::types::FontColor;
+
+use super::{Guild, store};
+use crate::game_state::GameState;
+use crate::network_manager;
+
+const GUILD_HELP: &str = "Guild commands: #guild create <name>, #guild invite <player>, \
+#guild accept, #guild leave, #guild kick <member>, #guild promote <member>, \
+#guild demote <member>, #guild leader <member>, #guild motd <text>. \
+Talk to your guild with #guildtell <text>.\n";
+
+/// Player slot showing character `cn`, if it is logged in and playing.
+pub(crate) fn online_slot(gs: &GameState, cn: usize) -> Option<usize> {
+    let nr = usize::try_from(gs.characters[cn].player).ok()?;
+    (nr > 0 && nr < MAXPLAYER && gs.players[nr].usnr == cn && gs.players[nr].state == ST_NORMAL)
+        .then_some(nr)
+}
+
+/// Member of `guild` whose name is `name` (case-insensitive).
+fn member_by_name(gs: &GameState, guild: &Guild, name: &str) -> Option<usize> {
+    guild
+        .members
+        .iter()
+        .map(|m| m.cn)
+        .find(|&cn| gs.characters[cn].get_name().eq_ignore_ascii_case(name))
+}
+
+/// Sends the whole guild state of its character to player slot `nr`.
+///
+/// An empty `SV_GUILDINFO` is sent when the character is in no guild, which
+/// also clears a stale roster on the client.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot; ignored unless it advertises `CLIENT_CAP_GUILDS`.
+pub fn plr_send_guild(gs: &mut GameState, nr: usize) {
+    if gs.players[nr].capabilities & CLIENT_CAP_GUILDS == 0 {
+        return;
+    }
+    let cn = gs.players[nr].usnr;
+    let Some(guild) = gs.guilds.guild_of(cn).cloned() else {
+        let buf = encode_guild_info(
+            ServerCommandType::GuildInfo as u8,
+            GuildRank::Member,
+            "",
+            "",
+        );
+        network_manager::xsend(gs, nr, &buf, buf.len());
+        return;
+    };
+
+    let rank = guild.rank_of(cn).unwrap_or_default();
+    let buf = encode_guild_info(
+        ServerCommandType::GuildInfo as u8,
+        rank,
+        &guild.name,
+        &guild.motd,
+    );
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    for member in &guild.members {
+        let buf = encode_guild_member(
+            ServerCommandType::GuildMember as u8,
+            GuildRosterOp::Set,
+            member.rank,
+            online_slot(gs, member.cn).is_some(),
+            gs.characters[member.cn].get_name(),
+        );
+        network_manager::xsend(gs, nr, &buf, buf.len());
+    }
+}
+
+/// Tells the other online members of `cn`'s guild that it logged in or out.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character that logged in or out.
+/// * `online` - Whether it is now online.
+pub fn guild_presence_changed(gs: &mut GameState, cn: usize, online: bool) {
+    let Some(guild) = gs.guilds.guild_of(cn).cloned() else {
+        return;
+    };
+    let buf = encode_guild_member(
+        ServerCommandType::GuildMember as u8,
+        GuildRosterOp::Set,
+        guild.rank_of(cn).unwrap_or_default(),
+        online,
+        gs.characters[cn].get_name(),
+    );
+    for member in guild.members.iter().filter(|m| m.cn != cn) {
+        let Some(nr) = online_slot(gs, member.cn) else {
+            continue;
+        };
+        if gs.players[nr].capabilities & CLIENT_CAP_GUILDS != 0 {
+            network_manager::xsend(gs, nr, &buf, buf.len());
+        }
+    }
+    if online && !guild.motd.is_empty() {
+        gs.do_character_styled_log(
+            cn,
+            ChatStyle::new(ChatChannel::Guild),
+            &format!("[{}] {}\n", guild.name, guild.motd),
+        );
+    }
+}
+
+impl GameState {
+    /// Handles `#guild <subcommand> [argument]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character issuing the command.
+    /// * `sub` - Subcommand; empty shows the guild.
+    /// * `rest` - Everything after the subcommand.
+    pub(crate) fn do_guild(&mut self, cn: usize, sub: &str, rest: &str) {
+        if self.characters[cn].flags & CharacterFlags::Player.bits() == 0 {
+            return;
+        }
+        let rest = rest.trim();
+        let now = self.globals.ticker;
+        let result = match sub.to_ascii_lowercase().as_str() {
+            "" | "info" => {
+                self.do_guild_info(cn);
+                return;
+            }
+            "help" => {
+                self.do_character_log(cn, FontColor::Yellow, GUILD_HELP);
+                return;
+            }
+            "create" => self.guilds.create(cn, rest).map(|id| {
+                log::info!("Character {} founded guild {} '{}'", cn, id, rest);
+                self.guild_changed(id);
+                self.guild_notify(id, &format!("The guild {} has been founded.\n", rest));
+            }),
+            "invite" => self.do_guild_invite(cn, rest, now),
+            "accept" => self.guilds.accept(cn, now).map(|id| {
+                self.guild_changed(id);
+                let name = self.characters[cn].get_name().to_owned();
+                self.guild_notify(id, &format!("{} joined the guild.\n", name));
+            }),
+            "leave" => self.do_guild_leave(cn),
+            "kick" | "promote" | "demote" | "leader" => self.do_guild_member_change(cn, sub, rest),
+            "motd" => self.guilds.set_motd(cn, rest).map(|id| {
+                self.guild_changed(id);
+                let motd = self
+                    .guilds
+                    .get(id)
+                    .map(|g| g.motd.clone())
+                    .unwrap_or_default();
+                self.guild_notify(id, &format!("Guild message: {}\n", motd));
+            }),
+            _ => Err("Unknown guild command. Try #guild help.".to_owned()),
+        };
+        if let Err(message) = result {
+            self.do_character_log(cn, FontColor::Red, &format!("{}\n", message));
+        }
+    }
+
+    /// Sends `text` to every member of `cn`'s guild (`#guildtell`).
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Speaking character.
+    /// * `text` - Message.
+    pub(crate) fn do_guildtell(&mut self, cn: usize, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            self.do_character_log(cn, FontColor::Red, "What do you want to tell your guild?\n");
+            return;
+        }
+        if self.characters[cn].flags & CharacterFlags::ShutUp.bits() != 0 {
+            self.do_character_log(
+                cn,
+                FontColor::Red,
+                "You try to guild-tell, but you only produce a croaking sound.\n",
+            );
+            return;
+        }
+        let Some(id) = self.guilds.guild_of(cn).map(|guild| guild.id) else {
+            self.do_character_log(cn, FontColor::Red, "You are not in a guild.\n");
+            return;
+        };
+        let name = self.characters[cn].get_name().to_owned();
+        self.guild_notify(id, &format!("{} guild-tells: \"{}\"\n", name, text));
+        log::info!("guild-tells \"{}\"", text);
+    }
+
+    fn do_guild_info(&mut self, cn: usize) {
+        let Some(guild) = self.guilds.guild_of(cn).cloned() else {
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                "You are not in a guild. #guild help lists the guild commands.\n",
+            );
+            return;
+        };
+        let style = ChatStyle::new(ChatChannel::Guild);
+        self.do_character_styled_log(
+            cn,
+            style,
+            &format!("{} ({} members)\n", guild.name, guild.members.len()),
+        );
+        if !guild.motd.is_empty() {
+            self.do_character_styled_log(cn, style, &format!("Message: {}\n", guild.motd));
+        }
+        for member in &guild.members {
+            let line = format!(
+                "  {} - {}{}\n",
+                self.characters[member.cn].get_name(),
+                member.rank.label(),
+                if online_slot(self, member.cn).is_some() {
+                    " (online)"
+                } else {
+                    ""
+                }
+            );
+            self.do_character_styled_log(cn, style, &line);
+        }
+    }
+
+    fn do_guild_invite(&mut self, cn: usize, name: &str, now: i32) -> Result<(), String> {
+        let co = usize::try_from(self.do_lookup_char(name)).unwrap_or(0);
+        if co == 0
+            || co == cn
+            || self.characters[co].flags & CharacterFlags::Player.bits() == 0
+            || online_slot(self, co).is_none()
+        {
+            return Err(format!("There is no player called {} online.", name));
+        }
+        let id = self.guilds.invite(cn, co, now)?;
+        let guild_name = self
+            .guilds
+            .get(id)
+            .map(|g| g.name.clone())
+            .unwrap_or_default();
+        let inviter = self.characters[cn].get_name().to_owned();
+        let invitee = self.characters[co].get_name().to_owned();
+        self.do_character_styled_log(
+            co,
+            ChatStyle::new(ChatChannel::Guild),
+            &format!(
+                "{} invites you to join the guild {}. Type #guild accept to join.\n",
+                inviter, guild_name
+            ),
+        );
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("You invited {} to the guild.\n", invitee),
+        );
+        Ok(())
+    }
+
+    fn do_guild_leave(&mut self, cn: usize) -> Result<(), String> {
+        let name = self.characters[cn].get_name().to_owned();
+        let departure = self.guilds.leave(cn)?;
+        if departure.disbanded {
+            log::info!("Guild {} disbanded", departure.guild);
+            store::save_disbanded(&self.storage, departure.guild);
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                "You were the last member. The guild is no more.\n",
+            );
+        } else {
+            self.guild_changed(departure.guild);
+            self.guild_notify(departure.guild, &format!("{} left the guild.\n", name));
+            if let Some(heir) = departure.new_leader {
+                let heir_name = self.characters[heir].get_name().to_owned();
+                self.guild_notify(
+                    departure.guild,
+                    &format!("{} now leads the guild.\n", heir_name),
+                );
+            }
+            self.do_character_log(cn, FontColor::Yellow, "You left the guild.\n");
+        }
+        self.guild_resync_character(cn);
+        Ok(())
+    }
+
+    fn do_guild_member_change(&mut self, cn: usize, sub: &str, name: &str) -> Result<(), String> {
+        let guild = self
+            .guilds
+            .guild_of(cn)
+            .cloned()
+            .ok_or_else(|| "You are not in a guild.".to_owned())?;
+        let co = member_by_name(self, &guild, name)
+            .ok_or_else(|| format!("There is no {} in your guild.", name))?;
+        let co_name = self.characters[co].get_name().to_owned();
+        let (id, message) = match sub.to_ascii_lowercase().as_str() {
+            "kick" => {
+                let id = self.guilds.kick(cn, co)?;
+                self.guild_resync_character(co);
+                self.do_character_log(
+                    co,
+                    FontColor::Red,
+                    &format!("You have been removed from the guild {}.\n", guild.name),
+                );
+                (id, format!("{} was removed from the guild.\n", co_name))
+            }
+            "promote" => (
+                self.guilds.set_rank(cn, co, GuildRank::Officer)?,
+                format!("{} is now an officer.\n", co_name),
+            ),
+            "demote" => (
+                self.guilds.set_rank(cn, co, GuildRank::Member)?,
+                format!("{} is no longer an officer.\n", co_name),
+            ),
+            _ => (
+                self.guilds.set_rank(cn, co, GuildRank::Leader)?,
+                format!("{} now leads the guild.\n", co_name),
+            ),
+        };
+        self.guild_changed(id);
+        self.guild_notify(id, &message);
+        Ok(())
+    }
+
+    /// Sends `message` on the guild channel to every online member.
+    fn guild_notify(&mut self, id: u32, message: &str) {
+        let Some(members) = self
+            .guilds
+            .get(id)
+            .map(|guild| guild.members.iter().map(|m| m.cn).collect::<Vec<_>>())
+        else {
+            return;
+        };
+        for co in members {
+            if online_slot(self, co).is_some() {
+                self.do_character_styled_log(co, ChatStyle::new(ChatChannel::Guild), message);
+            }
+        }
+    }
+
+    /// Saves guild `id` and resends it to its online members.
+    fn guild_changed(&mut self, id: u32) {
+        let Some(guild) = self.guilds.get(id).cloned() else {
+            return;
+        };
+        store::save_guild(&self.storage, &guild);
+        for member in &guild.members {
+            self.guild_resync_character(member.cn);
+        }
+    }
+
+    /// Resends the guild state to `cn`'s client, if it is online.
+    fn guild_resync_character(&mut self, cn: usize) {
+        if let Some(nr) = online_slot(self, cn) {
+            plr_send_guild(self, nr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn founding_a_guild_sends_it_to_the_client() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            gs.players[nr].capabilities |= CLIENT_CAP_GUILDS;
+
+            gs.do_guild(cn, "create", "Iron Wolves");
+            let guild = gs.guilds.guild_of(cn).expect("guild founded");
+            assert_eq!(guild.rank_of(cn), Some(GuildRank::Leader));
+
+            gs.do_guild(cn, "motd", "Raid at dusk");
+            assert_eq!(gs.guilds.guild_of(cn).unwrap().motd, "Raid at dusk");
+
+            gs.do_guild(cn, "leave", "");
+            assert!(gs.guilds.guild_of(cn).is_none());
+        });
+    }
+}
@@ -0,0 +1,431 @@
+//! Player guilds: membership, ranks and a guild message of the day.
+//!
+//! A guild is founded with `#guild create <name>`; its leader and officers
+//! invite players, who join with `#guild accept`. Members talk on
+//! [`ChatChannel::Guild`](core::chat::ChatChannel::Guild) with `#guildtell`.
+//! Members are kept by character slot, which stays fixed for a character's
+//! lifetime.
+//!
+//! [`GuildRegistry`] holds the rules and knows nothing about the world, so it
+//! can be tested on its own. [`commands`] turns text commands into registry
+//! calls, broadcasts the outcome and keeps the clients' guild panels in sync.
+//! [`store`] persists every guild to a KeyDB hash.
+
+pub mod commands;
+pub mod store;
+
+use std::collections::{BTreeMap, HashMap};
+
+use core::guilds::{GuildRank, MAX_GUILD_MEMBERS, MAX_GUILD_MOTD_LEN, validate_guild_name};
+
+/// How long an invitation stays valid, in ticks (two minutes).
+pub const INVITE_TICKS: i32 = 120 * core::constants::TICKS;
+
+/// One guild member.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuildMember {
+    /// Character slot of the member.
+    pub cn: usize,
+    pub rank: GuildRank,
+}
+
+/// A guild and its roster.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Guild {
+    pub id: u32,
+    pub name: String,
+    pub motd: String,
+    /// Members in joining order; the founder comes first.
+    pub members: Vec<GuildMember>,
+}
+
+impl Guild {
+    /// Rank of `cn` in this guild, if it is a member.
+    pub fn rank_of(&self, cn: usize) -> Option<GuildRank> {
+        self.members.iter().find(|m| m.cn == cn).map(|m| m.rank)
+    }
+}
+
+/// A pending invitation.
+#[derive(Clone, Copy, Debug)]
+struct Invite {
+    guild: u32,
+    expires: i32,
+}
+
+/// What happened when a member left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Departure {
+    pub guild: u32,
+    /// Member promoted because the leader left.
+    pub new_leader: Option<usize>,
+    /// The last member left and the guild is gone.
+    pub disbanded: bool,
+}
+
+/// Every guild in the world plus pending invitations.
+#[derive(Debug)]
+pub struct GuildRegistry {
+    guilds: BTreeMap<u32, Guild>,
+    /// Invitations by invited character slot.
+    invites: HashMap<usize, Invite>,
+    next_id: u32,
+}
+
+impl Default for GuildRegistry {
+    fn default() -> Self {
+        Self {
+            guilds: BTreeMap::new(),
+            invites: HashMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl GuildRegistry {
+    /// Builds a registry from stored guilds.
+    ///
+    /// # Arguments
+    ///
+    /// * `guilds` - Guilds as loaded by [`store::load_guilds`].
+    /// * `next_id` - Lowest id never handed out, so disbanded guilds' ids are
+    ///   not reused.
+    pub fn from_guilds(guilds: Vec<Guild>, next_id: u32) -> Self {
+        let next_id = guilds
+            .iter()
+            .map(|guild| guild.id + 1)
+            .max()
+            .unwrap_or(1)
+            .max(next_id);
+        Self {
+            guilds: guilds.into_iter().map(|guild| (guild.id, guild)).collect(),
+            invites: HashMap::new(),
+            next_id,
+        }
+    }
+
+    /// Guild with the given id.
+    pub fn get(&self, id: u32) -> Option<&Guild> {
+        self.guilds.get(&id)
+    }
+
+    /// Guild that `cn` belongs to.
+    pub fn guild_of(&self, cn: usize) -> Option<&Guild> {
+        self.guilds
+            .values()
+            .find(|guild| guild.members.iter().any(|m| m.cn == cn))
+    }
+
+    fn membership(&self, cn: usize) -> Result<(u32, GuildRank), String> {
+        self.guild_of(cn)
+            .and_then(|guild| Some((guild.id, guild.rank_of(cn)?)))
+            .ok_or_else(|| "You are not in a guild.".to_owned())
+    }
+
+    /// Founds a guild with `founder` as its leader.
+    ///
+    /// # Arguments
+    ///
+    /// * `founder` - Character slot of the founder.
+    /// * `name` - Guild name.
+    ///
+    /// # Returns
+    ///
+    /// * The new guild's id, or a message for the player.
+    pub fn create(&mut self, founder: usize, name: &str) -> Result<u32, String> {
+        validate_guild_name(name)?;
+        if self.guild_of(founder).is_some() {
+            return Err("You are already in a guild.".to_owned());
+        }
+        if self
+            .guilds
+            .values()
+            .any(|guild| guild.name.eq_ignore_ascii_case(name))
+        {
+            return Err(format!("There already is a guild called {}.", name));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.guilds.insert(
+            id,
+            Guild {
+                id,
+                name: name.to_owned(),
+                motd: String::new(),
+                members: vec![GuildMember {
+                    cn: founder,
+                    rank: GuildRank::Leader,
+                }],
+            },
+        );
+        self.invites.remove(&founder);
+        Ok(id)
+    }
+
+    /// Invites `target` into the guild of `by`.
+    ///
+    /// # Arguments
+    ///
+    /// * `by` - Inviting member; must be an officer or the leader.
+    /// * `target` - Invited character slot.
+    /// * `now` - Current tick.
+    ///
+    /// # Returns
+    ///
+    /// * The guild id, or a message for the inviter.
+    pub fn invite(&mut self, by: usize, target: usize, now: i32) -> Result<u32, String> {
+        let (id, rank) = self.membership(by)?;
+        if !rank.can_manage() {
+            return Err("Only officers and the leader may invite.".to_owned());
+        }
+        if self.guild_of(target).is_some() {
+            return Err("They are already in a guild.".to_owned());
+        }
+        if self.guilds[&id].members.len() >= MAX_GUILD_MEMBERS {
+            return Err("Your guild is full.".to_owned());
+        }
+        self.invites.insert(
+            target,
+            Invite {
+                guild: id,
+                expires: now.saturating_add(INVITE_TICKS),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Accepts the pending invitation of `cn`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Invited character slot.
+    /// * `now` - Current tick.
+    ///
+    /// # Returns
+    ///
+    /// * The joined guild's id, or a message for the player.
+    pub fn accept(&mut self, cn: usize, now: i32) -> Result<u32, String> {
+        let invite = self
+            .invites
+            .remove(&cn)
+            .filter(|invite| invite.expires > now)
+            .ok_or_else(|| "You have no pending guild invitation.".to_owned())?;
+        if self.guild_of(cn).is_some() {
+            return Err("You are already in a guild.".to_owned());
+        }
+        let guild = self
+            .guilds
+            .get_mut(&invite.guild)
+            .ok_or_else(|| "That guild no longer exists.".to_owned())?;
+        if guild.members.len() >= MAX_GUILD_MEMBERS {
+            return Err("That guild is full.".to_owned());
+        }
+        guild.members.push(GuildMember {
+            cn,
+            rank: GuildRank::Member,
+        });
+        Ok(invite.guild)
+    }
+
+    /// Removes `cn` from its guild.
+    ///
+    /// A leaving leader hands over to the highest ranked, longest serving
+    /// member; the last member leaving disbands the guild.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Leaving member.
+    ///
+    /// # Returns
+    ///
+    /// * What happened to the guild, or a message for the player.
+    pub fn leave(&mut self, cn: usize) -> Result<Departure, String> {
+        let (id, rank) = self.membership(cn)?;
+        let guild = self
+            .guilds
+            .get_mut(&id)
+            .expect("membership found the guild");
+        guild.members.retain(|m| m.cn != cn);
+
+        if guild.members.is_empty() {
+            self.guilds.remove(&id);
+            self.invites.retain(|_, invite| invite.guild != id);
+            return Ok(Departure {
+                guild: id,
+                new_leader: None,
+                disbanded: true,
+            });
+        }
+
+        let mut new_leader = None;
+        if rank == GuildRank::Leader {
+            // `max_by_key` keeps the last of equal keys; reverse so the
+            // longest serving member wins ties.
+            if let Some(heir) = guild.members.iter_mut().rev().max_by_key(|m| m.rank) {
+                heir.rank = GuildRank::Leader;
+                new_leader = Some(heir.cn);
+            }
+        }
+        Ok(Departure {
+            guild: id,
+            new_leader,
+            disbanded: false,
+        })
+    }
+
+    /// Removes `target` from the guild of `by`.
+    ///
+    /// # Arguments
+    ///
+    /// * `by` - Member doing the removal; must be an officer or the leader
+    ///   and outrank `target`.
+    /// * `target` - Member to remove.
+    ///
+    /// # Returns
+    ///
+    /// * The guild id, or a message for `by`.
+    pub fn kick(&mut self, by: usize, target: usize) -> Result<u32, String> {
+        let (id, rank) = self.membership(by)?;
+        let guild = self
+            .guilds
+            .get_mut(&id)
+            .expect("membership found the guild");
+        let target_rank = guild
+            .rank_of(target)
+            .ok_or_else(|| "They are not in your guild.".to_owned())?;
+        if !rank.can_manage() || target_rank >= rank {
+            return Err("You may only remove members ranked below you.".to_owned());
+        }
+        guild.members.retain(|m| m.cn != target);
+        Ok(id)
+    }
+
+    /// Changes the rank of `target` in the guild of `by`.
+    ///
+    /// Only the leader may do this. Making someone leader hands the
+    /// leadership over and turns `by` into an officer.
+    ///
+    /// # Arguments
+    ///
+    /// * `by` - The guild leader.
+    /// * `target` - Member whose rank changes.
+    /// * `rank` - New rank.
+    ///
+    /// # Returns
+    ///
+    /// * The guild id, or a message for `by`.
+    pub fn set_rank(&mut self, by: usize, target: usize, rank: GuildRank) -> Result<u32, String> {
+        let (id, by_rank) = self.membership(by)?;
+        if by_rank != GuildRank::Leader {
+            return Err("Only the guild leader may change ranks.".to_owned());
+        }
+        if by == target {
+            return Err("You cannot change your own rank.".to_owned());
+        }
+        let guild = self
+            .guilds
+            .get_mut(&id)
+            .expect("membership found the guild");
+        let current = guild
+            .rank_of(target)
+            .ok_or_else(|| "They are not in your guild.".to_owned())?;
+        if current == rank {
+            return Err(format!("They already are a {}.", rank.label()));
+        }
+        for member in &mut guild.members {
+            if member.cn == target {
+                member.rank = rank;
+            } else if member.cn == by && rank == GuildRank::Leader {
+                member.rank = GuildRank::Officer;
+            }
+        }
+        Ok(id)
+    }
+
+    /// Sets the message of the day of the guild of `by`.
+    ///
+    /// # Arguments
+    ///
+    /// * `by` - An officer or the leader.
+    /// * `motd` - New message, cut to [`MAX_GUILD_MOTD_LEN`] bytes; empty
+    ///   clears it.
+    ///
+    /// # Returns
+    ///
+    /// * The guild id, or a message for `by`.
+    pub fn set_motd(&mut self, by: usize, motd: &str) -> Result<u32, String> {
+        let (id, rank) = self.membership(by)?;
+        if !rank.can_manage() {
+            return Err("Only officers and the leader may set the message.".to_owned());
+        }
+        let mut end = motd.len().min(MAX_GUILD_MOTD_LEN);
+        while !motd.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.guilds
+            .get_mut(&id)
+            .expect("membership found the guild")
+            .motd = motd[..end].trim().to_owned();
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wolves() -> (GuildRegistry, u32) {
+        let mut registry = GuildRegistry::default();
+        let id = registry.create(10, "Iron Wolves").unwrap();
+        registry.invite(10, 11, 0).unwrap();
+        registry.accept(11, 5).unwrap();
+        registry.invite(10, 12, 0).unwrap();
+        registry.accept(12, 5).unwrap();
+        (registry, id)
+    }
+
+    #[test]
+    fn invitations_expire_and_need_an_officer() {
+        let (mut registry, id) = wolves();
+        assert_eq!(registry.guild_of(11).unwrap().id, id);
+        assert!(registry.invite(11, 13, 0).is_err());
+        assert!(registry.create(20, "iron wolves").is_err());
+
+        registry.invite(10, 13, 0).unwrap();
+        assert!(registry.accept(13, INVITE_TICKS).is_err());
+        assert!(registry.guild_of(13).is_none());
+    }
+
+    #[test]
+    fn ranks_gate_kicks_and_leadership_passes_on() {
+        let (mut registry, id) = wolves();
+        registry.set_rank(10, 12, GuildRank::Officer).unwrap();
+        assert!(registry.kick(11, 12).is_err());
+        assert!(registry.kick(12, 10).is_err());
+        registry.kick(12, 11).unwrap();
+        assert!(registry.guild_of(11).is_none());
+
+        let departure = registry.leave(10).unwrap();
+        assert_eq!(departure.new_leader, Some(12));
+        assert_eq!(
+            registry.get(id).unwrap().rank_of(12),
+            Some(GuildRank::Leader)
+        );
+
+        let departure = registry.leave(12).unwrap();
+        assert!(departure.disbanded);
+        assert!(registry.get(id).is_none());
+    }
+
+    #[test]
+    fn handing_over_leadership_demotes_the_old_leader() {
+        let (mut registry, id) = wolves();
+        registry.set_rank(10, 11, GuildRank::Leader).unwrap();
+        let guild = registry.get(id).unwrap();
+        assert_eq!(guild.rank_of(11), Some(GuildRank::Leader));
+        assert_eq!(guild.rank_of(10), Some(GuildRank::Officer));
+        assert!(registry.set_motd(12, "hello").is_err());
+        registry.set_motd(10, "Raid at dusk").unwrap();
+        assert_eq!(registry.get(id).unwrap().motd, "Raid at dusk");
+    }
+}
@@ -0,0 +1,180 @@
+//! Persistence for guilds.
+//!
+//! Every guild is the record `game:guild:{id}` with the fields `name`,
+//! `motd` and `members`, stored through [`StorageBackend::save_record`].
+//! Members are encoded as `cn:rank` pairs separated by `;`. A disbanded
+//! guild is written back with an empty name and skipped on load; its record
+//! keeps the id from being handed out again.
+
+use core::guilds::GuildRank;
+
+use server::storage::StorageBackend;
+
+use super::{Guild, GuildMember, GuildRegistry};
+
+/// Prefix of the per-guild record keys.
+pub const GUILD_KEY_PREFIX: &str = "game:guild:";
+
+/// Encodes a roster for the `members` field.
+pub fn encode_members(members: &[GuildMember]) -> String {
+    members
+        .iter()
+        .map(|m| format!("{}:{}", m.cn, m.rank.as_u8()))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Decodes the `members` field.
+///
+/// # Arguments
+///
+/// * `text` - Stored roster.
+///
+/// # Returns
+///
+/// * The members, or an error naming the first malformed entry.
+pub fn decode_members(text: &str) -> Result<Vec<GuildMember>, String> {
+    text.split(';')
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (cn, rank) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("malformed guild member '{}'", entry))?;
+            let cn = cn
+                .parse::<usize>()
+                .map_err(|_| format!("malformed guild member '{}'", entry))?;
+            let rank = rank
+                .parse::<u8>()
+                .ok()
+                .and_then(GuildRank::from_u8)
+                .ok_or_else(|| format!("unknown guild rank in '{}'", entry))?;
+            Ok(GuildMember { cn, rank })
+        })
+        .collect()
+}
+
+/// Saves `guild`.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `guild` - Guild to save.
+pub fn save_guild(storage: &StorageBackend, guild: &Guild) {
+    storage.save_record(
+        format!("{}{}", GUILD_KEY_PREFIX, guild.id),
+        vec![
+            ("name", guild.name.clone()),
+            ("motd", guild.motd.clone()),
+            ("members", encode_members(&guild.members)),
+        ],
+    );
+}
+
+/// Blanks a disbanded guild.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `id` - Id of the disbanded guild.
+pub fn save_disbanded(storage: &StorageBackend, id: u32) {
+    storage.save_record(
+        format!("{}{}", GUILD_KEY_PREFIX, id),
+        vec![
+            ("name", String::new()),
+            ("motd", String::new()),
+            ("members", String::new()),
+        ],
+    );
+}
+
+/// Loads every guild from the storage backend.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+///
+/// # Returns
+///
+/// * The registry. A guild record that cannot be parsed is logged and
+///   skipped; an error means the backend could not be read at all.
+pub fn load_guilds(storage: &StorageBackend) -> Result<GuildRegistry, String> {
+    let mut guilds = Vec::new();
+    let mut next_id = 1;
+    for (key, fields) in storage.load_records(GUILD_KEY_PREFIX)? {
+        let Some(id) = key
+            .strip_prefix(GUILD_KEY_PREFIX)
+            .and_then(|id| id.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        next_id = next_id.max(id + 1);
+        match guild_from_fields(id, &fields) {
+            Ok(Some(guild)) => guilds.push(guild),
+            Ok(None) => {}
+            Err(e) => log::error!("Skipping guild {}: {}", id, e),
+        }
+    }
+
+    log::info!("Loaded {} guild(s)", guilds.len());
+    Ok(GuildRegistry::from_guilds(guilds, next_id))
+}
+
+/// Builds a guild from its stored fields.
+///
+/// # Returns
+///
+/// * `Ok(None)` for a disbanded guild.
+fn guild_from_fields(
+    id: u32,
+    fields: &std::collections::HashMap<String, String>,
+) -> Result<Option<Guild>, String> {
+    let name = fields.get("name").cloned().unwrap_or_default();
+    let members = decode_members(fields.get("members").map_or("", String::as_str))?;
+    if name.is_empty() || members.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Guild {
+        id,
+        name,
+        motd: fields.get("motd").cloned().unwrap_or_default(),
+        members,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn members_round_trip() {
+        let members = vec![
+            GuildMember {
+                cn: 12,
+                rank: GuildRank::Leader,
+            },
+            GuildMember {
+                cn: 340,
+                rank: GuildRank::Member,
+            },
+        ];
+        let text = encode_members(&members);
+        assert_eq!(text, "12:2;340:0");
+        assert_eq!(decode_members(&text).unwrap(), members);
+        assert!(decode_members("12:7").is_err());
+        assert!(decode_members("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn disbanded_hash_loads_as_nothing() {
+        let mut fields = std::collections::HashMap::new();
+        fields.insert("name".to_owned(), String::new());
+        fields.insert("members".to_owned(), String::new());
+        assert_eq!(guild_from_fields(3, &fields), Ok(None));
+
+        fields.insert("name".to_owned(), "Iron Wolves".to_owned());
+        fields.insert("members".to_owned(), "12:2".to_owned());
+        let guild = guild_from_fields(3, &fields).unwrap().unwrap();
+        assert_eq!(guild.members.len(), 1);
+        assert_eq!(guild.motd, "");
+    }
+}
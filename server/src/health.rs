@@ -0,0 +1,107 @@
+//! Plain-HTTP liveness and readiness probes for container orchestrators.
+//!
+//! The game port speaks TLS and the custom protocol, so neither Docker nor
+//! Kubernetes can probe it meaningfully. Setting `MAG_HEALTH_PORT` starts a
+//! tiny HTTP listener on that port:
+//!
+//! * `GET /healthz` - `200` while the process is running.
+//! * `GET /readyz` - `200` once the world is loaded and the game port is
+//!   accepting connections, `503` before that and during shutdown.
+//!
+//! The listener runs on its own thread and never touches game state; the
+//! main loop only flips the shared readiness flag.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+const HEALTH_PORT_ENV: &str = "MAG_HEALTH_PORT";
+
+/// Start the probe listener when `MAG_HEALTH_PORT` is set.
+///
+/// # Returns
+///
+/// * `Ok(Some(flag))` - listener started; store `true` in `flag` once ready.
+/// * `Ok(None)` - `MAG_HEALTH_PORT` is unset or empty.
+/// * `Err` when the port is invalid or cannot be bound.
+pub(crate) fn spawn_from_env() -> Result<Option<Arc<AtomicBool>>, String> {
+    let port = match std::env::var(HEALTH_PORT_ENV) {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid {HEALTH_PORT_ENV} '{value}'"))?,
+        _ => return Ok(None),
+    };
+
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|err| format!("Failed to bind health port {port}: {err}"))?;
+    let ready = Arc::new(AtomicBool::new(false));
+    let flag = ready.clone();
+    thread::Builder::new()
+        .name("health".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(err) = handle(stream, &flag) {
+                    log::debug!("Health probe connection failed: {err}");
+                }
+            }
+        })
+        .map_err(|err| format!("Failed to spawn health thread: {err}"))?;
+
+    log::info!("Health probes listening on port {port} (/healthz, /readyz)");
+    Ok(Some(ready))
+}
+
+fn handle(mut stream: TcpStream, ready: &AtomicBool) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let (status, body) = response_for(&request_line, ready.load(Ordering::Relaxed));
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Map an HTTP request line to a status line and body.
+///
+/// # Arguments
+///
+/// * `request_line` - First line of the request, e.g. `GET /readyz HTTP/1.1`.
+/// * `ready` - Current readiness flag.
+///
+/// # Returns
+///
+/// * `(status, body)` for the response.
+fn response_for(request_line: &str, ready: bool) -> (&'static str, &'static str) {
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/healthz")) => ("200 OK", "ok"),
+        (Some("GET"), Some("/readyz")) if ready => ("200 OK", "ready"),
+        (Some("GET"), Some("/readyz")) => ("503 Service Unavailable", "not ready"),
+        _ => ("404 Not Found", "not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_for_reports_readiness() {
+        assert_eq!(response_for("GET /healthz HTTP/1.1\r\n", false).0, "200 OK");
+        assert_eq!(
+            response_for("GET /readyz HTTP/1.1\r\n", false).0,
+            "503 Service Unavailable"
+        );
+        assert_eq!(response_for("GET /readyz HTTP/1.1\r\n", true).0, "200 OK");
+        assert_eq!(
+            response_for("POST /readyz HTTP/1.1\r\n", true).0,
+            "404 Not Found"
+        );
+    }
+}
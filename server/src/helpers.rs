@@ -46,7 +46,8 @@ pub fn format_number(value: i32) -> String {
 /// Mimics `random() % a` from the original codebase. This intentionally has
 /// modulo-style distribution (including modulo bias) similar to the C macro.
 ///
-/// Returns `0` when `a == 0`.
+/// Returns `0` when `a == 0`. Draws from the seeded generator in
+/// deterministic mode (see [`crate::deterministic`]).
 ///
 /// # Arguments
 ///
@@ -60,7 +61,7 @@ pub fn random_mod(a: u32) -> u32 {
     if a == 0 {
         return 0;
     }
-    rand::random::<u32>() % a
+    crate::deterministic::with_rng(|rng| rng.next_u32()) % a
 }
 
 /// Signed convenience wrapper around [`random_mod`].
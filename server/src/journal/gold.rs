@@ -0,0 +1,191 @@
+//! Gold flow summaries over a journal.
+//!
+//! Used by `world-snapshot trace-gold` to follow where a character's gold
+//! came from and went to over a time range, e.g. to find everyone who
+//! received the proceeds of a duplication exploit before clawing them back.
+
+use std::collections::BTreeMap;
+
+use super::{GoldTransferKind, JournalRecord};
+
+/// One transfer involving the traced character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TracedTransfer {
+    /// Tick the transfer was recorded in.
+    pub tick: i32,
+    /// Wall-clock time of that tick.
+    pub unix_secs: i64,
+    /// Paying character slot; `0` is the world.
+    pub from: u32,
+    /// Receiving character slot; `0` is the world.
+    pub to: u32,
+    /// Amount in silver.
+    pub amount: i32,
+    /// How the gold moved.
+    pub kind: GoldTransferKind,
+}
+
+/// Gold exchanged between the traced character and one counterparty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoldFlow {
+    /// Counterparty character slot; `0` is the world.
+    pub counterparty: u32,
+    /// Silver the traced character received from the counterparty.
+    pub received: i64,
+    /// Silver the traced character paid to the counterparty.
+    pub sent: i64,
+    /// Number of transfers in either direction.
+    pub transfers: usize,
+}
+
+/// Result of [`trace_gold`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoldTrace {
+    /// Matching transfers in journal order.
+    pub transfers: Vec<TracedTransfer>,
+    /// One entry per counterparty, largest inflow first.
+    pub flows: Vec<GoldFlow>,
+}
+
+/// Collects the gold transfers of `character` within a time range.
+///
+/// # Arguments
+///
+/// * `records` - Journal records in file order.
+/// * `character` - Character slot to trace.
+/// * `since` - First unix second to include; `None` starts at the beginning.
+/// * `until` - Last unix second to include; `None` runs to the end.
+///
+/// # Returns
+///
+/// * The transfers and a per-counterparty summary.
+pub fn trace_gold(
+    records: &[JournalRecord],
+    character: u32,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> GoldTrace {
+    let mut trace = GoldTrace::default();
+    let mut flows: BTreeMap<u32, GoldFlow> = BTreeMap::new();
+    let (mut tick, mut unix_secs) = (0, 0);
+    for record in records {
+        match record {
+            JournalRecord::Tick {
+                tick: t,
+                unix_secs: s,
+            } => {
+                if until.is_some_and(|limit| *s > limit) {
+                    break;
+                }
+                tick = *t;
+                unix_secs = *s;
+            }
+            JournalRecord::GoldTransfer {
+                from,
+                to,
+                amount,
+                kind,
+            } => {
+                if since.is_some_and(|limit| unix_secs < limit)
+                    || (*from != character && *to != character)
+                {
+                    continue;
+                }
+                let counterparty = if *from == character { *to } else { *from };
+                let flow = flows.entry(counterparty).or_insert_with(|| GoldFlow {
+                    counterparty,
+                    ..Default::default()
+                });
+                if *to == character {
+                    flow.received += i64::from(*amount);
+                }
+                if *from == character {
+                    flow.sent += i64::from(*amount);
+                }
+                flow.transfers += 1;
+                trace.transfers.push(TracedTransfer {
+                    tick,
+                    unix_secs,
+                    from: *from,
+                    to: *to,
+                    amount: *amount,
+                    kind: *kind,
+                });
+            }
+            _ => {}
+        }
+    }
+    trace.flows = flows.into_values().collect();
+    trace
+        .flows
+        .sort_by(|a, b| b.received.cmp(&a.received).then(b.sent.cmp(&a.sent)));
+    trace
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(from: u32, to: u32, amount: i32, kind: GoldTransferKind) -> JournalRecord {
+        JournalRecord::GoldTransfer {
+            from,
+            to,
+            amount,
+            kind,
+        }
+    }
+
+    #[test]
+    fn sums_flows_per_counterparty_within_range() {
+        let records = vec![
+            JournalRecord::Tick {
+                tick: 1,
+                unix_secs: 100,
+            },
+            transfer(7, 3, 500, GoldTransferKind::Give),
+            JournalRecord::Tick {
+                tick: 2,
+                unix_secs: 200,
+            },
+            transfer(7, 3, 1_000, GoldTransferKind::Give),
+            transfer(3, 9, 250, GoldTransferKind::Give),
+            transfer(0, 3, 40, GoldTransferKind::Pickup),
+            transfer(4, 5, 1, GoldTransferKind::Give),
+            JournalRecord::Tick {
+                tick: 3,
+                unix_secs: 300,
+            },
+            transfer(7, 3, 9_999, GoldTransferKind::Give),
+        ];
+
+        let trace = trace_gold(&records, 3, Some(150), Some(250));
+        assert_eq!(trace.transfers.len(), 3);
+        assert_eq!(trace.transfers[0].tick, 2);
+        assert_eq!(
+            trace.flows,
+            vec![
+                GoldFlow {
+                    counterparty: 7,
+                    received: 1_000,
+                    sent: 0,
+                    transfers: 1,
+                },
+                GoldFlow {
+                    counterparty: 0,
+                    received: 40,
+                    sent: 0,
+                    transfers: 1,
+                },
+                GoldFlow {
+                    counterparty: 9,
+                    received: 0,
+                    sent: 250,
+                    transfers: 1,
+                },
+            ]
+        );
+
+        let all = trace_gold(&records, 3, None, None);
+        assert_eq!(all.flows[0].received, 11_499);
+    }
+}
@@ -0,0 +1,339 @@
+//! Append-only per-tick journal of world mutations.
+//!
+//! When `MAG_JOURNAL_PATH` is set the server records, after every game tick,
+//! each character whose position, gold or inventory changed and each item
+//! whose lifecycle fields (`used`, `temp`, `carried`, `x`, `y`) changed,
+//! together with the cause when it is known (e.g. a template reset). The
+//! records carry absolute values, so a journal can be re-applied onto a
+//! `.wsnap` snapshot with `world-snapshot replay` to reproduce the world at
+//! any tick, or filtered with `--trace-item` to follow a single item.
+//! `world-snapshot export-replay` turns a journal into a recording for the
+//! client's replay viewer.
+//!
+//! Gold that changes hands is also recorded explicitly as
+//! [`JournalRecord::GoldTransfer`], since a diff of purses cannot tell who
+//! paid whom. `world-snapshot trace-gold` summarises those records.
+//!
+//! * [`gold`] — gold flow summaries over a journal.
+//! * [`recorder`] — shadow-diffing recorder driven by the tick loop.
+//! * [`replay`] — applies a journal onto a [`WorldSnapshot`](crate::keydb::snapshot::WorldSnapshot).
+//!
+//! # File format
+//!
+//! The file starts with [`JOURNAL_MAGIC`] and a little-endian
+//! [`JOURNAL_VERSION`]. Each record follows as a little-endian `u32` length
+//! and a bincode-encoded [`JournalRecord`]. A truncated trailing record (e.g.
+//! after a crash) is ignored on read.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+use bincode::{Decode, Encode};
+
+/// Gold flow summaries over a journal.
+pub mod gold;
+
+/// Shadow-diffing recorder driven by the tick loop.
+pub mod recorder;
+
+/// Re-applies a journal onto a world snapshot.
+pub mod replay;
+
+/// Magic bytes at the start of every journal file.
+pub const JOURNAL_MAGIC: [u8; 4] = *b"MGJL";
+
+/// Journal format version written after [`JOURNAL_MAGIC`].
+pub const JOURNAL_VERSION: u32 = 1;
+
+/// Upper bound for a single encoded record; larger lengths mean corruption.
+const MAX_RECORD_BYTES: u32 = 1 << 20;
+
+/// Why an item record was written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum ItemCause {
+    /// Detected by diffing; the mutating code path is unknown.
+    Changed,
+    /// Rewritten from its template by `reset_item`.
+    TemplateReset,
+}
+
+/// How gold changed hands in a [`JournalRecord::GoldTransfer`].
+///
+/// Variants are bincode-encoded by position — append only.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Encode, Decode)]
+pub enum GoldTransferKind {
+    /// Cursor gold handed to another character.
+    Give,
+    /// Cursor gold dropped on the ground.
+    Drop,
+    /// A money item picked up from the ground.
+    Pickup,
+    /// Paid to a merchant for an item.
+    ShopBuy,
+    /// Paid by a merchant for an item.
+    ShopSell,
+    /// Taken from a corpse or grave.
+    CorpseLoot,
+    /// Created by a god command.
+    GmGrant,
+    /// Removed by a god claw-back.
+    Clawback,
+    /// Kept by a broker for listing an item.
+    ConsignmentFee,
+    /// Paid to a broker for a listed item.
+    ConsignmentBuy,
+    /// Paid out to a seller's bank account for a sold listing.
+    ConsignmentSale,
+    /// Paid to a trainer for learning a skill.
+    TrainerFee,
+    /// Offered at a shrine for a blessing.
+    ShrineOffering,
+    /// Part of a new character's starter kit.
+    StarterKit,
+    /// Found in a treasure chest.
+    ChestLoot,
+    /// Paid by an NPC for finished quest work.
+    QuestReward,
+    /// Created by using a gold-granting item.
+    ItemUse,
+    /// Money handed back by an NPC that does not take it.
+    NpcRefund,
+    /// Carried gold put into the character's bank account.
+    BankDeposit,
+    /// Bank money taken out as carried gold.
+    BankWithdraw,
+    /// Carried gold left on the grave when a character dies.
+    DeathLoss,
+    /// Stolen from a character's purse by a demon.
+    DemonTheft,
+}
+
+/// One journal entry.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum JournalRecord {
+    /// Start of the records produced by game tick `tick`.
+    Tick {
+        /// `globals.ticker` after the tick ran.
+        tick: i32,
+        /// Wall-clock time the tick was recorded.
+        unix_secs: i64,
+    },
+    /// Full item slot contents after a change.
+    Item {
+        /// Item slot index.
+        index: u32,
+        /// What caused the change, when known.
+        cause: ItemCause,
+        /// The item slot after the tick.
+        item: Box<core::types::Item>,
+    },
+    /// Position, gold and inventory of a character after a change.
+    Character {
+        /// Character slot index.
+        index: u32,
+        /// `USE_*` state of the slot.
+        used: u8,
+        /// Map position.
+        x: i16,
+        /// Map position.
+        y: i16,
+        /// Carried gold.
+        gold: i32,
+        /// Item on the cursor.
+        citem: u32,
+        /// Backpack item slots.
+        item: [u32; 40],
+        /// Equipment item slots.
+        worn: [u32; 20],
+    },
+    /// Gold that moved between two characters, or between a character and
+    /// the world.
+    GoldTransfer {
+        /// Paying character slot; `0` is the world (ground, god grants).
+        from: u32,
+        /// Receiving character slot; `0` is the world.
+        to: u32,
+        /// Amount in silver.
+        amount: i32,
+        /// How the gold moved.
+        kind: GoldTransferKind,
+    },
+}
+
+impl JournalRecord {
+    /// Returns `true` if this record describes item slot `index`, either
+    /// directly or as part of a character's inventory.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Item slot index.
+    pub fn mentions_item(&self, index: u32) -> bool {
+        match self {
+            JournalRecord::Tick { .. } | JournalRecord::GoldTransfer { .. } => false,
+            JournalRecord::Item { index: i, .. } => *i == index,
+            JournalRecord::Character {
+                citem, item, worn, ..
+            } => *citem == index || item.contains(&index) || worn.contains(&index),
+        }
+    }
+}
+
+/// Append-only journal file writer.
+pub struct JournalWriter {
+    out: BufWriter<File>,
+}
+
+impl JournalWriter {
+    /// Create `path` (or append to it if it already holds a journal).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Journal file path.
+    ///
+    /// # Returns
+    ///
+    /// * The writer, or an error if the file cannot be opened or belongs to
+    ///   another format.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let existing = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if existing > 0 {
+            let mut header = [0u8; 8];
+            File::open(path)
+                .and_then(|mut f| f.read_exact(&mut header))
+                .map_err(|e| format!("Journal read {}: {e}", path.display()))?;
+            check_header(&header, path)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Journal open {}: {e}", path.display()))?;
+        let mut writer = Self {
+            out: BufWriter::new(file),
+        };
+        if existing == 0 {
+            writer
+                .out
+                .write_all(&JOURNAL_MAGIC)
+                .and_then(|_| writer.out.write_all(&JOURNAL_VERSION.to_le_bytes()))
+                .map_err(|e| format!("Journal header {}: {e}", path.display()))?;
+        }
+        Ok(writer)
+    }
+
+    /// Append one record.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - Record to append.
+    pub fn append(&mut self, record: &JournalRecord) -> Result<(), String> {
+        let bytes = bincode::encode_to_vec(record, bincode::config::standard())
+            .map_err(|e| format!("Journal encode: {e}"))?;
+        self.out
+            .write_all(&(bytes.len() as u32).to_le_bytes())
+            .and_then(|_| self.out.write_all(&bytes))
+            .map_err(|e| format!("Journal write: {e}"))
+    }
+
+    /// Flush buffered records to the OS.
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.out.flush().map_err(|e| format!("Journal flush: {e}"))
+    }
+}
+
+fn check_header(header: &[u8], path: &Path) -> Result<(), String> {
+    if header.len() < 8 || header[..4] != JOURNAL_MAGIC {
+        return Err(format!("{} is not a journal file", path.display()));
+    }
+    let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    if version != JOURNAL_VERSION {
+        return Err(format!(
+            "Unsupported journal version {version} in {} (expected {JOURNAL_VERSION})",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Read every complete record from a journal file.
+///
+/// # Arguments
+///
+/// * `path` - Journal file path.
+///
+/// # Returns
+///
+/// * The records in file order; a truncated final record is dropped with a
+///   warning.
+pub fn read_journal(path: &Path) -> Result<Vec<JournalRecord>, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Journal read {}: {e}", path.display()))?;
+    check_header(&bytes, path)?;
+
+    let mut records = Vec::new();
+    let mut offset = 8;
+    while offset < bytes.len() {
+        let Some(len_bytes) = bytes.get(offset..offset + 4) else {
+            log::warn!("Journal {} ends in a truncated record", path.display());
+            break;
+        };
+        let len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]);
+        if len > MAX_RECORD_BYTES {
+            return Err(format!(
+                "Journal {} has an invalid record length {len} at byte {offset}",
+                path.display()
+            ));
+        }
+        let start = offset + 4;
+        let Some(body) = bytes.get(start..start + len as usize) else {
+            log::warn!("Journal {} ends in a truncated record", path.display());
+            break;
+        };
+        let (record, _): (JournalRecord, usize) =
+            bincode::decode_from_slice(body, bincode::config::standard())
+                .map_err(|e| format!("Journal decode {} at byte {offset}: {e}", path.display()))?;
+        records.push(record);
+        offset = start + len as usize;
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writer_round_trips_and_tolerates_truncated_tail() {
+        let dir = std::env::temp_dir().join(format!("mag-journal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("roundtrip.mjl");
+        let _ = std::fs::remove_file(&path);
+
+        let tick = JournalRecord::Tick {
+            tick: 7,
+            unix_secs: 1_700_000_000,
+        };
+        let item = JournalRecord::Item {
+            index: 42,
+            cause: ItemCause::TemplateReset,
+            item: Box::default(),
+        };
+        {
+            let mut writer = JournalWriter::open(&path).unwrap();
+            writer.append(&tick).unwrap();
+            writer.flush().unwrap();
+        }
+        {
+            // Re-opening appends without rewriting the header.
+            let mut writer = JournalWriter::open(&path).unwrap();
+            writer.append(&item).unwrap();
+            writer.flush().unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[9, 0, 0, 0, 1]).unwrap();
+
+        assert_eq!(read_journal(&path).unwrap(), vec![tick, item]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
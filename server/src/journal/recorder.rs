@@ -0,0 +1,283 @@
+//! Shadow-diffing journal recorder.
+//!
+//! Instrumenting every mutation site in the ported game logic is not
+//! practical, so the recorder keeps a compact shadow copy of the fields it
+//! tracks and compares it with live state once per tick. Only slots whose
+//! shadow differs are written. Causes that the diff cannot see (template
+//! resets) are reported explicitly via [`JournalRecorder::note_item_reset`],
+//! and gold changing hands via [`JournalRecorder::note_gold_transfer`].
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use core::types::{Character, Item};
+
+use super::{GoldTransferKind, ItemCause, JournalRecord, JournalWriter};
+
+/// Environment variable naming the journal file; unset disables journaling.
+pub const JOURNAL_PATH_ENV: &str = "MAG_JOURNAL_PATH";
+
+/// Tracked character fields.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct CharacterShadow {
+    used: u8,
+    x: i16,
+    y: i16,
+    gold: i32,
+    citem: u32,
+    item: [u32; 40],
+    worn: [u32; 20],
+}
+
+impl CharacterShadow {
+    fn of(ch: &Character) -> Self {
+        Self {
+            used: ch.used,
+            x: ch.x,
+            y: ch.y,
+            gold: ch.gold,
+            citem: ch.citem,
+            item: ch.item,
+            worn: ch.worn,
+        }
+    }
+}
+
+/// Tracked item fields.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ItemShadow {
+    used: u8,
+    temp: u16,
+    carried: u16,
+    x: u16,
+    y: u16,
+}
+
+impl ItemShadow {
+    fn of(it: &Item) -> Self {
+        Self {
+            used: it.used,
+            temp: it.temp,
+            carried: it.carried,
+            x: it.x,
+            y: it.y,
+        }
+    }
+}
+
+/// Records per-tick character and item changes to a [`JournalWriter`].
+pub struct JournalRecorder {
+    writer: JournalWriter,
+    characters: Vec<CharacterShadow>,
+    items: Vec<ItemShadow>,
+    item_resets: Vec<u32>,
+    gold_transfers: Vec<JournalRecord>,
+}
+
+impl JournalRecorder {
+    /// Open the journal named by `MAG_JOURNAL_PATH`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(None)` when the variable is unset or empty.
+    /// * `Err` when the file cannot be opened.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        match std::env::var(JOURNAL_PATH_ENV) {
+            Ok(path) if !path.trim().is_empty() => Self::open(Path::new(path.trim())).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Open (or append to) the journal at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Journal file path.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        log::info!("Recording world journal to {}", path.display());
+        Ok(Self {
+            writer: JournalWriter::open(path)?,
+            characters: Vec::new(),
+            items: Vec::new(),
+            item_resets: Vec::new(),
+            gold_transfers: Vec::new(),
+        })
+    }
+
+    /// Mark item slot `index` as reset from its template this tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Item slot index.
+    pub fn note_item_reset(&mut self, index: usize) {
+        self.item_resets.push(index as u32);
+    }
+
+    /// Queue a gold transfer to be written with this tick's records.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Paying character slot; `0` is the world.
+    /// * `to` - Receiving character slot; `0` is the world.
+    /// * `amount` - Amount in silver.
+    /// * `kind` - How the gold moved.
+    pub fn note_gold_transfer(
+        &mut self,
+        from: usize,
+        to: usize,
+        amount: i32,
+        kind: GoldTransferKind,
+    ) {
+        self.gold_transfers.push(JournalRecord::GoldTransfer {
+            from: from as u32,
+            to: to as u32,
+            amount,
+            kind,
+        });
+    }
+
+    /// Diff live state against the shadows and append this tick's records.
+    ///
+    /// The first call only captures the baseline. Write errors are logged and
+    /// never interrupt the game loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - `globals.ticker` after the tick ran.
+    /// * `characters` - All character slots.
+    /// * `items` - All item slots.
+    ///
+    /// # Returns
+    ///
+    /// * Number of gold transfer, character and item records written.
+    pub fn record_tick(&mut self, tick: i32, characters: &[Character], items: &[Item]) -> usize {
+        if self.characters.len() != characters.len() || self.items.len() != items.len() {
+            self.characters = characters.iter().map(CharacterShadow::of).collect();
+            self.items = items.iter().map(ItemShadow::of).collect();
+            self.item_resets.clear();
+            self.gold_transfers.clear();
+            return 0;
+        }
+
+        let mut records = std::mem::take(&mut self.gold_transfers);
+        for (index, ch) in characters.iter().enumerate() {
+            let shadow = CharacterShadow::of(ch);
+            if shadow != self.characters[index] {
+                self.characters[index] = shadow;
+                records.push(JournalRecord::Character {
+                    index: index as u32,
+                    used: shadow.used,
+                    x: shadow.x,
+                    y: shadow.y,
+                    gold: shadow.gold,
+                    citem: shadow.citem,
+                    item: shadow.item,
+                    worn: shadow.worn,
+                });
+            }
+        }
+
+        self.item_resets.sort_unstable();
+        self.item_resets.dedup();
+        for (index, it) in items.iter().enumerate() {
+            let shadow = ItemShadow::of(it);
+            let reset = self.item_resets.binary_search(&(index as u32)).is_ok();
+            if shadow != self.items[index] || reset {
+                self.items[index] = shadow;
+                records.push(JournalRecord::Item {
+                    index: index as u32,
+                    cause: if reset {
+                        ItemCause::TemplateReset
+                    } else {
+                        ItemCause::Changed
+                    },
+                    item: Box::new(*it),
+                });
+            }
+        }
+        self.item_resets.clear();
+
+        if records.is_empty() {
+            return 0;
+        }
+        let unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let result = std::iter::once(&JournalRecord::Tick { tick, unix_secs })
+            .chain(records.iter())
+            .try_for_each(|record| self.writer.append(record))
+            .and_then(|_| self.writer.flush());
+        if let Err(err) = result {
+            log::error!("World journal write failed at tick {tick}: {err}");
+        }
+        records.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::read_journal;
+
+    #[test]
+    fn records_only_changed_slots_after_baseline() {
+        let dir = std::env::temp_dir().join(format!("mag-journal-rec-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("recorder.mjl");
+        let _ = std::fs::remove_file(&path);
+
+        let mut characters = vec![Character::default(); 4];
+        let mut items = vec![Item::default(); 8];
+        let mut recorder = JournalRecorder::open(&path).unwrap();
+        assert_eq!(recorder.record_tick(1, &characters, &items), 0);
+
+        characters[2].x = 12;
+        characters[2].item[0] = 5;
+        items[5].carried = 2;
+        recorder.note_item_reset(6);
+        assert_eq!(recorder.record_tick(2, &characters, &items), 3);
+        assert_eq!(recorder.record_tick(3, &characters, &items), 0);
+        recorder.note_gold_transfer(2, 3, 150, GoldTransferKind::Give);
+        assert_eq!(recorder.record_tick(4, &characters, &items), 1);
+
+        let records = read_journal(&path).unwrap();
+        assert!(matches!(records[0], JournalRecord::Tick { tick: 2, .. }));
+        assert!(matches!(
+            records[1],
+            JournalRecord::Character {
+                index: 2,
+                x: 12,
+                ..
+            }
+        ));
+        assert!(matches!(
+            records[2],
+            JournalRecord::Item {
+                index: 5,
+                cause: ItemCause::Changed,
+                ..
+            }
+        ));
+        assert!(matches!(
+            records[3],
+            JournalRecord::Item {
+                index: 6,
+                cause: ItemCause::TemplateReset,
+                ..
+            }
+        ));
+        assert!(records[1].mentions_item(5));
+        assert!(matches!(records[4], JournalRecord::Tick { tick: 4, .. }));
+        assert_eq!(
+            records[5],
+            JournalRecord::GoldTransfer {
+                from: 2,
+                to: 3,
+                amount: 150,
+                kind: GoldTransferKind::Give,
+            }
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,359 @@
+//! Re-apply a journal onto a world snapshot.
+//!
+//! Records hold absolute values, so replaying onto a snapshot taken at (or
+//! before) the journal's first tick reproduces the tracked state at the
+//! chosen tick. Map tile `ch`/`it` back-references are kept consistent for
+//! moved characters and for items lying on the ground; everything else in
+//! the snapshot is left untouched.
+//!
+//! [`record`] replays tick by tick and captures a [`ReplayFrame`] around a
+//! followed character after each one, for the client's replay viewer.
+
+use core::constants::{ItemFlags, SERVER_MAPX, SERVER_MAPY, USE_EMPTY};
+use core::replay_store::{REPLAY_VIEW_RADIUS, ReplayFrame, ReplayRecording, ReplayTile};
+
+use super::JournalRecord;
+use crate::keydb::snapshot::WorldSnapshot;
+
+/// Outcome of [`apply`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// Ticks whose records were applied.
+    pub ticks: usize,
+    /// Character records applied.
+    pub characters: usize,
+    /// Item records applied.
+    pub items: usize,
+    /// Records skipped because their slot index is out of range.
+    pub skipped: usize,
+    /// Last tick applied, if any.
+    pub last_tick: Option<i32>,
+}
+
+/// Apply `records` to `snapshot`, stopping after tick `until_tick`.
+///
+/// # Arguments
+///
+/// * `snapshot` - Snapshot to mutate in place.
+/// * `records` - Journal records in file order.
+/// * `until_tick` - Last tick to apply; `None` applies everything.
+///
+/// # Returns
+///
+/// * Counts of what was applied.
+pub fn apply(
+    snapshot: &mut WorldSnapshot,
+    records: &[JournalRecord],
+    until_tick: Option<i32>,
+) -> ReplayStats {
+    let mut stats = ReplayStats::default();
+    for record in records {
+        match record {
+            JournalRecord::Tick { tick, .. } => {
+                if until_tick.is_some_and(|limit| *tick > limit) {
+                    break;
+                }
+                stats.ticks += 1;
+                stats.last_tick = Some(*tick);
+            }
+            JournalRecord::Character {
+                index,
+                used,
+                x,
+                y,
+                gold,
+                citem,
+                item,
+                worn,
+            } => {
+                let cn = *index as usize;
+                let Some(ch) = snapshot.characters.get_mut(cn) else {
+                    stats.skipped += 1;
+                    continue;
+                };
+                let (old_x, old_y) = (ch.x, ch.y);
+                ch.used = *used;
+                ch.x = *x;
+                ch.y = *y;
+                ch.gold = *gold;
+                ch.citem = *citem;
+                ch.item = *item;
+                ch.worn = *worn;
+                if (old_x, old_y) != (*x, *y) {
+                    if let Some(tile) = tile_index(i32::from(old_x), i32::from(old_y))
+                        .and_then(|m| snapshot.map.get_mut(m))
+                        && tile.ch == *index
+                    {
+                        tile.ch = 0;
+                    }
+                    if let Some(tile) = tile_index(i32::from(*x), i32::from(*y))
+                        .and_then(|m| snapshot.map.get_mut(m))
+                    {
+                        tile.ch = *index;
+                    }
+                }
+                stats.characters += 1;
+            }
+            JournalRecord::Item { index, item, .. } => {
+                let n = *index as usize;
+                let Some(slot) = snapshot.items.get_mut(n) else {
+                    stats.skipped += 1;
+                    continue;
+                };
+                let (old_x, old_y, old_carried) = (slot.x, slot.y, slot.carried);
+                *slot = **item;
+                if old_carried == 0
+                    && let Some(tile) = tile_index(i32::from(old_x), i32::from(old_y))
+                        .and_then(|m| snapshot.map.get_mut(m))
+                    && tile.it == *index
+                {
+                    tile.it = 0;
+                }
+                if item.carried == 0
+                    && item.used != USE_EMPTY
+                    && let Some(tile) = tile_index(i32::from(item.x), i32::from(item.y))
+                        .and_then(|m| snapshot.map.get_mut(m))
+                {
+                    tile.it = *index;
+                }
+                stats.items += 1;
+            }
+            // Purses are restored from the character records of the same tick.
+            JournalRecord::GoldTransfer { .. } => {}
+        }
+    }
+    stats
+}
+
+/// Replay `records` onto `snapshot` and capture a frame after every tick.
+///
+/// # Arguments
+///
+/// * `snapshot` - Snapshot to mutate in place.
+/// * `records` - Journal records in file order.
+/// * `follow` - Character slot the frames are centred on.
+/// * `until_tick` - Last tick to record; `None` records everything.
+///
+/// # Returns
+///
+/// * The recording, or an error if `follow` is not a character slot.
+pub fn record(
+    snapshot: &mut WorldSnapshot,
+    records: &[JournalRecord],
+    follow: u32,
+    until_tick: Option<i32>,
+) -> Result<ReplayRecording, String> {
+    if snapshot.characters.get(follow as usize).is_none() {
+        return Err(format!("Character {follow} is not in the snapshot"));
+    }
+    let mut frames = Vec::new();
+    let mut start = 0;
+    while start < records.len() {
+        let end = records[start + 1..]
+            .iter()
+            .position(|record| matches!(record, JournalRecord::Tick { .. }))
+            .map_or(records.len(), |offset| start + 1 + offset);
+        let stats = apply(snapshot, &records[start..end], until_tick);
+        let Some(tick) = stats.last_tick else {
+            break;
+        };
+        frames.push(capture_frame(snapshot, tick, follow));
+        start = end;
+    }
+    Ok(ReplayRecording::new(follow, frames))
+}
+
+/// Capture the tiles around character `follow` as the client would see them.
+///
+/// # Arguments
+///
+/// * `snapshot` - World state after the tick.
+/// * `tick` - Tick the frame belongs to.
+/// * `follow` - Character slot at the centre of the frame.
+///
+/// # Returns
+///
+/// * The captured frame.
+fn capture_frame(snapshot: &WorldSnapshot, tick: i32, follow: u32) -> ReplayFrame {
+    let (center_x, center_y) = snapshot
+        .characters
+        .get(follow as usize)
+        .map_or((0, 0), |ch| (ch.x, ch.y));
+    let mut tiles = Vec::new();
+    for dy in -REPLAY_VIEW_RADIUS..=REPLAY_VIEW_RADIUS {
+        for dx in -REPLAY_VIEW_RADIUS..=REPLAY_VIEW_RADIUS {
+            let tile = tile_index(i32::from(center_x + dx), i32::from(center_y + dy))
+                .and_then(|m| snapshot.map.get(m))
+                .map_or_else(ReplayTile::default, |map| {
+                    let character = character_sprite(snapshot, map.ch);
+                    ReplayTile {
+                        back: map.sprite,
+                        item: if map.fsprite != 0 {
+                            map.fsprite
+                        } else {
+                            ground_item_sprite(snapshot, map.it)
+                        },
+                        character,
+                        character_nr: if character != 0 { map.ch as u16 } else { 0 },
+                    }
+                });
+            tiles.push(tile);
+        }
+    }
+    ReplayFrame {
+        tick,
+        center_x,
+        center_y,
+        tiles,
+    }
+}
+
+/// Sprite of the visible item in slot `it`, or `0`.
+fn ground_item_sprite(snapshot: &WorldSnapshot, it: u32) -> u16 {
+    match snapshot.items.get(it as usize) {
+        Some(item)
+            if it != 0
+                && item.used != USE_EMPTY
+                && item.flags & ItemFlags::IF_HIDDEN.bits() == 0 =>
+        {
+            let active = usize::from(item.active != 0);
+            item.sprite[active].max(0) as u16
+        }
+        _ => 0,
+    }
+}
+
+/// Base sprite of the character in slot `cn`, or `0`.
+fn character_sprite(snapshot: &WorldSnapshot, cn: u32) -> u16 {
+    match snapshot.characters.get(cn as usize) {
+        Some(ch) if cn != 0 && ch.used != USE_EMPTY => {
+            if ch.sprite_override != 0 {
+                ch.sprite_override.max(0) as u16
+            } else {
+                ch.sprite
+            }
+        }
+        _ => 0,
+    }
+}
+
+/// Linear map index for `(x, y)`, or `None` for the void / off-map.
+fn tile_index(x: i32, y: i32) -> Option<usize> {
+    if x <= 0 || y <= 0 || x >= SERVER_MAPX || y >= SERVER_MAPY {
+        return None;
+    }
+    Some((x + y * SERVER_MAPX) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::ItemCause;
+    use core::constants::USE_ACTIVE;
+
+    fn small_snapshot() -> WorldSnapshot {
+        WorldSnapshot::new(
+            vec![core::types::Map::default(); (SERVER_MAPX * SERVER_MAPY) as usize],
+            vec![core::types::Item::default(); 4],
+            Vec::new(),
+            vec![core::types::Character::default(); 4],
+            Vec::new(),
+            Vec::new(),
+            core::types::Global::default(),
+            Vec::new(),
+            Vec::new(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn apply_moves_characters_and_items_and_honours_until_tick() {
+        let mut snapshot = small_snapshot();
+        let dropped = core::types::Item {
+            used: USE_ACTIVE,
+            x: 20,
+            y: 30,
+            ..Default::default()
+        };
+        let picked_up = core::types::Item {
+            carried: 1,
+            ..dropped
+        };
+        let records = vec![
+            JournalRecord::Tick {
+                tick: 10,
+                unix_secs: 0,
+            },
+            JournalRecord::Character {
+                index: 1,
+                used: USE_ACTIVE,
+                x: 5,
+                y: 6,
+                gold: 100,
+                citem: 0,
+                item: [0; 40],
+                worn: [0; 20],
+            },
+            JournalRecord::Item {
+                index: 2,
+                cause: ItemCause::Changed,
+                item: Box::new(dropped),
+            },
+            JournalRecord::Tick {
+                tick: 11,
+                unix_secs: 0,
+            },
+            JournalRecord::Item {
+                index: 2,
+                cause: ItemCause::Changed,
+                item: Box::new(picked_up),
+            },
+        ];
+
+        let stats = apply(&mut snapshot, &records, Some(10));
+        assert_eq!(stats.last_tick, Some(10));
+        assert_eq!((stats.characters, stats.items), (1, 1));
+        assert_eq!(snapshot.characters[1].gold, 100);
+        assert_eq!(snapshot.map[tile_index(5, 6).unwrap()].ch, 1);
+        assert_eq!(snapshot.map[tile_index(20, 30).unwrap()].it, 2);
+
+        let stats = apply(&mut snapshot, &records[3..], None);
+        assert_eq!(stats.last_tick, Some(11));
+        assert_eq!(snapshot.items[2].carried, 1);
+        assert_eq!(snapshot.map[tile_index(20, 30).unwrap()].it, 0);
+    }
+
+    #[test]
+    fn record_captures_one_frame_per_tick_centred_on_follow() {
+        let mut snapshot = small_snapshot();
+        snapshot.characters[1].sprite = 2000;
+        snapshot.map[tile_index(9, 6).unwrap()].sprite = 1010;
+        let step = |tick, x| {
+            [
+                JournalRecord::Tick { tick, unix_secs: 0 },
+                JournalRecord::Character {
+                    index: 1,
+                    used: USE_ACTIVE,
+                    x,
+                    y: 6,
+                    gold: 0,
+                    citem: 0,
+                    item: [0; 40],
+                    worn: [0; 20],
+                },
+            ]
+        };
+        let records: Vec<_> = [step(10, 5), step(11, 6), step(12, 7)].concat();
+
+        let recording = record(&mut snapshot, &records, 1, Some(11)).unwrap();
+        assert_eq!(recording.frames.len(), 2);
+        let frame = &recording.frames[1];
+        assert_eq!((frame.tick, frame.center_x, frame.center_y), (11, 6, 6));
+        let centre = frame.tile(0, 0).unwrap();
+        assert_eq!((centre.character, centre.character_nr), (2000, 1));
+        assert_eq!(frame.tile(3, 0).unwrap().back, 1010);
+        assert_eq!(*frame.tile(-1, 0).unwrap(), ReplayTile::default());
+
+        assert!(record(&mut snapshot, &records, 99, None).is_err());
+    }
+}
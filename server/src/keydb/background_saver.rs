@@ -1,9 +1,10 @@
-/// Background persistence thread for writing game data to KeyDB.
+/// Background persistence thread for writing game data to KeyDB or SQLite.
 ///
 /// The main game loop (single-threaded) periodically clones slices of
 /// in-memory data and sends them to this background thread via an `mpsc`
-/// channel.  The background thread owns a persistent `redis::Connection`
-/// and writes the data using pipelined commands.
+/// channel.  The background thread owns a persistent connection to the
+/// configured [`StorageBackend`]: a `redis::Connection` writing pipelined
+/// commands, or a [`SqliteStore`] writing one transaction per job.
 ///
 /// # Save rotation
 ///
@@ -20,10 +21,20 @@
 ///
 /// At default settings (`SAVE_INTERVAL_TICKS = 4_320`, 36 TPS) each cycle
 /// fires every ~2 minutes, so a full rotation ≈ 12 minutes.
+///
+/// # Player checkpoints
+///
+/// Independently of the rotation, every [`CHECKPOINT_INTERVAL_TICKS`] the
+/// game loop copies just the online players and the items they carry
+/// ([`checkpoint_job`]) and hands that copy to the same thread, so a crash
+/// loses at most a few seconds of player progress instead of everything
+/// since the last characters cycle.
 use std::sync::mpsc;
 use std::thread::{self, JoinHandle};
 
 use super::{connection, store};
+use crate::sqlite_store::SqliteStore;
+use crate::storage::StorageBackend;
 
 /// Ticks between each background save job.
 ///
@@ -38,6 +49,9 @@ pub const SAVE_INTERVAL_TICKS: u32 = 4_320;
 /// settings the full rotation takes approximately 12 minutes.
 pub const SAVE_CYCLE_COUNT: u32 = 6;
 
+/// Ticks between player checkpoints (~30 seconds at 36 TPS).
+pub const CHECKPOINT_INTERVAL_TICKS: u32 = 1_080;
+
 /// A unit of work sent to the background saver thread via
 /// [`BackgroundSaver::send`].
 ///
@@ -63,6 +77,15 @@ pub enum SaveJob {
         /// The single global state value (`game:global`).
         globals: core::types::Global,
     },
+    /// Persist individual character and item slots: the online players
+    /// and everything they carry, wear or keep in their depot. Built by
+    /// [`checkpoint_job`].
+    Checkpoint {
+        /// `(slot, character)` pairs (`game:char:*`).
+        characters: Vec<(usize, core::types::Character)>,
+        /// `(slot, item)` pairs (`game:item:*`).
+        items: Vec<(usize, core::types::Item)>,
+    },
     /// Request a synchronous flush — the saver thread will ack via the
     /// provided one-shot channel once the write completes.
     Flush(mpsc::Sender<Result<(), String>>),
@@ -133,7 +156,7 @@ impl Drop for BackgroundSaver {
     }
 }
 
-/// Spawn the background saver thread.
+/// Spawn the background saver thread writing to KeyDB.
 ///
 /// Creates an `mpsc` channel and starts a dedicated thread that
 /// listens for [`SaveJob`] messages.  The thread maintains its own
@@ -147,12 +170,41 @@ impl Drop for BackgroundSaver {
 ///
 /// Panics if the OS thread cannot be spawned.
 pub fn spawn() -> BackgroundSaver {
+    spawn_with(|| KeyDbSink(connect_with_retry()))
+}
+
+/// Spawn the background saver thread for the configured storage backend.
+///
+/// # Arguments
+///
+/// * `backend` - Where save jobs are written.
+///
+/// # Returns
+///
+/// * A [`BackgroundSaver`] handle, or an `Err` if the SQLite database
+///   cannot be opened.
+///
+/// # Panics
+///
+/// Panics if the OS thread cannot be spawned.
+pub fn spawn_for(backend: &StorageBackend) -> Result<BackgroundSaver, String> {
+    match backend {
+        StorageBackend::KeyDb => Ok(spawn()),
+        StorageBackend::Sqlite { path } => {
+            let db = SqliteStore::open(path)?;
+            Ok(spawn_with(move || db))
+        }
+    }
+}
+
+fn spawn_with<S: SaveSink>(connect: impl FnOnce() -> S + Send + 'static) -> BackgroundSaver {
     let (tx, rx) = mpsc::channel::<SaveJob>();
 
     let handle = thread::Builder::new()
         .name("bg-saver".into())
         .spawn(move || {
-            saver_thread_main(rx);
+            log::info!("Background saver thread started.");
+            saver_thread_main(rx, connect());
         })
         .expect("Failed to spawn background saver thread");
 
@@ -162,6 +214,165 @@ pub fn spawn() -> BackgroundSaver {
     }
 }
 
+/// Copy the online players and the items they carry, wear or keep in their
+/// depot into a checkpoint job.
+///
+/// Only the touched slots are cloned, so the copy stays small enough to
+/// take every few seconds on the tick thread.
+///
+/// # Arguments
+///
+/// * `characters` - All character slots.
+/// * `items` - All item slots.
+///
+/// # Returns
+///
+/// * A [`SaveJob::Checkpoint`], or `None` when no player is online.
+pub fn checkpoint_job(
+    characters: &[core::types::Character],
+    items: &[core::types::Item],
+) -> Option<SaveJob> {
+    let mut saved_characters = Vec::new();
+    let mut saved_items = Vec::new();
+    for (cn, ch) in characters.iter().enumerate() {
+        if cn == 0 || ch.used != core::constants::USE_ACTIVE || !ch.is_player() {
+            continue;
+        }
+        saved_characters.push((cn, *ch));
+        let owned = ch
+            .item
+            .iter()
+            .chain(ch.worn.iter())
+            .chain(ch.depot.iter())
+            .chain(std::iter::once(&ch.citem));
+        for &item in owned {
+            // The high bit of `citem` marks carried gold, not an item slot.
+            if item == 0 || item & 0x8000_0000 != 0 {
+                continue;
+            }
+            if let Some(slot) = items.get(item as usize) {
+                saved_items.push((item as usize, *slot));
+            }
+        }
+    }
+    if saved_characters.is_empty() {
+        return None;
+    }
+    Some(SaveJob::Checkpoint {
+        characters: saved_characters,
+        items: saved_items,
+    })
+}
+
+// ---------------------------------------------------------------------------
+//  Save destinations
+// ---------------------------------------------------------------------------
+
+/// Destination the saver thread writes jobs to.
+trait SaveSink {
+    fn save_characters(&mut self, data: &[core::types::Character]) -> Result<(), String>;
+    fn save_items(&mut self, data: &[core::types::Item], start_idx: usize) -> Result<(), String>;
+    fn save_map_tiles(
+        &mut self,
+        data: &[core::types::Map],
+        start_linear: usize,
+    ) -> Result<(), String>;
+    fn save_small_data(
+        &mut self,
+        effects: &[core::types::Effect],
+        globals: &core::types::Global,
+    ) -> Result<(), String>;
+    fn save_checkpoint(
+        &mut self,
+        characters: &[(usize, core::types::Character)],
+        items: &[(usize, core::types::Item)],
+    ) -> Result<(), String>;
+    /// Called after a failed write, e.g. to reconnect.
+    fn recover(&mut self) {}
+}
+
+/// KeyDB destination; reconnects after any failed write.
+struct KeyDbSink(redis::Connection);
+
+impl SaveSink for KeyDbSink {
+    fn save_characters(&mut self, data: &[core::types::Character]) -> Result<(), String> {
+        store::save_characters(&mut self.0, data)
+    }
+
+    fn save_items(&mut self, data: &[core::types::Item], start_idx: usize) -> Result<(), String> {
+        store::save_indexed_entities_range(&mut self.0, "game:item:", data, start_idx)
+    }
+
+    fn save_map_tiles(
+        &mut self,
+        data: &[core::types::Map],
+        start_linear: usize,
+    ) -> Result<(), String> {
+        store::save_map_range(&mut self.0, data, start_linear)
+    }
+
+    fn save_small_data(
+        &mut self,
+        effects: &[core::types::Effect],
+        globals: &core::types::Global,
+    ) -> Result<(), String> {
+        // Attempt both writes even if the first fails.
+        let effects =
+            store::save_effects(&mut self.0, effects).map_err(|e| format!("effects: {e}"));
+        let globals =
+            store::save_globals(&mut self.0, globals).map_err(|e| format!("globals: {e}"));
+        effects.and(globals)
+    }
+
+    fn save_checkpoint(
+        &mut self,
+        characters: &[(usize, core::types::Character)],
+        items: &[(usize, core::types::Item)],
+    ) -> Result<(), String> {
+        store::save_indexed_entities_sparse(&mut self.0, "game:char:", characters)?;
+        store::save_indexed_entities_sparse(&mut self.0, "game:item:", items)
+    }
+
+    fn recover(&mut self) {
+        self.0 = connect_with_retry();
+    }
+}
+
+/// SQLite destination; each job is one transaction.
+impl SaveSink for SqliteStore {
+    fn save_characters(&mut self, data: &[core::types::Character]) -> Result<(), String> {
+        self.save_range("character", data, 0)
+    }
+
+    fn save_items(&mut self, data: &[core::types::Item], start_idx: usize) -> Result<(), String> {
+        self.save_range("item", data, start_idx)
+    }
+
+    fn save_map_tiles(
+        &mut self,
+        data: &[core::types::Map],
+        start_linear: usize,
+    ) -> Result<(), String> {
+        self.save_range("map", data, start_linear)
+    }
+
+    fn save_small_data(
+        &mut self,
+        effects: &[core::types::Effect],
+        globals: &core::types::Global,
+    ) -> Result<(), String> {
+        SqliteStore::save_small_data(self, effects, globals)
+    }
+
+    fn save_checkpoint(
+        &mut self,
+        characters: &[(usize, core::types::Character)],
+        items: &[(usize, core::types::Item)],
+    ) -> Result<(), String> {
+        SqliteStore::save_checkpoint(self, characters, items)
+    }
+}
+
 // ---------------------------------------------------------------------------
 //  Background thread main loop
 // ---------------------------------------------------------------------------
@@ -192,11 +403,9 @@ fn connect_with_retry() -> redis::Connection {
 ///
 /// # Arguments
 ///
-/// * `rx` - The receiving end of the job channel.
-fn saver_thread_main(rx: mpsc::Receiver<SaveJob>) {
-    log::info!("Background saver thread started.");
-    let mut con = connect_with_retry();
-
+/// * `rx`   - The receiving end of the job channel.
+/// * `sink` - Destination the jobs are written to.
+fn saver_thread_main(rx: mpsc::Receiver<SaveJob>, mut sink: impl SaveSink) {
     loop {
         let job = match rx.recv() {
             Ok(job) => job,
@@ -206,73 +415,49 @@ fn saver_thread_main(rx: mpsc::Receiver<SaveJob>) {
             }
         };
 
-        match job {
-            SaveJob::Characters(data) => {
-                let t = std::time::Instant::now();
-                if let Err(e) = store::save_characters(&mut con, &data) {
-                    log::error!("Background save characters failed: {e}");
-                    con = connect_with_retry();
-                } else {
-                    log::debug!(
-                        "Background save: {} characters in {:.2?}",
-                        data.len(),
-                        t.elapsed()
-                    );
-                }
-            }
-            SaveJob::Items(data, start_idx) => {
-                let t = std::time::Instant::now();
-                if let Err(e) =
-                    store::save_indexed_entities_range(&mut con, "game:item:", &data, start_idx)
-                {
-                    log::error!("Background save items failed: {e}");
-                    con = connect_with_retry();
-                } else {
-                    log::debug!(
-                        "Background save: {} items (start {start_idx}) in {:.2?}",
-                        data.len(),
-                        t.elapsed()
-                    );
-                }
-            }
-            SaveJob::MapTiles(data, start_linear) => {
-                let t = std::time::Instant::now();
-                if let Err(e) = store::save_map_range(&mut con, &data, start_linear) {
-                    log::error!("Background save map tiles failed: {e}");
-                    con = connect_with_retry();
-                } else {
-                    log::debug!(
-                        "Background save: {} map tiles (start {start_linear}) in {:.2?}",
-                        data.len(),
-                        t.elapsed()
-                    );
-                }
-            }
-            SaveJob::SmallData { effects, globals } => {
-                let t = std::time::Instant::now();
-                let mut ok = true;
-                if let Err(e) = store::save_effects(&mut con, &effects) {
-                    log::error!("Background save effects failed: {e}");
-                    ok = false;
-                }
-                if let Err(e) = store::save_globals(&mut con, &globals) {
-                    log::error!("Background save globals failed: {e}");
-                    ok = false;
-                }
-                if !ok {
-                    con = connect_with_retry();
-                } else {
-                    log::debug!("Background save: small data in {:.2?}", t.elapsed());
-                }
-            }
+        let t = std::time::Instant::now();
+        let (what, result) = match job {
+            SaveJob::Characters(data) => (
+                format!("{} characters", data.len()),
+                sink.save_characters(&data),
+            ),
+            SaveJob::Items(data, start_idx) => (
+                format!("{} items (start {start_idx})", data.len()),
+                sink.save_items(&data, start_idx),
+            ),
+            SaveJob::MapTiles(data, start_linear) => (
+                format!("{} map tiles (start {start_linear})", data.len()),
+                sink.save_map_tiles(&data, start_linear),
+            ),
+            SaveJob::SmallData { effects, globals } => (
+                "small data".to_owned(),
+                sink.save_small_data(&effects, &globals),
+            ),
+            SaveJob::Checkpoint { characters, items } => (
+                format!(
+                    "checkpoint of {} players, {} items",
+                    characters.len(),
+                    items.len()
+                ),
+                sink.save_checkpoint(&characters, &items),
+            ),
             SaveJob::Flush(ack) => {
                 // All prior jobs have already been processed (channel is FIFO).
                 let _ = ack.send(Ok(()));
+                continue;
             }
             SaveJob::Shutdown => {
                 log::info!("Background saver: shutdown requested.");
                 break;
             }
+        };
+
+        match result {
+            Ok(()) => log::debug!("Background save: {what} in {:.2?}", t.elapsed()),
+            Err(e) => {
+                log::error!("Background save {what} failed: {e}");
+                sink.recover();
+            }
         }
     }
 
@@ -328,6 +513,38 @@ mod tests {
         };
     }
 
+    /// Checkpoints copy only online players and the items they own.
+    #[test]
+    fn checkpoint_job_collects_players_and_their_items() {
+        use core::constants::{CharacterFlags, USE_ACTIVE};
+
+        let mut characters = vec![core::types::Character::default(); 4];
+        let mut items = vec![core::types::Item::default(); 8];
+        assert!(checkpoint_job(&characters, &items).is_none());
+
+        characters[1].used = USE_ACTIVE;
+        characters[1].flags = CharacterFlags::Player.bits();
+        characters[1].item[0] = 2;
+        characters[1].worn[3] = 5;
+        characters[1].citem = 0x8000_0000 | 100;
+        characters[1].depot[7] = 3;
+        characters[2].used = USE_ACTIVE; // NPC
+        characters[2].item[0] = 6;
+        items[5].value = 9;
+
+        let Some(SaveJob::Checkpoint {
+            characters: saved,
+            items: saved_items,
+        }) = checkpoint_job(&characters, &items)
+        else {
+            panic!("expected a checkpoint");
+        };
+        assert_eq!(saved.iter().map(|(cn, _)| *cn).collect::<Vec<_>>(), [1]);
+        let slots: Vec<_> = saved_items.iter().map(|(n, _)| *n).collect();
+        assert_eq!(slots, [2, 5, 3]);
+        assert_eq!(saved_items[1].1.value, 9);
+    }
+
     /// Dropping a `BackgroundSaver` before calling `shutdown()` should not
     /// panic — the `Drop` impl calls `shutdown()` internally.
     ///
@@ -0,0 +1,691 @@
+//! `server backup <dir>` and `server restore <dir>`.
+//!
+//! A backup is a directory with one file per [`SECTIONS`] entry and a
+//! `MANIFEST` that is written last, so a directory without one is an
+//! interrupted backup. Every section file starts with [`SECTION_MAGIC`] and
+//! [`BACKUP_FORMAT_VERSION`], followed by a zstd stream of the
+//! `bincode`-encoded keys and their raw values. Values are copied byte for
+//! byte, so a backup does not depend on the shape of the entity structs;
+//! the manifest records the `game:meta:version` schema instead, and restore
+//! refuses a backup taken with a different one.
+//!
+//! The manifest lists, per section, the file, the number of keys and an
+//! FNV-1a checksum of the file. Restore reads and checks every file before
+//! touching KeyDB, then deletes the current keys of all sections and writes
+//! the backup in a single `MULTI`/`EXEC`, so KeyDB holds either the old
+//! world or the restored one. Run it with the server stopped, otherwise the
+//! next background save overwrites the restored world.
+//!
+//! Patch queues and their status keys are transient and left out.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bincode::{Decode, Encode};
+use redis::{Connection, pipe};
+
+use super::connection;
+use super::snapshot::SNAPSHOT_SCHEMA_VERSION;
+
+/// First command-line argument that takes a backup.
+pub const BACKUP_COMMAND: &str = "backup";
+
+/// First command-line argument that restores a backup.
+pub const RESTORE_COMMAND: &str = "restore";
+
+/// Version of the backup layout; bump when the file format changes.
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Name of the manifest inside a backup directory.
+pub const MANIFEST_FILE: &str = "MANIFEST";
+
+/// Magic bytes at the start of every section file.
+pub const SECTION_MAGIC: [u8; 4] = *b"MGBK";
+
+/// First word of the manifest.
+const MANIFEST_HEADER: &str = "mag-backup";
+
+/// Key holding the schema version of the stored world.
+const SCHEMA_VERSION_KEY: &str = "game:meta:version";
+
+/// zstd level for section files; backups favour speed over size.
+const SECTION_ZSTD_LEVEL: i32 = 3;
+
+/// Keys fetched per pipeline round-trip.
+const BATCH_SIZE: usize = 4096;
+
+/// How a section's keys are stored in KeyDB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionKind {
+    /// Plain string values (`GET` / `SET`).
+    String,
+    /// Hashes (`HGETALL` / `HSET`).
+    Hash,
+}
+
+/// One group of keys backed up into its own file.
+#[derive(Debug)]
+pub struct Section {
+    /// Section name, also the file name without `.bin`.
+    pub name: &'static str,
+    /// `SCAN` patterns selecting the section's keys.
+    pub patterns: &'static [&'static str],
+    /// Value type of every key in the section.
+    pub kind: SectionKind,
+}
+
+/// Every section a backup holds, in the order they are written.
+pub const SECTIONS: [Section; 13] = [
+    Section {
+        name: "map",
+        patterns: &["game:map:*"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "items",
+        patterns: &["game:item:*"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "item_templates",
+        patterns: &["game:titem:*"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "characters",
+        patterns: &["game:char:*"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "character_templates",
+        patterns: &["game:tchar:*"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "effects",
+        patterns: &["game:effect:*"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "globals",
+        patterns: &["game:global"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "text",
+        patterns: &["game:badnames", "game:badwords", "game:motd"],
+        kind: SectionKind::String,
+    },
+    Section {
+        name: "guilds",
+        patterns: &["game:guild:*"],
+        kind: SectionKind::Hash,
+    },
+    Section {
+        name: "mail",
+        patterns: &["game:mail:*"],
+        kind: SectionKind::Hash,
+    },
+    Section {
+        name: "friends",
+        patterns: &["game:friends:*"],
+        kind: SectionKind::Hash,
+    },
+    Section {
+        name: "consignments",
+        patterns: &["game:consign:*"],
+        kind: SectionKind::Hash,
+    },
+    Section {
+        name: "script_flags",
+        patterns: &["game:script_flag:*"],
+        kind: SectionKind::Hash,
+    },
+];
+
+/// A backup or restore requested on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackupCommand {
+    /// Dump KeyDB into a new directory.
+    Backup(PathBuf),
+    /// Replace the KeyDB world with a backup.
+    Restore(PathBuf),
+}
+
+/// Raw value of one key.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum BackupValue {
+    /// Bytes of a string key.
+    String(Vec<u8>),
+    /// Fields of a hash, sorted by name.
+    Hash(Vec<(String, Vec<u8>)>),
+}
+
+/// One key of a section file.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct BackupEntry {
+    /// Full KeyDB key.
+    pub key: String,
+    /// Its value.
+    pub value: BackupValue,
+}
+
+/// Manifest line describing one section file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionRecord {
+    /// Section name from [`SECTIONS`].
+    pub name: String,
+    /// File name inside the backup directory.
+    pub file: String,
+    /// Number of keys in the file.
+    pub keys: usize,
+    /// FNV-1a checksum of the whole file.
+    pub checksum: u64,
+}
+
+/// Contents of a backup's `MANIFEST`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    /// [`BACKUP_FORMAT_VERSION`] the backup was written with.
+    pub format_version: u32,
+    /// `game:meta:version` of the backed-up world.
+    pub schema_version: u32,
+    /// Unix time the backup was taken.
+    pub created: u64,
+    /// One record per section file.
+    pub sections: Vec<SectionRecord>,
+}
+
+impl Manifest {
+    /// Total number of keys over all sections.
+    pub fn total_keys(&self) -> usize {
+        self.sections.iter().map(|section| section.keys).sum()
+    }
+}
+
+/// Finds a backup or restore command in the server's command line.
+///
+/// # Arguments
+///
+/// * `args` - Command-line arguments without the program name.
+///
+/// # Returns
+///
+/// * `Ok(Some(command))` when the first argument is `backup` or `restore`,
+///   `Ok(None)` for a normal server start, or an error when the directory
+///   is missing.
+pub fn parse_command(args: &[String]) -> Result<Option<BackupCommand>, String> {
+    let Some(sub) = args.first() else {
+        return Ok(None);
+    };
+    if sub != BACKUP_COMMAND && sub != RESTORE_COMMAND {
+        return Ok(None);
+    }
+    let dir = match args.get(1) {
+        Some(dir) if !dir.starts_with("--") => PathBuf::from(dir),
+        _ => return Err(format!("Usage: server {} <dir>", sub)),
+    };
+    Ok(Some(if sub == BACKUP_COMMAND {
+        BackupCommand::Backup(dir)
+    } else {
+        BackupCommand::Restore(dir)
+    }))
+}
+
+/// Connects to KeyDB and runs `command`.
+///
+/// # Arguments
+///
+/// * `command` - Parsed command.
+///
+/// # Returns
+///
+/// * A one-line summary for the operator, or an error.
+pub fn run(command: &BackupCommand) -> Result<String, String> {
+    let mut con = connection::connect()?;
+    match command {
+        BackupCommand::Backup(dir) => {
+            let manifest = backup(&mut con, dir)?;
+            Ok(format!(
+                "Backed up {} keys in {} sections to {}.",
+                manifest.total_keys(),
+                manifest.sections.len(),
+                dir.display()
+            ))
+        }
+        BackupCommand::Restore(dir) => {
+            let manifest = restore(&mut con, dir)?;
+            Ok(format!(
+                "Restored {} keys in {} sections from {} (taken at {}).",
+                manifest.total_keys(),
+                manifest.sections.len(),
+                dir.display(),
+                manifest.created
+            ))
+        }
+    }
+}
+
+/// Dumps every section from KeyDB into `dir`.
+///
+/// # Arguments
+///
+/// * `con` - Open KeyDB connection.
+/// * `dir` - Backup directory; created if missing, refused if not empty.
+///
+/// # Returns
+///
+/// * The manifest that was written.
+pub fn backup(con: &mut Connection, dir: &Path) -> Result<Manifest, String> {
+    let schema_version: Option<u32> = redis::cmd("GET")
+        .arg(SCHEMA_VERSION_KEY)
+        .query(con)
+        .map_err(|e| format!("KeyDB GET {SCHEMA_VERSION_KEY}: {e}"))?;
+    let schema_version = schema_version
+        .ok_or_else(|| format!("KeyDB holds no game data ({SCHEMA_VERSION_KEY} is not set)"))?;
+
+    fs::create_dir_all(dir).map_err(|e| format!("Create {}: {e}", dir.display()))?;
+    let not_empty = fs::read_dir(dir)
+        .map_err(|e| format!("Read {}: {e}", dir.display()))?
+        .next()
+        .is_some();
+    if not_empty {
+        return Err(format!("Backup directory {} is not empty", dir.display()));
+    }
+
+    let mut sections = Vec::with_capacity(SECTIONS.len());
+    for section in &SECTIONS {
+        let entries = read_section(con, section)?;
+        let file = format!("{}.bin", section.name);
+        let bytes = encode_section(&entries)?;
+        fs::write(dir.join(&file), &bytes).map_err(|e| format!("Write {file}: {e}"))?;
+        log::info!("Backed up {} keys of {}", entries.len(), section.name);
+        sections.push(SectionRecord {
+            name: section.name.to_owned(),
+            file,
+            keys: entries.len(),
+            checksum: checksum(&bytes),
+        });
+    }
+
+    let manifest = Manifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version,
+        created: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        sections,
+    };
+    let tmp = dir.join(format!("{MANIFEST_FILE}.tmp"));
+    fs::write(&tmp, format_manifest(&manifest)).map_err(|e| format!("Write manifest: {e}"))?;
+    fs::rename(&tmp, dir.join(MANIFEST_FILE)).map_err(|e| format!("Write manifest: {e}"))?;
+    Ok(manifest)
+}
+
+/// Reads and checks a whole backup without touching KeyDB.
+///
+/// # Arguments
+///
+/// * `dir` - Backup directory.
+///
+/// # Returns
+///
+/// * The manifest and the entries of every section, or an error naming the
+///   first file that is missing, corrupt or from another format version.
+pub fn read_backup(dir: &Path) -> Result<(Manifest, Vec<Vec<BackupEntry>>), String> {
+    let text = fs::read_to_string(dir.join(MANIFEST_FILE)).map_err(|e| {
+        format!(
+            "Read {}: {e} (incomplete backup?)",
+            dir.join(MANIFEST_FILE).display()
+        )
+    })?;
+    let manifest = parse_manifest(&text)?;
+    if manifest.format_version != BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format version {} is not supported (expected {})",
+            manifest.format_version, BACKUP_FORMAT_VERSION
+        ));
+    }
+
+    let mut sections = Vec::with_capacity(manifest.sections.len());
+    for record in &manifest.sections {
+        let section = SECTIONS
+            .iter()
+            .find(|section| section.name == record.name)
+            .ok_or_else(|| format!("Unknown backup section '{}'", record.name))?;
+        let bytes =
+            fs::read(dir.join(&record.file)).map_err(|e| format!("Read {}: {e}", record.file))?;
+        if checksum(&bytes) != record.checksum {
+            return Err(format!("Checksum mismatch in {}", record.file));
+        }
+        let entries = decode_section(&bytes).map_err(|e| format!("{}: {e}", record.file))?;
+        if entries.len() != record.keys {
+            return Err(format!(
+                "{} holds {} keys, the manifest says {}",
+                record.file,
+                entries.len(),
+                record.keys
+            ));
+        }
+        let kind_matches = entries.iter().all(|entry| match entry.value {
+            BackupValue::String(_) => section.kind == SectionKind::String,
+            BackupValue::Hash(_) => section.kind == SectionKind::Hash,
+        });
+        if !kind_matches {
+            return Err(format!("{} holds values of the wrong type", record.file));
+        }
+        sections.push(entries);
+    }
+    Ok((manifest, sections))
+}
+
+/// Replaces every section in KeyDB with the contents of a backup.
+///
+/// # Arguments
+///
+/// * `con` - Open KeyDB connection.
+/// * `dir` - Backup directory.
+///
+/// # Returns
+///
+/// * The restored manifest. On error KeyDB is unchanged.
+pub fn restore(con: &mut Connection, dir: &Path) -> Result<Manifest, String> {
+    let (manifest, sections) = read_backup(dir)?;
+    if manifest.schema_version != SNAPSHOT_SCHEMA_VERSION {
+        return Err(format!(
+            "Backup holds schema version {}, this server expects {}",
+            manifest.schema_version, SNAPSHOT_SCHEMA_VERSION
+        ));
+    }
+
+    let mut stale = Vec::new();
+    for section in &SECTIONS {
+        stale.extend(scan_section(con, section)?);
+    }
+
+    let mut pipeline = pipe();
+    pipeline.atomic();
+    for chunk in stale.chunks(BATCH_SIZE) {
+        pipeline.cmd("DEL").arg(chunk).ignore();
+    }
+    for entry in sections.iter().flatten() {
+        match &entry.value {
+            BackupValue::String(bytes) => {
+                pipeline.cmd("SET").arg(&entry.key).arg(bytes).ignore();
+            }
+            BackupValue::Hash(fields) if !fields.is_empty() => {
+                let cmd = pipeline.cmd("HSET").arg(&entry.key);
+                for (field, value) in fields {
+                    cmd.arg(field).arg(value);
+                }
+                cmd.ignore();
+            }
+            BackupValue::Hash(_) => {}
+        }
+    }
+    pipeline
+        .cmd("SET")
+        .arg(SCHEMA_VERSION_KEY)
+        .arg(manifest.schema_version)
+        .ignore();
+    pipeline
+        .query::<()>(con)
+        .map_err(|e| format!("KeyDB restore transaction: {e}"))?;
+    Ok(manifest)
+}
+
+/// Whether `key` is a transient patch queue, request or status key.
+pub fn is_transient_key(key: &str) -> bool {
+    key.split(':')
+        .nth(2)
+        .is_some_and(|part| part.starts_with("patch"))
+}
+
+/// Lists the keys of `section`, sorted and without transient keys.
+fn scan_section(con: &mut Connection, section: &Section) -> Result<Vec<String>, String> {
+    let mut keys = Vec::new();
+    for pattern in section.patterns {
+        let mut cursor = 0u64;
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(*pattern)
+                .arg("COUNT")
+                .arg(BATCH_SIZE)
+                .query(con)
+                .map_err(|e| format!("KeyDB SCAN {pattern}: {e}"))?;
+            keys.extend(batch.into_iter().filter(|key| !is_transient_key(key)));
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+    }
+    keys.sort();
+    keys.dedup();
+    Ok(keys)
+}
+
+/// Reads every key of `section` with its value.
+fn read_section(con: &mut Connection, section: &Section) -> Result<Vec<BackupEntry>, String> {
+    let keys = scan_section(con, section)?;
+    let mut entries = Vec::with_capacity(keys.len());
+    for batch in keys.chunks(BATCH_SIZE) {
+        let mut pipeline = pipe();
+        match section.kind {
+            SectionKind::String => {
+                for key in batch {
+                    pipeline.cmd("GET").arg(key);
+                }
+                let values: Vec<Option<Vec<u8>>> = pipeline
+                    .query(con)
+                    .map_err(|e| format!("KeyDB pipeline GET {}: {e}", section.name))?;
+                // Keys deleted since the scan are skipped.
+                entries.extend(batch.iter().zip(values).filter_map(|(key, value)| {
+                    Some(BackupEntry {
+                        key: key.clone(),
+                        value: BackupValue::String(value?),
+                    })
+                }));
+            }
+            SectionKind::Hash => {
+                for key in batch {
+                    pipeline.cmd("HGETALL").arg(key);
+                }
+                let values: Vec<HashMap<String, Vec<u8>>> = pipeline
+                    .query(con)
+                    .map_err(|e| format!("KeyDB pipeline HGETALL {}: {e}", section.name))?;
+                entries.extend(batch.iter().zip(values).filter_map(|(key, fields)| {
+                    if fields.is_empty() {
+                        return None;
+                    }
+                    let mut fields: Vec<(String, Vec<u8>)> = fields.into_iter().collect();
+                    fields.sort();
+                    Some(BackupEntry {
+                        key: key.clone(),
+                        value: BackupValue::Hash(fields),
+                    })
+                }));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// FNV-1a 64 over `bytes`, the checksum stored in the manifest.
+pub fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Encodes a section file: magic, format version, then the zstd-compressed
+/// entries.
+pub fn encode_section(entries: &[BackupEntry]) -> Result<Vec<u8>, String> {
+    let payload = bincode::encode_to_vec(entries, bincode::config::standard())
+        .map_err(|e| format!("Encode section: {e}"))?;
+    let compressed = zstd::encode_all(payload.as_slice(), SECTION_ZSTD_LEVEL)
+        .map_err(|e| format!("Compress section: {e}"))?;
+    let mut bytes = Vec::with_capacity(8 + compressed.len());
+    bytes.extend_from_slice(&SECTION_MAGIC);
+    bytes.extend_from_slice(&BACKUP_FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&compressed);
+    Ok(bytes)
+}
+
+/// Decodes a section file written by [`encode_section`].
+pub fn decode_section(bytes: &[u8]) -> Result<Vec<BackupEntry>, String> {
+    if bytes.len() < 8 || bytes[..4] != SECTION_MAGIC {
+        return Err("not a backup section file".to_owned());
+    }
+    let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    if version != BACKUP_FORMAT_VERSION {
+        return Err(format!("section format version {version} is not supported"));
+    }
+    let payload = zstd::decode_all(&bytes[8..]).map_err(|e| format!("decompress: {e}"))?;
+    let (entries, _) = bincode::decode_from_slice(&payload, bincode::config::standard())
+        .map_err(|e| format!("decode: {e}"))?;
+    Ok(entries)
+}
+
+/// Renders a manifest as text, one `section` line per file.
+pub fn format_manifest(manifest: &Manifest) -> String {
+    let mut text = format!(
+        "{MANIFEST_HEADER} {}\nschema {}\ncreated {}\n",
+        manifest.format_version, manifest.schema_version, manifest.created
+    );
+    for section in &manifest.sections {
+        text.push_str(&format!(
+            "section {} {} {} {:016x}\n",
+            section.name, section.file, section.keys, section.checksum
+        ));
+    }
+    text
+}
+
+/// Parses a manifest written by [`format_manifest`].
+pub fn parse_manifest(text: &str) -> Result<Manifest, String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let mut header = |name: &str| -> Result<u64, String> {
+        let line = lines
+            .next()
+            .ok_or_else(|| format!("manifest is missing '{name}'"))?;
+        match line.split_once(' ') {
+            Some((key, value)) if key == name => value
+                .trim()
+                .parse()
+                .map_err(|_| format!("manifest has a malformed '{name}' line")),
+            _ => Err(format!("manifest line '{line}' is not '{name}'")),
+        }
+    };
+    let format_version = header(MANIFEST_HEADER)? as u32;
+    let schema_version = header("schema")? as u32;
+    let created = header("created")?;
+
+    let sections = lines
+        .map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let malformed = || format!("malformed manifest line '{line}'");
+            let ["section", name, file, keys, checksum] = parts[..] else {
+                return Err(malformed());
+            };
+            if file.contains(['/', '\\']) {
+                return Err(malformed());
+            }
+            Ok(SectionRecord {
+                name: name.to_owned(),
+                file: file.to_owned(),
+                keys: keys.parse().map_err(|_| malformed())?,
+                checksum: u64::from_str_radix(checksum, 16).map_err(|_| malformed())?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Manifest {
+        format_version,
+        schema_version,
+        created,
+        sections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn command_is_only_taken_from_the_first_argument() {
+        assert_eq!(parse_command(&args(&[])), Ok(None));
+        assert_eq!(
+            parse_command(&args(&["--deterministic", "backup"])),
+            Ok(None)
+        );
+        assert_eq!(
+            parse_command(&args(&["backup", "/tmp/b"])),
+            Ok(Some(BackupCommand::Backup(PathBuf::from("/tmp/b"))))
+        );
+        assert_eq!(
+            parse_command(&args(&["restore", "b"])),
+            Ok(Some(BackupCommand::Restore(PathBuf::from("b"))))
+        );
+        assert!(parse_command(&args(&["restore"])).is_err());
+    }
+
+    #[test]
+    fn patch_keys_are_transient() {
+        assert!(is_transient_key("game:map:patch_queue"));
+        assert!(is_transient_key("game:char:patch_status:abc"));
+        assert!(!is_transient_key("game:map:12:34"));
+        assert!(!is_transient_key("game:global"));
+        assert!(!is_transient_key("game:script_flag:12:patchwork"));
+    }
+
+    #[test]
+    fn manifest_round_trips() {
+        let manifest = Manifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            schema_version: 2,
+            created: 1_700_000_000,
+            sections: vec![SectionRecord {
+                name: "map".to_owned(),
+                file: "map.bin".to_owned(),
+                keys: 1_048_576,
+                checksum: 0xdead_beef_0000_0001,
+            }],
+        };
+        assert_eq!(parse_manifest(&format_manifest(&manifest)), Ok(manifest));
+        assert!(parse_manifest("mag-backup 1\nschema 2\n").is_err());
+        assert!(
+            parse_manifest("mag-backup 1\nschema 2\ncreated 0\nsection map ../x 1 ff\n").is_err()
+        );
+    }
+
+    #[test]
+    fn section_round_trips_and_detects_corruption() {
+        let entries = vec![
+            BackupEntry {
+                key: "game:global".to_owned(),
+                value: BackupValue::String(vec![1, 2, 3]),
+            },
+            BackupEntry {
+                key: "game:guild:1".to_owned(),
+                value: BackupValue::Hash(vec![("name".to_owned(), b"Knights".to_vec())]),
+            },
+        ];
+        let mut bytes = encode_section(&entries).unwrap();
+        assert_eq!(decode_section(&bytes), Ok(entries));
+
+        let sum = checksum(&bytes);
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert_ne!(checksum(&bytes), sum);
+        bytes[4] = 9;
+        assert!(decode_section(&bytes).is_err());
+    }
+}
@@ -2,13 +2,19 @@ use core::ranks::points2rank;
 use core::traits::{Class, Sex, class_from_kindred, sex_from_kindred};
 use core::types::api::GameLoginTicketMetadata;
 use core::types::{Character, CharacterSummary};
+
+use super::write_behind;
 use redis::Commands;
 use std::collections::HashMap;
 use std::env;
-use std::sync::Once;
+use std::sync::{Once, OnceLock};
+use std::time::{Duration, Instant};
 
 static LOAD_DOTENV_ONCE: Once = Once::new();
 
+/// URL from the server's `server.toml`, see [`set_configured_url`].
+static CONFIGURED_URL: OnceLock<String> = OnceLock::new();
+
 /// Load project `.env` exactly once for local utility binaries.
 ///
 /// Docker Compose reads `.env` for interpolation, but binaries run directly on
@@ -41,6 +47,9 @@ fn keydb_url_with_dotenv(load_dotenv: bool) -> String {
     if let Ok(url) = env::var("MAG_KEYDB_URL") {
         return url;
     }
+    if let Some(url) = CONFIGURED_URL.get() {
+        return url.clone();
+    }
     if let Ok(password) = env::var("KEYDB_PASSWORD") {
         // Percent-encode the password so special characters don't break the URL.
         let encoded: String = password
@@ -63,11 +72,13 @@ fn keydb_url_with_dotenv(load_dotenv: bool) -> String {
 ///
 /// 1. `MAG_KEYDB_URL` — used verbatim when set (covers all deployment
 ///    environments where the full URL including credentials is provided).
-/// 2. `KEYDB_PASSWORD` — when only the password is available (e.g. a
+/// 2. The `keydb.url` of the server's `server.toml`, once
+///    [`set_configured_url`] has been called.
+/// 3. `KEYDB_PASSWORD` — when only the password is available (e.g. a
 ///    developer sourcing the project `.env` file before running a local
 ///    utility against the docker-compose KeyDB), the URL is constructed as
 ///    `redis://:<password>@127.0.0.1:5556/`.
-/// 3. Hard-coded `redis://127.0.0.1:5556/` — unauthenticated local fallback
+/// 4. Hard-coded `redis://127.0.0.1:5556/` — unauthenticated local fallback
 ///    for development environments that run KeyDB without a password.
 ///
 /// # Returns
@@ -77,6 +88,18 @@ pub fn keydb_url() -> String {
     keydb_url_with_dotenv(!cfg!(test))
 }
 
+/// Sets the URL used when `MAG_KEYDB_URL` is not set.
+///
+/// Only the first call has an effect; the server calls it once at startup
+/// with the `keydb.url` of its configuration file.
+///
+/// # Arguments
+///
+/// * `url` - Connection URL.
+pub fn set_configured_url(url: String) {
+    let _ = CONFIGURED_URL.set(url);
+}
+
 /// Open a synchronous Redis/KeyDB connection.
 ///
 /// Uses the URL returned by [`keydb_url`].
@@ -94,6 +117,67 @@ pub fn connect() -> Result<redis::Connection, String> {
         .map_err(|err| format!("Failed to connect to KeyDB: {err}"))
 }
 
+/// Block until KeyDB accepts connections and has finished loading its
+/// dataset, or `timeout` elapses.
+///
+/// `PING` already succeeds while KeyDB is still loading its RDB snapshot, so
+/// readiness is taken from `INFO persistence` (`loading:0`), the same check
+/// the docker-compose healthcheck uses. Lets the server start alongside KeyDB
+/// in an orchestrator instead of crashing on the first connect.
+///
+/// # Arguments
+///
+/// * `timeout` - Maximum time to keep retrying.
+///
+/// # Returns
+///
+/// * `Ok(())` once KeyDB is ready.
+/// * `Err` with the last failure when `timeout` elapses first.
+pub fn wait_until_ready(timeout: Duration) -> Result<(), String> {
+    const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        let status = connect().and_then(|mut con| {
+            let info: String = redis::cmd("INFO")
+                .arg("persistence")
+                .query(&mut con)
+                .map_err(|err| format!("INFO persistence failed: {err}"))?;
+            if is_dataset_loaded(&info) {
+                Ok(())
+            } else {
+                Err("KeyDB is still loading its dataset".to_owned())
+            }
+        });
+        match status {
+            Ok(()) => return Ok(()),
+            Err(err) if Instant::now() + RETRY_DELAY < deadline => {
+                log::info!("Waiting for KeyDB: {err}");
+                std::thread::sleep(RETRY_DELAY);
+            }
+            Err(err) => {
+                return Err(format!(
+                    "KeyDB not ready after {}s: {err}",
+                    timeout.as_secs()
+                ));
+            }
+        }
+    }
+}
+
+/// Whether an `INFO persistence` reply reports the dataset as loaded.
+///
+/// # Arguments
+///
+/// * `info` - Raw `INFO persistence` text.
+///
+/// # Returns
+///
+/// * `true` when the reply contains `loading:0`.
+fn is_dataset_loaded(info: &str) -> bool {
+    info.lines().any(|line| line.trim() == "loading:0")
+}
+
 /// Load the current game MOTD value from KeyDB.
 ///
 /// Reads the `game:motd` key and returns its UTF-8 string payload.
@@ -236,7 +320,7 @@ fn derive_character_selection_metadata(character: &Character) -> Option<(Class,
     Some((class, sex, character.sprite, rank_index))
 }
 
-/// Writes selection metadata fields for an API-side character hash.
+/// Queues selection metadata fields for an API-side character hash.
 ///
 /// # Arguments
 ///
@@ -248,8 +332,8 @@ fn derive_character_selection_metadata(character: &Character) -> Option<(Class,
 ///
 /// # Returns
 ///
-/// * `Ok(())` on success.
-/// * `Err(String)` when connecting to KeyDB or updating the hash fails.
+/// * `Ok(())` once the fields are queued; the write itself happens on the
+///   [`write_behind`] flusher thread.
 fn set_character_selection_metadata(
     character_id: u64,
     class: Class,
@@ -257,23 +341,19 @@ fn set_character_selection_metadata(
     selection_sprite_id: u16,
     rank_index: u8,
 ) -> Result<(), String> {
-    let mut con = connect()?;
-    let key = format!("character:{}", character_id);
-    redis::cmd("HSET")
-        .arg(&key)
-        .arg("class")
-        .arg(class as u32)
-        .arg("sex")
-        .arg(sex as u32)
-        .arg("selection_sprite_id")
-        .arg(selection_sprite_id)
-        .arg("rank_index")
-        .arg(rank_index)
-        .query::<()>(&mut con)
-        .map_err(|err| format!("Failed to set character selection metadata: {err}"))
+    write_behind::queue(
+        format!("character:{}", character_id),
+        vec![
+            ("class", (class as u32).to_string()),
+            ("sex", (sex as u32).to_string()),
+            ("selection_sprite_id", selection_sprite_id.to_string()),
+            ("rank_index", rank_index.to_string()),
+        ],
+    );
+    Ok(())
 }
 
-/// Derives and writes selection metadata for a live gameplay character.
+/// Derives and queues selection metadata for a live gameplay character.
 ///
 /// # Arguments
 ///
@@ -282,8 +362,8 @@ fn set_character_selection_metadata(
 ///
 /// # Returns
 ///
-/// * `Ok(())` on success.
-/// * `Err(String)` when the metadata cannot be derived or the KeyDB update fails.
+/// * `Ok(())` once the fields are queued.
+/// * `Err(String)` when the metadata cannot be derived.
 pub fn sync_character_selection_metadata(
     character_id: u64,
     character: &Character,
@@ -295,7 +375,7 @@ pub fn sync_character_selection_metadata(
     set_character_selection_metadata(character_id, class, sex, selection_sprite_id, rank_index)
 }
 
-/// Persists the linked gameplay `server_id` for an API-side character hash.
+/// Queues the linked gameplay `server_id` for an API-side character hash.
 ///
 /// # Arguments
 ///
@@ -304,17 +384,36 @@ pub fn sync_character_selection_metadata(
 ///
 /// # Returns
 ///
-/// * `Ok(())` on success.
-/// * `Err(String)` when connecting to KeyDB or updating the hash fails.
+/// * `Ok(())` once the field is queued; the write itself happens on the
+///   [`write_behind`] flusher thread.
 pub fn set_character_server_id(character_id: u64, server_id: u32) -> Result<(), String> {
-    let mut con = connect()?;
-    let key = format!("character:{}", character_id);
-    redis::cmd("HSET")
-        .arg(&key)
-        .arg("server_id")
-        .arg(server_id)
-        .query::<()>(&mut con)
-        .map_err(|err| format!("Failed to set character server_id: {err}"))
+    write_behind::queue(
+        format!("character:{}", character_id),
+        vec![("server_id", server_id.to_string())],
+    );
+    Ok(())
+}
+
+/// Queues a player-edited description for the API-side character hash.
+///
+/// The login path re-syncs `Character::description` from this hash, so
+/// in-game profile edits must be mirrored here to survive a relog.
+///
+/// # Arguments
+///
+/// * `character_id` - API character ID whose `character:{id}` hash should be updated.
+/// * `description` - Already validated description text.
+///
+/// # Returns
+///
+/// * `Ok(())` once the field is queued; the write itself happens on the
+///   [`write_behind`] flusher thread.
+pub fn set_character_description(character_id: u64, description: &str) -> Result<(), String> {
+    write_behind::queue(
+        format!("character:{}", character_id),
+        vec![("description", description.to_owned())],
+    );
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -456,6 +555,15 @@ mod tests {
         assert_eq!(url, "redis://127.0.0.1:5556/");
     }
 
+    #[test]
+    fn is_dataset_loaded_reads_loading_flag() {
+        assert!(is_dataset_loaded(
+            "# Persistence\r\nloading:0\r\nrdb_changes_since_last_save:0\r\n"
+        ));
+        assert!(!is_dataset_loaded("# Persistence\r\nloading:1\r\n"));
+        assert!(!is_dataset_loaded(""));
+    }
+
     #[test]
     fn derive_character_selection_metadata_uses_live_values() {
         let character = Character {
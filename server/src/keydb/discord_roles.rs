@@ -0,0 +1,230 @@
+//! Background publisher for Discord role sync events.
+//!
+//! Every
+//! [`DISCORD_ROLE_INTERVAL_SECS`](core::discord_store::DISCORD_ROLE_INTERVAL_SECS)
+//! the tick thread hands this publisher the rank and guild of every online
+//! character that came in through the API. The publisher reads the set of
+//! Discord-linked accounts written by the API and queues a
+//! [`DiscordRoleEvent`] for each linked account whose character, rank or
+//! guild differs from what it last queued. An account is reported again
+//! after it is (re)linked or the server restarts.
+
+use core::discord_store::{
+    DISCORD_LINKED_KEY, DISCORD_ROLE_EVENTS_KEY, DiscordGuild, DiscordRoleEvent,
+    MAX_QUEUED_ROLE_EVENTS,
+};
+use redis::Commands;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Environment variable that disables publishing when set to
+/// `"true"`/`"1"`/`"yes"` (case-insensitive).
+pub const DISABLE_ENV: &str = "MAG_DISCORD_ROLES_DISABLED";
+
+/// Role state of all API-linked online characters.
+pub type RoleBatch = Vec<DiscordRoleEvent>;
+
+/// Last queued character name, rank and guild per account.
+type ReportedRoles = HashMap<u64, (String, String, DiscordGuild)>;
+
+/// Handle for the publisher thread.
+pub struct DiscordRolePublisher {
+    tx: Option<Sender<RoleBatch>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DiscordRolePublisher {
+    /// Spawn the publisher thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(publisher)` on success.
+    /// * `None` when disabled via [`DISABLE_ENV`] or when spawning fails.
+    pub fn spawn() -> Option<Self> {
+        if std::env::var(DISABLE_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+        {
+            log::info!(
+                "Discord role publisher disabled via {} env var",
+                DISABLE_ENV
+            );
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("discord-role-publisher".into())
+            .spawn(move || publisher_loop(rx))
+            .ok()?;
+
+        log::info!("Discord role publisher started");
+        Some(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Queue a batch for publishing without blocking.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - Role state taken on the tick thread.
+    pub fn publish(&self, batch: RoleBatch) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(batch);
+        }
+    }
+
+    /// Stop the publisher and join its thread.
+    pub fn shutdown(&mut self) {
+        // Dropping the sender ends the receive loop.
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DiscordRolePublisher {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+fn publisher_loop(rx: Receiver<RoleBatch>) {
+    let mut con: Option<redis::Connection> = None;
+    let mut reported = ReportedRoles::new();
+
+    while let Ok(mut batch) = rx.recv() {
+        // Only the newest batch matters; skip any that queued up meanwhile.
+        while let Ok(newer) = rx.try_recv() {
+            batch = newer;
+        }
+
+        if con.is_none() {
+            match super::connection::connect() {
+                Ok(connection) => con = Some(connection),
+                Err(error) => {
+                    log::warn!("discord role publisher: keydb connect failed: {}", error);
+                    continue;
+                }
+            }
+        }
+
+        let conn = con.as_mut().expect("connection just initialised");
+        if let Err(error) = publish_batch(conn, batch, &mut reported) {
+            log::warn!("discord role publisher: {}", error);
+            con = None;
+        }
+    }
+}
+
+fn publish_batch(
+    conn: &mut redis::Connection,
+    batch: RoleBatch,
+    reported: &mut ReportedRoles,
+) -> Result<(), String> {
+    let linked: HashSet<u64> = conn
+        .smembers(DISCORD_LINKED_KEY)
+        .map_err(|e| format!("SMEMBERS {} failed: {}", DISCORD_LINKED_KEY, e))?;
+
+    let changes = changed_roles(batch, &linked, reported);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut pipe = redis::pipe();
+    for event in &changes {
+        pipe.lpush(DISCORD_ROLE_EVENTS_KEY, event.to_bytes())
+            .ignore();
+    }
+    pipe.ltrim(
+        DISCORD_ROLE_EVENTS_KEY,
+        0,
+        MAX_QUEUED_ROLE_EVENTS as isize - 1,
+    )
+    .ignore();
+    pipe.query::<()>(conn)
+        .map_err(|e| format!("LPUSH {} failed: {}", DISCORD_ROLE_EVENTS_KEY, e))?;
+
+    for event in changes {
+        reported.insert(
+            event.account_id,
+            (event.character_name, event.rank, event.guild),
+        );
+    }
+    Ok(())
+}
+
+/// Picks the events of linked accounts whose role state changed.
+///
+/// Unlinked accounts are forgotten so they are reported again once relinked.
+/// `reported` is not updated for the returned events; the caller does that
+/// once they are queued.
+fn changed_roles(
+    batch: RoleBatch,
+    linked: &HashSet<u64>,
+    reported: &mut ReportedRoles,
+) -> Vec<DiscordRoleEvent> {
+    reported.retain(|account_id, _| linked.contains(account_id));
+    batch
+        .into_iter()
+        .filter(|event| linked.contains(&event.account_id))
+        .filter(|event| {
+            reported
+                .get(&event.account_id)
+                .is_none_or(|(name, rank, guild)| {
+                    *name != event.character_name || *rank != event.rank || *guild != event.guild
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(account_id: u64, rank: &str, guild: DiscordGuild) -> DiscordRoleEvent {
+        DiscordRoleEvent {
+            account_id,
+            character_name: format!("Char{}", account_id),
+            rank: rank.to_owned(),
+            guild,
+            unix_secs: 0,
+        }
+    }
+
+    #[test]
+    fn only_changes_of_linked_accounts_are_queued() {
+        let linked: HashSet<u64> = [1, 2].into_iter().collect();
+        let mut reported = ReportedRoles::new();
+        reported.insert(
+            1,
+            ("Char1".to_owned(), "Private".to_owned(), DiscordGuild::None),
+        );
+        reported.insert(
+            9,
+            ("Char9".to_owned(), "Private".to_owned(), DiscordGuild::None),
+        );
+
+        let batch = vec![
+            event(1, "Private", DiscordGuild::None),
+            event(2, "Private", DiscordGuild::None),
+            event(3, "Private", DiscordGuild::None),
+        ];
+        let changed: Vec<u64> = changed_roles(batch, &linked, &mut reported)
+            .iter()
+            .map(|e| e.account_id)
+            .collect();
+        assert_eq!(changed, vec![2]);
+        // Account 9 was unlinked and is forgotten.
+        assert!(!reported.contains_key(&9));
+
+        let batch = vec![event(1, "Private", DiscordGuild::PurpleOfHonor)];
+        assert_eq!(changed_roles(batch, &linked, &mut reported).len(), 1);
+    }
+}
@@ -0,0 +1,311 @@
+//! Background publisher for the public economy report.
+//!
+//! Every [`ECONOMY_INTERVAL_SECS`] the tick thread hands this publisher the
+//! current merchant stock and the merchant trades made since the last update.
+//! The publisher owns the aggregated [`EconomyReport`]: it folds the trades
+//! into hourly price buckets, drops buckets older than
+//! [`PRICE_HISTORY_HOURS`] and writes the report to [`ECONOMY_REPORT_KEY`].
+//!
+//! The key is written without a TTL and read back when the publisher starts,
+//! so price history survives server restarts.
+
+use core::economy_store::{
+    ECONOMY_REPORT_KEY, EconomyReport, ItemPriceHistory, MAX_RECENT_TRADES, MerchantListing,
+    PRICE_HISTORY_HOURS, PriceBucket, Trade,
+};
+use redis::Commands;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Environment variable that disables publishing when set to
+/// `"true"`/`"1"`/`"yes"` (case-insensitive).
+pub const DISABLE_ENV: &str = "MAG_ECONOMY_DISABLED";
+
+const SECS_PER_HOUR: u64 = 3600;
+
+/// One update from the tick thread.
+pub struct EconomyUpdate {
+    /// Wall-clock time of the update (seconds since Unix epoch).
+    pub generated_at: u64,
+    /// Current merchant stock.
+    pub listings: Vec<MerchantListing>,
+    /// Trades made since the previous update, oldest first.
+    pub trades: Vec<Trade>,
+}
+
+/// Handle for the publisher thread.
+pub struct EconomyPublisher {
+    tx: Option<Sender<EconomyUpdate>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl EconomyPublisher {
+    /// Spawn the publisher thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(publisher)` on success.
+    /// * `None` when disabled via [`DISABLE_ENV`] or when spawning fails.
+    pub fn spawn() -> Option<Self> {
+        if std::env::var(DISABLE_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+        {
+            log::info!("Economy publisher disabled via {} env var", DISABLE_ENV);
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("economy-publisher".into())
+            .spawn(move || publisher_loop(rx))
+            .ok()?;
+
+        log::info!("Economy publisher started");
+        Some(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Queue an update for publishing without blocking.
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - Listings and trades collected on the tick thread.
+    pub fn publish(&self, update: EconomyUpdate) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(update);
+        }
+    }
+
+    /// Stop the publisher and join its thread.
+    pub fn shutdown(&mut self) {
+        // Dropping the sender ends the receive loop.
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for EconomyPublisher {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+fn publisher_loop(rx: Receiver<EconomyUpdate>) {
+    let mut con: Option<redis::Connection> = None;
+    // Loaded lazily so a KeyDB outage at startup does not wipe the history.
+    let mut report: Option<EconomyReport> = None;
+    // Trades received while the history could not be loaded yet.
+    let mut pending: Vec<Trade> = Vec::new();
+
+    while let Ok(mut update) = rx.recv() {
+        // Every update carries trades, so merge queued ones instead of
+        // skipping them.
+        while let Ok(newer) = rx.try_recv() {
+            update.trades.extend(newer.trades);
+            update.generated_at = newer.generated_at;
+            update.listings = newer.listings;
+        }
+        pending.append(&mut update.trades);
+
+        if con.is_none() {
+            match super::connection::connect() {
+                Ok(connection) => con = Some(connection),
+                Err(error) => {
+                    log::warn!("economy publisher: keydb connect failed: {}", error);
+                    continue;
+                }
+            }
+        }
+        let conn = con.as_mut().expect("connection just initialised");
+
+        if report.is_none() {
+            match load_report(conn) {
+                Ok(loaded) => report = Some(loaded),
+                Err(error) => {
+                    log::warn!("economy publisher: {}", error);
+                    con = None;
+                    continue;
+                }
+            }
+        }
+        let report = report.as_mut().expect("report just loaded");
+
+        for trade in pending.drain(..) {
+            record_trade(report, trade);
+        }
+        report.generated_at = update.generated_at;
+        report.listings = update.listings;
+        prune_history(report, update.generated_at);
+
+        if let Err(error) = conn.set::<_, _, ()>(ECONOMY_REPORT_KEY, report.to_bytes()) {
+            log::warn!(
+                "economy publisher: SET {} failed: {}",
+                ECONOMY_REPORT_KEY,
+                error
+            );
+            con = None;
+        }
+    }
+}
+
+/// Read the previously published report, or start an empty one.
+fn load_report(conn: &mut redis::Connection) -> Result<EconomyReport, String> {
+    let bytes: Option<Vec<u8>> = conn
+        .get(ECONOMY_REPORT_KEY)
+        .map_err(|e| format!("GET {} failed: {}", ECONOMY_REPORT_KEY, e))?;
+    match bytes {
+        Some(bytes) => Ok(EconomyReport::from_bytes(&bytes).unwrap_or_else(|| {
+            log::warn!(
+                "economy publisher: discarding undecodable {}",
+                ECONOMY_REPORT_KEY
+            );
+            EconomyReport::default()
+        })),
+        None => Ok(EconomyReport::default()),
+    }
+}
+
+/// Add one trade to the recent trades and its item's hourly bucket.
+///
+/// # Arguments
+///
+/// * `report` - Report to update.
+/// * `trade` - The completed trade.
+fn record_trade(report: &mut EconomyReport, trade: Trade) {
+    let hour_start = trade.unix_secs - trade.unix_secs % SECS_PER_HOUR;
+
+    let position = report.price_history.binary_search_by(|h| {
+        (h.template_id, h.side as u8).cmp(&(trade.template_id, trade.side as u8))
+    });
+    let history = match position {
+        Ok(index) => &mut report.price_history[index],
+        Err(index) => {
+            report.price_history.insert(
+                index,
+                ItemPriceHistory {
+                    template_id: trade.template_id,
+                    item_name: trade.item_name.clone(),
+                    side: trade.side,
+                    buckets: Vec::new(),
+                },
+            );
+            &mut report.price_history[index]
+        }
+    };
+    history.item_name.clone_from(&trade.item_name);
+
+    match history.buckets.last_mut() {
+        Some(bucket) if bucket.hour_start == hour_start => {
+            bucket.trades += 1;
+            bucket.min_price = bucket.min_price.min(trade.price);
+            bucket.max_price = bucket.max_price.max(trade.price);
+            bucket.total_price += i64::from(trade.price);
+        }
+        _ => history.buckets.push(PriceBucket {
+            hour_start,
+            trades: 1,
+            min_price: trade.price,
+            max_price: trade.price,
+            total_price: i64::from(trade.price),
+        }),
+    }
+
+    report.recent_trades.insert(0, trade);
+    report.recent_trades.truncate(MAX_RECENT_TRADES);
+}
+
+/// Drop price buckets older than [`PRICE_HISTORY_HOURS`] and items left
+/// without any.
+///
+/// # Arguments
+///
+/// * `report` - Report to prune.
+/// * `now` - Current wall-clock time in seconds since Unix epoch.
+fn prune_history(report: &mut EconomyReport, now: u64) {
+    let cutoff = now.saturating_sub(PRICE_HISTORY_HOURS * SECS_PER_HOUR);
+    for history in report.price_history.iter_mut() {
+        history
+            .buckets
+            .retain(|bucket| bucket.hour_start + SECS_PER_HOUR > cutoff);
+    }
+    report
+        .price_history
+        .retain(|history| !history.buckets.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::economy_store::TradeSide;
+
+    fn trade(unix_secs: u64, template_id: u32, side: TradeSide, price: i32) -> Trade {
+        Trade {
+            unix_secs,
+            template_id,
+            item_name: format!("Item {}", template_id),
+            merchant: "Jefferson".to_owned(),
+            side,
+            price,
+        }
+    }
+
+    #[test]
+    fn trades_are_bucketed_per_item_side_and_hour() {
+        let mut report = EconomyReport::default();
+        let hour = 1_700_002_800; // Multiple of 3600.
+        record_trade(&mut report, trade(hour + 10, 59, TradeSide::Buy, 900));
+        record_trade(&mut report, trade(hour + 20, 59, TradeSide::Buy, 1100));
+        record_trade(&mut report, trade(hour + 30, 59, TradeSide::Sell, 200));
+        record_trade(&mut report, trade(hour + 40, 12, TradeSide::Buy, 50));
+        record_trade(&mut report, trade(hour + 3600, 59, TradeSide::Buy, 1000));
+
+        let keys: Vec<_> = report
+            .price_history
+            .iter()
+            .map(|h| (h.template_id, h.side, h.buckets.len()))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (12, TradeSide::Buy, 1),
+                (59, TradeSide::Buy, 2),
+                (59, TradeSide::Sell, 1)
+            ]
+        );
+        let first = &report.price_history[1].buckets[0];
+        assert_eq!(first.hour_start, hour);
+        assert_eq!(
+            (first.trades, first.min_price, first.max_price),
+            (2, 900, 1100)
+        );
+        assert_eq!(first.average_price(), 1000);
+        assert_eq!(report.recent_trades[0].unix_secs, hour + 3600);
+    }
+
+    #[test]
+    fn pruning_drops_old_buckets_and_empty_items() {
+        let mut report = EconomyReport::default();
+        let hour = 1_700_002_800;
+        record_trade(&mut report, trade(hour, 12, TradeSide::Buy, 50));
+        record_trade(&mut report, trade(hour, 59, TradeSide::Buy, 900));
+        record_trade(&mut report, trade(hour + 7200, 59, TradeSide::Buy, 950));
+
+        prune_history(
+            &mut report,
+            hour + (PRICE_HISTORY_HOURS + 1) * SECS_PER_HOUR + 60,
+        );
+        assert_eq!(report.price_history.len(), 1);
+        assert_eq!(report.price_history[0].template_id, 59);
+        assert_eq!(report.price_history[0].buckets.len(), 1);
+        assert_eq!(report.price_history[0].buckets[0].hour_start, hour + 7200);
+        // Recent trades are not affected by pruning.
+        assert_eq!(report.recent_trades.len(), 3);
+    }
+}
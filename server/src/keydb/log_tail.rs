@@ -0,0 +1,166 @@
+//! Background publisher that mirrors server log records into KeyDB.
+//!
+//! Records reach this module through a [`LogTailAppender`] installed in the
+//! global logger. The publisher thread numbers them, appends them to
+//! [`LOG_TAIL_KEY`] and trims the list to [`LOG_TAIL_MAX_LEN`] entries so the
+//! admin API can tail recent logs without access to the host.
+
+use core::log_tail_store::{LOG_TAIL_KEY, LOG_TAIL_MAX_LEN, LogTailAppender, LogTailRecord};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Environment variable that disables the log tail entirely when set to
+/// `"true"`/`"1"`/`"yes"` (case-insensitive).
+pub const DISABLE_ENV: &str = "MAG_ADMIN_LOG_TAIL_DISABLED";
+
+/// Records buffered between the logger and the publisher before new ones are
+/// dropped.
+const CHANNEL_CAPACITY: usize = 4096;
+
+/// Maximum number of records written per `RPUSH` round-trip.
+const PUBLISH_BATCH: usize = 256;
+
+/// How long the publisher waits for a record before checking for shutdown.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Delay before retrying after a failed KeyDB connect.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Handle for the publisher thread.
+pub struct LogTailPublisher {
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl LogTailPublisher {
+    /// Create the logger appender and spawn the publisher thread feeding
+    /// KeyDB from it.
+    ///
+    /// Call this before the global logger is initialised and pass the
+    /// returned appender to [`core::initialize_logger_with_tail`].
+    ///
+    /// # Returns
+    ///
+    /// * `Some((appender, publisher))` on success.
+    /// * `None` when disabled via [`DISABLE_ENV`] or when spawning fails.
+    pub fn spawn() -> Option<(LogTailAppender, Self)> {
+        if std::env::var(DISABLE_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+        {
+            return None;
+        }
+
+        let (appender, rx) = LogTailAppender::channel(CHANNEL_CAPACITY, module_path!());
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_thread = Arc::clone(&shutdown);
+
+        let handle = thread::Builder::new()
+            .name("log-tail-publisher".into())
+            .spawn(move || publisher_loop(rx, shutdown_thread))
+            .ok()?;
+
+        Some((
+            appender,
+            Self {
+                shutdown,
+                handle: Some(handle),
+            },
+        ))
+    }
+
+    /// Signal the publisher to stop and join its thread.
+    pub fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for LogTailPublisher {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+fn publisher_loop(rx: Receiver<LogTailRecord>, shutdown: Arc<AtomicBool>) {
+    let mut con: Option<redis::Connection> = None;
+    let mut next_seq: u64 = 1;
+    let mut connect_failed = false;
+
+    while !shutdown.load(Ordering::SeqCst) {
+        let first = match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(record) => record,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        let mut batch = vec![first];
+        batch.extend(rx.try_iter().take(PUBLISH_BATCH - 1));
+        for record in &mut batch {
+            record.seq = next_seq;
+            next_seq += 1;
+        }
+
+        if con.is_none() {
+            match super::connection::connect() {
+                Ok(connection) => {
+                    if connect_failed {
+                        log::info!("log tail: keydb connection restored");
+                    }
+                    connect_failed = false;
+                    con = Some(connection);
+                }
+                Err(error) => {
+                    // Only report the transition; the records are dropped.
+                    if !connect_failed {
+                        log::warn!("log tail: keydb connect failed: {}", error);
+                    }
+                    connect_failed = true;
+                    thread::sleep(RECONNECT_DELAY);
+                    continue;
+                }
+            }
+        }
+
+        let conn = con.as_mut().expect("connection just initialised");
+        if let Err(error) = publish_batch(conn, &batch) {
+            log::warn!("log tail: publish failed: {}", error);
+            con = None;
+        }
+    }
+}
+
+/// Append a batch of records to [`LOG_TAIL_KEY`] and trim the list.
+///
+/// # Arguments
+///
+/// * `conn` - Live KeyDB connection.
+/// * `batch` - Numbered records, oldest first.
+///
+/// # Returns
+///
+/// * `Ok(())` on success.
+/// * `Err(message)` on KeyDB failure.
+fn publish_batch(conn: &mut redis::Connection, batch: &[LogTailRecord]) -> Result<(), String> {
+    let payloads: Vec<Vec<u8>> = batch.iter().map(LogTailRecord::to_bytes).collect();
+    redis::pipe()
+        .cmd("RPUSH")
+        .arg(LOG_TAIL_KEY)
+        .arg(payloads)
+        .ignore()
+        .cmd("LTRIM")
+        .arg(LOG_TAIL_KEY)
+        .arg(-(LOG_TAIL_MAX_LEN as i64))
+        .arg(-1)
+        .ignore()
+        .query::<()>(conn)
+        .map_err(|error| format!("RPUSH {}: {}", LOG_TAIL_KEY, error))
+}
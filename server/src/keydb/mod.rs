@@ -6,12 +6,21 @@
 //! * [`connection`] — synchronous client connection helper.
 //! * [`store`] — load/save functions for every persisted entity type.
 //! * [`snapshot`] — portable, versioned `.wsnap` world-snapshot format.
+//! * [`backup`] — `server backup` / `server restore` of the raw game keys.
 //! * [`background_saver`] — rotating saver thread that flushes dirty
 //!   game data back to KeyDB on the ~12 minute schedule documented in
 //!   `docs/server/DESIGN.md`.
 //! * [`template_reload`], [`text_reload`], [`map_patch`], [`item_patch`],
 //!   [`character_patch`] — pub/sub watchers that ingest live patches
 //!   published to KeyDB by the admin tooling.
+//! * [`discord_roles`] — publisher for Discord role sync events.
+//! * [`economy`] — publisher for the public merchant economy report.
+//! * [`log_tail`] — publisher mirroring log records for the admin log tail.
+//! * [`online_roster`] — publisher for the online player roster.
+//! * [`stream_overlay`] — publisher for opted-in stream overlay snapshots.
+//! * [`tick_profile`] — publisher for tick cost attribution reports.
+//! * [`write_behind`] — queue and flusher thread for hash writes issued
+//!   during the tick.
 
 /// Synchronous KeyDB/Redis connection helper.
 pub mod connection;
@@ -22,6 +31,9 @@ pub mod store;
 /// Portable, versioned world-snapshot format (`.wsnap`).
 pub mod snapshot;
 
+/// Checksummed backup and atomic restore of every game key.
+pub mod backup;
+
 /// Background saver thread that flushes dirty data to KeyDB on a rotating
 /// schedule for crash resilience.
 pub mod background_saver;
@@ -35,17 +47,38 @@ pub mod ban_action;
 /// KeyDB pub/sub watcher for character-template hot reloads.
 pub mod character_patch;
 
+/// Background publisher for Discord role sync events.
+pub mod discord_roles;
+
+/// Background publisher for the public merchant economy report.
+pub mod economy;
+
 /// KeyDB pub/sub watcher for item-template hot reloads.
 pub mod item_patch;
 
+/// Background publisher mirroring server log records for the admin log tail.
+pub mod log_tail;
+
 /// KeyDB pub/sub watcher for static-map hot patches.
 pub mod map_patch;
 
+/// Background publisher for the online player roster.
+pub mod online_roster;
+
+/// Background publisher for opted-in stream overlay snapshots.
+pub mod stream_overlay;
+
 /// KeyDB pub/sub watcher for template (item + character) reload requests.
 pub mod template_reload;
 
 /// KeyDB watcher for externally managed text-data reload requests.
 pub mod text_reload;
 
+/// Background publisher for tick cost attribution reports.
+pub mod tick_profile;
+
 /// KeyDB watcher for admin-issued world actions.
 pub mod world_action;
+
+/// Write-behind queue for KeyDB hash writes issued during the tick.
+pub mod write_behind;
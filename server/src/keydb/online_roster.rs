@@ -0,0 +1,116 @@
+//! Background publisher for the online player roster.
+//!
+//! The tick thread hands an [`OnlineRoster`] to this publisher every
+//! [`ONLINE_ROSTER_INTERVAL_SECS`](core::online_roster_store::ONLINE_ROSTER_INTERVAL_SECS),
+//! and the publisher writes the latest one to [`ONLINE_ROSTER_KEY`] for the
+//! admin API. The key expires if the server stops refreshing it, so a
+//! stopped server does not keep reporting stale sessions.
+
+use core::online_roster_store::{ONLINE_ROSTER_INTERVAL_SECS, ONLINE_ROSTER_KEY, OnlineRoster};
+use redis::Commands;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Environment variable that disables publishing when set to
+/// `"true"`/`"1"`/`"yes"` (case-insensitive).
+pub const DISABLE_ENV: &str = "MAG_ADMIN_ONLINE_ROSTER_DISABLED";
+
+/// Handle for the publisher thread.
+pub struct OnlineRosterPublisher {
+    tx: Option<Sender<OnlineRoster>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl OnlineRosterPublisher {
+    /// Spawn the publisher thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(publisher)` on success.
+    /// * `None` when disabled via [`DISABLE_ENV`] or when spawning fails.
+    pub fn spawn() -> Option<Self> {
+        if std::env::var(DISABLE_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+        {
+            log::info!(
+                "Online roster publisher disabled via {} env var",
+                DISABLE_ENV
+            );
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("online-roster-publisher".into())
+            .spawn(move || publisher_loop(rx))
+            .ok()?;
+
+        log::info!("Online roster publisher started");
+        Some(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Queue a roster for publishing without blocking.
+    ///
+    /// # Arguments
+    ///
+    /// * `roster` - Roster taken on the tick thread.
+    pub fn publish(&self, roster: OnlineRoster) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(roster);
+        }
+    }
+
+    /// Stop the publisher and join its thread.
+    pub fn shutdown(&mut self) {
+        // Dropping the sender ends the receive loop.
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for OnlineRosterPublisher {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+fn publisher_loop(rx: Receiver<OnlineRoster>) {
+    let mut con: Option<redis::Connection> = None;
+    // Outlive a few missed refreshes before the roster disappears.
+    let ttl = ONLINE_ROSTER_INTERVAL_SECS * 3;
+
+    while let Ok(mut roster) = rx.recv() {
+        // Only the newest roster matters; skip any that queued up meanwhile.
+        while let Ok(newer) = rx.try_recv() {
+            roster = newer;
+        }
+
+        if con.is_none() {
+            match super::connection::connect() {
+                Ok(connection) => con = Some(connection),
+                Err(error) => {
+                    log::warn!("online roster publisher: keydb connect failed: {}", error);
+                    continue;
+                }
+            }
+        }
+
+        let conn = con.as_mut().expect("connection just initialised");
+        if let Err(error) = conn.set_ex::<_, _, ()>(ONLINE_ROSTER_KEY, roster.to_bytes(), ttl) {
+            log::warn!(
+                "online roster publisher: SETEX {} failed: {}",
+                ONLINE_ROSTER_KEY,
+                error
+            );
+            con = None;
+        }
+    }
+}
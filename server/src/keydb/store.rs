@@ -232,7 +232,7 @@ pub fn load_map(con: &mut Connection) -> Result<Vec<core::types::Map>, String> {
 /// # Returns
 ///
 /// * Number of tiles that are not [`core::types::Map::default`].
-fn count_non_default_map_tiles(map: &[core::types::Map]) -> usize {
+pub(crate) fn count_non_default_map_tiles(map: &[core::types::Map]) -> usize {
     let empty = core::types::Map::default();
     map.iter().filter(|tile| **tile != empty).count()
 }
@@ -352,6 +352,40 @@ pub fn save_indexed_entities_range<T: Encode>(
     Ok(())
 }
 
+/// Persist individual entities under `{prefix}{idx}` keys.
+///
+/// Used by player checkpoints, which write scattered slots rather than a
+/// contiguous range.
+///
+/// # Arguments
+///
+/// * `con`      - An open Redis/KeyDB connection.
+/// * `prefix`   - Key prefix including trailing colon (e.g. `"game:char:"`).
+/// * `entities` - `(index, entity)` pairs to persist.
+///
+/// # Returns
+///
+/// * `Ok(())` on success, or an `Err` describing the pipeline or encode failure.
+pub fn save_indexed_entities_sparse<T: Encode>(
+    con: &mut Connection,
+    prefix: &str,
+    entities: &[(usize, T)],
+) -> Result<(), String> {
+    for batch in entities.chunks(PIPELINE_BATCH_SIZE) {
+        let mut pipeline = pipe();
+        for (idx, entity) in batch {
+            pipeline
+                .cmd("SET")
+                .arg(format!("{prefix}{idx}"))
+                .arg(encode(entity)?);
+        }
+        pipeline
+            .query::<()>(con)
+            .map_err(|e| format!("KeyDB pipeline SET {prefix}*: {e}"))?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 //  Public load/save API
 // ---------------------------------------------------------------------------
@@ -0,0 +1,186 @@
+//! Background publisher for stream overlay snapshots.
+//!
+//! The tick thread hands this publisher an [`OverlayStats`] for every online
+//! character that came in through the API, every
+//! [`OVERLAY_INTERVAL_SECS`](core::stream_overlay_store::OVERLAY_INTERVAL_SECS).
+//! The publisher reads the opt-in set written by the API and stores only the
+//! snapshots of opted-in characters, each under its own expiring key, so an
+//! overlay goes blank shortly after the character logs out.
+
+use core::stream_overlay_store::{
+    OVERLAY_INTERVAL_SECS, OVERLAY_OPT_IN_KEY, OverlayStats, overlay_stats_key,
+};
+use redis::Commands;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Environment variable that disables publishing when set to
+/// `"true"`/`"1"`/`"yes"` (case-insensitive).
+pub const DISABLE_ENV: &str = "MAG_STREAM_OVERLAY_DISABLED";
+
+/// Snapshots of all API-linked online characters, keyed by API character id.
+pub type OverlayBatch = Vec<(u64, OverlayStats)>;
+
+/// Handle for the publisher thread.
+pub struct StreamOverlayPublisher {
+    tx: Option<Sender<OverlayBatch>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StreamOverlayPublisher {
+    /// Spawn the publisher thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(publisher)` on success.
+    /// * `None` when disabled via [`DISABLE_ENV`] or when spawning fails.
+    pub fn spawn() -> Option<Self> {
+        if std::env::var(DISABLE_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+        {
+            log::info!(
+                "Stream overlay publisher disabled via {} env var",
+                DISABLE_ENV
+            );
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("stream-overlay-publisher".into())
+            .spawn(move || publisher_loop(rx))
+            .ok()?;
+
+        log::info!("Stream overlay publisher started");
+        Some(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Queue a batch of snapshots for publishing without blocking.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - Snapshots taken on the tick thread.
+    pub fn publish(&self, batch: OverlayBatch) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(batch);
+        }
+    }
+
+    /// Stop the publisher and join its thread.
+    pub fn shutdown(&mut self) {
+        // Dropping the sender ends the receive loop.
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for StreamOverlayPublisher {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+fn publisher_loop(rx: Receiver<OverlayBatch>) {
+    let mut con: Option<redis::Connection> = None;
+    // Outlive a few missed refreshes before the overlay goes blank.
+    let ttl = OVERLAY_INTERVAL_SECS * 3;
+
+    while let Ok(mut batch) = rx.recv() {
+        // Only the newest batch matters; skip any that queued up meanwhile.
+        while let Ok(newer) = rx.try_recv() {
+            batch = newer;
+        }
+        if batch.is_empty() {
+            continue;
+        }
+
+        if con.is_none() {
+            match super::connection::connect() {
+                Ok(connection) => con = Some(connection),
+                Err(error) => {
+                    log::warn!("stream overlay publisher: keydb connect failed: {}", error);
+                    continue;
+                }
+            }
+        }
+
+        let conn = con.as_mut().expect("connection just initialised");
+        if let Err(error) = publish_batch(conn, batch, ttl) {
+            log::warn!("stream overlay publisher: {}", error);
+            con = None;
+        }
+    }
+}
+
+fn publish_batch(
+    conn: &mut redis::Connection,
+    batch: OverlayBatch,
+    ttl: u64,
+) -> Result<(), String> {
+    let opted_in: HashSet<u64> = conn
+        .smembers(OVERLAY_OPT_IN_KEY)
+        .map_err(|e| format!("SMEMBERS {} failed: {}", OVERLAY_OPT_IN_KEY, e))?;
+    if opted_in.is_empty() {
+        return Ok(());
+    }
+
+    let mut pipe = redis::pipe();
+    for (api_character_id, stats) in opted_snapshots(batch, &opted_in) {
+        pipe.set_ex(overlay_stats_key(api_character_id), stats.to_bytes(), ttl)
+            .ignore();
+    }
+    pipe.query::<()>(conn)
+        .map_err(|e| format!("overlay SETEX failed: {}", e))
+}
+
+/// Keeps only the snapshots of opted-in characters.
+fn opted_snapshots(
+    batch: OverlayBatch,
+    opted_in: &HashSet<u64>,
+) -> impl Iterator<Item = (u64, OverlayStats)> + '_ {
+    batch
+        .into_iter()
+        .filter(move |(api_character_id, _)| opted_in.contains(api_character_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(name: &str) -> OverlayStats {
+        OverlayStats {
+            generated_at: 0,
+            name: name.to_owned(),
+            rank: String::new(),
+            experience: 0,
+            hp: 0,
+            max_hp: 0,
+            endurance: 0,
+            max_endurance: 0,
+            mana: 0,
+            max_mana: 0,
+            area: String::new(),
+            recent_kills: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn only_opted_in_characters_are_published() {
+        let batch = vec![(7, stats("Ishtar")), (8, stats("Bob")), (9, stats("Eve"))];
+        let opted_in: HashSet<u64> = [7, 9, 42].into_iter().collect();
+
+        let names: Vec<String> = opted_snapshots(batch, &opted_in)
+            .map(|(_, stats)| stats.name)
+            .collect();
+        assert_eq!(names, vec!["Ishtar".to_owned(), "Eve".to_owned()]);
+    }
+}
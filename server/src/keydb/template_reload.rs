@@ -9,6 +9,13 @@
 //! drain pending requests, swap the affected slices in `GameState`, and
 //! write a status entry under
 //! [`core::template_store::reload_status_key`].
+//!
+//! Reloads can also be triggered from inside the server: the god command
+//! `#reload [items|chars]` and, on Unix, `SIGHUP` (see
+//! [`register_reload_signal`]) queue a [`ReloadRequest`] with a
+//! [`ReloadOrigin`] other than [`ReloadOrigin::Admin`], which the tick loop
+//! applies the same way but reports to the issuer or the log instead of a
+//! status key.
 
 use core::template_store::{self, RELOAD_REQUEST_KEY};
 use redis::Commands;
@@ -37,6 +44,17 @@ pub const STATUS_APPLIED: &str = "applied";
 /// Per-status TTL so stale status keys self-prune.
 const STATUS_TTL_SECS: u64 = 300;
 
+/// Where a reload request came from; decides how the outcome is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReloadOrigin {
+    /// Admin API; the outcome is written under the request's status key.
+    Admin,
+    /// `#reload` issued by this character, who is told the outcome.
+    Command(usize),
+    /// `SIGHUP` sent to the server process; the outcome is only logged.
+    Signal,
+}
+
 /// Drained reload request handed to the tick loop.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ReloadRequest {
@@ -46,6 +64,82 @@ pub struct ReloadRequest {
     pub reload_items: bool,
     /// Whether character templates should be reloaded.
     pub reload_characters: bool,
+    /// Who asked for the reload.
+    pub origin: ReloadOrigin,
+}
+
+impl ReloadRequest {
+    /// Build a request raised inside the server (god command or signal).
+    ///
+    /// # Arguments
+    ///
+    /// * `origin`            - Command issuer or signal.
+    /// * `reload_items`      - Whether item templates should be reloaded.
+    /// * `reload_characters` - Whether character templates should be reloaded.
+    ///
+    /// # Returns
+    ///
+    /// * A request whose id names its origin, for log correlation.
+    pub fn local(origin: ReloadOrigin, reload_items: bool, reload_characters: bool) -> Self {
+        let request_id = match origin {
+            ReloadOrigin::Admin => "admin".to_owned(),
+            ReloadOrigin::Command(cn) => format!("command:{}", cn),
+            ReloadOrigin::Signal => "sighup".to_owned(),
+        };
+        Self {
+            request_id,
+            reload_items,
+            reload_characters,
+            origin,
+        }
+    }
+}
+
+/// Parse the kinds argument of `#reload`.
+///
+/// # Arguments
+///
+/// * `arg` - `items`, `chars`/`characters`, `all`, or empty (all).
+///
+/// # Returns
+///
+/// * `Some((reload_items, reload_characters))` for a recognised argument.
+/// * `None` otherwise.
+pub fn parse_reload_kinds(arg: &str) -> Option<(bool, bool)> {
+    match arg.trim().to_ascii_lowercase().as_str() {
+        "" | "all" => Some((true, true)),
+        "items" | "item" => Some((true, false)),
+        "chars" | "characters" | "char" => Some((false, true)),
+        _ => None,
+    }
+}
+
+/// Install a `SIGHUP` handler that requests a full template reload.
+///
+/// # Returns
+///
+/// * `Some(flag)` set to `true` on every `SIGHUP`; the tick loop swaps it
+///   back to `false` and queues a [`ReloadOrigin::Signal`] request.
+/// * `None` when registration fails.
+#[cfg(unix)]
+pub fn register_reload_signal() -> Option<Arc<AtomicBool>> {
+    let flag = Arc::new(AtomicBool::new(false));
+    match signal_hook::flag::register(signal_hook::consts::SIGHUP, flag.clone()) {
+        Ok(_) => {
+            log::info!("SIGHUP reloads item and character templates from KeyDB");
+            Some(flag)
+        }
+        Err(e) => {
+            log::warn!("Failed to register SIGHUP template reload: {}", e);
+            None
+        }
+    }
+}
+
+/// `SIGHUP` does not exist outside Unix; use `#reload` or the admin API.
+#[cfg(not(unix))]
+pub fn register_reload_signal() -> Option<Arc<AtomicBool>> {
+    None
 }
 
 /// Handle for the watcher thread.
@@ -216,6 +310,7 @@ fn parse_reload_payload(raw: &str) -> Option<ReloadRequest> {
         request_id,
         reload_items,
         reload_characters,
+        origin: ReloadOrigin::Admin,
     })
 }
 
@@ -262,6 +357,7 @@ mod tests {
         assert_eq!(req.request_id, "abc");
         assert!(req.reload_items);
         assert!(req.reload_characters);
+        assert_eq!(req.origin, ReloadOrigin::Admin);
     }
 
     #[test]
@@ -298,6 +394,21 @@ mod tests {
         assert!(parse_reload_payload(raw).is_none());
     }
 
+    #[test]
+    fn parse_reload_kinds_accepts_aliases() {
+        assert_eq!(parse_reload_kinds(""), Some((true, true)));
+        assert_eq!(parse_reload_kinds("Items"), Some((true, false)));
+        assert_eq!(parse_reload_kinds("chars"), Some((false, true)));
+        assert_eq!(parse_reload_kinds("maps"), None);
+    }
+
+    #[test]
+    fn local_request_id_names_origin() {
+        let req = ReloadRequest::local(ReloadOrigin::Command(42), true, false);
+        assert_eq!(req.request_id, "command:42");
+        assert_eq!(req.origin, ReloadOrigin::Command(42));
+    }
+
     #[test]
     fn extract_string_field_handles_missing() {
         assert_eq!(extract_string_field("{}", "request_id"), None);
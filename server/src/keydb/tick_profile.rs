@@ -0,0 +1,113 @@
+//! Background publisher for tick cost attribution reports.
+//!
+//! The tick thread hands finished [`TickProfileReport`]s to this publisher,
+//! which writes the latest one to [`TICK_PROFILE_KEY`] for the admin API.
+//! Writing happens off the tick thread so KeyDB latency never shows up in
+//! the very measurements being reported.
+
+use core::tick_profile_store::{TICK_PROFILE_KEY, TickProfileReport};
+use redis::Commands;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Environment variable that disables publishing when set to
+/// `"true"`/`"1"`/`"yes"` (case-insensitive).
+pub const DISABLE_ENV: &str = "MAG_ADMIN_TICK_PROFILE_DISABLED";
+
+/// Handle for the publisher thread.
+pub struct TickProfilePublisher {
+    tx: Option<Sender<TickProfileReport>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TickProfilePublisher {
+    /// Spawn the publisher thread.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(publisher)` on success.
+    /// * `None` when disabled via [`DISABLE_ENV`] or when spawning fails.
+    pub fn spawn() -> Option<Self> {
+        if std::env::var(DISABLE_ENV)
+            .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+        {
+            log::info!(
+                "Tick profile publisher disabled via {} env var",
+                DISABLE_ENV
+            );
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("tick-profile-publisher".into())
+            .spawn(move || publisher_loop(rx))
+            .ok()?;
+
+        log::info!("Tick profile publisher started");
+        Some(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Queue a report for publishing without blocking.
+    ///
+    /// # Arguments
+    ///
+    /// * `report` - Report for the window that just closed.
+    pub fn publish(&self, report: TickProfileReport) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(report);
+        }
+    }
+
+    /// Stop the publisher and join its thread.
+    pub fn shutdown(&mut self) {
+        // Dropping the sender ends the receive loop.
+        self.tx = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for TickProfilePublisher {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+fn publisher_loop(rx: Receiver<TickProfileReport>) {
+    let mut con: Option<redis::Connection> = None;
+
+    while let Ok(mut report) = rx.recv() {
+        // Only the newest report matters; skip any that queued up meanwhile.
+        while let Ok(newer) = rx.try_recv() {
+            report = newer;
+        }
+
+        if con.is_none() {
+            match super::connection::connect() {
+                Ok(connection) => con = Some(connection),
+                Err(error) => {
+                    log::warn!("tick profile publisher: keydb connect failed: {}", error);
+                    continue;
+                }
+            }
+        }
+
+        let conn = con.as_mut().expect("connection just initialised");
+        if let Err(error) = conn.set::<_, _, ()>(TICK_PROFILE_KEY, report.to_bytes()) {
+            log::warn!(
+                "tick profile publisher: SET {} failed: {}",
+                TICK_PROFILE_KEY,
+                error
+            );
+            con = None;
+        }
+    }
+}
@@ -0,0 +1,461 @@
+//! Write-behind queue for KeyDB writes issued during the tick.
+//!
+//! Profile edits, rank changes and logins used to open a fresh KeyDB
+//! connection and wait for `HSET` right on the tick thread. They now call
+//! [`queue`], which records the dirty hash fields and returns immediately.
+//! A [`WriteBehindFlusher`] thread drains the queue every
+//! [`FLUSH_INTERVAL`] (sooner once [`HIGH_WATER_MARK`] entries are pending),
+//! sending up to [`MAX_BATCH`] fields per pipeline over one long-lived
+//! connection.
+//!
+//! With the SQLite backend, record writes (mail, consignments, ...) use a
+//! second queue, [`sqlite_records`], whose flusher writes each batch in one
+//! transaction over its own long-lived [`SqliteStore`] connection.
+//!
+//! Entries are keyed by `(hash key, field)`, so a value rewritten before it
+//! was flushed only keeps its latest version. A failed pipeline is put back
+//! (without overwriting anything newer) and retried after [`RETRY_DELAY`].
+//! [`WriteBehindStats`] exposes the queue depth, its high-water mark and the
+//! flush counters so backpressure shows up in the logs.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::sqlite_store::SqliteStore;
+
+/// How long the flusher waits for more writes before sending a batch.
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Most hash fields sent in one pipeline.
+pub const MAX_BATCH: usize = 512;
+
+/// Pending fields above which the flusher is woken early and a warning is
+/// logged.
+pub const HIGH_WATER_MARK: usize = 4_096;
+
+/// Pause after a failed pipeline before reconnecting.
+pub const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Dirty fields per hash key.
+type Pending = BTreeMap<String, BTreeMap<&'static str, String>>;
+
+/// One hash key and the fields to write to it.
+pub type HashWrite = (String, Vec<(&'static str, String)>);
+
+/// Counters describing the queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteBehindStats {
+    /// Fields handed to [`queue`].
+    pub enqueued: u64,
+    /// Fields that replaced a still-pending value for the same field.
+    pub coalesced: u64,
+    /// Fields written to KeyDB.
+    pub flushed: u64,
+    /// Pipelines sent successfully.
+    pub batches: u64,
+    /// Pipelines that failed and were requeued.
+    pub failed_batches: u64,
+    /// Fields currently pending.
+    pub depth: usize,
+    /// Largest `depth` seen.
+    pub high_water: usize,
+}
+
+#[derive(Default)]
+struct State {
+    pending: Pending,
+    stats: WriteBehindStats,
+    stopping: bool,
+}
+
+/// The shared queue behind [`queue`] and the flusher thread.
+pub struct WriteBehindQueue {
+    /// Backend named in log lines, e.g. `KeyDB`.
+    label: &'static str,
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+impl WriteBehindQueue {
+    fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            state: Mutex::new(State::default()),
+            wake: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Marks hash fields dirty.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Hash key, e.g. `character:42`.
+    /// * `fields` - `(field, value)` pairs; later values win.
+    pub fn hset(&self, key: impl Into<String>, fields: Vec<(&'static str, String)>) {
+        let mut state = self.lock();
+        let State { pending, stats, .. } = &mut *state;
+        let before = stats.depth;
+        let entry = pending.entry(key.into()).or_default();
+        for (field, value) in fields {
+            stats.enqueued += 1;
+            if entry.insert(field, value).is_some() {
+                stats.coalesced += 1;
+            } else {
+                stats.depth += 1;
+            }
+        }
+        stats.high_water = stats.high_water.max(stats.depth);
+        if stats.depth >= HIGH_WATER_MARK {
+            if before < HIGH_WATER_MARK {
+                log::warn!(
+                    "{} write-behind queue reached {} pending fields; flushing early",
+                    self.label,
+                    stats.depth
+                );
+            }
+            self.wake.notify_one();
+        }
+    }
+
+    /// Snapshot of the queue counters.
+    pub fn stats(&self) -> WriteBehindStats {
+        self.lock().stats
+    }
+
+    /// Removes up to [`MAX_BATCH`] fields, whole keys at a time.
+    fn take_batch(state: &mut State) -> Vec<HashWrite> {
+        let mut batch = Vec::new();
+        let mut fields = 0;
+        while fields < MAX_BATCH {
+            let Some((key, values)) = state.pending.pop_first() else {
+                break;
+            };
+            fields += values.len();
+            batch.push((key, values.into_iter().collect()));
+        }
+        state.stats.depth -= fields;
+        batch
+    }
+
+    /// Puts a failed batch back, keeping any newer value queued meanwhile.
+    fn requeue(state: &mut State, batch: Vec<HashWrite>) {
+        for (key, values) in batch {
+            let entry = state.pending.entry(key).or_default();
+            for (field, value) in values {
+                if !entry.contains_key(field) {
+                    entry.insert(field, value);
+                    state.stats.depth += 1;
+                }
+            }
+        }
+    }
+}
+
+/// The process-wide queue.
+pub fn global() -> &'static WriteBehindQueue {
+    static QUEUE: OnceLock<WriteBehindQueue> = OnceLock::new();
+    QUEUE.get_or_init(|| WriteBehindQueue::new("KeyDB"))
+}
+
+/// The process-wide queue of record writes for the SQLite backend (see
+/// [`crate::storage::StorageBackend::save_record`]).
+pub fn sqlite_records() -> &'static WriteBehindQueue {
+    static QUEUE: OnceLock<WriteBehindQueue> = OnceLock::new();
+    QUEUE.get_or_init(|| WriteBehindQueue::new("SQLite"))
+}
+
+/// Marks hash fields dirty on the process-wide queue.
+///
+/// # Arguments
+///
+/// * `key` - Hash key, e.g. `character:42`.
+/// * `fields` - `(field, value)` pairs.
+pub fn queue(key: impl Into<String>, fields: Vec<(&'static str, String)>) {
+    global().hset(key, fields);
+}
+
+/// Handle for the flusher thread.
+pub struct WriteBehindFlusher {
+    queue: &'static WriteBehindQueue,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WriteBehindFlusher {
+    /// Spawn the flusher thread for the process-wide queue.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(flusher)` on success, `None` when the thread cannot be spawned.
+    pub fn spawn() -> Option<Self> {
+        Self::spawn_with(global(), "keydb-write-behind", KeyDbBatches(None))
+    }
+
+    /// Spawn the flusher thread for [`sqlite_records`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - SQLite database file.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(flusher)` on success, `None` when the thread cannot be spawned.
+    pub fn spawn_sqlite(path: PathBuf) -> Option<Self> {
+        Self::spawn_with(
+            sqlite_records(),
+            "sqlite-write-behind",
+            SqliteBatches { path, db: None },
+        )
+    }
+
+    fn spawn_with(
+        queue: &'static WriteBehindQueue,
+        name: &str,
+        sink: impl BatchSink,
+    ) -> Option<Self> {
+        let handle = thread::Builder::new()
+            .name(name.into())
+            .spawn(move || flusher_loop(queue, sink))
+            .map_err(|err| {
+                log::error!(
+                    "Failed to spawn {} write-behind flusher: {err}",
+                    queue.label
+                )
+            })
+            .ok()?;
+        log::info!("{} write-behind flusher started", queue.label);
+        Some(Self {
+            queue,
+            handle: Some(handle),
+        })
+    }
+
+    /// Flush everything still pending, then stop and join the thread.
+    pub fn shutdown(&mut self) {
+        let queue = self.queue;
+        queue.lock().stopping = true;
+        queue.wake.notify_one();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let stats = queue.stats();
+        log::info!(
+            "{} write-behind stopped: {} fields flushed in {} batches ({} coalesced, {} failed batches, high water {})",
+            queue.label,
+            stats.flushed,
+            stats.batches,
+            stats.coalesced,
+            stats.failed_batches,
+            stats.high_water
+        );
+    }
+}
+
+impl Drop for WriteBehindFlusher {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.shutdown();
+        }
+    }
+}
+
+/// Destination a flusher writes its batches to.
+trait BatchSink: Send + 'static {
+    /// Writes one batch; all of it or nothing.
+    fn write_batch(&mut self, batch: &[HashWrite]) -> Result<(), String>;
+    /// Drops the connection after a failed batch so the next one reconnects.
+    fn reset(&mut self);
+}
+
+/// KeyDB destination: one `HSET` per key in a single pipeline.
+struct KeyDbBatches(Option<redis::Connection>);
+
+impl BatchSink for KeyDbBatches {
+    fn write_batch(&mut self, batch: &[HashWrite]) -> Result<(), String> {
+        if self.0.is_none() {
+            self.0 = Some(super::connection::connect()?);
+        }
+        let con = self.0.as_mut().expect("connection just initialised");
+        let mut pipeline = redis::pipe();
+        for (key, values) in batch {
+            let command = pipeline.cmd("HSET").arg(key);
+            for (field, value) in values {
+                command.arg(*field).arg(value);
+            }
+            command.ignore();
+        }
+        pipeline
+            .query::<()>(con)
+            .map_err(|err| format!("pipeline failed: {err}"))
+    }
+
+    fn reset(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// SQLite destination: one transaction per batch.
+struct SqliteBatches {
+    path: PathBuf,
+    db: Option<SqliteStore>,
+}
+
+impl BatchSink for SqliteBatches {
+    fn write_batch(&mut self, batch: &[HashWrite]) -> Result<(), String> {
+        if self.db.is_none() {
+            self.db = Some(SqliteStore::open(&self.path)?);
+        }
+        self.db
+            .as_mut()
+            .expect("database just opened")
+            .save_records(batch)
+    }
+
+    fn reset(&mut self) {
+        self.db = None;
+    }
+}
+
+fn flusher_loop(queue: &WriteBehindQueue, mut sink: impl BatchSink) {
+    loop {
+        let (batch, stopping) = {
+            let mut state = queue.lock();
+            if !state.stopping && state.stats.depth < HIGH_WATER_MARK {
+                state = queue
+                    .wake
+                    .wait_timeout(state, FLUSH_INTERVAL)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+            }
+            if state.stats.depth == 0 {
+                if state.stopping {
+                    return;
+                }
+                continue;
+            }
+            (WriteBehindQueue::take_batch(&mut state), state.stopping)
+        };
+
+        let fields: usize = batch.iter().map(|(_, values)| values.len()).sum();
+        let started = Instant::now();
+        let result = sink.write_batch(&batch);
+
+        let mut state = queue.lock();
+        match result {
+            Ok(()) => {
+                state.stats.flushed += fields as u64;
+                state.stats.batches += 1;
+                log::debug!(
+                    "{} write-behind flushed {} fields in {:?} ({} pending)",
+                    queue.label,
+                    fields,
+                    started.elapsed(),
+                    state.stats.depth
+                );
+            }
+            Err(err) if stopping => {
+                log::error!(
+                    "{} write-behind dropping {} fields at shutdown: {}",
+                    queue.label,
+                    fields + state.stats.depth,
+                    err
+                );
+                return;
+            }
+            Err(err) => {
+                state.stats.failed_batches += 1;
+                WriteBehindQueue::requeue(&mut state, batch);
+                log::warn!(
+                    "{} write-behind batch of {} fields failed ({} pending): {}",
+                    queue.label,
+                    fields,
+                    state.stats.depth,
+                    err
+                );
+                drop(state);
+                sink.reset();
+                thread::sleep(RETRY_DELAY);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_coalesce_and_batches_take_whole_keys() {
+        let queue = WriteBehindQueue::new("KeyDB");
+        queue.hset("character:1", vec![("description", "old".into())]);
+        queue.hset(
+            "character:1",
+            vec![("description", "new".into()), ("server_id", "7".into())],
+        );
+        queue.hset("character:2", vec![("rank_index", "3".into())]);
+
+        let stats = queue.stats();
+        assert_eq!((stats.enqueued, stats.coalesced, stats.depth), (4, 1, 3));
+
+        let batch = WriteBehindQueue::take_batch(&mut queue.lock());
+        assert_eq!(batch.len(), 2);
+        assert_eq!(
+            batch[0],
+            (
+                "character:1".to_owned(),
+                vec![
+                    ("description", "new".to_owned()),
+                    ("server_id", "7".to_owned())
+                ]
+            )
+        );
+        assert_eq!(queue.stats().depth, 0);
+        assert_eq!(queue.stats().high_water, 3);
+    }
+
+    #[test]
+    fn failed_batches_do_not_overwrite_newer_values() {
+        let queue = WriteBehindQueue::new("KeyDB");
+        queue.hset("character:1", vec![("description", "first".into())]);
+        let batch = WriteBehindQueue::take_batch(&mut queue.lock());
+
+        queue.hset("character:1", vec![("description", "second".into())]);
+        WriteBehindQueue::requeue(&mut queue.lock(), batch);
+
+        let batch = WriteBehindQueue::take_batch(&mut queue.lock());
+        assert_eq!(batch[0].1, vec![("description", "second".to_owned())]);
+    }
+
+    #[test]
+    fn failed_sqlite_batches_land_on_retry() {
+        let dir = std::env::temp_dir().join(format!("mag-write-behind-{}", std::process::id()));
+        let queue = WriteBehindQueue::new("SQLite");
+        let mut sink = SqliteBatches {
+            path: dir.join("game.sqlite3"),
+            db: None,
+        };
+        queue.hset("game:consign:9", vec![("seller", "0".into())]);
+
+        // The directory does not exist yet, so the first write fails.
+        let batch = WriteBehindQueue::take_batch(&mut queue.lock());
+        assert!(sink.write_batch(&batch).is_err());
+        WriteBehindQueue::requeue(&mut queue.lock(), batch);
+        sink.reset();
+
+        std::fs::create_dir_all(&dir).unwrap();
+        let batch = WriteBehindQueue::take_batch(&mut queue.lock());
+        sink.write_batch(&batch).unwrap();
+        let records = SqliteStore::open(&dir.join("game.sqlite3"))
+            .unwrap()
+            .load_records("game:consign:")
+            .unwrap();
+        assert_eq!(records[0].1["seller"], "0");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,7 +3,8 @@
 //! The `server` crate is primarily a binary (the game server), but this
 //! `lib.rs` exposes a small set of modules so that the `server-utils` crate
 //! (template viewer, map viewer) can reuse KeyDB connectivity and the
-//! points-calculation logic without duplicating code.
+//! persistence backends without duplicating code.
+//! The points-calculation logic lives in `core::balance`.
 
 /// KeyDB integration: connection helper, persistence layer, snapshot I/O,
 /// background saver, and pub/sub patch watchers.
@@ -13,9 +14,13 @@
 /// [`keydb::snapshot::WorldSnapshot`].
 pub mod keydb;
 
-/// Pure functions for calculating character experience points.
-///
-/// Provides [`points::calculate_points_tot`] for computing the total
-/// experience a character template is worth, based on its attributes, HP,
-/// endurance, mana, and skills.
-pub mod points;
+/// Append-only per-tick journal of character and item mutations, with
+/// replay onto `.wsnap` snapshots for post-mortem debugging.
+pub mod journal;
+
+/// SQLite-backed persistence layer, an alternative to KeyDB selected via
+/// [`storage::StorageBackend`].
+pub mod sqlite_store;
+
+/// Persistence backend selection (KeyDB or SQLite) from the environment.
+pub mod storage;
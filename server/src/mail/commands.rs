@@ -0,0 +1,468 @@
+//! `#mail` and the `SV_MAILENTRY` updates that keep mail panels current.
+
+use core::chat::{ChatChannel, ChatStyle};
+use core::constants::{
+    CLIENT_CAP_MAIL, CharacterFlags, ItemFlags, MAXCHARS, USE_ACTIVE, USE_NONACTIVE,
+};
+use core::item_binding::Transfer;
+use core::mail::{MailEntry, MailOp, encode_mail_entry};
+use core::server_commands::ServerCommandType;
+use core::types::FontColor;
+
+use super::{Letter, store};
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::guilds::commands::online_slot;
+use crate::network_manager;
+use crate::wall_clock;
+
+const MAIL_HELP: &str = "Mail commands: #mail (list your letters), #mail read <nr>, \
+#mail send <player> <text>, #mail parcel <player> <text> (attaches the item on your cursor), \
+#mail take <nr>, #mail delete <nr>.\n";
+
+/// Player character whose name is exactly `name` (case-insensitive), online
+/// or not.
+pub(crate) fn player_named(gs: &GameState, name: &str) -> Option<usize> {
+    (1..MAXCHARS).find(|&n| {
+        let ch = &gs.characters[n];
+        (ch.used == USE_ACTIVE || ch.used == USE_NONACTIVE)
+            && ch.is_player()
+            && ch.get_name().eq_ignore_ascii_case(name)
+    })
+}
+
+/// Builds the mailbox entry the client shows for `letter`.
+fn mail_entry(gs: &GameState, letter: &Letter) -> MailEntry {
+    MailEntry {
+        id: letter.id,
+        sent_unix: letter.sent_unix.clamp(0, i64::from(u32::MAX)) as u32,
+        unread: letter.unread,
+        from: letter.from_name.clone(),
+        attachment: if letter.item == 0 {
+            String::new()
+        } else {
+            gs.items[letter.item].get_name().to_owned()
+        },
+        body: letter.body.clone(),
+    }
+}
+
+/// Sends `buf` to `cn`'s client if it is online and shows mail.
+fn send_to_mail_client(gs: &mut GameState, cn: usize, buf: &[u8]) {
+    if let Some(nr) = online_slot(gs, cn)
+        && gs.players[nr].capabilities & CLIENT_CAP_MAIL != 0
+    {
+        network_manager::xsend(gs, nr, buf, buf.len());
+    }
+}
+
+/// Sends the whole mailbox of its character to player slot `nr`.
+///
+/// A `Clear` entry goes first, so letters deleted elsewhere disappear too.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot; ignored unless it advertises `CLIENT_CAP_MAIL`.
+pub fn plr_send_mailbox(gs: &mut GameState, nr: usize) {
+    if gs.players[nr].capabilities & CLIENT_CAP_MAIL == 0 {
+        return;
+    }
+    let cn = gs.players[nr].usnr;
+    let buf = encode_mail_entry(
+        ServerCommandType::MailEntry as u8,
+        MailOp::Clear,
+        &MailEntry::default(),
+    );
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    let letters: Vec<Letter> = gs.mail.mailbox(cn).cloned().collect();
+    for letter in &letters {
+        let buf = encode_mail_entry(
+            ServerCommandType::MailEntry as u8,
+            MailOp::Set,
+            &mail_entry(gs, letter),
+        );
+        network_manager::xsend(gs, nr, &buf, buf.len());
+    }
+}
+
+/// Tells `cn` about unread letters when it logs in.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character that logged in.
+pub fn mail_login_notice(gs: &mut GameState, cn: usize) {
+    let unread = gs.mail.unread_count(cn);
+    if unread == 0 {
+        return;
+    }
+    gs.do_character_styled_log(
+        cn,
+        ChatStyle::new(ChatChannel::Tell),
+        &format!(
+            "You have {} unread letter{}. Type #mail to read your mail.\n",
+            unread,
+            if unread == 1 { "" } else { "s" }
+        ),
+    );
+}
+
+impl GameState {
+    /// Handles `#mail <subcommand> [arguments]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character issuing the command.
+    /// * `sub` - Subcommand; empty lists the mailbox.
+    /// * `rest` - Everything after the subcommand.
+    pub(crate) fn do_mail(&mut self, cn: usize, sub: &str, rest: &str) {
+        if self.characters[cn].flags & CharacterFlags::Player.bits() == 0 {
+            return;
+        }
+        let rest = rest.trim();
+        let letter_id = || {
+            rest.trim_start_matches('#')
+                .parse::<u32>()
+                .map_err(|_| format!("Usage: #mail {} <letter number>", sub))
+        };
+        let result = match sub.to_ascii_lowercase().as_str() {
+            "" | "list" => {
+                self.do_mail_list(cn);
+                return;
+            }
+            "help" => {
+                self.do_character_log(cn, FontColor::Yellow, MAIL_HELP);
+                return;
+            }
+            "send" => self.do_mail_send(cn, rest, false),
+            "parcel" => self.do_mail_send(cn, rest, true),
+            "read" => letter_id().and_then(|id| self.do_mail_read(cn, id)),
+            "take" => letter_id().and_then(|id| self.do_mail_take(cn, id)),
+            "delete" => letter_id().and_then(|id| self.do_mail_delete(cn, id)),
+            _ => Err("Unknown mail command. Try #mail help.".to_owned()),
+        };
+        // An empty message means the player was already told why.
+        if let Err(message) = result
+            && !message.is_empty()
+        {
+            self.do_character_log(cn, FontColor::Red, &format!("{}\n", message));
+        }
+    }
+
+    fn do_mail_list(&mut self, cn: usize) {
+        let letters: Vec<Letter> = self.mail.mailbox(cn).cloned().collect();
+        if letters.is_empty() {
+            self.do_character_log(cn, FontColor::Yellow, "Your mailbox is empty.\n");
+            return;
+        }
+        let style = ChatStyle::new(ChatChannel::Tell);
+        for letter in &letters {
+            let attachment = if letter.item == 0 {
+                String::new()
+            } else {
+                format!(" [{}]", self.items[letter.item].get_name())
+            };
+            let line = format!(
+                "#{} from {}, {}{}{}\n",
+                letter.id,
+                letter.from_name,
+                wall_clock::format_display(letter.sent_unix),
+                if letter.unread { " (new)" } else { "" },
+                attachment
+            );
+            self.do_character_styled_log(cn, style, &line);
+        }
+    }
+
+    fn do_mail_send(&mut self, cn: usize, rest: &str, parcel: bool) -> Result<(), String> {
+        let (name, body) = rest
+            .split_once(' ')
+            .map(|(name, body)| (name, body.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "Usage: #mail {} <player> <text>",
+                    if parcel { "parcel" } else { "send" }
+                )
+            })?;
+        if self.characters[cn].flags & CharacterFlags::ShutUp.bits() != 0 {
+            return Err("You try to write, but your hand will not obey.".to_owned());
+        }
+        let co = player_named(self, name)
+            .ok_or_else(|| format!("There is no player called {}.", name))?;
+        if co == cn {
+            return Err("You cannot write to yourself.".to_owned());
+        }
+
+        let item = if parcel { self.mail_attachment(cn)? } else { 0 };
+        let from_name = self.characters[cn].get_name().to_owned();
+        let id = self.mail.send(Letter {
+            from: cn,
+            from_name: from_name.clone(),
+            to: co,
+            sent_unix: wall_clock::unix_now(),
+            body: body.to_owned(),
+            item,
+            ..Letter::default()
+        })?;
+        if item != 0 {
+            self.characters[cn].citem = 0;
+            self.items[item].carried = 0;
+            self.characters[cn].set_do_update_flags();
+        }
+
+        let to_name = self.characters[co].get_name().to_owned();
+        log::info!(
+            "Character {} mailed letter {} to {} ({}){}",
+            cn,
+            id,
+            co,
+            to_name,
+            if item != 0 {
+                format!(" with item {}", item)
+            } else {
+                String::new()
+            }
+        );
+        self.mail_changed(id);
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("Your letter to {} is on its way.\n", to_name),
+        );
+        if online_slot(self, co).is_some() {
+            self.do_character_styled_log(
+                co,
+                ChatStyle::new(ChatChannel::Tell),
+                &format!(
+                    "You have received a letter from {}. Type #mail read {} to read it.\n",
+                    from_name, id
+                ),
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the item on `cn`'s cursor before it is attached to a letter.
+    ///
+    /// # Returns
+    ///
+    /// * The item, or a message for the player; empty when
+    ///   [`GameState::refuse_transfer`] already explained the refusal.
+    fn mail_attachment(&mut self, cn: usize) -> Result<usize, String> {
+        let citem = self.characters[cn].citem;
+        if citem == 0 {
+            return Err("Hold the item you want to send on your cursor.".to_owned());
+        }
+        if citem & 0x8000_0000 != 0 {
+            return Err("Gold cannot be sent by mail.".to_owned());
+        }
+        let item = citem as usize;
+        if !self.do_maygive(cn, 0, item)
+            || self.items[item].flags & ItemFlags::IF_NODEPOT.bits() != 0
+        {
+            return Err("That cannot be sent by mail.".to_owned());
+        }
+        if self.refuse_transfer(cn, item, Transfer::Give) {
+            return Err(String::new());
+        }
+        Ok(item)
+    }
+
+    fn do_mail_read(&mut self, cn: usize, id: u32) -> Result<(), String> {
+        self.mail.mark_read(cn, id)?;
+        let letter = self.mail.get(id).cloned().expect("mark_read found it");
+        let style = ChatStyle::new(ChatChannel::Tell);
+        self.do_character_styled_log(
+            cn,
+            style,
+            &format!(
+                "Letter #{} from {}, {}:\n",
+                id,
+                letter.from_name,
+                wall_clock::format_display(letter.sent_unix)
+            ),
+        );
+        self.do_character_styled_log(cn, style, &format!("{}\n", letter.body));
+        if letter.item != 0 {
+            self.do_character_styled_log(
+                cn,
+                style,
+                &format!(
+                    "Attached: {}. Type #mail take {} to take it.\n",
+                    self.items[letter.item].get_name(),
+                    id
+                ),
+            );
+        }
+        self.mail_changed(id);
+        Ok(())
+    }
+
+    fn do_mail_take(&mut self, cn: usize, id: u32) -> Result<(), String> {
+        let item = self.mail.take_item(cn, id)?;
+        if !God::give_character_item(self, cn, item) {
+            self.mail.reattach(id, item);
+            return Err("Your backpack is full.".to_owned());
+        }
+        self.bind_item(cn, item, ItemFlags::IF_BIND_PICKUP);
+        log::info!("Character {} took item {} from letter {}", cn, item, id);
+        self.mail_changed(id);
+        let name = self.items[item].get_name().to_owned();
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("You take {} from the letter.\n", name),
+        );
+        Ok(())
+    }
+
+    fn do_mail_delete(&mut self, cn: usize, id: u32) -> Result<(), String> {
+        self.delete_letter(cn, id)?;
+        self.do_character_log(cn, FontColor::Yellow, "Letter deleted.\n");
+        Ok(())
+    }
+
+    /// Deletes letter `id` of `cn` and removes it from their mail panel.
+    ///
+    /// # Returns
+    ///
+    /// * A message for the player if the letter cannot be deleted.
+    pub(crate) fn delete_letter(&mut self, cn: usize, id: u32) -> Result<(), String> {
+        self.mail.delete(cn, id)?;
+        store::save_deleted(&self.storage, id);
+        let buf = encode_mail_entry(
+            ServerCommandType::MailEntry as u8,
+            MailOp::Remove,
+            &MailEntry {
+                id,
+                ..MailEntry::default()
+            },
+        );
+        send_to_mail_client(self, cn, &buf);
+        Ok(())
+    }
+
+    /// Delivers a letter that no player wrote, e.g. from a broker.
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - Recipient character.
+    /// * `from_name` - Name shown as the sender.
+    /// * `body` - Letter text.
+    /// * `item` - Item to attach, carried by no one; `0` for none.
+    /// * `expires_unix` - When an untaken attachment is returned, in Unix
+    ///   seconds; `0` for never.
+    ///
+    /// # Returns
+    ///
+    /// * The new letter's id, or why it could not be delivered.
+    pub(crate) fn send_system_letter(
+        &mut self,
+        to: usize,
+        from_name: &str,
+        body: &str,
+        item: usize,
+        expires_unix: i64,
+    ) -> Result<u32, String> {
+        let id = self.mail.send(Letter {
+            from: 0,
+            from_name: from_name.to_owned(),
+            to,
+            sent_unix: wall_clock::unix_now(),
+            body: body.to_owned(),
+            item,
+            expires_unix,
+            ..Letter::default()
+        })?;
+        self.mail_changed(id);
+        if online_slot(self, to).is_some() {
+            self.do_character_styled_log(
+                to,
+                ChatStyle::new(ChatChannel::Tell),
+                &format!(
+                    "You have received a letter from {}. Type #mail read {} to read it.\n",
+                    from_name, id
+                ),
+            );
+        }
+        Ok(id)
+    }
+
+    /// Saves letter `id` and resends it to its recipient's client.
+    fn mail_changed(&mut self, id: u32) {
+        let Some(letter) = self.mail.get(id).cloned() else {
+            return;
+        };
+        store::save_letter(&self.storage, &letter);
+        let buf = encode_mail_entry(
+            ServerCommandType::MailEntry as u8,
+            MailOp::Set,
+            &mail_entry(self, &letter),
+        );
+        send_to_mail_client(self, letter.to, &buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn parcels_carry_the_cursor_item_to_the_recipient() {
+        with_test_gs(|gs| {
+            let (co, _) = add_test_player(gs);
+            let cn = 2;
+            gs.characters[cn].used = USE_ACTIVE;
+            gs.characters[cn].flags = CharacterFlags::Player.bits();
+            gs.characters[cn].set_name("Sender");
+            let item = 5;
+            gs.items[item].used = USE_ACTIVE;
+            gs.items[item].carried = cn as u16;
+            gs.characters[cn].citem = item as u32;
+
+            gs.do_mail(cn, "parcel", "tester Here is your ring.");
+            assert_eq!(gs.characters[cn].citem, 0);
+            assert_eq!(gs.items[item].carried, 0);
+            let id = gs.mail.mailbox(co).next().expect("letter delivered").id;
+            assert_eq!(gs.mail.unread_count(co), 1);
+
+            gs.do_mail(co, "delete", &id.to_string());
+            assert!(gs.mail.get(id).is_some(), "attachment blocks deleting");
+            gs.do_mail(co, "take", &id.to_string());
+            assert_eq!(gs.items[item].carried, co as u16);
+            gs.do_mail(co, "delete", &id.to_string());
+            assert!(gs.mail.get(id).is_none());
+        });
+    }
+
+    #[test]
+    fn attachments_survive_item_garbage_collection() {
+        with_test_gs(|gs| {
+            let (co, _) = add_test_player(gs);
+            let cn = 2;
+            gs.characters[cn].used = USE_ACTIVE;
+            gs.characters[cn].flags = CharacterFlags::Player.bits();
+            gs.characters[cn].set_name("Sender");
+            let item = 5;
+            gs.items[item].used = USE_ACTIVE;
+            gs.items[item].temp = 42;
+            gs.items[item].carried = cn as u16;
+            gs.characters[cn].citem = item as u32;
+            gs.do_mail(cn, "parcel", "tester Here is your ring.");
+            let id = gs.mail.mailbox(co).next().expect("letter delivered").id;
+
+            // One full sweep over the item table.
+            for _ in 0..core::constants::MAXITEM.div_ceil(256) {
+                crate::driver::item_tick_gc(gs);
+            }
+            assert_eq!(gs.items[item].used, USE_ACTIVE);
+
+            gs.do_mail(co, "take", &id.to_string());
+            assert_eq!(gs.items[item].carried, co as u16);
+            assert_eq!(gs.items[item].temp, 42);
+            assert!(gs.characters[co].item.contains(&(item as u32)));
+        });
+    }
+}
@@ -0,0 +1,268 @@
+//! Player mail: letters, with an optional attached item, that wait in the
+//! recipient's mailbox until they are deleted.
+//!
+//! `#mail send <player> <text>` writes to any player character, online or
+//! not; `#mail parcel` also attaches the item on the sender's cursor. The
+//! recipient is told about new letters when they arrive or at the next
+//! login, reads them with `#mail read` or the client's mail panel and moves
+//! attachments into the backpack with `#mail take`.
+//!
+//! [`MailRegistry`] holds the letters and the rules and knows nothing about
+//! the world, so it can be tested on its own. [`commands`] turns text
+//! commands into registry calls, moves attached items and keeps the
+//! clients' mailboxes in sync. [`store`] persists every letter to a KeyDB
+//! hash.
+//!
+//! A system letter may carry an expiry date. Only reward letters set one;
+//! [`GameState::expire_reward_letters`](crate::game_state::GameState::expire_reward_letters)
+//! then returns the attachment.
+//!
+//! An attached item stays in the item table while the letter is in transit,
+//! carried by no one and placed nowhere. The item garbage collector would
+//! free such an item, so it asks [`MailRegistry::holds_item`] first.
+
+pub mod commands;
+pub mod store;
+
+use std::collections::BTreeMap;
+
+use core::mail::{MAX_MAILBOX_LETTERS, validate_mail_body};
+
+/// One letter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Letter {
+    /// Server-wide id; `0` until [`MailRegistry::send`] assigns one.
+    pub id: u32,
+    /// Character slot of the sender.
+    pub from: usize,
+    /// Sender's name when the letter was written.
+    pub from_name: String,
+    /// Character slot of the recipient.
+    pub to: usize,
+    /// When the letter was sent, in Unix seconds.
+    pub sent_unix: i64,
+    pub body: String,
+    /// Attached item; `0` when there is none or it was taken.
+    pub item: usize,
+    /// Whether the recipient has not read the letter yet.
+    pub unread: bool,
+    /// When an attachment still on the letter is returned, in Unix seconds;
+    /// `0` for never.
+    pub expires_unix: i64,
+}
+
+/// Every letter in the world.
+#[derive(Debug)]
+pub struct MailRegistry {
+    letters: BTreeMap<u32, Letter>,
+    next_id: u32,
+}
+
+impl Default for MailRegistry {
+    fn default() -> Self {
+        Self {
+            letters: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl MailRegistry {
+    /// Builds a registry from stored letters.
+    ///
+    /// # Arguments
+    ///
+    /// * `letters` - Letters as loaded by [`store::load_mail`].
+    /// * `next_id` - Lowest id never handed out.
+    pub fn from_letters(letters: Vec<Letter>, next_id: u32) -> Self {
+        let next_id = letters
+            .iter()
+            .map(|letter| letter.id + 1)
+            .max()
+            .unwrap_or(1)
+            .max(next_id);
+        Self {
+            letters: letters
+                .into_iter()
+                .map(|letter| (letter.id, letter))
+                .collect(),
+            next_id,
+        }
+    }
+
+    /// Letter with the given id.
+    pub fn get(&self, id: u32) -> Option<&Letter> {
+        self.letters.get(&id)
+    }
+
+    /// Letters addressed to `cn`, oldest first.
+    pub fn mailbox(&self, cn: usize) -> impl Iterator<Item = &Letter> {
+        self.letters.values().filter(move |letter| letter.to == cn)
+    }
+
+    /// Whether `item` is attached to a letter.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - Item index.
+    pub fn holds_item(&self, item: usize) -> bool {
+        item != 0 && self.letters.values().any(|letter| letter.item == item)
+    }
+
+    /// Ids of the letters whose attachment expired at or before `now`.
+    pub fn expired(&self, now: i64) -> Vec<u32> {
+        self.letters
+            .values()
+            .filter(|letter| letter.item != 0 && letter.expires_unix != 0)
+            .filter(|letter| letter.expires_unix <= now)
+            .map(|letter| letter.id)
+            .collect()
+    }
+
+    /// Number of letters `cn` has not read yet.
+    pub fn unread_count(&self, cn: usize) -> usize {
+        self.mailbox(cn).filter(|letter| letter.unread).count()
+    }
+
+    /// Delivers a letter to its recipient's mailbox.
+    ///
+    /// # Arguments
+    ///
+    /// * `letter` - The letter; its `id` is assigned here and it is marked
+    ///   unread.
+    ///
+    /// # Returns
+    ///
+    /// * The new letter's id, or a message for the sender.
+    pub fn send(&mut self, mut letter: Letter) -> Result<u32, String> {
+        validate_mail_body(&letter.body)?;
+        if self.mailbox(letter.to).count() >= MAX_MAILBOX_LETTERS {
+            return Err("Their mailbox is full.".to_owned());
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        letter.id = id;
+        letter.unread = true;
+        self.letters.insert(id, letter);
+        Ok(id)
+    }
+
+    fn letter_mut(&mut self, cn: usize, id: u32) -> Result<&mut Letter, String> {
+        self.letters
+            .get_mut(&id)
+            .filter(|letter| letter.to == cn)
+            .ok_or_else(|| format!("You have no letter #{}.", id))
+    }
+
+    /// Marks letter `id` of `cn` as read.
+    ///
+    /// # Returns
+    ///
+    /// * Whether the letter was unread, or a message for the player.
+    pub fn mark_read(&mut self, cn: usize, id: u32) -> Result<bool, String> {
+        let letter = self.letter_mut(cn, id)?;
+        Ok(std::mem::replace(&mut letter.unread, false))
+    }
+
+    /// Detaches the item of letter `id` of `cn`.
+    ///
+    /// # Returns
+    ///
+    /// * The detached item, or a message for the player.
+    pub fn take_item(&mut self, cn: usize, id: u32) -> Result<usize, String> {
+        let letter = self.letter_mut(cn, id)?;
+        if letter.item == 0 {
+            return Err("Nothing is attached to that letter.".to_owned());
+        }
+        letter.unread = false;
+        Ok(std::mem::take(&mut letter.item))
+    }
+
+    /// Puts an item back on letter `id`, e.g. when taking it failed.
+    pub fn reattach(&mut self, id: u32, item: usize) {
+        if let Some(letter) = self.letters.get_mut(&id) {
+            letter.item = item;
+        }
+    }
+
+    /// Deletes letter `id` of `cn`.
+    ///
+    /// # Returns
+    ///
+    /// * The deleted letter, or a message for the player. A letter with an
+    ///   attachment cannot be deleted, so items are never destroyed by
+    ///   accident.
+    pub fn delete(&mut self, cn: usize, id: u32) -> Result<Letter, String> {
+        if self.letter_mut(cn, id)?.item != 0 {
+            return Err("Take the attached item before deleting the letter.".to_owned());
+        }
+        Ok(self.letters.remove(&id).expect("letter_mut found it"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter(to: usize, item: usize) -> Letter {
+        Letter {
+            from: 10,
+            from_name: "Ishtar".to_owned(),
+            to,
+            sent_unix: 1_790_000_000,
+            body: "Hello there".to_owned(),
+            item,
+            ..Letter::default()
+        }
+    }
+
+    #[test]
+    fn letters_reach_only_their_recipient() {
+        let mut registry = MailRegistry::default();
+        let id = registry.send(letter(11, 0)).unwrap();
+        assert_eq!(registry.unread_count(11), 1);
+        assert_eq!(registry.unread_count(10), 0);
+        assert!(registry.mark_read(10, id).is_err());
+        assert_eq!(registry.mark_read(11, id), Ok(true));
+        assert_eq!(registry.mark_read(11, id), Ok(false));
+        assert!(registry.delete(11, id).is_ok());
+        assert!(registry.get(id).is_none());
+
+        for _ in 0..MAX_MAILBOX_LETTERS {
+            registry.send(letter(12, 0)).unwrap();
+        }
+        assert!(registry.send(letter(12, 0)).is_err());
+    }
+
+    #[test]
+    fn attachments_must_be_taken_before_deleting() {
+        let mut registry = MailRegistry::default();
+        let id = registry.send(letter(11, 77)).unwrap();
+        assert!(registry.delete(11, id).is_err());
+        assert_eq!(registry.take_item(11, id), Ok(77));
+        assert!(registry.take_item(11, id).is_err());
+        registry.reattach(id, 77);
+        assert_eq!(registry.take_item(11, id), Ok(77));
+        assert!(registry.delete(11, id).is_ok());
+
+        let expiring = registry
+            .send(Letter {
+                expires_unix: 1_790_000_100,
+                ..letter(11, 78)
+            })
+            .unwrap();
+        assert!(registry.expired(1_790_000_099).is_empty());
+        assert_eq!(registry.expired(1_790_000_100), vec![expiring]);
+        assert_eq!(registry.take_item(11, expiring), Ok(78));
+        assert!(registry.expired(1_790_000_100).is_empty());
+
+        let restored = MailRegistry::from_letters(
+            vec![Letter {
+                id: 9,
+                ..letter(11, 0)
+            }],
+            4,
+        );
+        assert_eq!(restored.next_id, 10);
+    }
+}
@@ -0,0 +1,159 @@
+//! Persistence for mail.
+//!
+//! Every letter is the record `game:mail:{id}` with the fields `from`,
+//! `from_name`, `to`, `sent`, `body`, `item`, `unread` and `expires`: a
+//! KeyDB hash written through the write-behind queue, so the tick never
+//! waits for KeyDB, or rows of the SQLite `record` table. A deleted letter is written
+//! back with `to` set to `0`; such records are removed the next time the
+//! server starts.
+
+use std::collections::HashMap;
+
+use server::storage::StorageBackend;
+
+use super::{Letter, MailRegistry};
+
+/// Prefix of the per-letter record keys.
+pub const MAIL_KEY_PREFIX: &str = "game:mail:";
+
+/// Saves `letter`.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `letter` - Letter to save.
+pub fn save_letter(storage: &StorageBackend, letter: &Letter) {
+    storage.save_record(
+        format!("{}{}", MAIL_KEY_PREFIX, letter.id),
+        vec![
+            ("from", letter.from.to_string()),
+            ("from_name", letter.from_name.clone()),
+            ("to", letter.to.to_string()),
+            ("sent", letter.sent_unix.to_string()),
+            ("body", letter.body.clone()),
+            ("item", letter.item.to_string()),
+            ("unread", u8::from(letter.unread).to_string()),
+            ("expires", letter.expires_unix.to_string()),
+        ],
+    );
+}
+
+/// Blanks a deleted letter.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `id` - Id of the deleted letter.
+pub fn save_deleted(storage: &StorageBackend, id: u32) {
+    storage.save_record(
+        format!("{}{}", MAIL_KEY_PREFIX, id),
+        vec![
+            ("to", "0".to_owned()),
+            ("body", String::new()),
+            ("item", "0".to_owned()),
+        ],
+    );
+}
+
+/// Loads every letter from the storage backend.
+///
+/// Records of deleted letters are removed while loading.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+///
+/// # Returns
+///
+/// * The registry. A letter record that cannot be parsed is logged and
+///   skipped; an error means the backend could not be read at all.
+pub fn load_mail(storage: &StorageBackend) -> Result<MailRegistry, String> {
+    let mut letters = Vec::new();
+    let mut deleted = Vec::new();
+    let mut next_id = 1;
+    for (key, fields) in storage.load_records(MAIL_KEY_PREFIX)? {
+        let Some(id) = key
+            .strip_prefix(MAIL_KEY_PREFIX)
+            .and_then(|id| id.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        next_id = next_id.max(id + 1);
+        match letter_from_fields(id, &fields) {
+            Ok(Some(letter)) => letters.push(letter),
+            // A blank record only has to outlive the write-behind queue.
+            // Its id may be handed out again after this restart, which is
+            // harmless because nothing refers to deleted letters.
+            Ok(None) => deleted.push(key),
+            Err(e) => log::error!("Skipping letter {}: {}", id, e),
+        }
+    }
+    if let Err(e) = storage.delete_records(&deleted) {
+        log::warn!("Failed to remove deleted letters: {}", e);
+    }
+
+    log::info!("Loaded {} letter(s)", letters.len());
+    Ok(MailRegistry::from_letters(letters, next_id))
+}
+
+/// Builds a letter from its stored fields.
+///
+/// # Returns
+///
+/// * `Ok(None)` for a deleted letter.
+fn letter_from_fields(id: u32, fields: &HashMap<String, String>) -> Result<Option<Letter>, String> {
+    let number = |name: &str| -> Result<i64, String> {
+        fields
+            .get(name)
+            .map_or(Ok(0), |value| value.parse::<i64>())
+            .map_err(|_| format!("malformed {} field", name))
+    };
+    let to = number("to")? as usize;
+    if to == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Letter {
+        id,
+        from: number("from")? as usize,
+        from_name: fields.get("from_name").cloned().unwrap_or_default(),
+        to,
+        sent_unix: number("sent")?,
+        body: fields.get("body").cloned().unwrap_or_default(),
+        item: number("item")? as usize,
+        unread: number("unread")? != 0,
+        expires_unix: number("expires")?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_fields_load_back() {
+        let mut fields = HashMap::new();
+        fields.insert("to".to_owned(), "0".to_owned());
+        assert_eq!(letter_from_fields(4, &fields), Ok(None));
+
+        for (name, value) in [
+            ("from", "12"),
+            ("from_name", "Ishtar"),
+            ("to", "340"),
+            ("sent", "1790000000"),
+            ("body", "Meet me in Aston."),
+            ("item", "5021"),
+            ("unread", "1"),
+            ("expires", "1791209600"),
+        ] {
+            fields.insert(name.to_owned(), value.to_owned());
+        }
+        let letter = letter_from_fields(4, &fields).unwrap().unwrap();
+        assert_eq!(letter.to, 340);
+        assert_eq!(letter.item, 5021);
+        assert!(letter.unread);
+        assert_eq!(letter.expires_unix, 1_791_209_600);
+
+        fields.insert("item".to_owned(), "lots".to_owned());
+        assert!(letter_from_fields(4, &fields).is_err());
+    }
+}
@@ -1,46 +1,133 @@
 mod area;
+mod config;
+mod consignment;
+mod console;
+mod decals;
+mod deterministic;
 mod driver;
 mod effect;
+mod friends;
 mod game_state;
 mod god;
+mod guilds;
+mod health;
 mod types;
 
 #[cfg(test)]
 mod test_helpers;
+#[cfg(test)]
+mod testutil;
 
 #[macro_use]
 pub mod helpers;
 mod lab9;
+mod mail;
+#[cfg(feature = "profiling")]
+mod mem_profile;
 mod network_manager;
 mod path_finding;
 mod player;
-mod points;
 mod populate;
+mod region_graph;
+mod scenario;
+mod scripting;
 mod server;
+#[cfg(test)]
+mod sim_fuzz;
 mod state;
 mod talk;
+mod tick_phases;
+mod tick_profile;
 mod tls;
+mod transport;
+mod validation;
+mod wall_clock;
+mod zones;
 
 use core::logout_reasons::LogoutReason;
 use std::env;
 use std::process;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use ::server::journal::recorder::JournalRecorder;
+use ::server::keydb::backup;
+use ::server::keydb::template_reload::{self, ReloadOrigin, ReloadRequest};
+use ::server::storage::StorageBackend;
 
 use crate::game_state::GameState;
 
 fn main() -> Result<(), String> {
-    let _: Vec<String> = env::args().collect();
-
-    core::initialize_logger(log::LevelFilter::Info, Some("server.log")).unwrap_or_else(|e| {
-        eprintln!("Failed to initialize logger: {}. Exiting.", e);
-        process::exit(1);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let config = config::ServerConfig::load().unwrap_or_else(|e| {
+        eprintln!("Invalid server configuration: {}", e);
+        process::exit(2);
+    });
+    if let Some(url) = &config.keydb.url {
+        ::server::keydb::connection::set_configured_url(url.clone());
+    }
+    match backup::parse_command(&args) {
+        Ok(Some(command)) => match backup::run(&command) {
+            Ok(summary) => {
+                println!("{}", summary);
+                process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+    let scenario_file = scenario::scenario_path(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+    let deterministic_options = deterministic::parse_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
     });
 
+    // Mirror log records into KeyDB for the admin API's live log tail.
+    let (log_tail_appender, _log_tail_publisher) =
+        ::server::keydb::log_tail::LogTailPublisher::spawn().unzip();
+    let log_level = config.log_level().unwrap_or(log::LevelFilter::Info);
+    core::initialize_logger_with_tail(log_level, Some(&config.log.file), log_tail_appender)
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to initialize logger: {}. Exiting.", e);
+            process::exit(1);
+        });
+
     log::info!(
         "Starting Men Among Gods: Reforged Server v{}",
         env!("CARGO_PKG_VERSION")
     );
+
+    if let Some(seed) = deterministic_options.seed {
+        deterministic::enable(seed);
+        log::info!(
+            "Deterministic mode: seed {}, game clock starts at {}.",
+            seed,
+            wall_clock::format_display(deterministic::DETERMINISTIC_EPOCH)
+        );
+    }
+
+    if let Some(path) = scenario_file {
+        match scenario::runner::run_file(&path) {
+            Ok(true) => process::exit(0),
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                log::error!("{}", e);
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        }
+    }
     log::info!("Process PID: {}", process::id());
 
     let quit_flag = Arc::new(AtomicBool::new(false));
@@ -60,6 +147,15 @@ fn main() -> Result<(), String> {
         process::exit(1);
     }
 
+    // ctrlc only covers SIGINT on Unix; SIGTERM (docker/kubernetes stop) is
+    // registered separately so SIGHUP can be used for template reloads.
+    #[cfg(unix)]
+    if let Err(e) = signal_hook::flag::register(signal_hook::consts::SIGTERM, quit_flag.clone()) {
+        log::error!("Error setting SIGTERM handler: {}. Exiting.", e);
+        process::exit(1);
+    }
+    let reload_signal = template_reload::register_reload_signal();
+
     let god_password = match env::var("MAG_GOD_PASSWORD") {
         Ok(v) if !v.is_empty() => v,
         _ => {
@@ -68,6 +164,29 @@ fn main() -> Result<(), String> {
         }
     };
 
+    let health_ready = health::spawn_from_env().unwrap_or_else(|e| {
+        log::error!("{}. Exiting.", e);
+        process::exit(1);
+    });
+
+    if let Ok(value) = env::var("MAG_KEYDB_WAIT_SECS")
+        && !value.is_empty()
+        && StorageBackend::from_env() == Ok(StorageBackend::KeyDb)
+    {
+        match value.parse::<u64>() {
+            Ok(secs) => {
+                log::info!("Waiting up to {}s for KeyDB to become ready...", secs);
+                if let Err(e) =
+                    ::server::keydb::connection::wait_until_ready(Duration::from_secs(secs))
+                {
+                    log::error!("{}. Exiting.", e);
+                    process::exit(1);
+                }
+            }
+            Err(_) => log::warn!("Ignoring invalid MAG_KEYDB_WAIT_SECS value '{}'.", value),
+        }
+    }
+
     let mut gs = GameState::initialize().unwrap_or_else(|e| {
         log::error!("Failed to initialize game state: {}. Exiting.", e);
         process::exit(1);
@@ -81,6 +200,77 @@ fn main() -> Result<(), String> {
         log::info!("Playtest mode enabled (MAG_PLAYTEST is set).");
     }
 
+    if env::var(state::weather::WEATHER_ENV)
+        .map(|v| !v.is_empty())
+        .unwrap_or(false)
+    {
+        gs.weather_enabled = true;
+        log::info!("Weather enabled ({} is set).", state::weather::WEATHER_ENV);
+    }
+
+    if let Ok(value) = env::var("MAG_LINKDEAD_GRACE_SECS")
+        && !value.is_empty()
+    {
+        match value.parse::<i32>() {
+            Ok(secs) if (0..=state::linkdead::MAX_LINKDEAD_GRACE_SECS).contains(&secs) => {
+                gs.linkdead_grace_ticks = core::constants::TICKS * secs;
+                log::info!("Linkdead grace period set to {}s.", secs);
+            }
+            _ => log::warn!(
+                "Ignoring invalid MAG_LINKDEAD_GRACE_SECS value '{}' (expected 0 to {}).",
+                value,
+                state::linkdead::MAX_LINKDEAD_GRACE_SECS
+            ),
+        }
+    }
+
+    let kit_setting = env::var(state::starter_kits::STARTER_KITS_ENV).ok();
+    gs.starter_kits = state::starter_kits::load_starter_kits(kit_setting.as_deref())
+        .unwrap_or_else(|e| {
+            log::error!("{}. Exiting.", e);
+            process::exit(1);
+        });
+    if gs.starter_kits.is_none() {
+        log::info!("Starter kits disabled; new characters get their template items.");
+    }
+
+    let view_setting = env::var(player::view::VIEW_RADIUS_ENV).ok();
+    gs.view_radius = player::view::parse_view_radius(view_setting.as_deref()).unwrap_or_else(|e| {
+        log::error!("{}. Exiting.", e);
+        process::exit(1);
+    });
+    log::info!("View radius set to {} tiles.", gs.view_radius);
+
+    let keyframe_setting = env::var(player::map_keyframe::MAP_KEYFRAME_ENV).ok();
+    gs.map_keyframe_ticks = player::map_keyframe::parse_map_keyframe_secs(
+        keyframe_setting.as_deref(),
+    )
+    .unwrap_or_else(|e| {
+        log::error!("{}. Exiting.", e);
+        process::exit(1);
+    });
+    if gs.map_keyframe_ticks == 0 {
+        log::info!("Map keyframes disabled.");
+    }
+
+    gs.dialogue = player::dialogue::load_dialogue(config.paths.dialogue_dir.as_deref())
+        .unwrap_or_else(|e| {
+            log::error!("{}. Exiting.", e);
+            process::exit(1);
+        });
+
+    scripting::load_scripts(config.paths.script_dir.as_deref(), &mut gs.scripts).unwrap_or_else(
+        |e| {
+            log::error!("{}. Exiting.", e);
+            process::exit(1);
+        },
+    );
+
+    gs.journal = JournalRecorder::from_env().unwrap_or_else(|e| {
+        log::error!("Failed to open world journal: {}. Exiting.", e);
+        process::exit(1);
+    });
+
     gs.god_password = god_password;
     log::info!("God password loaded from MAG_GOD_PASSWORD.");
 
@@ -93,15 +283,45 @@ fn main() -> Result<(), String> {
     }
 
     let mut server = server::Server::new();
+    server.configure(&config);
+    if config.game.tick_rate != core::constants::TICKS as u32 {
+        log::warn!(
+            "Running {} ticks per second instead of {}; game time runs at a different speed.",
+            config.game.tick_rate,
+            core::constants::TICKS
+        );
+    }
+    if let Some(path) = &deterministic_options.state_hashes {
+        let log = deterministic::state_hash::StateHashLog::create(path).unwrap_or_else(|e| {
+            log::error!("{}. Exiting.", e);
+            process::exit(1);
+        });
+        server.set_state_hash_log(log);
+        log::info!("Writing per-tick state hashes to {}.", path);
+    }
 
     server.initialize(&mut gs).unwrap_or_else(|e| {
         log::error!("Failed to initialize server: {}. Exiting.", e);
         process::exit(1);
     });
 
+    if let Some(ready) = &health_ready {
+        ready.store(true, Ordering::Relaxed);
+    }
+
     log::info!("Entering main game loop...");
 
     while !quit_flag.load(Ordering::SeqCst) {
+        if reload_signal
+            .as_ref()
+            .is_some_and(|flag| flag.swap(false, Ordering::SeqCst))
+        {
+            gs.pending_template_reloads.push(ReloadRequest::local(
+                ReloadOrigin::Signal,
+                true,
+                true,
+            ));
+        }
         server.drain_template_reloads(&mut gs);
         server.drain_text_reloads(&mut gs);
         server.drain_map_patches(&mut gs);
@@ -109,17 +329,28 @@ fn main() -> Result<(), String> {
         server.drain_character_patches(&mut gs);
         server.drain_ban_actions(&mut gs);
         server.drain_world_actions(&mut gs);
+        server.drain_console_commands(&mut gs);
         server.tick(&mut gs);
     }
 
     log::info!("Shutdown signal received, exiting main loop...");
+    if let Some(ready) = &health_ready {
+        ready.store(false, Ordering::Relaxed);
+    }
     let mut logout_entries: Vec<(usize, usize)> = Vec::new();
     for player_idx in 1..gs.players.len() {
         logout_entries.push((gs.players[player_idx].usnr, player_idx));
     }
     for (usnr, n) in &logout_entries {
-        player::connection::plr_logout(&mut gs, *usnr, *n, LogoutReason::Shutdown);
+        player::connection::plr_logout_with_text(
+            &mut gs,
+            *usnr,
+            *n,
+            LogoutReason::Shutdown,
+            "The server is shutting down. Please try again in a few minutes.",
+        );
     }
+    gs.logout_all_linkdead();
 
     log::info!("Enqueueing full save of all game data before shutdown...");
     server.enqueue_full_save(&gs);
@@ -0,0 +1,339 @@
+//! Allocation accounting and periodic memory reports (`profiling` feature).
+//!
+//! Installs a counting global allocator that forwards to the system
+//! allocator (so heaptrack/valgrind still see every `malloc`) or, with the
+//! `jemalloc` feature, to jemalloc (so `jeprof` heap dumps work via
+//! `_RJEM_MALLOC_CONF=prof:true`). Allocations are attributed to the
+//! [`Subsystem`] entered on the allocating thread, and [`MemoryReporter`]
+//! periodically logs RSS, heap totals, per-subsystem allocation churn and the
+//! footprint of the big game-state tables.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::game_state::GameState;
+
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+type Inner = tikv_jemallocator::Jemalloc;
+#[cfg(not(all(feature = "jemalloc", not(target_env = "msvc"))))]
+type Inner = std::alloc::System;
+
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+const INNER: Inner = tikv_jemallocator::Jemalloc;
+#[cfg(not(all(feature = "jemalloc", not(target_env = "msvc"))))]
+const INNER: Inner = std::alloc::System;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Environment variable overriding the report interval in seconds.
+const INTERVAL_ENV: &str = "MAG_MEM_PROFILE_INTERVAL_SECS";
+
+/// Default report interval.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Part of the server that allocations are attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Subsystem {
+    /// Anything outside an explicit scope, including background threads.
+    Other = 0,
+    /// World simulation (`game_tick`).
+    GameTick,
+    /// Building and compressing per-player tick packets.
+    CompressTicks,
+    /// Accepting connections and reading/writing sockets.
+    NetworkIo,
+}
+
+impl Subsystem {
+    const ALL: [Subsystem; 4] = [
+        Subsystem::Other,
+        Subsystem::GameTick,
+        Subsystem::CompressTicks,
+        Subsystem::NetworkIo,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Subsystem::Other => "other",
+            Subsystem::GameTick => "game_tick",
+            Subsystem::CompressTicks => "compress_ticks",
+            Subsystem::NetworkIo => "network_io",
+        }
+    }
+}
+
+const SUBSYSTEMS: usize = Subsystem::ALL.len();
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOC_COUNT: [AtomicU64; SUBSYSTEMS] = [const { AtomicU64::new(0) }; SUBSYSTEMS];
+static ALLOC_BYTES: [AtomicU64; SUBSYSTEMS] = [const { AtomicU64::new(0) }; SUBSYSTEMS];
+
+thread_local! {
+    static CURRENT: Cell<Subsystem> = const { Cell::new(Subsystem::Other) };
+}
+
+/// Global allocator that counts bytes before forwarding to [`Inner`].
+struct CountingAllocator;
+
+impl CountingAllocator {
+    fn record_alloc(size: usize) {
+        let live = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+        // `try_with` because the thread-local may already be torn down.
+        let subsystem = CURRENT.try_with(Cell::get).unwrap_or(Subsystem::Other) as usize;
+        ALLOC_COUNT[subsystem].fetch_add(1, Ordering::Relaxed);
+        ALLOC_BYTES[subsystem].fetch_add(size as u64, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        LIVE_BYTES.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { INNER.alloc(layout) };
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { INNER.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { INNER.dealloc(ptr, layout) };
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { INNER.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Guard returned by [`enter`]; restores the previous subsystem on drop.
+pub(crate) struct SubsystemScope {
+    previous: Subsystem,
+}
+
+impl Drop for SubsystemScope {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
+
+/// Attribute allocations on this thread to `subsystem` until the returned
+/// guard is dropped.
+///
+/// # Arguments
+///
+/// * `subsystem` - Subsystem being entered.
+///
+/// # Returns
+///
+/// * Guard restoring the previous subsystem.
+pub(crate) fn enter(subsystem: Subsystem) -> SubsystemScope {
+    SubsystemScope {
+        previous: CURRENT.with(|current| current.replace(subsystem)),
+    }
+}
+
+/// Logs a memory report every interval.
+pub(crate) struct MemoryReporter {
+    interval: Duration,
+    last_report: Instant,
+    last_counts: [u64; SUBSYSTEMS],
+    last_bytes: [u64; SUBSYSTEMS],
+}
+
+impl MemoryReporter {
+    /// Create a reporter using [`INTERVAL_ENV`] or the default interval.
+    ///
+    /// # Returns
+    ///
+    /// * A reporter whose first report is due one interval from now.
+    pub(crate) fn new() -> Self {
+        let interval = std::env::var(INTERVAL_ENV)
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_INTERVAL);
+        log::info!(
+            "Memory profiling enabled (allocator: {}, report every {}s)",
+            allocator_name(),
+            interval.as_secs()
+        );
+        Self {
+            interval,
+            last_report: Instant::now(),
+            last_counts: [0; SUBSYSTEMS],
+            last_bytes: [0; SUBSYSTEMS],
+        }
+    }
+
+    /// Log a report if the interval has elapsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state whose table footprint is reported.
+    pub(crate) fn maybe_report(&mut self, gs: &GameState) {
+        if self.last_report.elapsed() < self.interval {
+            return;
+        }
+        self.last_report = Instant::now();
+
+        let rss = read_rss_bytes()
+            .map(format_bytes)
+            .unwrap_or_else(|| "n/a".to_owned());
+        log::info!(
+            "Memory: rss={} heap_live={} heap_peak={}{}",
+            rss,
+            format_bytes(LIVE_BYTES.load(Ordering::Relaxed) as u64),
+            format_bytes(PEAK_BYTES.load(Ordering::Relaxed) as u64),
+            allocator_stats()
+        );
+
+        let mut churn = Vec::with_capacity(SUBSYSTEMS);
+        for subsystem in Subsystem::ALL {
+            let index = subsystem as usize;
+            let count = ALLOC_COUNT[index].load(Ordering::Relaxed);
+            let bytes = ALLOC_BYTES[index].load(Ordering::Relaxed);
+            churn.push(format!(
+                "{}={} allocs/{}",
+                subsystem.name(),
+                count - self.last_counts[index],
+                format_bytes(bytes - self.last_bytes[index])
+            ));
+            self.last_counts[index] = count;
+            self.last_bytes[index] = bytes;
+        }
+        log::info!(
+            "Memory: allocation churn since last report: {}",
+            churn.join(", ")
+        );
+
+        let tables: Vec<String> = table_footprint(gs)
+            .into_iter()
+            .map(|(name, bytes)| format!("{}={}", name, format_bytes(bytes)))
+            .collect();
+        log::info!("Memory: game state tables: {}", tables.join(", "));
+    }
+}
+
+/// Approximate heap footprint of the game-state tables.
+///
+/// Counts allocated capacity times element size; heap data owned by the
+/// elements themselves (strings, player buffers) is not included.
+///
+/// # Arguments
+///
+/// * `gs` - Game state to measure.
+///
+/// # Returns
+///
+/// * `(table name, bytes)` pairs.
+fn table_footprint(gs: &GameState) -> Vec<(&'static str, u64)> {
+    fn vec_bytes<T>(values: &Vec<T>) -> u64 {
+        (values.capacity() * std::mem::size_of::<T>()) as u64
+    }
+    vec![
+        ("map", vec_bytes(&gs.map)),
+        ("items", vec_bytes(&gs.items)),
+        ("item_templates", vec_bytes(&gs.item_templates)),
+        ("characters", vec_bytes(&gs.characters)),
+        ("character_templates", vec_bytes(&gs.character_templates)),
+        ("effects", vec_bytes(&gs.effects)),
+        ("see_map", vec_bytes(&gs.see_map)),
+        ("players", vec_bytes(&gs.players)),
+    ]
+}
+
+/// Resident set size of this process, where the platform exposes it.
+fn read_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+fn allocator_name() -> &'static str {
+    "jemalloc"
+}
+
+#[cfg(not(all(feature = "jemalloc", not(target_env = "msvc"))))]
+fn allocator_name() -> &'static str {
+    "system"
+}
+
+/// jemalloc's own view of the heap, including fragmentation and memory
+/// retained but not returned to the OS.
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc")))]
+fn allocator_stats() -> String {
+    use tikv_jemalloc_ctl::{epoch, stats};
+
+    if epoch::advance().is_err() {
+        return String::new();
+    }
+    let read = |value: Result<usize, tikv_jemalloc_ctl::Error>| {
+        value
+            .map(|bytes| format_bytes(bytes as u64))
+            .unwrap_or_else(|_| "n/a".to_owned())
+    };
+    format!(
+        " jemalloc_allocated={} active={} resident={} mapped={} retained={}",
+        read(stats::allocated::read()),
+        read(stats::active::read()),
+        read(stats::resident::read()),
+        read(stats::mapped::read()),
+        read(stats::retained::read())
+    )
+}
+
+#[cfg(not(all(feature = "jemalloc", not(target_env = "msvc"))))]
+fn allocator_stats() -> String {
+    String::new()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MIB: u64 = 1024 * 1024;
+    if bytes >= MIB {
+        format!("{:.1}MiB", bytes as f64 / MIB as f64)
+    } else {
+        format!("{:.1}KiB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_are_attributed_to_the_entered_subsystem() {
+        let index = Subsystem::CompressTicks as usize;
+        let before = ALLOC_BYTES[index].load(Ordering::Relaxed);
+        {
+            let _scope = enter(Subsystem::CompressTicks);
+            let buffer = vec![0u8; 4096];
+            std::hint::black_box(&buffer);
+        }
+        assert!(ALLOC_BYTES[index].load(Ordering::Relaxed) - before >= 4096);
+        assert_eq!(CURRENT.with(Cell::get), Subsystem::Other);
+    }
+}
@@ -3,6 +3,7 @@ use core::server_commands::ServerCommandType;
 use std::net::Shutdown;
 use std::sync::{OnceLock, RwLock};
 
+use crate::transport::Transport;
 use crate::{game_state::GameState, player};
 
 static PACKET_STATS: OnceLock<RwLock<PacketStats>> = OnceLock::new();
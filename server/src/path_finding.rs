@@ -3,6 +3,12 @@
 //! This module provides pathfinding capabilities for characters to navigate
 //! through the game world, taking into account obstacles, movement costs,
 //! and directional constraints.
+//!
+//! Targets further away than one region cluster are routed over the
+//! [`RegionGraph`] first: the tile search then only runs to the next portal
+//! on that route. The direct search's node budget is sized for open ground,
+//! so without the graph NPCs gave up (and stood still) as soon as a wall
+//! forced a long detour.
 
 use std::cmp::Ordering;
 use std::cmp::{max, min};
@@ -10,8 +16,13 @@ use std::collections::BinaryHeap;
 
 use core::{constants::*, traits};
 
+use crate::region_graph::{CLUSTER_SIZE, RegionGraph};
+
 const MAX_NODES: usize = 4096;
 
+/// Furthest a region-route waypoint may be from the searching character.
+const WAYPOINT_LOOKAHEAD: i16 = 2 * CLUSTER_SIZE as i16;
+
 /// A node in the A* search graph
 #[derive(Clone, Copy, Debug)]
 struct Node {
@@ -76,6 +87,8 @@ pub struct PathFinder {
     bad_targets: Vec<BadTarget>,
     /// Set when exceeding maxstep allocations.
     failed: bool,
+    /// Coarse connectivity used to route around long detours.
+    regions: RegionGraph,
 }
 
 impl PathFinder {
@@ -95,9 +108,47 @@ impl PathFinder {
             touched_visited: Vec::with_capacity(MAX_NODES),
             bad_targets: vec![BadTarget { tick: 0 }; map_size],
             failed: false,
+            regions: RegionGraph::new(),
         }
     }
 
+    /// Build the region graph for the loaded world.
+    ///
+    /// Until this runs, every search is a direct tile search.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - World map tiles.
+    /// * `items` - Item table, for static blocking items.
+    pub fn build_regions(&mut self, map: &[core::types::Map], items: &[core::types::Item]) {
+        let start = std::time::Instant::now();
+        self.regions.build(map, items);
+        if self.regions.is_built() {
+            log::info!(
+                "Built pathfinding region graph: {} regions in {:.2?}",
+                self.regions.region_count(),
+                start.elapsed()
+            );
+        }
+    }
+
+    /// Refresh the region graph around a tile whose static blockers changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`, `y` - Changed tile.
+    /// * `map` - World map tiles.
+    /// * `items` - Item table.
+    pub fn refresh_regions_at(
+        &mut self,
+        x: usize,
+        y: usize,
+        map: &[core::types::Map],
+        items: &[core::types::Item],
+    ) {
+        self.regions.rebuild_at(x, y, map, items);
+    }
+
     /// Reset internal search state prior to running a new A* invocation.
     ///
     /// Clears node allocations, the open set, and visited flags so the
@@ -663,6 +714,42 @@ impl PathFinder {
             max_step = MAX_NODES;
         }
 
+        // Distant targets: walk towards the next portal on the region route.
+        // Falls through to the direct search if that portal cannot be reached.
+        if distance > CLUSTER_SIZE {
+            if let Some((wx, wy)) =
+                self.regions
+                    .next_waypoint((character.x, character.y), (x1, y1), WAYPOINT_LOOKAHEAD)
+            {
+                let waypoint_m = (i32::from(wx) + i32::from(wy) * SERVER_MAPX) as usize;
+                let waypoint_mode = if Self::is_passable(map, items, waypoint_m, mapblock) {
+                    0
+                } else {
+                    1
+                };
+                let waypoint_distance =
+                    max((character.x - wx).abs(), (character.y - wy).abs()) as usize;
+                self.reset();
+                let result = self.astar(
+                    character.x,
+                    character.y,
+                    character.dir,
+                    map,
+                    items,
+                    mapblock,
+                    waypoint_mode,
+                    wx,
+                    wy,
+                    0,
+                    0,
+                    min(waypoint_distance * 8 + 100, MAX_NODES),
+                );
+                if result.is_some() {
+                    return result;
+                }
+            }
+        }
+
         // Reset state for new search
         self.reset();
 
@@ -838,4 +925,35 @@ mod tests {
         // exact target tile to be independently validated/passable.
         let _ = pf.find_path(&character, &map, &items, 0, edge_x, edge_y, 1, 0, 0);
     }
+
+    /// A wall with a single far-away gap needs a detour longer than the
+    /// direct search's node budget; the region route gets around it.
+    #[test]
+    fn find_path_routes_long_detours_over_regions() {
+        let mapx = SERVER_MAPX as usize;
+        let mut map = vec![core::types::Map::default(); (SERVER_MAPX * SERVER_MAPY) as usize];
+        for y in 0..SERVER_MAPY as usize {
+            if y != 500 {
+                map[40 + y * mapx].flags |= u64::from(MF_MOVEBLOCK);
+            }
+        }
+        let items = vec![core::types::Item::default(); 1];
+        let character = core::types::Character {
+            x: 20,
+            y: 20,
+            dir: DX_DOWN,
+            ..Default::default()
+        };
+
+        let mut pf = PathFinder::new();
+        assert_eq!(
+            pf.find_path(&character, &map, &items, 0, 60, 20, 0, 0, 0),
+            None
+        );
+
+        let mut pf = PathFinder::new();
+        pf.build_regions(&map, &items);
+        let dir = pf.find_path(&character, &map, &items, 0, 60, 20, 0, 0, 0);
+        assert!(matches!(dir, Some(DX_DOWN | DX_LEFTDOWN | DX_RIGHTDOWN)));
+    }
 }
@@ -0,0 +1,276 @@
+//! `SV_SETCHARSHEET` snapshots for clients advertising
+//! `CLIENT_CAP_CHAR_SHEET`.
+//!
+//! A snapshot carries everything `plr_change_stats` and friends would send
+//! piecemeal. After sending it the player's `cpl` baseline is overwritten
+//! with the same values, so the regular delta updates continue from the
+//! state the client was just reset to.
+//!
+//! Every [`CHAR_CHECKSUM_INTERVAL`] ticks the same clients also get an
+//! `SV_CHARCHECKSUM` of the sheet; a client whose own copy hashes
+//! differently answers with `CmdRequestResync` and receives a new sheet.
+
+use core::char_sheet::CharSheet;
+use core::constants::{CLIENT_CAP_CHAR_SHEET, ST_NORMAL, TICKS};
+use core::server_commands::ServerCommandType;
+use core::skills::MAX_SKILLS;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+use crate::player::tick::{citem_wire_sprite, item_wire_sprite};
+
+/// Ticks between two `SV_CHARCHECKSUM` packets to the same player.
+pub const CHAR_CHECKSUM_INTERVAL: i32 = TICKS * 10;
+
+/// Returns `true` if player `nr` advertised support for character sheets.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+///
+/// # Returns
+///
+/// * Whether `SV_SETCHARSHEET` may be sent to this player.
+pub fn supports_char_sheet(gs: &GameState, nr: usize) -> bool {
+    gs.players[nr].capabilities & CLIENT_CAP_CHAR_SHEET != 0
+}
+
+/// Builds the client-visible sheet of character `cn`.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character to describe.
+///
+/// # Returns
+///
+/// * The sheet in client units (sprites, whole points).
+pub fn build_char_sheet(gs: &GameState, cn: usize) -> CharSheet {
+    let ch = &gs.characters[cn];
+    let round = |v: i32| ((v + 500) / 1000).clamp(0, i32::from(u16::MAX)) as u16;
+    let mut sheet = CharSheet {
+        name: ch.name,
+        mode: ch.mode,
+        dir: ch.dir,
+        attrib: ch.attrib,
+        hp: ch.hp,
+        end: ch.end,
+        mana: ch.mana,
+        a_hp: round(ch.a_hp),
+        a_end: round(ch.a_end),
+        a_mana: round(ch.a_mana),
+        points: ch.points as u32,
+        points_tot: ch.points_tot as u32,
+        kindred: ch.kindred as u32,
+        gold: ch.gold,
+        armor: i32::from(ch.armor),
+        weapon: i32::from(ch.weapon),
+        ..CharSheet::default()
+    };
+    sheet.skill.copy_from_slice(&ch.skill[..MAX_SKILLS]);
+    for i in 0..40 {
+        let (sprite, placement) = item_wire_sprite(gs, ch.item[i] as usize);
+        sheet.item[i] = sprite as u16;
+        sheet.item_p[i] = placement;
+    }
+    for i in 0..20 {
+        let (sprite, placement) = item_wire_sprite(gs, ch.worn[i] as usize);
+        sheet.worn[i] = sprite as u16;
+        sheet.worn_p[i] = placement;
+    }
+    for i in 0..20 {
+        let in_idx = ch.spell[i] as usize;
+        if in_idx == 0 {
+            continue;
+        }
+        let it = &gs.items[in_idx];
+        sheet.spell[i] = it.sprite[1] as u16;
+        sheet.active[i] = ((it.active * 16) / it.duration.max(1)) as i16;
+        sheet.spell_type[i] = it.temp as i16;
+    }
+    let (sprite, placement) = citem_wire_sprite(gs, ch.citem as usize);
+    sheet.citem = sprite as u16;
+    sheet.citem_p = placement;
+    sheet
+}
+
+/// Sends a full character sheet to player `nr` and resets the delta
+/// baseline to match it.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+///
+/// # Returns
+///
+/// * `false` if the player is not in game or did not advertise
+///   `CLIENT_CAP_CHAR_SHEET`.
+pub fn plr_send_char_sheet(gs: &mut GameState, nr: usize) -> bool {
+    let cn = gs.players[nr].usnr;
+    if gs.players[nr].state != ST_NORMAL
+        || cn == 0
+        || cn >= core::constants::MAXCHARS
+        || !supports_char_sheet(gs, nr)
+    {
+        return false;
+    }
+
+    let sheet = build_char_sheet(gs, cn);
+    let buf = sheet.to_bytes(ServerCommandType::SetCharSheet as u8);
+    network_manager::xsend(gs, nr, &buf, buf.len());
+
+    let ch = gs.characters[cn];
+    let cpl = &mut gs.players[nr].cpl;
+    cpl.name = ch.name;
+    cpl.mode = i32::from(ch.mode);
+    cpl.dir = i32::from(ch.dir);
+    cpl.attrib = ch.attrib;
+    cpl.hp = ch.hp;
+    cpl.end = ch.end;
+    cpl.mana = ch.mana;
+    cpl.a_hp = i32::from(sheet.a_hp);
+    cpl.a_end = i32::from(sheet.a_end);
+    cpl.a_mana = i32::from(sheet.a_mana);
+    cpl.points = ch.points;
+    cpl.points_tot = ch.points_tot;
+    cpl.kindred = ch.kindred;
+    cpl.gold = ch.gold;
+    cpl.armor = i32::from(ch.armor);
+    cpl.weapon = i32::from(ch.weapon);
+    cpl.skill[..MAX_SKILLS].copy_from_slice(&ch.skill[..MAX_SKILLS]);
+    cpl.item = ch.item.map(|v| v as i32);
+    cpl.worn = ch.worn.map(|v| v as i32);
+    cpl.spell = ch.spell.map(|v| v as i32);
+    cpl.active = sheet.active.map(|v| v as i8);
+    cpl.spell_type = sheet.spell_type;
+    cpl.citem = ch.citem as i32;
+    true
+}
+
+/// Sends `SV_CHARCHECKSUM` to player `nr` if the checksum interval has
+/// elapsed. Must run right after `plr_change_stats`, so the character's
+/// state equals what the client has been sent.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+///
+/// # Returns
+///
+/// * `true` if a checksum was sent.
+pub fn plr_send_char_checksum(gs: &mut GameState, nr: usize) -> bool {
+    let cn = gs.players[nr].usnr;
+    let ticker = gs.globals.ticker;
+    if gs.players[nr].state != ST_NORMAL
+        || cn == 0
+        || cn >= core::constants::MAXCHARS
+        || !supports_char_sheet(gs, nr)
+        || ticker.wrapping_sub(gs.players[nr].last_checksum_tick) < CHAR_CHECKSUM_INTERVAL
+    {
+        return false;
+    }
+
+    let checksum = build_char_sheet(gs, cn).checksum();
+    let mut buf = [0u8; 5];
+    buf[0] = ServerCommandType::CharChecksum as u8;
+    buf[1..5].copy_from_slice(&checksum.to_le_bytes());
+    network_manager::xsend(gs, nr, &buf, 5);
+    gs.players[nr].last_checksum_tick = ticker;
+    true
+}
+
+/// Answers a client's `CmdRequestResync` with a fresh sheet, at most once
+/// per [`CHAR_CHECKSUM_INTERVAL`].
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+/// * `client_checksum` - Checksum the client computed, for the log.
+///
+/// # Returns
+///
+/// * `true` if a sheet was sent.
+pub fn plr_resync_char_sheet(gs: &mut GameState, nr: usize, client_checksum: u32) -> bool {
+    let ticker = gs.globals.ticker;
+    if ticker.wrapping_sub(gs.players[nr].last_resync_tick) < CHAR_CHECKSUM_INTERVAL {
+        log::debug!("Ignoring resync request from player {}: too soon", nr);
+        return false;
+    }
+    if !plr_send_char_sheet(gs, nr) {
+        return false;
+    }
+    gs.players[nr].last_resync_tick = ticker;
+
+    let cn = gs.players[nr].usnr;
+    let server_checksum = build_char_sheet(gs, cn).checksum();
+    chlog!(
+        cn,
+        "Client state checksum mismatch (client {:#010x}, server {:#010x}), resynced",
+        client_checksum,
+        server_checksum
+    );
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+
+    #[test]
+    fn sheet_uses_client_units() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].a_hp = 41_600;
+            gs.characters[cn].gold = 777;
+            gs.items[5].used = USE_ACTIVE;
+            gs.items[5].sprite = [100, 101];
+            gs.items[5].placement = 2;
+            gs.characters[cn].item[3] = 5;
+            gs.characters[cn].citem = 0x8000_0000 | 5_000;
+
+            let sheet = build_char_sheet(gs, cn);
+            assert_eq!(sheet.a_hp, 42);
+            assert_eq!(sheet.gold, 777);
+            assert_eq!((sheet.item[3], sheet.item_p[3]), (100, 2));
+            assert_eq!((sheet.citem, sheet.citem_p), (40, 0));
+        });
+    }
+
+    #[test]
+    fn sheet_requires_capability() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            gs.characters[cn].gold = 1_234;
+            assert!(!plr_send_char_sheet(gs, nr));
+            assert_ne!(gs.players[nr].cpl.gold, 1_234);
+
+            gs.players[nr].capabilities = CLIENT_CAP_CHAR_SHEET;
+            assert!(plr_send_char_sheet(gs, nr));
+            assert_eq!(gs.players[nr].cpl.gold, 1_234);
+        });
+    }
+
+    #[test]
+    fn checksum_and_resync_are_rate_limited() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            gs.players[nr].capabilities = CLIENT_CAP_CHAR_SHEET;
+            gs.globals.ticker = CHAR_CHECKSUM_INTERVAL;
+            assert!(plr_send_char_checksum(gs, nr));
+            assert!(!plr_send_char_checksum(gs, nr));
+
+            assert!(plr_resync_char_sheet(gs, nr, 0));
+            assert!(!plr_resync_char_sheet(gs, nr, 0));
+
+            gs.globals.ticker += CHAR_CHECKSUM_INTERVAL;
+            assert!(plr_send_char_checksum(gs, nr));
+            assert!(plr_resync_char_sheet(gs, nr, 0));
+        });
+    }
+}
@@ -0,0 +1,201 @@
+//! Structured combat events (`SV_COMBATEVENT`).
+//!
+//! `do_hurt` reports every hit it resolves through [`report_hit`], which
+//! sends one [`CombatEvent`] to the player who dealt it and one to the player
+//! who took it, if their clients advertise [`CLIENT_CAP_COMBAT_LOG`]. Most of
+//! the breakdown is known inside `do_hurt`; callers that know more, such as
+//! which skill caused the hit or how much spell immunity resisted, pass it
+//! in a [`HitSource`].
+
+use core::combat_log::{
+    COMBAT_FLAG_OUTGOING, CombatEvent, DamageKind, NO_SKILL, encode_combat_event,
+};
+use core::constants::{CLIENT_CAP_COMBAT_LOG, ST_NORMAL};
+use core::server_commands::ServerCommandType;
+use core::skills;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// What a caller of `do_hurt_from` knows about a hit beyond its damage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HitSource {
+    /// Skill that caused the hit; `None` lets [`HitSource::skill_byte`]
+    /// guess from the damage type.
+    pub skill: Option<usize>,
+    /// Damage, in `do_hurt` units, that spell immunity removed before the
+    /// damage passed to `do_hurt` was rolled.
+    pub resisted: i32,
+}
+
+impl HitSource {
+    /// A hit caused by `skill` that nothing resisted.
+    ///
+    /// # Arguments
+    ///
+    /// * `skill` - Skill number.
+    pub fn skill(skill: usize) -> Self {
+        Self {
+            skill: Some(skill),
+            resisted: 0,
+        }
+    }
+
+    /// A spell hit caused by `skill`, of which immunity resisted `resisted`.
+    ///
+    /// # Arguments
+    ///
+    /// * `skill` - Skill number.
+    /// * `resisted` - Damage lost to immunity, in `do_hurt` units.
+    pub fn resisted(skill: usize, resisted: i32) -> Self {
+        Self {
+            skill: Some(skill),
+            resisted: resisted.max(0),
+        }
+    }
+
+    /// Skill byte of the event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Attacker character index.
+    /// * `kind` - Damage type of the hit.
+    ///
+    /// # Returns
+    ///
+    /// * The given skill; otherwise `SK_WEAPON` for a melee hit by a
+    ///   character, the skill `do_attack` fights with, and [`NO_SKILL`] for
+    ///   anything else.
+    pub fn skill_byte(&self, cn: usize, kind: DamageKind) -> u8 {
+        match self.skill {
+            Some(skill) => u8::try_from(skill).unwrap_or(NO_SKILL),
+            None if cn != 0 && kind == DamageKind::Melee => skills::SK_WEAPON as u8,
+            None => NO_SKILL,
+        }
+    }
+}
+
+/// Sends the hit of `cn` on `co` to the players on both ends of it.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Attacker character index; 0 for damage without a source.
+/// * `co` - Target character index.
+/// * `event` - The hit with names and the outgoing flag left empty; they
+///   are filled in here.
+pub(crate) fn report_hit(gs: &mut GameState, cn: usize, co: usize, mut event: CombatEvent) {
+    event.attacker = if cn == 0 {
+        String::new()
+    } else {
+        gs.characters[cn].get_name().to_owned()
+    };
+    event.target = gs.characters[co].get_name().to_owned();
+
+    if let Some(nr) = gs.owning_player(co) {
+        plr_send_combat_event(gs, nr.index(), &event);
+    }
+    if cn != 0
+        && cn != co
+        && let Some(nr) = gs.owning_player(cn)
+    {
+        event.flags |= COMBAT_FLAG_OUTGOING;
+        plr_send_combat_event(gs, nr.index(), &event);
+    }
+}
+
+/// Sends `SV_COMBATEVENT` to player `nr`, if their client keeps a combat log.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+/// * `event` - The hit, as seen by that player.
+///
+/// # Returns
+///
+/// * `true` if a packet was sent.
+pub fn plr_send_combat_event(gs: &mut GameState, nr: usize, event: &CombatEvent) -> bool {
+    if gs.players[nr].state != ST_NORMAL || gs.players[nr].capabilities & CLIENT_CAP_COMBAT_LOG == 0
+    {
+        return false;
+    }
+
+    let buf = encode_combat_event(ServerCommandType::CombatEvent as u8, event);
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use crate::tls::GameStream;
+    use core::combat_log::{COMBAT_EVENT_PACKET_LEN, decode_combat_event};
+    use std::net::{TcpListener, TcpStream};
+
+    fn attach_test_socket(gs: &mut GameState, nr: usize) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind test listener");
+        let addr = listener.local_addr().expect("listener addr");
+        let client = TcpStream::connect(addr).expect("connect client");
+        let (server, _) = listener.accept().expect("accept client");
+        drop(client);
+        gs.players[nr].sock = Some(GameStream::Plain(server));
+    }
+
+    #[test]
+    fn skill_falls_back_to_the_fight_skill_for_melee() {
+        let source = HitSource::default();
+        assert_eq!(
+            source.skill_byte(5, DamageKind::Melee),
+            skills::SK_WEAPON as u8
+        );
+        assert_eq!(source.skill_byte(0, DamageKind::Melee), NO_SKILL);
+        assert_eq!(source.skill_byte(5, DamageKind::Reflect), NO_SKILL);
+        assert_eq!(
+            HitSource::resisted(skills::SK_BLAST, -3).skill_byte(5, DamageKind::Spell),
+            skills::SK_BLAST as u8
+        );
+        assert_eq!(HitSource::resisted(skills::SK_BLAST, -3).resisted, 0);
+    }
+
+    #[test]
+    fn combat_events_require_capability() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            let event = CombatEvent::default();
+            assert!(!plr_send_combat_event(gs, nr, &event));
+
+            gs.players[nr].capabilities = CLIENT_CAP_COMBAT_LOG;
+            assert!(plr_send_combat_event(gs, nr, &event));
+        });
+    }
+
+    #[test]
+    fn do_hurt_reports_the_armor_breakdown_to_the_target() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            gs.players[nr].capabilities = CLIENT_CAP_COMBAT_LOG;
+            gs.characters[cn].armor = 8;
+            gs.characters[cn].hp[5] = 100;
+            gs.characters[cn].a_hp = 100_000;
+
+            // A sourceless melee hit of 20 against armor 8 takes 12 * 250.
+            assert_eq!(gs.do_hurt(0, cn, 20, 0), 3);
+
+            assert!(gs.players[nr].tptr >= COMBAT_EVENT_PACKET_LEN);
+            assert_eq!(gs.players[nr].tbuf[0], ServerCommandType::CombatEvent as u8);
+            let event = decode_combat_event(&gs.players[nr].tbuf).unwrap();
+            assert_eq!(DamageKind::from_u8(event.kind), DamageKind::Melee);
+            assert_eq!(event.skill, NO_SKILL);
+            assert_eq!(
+                (event.raw, event.mitigated, event.dealt),
+                (5_000, 2_000, 3_000)
+            );
+            assert!(!event.is_outgoing() && !event.is_kill());
+            assert_eq!(event.attacker, "");
+            assert_eq!(event.target, "Tester");
+        });
+    }
+}
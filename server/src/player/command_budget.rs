@@ -0,0 +1,148 @@
+//! Soft execution-time budget for client commands.
+//!
+//! [`process_frames`](super::framing::process_frames) times every command it
+//! dispatches. A command that runs longer than [`COMMAND_BUDGET`] is logged
+//! with its opcode, raw arguments and character, and counts as a strike
+//! against the player. [`OFFENDER_STRIKES`] strikes within
+//! [`STRIKE_WINDOW_TICKS`] make the player an offender: until the window
+//! runs out their frames are dispatched at most
+//! [`OFFENDER_FRAMES_PER_TICK`] per tick and the rest stay buffered for the
+//! following ticks, so a single pathological command stream cannot blow
+//! the tick for everyone else.
+
+use std::time::Duration;
+
+use core::client_commands::ClientCommandType;
+use core::constants::TICKS;
+
+use crate::game_state::GameState;
+use crate::player::framing::CLIENT_FRAME_LEN;
+
+/// Longest a single command may run before it is reported.
+pub(crate) const COMMAND_BUDGET: Duration = Duration::from_millis(2);
+
+/// Overruns within one window that make a player an offender.
+pub(crate) const OFFENDER_STRIKES: u32 = 3;
+
+/// Length of the strike window (10 seconds).
+pub(crate) const STRIKE_WINDOW_TICKS: i32 = TICKS * 10;
+
+/// Frames dispatched per tick for an offender; the rest are deferred.
+pub(crate) const OFFENDER_FRAMES_PER_TICK: usize = 1;
+
+/// Budget overruns of one player slot.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommandBudget {
+    /// Overruns in the current window.
+    strikes: u32,
+    /// Ticker value of the first overrun in the current window.
+    window_start: i32,
+}
+
+impl CommandBudget {
+    /// Records how long one command took.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - Time spent in the command handler.
+    /// * `ticker` - Current server tick.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the command overran [`COMMAND_BUDGET`].
+    pub(crate) fn record(&mut self, elapsed: Duration, ticker: i32) -> bool {
+        if elapsed <= COMMAND_BUDGET {
+            return false;
+        }
+        if self.strikes == 0 || ticker - self.window_start > STRIKE_WINDOW_TICKS {
+            self.strikes = 0;
+            self.window_start = ticker;
+        }
+        self.strikes += 1;
+        true
+    }
+
+    /// Returns whether the player's frames are currently being throttled.
+    ///
+    /// # Arguments
+    ///
+    /// * `ticker` - Current server tick.
+    pub(crate) fn is_offender(&self, ticker: i32) -> bool {
+        self.strikes >= OFFENDER_STRIKES && ticker - self.window_start <= STRIKE_WINDOW_TICKS
+    }
+}
+
+/// Charges one dispatched command against the player's budget and logs it
+/// if it overran.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot that sent the command.
+/// * `frame` - The command frame as it was dispatched.
+/// * `elapsed` - Time spent in [`plr_cmd`](super::plr_cmd).
+pub(crate) fn charge(
+    gs: &mut GameState,
+    nr: usize,
+    frame: &[u8; CLIENT_FRAME_LEN],
+    elapsed: Duration,
+) {
+    let ticker = gs.globals.ticker;
+    let budget = &mut gs.players[nr].command_budget;
+    let was_offender = budget.is_offender(ticker);
+    if !budget.record(elapsed, ticker) {
+        return;
+    }
+    let offender = budget.is_offender(ticker);
+
+    let cn = gs.players[nr].usnr;
+    let name = gs
+        .characters
+        .get(cn)
+        .map(|ch| ch.get_name().to_owned())
+        .unwrap_or_default();
+    let args: Vec<String> = frame[1..].iter().map(|b| format!("{b:02x}")).collect();
+    log::warn!(
+        "Command over budget: plr_cmd/{:?} (args {}) from player {} (cn={} {}) took {} us, budget {} us",
+        ClientCommandType::from(frame[0]),
+        args.join(" "),
+        nr,
+        cn,
+        name,
+        elapsed.as_micros(),
+        COMMAND_BUDGET.as_micros()
+    );
+    if offender && !was_offender {
+        log::warn!(
+            "Player {} (cn={} {}) overran the command budget {} times; deferring to {} command(s) per tick",
+            nr,
+            cn,
+            name,
+            OFFENDER_STRIKES,
+            OFFENDER_FRAMES_PER_TICK
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strikes_within_the_window_make_an_offender() {
+        let slow = COMMAND_BUDGET * 2;
+        let mut budget = CommandBudget::default();
+        assert!(!budget.record(COMMAND_BUDGET, 100));
+        for _ in 0..OFFENDER_STRIKES - 1 {
+            assert!(budget.record(slow, 100));
+        }
+        assert!(!budget.is_offender(100));
+        assert!(budget.record(slow, 101));
+        assert!(budget.is_offender(101));
+        assert!(!budget.is_offender(100 + STRIKE_WINDOW_TICKS + 1));
+
+        // A late overrun starts a new window instead of extending the old one.
+        assert!(budget.record(slow, 100 + STRIKE_WINDOW_TICKS + 1));
+        assert!(!budget.is_offender(100 + STRIKE_WINDOW_TICKS + 1));
+    }
+}
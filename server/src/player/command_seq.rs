@@ -0,0 +1,204 @@
+//! Duplicate suppression for sequence-tagged client commands.
+//!
+//! Clients advertising `CLIENT_CAP_COMMAND_SEQ` send a `CmdSeq` frame in
+//! front of every command that must not be applied twice (see
+//! [`ClientCommandType::is_sequenced`]). [`plr_cmd_seq`] parks the number
+//! until the next frame arrives; [`admit`] then checks it against the
+//! connection's [`ReplayWindow`] and tells
+//! [`process_frames`](super::framing::process_frames) to skip the frame if
+//! that sequence number was already applied. After the frames of a tick are
+//! processed, [`plr_send_command_ack`] reports the highest applied number
+//! with `SV_COMMANDACK`, so the client knows which commands it may stop
+//! retransmitting.
+//!
+//! The window tracks the last [`REPLAY_WINDOW`] numbers below the highest
+//! one seen, so a retransmission that lands after newer commands is still
+//! recognised. Anything older than the window is treated as a duplicate.
+
+use core::client_commands::ClientCommandType;
+use core::constants::CLIENT_CAP_COMMAND_SEQ;
+use core::server_commands::ServerCommandType;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Sequence numbers remembered below the highest one applied.
+pub(crate) const REPLAY_WINDOW: u32 = 64;
+
+/// Sliding bitmap of recently applied sequence numbers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayWindow {
+    /// Highest sequence number applied; `0` before the first one.
+    highest: u32,
+    /// Bit `n` is set when `highest - n` was applied.
+    seen: u64,
+}
+
+impl ReplayWindow {
+    /// Records `seq` if it has not been applied yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `seq` - Sequence number carried by `CmdSeq`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the command should be applied, `false` for a duplicate,
+    ///   a number older than the window, or the reserved `0`.
+    pub(crate) fn accept(&mut self, seq: u32) -> bool {
+        if seq == 0 {
+            return false;
+        }
+        if seq > self.highest {
+            let shift = seq - self.highest;
+            self.seen = if shift >= REPLAY_WINDOW {
+                0
+            } else {
+                self.seen << shift
+            };
+            self.seen |= 1;
+            self.highest = seq;
+            return true;
+        }
+        let age = self.highest - seq;
+        if age >= REPLAY_WINDOW || self.seen & (1 << age) != 0 {
+            return false;
+        }
+        self.seen |= 1 << age;
+        true
+    }
+
+    /// Highest sequence number applied so far.
+    pub(crate) fn highest(&self) -> u32 {
+        self.highest
+    }
+}
+
+/// Per-connection sequencing state.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommandSeq {
+    /// Applied sequence numbers.
+    window: ReplayWindow,
+    /// Number from a `CmdSeq` frame waiting for the command it tags.
+    pending: Option<u32>,
+    /// A tagged command was applied or dropped since the last ack.
+    ack_due: bool,
+}
+
+/// Handle the `CmdSeq` packet.
+///
+/// Stores the sequence number from `inbuf[1..5]` for the next frame.
+/// Ignored for clients that did not advertise `CLIENT_CAP_COMMAND_SEQ`.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_seq(gs: &mut GameState, nr: usize) {
+    let inbuf = &gs.players[nr].inbuf;
+    let seq = u32::from_le_bytes([inbuf[1], inbuf[2], inbuf[3], inbuf[4]]);
+    if gs.players[nr].capabilities & CLIENT_CAP_COMMAND_SEQ == 0 {
+        log::warn!(
+            "Player {} sent CmdSeq {} without advertising CLIENT_CAP_COMMAND_SEQ",
+            nr,
+            seq
+        );
+        return;
+    }
+    gs.players[nr].command_seq.pending = Some(seq);
+}
+
+/// Decides whether the frame about to be dispatched should be applied.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index.
+/// * `opcode` - First byte of the frame.
+///
+/// # Returns
+///
+/// * `false` if the frame repeats an already applied sequence number.
+pub(crate) fn admit(gs: &mut GameState, nr: usize, opcode: u8) -> bool {
+    if ClientCommandType::from(opcode) == ClientCommandType::CmdSeq {
+        return true;
+    }
+    let state = &mut gs.players[nr].command_seq;
+    let Some(seq) = state.pending.take() else {
+        return true;
+    };
+    state.ack_due = true;
+    if state.window.accept(seq) {
+        return true;
+    }
+    let highest = state.window.highest();
+    log::info!(
+        "Dropping duplicate {:?} (seq {}, highest applied {}) from player {} (cn={})",
+        ClientCommandType::from(opcode),
+        seq,
+        highest,
+        nr,
+        gs.players[nr].usnr
+    );
+    false
+}
+
+/// Builds an `SV_COMMANDACK` packet.
+///
+/// # Arguments
+///
+/// * `seq` - Highest applied sequence number.
+///
+/// # Returns
+///
+/// * The 5-byte packet.
+pub fn command_ack_packet(seq: u32) -> [u8; 5] {
+    let mut buf = [0u8; 5];
+    buf[0] = ServerCommandType::CommandAck as u8;
+    buf[1..5].copy_from_slice(&seq.to_le_bytes());
+    buf
+}
+
+/// Sends `SV_COMMANDACK` if a tagged command arrived since the last one.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index.
+///
+/// # Returns
+///
+/// * `true` if a packet was sent.
+pub fn plr_send_command_ack(gs: &mut GameState, nr: usize) -> bool {
+    if !gs.players[nr].command_seq.ack_due || gs.players[nr].sock.is_none() {
+        return false;
+    }
+    let state = &mut gs.players[nr].command_seq;
+    state.ack_due = false;
+    let buf = command_ack_packet(state.window.highest());
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_drops_repeats_and_numbers_older_than_the_window() {
+        let mut window = ReplayWindow::default();
+        assert!(!window.accept(0));
+        assert!(window.accept(1));
+        assert!(!window.accept(1));
+        assert!(window.accept(3));
+        // 2 arrives late but inside the window.
+        assert!(window.accept(2));
+        assert!(!window.accept(2));
+        assert_eq!(window.highest(), 3);
+
+        assert!(window.accept(3 + REPLAY_WINDOW));
+        assert!(!window.accept(3));
+        assert!(window.accept(2 + REPLAY_WINDOW));
+        assert!(!window.accept(2 + REPLAY_WINDOW));
+    }
+}
@@ -1,7 +1,8 @@
 use core::{
-    constants::CharacterFlags, logout_reasons::LogoutReason, server_commands::ServerCommandType,
-    string_operations::c_string_to_str,
+    constants::CharacterFlags, item_binding::Transfer, logout_reasons::LogoutReason,
+    server_commands::ServerCommandType, string_operations::c_string_to_str,
 };
+use server::journal::GoldTransferKind;
 
 use crate::{
     driver,
@@ -10,6 +11,7 @@ use crate::{
     network_manager,
     player::{
         connection::plr_logout,
+        drop_scatter,
         map::{plr_map_remove, plr_map_set},
         notify_character_tile,
     },
@@ -141,6 +143,7 @@ pub fn plr_cmd_ctick(gs: &mut GameState, nr: usize) {
 ///
 /// Reads `seq` and `client_time_ms` from the client's inbuf and replies with
 /// `SV_PONG`, echoing both values back to the client so it can compute RTT.
+/// Pings double as keepalives and refresh the player's `lasttick`.
 ///
 /// # Arguments
 ///
@@ -160,6 +163,8 @@ pub fn plr_cmd_ping(gs: &mut GameState, nr: usize) {
         gs.players[nr].inbuf[8],
     ]);
 
+    gs.players[nr].lasttick = gs.globals.ticker as u32;
+
     let mut buf = [0u8; 16];
     buf[0] = ServerCommandType::Pong as u8;
     buf[1..5].copy_from_slice(&seq.to_le_bytes());
@@ -399,6 +404,11 @@ pub fn plr_cmd_move(gs: &mut GameState, nr: usize) {
     let y = u16::from_le_bytes([gs.players[nr].inbuf[3], gs.players[nr].inbuf[4]]);
     let cn = gs.players[nr].usnr;
 
+    if crate::player::observer::is_observer(gs, cn) {
+        crate::player::observer::move_camera(gs, cn, i32::from(x), i32::from(y));
+        return;
+    }
+
     let ticker = gs.globals.ticker;
 
     // Set to debug because otherwise this is incredibly noisy
@@ -815,6 +825,8 @@ pub fn plr_cmd_shop(gs: &mut GameState, nr: usize) {
     if (co & 0x8000) != 0 {
         let idx = co & 0x7fff;
         gs.do_depot_char(cn, idx, n);
+    } else if gs.is_broker(co) {
+        gs.do_broker_shop(cn, co, n);
     } else {
         gs.do_shop_char(cn, co, n, 0);
     }
@@ -1286,7 +1298,10 @@ pub fn plr_pickup(gs: &mut GameState, cn: usize) {
     let can_take =
         (gs.items[in_id as usize].flags & core::constants::ItemFlags::IF_TAKE.bits()) != 0;
 
-    if !can_take {
+    if !can_take
+        || !gs.check_loot_access(cn, in_id as usize)
+        || !gs.check_bound_access(cn, in_id as usize)
+    {
         gs.characters[cn].cerrno = core::constants::ERR_FAILED as u16;
         return;
     }
@@ -1302,7 +1317,7 @@ pub fn plr_pickup(gs: &mut GameState, cn: usize) {
     if is_money {
         let value = gs.items[in_id as usize].value;
 
-        gs.characters[cn].gold += value as i32;
+        gs.transfer_gold(0, cn, value as i32, GoldTransferKind::Pickup);
 
         let message = format!("You got {}G {}S\n", value / 100, value % 100);
         gs.do_character_log(cn, core::types::FontColor::Red, &message);
@@ -1361,6 +1376,12 @@ pub fn plr_pickup(gs: &mut GameState, cn: usize) {
     gs.items[in_id as usize].x = 0;
     gs.items[in_id as usize].y = 0;
     gs.items[in_id as usize].carried = cn as u16;
+    core::loot::release(&mut gs.items[in_id as usize]);
+    gs.bind_item(
+        cn,
+        in_id as usize,
+        core::constants::ItemFlags::IF_BIND_PICKUP,
+    );
 
     if active != 0 && light_active != 0 {
         gs.do_add_light(i32::from(x), i32::from(y), -i32::from(light_active));
@@ -1457,6 +1478,10 @@ pub fn plr_skill(gs: &mut GameState, cn: usize) {
 /// money-item template), building-mode drop semantics, step-action
 /// blockages, and updates lighting and map item references accordingly.
 ///
+/// If another item already lies there the drop is resolved by
+/// [`drop_scatter`]: money merges into money, anything else lands on a
+/// nearby free tile, and if none is in reach the item goes to the depot.
+///
 /// # Arguments
 /// * `cn` - Character index performing the drop
 pub fn plr_drop(gs: &mut GameState, cn: usize) {
@@ -1468,6 +1493,11 @@ pub fn plr_drop(gs: &mut GameState, cn: usize) {
         return;
     }
 
+    if in_id & 0x80000000 == 0 && gs.refuse_transfer(cn, in_id as usize, Transfer::Drop) {
+        gs.characters[cn].cerrno = core::constants::ERR_FAILED as u16;
+        return;
+    }
+
     let Some((m, x, y)) = plr_cardinal_front_tile(gs, cn) else {
         gs.characters[cn].cerrno = core::constants::ERR_FAILED as u16;
         return;
@@ -1489,7 +1519,6 @@ pub fn plr_drop(gs: &mut GameState, cn: usize) {
     // Check if tile is blocked
     let is_blocked = gs.map[m].ch != 0
         || gs.map[m].to_ch != 0
-        || gs.map[m].it != 0
         || (gs.map[m].flags & u64::from(core::constants::MF_MOVEBLOCK)) != 0;
 
     if is_blocked {
@@ -1497,6 +1526,33 @@ pub fn plr_drop(gs: &mut GameState, cn: usize) {
         return;
     }
 
+    // Resolve an occupied tile: merge money, scatter, or overflow to the depot.
+    let (m, x, y) = if in2 == 0 {
+        (m, x, y)
+    } else if in_id & 0x80000000 != 0
+        && gs.items[in2 as usize].flags & core::constants::ItemFlags::IF_MONEY.bits() != 0
+    {
+        let value = in_id & 0x7FFFFFFF;
+        drop_scatter::merge_money(gs, in2 as usize, value);
+        gs.characters[cn].citem = 0;
+        gs.record_gold_transfer(cn, 0, value as i32, GoldTransferKind::Drop);
+        gs.characters[cn].cerrno = core::constants::ERR_SUCCESS as u16;
+        gs.do_update_char(cn);
+        log::info!(
+            "Character {} dropped {}G {}S onto a pile",
+            cn,
+            value / 100,
+            value % 100
+        );
+        return;
+    } else if let Some((sx, sy)) = drop_scatter::find_scatter_tile(gs, i32::from(x), i32::from(y)) {
+        let sm = sx as usize + sy as usize * core::constants::SERVER_MAPX as usize;
+        (sm, sx as i16, sy as i16)
+    } else {
+        drop_to_depot(gs, cn, in_id);
+        return;
+    };
+
     gs.characters[cn].citem = 0;
     gs.characters[cn].cerrno = core::constants::ERR_SUCCESS as u16;
 
@@ -1523,35 +1579,10 @@ pub fn plr_drop(gs: &mut GameState, cn: usize) {
         let len = bytes.len().min(40);
         reference[..len].copy_from_slice(&bytes[..len]);
         gs.items[new_in].reference = reference;
-
-        let (description, sprite) = if tmp > 999999 {
-            ("A huge pile of gold coins", 121)
-        } else if tmp > 99999 {
-            ("A very large pile of gold coins", 120)
-        } else if tmp > 9999 {
-            ("A large pile of gold coins", 41)
-        } else if tmp > 999 {
-            ("A small pile of gold coins", 40)
-        } else if tmp > 99 {
-            ("Some gold coins", 39)
-        } else if tmp > 9 {
-            ("A pile of silver coins", 38)
-        } else if tmp > 2 {
-            ("A few silver coins", 37)
-        } else if tmp == 2 {
-            ("A couple of silver coins", 37)
-        } else {
-            ("A lonely silver coin", 37)
-        };
-
-        let mut description_bytes = [0u8; 200];
-        let bytes = description.as_bytes();
-        let len = bytes.len().min(200);
-        description_bytes[..len].copy_from_slice(&bytes[..len]);
-        gs.items[new_in].description = description_bytes;
-        gs.items[new_in].sprite[0] = sprite;
+        drop_scatter::refresh_money_appearance(gs, new_in);
 
         log::info!("Character {} dropped {}G {}S", cn, tmp / 100, tmp % 100);
+        gs.record_gold_transfer(cn, 0, tmp as i32, GoldTransferKind::Drop);
 
         new_in as u32
     } else {
@@ -1591,6 +1622,46 @@ pub fn plr_drop(gs: &mut GameState, cn: usize) {
     }
 }
 
+/// Overflow for [`plr_drop`] when no tile near the drop target is free:
+/// moves the carried item into the character's depot.
+///
+/// Money, depot-restricted items and items that may not be given away stay
+/// in hand and the drop fails.
+///
+/// # Arguments
+/// * `cn` - Character index performing the drop
+/// * `in_id` - The carried item (`citem`)
+fn drop_to_depot(gs: &mut GameState, cn: usize, in_id: u32) {
+    let item_id = in_id as usize;
+    let storable = in_id & 0x80000000 == 0
+        && gs.items[item_id].flags & core::constants::ItemFlags::IF_NODEPOT.bits() == 0
+        && gs.do_maygive(cn, 0, item_id);
+
+    if !storable || !gs.do_add_depot(cn, item_id) {
+        gs.characters[cn].cerrno = core::constants::ERR_FAILED as u16;
+        gs.do_character_log(
+            cn,
+            core::types::FontColor::Red,
+            "There is no room to drop that here.\n",
+        );
+        return;
+    }
+
+    gs.characters[cn].citem = 0;
+    gs.characters[cn].cerrno = core::constants::ERR_SUCCESS as u16;
+    gs.do_update_char(cn);
+    let item_ref = c_string_to_str(&gs.items[item_id].reference).to_owned();
+    gs.do_character_log(
+        cn,
+        core::types::FontColor::Yellow,
+        &format!(
+            "There is no room here, so you put {} into your depot.\n",
+            item_ref
+        ),
+    );
+    log::info!("Character {} overflowed a drop into the depot", cn);
+}
+
 /// Port of `plr_misc` from `svr_act.cpp`
 ///
 /// Dispatches the character's misc action (`status2`) to the appropriate
@@ -1863,6 +1934,159 @@ pub fn plr_cmd_reset_talents(gs: &mut GameState, nr: usize) {
     send_set_char_talents(gs, nr);
 }
 
+/// Handle one `CmdSetProfile` chunk.
+///
+/// Appends the chunk's 13 bio bytes to the player's
+/// [`ProfileUpload`](crate::player::framing::ProfileUpload). When the
+/// final-chunk flag is set, the assembled bio is handed to
+/// [`GameState::do_set_profile`]. Oversized or out-of-order uploads close the
+/// connection with `LogoutReason::ProtocolViolation`.
+///
+/// # Arguments
+///
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_set_profile(gs: &mut GameState, nr: usize) {
+    use core::profile::PROFILE_CHUNK_LEN;
+
+    let index_byte = gs.players[nr].inbuf[1];
+    let name_color = gs.players[nr].inbuf[2];
+    let mut chunk = [0u8; PROFILE_CHUNK_LEN];
+    chunk.copy_from_slice(&gs.players[nr].inbuf[3..3 + PROFILE_CHUNK_LEN]);
+
+    let raw = match gs.players[nr].profile_upload.push(index_byte, &chunk) {
+        Ok(Some(raw)) => raw,
+        Ok(None) => return,
+        Err(error) => {
+            crate::player::framing::reject_stream(gs, nr, error);
+            return;
+        }
+    };
+    let bio = c_string_to_str(&raw).to_owned();
+
+    let cn = gs.players[nr].usnr;
+    gs.do_set_profile(cn, name_color, &bio);
+}
+
+/// Send the earned/selected title snapshot for `nr`'s character.
+///
+/// Wire format: 1-byte opcode (`SetCharTitles = 77`), earned mask (u32 LE),
+/// selected title id (u8).
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index.
+pub fn send_set_char_titles(gs: &mut GameState, nr: usize) {
+    let cn = gs.players[nr].usnr;
+    let mut buf: [u8; 6] = [0; 6];
+    buf[0] = ServerCommandType::SetCharTitles as u8;
+    buf[1..5].copy_from_slice(&gs.earned_title_mask(cn).to_le_bytes());
+    buf[5] = gs.selected_title(cn);
+    network_manager::xsend(gs, nr, &buf, 6);
+}
+
+/// Handle the `CmdSetTitle` packet.
+///
+/// Reads the title id from `inbuf[1]` and applies it via
+/// [`GameState::do_select_title`], which validates ownership and replies
+/// with a fresh title snapshot.
+///
+/// # Arguments
+///
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_set_title(gs: &mut GameState, nr: usize) {
+    let cn = gs.players[nr].usnr;
+    let title_id = gs.players[nr].inbuf[1];
+    gs.do_select_title(cn, title_id);
+}
+
+/// Handle the `CmdNpcMenu` packet (right-click on a character).
+///
+/// Reads the target from `inbuf[1..3]` and either replies with
+/// `SV_NPCMENU` or falls back to the legacy look; see
+/// [`GameState::do_npc_menu`].
+///
+/// # Arguments
+///
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_npc_menu(gs: &mut GameState, nr: usize) {
+    let cn = gs.players[nr].usnr;
+    let co = u16::from_le_bytes([gs.players[nr].inbuf[1], gs.players[nr].inbuf[2]]) as usize;
+    gs.do_npc_menu(cn, co);
+}
+
+/// Handle the `CmdNpcAction` packet (NPC menu selection).
+///
+/// # Arguments
+///
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_npc_action(gs: &mut GameState, nr: usize) {
+    let cn = gs.players[nr].usnr;
+    let co = u16::from_le_bytes([gs.players[nr].inbuf[1], gs.players[nr].inbuf[2]]) as usize;
+    let Some(option) = core::npc_menu::NpcMenuOption::from_u8(gs.players[nr].inbuf[3]) else {
+        log::warn!(
+            "Player {} sent unknown NPC menu option {}",
+            nr,
+            gs.players[nr].inbuf[3]
+        );
+        return;
+    };
+    gs.do_npc_action(cn, co, option);
+}
+
+/// Handle the `CmdLearnSkill` packet (buy a skill from a trainer).
+///
+/// Reads the trainer from `inbuf[1..3]` and the skill from `inbuf[3]`; all
+/// validation happens in [`GameState::do_learn_skill`].
+///
+/// # Arguments
+///
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_learn_skill(gs: &mut GameState, nr: usize) {
+    let cn = gs.players[nr].usnr;
+    let co = u16::from_le_bytes([gs.players[nr].inbuf[1], gs.players[nr].inbuf[2]]) as usize;
+    let skill = gs.players[nr].inbuf[3] as usize;
+    gs.do_learn_skill(cn, co, skill);
+}
+
+/// Handle the `CmdClientCaps` packet (client capability announcement).
+///
+/// Stores the `CLIENT_CAP_*` mask from `inbuf[1..5]` and sends the
+/// initial character sheet, clock, view radius, minimap markers and
+/// backpack item names to clients that understand them.
+///
+/// # Arguments
+///
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_client_caps(gs: &mut GameState, nr: usize) {
+    let inbuf = &gs.players[nr].inbuf;
+    let caps = u32::from_le_bytes([inbuf[1], inbuf[2], inbuf[3], inbuf[4]]);
+    gs.players[nr].capabilities = caps;
+    crate::player::char_sheet::plr_send_char_sheet(gs, nr);
+    crate::player::time_sync::plr_send_time_sync(gs, nr, true);
+    crate::player::view::plr_send_view(gs, nr);
+    crate::player::map_markers::plr_send_map_markers(gs, nr, true);
+    crate::player::item_names::plr_send_item_names(gs, nr, true);
+    crate::guilds::commands::plr_send_guild(gs, nr);
+    crate::mail::commands::plr_send_mailbox(gs, nr);
+    crate::friends::commands::plr_send_friends(gs, nr);
+    crate::player::observer::plr_send_observer(gs, nr);
+}
+
+/// Handle the `CmdRequestResync` packet (state checksum mismatch).
+///
+/// Reads the client's own checksum from `inbuf[1..5]` for the log and
+/// answers with a fresh character sheet.
+///
+/// # Arguments
+///
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_request_resync(gs: &mut GameState, nr: usize) {
+    let inbuf = &gs.players[nr].inbuf;
+    let client_checksum = u32::from_le_bytes([inbuf[1], inbuf[2], inbuf[3], inbuf[4]]);
+    crate::player::char_sheet::plr_resync_char_sheet(gs, nr, client_checksum);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2061,6 +2285,28 @@ mod tests {
         });
     }
 
+    #[test]
+    fn plr_cmd_set_profile_reassembles_chunks() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            let packets = core::client_commands::ClientCommand::new_profile_packets(
+                4,
+                b"Tester keeps watch over the northern road.",
+            );
+            for packet in &packets {
+                write_inbuf(gs, nr, &packet.to_bytes());
+                plr_cmd_set_profile(gs, nr);
+            }
+
+            assert_eq!(
+                c_string_to_str(&gs.characters[cn].description),
+                "Tester keeps watch over the northern road."
+            );
+            assert_eq!(gs.name_color(cn), core::profile::NameColor::Azure);
+            assert!(gs.players[nr].profile_upload.is_idle());
+        });
+    }
+
     #[test]
     fn plr_cmd_ctick_updates_roundtrip_timing() {
         with_test_gs(|gs| {
@@ -2084,9 +2330,11 @@ mod tests {
             packet[1..5].copy_from_slice(&77u32.to_le_bytes());
             packet[5..9].copy_from_slice(&1234u32.to_le_bytes());
             write_inbuf(gs, nr, &packet);
+            gs.globals.ticker = 500;
 
             plr_cmd_ping(gs, nr);
 
+            assert_eq!(gs.players[nr].lasttick, 500);
             assert_eq!(gs.players[nr].tptr, 16);
             assert_eq!(gs.players[nr].tbuf[0], ServerCommandType::Pong as u8);
             assert_eq!(
@@ -2821,6 +3069,35 @@ mod tests {
         });
     }
 
+    #[test]
+    fn plr_pickup_refuses_loot_reserved_for_another_player() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            gs.characters[cn].dir = core::constants::DX_RIGHT;
+            configure_item(
+                gs,
+                11,
+                "Rock",
+                "rock",
+                "A test rock.",
+                ItemFlags::IF_TAKE.bits(),
+                11,
+                Some((11, 10)),
+            );
+            core::loot::protect(&mut gs.items[11], 2, gs.globals.ticker);
+
+            plr_pickup(gs, cn);
+            assert_eq!(gs.characters[cn].cerrno, core::constants::ERR_FAILED as u16);
+            assert_eq!(gs.map[map_index(11, 10)].it, 11);
+
+            gs.globals.ticker += core::loot::LOOT_PROTECT_SECONDS * core::constants::TICKS;
+            plr_pickup(gs, cn);
+            assert_eq!(gs.characters[cn].item[0], 11);
+            assert_eq!(core::loot::owner(&gs.items[11], 0), None);
+        });
+    }
+
     #[test]
     fn plr_use_and_plr_skill_cover_guard_paths() {
         with_test_gs(|gs| {
@@ -2888,6 +3165,54 @@ mod tests {
         });
     }
 
+    #[test]
+    fn plr_drop_scatters_merges_money_and_overflows_to_depot() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].dir = core::constants::DX_RIGHT;
+            configure_item(gs, 20, "Rock", "rock", "A rock.", 0, 10, Some((11, 10)));
+            configure_item(gs, 21, "Stick", "stick", "A stick.", 0, 10, None);
+
+            gs.characters[cn].citem = 21;
+            plr_drop(gs, cn);
+            assert_eq!(gs.characters[cn].citem, 0);
+            assert_eq!(gs.map[map_index(11, 10)].it, 20);
+            assert_eq!(gs.map[map_index(12, 10)].it, 21);
+            assert_eq!((gs.items[21].x, gs.items[21].y), (12, 10));
+
+            configure_item(
+                gs,
+                22,
+                "Money",
+                "some money",
+                "Coins.",
+                ItemFlags::IF_MONEY.bits(),
+                0,
+                Some((11, 10)),
+            );
+            gs.items[22].value = 100;
+            gs.characters[cn].citem = 0x8000_0000 | 50;
+            plr_drop(gs, cn);
+            assert_eq!(gs.characters[cn].citem, 0);
+            assert_eq!(gs.items[22].value, 150);
+
+            // Fill every tile in reach: the next drop goes to the depot.
+            gs.map[map_index(10, 10)].ch = cn as u32;
+            for y in 8..=12 {
+                for x in 9..=13 {
+                    if gs.map[map_index(x, y)].it == 0 && (x, y) != (10, 10) {
+                        gs.map[map_index(x, y)].flags |= u64::from(MF_MOVEBLOCK);
+                    }
+                }
+            }
+            configure_item(gs, 23, "Gem", "gem", "A gem.", 0, 10, None);
+            gs.characters[cn].citem = 23;
+            plr_drop(gs, cn);
+            assert_eq!(gs.characters[cn].citem, 0);
+            assert_eq!(gs.characters[cn].depot[0], 23);
+        });
+    }
+
     #[test]
     fn plr_misc_dispatch_and_status_helpers_cover_known_and_unknown_paths() {
         with_test_gs(|gs| {
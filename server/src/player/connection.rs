@@ -1,6 +1,7 @@
 use core::{
     ban_store::BanTarget,
-    constants::CharacterFlags,
+    constants::{CLIENT_CAP_DISCONNECT_REASON, CharacterFlags},
+    disconnect::{ban_text, encode_disconnect},
     logout_reasons::LogoutReason,
     server_commands::ServerCommandType,
     skills,
@@ -9,6 +10,7 @@ use core::{
     types::{CharacterSummary, Sex, api::GameLoginTicketMetadata},
 };
 
+use server::journal::GoldTransferKind;
 use server::keydb::connection as keydb;
 
 use crate::{game_state::GameState, god::God, network_manager};
@@ -47,7 +49,12 @@ pub fn plr_login(gs: &mut GameState, nr: usize) {
     let version = gs.players[nr].version as u32;
     if version < core::constants::MINVERSION {
         log::warn!("Client too old ({}). Logout demanded", version);
-        plr_logout(gs, 0, nr, LogoutReason::VersionMismatch);
+        let text = format!(
+            "Your client is version {}, this server needs {} or newer. Please update your client.",
+            version_text(version),
+            version_text(core::constants::MINVERSION)
+        );
+        plr_logout_with_text(gs, 0, nr, LogoutReason::VersionMismatch, &text);
         return;
     }
 
@@ -86,7 +93,11 @@ pub fn plr_login(gs: &mut GameState, nr: usize) {
     //       plr_logout(cn, ch[cn].player, LO_IDLE);
     //   }
     // and then continue the login (no early return).
-    let already_active = gs.characters[cn].used != core::constants::USE_NONACTIVE
+    // A linkdead character is still in the world; pick it up where it stands.
+    let resumed = gs.resume_linkdead(cn);
+
+    let already_active = !resumed
+        && gs.characters[cn].used != core::constants::USE_NONACTIVE
         && (gs.characters[cn].flags & CharacterFlags::ComputerControlledPlayer.bits()) == 0;
     if already_active {
         log::warn!("Login as {} who is already active", cn);
@@ -116,12 +127,18 @@ pub fn plr_login(gs: &mut GameState, nr: usize) {
     if is_kicked {
         log::warn!("Login as {} denied (kicked)", cn);
         gs.characters[cn].flags &= !CharacterFlags::Kicked.bits();
-        plr_logout(gs, 0, nr, LogoutReason::Kicked);
+        plr_logout_with_text(
+            gs,
+            0,
+            nr,
+            LogoutReason::Kicked,
+            "You were just kicked from the game. You may log in again.",
+        );
         return;
     }
 
-    if login_target_is_banned(gs, nr, cn) {
-        plr_logout(gs, 0, nr, LogoutReason::Kicked);
+    if let Some((reason, text)) = login_ban(gs, nr, cn) {
+        plr_logout_with_text(gs, 0, nr, reason, &text);
         return;
     }
 
@@ -132,7 +149,13 @@ pub fn plr_login(gs: &mut GameState, nr: usize) {
         != 0;
     if !exempt && God::is_banned(gs, banned as i32) {
         log::info!("{} is banned, sent away", cn);
-        plr_logout(gs, 0, nr, LogoutReason::Kicked);
+        plr_logout_with_text(
+            gs,
+            0,
+            nr,
+            LogoutReason::Banned,
+            "Your address is banned from this server.",
+        );
         return;
     }
 
@@ -174,11 +197,13 @@ pub fn plr_login(gs: &mut GameState, nr: usize) {
     // talent panel immediately after login.
     crate::player::commands::send_set_char_talents(gs, nr);
 
+    // unlock any rank titles the character already qualifies for (e.g.
+    // characters created before titles existed) and sync the picker.
+    gs.refresh_earned_titles(cn);
+    crate::player::commands::send_set_char_titles(gs, nr);
+
     // mark active and set login date, addr, add net history
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as u32;
+    let now = crate::wall_clock::unix_now() as u32;
 
     let ch = &mut gs.characters[cn];
     ch.used = core::constants::USE_ACTIVE;
@@ -206,12 +231,19 @@ pub fn plr_login(gs: &mut GameState, nr: usize) {
     // Try to drop character at tavern/nearby
     let tav_x = gs.characters[cn].tavern_x as usize;
     let tav_y = gs.characters[cn].tavern_y as usize;
-    if !God::drop_char_fuzzy_large(gs, cn, tav_x, tav_y, tav_x, tav_y)
+    if !resumed
+        && !God::drop_char_fuzzy_large(gs, cn, tav_x, tav_y, tav_x, tav_y)
         && !God::drop_char_fuzzy_large(gs, cn, tav_x + 3, tav_y, tav_x, tav_y)
         && !God::drop_char_fuzzy_large(gs, cn, tav_x, tav_y + 3, tav_x, tav_y)
     {
         log::error!("plr_login(): could not drop new character");
-        plr_logout(gs, cn, nr, LogoutReason::NoRoom);
+        plr_logout_with_text(
+            gs,
+            cn,
+            nr,
+            LogoutReason::NoRoom,
+            "There is no room to place your character right now. Please try again shortly.",
+        );
         return;
     }
 
@@ -273,10 +305,24 @@ pub fn plr_login(gs: &mut GameState, nr: usize) {
 
     // announce
     let name = gs.characters[cn].get_name().to_owned();
-    gs.do_announce(cn, 0, &format!("{} entered the game.\n", name));
+    if resumed {
+        gs.do_announce(cn, 0, &format!("{} reconnected.\n", name));
+    } else {
+        gs.do_announce(cn, 0, &format!("{} entered the game.\n", name));
+    }
+    crate::guilds::commands::guild_presence_changed(gs, cn, true);
+    crate::friends::commands::friend_presence_changed(gs, cn, true);
+    crate::mail::commands::mail_login_notice(gs, cn);
 }
 
-fn login_target_is_banned(gs: &GameState, nr: usize, cn: usize) -> bool {
+/// Checks the account, character and address of a login against the
+/// durable ban store.
+///
+/// # Returns
+///
+/// * `None` when the login may proceed, otherwise the logout reason and the
+///   text sent to the client. A failed lookup denies the login as well.
+fn login_ban(gs: &GameState, nr: usize, cn: usize) -> Option<(LogoutReason, String)> {
     let checks = [
         BanTarget::Account {
             account_id: gs.players[nr].api_account_id,
@@ -290,17 +336,22 @@ fn login_target_is_banned(gs: &GameState, nr: usize, cn: usize) -> bool {
     ];
 
     for target in checks {
-        match server::keydb::ban::target_is_banned(&target) {
-            Ok(true) => {
+        match server::keydb::ban::active_ban_for_target(&target) {
+            Ok(Some(record)) => {
                 log::info!(
                     "login for character {} denied by {} ban {}",
                     cn,
                     target.scope(),
                     target.value()
                 );
-                return true;
+                let text = ban_text(
+                    &record.reason,
+                    record.expires_at,
+                    server::keydb::ban::now_secs(),
+                );
+                return Some((LogoutReason::Banned, text));
             }
-            Ok(false) => {}
+            Ok(None) => {}
             Err(error) => {
                 log::warn!(
                     "login for character {} denied because ban lookup failed for {} {}: {}",
@@ -309,12 +360,15 @@ fn login_target_is_banned(gs: &GameState, nr: usize, cn: usize) -> bool {
                     target.value(),
                     error
                 );
-                return true;
+                return Some((
+                    LogoutReason::Failure,
+                    "The login could not be checked right now. Please try again later.".to_owned(),
+                ));
             }
         }
     }
 
-    false
+    None
 }
 
 fn resolve_api_login_character(
@@ -426,7 +480,10 @@ fn apply_api_login_character_record(
         }
         None => {
             let template_id = get_race_integer(character.sex == Sex::Male, character.class);
-            let maybe_cn = God::create_char(gs, template_id as usize, true);
+            // With starter kits configured the template's legacy items are
+            // skipped; the kit is granted below instead.
+            let with_template_items = gs.starter_kits.is_none();
+            let maybe_cn = God::create_char(gs, template_id as usize, with_template_items);
             let cn = match maybe_cn {
                 Some(value) => value as usize,
                 None => {
@@ -462,6 +519,14 @@ fn apply_api_login_character_record(
                 // need the legacy in-game name/description finalization state.
                 gs.characters[cn].flags |= CharacterFlags::Player.bits();
                 gs.characters[cn].flags &= !CharacterFlags::NewUser.bits();
+
+                if let Err(err) = gs.grant_starter_kit(cn) {
+                    log::error!(
+                        "Starter kit for API character {} not granted: {}",
+                        character.id,
+                        err
+                    );
+                }
             }
 
             cn
@@ -495,6 +560,27 @@ fn apply_api_login_character_record(
 /// * `player_id` - Associated player slot id (0 if none, interpreted as "any player")
 /// * `reason` - Reason for logout (enum)
 pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, reason: LogoutReason) {
+    plr_logout_with_text(gs, character_id, player_id, reason, "");
+}
+
+/// [`plr_logout`] with a sentence explaining the logout to the player.
+///
+/// Clients advertising `CLIENT_CAP_DISCONNECT_REASON` get `text` in an
+/// `SV_DISCONNECT` right before `SV_EXIT`; when `text` is empty they only
+/// see the fixed label of `reason`. Other clients get the plain `SV_EXIT`.
+///
+/// # Arguments
+/// * `character_id` - Character index being logged out (0 if none)
+/// * `player_id` - Associated player slot id (0 if none)
+/// * `reason` - Reason for logout
+/// * `text` - Explanation for the player; may be empty
+pub fn plr_logout_with_text(
+    gs: &mut GameState,
+    character_id: usize,
+    player_id: usize,
+    reason: LogoutReason,
+    text: &str,
+) {
     let player_id = if player_id < core::constants::MAXPLAYER {
         player_id
     } else {
@@ -537,6 +623,8 @@ pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, rea
 
     // Main logout logic for active players
     if character_matches_player {
+        gs.linkdead.remove(&character_id);
+
         let character_flags = gs.characters[character_id].flags;
         let (is_player, is_not_ccp) = (
             character_flags & CharacterFlags::Player.bits() != 0,
@@ -594,7 +682,7 @@ pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, rea
                             core::types::FontColor::Red,
                             money_stolen_message.as_str(),
                         );
-                        gs.characters[character_id].gold -= gold_tenth;
+                        gs.transfer_gold(character_id, 0, gold_tenth, GoldTransferKind::DemonTheft);
 
                         // In the original protocol, the high bit marks "money in hand".
                         let citem = gs.characters[character_id].citem;
@@ -611,6 +699,12 @@ pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, rea
                 }
             }
 
+            // An observer is not on the map; it logs in again on its feet and
+            // gets no lag scroll back to wherever its camera was.
+            let was_observer =
+                gs.characters[character_id].flags & CharacterFlags::Observer.bits() != 0;
+            gs.characters[character_id].flags &= !CharacterFlags::Observer.bits();
+
             // Clear map positions
             let ch = gs.characters[character_id];
             let (map_index, to_map_index, light, character_x, character_y) = (
@@ -640,9 +734,10 @@ pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, rea
             gs.remove_enemy(character_id);
 
             // Handle lag scroll
-            if reason == LogoutReason::IdleTooLong
-                || reason == LogoutReason::Shutdown
-                || reason == LogoutReason::Unknown
+            if !was_observer
+                && (reason == LogoutReason::IdleTooLong
+                    || reason == LogoutReason::Shutdown
+                    || reason == LogoutReason::Unknown)
             {
                 let ch = gs.characters[character_id];
                 let (is_close_to_temple, map_index) = (
@@ -718,15 +813,14 @@ pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, rea
 
                 character.data[96] = 0;
                 character.used = core::constants::USE_NONACTIVE;
-                character.logout_date = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as u32;
+                character.logout_date = crate::wall_clock::unix_now() as u32;
 
                 character.flags |= CharacterFlags::SaveMe.bits();
             }
 
             gs.do_announce(character_id, 0, &format!("{} left the game.\n", name));
+            crate::guilds::commands::guild_presence_changed(gs, character_id, false);
+            crate::friends::commands::friend_presence_changed(gs, character_id, false);
         }
     }
 
@@ -737,15 +831,28 @@ pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, rea
     // already active and kick the new connection.
     if player_id != 0 {
         if reason != LogoutReason::Unknown && reason != LogoutReason::Usurp {
+            let explained = gs.players[player_id].capabilities & CLIENT_CAP_DISCONNECT_REASON != 0;
             let mut buffer: [u8; 16] = [0; 16];
             buffer[0] = ServerCommandType::Exit as u8;
-            buffer[1] = reason as u8;
+            buffer[1] = if explained || reason != LogoutReason::Banned {
+                reason as u8
+            } else {
+                LogoutReason::Kicked as u8
+            };
 
             let player_state = gs.players[player_id].state;
+            let disconnect = explained
+                .then(|| encode_disconnect(ServerCommandType::Disconnect as u8, reason, text));
 
             if player_state == core::constants::ST_NORMAL {
+                if let Some(disconnect) = &disconnect {
+                    network_manager::xsend(gs, player_id, disconnect, disconnect.len());
+                }
                 network_manager::xsend(gs, player_id, &buffer, 2);
             } else {
+                if let Some(disconnect) = &disconnect {
+                    network_manager::csend(gs, player_id, disconnect, disconnect.len() as u8);
+                }
                 network_manager::csend(gs, player_id, &buffer, 2);
             }
         }
@@ -754,6 +861,22 @@ pub fn plr_logout(gs: &mut GameState, character_id: usize, player_id: usize, rea
     }
 }
 
+/// Handle a connection that dropped without a logout.
+///
+/// Puts the character into the linkdead grace period (see
+/// [`crate::state::linkdead`]) so it is not torn out of a fight by a network
+/// hiccup; characters that are not eligible are logged out immediately.
+///
+/// # Arguments
+/// * `gs` - Active game state.
+/// * `character_id` - Character controlled by the connection (0 if none).
+/// * `player_id` - Player slot of the dropped connection.
+pub fn plr_disconnected(gs: &mut GameState, character_id: usize, player_id: usize) {
+    if !gs.go_linkdead(character_id, player_id) {
+        plr_logout(gs, character_id, player_id, LogoutReason::Unknown);
+    }
+}
+
 /// Finalize player exit operations and clear player slot state.
 ///
 /// Called after `plr_logout` to complete exit bookkeeping: updates the
@@ -815,6 +938,14 @@ pub fn plr_api_login(gs: &mut GameState, nr: usize) {
         gs.players[nr].inbuf[8],
     ]);
 
+    // Capabilities that matter before `CmdClientCaps`; zero from old clients.
+    gs.players[nr].capabilities = u32::from_le_bytes([
+        gs.players[nr].inbuf[9],
+        gs.players[nr].inbuf[10],
+        gs.players[nr].inbuf[11],
+        gs.players[nr].inbuf[12],
+    ]);
+
     let ticker = gs.globals.ticker as u32;
     gs.players[nr].state = core::constants::ST_LOGIN;
     gs.players[nr].lasttick = ticker;
@@ -828,6 +959,16 @@ pub fn plr_api_login(gs: &mut GameState, nr: usize) {
     send_mod(gs, nr);
 }
 
+/// Formats a packed client version such as `0x020E07` as `2.14.7`.
+fn version_text(version: u32) -> String {
+    format!(
+        "{}.{}.{}",
+        (version >> 16) & 0xff,
+        (version >> 8) & 0xff,
+        version & 0xff
+    )
+}
+
 /// Port of `send_mod` from `svr_tick.cpp`
 /// Sends mod data to the client (8 packets of 15 bytes each)
 fn send_mod(gs: &mut GameState, nr: usize) {
@@ -1099,6 +1240,44 @@ mod tests {
         });
     }
 
+    #[test]
+    fn plr_logout_explains_itself_to_capable_clients() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            gs.players[nr].state = ST_LOGIN;
+            gs.players[nr].capabilities = CLIENT_CAP_DISCONNECT_REASON;
+
+            plr_logout_with_text(gs, 0, nr, LogoutReason::Banned, "You are banned.");
+
+            let sent = gs.players[nr].obuf[..gs.players[nr].iptr].to_vec();
+            let disconnect = encode_disconnect(
+                ServerCommandType::Disconnect as u8,
+                LogoutReason::Banned,
+                "You are banned.",
+            );
+            assert_eq!(&sent[..disconnect.len()], &disconnect[..]);
+            assert_eq!(
+                &sent[disconnect.len()..],
+                &[ServerCommandType::Exit as u8, LogoutReason::Banned as u8]
+            );
+        });
+
+        // Older clients only get the bare exit, with a reason they know.
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            gs.players[nr].state = ST_LOGIN;
+
+            plr_logout_with_text(gs, 0, nr, LogoutReason::Banned, "You are banned.");
+
+            assert_eq!(
+                &gs.players[nr].obuf[..gs.players[nr].iptr],
+                &[ServerCommandType::Exit as u8, LogoutReason::Kicked as u8]
+            );
+        });
+    }
+
     #[test]
     fn player_exit_sets_exit_state_and_clears_character_mapping() {
         with_test_gs(|gs| {
@@ -1120,14 +1299,16 @@ mod tests {
             let (_, nr) = add_test_player(gs);
             attach_test_socket(gs, nr);
             gs.globals.ticker = 301;
-            let mut packet = [0u8; 9];
+            let mut packet = [0u8; 13];
             packet[1..9].copy_from_slice(&0x1122334455667788u64.to_le_bytes());
+            packet[9..13].copy_from_slice(&CLIENT_CAP_DISCONNECT_REASON.to_le_bytes());
             write_inbuf(gs, nr, &packet);
 
             plr_api_login(gs, nr);
 
             assert_eq!(gs.players[nr].state, ST_LOGIN);
             assert_eq!(gs.players[nr].login_ticket, 0x1122334455667788);
+            assert_eq!(gs.players[nr].capabilities, CLIENT_CAP_DISCONNECT_REASON);
             assert_eq!(gs.players[nr].usnr, 0);
             assert_eq!(gs.players[nr].api_character_id, 0);
             assert_eq!(gs.players[nr].iptr, 16 * 8);
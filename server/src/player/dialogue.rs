@@ -0,0 +1,135 @@
+//! Delivery of keyed NPC dialogue (see [`core::dialogue`]).
+//!
+//! Clients advertising `CLIENT_CAP_DIALOGUE_KEYS` get `SV_DIALOGUE` with the
+//! key and parameters and translate the line themselves. Everyone else gets
+//! the line rendered from [`GameState::dialogue`] in the locale their client
+//! announced with `CmdClientLocale`, or English if it never did.
+
+use core::chat::{ChatChannel, ChatStyle};
+use core::constants::CLIENT_CAP_DIALOGUE_KEYS;
+use core::dialogue::{self, DialogueCatalog, DialogueLine, MAX_LOCALE_LEN};
+use core::server_commands::ServerCommandType;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Environment variable naming a directory of `<locale>.txt` dialogue
+/// catalogs loaded on top of the built-in English lines.
+pub const DIALOGUE_DIR_ENV: &str = "MAG_DIALOGUE_DIR";
+
+/// Builds the dialogue catalog.
+///
+/// # Arguments
+///
+/// * `setting` - Value of [`DIALOGUE_DIR_ENV`], if set.
+///
+/// # Returns
+///
+/// * The built-in catalog when unset, the catalog extended with the
+///   directory's translations, or an error if a file cannot be read or
+///   parsed.
+pub fn load_dialogue(setting: Option<&str>) -> Result<DialogueCatalog, String> {
+    let mut catalog = DialogueCatalog::default();
+    match setting.map(str::trim) {
+        None | Some("") => {}
+        Some(dir) => {
+            let loaded = catalog.load_dir(std::path::Path::new(dir))?;
+            log::info!("Loaded {} dialogue translation(s) from {}.", loaded, dir);
+        }
+    }
+    Ok(catalog)
+}
+
+/// Handle the `CmdClientLocale` packet.
+///
+/// Stores the language code from `inbuf[1..9]`; codes that are not a
+/// language keep the previous locale.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_client_locale(gs: &mut GameState, nr: usize) {
+    let raw = &gs.players[nr].inbuf[1..1 + MAX_LOCALE_LEN];
+    let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+    let requested = String::from_utf8_lossy(&raw[..end]).into_owned();
+    let Some(locale) = dialogue::normalize_locale(&requested) else {
+        log::warn!("Player {} sent invalid locale {:?}", nr, requested);
+        return;
+    };
+    if !gs.dialogue.has_locale(&locale) {
+        log::debug!(
+            "Player {} asked for locale '{}' without a translation; using English",
+            nr,
+            locale
+        );
+    }
+    gs.players[nr].locale = locale;
+}
+
+/// Sends one dialogue line to player `nr`.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot.
+/// * `speaker` - Name of the speaking character.
+/// * `line` - Key and parameters.
+///
+/// # Returns
+///
+/// * `true` if it went out as `SV_DIALOGUE`, `false` if it was rendered
+///   server-side and sent as chat text.
+pub fn plr_send_dialogue(
+    gs: &mut GameState,
+    nr: usize,
+    speaker: &str,
+    line: &DialogueLine,
+) -> bool {
+    if gs.players[nr].capabilities & CLIENT_CAP_DIALOGUE_KEYS != 0 {
+        let buf =
+            dialogue::encode_dialogue_packet(ServerCommandType::Dialogue as u8, speaker, line);
+        network_manager::xsend(gs, nr, &buf, buf.len());
+        return true;
+    }
+
+    let text = gs.dialogue.render(line, &gs.players[nr].locale);
+    let message = format!("{}: \"{}\"\n", speaker, text);
+    let cn = gs.players[nr].usnr;
+    gs.do_character_styled_log(cn, ChatStyle::new(ChatChannel::Npc), &message);
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn locale_command_normalizes_and_rejects_garbage() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            assert_eq!(gs.players[nr].locale, dialogue::DEFAULT_LOCALE);
+
+            gs.players[nr].inbuf[1..6].copy_from_slice(b"de-AT");
+            plr_cmd_client_locale(gs, nr);
+            assert_eq!(gs.players[nr].locale, "de");
+
+            gs.players[nr].inbuf[1..9].copy_from_slice(b"12345678");
+            plr_cmd_client_locale(gs, nr);
+            assert_eq!(gs.players[nr].locale, "de");
+        });
+    }
+
+    #[test]
+    fn keys_go_only_to_capable_clients() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            let line = DialogueLine::new("npc.give.no_money");
+            assert!(!plr_send_dialogue(gs, nr, "Gunther", &line));
+
+            gs.players[nr].capabilities = CLIENT_CAP_DIALOGUE_KEYS;
+            assert!(plr_send_dialogue(gs, nr, "Gunther", &line));
+        });
+    }
+}
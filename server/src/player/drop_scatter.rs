@@ -0,0 +1,209 @@
+//! Where dropped items land when the tile in front of the dropper is taken.
+//!
+//! A map tile holds a single item, so dropping onto an occupied tile used to
+//! fail outright. [`plr_drop`](super::commands::plr_drop) now resolves a busy
+//! tile in this order:
+//!
+//! 1. Money dropped onto money merges into the existing pile.
+//! 2. Otherwise the item scatters to the nearest free tile within
+//!    [`SCATTER_RADIUS`], searched breadth-first from the target tile so it
+//!    never crosses walls or closed doors. Ties resolve in the fixed
+//!    [`NEIGHBOURS`] order, so the same layout always scatters the same way.
+//! 3. When no tile in reach is free, the item goes into the dropper's depot
+//!    as an overflow container instead of being refused.
+
+use std::collections::VecDeque;
+
+use core::constants::{ItemFlags, MF_DEATHTRAP, MF_MOVEBLOCK, SERVER_MAPX, SERVER_MAPY};
+
+use crate::game_state::GameState;
+
+/// Furthest a dropped item may land from the tile it was dropped on.
+pub(crate) const SCATTER_RADIUS: i32 = 2;
+
+/// Search order for neighbouring tiles: orthogonal first, then diagonal.
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Linear map index for `(x, y)`, or `None` off the map.
+fn map_index(x: i32, y: i32) -> Option<usize> {
+    if x < 1 || y < 1 || x >= SERVER_MAPX - 1 || y >= SERVER_MAPY - 1 {
+        return None;
+    }
+    Some((x + y * SERVER_MAPX) as usize)
+}
+
+/// Returns `true` when an item may be placed on tile `m`.
+fn accepts_item(gs: &GameState, m: usize) -> bool {
+    let tile = &gs.map[m];
+    tile.ch == 0
+        && tile.to_ch == 0
+        && tile.it == 0
+        && tile.fsprite == 0
+        && tile.flags & u64::from(MF_MOVEBLOCK | MF_DEATHTRAP) == 0
+}
+
+/// Returns `true` when the scatter search may continue through tile `m`.
+fn is_passable(gs: &GameState, m: usize) -> bool {
+    let tile = &gs.map[m];
+    tile.flags & u64::from(MF_MOVEBLOCK | MF_DEATHTRAP) == 0
+        && (tile.it == 0 || gs.items[tile.it as usize].flags & ItemFlags::IF_MOVEBLOCK.bits() == 0)
+}
+
+/// Find the free tile a drop on the occupied tile `(x, y)` scatters to.
+///
+/// # Arguments
+///
+/// * `gs` - Game state whose map is searched.
+/// * `x` - Tile the item was dropped on.
+/// * `y` - Tile the item was dropped on.
+///
+/// # Returns
+///
+/// * The nearest reachable free tile within [`SCATTER_RADIUS`], or `None`.
+pub(crate) fn find_scatter_tile(gs: &GameState, x: i32, y: i32) -> Option<(i32, i32)> {
+    let origin = map_index(x, y)?;
+    if !is_passable(gs, origin) {
+        return None;
+    }
+
+    let side = (2 * SCATTER_RADIUS + 1) as usize;
+    let mut visited = vec![false; side * side];
+    let slot = |tx: i32, ty: i32| {
+        (tx - x + SCATTER_RADIUS) as usize + (ty - y + SCATTER_RADIUS) as usize * side
+    };
+    visited[slot(x, y)] = true;
+
+    let mut queue = VecDeque::from([(x, y)]);
+    while let Some((cx, cy)) = queue.pop_front() {
+        for (dx, dy) in NEIGHBOURS {
+            let (tx, ty) = (cx + dx, cy + dy);
+            if (tx - x).abs() > SCATTER_RADIUS || (ty - y).abs() > SCATTER_RADIUS {
+                continue;
+            }
+            if std::mem::replace(&mut visited[slot(tx, ty)], true) {
+                continue;
+            }
+            let Some(m) = map_index(tx, ty) else {
+                continue;
+            };
+            if accepts_item(gs, m) {
+                return Some((tx, ty));
+            }
+            if is_passable(gs, m) {
+                queue.push_back((tx, ty));
+            }
+        }
+    }
+    None
+}
+
+/// Description and sprite of a money pile worth `value` silver.
+///
+/// # Arguments
+///
+/// * `value` - Amount in silver.
+///
+/// # Returns
+///
+/// * `(description, sprite)` for the pile.
+pub(crate) fn money_appearance(value: u32) -> (&'static str, i16) {
+    if value > 999999 {
+        ("A huge pile of gold coins", 121)
+    } else if value > 99999 {
+        ("A very large pile of gold coins", 120)
+    } else if value > 9999 {
+        ("A large pile of gold coins", 41)
+    } else if value > 999 {
+        ("A small pile of gold coins", 40)
+    } else if value > 99 {
+        ("Some gold coins", 39)
+    } else if value > 9 {
+        ("A pile of silver coins", 38)
+    } else if value > 2 {
+        ("A few silver coins", 37)
+    } else if value == 2 {
+        ("A couple of silver coins", 37)
+    } else {
+        ("A lonely silver coin", 37)
+    }
+}
+
+/// Set the description and sprite of money item `item_id` from its value.
+///
+/// # Arguments
+///
+/// * `gs` - Game state owning the item.
+/// * `item_id` - Money item to refresh.
+pub(crate) fn refresh_money_appearance(gs: &mut GameState, item_id: usize) {
+    let (description, sprite) = money_appearance(gs.items[item_id].value);
+    let mut description_bytes = [0u8; 200];
+    let bytes = description.as_bytes();
+    let len = bytes.len().min(200);
+    description_bytes[..len].copy_from_slice(&bytes[..len]);
+    gs.items[item_id].description = description_bytes;
+    gs.items[item_id].sprite[0] = sprite;
+}
+
+/// Add `value` silver to the money pile `item_id` lying on the ground.
+///
+/// # Arguments
+///
+/// * `gs` - Game state owning the item.
+/// * `item_id` - Money item the coins merge into.
+/// * `value` - Amount in silver to add.
+pub(crate) fn merge_money(gs: &mut GameState, item_id: usize, value: u32) {
+    gs.items[item_id].value = gs.items[item_id].value.saturating_add(value);
+    refresh_money_appearance(gs, item_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::with_test_gs;
+
+    fn index(x: i32, y: i32) -> usize {
+        map_index(x, y).unwrap()
+    }
+
+    #[test]
+    fn scatter_prefers_orthogonal_neighbours_and_respects_walls() {
+        with_test_gs(|gs| {
+            gs.map[index(20, 20)].it = 5;
+            assert_eq!(find_scatter_tile(gs, 20, 20), Some((21, 20)));
+
+            gs.map[index(21, 20)].it = 6;
+            assert_eq!(find_scatter_tile(gs, 20, 20), Some((19, 20)));
+
+            // Wall off every neighbour: nothing is reachable any more.
+            for (dx, dy) in NEIGHBOURS {
+                gs.map[index(20 + dx, 20 + dy)].flags |= u64::from(MF_MOVEBLOCK);
+            }
+            assert_eq!(find_scatter_tile(gs, 20, 20), None);
+
+            // Open one side: the search walks through it to radius two.
+            gs.map[index(21, 20)].flags = 0;
+            assert_eq!(find_scatter_tile(gs, 20, 20), Some((22, 20)));
+        });
+    }
+
+    #[test]
+    fn merging_money_updates_value_and_appearance() {
+        with_test_gs(|gs| {
+            gs.items[3].value = 5;
+            refresh_money_appearance(gs, 3);
+            assert_eq!(gs.items[3].sprite[0], 37);
+            merge_money(gs, 3, 2000);
+            assert_eq!(gs.items[3].value, 2005);
+            assert_eq!(gs.items[3].sprite[0], 40);
+        });
+    }
+}
@@ -0,0 +1,307 @@
+//! Framing of the client command stream.
+//!
+//! Client commands are fixed [`CLIENT_FRAME_LEN`]-byte frames, but TCP (and
+//! TLS records) deliver arbitrary slices of that stream: a read can end in
+//! the middle of a frame or carry many frames at once. `rec_player` appends
+//! raw bytes to `ServerPlayer::inbuf`; [`process_frames`] dispatches every
+//! complete frame and keeps the partial tail for the next read.
+//!
+//! Messages larger than one frame (the profile bio) are split into chunks by
+//! the client and reassembled here by [`ProfileUpload`]. Chunks that arrive
+//! out of order or would exceed the maximum message size are protocol
+//! violations: the connection is closed with
+//! [`LogoutReason::ProtocolViolation`] rather than guessing what the client
+//! meant.
+
+use core::logout_reasons::LogoutReason;
+use core::profile::{MAX_BIO_LEN, MAX_PROFILE_CHUNKS, PROFILE_CHUNK_LEN, PROFILE_FINAL_CHUNK};
+
+use std::time::Instant;
+
+use crate::game_state::GameState;
+use crate::player;
+use crate::player::{command_budget, command_seq};
+
+/// Size of every client command frame in bytes.
+pub const CLIENT_FRAME_LEN: usize = 16;
+
+/// Reasons a client stream is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// A fragmented message would exceed its maximum size.
+    MessageTooLarge { chunks: usize, max_chunks: usize },
+    /// A fragment arrived that does not continue the message in progress.
+    FragmentOutOfOrder { expected: usize, got: usize },
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::MessageTooLarge { chunks, max_chunks } => write!(
+                f,
+                "message of at least {} chunks exceeds the limit of {}",
+                chunks, max_chunks
+            ),
+            FrameError::FragmentOutOfOrder { expected, got } => {
+                write!(f, "expected fragment {}, got {}", expected, got)
+            }
+        }
+    }
+}
+
+/// Dispatch every complete frame buffered for a player.
+///
+/// Stops early if a command closes the connection, or once a player who
+/// keeps overrunning the command budget has used their share of this tick
+/// (see [`command_budget`]). Leftover bytes stay at the front of `inbuf`
+/// for the next tick. Frames repeating an already applied `CmdSeq` number
+/// are consumed without being dispatched (see [`command_seq`]).
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index.
+pub fn process_frames(gs: &mut GameState, nr: usize) {
+    let mut consumed = 0;
+    let mut dispatched = 0;
+    while gs.players[nr].sock.is_some()
+        && gs.players[nr].in_len.saturating_sub(consumed) >= CLIENT_FRAME_LEN
+    {
+        if dispatched >= command_budget::OFFENDER_FRAMES_PER_TICK
+            && gs.players[nr].command_budget.is_offender(gs.globals.ticker)
+        {
+            break;
+        }
+        // Command handlers read their arguments from the start of `inbuf`.
+        if consumed > 0 {
+            let in_len = gs.players[nr].in_len;
+            gs.players[nr].inbuf.copy_within(consumed..in_len, 0);
+            gs.players[nr].in_len -= consumed;
+        }
+        let mut frame = [0u8; CLIENT_FRAME_LEN];
+        frame.copy_from_slice(&gs.players[nr].inbuf[..CLIENT_FRAME_LEN]);
+        consumed = CLIENT_FRAME_LEN;
+        if !command_seq::admit(gs, nr, frame[0]) {
+            continue;
+        }
+        let started = Instant::now();
+        player::plr_cmd(gs, nr);
+        command_budget::charge(gs, nr, &frame, started.elapsed());
+        dispatched += 1;
+    }
+    command_seq::plr_send_command_ack(gs, nr);
+
+    // The slot may have been reset by a logout inside the handler.
+    let in_len = gs.players[nr].in_len;
+    let consumed = consumed.min(in_len);
+    gs.players[nr].inbuf.copy_within(consumed..in_len, 0);
+    gs.players[nr].in_len = in_len - consumed;
+}
+
+/// Close a player's connection after a framing error.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index.
+/// * `error` - The violation that was detected.
+pub fn reject_stream(gs: &mut GameState, nr: usize, error: FrameError) {
+    log::warn!(
+        "Player {} (cn={}) sent a malformed command stream: {}",
+        nr,
+        gs.players[nr].usnr,
+        error
+    );
+    let cn = gs.players[nr].usnr;
+    player::connection::plr_logout(gs, cn, nr, LogoutReason::ProtocolViolation);
+}
+
+/// Reassembly state for a chunked profile bio upload.
+#[derive(Debug, Default)]
+pub struct ProfileUpload {
+    data: Vec<u8>,
+    next_chunk: usize,
+}
+
+impl ProfileUpload {
+    /// Whether no upload is in progress.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when no chunks are buffered.
+    pub fn is_idle(&self) -> bool {
+        self.next_chunk == 0
+    }
+
+    /// Add one `CmdSetProfile` chunk.
+    ///
+    /// Chunk 0 always starts a new upload; any other chunk must directly
+    /// follow the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `index_byte` - Chunk index, with [`PROFILE_FINAL_CHUNK`] on the last.
+    /// * `chunk` - The chunk's [`PROFILE_CHUNK_LEN`] bio bytes.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(bytes))` with the assembled bio once the final chunk arrives.
+    /// * `Ok(None)` while more chunks are expected.
+    /// * `Err(FrameError)` on an oversized or out-of-order upload; the partial
+    ///   upload is discarded.
+    pub fn push(&mut self, index_byte: u8, chunk: &[u8]) -> Result<Option<Vec<u8>>, FrameError> {
+        let index = (index_byte & !PROFILE_FINAL_CHUNK) as usize;
+        if index == 0 {
+            self.reset();
+        }
+
+        if index >= MAX_PROFILE_CHUNKS {
+            self.reset();
+            return Err(FrameError::MessageTooLarge {
+                chunks: index + 1,
+                max_chunks: MAX_PROFILE_CHUNKS,
+            });
+        }
+        if index != self.next_chunk {
+            let expected = self.next_chunk;
+            self.reset();
+            return Err(FrameError::FragmentOutOfOrder {
+                expected,
+                got: index,
+            });
+        }
+
+        self.data
+            .extend_from_slice(&chunk[..PROFILE_CHUNK_LEN.min(chunk.len())]);
+        self.next_chunk += 1;
+
+        if index_byte & PROFILE_FINAL_CHUNK == 0 {
+            return Ok(None);
+        }
+        let mut bio = std::mem::take(&mut self.data);
+        bio.truncate(MAX_BIO_LEN);
+        self.reset();
+        Ok(Some(bio))
+    }
+
+    fn reset(&mut self) {
+        self.data.clear();
+        self.next_chunk = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use crate::tls::GameStream;
+    use core::client_commands::ClientCommand;
+    use std::net::{TcpListener, TcpStream};
+
+    fn attach_test_socket(gs: &mut GameState, nr: usize) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind test listener");
+        let addr = listener.local_addr().expect("get listener address");
+        let client = TcpStream::connect(addr).expect("connect test client");
+        let (server, _) = listener.accept().expect("accept test client");
+        drop(client);
+        gs.players[nr].sock = Some(GameStream::Plain(server));
+    }
+
+    #[test]
+    fn profile_upload_rejects_gaps_and_oversize() {
+        let chunk = [b'a'; PROFILE_CHUNK_LEN];
+        let mut upload = ProfileUpload::default();
+
+        assert_eq!(upload.push(0, &chunk), Ok(None));
+        assert_eq!(
+            upload.push(2, &chunk),
+            Err(FrameError::FragmentOutOfOrder {
+                expected: 1,
+                got: 2
+            })
+        );
+        assert!(upload.is_idle());
+
+        let too_far = MAX_PROFILE_CHUNKS as u8 | PROFILE_FINAL_CHUNK;
+        assert!(matches!(
+            upload.push(too_far, &chunk),
+            Err(FrameError::MessageTooLarge { .. })
+        ));
+
+        assert_eq!(upload.push(0, &chunk), Ok(None));
+        let bio = upload.push(1 | PROFILE_FINAL_CHUNK, &chunk).unwrap();
+        assert_eq!(bio.unwrap().len(), 2 * PROFILE_CHUNK_LEN);
+        assert!(upload.is_idle());
+    }
+
+    #[test]
+    fn process_frames_keeps_partial_tail() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            let ping = ClientCommand::new_ping(1, 0).to_bytes();
+
+            // One and a half frames: the first is dispatched, the rest waits.
+            gs.players[nr].inbuf[..16].copy_from_slice(&ping);
+            gs.players[nr].inbuf[16..24].copy_from_slice(&ping[..8]);
+            gs.players[nr].in_len = 24;
+            process_frames(gs, nr);
+            assert_eq!(gs.players[nr].in_len, 8);
+            assert_eq!(gs.players[nr].inbuf[..8], ping[..8]);
+
+            // The rest of the frame arrives on the next read.
+            gs.players[nr].inbuf[8..16].copy_from_slice(&ping[8..]);
+            gs.players[nr].in_len = 16;
+            process_frames(gs, nr);
+            assert_eq!(gs.players[nr].in_len, 0);
+        });
+    }
+
+    #[test]
+    fn process_frames_defers_frames_of_budget_offenders() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            let ping = ClientCommand::new_ping(1, 0).to_bytes();
+            for n in 0..3 {
+                gs.players[nr].inbuf[n * 16..(n + 1) * 16].copy_from_slice(&ping);
+            }
+            gs.players[nr].in_len = 48;
+            let slow = command_budget::COMMAND_BUDGET * 2;
+            for _ in 0..command_budget::OFFENDER_STRIKES {
+                gs.players[nr]
+                    .command_budget
+                    .record(slow, gs.globals.ticker);
+            }
+
+            process_frames(gs, nr);
+            assert_eq!(
+                gs.players[nr].in_len,
+                48 - 16 * command_budget::OFFENDER_FRAMES_PER_TICK
+            );
+        });
+    }
+
+    #[test]
+    fn process_frames_drops_repeated_sequence_numbers() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            gs.players[nr].capabilities = core::constants::CLIENT_CAP_COMMAND_SEQ;
+            let frames = [
+                ClientCommand::new_seq(1).to_bytes(),
+                ClientCommand::new_mode(1).to_bytes(),
+                ClientCommand::new_seq(1).to_bytes(),
+                ClientCommand::new_mode(2).to_bytes(),
+            ];
+            for (n, frame) in frames.iter().enumerate() {
+                gs.players[nr].inbuf[n * 16..(n + 1) * 16].copy_from_slice(frame);
+            }
+            gs.players[nr].in_len = 64;
+
+            process_frames(gs, nr);
+            assert_eq!(gs.players[nr].in_len, 0);
+            assert_eq!(gs.characters[cn].mode, 1);
+        });
+    }
+}
@@ -0,0 +1,209 @@
+//! `CmdSortInventory`: reorders a character's backpack on request.
+//!
+//! The client only names an [`InventorySortKey`]; the new layout is worked
+//! out here from the server's item data and applied as a sequence of slot
+//! swaps. Every swap is checked again before it is applied, so an item that
+//! is not the character's own is never moved. Lag scrolls stay where they
+//! are, matching `plr_cmd_inv`, which refuses to pick them up.
+
+use std::cmp::Ordering;
+
+use core::constants::{IT_LAGSCROLL, TICKS, USE_ACTIVE};
+use core::inventory_sort::InventorySortKey;
+
+use crate::game_state::GameState;
+
+/// Backpack slots considered by a sort.
+const BACKPACK_SLOTS: usize = 40;
+
+/// Shortest time between two sorts of the same player (one second).
+pub const SORT_COOLDOWN_TICKS: i32 = TICKS;
+
+/// Whether backpack slot `slot` of `cn` holds an item that must stay put.
+fn is_pinned(gs: &GameState, cn: usize, slot: usize) -> bool {
+    let item_idx = gs.characters[cn].item[slot] as usize;
+    item_idx != 0 && i32::from(gs.items[item_idx].temp) == IT_LAGSCROLL
+}
+
+/// Orders two items for `key`; ties fall back to name, then template, then
+/// item index so the result is stable across repeated sorts.
+fn compare_items(gs: &GameState, key: InventorySortKey, a: usize, b: usize) -> Ordering {
+    let (ia, ib) = (&gs.items[a], &gs.items[b]);
+    let primary = match key {
+        // Unwearable items (placement 0) go last.
+        InventorySortKey::Type => (ia.placement == 0)
+            .cmp(&(ib.placement == 0))
+            .then(ia.placement.cmp(&ib.placement)),
+        InventorySortKey::Value => ib.value.cmp(&ia.value),
+    };
+    primary
+        .then_with(|| ia.get_name().cmp(ib.get_name()))
+        .then(ia.temp.cmp(&ib.temp))
+        .then(a.cmp(&b))
+}
+
+/// Works out the swaps that sort the backpack of `cn`.
+///
+/// Items are packed into the movable slots in sorted order with the empty
+/// slots last.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character whose backpack is sorted.
+/// * `key` - Sort order.
+///
+/// # Returns
+///
+/// * Pairs of backpack slots to swap, in order.
+pub fn sort_swaps(gs: &GameState, cn: usize, key: InventorySortKey) -> Vec<(usize, usize)> {
+    let slots: Vec<usize> = (0..BACKPACK_SLOTS)
+        .filter(|&slot| !is_pinned(gs, cn, slot))
+        .collect();
+    let mut current: Vec<usize> = slots
+        .iter()
+        .map(|&slot| gs.characters[cn].item[slot] as usize)
+        .collect();
+    let mut wanted: Vec<usize> = current.iter().copied().filter(|&i| i != 0).collect();
+    wanted.sort_by(|&a, &b| compare_items(gs, key, a, b));
+    wanted.resize(slots.len(), 0);
+
+    let mut swaps = Vec::new();
+    for pos in 0..slots.len() {
+        if current[pos] == wanted[pos] {
+            continue;
+        }
+        let from = (pos + 1..slots.len())
+            .find(|&other| current[other] == wanted[pos])
+            .expect("sorted items come from the same backpack");
+        current.swap(pos, from);
+        swaps.push((slots[pos], slots[from]));
+    }
+    swaps
+}
+
+/// Swaps two backpack slots after checking both still hold items of `cn`.
+///
+/// # Returns
+///
+/// * `false` if either slot holds an item `cn` does not carry.
+fn apply_swap(gs: &mut GameState, cn: usize, a: usize, b: usize) -> bool {
+    for slot in [a, b] {
+        let item_idx = gs.characters[cn].item[slot] as usize;
+        if item_idx != 0
+            && (gs.items[item_idx].used != USE_ACTIVE
+                || usize::from(gs.items[item_idx].carried) != cn)
+        {
+            return false;
+        }
+    }
+    gs.characters[cn].item.swap(a, b);
+    true
+}
+
+/// Handle the `CmdSortInventory` packet.
+///
+/// Reads the sort key from `inbuf[1]`, which validation has already
+/// checked. Requests within [`SORT_COOLDOWN_TICKS`] of the last sort are
+/// ignored.
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+/// * `nr` - Player slot index issuing the command.
+pub fn plr_cmd_sort_inventory(gs: &mut GameState, nr: usize) {
+    let Some(key) = InventorySortKey::from_u8(gs.players[nr].inbuf[1]) else {
+        return;
+    };
+    let ticker = gs.globals.ticker;
+    if gs.players[nr].last_sort_tick != 0
+        && ticker - gs.players[nr].last_sort_tick < SORT_COOLDOWN_TICKS
+    {
+        return;
+    }
+    gs.players[nr].last_sort_tick = ticker;
+
+    let cn = gs.players[nr].usnr;
+    let swaps = sort_swaps(gs, cn, key);
+    for &(a, b) in &swaps {
+        if !apply_swap(gs, cn, a, b) {
+            log::warn!(
+                "Sort for {} stopped at slots {}/{}: item not carried",
+                gs.characters[cn].get_name(),
+                a,
+                b
+            );
+            break;
+        }
+    }
+    if !swaps.is_empty() {
+        gs.do_update_char(cn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs, write_inbuf};
+    use core::string_operations::write_ascii_into_fixed;
+
+    fn give(gs: &mut GameState, cn: usize, slot: usize, idx: usize, name: &str, value: u32) {
+        let item = &mut gs.items[idx];
+        item.used = USE_ACTIVE;
+        item.carried = cn as u16;
+        item.value = value;
+        write_ascii_into_fixed(&mut item.name, name);
+        gs.characters[cn].item[slot] = idx as u32;
+    }
+
+    #[test]
+    fn value_sort_packs_items_most_valuable_first() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            give(gs, cn, 7, 10, "Dagger", 50);
+            give(gs, cn, 2, 11, "Ruby", 900);
+            give(gs, cn, 30, 12, "Apple", 50);
+            gs.globals.ticker = 100;
+
+            write_inbuf(gs, nr, &[0, InventorySortKey::Value.as_u8()]);
+            plr_cmd_sort_inventory(gs, nr);
+
+            assert_eq!(&gs.characters[cn].item[..4], &[11, 12, 10, 0]);
+            assert!(gs.characters[cn].item[4..].iter().all(|&i| i == 0));
+        });
+    }
+
+    #[test]
+    fn type_sort_keeps_lag_scrolls_and_unwearables_last() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            give(gs, cn, 0, 10, "Scroll", 1);
+            gs.items[10].temp = IT_LAGSCROLL as u16;
+            give(gs, cn, 1, 11, "Bread", 5);
+            give(gs, cn, 2, 12, "Helmet", 5);
+            gs.items[12].placement = 1;
+
+            for (a, b) in sort_swaps(gs, cn, InventorySortKey::Type) {
+                assert!(apply_swap(gs, cn, a, b));
+            }
+            assert_eq!(&gs.characters[cn].item[..3], &[10, 12, 11]);
+        });
+    }
+
+    #[test]
+    fn repeated_sorts_are_throttled() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            give(gs, cn, 5, 10, "Dagger", 50);
+            gs.globals.ticker = 100;
+            write_inbuf(gs, nr, &[0, InventorySortKey::Value.as_u8()]);
+            plr_cmd_sort_inventory(gs, nr);
+            assert_eq!(gs.characters[cn].item[0], 10);
+
+            gs.characters[cn].item.swap(0, 5);
+            gs.globals.ticker += 1;
+            plr_cmd_sort_inventory(gs, nr);
+            assert_eq!(gs.characters[cn].item[5], 10);
+        });
+    }
+}
@@ -0,0 +1,307 @@
+//! `SV_ITEMLINK` items linked in chat.
+//!
+//! `do_say` turns the slot tokens a client typed into link tokens with
+//! [`link_slot_tokens`], filing a snapshot of each item in
+//! [`ItemLinkRegistry`] so the link outlives the item being sold or
+//! dropped. Every chat line passes [`localize_item_links`] on its way to a
+//! player: clients advertising [`CLIENT_CAP_ITEM_LINKS`] get the snapshot
+//! right before the line and keep the tokens, everyone else reads the item
+//! name in brackets.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use core::constants::{CLIENT_CAP_ITEM_LINKS, USE_ACTIVE};
+use core::item_link::{
+    ChatSegment, ITEM_LINK_MARK, ITEM_STAT_ARMOR, ITEM_STAT_END, ITEM_STAT_HP, ITEM_STAT_MANA,
+    ITEM_STAT_SKILL_BASE, ITEM_STAT_WEAPON, ItemLink, ItemLinkStat, MAX_LINKS_PER_MESSAGE,
+    encode_item_link, link_token, split_item_links,
+};
+use core::server_commands::ServerCommandType;
+use core::skills::MAX_SKILLS;
+use core::string_operations::c_string_to_str;
+use core::types::Item;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Links kept for lines still being sent; older links read as "[item]".
+pub const MAX_ITEM_LINKS: usize = 512;
+
+/// Runtime-only snapshots of the items linked in chat, oldest first.
+#[derive(Debug, Default)]
+pub struct ItemLinkRegistry {
+    next_id: u32,
+    links: VecDeque<ItemLink>,
+}
+
+impl ItemLinkRegistry {
+    /// Files a snapshot under a new link id, forgetting the oldest link
+    /// once [`MAX_ITEM_LINKS`] are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `link` - The snapshot; its `id` is overwritten.
+    ///
+    /// # Returns
+    ///
+    /// * The new link id.
+    pub fn register(&mut self, mut link: ItemLink) -> u32 {
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        link.id = self.next_id;
+        self.links.push_back(link);
+        if self.links.len() > MAX_ITEM_LINKS {
+            self.links.pop_front();
+        }
+        self.next_id
+    }
+
+    /// Looks up a link.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Link id from a link token.
+    pub fn get(&self, id: u32) -> Option<&ItemLink> {
+        self.links.iter().rev().find(|link| link.id == id)
+    }
+}
+
+/// Snapshots an item for a link.
+///
+/// # Arguments
+///
+/// * `item` - The item; modifiers are taken for its current active state.
+///
+/// # Returns
+///
+/// * The snapshot with every non-zero modifier and requirement, and id 0.
+pub fn item_link_from_item(item: &Item) -> ItemLink {
+    let act = usize::from(item.active != 0);
+    let mut stats = Vec::new();
+    let mut push = |stat: u8, bonus: i16, required: i16| {
+        if bonus != 0 || required != 0 {
+            stats.push(ItemLinkStat {
+                stat,
+                bonus,
+                required,
+            });
+        }
+    };
+    push(ITEM_STAT_WEAPON, item.weapon[act].into(), 0);
+    push(ITEM_STAT_ARMOR, item.armor[act].into(), 0);
+    for (n, attrib) in item.attrib.iter().enumerate() {
+        push(n as u8, attrib[act].into(), attrib[2].into());
+    }
+    push(ITEM_STAT_HP, item.hp[act], item.hp[2]);
+    push(ITEM_STAT_END, item.end[act], item.end[2]);
+    push(ITEM_STAT_MANA, item.mana[act], item.mana[2]);
+    for (n, skill) in item.skill.iter().enumerate().take(MAX_SKILLS) {
+        push(
+            ITEM_STAT_SKILL_BASE + n as u8,
+            skill[act].into(),
+            skill[2].into(),
+        );
+    }
+
+    ItemLink {
+        id: 0,
+        name: item.get_name().to_owned(),
+        min_rank: item.min_rank.max(0) as u8,
+        stats,
+        description: c_string_to_str(&item.description).to_owned(),
+    }
+}
+
+/// Replaces the slot tokens in a line typed by `cn` with link tokens.
+///
+/// Slots without an item are dropped, as are link tokens, which only the
+/// server may write. Items past [`MAX_LINKS_PER_MESSAGE`] are named in
+/// brackets instead of linked.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Speaking character.
+/// * `text` - The line as received.
+///
+/// # Returns
+///
+/// * The line to speak; borrowed when it holds no tokens.
+pub(crate) fn link_slot_tokens<'a>(gs: &mut GameState, cn: usize, text: &'a str) -> Cow<'a, str> {
+    if !text.contains(ITEM_LINK_MARK) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut linked = 0;
+    for segment in split_item_links(text) {
+        match segment {
+            ChatSegment::Text(part) => out.push_str(part),
+            ChatSegment::Link(_) => {}
+            ChatSegment::Slot(slot) => {
+                let Some(item_idx) = backpack_item(gs, cn, slot) else {
+                    continue;
+                };
+                if linked == MAX_LINKS_PER_MESSAGE {
+                    out.push_str(&format!("[{}]", gs.items[item_idx].get_name()));
+                    continue;
+                }
+                let id = gs
+                    .item_links
+                    .register(item_link_from_item(&gs.items[item_idx]));
+                out.push_str(&link_token(id));
+                linked += 1;
+            }
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Item in backpack slot `slot` of `cn`, if there is one.
+fn backpack_item(gs: &GameState, cn: usize, slot: usize) -> Option<usize> {
+    let item_idx = *gs.characters[cn].item.get(slot)? as usize;
+    let used = gs.items.get(item_idx)?.used;
+    (item_idx != 0 && used == USE_ACTIVE).then_some(item_idx)
+}
+
+/// Prepares a line for player `nr`.
+///
+/// Sends `SV_ITEMLINK` for every link in the line if the client renders
+/// links, and otherwise replaces each link token with the item name.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Receiving player slot.
+/// * `text` - The line about to be sent.
+///
+/// # Returns
+///
+/// * The line to send; borrowed when it holds no tokens.
+pub(crate) fn localize_item_links<'a>(
+    gs: &mut GameState,
+    nr: usize,
+    text: &'a str,
+) -> Cow<'a, str> {
+    if !text.contains(ITEM_LINK_MARK) {
+        return Cow::Borrowed(text);
+    }
+
+    let renders_links = gs.players[nr].capabilities & CLIENT_CAP_ITEM_LINKS != 0;
+    let mut out = String::with_capacity(text.len());
+    for segment in split_item_links(text) {
+        match segment {
+            ChatSegment::Text(part) => out.push_str(part),
+            ChatSegment::Slot(_) => {}
+            ChatSegment::Link(id) => {
+                let Some(link) = gs.item_links.get(id) else {
+                    out.push_str("[item]");
+                    continue;
+                };
+                if !renders_links {
+                    out.push_str(&format!("[{}]", link.name));
+                    continue;
+                }
+                let buf = encode_item_link(ServerCommandType::ItemLink as u8, link);
+                network_manager::xsend(gs, nr, &buf, buf.len());
+                out.push_str(&link_token(id));
+            }
+        }
+    }
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use crate::tls::GameStream;
+    use core::chat::{ChatChannel, ChatStyle};
+    use core::item_link::{decode_item_link, slot_token};
+    use core::string_operations::write_ascii_into_fixed;
+    use std::net::{TcpListener, TcpStream};
+
+    fn attach_test_socket(gs: &mut GameState, nr: usize) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind test listener");
+        let addr = listener.local_addr().expect("listener addr");
+        let client = TcpStream::connect(addr).expect("connect client");
+        let (server, _) = listener.accept().expect("accept client");
+        drop(client);
+        gs.players[nr].sock = Some(GameStream::Plain(server));
+    }
+
+    /// Puts a sword with a strength requirement into backpack slot 4.
+    fn give_sword(gs: &mut GameState, cn: usize) -> usize {
+        let item_idx = 50;
+        let item = &mut gs.items[item_idx];
+        item.used = USE_ACTIVE;
+        write_ascii_into_fixed(&mut item.name, "Steel Sword");
+        write_ascii_into_fixed(&mut item.description, "A well-balanced steel sword.");
+        item.weapon = [18, 0];
+        item.attrib[4] = [0, 0, 25];
+        gs.characters[cn].item[4] = item_idx as u32;
+        item_idx
+    }
+
+    #[test]
+    fn snapshot_keeps_non_zero_stats_only() {
+        let mut item = Item::default();
+        write_ascii_into_fixed(&mut item.name, "Ring");
+        item.hp = [10, 20, 0];
+        item.active = 1;
+        item.min_rank = 3;
+
+        let link = item_link_from_item(&item);
+        assert_eq!(link.name, "Ring");
+        assert_eq!(link.min_rank, 3);
+        assert_eq!(
+            link.stats,
+            vec![ItemLinkStat {
+                stat: ITEM_STAT_HP,
+                bonus: 20,
+                required: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn slot_tokens_become_links_and_forged_links_are_dropped() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            give_sword(gs, cn);
+
+            let text = format!("wts {} {} {}", slot_token(4), slot_token(5), link_token(99));
+            let linked = link_slot_tokens(gs, cn, &text);
+            assert_eq!(linked, format!("wts {}  ", link_token(1)));
+
+            let link = gs.item_links.get(1).unwrap();
+            assert_eq!(link.name, "Steel Sword");
+            assert_eq!(link.stats.len(), 2);
+
+            assert!(matches!(
+                link_slot_tokens(gs, cn, "hi"),
+                Cow::Borrowed("hi")
+            ));
+        });
+    }
+
+    #[test]
+    fn lines_carry_links_only_to_clients_that_render_them() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            give_sword(gs, cn);
+            let text = link_slot_tokens(gs, cn, &format!("look {}", slot_token(4))).into_owned();
+
+            assert_eq!(localize_item_links(gs, nr, &text), "look [Steel Sword]");
+            assert_eq!(gs.players[nr].tptr, 0);
+
+            gs.players[nr].capabilities = CLIENT_CAP_ITEM_LINKS;
+            gs.do_character_styled_log(cn, ChatStyle::new(ChatChannel::Say), &text);
+            assert_eq!(gs.players[nr].tbuf[0], ServerCommandType::ItemLink as u8);
+            let link = decode_item_link(&gs.players[nr].tbuf).unwrap();
+            assert_eq!(link.id, 1);
+            assert_eq!(link.description, "A well-balanced steel sword.");
+        });
+    }
+}
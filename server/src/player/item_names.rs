@@ -0,0 +1,108 @@
+//! `SV_ITEMNAME` backpack item names for clients advertising
+//! `CLIENT_CAP_ITEM_NAMES`.
+//!
+//! The legacy protocol only tells the client which sprite sits in each
+//! backpack slot. Clients that search their inventory by name get the name
+//! of each slot's item as well. Like the minimap markers, the item in every
+//! slot is compared each tick with what was last named, so the many code
+//! paths that move items need no hook of their own.
+
+use core::constants::{CLIENT_CAP_ITEM_NAMES, ST_NORMAL, USE_ACTIVE};
+use core::server_commands::ServerCommandType;
+use core::string_operations::write_ascii_into_fixed;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Builds an `SV_ITEMNAME` packet.
+///
+/// # Arguments
+///
+/// * `slot` - Backpack slot (0..40).
+/// * `name` - Item name, empty for an empty slot; cut to 40 bytes.
+///
+/// # Returns
+///
+/// * The 42-byte packet.
+pub fn item_name_packet(slot: u8, name: &str) -> [u8; 42] {
+    let mut buf = [0u8; 42];
+    buf[0] = ServerCommandType::ItemName as u8;
+    buf[1] = slot;
+    write_ascii_into_fixed(&mut buf[2..42], name);
+    buf
+}
+
+/// Sends the names of backpack items that changed since they were last sent.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+/// * `force` - Name every slot regardless (used right after login).
+///
+/// # Returns
+///
+/// * Number of packets sent.
+pub fn plr_send_item_names(gs: &mut GameState, nr: usize, force: bool) -> usize {
+    if gs.players[nr].state != ST_NORMAL || gs.players[nr].capabilities & CLIENT_CAP_ITEM_NAMES == 0
+    {
+        return 0;
+    }
+
+    let cn = gs.players[nr].usnr;
+    let mut sent = 0;
+    for slot in 0..gs.players[nr].sent_item_names.len() {
+        let item_idx = gs.characters[cn].item[slot];
+        if !force && gs.players[nr].sent_item_names[slot] == item_idx {
+            continue;
+        }
+        let name = match gs.items.get(item_idx as usize) {
+            Some(item) if item_idx != 0 && item.used == USE_ACTIVE => item.get_name(),
+            _ => "",
+        };
+        let buf = item_name_packet(slot as u8, name);
+        network_manager::xsend(gs, nr, &buf, buf.len());
+        gs.players[nr].sent_item_names[slot] = item_idx;
+        sent += 1;
+    }
+    sent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::server_commands::{ServerCommand, ServerCommandData};
+
+    #[test]
+    fn packet_round_trips_through_parser() {
+        let buf = item_name_packet(39, "Potion of Life");
+        let cmd = ServerCommand::from_bytes(&buf).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::ItemName { slot, name } => {
+                assert_eq!(slot, 39);
+                assert_eq!(name, "Potion of Life");
+            }
+            _ => panic!("Expected ItemName variant"),
+        }
+    }
+
+    #[test]
+    fn only_changed_slots_are_renamed() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            assert_eq!(plr_send_item_names(gs, nr, true), 0);
+
+            gs.players[nr].capabilities = CLIENT_CAP_ITEM_NAMES;
+            assert_eq!(plr_send_item_names(gs, nr, true), 40);
+            assert_eq!(plr_send_item_names(gs, nr, false), 0);
+
+            gs.items[10].used = USE_ACTIVE;
+            gs.items[10].carried = cn as u16;
+            gs.characters[cn].item[3] = 10;
+            gs.characters[cn].item[5] = 10;
+            assert_eq!(plr_send_item_names(gs, nr, false), 2);
+            assert_eq!(plr_send_item_names(gs, nr, false), 0);
+        });
+    }
+}
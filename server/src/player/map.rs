@@ -1,9 +1,9 @@
 use core::{
     constants::{
         CharacterFlags, INFRARED, INJURED, INJURED1, INJURED2, INVIS, IS_GRAVE, ISCHAR, ISITEM,
-        ISUSABLE, ItemFlags, MF_GFX_CMAGIC, MF_GFX_DEATH, MF_GFX_EMAGIC, MF_GFX_GMAGIC,
-        MF_GFX_INJURED, MF_GFX_INJURED1, MF_GFX_INJURED2, MF_GFX_TOMB, MF_UWATER, STONED, STUNNED,
-        UWATER,
+        ISUSABLE, ItemFlags, LOOT_PROTECTED, MF_GFX_CMAGIC, MF_GFX_DEATH, MF_GFX_EMAGIC,
+        MF_GFX_GMAGIC, MF_GFX_INJURED, MF_GFX_INJURED1, MF_GFX_INJURED2, MF_GFX_TOMB, MF_UWATER,
+        STONED, STUNNED, UWATER,
     },
     logout_reasons::LogoutReason,
     server_commands::ServerCommandType,
@@ -19,13 +19,17 @@ use crate::{
 /// Removes a character from the world map tile and clears any transient
 /// references associated with that tile (to_ch, step-action items, lights).
 /// It also undoes light contributions for the character and clears step
-/// drivers for stepped-on items when appropriate.
+/// drivers for stepped-on items when appropriate. Observers are not on the
+/// map, so this does nothing for them.
 ///
 /// # Arguments
 /// * `gs` - Active game state used to update map occupancy and lighting.
 /// * `cn` - Character index to remove from the map
 pub fn plr_map_remove(gs: &mut GameState, cn: usize) {
     let ch = gs.characters[cn];
+    if ch.flags & CharacterFlags::Observer.bits() != 0 {
+        return;
+    }
     let m = (ch.x as usize) + (ch.y as usize) * core::constants::SERVER_MAPX as usize;
     let to_m = (ch.tox as usize) + (ch.toy as usize) * core::constants::SERVER_MAPX as usize;
     let light = ch.light;
@@ -56,15 +60,21 @@ pub fn plr_map_remove(gs: &mut GameState, cn: usize) {
 /// on arrival. This checks for step-action items (calling the step driver),
 /// taverns (triggering logout/tavern logic), "no magic" zones (removing
 /// spells and flagging the character), death traps (killing the character),
-/// and finally notifies nearby clients of the character's presence.
+/// and finally notifies nearby clients of the character's presence and fires
+/// the `on_enter_tile` script hook for players.
 ///
 /// The function will also restore the character to a previous tile when
 /// teleport/step-driver returns special values, and updates lighting.
+/// Observers stay off the map: for them only the coordinates change.
 ///
 /// # Arguments
 /// * `gs` - Active game state used for map transitions and tile effects.
 /// * `cn` - Character index to place on the map
 pub fn plr_map_set(gs: &mut GameState, cn: usize) {
+    if gs.characters[cn].flags & CharacterFlags::Observer.bits() != 0 {
+        return;
+    }
+
     let (x, y, flags, light) = (
         gs.characters[cn].x,
         gs.characters[cn].y,
@@ -263,6 +273,10 @@ pub fn plr_map_set(gs: &mut GameState, cn: usize) {
         0,
         0,
     );
+
+    if !is_body && is_player {
+        gs.script_enter_tile(cn);
+    }
 }
 
 /// Clear the saved small map for all players to force a full resend
@@ -320,6 +334,7 @@ pub fn plr_getmap_complete(gs: &mut GameState, nr: usize) {
 
     let current_x = i32::from(gs.characters[cn].x);
     let current_y = i32::from(gs.characters[cn].y);
+    let view_radius = gs.view_radius;
     gs.can_see(
         Some(cn),
         current_x,
@@ -361,11 +376,13 @@ pub fn plr_getmap_complete(gs: &mut GameState, nr: usize) {
     while y < ye {
         let mut x = xs;
         while x < xe {
-            // If we're outside the map, render the default empty tile and never touch map[]
+            // If we're outside the map or the server's view radius, render the
+            // default empty tile and never touch map[]
             if x < 0
                 || y < 0
                 || x >= core::constants::SERVER_MAPX
                 || y >= core::constants::SERVER_MAPY
+                || !core::view::in_view(x - current_x, y - current_y, view_radius)
             {
                 let needs_update = do_all
                     || gs.players[nr].xmap[n] != empty_map
@@ -496,6 +513,14 @@ pub fn plr_getmap_complete(gs: &mut GameState, nr: usize) {
 
                 if !visible {
                     smap[n].flags |= INVIS;
+                } else {
+                    // Hints for the client's path preview and minimap.
+                    if !crate::player::waypoints::tile_walkable(gs, x, y) {
+                        smap[n].flags2 |= core::constants::WALK_BLOCKED;
+                    }
+                    if map_m.flags & u64::from(core::constants::MF_BANK) != 0 {
+                        smap[n].flags2 |= core::constants::IS_BANK;
+                    }
                 }
 
                 // Begin of the light bucketing
@@ -621,6 +646,10 @@ pub fn plr_getmap_complete(gs: &mut GameState, nr: usize) {
                     if item.temp == core::constants::IT_TOMBSTONE as u16 {
                         smap[n].flags |= IS_GRAVE;
                     }
+
+                    if !gs.may_take_loot(cn, map_m.it as usize) {
+                        smap[n].flags |= LOOT_PROTECTED;
+                    }
                 } else {
                     // Just clear item flags
                     smap[n].it_sprite = 0;
@@ -1224,6 +1253,28 @@ mod tests {
         });
     }
 
+    #[test]
+    fn tiles_beyond_the_view_radius_are_sent_empty() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            gs.view_radius = core::view::LEGACY_VIEW_RADIUS;
+            let near = 10 + i16::from(core::view::LEGACY_VIEW_RADIUS);
+            for x in [near, near + 1] {
+                gs.map[map_index(x, 10)].sprite = 321;
+            }
+
+            plr_getmap(gs, nr);
+
+            let inside = small_map_index(gs, cn, i32::from(near), 10);
+            let outside = small_map_index(gs, cn, i32::from(near) + 1, 10);
+            assert_eq!(gs.players[nr].xmap[inside].sprite, 321);
+            assert_eq!(
+                gs.players[nr].xmap[outside].sprite,
+                core::constants::SPR_EMPTY
+            );
+        });
+    }
+
     #[test]
     fn light_packet_helpers_update_cmap_and_encode_expected_payloads() {
         with_test_gs(|gs| {
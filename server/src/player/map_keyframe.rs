@@ -0,0 +1,171 @@
+//! Rolling map keyframes.
+//!
+//! Map updates are already deltas: `cmap` is what the client was last sent,
+//! `smap` what it should see now, and `plr_change_light`/`plr_change_map`
+//! send only the tiles and fields that differ. A client whose copy drifted
+//! (a dropped decode, a bug in a scroll) would keep the wrong tile until the
+//! server happens to change it. To bound that, every tile the player can see
+//! is resent once per [`GameState::map_keyframe_ticks`]. The refresh walks a
+//! few rows per tick instead of sending the whole window at once, and each
+//! player starts at a different row, so keyframes never cause a spike.
+
+use core::constants::{SPR_EMPTY, TICKS, TILEX, TILEY};
+
+use crate::game_state::GameState;
+use crate::types::cmap::CMap;
+
+/// Environment variable setting the keyframe period in seconds.
+pub const MAP_KEYFRAME_ENV: &str = "MAG_MAP_KEYFRAME_SECS";
+
+/// Keyframe period used when `MAG_MAP_KEYFRAME_SECS` is not set.
+pub(crate) const DEFAULT_MAP_KEYFRAME_SECS: i32 = 30;
+
+/// A `cmap` entry no real tile matches, so every field of it is resent.
+const STALE: CMap = CMap {
+    ba_sprite: i16::MIN,
+    light: u8::MAX,
+    flags: u32::MAX,
+    flags2: u32::MAX,
+    ch_sprite: i16::MIN,
+    ch_status2: u8::MAX,
+    ch_status: u8::MAX,
+    ch_speed: u8::MAX,
+    ch_nr: u16::MAX,
+    ch_id: u16::MAX,
+    ch_proz: u8::MAX,
+    it_sprite: i16::MIN,
+    it_status: u8::MAX,
+};
+
+/// Parses the `MAG_MAP_KEYFRAME_SECS` setting.
+///
+/// # Arguments
+///
+/// * `setting` - Raw value, or `None` when unset.
+///
+/// # Returns
+///
+/// * The period in ticks (`0` turns keyframes off), the default when unset
+///   or empty, or an error for a value that is not a whole number of
+///   seconds.
+pub fn parse_map_keyframe_secs(setting: Option<&str>) -> Result<i32, String> {
+    match setting.map(str::trim) {
+        None | Some("") => Ok(DEFAULT_MAP_KEYFRAME_SECS * TICKS),
+        Some(value) => value
+            .parse::<u16>()
+            .map(|secs| i32::from(secs) * TICKS)
+            .map_err(|_| format!("Invalid {} value '{}'", MAP_KEYFRAME_ENV, value)),
+    }
+}
+
+/// Rows of the map window player `nr` refreshes on `ticker`.
+///
+/// Over `period` consecutive ticks every row comes up exactly once.
+fn keyframe_rows(ticker: i32, nr: usize, period: i32) -> std::ops::Range<usize> {
+    let period = period as usize;
+    let phase = (ticker as usize).wrapping_add(nr * 7) % period;
+    (phase * TILEY).div_ceil(period)..((phase + 1) * TILEY).div_ceil(period)
+}
+
+/// Whether a tile shows nothing, so resending it cannot fix anything.
+fn is_blank(tile: &CMap) -> bool {
+    *tile == CMap::default()
+        || *tile
+            == (CMap {
+                ba_sprite: SPR_EMPTY as i16,
+                ..CMap::default()
+            })
+}
+
+/// Marks this tick's keyframe rows as unknown to the client.
+///
+/// Must run after `plr_change_position` (which shifts `cmap` when the
+/// player scrolls) and before `plr_change_light` and `plr_change_map`,
+/// which then resend the marked tiles.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+///
+/// # Returns
+///
+/// * The number of tiles marked.
+pub fn plr_map_keyframe(gs: &mut GameState, nr: usize) -> usize {
+    let period = gs.map_keyframe_ticks;
+    if period <= 0 {
+        return 0;
+    }
+
+    let mut marked = 0;
+    let player = &mut gs.players[nr];
+    for row in keyframe_rows(gs.globals.ticker, nr, period) {
+        for n in row * TILEX..(row + 1) * TILEX {
+            if !is_blank(&player.smap[n]) {
+                player.cmap[n] = STALE;
+                marked += 1;
+            }
+        }
+    }
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::map::{plr_change_light, plr_change_map};
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn keyframe_setting_is_parsed_in_seconds() {
+        assert_eq!(
+            parse_map_keyframe_secs(None),
+            Ok(DEFAULT_MAP_KEYFRAME_SECS * TICKS)
+        );
+        assert_eq!(parse_map_keyframe_secs(Some(" 0 ")), Ok(0));
+        assert_eq!(parse_map_keyframe_secs(Some("10")), Ok(10 * TICKS));
+        assert!(parse_map_keyframe_secs(Some("-1")).is_err());
+    }
+
+    #[test]
+    fn every_row_is_refreshed_once_per_period() {
+        for period in [1, 7, TILEY as i32, 30 * TICKS] {
+            let mut seen = vec![0; TILEY];
+            for ticker in 1000..1000 + period {
+                for row in keyframe_rows(ticker, 3, period) {
+                    seen[row] += 1;
+                }
+            }
+            assert!(seen.iter().all(|&count| count == 1), "period {}", period);
+        }
+    }
+
+    #[test]
+    fn marked_tiles_are_resent_and_blank_ones_skipped() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            gs.map_keyframe_ticks = TILEY as i32;
+            let row = keyframe_rows(gs.globals.ticker, nr, gs.map_keyframe_ticks).start;
+            let seen = row * TILEX + 5;
+            let blank = row * TILEX + 6;
+            gs.players[nr].smap[seen] = CMap {
+                ba_sprite: 1012,
+                light: 3,
+                ..CMap::default()
+            };
+            gs.players[nr].cmap[seen] = gs.players[nr].smap[seen];
+            let blank_before = gs.players[nr].cmap[blank];
+
+            assert_eq!(plr_map_keyframe(gs, nr), 1);
+            assert_eq!(gs.players[nr].cmap[seen], STALE);
+            assert_eq!(gs.players[nr].cmap[blank], blank_before);
+
+            plr_change_light(gs, nr);
+            plr_change_map(gs, nr);
+            assert_eq!(gs.players[nr].cmap[seen], gs.players[nr].smap[seen]);
+
+            gs.map_keyframe_ticks = 0;
+            assert_eq!(plr_map_keyframe(gs, nr), 0);
+        });
+    }
+}
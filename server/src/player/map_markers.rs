@@ -0,0 +1,116 @@
+//! `SV_MAPMARKER` minimap points of interest for clients advertising
+//! `CLIENT_CAP_MAP_MARKERS`.
+//!
+//! The temple, tavern and last death position are compared every tick with
+//! what the player was last sent, so the many places that move a
+//! character's temple or tavern need no hook of their own. Banks are found
+//! by the client itself (see [`core::map_markers`]).
+
+use core::constants::{CLIENT_CAP_MAP_MARKERS, ST_NORMAL};
+use core::map_markers::MapMarkerKind;
+use core::server_commands::ServerCommandType;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Builds an `SV_MAPMARKER` packet.
+///
+/// # Arguments
+///
+/// * `kind` - Marker kind.
+/// * `x` - World tile X, `0` together with `y` to clear the marker.
+/// * `y` - World tile Y.
+///
+/// # Returns
+///
+/// * The 6-byte packet.
+pub fn map_marker_packet(kind: MapMarkerKind, x: u16, y: u16) -> [u8; 6] {
+    let mut buf = [0u8; 6];
+    buf[0] = ServerCommandType::MapMarker as u8;
+    buf[1] = kind as u8;
+    buf[2..4].copy_from_slice(&x.to_le_bytes());
+    buf[4..6].copy_from_slice(&y.to_le_bytes());
+    buf
+}
+
+/// Current position of a server-announced marker for player `nr`.
+fn marker_position(gs: &GameState, nr: usize, kind: MapMarkerKind) -> Option<(u16, u16)> {
+    let ch = &gs.characters[gs.players[nr].usnr];
+    match kind {
+        MapMarkerKind::Temple => Some((ch.temple_x, ch.temple_y)),
+        MapMarkerKind::Tavern => Some((ch.tavern_x, ch.tavern_y)),
+        MapMarkerKind::Death => Some(gs.players[nr].death_marker),
+        MapMarkerKind::Bank => None,
+    }
+}
+
+/// Sends the markers of player `nr` that moved since they were last sent.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+/// * `force` - Send every marker regardless (used right after login).
+///
+/// # Returns
+///
+/// * Number of packets sent.
+pub fn plr_send_map_markers(gs: &mut GameState, nr: usize, force: bool) -> usize {
+    if gs.players[nr].state != ST_NORMAL
+        || gs.players[nr].capabilities & CLIENT_CAP_MAP_MARKERS == 0
+    {
+        return 0;
+    }
+
+    let mut sent = 0;
+    for kind in MapMarkerKind::ALL {
+        let Some((x, y)) = marker_position(gs, nr, kind) else {
+            continue;
+        };
+        let slot = kind as usize;
+        if !force && gs.players[nr].sent_map_markers[slot] == (x, y) {
+            continue;
+        }
+        let buf = map_marker_packet(kind, x, y);
+        network_manager::xsend(gs, nr, &buf, buf.len());
+        gs.players[nr].sent_map_markers[slot] = (x, y);
+        sent += 1;
+    }
+    sent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::server_commands::{ServerCommand, ServerCommandData};
+
+    #[test]
+    fn packet_round_trips_through_parser() {
+        let buf = map_marker_packet(MapMarkerKind::Tavern, 813, 40);
+        let cmd = ServerCommand::from_bytes(&buf).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::MapMarker { kind, x, y } => {
+                assert_eq!(MapMarkerKind::from_u8(kind), Some(MapMarkerKind::Tavern));
+                assert_eq!((x, y), (813, 40));
+            }
+            _ => panic!("Expected MapMarker variant"),
+        }
+    }
+
+    #[test]
+    fn only_moved_markers_are_resent() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            assert_eq!(plr_send_map_markers(gs, nr, true), 0);
+
+            gs.players[nr].capabilities = CLIENT_CAP_MAP_MARKERS;
+            assert_eq!(plr_send_map_markers(gs, nr, true), 3);
+            assert_eq!(plr_send_map_markers(gs, nr, false), 0);
+
+            gs.characters[cn].temple_x = 512;
+            gs.players[nr].death_marker = (10, 10);
+            assert_eq!(plr_send_map_markers(gs, nr, false), 2);
+        });
+    }
+}
@@ -4,22 +4,42 @@ use crate::{
     game_state::GameState,
     player::{
         commands::{
-            plr_cmd_attack, plr_cmd_autoloot, plr_cmd_ctick, plr_cmd_drop, plr_cmd_exit,
-            plr_cmd_give, plr_cmd_input, plr_cmd_inv, plr_cmd_inv_look, plr_cmd_learn_talent,
-            plr_cmd_look, plr_cmd_look_item, plr_cmd_mode, plr_cmd_move, plr_cmd_pickup,
-            plr_cmd_ping, plr_cmd_reset, plr_cmd_reset_talents, plr_cmd_shop, plr_cmd_skill,
-            plr_cmd_stat, plr_cmd_turn, plr_cmd_use,
+            plr_cmd_attack, plr_cmd_autoloot, plr_cmd_client_caps, plr_cmd_ctick, plr_cmd_drop,
+            plr_cmd_exit, plr_cmd_give, plr_cmd_input, plr_cmd_inv, plr_cmd_inv_look,
+            plr_cmd_learn_skill, plr_cmd_learn_talent, plr_cmd_look, plr_cmd_look_item,
+            plr_cmd_mode, plr_cmd_move, plr_cmd_npc_action, plr_cmd_npc_menu, plr_cmd_pickup,
+            plr_cmd_ping, plr_cmd_request_resync, plr_cmd_reset, plr_cmd_reset_talents,
+            plr_cmd_set_profile, plr_cmd_set_title, plr_cmd_shop, plr_cmd_skill, plr_cmd_stat,
+            plr_cmd_turn, plr_cmd_use,
         },
         connection::plr_api_login,
     },
+    validation,
 };
 
+pub mod char_sheet;
+pub mod combat_log;
+pub mod command_budget;
+pub mod command_seq;
 pub mod commands;
 pub mod connection;
+pub mod dialogue;
+pub mod drop_scatter;
+pub mod framing;
+pub mod inventory_sort;
+pub mod item_links;
+pub mod item_names;
 pub mod map;
+pub mod map_keyframe;
+pub mod map_markers;
+pub mod observer;
 pub mod quest_log;
+pub mod skill_timers;
 pub mod talent_trees;
 pub mod tick;
+pub mod time_sync;
+pub mod view;
+pub mod waypoints;
 
 /// Port of `plr_cmd` from `svr_tick.cpp`
 /// Dispatches player commands from inbuf
@@ -60,6 +80,11 @@ pub fn plr_cmd(gs: &mut GameState, nr: usize) {
         gs.players[nr].lasttick2 = ticker;
     }
 
+    // Drop malformed or forged inventory/shop/stat packets before dispatch.
+    if !validation::check_command(gs, nr, parsed_cmd) {
+        return;
+    }
+
     // Handle commands that don't require stun check
     match parsed_cmd {
         ClientCommandType::Ping => {
@@ -127,6 +152,50 @@ pub fn plr_cmd(gs: &mut GameState, nr: usize) {
             plr_cmd_reset_talents(gs, nr);
             return;
         }
+        ClientCommandType::CmdSetProfile => {
+            log::debug!("PLR_CMD_SET_PROFILE received for player {}", nr);
+            plr_cmd_set_profile(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdSetTitle => {
+            log::debug!("PLR_CMD_SET_TITLE received for player {}", nr);
+            plr_cmd_set_title(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdNpcMenu => {
+            log::debug!("PLR_CMD_NPC_MENU received for player {}", nr);
+            plr_cmd_npc_menu(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdNpcAction => {
+            log::debug!("PLR_CMD_NPC_ACTION received for player {}", nr);
+            plr_cmd_npc_action(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdLearnSkill => {
+            log::debug!("PLR_CMD_LEARN_SKILL received for player {}", nr);
+            plr_cmd_learn_skill(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdClientCaps => {
+            log::debug!("PLR_CMD_CLIENT_CAPS received for player {}", nr);
+            plr_cmd_client_caps(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdRequestResync => {
+            log::debug!("PLR_CMD_REQUEST_RESYNC received for player {}", nr);
+            plr_cmd_request_resync(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdSeq => {
+            command_seq::plr_cmd_seq(gs, nr);
+            return;
+        }
+        ClientCommandType::CmdClientLocale => {
+            log::debug!("PLR_CMD_CLIENT_LOCALE received for player {}", nr);
+            dialogue::plr_cmd_client_locale(gs, nr);
+            return;
+        }
         _ => {}
     }
 
@@ -190,6 +259,11 @@ pub fn plr_cmd(gs: &mut GameState, nr: usize) {
             plr_cmd_reset(gs, nr);
             return;
         }
+        ClientCommandType::CmdWaypoints => {
+            log::debug!("PLR_CMD_WAYPOINTS received for player {}", character_name);
+            waypoints::plr_cmd_waypoints(gs, nr);
+            return;
+        }
         ClientCommandType::CmdSkill => {
             log::debug!("PLR_CMD_SKILL received for player {}", character_name);
             plr_cmd_skill(gs, nr);
@@ -232,6 +306,13 @@ pub fn plr_cmd(gs: &mut GameState, nr: usize) {
         ClientCommandType::CmdShop => {
             plr_cmd_shop(gs, nr);
         }
+        ClientCommandType::CmdSortInventory => {
+            log::debug!(
+                "PLR_CMD_SORT_INVENTORY received for player {}",
+                character_name
+            );
+            inventory_sort::plr_cmd_sort_inventory(gs, nr);
+        }
         _ => {
             log::warn!("Unknown CL command: {} for player {}", cmd, character_name);
         }
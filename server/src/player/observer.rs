@@ -0,0 +1,310 @@
+//! GM observer mode (`#observe`).
+//!
+//! An observer is lifted off the map: its tile is freed and
+//! [`CharacterFlags::Observer`] keeps `plr_map_set` / `plr_map_remove` from
+//! touching the map again, so nobody sees it and nothing can bump into,
+//! attack or target it. Its `x`/`y` stay the centre of the map view sent to
+//! its client, so moving them moves the camera: a move command jumps the
+//! camera to the clicked tile instead of walking, and `#goto` keeps working.
+//! Leaving observer mode lands the character on the nearest free tile
+//! around the camera.
+//!
+//! Clients advertising [`CLIENT_CAP_OBSERVER`] get `SV_OBSERVER` when the
+//! mode changes, so their free camera can steer the observer camera.
+
+use core::constants::{
+    CLIENT_CAP_OBSERVER, CharacterFlags, SERVER_MAPX, SERVER_MAPY, ST_NORMAL, USE_ACTIVE,
+};
+use core::server_commands::ServerCommandType;
+use core::types::FontColor;
+
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::network_manager;
+use crate::player::map::{plr_map_remove, plr_map_set};
+
+/// How far from the camera, in tiles, leaving observer mode looks for a
+/// free tile to land on.
+pub const LANDING_RADIUS: i32 = 4;
+
+/// Whether character `cn` is observing.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character index.
+///
+/// # Returns
+///
+/// * `true` while the `Observer` flag is set.
+pub fn is_observer(gs: &GameState, cn: usize) -> bool {
+    gs.characters[cn].flags & CharacterFlags::Observer.bits() != 0
+}
+
+/// Takes character `cn` off the map and makes it an observer.
+///
+/// Any action, walk or route in progress is dropped.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character index.
+///
+/// # Returns
+///
+/// * `Ok(())`, or an error when `cn` is already observing.
+pub fn start_observing(gs: &mut GameState, cn: usize) -> Result<(), String> {
+    if is_observer(gs, cn) {
+        return Err("You are already observing.".into());
+    }
+
+    plr_map_remove(gs, cn);
+    let ch = &mut gs.characters[cn];
+    ch.flags |= CharacterFlags::Observer.bits();
+    ch.status = 0;
+    ch.attack_cn = 0;
+    ch.skill_nr = 0;
+    ch.use_nr = 0;
+    ch.misc_action = 0;
+    ch.goto_x = 0;
+    ch.goto_y = 0;
+    ch.tox = ch.x;
+    ch.toy = ch.y;
+    ch.set_do_update_flags();
+
+    if let Some(nr) = player_slot(gs, cn) {
+        gs.players[nr].waypoints.clear();
+        plr_send_observer(gs, nr);
+    }
+    Ok(())
+}
+
+/// Ends observer mode and lands character `cn` near the camera.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character index.
+///
+/// # Returns
+///
+/// * `Ok(())`, or an error when `cn` is not observing or no free tile lies
+///   within [`LANDING_RADIUS`] of the camera; the character keeps observing
+///   then.
+pub fn stop_observing(gs: &mut GameState, cn: usize) -> Result<(), String> {
+    if !is_observer(gs, cn) {
+        return Err("You are not observing.".into());
+    }
+
+    let (x, y) = (
+        i32::from(gs.characters[cn].x),
+        i32::from(gs.characters[cn].y),
+    );
+    let Some((lx, ly)) = landing_tile(gs, x, y) else {
+        return Err("There is no room to land here.".into());
+    };
+
+    let ch = &mut gs.characters[cn];
+    ch.flags &= !CharacterFlags::Observer.bits();
+    ch.x = lx as i16;
+    ch.y = ly as i16;
+    ch.tox = lx as i16;
+    ch.toy = ly as i16;
+    ch.set_do_update_flags();
+    plr_map_set(gs, cn);
+
+    if let Some(nr) = player_slot(gs, cn) {
+        plr_send_observer(gs, nr);
+    }
+    Ok(())
+}
+
+/// Moves the camera of observer `cn` to `(x, y)`, clamped to the map.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Observing character index.
+/// * `x` - Target x.
+/// * `y` - Target y.
+pub fn move_camera(gs: &mut GameState, cn: usize, x: i32, y: i32) {
+    let x = x.clamp(0, SERVER_MAPX - 1) as i16;
+    let y = y.clamp(0, SERVER_MAPY - 1) as i16;
+    let ch = &mut gs.characters[cn];
+    ch.x = x;
+    ch.y = y;
+    ch.tox = x;
+    ch.toy = y;
+}
+
+/// Nearest tile around `(x, y)` a character may be dropped on.
+///
+/// # Returns
+///
+/// * The tile, searching rings of growing distance up to
+///   [`LANDING_RADIUS`], or `None` when all are taken.
+fn landing_tile(gs: &GameState, x: i32, y: i32) -> Option<(usize, usize)> {
+    (0..=LANDING_RADIUS).find_map(|r| {
+        (-r..=r)
+            .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+            .filter(|(dx, dy)| dx.abs().max(dy.abs()) == r)
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|&(tx, ty)| tx >= 0 && ty >= 0)
+            .map(|(tx, ty)| (tx as usize, ty as usize))
+            .find(|&(tx, ty)| God::can_drop_char_at(gs, tx, ty))
+    })
+}
+
+/// Player slot controlling character `cn`, if it is a connected player.
+fn player_slot(gs: &GameState, cn: usize) -> Option<usize> {
+    let nr = usize::try_from(gs.characters[cn].player).ok()?;
+    (nr > 0 && nr < gs.players.len() && gs.players[nr].usnr == cn).then_some(nr)
+}
+
+/// Sends `SV_OBSERVER` with the observer state of player `nr`'s character,
+/// if their client follows it.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+///
+/// # Returns
+///
+/// * `true` if a packet was sent.
+pub fn plr_send_observer(gs: &mut GameState, nr: usize) -> bool {
+    if gs.players[nr].state != ST_NORMAL || gs.players[nr].capabilities & CLIENT_CAP_OBSERVER == 0 {
+        return false;
+    }
+
+    let active = is_observer(gs, gs.players[nr].usnr);
+    let buf = [ServerCommandType::Observer as u8, u8::from(active)];
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    true
+}
+
+/// Handles `#observe [<player>]`.
+///
+/// Without an argument observer mode is toggled. With one, `cn` starts
+/// observing if needed and the camera jumps to that character.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `cn` - Character issuing the command.
+/// * `target` - Name or number of the character to watch, or empty.
+pub fn do_observe(gs: &mut GameState, cn: usize, target: &str) {
+    let target = target.trim();
+    let result = if target.is_empty() {
+        if is_observer(gs, cn) {
+            stop_observing(gs, cn).map(|()| "You stop observing.\n".to_owned())
+        } else {
+            start_observing(gs, cn)
+                .map(|()| "You are observing. Click to move the camera.\n".to_owned())
+        }
+    } else {
+        let found = God::find_character_by_name_or_id(gs, target)
+            .filter(|(co, _)| gs.characters[*co].used == USE_ACTIVE);
+        match found {
+            Some((co, name)) if co != cn => {
+                let (x, y) = (
+                    i32::from(gs.characters[co].x),
+                    i32::from(gs.characters[co].y),
+                );
+                let started = is_observer(gs, cn) || start_observing(gs, cn).is_ok();
+                if started {
+                    move_camera(gs, cn, x, y);
+                    Ok(format!("You are observing {}.\n", name))
+                } else {
+                    Err("You cannot observe right now.".to_owned())
+                }
+            }
+            Some(_) => Err("You cannot observe yourself.".to_owned()),
+            None => Err(format!("No such character: '{}'.", target)),
+        }
+    };
+
+    match result {
+        Ok(text) => gs.do_character_log(cn, FontColor::Green, &text),
+        Err(err) => gs.do_character_log(cn, FontColor::Red, &format!("{}\n", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::MF_MOVEBLOCK;
+
+    fn map_index(x: i32, y: i32) -> usize {
+        x as usize + y as usize * SERVER_MAPX as usize
+    }
+
+    #[test]
+    fn observing_frees_the_tile_and_moves_only_the_camera() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            let home = map_index(10, 10);
+            gs.map[home].ch = cn as u32;
+
+            start_observing(gs, cn).unwrap();
+            assert!(is_observer(gs, cn));
+            assert_eq!(gs.map[home].ch, 0);
+            assert!(start_observing(gs, cn).is_err());
+
+            move_camera(gs, cn, 40, -5);
+            assert_eq!((gs.characters[cn].x, gs.characters[cn].y), (40, 0));
+            plr_map_set(gs, cn);
+            assert_eq!(gs.map[map_index(40, 0)].ch, 0);
+        });
+    }
+
+    #[test]
+    fn leaving_lands_on_the_nearest_free_tile() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            start_observing(gs, cn).unwrap();
+            move_camera(gs, cn, 30, 30);
+            gs.map[map_index(30, 30)].ch = 99;
+
+            stop_observing(gs, cn).unwrap();
+            assert!(!is_observer(gs, cn));
+            let (x, y) = (
+                i32::from(gs.characters[cn].x),
+                i32::from(gs.characters[cn].y),
+            );
+            assert_eq!((x - 30).abs().max((y - 30).abs()), 1);
+            assert_eq!(gs.map[map_index(x, y)].ch, cn as u32);
+            assert_eq!(gs.map[map_index(30, 30)].ch, 99);
+            assert!(stop_observing(gs, cn).is_err());
+        });
+    }
+
+    #[test]
+    fn leaving_without_room_keeps_observing() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            start_observing(gs, cn).unwrap();
+            move_camera(gs, cn, 50, 50);
+            for y in 50 - LANDING_RADIUS..=50 + LANDING_RADIUS {
+                for x in 50 - LANDING_RADIUS..=50 + LANDING_RADIUS {
+                    gs.map[map_index(x, y)].flags |= u64::from(MF_MOVEBLOCK);
+                }
+            }
+
+            assert!(stop_observing(gs, cn).is_err());
+            assert!(is_observer(gs, cn));
+        });
+    }
+
+    #[test]
+    fn observer_state_requires_capability() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            assert!(!plr_send_observer(gs, nr));
+
+            gs.players[nr].capabilities = CLIENT_CAP_OBSERVER;
+            assert!(plr_send_observer(gs, nr));
+        });
+    }
+}
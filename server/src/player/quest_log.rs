@@ -39,6 +39,7 @@ use core::server_commands::{
     ServerCommandType,
 };
 use core::skills;
+use core::types::PlayerSlot;
 use std::sync::{Mutex, OnceLock};
 
 use crate::game_state::GameState;
@@ -230,6 +231,25 @@ pub fn get_completion(ch: &core::types::Character, idx: u8) -> i16 {
     *ch.future2.get(idx as usize).unwrap_or(&0)
 }
 
+/// Returns whether `ch` has turned in the quest of `npc_template_id` at
+/// least once.
+///
+/// # Arguments
+///
+/// * `ch` - Character to check.
+/// * `npc_template_id` - Template id of the quest giver.
+///
+/// # Returns
+///
+/// * `true` if the quest is in the catalog and its counter is positive;
+///   `false` otherwise, including before the catalog is initialised.
+pub fn has_completed(ch: &core::types::Character, npc_template_id: u16) -> bool {
+    if QUEST_CATALOG.get().is_none() {
+        return false;
+    }
+    with_catalog(|cat| cat.index_of(npc_template_id)).is_some_and(|idx| get_completion(ch, idx) > 0)
+}
+
 /// Increment the per-player completion counter at `idx` per the rules of
 /// `entry`. Repeatable quests are clamped at `1`; multi-stage quests
 /// saturate at `entry.stages`; everything else saturates at `1`. An
@@ -296,9 +316,8 @@ pub fn record_discovery(gs: &mut GameState, cn: usize, npc_template_id: u16) {
         return;
     }
     *slot = 0;
-    let player_slot = gs.characters[cn].player as usize;
-    if player_slot != 0 && player_slot < gs.players.len() {
-        plr_send_quest_completion_delta(gs, player_slot, idx, 0);
+    if let Some(slot) = PlayerSlot::from_i32(gs.characters[cn].player) {
+        plr_send_quest_completion_delta(gs, slot.index(), idx, 0);
     }
 }
 
@@ -403,9 +422,8 @@ pub fn record_turn_in(gs: &mut GameState, cn: usize, npc_template_id: u16) {
         return;
     }
     let count = get_completion(&gs.characters[cn], idx);
-    let player_slot = gs.characters[cn].player as usize;
-    if player_slot != 0 && player_slot < gs.players.len() {
-        plr_send_quest_completion_delta(gs, player_slot, idx, count);
+    if let Some(slot) = PlayerSlot::from_i32(gs.characters[cn].player) {
+        plr_send_quest_completion_delta(gs, slot.index(), idx, count);
     }
 }
 
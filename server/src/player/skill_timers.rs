@@ -0,0 +1,226 @@
+//! `SV_SKILLTIMER` cast and cooldown timers for clients advertising
+//! `CLIENT_CAP_SKILL_TIMERS`.
+//!
+//! Like the minimap markers, the timers are derived from character state
+//! every tick and compared with what the player was last sent: a cast is the
+//! skill animation (`status2 == 9`), a cooldown is any spell item that blocks
+//! reuse of its skill (see [`Recharge`]). The client counts both down
+//! locally, so packets only go out when a timer starts or ends early.
+
+use core::constants::{CLIENT_CAP_SKILL_TIMERS, CTICK_CYCLE_LEN, SPEEDTAB, ST_NORMAL};
+use core::server_commands::ServerCommandType;
+use core::skill_timers::SkillTimerKind;
+use core::skills::MAX_SKILLS;
+
+use crate::driver::skill_use::{Recharge, find_skill_use_def};
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Frames in one skill animation (`160..=167` and the other directions).
+const CAST_FRAMES: i32 = 8;
+
+/// Builds an `SV_SKILLTIMER` packet.
+///
+/// # Arguments
+///
+/// * `kind` - Cast or cooldown.
+/// * `skill` - Skill index.
+/// * `remaining` - Ticks left; `0` clears the timer.
+/// * `total` - Full length in ticks.
+///
+/// # Returns
+///
+/// * The 7-byte packet.
+pub fn skill_timer_packet(kind: SkillTimerKind, skill: u8, remaining: u16, total: u16) -> [u8; 7] {
+    let mut buf = [0u8; 7];
+    buf[0] = ServerCommandType::SkillTimer as u8;
+    buf[1] = kind as u8;
+    buf[2] = skill;
+    buf[3..5].copy_from_slice(&remaining.to_le_bytes());
+    buf[5..7].copy_from_slice(&total.to_le_bytes());
+    buf
+}
+
+/// Ticks until a character at `speed` has advanced `frames` animation
+/// frames, starting at `ticker` (see `speedo`).
+///
+/// # Arguments
+///
+/// * `speed` - Character speed (`SPEEDTAB` row).
+/// * `ticker` - Current global ticker.
+/// * `frames` - Frames still to play.
+///
+/// # Returns
+///
+/// * The tick count, at least `frames`.
+pub fn animation_ticks(speed: usize, ticker: i32, frames: i32) -> u16 {
+    let row = &SPEEDTAB[speed.min(SPEEDTAB.len() - 1)];
+    let mut left = frames;
+    let mut ticks = 0u16;
+    let mut ctick = ticker.max(0) as usize % CTICK_CYCLE_LEN;
+    while left > 0 && ticks < u16::MAX {
+        if row[ctick] != 0 {
+            left -= 1;
+        }
+        ticks += 1;
+        ctick = (ctick + 1) % CTICK_CYCLE_LEN;
+    }
+    ticks
+}
+
+/// Skill currently being cast by character `cn` and the ticks until it
+/// takes effect.
+fn current_cast(gs: &GameState, cn: usize) -> Option<(u8, u16)> {
+    let ch = &gs.characters[cn];
+    if ch.status2 != 9 || !(160..=191).contains(&ch.status) {
+        return None;
+    }
+    let frames = CAST_FRAMES - (i32::from(ch.status) - 160) % CAST_FRAMES;
+    let skill = u8::try_from(ch.skill_target2).ok()?;
+    Some((
+        skill,
+        animation_ticks(ch.speed as usize, gs.globals.ticker, frames),
+    ))
+}
+
+/// Cooldowns running on character `cn` as `(skill, remaining, total)`.
+fn current_cooldowns(gs: &GameState, cn: usize) -> Vec<(u8, u16, u16)> {
+    let mut cooldowns: Vec<(u8, u16, u16)> = Vec::new();
+    for &in_ in &gs.characters[cn].spell {
+        if in_ == 0 {
+            continue;
+        }
+        let item = &gs.items[in_ as usize];
+        if usize::from(item.temp) >= MAX_SKILLS {
+            continue;
+        }
+        let blocks_reuse = find_skill_use_def(usize::from(item.temp))
+            .is_some_and(|d| d.recharge != Recharge::None);
+        let skill = item.temp as u8;
+        if blocks_reuse && !cooldowns.iter().any(|c| c.0 == skill) {
+            let remaining = item.active.min(u32::from(u16::MAX)) as u16;
+            let total = item.duration.min(u32::from(u16::MAX)) as u16;
+            cooldowns.push((skill, remaining, total));
+        }
+    }
+    cooldowns
+}
+
+/// Sends the cast and cooldown timers of player `nr` that started or ended
+/// early since the last call.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+///
+/// # Returns
+///
+/// * Number of packets sent.
+pub fn plr_send_skill_timers(gs: &mut GameState, nr: usize) -> usize {
+    if gs.players[nr].state != ST_NORMAL
+        || gs.players[nr].capabilities & CLIENT_CAP_SKILL_TIMERS == 0
+    {
+        return 0;
+    }
+    let cn = gs.players[nr].usnr;
+    let ticker = gs.globals.ticker;
+    let mut packets: Vec<[u8; 7]> = Vec::new();
+
+    // Cast: announce a new animation, cancel one that stopped short.
+    let cast = current_cast(gs, cn);
+    let sent_cast = gs.players[nr].sent_cast.filter(|&(_, end)| end > ticker);
+    match (cast, sent_cast) {
+        (Some((skill, ticks)), None) => {
+            packets.push(skill_timer_packet(
+                SkillTimerKind::Cast,
+                skill,
+                ticks,
+                ticks,
+            ));
+            gs.players[nr].sent_cast = Some((skill, ticker + i32::from(ticks)));
+        }
+        (None, Some((skill, _))) => {
+            packets.push(skill_timer_packet(SkillTimerKind::Cast, skill, 0, 0));
+            gs.players[nr].sent_cast = None;
+        }
+        (Some(_), Some(_)) => {}
+        (None, None) => gs.players[nr].sent_cast = None,
+    }
+
+    // Cooldowns: the expected end tick of a running one stays put.
+    let cooldowns = current_cooldowns(gs, cn);
+    let mut sent: Vec<(u8, i32)> = Vec::with_capacity(cooldowns.len());
+    for &(skill, remaining, total) in &cooldowns {
+        let end = ticker + i32::from(remaining);
+        let known = gs.players[nr]
+            .sent_skill_cooldowns
+            .iter()
+            .any(|&(s, e)| s == skill && e.abs_diff(end) <= 1);
+        if !known {
+            packets.push(skill_timer_packet(
+                SkillTimerKind::Cooldown,
+                skill,
+                remaining,
+                total,
+            ));
+        }
+        sent.push((skill, end));
+    }
+    for &(skill, end) in &gs.players[nr].sent_skill_cooldowns {
+        if end > ticker + 1 && !cooldowns.iter().any(|c| c.0 == skill) {
+            packets.push(skill_timer_packet(SkillTimerKind::Cooldown, skill, 0, 0));
+        }
+    }
+    gs.players[nr].sent_skill_cooldowns = sent;
+
+    for buf in &packets {
+        network_manager::xsend(gs, nr, buf, buf.len());
+    }
+    packets.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+    use core::skills;
+
+    #[test]
+    fn animation_ticks_follow_the_speed_table() {
+        // Row 0 advances on every other tick, starting on even ticks.
+        assert_eq!(animation_ticks(0, 0, 8), 15);
+        assert_eq!(animation_ticks(0, 1, 8), 16);
+        assert_eq!(animation_ticks(0, 0, 1), 1);
+        assert!(animation_ticks(10, 0, 8) > animation_ticks(0, 0, 8));
+    }
+
+    #[test]
+    fn cooldowns_are_sent_once_and_cleared_early() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            gs.players[nr].capabilities = CLIENT_CAP_SKILL_TIMERS;
+            assert_eq!(plr_send_skill_timers(gs, nr), 0);
+
+            let in_ = 10;
+            gs.items[in_] = core::types::Item::default();
+            gs.items[in_].used = USE_ACTIVE;
+            gs.items[in_].temp = skills::SK_GASH as u16;
+            gs.items[in_].active = 300;
+            gs.items[in_].duration = 360;
+            gs.characters[cn].spell[0] = in_ as u32;
+            assert_eq!(plr_send_skill_timers(gs, nr), 1);
+
+            // Counting down in step with the ticker is not news.
+            gs.globals.ticker += 1;
+            gs.items[in_].active -= 1;
+            assert_eq!(plr_send_skill_timers(gs, nr), 0);
+
+            // Removed before it ran out: clear it on the client.
+            gs.characters[cn].spell[0] = 0;
+            assert_eq!(plr_send_skill_timers(gs, nr), 1);
+            assert_eq!(plr_send_skill_timers(gs, nr), 0);
+        });
+    }
+}
@@ -11,6 +11,7 @@
 //!   prerequisites and cost, and debits a point.
 
 use core::{
+    balance,
     skills::{Skill, SkillIndex},
     string_operations::c_string_to_str,
     talent_trees::{
@@ -21,7 +22,6 @@ use core::{
 };
 
 use crate::game_state::GameState;
-use crate::points;
 
 /// Top-level "spend a point on a talent" entry point.
 ///
@@ -408,7 +408,7 @@ fn ungrant_skill(cn: usize, game_state: &mut GameState, skill: Skill) {
     // `skill_needed` cost.
     let mut refund: i32 = 0;
     for value in 1..base {
-        refund = refund.saturating_add(points::skill_needed(value, diff));
+        refund = refund.saturating_add(balance::skill_needed(value, diff));
     }
 
     let ch = &mut game_state.characters[cn];
@@ -2,6 +2,7 @@ use core::{
     constants::{CharacterFlags, SPEEDTAB, TICKS},
     logout_reasons::LogoutReason,
     server_commands::ServerCommandType,
+    types::CharId,
 };
 
 use crate::{
@@ -15,13 +16,16 @@ use crate::{
             plr_turn_left, plr_turn_leftdown, plr_turn_leftup, plr_turn_right, plr_turn_rightdown,
             plr_turn_rightup, plr_turn_up,
         },
-        connection::{plr_login, plr_logout},
+        connection::{plr_disconnected, plr_login, plr_logout},
         map::{plr_change_light, plr_change_map, plr_change_position},
     },
 };
 
 /// Periodic medium-rate driver using an explicit game state.
 ///
+/// Keeps a player who used `#follow` walking after their target, and drops
+/// the follow once [`GameState::follow_refusal`] objects to it.
+///
 /// # Arguments
 /// * `gs` - Active game state used for ticker and follow target lookup.
 /// * `cn` - Character index to process.
@@ -31,10 +35,30 @@ pub fn player_driver_med(gs: &mut GameState, cn: usize) {
         return;
     }
 
-    let co = gs.characters[cn].data[10];
-    if co != 0 {
-        driver::follow_driver(gs, cn, co as usize);
+    let raw = gs.characters[cn].data[10];
+    if raw == 0 {
+        return;
     }
+    let Some(co) = CharId::from_i32(raw) else {
+        log::warn!(
+            "player_driver_med: {} follows invalid character {}",
+            cn,
+            raw
+        );
+        gs.characters[cn].data[10] = 0;
+        return;
+    };
+    if let Some(reason) = gs.follow_refusal(cn, co.index()) {
+        let name = gs.character(co).get_name().to_owned();
+        gs.characters[cn].data[10] = 0;
+        gs.do_character_log(
+            cn,
+            core::types::FontColor::Yellow,
+            &format!("You stop following {}: {}.\n", name, reason),
+        );
+        return;
+    }
+    driver::follow_driver(gs, cn, co.index());
 }
 
 /// Port of `plr_act` from `svr_tick.cpp`
@@ -63,6 +87,12 @@ pub fn plr_act(gs: &mut GameState, cn: usize) {
         return;
     }
 
+    // Observers are off the map: no actions, and no idle notify that would
+    // let NPCs see them.
+    if flags & CharacterFlags::Observer.bits() != 0 {
+        return;
+    }
+
     match status {
         // idle states: call idle and driver
         0..=7 => {
@@ -496,9 +526,11 @@ pub fn plr_state(gs: &mut GameState, nr: usize) {
         return;
     }
 
-    // Handle idle timeout - logout after 60 seconds
-    if ticker.wrapping_sub(lasttick) > core::constants::TICKS * 60 {
-        log::info!("Idle timeout for player {}", nr);
+    // Handle dead-peer timeout - no keepalive from the client
+    if ticker.wrapping_sub(lasttick)
+        > core::constants::TICKS * core::constants::CLIENT_KEEPALIVE_TIMEOUT_SECS
+    {
+        log::info!("Keepalive timeout for player {}", nr);
         plr_logout(gs, 0, nr, LogoutReason::IdleTooLong);
         return;
     }
@@ -544,7 +576,12 @@ pub fn plr_change(gs: &mut GameState, nr: usize) {
     if should_update {
         // Send full player stats update
         plr_change_stats(gs, nr, cn, ticker);
+        crate::player::char_sheet::plr_send_char_checksum(gs, nr);
     }
+    crate::player::time_sync::plr_send_time_sync(gs, nr, false);
+    crate::player::map_markers::plr_send_map_markers(gs, nr, false);
+    crate::player::item_names::plr_send_item_names(gs, nr, false);
+    crate::player::skill_timers::plr_send_skill_timers(gs, nr);
 
     // Always send combat-related updates
     plr_change_hp(gs, nr, cn);
@@ -560,6 +597,9 @@ pub fn plr_change(gs: &mut GameState, nr: usize) {
     // Send map position and scrolling
     plr_change_position(gs, nr, cn);
 
+    // Mark this tick's slice of the rolling keyframe for resending
+    crate::player::map_keyframe::plr_map_keyframe(gs, nr);
+
     // Send light updates
     plr_change_light(gs, nr);
 
@@ -709,24 +749,9 @@ fn plr_change_stats(gs: &mut GameState, nr: usize, cn: usize, _ticker: i32) {
             let idx_bytes = (i as u32).to_le_bytes();
             buf[1..5].copy_from_slice(&idx_bytes);
 
-            if in_idx != 0 {
-                let it = &gs.items[in_idx];
-                let sprite = if it.active != 0 {
-                    it.sprite[1]
-                } else {
-                    it.sprite[0]
-                };
-                let placement = it.placement as i16;
-                buf[5] = (sprite & 0xff) as u8;
-                buf[6] = ((sprite >> 8) & 0xff) as u8;
-                buf[7] = (placement & 0xff) as u8;
-                buf[8] = ((placement >> 8) & 0xff) as u8;
-            } else {
-                buf[5] = 0;
-                buf[6] = 0;
-                buf[7] = 0;
-                buf[8] = 0;
-            }
+            let (sprite, placement) = item_wire_sprite(gs, in_idx);
+            buf[5..7].copy_from_slice(&sprite.to_le_bytes());
+            buf[7..9].copy_from_slice(&placement.to_le_bytes());
 
             network_manager::xsend(gs, nr, &buf, 9);
             gs.players[nr].cpl.item[i] = in_idx as i32;
@@ -752,27 +777,12 @@ fn plr_change_stats(gs: &mut GameState, nr: usize, cn: usize, _ticker: i32) {
             let idx_bytes = (i as u32).to_le_bytes();
             buf[1..5].copy_from_slice(&idx_bytes);
 
+            let (sprite, placement) = item_wire_sprite(gs, in_idx);
+            buf[5..7].copy_from_slice(&sprite.to_le_bytes());
+            buf[7..9].copy_from_slice(&placement.to_le_bytes());
             if in_idx != 0 {
-                {
-                    let it = &gs.items[in_idx];
-                    let sprite = if it.active != 0 {
-                        it.sprite[1]
-                    } else {
-                        it.sprite[0]
-                    };
-                    let placement = it.placement as i16;
-                    buf[5] = (sprite & 0xff) as u8;
-                    buf[6] = ((sprite >> 8) & 0xff) as u8;
-                    buf[7] = (placement & 0xff) as u8;
-                    buf[8] = ((placement >> 8) & 0xff) as u8;
-                }
                 // Clear IF_UPDATE flag
                 gs.items[in_idx].flags &= !core::constants::ItemFlags::IF_UPDATE.bits();
-            } else {
-                buf[5] = 0;
-                buf[6] = 0;
-                buf[7] = 0;
-                buf[8] = 0;
             }
 
             network_manager::xsend(gs, nr, &buf, 9);
@@ -862,51 +872,12 @@ fn plr_change_stats(gs: &mut GameState, nr: usize, cn: usize, _ticker: i32) {
         let mut buf: [u8; 5] = [0; 5];
         buf[0] = ServerCommandType::SetCharObj as u8;
 
-        if (in_idx & 0x80000000) != 0 {
-            // Gold amount - use special sprites based on amount
-            let amount = in_idx & 0x7fffffff;
-            let sprite = if amount > 999999 {
-                121
-            } else if amount > 99999 {
-                120
-            } else if amount > 9999 {
-                41
-            } else if amount > 999 {
-                40
-            } else if amount > 99 {
-                39
-            } else if amount > 9 {
-                38
-            } else {
-                37
-            };
-            buf[1] = (sprite & 0xff) as u8;
-            buf[2] = ((sprite >> 8) & 0xff) as u8;
-            buf[3] = 0;
-            buf[4] = 0;
-        } else if in_idx != 0 {
-            // Normal item
-            {
-                let it = &gs.items[in_idx];
-                let sprite = if it.active != 0 {
-                    it.sprite[1]
-                } else {
-                    it.sprite[0]
-                };
-                let placement = it.placement as i16;
-                buf[1] = (sprite & 0xff) as u8;
-                buf[2] = ((sprite >> 8) & 0xff) as u8;
-                buf[3] = (placement & 0xff) as u8;
-                buf[4] = ((placement >> 8) & 0xff) as u8;
-            }
+        let (sprite, placement) = citem_wire_sprite(gs, in_idx);
+        buf[1..3].copy_from_slice(&sprite.to_le_bytes());
+        buf[3..5].copy_from_slice(&placement.to_le_bytes());
+        if in_idx != 0 && (in_idx & 0x80000000) == 0 {
             // Clear IF_UPDATE flag
             gs.items[in_idx].flags &= !core::constants::ItemFlags::IF_UPDATE.bits();
-        } else {
-            // Empty cursor
-            buf[1] = 0;
-            buf[2] = 0;
-            buf[3] = 0;
-            buf[4] = 0;
         }
 
         network_manager::xsend(gs, nr, &buf, 5);
@@ -914,6 +885,63 @@ fn plr_change_stats(gs: &mut GameState, nr: usize, cn: usize, _ticker: i32) {
     }
 }
 
+/// Returns the sprite and placement the client shows for item `in_idx`.
+///
+/// # Arguments
+///
+/// * `gs` - Game state holding the item table.
+/// * `in_idx` - Item index, `0` for an empty slot.
+///
+/// # Returns
+///
+/// * `(sprite, placement)`; `(0, 0)` for an empty slot.
+pub(crate) fn item_wire_sprite(gs: &GameState, in_idx: usize) -> (i16, i16) {
+    if in_idx == 0 {
+        return (0, 0);
+    }
+    let it = &gs.items[in_idx];
+    let sprite = if it.active != 0 {
+        it.sprite[1]
+    } else {
+        it.sprite[0]
+    };
+    (sprite, it.placement as i16)
+}
+
+/// Returns the sprite and placement the client shows for the cursor item.
+///
+/// # Arguments
+///
+/// * `gs` - Game state holding the item table.
+/// * `citem` - `Character::citem`; the high bit marks a gold amount.
+///
+/// # Returns
+///
+/// * `(sprite, placement)`; gold uses a coin-pile sprite by amount.
+pub(crate) fn citem_wire_sprite(gs: &GameState, citem: usize) -> (i16, i16) {
+    if (citem & 0x80000000) == 0 {
+        return item_wire_sprite(gs, citem);
+    }
+    // Gold amount - use special sprites based on amount
+    let amount = citem & 0x7fffffff;
+    let sprite = if amount > 999999 {
+        121
+    } else if amount > 99999 {
+        120
+    } else if amount > 9999 {
+        41
+    } else if amount > 999 {
+        40
+    } else if amount > 99 {
+        39
+    } else if amount > 9 {
+        38
+    } else {
+        37
+    };
+    (sprite, 0)
+}
+
 /// Send HP change to player
 fn plr_change_hp(gs: &mut GameState, nr: usize, cn: usize) {
     let current_hp = (gs.characters[cn].a_hp + 500) / 1000;
@@ -1136,6 +1164,8 @@ pub fn plr_tick(gs: &mut GameState, nr: usize) {
         return;
     }
 
+    crate::player::waypoints::plr_advance_waypoints(gs, nr);
+
     // Check lag-based stoning conditions
     let (data_19, flags) = (gs.characters[cn].data[19], gs.characters[cn].flags);
 
@@ -1219,10 +1249,12 @@ pub fn plr_idle(gs: &mut GameState, nr: usize) {
         gs.players[nr].usnr,
     );
 
-    // Check protocol level idle (60 seconds)
-    if ticker.wrapping_sub(lasttick) > (core::constants::TICKS * 60) as u32 {
-        log::info!("Player {} idle too long (protocol level)", nr);
-        plr_logout(gs, usnr, nr, LogoutReason::IdleTooLong);
+    // Check protocol level idle: no keepalive means a dead or half-open peer
+    if ticker.wrapping_sub(lasttick)
+        > (core::constants::TICKS * core::constants::CLIENT_KEEPALIVE_TIMEOUT_SECS) as u32
+    {
+        log::info!("Player {} idle too long (no keepalive, protocol level)", nr);
+        plr_disconnected(gs, usnr, nr);
     }
 
     if state == core::constants::ST_EXIT {
@@ -1346,6 +1378,32 @@ mod tests {
         });
     }
 
+    #[test]
+    fn player_driver_med_drops_follow_without_group_consent_or_in_range() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            let leader = 2usize;
+            setup_existing_character(gs, leader, 0, USE_ACTIVE, "Leader");
+            gs.characters[leader].flags = CharacterFlags::Player.bits();
+            gs.characters[leader].x = 10;
+            gs.characters[leader].y = 14;
+            gs.characters[cn].data[10] = leader as i32;
+            gs.characters[cn].data[12] = 0;
+            gs.globals.ticker = TICKS * 15;
+
+            assert_eq!(
+                gs.follow_refusal(cn, leader),
+                Some("they have not added you to their group")
+            );
+            gs.characters[leader].data[core::constants::CHD_MINGROUP] = cn as i32;
+            assert_eq!(gs.follow_refusal(cn, leader), None);
+
+            gs.characters[leader].y = 11 + crate::state::player_actions::FOLLOW_MAX_DISTANCE as i16;
+            player_driver_med(gs, cn);
+            assert_eq!(gs.characters[cn].data[10], 0);
+        });
+    }
+
     #[test]
     fn plr_act_short_circuits_and_resets_unknown_status() {
         with_test_gs(|gs| {
@@ -1726,10 +1784,15 @@ mod tests {
         with_test_gs(|gs| {
             let (cn, nr) = add_test_player(gs);
             attach_test_socket(gs, nr);
-            gs.globals.ticker = TICKS * 61;
+            gs.globals.ticker = TICKS * core::constants::CLIENT_KEEPALIVE_TIMEOUT_SECS;
             gs.players[nr].lasttick = 0;
             gs.players[nr].lasttick2 = 0;
 
+            // Exactly at the keepalive timeout the peer is still considered live.
+            plr_idle(gs, nr);
+            assert_eq!(gs.players[nr].state, ST_NORMAL);
+
+            gs.globals.ticker += 1;
             plr_idle(gs, nr);
             assert_eq!(gs.players[nr].state, ST_EXIT);
             assert_eq!(gs.characters[cn].player, 0);
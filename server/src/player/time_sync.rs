@@ -0,0 +1,104 @@
+//! `SV_TIMESYNC` clock packets for clients advertising
+//! `CLIENT_CAP_TIME_SYNC`.
+//!
+//! Buff, weather and cooldown countdowns on the client are derived from
+//! server ticks. Instead of counting `SV_TICK` packets (which arrive in
+//! bursts and drift under lag), the client anchors its timers to the tick
+//! and wall-clock carried here, sent once right after `CmdClientCaps` and
+//! then every [`TIME_SYNC_INTERVAL`] ticks.
+
+use core::constants::{CLIENT_CAP_TIME_SYNC, ST_NORMAL, TICKS};
+use core::server_commands::ServerCommandType;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Ticks between two `SV_TIMESYNC` packets to the same player.
+pub const TIME_SYNC_INTERVAL: i32 = TICKS * 5;
+
+/// Builds an `SV_TIMESYNC` packet.
+///
+/// # Arguments
+///
+/// * `tick` - Current server ticker.
+/// * `unix_ms` - Server wall-clock in milliseconds since the Unix epoch.
+///
+/// # Returns
+///
+/// * The 13-byte packet.
+pub fn time_sync_packet(tick: u32, unix_ms: u64) -> [u8; 13] {
+    let mut buf = [0u8; 13];
+    buf[0] = ServerCommandType::TimeSync as u8;
+    buf[1..5].copy_from_slice(&tick.to_le_bytes());
+    buf[5..13].copy_from_slice(&unix_ms.to_le_bytes());
+    buf
+}
+
+/// Sends `SV_TIMESYNC` to player `nr` if the sync interval has elapsed or
+/// `force` is set.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+/// * `force` - Send regardless of the interval (used right after login).
+///
+/// # Returns
+///
+/// * `true` if a packet was sent.
+pub fn plr_send_time_sync(gs: &mut GameState, nr: usize, force: bool) -> bool {
+    let ticker = gs.globals.ticker;
+    if gs.players[nr].state != ST_NORMAL
+        || gs.players[nr].capabilities & CLIENT_CAP_TIME_SYNC == 0
+        || (!force && ticker.wrapping_sub(gs.players[nr].last_time_sync_tick) < TIME_SYNC_INTERVAL)
+    {
+        return false;
+    }
+
+    let unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let buf = time_sync_packet(ticker as u32, unix_ms);
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    gs.players[nr].last_time_sync_tick = ticker;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::server_commands::{ServerCommand, ServerCommandData};
+
+    #[test]
+    fn packet_round_trips_through_parser() {
+        let buf = time_sync_packet(4_242, 1_760_000_000_000);
+        let cmd = ServerCommand::from_bytes(&buf).unwrap();
+        match cmd.structured_data {
+            ServerCommandData::TimeSync { tick, unix_ms } => {
+                assert_eq!(tick, 4_242);
+                assert_eq!(unix_ms, 1_760_000_000_000);
+            }
+            _ => panic!("Expected TimeSync variant"),
+        }
+    }
+
+    #[test]
+    fn time_sync_requires_capability_and_is_rate_limited() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            gs.globals.ticker = TIME_SYNC_INTERVAL;
+            assert!(!plr_send_time_sync(gs, nr, true));
+
+            gs.players[nr].capabilities = CLIENT_CAP_TIME_SYNC;
+            assert!(plr_send_time_sync(gs, nr, false));
+            assert!(!plr_send_time_sync(gs, nr, false));
+            assert!(plr_send_time_sync(gs, nr, true));
+
+            gs.globals.ticker += TIME_SYNC_INTERVAL;
+            assert!(plr_send_time_sync(gs, nr, false));
+        });
+    }
+}
@@ -0,0 +1,84 @@
+//! `SV_SETVIEW` view radius announcements for clients advertising
+//! `CLIENT_CAP_WIDE_VIEW`.
+//!
+//! The radius itself is server-wide ([`GameState::view_radius`], set from
+//! `MAG_VIEW_RADIUS`) and is enforced for every client by
+//! `plr_getmap_complete`, which sends tiles beyond it empty. Announcing it
+//! only tells zoom-capable clients how far they may zoom out.
+
+use core::constants::{CLIENT_CAP_WIDE_VIEW, ST_NORMAL};
+use core::server_commands::ServerCommandType;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+/// Environment variable overriding [`core::view::MAX_VIEW_RADIUS`].
+pub const VIEW_RADIUS_ENV: &str = "MAG_VIEW_RADIUS";
+
+/// Parses the `MAG_VIEW_RADIUS` setting.
+///
+/// # Arguments
+///
+/// * `setting` - Raw value, or `None` when unset.
+///
+/// # Returns
+///
+/// * The clamped radius, `MAX_VIEW_RADIUS` when unset or empty, or an
+///   error for a value that is not a number.
+pub fn parse_view_radius(setting: Option<&str>) -> Result<u8, String> {
+    match setting.map(str::trim) {
+        None | Some("") => Ok(core::view::MAX_VIEW_RADIUS),
+        Some(value) => value
+            .parse::<u8>()
+            .map(core::view::clamp_view_radius)
+            .map_err(|_| format!("Invalid {} value '{}'", VIEW_RADIUS_ENV, value)),
+    }
+}
+
+/// Sends `SV_SETVIEW` to player `nr` if their client can zoom.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+///
+/// # Returns
+///
+/// * `true` if a packet was sent.
+pub fn plr_send_view(gs: &mut GameState, nr: usize) -> bool {
+    if gs.players[nr].state != ST_NORMAL || gs.players[nr].capabilities & CLIENT_CAP_WIDE_VIEW == 0
+    {
+        return false;
+    }
+
+    let buf = [ServerCommandType::SetView as u8, gs.view_radius];
+    network_manager::xsend(gs, nr, &buf, buf.len());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::view::{LEGACY_VIEW_RADIUS, MAX_VIEW_RADIUS};
+
+    #[test]
+    fn view_radius_setting_is_clamped() {
+        assert_eq!(parse_view_radius(None), Ok(MAX_VIEW_RADIUS));
+        assert_eq!(parse_view_radius(Some("")), Ok(MAX_VIEW_RADIUS));
+        assert_eq!(parse_view_radius(Some("10")), Ok(LEGACY_VIEW_RADIUS));
+        assert_eq!(parse_view_radius(Some(" 35 ")), Ok(35));
+        assert!(parse_view_radius(Some("wide")).is_err());
+    }
+
+    #[test]
+    fn view_requires_capability() {
+        with_test_gs(|gs| {
+            let (_, nr) = add_test_player(gs);
+            assert!(!plr_send_view(gs, nr));
+
+            gs.players[nr].capabilities = CLIENT_CAP_WIDE_VIEW;
+            assert!(plr_send_view(gs, nr));
+        });
+    }
+}
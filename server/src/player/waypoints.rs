@@ -0,0 +1,194 @@
+//! `CmdWaypoints` routes planned by the client.
+//!
+//! Every hop of a route is checked when it arrives (short, on the map, not
+//! blocked); one bad hop drops the whole route. The character then walks to
+//! one waypoint at a time through the normal `goto_x`/`goto_y` steering.
+//! Any other command that changes the goto target, or a walk the driver
+//! gives up on, ends the route.
+
+use core::constants::{ItemFlags, MF_MOVEBLOCK, SERVER_MAPX, SERVER_MAPY};
+use core::waypoints::{MAX_WAYPOINT_SPAN, MAX_WAYPOINTS, decode_waypoints, span};
+
+use crate::game_state::GameState;
+
+/// Returns whether a character can stand on world tile `(x, y)`.
+///
+/// Mirrors the static part of the path finder's passability check: terrain
+/// `MF_MOVEBLOCK` and blocking items block, doors (driver 2) do not.
+/// Characters standing on the tile are ignored.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `x`, `y` - World tile.
+///
+/// # Returns
+///
+/// * `true` if the tile is inside the map and walkable.
+pub fn tile_walkable(gs: &GameState, x: i32, y: i32) -> bool {
+    if !(1..SERVER_MAPX - 1).contains(&x) || !(1..SERVER_MAPY - 1).contains(&y) {
+        return false;
+    }
+    let m = (x + y * SERVER_MAPX) as usize;
+    if gs.map[m].flags & u64::from(MF_MOVEBLOCK) != 0 {
+        return false;
+    }
+    let it = gs.map[m].it as usize;
+    !(it != 0
+        && it < gs.items.len()
+        && gs.items[it].flags & ItemFlags::IF_MOVEBLOCK.bits() != 0
+        && gs.items[it].driver != 2)
+}
+
+/// Handle the `CmdWaypoints` packet.
+///
+/// The first packet of a route replaces any route in progress and starts
+/// walking to its first waypoint; append packets extend it.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot that sent the packet.
+pub fn plr_cmd_waypoints(gs: &mut GameState, nr: usize) {
+    let cn = gs.players[nr].usnr;
+    let Some((append, points)) = decode_waypoints(&gs.players[nr].inbuf[1..16]) else {
+        log::warn!("plr_cmd_waypoints: malformed packet from player {}", nr);
+        return;
+    };
+
+    // Observers move their camera with plain move commands.
+    if crate::player::observer::is_observer(gs, cn) {
+        gs.players[nr].waypoints.clear();
+        return;
+    }
+
+    if !append {
+        gs.players[nr].waypoints.clear();
+    } else if gs.players[nr].waypoints.is_empty() {
+        // The start of this route was rejected (or already walked).
+        return;
+    }
+
+    let ch = &gs.characters[cn];
+    let mut prev = gs.players[nr]
+        .waypoints
+        .back()
+        .copied()
+        .unwrap_or((ch.x as u16, ch.y as u16));
+    for point in points {
+        let hop = span(prev, point);
+        if gs.players[nr].waypoints.len() >= MAX_WAYPOINTS
+            || hop == 0
+            || hop > MAX_WAYPOINT_SPAN
+            || !tile_walkable(gs, i32::from(point.0), i32::from(point.1))
+        {
+            log::debug!(
+                "plr_cmd_waypoints: dropping route of player {} at ({},{})",
+                nr,
+                point.0,
+                point.1
+            );
+            gs.players[nr].waypoints.clear();
+            return;
+        }
+        gs.players[nr].waypoints.push_back(point);
+        prev = point;
+    }
+
+    if !append && let Some(&(x, y)) = gs.players[nr].waypoints.front() {
+        let ticker = gs.globals.ticker;
+        let ch = &mut gs.characters[cn];
+        ch.attack_cn = 0;
+        ch.goto_x = x;
+        ch.goto_y = y;
+        ch.misc_action = 0;
+        ch.cerrno = 0;
+        ch.data[12] = ticker;
+    }
+}
+
+/// Moves player `nr` on to the next waypoint once the current one is
+/// reached, and drops the route when the goto target was changed or
+/// abandoned. Called once per tick.
+///
+/// # Arguments
+///
+/// * `gs` - Game state.
+/// * `nr` - Player slot.
+pub fn plr_advance_waypoints(gs: &mut GameState, nr: usize) {
+    let Some(&(wx, wy)) = gs.players[nr].waypoints.front() else {
+        return;
+    };
+    let cn = gs.players[nr].usnr;
+    let ch = &gs.characters[cn];
+
+    if (ch.x as u16, ch.y as u16) == (wx, wy) {
+        gs.players[nr].waypoints.pop_front();
+        if let Some(&(x, y)) = gs.players[nr].waypoints.front() {
+            gs.characters[cn].goto_x = x;
+            gs.characters[cn].goto_y = y;
+        }
+    } else if (ch.goto_x, ch.goto_y) != (wx, wy) {
+        gs.players[nr].waypoints.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs, write_inbuf};
+    use core::client_commands::ClientCommand;
+
+    fn send_route(gs: &mut GameState, nr: usize, route: &[(u16, u16)]) {
+        for packet in ClientCommand::new_waypoint_packets(route) {
+            write_inbuf(gs, nr, &packet.to_bytes());
+            plr_cmd_waypoints(gs, nr);
+        }
+    }
+
+    #[test]
+    fn route_is_walked_one_waypoint_at_a_time() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            send_route(gs, nr, &[(14, 10), (14, 16), (20, 16), (20, 20)]);
+            assert_eq!(gs.players[nr].waypoints.len(), 4);
+            assert_eq!(
+                (gs.characters[cn].goto_x, gs.characters[cn].goto_y),
+                (14, 10)
+            );
+
+            // Still walking: nothing changes.
+            plr_advance_waypoints(gs, nr);
+            assert_eq!(gs.players[nr].waypoints.len(), 4);
+
+            // Arrived (the driver clears goto on arrival).
+            gs.characters[cn].x = 14;
+            gs.characters[cn].goto_x = 0;
+            plr_advance_waypoints(gs, nr);
+            assert_eq!(
+                (gs.characters[cn].goto_x, gs.characters[cn].goto_y),
+                (14, 16)
+            );
+
+            // A plain move command replaces the goto target and ends the route.
+            gs.characters[cn].goto_x = 30;
+            plr_advance_waypoints(gs, nr);
+            assert!(gs.players[nr].waypoints.is_empty());
+        });
+    }
+
+    #[test]
+    fn long_hops_and_blocked_tiles_drop_the_route() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            send_route(gs, nr, &[(10 + MAX_WAYPOINT_SPAN + 1, 10)]);
+            assert!(gs.players[nr].waypoints.is_empty());
+            assert_eq!(gs.characters[cn].goto_x, 0);
+
+            let m = (12 + 12 * SERVER_MAPX) as usize;
+            gs.map[m].flags |= u64::from(MF_MOVEBLOCK);
+            send_route(gs, nr, &[(12, 10), (12, 12), (14, 12), (16, 12)]);
+            assert!(gs.players[nr].waypoints.is_empty());
+        });
+    }
+}
@@ -1,9 +1,11 @@
 use core::{
+    balance,
     constants::{
         AT_AGIL, AT_BRAVE, AT_INT, AT_STREN, AT_WILL, DX_DOWN, MAXCHARS, MAXEFFECT, MAXITEM,
-        MAXTCHARS, MAXTITEM, MF_INDOORS, MF_MOVEBLOCK, MF_SIGHTBLOCK, SERVER_MAPX, SERVER_MAPY,
-        TICKS, USE_ACTIVE, USE_EMPTY,
+        MAXTCHARS, MAXTITEM, MF_MOVEBLOCK, MF_SIGHTBLOCK, SERVER_MAPX, SERVER_MAPY, TICKS,
+        USE_ACTIVE, USE_EMPTY,
     },
+    logout_reasons::LogoutReason,
     skills,
     world_action_store::WorldActionKind,
 };
@@ -12,7 +14,7 @@ use {core::constants::CharacterFlags, core::constants::ItemFlags};
 
 use crate::{
     driver::use_item, effect::EffectManager, game_state::GameState, god::God, helpers, player,
-    points,
+    tick_phases,
 };
 
 /// Result summary returned after a world admin action executes.
@@ -71,11 +73,61 @@ pub fn execute_world_action(
             pop_reset_all(gs);
             "all templates reset".to_owned()
         }
+        WorldActionKind::KickPlayer { name } => {
+            let Some((player_id, character_id)) = find_online_player(gs, name) else {
+                return Err(format!("no online player named {}", name));
+            };
+            let character_name = gs.characters[character_id].get_name().to_owned();
+            player::connection::plr_logout_with_text(
+                gs,
+                character_id,
+                player_id,
+                LogoutReason::Kicked,
+                "An administrator removed you from the game.",
+            );
+            format!("kicked {}", character_name)
+        }
+        WorldActionKind::Broadcast { message } => {
+            let message = message.trim();
+            if message.is_empty() {
+                return Err("broadcast message is empty".to_owned());
+            }
+            gs.do_announce(0, 0, &format!("{}\n", message));
+            "broadcast sent".to_owned()
+        }
     };
 
     Ok(WorldActionOutcome { message })
 }
 
+/// Find the online session controlling the character called `name`.
+///
+/// # Arguments
+///
+/// * `gs`   - Game state to search.
+/// * `name` - Character name, compared case-insensitively.
+///
+/// # Returns
+///
+/// * `Some((player_id, character_id))` for a logged-in match, else `None`.
+pub(crate) fn find_online_player(gs: &GameState, name: &str) -> Option<(usize, usize)> {
+    let name = name.trim();
+    (1..core::constants::MAXPLAYER).find_map(|player_id| {
+        let player = &gs.players[player_id];
+        let character_id = player.usnr;
+        if player.sock.is_none()
+            || player.state != core::constants::ST_NORMAL
+            || !(1..MAXCHARS).contains(&character_id)
+            || !gs.characters[character_id]
+                .get_name()
+                .eq_ignore_ascii_case(name)
+        {
+            return None;
+        }
+        Some((player_id, character_id))
+    })
+}
+
 /// Port of `init_lights` from `populate.cpp`
 /// Initialize lighting on the map
 ///
@@ -83,9 +135,6 @@ pub fn execute_world_action(
 ///
 /// * `gs` - Active game state used by this function.
 pub fn init_lights(gs: &mut GameState) {
-    let mut cnt1 = 0;
-    let mut cnt2 = 0;
-
     // First pass: clear all light and dlight values
     for y in 0..SERVER_MAPY as usize {
         for x in 0..SERVER_MAPX as usize {
@@ -95,43 +144,9 @@ pub fn init_lights(gs: &mut GameState) {
         }
     }
 
-    // Second pass: compute dlight for indoor tiles, then add lights from items
-    for y in 0..SERVER_MAPY as usize {
-        for x in 0..SERVER_MAPX as usize {
-            let m = x + y * SERVER_MAPX as usize;
-
-            // Compute daylight for indoor tiles first
-            let is_indoors = gs.map[m].flags & u64::from(MF_INDOORS) != 0;
-
-            if is_indoors {
-                gs.compute_dlight(x as i32, y as i32);
-                cnt2 += 1;
-            }
-
-            // Then add light from items
-            let in_id = gs.map[m].it;
-
-            if in_id == 0 {
-                continue;
-            }
-
-            let active = gs.items[in_id as usize].active;
-            let light_active = gs.items[in_id as usize].light[1];
-            let light_inactive = gs.items[in_id as usize].light[0];
-
-            if active != 0 {
-                if light_active != 0 {
-                    gs.do_add_light(x as i32, y as i32, i32::from(light_active));
-                    cnt1 += 1;
-                }
-            } else {
-                if light_inactive != 0 {
-                    gs.do_add_light(x as i32, y as i32, i32::from(light_inactive));
-                    cnt1 += 1;
-                }
-            }
-        }
-    }
+    // Second pass: daylight for indoor tiles and light from items, computed
+    // on the worker pool and merged in map order.
+    let (cnt1, cnt2) = tick_phases::rebuild_lights(gs);
 
     log::info!("Initialized lights: {} items, {} indoor tiles", cnt1, cnt2);
 }
@@ -990,7 +1005,7 @@ pub fn reset_char(gs: &mut GameState, n: usize) {
     let name = gs.character_templates[n].get_name().to_owned();
     log::info!("Resetting char {} ({})", n, name);
 
-    let points_tot = points::calculate_points_tot(&gs.character_templates[n]);
+    let points_tot = balance::calculate_points_tot(&gs.character_templates[n]);
     gs.character_templates[n].points_tot = points_tot;
 
     let mut cnt = 0;
@@ -1212,6 +1227,9 @@ pub fn reset_item(gs: &mut GameState, n: usize) {
             gs.items[in_id].y = y;
             gs.items[in_id].carried = carried;
             gs.items[in_id].temp = n as u16;
+            if let Some(journal) = gs.journal.as_mut() {
+                journal.note_item_reset(in_id);
+            }
         } else {
             // Remove item and place floor sprite (for non-interactive map items)
             let map_index = x as usize + y as usize * SERVER_MAPX as usize;
@@ -1227,6 +1245,9 @@ pub fn reset_item(gs: &mut GameState, n: usize) {
             }
 
             gs.items[in_id].used = USE_EMPTY;
+            if let Some(journal) = gs.journal.as_mut() {
+                journal.note_item_reset(in_id);
+            }
         }
     }
 }
@@ -0,0 +1,428 @@
+//! Region graph for hierarchical pathfinding.
+//!
+//! The map is cut into `CLUSTER_SIZE` x `CLUSTER_SIZE` clusters. Inside each
+//! cluster, tiles that are not statically blocked are flood-filled into
+//! regions (tiles that reach each other without leaving the cluster), and
+//! regions touching across a cluster border are linked through a portal tile.
+//! A* over this graph finds a coarse route across the whole map in a handful
+//! of expansions; [`PathFinder`](crate::path_finding::PathFinder) then only
+//! runs its tile-level search to the next portal on that route, which keeps
+//! long detours inside its node budget.
+//!
+//! Only static blockers go into the graph: `MF_MOVEBLOCK` tiles and blocking
+//! items that cannot be picked up, doors excepted. Characters, doors and the
+//! per-character `MF_NOMONST`/`MF_DEATHTRAP` masks are left to the tile
+//! search, which falls back to a direct search when a portal turns out to be
+//! unreachable.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+
+use core::constants::{ItemFlags, MF_MOVEBLOCK, SERVER_MAPX, SERVER_MAPY};
+
+/// Side length of a cluster in tiles.
+pub const CLUSTER_SIZE: usize = 16;
+
+/// Region expansions after which a route search gives up.
+const MAX_REGION_NODES: usize = 4096;
+
+const MAPX: usize = SERVER_MAPX as usize;
+const MAPY: usize = SERVER_MAPY as usize;
+const CLUSTERS_X: usize = MAPX / CLUSTER_SIZE;
+const CLUSTERS_Y: usize = MAPY / CLUSTER_SIZE;
+const NO_REGION: u32 = u32::MAX;
+
+/// Link from one region into a neighbouring one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Portal {
+    /// Region entered.
+    to: u32,
+    /// Tile inside `to` next to the shared border.
+    x: i16,
+    y: i16,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Region {
+    portals: Vec<Portal>,
+}
+
+/// Precomputed connectivity between map regions.
+#[derive(Default)]
+pub struct RegionGraph {
+    /// Region per tile, `NO_REGION` for blocked tiles. Empty until built.
+    tile_region: Vec<u32>,
+    /// Regions by id; `None` for ids freed by a cluster rebuild.
+    regions: Vec<Option<Region>>,
+    /// Ids free for reuse.
+    free: Vec<u32>,
+    /// Region ids per cluster.
+    cluster_regions: Vec<Vec<u32>>,
+}
+
+/// Path cost estimate between two tiles, matching the tile search's weights.
+fn step_cost(fx: i16, fy: i16, tx: i16, ty: i16) -> i32 {
+    let dx = i32::from((fx - tx).abs());
+    let dy = i32::from((fy - ty).abs());
+    (dx.max(dy) << 1) + dx.min(dy)
+}
+
+fn is_static_block(map: &[core::types::Map], items: &[core::types::Item], m: usize) -> bool {
+    if map[m].flags & u64::from(MF_MOVEBLOCK) != 0 {
+        return true;
+    }
+    let it = map[m].it as usize;
+    it != 0
+        && it < items.len()
+        && items[it].flags & ItemFlags::IF_MOVEBLOCK.bits() != 0
+        && items[it].flags & ItemFlags::IF_TAKE.bits() == 0
+        && items[it].driver != 2
+}
+
+impl RegionGraph {
+    /// Create an empty, unbuilt graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether [`build`](Self::build) has run.
+    pub fn is_built(&self) -> bool {
+        !self.tile_region.is_empty()
+    }
+
+    /// Number of live regions.
+    pub fn region_count(&self) -> usize {
+        self.regions.iter().flatten().count()
+    }
+
+    /// Build the graph for the whole map.
+    ///
+    /// # Arguments
+    ///
+    /// * `map` - World map tiles (`SERVER_MAPX * SERVER_MAPY`).
+    /// * `items` - Item table, for static blocking items.
+    pub fn build(&mut self, map: &[core::types::Map], items: &[core::types::Item]) {
+        if map.len() != MAPX * MAPY {
+            log::warn!(
+                "Not building pathfinding regions: map has {} tiles, expected {}",
+                map.len(),
+                MAPX * MAPY
+            );
+            return;
+        }
+        self.tile_region = vec![NO_REGION; map.len()];
+        self.regions.clear();
+        self.free.clear();
+        self.cluster_regions = vec![Vec::new(); CLUSTERS_X * CLUSTERS_Y];
+
+        for c in 0..CLUSTERS_X * CLUSTERS_Y {
+            self.fill_cluster(c, map, items);
+        }
+        for cy in 0..CLUSTERS_Y {
+            for cx in 0..CLUSTERS_X {
+                if cx + 1 < CLUSTERS_X {
+                    self.link(cx, cy, cx + 1, cy);
+                }
+                if cy + 1 < CLUSTERS_Y {
+                    self.link(cx, cy, cx, cy + 1);
+                }
+            }
+        }
+    }
+
+    /// Rebuild the cluster containing a tile after its blockers changed.
+    ///
+    /// Does nothing while the graph is unbuilt.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`, `y` - Changed tile.
+    /// * `map` - World map tiles.
+    /// * `items` - Item table.
+    pub fn rebuild_at(
+        &mut self,
+        x: usize,
+        y: usize,
+        map: &[core::types::Map],
+        items: &[core::types::Item],
+    ) {
+        if !self.is_built() || x >= MAPX || y >= MAPY {
+            return;
+        }
+        let (cx, cy) = (x / CLUSTER_SIZE, y / CLUSTER_SIZE);
+        let c = cx + cy * CLUSTERS_X;
+
+        for id in std::mem::take(&mut self.cluster_regions[c]) {
+            if let Some(region) = self.regions[id as usize].take() {
+                for portal in region.portals {
+                    if let Some(other) = self.regions[portal.to as usize].as_mut() {
+                        other.portals.retain(|p| p.to != id);
+                    }
+                }
+            }
+            self.free.push(id);
+        }
+
+        self.fill_cluster(c, map, items);
+        if cx > 0 {
+            self.link(cx - 1, cy, cx, cy);
+        }
+        if cx + 1 < CLUSTERS_X {
+            self.link(cx, cy, cx + 1, cy);
+        }
+        if cy > 0 {
+            self.link(cx, cy - 1, cx, cy);
+        }
+        if cy + 1 < CLUSTERS_Y {
+            self.link(cx, cy, cx, cy + 1);
+        }
+    }
+
+    fn alloc_region(&mut self) -> u32 {
+        match self.free.pop() {
+            Some(id) => {
+                self.regions[id as usize] = Some(Region::default());
+                id
+            }
+            None => {
+                self.regions.push(Some(Region::default()));
+                (self.regions.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Flood-fill the open tiles of cluster `c` into regions.
+    fn fill_cluster(&mut self, c: usize, map: &[core::types::Map], items: &[core::types::Item]) {
+        let x0 = (c % CLUSTERS_X) * CLUSTER_SIZE;
+        let y0 = (c / CLUSTERS_X) * CLUSTER_SIZE;
+        for y in y0..y0 + CLUSTER_SIZE {
+            for x in x0..x0 + CLUSTER_SIZE {
+                self.tile_region[x + y * MAPX] = NO_REGION;
+            }
+        }
+
+        let mut stack = Vec::new();
+        for y in y0..y0 + CLUSTER_SIZE {
+            for x in x0..x0 + CLUSTER_SIZE {
+                let m = x + y * MAPX;
+                if self.tile_region[m] != NO_REGION || is_static_block(map, items, m) {
+                    continue;
+                }
+                let id = self.alloc_region();
+                self.cluster_regions[c].push(id);
+                self.tile_region[m] = id;
+                stack.push((x, y));
+                while let Some((px, py)) = stack.pop() {
+                    let neighbours = [
+                        (px.wrapping_sub(1), py),
+                        (px + 1, py),
+                        (px, py.wrapping_sub(1)),
+                        (px, py + 1),
+                    ];
+                    for (nx, ny) in neighbours {
+                        if nx < x0 || nx >= x0 + CLUSTER_SIZE || ny < y0 || ny >= y0 + CLUSTER_SIZE
+                        {
+                            continue;
+                        }
+                        let n = nx + ny * MAPX;
+                        if self.tile_region[n] == NO_REGION && !is_static_block(map, items, n) {
+                            self.tile_region[n] = id;
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add portals between regions of two adjacent clusters.
+    ///
+    /// `(bx, by)` must be the cluster right of or below `(ax, ay)`. One portal
+    /// is added per touching region pair, at the middle of their contact.
+    fn link(&mut self, ax: usize, ay: usize, bx: usize, by: usize) {
+        let mut contacts: BTreeMap<(u32, u32), Vec<(usize, usize)>> = BTreeMap::new();
+        for i in 0..CLUSTER_SIZE {
+            let (ta, tb) = if bx > ax {
+                let y = ay * CLUSTER_SIZE + i;
+                let x = bx * CLUSTER_SIZE;
+                ((x - 1, y), (x, y))
+            } else {
+                let x = ax * CLUSTER_SIZE + i;
+                let y = by * CLUSTER_SIZE;
+                ((x, y - 1), (x, y))
+            };
+            let ra = self.tile_region[ta.0 + ta.1 * MAPX];
+            let rb = self.tile_region[tb.0 + tb.1 * MAPX];
+            if ra != NO_REGION && rb != NO_REGION {
+                contacts
+                    .entry((ra, rb))
+                    .or_default()
+                    .push((ta.0 + ta.1 * MAPX, tb.0 + tb.1 * MAPX));
+            }
+        }
+
+        for ((ra, rb), pairs) in contacts {
+            let (ta, tb) = pairs[pairs.len() / 2];
+            let portal = |to: u32, m: usize| Portal {
+                to,
+                x: (m % MAPX) as i16,
+                y: (m / MAPX) as i16,
+            };
+            if let Some(region) = self.regions[ra as usize].as_mut() {
+                region.portals.push(portal(rb, tb));
+            }
+            if let Some(region) = self.regions[rb as usize].as_mut() {
+                region.portals.push(portal(ra, ta));
+            }
+        }
+    }
+
+    fn region_at(&self, x: i16, y: i16) -> Option<u32> {
+        if x < 0 || y < 0 || x as usize >= MAPX || y as usize >= MAPY {
+            return None;
+        }
+        let id = *self.tile_region.get(x as usize + y as usize * MAPX)?;
+        (id != NO_REGION).then_some(id)
+    }
+
+    /// Coarse route between two tiles as a list of portal tiles.
+    ///
+    /// The goal tile may itself be blocked (e.g. a chest the character
+    /// walks up to); any region next to it then counts as the goal.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Start tile.
+    /// * `to` - Goal tile.
+    ///
+    /// # Returns
+    ///
+    /// * Portal tiles in travel order; empty when start and goal share a
+    ///   region.
+    /// * `None` when the graph is unbuilt, either end has no region, or no
+    ///   route was found within the expansion limit.
+    pub fn route(&self, from: (i16, i16), to: (i16, i16)) -> Option<Vec<(i16, i16)>> {
+        let start = self.region_at(from.0, from.1)?;
+        let goals: Vec<u32> = [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .filter_map(|(dx, dy)| self.region_at(to.0 + dx, to.1 + dy))
+            .collect();
+        if goals.is_empty() {
+            return None;
+        }
+        if goals.contains(&start) {
+            return Some(Vec::new());
+        }
+
+        // region -> (cost so far, entry tile, previous region)
+        let mut best: HashMap<u32, (i32, (i16, i16), u32)> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        best.insert(start, (0, from, NO_REGION));
+        open.push(Reverse((step_cost(from.0, from.1, to.0, to.1), start)));
+
+        let mut expanded = 0;
+        while let Some(Reverse((_, region))) = open.pop() {
+            if goals.contains(&region) {
+                let mut route = Vec::new();
+                let mut current = region;
+                while current != start {
+                    let (_, entry, prev) = best[&current];
+                    route.push(entry);
+                    current = prev;
+                }
+                route.reverse();
+                return Some(route);
+            }
+            expanded += 1;
+            if expanded > MAX_REGION_NODES {
+                return None;
+            }
+
+            let (cost, pos, _) = best[&region];
+            let Some(Some(node)) = self.regions.get(region as usize) else {
+                continue;
+            };
+            for portal in &node.portals {
+                let next_cost = cost + step_cost(pos.0, pos.1, portal.x, portal.y) + 2;
+                if best
+                    .get(&portal.to)
+                    .is_some_and(|&(known, _, _)| known <= next_cost)
+                {
+                    continue;
+                }
+                best.insert(portal.to, (next_cost, (portal.x, portal.y), region));
+                let estimate = next_cost + step_cost(portal.x, portal.y, to.0, to.1);
+                open.push(Reverse((estimate, portal.to)));
+            }
+        }
+        None
+    }
+
+    /// Next tile worth walking to on the way to a distant target.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Current tile.
+    /// * `to` - Final target.
+    /// * `lookahead` - Furthest (Chebyshev) distance a waypoint may be.
+    ///
+    /// # Returns
+    ///
+    /// * The furthest portal on the route within `lookahead`, or the first
+    ///   one if even that is further; `None` when there is no route or the
+    ///   target is in the current region.
+    pub fn next_waypoint(
+        &self,
+        from: (i16, i16),
+        to: (i16, i16),
+        lookahead: i16,
+    ) -> Option<(i16, i16)> {
+        let route = self.route(from, to)?;
+        let within = |&&(x, y): &&(i16, i16)| {
+            let d = (x - from.0).abs().max((y - from.1).abs());
+            d > 0 && d <= lookahead
+        };
+        route
+            .iter()
+            .rev()
+            .find(within)
+            .or_else(|| route.iter().find(|&&p| p != from))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Open map with a wall along `x = 40` that has a single gap at `y = 500`.
+    fn walled_map() -> Vec<core::types::Map> {
+        let mut map = vec![core::types::Map::default(); MAPX * MAPY];
+        for y in 0..MAPY {
+            if y != 500 {
+                map[40 + y * MAPX].flags |= u64::from(MF_MOVEBLOCK);
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn route_goes_through_the_gap_and_follows_rebuilds() {
+        let mut map = walled_map();
+        let mut graph = RegionGraph::new();
+        assert!(graph.route((20, 20), (60, 20)).is_none());
+        graph.build(&map, &[]);
+
+        let route = graph.route((20, 20), (60, 20)).unwrap();
+        assert!(route.iter().any(|&(_, y)| (496..512).contains(&y)));
+        assert_eq!(graph.route((20, 20), (25, 25)), Some(Vec::new()));
+
+        map[40 + 500 * MAPX].flags |= u64::from(MF_MOVEBLOCK);
+        graph.rebuild_at(40, 500, &map, &[]);
+        assert!(graph.route((20, 20), (60, 20)).is_none());
+
+        map[40 + 500 * MAPX].flags = 0;
+        graph.rebuild_at(40, 500, &map, &[]);
+        assert!(graph.route((20, 20), (60, 20)).is_some());
+    }
+}
@@ -0,0 +1,265 @@
+//! Scripted encounter scenarios for content designers.
+//!
+//! A scenario is a RON file describing a small situation — NPCs and items
+//! created from templates at given positions — plus expectations about what
+//! happens next. `server --scenario <file>` loads the world from the
+//! configured storage backend, sets the situation up, runs the simulation
+//! without networking and prints a pass/fail line per expectation. Nothing
+//! is saved back to storage.
+//!
+//! ```ron
+//! Scenario(
+//!     name: "Guard kills the goblin",
+//!     ticks: 720,
+//!     characters: [
+//!         (id: "guard", template: 12, x: 512, y: 498, attacks: Some("goblin")),
+//!         (id: "goblin", template: 40, x: 514, y: 498, hp: Some(5)),
+//!     ],
+//!     items: [
+//!         (template: 57, carried_by: Some("goblin")),
+//!     ],
+//!     expect: [
+//!         Dies(who: "goblin", within: 360),
+//!         Drops(template: 57, within: 360),
+//!         Survives(who: "guard"),
+//!     ],
+//! )
+//! ```
+
+pub mod runner;
+
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+/// Command-line flag selecting a scenario file.
+pub const SCENARIO_FLAG: &str = "--scenario";
+
+/// Longest run a scenario may ask for (ten minutes of game time).
+pub const MAX_SCENARIO_TICKS: u32 = 10 * 60 * core::constants::TICKS as u32;
+
+/// A character created from a template when the scenario starts.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CharacterSetup {
+    /// Name other entries use to refer to this character.
+    pub id: String,
+    /// Character template to create it from.
+    pub template: usize,
+    pub x: usize,
+    pub y: usize,
+    /// Hit points to start with instead of full health.
+    #[serde(default)]
+    pub hp: Option<i32>,
+    /// Id of a scenario character this one attacks from the first tick.
+    #[serde(default)]
+    pub attacks: Option<String>,
+}
+
+/// An item created from a template when the scenario starts.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ItemSetup {
+    /// Item template to create it from.
+    pub template: usize,
+    /// Map tile to drop it on; ignored when `carried_by` is set.
+    #[serde(default)]
+    pub at: Option<(usize, usize)>,
+    /// Id of a scenario character that gets it in its inventory.
+    #[serde(default)]
+    pub carried_by: Option<String>,
+}
+
+/// Something the scenario expects to happen.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum Expectation {
+    /// The character dies within `within` ticks.
+    Dies { who: String, within: u32 },
+    /// The character is still alive when the scenario ends.
+    Survives { who: String },
+    /// An item of `template` that was not there at the start lies on the
+    /// ground or in a corpse within `within` ticks.
+    Drops { template: u16, within: u32 },
+}
+
+/// A parsed scenario file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    /// Ticks to simulate; the run stops earlier once every expectation
+    /// is decided.
+    pub ticks: u32,
+    #[serde(default)]
+    pub characters: Vec<CharacterSetup>,
+    #[serde(default)]
+    pub items: Vec<ItemSetup>,
+    pub expect: Vec<Expectation>,
+}
+
+impl Scenario {
+    /// Parses and checks a scenario.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - RON source of the scenario.
+    ///
+    /// # Returns
+    ///
+    /// * The scenario, or a message naming the first problem found.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let scenario: Scenario =
+            ron::from_str(text).map_err(|e| format!("Invalid scenario: {}", e))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Checks references between entries and the tick budget.
+    fn validate(&self) -> Result<(), String> {
+        if self.ticks == 0 || self.ticks > MAX_SCENARIO_TICKS {
+            return Err(format!(
+                "ticks must be between 1 and {}, got {}",
+                MAX_SCENARIO_TICKS, self.ticks
+            ));
+        }
+        if self.expect.is_empty() {
+            return Err("scenario has no expectations".to_owned());
+        }
+
+        let mut ids = HashSet::new();
+        for character in &self.characters {
+            if !ids.insert(character.id.as_str()) {
+                return Err(format!("character id '{}' is used twice", character.id));
+            }
+        }
+        let known = |id: &str| -> Result<(), String> {
+            if ids.contains(id) {
+                Ok(())
+            } else {
+                Err(format!("unknown character id '{}'", id))
+            }
+        };
+
+        for character in &self.characters {
+            if let Some(target) = &character.attacks {
+                known(target)?;
+                if target == &character.id {
+                    return Err(format!("'{}' cannot attack itself", character.id));
+                }
+            }
+        }
+        for item in &self.items {
+            match (&item.carried_by, item.at) {
+                (Some(carrier), _) => known(carrier)?,
+                (None, Some(_)) => {}
+                (None, None) => {
+                    return Err(format!(
+                        "item template {} needs either `at` or `carried_by`",
+                        item.template
+                    ));
+                }
+            }
+        }
+        for expectation in &self.expect {
+            match expectation {
+                Expectation::Dies { who, within } => {
+                    known(who)?;
+                    if *within > self.ticks {
+                        return Err(format!(
+                            "Dies({}) deadline {} is past the scenario end",
+                            who, within
+                        ));
+                    }
+                }
+                Expectation::Survives { who } => known(who)?,
+                Expectation::Drops { template, within } => {
+                    if *within > self.ticks {
+                        return Err(format!(
+                            "Drops({}) deadline {} is past the scenario end",
+                            template, within
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the scenario file in the server's command line.
+///
+/// # Arguments
+///
+/// * `args` - Command-line arguments without the program name.
+///
+/// # Returns
+///
+/// * `Ok(Some(path))` when `--scenario <path>` is given, `Ok(None)` when it
+///   is absent, or an error when the path is missing.
+pub fn scenario_path(args: &[String]) -> Result<Option<String>, String> {
+    let Some(pos) = args.iter().position(|arg| arg == SCENARIO_FLAG) else {
+        return Ok(None);
+    };
+    match args.get(pos + 1) {
+        Some(path) if !path.starts_with("--") => Ok(Some(path.clone())),
+        _ => Err(format!("{} needs a file path", SCENARIO_FLAG)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOBLIN: &str = r#"
+        Scenario(
+            name: "Guard kills the goblin",
+            ticks: 720,
+            characters: [
+                (id: "guard", template: 12, x: 512, y: 498, attacks: Some("goblin")),
+                (id: "goblin", template: 40, x: 514, y: 498, hp: Some(5)),
+            ],
+            items: [(template: 57, carried_by: Some("goblin"))],
+            expect: [
+                Dies(who: "goblin", within: 360),
+                Drops(template: 57, within: 360),
+                Survives(who: "guard"),
+            ],
+        )
+    "#;
+
+    #[test]
+    fn parses_example_scenario() {
+        let scenario = Scenario::parse(GOBLIN).unwrap();
+        assert_eq!(scenario.characters.len(), 2);
+        assert_eq!(scenario.characters[0].attacks.as_deref(), Some("goblin"));
+        assert_eq!(scenario.characters[1].hp, Some(5));
+        assert_eq!(
+            scenario.expect[0],
+            Expectation::Dies {
+                who: "goblin".to_owned(),
+                within: 360
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_references_and_deadlines() {
+        let unknown = GOBLIN.replace("Survives(who: \"guard\")", "Survives(who: \"ogre\")");
+        assert!(Scenario::parse(&unknown).unwrap_err().contains("ogre"));
+        let late = GOBLIN.replace(
+            "within: 360),\n                Drops",
+            "within: 900),\n                Drops",
+        );
+        assert!(Scenario::parse(&late).is_err());
+        let floating = GOBLIN.replace("carried_by: Some(\"goblin\")", "carried_by: None");
+        assert!(Scenario::parse(&floating).is_err());
+    }
+
+    #[test]
+    fn finds_scenario_flag() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(scenario_path(&args(&[])), Ok(None));
+        assert_eq!(
+            scenario_path(&args(&["--scenario", "goblin.ron"])),
+            Ok(Some("goblin.ron".to_owned()))
+        );
+        assert!(scenario_path(&args(&["--scenario"])).is_err());
+    }
+}
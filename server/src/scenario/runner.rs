@@ -0,0 +1,271 @@
+//! Sets up a [`Scenario`] in a loaded world and simulates it.
+
+use std::collections::{HashMap, HashSet};
+
+use core::constants::{CharacterFlags, MAXCHARS, MAXTCHARS, USE_ACTIVE, USE_EMPTY};
+
+use super::{Expectation, Scenario};
+use crate::driver::npc::npc_add_enemy;
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::populate;
+use crate::server::Server;
+
+/// How an expectation turned out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Not decided yet.
+    Pending,
+    /// Met at the given tick of the run.
+    Passed(u32),
+    /// Not met; the reason is shown to the designer.
+    Failed(String),
+}
+
+/// Scenario characters by id, mapped to their character slots.
+pub type Placed = HashMap<String, usize>;
+
+/// Creates the scenario's characters and items in `gs`.
+///
+/// # Arguments
+///
+/// * `gs` - Game state with templates and map loaded.
+/// * `scenario` - Validated scenario.
+///
+/// # Returns
+///
+/// * The character slot of every scenario character, or why setup failed.
+pub fn setup(gs: &mut GameState, scenario: &Scenario) -> Result<Placed, String> {
+    let mut placed = Placed::new();
+    for spec in &scenario.characters {
+        if !(1..MAXTCHARS).contains(&spec.template)
+            || gs.character_templates[spec.template].used == USE_EMPTY
+        {
+            return Err(format!(
+                "'{}': character template {} does not exist",
+                spec.id, spec.template
+            ));
+        }
+        let cn = populate::pop_create_char(gs, spec.template, false)
+            .ok_or_else(|| format!("'{}': could not create a character", spec.id))?;
+        if !God::drop_char_fuzzy(gs, cn, spec.x, spec.y) {
+            God::destroy_items(gs, cn);
+            gs.characters[cn].used = USE_EMPTY;
+            return Err(format!(
+                "'{}': no free tile near {},{}",
+                spec.id, spec.x, spec.y
+            ));
+        }
+        if let Some(hp) = spec.hp {
+            gs.characters[cn].a_hp = hp.max(1) * 1000;
+        }
+        placed.insert(spec.id.clone(), cn);
+    }
+
+    for spec in &scenario.characters {
+        if let Some(target) = &spec.attacks {
+            npc_add_enemy(gs, placed[&spec.id], placed[target], true);
+        }
+    }
+
+    for spec in &scenario.items {
+        let item_id = God::create_item(gs, spec.template)
+            .ok_or_else(|| format!("item template {} cannot be created", spec.template))?;
+        let ok = match (&spec.carried_by, spec.at) {
+            (Some(carrier), _) => God::give_character_item(gs, placed[carrier], item_id),
+            (None, Some((x, y))) => God::drop_item_fuzzy(gs, item_id, x, y),
+            (None, None) => false,
+        };
+        if !ok {
+            gs.items[item_id].used = USE_EMPTY;
+            return Err(format!(
+                "item template {} could not be placed",
+                spec.template
+            ));
+        }
+    }
+
+    Ok(placed)
+}
+
+/// Whether character slot `cn` no longer holds a living character.
+fn is_dead(gs: &GameState, cn: usize) -> bool {
+    let character = &gs.characters[cn];
+    character.used != USE_ACTIVE || character.flags & CharacterFlags::Body.bits() != 0
+}
+
+/// Items of `template` lying on the ground or inside a corpse.
+fn dropped_items(gs: &GameState, template: u16) -> HashSet<usize> {
+    (1..gs.items.len())
+        .filter(|&in_idx| {
+            let item = &gs.items[in_idx];
+            if item.used != USE_ACTIVE || item.temp != template {
+                return false;
+            }
+            let carrier = usize::from(item.carried);
+            if carrier == 0 {
+                item.x != 0 || item.y != 0
+            } else {
+                carrier < MAXCHARS
+                    && gs.characters[carrier].flags & CharacterFlags::Body.bits() != 0
+            }
+        })
+        .collect()
+}
+
+/// Runs a set-up scenario and judges every expectation.
+///
+/// # Arguments
+///
+/// * `server` - Server used only to drive ticks; never initialized, so
+///   nothing is saved or sent.
+/// * `gs` - Game state after [`setup`].
+/// * `scenario` - The scenario being run.
+/// * `placed` - Result of [`setup`].
+///
+/// # Returns
+///
+/// * One verdict per expectation, in order; none is left `Pending`.
+pub fn simulate(
+    server: &mut Server,
+    gs: &mut GameState,
+    scenario: &Scenario,
+    placed: &Placed,
+) -> Vec<Verdict> {
+    let baselines: Vec<HashSet<usize>> = scenario
+        .expect
+        .iter()
+        .map(|expectation| match expectation {
+            Expectation::Drops { template, .. } => dropped_items(gs, *template),
+            _ => HashSet::new(),
+        })
+        .collect();
+    let mut verdicts = vec![Verdict::Pending; scenario.expect.len()];
+
+    for tick in 1..=scenario.ticks {
+        server.game_tick(gs);
+
+        for (i, expectation) in scenario.expect.iter().enumerate() {
+            if verdicts[i] != Verdict::Pending {
+                continue;
+            }
+            verdicts[i] = match expectation {
+                Expectation::Dies { who, within } => {
+                    if is_dead(gs, placed[who]) {
+                        Verdict::Passed(tick)
+                    } else if tick >= *within {
+                        Verdict::Failed(format!("still alive after {} ticks", within))
+                    } else {
+                        Verdict::Pending
+                    }
+                }
+                Expectation::Survives { who } => {
+                    if is_dead(gs, placed[who]) {
+                        Verdict::Failed(format!("died at tick {}", tick))
+                    } else if tick == scenario.ticks {
+                        Verdict::Passed(tick)
+                    } else {
+                        Verdict::Pending
+                    }
+                }
+                Expectation::Drops { template, within } => {
+                    if dropped_items(gs, *template)
+                        .difference(&baselines[i])
+                        .next()
+                        .is_some()
+                    {
+                        Verdict::Passed(tick)
+                    } else if tick >= *within {
+                        Verdict::Failed(format!("nothing dropped within {} ticks", within))
+                    } else {
+                        Verdict::Pending
+                    }
+                }
+            };
+        }
+
+        if verdicts.iter().all(|verdict| *verdict != Verdict::Pending) {
+            break;
+        }
+    }
+
+    verdicts
+}
+
+/// Loads the world, runs the scenario file and prints a report.
+///
+/// # Arguments
+///
+/// * `path` - Scenario file.
+///
+/// # Returns
+///
+/// * `Ok(true)` when every expectation passed, `Ok(false)` when any failed,
+///   or an error when the scenario could not be run at all.
+pub fn run_file(path: &str) -> Result<bool, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read scenario {}: {}", path, e))?;
+    let scenario = Scenario::parse(&text)?;
+
+    let mut gs = GameState::initialize()?;
+    // The run is throwaway; never write the modified world back.
+    gs.discard_changes();
+    let placed = setup(&mut gs, &scenario)?;
+    let mut server = Server::new();
+    let verdicts = simulate(&mut server, &mut gs, &scenario, &placed);
+
+    println!("Scenario: {}", scenario.name);
+    let mut all_passed = true;
+    for (expectation, verdict) in scenario.expect.iter().zip(&verdicts) {
+        match verdict {
+            Verdict::Passed(tick) => println!("  PASS {:?} (tick {})", expectation, tick),
+            Verdict::Failed(reason) => {
+                all_passed = false;
+                println!("  FAIL {:?}: {}", expectation, reason);
+            }
+            Verdict::Pending => {
+                all_passed = false;
+                println!("  FAIL {:?}: undecided", expectation);
+            }
+        }
+    }
+    Ok(all_passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{CharSpec, World};
+
+    #[test]
+    fn judges_deaths_and_survivors() {
+        World::new()
+            .with_char(CharSpec::npc("Victim").at(10, 10))
+            .with_char(CharSpec::npc("Bystander").at(20, 20))
+            .run(|gs, w| {
+                let scenario = Scenario {
+                    name: "test".to_owned(),
+                    ticks: 5,
+                    characters: Vec::new(),
+                    items: Vec::new(),
+                    expect: vec![
+                        Expectation::Dies {
+                            who: "victim".to_owned(),
+                            within: 5,
+                        },
+                        Expectation::Survives {
+                            who: "bystander".to_owned(),
+                        },
+                    ],
+                };
+                let placed = Placed::from([
+                    ("victim".to_owned(), w.char(0)),
+                    ("bystander".to_owned(), w.char(1)),
+                ]);
+                gs.characters[w.char(0)].flags |= CharacterFlags::Body.bits();
+
+                let verdicts = simulate(&mut Server::new(), gs, &scenario, &placed);
+                assert_eq!(verdicts, vec![Verdict::Passed(1), Verdict::Passed(5)]);
+            });
+    }
+}
@@ -0,0 +1,277 @@
+//! Firing script hooks from the world and applying what scripts ask for.
+
+use core::constants::{CharacterFlags, MAXCHARS, SERVER_MAPX, SERVER_MAPY, USE_ACTIVE, USE_EMPTY};
+use core::types::FontColor;
+use rhai::Map;
+
+use super::{Hook, HookOutcome, ScriptAction, ScriptStatus, character_map, store};
+use crate::game_state::GameState;
+use crate::god::God;
+
+impl GameState {
+    /// Whether `cn` is an active player character.
+    fn is_script_player(&self, cn: usize) -> bool {
+        cn != 0
+            && cn < MAXCHARS
+            && self.characters[cn].used == USE_ACTIVE
+            && self.characters[cn].flags & CharacterFlags::Player.bits() != 0
+    }
+
+    /// The map scripts get for character `cn`.
+    fn script_character(&self, cn: usize) -> Map {
+        let character = &self.characters[cn];
+        character_map(
+            cn,
+            character.get_name(),
+            usize::from(character.temp),
+            i64::from(character.x),
+            i64::from(character.y),
+        )
+    }
+
+    /// Fires `on_use_item` when player `cn` uses item `item_idx`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if a script handled the use and the item driver must not run.
+    pub(crate) fn script_use_item(&mut self, cn: usize, item_idx: usize) -> bool {
+        if self.scripts.applying || !self.scripts.has_hook(Hook::UseItem) {
+            return false;
+        }
+        if !self.is_script_player(cn) {
+            return false;
+        }
+        let item = &self.items[item_idx];
+        let mut item_map = character_map(
+            item_idx,
+            item.get_name(),
+            usize::from(item.temp),
+            i64::from(item.x),
+            i64::from(item.y),
+        );
+        item_map.insert("carried".into(), (item.carried != 0).into());
+        let player = self.script_character(cn);
+        let outcome = self.scripts.fire(Hook::UseItem, (player, item_map));
+        self.apply_script_outcome(outcome)
+    }
+
+    /// Fires `on_npc_talk` when NPC `npc` hears player `cn` say `text`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if a script answered and the NPC's built-in replies must
+    ///   not run.
+    pub(crate) fn script_npc_talk(&mut self, npc: usize, cn: usize, text: &str) -> bool {
+        if self.scripts.applying || !self.scripts.has_hook(Hook::NpcTalk) {
+            return false;
+        }
+        if !self.is_script_player(cn) {
+            return false;
+        }
+        let player = self.script_character(cn);
+        let npc_map = self.script_character(npc);
+        let outcome = self
+            .scripts
+            .fire(Hook::NpcTalk, (player, npc_map, text.to_owned()));
+        self.apply_script_outcome(outcome)
+    }
+
+    /// Fires `on_enter_tile` after player `cn` was placed on a tile.
+    pub(crate) fn script_enter_tile(&mut self, cn: usize) {
+        if self.scripts.applying || !self.scripts.has_hook(Hook::EnterTile) {
+            return;
+        }
+        if !self.is_script_player(cn) {
+            return;
+        }
+        let player = self.script_character(cn);
+        let (x, y) = (
+            i64::from(self.characters[cn].x),
+            i64::from(self.characters[cn].y),
+        );
+        let outcome = self.scripts.fire(Hook::EnterTile, (player, x, y));
+        self.apply_script_outcome(outcome);
+    }
+
+    /// Fires `on_timer` for every script timer that ran out.
+    ///
+    /// Timers of characters that are no longer online are dropped.
+    pub(crate) fn run_script_timers(&mut self) {
+        for timer in self.scripts.take_due_timers(self.globals.ticker) {
+            if !self.is_script_player(timer.cn) {
+                continue;
+            }
+            let player = self.script_character(timer.cn);
+            let outcome = self.scripts.fire(Hook::Timer, (player, timer.name));
+            self.apply_script_outcome(outcome);
+        }
+    }
+
+    /// Applies the actions of a fired hook.
+    ///
+    /// # Returns
+    ///
+    /// * Whether a script handled the event.
+    fn apply_script_outcome(&mut self, outcome: HookOutcome) -> bool {
+        self.scripts.applying = true;
+        for action in outcome.actions {
+            if let Err(e) = self.apply_script_action(&action) {
+                log::warn!("Script action {:?} failed: {}", action, e);
+            }
+        }
+        self.scripts.applying = false;
+        outcome.handled
+    }
+
+    /// Carries out one script action after checking its target.
+    fn apply_script_action(&mut self, action: &ScriptAction) -> Result<(), String> {
+        match action {
+            ScriptAction::GiveItem { cn, template } => {
+                self.require_script_player(*cn)?;
+                let item_id = God::create_item(self, *template)
+                    .ok_or_else(|| format!("item template {} cannot be created", template))?;
+                if !God::give_character_item(self, *cn, item_id) {
+                    self.items[item_id].used = USE_EMPTY;
+                    self.do_character_log(*cn, FontColor::Red, "Your backpack is full.\n");
+                    return Err("inventory full".to_owned());
+                }
+                let item_name = self.items[item_id].get_name().to_owned();
+                chlog!(*cn, "SCRIPT: received {}", item_name);
+            }
+            ScriptAction::Teleport { cn, x, y } => {
+                self.require_script_player(*cn)?;
+                if *x >= SERVER_MAPX as usize || *y >= SERVER_MAPY as usize {
+                    return Err(format!("{},{} is off the map", x, y));
+                }
+                if !God::transfer_char(self, *cn, *x, *y) {
+                    return Err(format!("could not place near {},{}", x, y));
+                }
+                chlog!(*cn, "SCRIPT: teleported to {},{}", x, y);
+            }
+            ScriptAction::Tell { cn, text } => {
+                self.require_script_player(*cn)?;
+                self.do_character_log(*cn, FontColor::Yellow, &format!("{}\n", text));
+            }
+            ScriptAction::Say { cn, text } => {
+                if *cn == 0 || *cn >= MAXCHARS || self.characters[*cn].used != USE_ACTIVE {
+                    return Err(format!("character {} is not active", cn));
+                }
+                if self.is_script_player(*cn) {
+                    return Err("say() only makes NPCs speak".to_owned());
+                }
+                self.do_sayx(*cn, text);
+            }
+            ScriptAction::FlagChanged { cn, name, set } => {
+                store::save_flag(&self.storage, *cn, name, *set);
+            }
+            ScriptAction::StartTimer { cn, name, secs } => {
+                self.require_script_player(*cn)?;
+                let due_tick = self.globals.ticker + (*secs as i32) * core::constants::TICKS;
+                self.scripts.start_timer(*cn, name, due_tick);
+            }
+        }
+        Ok(())
+    }
+
+    fn require_script_player(&self, cn: usize) -> Result<(), String> {
+        if self.is_script_player(cn) {
+            Ok(())
+        } else {
+            Err(format!("character {} is not an online player", cn))
+        }
+    }
+
+    /// `#scripts [reload]`: lists the loaded scripts or reloads them.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - God issuing the command.
+    /// * `sub` - `reload`, or empty to list.
+    pub(crate) fn do_scripts(&mut self, cn: usize, sub: &str) {
+        if sub.eq_ignore_ascii_case("reload") {
+            match self.scripts.reload() {
+                Ok(count) => {
+                    log::info!("Scripts reloaded by {}: {} script(s)", cn, count);
+                    self.do_character_log(
+                        cn,
+                        FontColor::Yellow,
+                        &format!("Reloaded {} script(s).\n", count),
+                    );
+                }
+                Err(e) => {
+                    self.do_character_log(cn, FontColor::Red, &format!("{}\n", e));
+                    return;
+                }
+            }
+        } else if !sub.is_empty() {
+            self.do_character_log(cn, FontColor::Red, "Usage: #scripts [reload]\n");
+            return;
+        }
+
+        let statuses = self.scripts.statuses();
+        if statuses.is_empty() {
+            self.do_character_log(cn, FontColor::Yellow, "No scripts are loaded.\n");
+            return;
+        }
+        for (name, status) in statuses {
+            let line = match status {
+                ScriptStatus::Enabled => format!("{}: running\n", name),
+                ScriptStatus::Failed(e) => format!("{}: FAILED: {}\n", name, e),
+            };
+            self.do_character_log(cn, FontColor::Yellow, &line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn npc_talk_script_answers_for_the_npc() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            let npc = cn + 1;
+            gs.characters[npc].used = USE_ACTIVE;
+            gs.characters[npc].set_name("Gwendylon");
+            gs.scripts.load_source(
+                "greeting",
+                r#"
+                fn on_npc_talk(player, npc, text) {
+                    if text == "quest" {
+                        set_flag(player.id, "asked");
+                        say(npc.id, "Not yet.");
+                        say(player.id, "Players cannot be made to speak.");
+                        return true;
+                    }
+                    false
+                }
+                "#,
+            );
+
+            assert!(!gs.script_npc_talk(npc, cn, "hello"));
+            assert!(gs.script_npc_talk(npc, cn, "quest"));
+            assert!(gs.scripts.has_flag(cn, "asked"));
+            assert!(!gs.scripts.applying);
+            // NPCs do not trigger player hooks.
+            assert!(!gs.script_npc_talk(cn, npc, "quest"));
+        });
+    }
+
+    #[test]
+    fn timers_fire_only_for_online_players() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.scripts.load_source(
+                "timer",
+                r#"fn on_timer(player, name) { set_flag(player.id, name); }"#,
+            );
+            gs.scripts.start_timer(cn, "done", gs.globals.ticker);
+            gs.scripts.start_timer(cn + 1, "ghost", gs.globals.ticker);
+            gs.run_script_timers();
+            assert!(gs.scripts.has_flag(cn, "done"));
+            assert!(!gs.scripts.has_flag(cn + 1, "ghost"));
+        });
+    }
+}
@@ -0,0 +1,683 @@
+//! Server-side quest and dialogue scripts.
+//!
+//! Scripts are [rhai](https://rhai.rs) files in the directory named by
+//! [`SCRIPT_DIR_ENV`], one `<name>.rhai` per script, loaded at startup and
+//! again with `#scripts reload`. They let content be added without a
+//! recompile; the ported driver code keeps running for everything a script
+//! does not handle.
+//!
+//! A script may define any of these hooks. `player`, `npc` and `item` are
+//! read-only maps (`id`, `name`, `x`, `y`; items add `template`, npcs add
+//! `template`):
+//!
+//! * `on_use_item(player, item)` - a player uses an item; return `true` to
+//!   skip the item's own driver.
+//! * `on_npc_talk(player, npc, text)` - an NPC hears a player; return `true`
+//!   to skip the NPC's built-in answers.
+//! * `on_enter_tile(player, x, y)` - a player stepped onto a tile.
+//! * `on_timer(player, name)` - a timer started with `start_timer` ran out.
+//!
+//! Scripts run sandboxed like client addons: no file, network or module
+//! access and an operation budget per call. All they can do to the world is
+//! through this API, whose changes are queued and applied by [`commands`]
+//! after the hook returns:
+//!
+//! * `give_item(id, template)`, `teleport(id, x, y)`, `tell(id, text)` -
+//!   act on a player.
+//! * `say(id, text)` - make an NPC speak.
+//! * `has_flag(id, name)`, `set_flag(id, name)`, `clear_flag(id, name)` -
+//!   per-character quest flags, persisted by [`store`].
+//! * `start_timer(id, name, seconds)` - call `on_timer` later.
+//!
+//! A script that errors is switched off until the next reload.
+
+pub mod commands;
+pub mod store;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use rhai::{AST, CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope};
+
+/// Environment variable naming the script directory; no scripts are loaded
+/// while it is unset.
+pub const SCRIPT_DIR_ENV: &str = "MAG_SCRIPT_DIR";
+
+/// Extension of script files.
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Largest script accepted, in bytes.
+const MAX_SCRIPT_LEN: u64 = 256 * 1024;
+
+/// Operation budget for a single hook call.
+const MAX_OPERATIONS: u64 = 50_000;
+
+/// Longest flag or timer name, in bytes.
+pub const MAX_SCRIPT_NAME_LEN: usize = 40;
+
+/// Most flags one character may carry.
+pub const MAX_FLAGS_PER_CHARACTER: usize = 256;
+
+/// Longest timer, in seconds (one day).
+pub const MAX_TIMER_SECS: i64 = 24 * 60 * 60;
+
+/// Events scripts can react to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Hook {
+    UseItem,
+    NpcTalk,
+    EnterTile,
+    Timer,
+}
+
+impl Hook {
+    /// Name of the script function handling the event.
+    pub fn function_name(self) -> &'static str {
+        match self {
+            Hook::UseItem => "on_use_item",
+            Hook::NpcTalk => "on_npc_talk",
+            Hook::EnterTile => "on_enter_tile",
+            Hook::Timer => "on_timer",
+        }
+    }
+
+    const ALL: [Hook; 4] = [Hook::UseItem, Hook::NpcTalk, Hook::EnterTile, Hook::Timer];
+}
+
+/// A change to the world requested by a script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScriptAction {
+    GiveItem {
+        cn: usize,
+        template: usize,
+    },
+    Teleport {
+        cn: usize,
+        x: usize,
+        y: usize,
+    },
+    Tell {
+        cn: usize,
+        text: String,
+    },
+    Say {
+        cn: usize,
+        text: String,
+    },
+    /// A flag was set or cleared; only persistence is left to do.
+    FlagChanged {
+        cn: usize,
+        name: String,
+        set: bool,
+    },
+    StartTimer {
+        cn: usize,
+        name: String,
+        secs: i64,
+    },
+}
+
+/// A timer waiting to fire `on_timer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptTimer {
+    pub cn: usize,
+    pub name: String,
+    /// Tick at which it fires.
+    pub due_tick: i32,
+}
+
+/// Load state of a script, as listed by `#scripts`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScriptStatus {
+    Enabled,
+    /// Failed to load or errored while running.
+    Failed(String),
+}
+
+/// One loaded script.
+struct Script {
+    name: String,
+    ast: Option<AST>,
+    hooks: HashSet<Hook>,
+    status: ScriptStatus,
+}
+
+/// State the registered API functions share with the host.
+#[derive(Default)]
+struct Shared {
+    flags: HashMap<usize, BTreeSet<String>>,
+    actions: Vec<ScriptAction>,
+}
+
+/// Result of firing a hook.
+#[derive(Debug, Default)]
+pub struct HookOutcome {
+    /// A script returned `true`, so the built-in behaviour is skipped.
+    pub handled: bool,
+    /// World changes to apply, in the order the scripts asked for them.
+    pub actions: Vec<ScriptAction>,
+}
+
+/// Runs the server's scripts.
+pub struct ScriptHost {
+    engine: Engine,
+    scripts: Vec<Script>,
+    shared: Arc<Mutex<Shared>>,
+    timers: Vec<ScriptTimer>,
+    /// Directory the scripts came from, for reloading.
+    dir: Option<PathBuf>,
+    /// Set while queued actions are applied, so that e.g. a teleport does
+    /// not fire `on_enter_tile` from inside another hook.
+    pub(crate) applying: bool,
+}
+
+impl std::fmt::Debug for ScriptHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptHost")
+            .field("scripts", &self.statuses())
+            .field("timers", &self.timers.len())
+            .finish()
+    }
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptHost {
+    /// Creates a host with no scripts and a sandboxed engine.
+    pub fn new() -> Self {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let mut engine = Engine::new();
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(4096);
+        engine.set_max_array_size(1024);
+        engine.set_max_map_size(1024);
+        engine.on_print(|text| log::info!("script: {}", text));
+        register_api(&mut engine, &shared);
+        Self {
+            engine,
+            scripts: Vec::new(),
+            shared,
+            timers: Vec::new(),
+            dir: None,
+            applying: false,
+        }
+    }
+
+    /// Replaces the loaded scripts with the `.rhai` files in `dir`.
+    ///
+    /// Flags and pending timers are kept.
+    ///
+    /// # Returns
+    ///
+    /// * The number of scripts loaded, or an error if `dir` cannot be read.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize, String> {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {e}", dir.display()))?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case(SCRIPT_EXTENSION))
+            })
+            .collect();
+        files.sort();
+
+        self.scripts.clear();
+        self.dir = Some(dir.to_owned());
+        for file in files {
+            let name = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match read_script(&file) {
+                Ok(source) => self.load_source(&name, &source),
+                Err(e) => {
+                    log::error!("Script '{}' not loaded: {}", name, e);
+                    self.scripts.push(Script {
+                        name,
+                        ast: None,
+                        hooks: HashSet::new(),
+                        status: ScriptStatus::Failed(e),
+                    });
+                }
+            }
+        }
+        Ok(self.scripts.len())
+    }
+
+    /// Reloads the scripts from the directory they were loaded from.
+    ///
+    /// # Returns
+    ///
+    /// * The number of scripts loaded, or why they could not be.
+    pub fn reload(&mut self) -> Result<usize, String> {
+        let dir = self
+            .dir
+            .clone()
+            .ok_or_else(|| format!("{} is not set", SCRIPT_DIR_ENV))?;
+        self.load_dir(&dir)
+    }
+
+    /// Compiles one script.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Script name, shown by `#scripts` and in logs.
+    /// * `source` - Script source.
+    pub fn load_source(&mut self, name: &str, source: &str) {
+        let script = match self.engine.compile(source) {
+            Ok(ast) => {
+                let hooks = Hook::ALL
+                    .into_iter()
+                    .filter(|hook| ast.iter_functions().any(|f| f.name == hook.function_name()))
+                    .collect();
+                Script {
+                    name: name.to_owned(),
+                    ast: Some(ast),
+                    hooks,
+                    status: ScriptStatus::Enabled,
+                }
+            }
+            Err(e) => {
+                log::error!("Script '{}' failed to compile: {}", name, e);
+                Script {
+                    name: name.to_owned(),
+                    ast: None,
+                    hooks: HashSet::new(),
+                    status: ScriptStatus::Failed(e.to_string()),
+                }
+            }
+        };
+        self.scripts.push(script);
+    }
+
+    /// Lists the loaded scripts and their state.
+    pub fn statuses(&self) -> Vec<(String, ScriptStatus)> {
+        self.scripts
+            .iter()
+            .map(|script| (script.name.clone(), script.status.clone()))
+            .collect()
+    }
+
+    /// Whether any enabled script handles `hook`.
+    pub fn has_hook(&self, hook: Hook) -> bool {
+        self.scripts
+            .iter()
+            .any(|script| script.status == ScriptStatus::Enabled && script.hooks.contains(&hook))
+    }
+
+    /// Calls `hook` in every enabled script defining it, in name order,
+    /// until one returns `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Event that happened.
+    /// * `args` - Hook arguments; cloned for each script.
+    ///
+    /// # Returns
+    ///
+    /// * Whether a script handled the event, and the actions to apply.
+    pub fn fire(&mut self, hook: Hook, args: impl FuncArgs + Clone) -> HookOutcome {
+        let mut outcome = HookOutcome::default();
+        for script in &mut self.scripts {
+            if script.status != ScriptStatus::Enabled || !script.hooks.contains(&hook) {
+                continue;
+            }
+            let Some(ast) = script.ast.as_ref() else {
+                continue;
+            };
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                ast,
+                hook.function_name(),
+                args.clone(),
+            );
+            match result {
+                Ok(value) => {
+                    if value.as_bool().unwrap_or(false) {
+                        outcome.handled = true;
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::error!(
+                        "Script '{}' disabled after error in {}(): {}",
+                        script.name,
+                        hook.function_name(),
+                        e
+                    );
+                    script.status = ScriptStatus::Failed(e.to_string());
+                }
+            }
+        }
+        if let Ok(mut shared) = self.shared.lock() {
+            outcome.actions = std::mem::take(&mut shared.actions);
+        }
+        outcome
+    }
+
+    /// Whether character `cn` carries flag `name`.
+    pub fn has_flag(&self, cn: usize, name: &str) -> bool {
+        self.shared
+            .lock()
+            .is_ok_and(|shared| shared.flags.get(&cn).is_some_and(|set| set.contains(name)))
+    }
+
+    /// Replaces every character's flags, e.g. with the ones loaded by
+    /// [`store::load_script_flags`].
+    pub fn set_flags(&mut self, flags: HashMap<usize, BTreeSet<String>>) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.flags = flags;
+        }
+    }
+
+    /// Schedules `on_timer` for `cn`, replacing a timer of the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character the timer belongs to.
+    /// * `name` - Timer name passed to `on_timer`.
+    /// * `due_tick` - Tick at which it fires.
+    pub fn start_timer(&mut self, cn: usize, name: &str, due_tick: i32) {
+        self.timers
+            .retain(|timer| !(timer.cn == cn && timer.name == name));
+        self.timers.push(ScriptTimer {
+            cn,
+            name: name.to_owned(),
+            due_tick,
+        });
+    }
+
+    /// Removes and returns the timers due at or before `tick`.
+    pub fn take_due_timers(&mut self, tick: i32) -> Vec<ScriptTimer> {
+        let (due, pending) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition(|timer| timer.due_tick <= tick);
+        self.timers = pending;
+        due
+    }
+}
+
+/// Registers the script API on `engine`.
+fn register_api(engine: &mut Engine, shared: &Arc<Mutex<Shared>>) {
+    let push = |shared: &Arc<Mutex<Shared>>, action: ScriptAction| {
+        if let Ok(mut shared) = shared.lock() {
+            shared.actions.push(action);
+        }
+    };
+
+    let s = shared.clone();
+    engine.register_fn("give_item", move |cn: i64, template: i64| {
+        if let (Some(cn), Some(template)) = (slot(cn), slot(template)) {
+            push(&s, ScriptAction::GiveItem { cn, template });
+        }
+    });
+    let s = shared.clone();
+    engine.register_fn("teleport", move |cn: i64, x: i64, y: i64| {
+        if let (Some(cn), Some(x), Some(y)) = (slot(cn), slot(x), slot(y)) {
+            push(&s, ScriptAction::Teleport { cn, x, y });
+        }
+    });
+    let s = shared.clone();
+    engine.register_fn("tell", move |cn: i64, text: &str| {
+        if let Some(cn) = slot(cn) {
+            push(
+                &s,
+                ScriptAction::Tell {
+                    cn,
+                    text: text.to_owned(),
+                },
+            );
+        }
+    });
+    let s = shared.clone();
+    engine.register_fn("say", move |cn: i64, text: &str| {
+        if let Some(cn) = slot(cn) {
+            push(
+                &s,
+                ScriptAction::Say {
+                    cn,
+                    text: text.to_owned(),
+                },
+            );
+        }
+    });
+    let s = shared.clone();
+    engine.register_fn("has_flag", move |cn: i64, name: &str| -> bool {
+        let Some(cn) = slot(cn) else {
+            return false;
+        };
+        s.lock()
+            .is_ok_and(|shared| shared.flags.get(&cn).is_some_and(|set| set.contains(name)))
+    });
+    let s = shared.clone();
+    engine.register_fn(
+        "set_flag",
+        move |cn: i64, name: &str| -> Result<(), Box<rhai::EvalAltResult>> {
+            let (Some(cn), Some(name)) = (slot(cn), valid_name(name)) else {
+                return Err(format!("invalid flag '{}'", name).into());
+            };
+            let Ok(mut shared) = s.lock() else {
+                return Ok(());
+            };
+            let set = shared.flags.entry(cn).or_default();
+            if set.contains(&name) {
+                return Ok(());
+            }
+            if set.len() >= MAX_FLAGS_PER_CHARACTER {
+                return Err(format!("character {} has too many flags", cn).into());
+            }
+            set.insert(name.clone());
+            shared.actions.push(ScriptAction::FlagChanged {
+                cn,
+                name,
+                set: true,
+            });
+            Ok(())
+        },
+    );
+    let s = shared.clone();
+    engine.register_fn("clear_flag", move |cn: i64, name: &str| {
+        let Some(cn) = slot(cn) else {
+            return;
+        };
+        let Ok(mut shared) = s.lock() else {
+            return;
+        };
+        if shared
+            .flags
+            .get_mut(&cn)
+            .is_some_and(|set| set.remove(name))
+        {
+            shared.actions.push(ScriptAction::FlagChanged {
+                cn,
+                name: name.to_owned(),
+                set: false,
+            });
+        }
+    });
+    let s = shared.clone();
+    engine.register_fn(
+        "start_timer",
+        move |cn: i64, name: &str, secs: i64| -> Result<(), Box<rhai::EvalAltResult>> {
+            let (Some(cn), Some(name)) = (slot(cn), valid_name(name)) else {
+                return Err(format!("invalid timer '{}'", name).into());
+            };
+            if !(1..=MAX_TIMER_SECS).contains(&secs) {
+                return Err(format!("timer must run 1 to {} seconds", MAX_TIMER_SECS).into());
+            }
+            push(&s, ScriptAction::StartTimer { cn, name, secs });
+            Ok(())
+        },
+    );
+}
+
+/// Converts a script integer to a slot or coordinate.
+fn slot(value: i64) -> Option<usize> {
+    usize::try_from(value).ok()
+}
+
+/// Checks a flag or timer name: short, printable and without spaces.
+fn valid_name(name: &str) -> Option<String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_SCRIPT_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+    valid.then(|| name.to_owned())
+}
+
+/// Reads a script file, refusing oversized files.
+fn read_script(path: &Path) -> Result<String, String> {
+    let len = fs::metadata(path)
+        .map_err(|e| format!("Failed to stat {}: {e}", path.display()))?
+        .len();
+    if len > MAX_SCRIPT_LEN {
+        return Err(format!(
+            "{} is larger than {MAX_SCRIPT_LEN} bytes",
+            path.display()
+        ));
+    }
+    fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))
+}
+
+/// Builds the script host.
+///
+/// # Arguments
+///
+/// * `setting` - Value of [`SCRIPT_DIR_ENV`], if set.
+/// * `host` - Host to load into; its flags are kept.
+///
+/// # Returns
+///
+/// * `Ok(())` when unset or loaded, or an error if the directory cannot be
+///   read. Scripts that fail to compile are logged and listed as failed.
+pub fn load_scripts(setting: Option<&str>, host: &mut ScriptHost) -> Result<(), String> {
+    match setting.map(str::trim) {
+        None | Some("") => Ok(()),
+        Some(dir) => {
+            let loaded = host.load_dir(Path::new(dir))?;
+            log::info!("Loaded {} script(s) from {}.", loaded, dir);
+            Ok(())
+        }
+    }
+}
+
+/// Map handed to scripts for a character.
+///
+/// # Arguments
+///
+/// * `id` - Character slot.
+/// * `name` - Character name.
+/// * `template` - Character template; `0` for players.
+/// * `x`, `y` - Position.
+pub fn character_map(id: usize, name: &str, template: usize, x: i64, y: i64) -> Map {
+    let mut map = Map::new();
+    map.insert("id".into(), (id as i64).into());
+    map.insert("name".into(), name.to_owned().into());
+    map.insert("template".into(), (template as i64).into());
+    map.insert("x".into(), x.into());
+    map.insert("y".into(), y.into());
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUEST: &str = r#"
+        fn on_npc_talk(player, npc, text) {
+            if text.contains("help") && !has_flag(player.id, "rat_quest") {
+                set_flag(player.id, "rat_quest");
+                say(npc.id, `Kill the rats, ${player.name}.`);
+                start_timer(player.id, "rat_reminder", 60);
+                return true;
+            }
+            false
+        }
+        fn on_timer(player, name) { tell(player.id, name); }
+    "#;
+
+    #[test]
+    fn hooks_queue_actions_and_keep_flags() {
+        let mut host = ScriptHost::new();
+        host.load_source("rats", QUEST);
+        assert!(host.has_hook(Hook::NpcTalk));
+        assert!(!host.has_hook(Hook::EnterTile));
+
+        let player = character_map(7, "Ishtar", 0, 10, 10);
+        let npc = character_map(40, "Gwendylon", 12, 11, 10);
+        let outcome = host.fire(
+            Hook::NpcTalk,
+            (player.clone(), npc.clone(), "help me".to_owned()),
+        );
+        assert!(outcome.handled);
+        assert_eq!(
+            outcome.actions,
+            vec![
+                ScriptAction::FlagChanged {
+                    cn: 7,
+                    name: "rat_quest".to_owned(),
+                    set: true,
+                },
+                ScriptAction::Say {
+                    cn: 40,
+                    text: "Kill the rats, Ishtar.".to_owned(),
+                },
+                ScriptAction::StartTimer {
+                    cn: 7,
+                    name: "rat_reminder".to_owned(),
+                    secs: 60,
+                },
+            ]
+        );
+        assert!(host.has_flag(7, "rat_quest"));
+
+        let again = host.fire(Hook::NpcTalk, (player, npc, "help me".to_owned()));
+        assert!(!again.handled, "the flag stops a second quest start");
+        assert!(again.actions.is_empty());
+
+        host.start_timer(7, "rat_reminder", 100);
+        host.start_timer(7, "rat_reminder", 200);
+        assert!(host.take_due_timers(150).is_empty());
+        assert_eq!(host.take_due_timers(200).len(), 1);
+    }
+
+    #[test]
+    fn runaway_and_broken_scripts_are_switched_off() {
+        let mut host = ScriptHost::new();
+        host.load_source("spin", "fn on_enter_tile(player, x, y) { loop {} }");
+        host.load_source("typo", "fn on_timer( {");
+        host.load_source(
+            "badflag",
+            "fn on_use_item(player, item) { set_flag(player.id, \"has space\"); }",
+        );
+
+        let player = character_map(7, "Ishtar", 0, 10, 10);
+        host.fire(Hook::EnterTile, (player.clone(), 10_i64, 10_i64));
+        host.fire(Hook::UseItem, (player, Map::new()));
+
+        let statuses = host.statuses();
+        assert!(
+            statuses
+                .iter()
+                .all(|(_, status)| matches!(status, ScriptStatus::Failed(_)))
+        );
+        assert!(!host.has_hook(Hook::EnterTile));
+    }
+}
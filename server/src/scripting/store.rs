@@ -0,0 +1,89 @@
+//! Persistence for script flags.
+//!
+//! Every flag is the record `game:script_flag:{cn}:{name}` with the single
+//! field `set`, `1` while the flag is set, stored through
+//! [`StorageBackend::save_record`]. Clearing a flag writes `0`; such
+//! records are removed the next time the server starts.
+
+use std::collections::{BTreeSet, HashMap};
+
+use server::storage::StorageBackend;
+
+/// Prefix of the per-flag record keys.
+pub const SCRIPT_FLAG_KEY_PREFIX: &str = "game:script_flag:";
+
+/// Saves one flag change.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+/// * `cn` - Character slot.
+/// * `name` - Flag name.
+/// * `set` - Whether the flag is now set.
+pub fn save_flag(storage: &StorageBackend, cn: usize, name: &str, set: bool) {
+    storage.save_record(
+        format!("{}{}:{}", SCRIPT_FLAG_KEY_PREFIX, cn, name),
+        vec![("set", if set { "1" } else { "0" }.to_owned())],
+    );
+}
+
+/// Loads every character's flags from the storage backend.
+///
+/// # Arguments
+///
+/// * `storage` - Active storage backend.
+///
+/// # Returns
+///
+/// * Flags by character slot. An error means the backend could not be
+///   read.
+pub fn load_script_flags(
+    storage: &StorageBackend,
+) -> Result<HashMap<usize, BTreeSet<String>>, String> {
+    let mut flags: HashMap<usize, BTreeSet<String>> = HashMap::new();
+    let mut cleared = Vec::new();
+    for (key, fields) in storage.load_records(SCRIPT_FLAG_KEY_PREFIX)? {
+        let Some((cn, name)) = parse_key(&key) else {
+            continue;
+        };
+        if fields.get("set").map(String::as_str) == Some("1") {
+            flags.entry(cn).or_default().insert(name.to_owned());
+        } else {
+            cleared.push(key);
+        }
+    }
+    if let Err(e) = storage.delete_records(&cleared) {
+        log::warn!("Failed to remove cleared script flags: {}", e);
+    }
+
+    log::info!("Loaded script flags for {} character(s)", flags.len());
+    Ok(flags)
+}
+
+/// Splits a flag key into character slot and flag name.
+fn parse_key(key: &str) -> Option<(usize, &str)> {
+    let (cn, name) = key.strip_prefix(SCRIPT_FLAG_KEY_PREFIX)?.split_once(':')?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((cn.parse().ok()?, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_split_into_character_and_flag() {
+        assert_eq!(
+            parse_key("game:script_flag:12:rat_quest"),
+            Some((12, "rat_quest"))
+        );
+        assert_eq!(
+            parse_key("game:script_flag:12:quest:stage2"),
+            Some((12, "quest:stage2"))
+        );
+        assert_eq!(parse_key("game:script_flag:12:"), None);
+        assert_eq!(parse_key("game:script_flag:abc:rat_quest"), None);
+    }
+}
@@ -1,9 +1,12 @@
-use chrono::Timelike;
 use core::ban_action_store::BanActionKind;
 use core::ban_store::BanTarget;
 use core::constants::{CharacterFlags, TILEX, TILEY};
+use core::discord_store::{DISCORD_ROLE_INTERVAL_SECS, DiscordGuild, DiscordRoleEvent};
+use core::economy_store::{ECONOMY_INTERVAL_SECS, ListedItem, MerchantListing};
 use core::logout_reasons::LogoutReason;
+use core::online_roster_store::{ONLINE_ROSTER_INTERVAL_SECS, OnlinePlayer, OnlineRoster};
 use core::stat_buffer::StatisticsBuffer;
+use core::stream_overlay_store::{OVERLAY_INTERVAL_SECS, OverlayStats};
 use core::types::Map;
 use std::io::ErrorKind;
 use std::io::{Read, Write};
@@ -14,10 +17,13 @@ use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use crate::effect::EffectManager;
 use crate::game_state::GameState;
 use crate::god::God;
+use crate::tick_profile::TickProfiler;
 use crate::tls::{self, GameStream};
+use crate::transport::{self, Transport, WsConfig};
 use crate::types::cmap::CMap;
 use crate::types::server_player::ServerPlayer;
-use crate::{driver, player, populate};
+use crate::zones::ZoneScheduler;
+use crate::{driver, player, populate, tick_phases};
 use flate2::Compression;
 use flate2::write::ZlibEncoder;
 use server::keydb::background_saver::{self, BackgroundSaver, SaveJob};
@@ -40,6 +46,14 @@ enum CharacterTickState {
 }
 
 fn kick_matching_ban_target(gs: &mut GameState, target: &BanTarget) -> usize {
+    let text = match server::keydb::ban::active_ban_for_target(target) {
+        Ok(Some(record)) => core::disconnect::ban_text(
+            &record.reason,
+            record.expires_at,
+            server::keydb::ban::now_secs(),
+        ),
+        _ => "You are banned from this server.".to_owned(),
+    };
     let mut players_to_kick = Vec::new();
     for player_id in 1..core::constants::MAXPLAYER {
         if gs.players[player_id].sock.is_none() {
@@ -62,11 +76,23 @@ fn kick_matching_ban_target(gs: &mut GameState, target: &BanTarget) -> usize {
     let kicked = players_to_kick.len();
     for player_id in players_to_kick {
         let character_id = gs.players[player_id].usnr;
-        player::connection::plr_logout(gs, character_id, player_id, LogoutReason::Kicked);
+        player::connection::plr_logout_with_text(
+            gs,
+            character_id,
+            player_id,
+            LogoutReason::Banned,
+            &text,
+        );
     }
     kicked
 }
 
+/// Number of slots whose value differs between two equally sized template
+/// arrays.
+fn count_changed<T: PartialEq>(old: &[T], new: &[T]) -> usize {
+    old.iter().zip(new).filter(|(a, b)| a != b).count()
+}
+
 /// The server runtime object which manages networking and tick timing.
 ///
 /// Holds the listener socket and timing state used by the main loop. Create
@@ -78,6 +104,12 @@ pub struct Server {
     /// TLS configuration (loaded from `SERVER_TLS_CERT` / `SERVER_TLS_KEY`).
     tls_config: Option<Arc<rustls::ServerConfig>>,
 
+    /// WebSocket listener, bound when `MAG_WS_PORT` is set.
+    ws_sock: Option<TcpListener>,
+
+    /// Settings of the WebSocket listener.
+    ws_config: Option<WsConfig>,
+
     /// Tick rate performance statistics buffer.
     tick_perf_stats: StatisticsBuffer<f32>,
 
@@ -87,9 +119,48 @@ pub struct Server {
     /// Measurement interval in ticks for performance statistics.
     measurement_interval: u32,
 
-    /// Background saver handle (only present when using KeyDB backend).
+    /// Per-character and per-zone tick cost attribution.
+    tick_profiler: TickProfiler,
+
+    /// Background publisher that exposes tick profile reports to the admin
+    /// API.
+    tick_profile_publisher: Option<server::keydb::tick_profile::TickProfilePublisher>,
+
+    /// Background publisher that exposes the online player roster to the
+    /// admin API.
+    online_roster_publisher: Option<server::keydb::online_roster::OnlineRosterPublisher>,
+
+    /// Background publisher for opted-in stream overlay snapshots.
+    stream_overlay_publisher: Option<server::keydb::stream_overlay::StreamOverlayPublisher>,
+
+    /// Background publisher for Discord role sync events.
+    discord_role_publisher: Option<server::keydb::discord_roles::DiscordRolePublisher>,
+
+    /// Background publisher for the public merchant economy report.
+    economy_publisher: Option<server::keydb::economy::EconomyPublisher>,
+
+    /// Next character woken by `wakeup_character`. Kept here rather than
+    /// in a static so every server (and every replay) starts at slot 1.
+    wakeup_cursor: usize,
+
+    /// Per-tick world hashes written in deterministic mode
+    /// (`--state-hashes`).
+    state_hashes: Option<crate::deterministic::state_hash::StateHashLog>,
+
+    /// Periodic memory usage logging (`profiling` feature only).
+    #[cfg(feature = "profiling")]
+    memory_reporter: crate::mem_profile::MemoryReporter,
+
+    /// Background saver handle.
     background_saver: Option<BackgroundSaver>,
 
+    /// Flusher thread draining the KeyDB write-behind queue.
+    write_behind_flusher: Option<server::keydb::write_behind::WriteBehindFlusher>,
+
+    /// Flusher thread writing queued records (mail, consignments, ...) to
+    /// SQLite; only present with the SQLite backend.
+    record_flusher: Option<server::keydb::write_behind::WriteBehindFlusher>,
+
     /// Background watcher that surfaces admin-issued template reload
     /// requests to the tick loop.
     template_reload_watcher: Option<server::keydb::template_reload::TemplateReloadWatcher>,
@@ -118,9 +189,30 @@ pub struct Server {
     /// tick loop.
     ban_action_watcher: Option<server::keydb::ban_action::BanActionWatcher>,
 
+    /// Operator console listener; commands it receives run in the tick loop.
+    console: Option<crate::console::ConsoleServer>,
+
     /// Counter that drives the rotating save schedule (increments each tick
     /// when using KeyDB backend).
     save_tick_counter: u32,
+
+    /// Ticks since the last player checkpoint.
+    checkpoint_tick_counter: u32,
+
+    /// Throttles NPC logic in zones far away from players.
+    zones: ZoneScheduler,
+
+    /// Address the game and WebSocket listeners bind to.
+    bind_address: String,
+
+    /// TCP port of the game listener.
+    port: u16,
+
+    /// Real time between game ticks.
+    tick_interval: Duration,
+
+    /// Most players connected at once.
+    max_players: usize,
 }
 
 impl Server {
@@ -135,21 +227,55 @@ impl Server {
             sock: None,
             last_tick_time: None,
             tls_config: None,
+            ws_sock: None,
+            ws_config: None,
             tick_perf_stats: StatisticsBuffer::new(100),
             net_io_perf_stats: StatisticsBuffer::new(100),
             measurement_interval: 20,
+            tick_profiler: TickProfiler::new(),
+            tick_profile_publisher: None,
+            online_roster_publisher: None,
+            stream_overlay_publisher: None,
+            discord_role_publisher: None,
+            economy_publisher: None,
+            wakeup_cursor: 1,
+            state_hashes: None,
+            #[cfg(feature = "profiling")]
+            memory_reporter: crate::mem_profile::MemoryReporter::new(),
             background_saver: None,
+            write_behind_flusher: None,
+            record_flusher: None,
             template_reload_watcher: None,
             text_reload_watcher: None,
             map_patch_watcher: None,
             item_patch_watcher: None,
             character_patch_watcher: None,
             world_action_watcher: None,
+            console: None,
             ban_action_watcher: None,
             save_tick_counter: 0,
+            checkpoint_tick_counter: 0,
+            zones: ZoneScheduler::from_env(),
+            bind_address: "0.0.0.0".to_owned(),
+            port: 5555,
+            tick_interval: Duration::from_micros(core::constants::TICK as u64),
+            max_players: core::constants::MAXPLAYER - 1,
         }
     }
 
+    /// Applies the listener, pacing and capacity settings of `config`.
+    /// Call before `initialize()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Validated server configuration.
+    pub fn configure(&mut self, config: &crate::config::ServerConfig) {
+        self.bind_address = config.network.bind_address.clone();
+        self.port = config.network.port;
+        self.tick_interval = Duration::from_micros(1_000_000 / u64::from(config.game.tick_rate));
+        self.max_players = config.game.max_players;
+    }
+
     /// Check whether an item carried by a player is a 'labyrinth' item and
     /// remove it when the player is inside designated lab coordinates.
     ///
@@ -184,10 +310,21 @@ impl Server {
         );
     }
 
+    /// Write a hash of the world after every tick to `log`
+    /// (`--state-hashes`).
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - Open state hash file.
+    pub fn set_state_hash_log(&mut self, log: crate::deterministic::state_hash::StateHashLog) {
+        self.state_hashes = Some(log);
+    }
+
     /// Initialize the server: bind listening socket and initialize subsystems.
     ///
     /// Actions performed:
-    /// - Bind to 0.0.0.0:5555 and set the socket non-blocking
+    /// - Bind to the configured address and port (0.0.0.0:5555 by default)
+    ///   and set the socket non-blocking
     /// - Initialize the `PLAYERS` array, `State`, `NetworkManager` and other
     ///   subsystems
     /// - Mark repository data as dirty and perform startup cleanup (force
@@ -205,7 +342,7 @@ impl Server {
     /// * `Err(String)` if socket bind or subsystem initialization fails.
     pub fn initialize(&mut self, gs: &mut GameState) -> Result<(), String> {
         // Create and configure TCP socket (matching server.cpp socket setup)
-        let listener = TcpListener::bind("0.0.0.0:5555")
+        let listener = TcpListener::bind((self.bind_address.as_str(), self.port))
             .map_err(|e| format!("Failed to bind socket: {}", e))?;
 
         listener
@@ -213,14 +350,37 @@ impl Server {
             .map_err(|e| format!("Failed to set non-blocking mode: {}", e))?;
 
         self.sock = Some(listener);
-        log::info!("Socket bound to port 5555");
+        log::info!("Socket bound to {}:{}", self.bind_address, self.port);
 
         // Load TLS configuration (mandatory).
         let tls_config =
             tls::load_tls_config().map_err(|e| format!("TLS initialization failed: {e}"))?;
-        log::info!("TLS enabled — accepting encrypted connections on port 5555");
+        log::info!(
+            "TLS enabled — accepting encrypted connections on port {}",
+            self.port
+        );
         self.tls_config = Some(tls_config);
 
+        // Optional WebSocket listener for clients behind restrictive firewalls.
+        let ws_port = std::env::var(transport::WS_PORT_ENV).ok();
+        let ws_plain = std::env::var(transport::WS_PLAIN_ENV).ok();
+        if let Some(ws_config) =
+            transport::parse_ws_config(ws_port.as_deref(), ws_plain.as_deref())?
+        {
+            let ws_listener = TcpListener::bind((self.bind_address.as_str(), ws_config.port))
+                .map_err(|e| format!("Failed to bind WebSocket port {}: {}", ws_config.port, e))?;
+            ws_listener
+                .set_nonblocking(true)
+                .map_err(|e| format!("Failed to set non-blocking mode: {}", e))?;
+            log::info!(
+                "WebSocket listener bound to port {} ({})",
+                ws_config.port,
+                if ws_config.tls { "wss" } else { "plain ws" }
+            );
+            self.ws_sock = Some(ws_listener);
+            self.ws_config = Some(ws_config);
+        }
+
         crate::network_manager::initialize_packet_stats()?;
 
         // Mark data as dirty so a crash before clean shutdown is detectable.
@@ -295,9 +455,16 @@ impl Server {
             }
         }
 
-        // Always spawn the background KeyDB saver.
-        log::info!("Starting background KeyDB saver thread...");
-        self.background_saver = Some(background_saver::spawn());
+        // Always spawn the background saver for the configured backend.
+        log::info!("Starting background saver thread ({:?})...", gs.storage);
+        self.background_saver = Some(background_saver::spawn_for(&gs.storage)?);
+
+        // Spawn the flusher for hash writes queued during the tick.
+        self.write_behind_flusher = server::keydb::write_behind::WriteBehindFlusher::spawn();
+        if let server::storage::StorageBackend::Sqlite { path } = &gs.storage {
+            self.record_flusher =
+                server::keydb::write_behind::WriteBehindFlusher::spawn_sqlite(path.clone());
+        }
 
         // Spawn the admin template-reload watcher (no-op when disabled).
         self.template_reload_watcher =
@@ -322,6 +489,23 @@ impl Server {
         // Spawn the live ban-action watcher (no-op when disabled).
         self.ban_action_watcher = server::keydb::ban_action::BanActionWatcher::spawn();
 
+        // Open the operator console (no-op without MAG_CONSOLE_TOKEN).
+        self.console = crate::console::ConsoleServer::spawn();
+
+        // Spawn the tick profile publisher (no-op when disabled).
+        self.tick_profile_publisher = server::keydb::tick_profile::TickProfilePublisher::spawn();
+
+        // Spawn the online roster publisher (no-op when disabled).
+        self.online_roster_publisher = server::keydb::online_roster::OnlineRosterPublisher::spawn();
+        self.stream_overlay_publisher =
+            server::keydb::stream_overlay::StreamOverlayPublisher::spawn();
+
+        // Spawn the Discord role publisher (no-op when disabled).
+        self.discord_role_publisher = server::keydb::discord_roles::DiscordRolePublisher::spawn();
+
+        // Spawn the economy publisher (no-op when disabled).
+        self.economy_publisher = server::keydb::economy::EconomyPublisher::spawn();
+
         Ok(())
     }
 
@@ -348,14 +532,54 @@ impl Server {
         if now > last_time {
             let pre_tick_time = Instant::now();
 
-            self.last_tick_time =
-                Some(last_time + Duration::from_micros(core::constants::TICK as u64));
+            self.last_tick_time = Some(last_time + self.tick_interval);
 
             // Call main game tick (equivalent to: tick() in C++)
-            self.game_tick(gs);
+            {
+                #[cfg(feature = "profiling")]
+                let _mem_scope = crate::mem_profile::enter(crate::mem_profile::Subsystem::GameTick);
+                self.game_tick(gs);
+            }
+            if let Some(journal) = gs.journal.as_mut() {
+                journal.record_tick(gs.globals.ticker, &gs.characters, &gs.items);
+            }
+            if self.tick_profiler.record_tick(pre_tick_time.elapsed()) {
+                self.publish_tick_profile(gs);
+            }
+            if gs.globals.ticker % (core::constants::TICKS * ONLINE_ROSTER_INTERVAL_SECS as i32)
+                == 0
+            {
+                self.publish_online_roster(gs);
+            }
+            if gs.globals.ticker % (core::constants::TICKS * OVERLAY_INTERVAL_SECS as i32) == 0 {
+                self.publish_stream_overlays(gs);
+            }
+            if gs.globals.ticker % (core::constants::TICKS * DISCORD_ROLE_INTERVAL_SECS as i32) == 0
+            {
+                self.publish_discord_roles(gs);
+            }
+            if gs.globals.ticker % (core::constants::TICKS * ECONOMY_INTERVAL_SECS as i32) == 0 {
+                self.publish_economy(gs);
+            }
+            if gs.globals.ticker % core::constants::TICKS == 0 {
+                gs.decals.prune(gs.globals.ticker);
+            }
+            if gs.globals.ticker % (core::constants::TICKS * 60) == 0 {
+                gs.expire_consignments();
+                gs.expire_reward_letters();
+            }
+            gs.run_script_timers();
+            if let Some(log) = self.state_hashes.as_mut() {
+                log.record(gs);
+            }
 
             // Compress and send tick data to clients
-            self.compress_ticks(gs);
+            {
+                #[cfg(feature = "profiling")]
+                let _mem_scope =
+                    crate::mem_profile::enter(crate::mem_profile::Subsystem::CompressTicks);
+                self.compress_ticks(gs);
+            }
 
             let new_now = Instant::now();
             let new_last = self.last_tick_time.unwrap();
@@ -379,9 +603,9 @@ impl Server {
                     post_tick_time.duration_since(pre_tick_time).as_secs_f32() * 1000.0;
                 self.tick_perf_stats.push(tick_duration);
 
-                const DESIRED_TICK_TIME_MS: f32 = core::constants::TICK as f32 / 1000.0; // 1000 microseconds per millisecond
+                let desired_tick_time_ms = self.tick_interval.as_secs_f32() * 1000.0;
 
-                gs.globals.load = ((tick_duration / DESIRED_TICK_TIME_MS) * 100.0) as i64;
+                gs.globals.load = ((tick_duration / desired_tick_time_ms) * 100.0) as i64;
 
                 // TODO: Update this to be a proper moving average of the load
                 // gs.globals.load_avg = self.tick_perf_stats.stats().mean as i32;
@@ -399,7 +623,14 @@ impl Server {
         // Limiting this to every Nth game tick introduces noticeable input lag
         // and delayed map/tick packet delivery.
         let pre_io_time = Instant::now();
-        self.handle_network_io(gs);
+        {
+            #[cfg(feature = "profiling")]
+            let _mem_scope = crate::mem_profile::enter(crate::mem_profile::Subsystem::NetworkIo);
+            self.handle_network_io(gs);
+        }
+
+        #[cfg(feature = "profiling")]
+        self.memory_reporter.maybe_report(gs);
 
         if gs
             .globals
@@ -440,9 +671,11 @@ impl Server {
     /// # Arguments
     ///
     /// * `gs` - Mutable reference to the unified game state.
-    fn game_tick(&mut self, gs: &mut GameState) {
-        // Get current hour for statistics
-        let hour = chrono::Local::now().hour() as usize;
+    pub(crate) fn game_tick(&mut self, gs: &mut GameState) {
+        // Hourly statistics are bucketed by UTC hour so DST changes never
+        // skip or repeat a bucket.
+        crate::deterministic::advance_clock();
+        let hour = crate::wall_clock::utc_hour();
 
         // Increment global tick counters
         gs.globals.ticker = gs.globals.ticker.wrapping_add(1);
@@ -451,6 +684,9 @@ impl Server {
 
         let ticker = gs.globals.ticker;
         gs.tick_element_switch_states(ticker);
+        if gs.weather_enabled {
+            crate::state::weather::world_weather_tick(gs);
+        }
 
         // Background save scheduling (KeyDB only)
         self.maybe_enqueue_background_save(gs);
@@ -471,9 +707,9 @@ impl Server {
             }
 
             player::tick::plr_tick(gs, n);
-            // Weather (especially area-driven effects) is temporarily disabled
-            // while we tune things — re-enable once areas are configured.
-            // crate::state::weather::weather_tick(gs, n);
+            if gs.weather_enabled {
+                crate::state::weather::weather_tick(gs, n);
+            }
 
             if is_normal {
                 online += 1;
@@ -494,21 +730,14 @@ impl Server {
                 continue;
             }
 
-            // Process all pending commands (16 bytes each)
-            loop {
-                if gs.players[n].in_len < 16 {
-                    break;
-                }
-
-                player::plr_cmd(gs, n);
-
-                gs.players[n].in_len -= 16;
-                gs.players[n].inbuf.copy_within(16..256, 0);
-            }
+            // Process all complete commands; partial frames wait for more data.
+            player::framing::process_frames(gs, n);
 
             player::tick::plr_idle(gs, n);
         }
 
+        gs.expire_linkdead();
+
         // Do login stuff for players not in normal state
         for n in 1..gs.players.len() {
             if gs.players[n].sock.is_none() {
@@ -545,6 +774,17 @@ impl Server {
             self.wakeup_character(gs);
         }
 
+        self.zones.rebuild(gs);
+
+        // Build the see maps of everyone about to act on the worker pool
+        // before the (sequential) character loop needs them.
+        let seers: Vec<usize> = (1..core::constants::MAXCHARS)
+            .filter(|&n| tick_phases::is_see_map_candidate(gs, n))
+            .filter(|&n| gs.characters[n].status >= 8 || self.group_active(gs, n))
+            .filter(|&n| !gs.is_linkdead(n) && self.zones.should_act(gs, n, ticker))
+            .collect();
+        tick_phases::prefetch_see_maps(gs, &seers);
+
         for n in 1..core::constants::MAXCHARS {
             let char_state = {
                 if gs.characters[n].used == core::constants::USE_EMPTY {
@@ -604,6 +844,7 @@ impl Server {
             }
 
             awake += 1;
+            let act_start = Instant::now();
 
             if gs.characters[n].used == core::constants::USE_ACTIVE {
                 // Periodic validation
@@ -638,10 +879,15 @@ impl Server {
                     }
                 }
 
-                player::tick::plr_act(gs, n);
+                // Linkdead characters stand still until their player returns;
+                // NPCs far from any player only think every few ticks.
+                if !gs.is_linkdead(n) && self.zones.should_act(gs, n, ticker) {
+                    player::tick::plr_act(gs, n);
+                }
             }
 
             gs.do_regenerate(n);
+            self.tick_profiler.record_character(n, act_start.elapsed());
         }
 
         // Update global stats
@@ -658,6 +904,236 @@ impl Server {
         self.global_tick(gs);
     }
 
+    /// Close the current tick profile window and hand the report to the
+    /// publisher.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state the report describes.
+    fn publish_tick_profile(&mut self, gs: &GameState) {
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let report = self.tick_profiler.take_report(gs, generated_at);
+        if let Some(top) = report.top_characters.first() {
+            log::debug!(
+                "Tick profile: {} us over {} ticks, top character {} ({}) {} us",
+                report.tick_micros,
+                report.window_ticks,
+                top.character_id,
+                top.name,
+                top.micros
+            );
+        }
+        if let Some(publisher) = &self.tick_profile_publisher {
+            publisher.publish(report);
+        }
+    }
+
+    /// Snapshot the logged-in sessions and hand them to the roster
+    /// publisher.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state to read sessions from.
+    fn publish_online_roster(&self, gs: &GameState) {
+        let Some(publisher) = &self.online_roster_publisher else {
+            return;
+        };
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let mut players: Vec<OnlinePlayer> = (1..core::constants::MAXPLAYER)
+            .filter_map(|player_id| {
+                let player = &gs.players[player_id];
+                let character_id = player.usnr;
+                if player.sock.is_none()
+                    || player.state != core::constants::ST_NORMAL
+                    || !(1..core::constants::MAXCHARS).contains(&character_id)
+                {
+                    return None;
+                }
+                let ch = &gs.characters[character_id];
+                Some(OnlinePlayer {
+                    player_slot: player_id as u32,
+                    character_id: character_id as u32,
+                    name: ch.get_name().to_owned(),
+                    account_id: player.api_account_id,
+                    api_character_id: player.api_character_id,
+                    x: ch.x,
+                    y: ch.y,
+                    area: core::area::get_area_m(i32::from(ch.x), i32::from(ch.y))
+                        .unwrap_or_default(),
+                })
+            })
+            .collect();
+        players.sort_by(|a, b| a.name.cmp(&b.name));
+        publisher.publish(OnlineRoster {
+            generated_at,
+            ticker: gs.globals.ticker,
+            players,
+        });
+    }
+
+    /// Snapshot every API-linked online character for stream overlays.
+    ///
+    /// The publisher drops characters that have not opted in, so the tick
+    /// thread never has to query KeyDB.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state to read characters from.
+    fn publish_stream_overlays(&self, gs: &GameState) {
+        let Some(publisher) = &self.stream_overlay_publisher else {
+            return;
+        };
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let batch = (1..core::constants::MAXPLAYER)
+            .filter_map(|player_id| {
+                let player = &gs.players[player_id];
+                let character_id = player.usnr;
+                if player.sock.is_none()
+                    || player.state != core::constants::ST_NORMAL
+                    || player.api_character_id == 0
+                    || !(1..core::constants::MAXCHARS).contains(&character_id)
+                {
+                    return None;
+                }
+                let ch = &gs.characters[character_id];
+                let stats = OverlayStats {
+                    generated_at,
+                    name: ch.get_name().to_owned(),
+                    rank: core::ranks::rank_name(ch.points_tot.max(0) as u32).to_owned(),
+                    experience: ch.points_tot,
+                    hp: ch.a_hp / 1000,
+                    max_hp: i32::from(ch.hp[5]),
+                    endurance: ch.a_end / 1000,
+                    max_endurance: i32::from(ch.end[5]),
+                    mana: ch.a_mana / 1000,
+                    max_mana: i32::from(ch.mana[5]),
+                    area: core::area::get_area_m(i32::from(ch.x), i32::from(ch.y))
+                        .unwrap_or_default(),
+                    recent_kills: gs
+                        .overlay_kills
+                        .get(&character_id)
+                        .map(|kills| kills.iter().cloned().collect())
+                        .unwrap_or_default(),
+                };
+                Some((player.api_character_id, stats))
+            })
+            .collect();
+        publisher.publish(batch);
+    }
+
+    /// Snapshot the rank and guild of every API-linked online character for
+    /// Discord role sync.
+    ///
+    /// The publisher drops accounts that have not linked Discord and those
+    /// whose roles did not change.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state to read characters from.
+    fn publish_discord_roles(&self, gs: &GameState) {
+        let Some(publisher) = &self.discord_role_publisher else {
+            return;
+        };
+        let unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let batch = (1..core::constants::MAXPLAYER)
+            .filter_map(|player_id| {
+                let player = &gs.players[player_id];
+                let character_id = player.usnr;
+                if player.sock.is_none()
+                    || player.state != core::constants::ST_NORMAL
+                    || player.api_account_id == 0
+                    || !(1..core::constants::MAXCHARS).contains(&character_id)
+                {
+                    return None;
+                }
+                let ch = &gs.characters[character_id];
+                let guild = if ch.flags & CharacterFlags::PohLeader.bits() != 0 {
+                    DiscordGuild::PurpleOfHonorLeader
+                } else if ch.flags & CharacterFlags::Poh.bits() != 0 {
+                    DiscordGuild::PurpleOfHonor
+                } else {
+                    DiscordGuild::None
+                };
+                Some(DiscordRoleEvent {
+                    account_id: player.api_account_id,
+                    character_name: ch.get_name().to_owned(),
+                    rank: core::ranks::rank_name(ch.points_tot.max(0) as u32).to_owned(),
+                    guild,
+                    unix_secs,
+                })
+            })
+            .collect();
+        publisher.publish(batch);
+    }
+
+    /// Snapshot merchant stock and hand it to the economy publisher together
+    /// with the trades made since the last update.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state to read merchants from; its trade queue is drained.
+    fn publish_economy(&self, gs: &mut GameState) {
+        let Some(publisher) = &self.economy_publisher else {
+            return;
+        };
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let listings = (1..core::constants::MAXCHARS)
+            .filter_map(|cn| {
+                let ch = &gs.characters[cn];
+                if ch.used != core::constants::USE_ACTIVE
+                    || ch.flags & CharacterFlags::Merchant.bits() == 0
+                    || ch.flags & CharacterFlags::Player.bits() != 0
+                {
+                    return None;
+                }
+                let items: Vec<ListedItem> = ch
+                    .item
+                    .iter()
+                    .map(|&item_idx| item_idx as usize)
+                    .filter(|&item_idx| item_idx != 0 && item_idx < gs.items.len())
+                    .map(|item_idx| {
+                        let item = &gs.items[item_idx];
+                        ListedItem {
+                            template_id: u32::from(item.temp),
+                            item_name: item.get_name().to_owned(),
+                            base_price: item.value,
+                        }
+                    })
+                    .collect();
+                if items.is_empty() {
+                    return None;
+                }
+                Some(MerchantListing {
+                    merchant_id: cn as u32,
+                    merchant: ch.get_name().to_owned(),
+                    area: core::area::get_area_m(i32::from(ch.x), i32::from(ch.y))
+                        .unwrap_or_default(),
+                    items,
+                })
+            })
+            .collect();
+        publisher.publish(server::keydb::economy::EconomyUpdate {
+            generated_at,
+            listings,
+            trades: std::mem::take(&mut gs.market_trades),
+        });
+    }
+
     // Helper enum for character tick state
     /// Wake up one character in a round-robin fashion.
     ///
@@ -669,16 +1145,13 @@ impl Server {
     /// * `gs` - Mutable reference to the unified game state.
     fn wakeup_character(&mut self, gs: &mut GameState) {
         // Wakeup one character per 64 ticks
-        static WAKEUP: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(1);
-
-        let mut wakeup_idx = WAKEUP.load(std::sync::atomic::Ordering::Relaxed);
-        if wakeup_idx >= core::constants::MAXCHARS {
-            wakeup_idx = 1;
+        if self.wakeup_cursor >= core::constants::MAXCHARS {
+            self.wakeup_cursor = 1;
         }
 
-        gs.characters[wakeup_idx].data[92] = core::constants::TICKS * 60;
+        gs.characters[self.wakeup_cursor].data[92] = core::constants::TICKS * 60;
 
-        WAKEUP.store(wakeup_idx + 1, std::sync::atomic::Ordering::Relaxed);
+        self.wakeup_cursor += 1;
     }
 
     /// Return true if the character `cn` should be considered active.
@@ -1047,6 +1520,9 @@ impl Server {
     /// Check whether it is time to enqueue a background save job, and if so,
     /// clone the next slice of data and send it to the background saver thread.
     ///
+    /// Online players and their items are additionally checkpointed every
+    /// [`background_saver::CHECKPOINT_INTERVAL_TICKS`].
+    ///
     /// # Arguments
     ///
     /// * `gs` - Reference to the unified game state (read-only cloning).
@@ -1056,6 +1532,14 @@ impl Server {
             None => return,
         };
 
+        self.checkpoint_tick_counter += 1;
+        if self.checkpoint_tick_counter >= background_saver::CHECKPOINT_INTERVAL_TICKS {
+            self.checkpoint_tick_counter = 0;
+            if let Some(job) = background_saver::checkpoint_job(&gs.characters, &gs.items) {
+                saver.send(job);
+            }
+        }
+
         self.save_tick_counter += 1;
         if self.save_tick_counter < background_saver::SAVE_INTERVAL_TICKS {
             return;
@@ -1152,22 +1636,26 @@ impl Server {
         }
     }
 
-    /// Drain pending admin reload requests and apply them to `gs`.
+    /// Drain pending template reload requests and apply them to `gs`.
     ///
-    /// Each drained request causes the watcher's KeyDB connection to be
-    /// reopened here on the tick thread (the watcher only signals; the swap
-    /// runs synchronously on the tick thread to keep `GameState` access
-    /// single-threaded). After applying, an `applied:{ts}` status entry is
-    /// written so the API can confirm completion.
+    /// Requests come from the admin API watcher and from
+    /// `gs.pending_template_reloads` (`#reload` and `SIGHUP`). Templates are
+    /// re-read from KeyDB here on the tick thread, between ticks, so
+    /// `GameState` access stays single-threaded. Admin requests get an
+    /// `applied:{ts}` status entry so the API can confirm completion;
+    /// command requests are answered in the issuer's chat log.
     ///
     /// # Arguments
     ///
     /// * `gs` - Mutable game state whose template slices will be replaced.
     pub fn drain_template_reloads(&mut self, gs: &mut GameState) {
-        let Some(watcher) = self.template_reload_watcher.as_ref() else {
-            return;
-        };
-        while let Some(req) = watcher.try_recv() {
+        let mut requests = std::mem::take(&mut gs.pending_template_reloads);
+        if let Some(watcher) = self.template_reload_watcher.as_ref() {
+            while let Some(req) = watcher.try_recv() {
+                requests.push(req);
+            }
+        }
+        for req in requests {
             self.apply_template_reload(gs, req);
         }
     }
@@ -1177,69 +1665,111 @@ impl Server {
         gs: &mut GameState,
         req: server::keydb::template_reload::ReloadRequest,
     ) {
-        let mut con = match server::keydb::connection::connect() {
-            Ok(c) => c,
-            Err(e) => {
+        use server::keydb::template_reload::ReloadOrigin;
+
+        let result = server::keydb::connection::connect().and_then(|mut con| {
+            let summary = Self::swap_templates(gs, &mut con, &req)?;
+            if req.origin == ReloadOrigin::Admin
+                && let Err(e) =
+                    server::keydb::template_reload::write_applied_status(&mut con, &req.request_id)
+            {
                 log::warn!(
-                    "template reload {}: keydb connect failed: {}",
+                    "template reload {}: status write failed: {}",
                     req.request_id,
                     e
                 );
-                return;
             }
-        };
+            Ok(summary)
+        });
 
-        if req.reload_items {
-            match server::keydb::store::load_item_templates(&mut con) {
-                Ok(items) => {
-                    log::info!(
-                        "template reload {}: swapped {} item templates",
-                        req.request_id,
-                        items.len()
-                    );
-                    gs.item_templates = items;
-                }
-                Err(e) => {
-                    log::warn!(
-                        "template reload {}: load item templates failed: {}",
-                        req.request_id,
-                        e
-                    );
-                    return;
-                }
-            }
+        match &result {
+            Ok(summary) => log::info!("template reload {}: {}", req.request_id, summary),
+            Err(e) => log::warn!("template reload {}: {}", req.request_id, e),
         }
 
-        if req.reload_characters {
-            match server::keydb::store::load_character_templates(&mut con) {
-                Ok(chars) => {
-                    log::info!(
-                        "template reload {}: swapped {} character templates",
-                        req.request_id,
-                        chars.len()
-                    );
-                    gs.character_templates = chars;
-                }
-                Err(e) => {
-                    log::warn!(
-                        "template reload {}: load character templates failed: {}",
-                        req.request_id,
-                        e
-                    );
-                    return;
-                }
+        if let ReloadOrigin::Command(cn) = req.origin
+            && core::types::Character::is_sane_character(cn)
+            && gs.characters[cn].used != core::constants::USE_EMPTY
+        {
+            match result {
+                Ok(summary) => gs.do_character_log(
+                    cn,
+                    core::types::FontColor::Green,
+                    &format!("Templates reloaded: {}.\n", summary),
+                ),
+                Err(e) => gs.do_character_log(
+                    cn,
+                    core::types::FontColor::Red,
+                    &format!("Template reload failed: {}\n", e),
+                ),
             }
         }
+    }
 
-        if let Err(e) =
-            server::keydb::template_reload::write_applied_status(&mut con, &req.request_id)
-        {
-            log::warn!(
-                "template reload {}: status write failed: {}",
-                req.request_id,
-                e
-            );
+    /// Load the requested template kinds from KeyDB and swap them into `gs`.
+    ///
+    /// Every requested kind is loaded and checked against the live slot
+    /// count before anything is replaced, so a failure leaves all templates
+    /// untouched. Live items and characters keep their own copies of
+    /// template data; only future spawns and resets see the new values.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs`  - Game state whose template arrays are replaced.
+    /// * `con` - Open KeyDB connection.
+    /// * `req` - Which kinds to reload.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(summary)` describing how many templates changed.
+    /// * `Err` when loading fails or a slot count does not match.
+    fn swap_templates(
+        gs: &mut GameState,
+        con: &mut redis::Connection,
+        req: &server::keydb::template_reload::ReloadRequest,
+    ) -> Result<String, String> {
+        let items = if req.reload_items {
+            let items = server::keydb::store::load_item_templates(con)
+                .map_err(|e| format!("load item templates failed: {}", e))?;
+            if items.len() != gs.item_templates.len() {
+                return Err(format!(
+                    "item template count {} does not match live {}",
+                    items.len(),
+                    gs.item_templates.len()
+                ));
+            }
+            Some(items)
+        } else {
+            None
+        };
+
+        let characters = if req.reload_characters {
+            let characters = server::keydb::store::load_character_templates(con)
+                .map_err(|e| format!("load character templates failed: {}", e))?;
+            if characters.len() != gs.character_templates.len() {
+                return Err(format!(
+                    "character template count {} does not match live {}",
+                    characters.len(),
+                    gs.character_templates.len()
+                ));
+            }
+            Some(characters)
+        } else {
+            None
+        };
+
+        let mut parts = Vec::new();
+        if let Some(items) = items {
+            let changed = count_changed(&gs.item_templates, &items);
+            gs.item_templates = items;
+            parts.push(format!("{} item templates changed", changed));
         }
+        if let Some(characters) = characters {
+            let changed = count_changed(&gs.character_templates, &characters);
+            gs.character_templates = characters;
+            parts.push(format!("{} character templates changed", changed));
+        }
+        Ok(parts.join(", "))
     }
 
     /// Drain pending admin text reload requests and apply them to `gs`.
@@ -1401,6 +1931,7 @@ impl Server {
         tile.sprite = patch.sprite;
         tile.fsprite = patch.fsprite;
         tile.flags = patch.flags;
+        gs.pathfinder.refresh_regions_at(x, y, &gs.map, &gs.items);
         true
     }
 
@@ -1600,6 +2131,25 @@ impl Server {
         }
     }
 
+    /// Drain and execute commands from the operator console.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Mutable game state the commands act on.
+    pub fn drain_console_commands(&mut self, gs: &mut GameState) {
+        let Some(console) = self.console.as_ref() else {
+            return;
+        };
+
+        while let Some(request) = console.try_recv() {
+            let result = crate::console::command::execute_command(gs, &request.command);
+            if let Err(error) = &result {
+                log::warn!("Operator console command failed: {}", error);
+            }
+            request.respond(result);
+        }
+    }
+
     fn apply_world_action(
         &mut self,
         gs: &mut GameState,
@@ -1635,7 +2185,9 @@ impl Server {
             );
         }
 
-        if let Some(saver) = self.background_saver.as_ref()
+        let mutates_world = request.action.mutates_world();
+        if mutates_world
+            && let Some(saver) = self.background_saver.as_ref()
             && let Err(error) = saver.flush()
         {
             let message = format!("background saver flush failed: {}", error);
@@ -1645,8 +2197,13 @@ impl Server {
 
         match populate::execute_world_action(gs, &request.action) {
             Ok(outcome) => {
-                gs.globals.set_dirty(true);
-                match gs.save() {
+                let saved = if mutates_world {
+                    gs.globals.set_dirty(true);
+                    gs.save()
+                } else {
+                    Ok(())
+                };
+                match saved {
                     Ok(()) => {
                         let elapsed_ms = started.elapsed().as_millis();
                         let message = format!("{} ({} ms)", outcome.message, elapsed_ms);
@@ -1827,6 +2384,10 @@ impl Server {
             log::info!("Stopping ban action watcher...");
             watcher.shutdown();
         }
+        if let Some(mut console) = self.console.take() {
+            log::info!("Stopping operator console...");
+            console.shutdown();
+        }
         if let Some(mut saver) = self.background_saver.take() {
             log::info!("Flushing pending background save jobs...");
             if let Err(e) = saver.flush() {
@@ -1836,6 +2397,14 @@ impl Server {
             saver.shutdown();
             log::info!("Background saver thread stopped.");
         }
+        if let Some(mut flusher) = self.write_behind_flusher.take() {
+            log::info!("Flushing KeyDB write-behind queue...");
+            flusher.shutdown();
+        }
+        if let Some(mut flusher) = self.record_flusher.take() {
+            log::info!("Flushing SQLite record queue...");
+            flusher.shutdown();
+        }
     }
 
     /// Compress outgoing per-player tick buffers using zlib when beneficial.
@@ -1977,8 +2546,8 @@ impl Server {
 
     /// Accept new connections and perform per-player network IO.
     ///
-    /// Accepts new TCP connections on the listener, assigning them a free
-    /// /// player slot via `new_player`. For existing connections, it calls
+    /// Accepts new connections on the game and WebSocket listeners, assigning
+    /// them a free player slot via `new_player`. For existing connections, it calls
     /// `rec_player` and `send_player` as necessary to handle receive and send
     /// activity.
     ///
@@ -1987,31 +2556,11 @@ impl Server {
     /// * `gs` - Mutable reference to the unified game state.
     fn handle_network_io(&mut self, gs: &mut GameState) {
         // Handle new connections
-        if let Some(ref listener) = self.sock {
-            match listener.accept() {
-                Ok((stream, addr)) => {
-                    log::info!("New connection from {}", addr);
-                    let config = self
-                        .tls_config
-                        .as_ref()
-                        .expect("TLS config must be initialized before handle_network_io");
-                    match tls::accept_tls(stream, config.clone()) {
-                        Ok(tls_stream) => {
-                            log::info!("TLS handshake completed for {}", addr);
-                            self.new_player(gs, tls_stream, addr.ip());
-                        }
-                        Err(e) => {
-                            log::warn!("TLS handshake failed for {}: {}", addr, e);
-                        }
-                    }
-                }
-                Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                    // No pending connections, this is normal in non-blocking mode
-                }
-                Err(e) => {
-                    log::error!("Error accepting connection: {}", e);
-                }
-            }
+        if let Some((stream, addr)) = self.accept_game_stream() {
+            self.new_player(gs, stream, addr);
+        }
+        if let Some((stream, addr)) = self.accept_ws_stream() {
+            self.new_player(gs, stream, addr);
         }
 
         // Handle existing player connections
@@ -2026,6 +2575,89 @@ impl Server {
         }
     }
 
+    /// Accept one pending connection on the game port and complete its TLS
+    /// handshake.
+    ///
+    /// # Returns
+    ///
+    /// * The established stream and peer address, or `None` when nothing is
+    ///   pending or the handshake failed.
+    fn accept_game_stream(&self) -> Option<(GameStream, std::net::IpAddr)> {
+        let listener = self.sock.as_ref()?;
+        let (stream, addr) = match listener.accept() {
+            Ok(accepted) => accepted,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                // No pending connections, this is normal in non-blocking mode
+                return None;
+            }
+            Err(e) => {
+                log::error!("Error accepting connection: {}", e);
+                return None;
+            }
+        };
+        log::info!("New connection from {}", addr);
+        let config = self
+            .tls_config
+            .as_ref()
+            .expect("TLS config must be initialized before handle_network_io");
+        match tls::accept_tls(stream, config.clone()) {
+            Ok(tls_stream) => {
+                log::info!("TLS handshake completed for {}", addr);
+                Some((tls_stream, addr.ip()))
+            }
+            Err(e) => {
+                log::warn!("TLS handshake failed for {}: {}", addr, e);
+                None
+            }
+        }
+    }
+
+    /// Accept one pending connection on the WebSocket port and complete its
+    /// TLS (for `wss://`) and WebSocket handshakes.
+    ///
+    /// # Returns
+    ///
+    /// * The upgraded stream and peer address, or `None` when the listener is
+    ///   off, nothing is pending or a handshake failed.
+    fn accept_ws_stream(&self) -> Option<(GameStream, std::net::IpAddr)> {
+        let listener = self.ws_sock.as_ref()?;
+        let ws_config = self.ws_config?;
+        let (stream, addr) = match listener.accept() {
+            Ok(accepted) => accepted,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => return None,
+            Err(e) => {
+                log::error!("Error accepting WebSocket connection: {}", e);
+                return None;
+            }
+        };
+        log::info!("New WebSocket connection from {}", addr);
+        let stream = if ws_config.tls {
+            let config = self
+                .tls_config
+                .as_ref()
+                .expect("TLS config must be initialized before handle_network_io");
+            match tls::accept_tls(stream, config.clone()) {
+                Ok(tls_stream) => tls_stream,
+                Err(e) => {
+                    log::warn!("TLS handshake failed for {}: {}", addr, e);
+                    return None;
+                }
+            }
+        } else {
+            GameStream::Plain(stream)
+        };
+        match transport::accept_websocket(stream) {
+            Ok(ws_stream) => {
+                log::info!("WebSocket handshake completed for {}", addr);
+                Some((GameStream::WebSocket(Box::new(ws_stream)), addr.ip()))
+            }
+            Err(e) => {
+                log::warn!("WebSocket handshake failed for {}: {}", addr, e);
+                None
+            }
+        }
+    }
+
     /// Accept a new incoming connection and assign it a player slot.
     ///
     /// Converts the peer address into a u32 (IPv4) and initializes a fresh
@@ -2035,7 +2667,7 @@ impl Server {
     /// # Arguments
     ///
     /// * `gs` - Reference to the unified game state (for reading ticker).
-    /// * `stream` - The accepted game stream (plain or TLS).
+    /// * `stream` - The accepted game stream (plain, TLS or WebSocket).
     /// * `addr` - The peer IP address.
     fn new_player(&mut self, gs: &mut GameState, stream: GameStream, addr: std::net::IpAddr) {
         let _ = stream.set_nonblocking(true);
@@ -2047,8 +2679,9 @@ impl Server {
 
         let ticker = gs.globals.ticker as u32;
 
+        // Only the first `max_players` slots are handed out.
         let mut slot: Option<usize> = None;
-        for n in 1..gs.players.len() {
+        for n in 1..gs.players.len().min(self.max_players + 1) {
             if gs.players[n].sock.is_none() {
                 slot = Some(n);
                 break;
@@ -2056,7 +2689,14 @@ impl Server {
         }
 
         let Some(n) = slot else {
-            log::warn!("new_player: MAXPLAYER reached");
+            log::warn!("new_player: {} players reached", self.max_players);
+            // Capabilities are not known yet, so only the bare `SV_EXIT`
+            // every client understands; best effort before the drop.
+            let mut stream = stream;
+            let _ = stream.write_all(&[
+                core::server_commands::ServerCommandType::Exit as u8,
+                LogoutReason::NoRoom as u8,
+            ]);
             return;
         };
 
@@ -2122,7 +2762,7 @@ impl Server {
                     gs.players[player_idx].ltick = 0;
                     gs.players[player_idx].rtick = 0;
                     gs.players[player_idx].zs = None;
-                    player::connection::plr_logout(gs, cn, player_idx, LogoutReason::Unknown);
+                    player::connection::plr_disconnected(gs, cn, player_idx);
                 }
                 Ok(len) => {
                     gs.players[player_idx].in_len += len;
@@ -2138,7 +2778,7 @@ impl Server {
                     gs.players[player_idx].ltick = 0;
                     gs.players[player_idx].rtick = 0;
                     gs.players[player_idx].zs = None;
-                    player::connection::plr_logout(gs, cn, player_idx, LogoutReason::Unknown);
+                    player::connection::plr_disconnected(gs, cn, player_idx);
                 }
             }
         }
@@ -2189,7 +2829,7 @@ impl Server {
                     gs.players[player_idx].ltick = 0;
                     gs.players[player_idx].rtick = 0;
                     gs.players[player_idx].zs = None;
-                    player::connection::plr_logout(gs, cn, player_idx, LogoutReason::Unknown);
+                    player::connection::plr_disconnected(gs, cn, player_idx);
                 }
                 Ok(ret) => {
                     gs.globals.send += ret as i64;
@@ -2208,7 +2848,7 @@ impl Server {
                     gs.players[player_idx].ltick = 0;
                     gs.players[player_idx].rtick = 0;
                     gs.players[player_idx].zs = None;
-                    player::connection::plr_logout(gs, cn, player_idx, LogoutReason::Unknown);
+                    player::connection::plr_disconnected(gs, cn, player_idx);
                 }
             }
         }
@@ -2233,6 +2873,12 @@ impl Drop for Server {
 mod tests {
     use super::*;
 
+    #[test]
+    fn count_changed_counts_differing_slots() {
+        assert_eq!(count_changed(&[1, 2, 3], &[1, 5, 3]), 1);
+        assert_eq!(count_changed::<u8>(&[], &[]), 0);
+    }
+
     /// Test the Server::new() constructor
     #[test]
     fn test_server_new() {
@@ -0,0 +1,265 @@
+//! Property-based simulation test.
+//!
+//! Builds a small [`World`] (two players, a mortal NPC and loose items),
+//! feeds it long random sequences of ordinary client commands through
+//! [`player::plr_cmd`], advances the characters tick by tick and checks the
+//! global invariants after every tick:
+//!
+//! * items are conserved: every item of the scene is referenced by exactly
+//!   one holder (a map tile or a character's inventory, equipment or
+//!   cursor), and nothing references a free item slot;
+//! * gold is never negative and hit points stay within `0..=max`;
+//! * map tiles only reference live characters and ground items that agree
+//!   with the tile's coordinates.
+//!
+//! The tick is a trimmed copy of `Server::game_tick` (command dispatch,
+//! `really_update_char`, `plr_act`, regeneration) without networking,
+//! population or persistence.
+
+use std::collections::HashMap;
+
+use core::client_commands::ClientCommand;
+use core::constants::{CharacterFlags, SERVER_MAPX, USE_ACTIVE, USE_EMPTY, WN_BODY};
+use proptest::prelude::*;
+use proptest::test_runner::{TestCaseError, TestRunner};
+
+use crate::game_state::GameState;
+use crate::player;
+use crate::test_helpers::write_inbuf;
+use crate::testutil::{CharSpec, ItemSpec, World};
+
+/// Side length of the square the scene lives in; all targets are inside.
+const AREA: usize = 32;
+
+/// One scripted step: a command from one of the two players, followed by a
+/// few ticks.
+#[derive(Clone, Debug)]
+enum SimCmd {
+    Move(i16, i32),
+    Pickup(i16, i32),
+    Drop(i16, i32),
+    Turn(i16, i32),
+    Give(u32),
+    Attack(u32),
+    Inv(u32, u32),
+    Mode(i16),
+    Reset,
+}
+
+impl SimCmd {
+    fn to_client(&self) -> ClientCommand {
+        match *self {
+            SimCmd::Move(x, y) => ClientCommand::new_move(x, y),
+            SimCmd::Pickup(x, y) => ClientCommand::new_pickup(x, y),
+            SimCmd::Drop(x, y) => ClientCommand::new_drop(x, y),
+            SimCmd::Turn(x, y) => ClientCommand::new_turn(x, y),
+            SimCmd::Give(co) => ClientCommand::new_give(co),
+            SimCmd::Attack(co) => ClientCommand::new_attack(co),
+            SimCmd::Inv(what, n) => ClientCommand::new_inv(what, n, 0),
+            SimCmd::Mode(mode) => ClientCommand::new_mode(mode),
+            SimCmd::Reset => ClientCommand::new_reset(),
+        }
+    }
+}
+
+fn sim_cmd() -> impl Strategy<Value = SimCmd> {
+    let xy = (4..AREA as i16 - 4, 4..AREA as i32 - 4);
+    // Character slots 1..=3 exist in the scene; 4 is deliberately empty.
+    let target = 1u32..=4;
+    prop_oneof![
+        4 => xy.clone().prop_map(|(x, y)| SimCmd::Move(x, y)),
+        3 => xy.clone().prop_map(|(x, y)| SimCmd::Pickup(x, y)),
+        3 => xy.clone().prop_map(|(x, y)| SimCmd::Drop(x, y)),
+        1 => xy.prop_map(|(x, y)| SimCmd::Turn(x, y)),
+        2 => target.clone().prop_map(SimCmd::Give),
+        2 => target.prop_map(SimCmd::Attack),
+        3 => (0u32..=1, 0u32..40).prop_map(|(what, n)| SimCmd::Inv(what, n)),
+        1 => (0i16..=2).prop_map(SimCmd::Mode),
+        1 => Just(SimCmd::Reset),
+    ]
+}
+
+/// Builds the scene used by every case.
+fn scene() -> World {
+    let immortal = CharacterFlags::Immortal.bits();
+    World::new()
+        .with_char(
+            CharSpec::player("Alpha")
+                .at(10, 10)
+                .flags(immortal)
+                .carrying(ItemSpec::new("Torch"))
+                .carrying(ItemSpec::new("Rope"))
+                .wearing(WN_BODY, ItemSpec::new("Tunic").armor(1)),
+        )
+        .with_char(
+            CharSpec::player("Beta")
+                .at(14, 10)
+                .flags(immortal)
+                .holding(ItemSpec::new("Dagger").weapon(2)),
+        )
+        .with_char(
+            CharSpec::npc("Rat")
+                .at(12, 16)
+                .hp(10)
+                .carrying(ItemSpec::new("Tail")),
+        )
+        .with_item_at(8, 8, ItemSpec::new("Stone"))
+        .with_item_at(16, 12, ItemSpec::new("Bone"))
+        .with_item_at(20, 20, ItemSpec::new("Shell"))
+}
+
+/// Advances every character of the scene by one tick.
+fn sim_tick(gs: &mut GameState, chars: &[usize]) {
+    gs.globals.ticker = gs.globals.ticker.wrapping_add(1);
+    for &cn in chars {
+        if gs.characters[cn].used == USE_EMPTY {
+            continue;
+        }
+        if gs.characters[cn].flags & CharacterFlags::Update.bits() != 0 {
+            gs.really_update_char(cn);
+            gs.characters[cn].flags &= !CharacterFlags::Update.bits();
+        }
+        if gs.characters[cn].flags & CharacterFlags::Body.bits() != 0 {
+            continue;
+        }
+        player::tick::plr_act(gs, cn);
+        gs.do_regenerate(cn);
+    }
+}
+
+/// Checks the invariants listed in the module docs.
+///
+/// # Arguments
+///
+/// * `gs` - Game state after a tick.
+/// * `chars` - Character slots of the scene.
+/// * `items` - Item slots of the scene.
+///
+/// # Returns
+///
+/// * `Err(description)` for the first violated invariant.
+fn check_invariants(gs: &GameState, chars: &[usize], items: &[usize]) -> Result<(), String> {
+    let mut holders: HashMap<usize, Vec<String>> = HashMap::new();
+    let mut hold = |in_idx: u32, holder: String| {
+        if in_idx != 0 && in_idx & 0x8000_0000 == 0 {
+            holders.entry(in_idx as usize).or_default().push(holder);
+        }
+    };
+
+    for &cn in chars {
+        let ch = &gs.characters[cn];
+        if ch.used == USE_EMPTY {
+            continue;
+        }
+        if ch.gold < 0 {
+            return Err(format!("character {} has negative gold {}", cn, ch.gold));
+        }
+        let max_hp = i32::from(ch.hp[5]) * 1000;
+        if !(0..=max_hp).contains(&ch.a_hp) {
+            return Err(format!(
+                "character {} has a_hp {} outside 0..={}",
+                cn, ch.a_hp, max_hp
+            ));
+        }
+        let tile = ch.x as usize + ch.y as usize * SERVER_MAPX as usize;
+        if gs.map[tile].ch != cn as u32 {
+            return Err(format!(
+                "character {} at ({}, {}) missing from its tile (tile has {})",
+                cn, ch.x, ch.y, gs.map[tile].ch
+            ));
+        }
+        for (n, &in_idx) in ch.item.iter().enumerate() {
+            hold(in_idx, format!("character {} item[{}]", cn, n));
+        }
+        for (n, &in_idx) in ch.worn.iter().enumerate() {
+            hold(in_idx, format!("character {} worn[{}]", cn, n));
+        }
+        hold(ch.citem, format!("character {} citem", cn));
+    }
+
+    for y in 0..AREA {
+        for x in 0..AREA {
+            let tile = &gs.map[x + y * SERVER_MAPX as usize];
+            let co = tile.ch as usize;
+            if co != 0 {
+                let ch = &gs.characters[co];
+                if ch.used == USE_EMPTY || (ch.x as usize, ch.y as usize) != (x, y) {
+                    return Err(format!(
+                        "tile ({}, {}) references character {} (used {}, at {}, {})",
+                        x, y, co, ch.used, ch.x, ch.y
+                    ));
+                }
+            }
+            let in_idx = tile.it as usize;
+            if in_idx != 0 {
+                let it = &gs.items[in_idx];
+                if it.carried != 0 || (it.x as usize, it.y as usize) != (x, y) {
+                    return Err(format!(
+                        "tile ({}, {}) references item {} (carried by {}, at {}, {})",
+                        x, y, in_idx, it.carried, it.x, it.y
+                    ));
+                }
+                hold(tile.it, format!("tile ({}, {})", x, y));
+            }
+        }
+    }
+
+    for (&in_idx, refs) in &holders {
+        if gs.items[in_idx].used != USE_ACTIVE {
+            return Err(format!("{} references free item {}", refs[0], in_idx));
+        }
+        if refs.len() > 1 {
+            return Err(format!("item {} is duplicated: {:?}", in_idx, refs));
+        }
+    }
+    for &in_idx in items {
+        if !holders.contains_key(&in_idx) {
+            return Err(format!("item {} was lost", in_idx));
+        }
+    }
+    if holders.len() != items.len() {
+        return Err(format!(
+            "{} items referenced, scene has {}",
+            holders.len(),
+            items.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Runs `steps` against a fresh scene, checking invariants after each tick.
+fn run_simulation(steps: Vec<(usize, SimCmd, u8)>) -> Result<(), String> {
+    scene().run(move |gs, w| {
+        let chars = [w.char(0), w.char(1), w.char(2)];
+        let players = [w.player(0), w.player(1)];
+        let items: Vec<usize> = (1..gs.items.len())
+            .take_while(|&n| gs.items[n].used == USE_ACTIVE)
+            .collect();
+        check_invariants(gs, &chars, &items).map_err(|e| format!("initial scene: {}", e))?;
+
+        for (step, (who, cmd, ticks)) in steps.iter().enumerate() {
+            let nr = players[*who];
+            write_inbuf(gs, nr, &cmd.to_client().to_bytes());
+            player::plr_cmd(gs, nr);
+            for _ in 0..*ticks {
+                sim_tick(gs, &chars);
+                check_invariants(gs, &chars, &items)
+                    .map_err(|e| format!("step {} ({:?}): {}", step, cmd, e))?;
+            }
+        }
+        Ok(())
+    })
+}
+
+// `proptest!` expands to `core::` paths, which resolve to this workspace's
+// `core` crate here, so the runner is driven by hand.
+#[test]
+fn random_commands_preserve_world_invariants() {
+    let mut runner = TestRunner::new(ProptestConfig::with_cases(32));
+    let strategy = prop::collection::vec((0usize..2, sim_cmd(), 1u8..=12), 1..150);
+    if let Err(e) = runner.run(&strategy, |steps| {
+        run_simulation(steps).map_err(TestCaseError::fail)
+    }) {
+        panic!("{}", e);
+    }
+}
@@ -0,0 +1,589 @@
+/// SQLite-backed persistence layer for game data.
+///
+/// An alternative to [`crate::keydb::store`] for deployments that do not want
+/// to run KeyDB: the whole world lives in a single database file. Entities
+/// are stored as the same bincode blobs KeyDB uses, one row per slot, so
+/// both backends share encoding, schema versioning and the
+/// [`crate::keydb::store::GameData`] shape.
+///
+/// Tables:
+/// - `map(idx, data)`                 — map tiles in row-major order
+/// - `item(idx, data)`                — item slots
+/// - `item_template(idx, data)`       — item templates
+/// - `character(idx, data)`           — character slots
+/// - `character_template(idx, data)`  — character templates
+/// - `effect(idx, data)`              — effect slots
+/// - `meta(key, value)`               — `global`, `badnames`, `badwords`,
+///   `motd` and the `version` schema marker
+/// - `record(key, field, value)`      — string fields of small keyed records
+///   (mail, consignments), laid out like the KeyDB hashes they mirror
+///
+/// Writes happen in one transaction per save call, so a crash mid-save
+/// leaves the previous state intact.
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use bincode::{Decode, Encode};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::keydb::snapshot::{SNAPSHOT_SCHEMA_VERSION, WorldSnapshot};
+use crate::keydb::store::{self, GameData};
+use crate::keydb::write_behind::HashWrite;
+
+/// Current schema version written to the `meta.version` row.
+const SCHEMA_VERSION: u32 = SNAPSHOT_SCHEMA_VERSION;
+
+/// How long a write waits on a transaction of another connection; the
+/// background saver and the record flusher each hold their own.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Tables holding one bincode blob per entity slot.
+const INDEXED_TABLES: [&str; 6] = [
+    "map",
+    "item",
+    "item_template",
+    "character",
+    "character_template",
+    "effect",
+];
+
+/// An open SQLite game database.
+pub struct SqliteStore {
+    con: Connection,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) the database at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Database file path.
+    ///
+    /// # Returns
+    ///
+    /// * The opened store, or an `Err` if the file cannot be opened or the
+    ///   schema cannot be created.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let con =
+            Connection::open(path).map_err(|e| format!("SQLite open {}: {e}", path.display()))?;
+        // WAL keeps readers (admin tooling) from blocking the saver.
+        con.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| format!("SQLite journal_mode: {e}"))?;
+        con.pragma_update(None, "synchronous", "NORMAL")
+            .map_err(|e| format!("SQLite synchronous: {e}"))?;
+        con.busy_timeout(BUSY_TIMEOUT)
+            .map_err(|e| format!("SQLite busy_timeout: {e}"))?;
+        Self::with_connection(con)
+    }
+
+    /// Open a private in-memory database (tests and benchmarks).
+    ///
+    /// # Returns
+    ///
+    /// * The opened store, or an `Err` if the schema cannot be created.
+    pub fn open_in_memory() -> Result<Self, String> {
+        let con = Connection::open_in_memory().map_err(|e| format!("SQLite open: {e}"))?;
+        Self::with_connection(con)
+    }
+
+    fn with_connection(con: Connection) -> Result<Self, String> {
+        let mut schema = String::new();
+        for table in INDEXED_TABLES {
+            schema.push_str(&format!(
+                "CREATE TABLE IF NOT EXISTS {table} (idx INTEGER PRIMARY KEY, data BLOB NOT NULL);"
+            ));
+        }
+        schema.push_str(
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value BLOB NOT NULL);",
+        );
+        schema.push_str(
+            "CREATE TABLE IF NOT EXISTS record (key TEXT NOT NULL, field TEXT NOT NULL, \
+             value TEXT NOT NULL, PRIMARY KEY (key, field));",
+        );
+        con.execute_batch(&schema)
+            .map_err(|e| format!("SQLite create schema: {e}"))?;
+        Ok(Self { con })
+    }
+
+    /// Check whether game data has been seeded into the database.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` if the schema marker exists, `Ok(false)` otherwise.
+    pub fn has_game_data(&self) -> Result<bool, String> {
+        Ok(self.load_meta_raw("version")?.is_some())
+    }
+
+    /// Load all game data.
+    ///
+    /// # Returns
+    ///
+    /// * A fully populated [`GameData`] on success.
+    /// * `Err` if the data is missing, the schema version is unsupported, the
+    ///   map is empty, or a decode error occurs.
+    pub fn load_all(&self) -> Result<GameData, String> {
+        let Some(version) = self.load_meta_raw("version")? else {
+            return Err(
+                "No game data found in SQLite database (meta.version missing). \
+                 Seed it from a world snapshot first."
+                    .to_owned(),
+            );
+        };
+        let version: u32 = decode(&version, "meta.version")?;
+        if version != SCHEMA_VERSION {
+            return Err(format!(
+                "Unsupported SQLite schema version {version} (expected {SCHEMA_VERSION})"
+            ));
+        }
+
+        log::info!("Loading game data from SQLite (schema v{version})...");
+
+        let map: Vec<core::types::Map> = self.load_table(
+            "map",
+            core::constants::SERVER_MAPX as usize * core::constants::SERVER_MAPY as usize,
+        )?;
+        let non_default = store::count_non_default_map_tiles(&map);
+        if non_default == 0 {
+            return Err(
+                "Loaded SQLite map contains zero non-default tiles; the database appears empty or corrupt. Delete it to reseed from the world snapshot.".to_owned(),
+            );
+        }
+        log::info!(
+            "  Loaded {} map tiles ({} non-default).",
+            map.len(),
+            non_default
+        );
+
+        let items = self.load_table("item", core::constants::MAXITEM)?;
+        let item_templates = self.load_table(
+            "item_template",
+            core::template_store::ITEM_TEMPLATE_SLOT_COUNT,
+        )?;
+        let characters = self.load_table("character", core::constants::MAXCHARS)?;
+        let character_templates = self.load_table(
+            "character_template",
+            core::template_store::CHARACTER_TEMPLATE_SLOT_COUNT,
+        )?;
+        let effects = self.load_table("effect", core::constants::MAXEFFECT)?;
+        let globals = self.load_meta("global")?;
+        let bad_names: Vec<String> = self.load_meta("badnames")?;
+        let bad_words: Vec<String> = self.load_meta("badwords")?;
+        let message_of_the_day: String = self.load_meta("motd")?;
+
+        log::info!(
+            "  Loaded {} items, {} characters, {} effects, {} item templates, {} character templates.",
+            count_used(&items, |item: &core::types::Item| item.used),
+            count_used(&characters, |ch: &core::types::Character| ch.used),
+            count_used(&effects, |fx: &core::types::Effect| fx.used),
+            item_templates.len(),
+            character_templates.len()
+        );
+        log::info!("Game data loaded from SQLite successfully.");
+
+        Ok(GameData {
+            map,
+            items,
+            item_templates,
+            characters,
+            character_templates,
+            effects,
+            globals,
+            bad_names,
+            bad_words,
+            message_of_the_day,
+        })
+    }
+
+    /// Write every entity in a world snapshot, replacing existing data.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The decoded `.wsnap` snapshot.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn import_snapshot(&mut self, snapshot: &WorldSnapshot) -> Result<(), String> {
+        let tx = self.transaction()?;
+        for table in INDEXED_TABLES {
+            tx.execute(&format!("DELETE FROM {table}"), [])
+                .map_err(|e| format!("SQLite clear {table}: {e}"))?;
+        }
+        save_range(&tx, "map", &snapshot.map, 0)?;
+        save_range(&tx, "item", &snapshot.items, 0)?;
+        save_range(&tx, "item_template", &snapshot.item_templates, 0)?;
+        save_range(&tx, "character", &snapshot.characters, 0)?;
+        save_range(&tx, "character_template", &snapshot.character_templates, 0)?;
+        save_range(&tx, "effect", &snapshot.effects, 0)?;
+        save_meta(&tx, "global", &snapshot.globals)?;
+        save_meta(&tx, "badnames", &snapshot.bad_names)?;
+        save_meta(&tx, "badwords", &snapshot.bad_words)?;
+        save_meta(&tx, "motd", &snapshot.motd)?;
+        // Written last so an interrupted import is not mistaken for data.
+        save_meta(&tx, "version", &SCHEMA_VERSION)?;
+        tx.commit().map_err(|e| format!("SQLite commit: {e}"))
+    }
+
+    /// Save mutable runtime game data, excluding templates and text data.
+    ///
+    /// # Arguments
+    ///
+    /// * `map`        - All map tiles in row-major order.
+    /// * `items`      - All item slots.
+    /// * `characters` - All character slots.
+    /// * `effects`    - All effect slots.
+    /// * `globals`    - The single global state value.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn save_runtime_data(
+        &mut self,
+        map: &[core::types::Map],
+        items: &[core::types::Item],
+        characters: &[core::types::Character],
+        effects: &[core::types::Effect],
+        globals: &core::types::Global,
+    ) -> Result<(), String> {
+        log::info!("Saving runtime game data to SQLite (templates excluded)...");
+        let tx = self.transaction()?;
+        save_range(&tx, "map", map, 0)?;
+        save_range(&tx, "item", items, 0)?;
+        save_range(&tx, "character", characters, 0)?;
+        save_range(&tx, "effect", effects, 0)?;
+        save_meta(&tx, "global", globals)?;
+        save_meta(&tx, "version", &SCHEMA_VERSION)?;
+        tx.commit().map_err(|e| format!("SQLite commit: {e}"))?;
+        log::info!("Runtime game data saved to SQLite successfully.");
+        Ok(())
+    }
+
+    /// Save a sub-range of one entity table (background saver).
+    ///
+    /// # Arguments
+    ///
+    /// * `table`       - One of the indexed tables, e.g. `"item"`.
+    /// * `entities`    - The slice of entities to persist.
+    /// * `start_index` - The absolute slot index of `entities[0]`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn save_range<T: Encode>(
+        &mut self,
+        table: &str,
+        entities: &[T],
+        start_index: usize,
+    ) -> Result<(), String> {
+        let tx = self.transaction()?;
+        save_range(&tx, table, entities, start_index)?;
+        tx.commit().map_err(|e| format!("SQLite commit: {e}"))
+    }
+
+    /// Save effects and globals in one transaction (background saver).
+    ///
+    /// # Arguments
+    ///
+    /// * `effects` - All effect slots.
+    /// * `globals` - The single global state value.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn save_small_data(
+        &mut self,
+        effects: &[core::types::Effect],
+        globals: &core::types::Global,
+    ) -> Result<(), String> {
+        let tx = self.transaction()?;
+        save_range(&tx, "effect", effects, 0)?;
+        save_meta(&tx, "global", globals)?;
+        tx.commit().map_err(|e| format!("SQLite commit: {e}"))
+    }
+
+    /// Save individual character and item slots in one transaction
+    /// (player checkpoints).
+    ///
+    /// # Arguments
+    ///
+    /// * `characters` - `(slot, character)` pairs.
+    /// * `items` - `(slot, item)` pairs.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn save_checkpoint(
+        &mut self,
+        characters: &[(usize, core::types::Character)],
+        items: &[(usize, core::types::Item)],
+    ) -> Result<(), String> {
+        let tx = self.transaction()?;
+        for (idx, character) in characters {
+            save_range(&tx, "character", std::slice::from_ref(character), *idx)?;
+        }
+        for (idx, item) in items {
+            save_range(&tx, "item", std::slice::from_ref(item), *idx)?;
+        }
+        tx.commit().map_err(|e| format!("SQLite commit: {e}"))
+    }
+
+    /// Load the message of the day.
+    ///
+    /// # Returns
+    ///
+    /// * The stored MOTD, or an `Err` if it is missing or undecodable.
+    pub fn load_message_of_the_day(&self) -> Result<String, String> {
+        self.load_meta("motd")
+    }
+
+    /// Set fields of records in one transaction, keeping the fields not
+    /// given (like KeyDB `HSET`).
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - Record keys, e.g. `game:mail:17`, with the
+    ///   `(field, value)` pairs to write.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure; nothing is
+    ///   written then.
+    pub fn save_records(&mut self, records: &[HashWrite]) -> Result<(), String> {
+        let tx = self.transaction()?;
+        {
+            let mut stmt = tx
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO record (key, field, value) VALUES (?1, ?2, ?3)",
+                )
+                .map_err(|e| format!("SQLite prepare record: {e}"))?;
+            for (key, fields) in records {
+                for (field, value) in fields {
+                    stmt.execute(params![key, field, value])
+                        .map_err(|e| format!("SQLite write {key}.{field}: {e}"))?;
+                }
+            }
+        }
+        tx.commit().map_err(|e| format!("SQLite commit: {e}"))
+    }
+
+    /// Load every record whose key starts with `prefix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Key prefix, e.g. `game:mail:`.
+    ///
+    /// # Returns
+    ///
+    /// * `(key, fields)` pairs, or an `Err` describing the failure.
+    pub fn load_records(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, HashMap<String, String>)>, String> {
+        let mut stmt = self
+            .con
+            .prepare("SELECT key, field, value FROM record WHERE substr(key, 1, ?2) = ?1")
+            .map_err(|e| format!("SQLite prepare record: {e}"))?;
+        let rows = stmt
+            .query_map(params![prefix, prefix.len() as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(|e| format!("SQLite read {prefix}*: {e}"))?;
+        let mut records: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for row in rows {
+            let (key, field, value) = row.map_err(|e| format!("SQLite read {prefix}*: {e}"))?;
+            records.entry(key).or_default().insert(field, value);
+        }
+        Ok(records.into_iter().collect())
+    }
+
+    /// Remove records and all of their fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - Record keys.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn delete_records(&mut self, keys: &[String]) -> Result<(), String> {
+        let tx = self.transaction()?;
+        for key in keys {
+            tx.execute("DELETE FROM record WHERE key = ?1", [key])
+                .map_err(|e| format!("SQLite delete {key}: {e}"))?;
+        }
+        tx.commit().map_err(|e| format!("SQLite commit: {e}"))
+    }
+
+    fn transaction(&mut self) -> Result<rusqlite::Transaction<'_>, String> {
+        self.con
+            .transaction()
+            .map_err(|e| format!("SQLite begin transaction: {e}"))
+    }
+
+    fn load_meta_raw(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        self.con
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| format!("SQLite read meta.{key}: {e}"))
+    }
+
+    fn load_meta<T: Decode<()>>(&self, key: &str) -> Result<T, String> {
+        let bytes = self
+            .load_meta_raw(key)?
+            .ok_or_else(|| format!("SQLite meta.{key} missing"))?;
+        decode(&bytes, &format!("meta.{key}"))
+    }
+
+    /// Load `count` slots from an indexed table; missing rows are defaults.
+    fn load_table<T: Decode<()> + Default + Clone>(
+        &self,
+        table: &str,
+        count: usize,
+    ) -> Result<Vec<T>, String> {
+        let mut values = vec![T::default(); count];
+        let mut stmt = self
+            .con
+            .prepare(&format!("SELECT idx, data FROM {table} WHERE idx < ?1"))
+            .map_err(|e| format!("SQLite prepare {table}: {e}"))?;
+        let rows = stmt
+            .query_map([count as i64], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .map_err(|e| format!("SQLite read {table}: {e}"))?;
+        for row in rows {
+            let (idx, bytes) = row.map_err(|e| format!("SQLite read {table}: {e}"))?;
+            values[idx as usize] = decode(&bytes, &format!("{table}[{idx}]"))?;
+        }
+        Ok(values)
+    }
+}
+
+fn save_range<T: Encode>(
+    tx: &rusqlite::Transaction<'_>,
+    table: &str,
+    entities: &[T],
+    start_index: usize,
+) -> Result<(), String> {
+    let mut stmt = tx
+        .prepare_cached(&format!(
+            "INSERT OR REPLACE INTO {table} (idx, data) VALUES (?1, ?2)"
+        ))
+        .map_err(|e| format!("SQLite prepare {table}: {e}"))?;
+    for (offset, entity) in entities.iter().enumerate() {
+        let idx = (start_index + offset) as i64;
+        stmt.execute(params![idx, store::encode(entity)?])
+            .map_err(|e| format!("SQLite write {table}[{idx}]: {e}"))?;
+    }
+    Ok(())
+}
+
+fn save_meta<T: Encode>(
+    tx: &rusqlite::Transaction<'_>,
+    key: &str,
+    value: &T,
+) -> Result<(), String> {
+    tx.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        params![key, store::encode(value)?],
+    )
+    .map_err(|e| format!("SQLite write meta.{key}: {e}"))?;
+    Ok(())
+}
+
+fn decode<T: Decode<()>>(bytes: &[u8], what: &str) -> Result<T, String> {
+    let (value, _consumed) = bincode::decode_from_slice(bytes, bincode::config::standard())
+        .map_err(|e| format!("Decode {what}: {e}"))?;
+    Ok(value)
+}
+
+fn count_used<T>(values: &[T], used: impl Fn(&T) -> u8) -> usize {
+    values
+        .iter()
+        .filter(|value| used(value) != core::constants::USE_EMPTY)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_and_meta_round_trip() {
+        let mut db = SqliteStore::open_in_memory().unwrap();
+        assert!(!db.has_game_data().unwrap());
+
+        let mut items = vec![core::types::Item::default(); 3];
+        items[1].used = core::constants::USE_ACTIVE;
+        items[1].value = 42;
+        db.save_range("item", &items, 10).unwrap();
+
+        let mut globals = core::types::Global::default();
+        globals.ticker = 1234;
+        db.save_small_data(&[core::types::Effect::default()], &globals)
+            .unwrap();
+
+        let loaded: Vec<core::types::Item> = db.load_table("item", 16).unwrap();
+        assert_eq!(loaded.len(), 16);
+        assert_eq!(loaded[11].value, 42);
+        assert_eq!(loaded[0].value, 0);
+        let loaded_globals: core::types::Global = db.load_meta("global").unwrap();
+        assert_eq!(loaded_globals.ticker, 1234);
+        assert!(!db.has_game_data().unwrap());
+    }
+
+    #[test]
+    fn checkpoint_writes_only_given_slots() {
+        let mut db = SqliteStore::open_in_memory().unwrap();
+        let character = core::types::Character {
+            gold: 500,
+            ..Default::default()
+        };
+        let item = core::types::Item {
+            value: 7,
+            ..Default::default()
+        };
+        db.save_checkpoint(&[(3, character)], &[(5, item)]).unwrap();
+
+        let characters: Vec<core::types::Character> = db.load_table("character", 4).unwrap();
+        assert_eq!(characters[3].gold, 500);
+        assert_eq!(characters[2].gold, 0);
+        let items: Vec<core::types::Item> = db.load_table("item", 6).unwrap();
+        assert_eq!(items[5].value, 7);
+    }
+
+    #[test]
+    fn records_update_field_by_field() {
+        let mut db = SqliteStore::open_in_memory().unwrap();
+        db.save_records(&[(
+            "game:mail:3".to_owned(),
+            vec![("to", "12".to_owned()), ("body", "Hello".to_owned())],
+        )])
+        .unwrap();
+        db.save_records(&[
+            ("game:mail:3".to_owned(), vec![("to", "0".to_owned())]),
+            (
+                "game:consign:3".to_owned(),
+                vec![("seller", "4".to_owned())],
+            ),
+        ])
+        .unwrap();
+
+        let records = db.load_records("game:mail:").unwrap();
+        assert_eq!(records.len(), 1);
+        let (key, fields) = &records[0];
+        assert_eq!(key, "game:mail:3");
+        assert_eq!(fields["to"], "0");
+        assert_eq!(fields["body"], "Hello");
+
+        db.delete_records(&["game:mail:3".to_owned()]).unwrap();
+        assert!(db.load_records("game:mail:").unwrap().is_empty());
+        assert_eq!(db.load_records("game:consign:").unwrap().len(), 1);
+    }
+}
@@ -7,6 +7,7 @@ use core::{skills, traits};
 use crate::driver;
 use crate::game_state::GameState;
 use crate::helpers;
+use crate::player::combat_log::HitSource;
 
 const MERCENARY_BASE_DODGE_PERCENT: i32 = 10;
 const MERCENARY_MAX_DODGE_CHANCE: i32 = 100;
@@ -579,7 +580,13 @@ impl GameState {
                         if self.dodges_physical_attack(co2) {
                             self.emit_attack_miss(attacker_index, co2);
                         } else {
-                            self.do_hurt(attacker_index, co2, sdam, 0);
+                            self.do_hurt_from(
+                                attacker_index,
+                                co2,
+                                sdam,
+                                0,
+                                HitSource::skill(skills::SK_SURROUND),
+                            );
                         }
                     }
                 }
@@ -902,6 +909,18 @@ impl GameState {
             return true;
         }
 
+        // Linkdead characters are protected from players until they reconnect
+        if self.is_linkdead(co) {
+            if msg {
+                self.do_character_log(
+                    cn,
+                    core::types::FontColor::Red,
+                    "You can't attack someone who has lost their connection.\n",
+                );
+            }
+            return false;
+        }
+
         // Check for NOFIGHT
         let m1 = (i32::from(self.characters[cn_actual].x)
             + i32::from(self.characters[cn_actual].y) * SERVER_MAPX) as usize;
@@ -1100,6 +1119,7 @@ impl GameState {
 mod tests {
     use super::*;
     use crate::test_helpers::with_test_gs;
+    use crate::testutil::{CharSpec, ItemSpec, World};
     use core::constants::{CharacterFlags, USE_ACTIVE};
     use core::talent_trees::mercenary;
     use core::talent_trees::{TalentRef, tree_for};
@@ -1266,6 +1286,29 @@ mod tests {
         });
     }
 
+    #[test]
+    fn worn_armor_absorbs_physical_damage() {
+        World::new()
+            .with_char(CharSpec::npc("Attacker"))
+            .with_char(CharSpec::npc("Bare"))
+            .with_char(
+                CharSpec::npc("Armored")
+                    .wearing(core::constants::WN_BODY, ItemSpec::new("Mail").armor(5)),
+            )
+            .with_char(CharSpec::npc("Statue").flags(CharacterFlags::Immortal.bits()))
+            .run(|gs, w| {
+                let attacker = w.char(0);
+                let lost = |gs: &mut GameState, co: usize| {
+                    let before = gs.characters[co].a_hp;
+                    gs.do_hurt(attacker, co, 20, 0);
+                    before - gs.characters[co].a_hp
+                };
+                assert_eq!(lost(gs, w.char(1)), 20 * 250);
+                assert_eq!(lost(gs, w.char(2)), 15 * 250);
+                assert_eq!(lost(gs, w.char(3)), 0);
+            });
+    }
+
     #[test]
     fn percent_roll_succeeds_inside_percent_boundary() {
         assert!(GameState::percent_roll_succeeds(10, 0));
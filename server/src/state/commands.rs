@@ -1,8 +1,10 @@
+use core::chat::{ChatChannel, ChatStyle};
 use core::constants::{CharacterFlags, GF_CLOSEENEMY, GF_LOOTING, GF_MAYHEM, GF_SPEEDY};
 use core::string_operations::c_string_to_str;
 use core::talent_trees::{TalentStatBonuses, talent_stat_bonuses};
 use core::types::FontColor;
 use core::{skills, traits};
+use server::journal::GoldTransferKind;
 
 use crate::effect::EffectManager;
 use crate::game_state::GameState;
@@ -106,11 +108,14 @@ const ALL_COMMANDS: &[&str] = &[
     "black",
     "bling",
     "bow",
+    "broker",
     "build",
     "cap",
     "caution",
     "ccp",
+    "clawback",
     "closenemey",
+    "consign",
     "create",
     "createspecial",
     "creator",
@@ -127,6 +132,7 @@ const ALL_COMMANDS: &[&str] = &[
     "fightback",
     "follow",
     "force",
+    "friend",
     "gargoyle",
     "ggold",
     "give",
@@ -134,6 +140,7 @@ const ALL_COMMANDS: &[&str] = &[
     "gold",
     "golden",
     "goto",
+    "granttitle",
     "greatergod",
     "greaterinv",
     "grolm",
@@ -141,6 +148,8 @@ const ALL_COMMANDS: &[&str] = &[
     "grolmstart",
     "group",
     "gtell",
+    "guild",
+    "guildtell",
     "help",
     "ignore",
     "iignore",
@@ -168,6 +177,7 @@ const ALL_COMMANDS: &[&str] = &[
     "looting",
     "lower",
     "luck",
+    "mail",
     "mailpass",
     "mark",
     "mayhem",
@@ -183,21 +193,26 @@ const ALL_COMMANDS: &[&str] = &[
     "notell",
     "nowho",
     "npclist",
+    "observe",
     "password",
     "pent",
     "perase",
     "poh",
     "pol",
     "potion",
+    "pray",
     "prof",
     "purple",
     "quest",
     "raise",
     "rank",
     "recall",
+    "refresh",
+    "reload",
     "respawn",
     "safe",
     "save",
+    "scripts",
     "seen",
     "send",
     "shout",
@@ -222,6 +237,7 @@ const ALL_COMMANDS: &[&str] = &[
     "thrall",
     "time",
     "tinfo",
+    "title",
     "top",
     "unique",
     "unban",
@@ -414,23 +430,26 @@ impl GameState {
             self.do_character_log(cn, core::types::FontColor::Red, "You feel guilty.\n");
             log::info!("emote: feels guilty ({})", text);
         } else if invis {
-            self.do_area_log(
+            self.do_area_styled_log(
                 0,
                 0,
                 i32::from(self.characters[cn].x),
                 i32::from(self.characters[cn].y),
-                core::types::FontColor::Green,
+                ChatStyle::new(ChatChannel::Say).emote(),
                 &format!("Somebody {}.\n", text),
             );
             log::info!("emote(inv): {}", text);
         } else {
             let name = self.characters[cn].get_name().to_owned();
-            self.do_area_log(
+            let style = ChatStyle::new(ChatChannel::Say)
+                .with_gm(self.is_gm_speaker(cn))
+                .emote();
+            self.do_area_styled_log(
                 0,
                 0,
                 i32::from(self.characters[cn].x),
                 i32::from(self.characters[cn].y),
-                core::types::FontColor::Green,
+                style,
                 &format!("{} {}.\n", name, text),
             );
             log::info!("emote: {}", text);
@@ -611,6 +630,26 @@ impl GameState {
         }
     }
 
+    /// Resends the full character sheet so a drifted client can resync.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character number requesting the refresh.
+    pub(crate) fn do_refresh(&mut self, cn: usize) {
+        let Some(nr) = self.owning_player(cn) else {
+            return;
+        };
+        if crate::player::char_sheet::plr_send_char_sheet(self, nr.index()) {
+            self.do_character_log(cn, FontColor::Green, "Character sheet refreshed.\n");
+        } else {
+            self.do_character_log(
+                cn,
+                FontColor::Red,
+                "Your client does not support refreshing the character sheet.\n",
+            );
+        }
+    }
+
     /// Port of `do_respawn(int cn, int co)` from `svr_do.cpp`
     ///
     /// Admin command to respawn a character.
@@ -1011,6 +1050,11 @@ impl GameState {
                 God::set_flag(self, cn, arg_get(1), CharacterFlags::Black.bits());
                 return;
             }
+            Some("broker") if f_g => {
+                log::debug!("Processing broker command for {}", cn);
+                God::set_flag(self, cn, arg_get(1), CharacterFlags::Broker.bits());
+                return;
+            }
             Some("cap") if f_g => {
                 // TODO: `set_cap(int cn,int nr)` from original C++
                 // Original call: set_cap(cn, atoi(arg[1]));
@@ -1034,11 +1078,21 @@ impl GameState {
                 );
                 return;
             }
+            Some("clawback") if f_g => {
+                log::debug!("Processing clawback command for {}", cn);
+                self.do_clawback(cn, arg_get(1), parse_u32(arg_get(2)), parse_u32(arg_get(3)));
+                return;
+            }
             Some("closenemey") if f_g => {
                 log::debug!("Processing closeenemy command for {}", cn);
                 God::set_gflag(self, cn, GF_CLOSEENEMY);
                 return;
             }
+            Some("consign") if !f_m => {
+                log::debug!("Processing consign command for {}", cn);
+                self.do_consign(cn, arg_get(1), args_get(1));
+                return;
+            }
             Some("create") if f_g => {
                 log::debug!("Processing create command for {}", cn);
                 God::create(self, cn, parse_i32(arg_get(1)));
@@ -1132,11 +1186,26 @@ impl GameState {
                 God::force(self, cn, arg_get(1), args_get(1));
                 return;
             }
+            Some("friend") if !f_m => {
+                log::debug!("Processing friend command for {}", cn);
+                self.do_friend(cn, arg_get(1), args_get(1));
+                return;
+            }
             Some("gtell") if !f_m => {
                 log::debug!("Processing gtell command for {}", cn);
                 self.do_gtell(cn, args_get(0));
                 return;
             }
+            Some("guild") if !f_m => {
+                log::debug!("Processing guild command for {}", cn);
+                self.do_guild(cn, arg_get(1), args_get(1));
+                return;
+            }
+            Some("guildtell") if !f_m => {
+                log::debug!("Processing guildtell command for {}", cn);
+                self.do_guildtell(cn, args_get(0));
+                return;
+            }
             Some("gold") => {
                 log::debug!("Processing gold command for {}", cn);
                 self.do_gold(cn, parse_i32(arg_get(1)));
@@ -1178,6 +1247,11 @@ impl GameState {
                 God::goto(self, cn, cn, arg_get(1), arg_get(2));
                 return;
             }
+            Some("granttitle") if f_giu => {
+                log::debug!("Processing granttitle command for {}", cn);
+                self.do_grant_title(cn, arg_get(1), arg_get(2));
+                return;
+            }
             Some("god") if f_g => {
                 log::debug!("Processing god command for {}", cn);
                 God::set_flag(self, cn, arg_get(1), CharacterFlags::God.bits());
@@ -1339,6 +1413,11 @@ impl GameState {
                 self.do_list_all_flags(cn, CharacterFlags::Black.bits());
                 return;
             }
+            Some("mail") if !f_m => {
+                log::debug!("Processing mail command for {}", cn);
+                self.do_mail(cn, arg_get(1), args_get(1));
+                return;
+            }
             Some("mayhem") if f_g => {
                 log::debug!("Processing mayhem command for {}", cn);
                 God::set_gflag(self, cn, GF_MAYHEM);
@@ -1404,6 +1483,11 @@ impl GameState {
                 self.do_npclist(cn, args_get(0));
                 return;
             }
+            Some("observe") if f_giu => {
+                log::debug!("Processing observe command for {}", cn);
+                crate::player::observer::do_observe(self, cn, arg_get(1));
+                return;
+            }
             Some("password") => {
                 if f_g {
                     log::debug!("Processing others-password command for {}", cn);
@@ -1431,6 +1515,11 @@ impl GameState {
                 God::set_flag(self, cn, arg_get(1), CharacterFlags::PohLeader.bits());
                 return;
             }
+            Some("pray") if !f_m => {
+                log::debug!("Processing pray command for {}", cn);
+                self.do_pray(cn);
+                return;
+            }
             Some("prof") if f_g => {
                 log::debug!("Processing prof command for {}", cn);
                 God::set_flag(self, cn, arg_get(1), CharacterFlags::Profile.bits());
@@ -1469,6 +1558,16 @@ impl GameState {
                 God::goto(self, cn, cn, "512", "512");
                 return;
             }
+            Some("refresh") => {
+                log::debug!("Processing refresh command for {}", cn);
+                self.do_refresh(cn);
+                return;
+            }
+            Some("reload") if f_g => {
+                log::debug!("Processing reload command for {}", cn);
+                God::reload_templates(self, cn, arg_get(1));
+                return;
+            }
             Some("respawn") if f_giu => {
                 log::debug!("Processing respawn command for {}", cn);
                 self.do_respawn(cn, parse_usize(arg_get(1)));
@@ -1489,6 +1588,11 @@ impl GameState {
                 self.do_shout(cn, args_get(0));
                 return;
             }
+            Some("scripts") if f_g => {
+                log::debug!("Processing scripts command for {}", cn);
+                self.do_scripts(cn, arg_get(1));
+                return;
+            }
             Some("safe") if f_g => {
                 log::debug!("Processing safe command for {}", cn);
                 God::set_flag(self, cn, arg_get(1), CharacterFlags::Safe.bits());
@@ -1590,6 +1694,11 @@ impl GameState {
                 self.do_talents(cn);
                 return;
             }
+            Some("title") if f_p => {
+                log::debug!("Processing title command for {}", cn);
+                self.do_title(cn, args_get(0));
+                return;
+            }
             Some("tell") => {
                 log::debug!("Processing tell command for {}", cn);
                 self.do_tell(cn, arg_get(1), args_get(1));
@@ -1696,7 +1805,7 @@ impl GameState {
             c_string_to_str(&self.characters[cn].name)
         );
 
-        self.characters[cn].gold += PLAYTEST_BLING_GOLD * 100;
+        self.transfer_gold(0, cn, PLAYTEST_BLING_GOLD * 100, GoldTransferKind::GmGrant);
         self.characters[cn].set_do_update_flags();
         self.do_character_log(
             cn,
@@ -1873,6 +1982,7 @@ mod tests {
         test_helpers::{add_test_player, with_test_gs},
         tls::GameStream,
     };
+    use core::chat::{ChatChannel, ChatStyle};
     use core::server_commands::ServerCommandType;
     use core::{
         skills::{Attribute, SK_WEAPON, SkillIndex},
@@ -1899,6 +2009,8 @@ mod tests {
             let log_start = ServerCommandType::Log0 as u8;
             if (log_start..=log_start + 3).contains(&packet[0]) {
                 bytes.extend(packet[1..].iter().copied().filter(|b| *b != 0));
+            } else if packet[0] == ServerCommandType::LogStyled as u8 {
+                bytes.extend(packet[2..].iter().copied().filter(|b| *b != 0));
             }
         }
         String::from_utf8_lossy(&bytes).into_owned()
@@ -2089,4 +2201,36 @@ mod tests {
         assert_eq!(match_command("quest"), Some("quest"));
         assert_eq!(match_command("QUEST"), Some("quest"));
     }
+
+    #[test]
+    fn match_command_title_commands_recognized() {
+        assert_eq!(match_command("title"), Some("title"));
+        assert_eq!(match_command("tit"), Some("title"));
+        assert_eq!(match_command("granttitle"), Some("granttitle"));
+        assert_eq!(match_command("ti"), Some("time"));
+    }
+
+    #[test]
+    fn styled_log_carries_style_byte_in_every_chunk() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            let style = ChatStyle::new(ChatChannel::Shout).with_gm(true);
+
+            gs.do_character_styled_log(cn, style, "Tester shouts: \"Gather at the gate!\"");
+
+            let packets: Vec<&[u8]> = gs.players[nr].tbuf[..gs.players[nr].tptr]
+                .chunks(16)
+                .collect();
+            assert!(packets.len() > 1);
+            for packet in &packets {
+                assert_eq!(packet[0], ServerCommandType::LogStyled as u8);
+                assert_eq!(ChatStyle::from_byte(packet[1]), style);
+            }
+            assert_eq!(
+                logged_text(gs, nr),
+                "Tester shouts: \"Gather at the gate!\"\n"
+            );
+        });
+    }
 }
@@ -1,7 +1,10 @@
 use core::constants::{CharacterFlags, ItemFlags, TICKS};
+use core::economy_store::TradeSide;
+use core::item_binding::Transfer;
 use core::skills;
 use core::string_operations::c_string_to_str;
 use core::types::FontColor;
+use server::journal::GoldTransferKind;
 
 use crate::driver;
 use crate::game_state::GameState;
@@ -87,6 +90,9 @@ impl GameState {
             }
         }
 
+        // Corpse item being taken, if any.
+        let mut looted = 0usize;
+
         // Handle selling to merchant (player has citem)
         let citem = self.characters[cn].citem;
 
@@ -127,6 +133,10 @@ impl GameState {
                 accepts = true;
             }
 
+            if self.refuse_transfer(cn, item_idx, Transfer::Sell) {
+                return;
+            }
+
             if !accepts {
                 let merchant_name = self.characters[co].get_name().to_owned();
                 self.do_character_log(
@@ -154,8 +164,10 @@ impl GameState {
             }
 
             // Complete the sale
+            // Merchants are not charged for what they buy.
             self.characters[cn].citem = 0;
             self.characters[cn].gold += price;
+            self.record_gold_transfer(co, cn, price, GoldTransferKind::ShopSell);
 
             // Transfer item to merchant
             if !God::give_character_item(self, co, item_idx) {
@@ -190,6 +202,8 @@ impl GameState {
                 ),
             );
 
+            self.note_market_trade(co, item_idx, TradeSide::Sell, price);
+
             // Update item template statistics
             let temp_id = self.items[item_idx].temp as usize;
             if temp_id > 0 && temp_id < core::constants::MAXTITEM {
@@ -203,6 +217,24 @@ impl GameState {
         } else {
             // Handle buying/taking/examining items
             if nr < 62 {
+                // Freshly dropped grave loot is reserved for its owner's group.
+                if is_body {
+                    looted = match nr {
+                        0..=39 => self.characters[co].item[nr as usize] as usize,
+                        40..=59 => self.characters[co].worn[(nr - 40) as usize] as usize,
+                        60 => self.characters[co].citem as usize,
+                        _ => 0,
+                    };
+                    if looted >= self.items.len() {
+                        looted = 0;
+                    }
+                    if looted != 0
+                        && (!self.check_loot_access(cn, looted)
+                            || !self.check_bound_access(cn, looted))
+                    {
+                        return;
+                    }
+                }
                 // Buying or taking items
                 if nr < 40 {
                     // Inventory slot
@@ -238,9 +270,9 @@ impl GameState {
                         let gave_success = God::give_character_item(self, cn, item_idx);
 
                         if gave_success {
+                            self.bind_item(cn, item_idx, ItemFlags::IF_BIND_PICKUP);
                             if is_merchant {
-                                self.characters[cn].gold -= price;
-                                self.characters[co].gold += price;
+                                self.transfer_gold(cn, co, price, GoldTransferKind::ShopBuy);
 
                                 let item_name = self.items[item_idx].get_name().to_owned();
                                 let item_ref =
@@ -265,6 +297,8 @@ impl GameState {
                                     ),
                                 );
 
+                                self.note_market_trade(co, item_idx, TradeSide::Buy, price);
+
                                 // Update template statistics
                                 let temp_id = self.items[item_idx].temp as usize;
                                 if temp_id > 0 && temp_id < core::constants::MAXTITEM {
@@ -324,6 +358,7 @@ impl GameState {
                             let gave_success = God::give_character_item(self, cn, item_idx);
 
                             if gave_success {
+                                self.bind_item(cn, item_idx, ItemFlags::IF_BIND_PICKUP);
                                 let item_name = self.items[item_idx].get_name().to_owned();
                                 let item_ref =
                                     c_string_to_str(&self.items[item_idx].reference).to_owned();
@@ -371,6 +406,7 @@ impl GameState {
                             let gave_success = God::give_character_item(self, cn, item_idx);
 
                             if gave_success {
+                                self.bind_item(cn, item_idx, ItemFlags::IF_BIND_PICKUP);
                                 let item_name = self.items[item_idx].get_name().to_owned();
                                 let item_ref =
                                     c_string_to_str(&self.items[item_idx].reference).to_owned();
@@ -411,8 +447,7 @@ impl GameState {
                         let corpse_gold = self.characters[co].gold;
 
                         if corpse_gold > 0 {
-                            self.characters[cn].gold += corpse_gold;
-                            self.characters[co].gold = 0;
+                            self.transfer_gold(co, cn, corpse_gold, GoldTransferKind::CorpseLoot);
 
                             chlog!(
                                 cn,
@@ -500,6 +535,11 @@ impl GameState {
             }
         }
 
+        // Loot that changed hands is no longer reserved.
+        if looted != 0 && self.items[looted].carried == cn as u16 {
+            core::loot::release(&mut self.items[looted]);
+        }
+
         // Update merchant shop display if applicable
         if is_merchant {
             driver::update_shop(self, co);
@@ -1,7 +1,9 @@
 use crate::game_state::GameState;
 use crate::god::God;
 use crate::network_manager;
+use crate::player::item_links;
 use crate::{driver, helpers};
+use core::chat::{ChatChannel, ChatStyle};
 use core::constants::{CT_LGUARD, CharacterFlags};
 use core::server_commands::ServerCommandType;
 use core::string_operations::c_string_to_str;
@@ -444,6 +446,7 @@ impl GameState {
 
         // Send SV_LOOK2 packet
         buf[0] = ServerCommandType::Look2 as u8;
+        buf[15] = self.name_color(co).as_u8();
 
         if visibility <= 75 {
             let worn9 = if self.characters[co].worn[9] != 0 {
@@ -498,6 +501,7 @@ impl GameState {
 
         // Send SV_LOOK3 packet
         buf[0] = ServerCommandType::Look3 as u8;
+        buf[15] = self.selected_title(co);
 
         let end5 = self.characters[co].end[5];
         let a_hp = self.characters[co].a_hp;
@@ -970,6 +974,10 @@ impl GameState {
             return;
         }
 
+        // Backpack items linked with slot tokens
+        let linked = item_links::link_slot_tokens(self, cn, text);
+        let text: &str = &linked;
+
         if text.starts_with('#') || text.starts_with('/') {
             self.do_command(cn, &text[1..]);
             return;
@@ -1044,7 +1052,14 @@ impl GameState {
             self.do_area_say1(cn, cx, cy, ptr);
         } else {
             let msg = format!("{:.30}: \"{}\"\n", name, ptr);
-            self.do_area_log(0, 0, cx as i32, cy as i32, FontColor::Red, &msg);
+            self.do_area_styled_log(
+                0,
+                0,
+                cx as i32,
+                cy as i32,
+                ChatStyle::new(ChatChannel::Npc),
+                &msg,
+            );
         }
 
         if m_val == 4 {
@@ -1145,16 +1160,19 @@ impl GameState {
             );
             return;
         }
-        let buf = if cn_invis && cn_invis_level > co_invis_level {
+        let anonymous = cn_invis && cn_invis_level > co_invis_level;
+        let buf = if anonymous {
             format!("Somebody tells you: \"{:.200}\"\n", text)
         } else {
             format!("{} tells you: \"{:.200}\"\n", cn_name, text)
         };
-        self.do_character_log(co, core::types::FontColor::Yellow, &buf);
+        let tell_style =
+            ChatStyle::new(ChatChannel::Tell).with_gm(!anonymous && self.is_gm_speaker(cn));
+        self.do_character_styled_log(co, tell_style, &buf);
         // ccp_tell omitted
-        self.do_character_log(
+        self.do_character_styled_log(
             cn,
-            core::types::FontColor::Yellow,
+            ChatStyle::new(ChatChannel::Tell),
             &format!("Told {}: \"{:.200}\"\n", co_name, text),
         );
         if cn == co {
@@ -1200,9 +1218,9 @@ impl GameState {
                     self.characters[cn].data[n] = 0;
                 } else {
                     let name = self.characters[cn].get_name().to_owned();
-                    self.do_character_log(
+                    self.do_character_styled_log(
                         co,
-                        core::types::FontColor::Green,
+                        ChatStyle::new(ChatChannel::Group),
                         &format!("{} group-tells: \"{}\"\n", name, text),
                     );
                     found = true;
@@ -1210,9 +1228,9 @@ impl GameState {
             }
         }
         if found {
-            self.do_character_log(
+            self.do_character_styled_log(
                 cn,
-                core::types::FontColor::Green,
+                ChatStyle::new(ChatChannel::Group),
                 &format!("Told the group: \"{}\"\n", text),
             );
             if (self.characters[cn].flags & CharacterFlags::Player.bits()) != 0 {
@@ -1244,8 +1262,8 @@ impl GameState {
             return;
         }
         let name = self.characters[cn].get_name().to_owned();
-        self.do_staff_log(
-            core::types::FontColor::Blue,
+        self.do_staff_chat(
+            ChatStyle::new(ChatChannel::Staff),
             &format!("{:.30} staff-tells: \"{:.200}\"\n", name, text),
         );
         if (self.characters[cn].flags & CharacterFlags::Player.bits()) != 0 {
@@ -1272,14 +1290,14 @@ impl GameState {
         if (self.characters[cn].flags & CharacterFlags::Usurp.bits()) != 0 {
             // simplified
             let name = self.characters[cn].get_name().to_owned();
-            self.do_imp_log(
-                core::types::FontColor::Blue,
+            self.do_imp_chat(
+                ChatStyle::new(ChatChannel::Imp),
                 &format!("{:.30} (usurp) imp-tells: \"{:.170}\"\n", name, text),
             );
         } else {
             let name = self.characters[cn].get_name().to_owned();
-            self.do_imp_log(
-                core::types::FontColor::Blue,
+            self.do_imp_chat(
+                ChatStyle::new(ChatChannel::Imp),
                 &format!("{:.30} imp-tells: \"{:.200}\"\n", name, text),
             );
         }
@@ -1317,7 +1335,9 @@ impl GameState {
             return;
         }
         self.characters[cn].a_end -= 50000;
-        let buf = if (self.characters[cn].flags & CharacterFlags::Invisible.bits()) != 0 {
+        let invis = (self.characters[cn].flags & CharacterFlags::Invisible.bits()) != 0;
+        let style = ChatStyle::new(ChatChannel::Shout).with_gm(!invis && self.is_gm_speaker(cn));
+        let buf = if invis {
             format!("Somebody shouts: \"{}\"\n", text)
         } else {
             let name = self.characters[cn].get_name().to_owned();
@@ -1331,7 +1351,7 @@ impl GameState {
                 || self.characters[n].temp == CT_LGUARD as u16)
                 && self.characters[n].used == core::constants::USE_ACTIVE;
             if send {
-                self.do_character_log(n, core::types::FontColor::Blue, &buf);
+                self.do_character_styled_log(n, style, &buf);
             }
         }
         if (self.characters[cn].flags & CharacterFlags::Player.bits()) != 0 {
@@ -1,12 +1,15 @@
 use core::constants::{CHD_CORPSEOWNER, CharacterFlags, MAXCHARS, USE_EMPTY};
+use core::stream_overlay_store::{MAX_RECENT_KILLS, OverlayKill};
 use core::types::{Character, FontColor};
-use core::{skills, traits};
+use core::{item_binding, skills, traits};
+use server::journal::GoldTransferKind;
 
 use crate::effect::EffectManager;
 use crate::god::God;
 use crate::{helpers, player};
 
 use crate::game_state::GameState;
+use crate::types::server_player::ServerPlayer;
 
 impl GameState {
     /// Port of `do_character_killed(character_id, killer_id)` from the original
@@ -71,6 +74,12 @@ impl GameState {
         } else {
             log::info!("Character {} died", character_id);
         }
+        if killer_id != 0
+            && killer_id != character_id
+            && self.characters[killer_id].flags & CharacterFlags::Player.bits() != 0
+        {
+            self.note_overlay_kill(killer_id, character_id);
+        }
 
         // Get map flags for both characters
         let (co_x, co_y, co_temp, co_sound) = {
@@ -314,6 +323,15 @@ impl GameState {
             // Update player death statistics
             self.globals.players_died += 1;
 
+            // Remember where they fell for the minimap marker.
+            let nr = self.characters[character_id].player as usize;
+            if nr != 0 && ServerPlayer::is_sane_player(nr) {
+                self.players[nr].death_marker = (
+                    self.characters[character_id].x as u16,
+                    self.characters[character_id].y as u16,
+                );
+            }
+
             // Adjust luck if negative
             if self.characters[character_id].luck < 0 {
                 self.characters[character_id].luck =
@@ -444,6 +462,9 @@ impl GameState {
 
         // Drop items and money based on wimp chance
         self.handle_item_drops(co, cc, wimp as i32, cn, force_save);
+        if !force_save {
+            self.protect_grave_loot(cc, self.loot_owner_for_kill(co, cn));
+        }
 
         if force_save {
             let (cc_x, cc_y) = (self.characters[cc].x, self.characters[cc].y);
@@ -627,6 +648,7 @@ impl GameState {
         } else {
             self.characters[co].data[CHD_CORPSEOWNER] = 0;
         }
+        self.protect_grave_loot(co, self.loot_owner_for_kill(co, cn));
 
         self.characters[co].data[99] = 0;
         self.characters[co].data[98] = 0;
@@ -710,6 +732,7 @@ impl GameState {
     /// - Gold may be dropped based on `wimp` chance
     /// - Inventory, carried, and worn items are considered for dropping or keeping
     /// - Respects `do_maygive` to determine whether an item can be transferred to killer
+    /// - Quest and bound items always stay with the dead character
     /// - Active spells are always destroyed on death
     ///
     /// # Arguments
@@ -745,7 +768,10 @@ impl GameState {
         // Handle gold
         if self.characters[co].gold != 0 {
             if wimp < helpers::random_mod_i32(100) {
+                // The grave already holds a copy of the purse.
+                let gold = self.characters[co].gold;
                 self.characters[co].gold = 0;
+                self.record_gold_transfer(co, cc, gold, GoldTransferKind::DeathLoss);
             } else {
                 self.characters[cc].gold = 0;
             }
@@ -758,6 +784,12 @@ impl GameState {
                 continue;
             }
 
+            // Quest and bound items stay with their carrier.
+            if item_binding::is_restricted(&self.items[item_idx as usize]) {
+                self.characters[cc].item[n] = 0;
+                continue;
+            }
+
             // Check if item may be given
             if !self.do_maygive(cn, 0, item_idx as usize) {
                 if (item_idx as usize) < self.items.len() {
@@ -789,7 +821,12 @@ impl GameState {
 
         // Handle carried item (citem)
         let citem = self.characters[co].citem;
-        if citem != 0 {
+        if citem != 0
+            && citem & 0x80000000 == 0
+            && item_binding::is_restricted(&self.items[citem as usize])
+        {
+            self.characters[cc].citem = 0;
+        } else if citem != 0 {
             if !self.do_maygive(cn, 0, citem as usize) {
                 if (citem as usize) < self.items.len() {
                     self.items[citem as usize].used = USE_EMPTY;
@@ -821,6 +858,11 @@ impl GameState {
                 continue;
             }
 
+            if item_binding::is_restricted(&self.items[item_idx as usize]) {
+                self.characters[cc].worn[n] = 0;
+                continue;
+            }
+
             if !self.do_maygive(cn, 0, item_idx as usize) {
                 if (item_idx as usize) < self.items.len() {
                     self.items[item_idx as usize].used = USE_EMPTY;
@@ -914,6 +956,24 @@ impl GameState {
         }
     }
 
+    /// Remember a kill made by player `killer` for its stream overlay.
+    ///
+    /// Only the newest [`MAX_RECENT_KILLS`] kills per character are kept.
+    ///
+    /// # Arguments
+    /// * `killer` - The player character that made the kill
+    /// * `victim` - The character that died
+    fn note_overlay_kill(&mut self, killer: usize, victim: usize) {
+        let kill = OverlayKill {
+            victim: self.characters[victim].get_name().to_owned(),
+            victim_is_player: self.characters[victim].flags & CharacterFlags::Player.bits() != 0,
+            unix_secs: crate::wall_clock::unix_now().max(0) as u64,
+        };
+        let kills = self.overlay_kills.entry(killer).or_default();
+        kills.push_front(kill);
+        kills.truncate(MAX_RECENT_KILLS);
+    }
+
     /// On-death helper for the Contagion DoT. If the dying character carries
     /// an active Contagion spell-item, this spreads a fresh Contagion to up
     /// to four adjacent enemies (8-neighborhood). Each spread carries the
@@ -1100,3 +1160,38 @@ impl GameState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::constants::{CHD_CORPSEOWNER, CharacterFlags, USE_EMPTY};
+
+    use crate::testutil::{CharSpec, ItemSpec, World};
+
+    #[test]
+    fn killed_npc_becomes_owned_body_and_loses_spells() {
+        World::new()
+            .with_char(CharSpec::player("Hero"))
+            .with_char(
+                CharSpec::npc("Goblin")
+                    .carrying(ItemSpec::new("Pouch"))
+                    .with_spell(ItemSpec::new("Bless")),
+            )
+            .run(|gs, w| {
+                let (hero, goblin) = (w.char(0), w.char(1));
+                let pouch = gs.characters[goblin].item[0];
+                let bless = gs.characters[goblin].spell[0] as usize;
+
+                gs.do_character_killed(goblin, hero, false);
+
+                let body = &gs.characters[goblin];
+                assert_ne!(body.flags & CharacterFlags::Body.bits(), 0);
+                assert_eq!(body.a_hp, 0);
+                assert_eq!(body.data[CHD_CORPSEOWNER], hero as i32);
+                assert_eq!(body.item[0], pouch);
+                assert_eq!(body.spell[0], 0);
+                assert_eq!(gs.items[bless].used, USE_EMPTY);
+                assert_eq!(gs.globals.npcs_died, 1);
+                assert_eq!(gs.overlay_kills[&hero][0].victim, "Goblin");
+            });
+    }
+}
@@ -1,10 +1,44 @@
 use core::constants::TICKS;
+use core::economy_store::{MAX_RECENT_TRADES, Trade, TradeSide};
 use core::ranks;
+use server::journal::GoldTransferKind;
 
 use crate::game_state::GameState;
 use crate::god::God;
 
 impl GameState {
+    /// Remember a merchant trade for the public economy report.
+    ///
+    /// Only the item and merchant are recorded, never the player. The queue
+    /// is drained by the economy publisher; if it is not running the oldest
+    /// trades are dropped so the queue cannot grow without bound.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant character traded with
+    /// * `item_idx` - The item that changed hands
+    /// * `side` - Whether the player bought or sold the item
+    /// * `price` - Price paid in silver
+    pub(crate) fn note_market_trade(
+        &mut self,
+        merchant: usize,
+        item_idx: usize,
+        side: TradeSide,
+        price: i32,
+    ) {
+        let trade = Trade {
+            unix_secs: crate::wall_clock::unix_now().max(0) as u64,
+            template_id: u32::from(self.items[item_idx].temp),
+            item_name: self.items[item_idx].get_name().to_owned(),
+            merchant: self.characters[merchant].get_name().to_owned(),
+            side,
+            price,
+        };
+        if self.market_trades.len() >= MAX_RECENT_TRADES * 10 {
+            self.market_trades.remove(0);
+        }
+        self.market_trades.push(trade);
+    }
+
     /// Port of `do_balance(int cn)` from `svr_do.cpp`
     ///
     /// Display character's bank balance.
@@ -111,8 +145,8 @@ impl GameState {
             );
             return;
         }
-        self.characters[cn].gold += v;
         self.characters[cn].data[13] -= v;
+        self.transfer_gold(0, cn, v, GoldTransferKind::BankWithdraw);
         self.do_update_char(cn);
         let newbal = self.characters[cn].data[13];
         self.do_character_log(
@@ -171,7 +205,7 @@ impl GameState {
             );
             return;
         }
-        self.characters[cn].gold -= v;
+        self.transfer_gold(cn, 0, v, GoldTransferKind::BankDeposit);
         self.characters[cn].data[13] += v;
         self.do_update_char(cn);
         let newbal = self.characters[cn].data[13];
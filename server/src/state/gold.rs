@@ -0,0 +1,235 @@
+//! Gold transfers and the `#clawback` god command.
+//!
+//! Code that moves gold between characters, or between a character and the
+//! world, goes through [`GameState::transfer_gold`] or
+//! [`GameState::record_gold_transfer`] so that every movement lands in the
+//! world journal as a `GoldTransfer` record. `world-snapshot trace-gold`
+//! reads those records back to follow gold after an exploit, and
+//! `#clawback` removes the proceeds without editing KeyDB by hand.
+
+use core::types::FontColor;
+use server::journal::GoldTransferKind;
+
+use crate::game_state::GameState;
+
+impl GameState {
+    /// Moves carried gold from one character to another and journals it.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Paying character; `0` creates the gold.
+    /// * `to` - Receiving character; `0` destroys the gold.
+    /// * `amount` - Amount in silver.
+    /// * `kind` - How the gold moved.
+    ///
+    /// # Returns
+    ///
+    /// * `false` without changing anything when `amount` is not positive or
+    ///   `from` carries less than `amount`.
+    pub(crate) fn transfer_gold(
+        &mut self,
+        from: usize,
+        to: usize,
+        amount: i32,
+        kind: GoldTransferKind,
+    ) -> bool {
+        if amount <= 0 || (from != 0 && self.characters[from].gold < amount) {
+            return false;
+        }
+        if from != 0 {
+            self.characters[from].gold -= amount;
+        }
+        if to != 0 {
+            self.characters[to].gold += amount;
+        }
+        self.record_gold_transfer(from, to, amount, kind);
+        true
+    }
+
+    /// Journals a gold movement whose balances the caller adjusts itself,
+    /// e.g. gold held on the cursor or lying on the ground as a money item.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Paying character; `0` is the world.
+    /// * `to` - Receiving character; `0` is the world.
+    /// * `amount` - Amount in silver.
+    /// * `kind` - How the gold moved.
+    pub(crate) fn record_gold_transfer(
+        &mut self,
+        from: usize,
+        to: usize,
+        amount: i32,
+        kind: GoldTransferKind,
+    ) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.note_gold_transfer(from, to, amount, kind);
+        }
+    }
+
+    /// Handles `#clawback <player> <gold> [silver]`.
+    ///
+    /// Removes up to the given amount from the player's carried gold and then
+    /// from their bank account, journaling what was taken as a
+    /// [`GoldTransferKind::Clawback`].
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - God issuing the command.
+    /// * `target` - Player name or number.
+    /// * `gold` - Gold to remove.
+    /// * `silver` - Additional silver to remove.
+    pub(crate) fn do_clawback(&mut self, cn: usize, target: &str, gold: u32, silver: u32) {
+        let co = self.do_lookup_char_self(target, cn);
+        if co <= 0 || !self.characters[co as usize].is_player() {
+            self.do_character_log(
+                cn,
+                FontColor::Red,
+                &format!("No such player: '{}'\n", target),
+            );
+            return;
+        }
+        let co = co as usize;
+        let wanted = (i64::from(gold) * 100 + i64::from(silver)).min(i64::from(i32::MAX)) as i32;
+        if wanted <= 0 {
+            self.do_character_log(
+                cn,
+                FontColor::Red,
+                "Usage: #clawback <player> <gold> [silver]\n",
+            );
+            return;
+        }
+
+        let from_purse = wanted.min(self.characters[co].gold.max(0));
+        if from_purse > 0 {
+            self.transfer_gold(co, 0, from_purse, GoldTransferKind::Clawback);
+        }
+        let from_bank = (wanted - from_purse).min(self.characters[co].data[13].max(0));
+        if from_bank > 0 {
+            self.characters[co].data[13] -= from_bank;
+            self.record_gold_transfer(co, 0, from_bank, GoldTransferKind::Clawback);
+        }
+        self.characters[co].set_do_update_flags();
+
+        let taken = from_purse + from_bank;
+        let name = self.characters[co].get_name().to_owned();
+        self.do_character_log(
+            cn,
+            FontColor::Green,
+            &format!(
+                "Clawed back {}G {}S from {} ({}G {}S short).\n",
+                taken / 100,
+                taken % 100,
+                name,
+                (wanted - taken) / 100,
+                (wanted - taken) % 100
+            ),
+        );
+        log::info!(
+            "Character {} clawed back {} silver from {} ({}): {} carried, {} banked",
+            cn,
+            taken,
+            co,
+            name,
+            from_purse,
+            from_bank
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+    use server::journal::recorder::JournalRecorder;
+    use server::journal::{GoldTransferKind, JournalRecord, read_journal};
+
+    #[test]
+    fn transfer_refuses_overdraft() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].gold = 50;
+            assert!(!gs.transfer_gold(cn, 0, 60, GoldTransferKind::ShopBuy));
+            assert_eq!(gs.characters[cn].gold, 50);
+            assert!(gs.transfer_gold(cn, 0, 50, GoldTransferKind::ShopBuy));
+            assert_eq!(gs.characters[cn].gold, 0);
+            assert!(gs.transfer_gold(0, cn, 5, GoldTransferKind::GmGrant));
+            assert_eq!(gs.characters[cn].gold, 5);
+        });
+    }
+
+    #[test]
+    fn clawback_takes_purse_then_bank() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].gold = 300;
+            gs.characters[cn].data[13] = 1_000;
+            gs.do_clawback(cn, "self", 10, 0);
+            assert_eq!(gs.characters[cn].gold, 0);
+            assert_eq!(gs.characters[cn].data[13], 300);
+
+            gs.do_clawback(cn, "self", 50, 0);
+            assert_eq!(gs.characters[cn].data[13], 0);
+        });
+    }
+
+    #[test]
+    fn journal_balances_with_carried_gold() {
+        let dir = std::env::temp_dir().join(format!("mag-gold-journal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gold.mjl");
+        let _ = std::fs::remove_file(&path);
+
+        with_test_gs(move |gs| {
+            let (cn, _) = add_test_player(gs);
+            // add_test_player always fills slot 1.
+            let other = cn + 1;
+            gs.characters[other].used = USE_ACTIVE;
+            let mut recorder = JournalRecorder::open(&path).unwrap();
+            recorder.record_tick(1, &gs.characters, &gs.items);
+            gs.journal = Some(recorder);
+            let start = gs.characters[cn].gold;
+
+            for template in [31, 57, 59, 101] {
+                gs.item_templates[template].used = USE_ACTIVE;
+            }
+            gs.grant_starter_kit(cn).unwrap();
+            let coin = gs.items.len() - 1;
+            gs.items[coin].used = USE_ACTIVE;
+            gs.items[coin].data[0] = 2;
+            assert!(crate::driver::use_create_gold(gs, cn, coin));
+            gs.characters[cn].data[27] = 3;
+            crate::talk::answer_buygold(gs, other, cn);
+            assert!(gs.transfer_gold(cn, other, 150, GoldTransferKind::Give));
+            gs.do_clawback(cn, "self", 1, 0);
+            let m = gs.characters[cn].x as usize
+                + gs.characters[cn].y as usize * core::constants::SERVER_MAPX as usize;
+            gs.map[m].flags |= u64::from(core::constants::MF_BANK);
+            gs.do_deposit(cn, 0, 90);
+            gs.do_withdraw(cn, 0, 40);
+
+            let journal = gs.journal.as_mut().unwrap();
+            journal.record_tick(2, &gs.characters, &gs.items);
+            let net: i32 = read_journal(&path)
+                .unwrap()
+                .iter()
+                .map(|record| match *record {
+                    JournalRecord::GoldTransfer {
+                        from, to, amount, ..
+                    } => {
+                        i32::from(to as usize == cn) * amount
+                            - i32::from(from as usize == cn) * amount
+                    }
+                    _ => 0,
+                })
+                .sum();
+            assert_eq!(
+                gs.characters[cn].gold - start,
+                500 + 200 + 300 - 150 - 100 - 90 + 40
+            );
+            assert_eq!(gs.characters[other].gold, 150);
+            assert_eq!(net, gs.characters[cn].gold - start);
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,8 +3,10 @@ use crate::god::God;
 
 use crate::game_state::GameState;
 use core::constants::{CharacterFlags, ItemFlags};
+use core::item_binding::Transfer;
 use core::string_operations::c_string_to_str;
 use core::types::FontColor;
+use server::journal::GoldTransferKind;
 use std::cmp::Ordering;
 
 impl GameState {
@@ -287,6 +289,7 @@ impl GameState {
             // Transfer gold
             self.characters[co].gold += gold_amount as i32;
             self.characters[cn].citem = 0;
+            self.record_gold_transfer(cn, co, gold_amount as i32, GoldTransferKind::Give);
 
             // Log messages
             let cn_name = self.characters[cn].get_name().to_owned();
@@ -346,6 +349,12 @@ impl GameState {
             return false;
         }
 
+        // Quest and bound items may still be handed to NPCs (quest turn-ins).
+        if self.characters[co].is_player() && self.refuse_transfer(cn, item_idx, Transfer::Give) {
+            self.characters[cn].misc_action = core::constants::DR_IDLE as u16;
+            return false;
+        }
+
         // Log the give action
         let item_name = self.items[item_idx].get_name().to_owned();
         let co_name = self.characters[co].get_name().to_owned();
@@ -433,6 +442,8 @@ impl GameState {
             );
         }
 
+        self.bind_item(co, item_idx, ItemFlags::IF_BIND_PICKUP);
+
         // Notify receiver
         self.do_notify_character(
             co as u32,
@@ -554,6 +565,8 @@ impl GameState {
                 }
             }
 
+            self.describe_binding(cn, item_idx);
+
             // Show god-mode info
             let is_god = self.characters[cn].flags & CharacterFlags::God.bits() != 0;
 
@@ -709,3 +722,44 @@ impl GameState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::{CharSpec, ItemSpec, World};
+
+    #[test]
+    fn give_uses_empty_cursor_then_inventory() {
+        World::new()
+            .with_char(CharSpec::player("Hero").holding(ItemSpec::new("Sword")))
+            .with_char(CharSpec::npc("Squire"))
+            .with_char(CharSpec::npc("Porter").holding(ItemSpec::new("Shield")))
+            .run(|gs, w| {
+                let (hero, squire, porter) = (w.char(0), w.char(1), w.char(2));
+                let sword = gs.characters[hero].citem as usize;
+
+                assert!(gs.do_give(hero, squire));
+                assert_eq!(gs.characters[hero].citem, 0);
+                assert_eq!(gs.characters[squire].citem, sword as u32);
+                assert_eq!(gs.items[sword].carried, squire as u16);
+
+                assert!(gs.do_give(squire, porter));
+                assert_eq!(gs.characters[squire].citem, 0);
+                assert_eq!(gs.characters[porter].item[0], sword as u32);
+                assert_eq!(gs.items[sword].carried, porter as u16);
+            });
+    }
+
+    #[test]
+    fn give_without_cursor_item_fails() {
+        World::new()
+            .with_char(CharSpec::player("Hero"))
+            .with_char(CharSpec::npc("Squire"))
+            .run(|gs, w| {
+                assert!(!gs.do_give(w.char(0), w.char(1)));
+                assert_eq!(
+                    gs.characters[w.char(0)].cerrno,
+                    core::constants::ERR_FAILED as u16
+                );
+            });
+    }
+}
@@ -0,0 +1,150 @@
+//! Enforcement of quest and bind-on-pickup/equip items.
+//!
+//! The rules live in [`core::item_binding`]. The transfer points call in
+//! here: `plr_drop`, `do_give`, the merchant and corpse paths of
+//! `do_shop_char`, `plr_pickup`, `do_swap_item` and `handle_item_drops`.
+//! Depots are left open: storing an item keeps it with its owner. `#mail
+//! parcel` refuses bound items like `do_give` does.
+
+use core::constants::ItemFlags;
+use core::item_binding::{self, Transfer};
+use core::types::FontColor;
+
+use crate::game_state::GameState;
+
+impl GameState {
+    /// Refuses a transfer of a quest or bound item and tells `cn` why.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character giving up the item.
+    /// * `item_idx` - Item being transferred.
+    /// * `kind` - How the item would leave `cn`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the transfer must not happen.
+    pub(crate) fn refuse_transfer(&mut self, cn: usize, item_idx: usize, kind: Transfer) -> bool {
+        let Some(message) = item_binding::transfer_refusal(&self.items[item_idx], kind) else {
+            return false;
+        };
+        self.do_character_log(cn, FontColor::Red, &message);
+        log::info!(
+            "Character {} tried to move restricted item {} ({:?})",
+            cn,
+            self.items[item_idx].get_name(),
+            kind
+        );
+        true
+    }
+
+    /// Refuses taking an item that is bound to someone else.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character taking the item.
+    /// * `item_idx` - Item being taken.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the take may proceed.
+    pub(crate) fn check_bound_access(&mut self, cn: usize, item_idx: usize) -> bool {
+        if item_binding::may_possess(&self.items[item_idx], cn) {
+            return true;
+        }
+        self.do_character_log(cn, FontColor::Red, "That is bound to someone else.\n");
+        false
+    }
+
+    /// Binds an item a player just acquired or equipped, if it binds on
+    /// `trigger`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - New carrier; NPCs never bind items.
+    /// * `item_idx` - Item acquired.
+    /// * `trigger` - `IF_BIND_PICKUP` or `IF_BIND_EQUIP`.
+    pub(crate) fn bind_item(&mut self, cn: usize, item_idx: usize, trigger: ItemFlags) {
+        if item_idx == 0 || item_idx >= self.items.len() || !self.characters[cn].is_player() {
+            return;
+        }
+        if !item_binding::bind_on(&mut self.items[item_idx], cn, trigger) {
+            return;
+        }
+        let name = self.items[item_idx].get_name().to_owned();
+        self.do_character_log(
+            cn,
+            FontColor::Yellow,
+            &format!("{} is now bound to you.\n", name),
+        );
+        log::info!("Character {} bound {}", cn, name);
+    }
+
+    /// Tells `cn` whether an item they look at is a quest item or bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character looking at the item.
+    /// * `item_idx` - Item looked at.
+    pub(crate) fn describe_binding(&mut self, cn: usize, item_idx: usize) {
+        let item = &self.items[item_idx];
+        let text = if item.flags & ItemFlags::IF_QUEST.bits() != 0 {
+            "It is a quest item.\n".to_owned()
+        } else if let Some(owner) = item_binding::bound_owner(item) {
+            format!("It is bound to {}.\n", self.characters[owner].get_name())
+        } else if item.flags & ItemFlags::IF_BIND_PICKUP.bits() != 0 {
+            "It binds when picked up.\n".to_owned()
+        } else if item.flags & ItemFlags::IF_BIND_EQUIP.bits() != 0 {
+            "It binds when equipped.\n".to_owned()
+        } else {
+            return;
+        };
+        self.do_character_log(cn, FontColor::Yellow, &text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::{CharacterFlags, USE_ACTIVE};
+
+    #[test]
+    fn bound_items_cannot_be_given_to_other_players() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            let co = 2;
+            gs.characters[co].used = USE_ACTIVE;
+            gs.characters[co].flags = CharacterFlags::Player.bits();
+
+            let item = 5;
+            gs.items[item].used = USE_ACTIVE;
+            gs.items[item].flags = ItemFlags::IF_BIND_PICKUP.bits();
+            gs.bind_item(cn, item, ItemFlags::IF_BIND_PICKUP);
+            assert!(!gs.check_bound_access(co, item));
+
+            gs.characters[cn].citem = item as u32;
+            assert!(!gs.do_give(cn, co));
+            assert_eq!(gs.characters[cn].citem, item as u32);
+            assert_eq!(gs.characters[co].citem, 0);
+        });
+    }
+
+    #[test]
+    fn quest_items_survive_death() {
+        with_test_gs(|gs| {
+            let (co, _) = add_test_player(gs);
+            let grave = 3;
+            let quest = 5;
+            gs.items[quest].used = USE_ACTIVE;
+            gs.items[quest].flags = ItemFlags::IF_QUEST.bits();
+            gs.characters[co].item[0] = quest as u32;
+            gs.characters[grave].item[0] = quest as u32;
+
+            gs.handle_item_drops(co, grave, 0, 0, false);
+            assert_eq!(gs.characters[co].item[0], quest as u32);
+            assert_eq!(gs.characters[grave].item[0], 0);
+            assert_eq!(gs.items[quest].used, USE_ACTIVE);
+        });
+    }
+}
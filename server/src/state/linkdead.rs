@@ -0,0 +1,188 @@
+//! Linkdead grace period for dropped connections.
+//!
+//! When a player's connection drops (socket closed, keepalive timeout) the
+//! character is not pulled out of the world immediately, since that would
+//! let players escape a losing fight by disconnecting and punish players
+//! whose connection merely hiccuped. Instead the character goes linkdead:
+//! it stops acting, other players can no longer target it (NPCs already
+//! fighting it carry on), and it waits up to
+//! [`GameState::linkdead_grace_ticks`] for the player to reconnect. If
+//! nobody does, the character is logged out normally.
+
+use core::constants::{CharacterFlags, MAXCHARS, TICKS, USE_ACTIVE};
+use core::logout_reasons::LogoutReason;
+
+use crate::game_state::GameState;
+use crate::player;
+
+/// Grace period used when `MAG_LINKDEAD_GRACE_SECS` is not set.
+pub(crate) const DEFAULT_LINKDEAD_GRACE_SECS: i32 = 60;
+
+/// Longest grace period `MAG_LINKDEAD_GRACE_SECS` may set (one day); a
+/// linkdead character stays in the world for all of it.
+pub(crate) const MAX_LINKDEAD_GRACE_SECS: i32 = 24 * 60 * 60;
+
+impl GameState {
+    /// Returns whether a character is linkdead.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character index.
+    ///
+    /// # Returns
+    ///
+    /// * `true` while the character awaits a reconnect.
+    pub(crate) fn is_linkdead(&self, cn: usize) -> bool {
+        self.linkdead.contains_key(&cn)
+    }
+
+    /// Detach a character from a dropped connection and start its grace
+    /// period.
+    ///
+    /// Frees the player slot and clears the character's pending actions so
+    /// it stands still until the player reconnects or the grace period ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character controlled by the dropped connection.
+    /// * `nr` - Player slot of the dropped connection.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the character went linkdead.
+    /// * `false` if it is not eligible (not an active, in-world player, or
+    ///   the grace period is disabled); the caller should log out instead.
+    pub(crate) fn go_linkdead(&mut self, cn: usize, nr: usize) -> bool {
+        if self.linkdead_grace_ticks <= 0 || cn == 0 || cn >= MAXCHARS {
+            return false;
+        }
+        let ch = &self.characters[cn];
+        if ch.used != USE_ACTIVE
+            || ch.player != nr as i32
+            || ch.flags & CharacterFlags::Player.bits() == 0
+            || ch.flags & (CharacterFlags::ComputerControlledPlayer | CharacterFlags::Usurp).bits()
+                != 0
+            || self.players[nr].state != core::constants::ST_NORMAL
+        {
+            return false;
+        }
+
+        let ch = &mut self.characters[cn];
+        ch.goto_x = 0;
+        ch.goto_y = 0;
+        ch.misc_action = 0;
+        ch.use_nr = 0;
+        ch.skill_nr = 0;
+        ch.attack_cn = 0;
+
+        let expires_at = self.globals.ticker.wrapping_add(self.linkdead_grace_ticks);
+        self.linkdead.insert(cn, expires_at);
+        player::connection::player_exit(self, nr);
+
+        log::info!(
+            "Character {} ({}) went linkdead; logging out in {}s unless reconnected",
+            cn,
+            self.characters[cn].get_name(),
+            self.linkdead_grace_ticks / TICKS
+        );
+        true
+    }
+
+    /// End a character's linkdead state because its player reconnected.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character index.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the character was linkdead and is still in the world.
+    pub(crate) fn resume_linkdead(&mut self, cn: usize) -> bool {
+        if self.linkdead.remove(&cn).is_none() {
+            return false;
+        }
+        log::info!(
+            "Character {} ({}) reconnected while linkdead",
+            cn,
+            self.characters[cn].get_name()
+        );
+        true
+    }
+
+    /// Log out every linkdead character whose grace period has ended.
+    pub(crate) fn expire_linkdead(&mut self) {
+        let ticker = self.globals.ticker;
+        let expired: Vec<usize> = self
+            .linkdead
+            .iter()
+            .filter(|&(_, &expires_at)| ticker.wrapping_sub(expires_at) >= 0)
+            .map(|(&cn, _)| cn)
+            .collect();
+        for cn in expired {
+            log::info!("Linkdead grace period ended for character {}", cn);
+            self.logout_linkdead(cn);
+        }
+    }
+
+    /// Log out every linkdead character immediately (server shutdown).
+    pub(crate) fn logout_all_linkdead(&mut self) {
+        let all: Vec<usize> = self.linkdead.keys().copied().collect();
+        for cn in all {
+            self.logout_linkdead(cn);
+        }
+    }
+
+    fn logout_linkdead(&mut self, cn: usize) {
+        self.linkdead.remove(&cn);
+        player::connection::plr_logout(self, cn, 0, LogoutReason::IdleTooLong);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn linkdead_character_waits_then_logs_out() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            gs.characters[cn].goto_x = 10;
+            gs.linkdead_grace_ticks = TICKS * 5;
+
+            assert!(gs.go_linkdead(cn, nr));
+            assert!(gs.is_linkdead(cn));
+            assert_eq!(gs.characters[cn].player, 0);
+            assert_eq!(gs.characters[cn].goto_x, 0);
+            assert_eq!(gs.characters[cn].used, USE_ACTIVE);
+
+            gs.globals.ticker += TICKS * 5 - 1;
+            gs.expire_linkdead();
+            assert_eq!(gs.characters[cn].used, USE_ACTIVE);
+
+            gs.globals.ticker += 1;
+            gs.expire_linkdead();
+            assert!(!gs.is_linkdead(cn));
+            assert_eq!(gs.characters[cn].used, core::constants::USE_NONACTIVE);
+        });
+    }
+
+    #[test]
+    fn players_cannot_attack_linkdead_characters() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            gs.linkdead_grace_ticks = TICKS;
+            assert!(gs.go_linkdead(cn, nr));
+
+            let attacker = 2;
+            gs.characters[attacker] = gs.characters[cn];
+            gs.characters[attacker].kindred = core::traits::KIN_PURPLE as i32;
+            gs.characters[cn].kindred = core::traits::KIN_PURPLE as i32;
+            assert!(!gs.may_attack_msg(attacker, cn, false));
+
+            // NPCs are unaffected.
+            gs.characters[attacker].flags &= !CharacterFlags::Player.bits();
+            assert!(gs.may_attack_msg(attacker, cn, false));
+        });
+    }
+}
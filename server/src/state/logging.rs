@@ -1,9 +1,15 @@
-use core::constants::{CT_LGUARD, CharacterFlags, MAXCHARS, MAXPLAYER};
+use core::chat::{ChatChannel, ChatStyle, STYLED_LOG_CHUNK_LEN};
+use core::constants::{
+    CLIENT_CAP_POSITIONAL_SOUND, CT_LGUARD, CharacterFlags, MAXCHARS, MAXPLAYER,
+};
+use core::dialogue::DialogueLine;
 use core::server_commands::ServerCommandType;
+use core::types::PlayerSlot;
 use std::cmp;
 use std::sync::OnceLock;
 
 use crate::game_state::GameState;
+use crate::player::item_links::localize_item_links;
 use crate::talk::npc_hear;
 use crate::types::server_player::ServerPlayer;
 
@@ -46,7 +52,8 @@ impl GameState {
     /// Sends a log message directly to the player's network connection. Long
     /// lines are split into 15-byte chunks and transmitted as `SV_LOG` packets.
     /// Performs validation of the associated player and finds the matching
-    /// player index before sending.
+    /// player index before sending. Item links in the message are prepared
+    /// for the receiving client first.
     ///
     /// # Arguments
     /// * `cn` - Character whose player will receive the message
@@ -76,6 +83,7 @@ impl GameState {
             self.characters[cn].player = 0;
             return;
         }
+        let message = localize_item_links(self, player_number, message);
         let bytes = message.as_bytes();
         let len = bytes.len();
         let mut pos = 0usize;
@@ -102,6 +110,90 @@ impl GameState {
         }
     }
 
+    /// Sends a chat message with styling metadata to a character's player.
+    ///
+    /// Same delivery rules as [`Self::do_character_log`], but the message is
+    /// sent as `SV_LOGSTYLED` so the client can color it by channel and
+    /// highlight GM/emote lines.
+    ///
+    /// # Arguments
+    /// * `character_id` - Character id to receive the message
+    /// * `style` - Channel and presentation flags
+    /// * `message` - The text to send
+    pub(crate) fn do_character_styled_log(
+        &mut self,
+        character_id: usize,
+        style: ChatStyle,
+        message: &str,
+    ) {
+        let ch = &self.characters[character_id];
+        if ch.player == 0 && ch.temp != CT_LGUARD as u16 {
+            return;
+        }
+
+        let message_with_newline = if message.ends_with('\n') {
+            message.to_owned()
+        } else {
+            format!("{}\n", message)
+        };
+
+        self.do_styled_log(character_id, style, &message_with_newline);
+    }
+
+    /// Sends a combat notice to a character's player on
+    /// [`ChatChannel::Combat`], so clients can sort it into a combat tab.
+    ///
+    /// # Arguments
+    /// * `character_id` - Character id to receive the message
+    /// * `message` - The text to send
+    pub(crate) fn do_combat_log(&mut self, character_id: usize, message: &str) {
+        self.do_character_styled_log(character_id, ChatStyle::new(ChatChannel::Combat), message);
+    }
+
+    /// Styled counterpart of [`Self::do_log`]: splits `message` into
+    /// [`STYLED_LOG_CHUNK_LEN`]-byte `SV_LOGSTYLED` packets.
+    ///
+    /// # Arguments
+    /// * `cn` - Character whose player will receive the message
+    /// * `style` - Channel and presentation flags
+    /// * `message` - Message text (may be longer than a single packet)
+    fn do_styled_log(&mut self, cn: usize, style: ChatStyle, message: &str) {
+        let player_number = self.characters[cn].player as usize;
+
+        if !ServerPlayer::is_sane_player(player_number) {
+            log::error!(
+                "do_styled_log: Character {} has invalid player number: {}",
+                cn,
+                player_number
+            );
+            return;
+        }
+
+        if self.players[player_number].usnr != cn {
+            self.characters[cn].player = 0;
+            return;
+        }
+        let message = localize_item_links(self, player_number, message);
+
+        let mut buffer: [u8; 16] = [0; 16];
+        buffer[0] = ServerCommandType::LogStyled as u8;
+        buffer[1] = style.to_byte();
+
+        for chunk in message.as_bytes().chunks(STYLED_LOG_CHUNK_LEN) {
+            buffer[2..2 + chunk.len()].copy_from_slice(chunk);
+            buffer[2 + chunk.len()..].fill(0);
+            crate::network_manager::xsend(self, player_number, &buffer, 16);
+        }
+    }
+
+    /// Returns `true` when `cn` should get the GM chat highlight.
+    ///
+    /// # Arguments
+    /// * `cn` - Speaking character id
+    pub(crate) fn is_gm_speaker(&self, cn: usize) -> bool {
+        self.characters[cn].flags & (CharacterFlags::God.bits() | CharacterFlags::Imp.bits()) != 0
+    }
+
     /// Port of `do_area_log(cn, co, xs, ys, font, message)` from the original
     /// server.
     ///
@@ -124,6 +216,36 @@ impl GameState {
         font: core::types::FontColor,
         message: &str,
     ) {
+        for cc in self.area_log_recipients(cn, co, xs, ys) {
+            self.do_character_log(cc, font, message);
+        }
+    }
+
+    /// Styled counterpart of [`Self::do_area_log`], used for local chat.
+    ///
+    /// # Arguments
+    /// * `cn` - Character to exclude (usually source)
+    /// * `co` - Second character to exclude
+    /// * `xs, ys` - Source coordinates for the area
+    /// * `style` - Channel and presentation flags
+    /// * `message` - Text to broadcast
+    pub(crate) fn do_area_styled_log(
+        &mut self,
+        cn: usize,
+        co: usize,
+        xs: i32,
+        ys: i32,
+        style: ChatStyle,
+        message: &str,
+    ) {
+        for cc in self.area_log_recipients(cn, co, xs, ys) {
+            self.do_character_styled_log(cc, style, message);
+        }
+    }
+
+    /// Collects the active players within a 12-tile radius of `(xs, ys)`,
+    /// excluding `cn` and `co`.
+    fn area_log_recipients(&self, cn: usize, co: usize, xs: i32, ys: i32) -> Vec<usize> {
         let x_min = cmp::max(0, xs - 12);
         let x_max = cmp::min(core::constants::SERVER_MAPX, xs + 13);
         let y_min = cmp::max(0, ys - 12);
@@ -143,7 +265,7 @@ impl GameState {
             }
         }
 
-        let recipients: Vec<usize> = recipients
+        recipients
             .into_iter()
             .filter(|cc| {
                 *cc < MAXCHARS
@@ -151,18 +273,14 @@ impl GameState {
                     && self.characters[*cc].player != 0
                     && (self.characters[*cc].flags & CharacterFlags::Player.bits()) != 0
             })
-            .collect();
-
-        for cc in recipients {
-            self.do_character_log(cc, font, message);
-        }
+            .collect()
     }
 
     /// Port of `do_sayx(character_id, message)` from the original server.
     ///
     /// Formats and relays a speech message from `character_id` to nearby
-    /// characters. The message is prefixed with the speaker's name and uses
-    /// different colors for players (blue) and NPCs (yellow). Option prefixes
+    /// characters. The message is prefixed with the speaker's name and sent
+    /// on the `Say` channel for players and `Npc` for NPCs. Option prefixes
     /// (like `#<sound>`) are processed by `process_options`.
     ///
     /// # Arguments
@@ -185,13 +303,35 @@ impl GameState {
 
         let line = format!("{}: \"{}\"\n", name_short, msg_short);
 
-        let font = if is_player {
-            core::types::FontColor::Blue
+        let style = if is_player {
+            ChatStyle::new(ChatChannel::Say).with_gm(self.is_gm_speaker(character_id))
         } else {
-            core::types::FontColor::Yellow
+            ChatStyle::new(ChatChannel::Npc)
         };
 
-        self.do_area_log(0, 0, x, y, font, &line);
+        self.do_area_styled_log(0, 0, x, y, style, &line);
+    }
+
+    /// Keyed counterpart of [`Self::do_sayx`] for translatable NPC lines.
+    ///
+    /// Each nearby player gets the line in their own language: as
+    /// `SV_DIALOGUE` if their client renders keys itself, otherwise as
+    /// server-rendered chat text (see [`crate::player::dialogue`]).
+    ///
+    /// # Arguments
+    /// * `character_id` - Speaker character id
+    /// * `line` - Dialogue key and parameters
+    pub(crate) fn do_say_line(&mut self, character_id: usize, line: &DialogueLine) {
+        let ch = &self.characters[character_id];
+        let (x, y) = (i32::from(ch.x), i32::from(ch.y));
+        let speaker: String = ch.get_name().chars().take(30).collect();
+
+        for cc in self.area_log_recipients(0, 0, x, y) {
+            let Some(nr) = self.owning_player(cc).map(PlayerSlot::index) else {
+                continue;
+            };
+            crate::player::dialogue::plr_send_dialogue(self, nr, &speaker, line);
+        }
     }
 
     /// Port of `char_play_sound(character_id, sound, vol, pan)` from the
@@ -231,11 +371,36 @@ impl GameState {
         crate::network_manager::xsend(gs, player_id, &buf, 13);
     }
 
+    /// Sends an `SV_PLAYSOUNDAT` packet to a single character's player
+    /// connection, leaving attenuation and panning to the client.
+    ///
+    /// # Arguments
+    /// * `player_id` - Target player slot
+    /// * `sound` - Sound id to play
+    /// * `dx, dy` - Tile offset of the source from the listener
+    pub(crate) fn player_play_sound_at(
+        gs: &mut GameState,
+        player_id: usize,
+        sound: i32,
+        dx: i32,
+        dy: i32,
+    ) {
+        let mut buf: [u8; 16] = [0; 16];
+        buf[0] = ServerCommandType::PlaySoundAt as u8;
+        buf[1..5].copy_from_slice(&sound.to_le_bytes());
+        buf[5..7].copy_from_slice(&(dx as i16).to_le_bytes());
+        buf[7..9].copy_from_slice(&(dy as i16).to_le_bytes());
+
+        crate::network_manager::xsend(gs, player_id, &buf, 9);
+    }
+
     /// Port of `do_area_sound(cn, co, xs, ys, nr)` from the original server.
     ///
     /// Broadcasts a sound event to nearby characters within an 8-tile radius,
     /// computing volume and pan based on distance. Characters `cn` and `co`
-    /// are excluded from hearing the sound.
+    /// are excluded from hearing the sound. Clients advertising
+    /// `CLIENT_CAP_POSITIONAL_SOUND` get the source offset instead and mix
+    /// the sound themselves.
     ///
     /// # Arguments
     /// * `cn` - Character to exclude (usually source)
@@ -248,7 +413,7 @@ impl GameState {
         let y_min = cmp::max(0, ys - 8);
         let y_max = cmp::min(core::constants::SERVER_MAPY, ys + 9);
 
-        let mut recipients: Vec<(usize, i32, i32)> = Vec::new();
+        let mut recipients: Vec<(usize, i32, i32, i32, i32)> = Vec::new();
 
         for y in y_min..y_max {
             let row_base = y * core::constants::SERVER_MAPX;
@@ -274,17 +439,24 @@ impl GameState {
                     xvol = -5000;
                 }
 
-                recipients.push((cc, xvol, xpan));
+                recipients.push((cc, xvol, xpan, xs - x, ys - y));
             }
         }
 
-        let recipients_with_player: Vec<(usize, i32, i32)> = recipients
+        let recipients_with_player: Vec<(usize, i32, i32, i32, i32)> = recipients
             .into_iter()
-            .filter(|(cc, _, _)| self.characters[*cc].player != 0)
+            .filter(|(cc, ..)| self.characters[*cc].player != 0)
             .collect();
 
-        for (cc, vol, pan) in recipients_with_player {
-            Self::char_play_sound(self, cc, nr, vol, pan);
+        for (cc, vol, pan, dx, dy) in recipients_with_player {
+            let positional = (0..MAXPLAYER).find(|&i| {
+                self.players[i].usnr == cc
+                    && self.players[i].capabilities & CLIENT_CAP_POSITIONAL_SOUND != 0
+            });
+            match positional {
+                Some(player_id) => Self::player_play_sound_at(self, player_id, nr, dx, dy),
+                None => Self::char_play_sound(self, cc, nr, vol, pan),
+            }
         }
     }
 
@@ -330,20 +502,20 @@ impl GameState {
 
     /// Port of `do_imp_log(font, text)` from the original server.
     ///
-    /// Sends a log message to all IMP and USURPed characters (administrative
-    /// recipients). Empty text is ignored.
+    /// Sends a chat message to all IMP and USURPed characters (administrative
+    /// recipients), styled like the rest of `SV_LOGSTYLED` chat.
     ///
     /// # Arguments
-    /// * `font` - Font/color to use
+    /// * `style` - Channel and presentation flags
     /// * `text` - Message text
-    pub(crate) fn do_imp_log(&mut self, font: core::types::FontColor, text: &str) {
+    pub(crate) fn do_imp_chat(&mut self, style: ChatStyle, text: &str) {
         for n in 1..core::constants::MAXCHARS {
             if self.characters[n].player != 0
                 && (self.characters[n].flags
                     & (CharacterFlags::Imp.bits() | CharacterFlags::Usurp.bits()))
                     != 0
             {
-                self.do_log(n, font, text);
+                self.do_styled_log(n, style, text);
             }
         }
     }
@@ -351,8 +523,9 @@ impl GameState {
     /// Port of `do_caution(source, author, text)` from the original server.
     ///
     /// Sends a caution/broadcast message to all active characters. When an
-    /// `author` is supplied, the message is prefixed with `[author name]`.
-    /// Visibility rules for invisible sources are respected.
+    /// `author` is supplied, the message is prefixed with `[author name]`
+    /// and flagged for the client's GM highlight. Visibility rules for
+    /// invisible sources are respected.
     ///
     /// # Arguments
     /// * `source` - Source character id (for visibility checks)
@@ -373,6 +546,7 @@ impl GameState {
         } else {
             anon.clone()
         };
+        let style = ChatStyle::new(ChatChannel::Announce).with_gm(author != 0);
         for n in 1..core::constants::MAXCHARS {
             if !(self.characters[n].player != 0 || self.characters[n].temp == CT_LGUARD as u16) {
                 continue;
@@ -384,7 +558,7 @@ impl GameState {
             {
                 // visibility rules omitted
             }
-            self.do_log(n, core::types::FontColor::Blue, &named);
+            self.do_styled_log(n, style, &named);
         }
     }
 
@@ -393,7 +567,7 @@ impl GameState {
     /// Sends an announcement message to all active characters and respects
     /// invisibility levels so that the source may appear anonymous to some
     /// recipients. When `author` is provided, the message is prefixed with
-    /// `[author name]`.
+    /// `[author name]` and flagged for the client's GM highlight.
     ///
     /// # Arguments
     /// * `source` - Source character id for visibility rules
@@ -414,6 +588,7 @@ impl GameState {
         } else {
             anon.clone()
         };
+        let style = ChatStyle::new(ChatChannel::Announce).with_gm(author != 0);
         for n in 1..core::constants::MAXCHARS {
             // Exclude if not a player and not temp==15
             if !(self.characters[n].player != 0 || self.characters[n].temp == CT_LGUARD as u16) {
@@ -434,27 +609,27 @@ impl GameState {
                 }
                 // If source is not 0 and source's invis_level <= n's, show named, else anon
                 if source != 0 && src_invis_level <= n_invis_level {
-                    self.do_log(n, core::types::FontColor::Green, &named);
+                    self.do_styled_log(n, style, &named);
                 } else {
-                    self.do_log(n, core::types::FontColor::Green, &anon);
+                    self.do_styled_log(n, style, &anon);
                 }
             } else {
-                self.do_log(n, core::types::FontColor::Green, &named);
+                self.do_styled_log(n, style, &named);
             }
         }
     }
 
     /// Port of `do_staff_log(font, text)` from the original server.
     ///
-    /// Sends a message to staff/IMP/USURPed characters that do not have the
-    /// `CF_NOSTAFF` flag set. Empty text is ignored.
+    /// Sends a chat message to staff/IMP/USURPed characters that do not have
+    /// the `CF_NOSTAFF` flag set. Empty text is ignored.
     ///
     /// # Arguments
-    /// * `font` - Font/color to use
+    /// * `style` - Channel and presentation flags
     /// * `text` - Message text
-    pub(crate) fn do_staff_log(&mut self, font: core::types::FontColor, text: &str) {
+    pub(crate) fn do_staff_chat(&mut self, style: ChatStyle, text: &str) {
         if text.is_empty() {
-            log::error!("do_staff_log called with empty text");
+            log::error!("do_staff_chat called with empty text");
             return;
         }
         for n in 1..core::constants::MAXCHARS {
@@ -466,7 +641,7 @@ impl GameState {
                     != 0
                 && (self.characters[n].flags & CharacterFlags::NoStaff.bits()) == 0
             {
-                self.do_log(n, font, text);
+                self.do_styled_log(n, style, text);
             }
         }
     }
@@ -493,6 +668,7 @@ impl GameState {
 
         // Check invisibility of speaker
         let invis = (self.characters[cn].flags & CharacterFlags::Invisible.bits()) != 0;
+        let say_style = ChatStyle::new(ChatChannel::Say).with_gm(self.is_gm_speaker(cn) && !invis);
 
         // Static spiral generation (port of initspiral / areaspiral[] from original C++)
         static AREASPIRAL: OnceLock<Vec<i32>> = OnceLock::new();
@@ -559,9 +735,9 @@ impl GameState {
                     || self.characters[cn].get_invisibility_level()
                         <= self.characters[cc].get_invisibility_level();
                 if show_named {
-                    self.do_character_log(cc, core::types::FontColor::Blue, &msg_named);
+                    self.do_character_styled_log(cc, say_style, &msg_named);
                 } else {
-                    self.do_character_log(cc, core::types::FontColor::Blue, &msg_invis);
+                    self.do_character_styled_log(cc, say_style, &msg_invis);
                 }
             } else {
                 // Listener is NPC: store for second pass
@@ -578,7 +754,7 @@ impl GameState {
         for &npc in &npcs {
             let can_see = self.do_char_can_see(npc, cn);
 
-            if can_see != 0 {
+            if can_see != 0 && !self.script_npc_talk(npc, cn, text) {
                 npc_hear(self, npc, cn, text);
             }
         }
@@ -0,0 +1,171 @@
+//! Loot ownership: who may take freshly dropped grave loot.
+//!
+//! Tag layout and timings live in [`core::loot`]. Graves are tagged when a
+//! character dies; [`GameState::may_take_loot`] is checked by the pickup
+//! paths (`plr_pickup`, corpse looting through `do_shop_char`) and by the map
+//! builder to flag protected tiles for other clients.
+
+use core::constants::{CHD_MAXGROUP, CHD_MINGROUP, IT_TOMBSTONE};
+use core::loot;
+use core::types::{Character, FontColor};
+
+use crate::game_state::GameState;
+
+impl GameState {
+    /// Returns the player entitled to the loot of `co`, killed by `cn`.
+    ///
+    /// Kills by a player's companion count for that player. When no player
+    /// made the kill, a dead player keeps first claim on their own grave.
+    ///
+    /// # Arguments
+    ///
+    /// * `co` - Character that died.
+    /// * `cn` - Killer id (`0` for none).
+    ///
+    /// # Returns
+    ///
+    /// * The owner's character id, or `0` when the loot is free for all.
+    pub(crate) fn loot_owner_for_kill(&self, co: usize, cn: usize) -> usize {
+        if cn != 0 {
+            if self.characters[cn].is_player() {
+                return cn;
+            }
+            let master = self.characters[cn].data[63] as usize;
+            if Character::is_sane_character(master) && self.characters[master].is_player() {
+                return master;
+            }
+        }
+        if self.characters[co].is_player() {
+            co
+        } else {
+            0
+        }
+    }
+
+    /// Reserves every item carried by the body `body` for `owner`.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - Corpse or grave character holding the loot.
+    /// * `owner` - Character entitled to it; `0` leaves the loot untagged.
+    pub(crate) fn protect_grave_loot(&mut self, body: usize, owner: usize) {
+        if owner == 0 {
+            return;
+        }
+        let ch = self.characters[body];
+        let ticker = self.globals.ticker;
+        let carried = ch.item.iter().chain(ch.worn.iter()).copied();
+        for item_idx in carried.chain(std::iter::once(ch.citem)) {
+            let item_idx = item_idx as usize;
+            if item_idx != 0 && item_idx < self.items.len() {
+                loot::protect(&mut self.items[item_idx], owner, ticker);
+            }
+        }
+    }
+
+    /// Returns `true` if `a` and `b` have acknowledged each other as group
+    /// members.
+    fn in_same_group(&self, a: usize, b: usize) -> bool {
+        let lists = |owner: usize, member: usize| {
+            (CHD_MINGROUP..=CHD_MAXGROUP).any(|n| self.characters[owner].data[n] as usize == member)
+        };
+        lists(a, b) && lists(b, a)
+    }
+
+    /// Returns `true` if `cn` may take `item_idx` right now.
+    ///
+    /// Tombstones answer for the body they mark: they are protected while
+    /// any item in the grave still is.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character trying to take the loot.
+    /// * `item_idx` - Item on the ground, in a grave, or a tombstone.
+    ///
+    /// # Returns
+    ///
+    /// * `false` while the loot is reserved for someone outside `cn`'s group.
+    pub(crate) fn may_take_loot(&self, cn: usize, item_idx: usize) -> bool {
+        let ticker = self.globals.ticker;
+        let item = &self.items[item_idx];
+        if item.temp == IT_TOMBSTONE as u16 {
+            let body = item.data[0] as usize;
+            if !Character::is_sane_character(body) {
+                return true;
+            }
+            let ch = &self.characters[body];
+            return ch
+                .item
+                .iter()
+                .chain(ch.worn.iter())
+                .chain(std::iter::once(&ch.citem))
+                .filter(|&&it| it != 0 && (it as usize) < self.items.len())
+                .all(|&it| self.may_take_loot(cn, it as usize));
+        }
+        match loot::owner(item, ticker) {
+            None => true,
+            Some(owner) => owner == cn || self.in_same_group(owner, cn),
+        }
+    }
+
+    /// Checks [`may_take_loot`](Self::may_take_loot) and tells `cn` why a
+    /// take was refused.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character trying to take the loot.
+    /// * `item_idx` - Item being taken.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the take may proceed.
+    pub(crate) fn check_loot_access(&mut self, cn: usize, item_idx: usize) -> bool {
+        if self.may_take_loot(cn, item_idx) {
+            return true;
+        }
+        let message = match loot::owner(&self.items[item_idx], self.globals.ticker) {
+            Some(owner) => format!(
+                "That belongs to {} for a little while longer.\n",
+                self.characters[owner].get_name()
+            ),
+            None => "That is still reserved for someone else.\n".to_owned(),
+        };
+        self.do_character_log(cn, FontColor::Red, &message);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::{CHD_MINGROUP, CharacterFlags, TICKS, USE_ACTIVE};
+    use core::loot::LOOT_PROTECT_SECONDS;
+
+    #[test]
+    fn grave_loot_is_reserved_for_owner_and_group_until_it_expires() {
+        with_test_gs(|gs| {
+            let (owner, _) = add_test_player(gs);
+            let other = 2;
+            gs.characters[other].used = USE_ACTIVE;
+            gs.characters[other].flags = CharacterFlags::Player.bits();
+            let body = 3;
+            gs.characters[body].item[0] = 5;
+            gs.globals.ticker = 100;
+
+            assert_eq!(gs.loot_owner_for_kill(body, owner), owner);
+            gs.protect_grave_loot(body, owner);
+            assert!(gs.may_take_loot(owner, 5));
+            assert!(!gs.may_take_loot(other, 5));
+
+            // A one-sided invite is not enough; an acknowledged group is.
+            gs.characters[owner].data[CHD_MINGROUP] = other as i32;
+            assert!(!gs.may_take_loot(other, 5));
+            gs.characters[other].data[CHD_MINGROUP] = owner as i32;
+            assert!(gs.may_take_loot(other, 5));
+
+            gs.characters[other].data[CHD_MINGROUP] = 0;
+            gs.globals.ticker += LOOT_PROTECT_SECONDS * TICKS;
+            assert!(gs.may_take_loot(other, 5));
+        });
+    }
+}
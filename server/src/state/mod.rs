@@ -10,9 +10,21 @@ pub(crate) mod commerce;
 pub(crate) mod communication;
 pub(crate) mod death;
 pub(crate) mod economy;
+pub(crate) mod gold;
 pub(crate) mod inventory;
+pub(crate) mod item_binding;
+pub(crate) mod linkdead;
 pub(crate) mod logging;
+pub(crate) mod loot;
+pub(crate) mod npc_menu;
 pub(crate) mod player_actions;
+pub(crate) mod profile;
+pub(crate) mod rewards;
+pub(crate) mod shrines;
+pub(crate) mod sight;
+pub(crate) mod skill_training;
+pub(crate) mod starter_kits;
 pub(crate) mod stats;
+pub(crate) mod titles;
 pub(crate) mod visibility;
 pub(crate) mod weather;
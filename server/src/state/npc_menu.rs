@@ -0,0 +1,207 @@
+//! NPC interaction menu: role detection and menu actions.
+//!
+//! `CmdNpcMenu` asks which [`NpcMenuOption`]s a right-clicked NPC offers;
+//! the answer goes out as `SV_NPCMENU`. Characters without any role beyond
+//! being looked at keep the legacy look behavior. `CmdNpcAction` runs the
+//! chosen entry after re-checking that the NPC still offers it.
+
+use core::constants::{CharacterFlags, MAXCHARS, MAXTITEM, USE_ACTIVE};
+use core::dialogue::DialogueLine;
+use core::npc_menu::NpcMenuOption;
+use core::server_commands::ServerCommandType;
+use core::skills;
+use core::string_operations::c_string_to_str;
+use core::types::PlayerSlot;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+
+impl GameState {
+    /// Computes the interaction options an NPC offers.
+    ///
+    /// # Arguments
+    ///
+    /// * `co` - Character that was right-clicked.
+    ///
+    /// # Returns
+    ///
+    /// * Option bitmask, or `0` for players, bodies and unused slots.
+    pub(crate) fn npc_menu_options(&self, co: usize) -> u8 {
+        if co == 0 || co >= MAXCHARS {
+            return 0;
+        }
+        let ch = &self.characters[co];
+        if ch.used != USE_ACTIVE
+            || ch.flags & (CharacterFlags::Player.bits() | CharacterFlags::Body.bits()) != 0
+        {
+            return 0;
+        }
+
+        let mut mask = NpcMenuOption::Look.bit();
+        let greeting = c_string_to_str(&ch.text[2]);
+        if (!greeting.is_empty() && !greeting.starts_with('#')) || ch.data[68] > 0 {
+            mask |= NpcMenuOption::Talk.bit();
+        }
+        if ch.flags & (CharacterFlags::Merchant.bits() | CharacterFlags::Broker.bits()) != 0 {
+            mask |= NpcMenuOption::Trade.bit();
+        }
+        if ch.data[50] != 0 || self.skill_trainer_of(co).is_some() {
+            mask |= NpcMenuOption::Train.bit();
+        }
+        if ch.data[49] != 0 {
+            mask |= NpcMenuOption::Quests.bit();
+        }
+        mask
+    }
+
+    /// Handles a right-click on character `co`: sends the interaction menu
+    /// for NPCs with a role, otherwise performs the legacy look.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Player character that clicked.
+    /// * `co` - Character that was clicked.
+    pub(crate) fn do_npc_menu(&mut self, cn: usize, co: usize) {
+        let options = self.npc_menu_options(co);
+        if options & !NpcMenuOption::Look.bit() == 0 {
+            self.do_look_char(cn, co, 0, 0, 0);
+            return;
+        }
+        if self.do_char_can_see(cn, co) == 0 {
+            return;
+        }
+
+        let Some(nr) = self.owning_player(cn).map(PlayerSlot::index) else {
+            return;
+        };
+        let mut buf = [0u8; 4];
+        buf[0] = ServerCommandType::NpcMenu as u8;
+        buf[1..3].copy_from_slice(&(co as u16).to_le_bytes());
+        buf[3] = options;
+        network_manager::xsend(self, nr, &buf, 4);
+    }
+
+    /// Runs an NPC menu entry chosen by the player.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Player character that chose the entry.
+    /// * `co` - NPC the menu belongs to.
+    /// * `option` - Chosen entry.
+    pub(crate) fn do_npc_action(&mut self, cn: usize, co: usize, option: NpcMenuOption) {
+        if self.npc_menu_options(co) & option.bit() == 0 {
+            log::debug!(
+                "Character {} chose unavailable NPC menu option {:?} on {}",
+                cn,
+                option,
+                co
+            );
+            return;
+        }
+        if self.do_char_can_see(cn, co) == 0 {
+            return;
+        }
+
+        let player_name = self.characters[cn].get_name().to_owned();
+        match option {
+            NpcMenuOption::Trade if self.is_broker(co) => self.do_look_broker(cn, co, 0, ""),
+            // Merchants open their shop on look.
+            NpcMenuOption::Look | NpcMenuOption::Trade => self.do_look_char(cn, co, 0, 0, 0),
+            NpcMenuOption::Talk => {
+                let npc_name = self.characters[co].get_name().to_owned();
+                self.do_say(cn, &format!("Hello, {}!", npc_name));
+            }
+            NpcMenuOption::Quests => {
+                let line = DialogueLine::new("npc.quests.looking_for")
+                    .with("item", self.wanted_item_reference(co))
+                    .with("player", player_name);
+                self.do_say_line(co, &line);
+            }
+            NpcMenuOption::Train if self.skill_trainer_of(co).is_some() => {
+                self.do_send_trainer_offers(cn, co);
+            }
+            NpcMenuOption::Train => {
+                let skill_name = skills::get_skill_name(skills::canonicalize_weapon_skill(
+                    self.characters[co].data[50] as usize,
+                ));
+                let line = DialogueLine::new("npc.train.bring_item")
+                    .with("item", self.wanted_item_reference(co))
+                    .with("skill", skill_name)
+                    .with("player", player_name);
+                self.do_say_line(co, &line);
+            }
+        }
+    }
+
+    /// Describes the item an NPC wants (`data[49]` item template).
+    ///
+    /// # Arguments
+    ///
+    /// * `co` - NPC character.
+    ///
+    /// # Returns
+    ///
+    /// * The template's reference text, or `"something"` if unknown.
+    fn wanted_item_reference(&self, co: usize) -> String {
+        let temp = self.characters[co].data[49];
+        if temp <= 0 || temp as usize >= MAXTITEM {
+            return "something".to_owned();
+        }
+        let reference = c_string_to_str(&self.item_templates[temp as usize].reference);
+        if reference.is_empty() {
+            "something".to_owned()
+        } else {
+            reference.to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::constants::{CharacterFlags, USE_ACTIVE};
+    use core::npc_menu::NpcMenuOption;
+    use core::string_operations::write_ascii_into_fixed;
+
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    #[test]
+    fn options_reflect_npc_roles() {
+        with_test_gs(|gs| {
+            let co = 2;
+            gs.characters[co].used = USE_ACTIVE;
+            assert_eq!(gs.npc_menu_options(co), NpcMenuOption::Look.bit());
+
+            gs.characters[co].flags = CharacterFlags::Merchant.bits();
+            write_ascii_into_fixed(&mut gs.characters[co].text[2], "Hello, %s!");
+            gs.characters[co].data[49] = 740;
+            let mask = gs.npc_menu_options(co);
+            for option in [
+                NpcMenuOption::Talk,
+                NpcMenuOption::Trade,
+                NpcMenuOption::Quests,
+                NpcMenuOption::Look,
+            ] {
+                assert_ne!(mask & option.bit(), 0, "{:?} missing", option);
+            }
+            assert_eq!(mask & NpcMenuOption::Train.bit(), 0);
+        });
+    }
+
+    #[test]
+    fn brokers_trade() {
+        with_test_gs(|gs| {
+            let co = 2;
+            gs.characters[co].used = USE_ACTIVE;
+            gs.characters[co].flags = CharacterFlags::Broker.bits();
+            assert_ne!(gs.npc_menu_options(co) & NpcMenuOption::Trade.bit(), 0);
+        });
+    }
+
+    #[test]
+    fn players_have_no_menu() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            assert_eq!(gs.npc_menu_options(cn), 0);
+        });
+    }
+}
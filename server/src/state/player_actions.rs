@@ -1,7 +1,7 @@
 use core::constants::{
-    CharacterFlags, PL_ARMS, PL_BELT, PL_BODY, PL_CLOAK, PL_FEET, PL_HEAD, PL_LEGS, PL_NECK,
-    PL_RING, PL_SHIELD, PL_TWOHAND, PL_WEAPON, WN_ARMS, WN_BELT, WN_BODY, WN_CLOAK, WN_FEET,
-    WN_HEAD, WN_LEGS, WN_LHAND, WN_LRING, WN_NECK, WN_RHAND, WN_RRING,
+    CharacterFlags, ItemFlags, PL_ARMS, PL_BELT, PL_BODY, PL_CLOAK, PL_FEET, PL_HEAD, PL_LEGS,
+    PL_NECK, PL_RING, PL_SHIELD, PL_TWOHAND, PL_WEAPON, WN_ARMS, WN_BELT, WN_BODY, WN_CLOAK,
+    WN_FEET, WN_HEAD, WN_LEGS, WN_LHAND, WN_LRING, WN_NECK, WN_RHAND, WN_RRING,
 };
 use core::skills;
 use core::string_operations::c_string_to_str;
@@ -13,6 +13,10 @@ use crate::game_state::GameState;
 use crate::god::God;
 use crate::helpers;
 
+/// Farthest a follow may start from or stretch to, in tiles along either
+/// axis. Beyond it the follower gives up.
+pub(crate) const FOLLOW_MAX_DISTANCE: i32 = 20;
+
 impl GameState {
     /// Port of `do_swap_item(int cn, int n)` from `svr_do.cpp`
     ///
@@ -220,6 +224,8 @@ impl GameState {
         // Perform the swap
         let ch = &mut self.characters[cn];
         std::mem::swap(&mut ch.citem, &mut ch.worn[n]);
+        let worn = ch.worn[n] as usize;
+        self.bind_item(cn, worn, ItemFlags::IF_BIND_EQUIP);
 
         self.characters[cn].set_do_update_flags();
 
@@ -375,26 +381,16 @@ impl GameState {
                 self.characters[co].login_date,
                 self.characters[co].logout_date,
             );
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i32;
-
             let co_name = self.characters[co].get_name().to_owned();
 
-            // Format timestamps
-            use chrono::{TimeZone, Utc};
-            let last_dt = Utc.timestamp_opt(i64::from(last), 0).unwrap();
-            let now_dt = Utc.timestamp_opt(i64::from(now), 0).unwrap();
-
             self.do_character_log(
                 cn,
                 core::types::FontColor::Yellow,
                 &format!(
                     "{} was last seen on {} (time now: {})\n",
                     co_name,
-                    last_dt.format("%Y-%m-%d %H:%M:%S"),
-                    now_dt.format("%Y-%m-%d %H:%M:%S")
+                    crate::wall_clock::format_display(i64::from(last)),
+                    crate::wall_clock::format_display(crate::wall_clock::unix_now())
                 ),
             );
 
@@ -409,16 +405,12 @@ impl GameState {
             }
         } else {
             // Normal player view: relative time
-            let last_date = (std::cmp::max(
+            let last = std::cmp::max(
                 self.characters[co].login_date,
                 self.characters[co].logout_date,
-            ) / (24 * 3600)) as i32;
-            let current_date = (std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i32)
-                / (24 * 3600);
-            let days = current_date - last_date;
+            );
+            let days =
+                crate::wall_clock::days_between(i64::from(last), crate::wall_clock::unix_now());
 
             let when = match days {
                 0 => "earlier today".to_owned(),
@@ -436,11 +428,47 @@ impl GameState {
         }
     }
 
+    /// Returns why `cn` cannot (or can no longer) follow `co`.
+    ///
+    /// Players must have added `cn` to their group to be followed, and the
+    /// two must stay within [`FOLLOW_MAX_DISTANCE`] of each other.
+    ///
+    /// # Arguments
+    /// * `cn` - Follower
+    /// * `co` - Character being followed
+    ///
+    /// # Returns
+    /// * `None` while following is allowed, otherwise a reason for the log.
+    pub(crate) fn follow_refusal(&self, cn: usize, co: usize) -> Option<&'static str> {
+        let Some(target) = core::types::CharId::new(co).map(|id| self.character(id)) else {
+            return Some("they are gone");
+        };
+        if target.used != core::constants::USE_ACTIVE {
+            return Some("they are gone");
+        }
+        let is_player = target.flags & CharacterFlags::Player.bits() != 0;
+        let consents = (core::constants::CHD_MINGROUP..=core::constants::CHD_MAXGROUP)
+            .any(|n| target.data[n] as usize == cn);
+        if is_player && !consents {
+            return Some("they have not added you to their group");
+        }
+        let ch = &self.characters[cn];
+        let distance = (i32::from(ch.x) - i32::from(target.x))
+            .abs()
+            .max((i32::from(ch.y) - i32::from(target.y)).abs());
+        if distance > FOLLOW_MAX_DISTANCE {
+            return Some("they are too far away");
+        }
+        None
+    }
+
     /// Port of `do_follow(cn, name)` from `svr_do.cpp`.
     ///
     /// Sets or clears the follow target for the character `cn`. When called
     /// with an empty `name` it reports the current follow target. Visibility
-    /// and sanity checks are performed when resolving the target name.
+    /// and sanity checks are performed when resolving the target name, and
+    /// [`Self::follow_refusal`] decides whether the target may be followed.
+    /// Following starts right away; the target is told about it.
     ///
     /// # Arguments
     /// * `cn` - Character setting follow
@@ -501,13 +529,32 @@ impl GameState {
             return;
         }
 
-        self.characters[cn].data[10] = co as i32;
         let target = self.characters[co].get_name().to_owned();
+        if let Some(reason) = self.follow_refusal(cn, co) {
+            self.do_character_log(
+                cn,
+                core::types::FontColor::Red,
+                &format!("You cannot follow {}: {}.\n", target, reason),
+            );
+            return;
+        }
+
+        self.characters[cn].data[10] = co as i32;
+        // Start right away instead of waiting for the idle delay.
+        self.characters[cn].data[12] = 0;
         self.do_character_log(
             cn,
             core::types::FontColor::Yellow,
             &format!("Now following {}.\n", target),
         );
+        if self.characters[co].flags & CharacterFlags::Player.bits() != 0 {
+            let follower = self.characters[cn].get_name().to_owned();
+            self.do_character_log(
+                co,
+                core::types::FontColor::Yellow,
+                &format!("{} is now following you.\n", follower),
+            );
+        }
     }
 
     /// Port of `do_ignore(cn, name, flag)` from `svr_do.cpp`.
@@ -979,6 +1026,11 @@ impl GameState {
             core::types::FontColor::Green,
             "#bow                   you'll bow.\n",
         );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#consign help          list the broker commands.\n",
+        );
         self.do_character_log(
             cn,
             core::types::FontColor::Green,
@@ -989,6 +1041,11 @@ impl GameState {
             core::types::FontColor::Green,
             "#follow <player>|self  you'll follow player.\n",
         );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#friend help           list the friends list commands.\n",
+        );
         self.do_character_log(
             cn,
             core::types::FontColor::Green,
@@ -1004,6 +1061,16 @@ impl GameState {
             core::types::FontColor::Green,
             "#gtell <message>       tell to your group.\n",
         );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#guild help            list the guild commands.\n",
+        );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#guildtell <message>   tell to your guild.\n",
+        );
         self.do_character_log(
             cn,
             core::types::FontColor::Green,
@@ -1019,6 +1086,11 @@ impl GameState {
             core::types::FontColor::Green,
             "#lag <seconds>         lag control.\n",
         );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#mail help             list the mail commands.\n",
+        );
         self.do_character_log(
             cn,
             core::types::FontColor::Green,
@@ -1029,6 +1101,16 @@ impl GameState {
             core::types::FontColor::Green,
             "#notell                you won't hear tells.\n",
         );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#pray                  buy a blessing at a shrine.\n",
+        );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#refresh               resync your character sheet.\n",
+        );
         self.do_character_log(
             cn,
             core::types::FontColor::Green,
@@ -1064,6 +1146,11 @@ impl GameState {
             core::types::FontColor::Green,
             "#talents               show active talent bonuses.\n",
         );
+        self.do_character_log(
+            cn,
+            core::types::FontColor::Green,
+            "#title [name|none]     list or choose a title.\n",
+        );
         self.do_character_log(
             cn,
             core::types::FontColor::Green,
@@ -1168,6 +1255,11 @@ impl GameState {
                 core::types::FontColor::Blue,
                 "#goto <char>           go to char.\n",
             );
+            self.do_character_log(
+                cn,
+                core::types::FontColor::Blue,
+                "#granttitle <plr><ttl> grant/revoke a title.\n",
+            );
             self.do_character_log(
                 cn,
                 core::types::FontColor::Blue,
@@ -1243,6 +1335,11 @@ impl GameState {
                 core::types::FontColor::Blue,
                 "#npclist <search>      display list of NPCs.\n",
             );
+            self.do_character_log(
+                cn,
+                core::types::FontColor::Blue,
+                "#observe [<player>]    watch unseen, off the map.\n",
+            );
             self.do_character_log(
                 cn,
                 core::types::FontColor::Blue,
@@ -1289,6 +1386,16 @@ impl GameState {
         if (self.characters[cn].flags & CharacterFlags::God.bits()) != 0 {
             self.do_character_log(cn, core::types::FontColor::Blue, "God Commands:\n");
             self.do_character_log(cn, core::types::FontColor::Blue, " \n");
+            self.do_character_log(
+                cn,
+                core::types::FontColor::Blue,
+                "#broker <player>       toggle broker flag.\n",
+            );
+            self.do_character_log(
+                cn,
+                core::types::FontColor::Blue,
+                "#clawback <player> <gold> [silver] take back duped gold.\n",
+            );
             self.do_character_log(
                 cn,
                 core::types::FontColor::Blue,
@@ -1329,6 +1436,16 @@ impl GameState {
                 core::types::FontColor::Blue,
                 "#pol <player>           make player POH leader.\n",
             );
+            self.do_character_log(
+                cn,
+                core::types::FontColor::Blue,
+                "#reload [items|chars]   reload templates from KeyDB.\n",
+            );
+            self.do_character_log(
+                cn,
+                core::types::FontColor::Blue,
+                "#scripts [reload]       list or reload quest scripts.\n",
+            );
             self.do_character_log(
                 cn,
                 core::types::FontColor::Blue,
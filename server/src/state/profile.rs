@@ -0,0 +1,180 @@
+//! Player profile (bio + name color) editing.
+//!
+//! Bios arrive from the client's profile editor as `CmdSetProfile` chunks
+//! (reassembled in [`crate::player::commands::plr_cmd_set_profile`]) and are
+//! validated here before replacing `Character::description`. The validated
+//! text is mirrored to the API-side `character:{id}` hash because the login
+//! path re-syncs the description from there.
+
+use core::constants::CharacterFlags;
+use core::profile::{NameColor, PROFILE_NAME_COLOR_SLOT, find_bad_word, validate_bio};
+use core::string_operations::write_ascii_into_fixed;
+use core::types::{FontColor, PlayerSlot};
+
+use crate::game_state::GameState;
+
+impl GameState {
+    /// Returns the profile name color selected by a character.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character id.
+    ///
+    /// # Returns
+    ///
+    /// * The stored [`NameColor`], or [`NameColor::Default`] for NPCs and
+    ///   out-of-range values.
+    pub(crate) fn name_color(&self, cn: usize) -> NameColor {
+        let ch = &self.characters[cn];
+        if !ch.is_player() {
+            return NameColor::Default;
+        }
+        let raw = ch.future3[PROFILE_NAME_COLOR_SLOT];
+        u8::try_from(raw).map(NameColor::from).unwrap_or_default()
+    }
+
+    /// Applies a profile update submitted by a player.
+    ///
+    /// The name color is always applied. A non-empty `bio` is validated
+    /// (length, printable ASCII, must mention the character name, no banned
+    /// words, `NoDesc` not set) before it replaces the character's
+    /// description and is persisted to the API hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character submitting the profile.
+    /// * `name_color` - Raw [`NameColor`] byte from the client.
+    /// * `bio` - Reassembled bio text; empty leaves the description unchanged.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` when the profile was stored.
+    /// * `Err(String)` with a player-facing reason when the bio was rejected.
+    pub(crate) fn set_character_profile(
+        &mut self,
+        cn: usize,
+        name_color: u8,
+        bio: &str,
+    ) -> Result<(), String> {
+        if !self.characters[cn].is_player() {
+            return Err("Only players have a profile.".to_owned());
+        }
+
+        self.characters[cn].future3[PROFILE_NAME_COLOR_SLOT] =
+            i32::from(NameColor::from(name_color).as_u8());
+
+        let bio = bio.trim();
+        if bio.is_empty() {
+            return Ok(());
+        }
+
+        if self.characters[cn].flags & CharacterFlags::NoDesc.bits() != 0 {
+            return Err("You are not allowed to change your description.".to_owned());
+        }
+
+        let name = self.characters[cn].get_name().to_owned();
+        validate_bio(&name, bio)?;
+
+        if find_bad_word(bio, &self.bad_words).is_some() {
+            return Err("Your description contains language that is not allowed.".to_owned());
+        }
+
+        write_ascii_into_fixed(&mut self.characters[cn].description, bio);
+
+        if let Some(slot) = PlayerSlot::from_i32(self.characters[cn].player) {
+            let api_character_id = self.player(slot).api_character_id;
+            if api_character_id != 0
+                && let Err(err) =
+                    server::keydb::connection::set_character_description(api_character_id, bio)
+            {
+                log::warn!(
+                    "Failed to persist description for character {} (api id {}): {}",
+                    cn,
+                    api_character_id,
+                    err
+                );
+            }
+        }
+
+        log::info!("Character {} ({}) updated their profile", cn, name);
+        Ok(())
+    }
+
+    /// Applies a profile update and reports the outcome to the player.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character submitting the profile.
+    /// * `name_color` - Raw [`NameColor`] byte from the client.
+    /// * `bio` - Reassembled bio text.
+    pub(crate) fn do_set_profile(&mut self, cn: usize, name_color: u8, bio: &str) {
+        match self.set_character_profile(cn, name_color, bio) {
+            Ok(()) => {
+                self.do_character_log(cn, FontColor::Green, "Your profile has been updated.\n");
+            }
+            Err(reason) => self.do_character_log(cn, FontColor::Red, &format!("{}\n", reason)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::CharacterFlags;
+    use core::profile::{NameColor, PROFILE_NAME_COLOR_SLOT};
+    use core::string_operations::c_string_to_str;
+
+    #[test]
+    fn valid_bio_replaces_description_and_color() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            let result =
+                gs.set_character_profile(cn, NameColor::Azure.as_u8(), "Tester guards the gate.");
+            assert!(result.is_ok());
+            assert_eq!(
+                c_string_to_str(&gs.characters[cn].description),
+                "Tester guards the gate."
+            );
+            assert_eq!(gs.name_color(cn), NameColor::Azure);
+        });
+    }
+
+    #[test]
+    fn empty_bio_only_changes_color() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].set_description("Tester is old news.");
+            assert!(
+                gs.set_character_profile(cn, NameColor::Gold.as_u8(), "")
+                    .is_ok()
+            );
+            assert_eq!(
+                c_string_to_str(&gs.characters[cn].description),
+                "Tester is old news."
+            );
+            assert_eq!(
+                gs.characters[cn].future3[PROFILE_NAME_COLOR_SLOT],
+                i32::from(NameColor::Gold.as_u8())
+            );
+        });
+    }
+
+    #[test]
+    fn bad_words_and_nodesc_are_rejected() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.bad_words = vec!["gnoll".to_owned()];
+            assert!(
+                gs.set_character_profile(cn, 0, "Tester hunts GNOLLs for sport.")
+                    .is_err()
+            );
+
+            gs.characters[cn].flags |= CharacterFlags::NoDesc.bits();
+            assert!(
+                gs.set_character_profile(cn, 0, "Tester is perfectly polite.")
+                    .is_err()
+            );
+            assert_eq!(c_string_to_str(&gs.characters[cn].description), "");
+        });
+    }
+}
@@ -0,0 +1,286 @@
+//! Delivery of earned items (quest rewards, exchange gifts, purchases).
+//!
+//! A reward used to vanish when the player's pack was full.
+//! [`GameState::give_reward_item`] mails it to the player instead, attached
+//! to a letter from the quartermaster. If the mailbox is full the item goes
+//! into the depot, and as a last resort it is dropped at the player's feet,
+//! reserved for them for [`core::loot::LOOT_PROTECT_SECONDS`]. Either way
+//! the player gets a [`ChatChannel::Reward`] notice, which the client turns
+//! into a toast.
+//!
+//! The letter expires after [`REWARD_LETTER_SECS`].
+//! [`GameState::expire_reward_letters`] then moves a reward nobody took into
+//! the depot, or drops it at the feet of a player who is online, and leaves
+//! a letter saying where it went.
+
+use core::chat::{ChatChannel, ChatStyle};
+use core::constants::ItemFlags;
+use core::loot;
+use core::string_operations::c_string_to_str;
+
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::guilds::commands::online_slot;
+use crate::wall_clock;
+
+/// Sender shown on letters carrying overflowing rewards.
+const REWARD_LETTER_SENDER: &str = "Quartermaster";
+
+/// How long a mailed reward waits to be taken before it is returned.
+const REWARD_LETTER_SECS: i64 = 14 * 24 * 60 * 60;
+
+impl GameState {
+    /// Gives a freshly earned item to `cn`, overflowing when the pack is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character receiving the reward.
+    /// * `item_idx` - Item to hand over; it must not be carried yet.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the item reached the pack, the mailbox, the depot or the
+    ///   ground.
+    pub(crate) fn give_reward_item(&mut self, cn: usize, item_idx: usize) -> bool {
+        if God::give_character_item(self, cn, item_idx) {
+            return true;
+        }
+
+        let item_ref = c_string_to_str(&self.items[item_idx].reference).to_owned();
+        let storable = self.characters[cn].is_player()
+            && self.items[item_idx].flags & ItemFlags::IF_NODEPOT.bits() == 0;
+        let mailed = if storable {
+            self.items[item_idx].carried = 0;
+            self.send_system_letter(
+                cn,
+                REWARD_LETTER_SENDER,
+                &format!(
+                    "Your pack was full when you earned {}, so it is attached to this letter.",
+                    item_ref
+                ),
+                item_idx,
+                wall_clock::unix_now() + REWARD_LETTER_SECS,
+            )
+            .ok()
+        } else {
+            None
+        };
+        let message = if let Some(id) = mailed {
+            log::info!(
+                "Character {} reward {} mailed as letter {}",
+                cn,
+                item_ref,
+                id
+            );
+            format!(
+                "Your pack was full, so {} was mailed to you. Type #mail take {} to collect it.\n",
+                item_ref, id
+            )
+        } else if storable && self.do_add_depot(cn, item_idx) {
+            self.items[item_idx].carried = cn as u16;
+            log::info!("Character {} reward {} sent to depot", cn, item_ref);
+            format!(
+                "Your pack was full, so {} was sent to your depot.\n",
+                item_ref
+            )
+        } else {
+            let (x, y) = (
+                self.characters[cn].x as usize,
+                self.characters[cn].y as usize,
+            );
+            if !God::drop_item_fuzzy(self, item_idx, x, y) {
+                log::error!(
+                    "Character {} reward {} could not be delivered anywhere",
+                    cn,
+                    item_ref
+                );
+                self.items[item_idx].used = core::constants::USE_EMPTY;
+                self.do_character_styled_log(
+                    cn,
+                    ChatStyle::new(ChatChannel::Reward),
+                    &format!("There was no room anywhere for {}. It is lost.\n", item_ref),
+                );
+                return false;
+            }
+            let ticker = self.globals.ticker;
+            loot::protect(&mut self.items[item_idx], cn, ticker);
+            log::info!("Character {} reward {} dropped at their feet", cn, item_ref);
+            format!(
+                "Your pack was full, so {} was placed at your feet. It is yours for {} seconds.\n",
+                item_ref,
+                loot::LOOT_PROTECT_SECONDS
+            )
+        };
+        self.do_character_styled_log(cn, ChatStyle::new(ChatChannel::Reward), &message);
+        true
+    }
+
+    /// Returns the attachments of every expired reward letter.
+    ///
+    /// The item goes into the recipient's depot, or is dropped at their
+    /// feet if it may not be stored or the depot is full. The letter is
+    /// replaced by one saying where the item went. A reward that fits
+    /// nowhere, e.g. because its player is offline and their depot is full,
+    /// stays in the mail and is tried again on the next sweep.
+    pub(crate) fn expire_reward_letters(&mut self) {
+        for id in self.mail.expired(wall_clock::unix_now()) {
+            let Some(cn) = self.mail.get(id).map(|letter| letter.to) else {
+                continue;
+            };
+            let Ok(item_idx) = self.mail.take_item(cn, id) else {
+                continue;
+            };
+            let item_ref = c_string_to_str(&self.items[item_idx].reference).to_owned();
+            let storable = self.items[item_idx].flags & ItemFlags::IF_NODEPOT.bits() == 0;
+            let (x, y) = (
+                self.characters[cn].x as usize,
+                self.characters[cn].y as usize,
+            );
+            let body = if storable && self.do_add_depot(cn, item_idx) {
+                self.items[item_idx].carried = cn as u16;
+                log::info!("Character {} reward {} returned to depot", cn, item_ref);
+                format!(
+                    "Nobody took {} from my letter, so I put it in your depot.",
+                    item_ref
+                )
+            } else if online_slot(self, cn).is_some() && God::drop_item_fuzzy(self, item_idx, x, y)
+            {
+                let ticker = self.globals.ticker;
+                loot::protect(&mut self.items[item_idx], cn, ticker);
+                log::info!(
+                    "Character {} reward {} returned at their feet",
+                    cn,
+                    item_ref
+                );
+                format!(
+                    "Nobody took {} from my letter, so I placed it at your feet.",
+                    item_ref
+                )
+            } else {
+                self.mail.reattach(id, item_idx);
+                log::debug!("Reward letter {} expired but stays in the mail", id);
+                continue;
+            };
+
+            if let Err(e) = self.delete_letter(cn, id) {
+                log::warn!("Could not delete expired reward letter {}: {}", id, e);
+            }
+            if let Err(e) = self.send_system_letter(cn, REWARD_LETTER_SENDER, &body, 0, 0) {
+                log::warn!(
+                    "Could not tell character {} about reward {}: {}",
+                    cn,
+                    item_ref,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::{Letter, MailRegistry};
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+
+    fn fill_pack(gs: &mut GameState, cn: usize) {
+        for n in 0..40 {
+            gs.characters[cn].item[n] = 1;
+        }
+    }
+
+    /// Runs the item garbage collector over the whole item table.
+    fn sweep_items(gs: &mut GameState) {
+        for _ in 0..core::constants::MAXITEM.div_ceil(256) {
+            crate::driver::item_tick_gc(gs);
+        }
+    }
+
+    #[test]
+    fn full_pack_overflows_into_the_mailbox() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            fill_pack(gs, cn);
+            let reward = 5;
+            gs.items[reward].used = USE_ACTIVE;
+
+            assert!(gs.give_reward_item(cn, reward));
+            let letter = gs.mail.mailbox(cn).next().expect("reward mailed");
+            assert_eq!(letter.item, reward);
+            assert_eq!(letter.from_name, REWARD_LETTER_SENDER);
+
+            sweep_items(gs);
+            assert_eq!(gs.items[reward].used, USE_ACTIVE);
+        });
+    }
+
+    #[test]
+    fn expired_reward_letters_return_to_the_depot() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            fill_pack(gs, cn);
+            let reward = 5;
+            gs.items[reward].used = USE_ACTIVE;
+            assert!(gs.give_reward_item(cn, reward));
+            let id = gs.mail.mailbox(cn).next().expect("reward mailed").id;
+
+            gs.expire_reward_letters();
+            assert_eq!(gs.mail.get(id).map(|letter| letter.item), Some(reward));
+
+            let letter = gs.mail.get(id).cloned().unwrap();
+            gs.mail = MailRegistry::from_letters(
+                vec![Letter {
+                    expires_unix: 1,
+                    ..letter
+                }],
+                id + 1,
+            );
+            gs.expire_reward_letters();
+            assert!(gs.mail.get(id).is_none());
+            assert_eq!(gs.characters[cn].depot[0], reward as u32);
+            assert_eq!(gs.items[reward].carried, cn as u16);
+            let notice = gs.mail.mailbox(cn).next().expect("notice sent");
+            assert_eq!(notice.item, 0);
+            assert_eq!(notice.expires_unix, 0);
+        });
+    }
+
+    #[test]
+    fn full_mailbox_overflows_into_the_depot() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            fill_pack(gs, cn);
+            for _ in 0..core::mail::MAX_MAILBOX_LETTERS {
+                gs.send_system_letter(cn, "Spammer", "Hello.", 0, 0)
+                    .unwrap();
+            }
+            let reward = 5;
+            gs.items[reward].used = USE_ACTIVE;
+
+            assert!(gs.give_reward_item(cn, reward));
+            assert_eq!(gs.characters[cn].depot[0], reward as u32);
+            assert_eq!(gs.items[reward].carried, cn as u16);
+
+            sweep_items(gs);
+            assert_eq!(gs.items[reward].used, USE_ACTIVE);
+            assert_eq!(gs.characters[cn].depot[0], reward as u32);
+        });
+    }
+
+    #[test]
+    fn nodepot_rewards_are_reserved_on_the_ground() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            fill_pack(gs, cn);
+            let reward = 5;
+            gs.items[reward].used = USE_ACTIVE;
+            gs.items[reward].flags = ItemFlags::IF_NODEPOT.bits();
+
+            assert!(gs.give_reward_item(cn, reward));
+            assert_eq!(gs.characters[cn].depot[0], 0);
+            assert_eq!(gs.items[reward].carried, 0);
+            assert_eq!(loot::owner(&gs.items[reward], gs.globals.ticker), Some(cn));
+        });
+    }
+}
@@ -0,0 +1,205 @@
+//! `#pray`: shrine blessings.
+//!
+//! Shrine definitions and the diminishing-returns rule live in
+//! [`core::shrines`]. A blessing is an ordinary spell item with temp
+//! [`SHRINE_BLESSING_TEMP`]; `data[0]` counts how often it was extended and
+//! `data[1]` holds the id of the shrine that granted it.
+
+use core::constants::{ItemFlags, MF_NOMAGIC, MF_SHRINE, SERVER_MAPX};
+use core::shrines::{self, MAX_BLESSING_STACKS, SHRINE_BLESSING_TEMP, ShrineDef};
+use core::types::FontColor;
+use server::journal::GoldTransferKind;
+
+use crate::driver;
+use crate::game_state::GameState;
+use crate::god::God;
+
+/// Item template spell items are created from.
+const SPELL_TEMPLATE: usize = 1;
+
+impl GameState {
+    /// Buys a blessing at the shrine `cn` is standing on.
+    ///
+    /// A fresh blessing lasts the shrine's full time. Praying at the same
+    /// shrine while blessed extends the blessing by a shrinking amount;
+    /// praying at a different shrine replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Praying character.
+    pub(crate) fn do_pray(&mut self, cn: usize) {
+        let (x, y) = (
+            i32::from(self.characters[cn].x),
+            i32::from(self.characters[cn].y),
+        );
+        let m = (x + y * SERVER_MAPX) as usize;
+        if self.map[m].flags & MF_SHRINE == 0 {
+            self.do_character_log(cn, FontColor::Red, "There is no shrine here.\n");
+            return;
+        }
+        if self.map[m].flags & u64::from(MF_NOMAGIC) != 0 {
+            self.do_character_log(cn, FontColor::Red, "The shrine is silent.\n");
+            return;
+        }
+
+        let shrine = shrines::shrine_at(x, y);
+        let current = self.characters[cn]
+            .spell
+            .iter()
+            .position(|&in_| in_ != 0 && self.items[in_ as usize].temp == SHRINE_BLESSING_TEMP);
+        let stacks = current
+            .map(|n| &self.items[self.characters[cn].spell[n] as usize])
+            .filter(|it| it.data[1] == u32::from(shrine.id))
+            .map_or(0, |it| it.data[0] + 1);
+        if stacks > MAX_BLESSING_STACKS {
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                "The gods will not hear you again until your blessing fades.\n",
+            );
+            return;
+        }
+
+        let cost = shrine.cost as i32;
+        if self.characters[cn].gold < cost {
+            self.do_character_log(
+                cn,
+                FontColor::Red,
+                &format!(
+                    "The shrine asks an offering of {}G {}S.\n",
+                    cost / 100,
+                    cost % 100
+                ),
+            );
+            return;
+        }
+
+        let ticks = shrine.blessing_ticks(stacks);
+        if stacks > 0 {
+            let in_ = self.characters[cn].spell[current.unwrap_or_default()] as usize;
+            let it = &mut self.items[in_];
+            it.active += ticks;
+            it.duration = it.duration.max(it.active);
+            it.data[0] = stacks;
+        } else {
+            let Some(in_) = God::create_item(self, SPELL_TEMPLATE) else {
+                log::error!("god_create_item failed in do_pray");
+                return;
+            };
+            self.make_blessing(in_, shrine, ticks);
+            if let Some(n) = current {
+                // A blessing from another shrine gives way to the new one.
+                let old = self.characters[cn].spell[n] as usize;
+                self.items[old].used = core::constants::USE_EMPTY;
+                self.characters[cn].spell[n] = in_ as u32;
+                self.items[in_].carried = cn as u16;
+                self.do_update_char(cn);
+            } else if driver::add_spell(self, cn, in_) == 0 {
+                self.do_character_log(
+                    cn,
+                    FontColor::Yellow,
+                    "Magical interference drowns out your prayer.\n",
+                );
+                return;
+            }
+        }
+
+        self.transfer_gold(cn, 0, cost, GoldTransferKind::ShrineOffering);
+        self.characters[cn].set_do_update_flags();
+        let text = if stacks > 0 {
+            format!("Your {} grows longer.\n", shrine.blessing)
+        } else {
+            format!("You pray and receive {}.\n", shrine.blessing)
+        };
+        self.do_character_log(cn, FontColor::Green, &text);
+        log::info!(
+            "{} bought {} (stack {}) for {}",
+            self.characters[cn].get_name(),
+            shrine.blessing,
+            stacks,
+            cost
+        );
+    }
+
+    /// Turns a freshly created spell item into the blessing of `shrine`.
+    fn make_blessing(&mut self, in_: usize, shrine: &ShrineDef, ticks: u32) {
+        let it = &mut self.items[in_];
+        let mut name = [0u8; 40];
+        let len = shrine.blessing.len().min(name.len());
+        name[..len].copy_from_slice(&shrine.blessing.as_bytes()[..len]);
+        it.name = name;
+        it.flags |= ItemFlags::IF_SPELL.bits();
+        for attrib in it.attrib.iter_mut() {
+            attrib[1] = shrine.attrib_bonus;
+        }
+        it.hp[1] = shrine.hp_bonus;
+        it.mana[1] = shrine.mana_bonus;
+        it.sprite[1] = shrine.sprite;
+        it.duration = ticks;
+        it.active = ticks;
+        it.temp = SHRINE_BLESSING_TEMP;
+        it.power = shrine.power;
+        it.data[0] = 0;
+        it.data[1] = u32::from(shrine.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+    use core::shrines::DEFAULT_SHRINE;
+
+    fn blessing(gs: &GameState, cn: usize) -> Option<usize> {
+        gs.characters[cn]
+            .spell
+            .iter()
+            .map(|&in_| in_ as usize)
+            .find(|&in_| in_ != 0 && gs.items[in_].temp == SHRINE_BLESSING_TEMP)
+    }
+
+    #[test]
+    fn repeated_prayers_extend_less_and_stop_at_the_cap() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.item_templates[SPELL_TEMPLATE].used = USE_ACTIVE;
+            gs.map[(10 + 10 * SERVER_MAPX) as usize].flags |= MF_SHRINE;
+            gs.characters[cn].gold = 10_000;
+
+            gs.do_pray(cn);
+            let in_ = blessing(gs, cn).expect("blessed");
+            let full = DEFAULT_SHRINE.blessing_ticks(0);
+            assert_eq!(gs.items[in_].active, full);
+            assert_eq!(gs.characters[cn].gold, 8_000);
+
+            gs.do_pray(cn);
+            assert_eq!(blessing(gs, cn), Some(in_));
+            assert_eq!(gs.items[in_].active, full + full / 2);
+
+            gs.do_pray(cn);
+            gs.do_pray(cn);
+            assert_eq!(gs.characters[cn].gold, 2_000);
+            gs.do_pray(cn);
+            assert_eq!(gs.characters[cn].gold, 2_000);
+            assert_eq!(gs.items[in_].data[0], MAX_BLESSING_STACKS);
+        });
+    }
+
+    #[test]
+    fn praying_needs_a_shrine_and_the_offering() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.item_templates[SPELL_TEMPLATE].used = USE_ACTIVE;
+            gs.characters[cn].gold = 10_000;
+            gs.do_pray(cn);
+            assert_eq!(blessing(gs, cn), None);
+
+            gs.map[(10 + 10 * SERVER_MAPX) as usize].flags |= MF_SHRINE;
+            gs.characters[cn].gold = 100;
+            gs.do_pray(cn);
+            assert_eq!(blessing(gs, cn), None);
+            assert_eq!(gs.characters[cn].gold, 100);
+        });
+    }
+}
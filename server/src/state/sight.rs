@@ -0,0 +1,303 @@
+//! Line-of-sight and lighting maths that only read the map.
+//!
+//! These are the `&GameState`-free cores of `can_map_see`, `compute_dlight`
+//! and `do_add_light`. Taking plain slices lets the parallel tick phases in
+//! [`crate::tick_phases`] run them on worker threads; the `GameState`
+//! methods in `visibility.rs` call the same functions so both paths always
+//! agree.
+
+use core::constants::{
+    CharacterFlags, ItemFlags, LIGHTDIST, MF_INDOORS, MF_NOMONST, MF_SIGHTBLOCK, SERVER_MAPX,
+    SERVER_MAPY, VISI_BUFFER_LEN, VISI_CENTER, VISI_STRIDE,
+};
+use core::traits;
+use core::types::{Character, Item, Map};
+
+/// A see-map buffer as stored in `SeeMap::vis`.
+pub(crate) type VisBuffer = [i8; VISI_BUFFER_LEN];
+
+/// Whether `ch` is held to monster sight rules (no view into
+/// `MF_NOMONST` tiles): monsters that are neither usurped nor thralls.
+///
+/// # Arguments
+///
+/// * `ch` - Character whose see map is built.
+///
+/// # Returns
+///
+/// * `true` if monster sight rules apply.
+pub(crate) fn sees_as_monster(ch: &Character) -> bool {
+    ch.kindred & traits::KIN_MONSTER as i32 != 0
+        && ch.flags & (CharacterFlags::Usurp.bits() | CharacterFlags::Thrall.bits()) == 0
+}
+
+/// Port of `check_map_see(x,y)`: whether tile `(x, y)` lets sight through.
+///
+/// # Arguments
+///
+/// * `map` - World map.
+/// * `items` - Item table, for `IF_SIGHTBLOCK` items on the tile.
+/// * `x, y` - Tile coordinates.
+/// * `is_monster` - Monsters also cannot see into `MF_NOMONST` tiles.
+///
+/// # Returns
+///
+/// * `true` if the tile does not block line of sight.
+fn tile_passes_sight(map: &[Map], items: &[Item], x: i32, y: i32, is_monster: bool) -> bool {
+    if x <= 0 || x >= SERVER_MAPX || y <= 0 || y >= SERVER_MAPY {
+        return false;
+    }
+    let m = (x + y * SERVER_MAPX) as usize;
+    let mut blocking = u64::from(MF_SIGHTBLOCK);
+    if is_monster {
+        blocking |= u64::from(MF_NOMONST);
+    }
+    if map[m].flags & blocking != 0 {
+        return false;
+    }
+    let item_idx = map[m].it as usize;
+    !(item_idx != 0
+        && item_idx < items.len()
+        && items[item_idx].flags & ItemFlags::IF_SIGHTBLOCK.bits() != 0)
+}
+
+/// Buffer index of `(x, y)` relative to origin `(ox, oy)`, if inside.
+fn vis_index(ox: i32, oy: i32, x: i32, y: i32) -> Option<usize> {
+    let rx = x - ox + VISI_CENTER;
+    let ry = y - oy + VISI_CENTER;
+    let stride = VISI_STRIDE as i32;
+    if (0..stride).contains(&rx) && (0..stride).contains(&ry) {
+        Some((rx + ry * stride) as usize)
+    } else {
+        None
+    }
+}
+
+/// Values of the eight neighbours of buffer cell `(x, y)`.
+///
+/// `x` and `y` are buffer coordinates and must leave a one-cell border.
+fn neighbours(visi: &VisBuffer, x: i32, y: i32) -> [i8; 8] {
+    let stride = VISI_STRIDE as i32;
+    let at = |dx: i32, dy: i32| visi[((x + dx) + (y + dy) * stride) as usize];
+    [
+        at(1, 0),
+        at(-1, 0),
+        at(0, 1),
+        at(0, -1),
+        at(1, 1),
+        at(1, -1),
+        at(-1, 1),
+        at(-1, -1),
+    ]
+}
+
+/// Buffer coordinates of `(x, y)` if it leaves room for a neighbour check.
+fn inner_cell(ox: i32, oy: i32, x: i32, y: i32) -> Option<(i32, i32)> {
+    let bx = x - ox + VISI_CENTER;
+    let by = y - oy + VISI_CENTER;
+    let edge = VISI_STRIDE as i32 - 1;
+    (bx > 0 && bx < edge && by > 0 && by < edge).then_some((bx, by))
+}
+
+/// Port of `can_map_see(fx,fy,max_distance)`: fills `visi` with the
+/// line-of-sight wave from `(fx, fy)`.
+///
+/// # Arguments
+///
+/// * `map` - World map.
+/// * `items` - Item table.
+/// * `visi` - Buffer to overwrite.
+/// * `fx, fy` - Origin.
+/// * `max_distance` - Radius of the wave.
+/// * `is_monster` - Whether monster sight rules apply.
+pub(crate) fn fill_see_map(
+    map: &[Map],
+    items: &[Item],
+    visi: &mut VisBuffer,
+    fx: i32,
+    fy: i32,
+    max_distance: i32,
+    is_monster: bool,
+) {
+    visi.fill(0);
+    let add = |visi: &mut VisBuffer, x: i32, y: i32, value: i32| {
+        if let Some(index) = vis_index(fx, fy, x, y)
+            && visi[index] == 0
+        {
+            visi[index] = value as i8;
+        }
+    };
+    let reaches = |visi: &VisBuffer, x: i32, y: i32, value: i8| {
+        tile_passes_sight(map, items, x, y, is_monster)
+            && inner_cell(fx, fy, x, y)
+                .is_some_and(|(bx, by)| neighbours(visi, bx, by).contains(&value))
+    };
+
+    add(visi, fx, fy, 1);
+    for dist in 1..=max_distance {
+        let value = dist as i8;
+        // Top and bottom rows, then the sides without the corners.
+        for x in (fx - dist)..=(fx + dist) {
+            for y in [fy - dist, fy + dist] {
+                if reaches(visi, x, y, value) {
+                    add(visi, x, y, dist + 1);
+                }
+            }
+        }
+        for y in (fy - dist + 1)..=(fy + dist - 1) {
+            for x in [fx - dist, fx + dist] {
+                if reaches(visi, x, y, value) {
+                    add(visi, x, y, dist + 1);
+                }
+            }
+        }
+    }
+}
+
+/// Port of `check_vis(x,y)`: visibility of `(x, y)` in a filled buffer.
+///
+/// # Arguments
+///
+/// * `visi` - Buffer filled around `(ox, oy)`.
+/// * `ox, oy` - Origin the buffer was filled from.
+/// * `x, y` - Target tile.
+///
+/// # Returns
+///
+/// * `0` if not visible, otherwise the best neighbouring value (1 = best).
+pub(crate) fn vis_value(visi: &VisBuffer, ox: i32, oy: i32, x: i32, y: i32) -> i32 {
+    let Some((bx, by)) = inner_cell(ox, oy, x, y) else {
+        return 0;
+    };
+    neighbours(visi, bx, by)
+        .into_iter()
+        .filter(|&v| v != 0)
+        .min()
+        .map_or(0, i32::from)
+}
+
+/// Port of `compute_dlight(xc,yc)`: daylight reaching indoor tile
+/// `(xc, yc)` from the nearest visible outdoor tiles.
+///
+/// # Arguments
+///
+/// * `map` - World map.
+/// * `items` - Item table.
+/// * `xc, yc` - Indoor tile.
+///
+/// # Returns
+///
+/// * The tile's `dlight` value (0..=256).
+pub(crate) fn indoor_dlight(map: &[Map], items: &[Item], xc: i32, yc: i32) -> u16 {
+    let mut visi = [0i8; VISI_BUFFER_LEN];
+    fill_see_map(map, items, &mut visi, xc, yc, LIGHTDIST, false);
+
+    let mut best = 0;
+    for y in (yc - LIGHTDIST).max(0)..(yc + 1 + LIGHTDIST).min(SERVER_MAPY - 1) {
+        for x in (xc - LIGHTDIST).max(0)..(xc + 1 + LIGHTDIST).min(SERVER_MAPX - 1) {
+            let (dx, dy) = (xc - x, yc - y);
+            if dx * dx + dy * dy > LIGHTDIST * LIGHTDIST + 1 {
+                continue;
+            }
+            if map[(x + y * SERVER_MAPX) as usize].flags & u64::from(MF_INDOORS) != 0 {
+                continue;
+            }
+            let denom = vis_value(&visi, xc, yc, x, y) * (dx.abs() + dy.abs());
+            if denom > 0 {
+                best = best.max(256 / denom);
+            }
+        }
+    }
+    best.min(256) as u16
+}
+
+/// Port of the spreading part of `do_add_light(x,y,strength)`: the light
+/// change of every tile around the source, excluding the source itself.
+///
+/// # Arguments
+///
+/// * `map` - World map.
+/// * `items` - Item table.
+/// * `xc, yc` - Light source.
+/// * `strength` - Source strength; negative to remove light.
+///
+/// # Returns
+///
+/// * `(map index, delta)` pairs in row-major order.
+pub(crate) fn light_spread(
+    map: &[Map],
+    items: &[Item],
+    xc: i32,
+    yc: i32,
+    strength: i32,
+) -> Vec<(usize, i32)> {
+    let mut visi = [0i8; VISI_BUFFER_LEN];
+    fill_see_map(map, items, &mut visi, xc, yc, LIGHTDIST, false);
+
+    let sign = strength.signum();
+    let strength = strength.abs();
+    let mut deltas = Vec::new();
+    for y in (yc - LIGHTDIST).max(0)..(yc + 1 + LIGHTDIST).min(SERVER_MAPY - 1) {
+        for x in (xc - LIGHTDIST).max(0)..(xc + 1 + LIGHTDIST).min(SERVER_MAPX - 1) {
+            if x == xc && y == yc {
+                continue;
+            }
+            let (dx, dy) = ((x - xc).abs(), (y - yc).abs());
+            if dx * dx + dy * dy > LIGHTDIST * LIGHTDIST + 1 {
+                continue;
+            }
+            let v = vis_value(&visi, xc, yc, x, y);
+            if v != 0 {
+                let d = strength / (v * (dx + dy));
+                deltas.push(((y * SERVER_MAPX + x) as usize, sign * d));
+            }
+        }
+    }
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_map() -> (Vec<Map>, Vec<Item>) {
+        let map = vec![Map::default(); (SERVER_MAPX * SERVER_MAPY) as usize];
+        (map, vec![Item::default(); 4])
+    }
+
+    #[test]
+    fn walls_block_sight_and_light() {
+        let (mut map, items) = open_map();
+        // A wall column two tiles east of the origin.
+        for y in 90..=110 {
+            map[(102 + y * SERVER_MAPX) as usize].flags |= u64::from(MF_SIGHTBLOCK);
+        }
+        let mut visi = [0i8; VISI_BUFFER_LEN];
+        fill_see_map(&map, &items, &mut visi, 100, 100, LIGHTDIST, false);
+
+        assert_eq!(vis_value(&visi, 100, 100, 101, 100), 1);
+        assert_eq!(vis_value(&visi, 100, 100, 104, 100), 0);
+        assert_eq!(vis_value(&visi, 100, 100, 98, 100), 2);
+
+        let lit: Vec<usize> = light_spread(&map, &items, 100, 100, 100)
+            .into_iter()
+            .map(|(m, _)| m)
+            .collect();
+        assert!(lit.contains(&((100 * SERVER_MAPX + 98) as usize)));
+        assert!(!lit.contains(&((100 * SERVER_MAPX + 104) as usize)));
+    }
+
+    #[test]
+    fn indoor_tiles_next_to_open_sky_get_daylight() {
+        let (mut map, items) = open_map();
+        for x in 200..=210 {
+            map[(x + 200 * SERVER_MAPX) as usize].flags |= u64::from(MF_INDOORS);
+        }
+        assert_eq!(indoor_dlight(&map, &items, 205, 200), 256);
+
+        for tile in map.iter_mut() {
+            tile.flags |= u64::from(MF_INDOORS);
+        }
+        assert_eq!(indoor_dlight(&map, &items, 205, 200), 0);
+    }
+}
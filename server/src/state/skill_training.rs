@@ -0,0 +1,288 @@
+//! Skill trainers: offer lists and buying skills for gold.
+//!
+//! Picking "Train" on an NPC listed in [`core::skill_trainers::SKILL_TRAINERS`]
+//! sends `SV_TRAINEROFFERS`; `CmdLearnSkill` then buys one offer. The
+//! client only displays what it is told — visibility, the offer itself,
+//! prerequisites and gold are all checked again here before teaching.
+
+use core::constants::{CharacterFlags, MAXCHARS, USE_ACTIVE};
+use core::server_commands::ServerCommandType;
+use core::skill_trainers::{
+    self, OfferStatus, TRAINER_OFFER_ENTRY_LEN, TRAINER_OFFERS_PACKET_LEN, TrainerOffer,
+    TrainingPrereq,
+};
+use core::skills::{self, SkillIndex};
+use core::types::{FontColor, PlayerSlot};
+use server::journal::GoldTransferKind;
+
+use crate::game_state::GameState;
+use crate::network_manager;
+use crate::player::quest_log;
+
+impl GameState {
+    /// Returns the trainer definition for NPC `co`.
+    ///
+    /// # Arguments
+    ///
+    /// * `co` - Character to check.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&SkillTrainer)` for active, non-player trainers, else `None`.
+    pub(crate) fn skill_trainer_of(
+        &self,
+        co: usize,
+    ) -> Option<&'static skill_trainers::SkillTrainer> {
+        if co == 0 || co >= MAXCHARS {
+            return None;
+        }
+        let ch = &self.characters[co];
+        if ch.used != USE_ACTIVE
+            || ch.flags & (CharacterFlags::Player.bits() | CharacterFlags::Body.bits()) != 0
+        {
+            return None;
+        }
+        skill_trainers::find_trainer(ch.temp)
+    }
+
+    /// Computes whether player `cn` may buy `offer` (ignoring the price).
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Player character.
+    /// * `offer` - Trainer offer to check.
+    ///
+    /// # Returns
+    ///
+    /// * The offer's status for this player.
+    pub(crate) fn trainer_offer_status(&self, cn: usize, offer: &TrainerOffer) -> OfferStatus {
+        let ch = &self.characters[cn];
+        if ch.skill[offer.skill][SkillIndex::BaseValue as usize] != 0 {
+            return OfferStatus::Known;
+        }
+        if ch.skill[offer.skill][SkillIndex::MaxValue as usize] == 0 {
+            return OfferStatus::CannotLearn;
+        }
+        let met = match offer.prereq {
+            TrainingPrereq::None => true,
+            TrainingPrereq::Skill(skill) => ch.skill[skill][SkillIndex::BaseValue as usize] != 0,
+            TrainingPrereq::Quest(npc_template_id) => quest_log::has_completed(ch, npc_template_id),
+        };
+        if met {
+            OfferStatus::Available
+        } else {
+            OfferStatus::MissingPrereq
+        }
+    }
+
+    /// Sends trainer `co`'s offer list to player `cn` as `SV_TRAINEROFFERS`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Player character.
+    /// * `co` - Trainer NPC.
+    pub(crate) fn do_send_trainer_offers(&mut self, cn: usize, co: usize) {
+        let Some(trainer) = self.skill_trainer_of(co) else {
+            return;
+        };
+        let Some(nr) = self.owning_player(cn).map(PlayerSlot::index) else {
+            return;
+        };
+
+        let mut buf = [0u8; TRAINER_OFFERS_PACKET_LEN];
+        buf[0] = ServerCommandType::TrainerOffers as u8;
+        buf[1..3].copy_from_slice(&(co as u16).to_le_bytes());
+        let offers =
+            &trainer.offers[..trainer.offers.len().min(skill_trainers::MAX_TRAINER_OFFERS)];
+        buf[3] = offers.len() as u8;
+        for (i, offer) in offers.iter().enumerate() {
+            let off = 4 + i * TRAINER_OFFER_ENTRY_LEN;
+            buf[off] = offer.skill as u8;
+            buf[off + 1..off + 5].copy_from_slice(&offer.price.to_le_bytes());
+            buf[off + 5] = self.trainer_offer_status(cn, offer) as u8;
+        }
+        network_manager::xsend(self, nr, &buf, TRAINER_OFFERS_PACKET_LEN);
+    }
+
+    /// Handles `CmdLearnSkill`: player `cn` buys `skill` from trainer `co`.
+    ///
+    /// The trainer explains any refusal; on success the skill is taught,
+    /// the gold is taken and the refreshed offer list is sent back.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Player character.
+    /// * `co` - Trainer NPC.
+    /// * `skill` - Requested skill index.
+    pub(crate) fn do_learn_skill(&mut self, cn: usize, co: usize, skill: usize) {
+        let Some(trainer) = self.skill_trainer_of(co) else {
+            log::debug!(
+                "Character {} tried to learn skill {} from non-trainer {}",
+                cn,
+                skill,
+                co
+            );
+            return;
+        };
+        if self.do_char_can_see(cn, co) == 0 {
+            return;
+        }
+        let Some(offer) = trainer.offers.iter().find(|o| o.skill == skill) else {
+            log::debug!(
+                "Character {} asked trainer {} for unoffered skill {}",
+                cn,
+                co,
+                skill
+            );
+            return;
+        };
+
+        let player_name = self.characters[cn].get_name().to_owned();
+        let skill_name = skills::get_skill_name(offer.skill);
+        match self.teach_trainer_skill(cn, offer) {
+            Ok(()) => {
+                self.do_sayx(
+                    co,
+                    &format!("Now I'll teach you {}, {}.", skill_name, player_name),
+                );
+                self.do_character_log(
+                    cn,
+                    FontColor::Green,
+                    &format!(
+                        "You learned {} for {}G {}S!\n",
+                        skill_name,
+                        offer.price / 100,
+                        offer.price % 100
+                    ),
+                );
+                chlog!(
+                    cn,
+                    "Learned {} from {} for {}G {}S",
+                    skill_name,
+                    self.characters[co].get_name(),
+                    offer.price / 100,
+                    offer.price % 100
+                );
+                self.do_send_trainer_offers(cn, co);
+            }
+            Err(reason) => self.do_sayx(co, &format!("{}, {}.", reason, player_name)),
+        }
+    }
+
+    /// Validates and applies a skill purchase. Visibility and the offer
+    /// lookup are the caller's job.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Player character.
+    /// * `offer` - Offer being bought.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` once the skill is taught and paid for.
+    /// * `Err(reason)` with the trainer's refusal otherwise; nothing changes.
+    fn teach_trainer_skill(&mut self, cn: usize, offer: &TrainerOffer) -> Result<(), String> {
+        let skill_name = skills::get_skill_name(offer.skill);
+        match self.trainer_offer_status(cn, offer) {
+            OfferStatus::Available => {}
+            OfferStatus::Known => return Err(format!("You already know {}", skill_name)),
+            OfferStatus::CannotLearn => {
+                return Err(format!("I cannot teach {} to one of your kind", skill_name));
+            }
+            OfferStatus::MissingPrereq => {
+                return Err(match offer.prereq {
+                    TrainingPrereq::Skill(skill) => format!(
+                        "You must learn {} before {}",
+                        skills::get_skill_name(skill),
+                        skill_name
+                    ),
+                    _ => format!(
+                        "Prove yourself on a quest first, then I will teach you {}",
+                        skill_name
+                    ),
+                });
+            }
+        }
+        if self.characters[cn].gold < offer.price as i32 {
+            return Err(format!(
+                "{} costs {}G {}S. Come back when you can pay",
+                skill_name,
+                offer.price / 100,
+                offer.price % 100
+            ));
+        }
+
+        self.transfer_gold(cn, 0, offer.price as i32, GoldTransferKind::TrainerFee);
+        let ch = &mut self.characters[cn];
+        ch.skill[offer.skill][SkillIndex::BaseValue as usize] = 1;
+        ch.set_do_update_flags();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::constants::CharacterFlags;
+    use core::skill_trainers::{OfferStatus, TrainerOffer, TrainingPrereq};
+    use core::skills::{self, SkillIndex};
+
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    const MEDITATE: TrainerOffer = TrainerOffer {
+        skill: skills::SK_MEDIT,
+        price: 10_000,
+        prereq: TrainingPrereq::Skill(skills::SK_REST),
+    };
+
+    #[test]
+    fn status_checks_race_prereq_and_known() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            assert_eq!(
+                gs.trainer_offer_status(cn, &MEDITATE),
+                OfferStatus::CannotLearn
+            );
+
+            gs.characters[cn].skill[skills::SK_MEDIT][SkillIndex::MaxValue as usize] = 100;
+            assert_eq!(
+                gs.trainer_offer_status(cn, &MEDITATE),
+                OfferStatus::MissingPrereq
+            );
+
+            gs.characters[cn].skill[skills::SK_REST][SkillIndex::BaseValue as usize] = 1;
+            assert_eq!(
+                gs.trainer_offer_status(cn, &MEDITATE),
+                OfferStatus::Available
+            );
+
+            gs.characters[cn].skill[skills::SK_MEDIT][SkillIndex::BaseValue as usize] = 1;
+            assert_eq!(gs.trainer_offer_status(cn, &MEDITATE), OfferStatus::Known);
+        });
+    }
+
+    #[test]
+    fn purchase_requires_gold_and_teaches_skill() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].skill[skills::SK_MEDIT][SkillIndex::MaxValue as usize] = 100;
+            gs.characters[cn].skill[skills::SK_REST][SkillIndex::BaseValue as usize] = 1;
+            gs.characters[cn].gold = 9_999;
+
+            assert!(gs.teach_trainer_skill(cn, &MEDITATE).is_err());
+            assert_eq!(gs.characters[cn].gold, 9_999);
+            assert_eq!(
+                gs.characters[cn].skill[skills::SK_MEDIT][SkillIndex::BaseValue as usize],
+                0
+            );
+
+            gs.characters[cn].gold = 12_000;
+            assert!(gs.teach_trainer_skill(cn, &MEDITATE).is_ok());
+            assert_eq!(gs.characters[cn].gold, 2_000);
+            assert_eq!(
+                gs.characters[cn].skill[skills::SK_MEDIT][SkillIndex::BaseValue as usize],
+                1
+            );
+            assert_ne!(gs.characters[cn].flags & CharacterFlags::Update.bits(), 0);
+        });
+    }
+}
@@ -0,0 +1,157 @@
+//! Starter kit grant for brand-new characters.
+//!
+//! Kit format and the built-in defaults live in [`core::starter_kits`].
+//! The server reads its kit set once at startup via [`load_starter_kits`];
+//! `apply_api_login_character_record` then creates a new character without
+//! the legacy template items and calls [`GameState::grant_starter_kit`].
+
+use core::constants::USE_EMPTY;
+use core::starter_kits::{self, KitArchetype, StarterKit};
+use server::journal::GoldTransferKind;
+
+use crate::game_state::GameState;
+use crate::god::God;
+
+/// Environment variable selecting the starter kits: unset for the built-in
+/// kits, `legacy` for the race template items, or a path to a kit file.
+pub(crate) const STARTER_KITS_ENV: &str = "MAG_STARTER_KITS";
+
+/// Resolves the starter kit configuration.
+///
+/// # Arguments
+///
+/// * `setting` - Value of [`STARTER_KITS_ENV`], if set.
+///
+/// # Returns
+///
+/// * `Ok(Some(kits))` for kits, `Ok(None)` for the legacy template items,
+///   or an error if the kit file cannot be read or parsed.
+pub(crate) fn load_starter_kits(setting: Option<&str>) -> Result<Option<Vec<StarterKit>>, String> {
+    match setting.map(str::trim) {
+        None | Some("") => Ok(Some(starter_kits::default_kits())),
+        Some("legacy") => Ok(None),
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read starter kits '{}': {}", path, e))?;
+            starter_kits::parse_kits(&text)
+                .map(Some)
+                .map_err(|e| format!("invalid starter kits '{}': {}", path, e))
+        }
+    }
+}
+
+impl GameState {
+    /// Gives a brand-new character the kit for its archetype.
+    ///
+    /// Either the whole kit is granted or nothing is: every item is created
+    /// before any of them is handed over.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Newly created character.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the kit was granted or none applies, otherwise why not.
+    pub(crate) fn grant_starter_kit(&mut self, cn: usize) -> Result<(), String> {
+        let archetype = KitArchetype::for_character(&self.characters[cn]);
+        let Some(kit) = self
+            .starter_kits
+            .as_ref()
+            .and_then(|kits| kits.iter().find(|k| k.archetype == archetype))
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        let free_slots: Vec<usize> = (0..self.characters[cn].item.len())
+            .filter(|&n| self.characters[cn].item[n] == 0)
+            .collect();
+        if free_slots.len() < kit.items.len() {
+            return Err(format!(
+                "{:?} kit needs {} free slots, character {} has {}",
+                archetype,
+                kit.items.len(),
+                cn,
+                free_slots.len()
+            ));
+        }
+
+        let mut created = Vec::with_capacity(kit.items.len());
+        for &template in &kit.items {
+            match God::create_item(self, usize::from(template)) {
+                Some(item_idx) => created.push(item_idx),
+                None => {
+                    for item_idx in created {
+                        self.items[item_idx].used = USE_EMPTY;
+                    }
+                    return Err(format!(
+                        "{:?} kit item template {} could not be created",
+                        archetype, template
+                    ));
+                }
+            }
+        }
+
+        for (item_idx, slot) in created.into_iter().zip(free_slots) {
+            let item = &mut self.items[item_idx];
+            item.x = 0;
+            item.y = 0;
+            item.carried = cn as u16;
+            self.characters[cn].item[slot] = item_idx as u32;
+        }
+        self.transfer_gold(0, cn, kit.gold as i32, GoldTransferKind::StarterKit);
+        self.characters[cn].set_do_update_flags();
+
+        log::info!(
+            "Granted {:?} starter kit to {} ({} items, {} silver)",
+            archetype,
+            self.characters[cn].get_name(),
+            kit.items.len(),
+            kit.gold
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::constants::USE_ACTIVE;
+
+    #[test]
+    fn kit_is_granted_whole_or_not_at_all() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            for template in [31, 57, 59, 101] {
+                gs.item_templates[template].used = USE_ACTIVE;
+            }
+
+            // The armor template is missing: nothing may be handed out.
+            gs.item_templates[59].used = USE_EMPTY;
+            assert!(gs.grant_starter_kit(cn).is_err());
+            assert!(gs.characters[cn].item.iter().all(|&it| it == 0));
+            assert_eq!(gs.characters[cn].gold, 0);
+            assert!(gs.items.iter().all(|it| it.used == USE_EMPTY));
+
+            gs.item_templates[59].used = USE_ACTIVE;
+            assert_eq!(gs.grant_starter_kit(cn), Ok(()));
+            let granted: Vec<u16> = gs.characters[cn]
+                .item
+                .iter()
+                .filter(|&&it| it != 0)
+                .map(|&it| gs.items[it as usize].temp)
+                .collect();
+            assert_eq!(granted, vec![31, 57, 59, 101, 101, 101]);
+            assert_eq!(gs.characters[cn].gold, 500);
+        });
+    }
+
+    #[test]
+    fn legacy_setting_disables_kits() {
+        assert_eq!(load_starter_kits(Some("legacy")), Ok(None));
+        assert!(load_starter_kits(None).unwrap().is_some());
+        assert!(load_starter_kits(Some("/nonexistent/kits.txt")).is_err());
+    }
+}
@@ -1,3 +1,6 @@
+use core::balance;
+use core::chat::{ChatChannel, ChatStyle};
+use core::combat_log::{COMBAT_FLAG_KILLED, CombatEvent, DamageKind};
 use core::constants::{
     CharacterFlags, ItemFlags, MAX_SPEEDTAB_SPEED_INDEX, MAXCHARS, MIN_SPEEDTAB_INDEX,
 };
@@ -11,7 +14,8 @@ use core::{skills, traits};
 use crate::effect::EffectManager;
 use crate::game_state::GameState;
 use crate::god::God;
-use crate::{driver, helpers, points};
+use crate::player::combat_log::{HitSource, report_hit};
+use crate::{driver, helpers};
 
 impl GameState {
     /// Helper function to check if character wears a specific item
@@ -705,11 +709,7 @@ impl GameState {
                 if killed {
                     let spell_name = self.items[spell_item as usize].get_name().to_owned();
                     log::info!("Character {} killed by spell: {}", cn, spell_name);
-                    self.do_character_log(
-                        cn,
-                        FontColor::Red,
-                        &format!("The {} killed you!\n", spell_name),
-                    );
+                    self.do_combat_log(cn, &format!("The {} killed you!\n", spell_name));
                     self.do_area_log(
                         cn,
                         0,
@@ -857,11 +857,7 @@ impl GameState {
                         if self.characters[cn].a_hp < 500 {
                             self.characters[cn].a_hp = 500;
                             let spell_name = self.items[spell_item as usize].get_name().to_owned();
-                            self.do_character_log(
-                                cn,
-                                FontColor::Red,
-                                &format!("The {} killed you!\n", spell_name),
-                            );
+                            self.do_combat_log(cn, &format!("The {} killed you!\n", spell_name));
                             self.do_character_killed(cn, caster, false);
                             return;
                         }
@@ -1041,7 +1037,7 @@ impl GameState {
         }
 
         // Calculate points needed to raise this attribute
-        let points_needed = points::attrib_needed(i32::from(current_val), i32::from(diff));
+        let points_needed = balance::attrib_needed(i32::from(current_val), i32::from(diff));
 
         if points_needed > available_points {
             return false;
@@ -1074,7 +1070,7 @@ impl GameState {
             return false;
         }
 
-        let points_needed = points::hp_needed(i32::from(current_val), i32::from(diff));
+        let points_needed = balance::hp_needed(i32::from(current_val), i32::from(diff));
 
         if points_needed > available_points {
             return false;
@@ -1106,7 +1102,7 @@ impl GameState {
             return false;
         }
 
-        let points_needed = points::end_needed(i32::from(current_val), i32::from(diff));
+        let points_needed = balance::end_needed(i32::from(current_val), i32::from(diff));
 
         if points_needed > available_points {
             return false;
@@ -1140,7 +1136,7 @@ impl GameState {
             return false;
         }
 
-        let points_needed = points::mana_needed(i32::from(current_val), i32::from(diff));
+        let points_needed = balance::mana_needed(i32::from(current_val), i32::from(diff));
 
         if points_needed > available_points {
             return false;
@@ -1182,7 +1178,7 @@ impl GameState {
             return false;
         }
 
-        let points_needed = points::skill_needed(i32::from(current_val), i32::from(diff));
+        let points_needed = balance::skill_needed(i32::from(current_val), i32::from(diff));
 
         if points_needed > available_points {
             return false;
@@ -1217,7 +1213,7 @@ impl GameState {
         let new_val = self.characters[cn].hp[0];
         let diff = self.characters[cn].hp[3];
 
-        let points_lost = points::hp_needed(i32::from(new_val), i32::from(diff));
+        let points_lost = balance::hp_needed(i32::from(new_val), i32::from(diff));
 
         self.characters[cn].points_tot -= points_lost;
 
@@ -1248,7 +1244,7 @@ impl GameState {
         let new_val = self.characters[cn].mana[0];
         let diff = self.characters[cn].mana[3];
 
-        let points_lost = points::mana_needed(i32::from(new_val), i32::from(diff));
+        let points_lost = balance::mana_needed(i32::from(new_val), i32::from(diff));
 
         self.characters[cn].points_tot -= points_lost;
         self.do_update_char(cn);
@@ -1399,10 +1395,12 @@ impl GameState {
 
             self.do_update_char(cn);
 
-            let player_id = self.characters[cn].player as usize;
-            if player_id > 0 && player_id < self.players.len() && self.players[player_id].usnr == cn
-            {
-                crate::player::commands::send_set_char_talents(self, player_id);
+            if let Some(nr) = self.owning_player(cn) {
+                crate::player::commands::send_set_char_talents(self, nr.index());
+            }
+
+            if self.refresh_earned_titles(cn) {
+                self.send_titles_to_owner(cn);
             }
         }
     }
@@ -1423,6 +1421,32 @@ impl GameState {
     /// # Returns
     /// Actual damage dealt in game units (after internal scaling/truncation)
     pub(crate) fn do_hurt(&mut self, cn: usize, co: usize, dam: i32, type_hurt: i32) -> i32 {
+        self.do_hurt_from(cn, co, dam, type_hurt, HitSource::default())
+    }
+
+    /// [`Self::do_hurt`] for callers that know which skill caused the hit
+    /// or how much of it spell immunity resisted.
+    ///
+    /// Every hit is reported as an `SV_COMBATEVENT` to the players on both
+    /// ends of it; `source` fills in the parts `do_hurt` cannot work out.
+    ///
+    /// # Arguments
+    /// * `cn` - Attacker character id
+    /// * `co` - Target character id
+    /// * `dam` - Raw damage value (scaled internally)
+    /// * `type_hurt` - Damage type code (influences scaling/FX)
+    /// * `source` - Skill and resisted damage of the hit
+    ///
+    /// # Returns
+    /// Actual damage dealt in game units (after internal scaling/truncation)
+    pub(crate) fn do_hurt_from(
+        &mut self,
+        cn: usize,
+        co: usize,
+        dam: i32,
+        type_hurt: i32,
+        source: HitSource,
+    ) -> i32 {
         // Spectral Pact: high bit of `type_hurt` is reserved as a recursion
         // sentinel so a redirected hit on the companion does not itself
         // trigger another redirect. Strip it before any of the existing
@@ -1476,7 +1500,13 @@ impl GameState {
                             // figure; the headline number reported back to
                             // the attacker still reflects only the portion
                             // that landed on the original target.
-                            self.do_hurt(cn, cc, per_companion, type_hurt | PACT_REDIRECT_BIT);
+                            self.do_hurt_from(
+                                cn,
+                                cc,
+                                per_companion,
+                                type_hurt | PACT_REDIRECT_BIT,
+                                source,
+                            );
                         }
                         dam -= per_companion * n_companions;
                         if dam < 0 {
@@ -1565,7 +1595,22 @@ impl GameState {
         // Re-read armor after shield updates, matching C behavior.
         let co_armor = self.characters[co].armor;
 
-        // Compute damage scaling by type
+        // Compute damage scaling by type; the combat log reports the hit in
+        // the same thousandths of a hit point.
+        let kind = DamageKind::from_u8(type_hurt as u8);
+        let scale = match kind {
+            DamageKind::Melee => 250,
+            DamageKind::Reflect => 1000,
+            _ => 750,
+        };
+        let mut event = CombatEvent {
+            kind: kind as u8,
+            skill: source.skill_byte(cn, kind),
+            raw: (dam.max(0) * scale) as u32,
+            resisted: (source.resisted * scale) as u32,
+            ..CombatEvent::default()
+        };
+
         if type_hurt == 0 {
             dam -= i32::from(co_armor);
             if dam < 0 {
@@ -1623,7 +1668,11 @@ impl GameState {
             );
         }
 
+        event.dealt = dam.max(0) as u32;
+        event.mitigated = event.raw.saturating_sub(event.dealt);
+
         if dam < 1 {
+            report_hit(self, cn, co, event);
             return 0;
         }
 
@@ -1668,6 +1717,22 @@ impl GameState {
             );
         }
 
+        // Leave a fading mark on the tile for clients that draw decals.
+        let decal_kind = match type_hurt {
+            0 => Some(core::decals::DecalKind::Blood),
+            1 => Some(core::decals::DecalKind::Scorch),
+            _ => None,
+        };
+        if let Some(kind) = decal_kind {
+            crate::decals::emit_decal(
+                self,
+                i32::from(self.characters[co].x),
+                i32::from(self.characters[co].y),
+                kind,
+                dam >= 30000,
+            );
+        }
+
         // Combined map flags for arena checks (C includes both co/cn positions).
         let co_idx = (i32::from(self.characters[co].x)
             + i32::from(self.characters[co].y) * core::constants::SERVER_MAPX)
@@ -1688,6 +1753,8 @@ impl GameState {
             && (mf_flags & u64::from(core::constants::MF_ARENA)) == 0
             && helpers::random_mod_i32(10000) < 5000 + self.characters[co].luck
         {
+            report_hit(self, cn, co, event);
+
             // Save the character
             self.characters[co].a_hp = i32::from(self.characters[co].hp[5]) * 500;
             self.characters[co].luck /= 2;
@@ -1753,17 +1820,18 @@ impl GameState {
             return dam / 1000;
         }
 
+        if will_die_hp < 500 {
+            event.flags |= COMBAT_FLAG_KILLED;
+        }
+        report_hit(self, cn, co, event);
+
         // Subtract hp
         self.characters[co].a_hp -= dam;
 
         // Warn about low HP
         let cur_hp = self.characters[co].a_hp;
         if (500..8000).contains(&cur_hp) {
-            self.do_character_log(
-                co,
-                core::types::FontColor::Red,
-                "You're almost dead... Give running a try!\n",
-            );
+            self.do_combat_log(co, "You're almost dead... Give running a try!\n");
         }
 
         // Handle death
@@ -1771,31 +1839,22 @@ impl GameState {
             let cn_x = i32::from(self.characters[cn].x);
             let cn_y = i32::from(self.characters[cn].y);
             let co_name = self.characters[co].get_name().to_owned();
-            self.do_area_log(
+            self.do_area_styled_log(
                 cn,
                 co,
                 cn_x,
                 cn_y,
-                core::types::FontColor::Red,
+                ChatStyle::new(ChatChannel::Combat),
                 &format!("{} is dead!\n", co_name),
             );
             let cn_name = self.characters[cn].get_name().to_owned();
-            self.do_character_log(
-                cn,
-                core::types::FontColor::Red,
-                &format!("You killed {}.\n", co_name),
-            );
+            self.do_combat_log(cn, &format!("You killed {}.\n", co_name));
 
             if (self.characters[cn].flags & CharacterFlags::Invisible.bits()) != 0 {
-                self.do_character_log(
-                    co,
-                    core::types::FontColor::Red,
-                    "Oh dear, that blow was fatal. Somebody killed you...\n",
-                );
+                self.do_combat_log(co, "Oh dear, that blow was fatal. Somebody killed you...\n");
             } else {
-                self.do_character_log(
+                self.do_combat_log(
                     co,
-                    core::types::FontColor::Red,
                     &format!("Oh dear, that blow was fatal. {} killed you...\n", cn_name),
                 );
             }
@@ -0,0 +1,320 @@
+//! Character titles: unlocking, GM grants, and selection.
+//!
+//! Title definitions live in [`core::titles`]; this module owns the
+//! per-character state stored in `Character::future3` and the player-facing
+//! `#title` / `#granttitle` commands.
+
+use core::titles::{
+    TITLE_EARNED_SLOT, TITLE_SELECTED_SLOT, TitleSource, earned_titles, find_title, has_title,
+    rank_titles_mask, title_bit, title_by_id,
+};
+use core::types::FontColor;
+
+use crate::game_state::GameState;
+
+impl GameState {
+    /// Returns the earned-title bitmask for a character.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character id.
+    ///
+    /// # Returns
+    ///
+    /// * The earned mask, or `0` for NPCs.
+    pub(crate) fn earned_title_mask(&self, cn: usize) -> u32 {
+        let ch = &self.characters[cn];
+        if !ch.is_player() {
+            return 0;
+        }
+        ch.future3[TITLE_EARNED_SLOT] as u32
+    }
+
+    /// Returns the title a character currently displays.
+    ///
+    /// A stored selection that is no longer earned (e.g. revoked) reads as
+    /// no title.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character id.
+    ///
+    /// # Returns
+    ///
+    /// * The selected title id, or `0` for none.
+    pub(crate) fn selected_title(&self, cn: usize) -> u8 {
+        let ch = &self.characters[cn];
+        if !ch.is_player() {
+            return 0;
+        }
+        let id = u8::try_from(ch.future3[TITLE_SELECTED_SLOT]).unwrap_or(0);
+        if has_title(self.earned_title_mask(cn), id) {
+            id
+        } else {
+            0
+        }
+    }
+
+    /// Unlocks every rank-milestone title the character qualifies for and
+    /// announces newly earned ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character id.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when at least one new title was unlocked.
+    pub(crate) fn refresh_earned_titles(&mut self, cn: usize) -> bool {
+        if !self.characters[cn].is_player() {
+            return false;
+        }
+        let rank = self.characters[cn].data[45].max(0) as usize;
+        let old = self.earned_title_mask(cn);
+        let new = old | rank_titles_mask(rank);
+        if new == old {
+            return false;
+        }
+        self.characters[cn].future3[TITLE_EARNED_SLOT] = new as i32;
+
+        for title in earned_titles(new & !old) {
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                &format!(
+                    "You earned the title \"{}\". Choose it in your profile or with #title.\n",
+                    title.name
+                ),
+            );
+        }
+        true
+    }
+
+    /// Selects the title a player displays.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character id.
+    /// * `title_id` - Title to display, or `0` to clear.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` when the selection was stored.
+    /// * `Err(String)` with a player-facing reason otherwise.
+    pub(crate) fn set_selected_title(&mut self, cn: usize, title_id: u8) -> Result<(), String> {
+        if !self.characters[cn].is_player() {
+            return Err("Only players can display a title.".to_owned());
+        }
+        if title_id != 0 && !has_title(self.earned_title_mask(cn), title_id) {
+            return Err("You have not earned that title.".to_owned());
+        }
+        self.characters[cn].future3[TITLE_SELECTED_SLOT] = i32::from(title_id);
+        Ok(())
+    }
+
+    /// Sends the character's title snapshot to its player, if connected.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character id.
+    pub(crate) fn send_titles_to_owner(&mut self, cn: usize) {
+        if let Some(nr) = self.owning_player(cn) {
+            crate::player::commands::send_set_char_titles(self, nr.index());
+        }
+    }
+
+    /// Handles a title selection coming from the client or `#title`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character id.
+    /// * `title_id` - Title to display, or `0` to clear.
+    pub(crate) fn do_select_title(&mut self, cn: usize, title_id: u8) {
+        match self.set_selected_title(cn, title_id) {
+            Ok(()) => {
+                let msg = match title_by_id(title_id) {
+                    Some(t) => format!(
+                        "You are now known as {} {}.\n",
+                        t.name,
+                        self.characters[cn].get_name()
+                    ),
+                    None => "You no longer display a title.\n".to_owned(),
+                };
+                self.do_character_log(cn, FontColor::Green, &msg);
+            }
+            Err(reason) => self.do_character_log(cn, FontColor::Red, &format!("{}\n", reason)),
+        }
+        self.send_titles_to_owner(cn);
+    }
+
+    /// `#title [name|id|none]`: lists earned titles or selects one.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character issuing the command.
+    /// * `arg` - Title name or id; empty lists titles, `none` clears.
+    pub(crate) fn do_title(&mut self, cn: usize, arg: &str) {
+        let arg = arg.trim();
+        if arg.is_empty() {
+            let mask = self.earned_title_mask(cn);
+            let current = title_by_id(self.selected_title(cn)).map_or("none", |t| t.name);
+            self.do_character_log(
+                cn,
+                FontColor::Yellow,
+                &format!("Current title: {}\n", current),
+            );
+            let names: Vec<&str> = earned_titles(mask).map(|t| t.name).collect();
+            if names.is_empty() {
+                self.do_character_log(
+                    cn,
+                    FontColor::Yellow,
+                    "You have not earned any titles yet.\n",
+                );
+            } else {
+                self.do_character_log(
+                    cn,
+                    FontColor::Yellow,
+                    &format!("Earned titles: {}\n", names.join(", ")),
+                );
+            }
+            return;
+        }
+
+        if arg.eq_ignore_ascii_case("none") {
+            self.do_select_title(cn, 0);
+            return;
+        }
+
+        match find_title(arg) {
+            Some(title) => self.do_select_title(cn, title.id),
+            None => self.do_character_log(
+                cn,
+                FontColor::Red,
+                &format!("There is no title called \"{}\".\n", arg),
+            ),
+        }
+    }
+
+    /// `#granttitle <player> <title>`: grants (or, if already held, revokes)
+    /// a title on another character.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Staff character issuing the command.
+    /// * `target` - Target character name (or `self`).
+    /// * `title_arg` - Title name or id.
+    pub(crate) fn do_grant_title(&mut self, cn: usize, target: &str, title_arg: &str) {
+        let co = self.do_lookup_char_self(target, cn);
+        if co <= 0 || !self.characters[co as usize].is_player() {
+            self.do_character_log(
+                cn,
+                FontColor::Red,
+                &format!("No such player: '{}'\n", target),
+            );
+            return;
+        }
+        let co = co as usize;
+
+        let Some(title) = find_title(title_arg) else {
+            self.do_character_log(
+                cn,
+                FontColor::Red,
+                &format!("There is no title called \"{}\".\n", title_arg.trim()),
+            );
+            return;
+        };
+
+        let name = self.characters[co].get_name().to_owned();
+        let mask = self.earned_title_mask(co);
+        if has_title(mask, title.id) {
+            if matches!(title.source, TitleSource::Rank(_)) {
+                self.do_character_log(
+                    cn,
+                    FontColor::Red,
+                    &format!("{} is a rank title and cannot be revoked.\n", title.name),
+                );
+                return;
+            }
+            self.characters[co].future3[TITLE_EARNED_SLOT] = (mask & !title_bit(title.id)) as i32;
+            self.do_character_log(
+                cn,
+                FontColor::Green,
+                &format!("Revoked title {} from {}.\n", title.name, name),
+            );
+            log::info!(
+                "Character {} revoked title {} from {} ({})",
+                cn,
+                title.id,
+                co,
+                name
+            );
+        } else {
+            self.characters[co].future3[TITLE_EARNED_SLOT] = (mask | title_bit(title.id)) as i32;
+            self.do_character_log(
+                cn,
+                FontColor::Green,
+                &format!("Granted title {} to {}.\n", title.name, name),
+            );
+            self.do_character_log(
+                co,
+                FontColor::Yellow,
+                &format!(
+                    "You have been awarded the title \"{}\". Choose it in your profile or with #title.\n",
+                    title.name
+                ),
+            );
+            log::info!(
+                "Character {} granted title {} to {} ({})",
+                cn,
+                title.id,
+                co,
+                name
+            );
+        }
+        self.send_titles_to_owner(co);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::{add_test_player, with_test_gs};
+    use core::ranks::Rank;
+    use core::titles::{TITLE_EARNED_SLOT, title_bit};
+
+    #[test]
+    fn rank_titles_unlock_once() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.characters[cn].data[45] = Rank::SecondLieutenant as i32;
+            assert!(gs.refresh_earned_titles(cn));
+            assert!(!gs.refresh_earned_titles(cn));
+            let mask = gs.earned_title_mask(cn);
+            assert_eq!(mask, title_bit(1) | title_bit(2));
+        });
+    }
+
+    #[test]
+    fn selecting_requires_earned_title() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            assert!(gs.set_selected_title(cn, 16).is_err());
+            gs.characters[cn].future3[TITLE_EARNED_SLOT] = title_bit(16) as i32;
+            assert!(gs.set_selected_title(cn, 16).is_ok());
+            assert_eq!(gs.selected_title(cn), 16);
+            assert!(gs.set_selected_title(cn, 0).is_ok());
+            assert_eq!(gs.selected_title(cn), 0);
+        });
+    }
+
+    #[test]
+    fn revoked_title_is_no_longer_displayed() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.do_grant_title(cn, "self", "Hero");
+            assert!(gs.set_selected_title(cn, 16).is_ok());
+            gs.do_grant_title(cn, "self", "hero");
+            assert_eq!(gs.earned_title_mask(cn), 0);
+            assert_eq!(gs.selected_title(cn), 0);
+        });
+    }
+}
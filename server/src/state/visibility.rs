@@ -1,9 +1,10 @@
 use core::constants::{CharacterFlags, ItemFlags};
+use core::skills;
 use core::types::Character;
-use core::{skills, traits};
 use std::cmp;
 
 use crate::game_state::GameState;
+use crate::state::sight;
 
 impl GameState {
     #[inline]
@@ -42,8 +43,7 @@ impl GameState {
     ///
     /// Adds light originating at `(x_center, y_center)` and spreads it to
     /// nearby tiles according to line-of-sight. Negative `strength` values
-    /// remove light. Uses `can_see` to attenuate contribution based on
-    /// obstructions.
+    /// remove light. The spread is computed by [`sight::light_spread`].
     ///
     /// # Arguments
     /// * `x_center, y_center` - Source coordinates for the light
@@ -55,54 +55,10 @@ impl GameState {
 
         self.map[center_map_index].add_light(strength);
 
-        let mut strength = strength;
-        let flag = if strength < 0 {
-            strength = -strength;
-            1
-        } else {
-            0
-        };
-
-        let xs = cmp::max(0, x_center - core::constants::LIGHTDIST);
-        let ys = cmp::max(0, y_center - core::constants::LIGHTDIST);
-        let xe = cmp::min(
-            core::constants::SERVER_MAPX - 1,
-            x_center + 1 + core::constants::LIGHTDIST,
-        );
-        let ye = cmp::min(
-            core::constants::SERVER_MAPY - 1,
-            y_center + 1 + core::constants::LIGHTDIST,
-        );
-
-        for y in ys..ye {
-            for x in xs..xe {
-                if x == x_center && y == y_center {
-                    continue;
-                }
-
-                let dx = (x - x_center).abs();
-                let dy = (y - y_center).abs();
-
-                if (dx * dx + dy * dy)
-                    > (core::constants::LIGHTDIST * core::constants::LIGHTDIST + 1)
-                {
-                    continue;
-                }
-
-                let v = self.can_see(None, x_center, y_center, x, y, core::constants::LIGHTDIST);
-
-                if v != 0 {
-                    let d = strength / (v * ((x_center - x).abs() + (y_center - y).abs()));
-                    let map_index =
-                        (y as usize) * core::constants::SERVER_MAPX as usize + (x as usize);
-
-                    if flag == 1 {
-                        self.map[map_index].add_light(-d);
-                    } else {
-                        self.map[map_index].add_light(d);
-                    }
-                }
-            }
+        for (map_index, d) in
+            sight::light_spread(&self.map, &self.items, x_center, y_center, strength)
+        {
+            self.map[map_index].add_light(d);
         }
     }
 
@@ -115,62 +71,10 @@ impl GameState {
     /// # Arguments
     /// * `xc, yc` - Coordinates of the indoor tile to compute
     pub(crate) fn compute_dlight(&mut self, xc: i32, yc: i32) {
-        let xs = cmp::max(0, xc - core::constants::LIGHTDIST);
-        let ys = cmp::max(0, yc - core::constants::LIGHTDIST);
-        let xe = cmp::min(
-            core::constants::SERVER_MAPX - 1,
-            xc + 1 + core::constants::LIGHTDIST,
-        );
-        let ye = cmp::min(
-            core::constants::SERVER_MAPY - 1,
-            yc + 1 + core::constants::LIGHTDIST,
-        );
-
-        let mut best: i32 = 0;
-
-        for y in ys..ye {
-            for x in xs..xe {
-                let dx = xc - x;
-                let dy = yc - y;
-                if dx * dx + dy * dy > (core::constants::LIGHTDIST * core::constants::LIGHTDIST + 1)
-                {
-                    continue;
-                }
-
-                let m = (x + y * core::constants::SERVER_MAPX) as usize;
-
-                let should_continue =
-                    self.map[m].flags & u64::from(core::constants::MF_INDOORS) != 0;
-
-                if should_continue {
-                    continue;
-                }
-
-                let v = self.can_see(None, xc, yc, x, y, core::constants::LIGHTDIST);
-                if v == 0 {
-                    continue;
-                }
-
-                let denom = v * (dx.abs() + dy.abs());
-                if denom <= 0 {
-                    continue;
-                }
-
-                let d = 256 / denom;
-                if d > best {
-                    best = d;
-                }
-            }
-        }
-
-        if best > 256 {
-            best = 256;
-        }
-
         let center_index = (xc + yc * core::constants::SERVER_MAPX) as usize;
 
         if center_index < self.map.len() {
-            self.map[center_index].dlight = best as u16;
+            self.map[center_index].dlight = sight::indoor_dlight(&self.map, &self.items, xc, yc);
         }
     }
 
@@ -269,12 +173,7 @@ impl GameState {
             let (see_x, see_y) = (self.see_map[cn].x, self.see_map[cn].y);
 
             if fx != see_x || fy != see_y {
-                let (ch_kindred, ch_flags) =
-                    (self.characters[cn].kindred, self.characters[cn].flags);
-
-                self.is_monster = ch_kindred & traits::KIN_MONSTER as i32 != 0
-                    && (ch_flags & (CharacterFlags::Usurp.bits() | CharacterFlags::Thrall.bits()))
-                        == 0;
+                self.is_monster = sight::sees_as_monster(&self.characters[cn]);
 
                 self.can_map_see(fx, fy, max_distance);
 
@@ -366,44 +265,24 @@ impl GameState {
     /// * `fx, fy` - Origin coordinates
     /// * `max_distance` - Maximum radius to compute
     fn can_map_see(&mut self, fx: i32, fy: i32, max_distance: i32) {
-        // Clear the active visibility buffer (global or per-character).
-        self.vis_buf_mut().fill(0);
+        // Fill the active visibility buffer (global or per-character).
+        let visi = if self.vis_is_global {
+            &mut self._visi
+        } else {
+            &mut self.visi
+        };
+        sight::fill_see_map(
+            &self.map,
+            &self.items,
+            visi,
+            fx,
+            fy,
+            max_distance,
+            self.is_monster,
+        );
 
         self.ox = fx;
         self.oy = fy;
-
-        let xc = fx;
-        let yc = fy;
-
-        self.add_vis(fx, fy, 1);
-
-        for dist in 1..(max_distance + 1) {
-            // Top and bottom horizontal lines
-            for x in (xc - dist)..=(xc + dist) {
-                let y = yc - dist;
-                if self.close_vis_see(x, y, dist as i8) {
-                    self.add_vis(x, y, dist + 1);
-                }
-
-                let y = yc + dist;
-                if self.close_vis_see(x, y, dist as i8) {
-                    self.add_vis(x, y, dist + 1);
-                }
-            }
-
-            // Left and right vertical lines (excluding corners already done)
-            for y in (yc - dist + 1)..=(yc + dist - 1) {
-                let x = xc - dist;
-                if self.close_vis_see(x, y, dist as i8) {
-                    self.add_vis(x, y, dist + 1);
-                }
-
-                let x = xc + dist;
-                if self.close_vis_see(x, y, dist as i8) {
-                    self.add_vis(x, y, dist + 1);
-                }
-            }
-        }
     }
 
     /// Port of `can_go(fx,fy,target_x,target_y)` from original helper code.
@@ -774,112 +653,6 @@ impl GameState {
         }
     }
 
-    /// Port of `close_vis_see(x,y,value)` from original helper code.
-    ///
-    /// Returns `true` if tile `(x,y)` allows line-of-sight and is adjacent to
-    /// an already-visible tile with the specified `value`. Used by the wave
-    /// expansion algorithm while building visibility maps.
-    ///
-    /// # Arguments
-    /// * `x, y` - Tile coordinates
-    /// * `value` - Neighbor visibility value to match
-    fn close_vis_see(&mut self, x: i32, y: i32, value: i8) -> bool {
-        if !self.check_map_see(x, y) {
-            return false;
-        }
-
-        let x = x - self.ox + core::constants::VISI_CENTER;
-        let y = y - self.oy + core::constants::VISI_CENTER;
-        let stride = core::constants::VISI_STRIDE as i32;
-        let edge = core::constants::VISI_STRIDE as i32 - 1;
-
-        if x <= 0 || x >= edge || y <= 0 || y >= edge {
-            return false;
-        }
-
-        let visi = self.vis_buf();
-
-        if visi[((x + 1) + y * stride) as usize] == value {
-            return true;
-        }
-        if visi[((x - 1) + y * stride) as usize] == value {
-            return true;
-        }
-        if visi[(x + (y + 1) * stride) as usize] == value {
-            return true;
-        }
-        if visi[(x + (y - 1) * stride) as usize] == value {
-            return true;
-        }
-        if visi[((x + 1) + (y + 1) * stride) as usize] == value {
-            return true;
-        }
-        if visi[((x + 1) + (y - 1) * stride) as usize] == value {
-            return true;
-        }
-        if visi[((x - 1) + (y + 1) * stride) as usize] == value {
-            return true;
-        }
-        if visi[((x - 1) + (y - 1) * stride) as usize] == value {
-            return true;
-        }
-
-        false
-    }
-
-    /// Port of `check_map_see(x,y)` from original helper code.
-    ///
-    /// Returns `true` when the map tile at `(x,y)` does not block line of
-    /// sight. Considers map flags, monster/blocking rules, and items with
-    /// `IF_SIGHTBLOCK` flag.
-    ///
-    /// # Arguments
-    /// * `x, y` - Tile coordinates to test
-    fn check_map_see(&mut self, x: i32, y: i32) -> bool {
-        // Check boundaries
-        if x <= 0
-            || x >= core::constants::SERVER_MAPX
-            || y <= 0
-            || y >= core::constants::SERVER_MAPY
-        {
-            return false;
-        }
-
-        let m = (x + y * core::constants::SERVER_MAPX) as usize;
-
-        // Check if it's a monster and the map blocks monsters
-        if self.is_monster {
-            let blocked = self.map[m].flags
-                & u64::from(core::constants::MF_SIGHTBLOCK | core::constants::MF_NOMONST)
-                != 0;
-            if blocked {
-                return false;
-            }
-        } else {
-            // Check for sight blocking flags
-            let blocked = self.map[m].flags & u64::from(core::constants::MF_SIGHTBLOCK) != 0;
-            if blocked {
-                return false;
-            }
-        }
-
-        // Check if there's an item that blocks sight
-        let item_idx = self.map[m].it as usize;
-        let blocks_sight = if item_idx != 0 {
-            item_idx < self.items.len()
-                && self.items[item_idx].flags & core::constants::ItemFlags::IF_SIGHTBLOCK.bits()
-                    != 0
-        } else {
-            false
-        };
-
-        if blocks_sight {
-            return false;
-        }
-
-        true
-    }
-
     /// Port of `check_map_go(x,y)` from original helper code.
     ///
     /// Returns `true` when the map tile at `(x,y)` is traversable for
@@ -1,5 +1,10 @@
 //! Per-player weather dispatch and area-driven tick driver.
 //!
+//! Outside the areas listed in [`core::weather_areas`], outdoor players share
+//! a world weather spell ([`WorldWeather`]) that [`world_weather_tick`] rolls
+//! every few game minutes: mostly clear skies, sometimes rain, and fog that
+//! is more likely at night.
+//!
 //! Weather is purely a client-visible effect. The server keeps the *active*
 //! weather kind / intensity / tint cached on each [`crate::types::server_player::ServerPlayer`]
 //! so it can avoid retransmitting unchanged state every tick. State is
@@ -8,18 +13,100 @@
 //! See [`docs/server/DESIGN.md`](../../../docs/server/DESIGN.md) for the
 //! protocol overview and [`core::weather`] for the wire-format constants.
 
+use core::constants::{MF_INDOORS, SERVER_MAPX, TICKS};
 use core::server_commands::ServerCommandType;
 use core::weather::{WEATHER_FLAG_OVERRIDE, WeatherKind};
 use core::weather_areas::area_weather_for;
 
 use crate::game_state::GameState;
+use crate::helpers;
 use crate::network_manager::xsend;
 
+/// Environment variable that turns the weather driver on; it stays off
+/// while unset so areas can be tuned without players seeing it.
+pub const WEATHER_ENV: &str = "MAG_WEATHER";
+
 /// Approximately one second at 36 TPS — how often the area-driven driver
 /// re-evaluates each player's location.
-#[allow(dead_code)]
 const WEATHER_TICK_PERIOD: u32 = 36;
 
+/// Shortest world weather spell, in ticks (three minutes).
+const WORLD_SPELL_MIN_TICKS: u32 = 3 * 60 * TICKS as u32;
+
+/// Extra random length of a world weather spell, in ticks (up to seven
+/// more minutes).
+const WORLD_SPELL_SPREAD_TICKS: u32 = 7 * 60 * TICKS as u32;
+
+/// Daylight (`Global::dlight`) below which it counts as night.
+const NIGHT_DLIGHT: i32 = 64;
+
+/// Weather shared by every outdoor player outside the area table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldWeather {
+    pub kind: WeatherKind,
+    pub intensity: u8,
+    /// Tick at which the next spell is rolled.
+    pub until_tick: u32,
+}
+
+impl Default for WorldWeather {
+    fn default() -> Self {
+        Self {
+            kind: WeatherKind::None,
+            intensity: 0,
+            until_tick: 0,
+        }
+    }
+}
+
+/// Picks the next world weather from a roll in `0..100`.
+///
+/// # Arguments
+///
+/// * `roll` - Random number in `0..100`.
+/// * `night` - Whether it is night; fog is more likely then.
+///
+/// # Returns
+///
+/// * The weather kind and its intensity.
+fn pick_world_weather(roll: u32, night: bool) -> (WeatherKind, u8) {
+    let fog_chance = if night { 30 } else { 10 };
+    match roll {
+        r if r < 20 => (WeatherKind::Rain, 120 + (r * 6) as u8),
+        r if r < 20 + fog_chance => (WeatherKind::Fog, 100),
+        _ => (WeatherKind::None, 0),
+    }
+}
+
+/// Rolls a new world weather spell once the current one has run out.
+///
+/// Called once per server tick while [`WEATHER_ENV`] is set; players pick
+/// the change up on their next [`weather_tick`].
+///
+/// # Arguments
+///
+/// * `gs` - Mutable game state.
+pub fn world_weather_tick(gs: &mut GameState) {
+    let ticker = gs.globals.ticker as u32;
+    if ticker < gs.world_weather.until_tick {
+        return;
+    }
+    let night = gs.globals.dlight < NIGHT_DLIGHT;
+    let (kind, intensity) = pick_world_weather(helpers::random_mod(100), night);
+    let length = WORLD_SPELL_MIN_TICKS + helpers::random_mod(WORLD_SPELL_SPREAD_TICKS);
+    gs.world_weather = WorldWeather {
+        kind,
+        intensity,
+        until_tick: ticker.wrapping_add(length),
+    };
+    log::info!(
+        "World weather is now {:?} ({}) for {}s",
+        kind,
+        intensity,
+        length / TICKS as u32
+    );
+}
+
 /// Build the 10-byte `SV_WEATHER` packet body and send it to a single player.
 ///
 /// Updates the player's cached weather fields so subsequent ticks can decide
@@ -83,7 +170,6 @@ pub fn send_weather(
 ///
 /// * `gs` - Mutable game state.
 /// * `player_id` - Index into `gs.players`.
-#[allow(dead_code)]
 pub fn clear_weather(gs: &mut GameState, player_id: usize) {
     send_weather(gs, player_id, WeatherKind::None as u8, 0, 0, [0; 4], 0);
 }
@@ -97,7 +183,8 @@ pub fn clear_weather(gs: &mut GameState, player_id: usize) {
 ///    has expired, clear it (so the area driver can take over again).
 /// 2. Otherwise, look up the player's tile in the area-weather table and
 ///    transition to/from the area-default weather only if it actually
-///    changes.
+///    changes. Outdoor tiles outside every listed area get the
+///    [`WorldWeather`].
 ///
 /// Skips players that aren't connected and in a normal play state.
 ///
@@ -105,7 +192,6 @@ pub fn clear_weather(gs: &mut GameState, player_id: usize) {
 ///
 /// * `gs` - Mutable game state.
 /// * `nr` - Player index.
-#[allow(dead_code)]
 pub fn weather_tick(gs: &mut GameState, nr: usize) {
     if nr == 0 || nr >= gs.players.len() {
         return;
@@ -156,7 +242,20 @@ pub fn weather_tick(gs: &mut GameState, nr: usize) {
             a.tint.unwrap_or([0u8; 4]),
             a.flags,
         ),
-        None => (WeatherKind::None as u8, 0u8, [0u8; 4], 0u8),
+        None => {
+            let indoors = x >= 0
+                && y >= 0
+                && gs
+                    .map
+                    .get((x + y * SERVER_MAPX) as usize)
+                    .is_some_and(|tile| tile.flags & u64::from(MF_INDOORS) != 0);
+            if indoors {
+                (WeatherKind::None as u8, 0u8, [0u8; 4], 0u8)
+            } else {
+                let world = gs.world_weather;
+                (world.kind as u8, world.intensity, [0u8; 4], 0u8)
+            }
+        }
     };
 
     let new_cur_kind = gs.players[nr].weather_kind;
@@ -286,6 +385,41 @@ mod tests {
         });
     }
 
+    #[test]
+    fn world_weather_reaches_outdoor_players_only() {
+        with_test_gs(|gs| {
+            let (_cn, nr) = add_test_player(gs);
+            attach_test_socket(gs, nr);
+            let cn = gs.players[nr].usnr;
+            gs.characters[cn].x = 1;
+            gs.characters[cn].y = 1;
+            gs.world_weather = WorldWeather {
+                kind: WeatherKind::Rain,
+                intensity: 150,
+                until_tick: u32::MAX,
+            };
+
+            let phase = (nr as u32) % WEATHER_TICK_PERIOD;
+            gs.globals.ticker = phase as i32;
+            weather_tick(gs, nr);
+            assert_eq!(gs.players[nr].weather_kind, WeatherKind::Rain as u8);
+            assert_eq!(gs.players[nr].weather_intensity, 150);
+
+            gs.map[1 + SERVER_MAPX as usize].flags |= u64::from(MF_INDOORS);
+            gs.globals.ticker = (phase + WEATHER_TICK_PERIOD) as i32;
+            weather_tick(gs, nr);
+            assert_eq!(gs.players[nr].weather_kind, WeatherKind::None as u8);
+        });
+    }
+
+    #[test]
+    fn night_makes_fog_more_likely() {
+        assert_eq!(pick_world_weather(5, false).0, WeatherKind::Rain);
+        assert_eq!(pick_world_weather(35, false).0, WeatherKind::None);
+        assert_eq!(pick_world_weather(35, true).0, WeatherKind::Fog);
+        assert_eq!(pick_world_weather(99, true).0, WeatherKind::None);
+    }
+
     #[test]
     fn weather_tick_clears_expired_override() {
         with_test_gs(|gs| {
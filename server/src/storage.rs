@@ -0,0 +1,235 @@
+/// Selection of the persistence backend for world data.
+///
+/// KeyDB is the default. Setting `MAG_STORAGE_BACKEND=sqlite` stores the
+/// world in a single SQLite file instead (see [`crate::sqlite_store`]):
+///
+/// * `MAG_SQLITE_PATH` - database file (default `game.sqlite3`).
+/// * `MAG_SQLITE_SEED` - world snapshot imported when the database is empty
+///   (default `assets/world_seed.wsnap`).
+///
+/// Admin live-patch features (template/text reloads, map/item/character
+/// patches, bans) still go through KeyDB and are unavailable without it.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::keydb::snapshot::WorldSnapshot;
+use crate::keydb::store::GameData;
+use crate::keydb::{connection, store, write_behind};
+use crate::sqlite_store::SqliteStore;
+
+const BACKEND_ENV: &str = "MAG_STORAGE_BACKEND";
+const SQLITE_PATH_ENV: &str = "MAG_SQLITE_PATH";
+const SQLITE_SEED_ENV: &str = "MAG_SQLITE_SEED";
+
+const DEFAULT_SQLITE_PATH: &str = "game.sqlite3";
+const DEFAULT_SQLITE_SEED: &str = "assets/world_seed.wsnap";
+
+/// Where world data is loaded from and saved to.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum StorageBackend {
+    /// KeyDB, keyed per entity (see [`crate::keydb::store`]).
+    #[default]
+    KeyDb,
+    /// A SQLite database file.
+    Sqlite { path: PathBuf },
+}
+
+impl StorageBackend {
+    /// Read the backend from the environment.
+    ///
+    /// # Returns
+    ///
+    /// * The configured backend, or an `Err` naming an unknown value.
+    pub fn from_env() -> Result<Self, String> {
+        let backend = std::env::var(BACKEND_ENV).unwrap_or_default();
+        match backend.trim().to_ascii_lowercase().as_str() {
+            "" | "keydb" => Ok(Self::KeyDb),
+            "sqlite" => Ok(Self::Sqlite {
+                path: env_path(SQLITE_PATH_ENV, DEFAULT_SQLITE_PATH),
+            }),
+            other => Err(format!(
+                "Unknown {BACKEND_ENV} '{other}' (expected 'keydb' or 'sqlite')"
+            )),
+        }
+    }
+
+    /// Load all world data, seeding an empty SQLite database from the world
+    /// snapshot first.
+    ///
+    /// # Returns
+    ///
+    /// * The loaded [`GameData`], or an `Err` describing the failure.
+    pub fn load_all(&self) -> Result<GameData, String> {
+        match self {
+            Self::KeyDb => store::load_all(&mut connection::connect()?),
+            Self::Sqlite { path } => {
+                let mut db = SqliteStore::open(path)?;
+                if !db.has_game_data()? {
+                    let seed = env_path(SQLITE_SEED_ENV, DEFAULT_SQLITE_SEED);
+                    seed_from_snapshot(&mut db, path, &seed)?;
+                }
+                db.load_all()
+            }
+        }
+    }
+
+    /// Save mutable runtime data (templates and text data are excluded).
+    ///
+    /// # Arguments
+    ///
+    /// * `map`        - All map tiles in row-major order.
+    /// * `items`      - All item slots.
+    /// * `characters` - All character slots.
+    /// * `effects`    - All effect slots.
+    /// * `globals`    - The single global state value.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn save_runtime_data(
+        &self,
+        map: &[core::types::Map],
+        items: &[core::types::Item],
+        characters: &[core::types::Character],
+        effects: &[core::types::Effect],
+        globals: &core::types::Global,
+    ) -> Result<(), String> {
+        match self {
+            Self::KeyDb => store::save_runtime_data(
+                &mut connection::connect()?,
+                map,
+                items,
+                characters,
+                effects,
+                globals,
+            ),
+            Self::Sqlite { path } => {
+                SqliteStore::open(path)?.save_runtime_data(map, items, characters, effects, globals)
+            }
+        }
+    }
+
+    /// Read the current message of the day.
+    ///
+    /// # Returns
+    ///
+    /// * The MOTD, or an `Err` if the backend read fails.
+    pub fn load_message_of_the_day(&self) -> Result<String, String> {
+        match self {
+            Self::KeyDb => connection::load_message_of_the_day(),
+            Self::Sqlite { path } => SqliteStore::open(path)?.load_message_of_the_day(),
+        }
+    }
+
+    /// Set fields of a small keyed record (a letter, a listing), keeping the
+    /// fields not given.
+    ///
+    /// The write is queued and returns immediately: KeyDB writes go through
+    /// the write-behind queue, SQLite writes through
+    /// [`write_behind::sqlite_records`], whose flusher retries a failed
+    /// batch until it lands.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Record key, e.g. `game:mail:17`.
+    /// * `fields` - `(field, value)` pairs to write.
+    pub fn save_record(&self, key: String, fields: Vec<(&'static str, String)>) {
+        match self {
+            Self::KeyDb => write_behind::queue(key, fields),
+            Self::Sqlite { .. } => write_behind::sqlite_records().hset(key, fields),
+        }
+    }
+
+    /// Load every record whose key starts with `prefix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Key prefix, e.g. `game:mail:`.
+    ///
+    /// # Returns
+    ///
+    /// * `(key, fields)` pairs, or an `Err` if the backend cannot be read.
+    pub fn load_records(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, HashMap<String, String>)>, String> {
+        match self {
+            Self::KeyDb => {
+                let mut con = connection::connect()?;
+                let mut keys: Vec<String> = Vec::new();
+                let mut cursor = 0u64;
+                loop {
+                    let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                        .arg(cursor)
+                        .arg("MATCH")
+                        .arg(format!("{prefix}*"))
+                        .arg("COUNT")
+                        .arg(256)
+                        .query(&mut con)
+                        .map_err(|e| format!("Failed to scan {prefix}* in KeyDB: {e}"))?;
+                    keys.extend(batch);
+                    cursor = next;
+                    if cursor == 0 {
+                        break;
+                    }
+                }
+                let mut records = Vec::with_capacity(keys.len());
+                for key in keys {
+                    let fields: HashMap<String, String> = redis::cmd("HGETALL")
+                        .arg(&key)
+                        .query(&mut con)
+                        .map_err(|e| format!("Failed to load {key} from KeyDB: {e}"))?;
+                    records.push((key, fields));
+                }
+                Ok(records)
+            }
+            Self::Sqlite { path } => SqliteStore::open(path)?.load_records(prefix),
+        }
+    }
+
+    /// Remove records written with [`StorageBackend::save_record`].
+    ///
+    /// Runs synchronously; it is meant for loading, before the write-behind
+    /// flushers start.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - Record keys.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` on success, or an `Err` describing the failure.
+    pub fn delete_records(&self, keys: &[String]) -> Result<(), String> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        match self {
+            Self::KeyDb => redis::cmd("DEL")
+                .arg(keys)
+                .query::<()>(&mut connection::connect()?)
+                .map_err(|e| format!("Failed to remove {} record(s) from KeyDB: {e}", keys.len())),
+            Self::Sqlite { path } => SqliteStore::open(path)?.delete_records(keys),
+        }
+    }
+}
+
+fn env_path(name: &str, default: &str) -> PathBuf {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(default))
+}
+
+fn seed_from_snapshot(db: &mut SqliteStore, path: &Path, seed: &Path) -> Result<(), String> {
+    log::info!(
+        "SQLite database {} is empty; importing world snapshot {}...",
+        path.display(),
+        seed.display()
+    );
+    let snapshot = WorldSnapshot::from_file(seed)
+        .map_err(|e| format!("Cannot seed SQLite database from {}: {e}", seed.display()))?;
+    db.import_snapshot(&snapshot)?;
+    log::info!("World snapshot imported into SQLite.");
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use core::constants::{CT_COMPANION, NT_GOTMISS, SERVER_MAPX, SERVER_MAPY, TICKS};
 use core::string_operations::c_string_to_str;
 use core::traits;
+use server::journal::GoldTransferKind;
 
 use crate::game_state::GameState;
 use core::types::Character;
@@ -3732,12 +3733,9 @@ pub fn answer_buygold(gs: &mut GameState, cn: usize, co: usize) {
         return;
     }
 
-    let co_name = {
-        let characters = &mut gs.characters;
-        characters[co].data[41] += pts;
-        characters[co].gold += pts * 100;
-        characters[co].get_name().to_owned()
-    };
+    gs.characters[co].data[41] += pts;
+    gs.transfer_gold(0, co, pts * 100, GoldTransferKind::QuestReward);
+    let co_name = gs.characters[co].get_name().to_owned();
     gs.do_sayx(
         cn,
         &format!(
@@ -3774,7 +3772,7 @@ pub fn answer_buyhealth(gs: &mut GameState, cn: usize, co: usize) {
     gs.characters[co].data[41] += 6;
 
     if let Some(item_id) = God::create_item(gs, 101) {
-        God::give_character_item(gs, co, item_id);
+        gs.give_reward_item(co, item_id);
 
         gs.do_sayx(
             cn,
@@ -3813,7 +3811,7 @@ pub fn answer_buymana(gs: &mut GameState, cn: usize, co: usize) {
     gs.characters[co].data[41] += 9;
 
     if let Some(item_id) = God::create_item(gs, 102) {
-        God::give_character_item(gs, co, item_id);
+        gs.give_reward_item(co, item_id);
 
         gs.do_sayx(
             cn,
@@ -0,0 +1,405 @@
+//! World-builder fixtures for server unit tests.
+//!
+//! [`World`] describes a small scene — characters, what they carry and wear,
+//! items lying on the ground — and builds it into a fresh [`GameState`]
+//! through the same helpers the game uses (`God::drop_char`,
+//! `God::drop_item`, `really_update_char`), so derived values such as armor
+//! and maximum hit points are already consistent when the test starts.
+//!
+//! ```ignore
+//! World::new()
+//!     .with_char(CharSpec::player("Hero").at(10, 10).wearing(WN_BODY, ItemSpec::new("Mail").armor(5)))
+//!     .with_char(CharSpec::npc("Goblin").at(11, 10).hp(30))
+//!     .with_item_at(12, 10, ItemSpec::new("Sword").weapon(8))
+//!     .run(|gs, w| {
+//!         let (hero, goblin) = (w.char(0), w.char(1));
+//!         // ...
+//!     });
+//! ```
+//!
+//! Characters occupy slots `1..`, player slots are handed out in the same
+//! order to player characters, and items take slots `1..` in build order.
+
+use core::constants::{CharacterFlags, ItemFlags, ST_NORMAL, USE_ACTIVE};
+use core::string_operations::write_ascii_into_fixed;
+use core::types::{Character, Item};
+
+use crate::game_state::GameState;
+use crate::god::God;
+use crate::test_helpers::with_test_gs;
+
+/// Description of one item to create.
+#[derive(Clone, Debug)]
+pub(crate) struct ItemSpec {
+    name: String,
+    armor: i8,
+    weapon: i8,
+}
+
+impl ItemSpec {
+    /// Starts a takeable item called `name` with no bonuses.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Item name (also used as its reference).
+    ///
+    /// # Returns
+    ///
+    /// * A new `ItemSpec`.
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            armor: 0,
+            weapon: 0,
+        }
+    }
+
+    /// Sets the armor bonus (active and inactive).
+    pub(crate) fn armor(mut self, armor: i8) -> Self {
+        self.armor = armor;
+        self
+    }
+
+    /// Sets the weapon bonus (active and inactive).
+    pub(crate) fn weapon(mut self, weapon: i8) -> Self {
+        self.weapon = weapon;
+        self
+    }
+
+    /// Writes the item into slot `in_idx`, not yet placed anywhere.
+    fn build(&self, gs: &mut GameState, in_idx: usize) {
+        let it = &mut gs.items[in_idx];
+        *it = Item::default();
+        it.used = USE_ACTIVE;
+        write_ascii_into_fixed(&mut it.name, &self.name);
+        write_ascii_into_fixed(&mut it.reference, &self.name);
+        it.sprite = [1, 1];
+        it.flags = ItemFlags::IF_TAKE.bits();
+        it.armor = [self.armor, self.armor];
+        it.weapon = [self.weapon, self.weapon];
+    }
+}
+
+/// Description of one character to create.
+#[derive(Clone, Debug)]
+pub(crate) struct CharSpec {
+    name: String,
+    player: bool,
+    pos: Option<(usize, usize)>,
+    flags: u64,
+    hp: u16,
+    holding: Option<ItemSpec>,
+    carried: Vec<ItemSpec>,
+    worn: Vec<(usize, ItemSpec)>,
+    spells: Vec<ItemSpec>,
+}
+
+impl CharSpec {
+    fn new(name: &str, player: bool) -> Self {
+        Self {
+            name: name.to_owned(),
+            player,
+            pos: None,
+            flags: 0,
+            hp: 50,
+            holding: None,
+            carried: Vec::new(),
+            worn: Vec::new(),
+            spells: Vec::new(),
+        }
+    }
+
+    /// Starts a player character linked to a player slot in `ST_NORMAL`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Character name.
+    ///
+    /// # Returns
+    ///
+    /// * A new `CharSpec`.
+    pub(crate) fn player(name: &str) -> Self {
+        Self::new(name, true)
+    }
+
+    /// Starts a computer-controlled character.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Character name.
+    ///
+    /// # Returns
+    ///
+    /// * A new `CharSpec`.
+    pub(crate) fn npc(name: &str) -> Self {
+        Self::new(name, false)
+    }
+
+    /// Places the character at `(x, y)`. Without it, character `i` of the
+    /// world stands at `(10 + 2 * i, 10)`.
+    pub(crate) fn at(mut self, x: usize, y: usize) -> Self {
+        self.pos = Some((x, y));
+        self
+    }
+
+    /// Adds `flags` (`CharacterFlags` bits) to the character.
+    pub(crate) fn flags(mut self, flags: u64) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Sets base hit points (default 50). The character starts at full
+    /// health.
+    pub(crate) fn hp(mut self, hp: u16) -> Self {
+        self.hp = hp;
+        self
+    }
+
+    /// Puts `item` on the character's cursor (`citem`).
+    pub(crate) fn holding(mut self, item: ItemSpec) -> Self {
+        self.holding = Some(item);
+        self
+    }
+
+    /// Puts `item` into the next free inventory slot.
+    pub(crate) fn carrying(mut self, item: ItemSpec) -> Self {
+        self.carried.push(item);
+        self
+    }
+
+    /// Equips `item` in worn slot `slot` (`WN_*`).
+    pub(crate) fn wearing(mut self, slot: usize, item: ItemSpec) -> Self {
+        self.worn.push((slot, item));
+        self
+    }
+
+    /// Puts `item` into the next free spell slot as an active spell.
+    pub(crate) fn with_spell(mut self, item: ItemSpec) -> Self {
+        self.spells.push(item);
+        self
+    }
+}
+
+/// A scene to build into a fresh [`GameState`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct World {
+    chars: Vec<CharSpec>,
+    ground: Vec<(usize, usize, ItemSpec)>,
+}
+
+/// Slot numbers assigned while building a [`World`], in declaration order.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Built {
+    chars: Vec<usize>,
+    players: Vec<Option<usize>>,
+    ground: Vec<usize>,
+}
+
+impl Built {
+    /// Character slot of the `i`-th `with_char`.
+    pub(crate) fn char(&self, i: usize) -> usize {
+        self.chars[i]
+    }
+
+    /// Player slot of the `i`-th `with_char`.
+    ///
+    /// # Panics
+    ///
+    /// * If that character is not a player.
+    pub(crate) fn player(&self, i: usize) -> usize {
+        self.players[i].expect("character has no player slot")
+    }
+
+    /// Item slot of the `i`-th `with_item_at`.
+    pub(crate) fn ground_item(&self, i: usize) -> usize {
+        self.ground[i]
+    }
+}
+
+impl World {
+    /// Starts an empty world.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a character.
+    pub(crate) fn with_char(mut self, spec: CharSpec) -> Self {
+        self.chars.push(spec);
+        self
+    }
+
+    /// Drops `item` on the ground at `(x, y)`.
+    pub(crate) fn with_item_at(mut self, x: usize, y: usize, item: ItemSpec) -> Self {
+        self.ground.push((x, y, item));
+        self
+    }
+
+    /// Builds the world into `gs`.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Freshly created game state.
+    ///
+    /// # Returns
+    ///
+    /// * The slots assigned to the declared characters and ground items.
+    ///
+    /// # Panics
+    ///
+    /// * If a character or item cannot be placed on its tile.
+    pub(crate) fn build(&self, gs: &mut GameState) -> Built {
+        let mut built = Built::default();
+        let mut next_item = 1;
+        let mut next_player = 1;
+
+        for (i, spec) in self.chars.iter().enumerate() {
+            let cn = i + 1;
+            let nr = spec.player.then(|| {
+                let nr = next_player;
+                next_player += 1;
+                let pl = &mut gs.players[nr];
+                pl.state = ST_NORMAL;
+                pl.usnr = cn;
+                pl.lasttick = 0;
+                pl.lasttick2 = 0;
+                pl.ltick = 0;
+                pl.rtick = 0;
+                nr
+            });
+
+            let ch = &mut gs.characters[cn];
+            *ch = Character::default();
+            ch.used = USE_ACTIVE;
+            ch.flags = spec.flags;
+            if let Some(nr) = nr {
+                ch.flags |= CharacterFlags::Player.bits();
+                ch.player = nr as i32;
+            }
+            write_ascii_into_fixed(&mut ch.name, &spec.name);
+            write_ascii_into_fixed(&mut ch.reference, &spec.name);
+            for attrib in ch.attrib.iter_mut() {
+                attrib[0] = 10;
+            }
+            ch.hp[0] = spec.hp;
+            ch.end[0] = 50;
+            ch.mana[0] = 50;
+
+            let (x, y) = spec.pos.unwrap_or((10 + 2 * i, 10));
+            gs.characters[cn].frx = x as i16;
+            gs.characters[cn].fry = y as i16;
+            assert!(
+                God::drop_char(gs, cn, x, y),
+                "cannot place {} at ({}, {})",
+                spec.name,
+                x,
+                y
+            );
+
+            if let Some(item) = &spec.holding {
+                item.build(gs, next_item);
+                gs.items[next_item].carried = cn as u16;
+                gs.characters[cn].citem = next_item as u32;
+                next_item += 1;
+            }
+            for (n, item) in spec.carried.iter().enumerate() {
+                item.build(gs, next_item);
+                gs.items[next_item].carried = cn as u16;
+                gs.characters[cn].item[n] = next_item as u32;
+                next_item += 1;
+            }
+            for (slot, item) in &spec.worn {
+                item.build(gs, next_item);
+                gs.items[next_item].carried = cn as u16;
+                gs.characters[cn].worn[*slot] = next_item as u32;
+                next_item += 1;
+            }
+            for (n, item) in spec.spells.iter().enumerate() {
+                item.build(gs, next_item);
+                gs.items[next_item].carried = cn as u16;
+                gs.items[next_item].duration = 1;
+                gs.items[next_item].active = 1;
+                gs.characters[cn].spell[n] = next_item as u32;
+                next_item += 1;
+            }
+
+            gs.really_update_char(cn);
+            let ch = &mut gs.characters[cn];
+            ch.a_hp = i32::from(ch.hp[5]) * 1000;
+            ch.a_end = i32::from(ch.end[5]) * 1000;
+            ch.a_mana = i32::from(ch.mana[5]) * 1000;
+
+            built.chars.push(cn);
+            built.players.push(nr);
+        }
+
+        for (x, y, item) in &self.ground {
+            item.build(gs, next_item);
+            assert!(
+                God::drop_item(gs, next_item, *x, *y),
+                "cannot drop {} at ({}, {})",
+                item.name,
+                x,
+                y
+            );
+            built.ground.push(next_item);
+            next_item += 1;
+        }
+
+        built
+    }
+
+    /// Builds the world into a fresh game state and runs `f` against it.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Test body; receives the game state and the assigned slots.
+    ///
+    /// # Returns
+    ///
+    /// * The closure's return value.
+    pub(crate) fn run<F, R>(self, f: F) -> R
+    where
+        F: FnOnce(&mut GameState, &Built) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        with_test_gs(move |gs| {
+            let built = self.build(gs);
+            f(gs, &built)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::constants::WN_BODY;
+
+    #[test]
+    fn builds_linked_players_items_and_derived_stats() {
+        World::new()
+            .with_char(
+                CharSpec::player("Hero")
+                    .hp(80)
+                    .carrying(ItemSpec::new("Potion"))
+                    .wearing(WN_BODY, ItemSpec::new("Mail").armor(6)),
+            )
+            .with_char(CharSpec::npc("Goblin").at(20, 20))
+            .with_item_at(15, 15, ItemSpec::new("Sword").weapon(8))
+            .run(|gs, w| {
+                let (hero, goblin) = (w.char(0), w.char(1));
+                assert_eq!(gs.players[w.player(0)].usnr, hero);
+                assert!(w.players[1].is_none());
+
+                let tile = |x: usize, y: usize| x + y * core::constants::SERVER_MAPX as usize;
+                assert_eq!(gs.map[tile(10, 10)].ch, hero as u32);
+                assert_eq!(gs.map[tile(20, 20)].ch, goblin as u32);
+                assert_eq!(gs.map[tile(15, 15)].it, w.ground_item(0) as u32);
+
+                let ch = &gs.characters[hero];
+                assert_eq!(gs.items[ch.item[0] as usize].carried, hero as u16);
+                assert_eq!(ch.armor, 6);
+                assert_eq!(ch.a_hp, i32::from(ch.hp[5]) * 1000);
+                assert!(ch.hp[5] >= 80);
+            });
+    }
+}
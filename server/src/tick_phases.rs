@@ -0,0 +1,233 @@
+//! Parallel phases of the game tick.
+//!
+//! The tick stays single-threaded where it mutates the world: characters
+//! act, effects run and items tick in a fixed order on the game thread.
+//! What moves onto rayon workers are the read-only computations those
+//! phases spend most of their time in:
+//!
+//! * line-of-sight maps of the NPCs about to act ([`prefetch_see_maps`]),
+//!   which `can_see` would otherwise rebuild one by one inside `plr_act`;
+//! * daylight and light spread of a full lighting rebuild
+//!   ([`rebuild_lights`]).
+//!
+//! Workers only read `gs.map` and `gs.items` and return their results. The
+//! merge step then writes them back on the game thread in ascending
+//! character or map order, so the outcome does not depend on the number of
+//! threads or how rayon schedules the work.
+//!
+//! Effect handlers are deliberately left sequential: they reserve tiles,
+//! spawn characters and chain into other effects, so their order is part of
+//! the game rules.
+
+use core::constants::{
+    CharacterFlags, MF_INDOORS, SERVER_MAPX, SERVER_MAPY, TILEX, USE_ACTIVE, VISI_BUFFER_LEN,
+};
+use rayon::prelude::*;
+
+use crate::game_state::GameState;
+use crate::state::sight::{self, VisBuffer};
+
+/// Fewest stale see maps worth handing to the thread pool.
+const MIN_PARALLEL_SEE_MAPS: usize = 4;
+
+/// Rebuilds the see maps of `candidates` whose cache no longer matches
+/// their position.
+///
+/// The result is exactly what `can_see` would store on its next cache miss,
+/// so characters that end up not looking around this tick only cost the
+/// wasted computation.
+///
+/// # Arguments
+///
+/// * `gs` - Game state whose `see_map` cache is refreshed.
+/// * `candidates` - Active characters expected to act this tick.
+///
+/// # Returns
+///
+/// * Number of see maps rebuilt.
+pub(crate) fn prefetch_see_maps(gs: &mut GameState, candidates: &[usize]) -> usize {
+    let stale: Vec<(usize, i32, i32, bool)> = candidates
+        .iter()
+        .filter_map(|&cn| {
+            let ch = &gs.characters[cn];
+            let (x, y) = (i32::from(ch.x), i32::from(ch.y));
+            let see = &gs.see_map[cn];
+            (see.x != x || see.y != y).then(|| (cn, x, y, sight::sees_as_monster(ch)))
+        })
+        .collect();
+    if stale.len() < MIN_PARALLEL_SEE_MAPS {
+        return 0;
+    }
+
+    let (map, items) = (&gs.map, &gs.items);
+    let max_distance = (TILEX / 2) as i32;
+    let built: Vec<Box<VisBuffer>> = stale
+        .par_iter()
+        .map(|&(_, x, y, is_monster)| {
+            let mut visi = Box::new([0i8; VISI_BUFFER_LEN]);
+            sight::fill_see_map(map, items, &mut visi, x, y, max_distance, is_monster);
+            visi
+        })
+        .collect();
+
+    for (&(cn, x, y, _), visi) in stale.iter().zip(built) {
+        gs.see_map[cn].x = x;
+        gs.see_map[cn].y = y;
+        gs.see_map[cn].vis = *visi;
+        gs.see_miss += 1;
+    }
+    stale.len()
+}
+
+/// Returns whether character `cn` should get its see map prefetched.
+///
+/// # Arguments
+///
+/// * `gs` - Game state holding the character.
+/// * `cn` - Character number.
+///
+/// # Returns
+///
+/// * `true` for active characters that are not bodies.
+pub(crate) fn is_see_map_candidate(gs: &GameState, cn: usize) -> bool {
+    let ch = &gs.characters[cn];
+    ch.used == USE_ACTIVE && ch.flags & CharacterFlags::Body.bits() == 0
+}
+
+/// Recomputes indoor daylight and item lights for the whole map.
+///
+/// Expects `light` and `dlight` to be cleared already. Daylight of every
+/// indoor tile and the spread of every item light are computed in
+/// parallel; the spreads are then applied in map order, since
+/// `Map::add_light` clamps and the sum must match the sequential rebuild.
+///
+/// # Arguments
+///
+/// * `gs` - Game state whose map lighting is rebuilt.
+///
+/// # Returns
+///
+/// * `(light sources, indoor tiles)` processed.
+pub(crate) fn rebuild_lights(gs: &mut GameState) -> (usize, usize) {
+    let mut indoor = Vec::new();
+    let mut sources = Vec::new();
+    for m in 0..(SERVER_MAPX * SERVER_MAPY) as usize {
+        if gs.map[m].flags & u64::from(MF_INDOORS) != 0 {
+            indoor.push(m);
+        }
+        let in_id = gs.map[m].it as usize;
+        if in_id != 0 {
+            let it = &gs.items[in_id];
+            let light = if it.active != 0 {
+                it.light[1]
+            } else {
+                it.light[0]
+            };
+            if light != 0 {
+                sources.push((m, i32::from(light)));
+            }
+        }
+    }
+
+    let (map, items) = (&gs.map, &gs.items);
+    let xy = |m: usize| {
+        (
+            (m % SERVER_MAPX as usize) as i32,
+            (m / SERVER_MAPX as usize) as i32,
+        )
+    };
+    let dlight: Vec<u16> = indoor
+        .par_iter()
+        .map(|&m| {
+            let (x, y) = xy(m);
+            sight::indoor_dlight(map, items, x, y)
+        })
+        .collect();
+    let spreads: Vec<Vec<(usize, i32)>> = sources
+        .par_iter()
+        .map(|&(m, strength)| {
+            let (x, y) = xy(m);
+            sight::light_spread(map, items, x, y, strength)
+        })
+        .collect();
+
+    for (&m, value) in indoor.iter().zip(dlight) {
+        gs.map[m].dlight = value;
+    }
+    for (&(m, strength), spread) in sources.iter().zip(spreads) {
+        gs.map[m].add_light(strength);
+        for (target, d) in spread {
+            gs.map[target].add_light(d);
+        }
+    }
+    (sources.len(), indoor.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::with_test_gs;
+    use core::constants::MF_SIGHTBLOCK;
+
+    #[test]
+    fn prefetched_see_maps_match_can_see() {
+        with_test_gs(|gs| {
+            for y in 95..=105 {
+                gs.map[(103 + y * SERVER_MAPX) as usize].flags |= u64::from(MF_SIGHTBLOCK);
+            }
+            let npcs: Vec<usize> = (2..8).collect();
+            for &cn in &npcs {
+                gs.characters[cn].used = USE_ACTIVE;
+                gs.characters[cn].x = 100 + cn as i16;
+                gs.characters[cn].y = 100;
+                gs.see_map[cn].x = 0;
+                gs.see_map[cn].y = 0;
+            }
+
+            assert_eq!(prefetch_see_maps(gs, &npcs), npcs.len());
+            assert_eq!(prefetch_see_maps(gs, &npcs), 0);
+
+            for &cn in &npcs {
+                let prefetched = gs.see_map[cn].vis;
+                gs.see_map[cn].x = 0;
+                let (x, y) = (i32::from(gs.characters[cn].x), 100);
+                gs.can_see(Some(cn), x, y, x + 1, y, (TILEX / 2) as i32);
+                assert!(gs.see_map[cn].vis == prefetched, "see map of {}", cn);
+            }
+        });
+    }
+
+    #[test]
+    fn parallel_light_rebuild_matches_sequential_lighting() {
+        with_test_gs(|gs| {
+            for x in 300..=310 {
+                gs.map[(x + 300 * SERVER_MAPX) as usize].flags |= u64::from(MF_INDOORS);
+            }
+            for (n, (x, y, light)) in [(302, 301, 120), (306, 299, 80), (309, 303, -40)]
+                .into_iter()
+                .enumerate()
+            {
+                let in_id = n + 1;
+                gs.items[in_id].used = USE_ACTIVE;
+                gs.items[in_id].light[0] = light;
+                gs.map[(x + y * SERVER_MAPX) as usize].it = in_id as u32;
+            }
+
+            assert_eq!(rebuild_lights(gs), (3, 11));
+            let parallel: Vec<(i16, u16)> = gs.map.iter().map(|t| (t.light, t.dlight)).collect();
+
+            for tile in gs.map.iter_mut() {
+                tile.light = 0;
+                tile.dlight = 0;
+            }
+            for x in 300..=310 {
+                gs.compute_dlight(x, 300);
+            }
+            gs.do_add_light(306, 299, 80);
+            gs.do_add_light(302, 301, 120);
+            gs.do_add_light(309, 303, -40);
+            let sequential: Vec<(i16, u16)> = gs.map.iter().map(|t| (t.light, t.dlight)).collect();
+            assert!(parallel == sequential);
+        });
+    }
+}
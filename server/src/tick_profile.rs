@@ -0,0 +1,203 @@
+//! Per-character and per-zone tick cost attribution.
+//!
+//! [`TickProfiler`] accumulates the time spent acting for each character over
+//! a window of [`WINDOW_TICKS`] ticks. When the window closes it builds a
+//! [`TickProfileReport`] grouping the cost by map area and listing the most
+//! expensive characters, then starts over.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use core::area::get_area_m;
+use core::constants::{CharacterFlags, MAXCHARS, TICKS};
+use core::tick_profile_store::{CharacterCost, TickProfileReport, UNZONED, ZoneCost};
+
+use crate::game_state::GameState;
+
+/// Number of ticks aggregated into one report (30 seconds).
+pub(crate) const WINDOW_TICKS: u32 = TICKS as u32 * 30;
+
+/// Number of characters listed in [`TickProfileReport::top_characters`].
+pub(crate) const TOP_CHARACTERS: usize = 20;
+
+/// Accumulates tick cost per character for the current window.
+pub(crate) struct TickProfiler {
+    micros: Vec<u64>,
+    ticks: Vec<u32>,
+    max_micros: Vec<u64>,
+    window_ticks: u32,
+    tick_micros: u64,
+}
+
+impl TickProfiler {
+    /// Create a profiler with an empty window.
+    ///
+    /// # Returns
+    ///
+    /// * A new profiler.
+    pub(crate) fn new() -> Self {
+        Self {
+            micros: vec![0; MAXCHARS],
+            ticks: vec![0; MAXCHARS],
+            max_micros: vec![0; MAXCHARS],
+            window_ticks: 0,
+            tick_micros: 0,
+        }
+    }
+
+    /// Attribute time spent acting for one character during this tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `cn` - Character index.
+    /// * `elapsed` - Time spent in the character's action and regeneration.
+    pub(crate) fn record_character(&mut self, cn: usize, elapsed: Duration) {
+        let micros = elapsed.as_micros() as u64;
+        self.micros[cn] += micros;
+        self.ticks[cn] += 1;
+        self.max_micros[cn] = self.max_micros[cn].max(micros);
+    }
+
+    /// Record the duration of a whole game tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - Time spent in the game tick.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the window is complete and a report should be taken.
+    pub(crate) fn record_tick(&mut self, elapsed: Duration) -> bool {
+        self.tick_micros += elapsed.as_micros() as u64;
+        self.window_ticks += 1;
+        self.window_ticks >= WINDOW_TICKS
+    }
+
+    /// Build the report for the current window and start a new one.
+    ///
+    /// Characters are attributed to the area they stand in when the window
+    /// closes.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state, used for character names and positions.
+    /// * `generated_at` - Wall-clock time in seconds since Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// * The finished report.
+    pub(crate) fn take_report(&mut self, gs: &GameState, generated_at: u64) -> TickProfileReport {
+        let mut zones: HashMap<String, ZoneCost> = HashMap::new();
+        let mut characters = Vec::new();
+        let mut character_micros = 0;
+
+        for cn in 1..MAXCHARS {
+            if self.ticks[cn] == 0 {
+                continue;
+            }
+            let ch = &gs.characters[cn];
+            let zone =
+                get_area_m(i32::from(ch.x), i32::from(ch.y)).unwrap_or_else(|| UNZONED.to_owned());
+            let entry = zones.entry(zone.clone()).or_insert_with(|| ZoneCost {
+                zone: zone.clone(),
+                micros: 0,
+                characters: 0,
+            });
+            entry.micros += self.micros[cn];
+            entry.characters += 1;
+            character_micros += self.micros[cn];
+
+            characters.push(CharacterCost {
+                character_id: cn as u32,
+                name: ch.get_name().to_owned(),
+                is_player: ch.flags & CharacterFlags::Player.bits() != 0,
+                x: ch.x,
+                y: ch.y,
+                zone,
+                micros: self.micros[cn],
+                ticks: self.ticks[cn],
+                max_micros: self.max_micros[cn],
+            });
+        }
+
+        let mut zones: Vec<ZoneCost> = zones.into_values().collect();
+        zones.sort_by(|a, b| b.micros.cmp(&a.micros).then_with(|| a.zone.cmp(&b.zone)));
+        characters.sort_by(|a, b| {
+            b.micros
+                .cmp(&a.micros)
+                .then_with(|| a.character_id.cmp(&b.character_id))
+        });
+        characters.truncate(TOP_CHARACTERS);
+
+        let report = TickProfileReport {
+            generated_at,
+            ticker: gs.globals.ticker,
+            window_ticks: self.window_ticks,
+            tick_micros: self.tick_micros,
+            character_micros,
+            zones,
+            top_characters: characters,
+        };
+
+        self.micros.fill(0);
+        self.ticks.fill(0);
+        self.max_micros.fill(0);
+        self.window_ticks = 0;
+        self.tick_micros = 0;
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::with_test_gs;
+
+    #[test]
+    fn report_ranks_characters_and_groups_by_zone() {
+        with_test_gs(|gs| {
+            // Aston covers (481..=633, 407..=596).
+            for (cn, name, x, y) in [
+                (5, "Rat", 500, 500),
+                (6, "Guard", 510, 500),
+                (7, "Wolf", 1000, 1000),
+            ] {
+                gs.characters[cn].set_name(name);
+                gs.characters[cn].x = x;
+                gs.characters[cn].y = y;
+            }
+
+            let mut profiler = TickProfiler::new();
+            for _ in 0..3 {
+                profiler.record_character(5, Duration::from_micros(10));
+                profiler.record_character(6, Duration::from_micros(100));
+            }
+            profiler.record_character(7, Duration::from_micros(50));
+            assert!(!profiler.record_tick(Duration::from_micros(1000)));
+
+            let report = profiler.take_report(gs, 42);
+            assert_eq!(report.window_ticks, 1);
+            assert_eq!(report.character_micros, 380);
+            let top: Vec<_> = report
+                .top_characters
+                .iter()
+                .map(|c| (c.name.as_str(), c.micros, c.ticks, c.max_micros))
+                .collect();
+            assert_eq!(
+                top,
+                [
+                    ("Guard", 300, 3, 100),
+                    ("Wolf", 50, 1, 50),
+                    ("Rat", 30, 3, 10)
+                ]
+            );
+            assert_eq!(report.zones[0].micros, 330);
+            assert_eq!(report.zones[0].characters, 2);
+            assert!(report.zones[0].zone.contains("Aston"));
+            assert_eq!(report.zones[1].zone, UNZONED);
+
+            // The window was reset.
+            assert!(profiler.take_report(gs, 43).top_characters.is_empty());
+        });
+    }
+}
@@ -1,13 +1,16 @@
 //! TLS helper for the game server.
 //!
-//! Provides [`GameStream`], a wrapper that abstracts over plain TCP and
-//! TLS-encrypted connections so that the rest of the server code can use
-//! `Read` + `Write` without caring about the transport.
+//! Provides [`GameStream`], a wrapper that abstracts over plain TCP,
+//! TLS-encrypted and WebSocket connections so that the rest of the server
+//! code can use `Read` + `Write` without caring about the transport.
 
 use rustls::ServerConnection;
 use std::io::{self, Read, Write};
 use std::net::{Shutdown, TcpStream};
 use std::sync::Arc;
+use std::time::Duration;
+
+use crate::transport::{Transport, WsStream};
 
 /// A game-server connection that may or may not be TLS-encrypted.
 #[allow(clippy::large_enum_variant)]
@@ -16,30 +19,32 @@ pub enum GameStream {
     Plain(TcpStream),
     /// TLS-encrypted connection wrapping a TCP stream.
     Tls(rustls::StreamOwned<ServerConnection, TcpStream>),
+    /// WebSocket connection over a plain or TLS stream.
+    WebSocket(Box<WsStream<GameStream>>),
 }
 
-impl GameStream {
-    /// Sets the underlying TCP stream to non-blocking mode.
-    ///
-    /// # Arguments
-    ///
-    /// * `nonblocking` - Value passed to `set_nonblocking`.
-    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+impl Transport for GameStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
         match self {
             GameStream::Plain(s) => s.set_nonblocking(nonblocking),
-            GameStream::Tls(s) => s.sock.set_nonblocking(nonblocking),
+            GameStream::Tls(s) => s.set_nonblocking(nonblocking),
+            GameStream::WebSocket(s) => s.set_nonblocking(nonblocking),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            GameStream::Plain(s) => s.set_read_timeout(timeout),
+            GameStream::Tls(s) => s.set_read_timeout(timeout),
+            GameStream::WebSocket(s) => s.set_read_timeout(timeout),
         }
     }
 
-    /// Shuts down the underlying TCP connection.
-    ///
-    /// # Arguments
-    ///
-    /// * `how` - Value passed to `shutdown`.
-    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         match self {
             GameStream::Plain(s) => s.shutdown(how),
-            GameStream::Tls(s) => s.sock.shutdown(how),
+            GameStream::Tls(s) => s.shutdown(how),
+            GameStream::WebSocket(s) => s.shutdown(how),
         }
     }
 }
@@ -49,6 +54,7 @@ impl Read for GameStream {
         match self {
             GameStream::Plain(s) => s.read(buf),
             GameStream::Tls(s) => s.read(buf),
+            GameStream::WebSocket(s) => s.read(buf),
         }
     }
 }
@@ -58,6 +64,7 @@ impl Write for GameStream {
         match self {
             GameStream::Plain(s) => s.write(buf),
             GameStream::Tls(s) => s.write(buf),
+            GameStream::WebSocket(s) => s.write(buf),
         }
     }
 
@@ -65,6 +72,7 @@ impl Write for GameStream {
         match self {
             GameStream::Plain(s) => s.flush(),
             GameStream::Tls(s) => s.flush(),
+            GameStream::WebSocket(s) => s.flush(),
         }
     }
 }
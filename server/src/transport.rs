@@ -0,0 +1,336 @@
+//! Transports the game protocol can run over.
+//!
+//! The protocol is a plain byte stream: fixed 16-byte client frames one way,
+//! length-prefixed tick packets the other. [`Transport`] is what the rest of
+//! the server needs from a connection to carry that stream; raw TCP, TLS and
+//! WebSocket connections all implement it, and [`crate::tls::GameStream`]
+//! dispatches to whichever one a player is using.
+//!
+//! [`WsStream`] carries the stream in binary WebSocket messages so the game
+//! works through proxies and firewalls that only pass HTTP(S), and later from
+//! a browser. Message boundaries carry no meaning: a write becomes one
+//! message, and reads hand out message payloads as a continuous stream, so
+//! `rec_player` and `process_frames` need not know which transport they read.
+//!
+//! The WebSocket listener is off unless [`WS_PORT_ENV`] is set. It speaks TLS
+//! (`wss://`) with the game port's certificate; [`WS_PLAIN_ENV`] drops that
+//! for deployments where a reverse proxy terminates TLS in front of it.
+
+use rustls::ServerConnection;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// Environment variable holding the WebSocket listener port; the listener
+/// is disabled while it is unset or empty.
+pub const WS_PORT_ENV: &str = "MAG_WS_PORT";
+
+/// Environment variable that, when `1`, serves plain `ws://` instead of
+/// `wss://` on [`WS_PORT_ENV`].
+pub const WS_PLAIN_ENV: &str = "MAG_WS_PLAIN";
+
+/// How long an opening WebSocket handshake may take.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A connection that carries the game's byte stream.
+pub trait Transport: Read + Write {
+    /// Switches the underlying socket between blocking and non-blocking.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonblocking` - Value passed to `set_nonblocking`.
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+
+    /// Sets the read timeout of the underlying socket.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Value passed to `set_read_timeout`.
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    /// Shuts down the underlying socket.
+    ///
+    /// # Arguments
+    ///
+    /// * `how` - Value passed to `shutdown`.
+    fn shutdown(&self, how: Shutdown) -> io::Result<()>;
+}
+
+impl Transport for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        TcpStream::shutdown(self, how)
+    }
+}
+
+impl Transport for rustls::StreamOwned<ServerConnection, TcpStream> {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.sock.set_nonblocking(nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.sock.set_read_timeout(timeout)
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.sock.shutdown(how)
+    }
+}
+
+/// WebSocket listener settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WsConfig {
+    /// Port the listener binds on all interfaces.
+    pub port: u16,
+    /// Whether connections are wrapped in TLS before the WebSocket upgrade.
+    pub tls: bool,
+}
+
+/// Reads the WebSocket listener settings.
+///
+/// # Arguments
+///
+/// * `port` - Value of [`WS_PORT_ENV`], if set.
+/// * `plain` - Value of [`WS_PLAIN_ENV`], if set.
+///
+/// # Returns
+///
+/// * `Ok(None)` when no port is configured, `Ok(Some(config))` otherwise, or
+///   an error for a port that is not a number from 1 to 65535.
+pub fn parse_ws_config(
+    port: Option<&str>,
+    plain: Option<&str>,
+) -> Result<Option<WsConfig>, String> {
+    let port = match port.map(str::trim) {
+        None | Some("") => return Ok(None),
+        Some(port) => port,
+    };
+    let port = port
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port != 0)
+        .ok_or_else(|| format!("Invalid {} value '{}'", WS_PORT_ENV, port))?;
+    let tls = plain.map(str::trim) != Some("1");
+    Ok(Some(WsConfig { port, tls }))
+}
+
+/// The game byte stream carried in binary WebSocket messages.
+pub struct WsStream<S: Transport> {
+    ws: WebSocket<S>,
+    /// Payload of the last message, handed out by `read`.
+    pending: Vec<u8>,
+    /// Bytes of `pending` already handed out.
+    pos: usize,
+}
+
+impl<S: Transport> WsStream<S> {
+    /// Wraps an established WebSocket connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `ws` - Connection whose opening handshake has completed.
+    pub fn new(ws: WebSocket<S>) -> Self {
+        WsStream {
+            ws,
+            pending: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+/// Converts a WebSocket error into the `io::Error` `Read`/`Write` callers
+/// expect; `WouldBlock` passes through unchanged.
+fn ws_io_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(e) => e,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::ConnectionAborted, "WebSocket closed")
+        }
+        other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+    }
+}
+
+impl<S: Transport> Read for WsStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.pending.len() {
+            match self.ws.read() {
+                Ok(Message::Binary(data)) => {
+                    self.pending = data.into();
+                    self.pos = 0;
+                }
+                Ok(Message::Close(_)) => return Ok(0),
+                // Pings are answered by tungstenite itself.
+                Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_)) => {}
+                Ok(Message::Text(_)) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "text WebSocket message on the game stream",
+                    ));
+                }
+                Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                    return Ok(0);
+                }
+                Err(e) => return Err(ws_io_error(e)),
+            }
+        }
+        let len = buf.len().min(self.pending.len() - self.pos);
+        buf[..len].copy_from_slice(&self.pending[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+impl<S: Transport> Write for WsStream<S> {
+    /// Sends `buf` as one binary message.
+    ///
+    /// Refuses with `WouldBlock` while an earlier message is still queued, so
+    /// a stalled peer backs up into `obuf` like on a raw socket instead of
+    /// into an unbounded WebSocket buffer.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.flush()?;
+        match self.ws.write(Message::binary(buf.to_vec())) {
+            Ok(()) => {}
+            // The message is queued; the next write or flush sends the rest.
+            Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(ws_io_error(e)),
+        }
+        match self.ws.flush() {
+            Ok(()) => Ok(buf.len()),
+            Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {
+                Ok(buf.len())
+            }
+            Err(e) => Err(ws_io_error(e)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.ws.flush().map_err(ws_io_error)
+    }
+}
+
+impl<S: Transport> Transport for WsStream<S> {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.ws.get_ref().set_nonblocking(nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.ws.get_ref().set_read_timeout(timeout)
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.ws.get_ref().shutdown(how)
+    }
+}
+
+/// Perform a blocking WebSocket opening handshake on `stream`.
+///
+/// Like `tls::accept_tls`, the stream is blocking for the handshake and
+/// switched back to non-blocking afterwards. `stream` is already TLS-wrapped
+/// for `wss://`.
+///
+/// # Arguments
+///
+/// * `stream` - Freshly accepted (and, for `wss://`, TLS-established) stream.
+///
+/// # Returns
+///
+/// * `Ok` with the upgraded stream, or `Err` with failure details.
+pub fn accept_websocket<S: Transport>(stream: S) -> Result<WsStream<S>, String> {
+    stream
+        .set_nonblocking(false)
+        .map_err(|e| format!("set_nonblocking(false): {e}"))?;
+    stream
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .map_err(|e| format!("set_read_timeout: {e}"))?;
+
+    let ws = tungstenite::accept(stream).map_err(|e| format!("WebSocket handshake: {e}"))?;
+
+    ws.get_ref()
+        .set_nonblocking(true)
+        .map_err(|e| format!("set_nonblocking(true): {e}"))?;
+    ws.get_ref()
+        .set_read_timeout(None)
+        .map_err(|e| format!("clear read_timeout: {e}"))?;
+
+    Ok(WsStream::new(ws))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn ws_config_is_off_without_a_port() {
+        assert_eq!(parse_ws_config(None, None), Ok(None));
+        assert_eq!(parse_ws_config(Some("  "), Some("1")), Ok(None));
+    }
+
+    #[test]
+    fn ws_config_defaults_to_tls() {
+        assert_eq!(
+            parse_ws_config(Some("5558"), None),
+            Ok(Some(WsConfig {
+                port: 5558,
+                tls: true
+            }))
+        );
+        assert_eq!(
+            parse_ws_config(Some("5558"), Some("1")),
+            Ok(Some(WsConfig {
+                port: 5558,
+                tls: false
+            }))
+        );
+    }
+
+    #[test]
+    fn ws_config_rejects_bad_ports() {
+        assert!(parse_ws_config(Some("0"), None).is_err());
+        assert!(parse_ws_config(Some("70000"), None).is_err());
+        assert!(parse_ws_config(Some("ws"), None).is_err());
+    }
+
+    #[test]
+    fn ws_stream_carries_the_byte_stream_across_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind test listener");
+        let addr = listener.local_addr().expect("listener addr");
+        let client = std::thread::spawn(move || {
+            let stream = TcpStream::connect(addr).expect("connect test client");
+            let (mut ws, _) =
+                tungstenite::client(format!("ws://{addr}/"), stream).expect("client handshake");
+            // One 16-byte frame split over two messages, then a whole one.
+            ws.send(Message::binary(vec![1u8; 10])).expect("send");
+            ws.send(Message::binary(vec![1u8; 6])).expect("send");
+            ws.send(Message::binary(vec![2u8; 16])).expect("send");
+            match ws.read().expect("read reply") {
+                Message::Binary(data) => data.to_vec(),
+                other => panic!("unexpected message {other:?}"),
+            }
+        });
+
+        let (server, _) = listener.accept().expect("accept test client");
+        let mut stream = accept_websocket(server).expect("server handshake");
+        stream.set_nonblocking(false).expect("blocking reads");
+
+        let mut frame = [0u8; 16];
+        stream.read_exact(&mut frame).expect("first frame");
+        assert_eq!(frame, [1u8; 16]);
+        stream.read_exact(&mut frame).expect("second frame");
+        assert_eq!(frame, [2u8; 16]);
+
+        assert_eq!(stream.write(&[7, 8, 9]).expect("write reply"), 3);
+        assert_eq!(client.join().expect("client thread"), vec![7, 8, 9]);
+    }
+}
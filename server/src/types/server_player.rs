@@ -1,11 +1,16 @@
 use core::{
     constants::MAXPLAYER,
+    map_markers::MapMarkerKind,
     types::{ClientPlayer, Map},
 };
 
 use flate2::write::ZlibEncoder;
 
-use crate::{tls::GameStream, types::cmap::CMap};
+use crate::{
+    player::{command_budget::CommandBudget, command_seq::CommandSeq, framing::ProfileUpload},
+    tls::GameStream,
+    types::cmap::CMap,
+};
 use core::constants::{OBUFSIZE, SPR_EMPTY, TBUFSIZE, TILEX, TILEY};
 
 // Server side player data
@@ -84,6 +89,62 @@ pub struct ServerPlayer {
     /// `SV_SETQUESTCOMPLETION` snapshots have been dispatched to this
     /// player. Set to `true` immediately after that first send.
     pub sent_quest_init: bool,
+
+    /// `CLIENT_CAP_*` bits from the client's `CmdClientCaps`; `0` for
+    /// clients that never sent one.
+    pub capabilities: u32,
+
+    /// Ticker value of the last `SV_CHARCHECKSUM` sent to this player.
+    pub last_checksum_tick: i32,
+
+    /// Ticker value of the last `SV_TIMESYNC` sent to this player.
+    pub last_time_sync_tick: i32,
+
+    /// Ticker value of the last checksum-triggered resync, used to
+    /// rate-limit `CmdRequestResync`.
+    pub last_resync_tick: i32,
+
+    /// Where this player's character last died this session, `(0, 0)` if
+    /// it has not. Shown as a minimap marker.
+    pub death_marker: (u16, u16),
+
+    /// Minimap marker positions last sent with `SV_MAPMARKER`, indexed by
+    /// `MapMarkerKind`.
+    pub sent_map_markers: [(u16, u16); MapMarkerKind::ALL.len()],
+
+    /// Item index last named with `SV_ITEMNAME`, per backpack slot.
+    pub sent_item_names: [u32; 40],
+
+    /// Ticker value of the last `CmdSortInventory` applied, used to
+    /// rate-limit sorting.
+    pub last_sort_tick: i32,
+
+    /// Reassembly state for `CmdSetProfile` bio chunks. Cleared after
+    /// each completed upload; never persisted.
+    pub profile_upload: ProfileUpload,
+
+    /// Remaining `CmdWaypoints` route, front first. Dropped as soon as the
+    /// character's goto target no longer matches the front waypoint.
+    pub waypoints: std::collections::VecDeque<(u16, u16)>,
+
+    /// Skill cast last announced with `SV_SKILLTIMER` as
+    /// `(skill, expected end tick)`.
+    pub sent_cast: Option<(u8, i32)>,
+
+    /// Cooldowns last announced with `SV_SKILLTIMER` as
+    /// `(skill, expected end tick)`.
+    pub sent_skill_cooldowns: Vec<(u8, i32)>,
+
+    /// Command execution-time overruns, used to throttle players whose
+    /// commands keep blowing the budget. Per session.
+    pub command_budget: CommandBudget,
+
+    /// `CmdSeq` replay window of this connection.
+    pub command_seq: CommandSeq,
+
+    /// Language code NPC dialogue is rendered in for clients without
+    /// `CLIENT_CAP_DIALOGUE_KEYS`; set by `CmdClientLocale`.
+    pub locale: String,
 }
 
 impl ServerPlayer {
@@ -141,6 +202,21 @@ impl ServerPlayer {
             weather_tint: [0; 4],
             weather_flags: 0,
             sent_quest_init: false,
+            capabilities: 0,
+            last_checksum_tick: 0,
+            last_time_sync_tick: 0,
+            last_resync_tick: 0,
+            death_marker: (0, 0),
+            sent_map_markers: [(0, 0); MapMarkerKind::ALL.len()],
+            sent_item_names: [0; 40],
+            last_sort_tick: 0,
+            profile_upload: ProfileUpload::default(),
+            waypoints: std::collections::VecDeque::new(),
+            sent_cast: None,
+            sent_skill_cooldowns: Vec::new(),
+            command_budget: CommandBudget::default(),
+            command_seq: CommandSeq::default(),
+            locale: core::dialogue::DEFAULT_LOCALE.to_owned(),
         }
     }
 
@@ -0,0 +1,89 @@
+//! `CMD-INV` validation.
+
+use super::{check_citem, check_owned};
+use crate::game_state::GameState;
+
+/// Number of backpack slots.
+const BACKPACK_SLOTS: usize = 40;
+
+/// Number of worn equipment slots.
+const WORN_SLOTS: usize = 20;
+
+/// Validates a `CMD-INV` packet.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `cn` - Character issuing the command.
+/// * `what` - Sub-command (see `plr_cmd_inv`).
+/// * `n` - Slot index or gold amount, depending on `what`.
+///
+/// # Returns
+///
+/// * `Ok(())` if the packet is well formed for `cn`.
+pub(super) fn validate_inv(gs: &GameState, cn: usize, what: usize, n: usize) -> Result<(), String> {
+    match what {
+        // Swap cursor with backpack slot; use / look at backpack slot.
+        0 | 6 | 8 => {
+            if n >= BACKPACK_SLOTS {
+                return Err(format!("backpack slot {} out of range", n));
+            }
+            let slot_item = gs.characters[cn].item[n] as usize;
+            if slot_item != 0 {
+                check_owned(gs, cn, slot_item)?;
+            }
+            if what == 0 {
+                check_citem(gs, cn)?;
+            }
+            Ok(())
+        }
+        // Swap cursor with worn slot; use / look at worn slot.
+        1 | 5 | 7 => {
+            if n >= WORN_SLOTS {
+                return Err(format!("worn slot {} out of range", n));
+            }
+            let worn_item = gs.characters[cn].worn[n] as usize;
+            if worn_item != 0 {
+                check_owned(gs, cn, worn_item)?;
+            }
+            if what == 1 {
+                check_citem(gs, cn)?;
+            }
+            Ok(())
+        }
+        // Take gold onto the cursor.
+        2 => {
+            if n == 0 || n > gs.characters[cn].gold.max(0) as usize {
+                return Err(format!(
+                    "withdraw of {} with {} gold",
+                    n, gs.characters[cn].gold
+                ));
+            }
+            Ok(())
+        }
+        _ => Err(format!("unknown sub-command {}", what)),
+    }
+}
+
+/// Validates a `CmdSortInventory` packet.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `cn` - Character issuing the command.
+/// * `key` - Sort key byte.
+///
+/// # Returns
+///
+/// * `Ok(())` if the key is known and every backpack item belongs to `cn`.
+pub(super) fn validate_sort(gs: &GameState, cn: usize, key: u8) -> Result<(), String> {
+    if core::inventory_sort::InventorySortKey::from_u8(key).is_none() {
+        return Err(format!("unknown sort key {}", key));
+    }
+    for &slot_item in gs.characters[cn].item.iter().take(BACKPACK_SLOTS) {
+        if slot_item != 0 {
+            check_owned(gs, cn, slot_item as usize)?;
+        }
+    }
+    Ok(())
+}
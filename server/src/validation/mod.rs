@@ -0,0 +1,255 @@
+//! Validation of incoming client commands against the character's state.
+//!
+//! The command handlers in `player::commands` were ported from the original
+//! server and trust most of what the client sends. This layer runs in
+//! [`crate::player::plr_cmd`] before dispatch and drops packets that no
+//! honest client can produce: out-of-range slots, unknown sub-commands,
+//! items the character does not own and raises for skills it never learned.
+//! Dropped packets are logged with the reason so abuse shows up in the logs.
+//!
+//! Only structural checks live here. Situational refusals (stunned, out of
+//! range, not enough points) stay in the handlers, which tell the player.
+
+mod inventory;
+mod shop;
+mod stats;
+
+use core::client_commands::ClientCommandType;
+use core::constants::USE_ACTIVE;
+
+use crate::game_state::GameState;
+
+/// Reads a little-endian `u16` from `inbuf[at..at + 2]`.
+fn read_u16(gs: &GameState, nr: usize, at: usize) -> u16 {
+    u16::from_le_bytes([gs.players[nr].inbuf[at], gs.players[nr].inbuf[at + 1]])
+}
+
+/// Reads a little-endian `u32` from `inbuf[at..at + 4]`.
+fn read_u32(gs: &GameState, nr: usize, at: usize) -> u32 {
+    let b = &gs.players[nr].inbuf;
+    u32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]])
+}
+
+/// Checks that `item_idx` is a live item carried by `cn`.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `cn` - Character claiming the item.
+/// * `item_idx` - Item index from one of the character's slots.
+///
+/// # Returns
+///
+/// * `Ok(())` if `cn` owns the item, otherwise the reason it does not.
+fn check_owned(gs: &GameState, cn: usize, item_idx: usize) -> Result<(), String> {
+    let Some(item) = gs.items.get(item_idx) else {
+        return Err(format!("item {} out of range", item_idx));
+    };
+    if item.used != USE_ACTIVE {
+        return Err(format!("item {} is not in use", item_idx));
+    }
+    if usize::from(item.carried) != cn {
+        return Err(format!(
+            "item {} is carried by {}, not {}",
+            item_idx, item.carried, cn
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the item on `cn`'s cursor, if any, belongs to `cn`.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `cn` - Character whose cursor item is checked.
+///
+/// # Returns
+///
+/// * `Ok(())` for an empty cursor, money or an owned item.
+fn check_citem(gs: &GameState, cn: usize) -> Result<(), String> {
+    let citem = gs.characters[cn].citem;
+    if citem == 0 || citem & 0x8000_0000 != 0 {
+        return Ok(());
+    }
+    check_owned(gs, cn, citem as usize).map_err(|e| format!("cursor {}", e))
+}
+
+/// Validates the command in `players[nr].inbuf` for its character.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `nr` - Player slot that sent the command.
+/// * `cmd` - Parsed command type.
+///
+/// # Returns
+///
+/// * `Ok(())` if the command may be applied, otherwise why it was rejected.
+pub(crate) fn validate_command(
+    gs: &GameState,
+    nr: usize,
+    cmd: ClientCommandType,
+) -> Result<(), String> {
+    let cn = gs.players[nr].usnr;
+    match cmd {
+        ClientCommandType::CmdInv => inventory::validate_inv(
+            gs,
+            cn,
+            read_u32(gs, nr, 1) as usize,
+            read_u32(gs, nr, 5) as usize,
+        ),
+        ClientCommandType::CmdShop => shop::validate_shop(
+            gs,
+            cn,
+            usize::from(read_u16(gs, nr, 1)),
+            usize::from(read_u16(gs, nr, 3)),
+        ),
+        ClientCommandType::CmdStat => stats::validate_stat(
+            gs,
+            cn,
+            usize::from(read_u16(gs, nr, 1)),
+            usize::from(read_u16(gs, nr, 3)),
+        ),
+        ClientCommandType::CmdSortInventory => {
+            inventory::validate_sort(gs, cn, gs.players[nr].inbuf[1])
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Validates a command and logs it if it has to be dropped.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `nr` - Player slot that sent the command.
+/// * `cmd` - Parsed command type.
+///
+/// # Returns
+///
+/// * `true` if the command may be dispatched.
+pub(crate) fn check_command(gs: &GameState, nr: usize, cmd: ClientCommandType) -> bool {
+    match validate_command(gs, nr, cmd) {
+        Ok(()) => true,
+        Err(reason) => {
+            let cn = gs.players[nr].usnr;
+            log::warn!(
+                "Dropped {:?} from player {} ({}): {}",
+                cmd,
+                nr,
+                gs.characters[cn].get_name(),
+                reason
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs, write_inbuf};
+
+    fn inv_packet(what: u32, n: u32) -> [u8; 13] {
+        let mut packet = [0u8; 13];
+        packet[1..5].copy_from_slice(&what.to_le_bytes());
+        packet[5..9].copy_from_slice(&n.to_le_bytes());
+        packet
+    }
+
+    #[test]
+    fn inventory_commands_need_known_sub_command_valid_slot_and_owned_items() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            let check = |gs: &mut GameState, what, n| {
+                write_inbuf(gs, nr, &inv_packet(what, n));
+                check_command(gs, nr, ClientCommandType::CmdInv)
+            };
+
+            assert!(check(gs, 0, 39));
+            assert!(!check(gs, 0, 40));
+            assert!(!check(gs, 5, 20));
+            assert!(!check(gs, 3, 0));
+
+            gs.items[10].used = USE_ACTIVE;
+            gs.items[10].carried = 2;
+            gs.characters[cn].item[4] = 10;
+            assert!(!check(gs, 6, 4));
+            gs.items[10].carried = cn as u16;
+            assert!(check(gs, 6, 4));
+
+            gs.characters[cn].citem = 10;
+            gs.items[10].carried = 2;
+            assert!(!check(gs, 0, 0));
+            gs.characters[cn].citem = 0x8000_0000 | 10;
+            assert!(check(gs, 0, 0));
+        });
+    }
+
+    #[test]
+    fn stat_raises_need_a_learned_skill() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            let check = |gs: &mut GameState, n: u16, v: u16| {
+                let mut packet = [0u8; 5];
+                packet[1..3].copy_from_slice(&n.to_le_bytes());
+                packet[3..5].copy_from_slice(&v.to_le_bytes());
+                write_inbuf(gs, nr, &packet);
+                check_command(gs, nr, ClientCommandType::CmdStat)
+            };
+
+            assert!(check(gs, 0, 1));
+            assert!(!check(gs, 0, 0));
+            assert!(!check(gs, 0, 100));
+            assert!(!check(gs, 8 + core::skills::SK_LOCK as u16, 1));
+            gs.characters[cn].skill[core::skills::SK_LOCK][0] = 1;
+            assert!(check(gs, 8 + core::skills::SK_LOCK as u16, 1));
+            assert!(!check(gs, 8 + core::skills::MAX_SKILLS as u16, 1));
+        });
+    }
+
+    #[test]
+    fn shop_commands_only_target_own_depot_and_merchants_or_bodies() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            let check = |gs: &mut GameState, co: u16, n: u16| {
+                let mut packet = [0u8; 5];
+                packet[1..3].copy_from_slice(&co.to_le_bytes());
+                packet[3..5].copy_from_slice(&n.to_le_bytes());
+                write_inbuf(gs, nr, &packet);
+                check_command(gs, nr, ClientCommandType::CmdShop)
+            };
+
+            assert!(check(gs, 0x8000 | cn as u16, 0));
+            assert!(!check(gs, 0x8000 | 2, 0));
+            assert!(!check(gs, 0x8000 | cn as u16, 124));
+
+            gs.characters[2].used = USE_ACTIVE;
+            assert!(!check(gs, 2, 0));
+            gs.characters[2].flags = core::constants::CharacterFlags::Merchant.bits();
+            assert!(check(gs, 2, 0));
+        });
+    }
+
+    #[test]
+    fn sort_needs_known_key_and_owned_backpack() {
+        with_test_gs(|gs| {
+            let (cn, nr) = add_test_player(gs);
+            let check = |gs: &mut GameState, key: u8| {
+                write_inbuf(gs, nr, &[0, key]);
+                check_command(gs, nr, ClientCommandType::CmdSortInventory)
+            };
+
+            assert!(check(gs, 1));
+            assert!(!check(gs, 9));
+
+            gs.items[10].used = USE_ACTIVE;
+            gs.items[10].carried = 2;
+            gs.characters[cn].item[12] = 10;
+            assert!(!check(gs, 0));
+            gs.items[10].carried = cn as u16;
+            assert!(check(gs, 0));
+        });
+    }
+}
@@ -0,0 +1,44 @@
+//! `CMD-SHOP` validation.
+
+use core::constants::{CharacterFlags, MAXCHARS, USE_EMPTY};
+
+use super::check_citem;
+use crate::game_state::GameState;
+
+/// Number of shop/depot slot indices: 62 to take plus 62 to look at.
+const SHOP_SLOTS: usize = 124;
+
+/// Validates a `CMD-SHOP` packet.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `cn` - Character issuing the command.
+/// * `co` - Target merchant or body, or `0x8000 | cn` for the own depot.
+/// * `n` - Slot index.
+///
+/// # Returns
+///
+/// * `Ok(())` if the packet is well formed for `cn`.
+pub(super) fn validate_shop(gs: &GameState, cn: usize, co: usize, n: usize) -> Result<(), String> {
+    if n >= SHOP_SLOTS {
+        return Err(format!("slot {} out of range", n));
+    }
+
+    if co & 0x8000 != 0 {
+        let owner = co & 0x7fff;
+        if owner != cn {
+            return Err(format!("depot of character {}", owner));
+        }
+        return check_citem(gs, cn);
+    }
+
+    if co == 0 || co >= MAXCHARS || gs.characters[co].used == USE_EMPTY {
+        return Err(format!("no such character {}", co));
+    }
+    let trade_flags = CharacterFlags::Merchant.bits() | CharacterFlags::Body.bits();
+    if gs.characters[co].flags & trade_flags == 0 {
+        return Err(format!("character {} is neither merchant nor body", co));
+    }
+    check_citem(gs, cn)
+}
@@ -0,0 +1,45 @@
+//! `CMD-STAT` validation.
+
+use core::skills::{self, SkillIndex};
+
+use crate::game_state::GameState;
+use crate::helpers;
+
+/// Stat indices before the skills: five attributes, HP, endurance, mana.
+const SKILL_OFFSET: usize = 8;
+
+/// Most raises the client batches into one packet.
+const MAX_RAISES: usize = 99;
+
+/// Validates a `CMD-STAT` packet.
+///
+/// # Arguments
+///
+/// * `gs` - Active game state.
+/// * `cn` - Character raising the stat.
+/// * `n` - Stat index: attributes, HP, endurance, mana, then skills.
+/// * `v` - Number of raises.
+///
+/// # Returns
+///
+/// * `Ok(())` if the packet is well formed for `cn`.
+pub(super) fn validate_stat(gs: &GameState, cn: usize, n: usize, v: usize) -> Result<(), String> {
+    if v == 0 || v > MAX_RAISES {
+        return Err(format!("raise count {} out of range", v));
+    }
+    if n < SKILL_OFFSET {
+        return Ok(());
+    }
+
+    let skill = n - SKILL_OFFSET;
+    if skill >= skills::MAX_SKILLS {
+        return Err(format!("stat {} out of range", n));
+    }
+    // Raises of retired weapon skills land on the merged weapon skill.
+    let mut learned = gs.characters[cn].skill;
+    helpers::sync_weapon_skill(&mut learned);
+    if learned[skills::canonicalize_weapon_skill(skill)][SkillIndex::BaseValue as usize] == 0 {
+        return Err(format!("skill {} not learned", skill));
+    }
+    Ok(())
+}
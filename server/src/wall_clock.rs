@@ -0,0 +1,179 @@
+//! Wall-clock helpers: UTC for everything stored or scheduled, a configurable
+//! time zone for text shown to people.
+//!
+//! Hour-of-day statistics in [`Global`] are indexed by UTC hour so a daylight
+//! saving change neither skips nor double-counts a bucket. Older saves
+//! indexed them by the host's local hour; [`migrate_hourly_buckets`] rotates
+//! those once on load and marks the globals with [`GF_UTC_HOURS`].
+
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use core::constants::GF_UTC_HOURS;
+use core::types::Global;
+
+/// Environment variable naming the IANA time zone used for display
+/// (e.g. `Europe/Berlin`). Defaults to UTC.
+pub const DISPLAY_TZ_ENV: &str = "MAG_DISPLAY_TZ";
+
+/// Current UTC time in seconds since the Unix epoch.
+///
+/// In deterministic mode this is the fixed tick clock of
+/// [`crate::deterministic`].
+pub fn unix_now() -> i64 {
+    if let Some(fixed) = crate::deterministic::fixed_unix_now() {
+        return fixed;
+    }
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Current UTC hour of day, used to index the hourly statistics.
+///
+/// # Returns
+///
+/// * Hour in `0..24`.
+pub fn utc_hour() -> usize {
+    if let Some(fixed) = crate::deterministic::fixed_unix_now() {
+        return (fixed.rem_euclid(86_400) / 3600) as usize;
+    }
+    Utc::now().hour() as usize
+}
+
+/// Time zone used by [`format_display`], read once from [`DISPLAY_TZ_ENV`].
+fn display_tz() -> Tz {
+    static TZ: OnceLock<Tz> = OnceLock::new();
+    *TZ.get_or_init(|| match std::env::var(DISPLAY_TZ_ENV) {
+        Ok(name) if !name.trim().is_empty() => name.trim().parse().unwrap_or_else(|_| {
+            log::warn!(
+                "Unknown time zone '{}' in {}; displaying times in UTC.",
+                name,
+                DISPLAY_TZ_ENV
+            );
+            Tz::UTC
+        }),
+        _ => Tz::UTC,
+    })
+}
+
+/// Format a stored Unix timestamp for players and staff.
+///
+/// # Arguments
+///
+/// * `unix_secs` - Seconds since the Unix epoch (UTC).
+///
+/// # Returns
+///
+/// * `YYYY-MM-DD HH:MM:SS` in the display time zone, followed by the zone
+///   abbreviation.
+pub fn format_display(unix_secs: i64) -> String {
+    format_in(unix_secs, display_tz())
+}
+
+fn format_in(unix_secs: i64, tz: Tz) -> String {
+    let utc = DateTime::from_timestamp(unix_secs, 0).unwrap_or_default();
+    tz.from_utc_datetime(&utc.naive_utc())
+        .format("%Y-%m-%d %H:%M:%S %Z")
+        .to_string()
+}
+
+/// Number of calendar days between two timestamps, counted in the display
+/// time zone so "today" matches the viewer's clock.
+///
+/// # Arguments
+///
+/// * `earlier` - Earlier Unix timestamp.
+/// * `later` - Later Unix timestamp.
+///
+/// # Returns
+///
+/// * Whole days between the two local dates (0 for the same day).
+pub fn days_between(earlier: i64, later: i64) -> i64 {
+    days_between_in(earlier, later, display_tz())
+}
+
+fn days_between_in(earlier: i64, later: i64, tz: Tz) -> i64 {
+    let date = |secs: i64| {
+        let utc = DateTime::from_timestamp(secs, 0).unwrap_or_default();
+        tz.from_utc_datetime(&utc.naive_utc()).date_naive()
+    };
+    (date(later) - date(earlier)).num_days()
+}
+
+/// Re-index hourly statistics written by local hour to UTC hour.
+///
+/// Does nothing when `globals` already carries [`GF_UTC_HOURS`]. The host's
+/// current offset is assumed for the whole history, which is exact for
+/// servers that always ran in UTC and off by one hour for the DST half of
+/// the year elsewhere.
+///
+/// # Arguments
+///
+/// * `globals` - Loaded globals to migrate in place.
+/// * `local_offset_secs` - Local time minus UTC, in seconds.
+///
+/// # Returns
+///
+/// * `true` when the buckets were migrated and the globals need saving.
+pub fn migrate_hourly_buckets(globals: &mut Global, local_offset_secs: i32) -> bool {
+    if globals.flags & GF_UTC_HOURS != 0 {
+        return false;
+    }
+    // Bucket `h` was written while the local hour was `h`, i.e. at UTC hour
+    // `h - offset`, so shift everything left by the offset.
+    let shift = (local_offset_secs / 3600).rem_euclid(24) as usize;
+    globals.online_per_hour.rotate_left(shift);
+    globals.uptime_per_hour.rotate_left(shift);
+    globals.max_online_per_hour.rotate_left(shift);
+    globals.flags |= GF_UTC_HOURS;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_rotates_local_buckets_to_utc_once() {
+        let mut globals = Global {
+            flags: 0,
+            ..Default::default()
+        };
+        for hour in 0..24 {
+            globals.uptime_per_hour[hour] = hour as i64;
+        }
+
+        // UTC+2: the local 14:00 bucket belongs to 12:00 UTC.
+        assert!(migrate_hourly_buckets(&mut globals, 2 * 3600));
+        assert_eq!(globals.uptime_per_hour[12], 14);
+        assert_eq!(globals.uptime_per_hour[23], 1);
+        assert!(!migrate_hourly_buckets(&mut globals, 2 * 3600));
+        assert_eq!(globals.uptime_per_hour[12], 14);
+
+        // UTC-5: the local 07:00 bucket belongs to 12:00 UTC.
+        let mut western = Global {
+            flags: 0,
+            ..Default::default()
+        };
+        western.online_per_hour[7] = 99;
+        assert!(migrate_hourly_buckets(&mut western, -5 * 3600));
+        assert_eq!(western.online_per_hour[12], 99);
+    }
+
+    #[test]
+    fn display_format_and_days_follow_the_zone() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        // 2024-03-31 00:30 UTC is before the spring-forward, 01:30 after it.
+        assert_eq!(format_in(1_711_845_000, berlin), "2024-03-31 01:30:00 CET");
+        assert_eq!(format_in(1_711_848_600, berlin), "2024-03-31 03:30:00 CEST");
+        assert_eq!(format_in(0, Tz::UTC), "1970-01-01 00:00:00 UTC");
+
+        // 23:30 UTC is already the next day in Berlin.
+        assert_eq!(days_between_in(1_711_845_000, 1_711_927_800, berlin), 1);
+        assert_eq!(days_between_in(1_711_845_000, 1_711_927_800, Tz::UTC), 0);
+    }
+}
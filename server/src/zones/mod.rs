@@ -0,0 +1,238 @@
+//! Zone partitioning of the character tick.
+//!
+//! The map is split into square zones of [`ZONE_SIZE`] tiles. Once per tick
+//! [`ZoneScheduler::rebuild`] marks every zone within [`ACTIVE_RADIUS`]
+//! zones of an in-world player as *hot*. NPCs in hot zones act every tick as
+//! before; NPCs elsewhere only act every [`ZoneScheduler::distant_rate`]-th
+//! tick, staggered by character number so the load spreads evenly.
+//!
+//! The radius keeps at least one full zone between any player and a
+//! throttled NPC, which is further than a client can see, so players never
+//! watch an NPC slow down. Players, usurped characters and `NoSleep`
+//! characters are never throttled.
+//!
+//! The divisor is read from `MAG_DISTANT_ZONE_RATE`; `1` disables
+//! throttling.
+
+use core::constants::{CharacterFlags, SERVER_MAPX, SERVER_MAPY, ST_NORMAL, USE_ACTIVE};
+
+use crate::game_state::GameState;
+
+/// Edge length of a zone in tiles.
+pub(crate) const ZONE_SIZE: usize = 32;
+
+/// Zones around a player's zone that are simulated in full.
+pub(crate) const ACTIVE_RADIUS: usize = 1;
+
+/// Default tick divisor for NPCs in zones without players.
+pub(crate) const DEFAULT_DISTANT_RATE: u32 = 4;
+
+/// Environment variable overriding [`DEFAULT_DISTANT_RATE`].
+pub(crate) const DISTANT_RATE_ENV: &str = "MAG_DISTANT_ZONE_RATE";
+
+/// Zones per map row.
+const ZONE_COLS: usize = (SERVER_MAPX as usize).div_ceil(ZONE_SIZE);
+
+/// Zones per map column.
+const ZONE_ROWS: usize = (SERVER_MAPY as usize).div_ceil(ZONE_SIZE);
+
+/// Decides per tick which characters get their full simulation.
+pub(crate) struct ZoneScheduler {
+    /// One flag per zone, row-major: `true` if a player is nearby.
+    hot: Vec<bool>,
+    /// Number of hot zones after the last rebuild.
+    hot_count: usize,
+    /// Tick divisor for NPCs in cold zones (`1` = no throttling).
+    distant_rate: u32,
+}
+
+impl ZoneScheduler {
+    /// Creates a scheduler with every zone cold.
+    ///
+    /// # Arguments
+    ///
+    /// * `distant_rate` - Tick divisor for cold zones; `0` is treated as `1`.
+    ///
+    /// # Returns
+    ///
+    /// * A new scheduler.
+    pub(crate) fn new(distant_rate: u32) -> Self {
+        Self {
+            hot: vec![false; ZONE_COLS * ZONE_ROWS],
+            hot_count: 0,
+            distant_rate: distant_rate.max(1),
+        }
+    }
+
+    /// Creates a scheduler using [`DISTANT_RATE_ENV`].
+    ///
+    /// # Returns
+    ///
+    /// * A scheduler with the configured rate, or the default when unset or
+    ///   invalid.
+    pub(crate) fn from_env() -> Self {
+        let rate = match std::env::var(DISTANT_RATE_ENV) {
+            Ok(value) if !value.is_empty() => match value.parse::<u32>() {
+                Ok(rate) if rate > 0 => {
+                    log::info!("Distant zone NPCs act every {} ticks.", rate);
+                    rate
+                }
+                _ => {
+                    log::warn!("Ignoring invalid {} value '{}'.", DISTANT_RATE_ENV, value);
+                    DEFAULT_DISTANT_RATE
+                }
+            },
+            _ => DEFAULT_DISTANT_RATE,
+        };
+        Self::new(rate)
+    }
+
+    /// Tick divisor applied to NPCs in cold zones.
+    ///
+    /// # Returns
+    ///
+    /// * The divisor (`1` when throttling is off).
+    #[cfg(test)]
+    pub(crate) fn distant_rate(&self) -> u32 {
+        self.distant_rate
+    }
+
+    /// Number of zones simulated in full since the last rebuild.
+    ///
+    /// # Returns
+    ///
+    /// * The hot zone count.
+    #[cfg(test)]
+    pub(crate) fn hot_zones(&self) -> usize {
+        self.hot_count
+    }
+
+    /// Zone index of tile `(x, y)`, clamped to the map.
+    fn zone_of(x: usize, y: usize) -> (usize, usize) {
+        (
+            (x / ZONE_SIZE).min(ZONE_COLS - 1),
+            (y / ZONE_SIZE).min(ZONE_ROWS - 1),
+        )
+    }
+
+    /// Marks the zones around tile `(x, y)` hot.
+    fn heat(&mut self, x: usize, y: usize) {
+        let (zx, zy) = Self::zone_of(x, y);
+        let cols = zx.saturating_sub(ACTIVE_RADIUS)..=(zx + ACTIVE_RADIUS).min(ZONE_COLS - 1);
+        for row in zy.saturating_sub(ACTIVE_RADIUS)..=(zy + ACTIVE_RADIUS).min(ZONE_ROWS - 1) {
+            for col in cols.clone() {
+                let idx = row * ZONE_COLS + col;
+                if !self.hot[idx] {
+                    self.hot[idx] = true;
+                    self.hot_count += 1;
+                }
+            }
+        }
+    }
+
+    /// Recomputes the hot zones from the players currently in the world.
+    ///
+    /// Logged-in players and linkdead characters both heat their
+    /// surroundings, so fights keep their pace while a player reconnects.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state to read player positions from.
+    pub(crate) fn rebuild(&mut self, gs: &GameState) {
+        self.hot.fill(false);
+        self.hot_count = 0;
+        if self.distant_rate == 1 {
+            return;
+        }
+
+        let online = (1..gs.players.len())
+            .filter(|&nr| gs.players[nr].sock.is_some() && gs.players[nr].state == ST_NORMAL)
+            .map(|nr| gs.players[nr].usnr);
+        let linkdead = gs.linkdead.keys().copied();
+        let positions: Vec<(usize, usize)> = online
+            .chain(linkdead)
+            .filter(|&cn| cn != 0 && cn < gs.characters.len())
+            .filter(|&cn| gs.characters[cn].used == USE_ACTIVE)
+            .map(|cn| (gs.characters[cn].x as usize, gs.characters[cn].y as usize))
+            .collect();
+        for (x, y) in positions {
+            self.heat(x, y);
+        }
+    }
+
+    /// Returns whether character `cn` should run its logic this tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `gs` - Game state holding the character.
+    /// * `cn` - Character about to act.
+    /// * `ticker` - Current `globals.ticker`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` for players, `NoSleep` characters and anyone in a hot zone;
+    ///   otherwise `true` on one tick in every `distant_rate`.
+    pub(crate) fn should_act(&self, gs: &GameState, cn: usize, ticker: i32) -> bool {
+        if self.distant_rate == 1 {
+            return true;
+        }
+        let ch = &gs.characters[cn];
+        let exempt = CharacterFlags::Player.bits()
+            | CharacterFlags::Usurp.bits()
+            | CharacterFlags::NoSleep.bits();
+        if ch.flags & exempt != 0 {
+            return true;
+        }
+        let (zx, zy) = Self::zone_of(ch.x as usize, ch.y as usize);
+        if self.hot[zy * ZONE_COLS + zx] {
+            return true;
+        }
+        (cn as u32)
+            .wrapping_add(ticker as u32)
+            .is_multiple_of(self.distant_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{add_test_player, with_test_gs};
+
+    /// Places an active NPC at `(x, y)`.
+    fn place_npc(gs: &mut GameState, cn: usize, x: i16, y: i16) {
+        gs.characters[cn].used = USE_ACTIVE;
+        gs.characters[cn].flags = 0;
+        gs.characters[cn].x = x;
+        gs.characters[cn].y = y;
+    }
+
+    #[test]
+    fn npcs_near_linkdead_players_act_every_tick_and_distant_ones_are_staggered() {
+        with_test_gs(|gs| {
+            let (cn, _) = add_test_player(gs);
+            gs.linkdead.insert(cn, 0);
+            place_npc(gs, 2, 40, 40);
+            place_npc(gs, 3, 500, 500);
+
+            let mut zones = ZoneScheduler::new(4);
+            zones.rebuild(gs);
+            assert_eq!(zones.hot_zones(), 4);
+
+            let acted = |cn| (0..8).filter(|&t| zones.should_act(gs, cn, t)).count();
+            assert_eq!(acted(cn), 8);
+            assert_eq!(acted(2), 8);
+            assert_eq!(acted(3), 2);
+        });
+    }
+
+    #[test]
+    fn rate_of_one_disables_throttling() {
+        with_test_gs(|gs| {
+            place_npc(gs, 3, 500, 500);
+            let mut zones = ZoneScheduler::new(1);
+            zones.rebuild(gs);
+            assert!((0..4).all(|t| zones.should_act(gs, 3, t)));
+            assert_eq!(ZoneScheduler::new(0).distant_rate(), 1);
+        });
+    }
+}
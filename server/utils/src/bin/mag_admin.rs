@@ -10,6 +10,9 @@ use mag_core::{ranks, skills, traits};
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
+use server::keydb::connection as keydb_connection;
+use server::keydb::snapshot::WorldSnapshot;
+use server::storage::StorageBackend;
 use server_utils::admin_client::{
     AdminClient, BadwordEntryResponse, BadwordsListResponse, BadwordsMutationResponse,
     BanActionStatusResponse, BanCreateRequest, BanCreateTargetRequest, BanListResponse,
@@ -17,9 +20,12 @@ use server_utils::admin_client::{
     TemplateSummary, TextReloadResponse, TextReloadStatusResponse, WorldActionKind,
     WorldActionResponse, WorldActionStatusResponse,
 };
+use server_utils::loadtest_report::{self, LoadTestReport};
+use server_utils::world_diff::{self, DiffEntry};
+use server_utils::world_integrity::{self, IntegrityReport};
 use std::fs;
 use std::io::{self, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::time::{Duration, Instant};
 
@@ -119,6 +125,45 @@ enum Commands {
         #[command(subcommand)]
         command: WorldActionCommand,
     },
+    /// Compare two world snapshot files entity by entity (offline).
+    DiffWorld {
+        /// Baseline `.wsnap` snapshot.
+        old: PathBuf,
+        /// Snapshot to compare against the baseline.
+        new: PathBuf,
+        #[arg(
+            long = "kind",
+            help = "Only report these entity kinds (character, item, map_tile, character_template, item_template, effect, globals, text); repeatable"
+        )]
+        kinds: Vec<String>,
+    },
+    /// Check the persisted world for integrity problems (offline; run nightly from cron).
+    IntegrityReport {
+        #[arg(
+            long,
+            help = "Check this `.wsnap` snapshot instead of the configured storage backend"
+        )]
+        snapshot: Option<PathBuf>,
+        #[arg(
+            long = "discord-webhook",
+            env = "MAG_INTEGRITY_WEBHOOK_URL",
+            help = "Also post a summary to this Discord webhook"
+        )]
+        discord_webhook: Option<String>,
+        #[arg(long, help = "Do not write the report to KeyDB")]
+        no_store: bool,
+    },
+    /// Compare `mag-loadtest --summary` runs and check them against an SLO (offline).
+    LoadtestReport {
+        /// Run summaries; the first run of each client count is its baseline.
+        #[arg(required = true)]
+        runs: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "JSON SLO file (max_p50_ms, max_p95_ms, max_p99_ms, min_ticks_per_sec, max_connect_error_pct, max_bytes_in_per_client_sec); exits 1 when a run violates it"
+        )]
+        slo: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -454,6 +499,28 @@ fn run(cli: Cli) -> Result<(), CliError> {
         ));
     }
 
+    // Snapshot diffs and integrity reports read world data directly and need
+    // no API credentials.
+    if let Some(Commands::DiffWorld { old, new, kinds }) = &cli.command {
+        return run_diff_world(&cli, old, new, kinds);
+    }
+    if let Some(Commands::IntegrityReport {
+        snapshot,
+        discord_webhook,
+        no_store,
+    }) = &cli.command
+    {
+        return run_integrity_report(
+            &cli,
+            snapshot.as_deref(),
+            discord_webhook.as_deref(),
+            *no_store,
+        );
+    }
+    if let Some(Commands::LoadtestReport { runs, slo }) = &cli.command {
+        return run_loadtest_report(&cli, runs, slo.as_deref());
+    }
+
     let admin_token = cli
         .admin_token
         .as_deref()
@@ -483,6 +550,11 @@ fn run(cli: Cli) -> Result<(), CliError> {
         Commands::Templates { command } => run_templates(&cli, &client, command),
         Commands::Globals { command } => run_globals(&cli, &client, command),
         Commands::World { command } => run_world_action(&cli, &client, command),
+        Commands::DiffWorld { .. }
+        | Commands::IntegrityReport { .. }
+        | Commands::LoadtestReport { .. } => {
+            unreachable!("handled before connecting")
+        }
     }
 }
 
@@ -1452,6 +1524,93 @@ fn run_globals(cli: &Cli, client: &AdminClient, command: &GlobalsCommand) -> Res
     }
 }
 
+fn run_diff_world(cli: &Cli, old: &Path, new: &Path, kinds: &[String]) -> Result<(), CliError> {
+    let old_snapshot = WorldSnapshot::from_file(old).map_err(CliError::Runtime)?;
+    let new_snapshot = WorldSnapshot::from_file(new).map_err(CliError::Runtime)?;
+    let entries: Vec<DiffEntry> = world_diff::diff_world(&old_snapshot, &new_snapshot)
+        .into_iter()
+        .filter(|entry| kinds.is_empty() || kinds.iter().any(|k| k == entry.kind.as_str()))
+        .collect();
+    if !cli.quiet {
+        eprintln!(
+            "{} -> {}: {} differences",
+            old.display(),
+            new.display(),
+            entries.len()
+        );
+    }
+    print_world_diff(&entries, cli.format)
+}
+
+fn run_integrity_report(
+    cli: &Cli,
+    snapshot: Option<&Path>,
+    discord_webhook: Option<&str>,
+    no_store: bool,
+) -> Result<(), CliError> {
+    let world = match snapshot {
+        Some(path) => WorldSnapshot::from_file(path).map_err(CliError::Runtime)?,
+        None => {
+            let data = StorageBackend::from_env()
+                .and_then(|backend| backend.load_all())
+                .map_err(CliError::Runtime)?;
+            WorldSnapshot::new(
+                data.map,
+                data.items,
+                data.item_templates,
+                data.characters,
+                data.character_templates,
+                data.effects,
+                data.globals,
+                data.bad_names,
+                data.bad_words,
+                data.message_of_the_day,
+            )
+        }
+    };
+    let report = world_integrity::check_world(&world);
+    if !cli.quiet {
+        eprintln!("{} integrity issues found", report.issue_count());
+    }
+
+    if !no_store {
+        let mut con = keydb_connection::connect().map_err(CliError::Runtime)?;
+        world_integrity::store_report(&mut con, &report).map_err(CliError::Runtime)?;
+        if !cli.quiet {
+            eprintln!("stored in {}", world_integrity::INTEGRITY_REPORT_KEY);
+        }
+    }
+    if let Some(url) = discord_webhook.map(str::trim).filter(|url| !url.is_empty()) {
+        world_integrity::post_to_webhook(url, &report).map_err(CliError::Runtime)?;
+    }
+    print_integrity_report(&report, cli.format)
+}
+
+fn run_loadtest_report(cli: &Cli, paths: &[PathBuf], slo: Option<&Path>) -> Result<(), CliError> {
+    let runs = paths
+        .iter()
+        .map(|path| loadtest_report::load_run(path))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(CliError::Runtime)?;
+    let slo = match slo {
+        Some(path) => loadtest_report::load_slo(path).map_err(CliError::Runtime)?,
+        None => loadtest_report::Slo::default(),
+    };
+    let report = loadtest_report::build_report(&runs, &slo);
+    if !cli.quiet {
+        eprintln!("{} runs compared", runs.len());
+    }
+    print_loadtest_report(&report, cli.format)?;
+    if report.violations.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::Runtime(format!(
+            "{} SLO violation(s)",
+            report.violations.len()
+        )))
+    }
+}
+
 fn run_world_action(
     cli: &Cli,
     client: &AdminClient,
@@ -1966,6 +2125,107 @@ fn print_world_action_status(
     Ok(())
 }
 
+fn print_world_diff(entries: &[DiffEntry], format: OutputFormat) -> Result<(), CliError> {
+    match format {
+        OutputFormat::Json => println!("{}", json_string(&entries)?),
+        OutputFormat::Plain => {
+            for entry in entries {
+                println!(
+                    "{}\t{}\t{:?}\t{}\t{}",
+                    entry.kind.as_str(),
+                    entry.index,
+                    entry.change,
+                    entry.label,
+                    entry.details.join("; ")
+                );
+            }
+        }
+        OutputFormat::Table => {
+            println!("KIND  INDEX  CHANGE  LABEL  DETAILS");
+            for entry in entries {
+                println!(
+                    "{}  {}  {:?}  {}  {}",
+                    entry.kind.as_str(),
+                    entry.index,
+                    entry.change,
+                    entry.label,
+                    entry.details.join("; ")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_integrity_report(report: &IntegrityReport, format: OutputFormat) -> Result<(), CliError> {
+    match format {
+        OutputFormat::Json => println!("{}", json_string(report)?),
+        OutputFormat::Plain => {
+            for item in &report.orphaned_items {
+                println!(
+                    "orphaned_item\t{}\t{}\t{}",
+                    item.item, item.name, item.problem
+                );
+            }
+            for item in &report.duplicate_items {
+                println!(
+                    "duplicate_item\t{}\t{}\t{}",
+                    item.item,
+                    item.name,
+                    item.holders.join("; ")
+                );
+            }
+            for ch in &report.impossible_stats {
+                println!(
+                    "impossible_stats\t{}\t{}\t{}",
+                    ch.character,
+                    ch.name,
+                    ch.problems.join("; ")
+                );
+            }
+            for tile in &report.missing_templates {
+                println!(
+                    "missing_template\t{},{}\t\t{}",
+                    tile.x, tile.y, tile.problem
+                );
+            }
+        }
+        OutputFormat::Table => print!("{}", report.summary()),
+    }
+    Ok(())
+}
+
+fn print_loadtest_report(report: &LoadTestReport, format: OutputFormat) -> Result<(), CliError> {
+    match format {
+        OutputFormat::Json => println!("{}", json_string(report)?),
+        OutputFormat::Plain => {
+            for group in &report.groups {
+                for run in &group.runs {
+                    println!(
+                        "{}\t{}\t{}\t{}\t{}\t{:.0}\t{:.0}\t{:.2}",
+                        run.clients,
+                        run.label,
+                        run.p50_ms.map_or(String::new(), |v| v.to_string()),
+                        run.p95_ms.map_or(String::new(), |v| v.to_string()),
+                        run.p99_ms.map_or(String::new(), |v| v.to_string()),
+                        run.bytes_in_per_sec,
+                        run.bytes_out_per_sec,
+                        run.ticks_per_sec
+                    );
+                }
+            }
+            for violation in &report.violations {
+                println!(
+                    "slo_violation\t{}\t{}\t{}",
+                    violation.clients, violation.label, violation.problem
+                );
+            }
+        }
+        OutputFormat::Table => print!("{}", report.summary()),
+    }
+    Ok(())
+}
+
 fn print_ban_list(response: &BanListResponse, format: OutputFormat) -> Result<(), CliError> {
     match format {
         OutputFormat::Json => println!("{}", json_string(response)?),
@@ -2630,6 +2890,9 @@ fn item_flag_info() -> &'static [(ItemFlags, &'static str)] {
         (ItemFlags::IF_IDENTIFIED, "Identified"),
         (ItemFlags::IF_NOEXPIRE, "NoExpire"),
         (ItemFlags::IF_SOULSTONE, "Soulstone"),
+        (ItemFlags::IF_QUEST, "Quest"),
+        (ItemFlags::IF_BIND_PICKUP, "BindPickup"),
+        (ItemFlags::IF_BIND_EQUIP, "BindEquip"),
     ]
 }
 
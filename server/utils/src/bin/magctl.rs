@@ -0,0 +1,146 @@
+//! Client for the game server's operator console (`MAG_CONSOLE_TOKEN`).
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+const DEFAULT_CONSOLE_ADDR: &str = "127.0.0.1:5557";
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "magctl",
+    version,
+    about = "Send GM commands to a running Men Among Gods server over its local console"
+)]
+struct Cli {
+    #[arg(
+        long,
+        env = "MAG_CONSOLE_ADDR",
+        default_value = DEFAULT_CONSOLE_ADDR,
+        help = "Console address of the server"
+    )]
+    addr: String,
+
+    #[arg(
+        long,
+        env = "MAG_CONSOLE_TOKEN",
+        hide_env_values = true,
+        help = "Console token configured on the server"
+    )]
+    token: String,
+
+    #[arg(
+        trailing_var_arg = true,
+        help = "Command to run, e.g. `announce Reboot in 5 minutes`; reads commands from stdin when omitted"
+    )]
+    command: Vec<String>,
+}
+
+/// Connected and authenticated console session.
+struct Session {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Session {
+    fn connect(addr: &str, token: &str) -> Result<Self, String> {
+        let writer = TcpStream::connect(addr)
+            .map_err(|e| format!("Failed to connect to {}: {}", addr, e))?;
+        let reader = BufReader::new(
+            writer
+                .try_clone()
+                .map_err(|e| format!("Failed to clone console socket: {}", e))?,
+        );
+        let mut session = Self { reader, writer };
+        let reply = session.send(&format!("AUTH {}", token))?;
+        if !reply.starts_with("OK") {
+            return Err(format!("Console rejected the token: {}", reply));
+        }
+        Ok(session)
+    }
+
+    /// Sends one line and returns the server's reply line.
+    fn send(&mut self, line: &str) -> Result<String, String> {
+        writeln!(self.writer, "{}", line).map_err(|e| format!("Console write failed: {}", e))?;
+        let mut reply = String::new();
+        let read = self
+            .reader
+            .read_line(&mut reply)
+            .map_err(|e| format!("Console read failed: {}", e))?;
+        if read == 0 {
+            return Err("Console closed the connection".to_owned());
+        }
+        Ok(reply.trim_end().to_owned())
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    let mut session = match Session::connect(&cli.addr, &cli.token) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(1);
+        }
+    };
+
+    if !cli.command.is_empty() {
+        return match session.send(&cli.command.join(" ")) {
+            Ok(reply) => {
+                println!("{}", reply);
+                if reply.starts_with("OK") {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(1)
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::from(1)
+            }
+        };
+    }
+
+    let stdin = io::stdin();
+    let mut failed = false;
+    loop {
+        eprint!("magctl> ");
+        let _ = io::stderr().flush();
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to read stdin: {}", e);
+                return ExitCode::from(1);
+            }
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.eq_ignore_ascii_case("quit") || line.eq_ignore_ascii_case("exit") {
+            break;
+        }
+        match session.send(line) {
+            Ok(reply) => {
+                failed |= !reply.starts_with("OK");
+                println!("{}", reply);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::from(1);
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -1,9 +1,10 @@
 use super::graphics::GraphicsZipCache;
+use super::undo::{EditHistory, EditTarget, Touched};
 use eframe::egui;
 use egui::{Pos2, Rect, Vec2};
 use mag_core::constants::{ItemFlags, SERVER_MAPX, SERVER_MAPY, TILEX, USE_EMPTY, XPOS, YPOS};
 use mag_core::map_store::MapPatch;
-use mag_core::types::{Item, Map};
+use mag_core::types::{Character, Item, Map};
 use server::keydb::snapshot::WorldSnapshot;
 use server_utils::admin_client::AdminClient;
 use server_utils::{DataSource, load_world_snapshot, save_world_snapshot};
@@ -15,6 +16,7 @@ use std::path::PathBuf;
 enum PaletteEntryKind {
     Sprite(u16),
     Item(u32),
+    CharacterTemplate(u32),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -35,6 +37,7 @@ pub(crate) struct MapViewerApp {
     items_error: Option<String>,
     item_templates: Vec<Item>,
     item_templates_error: Option<String>,
+    character_templates: Vec<Character>,
 
     graphics_zip: Option<GraphicsZipCache>,
     graphics_zip_error: Option<String>,
@@ -54,6 +57,10 @@ pub(crate) struct MapViewerApp {
     // Frozen selection (click on map when no palette entry is selected).
     selected_tile: Option<(usize, usize)>,
 
+    // Edit mode: enables the palette, flag toggles and undo/redo.
+    edit_mode: bool,
+    history: EditHistory,
+
     // Hide mode: clips non-background sprites to show only top half
     hide_enabled: bool,
 
@@ -68,6 +75,7 @@ pub(crate) struct MapViewerApp {
     selected_palette_index: Option<usize>,
     draft_sprite: u16,
     draft_item_instance_id: u32,
+    draft_character_template: u32,
     palette_rect: Option<Rect>,
     line_anchor: Option<(usize, usize)>,
 
@@ -76,6 +84,8 @@ pub(crate) struct MapViewerApp {
 
     /// Tiles with unsaved edits (LiveApi mode). Keyed by `(x, y)`.
     dirty_tiles: BTreeSet<(usize, usize)>,
+    /// Character templates whose spawn point was moved (LiveApi mode).
+    dirty_character_templates: BTreeSet<usize>,
     /// Cached admin API client for LiveApi mode.
     admin_client: Option<AdminClient>,
     /// Pending map-reload request id awaiting a status update.
@@ -121,18 +131,22 @@ impl MapViewerApp {
         self.map_tiles.clear();
         self.items.clear();
         self.item_templates.clear();
+        self.character_templates.clear();
         self.hovered_tile = None;
         self.selected_tile = None;
         self.selected_palette_index = None;
         self.line_anchor = None;
         self.dirty = false;
         self.dirty_tiles.clear();
+        self.dirty_character_templates.clear();
+        self.history.clear();
     }
 
     fn apply_loaded_world(&mut self, world: WorldSnapshot, status: String) {
         self.map_tiles = world.map.clone();
         self.items = world.items.clone();
         self.item_templates = world.item_templates.clone();
+        self.character_templates = world.character_templates.clone();
         self.loaded_world = Some(world);
         self.save_status = Some(status);
         self.pan_initialized = false;
@@ -142,6 +156,8 @@ impl MapViewerApp {
         self.line_anchor = None;
         self.dirty = false;
         self.dirty_tiles.clear();
+        self.dirty_character_templates.clear();
+        self.history.clear();
     }
 
     fn load_current_source(&mut self) {
@@ -186,6 +202,7 @@ impl MapViewerApp {
         world.map = self.map_tiles.clone();
         world.items = self.items.clone();
         world.item_templates = self.item_templates.clone();
+        world.character_templates = self.character_templates.clone();
         Ok(())
     }
 
@@ -318,6 +335,8 @@ impl MapViewerApp {
         self.load_current_source();
         self.dirty = false;
         self.dirty_tiles.clear();
+        self.dirty_character_templates.clear();
+        self.history.clear();
         self.save_status = Some("Reverted (discarded unsaved changes)".to_owned());
     }
 
@@ -347,6 +366,10 @@ impl MapViewerApp {
 
     /// Apply a palette entry to one map tile and mark it dirty when changed.
     fn apply_palette_to_tile(&mut self, x: usize, y: usize, entry: PaletteEntry) -> bool {
+        if let PaletteEntryKind::CharacterTemplate(template) = entry.kind {
+            return self.place_character_template(x, y, template as usize);
+        }
+
         let idx = tile_index(x, y);
         let Some(current) = self.map_tiles.get(idx).copied() else {
             return false;
@@ -365,17 +388,133 @@ impl MapViewerApp {
                     tile.fsprite = 0;
                 }
             }
+            PaletteEntryKind::CharacterTemplate(_) => {}
         }
 
+        self.set_tile(x, y, tile)
+    }
+
+    /// Replace tile `(x, y)`, recording the old state for undo.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Tile X coordinate.
+    /// * `y` - Tile Y coordinate.
+    /// * `tile` - New tile state.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the tile changed.
+    fn set_tile(&mut self, x: usize, y: usize, tile: Map) -> bool {
+        let idx = tile_index(x, y);
+        let Some(current) = self.map_tiles.get(idx).copied() else {
+            return false;
+        };
         if tile == current {
             return false;
         }
 
+        self.history.record_tile(idx, current);
         self.map_tiles[idx] = tile;
         self.mark_tile_dirty(x, y);
         true
     }
 
+    /// Move a character template's spawn point to tile `(x, y)`.
+    ///
+    /// NPCs are (re)spawned at their template's position, so this is how a
+    /// character is placed on the map. The resting position (`data[29]`) is
+    /// moved along with it. In LiveApi mode the loader only keeps template
+    /// summaries, so the full template is fetched before it is first edited.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Tile X coordinate.
+    /// * `y` - Tile Y coordinate.
+    /// * `template` - Character template index.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the template changed.
+    fn place_character_template(&mut self, x: usize, y: usize, template: usize) -> bool {
+        if template == 0
+            || self
+                .character_templates
+                .get(template)
+                .is_none_or(|t| t.used == USE_EMPTY)
+        {
+            self.save_status = Some(format!(
+                "Place failed: character template {template} is not in use"
+            ));
+            return false;
+        }
+
+        if self.data_source.is_live_api() && !self.dirty_character_templates.contains(&template) {
+            let Some(client) = self.admin_client.as_ref() else {
+                self.save_status = Some("Admin client not initialized".to_owned());
+                return false;
+            };
+            match client.fetch_single_character_template(template) {
+                Ok(full) => self.character_templates[template] = full,
+                Err(e) => {
+                    self.save_status = Some(format!("Place failed: {e}"));
+                    return false;
+                }
+            }
+        }
+
+        let current = self.character_templates[template];
+        let mut updated = current;
+        updated.x = x as i16;
+        updated.y = y as i16;
+        updated.data[29] = x as i32 + y as i32 * SERVER_MAPX;
+        if updated == current {
+            return false;
+        }
+
+        self.history.record_character_template(template, current);
+        self.character_templates[template] = updated;
+        self.dirty = true;
+        self.dirty_character_templates.insert(template);
+        true
+    }
+
+    /// Mark every slot restored by an undo or redo as unsaved.
+    fn mark_touched_dirty(&mut self, touched: Touched) {
+        let width = SERVER_MAPX as usize;
+        for idx in touched.tiles {
+            self.mark_tile_dirty(idx % width, idx / width);
+        }
+        for template in touched.character_templates {
+            self.dirty = true;
+            self.dirty_character_templates.insert(template);
+        }
+    }
+
+    /// Revert the most recent edit.
+    fn undo_edit(&mut self) {
+        let target = EditTarget {
+            tiles: &mut self.map_tiles,
+            character_templates: &mut self.character_templates,
+        };
+        if let Some(touched) = self.history.undo(target) {
+            self.mark_touched_dirty(touched);
+            self.save_status = Some("Undid edit".to_owned());
+        }
+    }
+
+    /// Re-apply the most recently undone edit.
+    fn redo_edit(&mut self) {
+        let target = EditTarget {
+            tiles: &mut self.map_tiles,
+            character_templates: &mut self.character_templates,
+        };
+        if let Some(touched) = self.history.redo(target) {
+            self.mark_touched_dirty(touched);
+            self.save_status = Some("Redid edit".to_owned());
+        }
+    }
+
     /// Push every dirty map tile to the admin API and clear the dirty set.
     ///
     /// Called instead of snapshot save in LiveApi mode. Each tile produces
@@ -418,14 +557,32 @@ impl MapViewerApp {
             }
         }
 
+        let template_targets: Vec<usize> = self.dirty_character_templates.iter().copied().collect();
+        let mut pushed_templates = 0usize;
+        for n in template_targets {
+            match client.put_character_template(n, &self.character_templates[n]) {
+                Ok(()) => {
+                    pushed_templates += 1;
+                    self.dirty_character_templates.remove(&n);
+                }
+                Err(e) => errors.push(format!("template {n}: {e}")),
+            }
+        }
+        if pushed_templates > 0
+            && let Err(e) = client.request_reload(false, true)
+        {
+            errors.push(format!("template reload: {e}"));
+        }
+
+        self.dirty = !self.dirty_tiles.is_empty() || !self.dirty_character_templates.is_empty();
         if errors.is_empty() {
-            self.dirty = !self.dirty_tiles.is_empty();
             self.save_status = Some(format!(
-                "Saved to API: {pushed} tile(s). Use 'Reload server map' to apply."
+                "Saved to API: {pushed} tile(s), {pushed_templates} template(s). \
+                 Use 'Reload server map' to apply."
             ));
         } else {
             self.save_status = Some(format!(
-                "Save partial: {pushed} tile(s); {} error(s): {}",
+                "Save partial: {pushed} tile(s), {pushed_templates} template(s); {} error(s): {}",
                 errors.len(),
                 errors.join("; ")
             ));
@@ -777,6 +934,32 @@ impl MapViewerApp {
                                 }
                             });
 
+                            ui.horizontal(|ui| {
+                                ui.label("ch:");
+                                ui.add(egui::DragValue::new(&mut self.draft_character_template));
+
+                                let name = self
+                                    .character_templates
+                                    .get(self.draft_character_template as usize)
+                                    .filter(|t| t.used != USE_EMPTY)
+                                    .map(|t| t.get_name().to_owned())
+                                    .unwrap_or_else(|| "(unused)".to_owned());
+                                ui.label(name);
+
+                                if ui
+                                    .small_button("Add")
+                                    .on_hover_text("Place this template's spawn point")
+                                    .clicked()
+                                    && self.draft_character_template != 0
+                                {
+                                    self.palette.push(PaletteEntry {
+                                        kind: PaletteEntryKind::CharacterTemplate(
+                                            self.draft_character_template,
+                                        ),
+                                    });
+                                }
+                            });
+
                             ui.separator();
 
                             egui::ScrollArea::vertical()
@@ -789,6 +972,37 @@ impl MapViewerApp {
                                         .show(ui, |ui| {
                                             let mut col = 0;
                                             for (idx, entry) in self.palette.iter().enumerate() {
+                                                let selected =
+                                                    self.selected_palette_index == Some(idx);
+
+                                                // Character templates are listed by name; their
+                                                // sprite depends on the animation state.
+                                                if let PaletteEntryKind::CharacterTemplate(n) =
+                                                    entry.kind
+                                                {
+                                                    let name = self
+                                                        .character_templates
+                                                        .get(n as usize)
+                                                        .map(|t| t.get_name().to_owned())
+                                                        .unwrap_or_default();
+                                                    if ui
+                                                        .selectable_label(
+                                                            selected,
+                                                            format!("#{n} {name}"),
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        self.selected_palette_index =
+                                                            if selected { None } else { Some(idx) };
+                                                    }
+                                                    col += 1;
+                                                    if col == 4 {
+                                                        ui.end_row();
+                                                        col = 0;
+                                                    }
+                                                    continue;
+                                                }
+
                                                 let sprite_id: Option<usize> = match entry.kind {
                                                     PaletteEntryKind::Sprite(sprite) => {
                                                         if sprite == 0 {
@@ -815,6 +1029,7 @@ impl MapViewerApp {
                                                             }
                                                         }
                                                     }
+                                                    PaletteEntryKind::CharacterTemplate(_) => None,
                                                 };
 
                                                 let Some(sprite_id) = sprite_id else {
@@ -831,8 +1046,6 @@ impl MapViewerApp {
                                                     continue;
                                                 };
 
-                                                let selected =
-                                                    self.selected_palette_index == Some(idx);
                                                 let tint = if selected {
                                                     egui::Color32::from_rgb(180, 255, 180)
                                                 } else {
@@ -1035,6 +1248,20 @@ impl eframe::App for MapViewerApp {
             }
         }
 
+        // Undo (Cmd/Ctrl+Z) and redo (Cmd/Ctrl+Shift+Z or Cmd/Ctrl+Y) in edit mode.
+        if self.edit_mode {
+            let (undo, redo) = ctx.input(|i| {
+                let z = i.modifiers.command && i.key_pressed(egui::Key::Z);
+                let y = i.modifiers.command && i.key_pressed(egui::Key::Y);
+                (z && !i.modifiers.shift, y || (z && i.modifiers.shift))
+            });
+            if undo {
+                self.undo_edit();
+            } else if redo {
+                self.redo_edit();
+            }
+        }
+
         // Auto-poll map-reload status every ~2 s while a request is pending.
         if self.pending_map_reload_request_id.is_some() {
             const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
@@ -1207,8 +1434,46 @@ impl eframe::App for MapViewerApp {
                     });
                 });
 
+                ui.menu_button("Edit", |ui| {
+                    if ui
+                        .add_enabled(
+                            self.edit_mode && self.history.can_undo(),
+                            egui::Button::new("Undo\tCtrl+Z"),
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.undo_edit();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.edit_mode && self.history.can_redo(),
+                            egui::Button::new("Redo\tCtrl+Y"),
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.redo_edit();
+                    }
+                });
+
                 ui.separator();
 
+                if ui
+                    .button(if self.edit_mode {
+                        "Edit: ON"
+                    } else {
+                        "Edit: OFF"
+                    })
+                    .on_hover_text("Enable the palette, tile flag toggles and undo/redo")
+                    .clicked()
+                {
+                    self.edit_mode = !self.edit_mode;
+                    self.selected_palette_index = None;
+                    self.line_anchor = None;
+                    ctx.request_repaint();
+                }
+
                 if ui.button("Reset view").clicked() {
                     self.pan = Vec2::ZERO;
                     self.pan_initialized = false;
@@ -1230,7 +1495,11 @@ impl eframe::App for MapViewerApp {
                     ui.separator();
                     ui.colored_label(
                         egui::Color32::YELLOW,
-                        format!("Unsaved: {} tile(s)", self.dirty_tiles.len()),
+                        format!(
+                            "Unsaved: {} tile(s), {} template(s)",
+                            self.dirty_tiles.len(),
+                            self.dirty_character_templates.len()
+                        ),
                     );
                 }
 
@@ -1320,7 +1589,9 @@ impl eframe::App for MapViewerApp {
                 ui.label("- WASD: pan");
                 ui.label("- Drag: pan");
                 ui.label("- Mouse wheel: zoom");
+                ui.label("- Edit mode: left click paints the palette entry");
                 ui.label("- Shift + left click: line mode");
+                ui.label("- Ctrl+Z / Ctrl+Y: undo / redo");
 
                 ui.separator();
                 ui.label(format!("Pan: [{:.1}, {:.1}]", self.pan.x, self.pan.y));
@@ -1419,16 +1690,35 @@ impl eframe::App for MapViewerApp {
                             let preview_size = Vec2::new(64.0, 64.0);
                             self.ui_tile_preview_row(ui, ctx, sprite, fsprite, it, preview_size);
 
-                            if sprite != 0 && fsprite != 0 && ui.button("Clear fsprite").clicked() {
+                            if self.edit_mode
+                                && sprite != 0
+                                && fsprite != 0
+                                && ui.button("Clear fsprite").clicked()
+                            {
                                 let mut updated = self.map_tiles[idx];
                                 updated.fsprite = 0;
-                                if updated != self.map_tiles[idx] {
-                                    self.map_tiles[idx] = updated;
-                                    self.mark_tile_dirty(x, y);
+                                if self.set_tile(x, y, updated) {
+                                    self.history.commit();
                                     ctx.request_repaint();
                                 }
                             }
 
+                            let spawns: Vec<String> = self
+                                .character_templates
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, t)| {
+                                    t.used != USE_EMPTY
+                                        && t.x as usize == x
+                                        && t.y as usize == y
+                                        && (t.x, t.y) != (0, 0)
+                                })
+                                .map(|(n, t)| format!("#{n} {}", t.get_name()))
+                                .collect();
+                            if !spawns.is_empty() {
+                                ui.label(format!("spawns: {}", spawns.join(", ")));
+                            }
+
                             if it != 0 {
                                 let it_idx = it as usize;
                                 if it_idx < self.items.len() {
@@ -1464,6 +1754,7 @@ impl eframe::App for MapViewerApp {
                                 (u64::from(mag_core::constants::MF_ARENA), "MF_ARENA"),
                                 (u64::from(mag_core::constants::MF_NOEXPIRE), "MF_NOEXPIRE"),
                                 (mag_core::constants::MF_NOFIGHT, "MF_NOFIGHT"),
+                                (mag_core::constants::MF_SHRINE, "MF_SHRINE"),
                                 (mag_core::constants::MF_GFX_INJURED, "MF_GFX_INJURED"),
                                 (mag_core::constants::MF_GFX_INJURED1, "MF_GFX_INJURED1"),
                                 (mag_core::constants::MF_GFX_INJURED2, "MF_GFX_INJURED2"),
@@ -1479,7 +1770,7 @@ impl eframe::App for MapViewerApp {
                                 (mag_core::constants::MF_GFX_CMAGIC1, "MF_GFX_CMAGIC1"),
                             ];
 
-                            ui.add_enabled_ui(true, |ui| {
+                            ui.add_enabled_ui(self.edit_mode, |ui| {
                                 egui::ScrollArea::vertical()
                                     .max_height(220.0)
                                     .show(ui, |ui| {
@@ -1510,9 +1801,8 @@ impl eframe::App for MapViewerApp {
                             if flags != original_flags {
                                 let mut updated = self.map_tiles[idx];
                                 updated.flags = flags;
-                                if updated != self.map_tiles[idx] {
-                                    self.map_tiles[idx] = updated;
-                                    self.mark_tile_dirty(x, y);
+                                if self.set_tile(x, y, updated) {
+                                    self.history.commit();
                                     ctx.request_repaint();
                                 }
                             }
@@ -1527,9 +1817,12 @@ impl eframe::App for MapViewerApp {
             let (rect, response) =
                 ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
 
-            // Overlay palette anchored in the map canvas.
-            let palette_rect =
-                self.render_palette_overlay(ctx, rect.left_top() + Vec2::new(12.0, 12.0));
+            // Overlay palette anchored in the map canvas (edit mode only).
+            let palette_rect = if self.edit_mode {
+                self.render_palette_overlay(ctx, rect.left_top() + Vec2::new(12.0, 12.0))
+            } else {
+                Rect::NOTHING
+            };
             self.palette_rect = Some(palette_rect);
 
             if response.dragged() {
@@ -1569,6 +1862,7 @@ impl eframe::App for MapViewerApp {
                         self.line_anchor = None;
                         self.apply_palette_to_tile(x, y, entry)
                     };
+                    self.history.commit();
 
                     if changed {
                         ctx.request_repaint();
@@ -1871,8 +2165,40 @@ fn paint_sprite_dd(
 
 #[cfg(test)]
 mod tests {
-    use super::{dd_tile_center_screen_pos, line_tiles, map_point_to_tile};
+    use super::{
+        MapViewerApp, PaletteEntry, PaletteEntryKind, dd_tile_center_screen_pos, line_tiles,
+        map_point_to_tile,
+    };
     use eframe::egui::Vec2;
+    use mag_core::constants::{SERVER_MAPX, USE_ACTIVE};
+    use mag_core::types::Character;
+
+    #[test]
+    fn placing_a_character_template_moves_its_spawn_and_undoes() {
+        let mut app = MapViewerApp::default();
+        app.character_templates = vec![Character::default(); 3];
+        app.character_templates[2].used = USE_ACTIVE;
+
+        let entry = PaletteEntry {
+            kind: PaletteEntryKind::CharacterTemplate(2),
+        };
+        assert!(app.apply_palette_to_tile(12, 7, entry));
+        app.history.commit();
+        let template = app.character_templates[2];
+        assert_eq!((template.x, template.y), (12, 7));
+        assert_eq!(template.data[29], 12 + 7 * SERVER_MAPX);
+
+        app.undo_edit();
+        assert_eq!(app.character_templates[2].x, 0);
+        assert!(app.dirty_character_templates.contains(&2));
+        assert!(!app.apply_palette_to_tile(
+            1,
+            1,
+            PaletteEntry {
+                kind: PaletteEntryKind::CharacterTemplate(1),
+            }
+        ));
+    }
 
     #[test]
     fn line_tiles_single_point() {
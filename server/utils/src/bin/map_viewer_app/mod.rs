@@ -1,4 +1,5 @@
 pub(crate) mod app;
+pub(crate) mod undo;
 
 // Reuse the existing graphics zip cache used by template_viewer.
 #[path = "../template_viewer_app/graphics.rs"]
@@ -0,0 +1,236 @@
+//! Undo/redo history for map edits.
+//!
+//! Each user action in edit mode (a click, a painted line, a flag toggle) is
+//! one step holding the state every touched tile or character template had
+//! before the action. Undoing swaps those states back in and keeps the
+//! replaced ones, so the same step can be redone.
+
+use mag_core::types::{Character, Map};
+
+/// Steps kept before the oldest one is dropped.
+pub(crate) const MAX_UNDO_STEPS: usize = 200;
+
+/// Previous state of one edited slot.
+#[derive(Clone, Copy, Debug)]
+enum Change {
+    Tile(usize, Map),
+    CharacterTemplate(usize, Character),
+}
+
+/// Slots touched by an undo or redo, so the caller can mark them dirty.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Touched {
+    /// Map tile indexes.
+    pub(crate) tiles: Vec<usize>,
+    /// Character template indexes.
+    pub(crate) character_templates: Vec<usize>,
+}
+
+/// Mutable views of the data an edit step may touch.
+pub(crate) struct EditTarget<'a> {
+    pub(crate) tiles: &'a mut [Map],
+    pub(crate) character_templates: &'a mut [Character],
+}
+
+#[derive(Default)]
+pub(crate) struct EditHistory {
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    pending: Vec<Change>,
+}
+
+impl EditHistory {
+    /// Record a tile's state before it is changed by the pending step.
+    ///
+    /// Only the first recording of a tile per step is kept, so painting over
+    /// the same tile twice still undoes to the original state.
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - Map tile index.
+    /// * `before` - Tile state before the change.
+    pub(crate) fn record_tile(&mut self, idx: usize, before: Map) {
+        if !self
+            .pending
+            .iter()
+            .any(|change| matches!(change, Change::Tile(i, _) if *i == idx))
+        {
+            self.pending.push(Change::Tile(idx, before));
+        }
+    }
+
+    /// Record a character template's state before it is changed by the
+    /// pending step.
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - Character template index.
+    /// * `before` - Template state before the change.
+    pub(crate) fn record_character_template(&mut self, idx: usize, before: Character) {
+        if !self
+            .pending
+            .iter()
+            .any(|change| matches!(change, Change::CharacterTemplate(i, _) if *i == idx))
+        {
+            self.pending.push(Change::CharacterTemplate(idx, before));
+        }
+    }
+
+    /// Close the pending step and make it undoable.
+    ///
+    /// Empty steps are dropped. A new step clears the redo list.
+    pub(crate) fn commit(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        self.undo.push(std::mem::take(&mut self.pending));
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Forget every recorded step, e.g. after loading another world.
+    pub(crate) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending.clear();
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Revert the most recent step.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Data the step applies to.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(touched)` with the restored slots, or `None` if there is
+    ///   nothing to undo.
+    pub(crate) fn undo(&mut self, target: EditTarget<'_>) -> Option<Touched> {
+        let step = self.undo.pop()?;
+        let (inverse, touched) = apply(step, target);
+        self.redo.push(inverse);
+        Some(touched)
+    }
+
+    /// Re-apply the most recently undone step.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Data the step applies to.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(touched)` with the changed slots, or `None` if there is
+    ///   nothing to redo.
+    pub(crate) fn redo(&mut self, target: EditTarget<'_>) -> Option<Touched> {
+        let step = self.redo.pop()?;
+        let (inverse, touched) = apply(step, target);
+        self.undo.push(inverse);
+        Some(touched)
+    }
+}
+
+/// Write a step's saved states into `target`, returning the states they
+/// replaced (the inverse step) and the touched slots.
+fn apply(step: Vec<Change>, target: EditTarget<'_>) -> (Vec<Change>, Touched) {
+    let mut inverse = Vec::with_capacity(step.len());
+    let mut touched = Touched::default();
+    for change in step.into_iter().rev() {
+        match change {
+            Change::Tile(idx, state) => {
+                let Some(slot) = target.tiles.get_mut(idx) else {
+                    continue;
+                };
+                inverse.push(Change::Tile(idx, std::mem::replace(slot, state)));
+                touched.tiles.push(idx);
+            }
+            Change::CharacterTemplate(idx, state) => {
+                let Some(slot) = target.character_templates.get_mut(idx) else {
+                    continue;
+                };
+                inverse.push(Change::CharacterTemplate(
+                    idx,
+                    std::mem::replace(slot, state),
+                ));
+                touched.character_templates.push(idx);
+            }
+        }
+    }
+    (inverse, touched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target<'a>(tiles: &'a mut [Map], templates: &'a mut [Character]) -> EditTarget<'a> {
+        EditTarget {
+            tiles,
+            character_templates: templates,
+        }
+    }
+
+    #[test]
+    fn undo_restores_the_first_recorded_state_and_redo_reapplies() {
+        let mut tiles = vec![Map::default(); 4];
+        let mut templates = vec![Character::default(); 2];
+        let mut history = EditHistory::default();
+
+        history.record_tile(1, tiles[1]);
+        tiles[1].fsprite = 10;
+        history.record_tile(1, tiles[1]);
+        tiles[1].fsprite = 20;
+        history.record_character_template(1, templates[1]);
+        templates[1].x = 5;
+        history.commit();
+
+        let touched = history
+            .undo(target(&mut tiles, &mut templates))
+            .expect("undo step");
+        assert_eq!(tiles[1].fsprite, 0);
+        assert_eq!(templates[1].x, 0);
+        assert_eq!(touched.tiles, vec![1]);
+        assert_eq!(touched.character_templates, vec![1]);
+        assert!(!history.can_undo());
+
+        history
+            .redo(target(&mut tiles, &mut templates))
+            .expect("redo step");
+        assert_eq!(tiles[1].fsprite, 20);
+        assert_eq!(templates[1].x, 5);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn new_edits_clear_redo_and_history_is_bounded() {
+        let mut tiles = vec![Map::default(); 1];
+        let mut templates = Vec::new();
+        let mut history = EditHistory::default();
+
+        history.commit();
+        assert!(!history.can_undo());
+
+        for n in 0..=MAX_UNDO_STEPS {
+            history.record_tile(0, tiles[0]);
+            tiles[0].flags = n as u64 + 1;
+            history.commit();
+        }
+        assert_eq!(history.undo.len(), MAX_UNDO_STEPS);
+
+        history.undo(target(&mut tiles, &mut templates));
+        assert!(history.can_redo());
+        history.record_tile(0, tiles[0]);
+        history.commit();
+        assert!(!history.can_redo());
+    }
+}
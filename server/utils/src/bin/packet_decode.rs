@@ -0,0 +1,125 @@
+//! Pretty-prints game protocol messages from a packet capture or raw dump.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use server_utils::packet_capture::{self, DecodedMessage, Direction};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "packet_decode",
+    version,
+    about = "Decode Men Among Gods game traffic from a pcap or raw stream dump"
+)]
+struct Cli {
+    #[arg(help = "Capture file (classic pcap, or raw bytes with --raw)")]
+    input: PathBuf,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Treat the input as a raw dump of one direction of one connection"
+    )]
+    raw: Option<RawDirection>,
+
+    #[arg(
+        long,
+        help = "Raw server dumps: the dump starts at a tick frame, not at login"
+    )]
+    mid_stream: bool,
+
+    #[arg(
+        long,
+        default_value_t = 5555,
+        help = "Game server TCP port (pcap input)"
+    )]
+    port: u16,
+
+    #[arg(
+        long = "command",
+        short = 'c',
+        help = "Only show these opcodes (name such as `inv`/`CmdInv`/`SetCharItem`, or number); repeatable"
+    )]
+    commands: Vec<String>,
+
+    #[arg(
+        long,
+        short = 'p',
+        help = "Only show one connection (connection number, client port, or ip:port)"
+    )]
+    player: Option<String>,
+
+    #[arg(long, value_enum, help = "Only show messages sent by one side")]
+    from: Option<RawDirection>,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum RawDirection {
+    Client,
+    Server,
+}
+
+impl From<RawDirection> for Direction {
+    fn from(value: RawDirection) -> Self {
+        match value {
+            RawDirection::Client => Direction::ClientToServer,
+            RawDirection::Server => Direction::ServerToClient,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    let bytes = match std::fs::read(&cli.input) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", cli.input.display(), e);
+            return ExitCode::from(1);
+        }
+    };
+
+    let (messages, warnings) = match cli.raw {
+        Some(direction) => {
+            let (messages, err) =
+                packet_capture::decode_stream(&bytes, direction.into(), !cli.mid_stream);
+            (messages, err.into_iter().collect())
+        }
+        None => match packet_capture::decode_pcap(&bytes, cli.port) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                eprintln!("{}: {}", cli.input.display(), e);
+                return ExitCode::from(1);
+            }
+        },
+    };
+
+    let selected = |m: &DecodedMessage| {
+        (cli.commands.is_empty() || cli.commands.iter().any(|c| m.matches_command(c)))
+            && cli.player.as_deref().is_none_or(|p| m.matches_player(p))
+            && cli.from.is_none_or(|d| m.direction == d.into())
+    };
+    for m in messages.iter().filter(|m| selected(m)) {
+        println!(
+            "#{} {} {} f{:<6} {:>3} {:<18} {}",
+            m.flow,
+            m.peer,
+            m.direction.arrow(),
+            m.frame,
+            m.opcode,
+            m.name,
+            m.detail
+        );
+    }
+
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if warnings.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
@@ -149,6 +149,9 @@ fn get_item_flag_info() -> Vec<(mag_core::constants::ItemFlags, &'static str)> {
         (ItemFlags::IF_IDENTIFIED, "Identified"),
         (ItemFlags::IF_NOEXPIRE, "No Expire"),
         (ItemFlags::IF_SOULSTONE, "Soulstone"),
+        (ItemFlags::IF_QUEST, "Quest"),
+        (ItemFlags::IF_BIND_PICKUP, "Bind on Pickup"),
+        (ItemFlags::IF_BIND_EQUIP, "Bind on Equip"),
     ]
 }
 
@@ -201,5 +204,7 @@ fn get_character_flag_info() -> Vec<mag_core::constants::CharacterFlags> {
         CharacterFlags::SaveMe,
         CharacterFlags::GreaterGod,
         CharacterFlags::GreaterInv,
+        CharacterFlags::Broker,
+        CharacterFlags::Observer,
     ]
 }
@@ -5,6 +5,7 @@ use mag_core::skills;
 use mag_core::string_operations::c_string_to_str;
 use mag_core::{ranks, traits};
 use server::keydb::snapshot::WorldSnapshot;
+use server_utils::item_search::{ItemQuery, compare_items};
 use server_utils::{AdminClient, DataSource, load_world_snapshot, save_world_snapshot};
 use std::collections::HashSet;
 use std::path::Path;
@@ -40,6 +41,13 @@ pub(crate) struct TemplateViewerApp {
     item_instance_filter: String,
     character_instance_filter: String,
     character_instances_player_only: bool,
+    /// Search text, flag filter and unused toggle of the Item Search mode.
+    item_query: ItemQuery,
+    /// Templates compared side by side in the Item Search mode.
+    compare_left: Option<usize>,
+    compare_right: Option<usize>,
+    /// Hide fields that are equal in both compared templates.
+    compare_only_differences: bool,
     show_unused_templates: bool,
     show_all_data_fields: bool,
     load_error: Option<String>,
@@ -94,6 +102,7 @@ enum ViewMode {
     CharacterTemplates,
     Items,
     Characters,
+    ItemSearch,
 }
 
 impl Default for TemplateViewerApp {
@@ -116,6 +125,10 @@ impl Default for TemplateViewerApp {
             item_instance_filter: String::new(),
             character_instance_filter: String::new(),
             character_instances_player_only: false,
+            item_query: ItemQuery::default(),
+            compare_left: None,
+            compare_right: None,
+            compare_only_differences: true,
             show_unused_templates: false,
             show_all_data_fields: false,
             load_error: None,
@@ -172,6 +185,8 @@ impl TemplateViewerApp {
         self.selected_character_index = None;
         self.selected_item_instance_index = None;
         self.selected_character_instance_index = None;
+        self.compare_left = None;
+        self.compare_right = None;
         self.item_popup_id = None;
         self.dirty = false;
         self.dirty_item_template_slots.clear();
@@ -261,7 +276,7 @@ impl TemplateViewerApp {
 
     fn sync_loaded_world_from_views(&mut self) -> Result<(), String> {
         for tpl in &mut self.character_templates {
-            tpl.points_tot = server::points::calculate_points_tot(tpl);
+            tpl.points_tot = mag_core::balance::calculate_points_tot(tpl);
         }
 
         let Some(world) = self.loaded_world.as_mut() else {
@@ -773,6 +788,11 @@ impl TemplateViewerApp {
                         self.dirty_character_slots.insert(idx);
                     }
                 }
+                ViewMode::ItemSearch => {
+                    if let Some(idx) = self.compare_left {
+                        self.dirty_item_template_slots.insert(idx);
+                    }
+                }
             }
         }
     }
@@ -842,35 +862,39 @@ impl TemplateViewerApp {
             .position(|item| item.temp == temp_id)
     }
 
+    /// In LiveApi mode, item templates are populated with summary-only stubs
+    /// on connect. Fetches the full bincode payload of slot `idx` the first
+    /// time it is needed (lazy load, 1 request per unique slot).
+    fn ensure_item_template_loaded(&mut self, idx: usize) -> Result<(), String> {
+        if !self.data_source.is_live_api() || self.fully_loaded_item_slots.contains(&idx) {
+            return Ok(());
+        }
+        let Some(client) = self.admin_client.as_ref().cloned() else {
+            return Ok(());
+        };
+        match client.fetch_single_item_template(idx) {
+            Ok(item) => {
+                if idx < self.item_templates.len() {
+                    self.item_templates[idx] = item;
+                }
+                self.fully_loaded_item_slots.insert(idx);
+                Ok(())
+            }
+            Err(e) => Err(format!("Failed to load item template {idx}: {e}")),
+        }
+    }
+
     fn render_item_details_by_index(
         &mut self,
         ui: &mut egui::Ui,
         source: ItemDetailsSource,
         idx: usize,
     ) {
-        // In LiveApi mode, item templates are populated with summary-only stubs
-        // on connect. Fetch the full bincode payload the first time a slot is
-        // selected (lazy load, 1 request per unique slot).
         if source == ItemDetailsSource::ItemTemplates
-            && self.data_source.is_live_api()
-            && !self.fully_loaded_item_slots.contains(&idx)
-            && let Some(client) = self.admin_client.as_ref().cloned()
+            && let Err(e) = self.ensure_item_template_loaded(idx)
         {
-            match client.fetch_single_item_template(idx) {
-                Ok(item) => {
-                    if idx < self.item_templates.len() {
-                        self.item_templates[idx] = item;
-                    }
-                    self.fully_loaded_item_slots.insert(idx);
-                }
-                Err(e) => {
-                    ui.colored_label(
-                        egui::Color32::RED,
-                        format!("Failed to load item template {idx}: {e}"),
-                    );
-                    return;
-                }
-            }
+            ui.colored_label(egui::Color32::RED, e);
+            return;
         }
         let item_ptr: *mut mag_core::types::Item = match source {
             ItemDetailsSource::ItemTemplates => {
@@ -1031,6 +1055,149 @@ impl TemplateViewerApp {
             });
     }
 
+    fn render_item_search_list(&mut self, ui: &mut egui::Ui) {
+        use mag_core::constants::ItemFlags;
+
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.item_query.text)
+                .on_hover_text("Words in the name, reference or description, or a slot number");
+        });
+
+        let mut flag_checkbox = |ui: &mut egui::Ui, flag: ItemFlags, label: &str| {
+            let mut on = self.item_query.required_flags & flag.bits() != 0;
+            if ui.checkbox(&mut on, label).changed() {
+                self.item_query.required_flags ^= flag.bits();
+            }
+        };
+        ui.horizontal(|ui| {
+            flag_checkbox(ui, ItemFlags::IF_SOULSTONE, "Soulstone");
+            flag_checkbox(ui, ItemFlags::IF_LABYDESTROY, "Laby Destroy");
+        });
+        ui.collapsing("More flags", |ui| {
+            egui::Grid::new("item_search_flags")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (n, (flag, label)) in crate::get_item_flag_info().into_iter().enumerate() {
+                        flag_checkbox(ui, flag, label);
+                        if n % 2 == 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.item_query.include_unused, "Show unused");
+            if self.item_query.required_flags != 0 && ui.button("Clear flags").clicked() {
+                self.item_query.required_flags = 0;
+            }
+        });
+
+        ui.separator();
+
+        let results = self.item_query.search(&self.item_templates);
+        ui.label(format!("{} matching templates", results.len()));
+        if self.data_source.is_live_api() {
+            ui.small("API mode: only names are searched until a template is opened.");
+        }
+
+        let list_width = ui.available_width();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                ui.set_min_width(list_width);
+                for idx in results {
+                    let name = self.item_templates[idx].get_name().to_owned();
+                    ui.horizontal(|ui| {
+                        if ui
+                            .selectable_label(self.compare_right == Some(idx), "B")
+                            .on_hover_text("Compare against this template")
+                            .clicked()
+                        {
+                            self.compare_right = Some(idx);
+                        }
+                        if ui
+                            .selectable_label(
+                                self.compare_left == Some(idx),
+                                format!("[{}] {}", idx, name),
+                            )
+                            .clicked()
+                        {
+                            self.compare_left = Some(idx);
+                        }
+                    });
+                }
+            });
+    }
+
+    /// Side-by-side comparison of the two templates picked in the Item
+    /// Search list; differing fields are highlighted.
+    fn render_item_comparison(&mut self, ui: &mut egui::Ui, left: usize, right: usize) {
+        for idx in [left, right] {
+            if let Err(e) = self.ensure_item_template_loaded(idx) {
+                ui.colored_label(egui::Color32::RED, e);
+                return;
+            }
+        }
+        let (Some(a), Some(b)) = (
+            self.item_templates.get(left),
+            self.item_templates.get(right),
+        ) else {
+            return;
+        };
+        let rows = compare_items(a, b);
+        let changed = rows.iter().filter(|row| row.differs()).count();
+
+        ui.horizontal(|ui| {
+            ui.heading(format!(
+                "[{}] {}  vs  [{}] {}",
+                left,
+                a.get_name(),
+                right,
+                b.get_name()
+            ));
+        });
+        ui.horizontal(|ui| {
+            ui.label(format!("{} of {} fields differ", changed, rows.len()));
+            ui.checkbox(&mut self.compare_only_differences, "Only differences");
+            if ui.button("Swap").clicked() {
+                std::mem::swap(&mut self.compare_left, &mut self.compare_right);
+            }
+            if ui.button("Close comparison").clicked() {
+                self.compare_right = None;
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::both()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("item_comparison")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Field");
+                        ui.strong(format!("[{}]", left));
+                        ui.strong(format!("[{}]", right));
+                        ui.end_row();
+                        for row in &rows {
+                            if self.compare_only_differences && !row.differs() {
+                                continue;
+                            }
+                            let color = if row.differs() {
+                                egui::Color32::YELLOW
+                            } else {
+                                ui.visuals().text_color()
+                            };
+                            ui.label(&row.field);
+                            ui.colored_label(color, &row.left);
+                            ui.colored_label(color, &row.right);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
     fn render_item_instance_list(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.label("Filter:");
@@ -2495,6 +2662,12 @@ impl eframe::App for TemplateViewerApp {
                 {
                     self.view_mode = ViewMode::Characters;
                 }
+                if ui
+                    .selectable_label(self.view_mode == ViewMode::ItemSearch, "Item Search")
+                    .clicked()
+                {
+                    self.view_mode = ViewMode::ItemSearch;
+                }
 
                 // Right-aligned action buttons for connection and reload.
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -2701,6 +2874,36 @@ impl eframe::App for TemplateViewerApp {
                     }
                 });
             }
+            ViewMode::ItemSearch => {
+                egui::SidePanel::left("item_search_list")
+                    .resizable(true)
+                    .default_width(320.0)
+                    .show_inside(ui, |ui| {
+                        ui.heading("Item Search");
+                        ui.separator();
+                        self.render_item_search_list(ui);
+                    });
+
+                egui::CentralPanel::default().show_inside(ui, |ui| {
+                    match (self.compare_left, self.compare_right) {
+                        (Some(left), Some(right)) => self.render_item_comparison(ui, left, right),
+                        (Some(left), None) if left < self.item_templates.len() => {
+                            self.render_item_details_by_index(
+                                ui,
+                                ItemDetailsSource::ItemTemplates,
+                                left,
+                            );
+                        }
+                        _ => {
+                            ui.centered_and_justified(|ui| {
+                                ui.label(
+                                    "Select a template; pick a second one with \"B\" to compare",
+                                );
+                            });
+                        }
+                    }
+                });
+            }
         });
 
         self.render_item_popup(ctx);
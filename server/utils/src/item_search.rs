@@ -0,0 +1,277 @@
+//! Searching and comparing item templates.
+//!
+//! Backs the template viewer's "Item Search" mode, used to look into item
+//! corruption reports: full-text search over the template texts, filters on
+//! the flags that matter most for those reports, and a field-by-field
+//! comparison of two templates.
+
+use mag_core::constants::{ItemFlags, USE_EMPTY};
+use mag_core::skills;
+use mag_core::string_operations::c_string_to_str;
+use mag_core::types::Item;
+
+/// Search criteria for item templates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemQuery {
+    /// Whitespace-separated terms; every term must appear (ignoring case)
+    /// in the name, reference or description, or equal the slot number.
+    pub text: String,
+    /// `ItemFlags` bits the template must have.
+    pub required_flags: u64,
+    /// Also list empty template slots.
+    pub include_unused: bool,
+}
+
+impl ItemQuery {
+    /// Returns whether template slot `idx` matches the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - Template slot.
+    /// * `item` - Template in that slot.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the template passes every criterion.
+    pub fn matches(&self, idx: usize, item: &Item) -> bool {
+        if !self.include_unused && item.used == USE_EMPTY {
+            return false;
+        }
+        if item.flags & self.required_flags != self.required_flags {
+            return false;
+        }
+        let haystack = [
+            c_string_to_str(&item.name),
+            c_string_to_str(&item.reference),
+            c_string_to_str(&item.description),
+        ]
+        .join("\n")
+        .to_lowercase();
+        let slot = idx.to_string();
+        self.text
+            .split_whitespace()
+            .all(|term| term == slot || haystack.contains(&term.to_lowercase()))
+    }
+
+    /// Slots of all templates matching the query, in slot order.
+    ///
+    /// # Arguments
+    ///
+    /// * `templates` - The item template table.
+    ///
+    /// # Returns
+    ///
+    /// * The matching slot numbers.
+    pub fn search(&self, templates: &[Item]) -> Vec<usize> {
+        templates
+            .iter()
+            .enumerate()
+            .filter(|(idx, item)| self.matches(*idx, item))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
+
+/// Names of the flags set in `flags`, joined with `" | "`.
+///
+/// # Arguments
+///
+/// * `flags` - Raw `Item::flags` value.
+///
+/// # Returns
+///
+/// * E.g. `"IF_TAKE | IF_SOULSTONE"`, `"-"` when none are set, with any
+///   unknown bits appended in hex.
+pub fn flag_names(flags: u64) -> String {
+    let known = ItemFlags::from_bits_truncate(flags);
+    let mut names: Vec<String> = known
+        .iter_names()
+        .map(|(name, _)| name.to_owned())
+        .collect();
+    let unknown = flags & !ItemFlags::all().bits();
+    if unknown != 0 {
+        names.push(format!("0x{:X}", unknown));
+    }
+    if names.is_empty() {
+        "-".to_owned()
+    } else {
+        names.join(" | ")
+    }
+}
+
+/// One field of a template comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldRow {
+    /// Field name, e.g. `"value"` or `"skill[Sword]"`.
+    pub field: String,
+    /// Value in the left template.
+    pub left: String,
+    /// Value in the right template.
+    pub right: String,
+}
+
+impl FieldRow {
+    /// Returns `true` when the two sides differ.
+    pub fn differs(&self) -> bool {
+        self.left != self.right
+    }
+}
+
+/// Lists the fields of two templates side by side.
+///
+/// Per-attribute and per-skill modifiers are only listed when either
+/// template sets them, so unrelated skills do not drown out the rest.
+///
+/// # Arguments
+///
+/// * `a` - Left template.
+/// * `b` - Right template.
+///
+/// # Returns
+///
+/// * One row per field, in struct order.
+pub fn compare_items(a: &Item, b: &Item) -> Vec<FieldRow> {
+    let mut rows = Vec::new();
+    let mut row = |field: String, left: String, right: String| {
+        rows.push(FieldRow { field, left, right });
+    };
+    macro_rules! plain {
+        ($($name:ident),*) => {
+            $(row(stringify!($name).to_owned(), format!("{:?}", a.$name), format!("{:?}", b.$name));)*
+        };
+    }
+
+    row("name".into(), a.get_name().into(), b.get_name().into());
+    row(
+        "reference".into(),
+        c_string_to_str(&a.reference).into(),
+        c_string_to_str(&b.reference).into(),
+    );
+    row(
+        "description".into(),
+        c_string_to_str(&a.description).into(),
+        c_string_to_str(&b.description).into(),
+    );
+    row("flags".into(), flag_names(a.flags), flag_names(b.flags));
+    plain!(
+        used,
+        value,
+        placement,
+        temp,
+        damage_state,
+        max_age,
+        current_age,
+        max_damage,
+        current_damage,
+        hp,
+        end,
+        mana,
+        armor,
+        weapon,
+        light,
+        duration,
+        cost,
+        power,
+        active,
+        x,
+        y,
+        carried,
+        sprite_override,
+        sprite,
+        status,
+        gethit_dam,
+        min_rank,
+        driver,
+        data
+    );
+    for n in 0..a.attrib.len() {
+        if a.attrib[n] != [0; 3] || b.attrib[n] != [0; 3] {
+            row(
+                format!("attrib[{}]", skills::attribute_name(n)),
+                format!("{:?}", a.attrib[n]),
+                format!("{:?}", b.attrib[n]),
+            );
+        }
+    }
+    for n in 0..a.skill.len() {
+        if a.skill[n] != [0; 3] || b.skill[n] != [0; 3] {
+            row(
+                format!("skill[{}]", skills::get_skill_name(n)),
+                format!("{:?}", a.skill[n]),
+                format!("{:?}", b.skill[n]),
+            );
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use mag_core::constants::USE_ACTIVE;
+
+    use super::*;
+
+    fn template(name: &str, description: &str, flags: ItemFlags) -> Item {
+        let mut item = Item {
+            used: USE_ACTIVE,
+            flags: flags.bits(),
+            ..Default::default()
+        };
+        item.name[..name.len()].copy_from_slice(name.as_bytes());
+        item.description[..description.len()].copy_from_slice(description.as_bytes());
+        item
+    }
+
+    #[test]
+    fn search_matches_text_slots_and_flags() {
+        let templates = vec![
+            Item::default(),
+            template("Soulstone", "A glowing stone.", ItemFlags::IF_SOULSTONE),
+            template("Torch", "A burning torch.", ItemFlags::IF_LABYDESTROY),
+            template("Stone Axe", "Heavy.", ItemFlags::IF_WP_AXE),
+        ];
+
+        let text = |text: &str| ItemQuery {
+            text: text.to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(text("STONE").search(&templates), vec![1, 3]);
+        assert_eq!(text("glowing stone").search(&templates), vec![1]);
+        assert_eq!(text("2").search(&templates), vec![2]);
+        assert_eq!(text("").search(&templates), vec![1, 2, 3]);
+
+        let flagged = ItemQuery {
+            required_flags: ItemFlags::IF_LABYDESTROY.bits(),
+            ..Default::default()
+        };
+        assert_eq!(flagged.search(&templates), vec![2]);
+        let all = ItemQuery {
+            include_unused: true,
+            ..Default::default()
+        };
+        assert_eq!(all.search(&templates).len(), 4);
+    }
+
+    #[test]
+    fn comparison_lists_changed_fields_and_used_skills() {
+        let a = template("Sword", "", ItemFlags::IF_TAKE | ItemFlags::IF_WP_SWORD);
+        let mut b = a;
+        b.value = 250;
+        b.skill[skills::SK_SWORD] = [2, 0, 10];
+        b.flags |= 1 << 63;
+
+        let rows = compare_items(&a, &b);
+        let changed: Vec<&str> = rows
+            .iter()
+            .filter(|r| r.differs())
+            .map(|r| r.field.as_str())
+            .collect();
+        assert_eq!(changed, vec!["flags", "value", "skill[Sword]"]);
+        assert_eq!(
+            rows.iter().filter(|r| r.field.starts_with("skill")).count(),
+            1
+        );
+        assert_eq!(flag_names(0), "-");
+        assert!(flag_names(b.flags).ends_with("0x8000000000000000"));
+    }
+}
@@ -6,6 +6,21 @@ pub mod viewer_support;
 /// Blocking HTTP client for the server admin API (template editing).
 pub mod admin_client;
 
+/// Item template search, flag filters and side-by-side comparison.
+pub mod item_search;
+
+/// Comparison of load-test run summaries and SLO checks.
+pub mod loadtest_report;
+
+/// Offline decoder for captured game protocol traffic.
+pub mod packet_capture;
+
+/// Per-entity comparison of two world snapshots.
+pub mod world_diff;
+
+/// Nightly world integrity checks and their KeyDB/Discord output.
+pub mod world_integrity;
+
 pub use admin_client::AdminClient;
 pub use viewer_support::{
     DataSource, data_source_from_args, default_graphics_zip_path, graphics_zip_from_args,
@@ -0,0 +1,423 @@
+//! Comparison of `mag-loadtest` run summaries against each other and an SLO.
+//!
+//! Used by `mag-admin loadtest-report`. Each run is the JSON file written by
+//! `mag-loadtest --summary`. Runs are grouped by client count; within a group
+//! the first run given is the baseline the others are compared against.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Outcome of one load-test run, as written by `mag-loadtest --summary`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RunSummary {
+    /// Free-form name of the run, e.g. a branch or commit.
+    pub label: String,
+    /// Configured number of bot clients.
+    pub clients: usize,
+    /// Wall-clock duration of the run in seconds.
+    pub duration_secs: f64,
+    /// Clients that completed the game handshake.
+    pub connected: u64,
+    /// Clients that failed to connect or bootstrap.
+    pub connect_errors: u64,
+    /// Total inbound bytes across all clients.
+    pub bytes_in: u64,
+    /// Total outbound bytes across all clients.
+    pub bytes_out: u64,
+    /// Total tick packets received across all clients.
+    pub ticks_total: u64,
+    /// Sum of per-client connected durations in milliseconds.
+    pub client_connected_ms: u64,
+    /// Inter-tick gaps over 100 ms.
+    pub late_gaps: u64,
+    /// Every RTT sample in milliseconds, sorted ascending.
+    pub rtt_ms: Vec<u32>,
+}
+
+/// Limits a run must stay within; unset limits are not checked.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Slo {
+    /// Highest allowed median RTT in milliseconds.
+    pub max_p50_ms: Option<u32>,
+    /// Highest allowed 95th percentile RTT in milliseconds.
+    pub max_p95_ms: Option<u32>,
+    /// Highest allowed 99th percentile RTT in milliseconds.
+    pub max_p99_ms: Option<u32>,
+    /// Lowest allowed average ticks per second per client (the server runs
+    /// at 36).
+    pub min_ticks_per_sec: Option<f64>,
+    /// Highest allowed share of clients that failed to connect, in percent.
+    pub max_connect_error_pct: Option<f64>,
+    /// Highest allowed server-to-client bytes per second per client.
+    pub max_bytes_in_per_client_sec: Option<f64>,
+}
+
+/// Figures derived from one [`RunSummary`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RunStats {
+    pub label: String,
+    pub clients: usize,
+    pub connected: u64,
+    pub connect_error_pct: f64,
+    pub rtt_samples: usize,
+    pub p50_ms: Option<u32>,
+    pub p95_ms: Option<u32>,
+    pub p99_ms: Option<u32>,
+    pub max_ms: Option<u32>,
+    /// Server-to-client bytes per second, all clients together.
+    pub bytes_in_per_sec: f64,
+    /// Client-to-server bytes per second, all clients together.
+    pub bytes_out_per_sec: f64,
+    /// Server-to-client bytes per second per connected client.
+    pub bytes_in_per_client_sec: f64,
+    pub ticks_per_sec: f64,
+    pub late_gaps: u64,
+}
+
+impl RunStats {
+    /// Derives the report figures from a run summary.
+    pub fn from_summary(run: &RunSummary) -> Self {
+        let attempts = run.connected + run.connect_errors;
+        let duration = run.duration_secs.max(f64::EPSILON);
+        let bytes_in_per_sec = run.bytes_in as f64 / duration;
+        Self {
+            label: run.label.clone(),
+            clients: run.clients,
+            connected: run.connected,
+            connect_error_pct: if attempts > 0 {
+                run.connect_errors as f64 * 100.0 / attempts as f64
+            } else {
+                0.0
+            },
+            rtt_samples: run.rtt_ms.len(),
+            p50_ms: percentile(&run.rtt_ms, 50.0),
+            p95_ms: percentile(&run.rtt_ms, 95.0),
+            p99_ms: percentile(&run.rtt_ms, 99.0),
+            max_ms: run.rtt_ms.last().copied(),
+            bytes_in_per_sec,
+            bytes_out_per_sec: run.bytes_out as f64 / duration,
+            bytes_in_per_client_sec: bytes_in_per_sec / run.connected.max(1) as f64,
+            ticks_per_sec: if run.client_connected_ms > 0 {
+                run.ticks_total as f64 * 1000.0 / run.client_connected_ms as f64
+            } else {
+                0.0
+            },
+            late_gaps: run.late_gaps,
+        }
+    }
+}
+
+/// Runs that used the same number of clients.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerCountGroup {
+    pub clients: usize,
+    /// In the order given; the first is the baseline.
+    pub runs: Vec<RunStats>,
+}
+
+/// One limit one run did not stay within.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloViolation {
+    pub label: String,
+    pub clients: usize,
+    pub problem: String,
+}
+
+/// Comparison of every run, and what broke the SLO.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadTestReport {
+    /// Ordered by client count.
+    pub groups: Vec<PlayerCountGroup>,
+    pub violations: Vec<SloViolation>,
+}
+
+impl LoadTestReport {
+    /// Human-readable comparison table, one line per run.
+    ///
+    /// # Returns
+    ///
+    /// * The table followed by the SLO violations, newline-terminated.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        for group in &self.groups {
+            out.push_str(&format!("=== {} clients ===\n", group.clients));
+            out.push_str(
+                "RUN                  CONN  ERR%   P50   P95   P99   MAX   IN/S       OUT/S      IN/S/CLIENT  TICKS/S  LATE\n",
+            );
+            let baseline = &group.runs[0];
+            for (n, run) in group.runs.iter().enumerate() {
+                out.push_str(&format!(
+                    "{:<20} {:>4}  {:>4.1}  {:>4}  {:>4}  {:>4}  {:>4}  {:>9}  {:>9}  {:>11}  {:>7.2}  {:>4}\n",
+                    truncate(&run.label, 20),
+                    run.connected,
+                    run.connect_error_pct,
+                    ms(run.p50_ms),
+                    ms(run.p95_ms),
+                    ms(run.p99_ms),
+                    ms(run.max_ms),
+                    human_rate(run.bytes_in_per_sec),
+                    human_rate(run.bytes_out_per_sec),
+                    human_rate(run.bytes_in_per_client_sec),
+                    run.ticks_per_sec,
+                    run.late_gaps,
+                ));
+                if n > 0 {
+                    out.push_str(&format!(
+                        "{:<20} vs {}: p95 {}, p99 {}, in/s {}\n",
+                        "",
+                        truncate(&baseline.label, 20),
+                        change_ms(baseline.p95_ms, run.p95_ms),
+                        change_ms(baseline.p99_ms, run.p99_ms),
+                        change_pct(baseline.bytes_in_per_sec, run.bytes_in_per_sec),
+                    ));
+                }
+            }
+            out.push('\n');
+        }
+        if self.violations.is_empty() {
+            out.push_str("SLO: met\n");
+        } else {
+            out.push_str(&format!("SLO: {} violation(s)\n", self.violations.len()));
+            for violation in &self.violations {
+                out.push_str(&format!(
+                    "  {} ({} clients): {}\n",
+                    violation.label, violation.clients, violation.problem
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Reads a run summary written by `mag-loadtest --summary`.
+///
+/// # Arguments
+///
+/// * `path` - Summary file.
+///
+/// # Returns
+///
+/// * The run, labelled with the file stem when it has no label.
+pub fn load_run(path: &Path) -> Result<RunSummary, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let mut run: RunSummary = serde_json::from_str(&text)
+        .map_err(|e| format!("failed to parse {}: {e}", path.display()))?;
+    if run.label.trim().is_empty() {
+        run.label = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+    }
+    // Older or hand-edited summaries may not be sorted.
+    run.rtt_ms.sort_unstable();
+    Ok(run)
+}
+
+/// Reads an SLO file, a JSON object with the fields of [`Slo`].
+///
+/// # Arguments
+///
+/// * `path` - SLO file.
+pub fn load_slo(path: &Path) -> Result<Slo, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("failed to parse {}: {e}", path.display()))
+}
+
+/// Compares runs and checks each against the SLO.
+///
+/// # Arguments
+///
+/// * `runs` - Runs in the order given; the first of each client count is
+///   that group's baseline.
+/// * `slo` - Limits every run must stay within.
+///
+/// # Returns
+///
+/// * The report; [`LoadTestReport::violations`] is empty when the SLO is met.
+pub fn build_report(runs: &[RunSummary], slo: &Slo) -> LoadTestReport {
+    let mut groups: Vec<PlayerCountGroup> = Vec::new();
+    let mut violations = Vec::new();
+    for run in runs {
+        let stats = RunStats::from_summary(run);
+        violations.extend(check_slo(&stats, slo));
+        match groups.iter_mut().find(|group| group.clients == run.clients) {
+            Some(group) => group.runs.push(stats),
+            None => groups.push(PlayerCountGroup {
+                clients: run.clients,
+                runs: vec![stats],
+            }),
+        }
+    }
+    groups.sort_by_key(|group| group.clients);
+    LoadTestReport { groups, violations }
+}
+
+/// Every SLO limit `run` exceeds.
+fn check_slo(run: &RunStats, slo: &Slo) -> Vec<SloViolation> {
+    let mut problems = Vec::new();
+    for (name, limit, value) in [
+        ("p50", slo.max_p50_ms, run.p50_ms),
+        ("p95", slo.max_p95_ms, run.p95_ms),
+        ("p99", slo.max_p99_ms, run.p99_ms),
+    ] {
+        let Some(limit) = limit else {
+            continue;
+        };
+        match value {
+            Some(value) if value > limit => {
+                problems.push(format!("{name} RTT {value}ms exceeds {limit}ms"));
+            }
+            Some(_) => {}
+            None => problems.push(format!("{name} RTT limited but no RTT samples collected")),
+        }
+    }
+    if let Some(min) = slo.min_ticks_per_sec
+        && run.ticks_per_sec < min
+    {
+        problems.push(format!(
+            "{:.2} ticks/s per client is below {:.2}",
+            run.ticks_per_sec, min
+        ));
+    }
+    if let Some(max) = slo.max_connect_error_pct
+        && run.connect_error_pct > max
+    {
+        problems.push(format!(
+            "{:.1}% of clients failed to connect, over {:.1}%",
+            run.connect_error_pct, max
+        ));
+    }
+    if let Some(max) = slo.max_bytes_in_per_client_sec
+        && run.bytes_in_per_client_sec > max
+    {
+        problems.push(format!(
+            "{} per client exceeds {}",
+            human_rate(run.bytes_in_per_client_sec),
+            human_rate(max)
+        ));
+    }
+    problems
+        .into_iter()
+        .map(|problem| SloViolation {
+            label: run.label.clone(),
+            clients: run.clients,
+            problem,
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of ascending `sorted` samples.
+///
+/// # Returns
+///
+/// * `None` without samples.
+pub fn percentile(sorted: &[u32], pct: f64) -> Option<u32> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn ms(value: Option<u32>) -> String {
+    value.map_or_else(|| "-".to_owned(), |v| v.to_string())
+}
+
+fn change_ms(baseline: Option<u32>, value: Option<u32>) -> String {
+    match (baseline, value) {
+        (Some(baseline), Some(value)) => {
+            format!("{:+}ms", i64::from(value) - i64::from(baseline))
+        }
+        _ => "n/a".to_owned(),
+    }
+}
+
+fn change_pct(baseline: f64, value: f64) -> String {
+    if baseline <= 0.0 {
+        return "n/a".to_owned();
+    }
+    format!("{:+.1}%", (value - baseline) * 100.0 / baseline)
+}
+
+fn human_rate(bytes_per_sec: f64) -> String {
+    if bytes_per_sec < 1024.0 {
+        format!("{bytes_per_sec:.0}B/s")
+    } else if bytes_per_sec < 1024.0 * 1024.0 {
+        format!("{:.1}KiB/s", bytes_per_sec / 1024.0)
+    } else {
+        format!("{:.1}MiB/s", bytes_per_sec / 1024.0 / 1024.0)
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(label: &str, clients: usize, rtt_ms: Vec<u32>) -> RunSummary {
+        RunSummary {
+            label: label.to_owned(),
+            clients,
+            duration_secs: 100.0,
+            connected: clients as u64,
+            bytes_in: 1_000_000,
+            bytes_out: 50_000,
+            ticks_total: 36 * 100 * clients as u64,
+            client_connected_ms: 100_000 * clients as u64,
+            rtt_ms,
+            ..RunSummary::default()
+        }
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples: Vec<u32> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50.0), Some(50));
+        assert_eq!(percentile(&samples, 95.0), Some(95));
+        assert_eq!(percentile(&samples, 99.0), Some(99));
+        assert_eq!(percentile(&[7], 99.0), Some(7));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn runs_group_by_client_count_and_check_the_slo() {
+        let runs = vec![
+            run("main", 200, (1..=100).collect()),
+            run("main", 50, vec![10; 20]),
+            run("branch", 200, (1..=100).map(|v| v * 2).collect()),
+        ];
+        let slo = Slo {
+            max_p95_ms: Some(100),
+            min_ticks_per_sec: Some(35.0),
+            ..Slo::default()
+        };
+        let report = build_report(&runs, &slo);
+        assert_eq!(
+            report
+                .groups
+                .iter()
+                .map(|group| group.clients)
+                .collect::<Vec<_>>(),
+            vec![50, 200]
+        );
+        assert_eq!(report.groups[1].runs[0].label, "main");
+        assert_eq!(report.groups[1].runs[1].p95_ms, Some(190));
+        assert!((report.groups[0].runs[0].bytes_in_per_sec - 10_000.0).abs() < 1e-9);
+        assert!((report.groups[0].runs[0].ticks_per_sec - 36.0).abs() < 1e-9);
+
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].label, "branch");
+        assert!(report.summary().contains("p95 +95ms"));
+
+        let no_samples = build_report(&[run("idle", 10, Vec::new())], &slo);
+        assert_eq!(no_samples.violations.len(), 1);
+    }
+}
@@ -0,0 +1,578 @@
+//! Offline decoder for captured game traffic.
+//!
+//! Turns a classic libpcap capture, or a raw byte dump of one direction of a
+//! game connection, into a list of [`DecodedMessage`]s using the shared
+//! protocol definitions from `mag_core`.
+//!
+//! The game port is TLS-only, so pcap input is only useful for plaintext
+//! captures (local test servers, or captures re-exported after decryption).
+//! For live TLS traffic, save each direction of the decrypted stream as raw
+//! bytes and decode it with [`decode_stream`].
+
+use std::collections::HashMap;
+
+use flate2::{Decompress, FlushDecompress, Status};
+use mag_core::client_commands::ClientCommandType;
+use mag_core::server_commands::{ServerCommand, ServerCommandType};
+
+/// Fixed size of every client-to-server command.
+const CLIENT_COMMAND_LEN: usize = 16;
+
+/// Which side sent a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+}
+
+impl Direction {
+    /// Short arrow used when printing messages.
+    ///
+    /// # Returns
+    ///
+    /// * `"C>S"` or `"S>C"`.
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Direction::ClientToServer => "C>S",
+            Direction::ServerToClient => "S>C",
+        }
+    }
+}
+
+/// One decoded protocol message.
+#[derive(Clone, Debug)]
+pub struct DecodedMessage {
+    /// 1-based connection number, in order of first appearance.
+    pub flow: usize,
+    /// Client endpoint (`ip:port`) of the connection, or `"raw"`.
+    pub peer: String,
+    pub direction: Direction,
+    /// Server tick frame the message arrived in (0 for client messages and
+    /// login-phase server messages).
+    pub frame: usize,
+    pub opcode: u8,
+    /// Protocol name of the opcode, e.g. `CmdInv` or `SetCharItem`.
+    pub name: String,
+    /// Decoded fields, or a hex dump when the payload could not be decoded.
+    pub detail: String,
+}
+
+impl DecodedMessage {
+    /// Checks whether the message matches a `--command` filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Opcode number, or opcode name (case-insensitive, with or
+    ///   without the `Cmd` prefix).
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the filter selects this message.
+    pub fn matches_command(&self, filter: &str) -> bool {
+        if let Ok(opcode) = filter.parse::<u8>() {
+            return self.opcode == opcode;
+        }
+        let name = self.name.to_ascii_lowercase();
+        let filter = filter.to_ascii_lowercase();
+        name == filter || name.strip_prefix("cmd") == Some(filter.as_str())
+    }
+
+    /// Checks whether the message matches a `--player` filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Connection number, client port, or client `ip:port`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` when the filter selects this message's connection.
+    pub fn matches_player(&self, filter: &str) -> bool {
+        if filter.parse::<usize>().ok() == Some(self.flow) {
+            return true;
+        }
+        self.peer == filter || self.peer.ends_with(&format!(":{}", filter))
+    }
+}
+
+/// Incremental decoder for one direction of one connection.
+pub struct StreamDecoder {
+    flow: usize,
+    peer: String,
+    direction: Direction,
+    buf: Vec<u8>,
+    /// Server side only: still reading unframed login responses.
+    login_phase: bool,
+    zlib: Decompress,
+    frame: usize,
+}
+
+impl StreamDecoder {
+    /// Creates a decoder for one direction of a connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `flow` - Connection number reported in decoded messages.
+    /// * `peer` - Client endpoint reported in decoded messages.
+    /// * `direction` - Which side produced the bytes.
+    /// * `from_login` - Server side: `true` when the bytes start at the login
+    ///   handshake, `false` when they start at a tick frame boundary.
+    ///
+    /// # Returns
+    ///
+    /// * A fresh decoder.
+    pub fn new(flow: usize, peer: String, direction: Direction, from_login: bool) -> Self {
+        StreamDecoder {
+            flow,
+            peer,
+            direction,
+            buf: Vec::new(),
+            login_phase: from_login,
+            zlib: Decompress::new(true),
+            frame: 0,
+        }
+    }
+
+    /// Feeds bytes and appends every message completed by them to `out`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Next chunk of the byte stream, in order.
+    /// * `out` - Receives the decoded messages.
+    ///
+    /// # Returns
+    ///
+    /// * `Err` when the stream cannot be resynchronised (bad frame header,
+    ///   inflate failure, unknown server opcode); messages decoded before the
+    ///   error are still appended.
+    pub fn push(&mut self, bytes: &[u8], out: &mut Vec<DecodedMessage>) -> Result<(), String> {
+        self.buf.extend_from_slice(bytes);
+        match self.direction {
+            Direction::ClientToServer => {
+                while self.buf.len() >= CLIENT_COMMAND_LEN {
+                    let cmd: Vec<u8> = self.buf.drain(..CLIENT_COMMAND_LEN).collect();
+                    out.push(self.client_message(&cmd));
+                }
+            }
+            Direction::ServerToClient => {
+                while self.login_phase && !self.buf.is_empty() {
+                    let opcode = self.buf[0];
+                    let len = match ServerCommandType::from(opcode) {
+                        ServerCommandType::Tick | ServerCommandType::Exit => 2,
+                        _ => 16,
+                    };
+                    if self.buf.len() < len {
+                        return Ok(());
+                    }
+                    let cmd: Vec<u8> = self.buf.drain(..len).collect();
+                    out.push(self.server_message(&cmd));
+                    if ServerCommandType::from(opcode) == ServerCommandType::LoginOk {
+                        self.login_phase = false;
+                    }
+                }
+                while let Some(payload) = self.next_frame()? {
+                    self.split_frame(&payload, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pops one complete tick frame and returns its uncompressed payload.
+    fn next_frame(&mut self) -> Result<Option<Vec<u8>>, String> {
+        if self.buf.len() < 2 {
+            return Ok(None);
+        }
+        let len_flags = u16::from_le_bytes([self.buf[0], self.buf[1]]);
+        let total_len = (len_flags & 0x7FFF) as usize;
+        if total_len < 2 {
+            return Err(format!("invalid frame header 0x{:04X}", len_flags));
+        }
+        if self.buf.len() < total_len {
+            return Ok(None);
+        }
+        let payload: Vec<u8> = self.buf.drain(..total_len).skip(2).collect();
+        self.frame += 1;
+        if len_flags & 0x8000 == 0 {
+            return Ok(Some(payload));
+        }
+        inflate(&mut self.zlib, &payload).map(Some)
+    }
+
+    fn split_frame(&self, payload: &[u8], out: &mut Vec<DecodedMessage>) -> Result<(), String> {
+        let mut idx = 0;
+        let mut last_setmap_n = -1;
+        while idx < payload.len() {
+            let len = ServerCommandType::get_expected_length(&payload[idx..], &mut last_setmap_n)
+                .map_err(|e| format!("frame {} offset {}: {}", self.frame, idx, e))?;
+            if len == 0 || idx + len > payload.len() {
+                return Err(format!(
+                    "frame {} offset {}: opcode {} needs {} bytes, {} left",
+                    self.frame,
+                    idx,
+                    payload[idx],
+                    len,
+                    payload.len() - idx
+                ));
+            }
+            out.push(self.server_message(&payload[idx..idx + len]));
+            idx += len;
+        }
+        Ok(())
+    }
+
+    fn server_message(&self, bytes: &[u8]) -> DecodedMessage {
+        let (name, detail) = match ServerCommand::from_bytes(bytes) {
+            Some(cmd) => (
+                format!("{:?}", cmd.header),
+                format!("{:?}", cmd.structured_data),
+            ),
+            None => (
+                format!("{:?}", ServerCommandType::from(bytes[0])),
+                format!("undecoded {}", hex(&bytes[1..])),
+            ),
+        };
+        self.message(bytes[0], name, detail)
+    }
+
+    fn client_message(&self, bytes: &[u8]) -> DecodedMessage {
+        let opcode = bytes[0];
+        let name = match ClientCommandType::from(opcode) {
+            ClientCommandType::_Empty if opcode != 0 => format!("Unknown({})", opcode),
+            kind => format!("{:?}", kind),
+        };
+        let word =
+            |n: usize| u32::from_le_bytes([bytes[n], bytes[n + 1], bytes[n + 2], bytes[n + 3]]);
+        let detail = format!(
+            "u32=[{}, {}, {}] raw={}",
+            word(1),
+            word(5),
+            word(9),
+            hex(&bytes[1..])
+        );
+        self.message(opcode, name, detail)
+    }
+
+    fn message(&self, opcode: u8, name: String, detail: String) -> DecodedMessage {
+        DecodedMessage {
+            flow: self.flow,
+            peer: self.peer.clone(),
+            direction: self.direction,
+            frame: if self.direction == Direction::ServerToClient {
+                self.frame
+            } else {
+                0
+            },
+            opcode,
+            name,
+            detail,
+        }
+    }
+}
+
+/// Inflates one chunk of the connection's continuous zlib stream.
+fn inflate(z: &mut Decompress, input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut scratch = [0u8; 8192];
+    let mut in_pos = 0;
+    loop {
+        let before_in = z.total_in();
+        let before_out = z.total_out();
+        let status = z
+            .decompress(&input[in_pos..], &mut scratch, FlushDecompress::Sync)
+            .map_err(|e| format!("zlib inflate failed: {}", e))?;
+        let consumed = (z.total_in() - before_in) as usize;
+        let produced = (z.total_out() - before_out) as usize;
+        out.extend_from_slice(&scratch[..produced]);
+        in_pos += consumed;
+        if consumed == 0 && produced == 0 {
+            if in_pos < input.len() && status == Status::Ok {
+                return Err("zlib inflate made no progress".to_owned());
+            }
+            return Ok(out);
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes a raw dump of one direction of a single connection.
+///
+/// # Arguments
+///
+/// * `bytes` - The dumped byte stream.
+/// * `direction` - Which side sent the bytes.
+/// * `from_login` - Server side: whether the dump starts at the login
+///   handshake rather than at a tick frame.
+///
+/// # Returns
+///
+/// * Decoded messages; decoding stops at the first unrecoverable error,
+///   which is returned alongside the messages decoded before it.
+pub fn decode_stream(
+    bytes: &[u8],
+    direction: Direction,
+    from_login: bool,
+) -> (Vec<DecodedMessage>, Option<String>) {
+    let mut decoder = StreamDecoder::new(1, "raw".to_owned(), direction, from_login);
+    let mut messages = Vec::new();
+    let err = decoder.push(bytes, &mut messages).err();
+    (messages, err)
+}
+
+/// One direction of one TCP connection being reassembled from a pcap.
+struct TcpHalf {
+    next_seq: Option<u32>,
+    decoder: StreamDecoder,
+    failed: bool,
+}
+
+/// Decodes all game connections found in a classic (non-ng) pcap file.
+///
+/// Supports Ethernet, Linux cooked (SLL), BSD loopback and raw IP link
+/// types carrying IPv4 or IPv6 TCP. Streams are reassembled by sequence
+/// number; retransmitted bytes are dropped and gaps are reported.
+///
+/// # Arguments
+///
+/// * `bytes` - Contents of the pcap file.
+/// * `server_port` - TCP port of the game server.
+///
+/// # Returns
+///
+/// * `Ok((messages, warnings))` in capture order, or `Err` when the file is
+///   not a readable pcap.
+pub fn decode_pcap(
+    bytes: &[u8],
+    server_port: u16,
+) -> Result<(Vec<DecodedMessage>, Vec<String>), String> {
+    if bytes.len() < 24 {
+        return Err("file too short for a pcap header".to_owned());
+    }
+    let magic = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let little = match magic {
+        0xa1b2_c3d4 | 0xa1b2_3c4d => true,
+        0xd4c3_b2a1 | 0x4d3c_b2a1 => false,
+        0x0a0d_0d0a => return Err("pcapng is not supported; save as classic pcap".to_owned()),
+        _ => return Err(format!("not a pcap file (magic 0x{:08x})", magic)),
+    };
+    let read_u32 = |at: usize| {
+        let b = [bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]];
+        if little {
+            u32::from_le_bytes(b)
+        } else {
+            u32::from_be_bytes(b)
+        }
+    };
+    let linktype = read_u32(20);
+
+    let mut halves: HashMap<(String, Direction), TcpHalf> = HashMap::new();
+    let mut flows: HashMap<String, usize> = HashMap::new();
+    let mut messages = Vec::new();
+    let mut warnings = Vec::new();
+    let mut pos = 24;
+    let mut record = 0;
+    while pos + 16 <= bytes.len() {
+        record += 1;
+        let incl_len = read_u32(pos + 8) as usize;
+        let start = pos + 16;
+        pos = start + incl_len;
+        if pos > bytes.len() {
+            warnings.push(format!("record {}: truncated", record));
+            break;
+        }
+        let Some(segment) = parse_tcp(linktype, &bytes[start..pos]) else {
+            continue;
+        };
+        let (direction, client) = if segment.dst_port == server_port {
+            (Direction::ClientToServer, segment.src)
+        } else if segment.src_port == server_port {
+            (Direction::ServerToClient, segment.dst)
+        } else {
+            continue;
+        };
+
+        let next_flow = flows.len() + 1;
+        let flow = *flows.entry(client.clone()).or_insert(next_flow);
+        let half = halves
+            .entry((client.clone(), direction))
+            .or_insert_with(|| TcpHalf {
+                next_seq: None,
+                decoder: StreamDecoder::new(flow, client.clone(), direction, true),
+                failed: false,
+            });
+
+        let mut seq = segment.seq;
+        if segment.syn {
+            half.next_seq = Some(seq.wrapping_add(1));
+            seq = seq.wrapping_add(1);
+        }
+        let mut payload = segment.payload;
+        let expected = *half.next_seq.get_or_insert(seq);
+        let behind = expected.wrapping_sub(seq) as i32;
+        if behind > 0 {
+            // Retransmission, possibly with some new bytes at the end.
+            payload = &payload[payload.len().min(behind as usize)..];
+        } else if behind < 0 {
+            warnings.push(format!(
+                "record {}: {} missing {} bytes",
+                record, client, -behind
+            ));
+        }
+        if payload.is_empty() || half.failed {
+            continue;
+        }
+        half.next_seq = Some(
+            expected
+                .wrapping_add((-behind).max(0) as u32)
+                .wrapping_add(payload.len() as u32),
+        );
+        if let Err(e) = half.decoder.push(payload, &mut messages) {
+            warnings.push(format!(
+                "record {}: {} {}: {}",
+                record,
+                client,
+                direction.arrow(),
+                e
+            ));
+            half.failed = true;
+        }
+    }
+    Ok((messages, warnings))
+}
+
+struct TcpSegment<'a> {
+    src: String,
+    dst: String,
+    src_port: u16,
+    dst_port: u16,
+    seq: u32,
+    syn: bool,
+    payload: &'a [u8],
+}
+
+/// Strips link, IP and TCP headers from one captured frame.
+fn parse_tcp(linktype: u32, frame: &[u8]) -> Option<TcpSegment<'_>> {
+    let ip = match linktype {
+        // DLT_NULL: 4-byte host-order address family.
+        0 => frame.get(4..)?,
+        // DLT_EN10MB, skipping one optional 802.1Q tag.
+        1 => {
+            let ethertype = u16::from_be_bytes([*frame.get(12)?, *frame.get(13)?]);
+            if ethertype == 0x8100 {
+                frame.get(18..)?
+            } else {
+                frame.get(14..)?
+            }
+        }
+        // DLT_RAW / LINKTYPE_RAW.
+        12 | 101 => frame,
+        // LINKTYPE_LINUX_SLL and LINUX_SLL2.
+        113 => frame.get(16..)?,
+        276 => frame.get(20..)?,
+        _ => return None,
+    };
+    let (src, dst, tcp) = match ip.first()? >> 4 {
+        4 => {
+            ip.get(19)?;
+            let ihl = usize::from(ip[0] & 0x0F) * 4;
+            let total = usize::from(u16::from_be_bytes([*ip.get(2)?, *ip.get(3)?]));
+            if *ip.get(9)? != 6 {
+                return None;
+            }
+            let src = format!("{}.{}.{}.{}", ip[12], ip[13], ip[14], ip[15]);
+            let dst = format!("{}.{}.{}.{}", ip[16], ip[17], ip[18], ip[19]);
+            (src, dst, ip.get(ihl..total.min(ip.len()))?)
+        }
+        6 => {
+            let len = usize::from(u16::from_be_bytes([*ip.get(4)?, *ip.get(5)?]));
+            if *ip.get(6)? != 6 {
+                return None;
+            }
+            let addr = |at: usize| {
+                let b: [u8; 16] = ip[at..at + 16].try_into().unwrap_or_default();
+                format!("[{}]", std::net::Ipv6Addr::from(b))
+            };
+            ip.get(40)?;
+            (addr(8), addr(24), ip.get(40..(40 + len).min(ip.len()))?)
+        }
+        _ => return None,
+    };
+    let data_offset = usize::from(tcp.get(12)? >> 4) * 4;
+    Some(TcpSegment {
+        src_port: u16::from_be_bytes([tcp[0], tcp[1]]),
+        dst_port: u16::from_be_bytes([tcp[2], tcp[3]]),
+        src: format!("{}:{}", src, u16::from_be_bytes([tcp[0], tcp[1]])),
+        dst: format!("{}:{}", dst, u16::from_be_bytes([tcp[2], tcp[3]])),
+        seq: u32::from_be_bytes([tcp[4], tcp[5], tcp[6], tcp[7]]),
+        syn: tcp[13] & 0x02 != 0,
+        payload: tcp.get(data_offset..)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use mag_core::client_commands::ClientCommand;
+
+    use super::*;
+
+    fn frame(payload: &[u8], compressed: bool) -> Vec<u8> {
+        let len = (payload.len() + 2) as u16 | if compressed { 0x8000 } else { 0 };
+        let mut out = len.to_le_bytes().to_vec();
+        out.extend_from_slice(payload);
+        out
+    }
+
+    #[test]
+    fn decodes_login_then_plain_and_compressed_frames() {
+        let mut stream = vec![ServerCommandType::LoginOk as u8];
+        stream.extend_from_slice(&[0u8; 15]);
+        stream.extend(frame(&[ServerCommandType::Tick as u8, 7], false));
+
+        let mut zs = ZlibEncoder::new(Vec::new(), Compression::default());
+        zs.write_all(&[ServerCommandType::Tick as u8, 8]).unwrap();
+        zs.flush().unwrap();
+        let first = std::mem::take(zs.get_mut());
+        zs.write_all(&[ServerCommandType::Tick as u8, 9]).unwrap();
+        zs.flush().unwrap();
+        let second = std::mem::take(zs.get_mut());
+        stream.extend(frame(&first, true));
+        stream.extend(frame(&second, true));
+
+        let (messages, err) = decode_stream(&stream, Direction::ServerToClient, true);
+        assert_eq!(err, None);
+        let names: Vec<_> = messages
+            .iter()
+            .map(|m| (m.name.as_str(), m.frame))
+            .collect();
+        assert_eq!(
+            names,
+            [("LoginOk", 0), ("Tick", 1), ("Tick", 2), ("Tick", 3)]
+        );
+    }
+
+    #[test]
+    fn client_commands_decode_and_filter() {
+        let mut stream = ClientCommand::new_inv(0, 5, 0).to_bytes();
+        stream.extend(ClientCommand::new_move(3, 4).to_bytes());
+        stream.extend_from_slice(&[0u8; 5]); // partial trailing command
+
+        let (messages, err) = decode_stream(&stream, Direction::ClientToServer, true);
+        assert_eq!(err, None);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].matches_command("inv"));
+        assert!(messages[0].matches_command("9"));
+        assert!(!messages[1].matches_command("CmdInv"));
+        assert!(messages[0].detail.starts_with("u32=[0, 5, 0]"));
+        assert!(messages[1].matches_player("1"));
+    }
+}
@@ -0,0 +1,421 @@
+//! Per-entity comparison of two world snapshots.
+//!
+//! Used by `mag-admin diff-world` to check what a migration changed, or what
+//! restoring an older snapshot would undo.
+
+use mag_core::constants::{SERVER_MAPX, USE_EMPTY};
+use mag_core::types::{Character, Item, Map};
+use serde::Serialize;
+use server::keydb::snapshot::WorldSnapshot;
+
+/// Which part of the snapshot an entry refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Character,
+    Item,
+    MapTile,
+    CharacterTemplate,
+    ItemTemplate,
+    Effect,
+    Globals,
+    Text,
+}
+
+impl EntityKind {
+    /// Name used on the command line and in JSON output.
+    ///
+    /// # Returns
+    ///
+    /// * The snake_case kind name, e.g. `"map_tile"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntityKind::Character => "character",
+            EntityKind::Item => "item",
+            EntityKind::MapTile => "map_tile",
+            EntityKind::CharacterTemplate => "character_template",
+            EntityKind::ItemTemplate => "item_template",
+            EntityKind::Effect => "effect",
+            EntityKind::Globals => "globals",
+            EntityKind::Text => "text",
+        }
+    }
+}
+
+/// How an entity differs between the two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One entity that differs between the two snapshots.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffEntry {
+    pub kind: EntityKind,
+    /// Slot number (map tiles: `x + y * SERVER_MAPX`).
+    pub index: usize,
+    pub change: ChangeKind,
+    /// Short human-readable name, e.g. `"Gareth"` or `"(12, 40)"`.
+    pub label: String,
+    /// Changed fields as `field: old -> new`.
+    pub details: Vec<String>,
+}
+
+/// Compares two snapshots entity by entity.
+///
+/// Map tiles ignore `ch`, `to_ch` and `dlight`, which follow from character
+/// positions and lighting and would otherwise drown out real changes.
+///
+/// # Arguments
+///
+/// * `old` - The baseline snapshot.
+/// * `new` - The snapshot to compare against it.
+///
+/// # Returns
+///
+/// * All differences, grouped by entity kind and ordered by slot.
+pub fn diff_world(old: &WorldSnapshot, new: &WorldSnapshot) -> Vec<DiffEntry> {
+    let mut out = Vec::new();
+    diff_slots(
+        &mut out,
+        EntityKind::Character,
+        &old.characters,
+        &new.characters,
+        |c| c.used != USE_EMPTY,
+        character_label,
+        character_fields,
+    );
+    diff_slots(
+        &mut out,
+        EntityKind::Item,
+        &old.items,
+        &new.items,
+        |it| it.used != USE_EMPTY,
+        item_label,
+        item_fields,
+    );
+    diff_slots(
+        &mut out,
+        EntityKind::MapTile,
+        &without_occupancy(&old.map),
+        &without_occupancy(&new.map),
+        |_| true,
+        |n, _| tile_label(n),
+        map_fields,
+    );
+    diff_slots(
+        &mut out,
+        EntityKind::CharacterTemplate,
+        &old.character_templates,
+        &new.character_templates,
+        |c| c.used != USE_EMPTY,
+        character_label,
+        character_fields,
+    );
+    diff_slots(
+        &mut out,
+        EntityKind::ItemTemplate,
+        &old.item_templates,
+        &new.item_templates,
+        |it| it.used != USE_EMPTY,
+        item_label,
+        item_fields,
+    );
+    diff_slots(
+        &mut out,
+        EntityKind::Effect,
+        &old.effects,
+        &new.effects,
+        |fx| fx.used != USE_EMPTY,
+        |_, fx| format!("type {}", fx.effect_type),
+        |_, _| Vec::new(),
+    );
+
+    if old.globals != new.globals {
+        out.push(DiffEntry {
+            kind: EntityKind::Globals,
+            index: 0,
+            change: ChangeKind::Changed,
+            label: "globals".to_owned(),
+            details: Vec::new(),
+        });
+    }
+    diff_word_list(&mut out, "bad_names", &old.bad_names, &new.bad_names);
+    diff_word_list(&mut out, "bad_words", &old.bad_words, &new.bad_words);
+    if old.motd != new.motd {
+        out.push(DiffEntry {
+            kind: EntityKind::Text,
+            index: 0,
+            change: ChangeKind::Changed,
+            label: "motd".to_owned(),
+            details: vec![format!("motd: {:?} -> {:?}", old.motd, new.motd)],
+        });
+    }
+    out
+}
+
+/// Compares two slot arrays of the same entity type.
+fn diff_slots<T: PartialEq>(
+    out: &mut Vec<DiffEntry>,
+    kind: EntityKind,
+    old: &[T],
+    new: &[T],
+    used: impl Fn(&T) -> bool,
+    label: impl Fn(usize, &T) -> String,
+    fields: impl Fn(&T, &T) -> Vec<String>,
+) {
+    for n in 0..old.len().max(new.len()) {
+        let (a, b) = (
+            old.get(n).filter(|a| used(a)),
+            new.get(n).filter(|b| used(b)),
+        );
+        let (change, shown, details) = match (a, b) {
+            (None, None) => continue,
+            (None, Some(b)) => (ChangeKind::Added, b, Vec::new()),
+            (Some(a), None) => (ChangeKind::Removed, a, Vec::new()),
+            (Some(a), Some(b)) if a == b => continue,
+            (Some(a), Some(b)) => {
+                let mut details = fields(a, b);
+                if details.is_empty() {
+                    details.push("other fields changed".to_owned());
+                }
+                (ChangeKind::Changed, b, details)
+            }
+        };
+        out.push(DiffEntry {
+            kind,
+            index: n,
+            change,
+            label: label(n, shown),
+            details,
+        });
+    }
+}
+
+fn diff_word_list(out: &mut Vec<DiffEntry>, label: &str, old: &[String], new: &[String]) {
+    let added: Vec<&String> = new.iter().filter(|w| !old.contains(w)).collect();
+    let removed: Vec<&String> = old.iter().filter(|w| !new.contains(w)).collect();
+    for (change, words) in [(ChangeKind::Added, added), (ChangeKind::Removed, removed)] {
+        if !words.is_empty() {
+            out.push(DiffEntry {
+                kind: EntityKind::Text,
+                index: 0,
+                change,
+                label: label.to_owned(),
+                details: words.iter().map(|w| w.to_string()).collect(),
+            });
+        }
+    }
+}
+
+/// Appends `name: old -> new` when the two values differ.
+fn field<V: PartialEq + std::fmt::Debug>(out: &mut Vec<String>, name: &str, old: V, new: V) {
+    if old != new {
+        out.push(format!("{}: {:?} -> {:?}", name, old, new));
+    }
+}
+
+/// Appends one entry per changed slot of an item reference array.
+fn slot_fields(out: &mut Vec<String>, name: &str, old: &[u32], new: &[u32]) {
+    for (n, (a, b)) in old.iter().zip(new).enumerate() {
+        if a != b {
+            out.push(format!("{}[{}]: {} -> {}", name, n, a, b));
+        }
+    }
+}
+
+fn character_label(_n: usize, ch: &Character) -> String {
+    ch.get_name().to_owned()
+}
+
+fn character_fields(a: &Character, b: &Character) -> Vec<String> {
+    let mut out = Vec::new();
+    field(&mut out, "name", a.get_name(), b.get_name());
+    field(&mut out, "used", a.used, b.used);
+    field(&mut out, "temp", a.temp, b.temp);
+    field(&mut out, "pos", (a.x, a.y), (b.x, b.y));
+    field(&mut out, "flags", a.flags, b.flags);
+    field(&mut out, "a_hp", a.a_hp, b.a_hp);
+    field(&mut out, "gold", a.gold, b.gold);
+    field(&mut out, "points_tot", a.points_tot, b.points_tot);
+    field(&mut out, "citem", a.citem, b.citem);
+    slot_fields(&mut out, "item", &a.item, &b.item);
+    slot_fields(&mut out, "worn", &a.worn, &b.worn);
+    slot_fields(&mut out, "spell", &a.spell, &b.spell);
+    if a.attrib != b.attrib || a.hp != b.hp || a.end != b.end || a.mana != b.mana {
+        out.push("attributes changed".to_owned());
+    }
+    if a.skill != b.skill {
+        out.push("skills changed".to_owned());
+    }
+    out
+}
+
+fn item_label(_n: usize, it: &Item) -> String {
+    it.get_name().to_owned()
+}
+
+/// Describes where an item is: on the map, carried, or nowhere.
+fn item_location(it: &Item) -> String {
+    if it.carried != 0 {
+        format!("carried by {}", it.carried)
+    } else if it.x != 0 || it.y != 0 {
+        format!("at ({}, {})", it.x, it.y)
+    } else {
+        "void".to_owned()
+    }
+}
+
+fn item_fields(a: &Item, b: &Item) -> Vec<String> {
+    let mut out = Vec::new();
+    field(&mut out, "name", a.get_name(), b.get_name());
+    field(&mut out, "used", a.used, b.used);
+    field(&mut out, "temp", a.temp, b.temp);
+    let (from, to) = (item_location(a), item_location(b));
+    if from != to {
+        out.push(format!("moved: {} -> {}", from, to));
+    }
+    field(&mut out, "flags", a.flags, b.flags);
+    field(&mut out, "value", a.value, b.value);
+    field(&mut out, "active", a.active, b.active);
+    field(&mut out, "data", a.data, b.data);
+    out
+}
+
+fn tile_label(n: usize) -> String {
+    let mapx = SERVER_MAPX as usize;
+    format!("({}, {})", n % mapx, n / mapx)
+}
+
+/// Copies `map` with the derived `ch`, `to_ch` and `dlight` fields cleared.
+fn without_occupancy(map: &[Map]) -> Vec<Map> {
+    map.iter()
+        .map(|m| Map {
+            ch: 0,
+            to_ch: 0,
+            dlight: 0,
+            ..*m
+        })
+        .collect()
+}
+
+fn map_fields(a: &Map, b: &Map) -> Vec<String> {
+    let mut out = Vec::new();
+    field(&mut out, "sprite", a.sprite, b.sprite);
+    field(&mut out, "fsprite", a.fsprite, b.fsprite);
+    field(&mut out, "it", a.it, b.it);
+    field(&mut out, "light", a.light, b.light);
+    field(&mut out, "flags", a.flags, b.flags);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use mag_core::constants::USE_ACTIVE;
+    use mag_core::types::{Effect, Global};
+
+    use super::*;
+
+    fn snapshot(characters: Vec<Character>, items: Vec<Item>, map: Vec<Map>) -> WorldSnapshot {
+        WorldSnapshot::new(
+            map,
+            items,
+            Vec::new(),
+            characters,
+            Vec::new(),
+            vec![Effect::default()],
+            Global::default(),
+            Vec::new(),
+            vec!["foo".to_owned()],
+            "hello".to_owned(),
+        )
+    }
+
+    #[test]
+    fn reports_moved_items_changed_characters_and_tiles() {
+        let mut ch = Character {
+            used: USE_ACTIVE,
+            ..Default::default()
+        };
+        ch.set_name("Gareth");
+        let mut sword = Item {
+            used: USE_ACTIVE,
+            x: 5,
+            y: 6,
+            ..Default::default()
+        };
+        let old = snapshot(
+            vec![Character::default(), ch],
+            vec![Item::default(), sword],
+            vec![Map::default(); 4],
+        );
+
+        ch.item[0] = 1;
+        sword.x = 0;
+        sword.y = 0;
+        sword.carried = 1;
+        let mut map = vec![Map::default(); 4];
+        map[3].fsprite = 100;
+        map[2].ch = 1; // occupancy is ignored
+        let mut new = snapshot(
+            vec![Character::default(), ch],
+            vec![Item::default(), sword],
+            map,
+        );
+        new.motd = "bye".to_owned();
+
+        let diff = diff_world(&old, &new);
+        let summary: Vec<_> = diff
+            .iter()
+            .map(|d| (d.kind, d.index, d.change, d.details.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    EntityKind::Character,
+                    1,
+                    ChangeKind::Changed,
+                    vec!["item[0]: 0 -> 1".to_owned()]
+                ),
+                (
+                    EntityKind::Item,
+                    1,
+                    ChangeKind::Changed,
+                    vec!["moved: at (5, 6) -> carried by 1".to_owned()]
+                ),
+                (
+                    EntityKind::MapTile,
+                    3,
+                    ChangeKind::Changed,
+                    vec!["fsprite: 0 -> 100".to_owned()]
+                ),
+                (
+                    EntityKind::Text,
+                    0,
+                    ChangeKind::Changed,
+                    vec!["motd: \"hello\" -> \"bye\"".to_owned()]
+                ),
+            ]
+        );
+        assert_eq!(diff[0].label, "Gareth");
+    }
+
+    #[test]
+    fn reports_added_and_removed_slots() {
+        let live = Item {
+            used: USE_ACTIVE,
+            ..Default::default()
+        };
+        let old = snapshot(Vec::new(), vec![live, Item::default()], Vec::new());
+        let new = snapshot(Vec::new(), vec![Item::default(), live], Vec::new());
+
+        let diff = diff_world(&old, &new);
+        let changes: Vec<_> = diff.iter().map(|d| (d.index, d.change)).collect();
+        assert_eq!(changes, [(0, ChangeKind::Removed), (1, ChangeKind::Added)]);
+        assert!(diff_world(&old, &old).is_empty());
+    }
+}
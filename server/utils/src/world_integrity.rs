@@ -0,0 +1,594 @@
+//! Nightly world integrity report.
+//!
+//! Used by `mag-admin integrity-report`, which is meant to run from cron once
+//! a night against the persisted world. The report lists:
+//!
+//! * items that are not where they claim to be (orphans),
+//! * item ids referenced from more than one tile or character slot,
+//! * player characters whose stats cannot be reached in play,
+//! * map tiles whose item or character comes from a missing template,
+//! * gold held by players and NPCs.
+//!
+//! The latest report is kept as JSON in [`INTEGRITY_REPORT_KEY`]; each run is
+//! also kept for [`INTEGRITY_HISTORY_TTL_SECS`] under a timestamped key so a
+//! sudden jump can be compared against previous nights.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use mag_core::constants::{SERVER_MAPX, USE_EMPTY};
+use mag_core::types::{Character, Item};
+use redis::Commands;
+use serde::Serialize;
+use server::keydb::snapshot::WorldSnapshot;
+
+/// KeyDB key holding the latest JSON-encoded [`IntegrityReport`].
+pub const INTEGRITY_REPORT_KEY: &str = "game:admin:integrity_report";
+
+/// How long the per-run copies under `INTEGRITY_REPORT_KEY:<unix secs>` live.
+pub const INTEGRITY_HISTORY_TTL_SECS: u64 = 30 * 24 * 3600;
+
+/// Number of richest players listed in [`GoldTotals::richest`].
+pub const RICHEST_PLAYERS: usize = 10;
+
+/// Examples per category included in the Discord summary.
+const SUMMARY_EXAMPLES: usize = 5;
+
+/// Discord rejects messages longer than this.
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// An item that is not referenced from where it says it is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrphanedItem {
+    pub item: usize,
+    pub name: String,
+    pub problem: String,
+}
+
+/// An item id referenced from more than one place.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateItem {
+    pub item: usize,
+    pub name: String,
+    /// Every tile or character slot holding the id.
+    pub holders: Vec<String>,
+}
+
+/// A player character with stats that cannot be reached in play.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImpossibleStats {
+    pub character: usize,
+    pub name: String,
+    pub problems: Vec<String>,
+}
+
+/// A map tile whose item or character is gone or has no template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingTemplateTile {
+    pub x: usize,
+    pub y: usize,
+    pub problem: String,
+}
+
+/// One entry of [`GoldTotals::richest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GoldHolder {
+    pub character: usize,
+    pub name: String,
+    pub gold: i64,
+}
+
+/// Gold held by characters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GoldTotals {
+    pub players: usize,
+    pub player_gold: i64,
+    pub npc_gold: i64,
+    pub richest: Vec<GoldHolder>,
+}
+
+/// Result of [`check_world`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IntegrityReport {
+    /// When the report was built (seconds since Unix epoch).
+    pub generated_at: i64,
+    /// When the checked world data was taken (seconds since Unix epoch).
+    pub world_created_at: i64,
+    pub orphaned_items: Vec<OrphanedItem>,
+    pub duplicate_items: Vec<DuplicateItem>,
+    pub impossible_stats: Vec<ImpossibleStats>,
+    pub missing_templates: Vec<MissingTemplateTile>,
+    pub gold: GoldTotals,
+}
+
+impl IntegrityReport {
+    /// Total number of problems found.
+    pub fn issue_count(&self) -> usize {
+        self.orphaned_items.len()
+            + self.duplicate_items.len()
+            + self.impossible_stats.len()
+            + self.missing_templates.len()
+    }
+
+    /// Short text summary, sized for a Discord message.
+    ///
+    /// # Returns
+    ///
+    /// * Counts per category, a few examples of each and the gold totals.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "**World integrity report**: {} issue(s)",
+            self.issue_count()
+        );
+        summary_section(
+            &mut out,
+            "Orphaned items",
+            self.orphaned_items
+                .iter()
+                .map(|o| format!("#{} {}: {}", o.item, o.name, o.problem)),
+        );
+        summary_section(
+            &mut out,
+            "Duplicate item ids",
+            self.duplicate_items
+                .iter()
+                .map(|d| format!("#{} {}: {}", d.item, d.name, d.holders.join(", "))),
+        );
+        summary_section(
+            &mut out,
+            "Impossible stats",
+            self.impossible_stats
+                .iter()
+                .map(|s| format!("#{} {}: {}", s.character, s.name, s.problems.join(", "))),
+        );
+        summary_section(
+            &mut out,
+            "Tiles with missing templates",
+            self.missing_templates
+                .iter()
+                .map(|t| format!("({}, {}): {}", t.x, t.y, t.problem)),
+        );
+        let _ = writeln!(
+            out,
+            "Gold: {} held by {} players, {} by NPCs",
+            self.gold.player_gold, self.gold.players, self.gold.npc_gold
+        );
+        if let Some(top) = self.gold.richest.first() {
+            let _ = writeln!(out, "Richest: {} ({})", top.name, top.gold);
+        }
+        if out.len() > DISCORD_MESSAGE_LIMIT {
+            let mut end = DISCORD_MESSAGE_LIMIT - 3;
+            while !out.is_char_boundary(end) {
+                end -= 1;
+            }
+            out.truncate(end);
+            out.push_str("...");
+        }
+        out
+    }
+}
+
+fn summary_section(out: &mut String, title: &str, lines: impl ExactSizeIterator<Item = String>) {
+    let count = lines.len();
+    let _ = writeln!(out, "{title}: {count}");
+    for line in lines.take(SUMMARY_EXAMPLES) {
+        let _ = writeln!(out, "- {line}");
+    }
+    if count > SUMMARY_EXAMPLES {
+        let _ = writeln!(out, "- ... and {} more", count - SUMMARY_EXAMPLES);
+    }
+}
+
+/// A tile or character slot referencing an item id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Holder {
+    /// Map tile index (`x + y * SERVER_MAPX`).
+    Tile(usize),
+    /// Character number and the slot array holding the id.
+    Character(usize, &'static str),
+}
+
+fn tile_xy(n: usize) -> (usize, usize) {
+    let mapx = SERVER_MAPX as usize;
+    (n % mapx, n / mapx)
+}
+
+fn holder_label(world: &WorldSnapshot, holder: Holder) -> String {
+    match holder {
+        Holder::Tile(n) => {
+            let (x, y) = tile_xy(n);
+            format!("tile ({x}, {y})")
+        }
+        Holder::Character(cn, slot) => {
+            format!("{} #{cn} {slot}", world.characters[cn].get_name())
+        }
+    }
+}
+
+/// Collects every reference to an item id from map tiles and characters.
+fn item_holders(world: &WorldSnapshot) -> HashMap<usize, Vec<Holder>> {
+    let mut holders: HashMap<usize, Vec<Holder>> = HashMap::new();
+    let item_count = world.items.len();
+    let mut add = |id: u32, holder: Holder| {
+        let id = id as usize;
+        if id != 0 && id < item_count {
+            holders.entry(id).or_default().push(holder);
+        }
+    };
+    for (n, tile) in world.map.iter().enumerate() {
+        add(tile.it, Holder::Tile(n));
+    }
+    for (cn, ch) in world.characters.iter().enumerate() {
+        if ch.used == USE_EMPTY {
+            continue;
+        }
+        for &id in &ch.item {
+            add(id, Holder::Character(cn, "inventory"));
+        }
+        for &id in &ch.worn {
+            add(id, Holder::Character(cn, "worn"));
+        }
+        for &id in &ch.spell {
+            add(id, Holder::Character(cn, "spell"));
+        }
+        for &id in &ch.depot {
+            add(id, Holder::Character(cn, "depot"));
+        }
+        // Set high bit means the cursor holds money, not an item.
+        if ch.citem & 0x8000_0000 == 0 {
+            add(ch.citem, Holder::Character(cn, "cursor"));
+        }
+    }
+    holders
+}
+
+/// Explains why `it` is not where it claims to be, if it is not.
+fn orphan_problem(world: &WorldSnapshot, it: &Item, holders: &[Holder]) -> Option<String> {
+    if it.carried != 0 {
+        let cn = it.carried as usize;
+        let carrier = world.characters.get(cn).filter(|ch| ch.used != USE_EMPTY);
+        return match carrier {
+            None => Some(format!("carried by empty character slot {cn}")),
+            Some(_)
+                if holders
+                    .iter()
+                    .any(|h| matches!(h, Holder::Character(c, _) if *c == cn)) =>
+            {
+                None
+            }
+            Some(ch) => Some(format!(
+                "carried by {} #{cn} but not in any of their slots",
+                ch.get_name()
+            )),
+        };
+    }
+    if it.x == 0 && it.y == 0 {
+        return Some("in the void".to_owned());
+    }
+    let tile = it.x as usize + it.y as usize * SERVER_MAPX as usize;
+    if holders.contains(&Holder::Tile(tile)) {
+        None
+    } else {
+        Some(format!(
+            "at ({}, {}) but that tile does not hold it",
+            it.x, it.y
+        ))
+    }
+}
+
+/// Returns what is impossible about a player's stats.
+fn stat_problems(ch: &Character) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut check = |what: String, base: u16, max: u16| {
+        if max != 0 && base > max {
+            out.push(format!("{what} base {base} above max {max}"));
+        }
+    };
+    for (n, attrib) in ch.attrib.iter().enumerate() {
+        check(
+            format!("attribute {n}"),
+            u16::from(attrib[0]),
+            u16::from(attrib[2]),
+        );
+    }
+    check("hp".to_owned(), ch.hp[0], ch.hp[2]);
+    check("endurance".to_owned(), ch.end[0], ch.end[2]);
+    check("mana".to_owned(), ch.mana[0], ch.mana[2]);
+    for (n, skill) in ch.skill.iter().enumerate() {
+        check(
+            format!("skill {n}"),
+            u16::from(skill[0]),
+            u16::from(skill[2]),
+        );
+    }
+    if ch.gold < 0 {
+        out.push(format!("negative gold {}", ch.gold));
+    }
+    if ch.points < 0 || ch.points_tot < 0 {
+        out.push(format!(
+            "negative points {} / total {}",
+            ch.points, ch.points_tot
+        ));
+    }
+    out
+}
+
+/// Explains what is missing behind a map tile's item and character.
+fn tile_problems(world: &WorldSnapshot, n: usize) -> Vec<String> {
+    let tile = &world.map[n];
+    let mut out = Vec::new();
+    if tile.it != 0 {
+        match world.items.get(tile.it as usize) {
+            Some(it) if it.used != USE_EMPTY => {
+                let temp = it.temp as usize;
+                let known = world
+                    .item_templates
+                    .get(temp)
+                    .is_some_and(|t| t.used != USE_EMPTY);
+                if temp != 0 && !known {
+                    out.push(format!(
+                        "item #{} {} uses missing item template {temp}",
+                        tile.it,
+                        it.get_name()
+                    ));
+                }
+            }
+            _ => out.push(format!("item #{} does not exist", tile.it)),
+        }
+    }
+    if tile.ch != 0 {
+        match world.characters.get(tile.ch as usize) {
+            Some(ch) if ch.used != USE_EMPTY => {
+                let temp = ch.temp as usize;
+                let known = world
+                    .character_templates
+                    .get(temp)
+                    .is_some_and(|t| t.used != USE_EMPTY);
+                if temp != 0 && !known {
+                    out.push(format!(
+                        "character #{} {} uses missing character template {temp}",
+                        tile.ch,
+                        ch.get_name()
+                    ));
+                }
+            }
+            _ => out.push(format!("character #{} does not exist", tile.ch)),
+        }
+    }
+    out
+}
+
+/// Runs every integrity check against a world.
+///
+/// # Arguments
+///
+/// * `world` - World data loaded from storage or a snapshot file.
+///
+/// # Returns
+///
+/// * The report, each list ordered by item, character or tile number.
+pub fn check_world(world: &WorldSnapshot) -> IntegrityReport {
+    let holders = item_holders(world);
+    let mut report = IntegrityReport {
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0),
+        world_created_at: world.created_unix_secs,
+        ..Default::default()
+    };
+
+    for (n, it) in world.items.iter().enumerate().skip(1) {
+        if it.used == USE_EMPTY {
+            continue;
+        }
+        let refs = holders.get(&n).map(Vec::as_slice).unwrap_or_default();
+        if let Some(problem) = orphan_problem(world, it, refs) {
+            report.orphaned_items.push(OrphanedItem {
+                item: n,
+                name: it.get_name().to_owned(),
+                problem,
+            });
+        }
+        if refs.len() > 1 {
+            report.duplicate_items.push(DuplicateItem {
+                item: n,
+                name: it.get_name().to_owned(),
+                holders: refs.iter().map(|&h| holder_label(world, h)).collect(),
+            });
+        }
+    }
+
+    for (cn, ch) in world.characters.iter().enumerate() {
+        if ch.used == USE_EMPTY {
+            continue;
+        }
+        if !ch.is_player() {
+            report.gold.npc_gold += i64::from(ch.gold);
+            continue;
+        }
+        report.gold.players += 1;
+        report.gold.player_gold += i64::from(ch.gold);
+        report.gold.richest.push(GoldHolder {
+            character: cn,
+            name: ch.get_name().to_owned(),
+            gold: i64::from(ch.gold),
+        });
+        let problems = stat_problems(ch);
+        if !problems.is_empty() {
+            report.impossible_stats.push(ImpossibleStats {
+                character: cn,
+                name: ch.get_name().to_owned(),
+                problems,
+            });
+        }
+    }
+    report
+        .gold
+        .richest
+        .sort_by(|a, b| b.gold.cmp(&a.gold).then(a.character.cmp(&b.character)));
+    report.gold.richest.truncate(RICHEST_PLAYERS);
+
+    for n in 0..world.map.len() {
+        let (x, y) = tile_xy(n);
+        for problem in tile_problems(world, n) {
+            report
+                .missing_templates
+                .push(MissingTemplateTile { x, y, problem });
+        }
+    }
+    report
+}
+
+/// Writes a report to [`INTEGRITY_REPORT_KEY`] and its history key.
+///
+/// # Arguments
+///
+/// * `con` - Open KeyDB connection.
+/// * `report` - The report to store.
+///
+/// # Returns
+///
+/// * `Ok(())` once both keys are written.
+pub fn store_report(con: &mut redis::Connection, report: &IntegrityReport) -> Result<(), String> {
+    let json = serde_json::to_string(report)
+        .map_err(|e| format!("Failed to encode integrity report: {e}"))?;
+    let history_key = format!("{INTEGRITY_REPORT_KEY}:{}", report.generated_at);
+    con.set::<_, _, ()>(INTEGRITY_REPORT_KEY, &json)
+        .map_err(|e| format!("KeyDB SET {INTEGRITY_REPORT_KEY}: {e}"))?;
+    con.set_ex::<_, _, ()>(&history_key, &json, INTEGRITY_HISTORY_TTL_SECS)
+        .map_err(|e| format!("KeyDB SET {history_key}: {e}"))
+}
+
+/// Posts [`IntegrityReport::summary`] to a Discord webhook.
+///
+/// # Arguments
+///
+/// * `url` - Discord webhook URL.
+/// * `report` - The report to announce.
+///
+/// # Returns
+///
+/// * `Ok(())` if Discord accepted the message.
+pub fn post_to_webhook(url: &str, report: &IntegrityReport) -> Result<(), String> {
+    let response = reqwest::blocking::Client::new()
+        .post(url)
+        .json(&serde_json::json!({ "content": report.summary() }))
+        .send()
+        .map_err(|e| format!("Failed to post integrity report to webhook: {e}"))?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Webhook rejected integrity report with status {}",
+            response.status()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mag_core::constants::{CharacterFlags, USE_ACTIVE};
+    use mag_core::types::{Effect, Global, Map};
+
+    use super::*;
+
+    fn world(characters: Vec<Character>, items: Vec<Item>, map: Vec<Map>) -> WorldSnapshot {
+        let template = Item {
+            used: USE_ACTIVE,
+            ..Default::default()
+        };
+        WorldSnapshot::new(
+            map,
+            items,
+            vec![Item::default(), template],
+            characters,
+            Vec::new(),
+            vec![Effect::default()],
+            Global::default(),
+            Vec::new(),
+            Vec::new(),
+            String::new(),
+        )
+    }
+
+    fn item(temp: u16) -> Item {
+        Item {
+            used: USE_ACTIVE,
+            temp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_orphans_duplicates_and_missing_templates() {
+        let mut player = Character {
+            used: USE_ACTIVE,
+            flags: CharacterFlags::Player.bits(),
+            gold: 250,
+            ..Default::default()
+        };
+        player.set_name("Gareth");
+        player.item[0] = 1;
+        player.item[1] = 2;
+
+        let mut held = item(1);
+        held.carried = 1;
+        let mut on_floor = item(9);
+        on_floor.x = 3;
+        on_floor.name[..5].copy_from_slice(b"Torch");
+        let lost = item(1);
+
+        let mut map = vec![Map::default(); 4];
+        map[3].it = 2; // also in Gareth's inventory
+        map[2].it = 5; // no such item
+
+        let report = check_world(&world(
+            vec![Character::default(), player],
+            vec![Item::default(), held, on_floor, lost],
+            map,
+        ));
+
+        let orphans: Vec<_> = report.orphaned_items.iter().map(|o| o.item).collect();
+        assert_eq!(orphans, [3]);
+        assert_eq!(report.orphaned_items[0].problem, "in the void");
+        assert_eq!(report.duplicate_items.len(), 1);
+        assert_eq!(
+            report.duplicate_items[0].holders,
+            ["tile (3, 0)", "Gareth #1 inventory"]
+        );
+        let tiles: Vec<_> = report
+            .missing_templates
+            .iter()
+            .map(|t| (t.x, t.problem.as_str()))
+            .collect();
+        assert_eq!(
+            tiles,
+            [
+                (2, "item #5 does not exist"),
+                (3, "item #2 Torch uses missing item template 9"),
+            ]
+        );
+        assert_eq!(report.gold.player_gold, 250);
+        assert_eq!(report.issue_count(), 4);
+    }
+
+    #[test]
+    fn flags_stats_above_their_maximum() {
+        let mut ch = Character {
+            used: USE_ACTIVE,
+            gold: -5,
+            ..Default::default()
+        };
+        ch.attrib[1] = [120, 0, 100, 0, 0, 120];
+        ch.hp[0] = 50;
+        ch.hp[2] = 60;
+        assert_eq!(
+            stat_problems(&ch),
+            ["attribute 1 base 120 above max 100", "negative gold -5"]
+        );
+    }
+}
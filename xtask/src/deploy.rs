@@ -0,0 +1,584 @@
+//! Deployment manifests for the server, API and KeyDB.
+//!
+//! `gen-deploy` renders either a docker-compose file or Kubernetes manifests
+//! from the same settings the server reads at runtime (`.env` overridden by
+//! the process environment): API port, health port, storage backend and the
+//! KeyDB wait timeout. Secrets are never written out; compose interpolates
+//! them from the environment and Kubernetes reads them from the `mag-secrets`
+//! and `mag-tls` secrets.
+//!
+//! Both targets wire the same checks: KeyDB is ready once
+//! `INFO persistence` reports `loading:0`, the API answers `/readyz` once
+//! KeyDB is usable, and the server answers `/readyz` on `MAG_HEALTH_PORT`
+//! once the world is loaded. The server also waits for KeyDB itself
+//! (`MAG_KEYDB_WAIT_SECS`) rather than relying on start ordering.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+
+use crate::dev;
+use crate::stage::Workspace;
+
+/// Port the game server listens on (fixed in `server::Server::initialize`).
+const GAME_PORT: u16 = 5555;
+
+/// Port KeyDB listens on inside the deployment.
+const KEYDB_PORT: u16 = 5556;
+
+/// Arguments for `cargo xtask gen-deploy`.
+#[derive(clap::Args, Debug)]
+pub(crate) struct GenDeployArgs {
+    /// Manifest flavour to generate.
+    #[arg(long, value_enum)]
+    target: DeployTarget,
+
+    /// Output directory, relative to the workspace root.
+    #[arg(long, default_value = "deploy")]
+    out: PathBuf,
+
+    /// Image registry/prefix; images are `<registry>/server` and `<registry>/api`.
+    #[arg(long, default_value = "ghcr.io/engineerjames/men-among-gods")]
+    registry: String,
+
+    /// Image tag.
+    #[arg(long, default_value = "latest")]
+    tag: String,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum DeployTarget {
+    Compose,
+    Kubernetes,
+}
+
+/// Deployment settings derived from the server/API environment.
+#[derive(Debug, PartialEq, Eq)]
+struct DeployConfig {
+    api_port: u16,
+    health_port: u16,
+    keydb_wait_secs: u64,
+    /// `Some(path)` when the server persists to SQLite instead of KeyDB.
+    sqlite_path: Option<String>,
+    server_image: String,
+    api_image: String,
+}
+
+impl DeployConfig {
+    fn from_env(env: &BTreeMap<String, String>, registry: &str, tag: &str) -> Result<Self> {
+        let sqlite_path = match env
+            .get("MAG_STORAGE_BACKEND")
+            .map(|value| value.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            None | Some("") | Some("keydb") => None,
+            Some("sqlite") => Some(
+                env.get("MAG_SQLITE_PATH")
+                    .filter(|path| !path.is_empty())
+                    .map_or("/data/game.sqlite3", String::as_str)
+                    .to_owned(),
+            ),
+            Some(other) => bail!("unknown MAG_STORAGE_BACKEND '{other}'"),
+        };
+        let registry = registry.trim_end_matches('/');
+        Ok(Self {
+            api_port: parse_or(env, "API_PORT", 5554)?,
+            health_port: parse_or(env, "MAG_HEALTH_PORT", 8080)?,
+            keydb_wait_secs: parse_or(env, "MAG_KEYDB_WAIT_SECS", 120)?,
+            sqlite_path,
+            server_image: format!("{registry}/server:{tag}"),
+            api_image: format!("{registry}/api:{tag}"),
+        })
+    }
+}
+
+fn parse_or<T: std::str::FromStr>(
+    env: &BTreeMap<String, String>,
+    name: &str,
+    default: T,
+) -> Result<T> {
+    match env.get(name).map(|value| value.trim()) {
+        None | Some("") => Ok(default),
+        Some(value) => value
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid {name} '{value}'")),
+    }
+}
+
+/// Render the manifests for `args.target` into `args.out`.
+pub(crate) fn generate(workspace: &Workspace, args: &GenDeployArgs) -> Result<()> {
+    let config = DeployConfig::from_env(&dev::project_env(workspace), &args.registry, &args.tag)?;
+    let (file, contents) = match args.target {
+        DeployTarget::Compose => ("docker-compose.yml", compose(&config)),
+        DeployTarget::Kubernetes => ("kubernetes.yaml", kubernetes(&config)),
+    };
+    let dir = workspace.root().join(&args.out);
+    std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+    let path = dir.join(file);
+    std::fs::write(&path, contents).with_context(|| format!("writing {}", path.display()))?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+const HEADER: &str = "# Generated by `cargo xtask gen-deploy`; regenerate instead of editing.\n";
+
+/// KeyDB readiness: `PING` succeeds while the dataset is still loading.
+const KEYDB_READY: &str =
+    "keydb-cli -p 5556 -a \"$KEYDB_PASSWORD\" INFO persistence | grep -q 'loading:0'";
+
+fn compose(config: &DeployConfig) -> String {
+    let keydb_url =
+        format!("redis://:${{KEYDB_PASSWORD:?KEYDB_PASSWORD is required}}@keydb:{KEYDB_PORT}/");
+    let mut out = String::from(HEADER);
+    let _ = write!(
+        out,
+        r#"services:
+  certgen:
+    image: alpine:3.20
+    entrypoint: ["/bin/sh", "-c"]
+    command:
+      - test -f /certs/server.crt || (apk add --no-cache openssl >/dev/null && openssl req -x509 -newkey rsa:2048 -nodes -days 825 -subj /CN=localhost -keyout /certs/server.key -out /certs/server.crt)
+    volumes:
+      - tls-certs:/certs
+    restart: "no"
+
+  keydb:
+    image: eqalpha/keydb:latest
+    command: ["keydb-server", "--port", "{KEYDB_PORT}", "--requirepass", "${{KEYDB_PASSWORD:?KEYDB_PASSWORD is required}}", "--save", "900", "1"]
+    environment:
+      KEYDB_PASSWORD: ${{KEYDB_PASSWORD:?KEYDB_PASSWORD is required}}
+    volumes:
+      - keydb-data:/data
+    healthcheck:
+      test: ["CMD-SHELL", "{keydb_ready}"]
+      interval: 5s
+      timeout: 5s
+      retries: 20
+      start_period: 10s
+    restart: unless-stopped
+"#,
+        keydb_ready = KEYDB_READY.replace('"', "\\\"").replace('$', "$$"),
+    );
+
+    if config.sqlite_path.is_none() {
+        let _ = write!(
+            out,
+            r#"
+  server-seed:
+    image: {server_image}
+    depends_on:
+      keydb:
+        condition: service_healthy
+    environment:
+      MAG_KEYDB_URL: {keydb_url}
+    command: ["/app/world-snapshot", "import", "--skip-if-seeded", "--input", "/app/world_seed.wsnap"]
+    restart: "no"
+"#,
+            server_image = config.server_image,
+        );
+    }
+
+    let _ = write!(
+        out,
+        r#"
+  api:
+    image: {api_image}
+    depends_on:
+      certgen:
+        condition: service_completed_successfully
+      keydb:
+        condition: service_healthy
+    environment:
+      API_JWT_SECRET: ${{API_JWT_SECRET:?API_JWT_SECRET is required}}
+      API_BIND_ADDR: 0.0.0.0
+      API_PORT: {api_port}
+      MAG_KEYDB_URL: {keydb_url}
+      API_TLS_CERT: /certs/server.crt
+      API_TLS_KEY: /certs/server.key
+      MAG_ADMIN_API_TOKEN: ${{MAG_ADMIN_API_TOKEN:-}}
+    volumes:
+      - tls-certs:/certs:ro
+    ports:
+      - "{api_port}:{api_port}"
+    healthcheck:
+      test: ["CMD", "curl", "-fsk", "https://127.0.0.1:{api_port}/readyz"]
+      interval: 10s
+      timeout: 5s
+      retries: 6
+    restart: unless-stopped
+
+  server:
+    image: {server_image}
+    depends_on:
+      certgen:
+        condition: service_completed_successfully
+"#,
+        api_image = config.api_image,
+        api_port = config.api_port,
+        server_image = config.server_image,
+    );
+    if config.sqlite_path.is_none() {
+        out.push_str("      server-seed:\n        condition: service_completed_successfully\n");
+    }
+    let _ = write!(
+        out,
+        r#"    environment:
+      MAG_KEYDB_URL: {keydb_url}
+      MAG_KEYDB_WAIT_SECS: {wait}
+      MAG_HEALTH_PORT: {health_port}
+      SERVER_TLS_CERT: /certs/server.crt
+      SERVER_TLS_KEY: /certs/server.key
+      MAG_GOD_PASSWORD: ${{MAG_GOD_PASSWORD:?MAG_GOD_PASSWORD is required}}
+      MAG_ADMIN_API_TOKEN: ${{MAG_ADMIN_API_TOKEN:-}}
+"#,
+        wait = config.keydb_wait_secs,
+        health_port = config.health_port,
+    );
+    if let Some(path) = &config.sqlite_path {
+        let _ = write!(
+            out,
+            "      MAG_STORAGE_BACKEND: sqlite\n      MAG_SQLITE_PATH: {path}\n      MAG_SQLITE_SEED: /app/world_seed.wsnap\n"
+        );
+    }
+    out.push_str("    volumes:\n      - tls-certs:/certs:ro\n");
+    if config.sqlite_path.is_some() {
+        out.push_str("      - server-data:/data\n");
+    }
+    let _ = write!(
+        out,
+        r#"    ports:
+      - "{GAME_PORT}:{GAME_PORT}"
+    healthcheck:
+      test: ["CMD", "curl", "-fs", "http://127.0.0.1:{health_port}/readyz"]
+      interval: 10s
+      timeout: 5s
+      retries: 6
+      start_period: 60s
+    stop_signal: SIGINT
+    stop_grace_period: 2m
+    restart: unless-stopped
+
+volumes:
+  keydb-data:
+  tls-certs:
+"#,
+        health_port = config.health_port,
+    );
+    if config.sqlite_path.is_some() {
+        out.push_str("  server-data:\n");
+    }
+    out
+}
+
+fn kubernetes(config: &DeployConfig) -> String {
+    let keydb_url = format!("redis://:$(KEYDB_PASSWORD)@keydb:{KEYDB_PORT}/");
+    let keydb_password = secret_env("KEYDB_PASSWORD", "keydb-password");
+    let mut out = String::from(HEADER);
+    out.push_str(
+        "# Expects secrets `mag-secrets` (keydb-password, api-jwt-secret, god-password,\n\
+         # optional admin-api-token) and `mag-tls` (a kubernetes.io/tls secret).\n",
+    );
+
+    let _ = write!(
+        out,
+        r#"---
+apiVersion: v1
+kind: Service
+metadata:
+  name: keydb
+spec:
+  selector:
+    app: keydb
+  ports:
+    - port: {KEYDB_PORT}
+---
+apiVersion: apps/v1
+kind: StatefulSet
+metadata:
+  name: keydb
+spec:
+  serviceName: keydb
+  replicas: 1
+  selector:
+    matchLabels:
+      app: keydb
+  template:
+    metadata:
+      labels:
+        app: keydb
+    spec:
+      containers:
+        - name: keydb
+          image: eqalpha/keydb:latest
+          args: ["keydb-server", "--port", "{KEYDB_PORT}", "--requirepass", "$(KEYDB_PASSWORD)", "--save", "900", "1"]
+          env:
+{keydb_password}          ports:
+            - containerPort: {KEYDB_PORT}
+          readinessProbe:
+            exec:
+              command: ["/bin/sh", "-c", "{keydb_ready}"]
+            periodSeconds: 5
+          volumeMounts:
+            - name: keydb-data
+              mountPath: /data
+  volumeClaimTemplates:
+    - metadata:
+        name: keydb-data
+      spec:
+        accessModes: ["ReadWriteOnce"]
+        resources:
+          requests:
+            storage: 2Gi
+"#,
+        keydb_ready = KEYDB_READY.replace('"', "\\\""),
+    );
+
+    if config.sqlite_path.is_none() {
+        let _ = write!(
+            out,
+            r#"---
+apiVersion: batch/v1
+kind: Job
+metadata:
+  name: world-seed
+spec:
+  backoffLimit: 10
+  template:
+    spec:
+      restartPolicy: OnFailure
+      containers:
+        - name: world-seed
+          image: {server_image}
+          command: ["/app/world-snapshot", "import", "--skip-if-seeded", "--input", "/app/world_seed.wsnap"]
+          env:
+{keydb_password}            - name: MAG_KEYDB_URL
+              value: "{keydb_url}"
+"#,
+            server_image = config.server_image,
+        );
+    }
+
+    let _ = write!(
+        out,
+        r#"---
+apiVersion: v1
+kind: Service
+metadata:
+  name: api
+spec:
+  type: LoadBalancer
+  selector:
+    app: api
+  ports:
+    - port: {api_port}
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: api
+spec:
+  replicas: 1
+  selector:
+    matchLabels:
+      app: api
+  template:
+    metadata:
+      labels:
+        app: api
+    spec:
+      containers:
+        - name: api
+          image: {api_image}
+          env:
+{keydb_password}{jwt}{admin}            - name: API_BIND_ADDR
+              value: "0.0.0.0"
+            - name: API_PORT
+              value: "{api_port}"
+            - name: MAG_KEYDB_URL
+              value: "{keydb_url}"
+            - name: API_TLS_CERT
+              value: /certs/tls.crt
+            - name: API_TLS_KEY
+              value: /certs/tls.key
+          ports:
+            - containerPort: {api_port}
+          livenessProbe:
+            httpGet:
+              path: /healthz
+              port: {api_port}
+              scheme: HTTPS
+          readinessProbe:
+            httpGet:
+              path: /readyz
+              port: {api_port}
+              scheme: HTTPS
+          volumeMounts:
+            - name: tls
+              mountPath: /certs
+              readOnly: true
+      volumes:
+        - name: tls
+          secret:
+            secretName: mag-tls
+"#,
+        api_port = config.api_port,
+        api_image = config.api_image,
+        jwt = secret_env("API_JWT_SECRET", "api-jwt-secret"),
+        admin = optional_secret_env("MAG_ADMIN_API_TOKEN", "admin-api-token"),
+    );
+
+    if config.sqlite_path.is_some() {
+        out.push_str(
+            r#"---
+apiVersion: v1
+kind: PersistentVolumeClaim
+metadata:
+  name: server-data
+spec:
+  accessModes: ["ReadWriteOnce"]
+  resources:
+    requests:
+      storage: 1Gi
+"#,
+        );
+    }
+
+    let mut server_env = format!(
+        "{keydb_password}{god}{admin}            - name: MAG_KEYDB_URL\n              value: \"{keydb_url}\"\n            - name: MAG_KEYDB_WAIT_SECS\n              value: \"{wait}\"\n            - name: MAG_HEALTH_PORT\n              value: \"{health}\"\n            - name: SERVER_TLS_CERT\n              value: /certs/tls.crt\n            - name: SERVER_TLS_KEY\n              value: /certs/tls.key\n",
+        god = secret_env("MAG_GOD_PASSWORD", "god-password"),
+        admin = optional_secret_env("MAG_ADMIN_API_TOKEN", "admin-api-token"),
+        wait = config.keydb_wait_secs,
+        health = config.health_port,
+    );
+    let mut mounts = String::from(
+        "            - name: tls\n              mountPath: /certs\n              readOnly: true\n",
+    );
+    let mut volumes =
+        String::from("        - name: tls\n          secret:\n            secretName: mag-tls\n");
+    if let Some(path) = &config.sqlite_path {
+        let _ = write!(
+            server_env,
+            "            - name: MAG_STORAGE_BACKEND\n              value: sqlite\n            - name: MAG_SQLITE_PATH\n              value: \"{path}\"\n            - name: MAG_SQLITE_SEED\n              value: /app/world_seed.wsnap\n"
+        );
+        mounts.push_str("            - name: data\n              mountPath: /data\n");
+        volumes.push_str(
+            "        - name: data\n          persistentVolumeClaim:\n            claimName: server-data\n",
+        );
+    }
+
+    let _ = write!(
+        out,
+        r#"---
+apiVersion: v1
+kind: Service
+metadata:
+  name: server
+spec:
+  type: LoadBalancer
+  selector:
+    app: server
+  ports:
+    - port: {GAME_PORT}
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: server
+spec:
+  # One world per server; never run two instances against the same data.
+  replicas: 1
+  strategy:
+    type: Recreate
+  selector:
+    matchLabels:
+      app: server
+  template:
+    metadata:
+      labels:
+        app: server
+    spec:
+      terminationGracePeriodSeconds: 120
+      containers:
+        - name: server
+          image: {server_image}
+          env:
+{server_env}          ports:
+            - containerPort: {GAME_PORT}
+            - containerPort: {health_port}
+          startupProbe:
+            httpGet:
+              path: /healthz
+              port: {health_port}
+            failureThreshold: 30
+            periodSeconds: 5
+          livenessProbe:
+            httpGet:
+              path: /healthz
+              port: {health_port}
+          readinessProbe:
+            httpGet:
+              path: /readyz
+              port: {health_port}
+          volumeMounts:
+{mounts}      volumes:
+{volumes}"#,
+        server_image = config.server_image,
+        health_port = config.health_port,
+    );
+    out
+}
+
+fn secret_env(name: &str, key: &str) -> String {
+    format!(
+        "            - name: {name}\n              valueFrom:\n                secretKeyRef:\n                  name: mag-secrets\n                  key: {key}\n"
+    )
+}
+
+fn optional_secret_env(name: &str, key: &str) -> String {
+    format!(
+        "            - name: {name}\n              valueFrom:\n                secretKeyRef:\n                  name: mag-secrets\n                  key: {key}\n                  optional: true\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pairs: &[(&str, &str)]) -> DeployConfig {
+        let env = pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect();
+        DeployConfig::from_env(&env, "registry.example/", "v1").unwrap()
+    }
+
+    #[test]
+    fn config_reads_ports_and_backend() {
+        let keydb = config(&[("API_PORT", "6000")]);
+        assert_eq!(keydb.api_port, 6000);
+        assert_eq!(keydb.health_port, 8080);
+        assert_eq!(keydb.sqlite_path, None);
+        assert_eq!(keydb.server_image, "registry.example/server:v1");
+
+        let sqlite = config(&[("MAG_STORAGE_BACKEND", "SQLite")]);
+        assert_eq!(sqlite.sqlite_path.as_deref(), Some("/data/game.sqlite3"));
+    }
+
+    #[test]
+    fn manifests_wire_readiness_and_persistence() {
+        let keydb = config(&[]);
+        let compose_keydb = compose(&keydb);
+        assert!(compose_keydb.contains("server-seed:"));
+        assert!(compose_keydb.contains("http://127.0.0.1:8080/readyz"));
+        assert!(!compose_keydb.contains("server-data"));
+
+        let sqlite = config(&[("MAG_STORAGE_BACKEND", "sqlite")]);
+        let k8s = kubernetes(&sqlite);
+        assert!(k8s.contains("claimName: server-data"));
+        assert!(k8s.contains("path: /readyz"));
+        assert!(!k8s.contains("kind: Job"));
+    }
+}
@@ -0,0 +1,328 @@
+//! Local development environment: KeyDB, world seeding, and running the
+//! server, API and client together.
+//!
+//! Configuration comes from the project `.env` (see `.env.example`), the
+//! same file docker compose reads; variables already set in the environment
+//! win. `run-all` fills in what a local run needs on top of that: the KeyDB
+//! URL on localhost and self-signed TLS certificates under
+//! `target/dev-certs`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+
+use crate::stage::{self, CLIENT_BIN, Workspace};
+
+/// Port KeyDB is published on by `docker-compose.yml`.
+const KEYDB_PORT: u16 = 5556;
+
+/// Grace period for the server to save and exit after SIGTERM.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// Arguments for `cargo xtask run-all`.
+#[derive(clap::Args, Debug)]
+pub(crate) struct RunAllArgs {
+    /// Build and run in release mode.
+    #[arg(long)]
+    release: bool,
+
+    /// Use an already running KeyDB instead of `docker compose up keydb`.
+    #[arg(long)]
+    no_keydb: bool,
+
+    /// Start the server and API only (no client).
+    #[arg(long)]
+    headless: bool,
+}
+
+/// Arguments for `cargo xtask seed-world`.
+#[derive(clap::Args, Debug)]
+pub(crate) struct SeedWorldArgs {
+    /// Snapshot to import.
+    #[arg(long, default_value = "server/assets/world_seed.wsnap")]
+    input: PathBuf,
+
+    /// Overwrite existing world data instead of skipping when seeded.
+    #[arg(long)]
+    force: bool,
+}
+
+/// Import the world snapshot into KeyDB via `world-snapshot import`.
+pub(crate) fn seed_world(workspace: &Workspace, args: &SeedWorldArgs) -> Result<()> {
+    let env = keydb_env(workspace)?;
+    seed(workspace, &env, &args.input, args.force)
+}
+
+/// Start KeyDB, seed it, and run the server, API and client until the
+/// client exits (or Ctrl-C with `--headless`).
+pub(crate) fn run_all(workspace: &Workspace, args: &RunAllArgs) -> Result<()> {
+    let env = service_env(workspace)?;
+
+    if !args.no_keydb {
+        stage::run(
+            Command::new("docker")
+                .current_dir(workspace.root())
+                .envs(&env)
+                .args(["compose", "up", "-d", "--wait", "keydb"]),
+        )
+        .context("starting KeyDB with docker compose")?;
+    }
+    seed(
+        workspace,
+        &env,
+        Path::new("server/assets/world_seed.wsnap"),
+        false,
+    )?;
+
+    let profile_flag: &[&str] = if args.release { &["--release"] } else { &[] };
+    let mut build = workspace.cargo();
+    build.arg("build").args(profile_flag).args([
+        "--package",
+        "server",
+        "--bin",
+        "server",
+        "--package",
+        "api",
+    ]);
+    if !args.headless {
+        build.args(["--package", "client", "--bin", CLIENT_BIN]);
+    }
+    stage::run(&mut build)?;
+
+    let bin_dir =
+        workspace
+            .root()
+            .join("target")
+            .join(if args.release { "release" } else { "debug" });
+    let mut services = Services::default();
+    services.spawn(workspace, &env, &bin_dir, "server")?;
+    services.spawn(workspace, &env, &bin_dir, "api")?;
+
+    if args.headless {
+        println!("Server and API running; press Ctrl-C to stop.");
+        services.wait_any()?;
+    } else {
+        let status = Command::new(bin_dir.join(exe(CLIENT_BIN)))
+            .current_dir(workspace.root())
+            .envs(&env)
+            .status()
+            .context("starting the client")?;
+        println!("Client exited ({status}); stopping services...");
+    }
+    services.stop();
+    Ok(())
+}
+
+fn seed(
+    workspace: &Workspace,
+    env: &BTreeMap<String, String>,
+    input: &Path,
+    force: bool,
+) -> Result<()> {
+    let mut command = workspace.cargo();
+    command
+        .envs(env)
+        .args([
+            "run",
+            "--release",
+            "--package",
+            "server",
+            "--bin",
+            "world-snapshot",
+            "--",
+        ])
+        .args(["import", "--input"])
+        .arg(input);
+    command.arg(if force { "--force" } else { "--skip-if-seeded" });
+    stage::run(&mut command).context("seeding the world into KeyDB")
+}
+
+/// The project `.env` overridden by the process environment.
+pub(crate) fn project_env(workspace: &Workspace) -> BTreeMap<String, String> {
+    let mut env = match std::fs::read_to_string(workspace.root().join(".env")) {
+        Ok(text) => parse_dotenv(&text),
+        Err(_) => BTreeMap::new(),
+    };
+    env.extend(std::env::vars());
+    env
+}
+
+/// [`project_env`] plus the localhost KeyDB URL.
+fn keydb_env(workspace: &Workspace) -> Result<BTreeMap<String, String>> {
+    let mut env = project_env(workspace);
+    require(&env, "KEYDB_PASSWORD")?;
+    let password = env["KEYDB_PASSWORD"].clone();
+    env.entry("MAG_KEYDB_URL".to_owned())
+        .or_insert_with(|| format!("redis://:{password}@127.0.0.1:{KEYDB_PORT}/"));
+    Ok(env)
+}
+
+/// [`keydb_env`] plus what the server and API need to start locally.
+fn service_env(workspace: &Workspace) -> Result<BTreeMap<String, String>> {
+    let mut env = keydb_env(workspace)?;
+    require(&env, "API_JWT_SECRET")?;
+    env.entry("MAG_GOD_PASSWORD".to_owned())
+        .or_insert_with(|| "devpassword".to_owned());
+
+    let certs = dev_certs(workspace)?;
+    let cert = certs.join("server.crt").display().to_string();
+    let key = certs.join("server.key").display().to_string();
+    for (name, value) in [
+        ("SERVER_TLS_CERT", &cert),
+        ("SERVER_TLS_KEY", &key),
+        ("API_TLS_CERT", &cert),
+        ("API_TLS_KEY", &key),
+    ] {
+        env.entry(name.to_owned()).or_insert_with(|| value.clone());
+    }
+    Ok(env)
+}
+
+fn require(env: &BTreeMap<String, String>, name: &str) -> Result<()> {
+    if env.get(name).is_none_or(|value| value.is_empty()) {
+        bail!("{name} is not set; copy .env.example to .env and fill it in");
+    }
+    Ok(())
+}
+
+/// Generate self-signed development certificates once.
+fn dev_certs(workspace: &Workspace) -> Result<PathBuf> {
+    let dir = workspace.root().join("target").join("dev-certs");
+    if !dir.join("server.crt").is_file() {
+        stage::run(
+            Command::new("bash")
+                .current_dir(workspace.root())
+                .arg("scripts/generate_certs.sh")
+                .arg("--out")
+                .arg(&dir),
+        )
+        .context("generating development TLS certificates")?;
+    }
+    Ok(dir)
+}
+
+/// Parse `KEY=VALUE` lines, ignoring comments, blank lines and `export`.
+fn parse_dotenv(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((key.trim().to_owned(), value.to_owned()))
+        })
+        .collect()
+}
+
+fn exe(name: &str) -> String {
+    format!("{}{}", name, std::env::consts::EXE_SUFFIX)
+}
+
+/// Background services started by `run-all`, stopped in reverse order.
+#[derive(Default)]
+struct Services {
+    children: Vec<(&'static str, Child)>,
+}
+
+impl Services {
+    fn spawn(
+        &mut self,
+        workspace: &Workspace,
+        env: &BTreeMap<String, String>,
+        bin_dir: &Path,
+        name: &'static str,
+    ) -> Result<()> {
+        let child = Command::new(bin_dir.join(exe(name)))
+            .current_dir(workspace.root())
+            .envs(env)
+            .spawn()
+            .with_context(|| format!("starting {name}"))?;
+        println!("Started {name} (pid {})", child.id());
+        self.children.push((name, child));
+        Ok(())
+    }
+
+    /// Block until any service exits.
+    fn wait_any(&mut self) -> Result<()> {
+        loop {
+            for (name, child) in &mut self.children {
+                if let Some(status) = child.try_wait()? {
+                    println!("{name} exited ({status})");
+                    return Ok(());
+                }
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    }
+
+    /// Ask every service to shut down cleanly (so the server saves the
+    /// world), killing any that do not exit within [`SHUTDOWN_GRACE`].
+    fn stop(&mut self) {
+        while let Some((name, mut child)) = self.children.pop() {
+            if matches!(child.try_wait(), Ok(Some(_))) {
+                continue;
+            }
+            let deadline = if request_shutdown(&child) {
+                Instant::now() + SHUTDOWN_GRACE
+            } else {
+                Instant::now()
+            };
+            while Instant::now() < deadline {
+                if matches!(child.try_wait(), Ok(Some(_))) {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(250));
+            }
+            if matches!(child.try_wait(), Ok(None)) {
+                eprintln!("{name} did not stop in time; killing it");
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+}
+
+impl Drop for Services {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Send SIGTERM; returns whether a graceful shutdown was requested.
+#[cfg(unix)]
+fn request_shutdown(child: &Child) -> bool {
+    Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Windows has no SIGTERM for console children, so services are killed
+/// directly; stop the server from its own console first to keep its save.
+#[cfg(not(unix))]
+fn request_shutdown(_child: &Child) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dotenv_skips_comments_and_strips_quotes() {
+        let env = parse_dotenv(
+            "# comment\nKEYDB_PASSWORD=secret\n\nexport API_JWT_SECRET=\"abc=def\"\n# MAG_X=1\n",
+        );
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["KEYDB_PASSWORD"], "secret");
+        assert_eq!(env["API_JWT_SECRET"], "abc=def");
+    }
+}
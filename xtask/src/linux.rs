@@ -0,0 +1,76 @@
+//! Linux `.AppImage` built with `appimagetool`.
+//!
+//! The client and its `assets/` go in `usr/bin` of the AppDir; inside the
+//! mounted image `current_exe()` resolves there, so the client finds its
+//! assets as usual. Set `MAG_APPIMAGE_SIGN_KEY` to a GPG key id to embed a
+//! signature.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::PackageArgs;
+use crate::stage::{self, CLIENT_BIN, PRODUCT_NAME, Workspace};
+
+/// Build the `.AppImage`.
+///
+/// # Returns
+///
+/// * Path of the image.
+pub(crate) fn package(workspace: &Workspace, args: &PackageArgs) -> Result<PathBuf> {
+    let work = workspace.work_dir("linux");
+    stage::clean_dir(&work)?;
+    let app_dir = work.join("AppDir");
+    workspace.stage_client(&app_dir.join("usr").join("bin"))?;
+
+    let app_run = app_dir.join("AppRun");
+    std::fs::write(
+        &app_run,
+        format!(
+            "#!/bin/sh\nHERE=\"$(dirname \"$(readlink -f \"$0\")\")\"\nexec \"$HERE/usr/bin/{}\" \"$@\"\n",
+            CLIENT_BIN
+        ),
+    )?;
+    make_executable(&app_run)?;
+
+    std::fs::write(
+        app_dir.join(format!("{}.desktop", CLIENT_BIN)),
+        format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={}\nIcon={}\nCategories=Game;RolePlaying;\n",
+            PRODUCT_NAME, CLIENT_BIN, CLIENT_BIN
+        ),
+    )?;
+    std::fs::copy(
+        workspace.client_assets().join("gfx").join("mag_logo.png"),
+        app_dir.join(format!("{}.png", CLIENT_BIN)),
+    )?;
+
+    let image = workspace.dist_dir().join(format!(
+        "men-among-gods-client-{}-linux-{}.AppImage",
+        args.version,
+        std::env::consts::ARCH
+    ));
+    let mut appimagetool = Command::new("appimagetool");
+    appimagetool.env("ARCH", std::env::consts::ARCH);
+    match stage::env_var("MAG_APPIMAGE_SIGN_KEY") {
+        Some(key) => {
+            appimagetool.args(["--sign", "--sign-key"]).arg(key);
+        }
+        None => stage::unsigned(args.require_signing, "MAG_APPIMAGE_SIGN_KEY is not set")?,
+    }
+    stage::run(appimagetool.arg(&app_dir).arg(&image))?;
+    Ok(image)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &std::path::Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &std::path::Path) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! macOS `.app` bundle and `.dmg`.
+//!
+//! `cargo bundle` builds the bundle skeleton from the client's
+//! `[package.metadata.bundle]` section, converting `mag_logo.png` into the
+//! `.icns` referenced by `Info.plist` so the Dock shows the game icon.
+//! `assets/` is then copied into `Contents/MacOS`, next to the executable.
+//!
+//! Signing uses `codesign` with `MAG_MACOS_SIGN_IDENTITY` (a Developer ID
+//! identity from the keychain). Without it the bundle gets an ad-hoc
+//! signature, which macOS requires before it will launch the app at all.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::PackageArgs;
+use crate::stage::{self, CLIENT_BIN, PRODUCT_NAME, Workspace};
+
+/// Build, sign and wrap the `.app` in a `.dmg`.
+///
+/// # Returns
+///
+/// * Path of the disk image.
+pub(crate) fn package(workspace: &Workspace, args: &PackageArgs) -> Result<PathBuf> {
+    stage::run(
+        workspace
+            .cargo()
+            .args([
+                "bundle",
+                "--release",
+                "--package",
+                "client",
+                "--bin",
+                CLIENT_BIN,
+            ])
+            .args(["--format", "osx"]),
+    )
+    .context("cargo bundle failed (install it with `cargo install cargo-bundle`)")?;
+
+    let built = workspace
+        .root()
+        .join("target")
+        .join("release")
+        .join("bundle")
+        .join("osx")
+        .join(format!("{}.app", PRODUCT_NAME));
+    if !built.is_dir() {
+        bail!("cargo bundle did not produce {}", built.display());
+    }
+
+    let work = workspace.work_dir("macos");
+    stage::clean_dir(&work)?;
+    let app = work.join(format!("{}.app", PRODUCT_NAME));
+    stage::copy_dir(&built, &app)?;
+    stage::copy_dir(
+        &workspace.client_assets(),
+        &app.join("Contents").join("MacOS").join("assets"),
+    )?;
+
+    let mut codesign = Command::new("codesign");
+    codesign.args(["--force", "--deep", "--sign"]);
+    match stage::env_var("MAG_MACOS_SIGN_IDENTITY") {
+        Some(identity) => {
+            codesign
+                .arg(identity)
+                .args(["--options", "runtime", "--timestamp"]);
+        }
+        None => {
+            stage::unsigned(args.require_signing, "MAG_MACOS_SIGN_IDENTITY is not set")?;
+            codesign.arg("-");
+        }
+    }
+    stage::run(codesign.arg(&app))?;
+
+    let dmg = workspace
+        .dist_dir()
+        .join(format!("men-among-gods-client-{}-macos.dmg", args.version));
+    stage::run(
+        Command::new("hdiutil")
+            .args(["create", "-volname", PRODUCT_NAME, "-srcfolder"])
+            .arg(&app)
+            .args(["-ov", "-format", "UDZO"])
+            .arg(&dmg),
+    )?;
+    Ok(dmg)
+}
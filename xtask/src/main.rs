@@ -0,0 +1,144 @@
+//! `xtask` — workspace automation.
+//!
+//! Commands for the common contributor workflows, so a working environment
+//! does not depend on a pile of shell scripts:
+//!
+//! * `run-all` - start KeyDB, seed the world, then run the server, API and
+//!   client together (see [`dev`]).
+//! * `seed-world` - import the bundled world snapshot into KeyDB.
+//! * `package-client` - build platform-native client packages:
+//!   * Windows: a signed `.msi` installer (WiX v4+, `signtool`).
+//!   * macOS: a signed `.app` (via `cargo bundle`) with the Dock icon baked
+//!     in, wrapped in a `.dmg`.
+//!   * Linux: an `.AppImage` (`appimagetool`).
+//!
+//!   Every package keeps the layout `filepaths::get_asset_directory()`
+//!   expects: an `assets/` directory next to the client executable.
+//! * `gen-protocol-docs` - regenerate the `docs/protocol.md` message catalog
+//!   from the protocol types in `core` (see [`protocol_docs`]).
+//! * `gen-deploy` - render docker-compose or Kubernetes manifests for the
+//!   server, API and KeyDB (see [`deploy`]).
+//!
+//! # Usage
+//!
+//! ```text
+//! cargo xtask run-all
+//! cargo xtask seed-world --force
+//! cargo xtask package-client --version v1.4.0 --require-signing
+//! cargo xtask gen-protocol-docs --check
+//! cargo xtask gen-deploy --target kubernetes --tag v1.4.0
+//! ```
+
+mod deploy;
+mod dev;
+mod linux;
+mod macos;
+mod protocol_docs;
+mod stage;
+mod windows;
+
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use stage::Workspace;
+
+/// Workspace automation tasks.
+#[derive(Parser, Debug)]
+#[command(about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Start KeyDB, seed the world, and run the server, API and client.
+    RunAll(dev::RunAllArgs),
+    /// Import the bundled world snapshot into KeyDB.
+    SeedWorld(dev::SeedWorldArgs),
+    /// Build the client and package it for the host platform.
+    PackageClient(PackageArgs),
+    /// Regenerate the protocol message catalog from `core`.
+    GenProtocolDocs(protocol_docs::GenProtocolDocsArgs),
+    /// Render docker-compose or Kubernetes manifests for a deployment.
+    GenDeploy(deploy::GenDeployArgs),
+}
+
+/// Arguments for `cargo xtask package-client`.
+#[derive(clap::Args, Debug)]
+pub(crate) struct PackageArgs {
+    /// Release version/tag, e.g. `v1.4.0`.
+    #[arg(long)]
+    pub(crate) version: String,
+
+    /// Platform to package for; must match the host. Defaults to the host.
+    #[arg(long, value_enum)]
+    pub(crate) platform: Option<Platform>,
+
+    /// Reuse the existing `target/release` build instead of rebuilding.
+    #[arg(long)]
+    pub(crate) skip_build: bool,
+
+    /// Fail instead of producing an unsigned package when no signing
+    /// identity is configured.
+    #[arg(long)]
+    pub(crate) require_signing: bool,
+}
+
+/// Packaging target platform.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Platform {
+    Windows,
+    Macos,
+    Linux,
+}
+
+impl Platform {
+    fn host() -> Option<Self> {
+        match std::env::consts::OS {
+            "windows" => Some(Platform::Windows),
+            "macos" => Some(Platform::Macos),
+            "linux" => Some(Platform::Linux),
+            _ => None,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let workspace = Workspace::locate()?;
+    match cli.command {
+        Command::RunAll(args) => dev::run_all(&workspace, &args),
+        Command::SeedWorld(args) => dev::seed_world(&workspace, &args),
+        Command::PackageClient(args) => package_client(&workspace, &args),
+        Command::GenProtocolDocs(args) => protocol_docs::generate(&workspace, &args),
+        Command::GenDeploy(args) => deploy::generate(&workspace, &args),
+    }
+}
+
+fn package_client(workspace: &Workspace, args: &PackageArgs) -> Result<()> {
+    let Some(host) = Platform::host() else {
+        bail!("unsupported host OS: {}", std::env::consts::OS);
+    };
+    let platform = args.platform.unwrap_or(host);
+    if platform != host {
+        bail!(
+            "cannot package for {:?} on a {:?} host; the platform tooling only runs natively",
+            platform,
+            host
+        );
+    }
+
+    if !args.skip_build {
+        workspace.build_client()?;
+    }
+    std::fs::create_dir_all(workspace.dist_dir())?;
+
+    let output = match platform {
+        Platform::Windows => windows::package(workspace, args)?,
+        Platform::Macos => macos::package(workspace, args)?,
+        Platform::Linux => linux::package(workspace, args)?,
+    };
+    println!("Created {}", output.display());
+    Ok(())
+}
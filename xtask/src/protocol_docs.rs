@@ -0,0 +1,468 @@
+//! Protocol message catalog generated from `core`.
+//!
+//! Reads the `ClientCommandType` and `ServerCommandType` enums straight from
+//! their source files and writes one Markdown table per direction: opcode,
+//! name, wire size, payload fields, the release that introduced it and the
+//! first paragraph of the variant's doc comment. Sizes come from the
+//! server command length table (`get_expected_length`), fields from the
+//! `ServerCommandData` variants and the `ClientCommand::new_*` constructors,
+//! and the release from a `Since: <version>` line in the variant docs.
+//! Keeping the type definitions as the single source of truth means the
+//! catalog cannot drift from the wire format; `--check` fails when the
+//! committed file is stale. The admin API embeds the generated file and
+//! serves it at `/admin/protocol`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+
+use crate::stage::Workspace;
+
+/// Arguments for `cargo xtask gen-protocol-docs`.
+#[derive(clap::Args, Debug)]
+pub(crate) struct GenProtocolDocsArgs {
+    /// Output file, relative to the workspace root.
+    #[arg(long, default_value = "docs/protocol.md")]
+    output: PathBuf,
+
+    /// Fail if the output file is out of date instead of writing it.
+    #[arg(long)]
+    check: bool,
+}
+
+/// One enum variant with its opcode.
+#[derive(Debug, PartialEq, Eq)]
+struct Opcode {
+    value: u32,
+    name: String,
+    summary: String,
+    /// Release from a `Since: <version>` doc line, if any.
+    since: Option<String>,
+}
+
+/// Size of every client command frame.
+const CLIENT_FRAME_LEN: &str = "16";
+
+/// Generate (or check) the protocol reference.
+pub(crate) fn generate(workspace: &Workspace, args: &GenProtocolDocsArgs) -> Result<()> {
+    let core_src = workspace.root().join("core").join("src");
+    let read = |file: &str| {
+        let path = core_src.join(file);
+        std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))
+    };
+    let client_source = read("client_commands.rs")?;
+    let server_source = read("server_commands.rs")?;
+
+    let mut doc = String::from(
+        "<!-- Generated by `cargo xtask gen-protocol-docs`; do not edit by hand. -->\n\n\
+         # Protocol Messages\n\n\
+         Client commands are 16-byte frames whose first byte is the opcode. \
+         Server commands start with the opcode byte and are packed into the \
+         per-tick stream. Sizes include the opcode byte; *Since* is the \
+         release that introduced a message (blank for messages that predate \
+         versioned releases).\n",
+    );
+
+    let client_fields = parse_constructor_fields(&client_source, "ClientCommandType");
+    let client = parse_enum(&client_source, "ClientCommandType")
+        .context("parsing ClientCommandType in client_commands.rs")?;
+    push_table(
+        &mut doc,
+        "Client to server",
+        "ClientCommandType",
+        &client,
+        |_| CLIENT_FRAME_LEN.to_owned(),
+        |name| client_fields.get(name).cloned().unwrap_or_default(),
+    );
+
+    let server_sizes = parse_lengths(&server_source, "ServerCommandType")
+        .context("parsing get_expected_length in server_commands.rs")?;
+    let server_fields = parse_data_fields(&server_source, "ServerCommandData")
+        .context("parsing ServerCommandData in server_commands.rs")?;
+    let server = parse_enum(&server_source, "ServerCommandType")
+        .context("parsing ServerCommandType in server_commands.rs")?;
+    push_table(
+        &mut doc,
+        "Server to client",
+        "ServerCommandType",
+        &server,
+        |name| server_sizes.size_of(name),
+        |name| server_fields.get(name).cloned().unwrap_or_default(),
+    );
+
+    let output = workspace.root().join(&args.output);
+    if args.check {
+        let current = std::fs::read_to_string(&output).unwrap_or_default();
+        if current != doc {
+            bail!(
+                "{} is out of date; run `cargo xtask gen-protocol-docs`",
+                args.output.display()
+            );
+        }
+        println!("{} is up to date", args.output.display());
+        return Ok(());
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&output, doc).with_context(|| format!("writing {}", output.display()))?;
+    println!("Wrote {}", args.output.display());
+    Ok(())
+}
+
+/// Append one direction's table to `doc`.
+fn push_table(
+    doc: &mut String,
+    title: &str,
+    enum_name: &str,
+    opcodes: &[Opcode],
+    size: impl Fn(&str) -> String,
+    fields: impl Fn(&str) -> Vec<String>,
+) {
+    doc.push_str(&format!(
+        "\n## {title} (`{enum_name}`)\n\n\
+         | Opcode | Name | Size | Fields | Since | Description |\n\
+         |---:|---|---:|---|---|---|\n"
+    ));
+    for opcode in opcodes {
+        let fields = fields(&opcode.name)
+            .iter()
+            .map(|field| format!("`{field}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+            .replace('|', "\\|");
+        doc.push_str(&format!(
+            "| {} | `{}` | {} | {} | {} | {} |\n",
+            opcode.value,
+            opcode.name,
+            size(&opcode.name),
+            fields,
+            opcode.since.as_deref().unwrap_or(""),
+            opcode.summary
+        ));
+    }
+}
+
+/// Server command sizes read from `get_expected_length`.
+#[derive(Debug, Default)]
+struct Lengths {
+    /// Size per variant name, already formatted for the table.
+    by_name: HashMap<String, String>,
+    /// Size of the `_ =>` fallback arm.
+    fallback: String,
+    /// Variant whose opcode bit marks a delta-encoded map packet.
+    bit_flag: Option<String>,
+}
+
+impl Lengths {
+    /// Table cell for the size of `name`.
+    fn size_of(&self, name: &str) -> String {
+        if self.bit_flag.as_deref() == Some(name) {
+            return "variable".to_owned();
+        }
+        self.by_name
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| self.fallback.clone())
+    }
+}
+
+/// Extract the per-opcode arms of `get_expected_length`.
+///
+/// Plain numbers are kept as-is, other expressions are shown as code, and
+/// arms with a block body (the size depends on the payload) are reported as
+/// `variable`.
+fn parse_lengths(source: &str, enum_name: &str) -> Result<Lengths> {
+    let mut lines = source
+        .lines()
+        .skip_while(|line| !line.contains("fn get_expected_length("));
+    if lines.next().is_none() {
+        bail!("get_expected_length not found");
+    }
+
+    let prefix = format!("{enum_name}::");
+    let mut lengths = Lengths::default();
+    for line in lines {
+        let line = line.trim();
+        if line.starts_with("Ok(len)") {
+            if lengths.fallback.is_empty() {
+                bail!("get_expected_length has no `_ =>` arm");
+            }
+            return Ok(lengths);
+        }
+        if line.starts_with("if (op & ")
+            && let Some(rest) = line.split(&prefix).nth(1)
+        {
+            lengths.bit_flag = rest.split_whitespace().next().map(str::to_owned);
+            continue;
+        }
+        let Some((pattern, expr)) = line.split_once("=>") else {
+            continue;
+        };
+        let expr = expr.trim().trim_end_matches(',');
+        let size = if expr == "{" {
+            "variable".to_owned()
+        } else if expr.parse::<usize>().is_ok() {
+            expr.to_owned()
+        } else {
+            format!("`{expr}`")
+        };
+        match pattern.trim() {
+            "_" => lengths.fallback = size,
+            pattern => {
+                if let Some(name) = pattern.strip_prefix(&prefix) {
+                    lengths.by_name.insert(name.to_owned(), size);
+                }
+            }
+        }
+    }
+    bail!("get_expected_length is not terminated")
+}
+
+/// Extract `name: Type` fields of each variant of a data enum such as
+/// `ServerCommandData`. Tuple variants list their types.
+fn parse_data_fields(source: &str, enum_name: &str) -> Result<HashMap<String, Vec<String>>> {
+    let header = format!("pub enum {enum_name} {{");
+    let mut lines = source.lines().skip_while(|line| line.trim() != header);
+    if lines.next().is_none() {
+        bail!("enum {enum_name} not found");
+    }
+
+    let mut variants = HashMap::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+    for line in lines {
+        let line = line.trim();
+        if line.starts_with("///") || line.starts_with("#[") || line.is_empty() {
+            continue;
+        }
+        if let Some((name, fields)) = current.as_mut() {
+            if line.starts_with('}') {
+                variants.insert(std::mem::take(name), std::mem::take(fields));
+                current = None;
+            } else {
+                fields.push(line.trim_end_matches(',').to_owned());
+            }
+            continue;
+        }
+        if line == "}" {
+            return Ok(variants);
+        }
+        if let Some(name) = line.strip_suffix(" {") {
+            current = Some((name.to_owned(), Vec::new()));
+        } else if let Some((name, tuple)) = line.trim_end_matches(',').split_once('(') {
+            variants.insert(
+                name.to_owned(),
+                vec![tuple.trim_end_matches(')').to_owned()],
+            );
+        } else {
+            variants.insert(line.trim_end_matches(',').to_owned(), Vec::new());
+        }
+    }
+    bail!("enum {enum_name} is not terminated")
+}
+
+/// Map opcodes to the arguments of the first `pub fn new_*` constructor
+/// that builds them (client commands have no data enum of their own).
+fn parse_constructor_fields(source: &str, enum_name: &str) -> HashMap<String, Vec<String>> {
+    let prefix = format!("{enum_name}::");
+    let mut fields = HashMap::new();
+    let mut args: Option<Vec<String>> = None;
+    for line in source.lines() {
+        let line = line.trim();
+        if let Some(signature) = line.strip_prefix("pub fn new_") {
+            args = signature
+                .split_once('(')
+                .and_then(|(_, rest)| rest.split_once(')'))
+                .map(|(list, _)| {
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|arg| !arg.is_empty())
+                        .map(str::to_owned)
+                        .collect()
+                });
+            continue;
+        }
+        if line.contains("fn ") {
+            args = None;
+            continue;
+        }
+        let Some(current) = args.as_ref() else {
+            continue;
+        };
+        for (idx, _) in line.match_indices(prefix.as_str()) {
+            let name: String = line[idx + prefix.len()..]
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            fields.entry(name).or_insert_with(|| current.clone());
+        }
+    }
+    fields
+}
+
+/// Extract `Name = value` variants and their doc comments from a fieldless
+/// `pub enum`.
+fn parse_enum(source: &str, enum_name: &str) -> Result<Vec<Opcode>> {
+    let header = format!("pub enum {enum_name} {{");
+    let mut lines = source.lines().skip_while(|line| line.trim() != header);
+    if lines.next().is_none() {
+        bail!("enum {enum_name} not found");
+    }
+
+    let mut opcodes = Vec::new();
+    let mut docs: Vec<&str> = Vec::new();
+    let mut since = None;
+    for line in lines {
+        let line = line.trim();
+        if line == "}" {
+            return Ok(opcodes);
+        }
+        if let Some(doc) = line.strip_prefix("///") {
+            match doc.trim().strip_prefix("Since:") {
+                Some(version) => since = Some(version.trim().to_owned()),
+                None => docs.push(doc.trim()),
+            }
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+        if let Some((name, value)) = line.trim_end_matches(',').split_once('=') {
+            let value = value
+                .trim()
+                .parse()
+                .with_context(|| format!("opcode of {}", name.trim()))?;
+            opcodes.push(Opcode {
+                value,
+                name: name.trim().to_owned(),
+                summary: summary(&docs),
+                since: since.take(),
+            });
+        }
+        docs.clear();
+        since = None;
+    }
+    bail!("enum {enum_name} is not terminated")
+}
+
+/// First paragraph of a doc comment as a single Markdown table cell.
+fn summary(docs: &[&str]) -> String {
+    let paragraph: Vec<&str> = docs
+        .iter()
+        .copied()
+        .take_while(|line| !line.is_empty())
+        .collect();
+    strip_intra_doc_links(&paragraph.join(" ")).replace('|', "\\|")
+}
+
+/// Turn rustdoc links (`` [`X`](path) `` and `` [`X`] ``) into plain code
+/// spans, since their targets do not resolve outside rustdoc.
+fn strip_intra_doc_links(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("[`") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find("`]") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        out.push_str(&after[..end + 1]);
+        rest = &after[end + 2..];
+        if rest.starts_with('(')
+            && let Some(close) = rest.find(')')
+        {
+            rest = &rest[close + 1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_enum_reads_opcodes_and_summaries() {
+        let source = "\
+pub enum Demo {
+    _Empty = 0,
+    /// Move somewhere, see [`crate::moves`].
+    ///
+    /// Wire format details.
+    CmdMove = 5,
+    #[allow(dead_code)]
+    /// Uses a [`Thing`](crate::thing::Thing) | pipe.
+    ///
+    /// Since: 1.4.0
+    CmdUse = 18,
+}
+";
+        let opcodes = parse_enum(source, "Demo").unwrap();
+        assert_eq!(opcodes.len(), 3);
+        assert_eq!(opcodes[1].value, 5);
+        assert_eq!(opcodes[1].summary, "Move somewhere, see `crate::moves`.");
+        assert_eq!(opcodes[2].name, "CmdUse");
+        assert_eq!(opcodes[2].summary, "Uses a `Thing` \\| pipe.");
+        assert_eq!(opcodes[2].since.as_deref(), Some("1.4.0"));
+        assert_eq!(opcodes[1].since, None);
+    }
+
+    #[test]
+    fn sizes_and_fields_come_from_the_type_definitions() {
+        let source = "\
+pub enum Data {
+    Empty,
+    /// Mode change.
+    SetMode {
+        #[allow(dead_code)]
+        mode: u8,
+    },
+    Sheet(Box<Sheet>),
+}
+
+impl Demo {
+    pub fn get_expected_length(bytes: &[u8]) -> Result<usize, String> {
+        if (op & Demo::SetMap as u8) != 0 {
+            return sv_setmap_len(bytes);
+        }
+        let len = match parsed_op {
+            Demo::SetMode => 2,
+            Demo::Sheet => SHEET_LEN,
+            Demo::Ignore => {
+                read_len(bytes)
+            }
+            _ => 16,
+        };
+        Ok(len)
+    }
+}
+
+impl Cmd {
+    pub fn new_move(x: i16, y: i32) -> Self {
+        Self::from_opcode(Demo::CmdMove, x, y)
+    }
+    fn helper() {
+        Demo::CmdUse;
+    }
+}
+";
+        let lengths = parse_lengths(source, "Demo").unwrap();
+        assert_eq!(lengths.size_of("SetMode"), "2");
+        assert_eq!(lengths.size_of("Sheet"), "`SHEET_LEN`");
+        assert_eq!(lengths.size_of("Ignore"), "variable");
+        assert_eq!(lengths.size_of("SetMap"), "variable");
+        assert_eq!(lengths.size_of("Log0"), "16");
+
+        let fields = parse_data_fields(source, "Data").unwrap();
+        assert_eq!(fields["SetMode"], vec!["mode: u8"]);
+        assert_eq!(fields["Sheet"], vec!["Box<Sheet>"]);
+        assert!(fields["Empty"].is_empty());
+
+        let args = parse_constructor_fields(source, "Demo");
+        assert_eq!(args["CmdMove"], vec!["x: i16", "y: i32"]);
+        assert!(!args.contains_key("CmdUse"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Shared helpers: workspace paths, running tools, staging the client.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+/// Name of the client executable (without extension).
+pub(crate) const CLIENT_BIN: &str = "men-among-gods-client";
+
+/// Human-readable product name used for bundles and installers.
+pub(crate) const PRODUCT_NAME: &str = "Men Among Gods - Reforged";
+
+/// Paths within the repository checkout.
+pub(crate) struct Workspace {
+    root: PathBuf,
+}
+
+impl Workspace {
+    /// Find the workspace root (the parent of this crate).
+    ///
+    /// # Returns
+    ///
+    /// * The workspace, or an error if the checkout layout is unexpected.
+    pub(crate) fn locate() -> Result<Self> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .context("xtask crate has no parent directory")?
+            .to_path_buf();
+        if !root.join("client").join("assets").is_dir() {
+            bail!("client/assets not found under {}", root.display());
+        }
+        Ok(Self { root })
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory the finished packages are written to.
+    pub(crate) fn dist_dir(&self) -> PathBuf {
+        self.root.join("dist")
+    }
+
+    /// Scratch directory for staging package contents.
+    pub(crate) fn work_dir(&self, name: &str) -> PathBuf {
+        self.root.join("target").join("xtask").join(name)
+    }
+
+    pub(crate) fn client_assets(&self) -> PathBuf {
+        self.root.join("client").join("assets")
+    }
+
+    /// Path of the release client executable.
+    pub(crate) fn client_binary(&self) -> PathBuf {
+        self.root.join("target").join("release").join(format!(
+            "{}{}",
+            CLIENT_BIN,
+            std::env::consts::EXE_SUFFIX
+        ))
+    }
+
+    /// A `cargo` invocation rooted at the workspace.
+    pub(crate) fn cargo(&self) -> Command {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+        let mut command = Command::new(cargo);
+        command.current_dir(&self.root);
+        command
+    }
+
+    /// Build the release client.
+    pub(crate) fn build_client(&self) -> Result<()> {
+        run(self.cargo().args([
+            "build",
+            "--release",
+            "--package",
+            "client",
+            "--bin",
+            CLIENT_BIN,
+        ]))
+    }
+
+    /// Copy the client executable and `assets/` into `dir`, matching the
+    /// layout the client resolves at runtime (`<exe dir>/assets`).
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory that will contain the executable.
+    ///
+    /// # Returns
+    ///
+    /// * Path of the copied executable.
+    pub(crate) fn stage_client(&self, dir: &Path) -> Result<PathBuf> {
+        let binary = self.client_binary();
+        if !binary.is_file() {
+            bail!(
+                "missing client build at {}; run without --skip-build",
+                binary.display()
+            );
+        }
+        std::fs::create_dir_all(dir)?;
+        let exe = dir.join(
+            binary
+                .file_name()
+                .context("client binary has no file name")?,
+        );
+        std::fs::copy(&binary, &exe)
+            .with_context(|| format!("copying {} to {}", binary.display(), exe.display()))?;
+        copy_dir(&self.client_assets(), &dir.join("assets"))?;
+        Ok(exe)
+    }
+}
+
+/// Strip a leading `v` from a release tag.
+///
+/// # Arguments
+///
+/// * `version` - Tag such as `v1.4.0`.
+///
+/// # Returns
+///
+/// * Bare version such as `1.4.0`.
+pub(crate) fn bare_version(version: &str) -> &str {
+    version.strip_prefix('v').unwrap_or(version)
+}
+
+/// Recreate `dir` as an empty directory.
+pub(crate) fn clean_dir(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir).with_context(|| format!("removing {}", dir.display()))?;
+    }
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))
+}
+
+/// Recursively copy `src` into `dest`.
+pub(crate) fn copy_dir(src: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(src).with_context(|| format!("reading {}", src.display()))? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .with_context(|| format!("copying {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a command, failing if it cannot start or exits unsuccessfully.
+pub(crate) fn run(command: &mut Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .status()
+        .with_context(|| format!("failed to run `{}` (is it installed and on PATH?)", program))?;
+    if !status.success() {
+        bail!("`{}` failed with {}", program, status);
+    }
+    Ok(())
+}
+
+/// Read a non-empty environment variable.
+pub(crate) fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Handle a missing signing identity: an error with `--require-signing`,
+/// otherwise a warning.
+///
+/// # Arguments
+///
+/// * `require_signing` - Whether unsigned output is an error.
+/// * `what` - Description of the missing configuration.
+pub(crate) fn unsigned(require_signing: bool, what: &str) -> Result<()> {
+    if require_signing {
+        bail!("signing required but {}", what);
+    }
+    eprintln!("Warning: {}; the package will be unsigned", what);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_version_strips_tag_prefix() {
+        assert_eq!(bare_version("v1.4.0"), "1.4.0");
+        assert_eq!(bare_version("1.4.0"), "1.4.0");
+    }
+}
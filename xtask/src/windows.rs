@@ -0,0 +1,225 @@
+//! Windows `.msi` installer built with the WiX toolset (v4 or later).
+//!
+//! Signing uses `signtool` with a PFX certificate:
+//!
+//! * `MAG_WINDOWS_SIGN_CERT` - path to the `.pfx` file.
+//! * `MAG_WINDOWS_SIGN_PASSWORD` - its password (optional).
+//! * `MAG_WINDOWS_TIMESTAMP_URL` - RFC 3161 timestamp server (optional).
+//!
+//! Both the client executable and the finished `.msi` are signed.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::PackageArgs;
+use crate::stage::{self, CLIENT_BIN, PRODUCT_NAME, Workspace};
+
+/// Stable upgrade code so new installers replace older installs.
+const UPGRADE_CODE: &str = "6F0C7B9E-3D2A-4E51-9B8F-2C4A1D7E5B30";
+
+const DEFAULT_TIMESTAMP_URL: &str = "http://timestamp.digicert.com";
+
+/// Build and sign the `.msi`.
+///
+/// # Returns
+///
+/// * Path of the installer.
+pub(crate) fn package(workspace: &Workspace, args: &PackageArgs) -> Result<PathBuf> {
+    let work = workspace.work_dir("windows");
+    stage::clean_dir(&work)?;
+    let install_dir = work.join("install");
+    let exe = workspace.stage_client(&install_dir)?;
+
+    let signing = Signing::from_env();
+    match &signing {
+        Some(signing) => signing.sign(&exe)?,
+        None => stage::unsigned(args.require_signing, "MAG_WINDOWS_SIGN_CERT is not set")?,
+    }
+
+    let icon = workspace.client_assets().join("gfx").join("mag_logo.ico");
+    let wxs = work.join("client.wxs");
+    std::fs::write(
+        &wxs,
+        wix_source(stage::bare_version(&args.version), &install_dir, &icon)?,
+    )
+    .with_context(|| format!("writing {}", wxs.display()))?;
+
+    let msi = workspace.dist_dir().join(format!(
+        "men-among-gods-client-{}-windows.msi",
+        args.version
+    ));
+    stage::run(
+        Command::new("wix")
+            .arg("build")
+            .arg("-arch")
+            .arg("x64")
+            .arg("-o")
+            .arg(&msi)
+            .arg(&wxs),
+    )?;
+
+    if let Some(signing) = &signing {
+        signing.sign(&msi)?;
+    }
+    Ok(msi)
+}
+
+/// `signtool` configuration read from the environment.
+struct Signing {
+    cert: String,
+    password: Option<String>,
+    timestamp_url: String,
+}
+
+impl Signing {
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            cert: stage::env_var("MAG_WINDOWS_SIGN_CERT")?,
+            password: stage::env_var("MAG_WINDOWS_SIGN_PASSWORD"),
+            timestamp_url: stage::env_var("MAG_WINDOWS_TIMESTAMP_URL")
+                .unwrap_or_else(|| DEFAULT_TIMESTAMP_URL.to_owned()),
+        })
+    }
+
+    fn sign(&self, file: &Path) -> Result<()> {
+        let mut command = Command::new("signtool");
+        command
+            .args(["sign", "/fd", "SHA256", "/td", "SHA256", "/tr"])
+            .arg(&self.timestamp_url)
+            .arg("/f")
+            .arg(&self.cert);
+        if let Some(password) = &self.password {
+            command.arg("/p").arg(password);
+        }
+        stage::run(command.arg(file))
+    }
+}
+
+/// Generate the WiX source installing every file under `install_dir`.
+///
+/// # Arguments
+///
+/// * `version` - Bare `major.minor.patch` product version.
+/// * `install_dir` - Staged install tree (executable plus `assets/`).
+/// * `icon` - `.ico` used for the shortcut and Add/Remove Programs.
+///
+/// # Returns
+///
+/// * The `.wxs` document.
+fn wix_source(version: &str, install_dir: &Path, icon: &Path) -> Result<String> {
+    let mut files = String::new();
+    let mut refs = Vec::new();
+    write_directory(&mut files, install_dir, 3, &mut refs)?;
+
+    let mut feature = String::new();
+    for id in &refs {
+        writeln!(feature, "      <ComponentRef Id=\"{}\" />", id)?;
+    }
+
+    Ok(format!(
+        r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">
+  <Package Name="{name}" Manufacturer="{name}" Version="{version}" UpgradeCode="{upgrade}">
+    <MajorUpgrade DowngradeErrorMessage="A newer version of [ProductName] is already installed." />
+    <MediaTemplate EmbedCab="yes" />
+    <Icon Id="AppIcon" SourceFile="{icon}" />
+    <Property Id="ARPPRODUCTICON" Value="AppIcon" />
+    <StandardDirectory Id="ProgramFiles64Folder">
+      <Directory Id="INSTALLFOLDER" Name="{name}">
+{files}      </Directory>
+    </StandardDirectory>
+    <StandardDirectory Id="ProgramMenuFolder">
+      <Component Id="StartMenuShortcut">
+        <Shortcut Id="ClientShortcut" Name="{name}" Target="[INSTALLFOLDER]{exe}.exe" WorkingDirectory="INSTALLFOLDER" Icon="AppIcon" />
+        <RegistryValue Root="HKCU" Key="Software\MenAmongGods\Reforged" Name="StartMenuShortcut" Type="integer" Value="1" KeyPath="yes" />
+      </Component>
+    </StandardDirectory>
+    <Feature Id="Main">
+      <ComponentRef Id="StartMenuShortcut" />
+{feature}    </Feature>
+  </Package>
+</Wix>
+"#,
+        name = xml_escape(PRODUCT_NAME),
+        version = xml_escape(version),
+        upgrade = UPGRADE_CODE,
+        icon = xml_escape(&icon.display().to_string()),
+        exe = CLIENT_BIN,
+    ))
+}
+
+/// Emit one `<Component>` per file and a nested `<Directory>` per
+/// subdirectory, collecting component ids into `refs`.
+fn write_directory(
+    out: &mut String,
+    dir: &Path,
+    depth: usize,
+    refs: &mut Vec<String>,
+) -> Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)
+        .with_context(|| format!("reading {}", dir.display()))?
+        .collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let indent = "  ".repeat(depth);
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() {
+            let id = format!("d{}", refs.len());
+            writeln!(
+                out,
+                "{}<Directory Id=\"{}\" Name=\"{}\">",
+                indent,
+                id,
+                xml_escape(&name)
+            )?;
+            write_directory(out, &path, depth + 1, refs)?;
+            writeln!(out, "{}</Directory>", indent)?;
+        } else {
+            let id = format!("c{}", refs.len());
+            writeln!(
+                out,
+                "{}<Component Id=\"{}\"><File Id=\"f{}\" Source=\"{}\" KeyPath=\"yes\" /></Component>",
+                indent,
+                id,
+                refs.len(),
+                xml_escape(&path.display().to_string())
+            )?;
+            refs.push(id);
+        }
+    }
+    Ok(())
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wix_source_lists_every_staged_file() {
+        let root = std::env::temp_dir().join(format!("mag_xtask_wix_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("assets").join("gfx")).unwrap();
+        std::fs::write(root.join("men-among-gods-client.exe"), b"").unwrap();
+        std::fs::write(root.join("assets").join("gfx").join("a&b.png"), b"").unwrap();
+
+        let wxs = wix_source("1.4.0", &root, Path::new("icon.ico")).unwrap();
+        assert!(wxs.contains("Version=\"1.4.0\""));
+        assert!(wxs.contains("Name=\"assets\""));
+        assert!(wxs.contains("a&amp;b.png"));
+        assert_eq!(wxs.matches("<ComponentRef Id=\"c").count(), 2);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}